@@ -0,0 +1,28 @@
+import copy
+
+from savant_rs.primitives import Attribute, AttributeValue
+from savant_rs.utils import gen_frame
+
+# frames and borrowed objects have identity semantics
+f = gen_frame()
+same = copy.copy(f)
+assert f == same and hash(f) == hash(same)
+
+independent = copy.deepcopy(f)
+assert f != independent
+assert f.uuid == independent.uuid
+
+o1 = f.get_object(1)
+o2 = same.get_object(1)
+assert o1 == o2 and hash(o1) == hash(o2)
+
+# detached objects and attributes have value semantics
+d1 = o1.detached_copy()
+d2 = copy.deepcopy(d1)
+assert d1 == d2
+
+a = Attribute(namespace="some", name="attr", hint="x", values=[AttributeValue.integer(1)])
+b = copy.deepcopy(a)
+assert a == b
+b.values = [AttributeValue.integer(2)]
+assert a != b
@@ -0,0 +1,19 @@
+from savant_rs.match_query import MatchQuery as Q, StringExpression as SE
+from savant_rs.utils import gen_frame
+
+f = gen_frame()
+objects = f.objects
+
+assert len(objects) == 3
+assert objects
+assert [o.id for o in objects] == objects.ids
+
+assert objects[-1].id == objects[len(objects) - 1].id
+assert len(objects[1:]) == 2
+assert objects[::2].ids == [objects[0].id, objects[2].id]
+
+test2 = f.objects.filter(Q.label(SE.eq("test2")))
+assert len(test2) == 2
+
+matched, rest = f.objects.partition(Q.label(SE.eq("test2")))
+assert len(matched) == 2 and len(rest) == 1
@@ -0,0 +1,27 @@
+import pickle
+
+from savant_rs.primitives import Attribute, AttributeValue, VideoFrameUpdate
+from savant_rs.utils import gen_frame
+from savant_rs.utils.serialization import Message
+
+f = gen_frame()
+restored = pickle.loads(pickle.dumps(f))
+assert f.uuid == restored.uuid
+assert len(f.get_all_objects()) == len(restored.get_all_objects())
+
+o = f.get_object(1).detached_copy()
+restored = pickle.loads(pickle.dumps(o))
+assert o.id == restored.id
+
+a = Attribute(namespace="some", name="attr", hint="x", values=[AttributeValue.integer(1)])
+restored = pickle.loads(pickle.dumps(a))
+assert restored.namespace == "some" and restored.name == "attr"
+
+u = VideoFrameUpdate()
+u.add_frame_attribute(a)
+restored = pickle.loads(pickle.dumps(u))
+assert len(restored.get_objects()) == 0
+
+m = Message.video_frame(f)
+restored = pickle.loads(pickle.dumps(m))
+assert restored.is_video_frame()
@@ -0,0 +1,51 @@
+#![feature(test)]
+
+extern crate test;
+
+use savant_core::primitives::rust::AttributeValue;
+use savant_core::primitives::{Attribute, WithAttributes};
+use savant_core::test::gen_frame;
+use test::Bencher;
+
+fn single_value_attribute(i: i64) -> Attribute {
+    Attribute::persistent(
+        "test",
+        "test",
+        vec![AttributeValue::integer(i, Some(0.96))],
+        &None,
+        false,
+    )
+}
+
+#[bench]
+fn bench_single_value_attribute_create(b: &mut Bencher) {
+    b.iter(|| {
+        _ = single_value_attribute(1);
+    });
+}
+
+#[bench]
+fn bench_single_value_attribute_clone(b: &mut Bencher) {
+    let attr = single_value_attribute(1);
+    b.iter(|| {
+        _ = attr.clone();
+    });
+}
+
+#[bench]
+fn bench_gen_frame_object_attributes(b: &mut Bencher) {
+    let frame = gen_frame();
+    b.iter(|| {
+        for mut o in frame.get_all_objects() {
+            for i in 0..10 {
+                o.set_attribute(Attribute::persistent(
+                    "test",
+                    &format!("attr{}", i),
+                    vec![AttributeValue::integer(i, Some(0.96))],
+                    &None,
+                    false,
+                ));
+            }
+        }
+    });
+}
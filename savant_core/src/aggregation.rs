@@ -0,0 +1,557 @@
+use crate::match_query::MatchQuery;
+use crate::primitives::attribute_value::{AttributeValue, AttributeValueVariant};
+use crate::primitives::frame::VideoFrameProxy;
+use crate::primitives::object::ObjectOperations;
+use crate::primitives::rust::UserData;
+use crate::primitives::{Attribute, WithAttributes};
+use crate::webserver::kvs::synchronous::set_attributes;
+use anyhow::bail;
+use hashbrown::HashMap;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// The windows the observations are grouped into, by the observation timestamp.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Window {
+    /// Adjacent non-overlapping windows.
+    Tumbling { size_ms: u64 },
+    /// Windows of `size_ms` starting every `step_ms`, an observation belongs to every window
+    /// covering it.
+    Sliding { size_ms: u64, step_ms: u64 },
+}
+
+impl Window {
+    fn size(&self) -> u64 {
+        match self {
+            Window::Tumbling { size_ms } | Window::Sliding { size_ms, .. } => *size_ms,
+        }
+    }
+
+    fn step(&self) -> u64 {
+        match self {
+            Window::Tumbling { size_ms } => *size_ms,
+            Window::Sliding { step_ms, .. } => *step_ms,
+        }
+    }
+
+    fn starts(&self, ts: u64) -> impl Iterator<Item = u64> {
+        let (size, step) = (self.size(), self.step());
+        let last = ts - ts % step;
+        (0..)
+            .map_while(move |i: u64| last.checked_sub(i * step))
+            .take_while(move |start| start + size > ts)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Statistic {
+    Count,
+    Mean,
+    Max,
+    /// The percentile from `0` to `100`, the nearest-rank method is used.
+    Percentile(f64),
+}
+
+impl Statistic {
+    /// The name used for the emitted attributes, e.g. `mean` or `p95`.
+    ///
+    pub fn name(&self) -> String {
+        match self {
+            Statistic::Count => "count".to_string(),
+            Statistic::Mean => "mean".to_string(),
+            Statistic::Max => "max".to_string(),
+            Statistic::Percentile(p) => format!("p{}", p),
+        }
+    }
+
+    /// Computes the statistic of the sorted values.
+    ///
+    fn compute(&self, sorted: &[f64]) -> f64 {
+        match self {
+            Statistic::Count => sorted.len() as f64,
+            Statistic::Mean => sorted.iter().sum::<f64>() / sorted.len() as f64,
+            Statistic::Max => sorted[sorted.len() - 1],
+            Statistic::Percentile(p) => {
+                let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+                sorted[rank.clamp(1, sorted.len()) - 1]
+            }
+        }
+    }
+}
+
+impl FromStr for Statistic {
+    type Err = anyhow::Error;
+
+    /// Parses the [`Statistic::name`] of the statistic.
+    ///
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "count" => Ok(Statistic::Count),
+            "mean" => Ok(Statistic::Mean),
+            "max" => Ok(Statistic::Max),
+            _ => match s.strip_prefix('p').map(str::parse::<f64>) {
+                Some(Ok(p)) => Ok(Statistic::Percentile(p)),
+                _ => bail!(
+                    "Unknown statistic '{}', expected count, mean, max or p<N>",
+                    s
+                ),
+            },
+        }
+    }
+}
+
+/// What the observations of a frame are grouped by, the key of a group is the list of the
+/// values in the order of the configuration.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum GroupKey {
+    Source,
+    /// The `namespace.label` of the object, empty for frame observations.
+    Class,
+    /// The value of the string, integer or boolean attribute of the object (or the frame for
+    /// frame observations), e.g. a zone. Observations without the attribute are skipped.
+    Attribute {
+        namespace: String,
+        name: String,
+    },
+}
+
+impl FromStr for GroupKey {
+    type Err = anyhow::Error;
+
+    /// Parses `source`, `class` or `<namespace>/<name>` of an attribute.
+    ///
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "source" => Ok(GroupKey::Source),
+            "class" => Ok(GroupKey::Class),
+            _ => match s.split_once('/') {
+                Some((namespace, name)) if !namespace.is_empty() && !name.is_empty() => {
+                    Ok(GroupKey::Attribute {
+                        namespace: namespace.to_string(),
+                        name: name.to_string(),
+                    })
+                }
+                _ => bail!(
+                    "Unknown group key '{}', expected source, class or <namespace>/<name>",
+                    s
+                ),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AggregationConfiguration {
+    pub window: Window,
+    pub statistics: Vec<Statistic>,
+    pub group_by: Vec<GroupKey>,
+    /// The numeric attribute aggregated, every observation counts as `1` when not set.
+    pub value: Option<(String, String)>,
+    /// The objects observed, the frame itself is observed when not set.
+    pub objects: Option<MatchQuery>,
+}
+
+/// The statistics of a group for a closed window.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowResult {
+    pub key: Vec<String>,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub statistics: Vec<(Statistic, f64)>,
+}
+
+impl WindowResult {
+    /// Builds a user data message with the `key` and `window` attributes and an attribute
+    /// per statistic in the namespace.
+    ///
+    pub fn to_user_data(&self, source_id: &str, namespace: &str) -> UserData {
+        let mut user_data = UserData::new(source_id);
+        user_data.set_attribute(Attribute::persistent(
+            namespace,
+            "key",
+            vec![AttributeValue::string_vector(self.key.clone(), None)],
+            &None,
+            false,
+        ));
+        user_data.set_attribute(Attribute::persistent(
+            namespace,
+            "window",
+            vec![AttributeValue::integer_vector(
+                vec![self.start_ms as i64, self.end_ms as i64],
+                None,
+            )],
+            &None,
+            false,
+        ));
+        for (statistic, value) in &self.statistics {
+            user_data.set_attribute(Attribute::persistent(
+                namespace,
+                &statistic.name(),
+                vec![AttributeValue::float(*value, None)],
+                &None,
+                false,
+            ));
+        }
+        user_data
+    }
+
+    /// Builds the KVS attributes named `<key>/<statistic>`, the key values are joined with
+    /// `/`, so the latest window of a group overwrites the previous one.
+    ///
+    pub fn to_kvs_attributes(&self, namespace: &str) -> Vec<Attribute> {
+        let key = self.key.join("/");
+        self.statistics
+            .iter()
+            .map(|(statistic, value)| {
+                Attribute::persistent(
+                    namespace,
+                    &format!("{}/{}", key, statistic.name()),
+                    vec![AttributeValue::float(*value, None)],
+                    &None,
+                    false,
+                )
+            })
+            .collect()
+    }
+
+    /// Stores the statistics in the KVS, see [`Self::to_kvs_attributes`].
+    ///
+    pub fn store_in_kvs(&self, namespace: &str, ttl: Option<u64>) {
+        set_attributes(&self.to_kvs_attributes(namespace), ttl);
+    }
+}
+
+fn numeric_value(attribute: &Attribute) -> Option<f64> {
+    match attribute.get_values().first().map(|v| &v.value) {
+        Some(AttributeValueVariant::Float(v)) => Some(*v),
+        Some(AttributeValueVariant::Integer(v)) => Some(*v as f64),
+        Some(AttributeValueVariant::Boolean(v)) => Some(*v as u8 as f64),
+        _ => None,
+    }
+}
+
+fn key_value(attribute: &Attribute) -> Option<String> {
+    match attribute.get_values().first().map(|v| &v.value) {
+        Some(AttributeValueVariant::String(v)) => Some(v.clone()),
+        Some(AttributeValueVariant::Integer(v)) => Some(v.to_string()),
+        Some(AttributeValueVariant::Boolean(v)) => Some(v.to_string()),
+        _ => None,
+    }
+}
+
+/// Computes windowed statistics of numeric attributes grouped by key, so simple dashboards
+/// (e.g. the occupancy of zones) are fed without a stream processor. The timestamps are
+/// provided by the caller, e.g. [`crate::clock::now_ms`] which follows the frames when
+/// recorded video is backfilled; a window is emitted by [`Self::close`] once the time passes
+/// its end, and observations of the emitted windows are dropped as late.
+///
+#[derive(Debug)]
+pub struct WindowedAggregator {
+    configuration: AggregationConfiguration,
+    windows: BTreeMap<u64, HashMap<Vec<String>, Vec<f64>>>,
+    closed_until: u64,
+    late: u64,
+}
+
+impl WindowedAggregator {
+    pub fn new(configuration: AggregationConfiguration) -> anyhow::Result<Self> {
+        let (size, step) = (configuration.window.size(), configuration.window.step());
+        if size == 0 || step == 0 || step > size {
+            bail!(
+                "The window step must be positive and not greater than the size, got size {} and step {}",
+                size,
+                step
+            );
+        }
+        if configuration.statistics.is_empty() {
+            bail!("At least one statistic must be computed");
+        }
+        if let Some(Statistic::Percentile(p)) = configuration
+            .statistics
+            .iter()
+            .find(|s| matches!(s, Statistic::Percentile(p) if !(0.0..=100.0).contains(p)))
+        {
+            bail!("The percentile must be from 0 to 100, got {}", p);
+        }
+        Ok(Self {
+            configuration,
+            windows: BTreeMap::new(),
+            closed_until: 0,
+            late: 0,
+        })
+    }
+
+    pub fn get_configuration(&self) -> &AggregationConfiguration {
+        &self.configuration
+    }
+
+    /// The number of observations dropped because their windows were already emitted.
+    ///
+    pub fn get_late(&self) -> u64 {
+        self.late
+    }
+
+    /// Adds the value to the windows covering the timestamp. Returns `false` when the
+    /// observation is late.
+    ///
+    pub fn observe(&mut self, key: Vec<String>, value: f64, ts_ms: u64) -> bool {
+        let size = self.configuration.window.size();
+        let mut observed = false;
+        for start in self.configuration.window.starts(ts_ms) {
+            if start + size <= self.closed_until {
+                continue;
+            }
+            observed = true;
+            self.windows
+                .entry(start)
+                .or_default()
+                .entry(key.clone())
+                .or_default()
+                .push(value);
+        }
+        if !observed {
+            self.late += 1;
+        }
+        observed
+    }
+
+    /// Observes the frame or its objects according to the configuration. Returns the number
+    /// of observations made.
+    ///
+    pub fn observe_frame(&mut self, frame: &VideoFrameProxy, ts_ms: u64) -> usize {
+        let source_id = frame.get_source_id();
+        let observations = match &self.configuration.objects {
+            None => self
+                .frame_observation(frame, &source_id)
+                .into_iter()
+                .collect::<Vec<_>>(),
+            Some(q) => frame
+                .access_objects(q)
+                .iter()
+                .filter_map(|o| {
+                    let class = format!("{}.{}", o.get_namespace(), o.get_label());
+                    self.observation(o, &source_id, &class)
+                })
+                .collect(),
+        };
+        observations
+            .into_iter()
+            .filter(|(key, value)| self.observe(key.clone(), *value, ts_ms))
+            .count()
+    }
+
+    fn frame_observation(
+        &self,
+        frame: &VideoFrameProxy,
+        source_id: &str,
+    ) -> Option<(Vec<String>, f64)> {
+        self.observation(frame, source_id, "")
+    }
+
+    fn observation<T: WithAttributes>(
+        &self,
+        owner: &T,
+        source_id: &str,
+        class: &str,
+    ) -> Option<(Vec<String>, f64)> {
+        let value = match &self.configuration.value {
+            Some((namespace, name)) => numeric_value(&owner.get_attribute(namespace, name)?)?,
+            None => 1.0,
+        };
+        let key = self
+            .configuration
+            .group_by
+            .iter()
+            .map(|k| match k {
+                GroupKey::Source => Some(source_id.to_string()),
+                GroupKey::Class => Some(class.to_string()),
+                GroupKey::Attribute { namespace, name } => {
+                    key_value(&owner.get_attribute(namespace, name)?)
+                }
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some((key, value))
+    }
+
+    fn emit(&self, start: u64, groups: HashMap<Vec<String>, Vec<f64>>) -> Vec<WindowResult> {
+        let end = start + self.configuration.window.size();
+        let mut results = groups
+            .into_iter()
+            .map(|(key, mut values)| {
+                values.sort_by(f64::total_cmp);
+                WindowResult {
+                    key,
+                    start_ms: start,
+                    end_ms: end,
+                    statistics: self
+                        .configuration
+                        .statistics
+                        .iter()
+                        .map(|s| (*s, s.compute(&values)))
+                        .collect(),
+                }
+            })
+            .collect::<Vec<_>>();
+        results.sort_by(|a, b| a.key.cmp(&b.key));
+        results
+    }
+
+    /// Emits the windows ending at or before the time, ordered by the window start and key.
+    ///
+    pub fn close(&mut self, now_ms: u64) -> Vec<WindowResult> {
+        let size = self.configuration.window.size();
+        self.closed_until = self.closed_until.max(now_ms);
+        let open = match now_ms.checked_sub(size) {
+            Some(last_closed) => self.windows.split_off(&(last_closed + 1)),
+            None => return Vec::new(),
+        };
+        let closed = std::mem::replace(&mut self.windows, open);
+        closed
+            .into_iter()
+            .flat_map(|(start, groups)| self.emit(start, groups))
+            .collect()
+    }
+
+    /// Emits all windows, including the ones still open, e.g. at shutdown.
+    ///
+    pub fn flush(&mut self) -> Vec<WindowResult> {
+        let windows = std::mem::take(&mut self.windows);
+        if let Some(last) = windows.keys().last() {
+            self.closed_until = self
+                .closed_until
+                .max(last + self.configuration.window.size());
+        }
+        windows
+            .into_iter()
+            .flat_map(|(start, groups)| self.emit(start, groups))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aggregation::{
+        AggregationConfiguration, GroupKey, Statistic, Window, WindowedAggregator,
+    };
+    use crate::match_query::MatchQuery;
+    use crate::primitives::attribute_value::AttributeValue;
+    use crate::primitives::{Attribute, WithAttributes};
+    use crate::test::gen_frame;
+
+    fn s(v: &str) -> String {
+        v.to_string()
+    }
+
+    #[test]
+    fn test_parse() -> anyhow::Result<()> {
+        assert_eq!("p95".parse::<Statistic>()?, Statistic::Percentile(95.0));
+        assert_eq!(Statistic::Percentile(95.0).name(), "p95");
+        assert!("median".parse::<Statistic>().is_err());
+        assert_eq!(
+            "zone/name".parse::<GroupKey>()?,
+            GroupKey::Attribute {
+                namespace: s("zone"),
+                name: s("name")
+            }
+        );
+        assert!("zone".parse::<GroupKey>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_tumbling() -> anyhow::Result<()> {
+        let mut aggregator = WindowedAggregator::new(AggregationConfiguration {
+            window: Window::Tumbling { size_ms: 1000 },
+            statistics: vec![
+                Statistic::Count,
+                Statistic::Mean,
+                Statistic::Max,
+                Statistic::Percentile(50.0),
+            ],
+            group_by: vec![GroupKey::Source],
+            value: None,
+            objects: None,
+        })?;
+        for (i, v) in [1.0, 2.0, 6.0].into_iter().enumerate() {
+            aggregator.observe(vec![s("cam")], v, 100 * i as u64);
+        }
+        aggregator.observe(vec![s("cam")], 10.0, 1500);
+        assert!(aggregator.close(999).is_empty());
+
+        let results = aggregator.close(1000);
+        assert_eq!(results.len(), 1);
+        assert_eq!((results[0].start_ms, results[0].end_ms), (0, 1000));
+        let values = results[0]
+            .statistics
+            .iter()
+            .map(|(_, v)| *v)
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![3.0, 3.0, 6.0, 2.0]);
+
+        assert!(!aggregator.observe(vec![s("cam")], 1.0, 500));
+        assert_eq!(aggregator.get_late(), 1);
+        let results = aggregator.flush();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].start_ms, 1000);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sliding_objects() -> anyhow::Result<()> {
+        assert!(WindowedAggregator::new(AggregationConfiguration {
+            window: Window::Sliding {
+                size_ms: 1000,
+                step_ms: 2000
+            },
+            statistics: vec![Statistic::Count],
+            group_by: vec![],
+            value: None,
+            objects: None,
+        })
+        .is_err());
+
+        let mut aggregator = WindowedAggregator::new(AggregationConfiguration {
+            window: Window::Sliding {
+                size_ms: 1000,
+                step_ms: 500,
+            },
+            statistics: vec![Statistic::Count],
+            group_by: vec![
+                GroupKey::Class,
+                GroupKey::Attribute {
+                    namespace: s("zone"),
+                    name: s("name"),
+                },
+            ],
+            value: None,
+            objects: Some(MatchQuery::Idle),
+        })?;
+        let frame = gen_frame();
+        for mut o in frame.get_all_objects() {
+            o.set_attribute(Attribute::persistent(
+                "zone",
+                "name",
+                vec![AttributeValue::string("entrance", None)],
+                &None,
+                false,
+            ));
+        }
+        assert_eq!(aggregator.observe_frame(&frame, 700), 3);
+
+        // the frame belongs to the windows starting at 0 and 500
+        let results = aggregator.close(1000);
+        assert!(results.iter().all(|r| r.start_ms == 0));
+        assert_eq!(results.iter().map(|r| r.statistics[0].1).sum::<f64>(), 3.0);
+        assert_eq!(results[0].key[1], "entrance");
+        let attributes = results[0].to_kvs_attributes("occupancy");
+        assert_eq!(
+            attributes[0].name,
+            format!("{}/count", results[0].key.join("/"))
+        );
+        assert_eq!(aggregator.close(1500).len(), results.len());
+        Ok(())
+    }
+}
@@ -0,0 +1,275 @@
+use anyhow::{bail, Result};
+use opentelemetry::trace::TraceContextExt;
+use savant_core::pipeline::{Pipeline, PipelineStagePayloadType};
+use savant_core::primitives::frame::VideoFrameProxy;
+use savant_core::primitives::object::IdCollisionResolutionPolicy;
+use savant_core::rust::PipelineConfigurationBuilder;
+use savant_core::telemetry::{init, TelemetryConfiguration};
+use savant_core::test::{gen_empty_frame, gen_object};
+use serde::Deserialize;
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "Usage: savant-bench CONFIG
+
+Builds a pipeline from the YAML configuration, drives it with synthetic frames and prints
+throughput, latency percentiles and per-stage metrics as a JSON document.
+
+Configuration example:
+
+  frames: 10000
+  sources: 4
+  objects_per_frame: 10
+  sampling_period: 0
+  stages:
+    - name: decode
+    - name: infer
+      payload: batch
+      batch_size: 8
+    - name: sink";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Payload {
+    #[default]
+    Frame,
+    Batch,
+}
+
+impl From<Payload> for PipelineStagePayloadType {
+    fn from(p: Payload) -> Self {
+        match p {
+            Payload::Frame => PipelineStagePayloadType::Frame,
+            Payload::Batch => PipelineStagePayloadType::Batch,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct StageConfig {
+    name: String,
+    #[serde(default)]
+    payload: Payload,
+    #[serde(default = "default_batch_size")]
+    batch_size: usize,
+}
+
+fn default_batch_size() -> usize {
+    1
+}
+
+#[derive(Debug, Deserialize)]
+struct BenchConfig {
+    #[serde(default = "default_frames")]
+    frames: usize,
+    #[serde(default = "default_sources")]
+    sources: usize,
+    #[serde(default)]
+    objects_per_frame: usize,
+    #[serde(default)]
+    sampling_period: i64,
+    #[serde(default = "default_frame_period")]
+    frame_period: i64,
+    stages: Vec<StageConfig>,
+}
+
+fn default_frames() -> usize {
+    10000
+}
+
+fn default_sources() -> usize {
+    1
+}
+
+fn default_frame_period() -> i64 {
+    1000
+}
+
+impl BenchConfig {
+    fn validate(&self) -> Result<()> {
+        match self.stages.first() {
+            None => bail!("At least one stage must be configured"),
+            Some(s) if s.payload != Payload::Frame => {
+                bail!("The first stage '{}' must accept frames", s.name)
+            }
+            _ => (),
+        }
+        if self.sources == 0 {
+            bail!("At least one source must be configured");
+        }
+        if self.stages.iter().any(|s| s.batch_size == 0) {
+            bail!("Batch size must be positive");
+        }
+        Ok(())
+    }
+
+    // frames are pushed through the pipeline in waves large enough to fill the largest batch
+    fn wave_size(&self) -> usize {
+        self.stages.iter().map(|s| s.batch_size).max().unwrap_or(1)
+    }
+}
+
+enum Units {
+    Frames(Vec<i64>),
+    Batches(Vec<i64>),
+}
+
+fn gen_workload_frame(config: &BenchConfig, n: usize) -> Result<VideoFrameProxy> {
+    let mut frame = gen_empty_frame();
+    frame.set_source_id(&format!("source-{}", n % config.sources));
+    frame.set_pts((n / config.sources) as i64);
+    for i in 0..config.objects_per_frame {
+        frame.add_object(
+            gen_object(i as i64),
+            IdCollisionResolutionPolicy::GenerateNewId,
+        )?;
+    }
+    Ok(frame)
+}
+
+fn move_to(pipeline: &Pipeline, stage: &StageConfig, units: Units) -> Result<Units> {
+    Ok(match (units, stage.payload) {
+        (Units::Frames(ids), Payload::Frame) => {
+            pipeline.move_as_is(&stage.name, ids.clone())?;
+            Units::Frames(ids)
+        }
+        (Units::Batches(ids), Payload::Batch) => {
+            pipeline.move_as_is(&stage.name, ids.clone())?;
+            Units::Batches(ids)
+        }
+        (Units::Frames(ids), Payload::Batch) => Units::Batches(
+            ids.chunks(stage.batch_size)
+                .map(|chunk| pipeline.move_and_pack_frames(&stage.name, chunk.to_vec()))
+                .collect::<Result<_>>()?,
+        ),
+        (Units::Batches(ids), Payload::Frame) => {
+            let mut frames = Vec::new();
+            for id in ids {
+                frames.extend(pipeline.move_and_unpack_batch(&stage.name, id)?);
+            }
+            Units::Frames(frames)
+        }
+    })
+}
+
+fn percentile(sorted: &[Duration], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let idx = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[idx].as_secs_f64() * 1000.0
+}
+
+fn stage_metrics(pipeline: &Pipeline) -> serde_json::Value {
+    pipeline.log_final_fps();
+    let Some(record) = pipeline.get_stat_records(1).pop() else {
+        return json!([]);
+    };
+    record
+        .stage_stats
+        .iter()
+        .map(|(processing, latency)| {
+            let latencies = latency
+                .latencies
+                .values()
+                .map(|m| {
+                    json!({
+                        "from": m.source_stage_name,
+                        "min_us": m.min_latency.as_micros(),
+                        "max_us": m.max_latency.as_micros(),
+                        "avg_us": m.accumulated_latency.as_micros() / m.count.max(1) as u128,
+                        "count": m.count,
+                    })
+                })
+                .collect::<Vec<_>>();
+            json!({
+                "name": processing.stage_name,
+                "queue_length": processing.queue_length,
+                "frames": processing.frame_counter,
+                "objects": processing.object_counter,
+                "batches": processing.batch_counter,
+                "latencies": latencies,
+            })
+        })
+        .collect()
+}
+
+fn run(config: &BenchConfig) -> Result<serde_json::Value> {
+    let conf = PipelineConfigurationBuilder::default()
+        .frame_period(Some(config.frame_period))
+        .timestamp_period(None)
+        .build()?;
+    let stages = config
+        .stages
+        .iter()
+        .map(|s| (s.name.clone(), s.payload.into(), None, None))
+        .collect();
+    let pipeline = Pipeline::new(stages, conf)?;
+    pipeline.set_root_span_name("savant-bench".to_owned())?;
+    pipeline.set_sampling_period(config.sampling_period)?;
+
+    let first_stage = &config.stages[0].name;
+    let wave_size = config.wave_size();
+    let mut latencies = Vec::with_capacity(config.frames);
+    let started = Instant::now();
+    let mut produced = 0;
+    while produced < config.frames {
+        let wave = wave_size.min(config.frames - produced);
+        let mut ids = Vec::with_capacity(wave);
+        let mut added = Vec::with_capacity(wave);
+        for n in produced..produced + wave {
+            let frame = gen_workload_frame(config, n)?;
+            added.push(Instant::now());
+            ids.push(pipeline.add_frame(first_stage, frame)?);
+        }
+        produced += wave;
+
+        let mut units = Units::Frames(ids);
+        for stage in &config.stages[1..] {
+            units = move_to(&pipeline, stage, units)?;
+        }
+        let ids = match units {
+            Units::Frames(ids) | Units::Batches(ids) => ids,
+        };
+        for id in ids {
+            for (_, ctx) in pipeline.delete(id)? {
+                ctx.span().end();
+            }
+        }
+        let done = Instant::now();
+        latencies.extend(added.into_iter().map(|t| done - t));
+    }
+    let elapsed = started.elapsed();
+
+    latencies.sort();
+    Ok(json!({
+        "frames": config.frames,
+        "sources": config.sources,
+        "objects_per_frame": config.objects_per_frame,
+        "elapsed_ms": elapsed.as_secs_f64() * 1000.0,
+        "throughput_fps": config.frames as f64 / elapsed.as_secs_f64(),
+        "latency_ms": {
+            "p50": percentile(&latencies, 0.5),
+            "p90": percentile(&latencies, 0.9),
+            "p99": percentile(&latencies, 0.99),
+            "max": percentile(&latencies, 1.0),
+        },
+        "stages": stage_metrics(&pipeline),
+    }))
+}
+
+fn main() -> Result<()> {
+    let path = match std::env::args().nth(1).as_deref() {
+        None | Some("-h") | Some("--help") => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        Some(path) => path.to_string(),
+    };
+    let config: BenchConfig = serde_yaml::from_reader(std::fs::File::open(path)?)?;
+    config.validate()?;
+    init(&TelemetryConfiguration::no_op());
+    let report = run(&config)?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
@@ -0,0 +1,230 @@
+use anyhow::{anyhow, bail, Result};
+use savant_core::primitives::attribute_set::AttributeSet;
+use savant_core::protobuf::ToProtobuf;
+use std::io::{stdin, stdout, Read, Write};
+use std::time::Duration;
+
+const DEFAULT_URL: &str = "http://localhost:8080";
+
+const USAGE: &str = "Usage: savant-ctl [--url URL] COMMAND
+
+Controls a running pipeline through its webserver (default URL: http://localhost:8080).
+
+Commands:
+  kvs get NS NAME                      print the attribute as JSON
+  kvs search [NS] [NAME] [--keys]      print matching attributes (or only their keys), globs allowed
+  kvs set [--ttl MS] [FILE]            store attributes read as JSON from FILE or stdin
+  kvs delete NS NAME [--single]        delete matching attributes, --single prints the removed one
+  kvs watch [NS] [NAME] [--interval MS]
+                                       print matching attributes whenever they change
+  kvs encode [FILE]                    convert JSON attributes to the protobuf body of /kvs/set
+  kvs decode [FILE]                    convert a protobuf attribute set to JSON
+
+JSON input is a single attribute, a list of attributes or an {\"attributes\": [...]} document.";
+
+struct Client {
+    url: String,
+    client: reqwest::Client,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl Client {
+    fn new(url: String) -> Result<Self> {
+        Ok(Self {
+            url: url.trim_end_matches('/').to_string(),
+            client: reqwest::Client::new(),
+            runtime: tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?,
+        })
+    }
+
+    fn get(&self, path: &str) -> Result<Vec<u8>> {
+        self.send(self.client.get(format!("{}{}", self.url, path)))
+    }
+
+    fn post(&self, path: &str, body: Vec<u8>) -> Result<Vec<u8>> {
+        self.send(self.client.post(format!("{}{}", self.url, path)).body(body))
+    }
+
+    fn send(&self, request: reqwest::RequestBuilder) -> Result<Vec<u8>> {
+        self.runtime.block_on(async {
+            let response = request.send().await?;
+            let status = response.status();
+            if !status.is_success() {
+                bail!("Request failed with status {}", status);
+            }
+            Ok(response.bytes().await?.to_vec())
+        })
+    }
+
+    fn get_attributes(&self, path: &str) -> Result<AttributeSet> {
+        Ok(AttributeSet::from(AttributeSet::deserialize(
+            &self.get(path)?,
+        )?))
+    }
+}
+
+fn read_input(file: Option<&String>) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    match file.map(|f| f.as_str()) {
+        None | Some("-") => {
+            stdin().lock().read_to_end(&mut bytes)?;
+        }
+        Some(path) => bytes = std::fs::read(path)?,
+    }
+    Ok(bytes)
+}
+
+fn encode_json(bytes: &[u8]) -> Result<Vec<u8>> {
+    let set = AttributeSet::from_json(std::str::from_utf8(bytes)?)?;
+    Ok(set.to_pb()?)
+}
+
+// splits positional arguments from `--flag [value]` options
+fn split_options<'a>(
+    args: &'a [String],
+    with_value: &[&str],
+    flags: &[&str],
+) -> Result<(Vec<&'a String>, Vec<(&'a str, Option<&'a String>)>)> {
+    let mut positional = Vec::new();
+    let mut options = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if with_value.contains(&arg.as_str()) {
+            let value = iter
+                .next()
+                .ok_or_else(|| anyhow!("Missing value for {}", arg))?;
+            options.push((arg.as_str(), Some(value)));
+        } else if flags.contains(&arg.as_str()) {
+            options.push((arg.as_str(), None));
+        } else if arg.starts_with("--") {
+            bail!("Unknown option {}\n\n{}", arg, USAGE);
+        } else {
+            positional.push(arg);
+        }
+    }
+    Ok((positional, options))
+}
+
+fn option_value<'a>(options: &[(&str, Option<&'a String>)], name: &str) -> Option<&'a String> {
+    options
+        .iter()
+        .find(|(n, _)| *n == name)
+        .and_then(|(_, v)| *v)
+}
+
+fn has_flag(options: &[(&str, Option<&String>)], name: &str) -> bool {
+    options.iter().any(|(n, _)| *n == name)
+}
+
+fn pattern(positional: &[&String], idx: usize) -> String {
+    positional
+        .get(idx)
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| "*".to_string())
+}
+
+fn key(positional: &[&String]) -> Result<(String, String)> {
+    match positional {
+        [ns, name] => Ok((ns.to_string(), name.to_string())),
+        _ => bail!("Namespace and name are required\n\n{}", USAGE),
+    }
+}
+
+fn kvs(client: &Client, command: &str, args: &[String]) -> Result<()> {
+    let mut out = stdout().lock();
+    match command {
+        "get" => {
+            let (positional, _) = split_options(args, &[], &[])?;
+            let (ns, name) = key(&positional)?;
+            let set = client.get_attributes(&format!("/kvs/get/{}/{}", ns, name))?;
+            writeln!(out, "{}", set.json_pretty())?;
+        }
+        "search" => {
+            let (positional, options) = split_options(args, &[], &["--keys"])?;
+            let (ns, name) = (pattern(&positional, 0), pattern(&positional, 1));
+            if has_flag(&options, "--keys") {
+                let keys = client.get(&format!("/kvs/search-keys/{}/{}", ns, name))?;
+                let keys: serde_json::Value = serde_json::from_slice(&keys)?;
+                writeln!(out, "{}", serde_json::to_string_pretty(&keys)?)?;
+            } else {
+                let set = client.get_attributes(&format!("/kvs/search/{}/{}", ns, name))?;
+                writeln!(out, "{}", set.json_pretty())?;
+            }
+        }
+        "set" => {
+            let (positional, options) = split_options(args, &["--ttl"], &[])?;
+            let body = encode_json(&read_input(positional.first().copied())?)?;
+            let path = match option_value(&options, "--ttl") {
+                Some(ttl) => format!("/kvs/set-with-ttl/{}", ttl.parse::<u64>()?),
+                None => "/kvs/set".to_string(),
+            };
+            client.post(&path, body)?;
+        }
+        "delete" => {
+            let (positional, options) = split_options(args, &[], &["--single"])?;
+            let (ns, name) = key(&positional)?;
+            if has_flag(&options, "--single") {
+                let removed =
+                    client.post(&format!("/kvs/delete-single/{}/{}", ns, name), vec![])?;
+                let set = AttributeSet::from(AttributeSet::deserialize(&removed)?);
+                writeln!(out, "{}", set.json_pretty())?;
+            } else {
+                client.post(&format!("/kvs/delete/{}/{}", ns, name), vec![])?;
+            }
+        }
+        "watch" => {
+            let (positional, options) = split_options(args, &["--interval"], &[])?;
+            let (ns, name) = (pattern(&positional, 0), pattern(&positional, 1));
+            let interval = option_value(&options, "--interval")
+                .map(|i| i.parse::<u64>())
+                .transpose()?
+                .unwrap_or(1000);
+            let path = format!("/kvs/search/{}/{}", ns, name);
+            let mut last = None;
+            loop {
+                let set = client.get_attributes(&path)?;
+                let json = set.json();
+                if last.as_ref() != Some(&json) {
+                    writeln!(out, "{}", json)?;
+                    out.flush()?;
+                    last = Some(json);
+                }
+                std::thread::sleep(Duration::from_millis(interval));
+            }
+        }
+        "encode" => {
+            let (positional, _) = split_options(args, &[], &[])?;
+            out.write_all(&encode_json(&read_input(positional.first().copied())?)?)?;
+        }
+        "decode" => {
+            let (positional, _) = split_options(args, &[], &[])?;
+            let bytes = read_input(positional.first().copied())?;
+            let set = AttributeSet::from(AttributeSet::deserialize(&bytes)?);
+            writeln!(out, "{}", set.json_pretty())?;
+        }
+        _ => bail!("Unknown kvs command {}\n\n{}", command, USAGE),
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let mut url = DEFAULT_URL.to_string();
+    if args.first().map(|a| a == "--url").unwrap_or(false) {
+        if args.len() < 2 {
+            bail!("Missing value for --url");
+        }
+        url = args.remove(1);
+        args.remove(0);
+    }
+    match args.first().map(|a| a.as_str()) {
+        Some("kvs") if args.len() > 1 => kvs(&Client::new(url)?, &args[1], &args[2..]),
+        Some("-h") | Some("--help") | None => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => bail!("Unknown command\n\n{}", USAGE),
+    }
+}
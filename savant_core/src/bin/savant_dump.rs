@@ -0,0 +1,119 @@
+use savant_core::dump::{format_message, DumpFilter, DumpFormat};
+use savant_core::message::{load_message, Message};
+use savant_core::protobuf::{
+    EncryptedMessageStreamReader, EnvKeyResolver, Error, MessageStreamReader,
+};
+use std::fs::File;
+use std::io::{stdin, stdout, BufReader, IsTerminal, Read, Write};
+use std::sync::Arc;
+
+const USAGE: &str = "Usage: savant-dump [OPTIONS] [FILE]...
+
+Prints serialized messages from journals (length-delimited message streams) or
+single-message files. Reads stdin when no files are given or FILE is '-'.
+
+Options:
+  --json               print messages as JSON documents, one per line
+  --no-color           disable colors in text output
+  --single             treat every file as a single serialized message
+  --decrypt            read encrypted journals, the master keys are read as hex
+                       digits from SAVANT_JOURNAL_KEY_<KEY_ID>
+  --source <ID>        only messages of the source
+  --pts-from <PTS>     only frames with pts >= PTS
+  --pts-to <PTS>       only frames with pts <= PTS
+  --label <LABEL>      only frames containing objects with the label
+  -h, --help           print this help";
+
+struct Args {
+    format: DumpFormat,
+    single: bool,
+    decrypt: bool,
+    filter: DumpFilter,
+    files: Vec<String>,
+}
+
+fn parse_args() -> anyhow::Result<Args> {
+    let mut json = false;
+    let mut color = stdout().is_terminal();
+    let mut single = false;
+    let mut decrypt = false;
+    let mut filter = DumpFilter::default();
+    let mut files = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| anyhow::anyhow!("Missing value for {}", name))
+        };
+        match arg.as_str() {
+            "--json" => json = true,
+            "--no-color" => color = false,
+            "--single" => single = true,
+            "--decrypt" => decrypt = true,
+            "--source" => filter.source_id = Some(value("--source")?),
+            "--pts-from" => filter.pts_from = Some(value("--pts-from")?.parse()?),
+            "--pts-to" => filter.pts_to = Some(value("--pts-to")?.parse()?),
+            "--label" => filter.label = Some(value("--label")?),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            s if s.starts_with("--") => anyhow::bail!("Unknown option {}\n\n{}", s, USAGE),
+            _ => files.push(arg),
+        }
+    }
+    if files.is_empty() {
+        files.push("-".to_string());
+    }
+    Ok(Args {
+        format: if json {
+            DumpFormat::Json
+        } else {
+            DumpFormat::Text { color }
+        },
+        single,
+        decrypt,
+        filter,
+        files,
+    })
+}
+
+fn open(file: &str) -> anyhow::Result<Box<dyn Read>> {
+    if file == "-" {
+        Ok(Box::new(stdin().lock()))
+    } else {
+        Ok(Box::new(BufReader::new(File::open(file)?)))
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = parse_args()?;
+    let mut out = stdout().lock();
+    for file in &args.files {
+        let mut reader = open(file)?;
+        if args.single {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes)?;
+            let m = load_message(&bytes);
+            if args.filter.matches(&m) {
+                out.write_all(format_message(&m, args.format).as_bytes())?;
+            }
+            continue;
+        }
+        let messages: Box<dyn Iterator<Item = Result<Message, Error>>> = if args.decrypt {
+            Box::new(EncryptedMessageStreamReader::new(
+                reader,
+                Arc::new(EnvKeyResolver::default()),
+            ))
+        } else {
+            Box::new(MessageStreamReader::new(reader))
+        };
+        for m in messages {
+            let m = m?;
+            if args.filter.matches(&m) {
+                out.write_all(format_message(&m, args.format).as_bytes())?;
+            }
+        }
+    }
+    Ok(())
+}
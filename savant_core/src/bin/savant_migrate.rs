@@ -0,0 +1,36 @@
+use anyhow::Result;
+use savant_core::pipeline::compat::migration_report;
+use std::path::PathBuf;
+
+const USAGE: &str = "Usage: savant-migrate [--json] PATH...
+
+Reports the uses of the legacy pipeline API in the Python and Rust files of the paths, the
+directories are scanned recursively. Every finding is printed as PATH:LINE: [RULE] MESSAGE,
+or as a JSON array with --json.";
+
+fn main() -> Result<()> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.is_empty() || args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    let json = args.iter().any(|a| a == "--json");
+    let paths = args
+        .iter()
+        .filter(|a| *a != "--json")
+        .map(PathBuf::from)
+        .collect::<Vec<_>>();
+    let findings = migration_report(&paths)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&findings)?);
+        return Ok(());
+    }
+    for finding in &findings {
+        println!(
+            "{}:{}: [{}] {}",
+            finding.path, finding.line, finding.rule, finding.message
+        );
+    }
+    println!("{} finding(s)", findings.len());
+    Ok(())
+}
@@ -0,0 +1,209 @@
+use anyhow::{anyhow, bail, Result};
+use savant_core::replay::{
+    capture_journal, compare_journals, diff_journals, replay_journal, ComparisonTolerance,
+};
+use savant_core::transport::zeromq::{ReaderConfig, SyncReader, SyncWriter, WriterConfig};
+use std::time::Duration;
+
+const DEFAULT_TOPIC: &str = "replay";
+
+const USAGE: &str = "Usage: savant-replay COMMAND
+
+Replays recorded journals through a pipeline and compares the results.
+
+Commands:
+  replay --to URL [--topic TOPIC] [--from URL --capture FILE] [--idle MS] JOURNAL
+                                       send the journal messages in order to the pipeline
+                                       input socket; the messages before the first frame are
+                                       sent to TOPIC (default replay); with --from the sink
+                                       output is captured to FILE until nothing arrives for
+                                       MS (default 5000)
+  compare [--json] [--match-iou X] [--move-iou X] BASELINE CANDIDATE
+                                       compare the frames of the captured journals and
+                                       print the report; exits with 1 on differences
+  diff [--changed-only] [--match-iou X] [--move-iou X] BASELINE CANDIDATE
+                                       diff the objects and attributes of the frames with
+                                       the same uuid, one JSON document per line; exits with
+                                       1 on differences
+
+The compare command identifies frames by the source and the PTS, the diff command by the
+uuid. Objects with the same namespace and label are paired when their IoU is at least
+--match-iou (default 0.3); paired objects with an IoU below --move-iou (default 0.9) are
+reported as moved.";
+
+struct Options {
+    values: Vec<(String, String)>,
+    flags: Vec<String>,
+    positional: Vec<String>,
+}
+
+impl Options {
+    fn parse(args: &[String], with_values: &[&str]) -> Result<Self> {
+        let mut options = Options {
+            values: Vec::new(),
+            flags: Vec::new(),
+            positional: Vec::new(),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if with_values.contains(&arg.as_str()) {
+                let value = args
+                    .next()
+                    .ok_or_else(|| anyhow!("Missing value for {}", arg))?;
+                options.values.push((arg.clone(), value.clone()));
+            } else if arg.starts_with("--") {
+                options.flags.push(arg.clone());
+            } else {
+                options.positional.push(arg.clone());
+            }
+        }
+        Ok(options)
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+fn replay(args: &[String]) -> Result<()> {
+    let options = Options::parse(args, &["--to", "--topic", "--from", "--capture", "--idle"])?;
+    let [journal] = options.positional.as_slice() else {
+        bail!("A single journal is expected\n\n{}", USAGE);
+    };
+    let to = options
+        .value("--to")
+        .ok_or_else(|| anyhow!("Missing --to\n\n{}", USAGE))?;
+    let idle = match options.value("--idle") {
+        Some(ms) => Duration::from_millis(ms.parse()?),
+        None => Duration::from_secs(5),
+    };
+    let capture = match (options.value("--from"), options.value("--capture")) {
+        (Some(from), Some(file)) => {
+            let reader = SyncReader::new(
+                &ReaderConfig::new()
+                    .url(from)?
+                    .with_receive_timeout(100)?
+                    .build()?,
+            )?;
+            let file = file.to_string();
+            Some(std::thread::spawn(move || {
+                let res = capture_journal(&reader, &file, idle, None);
+                reader.shutdown()?;
+                res
+            }))
+        }
+        (None, None) => None,
+        _ => bail!("--from and --capture must be used together"),
+    };
+    let writer = SyncWriter::new(&WriterConfig::new().url(to)?.build()?)?;
+    let topic = options.value("--topic").unwrap_or(DEFAULT_TOPIC);
+    let sent = replay_journal(journal, &writer, topic)?;
+    writer.shutdown()?;
+    eprintln!("Sent {} messages", sent);
+    if let Some(capture) = capture {
+        let captured = capture
+            .join()
+            .map_err(|_| anyhow!("The capture thread panicked"))??;
+        eprintln!("Captured {} messages", captured);
+    }
+    Ok(())
+}
+
+fn tolerance(options: &Options) -> Result<ComparisonTolerance> {
+    let mut tolerance = ComparisonTolerance::default();
+    if let Some(iou) = options.value("--match-iou") {
+        tolerance.match_iou = iou.parse()?;
+    }
+    if let Some(iou) = options.value("--move-iou") {
+        tolerance.move_iou = iou.parse()?;
+    }
+    Ok(tolerance)
+}
+
+fn compare(args: &[String]) -> Result<bool> {
+    let options = Options::parse(args, &["--match-iou", "--move-iou"])?;
+    let [baseline, candidate] = options.positional.as_slice() else {
+        bail!("Baseline and candidate journals are expected\n\n{}", USAGE);
+    };
+    let tolerance = tolerance(&options)?;
+    let report = compare_journals(baseline, candidate, &tolerance)?;
+    if options.flags.iter().any(|f| f == "--json") {
+        println!("{}", report.to_json()?);
+        return Ok(report.is_equal());
+    }
+    println!("Compared frames: {}", report.compared_frames);
+    for (source_id, pts) in &report.missing_frames {
+        println!("missing {} pts={}", source_id, pts);
+    }
+    for (source_id, pts) in &report.extra_frames {
+        println!("extra {} pts={}", source_id, pts);
+    }
+    for frame in &report.frames {
+        println!("frame {} pts={}", frame.source_id, frame.pts);
+        for o in &frame.added {
+            println!("  added #{} {}/{} {:?}", o.id, o.namespace, o.label, o.bbox);
+        }
+        for o in &frame.removed {
+            println!(
+                "  removed #{} {}/{} {:?}",
+                o.id, o.namespace, o.label, o.bbox
+            );
+        }
+        for m in &frame.moved {
+            println!(
+                "  moved #{} {}/{} {:?} -> {:?} iou={:.3}",
+                m.baseline.id,
+                m.baseline.namespace,
+                m.baseline.label,
+                m.baseline.bbox,
+                m.candidate.bbox,
+                m.iou
+            );
+        }
+    }
+    Ok(report.is_equal())
+}
+
+fn diff(args: &[String]) -> Result<bool> {
+    let options = Options::parse(args, &["--match-iou", "--move-iou"])?;
+    let [baseline, candidate] = options.positional.as_slice() else {
+        bail!("Baseline and candidate journals are expected\n\n{}", USAGE);
+    };
+    let changed_only = options.flags.iter().any(|f| f == "--changed-only");
+    let mut equal = true;
+    for frame in diff_journals(baseline, candidate, &tolerance(&options)?)? {
+        let frame_equal = frame.is_equal();
+        equal &= frame_equal;
+        if !(changed_only && frame_equal) {
+            println!("{}", frame.to_json()?);
+        }
+    }
+    Ok(equal)
+}
+
+fn main() -> Result<()> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.first().map(|a| a.as_str()) {
+        Some("replay") => replay(&args[1..]),
+        Some("compare") => {
+            if !compare(&args[1..])? {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Some("diff") => {
+            if !diff(&args[1..])? {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Some("-h") | Some("--help") | None => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => bail!("Unknown command\n\n{}", USAGE),
+    }
+}
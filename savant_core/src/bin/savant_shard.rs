@@ -0,0 +1,108 @@
+use anyhow::{anyhow, bail, Result};
+use savant_core::sharding::{ShardCoordinator, DEFAULT_VIRTUAL_NODES};
+use savant_core::transport::zeromq::reader::ReaderResult;
+use savant_core::transport::zeromq::{ReaderConfig, SyncReader, WriterConfig};
+use std::path::Path;
+use std::time::SystemTime;
+
+const USAGE: &str = "Usage: savant-shard --from URL --shards FILE [--virtual-nodes N]
+
+Shards the sources received from URL across pipeline processes by consistent hashing of the
+source id (the message topic).
+
+FILE lists a shard per line as NAME URL, lines starting with # are ignored. The file is
+re-read when it changes; the sources moving to another shard are handed off with user data
+messages carrying the sharding/handoff attribute: the previous shard receives the release
+message, the next shard receives the acquire message before the messages of the source.
+
+The default number of virtual nodes per shard is 64.";
+
+fn read_shards(path: &Path) -> Result<Vec<(String, WriterConfig)>> {
+    let mut shards = Vec::new();
+    for line in std::fs::read_to_string(path)?.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((name, url)) = line.split_once(char::is_whitespace) else {
+            bail!("Invalid shard line: {}", line);
+        };
+        shards.push((
+            name.to_string(),
+            WriterConfig::new().url(url.trim())?.build()?,
+        ));
+    }
+    Ok(shards)
+}
+
+fn modified(path: &Path) -> Result<SystemTime> {
+    Ok(std::fs::metadata(path)?.modified()?)
+}
+
+fn main() -> Result<()> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.is_empty() || args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    let mut from = None;
+    let mut shards_file = None;
+    let mut virtual_nodes = DEFAULT_VIRTUAL_NODES;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| anyhow!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--from" => from = Some(value()?.clone()),
+            "--shards" => shards_file = Some(value()?.clone()),
+            "--virtual-nodes" => virtual_nodes = value()?.parse()?,
+            _ => bail!("Unknown argument {}\n\n{}", arg, USAGE),
+        }
+    }
+    let from = from.ok_or_else(|| anyhow!("Missing --from\n\n{}", USAGE))?;
+    let shards_file = shards_file.ok_or_else(|| anyhow!("Missing --shards\n\n{}", USAGE))?;
+    let shards_file = Path::new(&shards_file);
+
+    let mut last_modified = modified(shards_file)?;
+    let mut coordinator = ShardCoordinator::new(&read_shards(shards_file)?, virtual_nodes)?;
+    let reader = SyncReader::new(
+        &ReaderConfig::new()
+            .url(&from)?
+            .with_receive_timeout(1000)?
+            .build()?,
+    )?;
+    loop {
+        match reader.receive()? {
+            ReaderResult::Message {
+                message,
+                topic,
+                data,
+                ..
+            } => {
+                let topic = String::from_utf8_lossy(&topic);
+                let data = data.iter().map(|d| d.as_slice()).collect::<Vec<_>>();
+                if message.is_shutdown() {
+                    break;
+                }
+                coordinator.route(&topic, &message, &data)?;
+            }
+            ReaderResult::TooShort(_) => eprintln!("Skipping a malformed message"),
+            _ => (),
+        }
+        let current = modified(shards_file)?;
+        if current != last_modified {
+            last_modified = current;
+            match read_shards(shards_file).and_then(|s| coordinator.update_shards(&s)) {
+                Ok(handoffs) => {
+                    eprintln!("Shard map updated, {} sources handed off", handoffs.len())
+                }
+                Err(e) => eprintln!("Failed to update the shard map: {}", e),
+            }
+        }
+    }
+    coordinator.shutdown()?;
+    reader.shutdown()?;
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use crate::simd::selected_simd_paths;
+use crate::version;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The features compiled into this build. Components use them to check compatibility before
+/// they are wired together.
+///
+pub const FEATURES: &[&str] = &[
+    "etcd",
+    "kvs",
+    "otlp",
+    "pipeline",
+    "plugins",
+    "protobuf",
+    "webserver",
+    "zmq",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Capabilities {
+    pub version: String,
+    pub protocol_version: String,
+    pub features: Vec<String>,
+    /// The instruction set selected for every vectorized kernel.
+    #[serde(default)]
+    pub simd: BTreeMap<String, String>,
+}
+
+impl Capabilities {
+    pub fn supports(&self, feature: &str) -> bool {
+        self.features.iter().any(|f| f == feature)
+    }
+}
+
+/// Reports the package version, the protocol version of serialized messages, the compiled
+/// features and the instruction sets selected for the vectorized kernels.
+///
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        version: version(),
+        protocol_version: savant_protobuf::version().to_string(),
+        features: FEATURES
+            .iter()
+            .map(|f| f.to_string())
+            .chain(cfg!(feature = "hnsw").then(|| "hnsw".to_string()))
+            .chain(cfg!(feature = "chaos").then(|| "chaos".to_string()))
+            .collect(),
+        simd: selected_simd_paths()
+            .into_iter()
+            .map(|(kernel, path)| (kernel.to_string(), path.as_str().to_string()))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::capabilities::capabilities;
+
+    #[test]
+    fn test_capabilities() {
+        let caps = capabilities();
+        assert_eq!(caps.version, crate::version());
+        assert!(caps.supports("pipeline"));
+        assert!(!caps.supports("unknown"));
+        assert!(caps.simd.contains_key("bbox_iou"));
+    }
+}
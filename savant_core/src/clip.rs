@@ -0,0 +1,199 @@
+use crate::clock::frame_time_ms;
+use crate::message::Message;
+use crate::primitives::frame::VideoFrameProxy;
+use crate::protobuf::MessageStreamReader;
+use anyhow::{bail, Context};
+use parking_lot::Mutex;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+/// The most messages a clip holds, including the frames since the keyframe preceding the
+/// interval, so a long interval or a source without keyframes does not exhaust the memory.
+///
+pub const MAX_CLIP_MESSAGES: usize = 10_000;
+
+/// The journal served by the `/clips` endpoint of the webserver.
+static CLIP_JOURNAL: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Sets the journal recording the sources, so the clips are extracted without the path.
+///
+pub fn set_clip_journal(path: Option<PathBuf>) {
+    *CLIP_JOURNAL.lock() = path;
+}
+
+pub fn get_clip_journal() -> Option<PathBuf> {
+    CLIP_JOURNAL.lock().clone()
+}
+
+/// Frames without the keyframe flag are raw and decodable on their own.
+///
+fn is_keyframe(frame: &VideoFrameProxy) -> bool {
+    frame.get_keyframe() != Some(false)
+}
+
+/// Extracts the frames of the source covering the interval from the journal, together with
+/// the user data of the source recorded between them. The times are the PTS of the frames in
+/// milliseconds, see [`crate::clock::frame_time_ms`]. The clip starts at the last keyframe
+/// before the interval so it decodes without the preceding frames, and ends before the first
+/// keyframe after the interval. Returns no messages when the journal has no frames of the
+/// source in the interval. The journal is read message by message, the extraction fails when
+/// the clip grows over [`MAX_CLIP_MESSAGES`].
+///
+pub fn extract_clip(
+    path: impl AsRef<Path>,
+    source_id: &str,
+    start_ms: u64,
+    end_ms: u64,
+) -> anyhow::Result<Vec<Message>> {
+    if start_ms > end_ms {
+        bail!("The clip start {} is after the end {}", start_ms, end_ms);
+    }
+    let path = path.as_ref();
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open the journal {}", path.display()))?;
+    // the messages since the last keyframe before the interval
+    let mut gop = Vec::new();
+    let mut clip = Vec::new();
+    let mut started = false;
+    let mut past_end = false;
+    for message in MessageStreamReader::new(BufReader::new(file)) {
+        check_clip_size(source_id, gop.len() + clip.len())?;
+        let message = message?;
+        if let Some(frame) = message.as_video_frame() {
+            if frame.get_source_id() != source_id {
+                continue;
+            }
+            let time = frame_time_ms(&frame);
+            let keyframe = is_keyframe(&frame);
+            if keyframe && time > end_ms {
+                break;
+            }
+            if started {
+                if time <= end_ms {
+                    clip.push(message);
+                } else {
+                    past_end = true;
+                }
+                continue;
+            }
+            if keyframe {
+                gop.clear();
+            }
+            if !keyframe && gop.is_empty() {
+                // the frames before the first keyframe are not decodable
+                continue;
+            }
+            gop.push(message);
+            if time >= start_ms {
+                started = true;
+                clip.append(&mut gop);
+            }
+        } else if message
+            .as_user_data()
+            .is_some_and(|u| u.get_source_id() == source_id)
+        {
+            if started {
+                if !past_end {
+                    clip.push(message);
+                }
+            } else if !gop.is_empty() {
+                gop.push(message);
+            }
+        }
+    }
+    check_clip_size(source_id, clip.len())?;
+    Ok(clip)
+}
+
+fn check_clip_size(source_id: &str, messages: usize) -> anyhow::Result<()> {
+    if messages > MAX_CLIP_MESSAGES {
+        bail!(
+            "The clip of source {} exceeds {} messages",
+            source_id,
+            MAX_CLIP_MESSAGES
+        );
+    }
+    Ok(())
+}
+
+/// Extracts the clip from the journal set with [`set_clip_journal`].
+///
+pub fn extract_recorded_clip(
+    source_id: &str,
+    start_ms: u64,
+    end_ms: u64,
+) -> anyhow::Result<Vec<Message>> {
+    let Some(path) = get_clip_journal() else {
+        bail!("No clip journal is set");
+    };
+    extract_clip(path, source_id, start_ms, end_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clip::{extract_clip, MAX_CLIP_MESSAGES};
+    use crate::message::Message;
+    use crate::primitives::userdata::UserData;
+    use crate::protobuf::MessageStreamWriter;
+    use crate::test::gen_frame;
+    use crate::utils::uuid_v7::incremental_uuid_v7;
+
+    #[test]
+    fn test_extract_clip() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join(format!("savant-clip-{}", incremental_uuid_v7()));
+        let mut writer = MessageStreamWriter::new(std::fs::File::create(&path)?);
+        // a keyframe every 4 frames, 100 ms apart
+        for i in 0..12i64 {
+            for source in ["a", "b"] {
+                let mut frame = gen_frame();
+                frame.set_source_id(source);
+                frame.set_time_base((1, 1000));
+                frame.set_pts(i * 100);
+                frame.set_keyframe(Some(i % 4 == 0));
+                writer.write_message(&Message::video_frame(&frame))?;
+            }
+            if i == 5 {
+                writer.write_message(&Message::user_data(UserData::new("a")))?;
+            }
+        }
+        writer.flush()?;
+
+        let clip = extract_clip(&path, "a", 550, 650)?;
+        let pts = clip
+            .iter()
+            .filter_map(|m| m.as_video_frame().map(|f| f.get_pts()))
+            .collect::<Vec<_>>();
+        assert_eq!(pts, vec![400, 500, 600]);
+        assert_eq!(clip.len(), 4);
+        assert!(clip[0].as_video_frame().unwrap().get_keyframe().unwrap());
+
+        let pts = extract_clip(&path, "b", 800, 2000)?
+            .iter()
+            .filter_map(|m| m.as_video_frame().map(|f| f.get_pts()))
+            .collect::<Vec<_>>();
+        assert_eq!(pts, vec![800, 900, 1000, 1100]);
+        assert!(extract_clip(&path, "c", 0, 2000)?.is_empty());
+        assert!(extract_clip(&path, "a", 2000, 0).is_err());
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_clip_limit() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join(format!("savant-clip-{}", incremental_uuid_v7()));
+        let mut writer = MessageStreamWriter::new(std::fs::File::create(&path)?);
+        let mut frame = gen_frame();
+        frame.set_time_base((1, 1000));
+        for i in 0..=MAX_CLIP_MESSAGES as i64 {
+            frame.set_pts(i);
+            frame.set_keyframe(Some(i == 0));
+            writer.write_message(&Message::video_frame(&frame))?;
+        }
+        writer.flush()?;
+        assert_eq!(extract_clip(&path, "test", 0, 10)?.len(), 11);
+        assert!(extract_clip(&path, "test", 0, MAX_CLIP_MESSAGES as u64).is_err());
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
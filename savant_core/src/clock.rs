@@ -0,0 +1,220 @@
+use crate::primitives::frame::VideoFrameProxy;
+use lazy_static::lazy_static;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How the time of the analytics is measured: the TTLs of attributes and gallery
+/// identities, the stage latencies and budgets, and the FPS statistics.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockMode {
+    /// The system time.
+    Live,
+    /// The time of the latest frame added to a pipeline, so recorded video processed faster
+    /// than real time produces the same results as live processing. The time is the PTS of
+    /// the frame converted to milliseconds and shifted by the origin, e.g. the recording
+    /// start in milliseconds since the UNIX epoch. The time never goes backwards.
+    Backfill { origin_ms: u64 },
+}
+
+/// A clock of the analytics. The process clock, see [`process_clock`], is used by the
+/// components without a pipeline, e.g. the attribute TTLs and the galleries, and by the
+/// pipelines without their own clock mode; a pipeline configured with its own clock mode
+/// has a clock advanced by its frames only, so the pipelines backfilling different
+/// recordings or running live in one process do not move each other's time.
+///
+#[derive(Debug, Default)]
+pub struct Clock {
+    backfill: AtomicBool,
+    origin_ms: AtomicU64,
+    media_ms: AtomicU64,
+}
+
+impl Clock {
+    pub fn new(mode: ClockMode) -> Self {
+        let clock = Self::default();
+        clock.set_mode(mode);
+        clock
+    }
+
+    /// Switches the clock, the backfill time restarts at the origin.
+    ///
+    pub fn set_mode(&self, mode: ClockMode) {
+        match mode {
+            ClockMode::Live => self.backfill.store(false, Ordering::SeqCst),
+            ClockMode::Backfill { origin_ms } => {
+                self.origin_ms.store(origin_ms, Ordering::SeqCst);
+                self.media_ms.store(0, Ordering::SeqCst);
+                self.backfill.store(true, Ordering::SeqCst);
+            }
+        }
+    }
+
+    pub fn get_mode(&self) -> ClockMode {
+        if self.is_backfill() {
+            ClockMode::Backfill {
+                origin_ms: self.origin_ms.load(Ordering::SeqCst),
+            }
+        } else {
+            ClockMode::Live
+        }
+    }
+
+    pub fn is_backfill(&self) -> bool {
+        self.backfill.load(Ordering::Relaxed)
+    }
+
+    /// Moves the backfill time forward to the frame.
+    ///
+    pub fn advance_to_frame(&self, frame: &VideoFrameProxy) {
+        if self.is_backfill() {
+            self.media_ms
+                .fetch_max(frame_time_ms(frame), Ordering::SeqCst);
+        }
+    }
+
+    /// The current time in milliseconds since the UNIX epoch.
+    ///
+    pub fn now_ms(&self) -> u64 {
+        if self.is_backfill() {
+            self.origin_ms
+                .load(Ordering::Relaxed)
+                .saturating_add(self.media_ms.load(Ordering::Relaxed))
+        } else {
+            system_now_ms()
+        }
+    }
+
+    pub fn now(&self) -> SystemTime {
+        if self.is_backfill() {
+            UNIX_EPOCH + Duration::from_millis(self.now_ms())
+        } else {
+            SystemTime::now()
+        }
+    }
+
+    /// The time passed since the moment, 0 if the moment is in the future.
+    ///
+    pub fn elapsed(&self, since: SystemTime) -> Duration {
+        self.now().duration_since(since).unwrap_or_default()
+    }
+}
+
+lazy_static! {
+    static ref PROCESS_CLOCK: Arc<Clock> = Arc::new(Clock::default());
+}
+
+pub fn process_clock() -> Arc<Clock> {
+    PROCESS_CLOCK.clone()
+}
+
+/// Switches the process clock, the backfill time restarts at the origin.
+///
+pub fn set_clock_mode(mode: ClockMode) {
+    PROCESS_CLOCK.set_mode(mode)
+}
+
+pub fn get_clock_mode() -> ClockMode {
+    PROCESS_CLOCK.get_mode()
+}
+
+pub fn is_backfill() -> bool {
+    PROCESS_CLOCK.is_backfill()
+}
+
+/// The system time in milliseconds since the UNIX epoch regardless of the clock mode, for
+/// the records which must follow the wall clock, e.g. the leases and the audit log.
+///
+pub fn system_now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// The position of the frame in its stream in milliseconds, negative positions are 0.
+///
+pub fn frame_time_ms(frame: &VideoFrameProxy) -> u64 {
+    let (num, den) = frame.get_time_base();
+    if den == 0 {
+        return 0;
+    }
+    let ms = frame.get_pts() as i128 * num as i128 * 1000 / den as i128;
+    ms.clamp(0, u64::MAX as i128) as u64
+}
+
+/// Moves the backfill time of the process clock forward to the frame, called by the
+/// pipelines without their own clock for every added frame.
+///
+pub fn advance_to_frame(frame: &VideoFrameProxy) {
+    PROCESS_CLOCK.advance_to_frame(frame)
+}
+
+/// The current time of the process clock in milliseconds since the UNIX epoch.
+///
+pub fn now_ms() -> u64 {
+    PROCESS_CLOCK.now_ms()
+}
+
+pub fn now() -> SystemTime {
+    PROCESS_CLOCK.now()
+}
+
+/// The time passed since the moment by the process clock, 0 if the moment is in the future.
+///
+pub fn elapsed(since: SystemTime) -> Duration {
+    PROCESS_CLOCK.elapsed(since)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clock::{
+        advance_to_frame, elapsed, frame_time_ms, get_clock_mode, now, now_ms, set_clock_mode,
+        Clock, ClockMode,
+    };
+    use crate::primitives::attribute::Attribute;
+    use crate::test::gen_frame;
+    use std::time::Duration;
+
+    #[test]
+    #[serial_test::serial]
+    fn test_backfill_clock() {
+        let mut frame = gen_frame();
+        frame.set_time_base((1, 90000));
+        frame.set_pts(90000);
+        assert_eq!(frame_time_ms(&frame), 1000);
+
+        set_clock_mode(ClockMode::Backfill { origin_ms: 10_000 });
+        assert_eq!(get_clock_mode(), ClockMode::Backfill { origin_ms: 10_000 });
+        assert_eq!(now_ms(), 10_000);
+        let started = now();
+        let mut attribute = Attribute::persistent("backfill", "ttl", vec![], &None, false);
+        attribute.set_ttl(Duration::from_millis(500));
+        advance_to_frame(&frame);
+        assert_eq!(now_ms(), 11_000);
+        assert_eq!(elapsed(started), Duration::from_secs(1));
+        assert!(attribute.is_expired());
+
+        // the time never goes backwards
+        frame.set_pts(0);
+        advance_to_frame(&frame);
+        assert_eq!(now_ms(), 11_000);
+
+        set_clock_mode(ClockMode::Live);
+        assert!(now_ms() > 1_600_000_000_000);
+    }
+
+    #[test]
+    fn test_scoped_clock() {
+        let mut frame = gen_frame();
+        frame.set_time_base((1, 1000));
+        frame.set_pts(5000);
+        let clock = Clock::new(ClockMode::Backfill { origin_ms: 0 });
+        let other = Clock::new(ClockMode::Backfill { origin_ms: 100 });
+        clock.advance_to_frame(&frame);
+        assert_eq!(clock.now_ms(), 5000);
+        // the frames of one clock do not move the others
+        assert_eq!(other.now_ms(), 100);
+    }
+}
@@ -1,3 +1,8 @@
+pub mod template;
+
+use crate::draw::template::get_compiled_label_template;
+use crate::primitives::object::{BorrowedVideoObject, ObjectOperations};
+use crate::symbol_mapper::SymbolMapper;
 use anyhow::{bail, Result};
 
 /// Defines the padding for a draw operation.
@@ -171,6 +176,9 @@ pub struct LabelDraw {
     pub position: LabelPosition,
     pub padding: PaddingDraw,
     pub format: Vec<String>,
+    /// The locale of the display labels substituted for `{label}`, the registered label is
+    /// used when not set.
+    pub locale: Option<String>,
 }
 impl LabelDraw {
     #[allow(clippy::too_many_arguments)]
@@ -197,8 +205,42 @@ impl LabelDraw {
             position,
             padding,
             format,
+            locale: None,
         })
     }
+
+    pub fn with_locale(self, locale: Option<String>) -> Self {
+        Self { locale, ..self }
+    }
+
+    /// Returns the label to display for the object of the model according to the locale.
+    ///
+    pub fn display_label(
+        &self,
+        mapper: &SymbolMapper,
+        model_name: &str,
+        object_label: &str,
+    ) -> String {
+        self.locale
+            .as_ref()
+            .and_then(|l| mapper.get_display_label_by_name(model_name, object_label, l))
+            .unwrap_or_else(|| object_label.to_string())
+    }
+
+    /// Renders the format strings for the object, see [`template::LabelTemplate`]; `{label}`
+    /// is the display label of the locale.
+    ///
+    pub fn render_labels(
+        &self,
+        mapper: &SymbolMapper,
+        object: &BorrowedVideoObject,
+    ) -> Result<Vec<String>> {
+        let label = self.display_label(mapper, &object.get_namespace(), &object.get_label());
+        self.format
+            .iter()
+            .map(|f| Ok(get_compiled_label_template(f)?.render_with_label(object, &label)))
+            .collect()
+    }
 }
 
 #[derive(Clone, Debug)]
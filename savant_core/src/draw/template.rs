@@ -0,0 +1,319 @@
+use crate::primitives::attribute_value::AttributeValueVariant;
+use crate::primitives::object::private::SealedWithFrame;
+use crate::primitives::object::{BorrowedVideoObject, ObjectOperations};
+use crate::primitives::{Attribute, WithAttributes};
+use anyhow::{bail, Context, Result};
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+const MAX_LABEL_TEMPLATE_CACHE_SIZE: usize = 1024;
+
+lazy_static! {
+    static ref COMPILED_LABEL_TEMPLATES: Mutex<lru::LruCache<String, Arc<LabelTemplate>>> =
+        Mutex::new(lru::LruCache::new(
+            std::num::NonZeroUsize::new(MAX_LABEL_TEMPLATE_CACHE_SIZE).unwrap()
+        ));
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Field {
+    Id,
+    Namespace,
+    Label,
+    DrawLabel,
+    TrackId,
+    Confidence,
+    /// An attribute of the object or, with `frame`, of its frame. Without the namespace the
+    /// first attribute with the name is used.
+    Attribute {
+        frame: bool,
+        namespace: Option<String>,
+        name: String,
+        index: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FormatSpec {
+    Default,
+    /// `.Nf`, the numbers with N decimal places.
+    Fixed(usize),
+    /// `d`, the numbers rounded to integers.
+    Integer,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Field(Field, FormatSpec),
+}
+
+/// A compiled label template, e.g. `{label} #{track_id} {speed:.1f} km/h`. The fields are
+/// `id`, `namespace`, `label`, `draw_label` (the draw label or the label), `track_id`,
+/// `confidence` and the attributes: `name` (the first attribute of the object with the
+/// name), `namespace/name` and `frame:namespace/name` (an attribute of the frame), with an
+/// optional value index, e.g. `color[1]`. A field may have a format: `.Nf` or `d`. Missing
+/// values render as empty strings; `{{` and `}}` are literal braces.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelTemplate {
+    segments: Vec<Segment>,
+}
+
+fn parse_spec(spec: &str) -> Result<FormatSpec> {
+    match spec {
+        "" => Ok(FormatSpec::Default),
+        "d" => Ok(FormatSpec::Integer),
+        _ => match spec
+            .strip_prefix('.')
+            .and_then(|s| s.strip_suffix('f'))
+            .and_then(|p| p.parse().ok())
+        {
+            Some(precision) => Ok(FormatSpec::Fixed(precision)),
+            None => bail!("Unknown format `{}`, expected .Nf or d", spec),
+        },
+    }
+}
+
+fn parse_field(frame: bool, field: &str) -> Result<Field> {
+    Ok(match field {
+        "id" if !frame => Field::Id,
+        "namespace" if !frame => Field::Namespace,
+        "label" if !frame => Field::Label,
+        "draw_label" if !frame => Field::DrawLabel,
+        "track_id" if !frame => Field::TrackId,
+        "confidence" if !frame => Field::Confidence,
+        path => {
+            let (path, index) = match path.strip_suffix(']').and_then(|p| p.split_once('[')) {
+                Some((path, index)) => (
+                    path,
+                    index
+                        .parse()
+                        .with_context(|| format!("Invalid value index in `{}`", field))?,
+                ),
+                None => (path, 0),
+            };
+            let (namespace, name) = match path.split_once('/') {
+                Some((namespace, name)) => (Some(namespace.to_string()), name),
+                None if frame => bail!("Frame attribute `{}` requires a namespace", field),
+                None => (None, path),
+            };
+            if name.is_empty() || namespace.as_ref().is_some_and(|n| n.is_empty()) {
+                bail!("Invalid field `{}`", field);
+            }
+            Field::Attribute {
+                frame,
+                namespace,
+                name: name.to_string(),
+                index,
+            }
+        }
+    })
+}
+
+fn format_number(value: f64, spec: FormatSpec) -> String {
+    match spec {
+        FormatSpec::Default => value.to_string(),
+        FormatSpec::Fixed(precision) => format!("{:.*}", precision, value),
+        FormatSpec::Integer => format!("{}", value.round() as i64),
+    }
+}
+
+fn format_value(value: &AttributeValueVariant, spec: FormatSpec) -> String {
+    match value {
+        AttributeValueVariant::String(s) => s.clone(),
+        AttributeValueVariant::Integer(i) => match spec {
+            FormatSpec::Fixed(_) => format_number(*i as f64, spec),
+            _ => i.to_string(),
+        },
+        AttributeValueVariant::Float(f) => format_number(*f, spec),
+        AttributeValueVariant::Boolean(b) => b.to_string(),
+        _ => String::new(),
+    }
+}
+
+impl LabelTemplate {
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => placeholder.push(c),
+                            None => bail!("Unclosed placeholder in `{}`", template),
+                        }
+                    }
+                    let placeholder = placeholder.trim();
+                    let (frame, placeholder) = match placeholder.strip_prefix("frame:") {
+                        Some(rest) => (true, rest),
+                        None => (false, placeholder),
+                    };
+                    let (field, spec) = placeholder.split_once(':').unwrap_or((placeholder, ""));
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+                    segments.push(Segment::Field(
+                        parse_field(frame, field.trim())?,
+                        parse_spec(spec.trim())?,
+                    ));
+                }
+                '}' => bail!("Unmatched `}}` in `{}`", template),
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+        Ok(Self { segments })
+    }
+
+    fn attribute(object: &BorrowedVideoObject, field: &Field) -> Option<Attribute> {
+        let Field::Attribute {
+            frame,
+            namespace,
+            name,
+            ..
+        } = field
+        else {
+            return None;
+        };
+        match (frame, namespace) {
+            (true, Some(namespace)) => object.get_frame()?.get_attribute(namespace, name),
+            (_, Some(namespace)) => object.get_attribute(namespace, name),
+            (_, None) => {
+                let (namespace, name) = object
+                    .find_attributes_with_names(&[name.as_str()])
+                    .into_iter()
+                    .next()?;
+                object.get_attribute(&namespace, &name)
+            }
+        }
+    }
+
+    fn render_field(
+        object: &BorrowedVideoObject,
+        label: &str,
+        field: &Field,
+        spec: FormatSpec,
+    ) -> String {
+        match field {
+            Field::Id => object.get_id().to_string(),
+            Field::Namespace => object.get_namespace(),
+            Field::Label => label.to_string(),
+            Field::DrawLabel => object.get_draw_label().unwrap_or_else(|| label.to_string()),
+            Field::TrackId => object
+                .get_track_id()
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+            Field::Confidence => object
+                .get_confidence()
+                .map(|c| format_number(c as f64, spec))
+                .unwrap_or_default(),
+            Field::Attribute { index, .. } => Self::attribute(object, field)
+                .and_then(|a| {
+                    a.get_values()
+                        .get(*index)
+                        .map(|v| format_value(v.get(), spec))
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Renders the template for the object, `label` is substituted for `{label}`, e.g. the
+    /// display label of the locale.
+    ///
+    pub fn render_with_label(&self, object: &BorrowedVideoObject, label: &str) -> String {
+        self.segments
+            .iter()
+            .map(|s| match s {
+                Segment::Text(text) => text.clone(),
+                Segment::Field(field, spec) => Self::render_field(object, label, field, *spec),
+            })
+            .collect()
+    }
+
+    pub fn render(&self, object: &BorrowedVideoObject) -> String {
+        self.render_with_label(object, &object.get_label())
+    }
+}
+
+/// Returns the compiled template, the templates are compiled once and cached.
+///
+pub fn get_compiled_label_template(template: &str) -> Result<Arc<LabelTemplate>> {
+    let mut compiled = COMPILED_LABEL_TEMPLATES.lock();
+    if let Some(t) = compiled.get(template) {
+        return Ok(t.clone());
+    }
+    let t = Arc::new(LabelTemplate::parse(template)?);
+    compiled.put(template.to_string(), t.clone());
+    Ok(t)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::draw::template::{get_compiled_label_template, LabelTemplate};
+    use crate::primitives::attribute_value::AttributeValue;
+    use crate::primitives::object::ObjectOperations;
+    use crate::primitives::{RBBox, WithAttributes};
+    use crate::test::gen_frame;
+
+    #[test]
+    fn test_render() -> anyhow::Result<()> {
+        let mut frame = gen_frame();
+        frame.set_persistent_attribute(
+            "camera",
+            "zone",
+            &None,
+            false,
+            vec![AttributeValue::string("gate", None)],
+        );
+        let mut object = frame.get_object(1).unwrap();
+        object.set_track_info(42, RBBox::new(0.0, 0.0, 1.0, 1.0, None));
+        object.set_persistent_attribute(
+            "speed",
+            "speed",
+            &None,
+            false,
+            vec![AttributeValue::float(57.26, None)],
+        );
+        object.set_persistent_attribute(
+            "color",
+            "color",
+            &None,
+            false,
+            vec![
+                AttributeValue::string("red", None),
+                AttributeValue::string("blue", None),
+            ],
+        );
+
+        let template = get_compiled_label_template("{label} #{track_id} {speed:.1f} km/h")?;
+        assert_eq!(template.render(&object), "test #42 57.3 km/h");
+        let template = LabelTemplate::parse("{{{color/color[1]}}} {speed:d} {frame:camera/zone}")?;
+        assert_eq!(template.render(&object), "{blue} 57 gate");
+        assert_eq!(
+            LabelTemplate::parse("{label}{missing}")?.render_with_label(&object, "Test"),
+            "Test"
+        );
+
+        assert!(LabelTemplate::parse("{label").is_err());
+        assert!(LabelTemplate::parse("label}").is_err());
+        assert!(LabelTemplate::parse("{speed:x}").is_err());
+        assert!(LabelTemplate::parse("{frame:zone}").is_err());
+        Ok(())
+    }
+}
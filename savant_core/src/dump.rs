@@ -0,0 +1,354 @@
+use crate::json_api::ToSerdeJsonValue;
+use crate::message::{Message, MessageEnvelope};
+use crate::primitives::frame::VideoFrameProxy;
+use crate::primitives::object::ObjectOperations;
+use crate::primitives::WithAttributes;
+use serde_json::Value;
+use std::fmt::Write;
+
+const BOLD: &str = "\x1b[1m";
+const CYAN: &str = "\x1b[36m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+/// Selects the messages to dump. Empty criteria match everything. The label criterion
+/// matches frames containing at least one object with the label.
+///
+#[derive(Debug, Clone, Default)]
+pub struct DumpFilter {
+    pub source_id: Option<String>,
+    pub pts_from: Option<i64>,
+    pub pts_to: Option<i64>,
+    pub label: Option<String>,
+}
+
+impl DumpFilter {
+    fn frame_matches(&self, frame: &VideoFrameProxy) -> bool {
+        if let Some(source_id) = &self.source_id {
+            if &frame.get_source_id() != source_id {
+                return false;
+            }
+        }
+        let pts = frame.get_pts();
+        if self.pts_from.map(|from| pts < from).unwrap_or(false)
+            || self.pts_to.map(|to| pts > to).unwrap_or(false)
+        {
+            return false;
+        }
+        if let Some(label) = &self.label {
+            return frame
+                .get_all_objects()
+                .iter()
+                .any(|o| &o.get_label() == label);
+        }
+        true
+    }
+
+    pub fn matches(&self, m: &Message) -> bool {
+        let frame_only = self.pts_from.is_some() || self.pts_to.is_some() || self.label.is_some();
+        match &m.payload {
+            MessageEnvelope::VideoFrame(frame) => self.frame_matches(frame),
+            MessageEnvelope::VideoFrameBatch(batch) => {
+                batch.frames().values().any(|f| self.frame_matches(f))
+            }
+            MessageEnvelope::EndOfStream(eos) => {
+                !frame_only
+                    && self
+                        .source_id
+                        .as_ref()
+                        .map(|s| s == &eos.source_id)
+                        .unwrap_or(true)
+            }
+            MessageEnvelope::AudioFrame(frame) => {
+                self.label.is_none()
+                    && self
+                        .source_id
+                        .as_ref()
+                        .map(|s| s == &frame.source_id)
+                        .unwrap_or(true)
+                    && self.pts_from.map(|from| frame.pts >= from).unwrap_or(true)
+                    && self.pts_to.map(|to| frame.pts <= to).unwrap_or(true)
+            }
+            MessageEnvelope::TelemetryFrame(frame) => {
+                self.label.is_none()
+                    && self
+                        .source_id
+                        .as_ref()
+                        .map(|s| s == &frame.source_id)
+                        .unwrap_or(true)
+                    && self.pts_from.map(|from| frame.pts >= from).unwrap_or(true)
+                    && self.pts_to.map(|to| frame.pts <= to).unwrap_or(true)
+            }
+            MessageEnvelope::UserData(data) => {
+                !frame_only
+                    && self
+                        .source_id
+                        .as_ref()
+                        .map(|s| s == data.get_source_id())
+                        .unwrap_or(true)
+            }
+            _ => !frame_only && self.source_id.is_none(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DumpFormat {
+    Text { color: bool },
+    Json,
+}
+
+struct Palette {
+    color: bool,
+}
+
+impl Palette {
+    fn paint(&self, color: &str, text: &str) -> String {
+        if self.color {
+            format!("{}{}{}", color, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+fn frame_summary(frame: &VideoFrameProxy, palette: &Palette, out: &mut String) {
+    let objects = frame.get_all_objects();
+    let _ = writeln!(
+        out,
+        "{} source={} pts={} uuid={} {}x{} codec={} keyframe={} objects={} attributes={}",
+        palette.paint(BOLD, "VideoFrame"),
+        palette.paint(CYAN, &frame.get_source_id()),
+        frame.get_pts(),
+        frame.get_uuid(),
+        frame.get_width(),
+        frame.get_height(),
+        frame.get_codec().unwrap_or_else(|| "-".to_string()),
+        frame
+            .get_keyframe()
+            .map(|k| k.to_string())
+            .unwrap_or_else(|| "-".to_string()),
+        objects.len(),
+        frame.get_attributes().len(),
+    );
+    for (namespace, name) in frame.get_attributes() {
+        let _ = writeln!(
+            out,
+            "  {} {}/{}",
+            palette.paint(YELLOW, "attribute"),
+            namespace,
+            name
+        );
+    }
+    for o in objects {
+        let bbox = o.get_detection_box();
+        let _ = writeln!(
+            out,
+            "  {} #{} {}/{} confidence={} track={} box=(xc={:.1}, yc={:.1}, w={:.1}, h={:.1})",
+            palette.paint(GREEN, "object"),
+            o.get_id(),
+            o.get_namespace(),
+            o.get_label(),
+            o.get_confidence()
+                .map(|c| format!("{:.2}", c))
+                .unwrap_or_else(|| "-".to_string()),
+            o.get_track_id()
+                .map(|t| t.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            bbox.get_xc(),
+            bbox.get_yc(),
+            bbox.get_width(),
+            bbox.get_height(),
+        );
+    }
+}
+
+fn message_json(m: &Message) -> Value {
+    let payload = match &m.payload {
+        MessageEnvelope::EndOfStream(eos) => {
+            serde_json::json!({"end_of_stream": {"source_id": eos.source_id}})
+        }
+        MessageEnvelope::VideoFrame(frame) => {
+            serde_json::json!({"video_frame": frame.to_serde_json_value()})
+        }
+        MessageEnvelope::VideoFrameBatch(batch) => serde_json::json!({
+            "video_frame_batch": batch
+                .frames()
+                .iter()
+                .map(|(id, f)| (id.to_string(), f.to_serde_json_value()))
+                .collect::<serde_json::Map<_, _>>()
+        }),
+        MessageEnvelope::VideoFrameUpdate(update) => serde_json::json!({
+            "video_frame_update": update
+                .to_json(false)
+                .ok()
+                .and_then(|s| serde_json::from_str::<Value>(&s).ok())
+        }),
+        MessageEnvelope::UserData(data) => {
+            serde_json::json!({"user_data": data.to_serde_json_value()})
+        }
+        MessageEnvelope::AudioFrame(frame) => {
+            serde_json::json!({"audio_frame": frame.to_serde_json_value()})
+        }
+        MessageEnvelope::TelemetryFrame(frame) => {
+            serde_json::json!({"telemetry_frame": frame.to_serde_json_value()})
+        }
+        MessageEnvelope::Shutdown(shutdown) => {
+            serde_json::json!({"shutdown": shutdown.to_serde_json_value()})
+        }
+        MessageEnvelope::Unknown(s) => serde_json::json!({ "unknown": s }),
+    };
+    serde_json::json!({
+        "protocol_version": m.meta.protocol_version,
+        "seq_id": m.meta.seq_id,
+        "routing_labels": m.meta.routing_labels,
+        "payload": payload,
+    })
+}
+
+/// Formats a message as a human-readable summary or as a JSON document (one line per message).
+///
+pub fn format_message(m: &Message, format: DumpFormat) -> String {
+    let palette = match format {
+        DumpFormat::Text { color } => Palette { color },
+        DumpFormat::Json => return message_json(m).to_string() + "\n",
+    };
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "{} seq_id={} labels={:?} ",
+        palette.paint(BOLD, "Message"),
+        m.meta.seq_id,
+        m.meta.routing_labels
+    );
+    match &m.payload {
+        MessageEnvelope::VideoFrame(frame) => frame_summary(frame, &palette, &mut out),
+        MessageEnvelope::VideoFrameBatch(batch) => {
+            let _ = writeln!(
+                out,
+                "{} frames={}",
+                palette.paint(BOLD, "VideoFrameBatch"),
+                batch.frames().len()
+            );
+            let mut ids = batch.frames().keys().copied().collect::<Vec<_>>();
+            ids.sort();
+            for id in ids {
+                let _ = write!(out, "  [{}] ", id);
+                frame_summary(&batch.frames()[&id], &palette, &mut out);
+            }
+        }
+        MessageEnvelope::EndOfStream(eos) => {
+            let _ = writeln!(
+                out,
+                "{} source={}",
+                palette.paint(BOLD, "EndOfStream"),
+                palette.paint(CYAN, &eos.source_id)
+            );
+        }
+        MessageEnvelope::UserData(data) => {
+            let _ = writeln!(
+                out,
+                "{} source={} attributes={}",
+                palette.paint(BOLD, "UserData"),
+                palette.paint(CYAN, data.get_source_id()),
+                data.get_attributes().len()
+            );
+        }
+        MessageEnvelope::AudioFrame(frame) => {
+            let _ = writeln!(
+                out,
+                "{} source={} pts={} uuid={} format={} rate={} channels={} samples={} attributes={}",
+                palette.paint(BOLD, "AudioFrame"),
+                palette.paint(CYAN, &frame.source_id),
+                frame.pts,
+                frame.get_uuid(),
+                frame.sample_format.as_str(),
+                frame.sample_rate,
+                frame.channels,
+                frame.samples,
+                frame.get_attributes().len()
+            );
+        }
+        MessageEnvelope::TelemetryFrame(frame) => {
+            let _ = writeln!(
+                out,
+                "{} source={} sensor={} pts={} uuid={} attributes={}",
+                palette.paint(BOLD, "TelemetryFrame"),
+                palette.paint(CYAN, &frame.source_id),
+                frame.sensor,
+                frame.pts,
+                frame.get_uuid(),
+                frame.get_attributes().len()
+            );
+        }
+        MessageEnvelope::VideoFrameUpdate(update) => {
+            let _ = writeln!(
+                out,
+                "{} frame_attributes={} object_attributes={} objects={}",
+                palette.paint(BOLD, "VideoFrameUpdate"),
+                update.get_frame_attributes().len(),
+                update.get_object_attributes().len(),
+                update.get_objects().len()
+            );
+        }
+        MessageEnvelope::Shutdown(shutdown) => {
+            let _ = writeln!(
+                out,
+                "{} {}",
+                palette.paint(BOLD, "Shutdown"),
+                shutdown.to_serde_json_value()
+            );
+        }
+        MessageEnvelope::Unknown(s) => {
+            let _ = writeln!(out, "{} {}", palette.paint(BOLD, "Unknown"), s);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dump::{format_message, DumpFilter, DumpFormat};
+    use crate::message::Message;
+    use crate::primitives::eos::EndOfStream;
+    use crate::test::gen_frame;
+
+    #[test]
+    fn test_filter() {
+        let frame = gen_frame();
+        let m = Message::video_frame(&frame);
+        assert!(DumpFilter::default().matches(&m));
+
+        let by_label = DumpFilter {
+            label: Some("test2".to_string()),
+            ..Default::default()
+        };
+        assert!(by_label.matches(&m));
+
+        let by_pts = DumpFilter {
+            pts_to: Some(frame.get_pts() - 1),
+            ..Default::default()
+        };
+        assert!(!by_pts.matches(&m));
+
+        let eos = Message::end_of_stream(EndOfStream::new("test".to_string()));
+        assert!(!by_label.matches(&eos));
+        let by_source = DumpFilter {
+            source_id: Some("test".to_string()),
+            ..Default::default()
+        };
+        assert!(by_source.matches(&eos));
+    }
+
+    #[test]
+    fn test_format() {
+        let m = Message::video_frame(&gen_frame());
+        let text = format_message(&m, DumpFormat::Text { color: false });
+        assert!(text.starts_with("Message"));
+        assert_eq!(text.matches("object #").count(), 3);
+        let json = format_message(&m, DumpFormat::Json);
+        let v: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(v["payload"]["video_frame"].is_object());
+    }
+}
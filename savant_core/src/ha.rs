@@ -0,0 +1,555 @@
+use crate::clock::system_now_ms;
+use crate::get_or_init_async_runtime;
+use crate::metrics::{get_counter_family, get_counter_family_names, get_or_create_counter_family};
+use crate::webserver::kvs::synchronous::{export_snapshot, import_snapshot};
+use crate::webserver::kvs::MergePolicy;
+use anyhow::bail;
+use hashbrown::HashMap;
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use prost::Message;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+pub(crate) const HA_SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct HaCounterRecord {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, repeated, tag = "2")]
+    pub label_names: Vec<String>,
+    #[prost(string, repeated, tag = "3")]
+    pub label_values: Vec<String>,
+    #[prost(uint64, tag = "4")]
+    pub value: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct HaStateRecord {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub data: Vec<u8>,
+}
+
+/// The state transferred from the active pipeline to the standby: the KVS snapshot, the
+/// counters and the states of the registered providers.
+///
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct HaSnapshot {
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    #[prost(uint64, tag = "2")]
+    pub taken_at: u64,
+    #[prost(bytes = "vec", tag = "3")]
+    pub kvs: Vec<u8>,
+    #[prost(message, repeated, tag = "4")]
+    pub counters: Vec<HaCounterRecord>,
+    #[prost(message, repeated, tag = "5")]
+    pub states: Vec<HaStateRecord>,
+    /// The fencing token of the active pipeline which took the snapshot.
+    #[prost(uint64, tag = "6")]
+    pub fencing_token: u64,
+}
+
+/// A state transferred to the standby besides the KVS and the counters, e.g. the tracks of
+/// a tracker. The state is restored on the standby before every snapshot is acknowledged,
+/// so it must be replaceable as a whole.
+///
+pub trait StateProvider: Send + Sync {
+    fn snapshot(&self) -> anyhow::Result<Vec<u8>>;
+    fn restore(&self, data: &[u8]) -> anyhow::Result<()>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HaRole {
+    /// The pipeline ingests the data and replicates its state.
+    Active,
+    /// The pipeline receives the state and takes over when the active one fails.
+    Standby,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HaStatus {
+    pub role: Option<HaRole>,
+    /// The time of the last snapshot taken or restored, in milliseconds since the epoch.
+    pub last_snapshot_at: Option<u64>,
+    pub snapshots: u64,
+    /// The consecutive failed health checks of the active pipeline.
+    pub active_failures: u32,
+    pub promoted_at: Option<u64>,
+    /// Grows with every promotion. The standby learns it from the snapshots and rejects the
+    /// snapshots of a pipeline with a lower token, i.e. of an active pipeline which has been
+    /// replaced.
+    pub fencing_token: u64,
+}
+
+/// Called with the fencing token of the promotion, which the ingestion passes on to fence
+/// off the writes of the replaced active pipeline.
+///
+pub type PromotionCallback = Arc<dyn Fn(u64) + Send + Sync>;
+
+struct HaWorker {
+    shutdown: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+struct HaState {
+    status: HaStatus,
+    providers: HashMap<String, Arc<dyn StateProvider>>,
+    on_promote: Option<PromotionCallback>,
+}
+
+lazy_static! {
+    static ref HA: Mutex<HaState> = Mutex::new(HaState {
+        status: HaStatus {
+            role: None,
+            last_snapshot_at: None,
+            snapshots: 0,
+            active_failures: 0,
+            promoted_at: None,
+            fencing_token: 0,
+        },
+        providers: HashMap::new(),
+        on_promote: None,
+    });
+    static ref HA_WORKER: Mutex<Option<HaWorker>> = Mutex::new(None);
+}
+
+static PROMOTION_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+pub fn register_state_provider(name: &str, provider: Arc<dyn StateProvider>) {
+    HA.lock().providers.insert(name.to_string(), provider);
+}
+
+pub fn unregister_state_provider(name: &str) {
+    HA.lock().providers.remove(name);
+}
+
+fn providers() -> Vec<(String, Arc<dyn StateProvider>)> {
+    HA.lock()
+        .providers
+        .iter()
+        .map(|(name, p)| (name.clone(), p.clone()))
+        .collect()
+}
+
+pub fn get_ha_status() -> HaStatus {
+    HA.lock().status.clone()
+}
+
+/// Serializes the state of the pipeline: the KVS, the counters and the registered states.
+///
+pub fn take_snapshot() -> anyhow::Result<Vec<u8>> {
+    let mut counters = Vec::new();
+    for name in get_counter_family_names() {
+        let Some(family) = get_counter_family(&name) else {
+            continue;
+        };
+        let family = family.lock();
+        for (label_values, value) in family.get_all() {
+            counters.push(HaCounterRecord {
+                name: name.clone(),
+                label_names: family.get_label_names().to_vec(),
+                label_values: label_values.clone(),
+                value: *value,
+            });
+        }
+    }
+    let mut states = Vec::new();
+    for (name, provider) in providers() {
+        states.push(HaStateRecord {
+            name,
+            data: provider.snapshot()?,
+        });
+    }
+    let taken_at = system_now_ms();
+    let snapshot = HaSnapshot {
+        version: HA_SNAPSHOT_VERSION,
+        taken_at,
+        kvs: export_snapshot(),
+        counters,
+        states,
+        fencing_token: HA.lock().status.fencing_token,
+    };
+    let mut ha = HA.lock();
+    ha.status.last_snapshot_at = Some(taken_at);
+    ha.status.snapshots += 1;
+    Ok(snapshot.encode_to_vec())
+}
+
+/// Replaces the state of the pipeline with the snapshot. Snapshots are accepted only by a
+/// standby, older snapshots than the last restored one and the snapshots with a lower fencing
+/// token than the known one are rejected. The states without a registered provider are
+/// skipped.
+///
+pub fn restore_snapshot(data: &[u8]) -> anyhow::Result<()> {
+    let snapshot = HaSnapshot::decode(data)?;
+    if snapshot.version != HA_SNAPSHOT_VERSION {
+        bail!("Unsupported HA snapshot version {}", snapshot.version);
+    }
+    {
+        let ha = HA.lock();
+        if ha.status.role != Some(HaRole::Standby) {
+            bail!("Only a standby accepts snapshots");
+        }
+        if ha
+            .status
+            .last_snapshot_at
+            .is_some_and(|t| t > snapshot.taken_at)
+        {
+            bail!("The snapshot taken at {} is stale", snapshot.taken_at);
+        }
+        if snapshot.fencing_token < ha.status.fencing_token {
+            bail!(
+                "The snapshot with the fencing token {} is fenced off by the token {}",
+                snapshot.fencing_token,
+                ha.status.fencing_token
+            );
+        }
+    }
+    import_snapshot(&snapshot.kvs, MergePolicy::Replace)?;
+    for record in snapshot.counters {
+        let label_names = record
+            .label_names
+            .iter()
+            .map(|s| s.as_str())
+            .collect::<Vec<_>>();
+        let label_values = record
+            .label_values
+            .iter()
+            .map(|s| s.as_str())
+            .collect::<Vec<_>>();
+        get_or_create_counter_family(&record.name, None, &label_names, None)
+            .lock()
+            .set(record.value, &label_values)?;
+    }
+    let providers = providers().into_iter().collect::<HashMap<_, _>>();
+    for record in snapshot.states {
+        match providers.get(&record.name) {
+            Some(provider) => provider.restore(&record.data)?,
+            None => log::warn!(
+                target: "savant_rs::ha",
+                "No state provider {}, the state is skipped",
+                record.name
+            ),
+        }
+    }
+    let mut ha = HA.lock();
+    ha.status.last_snapshot_at = Some(snapshot.taken_at);
+    ha.status.snapshots += 1;
+    ha.status.fencing_token = ha.status.fencing_token.max(snapshot.fencing_token);
+    Ok(())
+}
+
+/// Turns the standby into the active pipeline with the next fencing token and calls the
+/// promotion callback, which starts the ingestion. Returns the fencing token, `None` when
+/// the pipeline is not a standby.
+///
+pub fn promote() -> Option<u64> {
+    let (callback, fencing_token) = {
+        let mut ha = HA.lock();
+        if ha.status.role != Some(HaRole::Standby) {
+            return None;
+        }
+        ha.status.role = Some(HaRole::Active);
+        ha.status.promoted_at = Some(system_now_ms());
+        ha.status.fencing_token += 1;
+        (ha.on_promote.clone(), ha.status.fencing_token)
+    };
+    log::warn!(
+        target: "savant_rs::ha",
+        "The standby pipeline is promoted to active with the fencing token {}",
+        fencing_token
+    );
+    if let Some(callback) = callback {
+        callback(fencing_token);
+    }
+    Some(fencing_token)
+}
+
+/// Requests the promotion of the standby without running the promotion callback on the
+/// calling thread: the HA worker promotes the standby on its next iteration, a thread is
+/// spawned when no worker runs. Returns false when the pipeline is not a standby.
+///
+pub fn request_promotion() -> bool {
+    if HA.lock().status.role != Some(HaRole::Standby) {
+        return false;
+    }
+    let worker = HA_WORKER.lock();
+    match worker.as_ref() {
+        Some(worker) if !worker.thread.is_finished() => {
+            PROMOTION_REQUESTED.store(true, Ordering::Relaxed);
+            worker.thread.thread().unpark();
+        }
+        _ => {
+            std::thread::spawn(promote);
+        }
+    }
+    true
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveConfig {
+    /// The webserver of the standby, e.g. `http://standby:8080`.
+    pub standby_url: String,
+    /// How often the snapshots are sent.
+    pub period: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StandbyConfig {
+    /// The webserver of the active pipeline, e.g. `http://active:8080`.
+    pub active_url: String,
+    /// How often the status of the active pipeline is checked.
+    pub check_period: Duration,
+    /// The number of consecutive failed checks which promotes the standby.
+    pub failure_threshold: u32,
+}
+
+fn start_worker(period: Duration, f: impl Fn() -> bool + Send + 'static) {
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let thread_shutdown = shutdown.clone();
+    let thread = std::thread::spawn(move || {
+        while !thread_shutdown.load(Ordering::Relaxed) && f() {
+            std::thread::park_timeout(period);
+        }
+    });
+    let previous = HA_WORKER.lock().replace(HaWorker { shutdown, thread });
+    if let Some(worker) = previous {
+        stop_worker(worker);
+    }
+}
+
+fn stop_worker(worker: HaWorker) {
+    worker.shutdown.store(true, Ordering::Relaxed);
+    worker.thread.thread().unpark();
+    if worker.thread.join().is_err() {
+        log::error!(target: "savant_rs::ha", "The HA thread panicked");
+    }
+}
+
+fn send_snapshot(client: &reqwest::Client, url: &str, period: Duration) -> anyhow::Result<()> {
+    let snapshot = take_snapshot()?;
+    get_or_init_async_runtime().block_on(async {
+        let response = client
+            .post(url)
+            .header("Content-Type", "application/x-protobuf")
+            .body(snapshot)
+            .timeout(period)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            bail!(
+                "The standby rejected the snapshot with {}: {}",
+                response.status(),
+                response.text().await.unwrap_or_default()
+            );
+        }
+        Ok(())
+    })
+}
+
+/// Makes the pipeline active and starts sending the snapshots to the standby in the
+/// background. A running HA worker is replaced.
+///
+pub fn start_active(config: ActiveConfig) -> anyhow::Result<()> {
+    if config.period.is_zero() {
+        bail!("The snapshot period must be greater than 0");
+    }
+    HA.lock().status.role = Some(HaRole::Active);
+    let url = format!("{}/ha/snapshot", config.standby_url.trim_end_matches('/'));
+    let client = reqwest::Client::new();
+    PROMOTION_REQUESTED.store(false, Ordering::Relaxed);
+    start_worker(config.period, move || {
+        if let Err(e) = send_snapshot(&client, &url, config.period) {
+            log::warn!(target: "savant_rs::ha", "Failed to replicate the state: {}", e);
+        }
+        true
+    });
+    Ok(())
+}
+
+fn is_active_healthy(client: &reqwest::Client, url: &str, timeout: Duration) -> bool {
+    get_or_init_async_runtime().block_on(async {
+        let Ok(response) = client.get(url).timeout(timeout).send().await else {
+            return false;
+        };
+        response.status().is_success()
+            && response
+                .json::<String>()
+                .await
+                .is_ok_and(|s| s == "running")
+    })
+}
+
+/// Makes the pipeline a standby checking the status of the active pipeline in the
+/// background. The standby is promoted when the active pipeline fails the consecutive
+/// checks, the callback starts the ingestion. A running HA worker is replaced.
+///
+pub fn start_standby(
+    config: StandbyConfig,
+    on_promote: Option<PromotionCallback>,
+) -> anyhow::Result<()> {
+    if config.check_period.is_zero() || config.failure_threshold == 0 {
+        bail!("The check period and the failure threshold must be greater than 0");
+    }
+    {
+        let mut ha = HA.lock();
+        ha.status.role = Some(HaRole::Standby);
+        ha.status.active_failures = 0;
+        ha.status.promoted_at = None;
+        ha.on_promote = on_promote;
+    }
+    PROMOTION_REQUESTED.store(false, Ordering::Relaxed);
+    let url = format!("{}/status", config.active_url.trim_end_matches('/'));
+    let client = reqwest::Client::new();
+    start_worker(config.check_period, move || {
+        if PROMOTION_REQUESTED.swap(false, Ordering::Relaxed) {
+            log::warn!(target: "savant_rs::ha", "The promotion is requested manually");
+            promote();
+            return false;
+        }
+        let healthy = is_active_healthy(&client, &url, config.check_period);
+        let failures = {
+            let mut ha = HA.lock();
+            if ha.status.role != Some(HaRole::Standby) {
+                // promoted manually
+                return false;
+            }
+            ha.status.active_failures = if healthy {
+                0
+            } else {
+                ha.status.active_failures + 1
+            };
+            ha.status.active_failures
+        };
+        if failures < config.failure_threshold {
+            return true;
+        }
+        log::warn!(
+            target: "savant_rs::ha",
+            "The active pipeline failed {} health checks",
+            failures
+        );
+        promote();
+        false
+    });
+    Ok(())
+}
+
+/// Stops the HA worker, the role is kept.
+///
+pub fn stop_ha() {
+    if let Some(worker) = HA_WORKER.lock().take() {
+        stop_worker(worker);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ha::{
+        get_ha_status, promote, register_state_provider, request_promotion, restore_snapshot,
+        take_snapshot, unregister_state_provider, HaRole, StateProvider, HA,
+    };
+    use crate::metrics::get_or_create_counter_family;
+    use parking_lot::Mutex;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct Tracks(Mutex<Vec<u8>>);
+
+    impl StateProvider for Tracks {
+        fn snapshot(&self) -> anyhow::Result<Vec<u8>> {
+            Ok(self.0.lock().clone())
+        }
+
+        fn restore(&self, data: &[u8]) -> anyhow::Result<()> {
+            *self.0.lock() = data.to_vec();
+            Ok(())
+        }
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_snapshot_and_promotion() -> anyhow::Result<()> {
+        let tracks = Arc::new(Tracks::default());
+        register_state_provider("test_tracks", tracks.clone());
+        let counter = get_or_create_counter_family("test_ha_counter", None, &["source"], None);
+        counter.lock().set(42, &["cam-1"])?;
+        *tracks.0.lock() = vec![1, 2, 3];
+        HA.lock().status.role = Some(HaRole::Active);
+        let snapshot = take_snapshot()?;
+        // only a standby accepts snapshots
+        assert!(restore_snapshot(&snapshot).is_err());
+
+        let promoted = Arc::new(AtomicU64::new(0));
+        let thread_promoted = promoted.clone();
+        let fencing_token = {
+            let mut ha = HA.lock();
+            ha.status.role = Some(HaRole::Standby);
+            ha.status.last_snapshot_at = None;
+            ha.on_promote = Some(Arc::new(move |token| {
+                thread_promoted.store(token, Ordering::Relaxed)
+            }));
+            ha.status.fencing_token
+        };
+        counter.lock().set(0, &["cam-1"])?;
+        tracks.0.lock().clear();
+        restore_snapshot(&snapshot)?;
+        assert_eq!(counter.lock().get(&["cam-1"])?, Some(42));
+        assert_eq!(*tracks.0.lock(), vec![1, 2, 3]);
+
+        assert_eq!(promote(), Some(fencing_token + 1));
+        assert_eq!(promote(), None);
+        assert_eq!(promoted.load(Ordering::Relaxed), fencing_token + 1);
+        assert_eq!(get_ha_status().role, Some(HaRole::Active));
+
+        // the snapshots of the replaced active pipeline are fenced off
+        {
+            let mut ha = HA.lock();
+            ha.status.role = Some(HaRole::Standby);
+            ha.status.last_snapshot_at = None;
+        }
+        assert!(restore_snapshot(&snapshot).is_err());
+        unregister_state_provider("test_tracks");
+        Ok(())
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_requested_promotion() {
+        let promoted = Arc::new(AtomicU64::new(0));
+        let thread_promoted = promoted.clone();
+        {
+            let mut ha = HA.lock();
+            ha.status.role = Some(HaRole::Active);
+            ha.on_promote = Some(Arc::new(move |token| {
+                thread_promoted.store(token, Ordering::Relaxed)
+            }));
+        }
+        assert!(!request_promotion());
+        HA.lock().status.role = Some(HaRole::Standby);
+        // the callback does not run on the requesting thread
+        assert!(request_promotion());
+        for _ in 0..100 {
+            if promoted.load(Ordering::Relaxed) != 0 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(
+            promoted.load(Ordering::Relaxed),
+            get_ha_status().fencing_token
+        );
+        assert_eq!(get_ha_status().role, Some(HaRole::Active));
+        HA.lock().on_promote = None;
+    }
+}
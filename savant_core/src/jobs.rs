@@ -0,0 +1,632 @@
+use crate::clip::get_clip_journal;
+use crate::clock::{frame_time_ms, system_now_ms};
+use crate::metrics::{get_or_create_counter_family, get_or_create_gauge_family};
+use crate::module::FrameProcessor;
+use crate::protobuf::{MessageStreamReader, MessageStreamWriter};
+use crate::utils::uuid_v7::incremental_uuid_v7;
+use anyhow::{bail, Context};
+use hashbrown::{HashMap, HashSet};
+use lazy_static::lazy_static;
+use log::{info, warn};
+use parking_lot::{Condvar, Mutex};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+const JOB_MESSAGES_METRIC: &str = "job_messages";
+const JOB_PROGRESS_METRIC: &str = "job_progress";
+const JOB_OUTCOMES: [&str; 3] = ["processed", "failed", "skipped"];
+
+/// The finished jobs kept for the status requests, the oldest ones are forgotten when more
+/// jobs finish.
+///
+pub const MAX_FINISHED_JOBS: usize = 100;
+
+/// Creates the processor of a job from the pipeline configuration of the request.
+///
+pub type JobProcessorFactory =
+    Arc<dyn Fn(&serde_json::Value) -> anyhow::Result<Box<dyn FrameProcessor>> + Send + Sync>;
+
+lazy_static! {
+    static ref JOB_PROCESSORS: Mutex<HashMap<String, JobProcessorFactory>> =
+        Mutex::new(HashMap::new());
+    static ref JOBS: Mutex<HashMap<String, Arc<Job>>> = Mutex::new(HashMap::new());
+    static ref JOBS_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+
+/// Sets the directory the journal and the output paths of the requests are resolved in, the
+/// requests with the paths are rejected when it is not set.
+///
+pub fn set_jobs_dir(path: Option<PathBuf>) {
+    *JOBS_DIR.lock() = path;
+}
+
+pub fn get_jobs_dir() -> Option<PathBuf> {
+    JOBS_DIR.lock().clone()
+}
+
+/// Resolves the path of a request in the jobs directory, the absolute paths and the paths
+/// with `..` are rejected.
+///
+fn resolve_job_path(path: &Path) -> anyhow::Result<PathBuf> {
+    let Some(dir) = get_jobs_dir() else {
+        bail!("The jobs directory is not set, the request cannot refer to the files");
+    };
+    if path.as_os_str().is_empty() || !path.components().all(|c| matches!(c, Component::Normal(_)))
+    {
+        bail!(
+            "The path {} must be relative to the jobs directory",
+            path.display()
+        );
+    }
+    Ok(dir.join(path))
+}
+
+/// Registers the processor the jobs refer to by the name, usually the same code the live
+/// pipeline runs.
+///
+pub fn register_job_processor(name: &str, factory: JobProcessorFactory) {
+    JOB_PROCESSORS.lock().insert(name.to_string(), factory);
+}
+
+pub fn unregister_job_processor(name: &str) {
+    JOB_PROCESSORS.lock().remove(name);
+}
+
+/// A re-processing of the frames recorded in a journal.
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobRequest {
+    /// The journal to read, the clip journal when not set, see [`crate::clip::set_clip_journal`].
+    /// The path is relative to the jobs directory, see [`set_jobs_dir`].
+    #[serde(default)]
+    pub journal: Option<PathBuf>,
+    /// The journal the processed frames are written to, they are not kept when not set. The
+    /// path is relative to the jobs directory.
+    #[serde(default)]
+    pub output: Option<PathBuf>,
+    /// Only the frames of the source are processed when set.
+    #[serde(default)]
+    pub source_id: Option<String>,
+    /// The interval of the frames processed, the frame PTS in milliseconds, see
+    /// [`crate::clock::frame_time_ms`].
+    #[serde(default)]
+    pub start_ms: Option<u64>,
+    #[serde(default)]
+    pub end_ms: Option<u64>,
+    /// The processor registered with [`register_job_processor`].
+    pub processor: String,
+    /// The pipeline configuration passed to the processor factory.
+    #[serde(default)]
+    pub config: serde_json::Value,
+}
+
+impl JobRequest {
+    fn accepts(&self, source_id: &str, time_ms: u64) -> bool {
+        self.source_id.as_ref().is_none_or(|s| s == source_id)
+            && self.start_ms.is_none_or(|start| time_ms >= start)
+            && self.end_ms.is_none_or(|end| time_ms <= end)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Running,
+    Paused,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+impl JobState {
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            JobState::Completed | JobState::Cancelled | JobState::Failed
+        )
+    }
+}
+
+/// The progress of a job.
+///
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JobStatus {
+    pub id: String,
+    pub state: JobState,
+    pub request: JobRequest,
+    /// The frames processed successfully.
+    pub processed: u64,
+    /// The frames the processor failed on.
+    pub failed: u64,
+    /// The messages outside the source or the interval of the request.
+    pub skipped: u64,
+    pub bytes_read: u64,
+    pub bytes_total: u64,
+    /// The share of the journal read, from 0 to 1.
+    pub progress: f64,
+    /// The times in milliseconds since the UNIX epoch.
+    pub started_at_ms: u64,
+    pub finished_at_ms: Option<u64>,
+    /// Why the job failed.
+    pub error: Option<String>,
+}
+
+struct Job {
+    status: Mutex<JobStatus>,
+    bytes_read: Arc<AtomicU64>,
+    cancelled: AtomicBool,
+    paused: Mutex<bool>,
+    resumed: Condvar,
+}
+
+impl Job {
+    fn status(&self) -> JobStatus {
+        let mut status = self.status.lock().clone();
+        status.bytes_read = self.bytes_read.load(Ordering::Relaxed);
+        if status.bytes_total > 0 {
+            status.progress =
+                (status.bytes_read.min(status.bytes_total) as f64) / (status.bytes_total as f64);
+        }
+        status
+    }
+
+    /// Blocks while the job is paused, `false` when the job is cancelled.
+    ///
+    fn wait_resumed(&self) -> bool {
+        let mut paused = self.paused.lock();
+        while *paused && !self.cancelled.load(Ordering::SeqCst) {
+            self.resumed.wait(&mut paused);
+        }
+        !self.cancelled.load(Ordering::SeqCst)
+    }
+
+    fn count(&self, outcome: &str) {
+        let id = {
+            let mut status = self.status.lock();
+            match outcome {
+                "processed" => status.processed += 1,
+                "failed" => status.failed += 1,
+                _ => status.skipped += 1,
+            }
+            status.id.clone()
+        };
+        let res = get_or_create_counter_family(
+            JOB_MESSAGES_METRIC,
+            Some("The number of messages handled by the jobs"),
+            &["job", "outcome"],
+            None,
+        )
+        .lock()
+        .inc(1, &[&id, outcome]);
+        if let Err(e) = res {
+            warn!(target: "savant_rs::jobs", "Failed to count the job message: {}", e);
+        }
+    }
+
+    fn report_progress(&self) {
+        let status = self.status();
+        let res = get_or_create_gauge_family(
+            JOB_PROGRESS_METRIC,
+            Some("The share of the journal read by the jobs"),
+            &["job"],
+            None,
+        )
+        .lock()
+        .set(status.progress, &[&status.id]);
+        if let Err(e) = res {
+            warn!(target: "savant_rs::jobs", "Failed to set the job progress: {}", e);
+        }
+    }
+}
+
+/// Counts the bytes read from the journal for the progress.
+///
+struct CountingReader<R: Read> {
+    inner: R,
+    read: Arc<AtomicU64>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+/// Processes the journal, returns `false` when the job is cancelled.
+///
+fn run_job(
+    job: &Job,
+    journal: File,
+    output: Option<PathBuf>,
+    mut processor: Box<dyn FrameProcessor>,
+) -> anyhow::Result<bool> {
+    let request = job.status.lock().request.clone();
+    let mut output = output
+        .as_ref()
+        .map(|path| {
+            File::create(path)
+                .with_context(|| format!("Failed to create the output {}", path.display()))
+                .map(|f| MessageStreamWriter::new(BufWriter::new(f)))
+        })
+        .transpose()?;
+    let reader = CountingReader {
+        inner: journal,
+        read: job.bytes_read.clone(),
+    };
+    let mut sources = HashSet::new();
+    for message in MessageStreamReader::new(BufReader::new(reader)) {
+        if !job.wait_resumed() {
+            return Ok(false);
+        }
+        let message = message?;
+        if let Some(frame) = message.as_video_frame() {
+            if !request.accepts(&frame.get_source_id(), frame_time_ms(&frame)) {
+                job.count("skipped");
+                continue;
+            }
+            sources.insert(frame.get_source_id());
+            match processor.process(&frame) {
+                Ok(()) => {
+                    job.count("processed");
+                    if let Some(output) = output.as_mut() {
+                        output.write_message(&message)?;
+                    }
+                }
+                Err(e) => {
+                    warn!(
+                        target: "savant_rs::jobs",
+                        "Job processor failed on the frame {}: {}", frame.get_uuid(), e
+                    );
+                    job.count("failed");
+                }
+            }
+            job.report_progress();
+        } else {
+            job.count("skipped");
+        }
+    }
+    for source_id in sources {
+        processor.on_eos(&source_id)?;
+    }
+    if let Some(output) = output.as_mut() {
+        output.flush()?;
+    }
+    job.report_progress();
+    Ok(true)
+}
+
+/// Starts the job on a dedicated thread and returns its id. The job reads the frames of the
+/// journal in the order they are recorded and passes the ones of the source and the interval
+/// to the processor created for the job. The messages are counted in the `job_messages`
+/// counter labelled with the job id and the outcome (`processed`, `failed`, `skipped`), the
+/// share of the journal read is reported in the `job_progress` gauge.
+///
+pub fn submit_job(request: JobRequest) -> anyhow::Result<String> {
+    let factory = JOB_PROCESSORS.lock().get(&request.processor).cloned();
+    let Some(factory) = factory else {
+        bail!("Unknown job processor: {}", request.processor);
+    };
+    let journal_path = match &request.journal {
+        Some(path) => resolve_job_path(path)?,
+        None => match get_clip_journal() {
+            Some(path) => path,
+            None => bail!("The request has no journal and no clip journal is set"),
+        },
+    };
+    let output = request
+        .output
+        .as_deref()
+        .map(resolve_job_path)
+        .transpose()?;
+    let journal = File::open(&journal_path)
+        .with_context(|| format!("Failed to open the journal {}", journal_path.display()))?;
+    let bytes_total = journal.metadata()?.len();
+    let processor = factory(&request.config)?;
+
+    let id = incremental_uuid_v7().to_string();
+    let job = Arc::new(Job {
+        status: Mutex::new(JobStatus {
+            id: id.clone(),
+            state: JobState::Running,
+            request,
+            processed: 0,
+            failed: 0,
+            skipped: 0,
+            bytes_read: 0,
+            bytes_total,
+            progress: 0.0,
+            started_at_ms: system_now_ms(),
+            finished_at_ms: None,
+            error: None,
+        }),
+        bytes_read: Arc::new(AtomicU64::new(0)),
+        cancelled: AtomicBool::new(false),
+        paused: Mutex::new(false),
+        resumed: Condvar::new(),
+    });
+    JOBS.lock().insert(id.clone(), job.clone());
+    info!(
+        target: "savant_rs::jobs",
+        "Job {} started on {}", id, journal_path.display()
+    );
+    std::thread::Builder::new()
+        .name(format!("job-{}", id))
+        .spawn(move || {
+            let res = run_job(&job, journal, output, processor);
+            let mut status = job.status.lock();
+            status.state = match res {
+                Ok(true) => JobState::Completed,
+                Ok(false) => JobState::Cancelled,
+                Err(e) => {
+                    status.error = Some(e.to_string());
+                    JobState::Failed
+                }
+            };
+            status.finished_at_ms = Some(system_now_ms());
+            info!(
+                target: "savant_rs::jobs",
+                "Job {} finished: {:?}", status.id, status.state
+            );
+            drop(status);
+            evict_finished_jobs();
+        })?;
+    Ok(id)
+}
+
+/// Forgets the oldest finished jobs above [`MAX_FINISHED_JOBS`].
+///
+fn evict_finished_jobs() {
+    let evicted = {
+        let mut jobs = JOBS.lock();
+        let mut finished = jobs
+            .iter()
+            .filter(|(_, job)| job.status.lock().state.is_finished())
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+        if finished.len() <= MAX_FINISHED_JOBS {
+            return;
+        }
+        // the ids are UUIDv7, so they are ordered by the start time
+        finished.sort();
+        finished.truncate(finished.len() - MAX_FINISHED_JOBS);
+        for id in &finished {
+            jobs.remove(id);
+        }
+        finished
+    };
+    for id in evicted {
+        forget_job_metrics(&id);
+    }
+}
+
+/// Deletes the metric values labelled with the job id, so the labels do not grow with the
+/// jobs.
+///
+fn forget_job_metrics(id: &str) {
+    let counters = get_or_create_counter_family(
+        JOB_MESSAGES_METRIC,
+        Some("The number of messages handled by the jobs"),
+        &["job", "outcome"],
+        None,
+    );
+    let mut counters = counters.lock();
+    for outcome in JOB_OUTCOMES {
+        if let Err(e) = counters.delete(&[id, outcome]) {
+            warn!(target: "savant_rs::jobs", "Failed to delete the job messages: {}", e);
+        }
+    }
+    let res = get_or_create_gauge_family(
+        JOB_PROGRESS_METRIC,
+        Some("The share of the journal read by the jobs"),
+        &["job"],
+        None,
+    )
+    .lock()
+    .delete(&[id]);
+    if let Err(e) = res {
+        warn!(target: "savant_rs::jobs", "Failed to delete the job progress: {}", e);
+    }
+}
+
+fn find_job(id: &str) -> Option<Arc<Job>> {
+    JOBS.lock().get(id).cloned()
+}
+
+pub fn get_job(id: &str) -> Option<JobStatus> {
+    find_job(id).map(|job| job.status())
+}
+
+/// The jobs in the order they are started, the finished ones included.
+///
+pub fn list_jobs() -> Vec<JobStatus> {
+    let jobs = JOBS.lock().values().cloned().collect::<Vec<_>>();
+    let mut statuses = jobs.iter().map(|job| job.status()).collect::<Vec<_>>();
+    statuses.sort_by(|a, b| a.id.cmp(&b.id));
+    statuses
+}
+
+/// Pauses the running job before the next message, `false` when the job is not running.
+///
+pub fn pause_job(id: &str) -> bool {
+    let Some(job) = find_job(id) else {
+        return false;
+    };
+    let mut status = job.status.lock();
+    if status.state != JobState::Running {
+        return false;
+    }
+    status.state = JobState::Paused;
+    *job.paused.lock() = true;
+    true
+}
+
+/// Resumes the paused job, `false` when the job is not paused.
+///
+pub fn resume_job(id: &str) -> bool {
+    let Some(job) = find_job(id) else {
+        return false;
+    };
+    let mut status = job.status.lock();
+    if status.state != JobState::Paused {
+        return false;
+    }
+    status.state = JobState::Running;
+    *job.paused.lock() = false;
+    job.resumed.notify_all();
+    true
+}
+
+/// Cancels the running or paused job, `false` when the job is finished. The job stops before
+/// the next message.
+///
+pub fn cancel_job(id: &str) -> bool {
+    let Some(job) = find_job(id) else {
+        return false;
+    };
+    if job.status.lock().state.is_finished() {
+        return false;
+    }
+    job.cancelled.store(true, Ordering::SeqCst);
+    let _paused = job.paused.lock();
+    job.resumed.notify_all();
+    true
+}
+
+/// Forgets the finished job, `false` when the job is not finished.
+///
+pub fn remove_job(id: &str) -> bool {
+    {
+        let mut jobs = JOBS.lock();
+        match jobs.get(id) {
+            Some(job) if job.status.lock().state.is_finished() => {
+                jobs.remove(id);
+            }
+            _ => return false,
+        }
+    }
+    forget_job_metrics(id);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::jobs::{
+        cancel_job, get_job, pause_job, register_job_processor, remove_job, resume_job,
+        set_jobs_dir, submit_job, JobRequest, JobState, JobStatus,
+    };
+    use crate::message::Message;
+    use crate::module::FrameProcessor;
+    use crate::primitives::frame::VideoFrameProxy;
+    use crate::protobuf::{MessageStreamReader, MessageStreamWriter};
+    use crate::test::gen_frame;
+    use crate::utils::uuid_v7::incremental_uuid_v7;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    struct Failing(i64);
+
+    impl FrameProcessor for Failing {
+        fn process(&mut self, frame: &VideoFrameProxy) -> anyhow::Result<()> {
+            if frame.get_pts() == self.0 {
+                anyhow::bail!("Broken frame");
+            }
+            Ok(())
+        }
+    }
+
+    fn wait_finished(id: &str) -> JobStatus {
+        loop {
+            let status = get_job(id).unwrap();
+            if status.state.is_finished() {
+                return status;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_job() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir();
+        let journal_name =
+            std::path::PathBuf::from(format!("savant-job-{}", incremental_uuid_v7()));
+        let output_name = journal_name.with_extension("out");
+        let journal = dir.join(&journal_name);
+        let output = dir.join(&output_name);
+        let mut writer = MessageStreamWriter::new(std::fs::File::create(&journal)?);
+        for i in 0..10i64 {
+            let mut frame = gen_frame();
+            frame.set_time_base((1, 1000));
+            frame.set_pts(i * 100);
+            writer.write_message(&Message::video_frame(&frame))?;
+        }
+        writer.flush()?;
+
+        register_job_processor(
+            "failing",
+            Arc::new(|config| {
+                let pts = config["fail_pts"].as_i64().unwrap_or(-1);
+                Ok(Box::new(Failing(pts)) as Box<dyn FrameProcessor>)
+            }),
+        );
+        let request = JobRequest {
+            journal: Some(journal_name.clone()),
+            output: Some(output_name.clone()),
+            source_id: None,
+            start_ms: Some(200),
+            end_ms: Some(700),
+            processor: "failing".to_string(),
+            config: serde_json::json!({"fail_pts": 300}),
+        };
+        // the paths are resolved in the jobs directory only
+        assert!(submit_job(request.clone()).is_err());
+        set_jobs_dir(Some(dir));
+        for path in [journal.clone(), std::path::PathBuf::from("../job")] {
+            let escaping = JobRequest {
+                journal: Some(path),
+                ..request.clone()
+            };
+            assert!(submit_job(escaping).is_err());
+        }
+
+        let id = submit_job(request.clone())?;
+        let status = wait_finished(&id);
+        assert_eq!(status.state, JobState::Completed);
+        assert_eq!((status.processed, status.failed, status.skipped), (5, 1, 4));
+        assert_eq!(status.progress, 1.0);
+        let written = MessageStreamReader::new(std::fs::File::open(&output)?).count();
+        assert_eq!(written, 5);
+        assert!(!pause_job(&id));
+        assert!(!cancel_job(&id));
+        assert!(remove_job(&id));
+        assert!(get_job(&id).is_none());
+
+        let id = submit_job(request.clone())?;
+        if pause_job(&id) {
+            assert_eq!(get_job(&id).unwrap().state, JobState::Paused);
+            assert!(resume_job(&id));
+            assert!(pause_job(&id) || get_job(&id).unwrap().state.is_finished());
+        }
+        cancel_job(&id);
+        assert!(matches!(
+            wait_finished(&id).state,
+            JobState::Cancelled | JobState::Completed
+        ));
+
+        let unknown = JobRequest {
+            processor: "unknown".to_string(),
+            ..request
+        };
+        assert!(submit_job(unknown).is_err());
+        set_jobs_dir(None);
+        std::fs::remove_file(&journal)?;
+        std::fs::remove_file(&output)?;
+        Ok(())
+    }
+}
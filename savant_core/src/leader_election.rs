@@ -0,0 +1,263 @@
+use crate::get_or_init_async_runtime;
+use crate::webserver::lease::synchronous::{acquire_lease, release_lease};
+use crate::webserver::lease::{Lease, LeaseError};
+use anyhow::bail;
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Where the lease is kept: the KVS of this process or the KVS of the process serving the
+/// webserver at the URL, e.g. `http://coordinator:8080`. The replicas competing for a lease
+/// must use the same KVS.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum LeaseStore {
+    Local,
+    Remote(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeaderElectorConfig {
+    /// The name of the singleton task.
+    pub name: String,
+    /// The identity of the replica, unique among the replicas.
+    pub holder: String,
+    pub ttl: Duration,
+    /// How often the lease is renewed or acquired, must be shorter than the TTL.
+    pub renew_interval: Duration,
+    pub store: LeaseStore,
+}
+
+fn acquire_remote(url: &str, config: &LeaderElectorConfig) -> anyhow::Result<Option<Lease>> {
+    let url = format!(
+        "{}/kvs/lease/{}/acquire",
+        url.trim_end_matches('/'),
+        config.name
+    );
+    let query = [
+        ("holder", config.holder.clone()),
+        ("ttl_ms", config.ttl.as_millis().to_string()),
+    ];
+    get_or_init_async_runtime().block_on(async {
+        let response = reqwest::Client::new()
+            .post(&url)
+            .query(&query)
+            .timeout(config.renew_interval)
+            .send()
+            .await?;
+        match response.status() {
+            reqwest::StatusCode::OK => Ok(Some(response.json::<Lease>().await?)),
+            reqwest::StatusCode::CONFLICT => Ok(None),
+            status => bail!("Unexpected status {} from {}", status, url),
+        }
+    })
+}
+
+fn release_remote(url: &str, config: &LeaderElectorConfig) -> anyhow::Result<()> {
+    let url = format!(
+        "{}/kvs/lease/{}/release",
+        url.trim_end_matches('/'),
+        config.name
+    );
+    get_or_init_async_runtime().block_on(async {
+        let response = reqwest::Client::new()
+            .post(&url)
+            .query(&[("holder", config.holder.as_str())])
+            .timeout(config.renew_interval)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            bail!(
+                "Failed to release the lease with {} from {}: {}",
+                response.status(),
+                url,
+                response.text().await.unwrap_or_default()
+            );
+        }
+        Ok(())
+    })
+}
+
+/// The lease held by the replica and the local deadline of the leadership. The deadline is
+/// measured from the moment the renewal was sent, so the leadership ends locally before the
+/// lease expires in the store regardless of the clock skew.
+///
+type Leadership = Option<(Lease, Instant)>;
+
+/// Keeps acquiring the lease in the background, so exactly one of the replicas sharing the
+/// KVS is the leader and runs the singleton tasks. A leader which fails to renew the lease
+/// steps down when the TTL passes; the token of the lease fences the writes of a previous
+/// leader.
+///
+pub struct LeaderElector {
+    config: LeaderElectorConfig,
+    leadership: Arc<Mutex<Leadership>>,
+    shutdown: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl LeaderElector {
+    pub fn start(config: LeaderElectorConfig) -> anyhow::Result<Self> {
+        if config.renew_interval.is_zero() || config.renew_interval >= config.ttl {
+            bail!(
+                "The renew interval {:?} must be positive and shorter than the TTL {:?}",
+                config.renew_interval,
+                config.ttl
+            );
+        }
+        let leadership = Arc::new(Mutex::new(None));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let thread_config = config.clone();
+        let thread_leadership = leadership.clone();
+        let thread_shutdown = shutdown.clone();
+        let thread = std::thread::spawn(move || {
+            while !thread_shutdown.load(Ordering::Relaxed) {
+                Self::renew(&thread_config, &thread_leadership);
+                std::thread::park_timeout(thread_config.renew_interval);
+            }
+        });
+        Ok(Self {
+            config,
+            leadership,
+            shutdown,
+            thread: Some(thread),
+        })
+    }
+
+    fn renew(config: &LeaderElectorConfig, leadership: &Mutex<Leadership>) {
+        let sent = Instant::now();
+        let res = match &config.store {
+            LeaseStore::Local => match acquire_lease(&config.name, &config.holder, config.ttl) {
+                Ok(lease) => Ok(Some(lease)),
+                Err(LeaseError::Held(_)) => Ok(None),
+                Err(e) => Err(e.into()),
+            },
+            LeaseStore::Remote(url) => acquire_remote(url, config),
+        };
+        let mut leadership = leadership.lock();
+        let was_leader = leadership.is_some();
+        match res {
+            Ok(Some(lease)) => *leadership = Some((lease, sent + config.ttl)),
+            Ok(None) => *leadership = None,
+            // the leadership lasts until the local deadline
+            Err(e) => log::warn!(
+                target: "savant_rs::leader_election",
+                "Failed to renew the lease {}: {}",
+                config.name,
+                e
+            ),
+        }
+        match (was_leader, leadership.as_ref()) {
+            (false, Some((lease, _))) => log::info!(
+                target: "savant_rs::leader_election",
+                "{} became the leader of {} with token {}",
+                config.holder,
+                config.name,
+                lease.token
+            ),
+            (true, None) => log::info!(
+                target: "savant_rs::leader_election",
+                "{} lost the leadership of {}",
+                config.holder,
+                config.name
+            ),
+            _ => (),
+        }
+    }
+
+    pub fn get_config(&self) -> &LeaderElectorConfig {
+        &self.config
+    }
+
+    /// The lease while the replica is the leader. The singleton task passes the token of
+    /// the lease with its writes.
+    ///
+    pub fn get_lease(&self) -> Option<Lease> {
+        let mut leadership = self.leadership.lock();
+        if leadership
+            .as_ref()
+            .is_some_and(|(_, deadline)| Instant::now() >= *deadline)
+        {
+            *leadership = None;
+        }
+        leadership.as_ref().map(|(lease, _)| lease.clone())
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.get_lease().is_some()
+    }
+
+    /// Stops the renewals and releases the lease, so another replica takes over without
+    /// waiting for the TTL.
+    ///
+    pub fn stop(&mut self) -> anyhow::Result<()> {
+        let Some(thread) = self.thread.take() else {
+            return Ok(());
+        };
+        self.shutdown.store(true, Ordering::Relaxed);
+        thread.thread().unpark();
+        if thread.join().is_err() {
+            bail!("The leader election thread panicked");
+        }
+        if self.leadership.lock().take().is_none() {
+            return Ok(());
+        }
+        match &self.config.store {
+            LeaseStore::Local => {
+                release_lease(&self.config.name, &self.config.holder)?;
+            }
+            LeaseStore::Remote(url) => release_remote(url, &self.config)?,
+        }
+        Ok(())
+    }
+}
+
+impl Drop for LeaderElector {
+    fn drop(&mut self) {
+        if let Err(e) = self.stop() {
+            log::error!(target: "savant_rs::leader_election", "Failed to stop the leader election: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::leader_election::{LeaderElector, LeaderElectorConfig, LeaseStore};
+    use std::time::Duration;
+
+    fn config(holder: &str) -> LeaderElectorConfig {
+        LeaderElectorConfig {
+            name: "test_election".to_string(),
+            holder: holder.to_string(),
+            ttl: Duration::from_millis(200),
+            renew_interval: Duration::from_millis(20),
+            store: LeaseStore::Local,
+        }
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_single_leader() -> anyhow::Result<()> {
+        assert!(LeaderElector::start(LeaderElectorConfig {
+            renew_interval: Duration::from_secs(1),
+            ..config("a")
+        })
+        .is_err());
+        let mut a = LeaderElector::start(config("a"))?;
+        std::thread::sleep(Duration::from_millis(50));
+        let b = LeaderElector::start(config("b"))?;
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(a.is_leader());
+        assert!(!b.is_leader());
+        let token = a.get_lease().unwrap().token;
+
+        a.stop()?;
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(!a.is_leader());
+        assert!(b.is_leader());
+        assert!(b.get_lease().unwrap().token > token);
+        Ok(())
+    }
+}
@@ -0,0 +1,332 @@
+use crate::clock::system_now_ms;
+use crate::get_or_init_async_runtime;
+use crate::metrics::{get_or_create_counter_family, get_or_create_gauge_family};
+use crate::webserver::get_registered_pipelines;
+use crate::webserver::kvs::kvs_entry_count;
+use anyhow::bail;
+use hashbrown::{HashMap, HashSet};
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// The gauge holding the sampled sizes, labeled with the resource name.
+pub const LEAK_MONITOR_SIZE_GAUGE: &str = "leak_monitor_size";
+/// The counter of the detected growths, labeled with the resource name and the horizon.
+pub const LEAK_SUSPECT_COUNTER: &str = "leak_suspect_counter";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeakDetectorConfig {
+    /// How often the sizes are sampled.
+    pub period: Duration,
+    /// The windows over which a size growing at every sample is reported, the history covers
+    /// the longest one.
+    pub horizons: Vec<Duration>,
+    /// The minimum growth of the size over a horizon to report it, so small fluctuations of
+    /// mostly constant sizes are ignored.
+    pub min_growth: u64,
+}
+
+impl Default for LeakDetectorConfig {
+    fn default() -> Self {
+        Self {
+            period: Duration::from_secs(60),
+            horizons: vec![Duration::from_secs(3600), Duration::from_secs(6 * 3600)],
+            min_growth: 1,
+        }
+    }
+}
+
+/// A size which grew at every sample over a horizon.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeakSuspect {
+    pub resource: String,
+    pub horizon: Duration,
+    pub from: u64,
+    pub to: u64,
+    /// The time of detection in milliseconds since the UNIX epoch.
+    pub detected_at: u64,
+}
+
+/// Tracks the history of sizes and reports the ones growing monotonically over the
+/// configured horizons. A growth is reported once, it is reported again after the size
+/// stops growing for a while and starts again.
+///
+#[derive(Debug)]
+pub struct LeakDetector {
+    config: LeakDetectorConfig,
+    history: HashMap<String, VecDeque<(u64, u64)>>,
+    suspected: HashSet<(String, Duration)>,
+    suspects: Vec<LeakSuspect>,
+}
+
+impl LeakDetector {
+    pub fn new(config: LeakDetectorConfig) -> anyhow::Result<Self> {
+        if config.period.is_zero() {
+            bail!("The sampling period must be greater than 0");
+        }
+        if config.horizons.is_empty() {
+            bail!("At least one horizon is required");
+        }
+        if let Some(h) = config.horizons.iter().find(|h| **h < config.period * 2) {
+            bail!(
+                "The horizon {:?} must cover at least two sampling periods of {:?}",
+                h,
+                config.period
+            );
+        }
+        Ok(Self {
+            config,
+            history: HashMap::new(),
+            suspected: HashSet::new(),
+            suspects: Vec::new(),
+        })
+    }
+
+    pub fn get_config(&self) -> &LeakDetectorConfig {
+        &self.config
+    }
+
+    /// The growths detected so far.
+    ///
+    pub fn get_suspects(&self) -> &[LeakSuspect] {
+        &self.suspects
+    }
+
+    fn max_horizon_ms(&self) -> u64 {
+        self.config
+            .horizons
+            .iter()
+            .max()
+            .map(|h| h.as_millis() as u64)
+            .unwrap_or_default()
+    }
+
+    /// Records the size of the resource at the time and returns the growths detected by the
+    /// sample. The history of the resources missing in the samples is kept, so a stage
+    /// disappearing with its pipeline does not reset anything.
+    ///
+    pub fn observe(&mut self, resource: &str, size: u64, now_ms: u64) -> Vec<LeakSuspect> {
+        let max_horizon = self.max_horizon_ms();
+        let history = self.history.entry(resource.to_string()).or_default();
+        history.push_back((now_ms, size));
+        while history
+            .front()
+            .is_some_and(|(ts, _)| now_ms.saturating_sub(*ts) > max_horizon)
+        {
+            history.pop_front();
+        }
+
+        let mut detected = Vec::new();
+        for horizon in &self.config.horizons {
+            let key = (resource.to_string(), *horizon);
+            let horizon_ms = horizon.as_millis() as u64;
+            // the history must span the whole horizon
+            let Some(start) = history
+                .iter()
+                .position(|(ts, _)| now_ms.saturating_sub(*ts) <= horizon_ms)
+            else {
+                continue;
+            };
+            let spans_horizon = start > 0
+                || history
+                    .front()
+                    .is_some_and(|(ts, _)| now_ms.saturating_sub(*ts) == horizon_ms);
+            let window = history
+                .range(start..)
+                .map(|(_, size)| *size)
+                .collect::<Vec<_>>();
+            // the window holds at least the sample just recorded
+            let (first, last) = (window[0], window[window.len() - 1]);
+            let growing = spans_horizon
+                && window.windows(2).all(|w| w[0] <= w[1])
+                && last >= first + self.config.min_growth;
+            if !growing {
+                self.suspected.remove(&key);
+                continue;
+            }
+            if self.suspected.insert(key) {
+                detected.push(LeakSuspect {
+                    resource: resource.to_string(),
+                    horizon: *horizon,
+                    from: first,
+                    to: last,
+                    detected_at: now_ms,
+                });
+            }
+        }
+        self.suspects.extend(detected.iter().cloned());
+        detected
+    }
+}
+
+/// The sizes of the registered pipelines and of the KVS: the root spans, the frame locations
+/// and the stage queues of each pipeline, named `<pipeline>/<resource>`.
+///
+pub fn sample_sizes() -> Vec<(String, u64)> {
+    let pipelines = get_or_init_async_runtime().block_on(get_registered_pipelines());
+    let mut sizes = vec![("kvs".to_string(), kvs_entry_count())];
+    for (i, p) in pipelines.iter().enumerate() {
+        let name = p.get_name().unwrap_or_else(|| format!("pipeline-{}", i));
+        sizes.push((
+            format!("{}/root_spans", name),
+            p.get_root_spans_len() as u64,
+        ));
+        sizes.push((
+            format!("{}/frame_locations", name),
+            p.get_id_locations_len() as u64,
+        ));
+        for stage in p.get_topology().stages {
+            sizes.push((
+                format!("{}/stage/{}", name, stage.name),
+                stage.queue_length as u64,
+            ));
+        }
+    }
+    sizes
+}
+
+fn report(detector: &mut LeakDetector) {
+    let sizes = get_or_create_gauge_family(
+        LEAK_MONITOR_SIZE_GAUGE,
+        Some("Sizes sampled by the leak detector"),
+        &["resource"],
+        None,
+    );
+    let suspects = get_or_create_counter_family(
+        LEAK_SUSPECT_COUNTER,
+        Some("Number of monotonic growths detected by the leak detector"),
+        &["resource", "horizon_s"],
+        None,
+    );
+    let now = system_now_ms();
+    for (resource, size) in sample_sizes() {
+        if let Err(e) = sizes.lock().set(size as f64, &[&resource]) {
+            log::error!(target: "savant_rs::leak_detector", "Failed to set the size of {}: {}", resource, e);
+        }
+        for suspect in detector.observe(&resource, size, now) {
+            log::warn!(
+                target: "savant_rs::leak_detector",
+                "Possible leak: {} grew from {} to {} over {:?}",
+                suspect.resource,
+                suspect.from,
+                suspect.to,
+                suspect.horizon
+            );
+            let horizon = suspect.horizon.as_secs().to_string();
+            if let Err(e) = suspects.lock().inc(1, &[&resource, &horizon]) {
+                log::error!(target: "savant_rs::leak_detector", "Failed to count the leak of {}: {}", resource, e);
+            }
+        }
+    }
+}
+
+struct LeakMonitor {
+    detector: Arc<Mutex<LeakDetector>>,
+    shutdown: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+lazy_static! {
+    static ref LEAK_MONITOR: Mutex<Option<LeakMonitor>> = Mutex::new(None);
+}
+
+/// Starts the background thread sampling the sizes with the period, a running monitor is
+/// replaced. Detected growths are logged with the `savant_rs::leak_detector` target and
+/// counted in [`LEAK_SUSPECT_COUNTER`].
+///
+pub fn enable_leak_detection(config: LeakDetectorConfig) -> anyhow::Result<()> {
+    let detector = Arc::new(Mutex::new(LeakDetector::new(config.clone())?));
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let thread_detector = detector.clone();
+    let thread_shutdown = shutdown.clone();
+    let thread = std::thread::spawn(move || {
+        while !thread_shutdown.load(Ordering::Relaxed) {
+            report(&mut thread_detector.lock());
+            std::thread::park_timeout(config.period);
+        }
+    });
+    let previous = LEAK_MONITOR.lock().replace(LeakMonitor {
+        detector,
+        shutdown,
+        thread,
+    });
+    if let Some(monitor) = previous {
+        stop(monitor);
+    }
+    Ok(())
+}
+
+fn stop(monitor: LeakMonitor) {
+    monitor.shutdown.store(true, Ordering::Relaxed);
+    monitor.thread.thread().unpark();
+    if monitor.thread.join().is_err() {
+        log::error!(target: "savant_rs::leak_detector", "The leak detector thread panicked");
+    }
+}
+
+pub fn disable_leak_detection() {
+    if let Some(monitor) = LEAK_MONITOR.lock().take() {
+        stop(monitor);
+    }
+}
+
+/// The growths detected by the running monitor.
+///
+pub fn get_leak_suspects() -> Vec<LeakSuspect> {
+    LEAK_MONITOR
+        .lock()
+        .as_ref()
+        .map(|m| m.detector.lock().get_suspects().to_vec())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::leak_detection::{LeakDetector, LeakDetectorConfig};
+    use std::time::Duration;
+
+    #[test]
+    fn test_monotonic_growth() -> anyhow::Result<()> {
+        assert!(LeakDetector::new(LeakDetectorConfig {
+            period: Duration::from_secs(10),
+            horizons: vec![Duration::from_secs(15)],
+            min_growth: 1,
+        })
+        .is_err());
+        let mut detector = LeakDetector::new(LeakDetectorConfig {
+            period: Duration::from_secs(1),
+            horizons: vec![Duration::from_secs(3), Duration::from_secs(5)],
+            min_growth: 2,
+        })?;
+        // the history does not span the horizon yet
+        for (t, size) in [(0, 10), (1000, 11), (2000, 12)] {
+            assert!(detector.observe("spans", size, t).is_empty());
+            assert!(detector.observe("queue", 5, t).is_empty());
+        }
+        let detected = detector.observe("spans", 13, 3000);
+        assert_eq!(detected.len(), 1);
+        assert_eq!(detected[0].horizon, Duration::from_secs(3));
+        assert_eq!((detected[0].from, detected[0].to), (10, 13));
+        assert!(detector.observe("queue", 5, 3000).is_empty());
+
+        // reported once while growing
+        assert!(detector.observe("spans", 14, 4000).is_empty());
+        let detected = detector.observe("spans", 15, 5000);
+        assert_eq!(detected.len(), 1);
+        assert_eq!(detected[0].horizon, Duration::from_secs(5));
+
+        // a drop re-arms the detection
+        assert!(detector.observe("spans", 1, 6000).is_empty());
+        for (t, size) in [(7000, 2), (8000, 3)] {
+            assert!(detector.observe("spans", size, t).is_empty());
+        }
+        assert_eq!(detector.observe("spans", 4, 9000).len(), 1);
+        assert_eq!(detector.get_suspects().len(), 3);
+        Ok(())
+    }
+}
@@ -3,25 +3,44 @@ use opentelemetry::global::BoxedTracer;
 use std::sync::OnceLock;
 use tokio::runtime::Runtime;
 
+pub mod aggregation;
 pub mod atomic_f32;
+pub mod capabilities;
+pub mod clip;
+pub mod clock;
 pub mod deadlock_detection;
 pub mod draw;
+pub mod dump;
 pub mod eval_cache;
 pub mod eval_context;
 pub mod eval_resolvers;
+pub mod ha;
+pub mod jobs;
 /// A trait to serialize various objects to json.
 pub mod json_api;
+pub mod leader_election;
+pub mod leak_detection;
 pub mod macros;
 pub mod match_query;
 pub mod message;
+pub mod module;
+pub mod mot;
 pub mod otlp;
 pub mod pipeline;
 pub mod primitives;
 pub mod protobuf;
+pub mod provenance;
+pub mod reid;
+pub mod replay;
+pub mod retention;
 pub mod rwlock;
+pub mod sharding;
+pub mod simd;
+pub mod state;
 pub mod symbol_mapper;
 pub mod telemetry;
 pub mod test;
+pub mod transcoding;
 pub mod transport;
 pub mod utils;
 
@@ -64,11 +83,15 @@ pub fn get_tracer() -> BoxedTracer {
 
 pub mod rust {
     pub use super::otlp::PropagatedContext;
+    pub use super::pipeline::rates::StageRate;
     pub use super::pipeline::stats::FrameProcessingStatRecord;
     pub use super::pipeline::stats::FrameProcessingStatRecordType;
     pub use super::pipeline::stats::StageLatencyMeasurements;
     pub use super::pipeline::stats::StageLatencyStat;
     pub use super::pipeline::stats::StageProcessingStat;
+    pub use super::pipeline::synchronizer::StreamSynchronizer;
+    pub use super::pipeline::synchronizer::SynchronizerConfiguration;
+    pub use super::pipeline::synchronizer::SynchronizerConfigurationBuilder;
     pub use super::pipeline::Pipeline;
     pub use super::pipeline::PipelineConfiguration;
     pub use super::pipeline::PipelineConfigurationBuilder;
@@ -17,11 +17,14 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::ops::ControlFlow;
 
+pub mod compiled;
+
 pub use crate::query_and as and;
 pub use crate::query_not as not;
 pub use crate::query_or as or;
 pub use crate::query_stop_if_false as stop_if_false;
 pub use crate::query_stop_if_true as stop_if_true;
+pub use compiled::{CompiledMatchQuery, PredicateStats};
 
 pub type VideoObjectsProxyBatch = HashMap<i64, Vec<BorrowedVideoObject>>;
 
@@ -547,20 +550,30 @@ impl ExecutableMatchQuery<&VideoObject, ObjectContext<'_>> for MatchQuery {
     }
 }
 
+pub(crate) fn new_object_context(o: &VideoObject) -> ObjectContext<'_> {
+    ObjectContext::new(
+        o,
+        &[
+            utility_resolver_name(),
+            etcd_resolver_name(),
+            config_resolver_name(),
+            env_resolver_name(),
+        ],
+    )
+}
+
 impl MatchQuery {
     pub fn execute_with_new_context(&self, o: &VideoObject) -> ControlFlow<bool, bool> {
-        let mut context = ObjectContext::new(
-            o,
-            &[
-                utility_resolver_name(),
-                etcd_resolver_name(),
-                config_resolver_name(),
-                env_resolver_name(),
-            ],
-        );
+        let mut context = new_object_context(o);
         self.execute(o, &mut context)
     }
 
+    /// Compiles the query to an evaluator reused across frames, see [`CompiledMatchQuery`].
+    ///
+    pub fn compile(&self) -> CompiledMatchQuery {
+        CompiledMatchQuery::new(self)
+    }
+
     pub fn to_json_pretty(&self) -> String {
         serde_json::to_string_pretty(self).unwrap()
     }
@@ -0,0 +1,567 @@
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::eval_context::ObjectContext;
+use crate::match_query::{new_object_context, ExecutableMatchQuery, MatchQuery};
+use crate::primitives::object::{BorrowedVideoObject, ObjectAccess, VideoObject};
+use crate::rwlock::SavantRwLock;
+use crate::utils::iter::{fiter_map_with_control_flow, partition_with_control_flow};
+
+/// The number of evaluations after which the children of the combinators are reordered by
+/// the observed selectivity.
+///
+const REORDER_PERIOD: u64 = 4096;
+
+/// The pass rate assumed for the predicates not evaluated yet.
+///
+const UNKNOWN_PASS_RATE: f64 = 0.5;
+
+/// The statistics of a predicate of the compiled query. The path addresses the predicate in
+/// the source query: `$` is the root, `$.1.0` is the first child of its second child.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct PredicateStats {
+    pub path: String,
+    /// The JSON of a leaf predicate or the name of a combinator.
+    pub query: String,
+    pub evaluations: u64,
+    /// The number of evaluations which yielded true.
+    pub hits: u64,
+}
+
+impl PredicateStats {
+    /// The share of the evaluations which yielded true, `None` before the first evaluation.
+    ///
+    pub fn pass_rate(&self) -> Option<f64> {
+        (self.evaluations > 0).then(|| self.hits as f64 / self.evaluations as f64)
+    }
+}
+
+#[derive(Debug)]
+struct Predicate {
+    path: String,
+    query: String,
+    /// The relative cost of one evaluation.
+    cost: f64,
+    evaluations: AtomicU64,
+    hits: AtomicU64,
+}
+
+impl Predicate {
+    fn pass_rate(&self) -> f64 {
+        let evaluations = self.evaluations.load(Ordering::Relaxed);
+        if evaluations == 0 {
+            return UNKNOWN_PASS_RATE;
+        }
+        self.hits.load(Ordering::Relaxed) as f64 / evaluations as f64
+    }
+}
+
+#[derive(Debug)]
+struct Combinator {
+    children: Vec<Node>,
+    /// The evaluation order of the children.
+    order: SavantRwLock<Vec<usize>>,
+    /// The children can be reordered when none of them stops the evaluation or evaluates
+    /// expressions, which may depend on the variables set by the previous ones.
+    reorderable: bool,
+}
+
+#[derive(Debug)]
+enum Node {
+    Constant(bool),
+    /// A leaf evaluated without the expression context.
+    Plain(usize, MatchQuery),
+    /// A leaf using the object relations or the expression context.
+    Contextual(usize, MatchQuery),
+    And(usize, Combinator),
+    Or(usize, Combinator),
+    Not(usize, Box<Node>),
+    StopIfFalse(usize, Box<Node>),
+    StopIfTrue(usize, Box<Node>),
+}
+
+fn leaf_cost(query: &MatchQuery) -> f64 {
+    match query {
+        MatchQuery::EvalExpr(_)
+        | MatchQuery::AttributesJMESQuery(_)
+        | MatchQuery::FrameAttributesJMESQuery(_)
+        | MatchQuery::WithChildren(..) => 16.0,
+        MatchQuery::ParentNamespace(_)
+        | MatchQuery::ParentLabel(_)
+        | MatchQuery::FrameSourceId(_)
+        | MatchQuery::FrameIsKeyFrame
+        | MatchQuery::FrameTranscodingIsCopy
+        | MatchQuery::FrameWidth(_)
+        | MatchQuery::FrameHeight(_)
+        | MatchQuery::FrameNoVideo
+        | MatchQuery::FrameAttributeExists(..)
+        | MatchQuery::FrameAttributesEmpty => 4.0,
+        MatchQuery::BoxMetric { .. } | MatchQuery::TrackBoxMetric { .. } => 2.0,
+        _ => 1.0,
+    }
+}
+
+/// Whether the leaf is executed by the plain object evaluator, the others need the object
+/// relations or the expression context.
+///
+fn is_plain(query: &MatchQuery) -> bool {
+    !matches!(
+        query,
+        MatchQuery::WithChildren(..)
+            | MatchQuery::EvalExpr(_)
+            | MatchQuery::ParentId(_)
+            | MatchQuery::ParentNamespace(_)
+            | MatchQuery::ParentLabel(_)
+            | MatchQuery::FrameSourceId(_)
+            | MatchQuery::FrameIsKeyFrame
+            | MatchQuery::FrameTranscodingIsCopy
+            | MatchQuery::FrameWidth(_)
+            | MatchQuery::FrameHeight(_)
+            | MatchQuery::FrameNoVideo
+            | MatchQuery::FrameAttributeExists(..)
+            | MatchQuery::FrameAttributesEmpty
+            | MatchQuery::FrameAttributesJMESQuery(_)
+    )
+}
+
+impl Node {
+    fn is_order_sensitive(&self) -> bool {
+        match self {
+            Node::Constant(_) | Node::Plain(..) => false,
+            Node::Contextual(_, query) => matches!(query, MatchQuery::EvalExpr(_)),
+            Node::And(_, c) | Node::Or(_, c) => !c.reorderable,
+            Node::Not(_, child) => child.is_order_sensitive(),
+            Node::StopIfFalse(..) | Node::StopIfTrue(..) => true,
+        }
+    }
+
+    fn predicate(&self) -> Option<usize> {
+        match self {
+            Node::Constant(_) => None,
+            Node::Plain(id, _)
+            | Node::Contextual(id, _)
+            | Node::And(id, _)
+            | Node::Or(id, _)
+            | Node::Not(id, _)
+            | Node::StopIfFalse(id, _)
+            | Node::StopIfTrue(id, _) => Some(*id),
+        }
+    }
+}
+
+/// The context is created on the first use, so the queries evaluated without it do not pay
+/// for it.
+///
+struct LazyContext<'a> {
+    object: &'a VideoObject,
+    context: Option<ObjectContext<'a>>,
+}
+
+impl<'a> LazyContext<'a> {
+    fn get(&mut self) -> &mut ObjectContext<'a> {
+        self.context
+            .get_or_insert_with(|| new_object_context(self.object))
+    }
+}
+
+/// A query compiled to an evaluator reused across frames. The nested `and` and `or` are
+/// flattened, the `pass` predicates are removed, and the evaluation short-circuits like
+/// the interpreted query. Every predicate counts its evaluations and hits; periodically the
+/// children of the combinators are reordered, so the cheap predicates most likely to decide
+/// the result are evaluated first. The combinators with `stop_if_*` or `eval` children keep
+/// their order, so the compiled query yields the same results as the source one.
+///
+#[derive(Debug)]
+pub struct CompiledMatchQuery {
+    source: MatchQuery,
+    root: Node,
+    predicates: Vec<Predicate>,
+    evaluations: AtomicU64,
+}
+
+impl CompiledMatchQuery {
+    pub fn new(query: &MatchQuery) -> Self {
+        let mut predicates = Vec::new();
+        let root = Self::compile(query, "$".to_string(), &mut predicates);
+        Self {
+            source: query.clone(),
+            root,
+            predicates,
+            evaluations: AtomicU64::new(0),
+        }
+    }
+
+    pub fn get_source(&self) -> &MatchQuery {
+        &self.source
+    }
+
+    fn register(predicates: &mut Vec<Predicate>, path: String, query: String, cost: f64) -> usize {
+        predicates.push(Predicate {
+            path,
+            query,
+            cost,
+            evaluations: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+        });
+        predicates.len() - 1
+    }
+
+    /// Compiles the children of the combinator inlining the nested combinators of the same
+    /// kind.
+    ///
+    fn compile_children(
+        queries: &[MatchQuery],
+        path: &str,
+        is_and: bool,
+        predicates: &mut Vec<Predicate>,
+        children: &mut Vec<Node>,
+    ) {
+        for (i, query) in queries.iter().enumerate() {
+            let path = format!("{}.{}", path, i);
+            match (query, is_and) {
+                (MatchQuery::And(nested), true) | (MatchQuery::Or(nested), false) => {
+                    Self::compile_children(nested, &path, is_and, predicates, children)
+                }
+                _ => match Self::compile(query, path, predicates) {
+                    // the neutral element does not change the result
+                    Node::Constant(value) if value == is_and => (),
+                    node => children.push(node),
+                },
+            }
+        }
+    }
+
+    fn compile_combinator(
+        queries: &[MatchQuery],
+        path: String,
+        is_and: bool,
+        predicates: &mut Vec<Predicate>,
+    ) -> Node {
+        let mut children = Vec::with_capacity(queries.len());
+        Self::compile_children(queries, &path, is_and, predicates, &mut children);
+        if children.is_empty() {
+            return Node::Constant(is_and);
+        }
+        if children.len() == 1 {
+            return children.pop().unwrap();
+        }
+        let cost = children
+            .iter()
+            .filter_map(|c| c.predicate())
+            .map(|id| predicates[id].cost)
+            .sum();
+        let name = if is_and { "and" } else { "or" };
+        let id = Self::register(predicates, path, name.to_string(), cost);
+        let combinator = Combinator {
+            reorderable: !children.iter().any(|c| c.is_order_sensitive()),
+            order: SavantRwLock::new((0..children.len()).collect()),
+            children,
+        };
+        if is_and {
+            Node::And(id, combinator)
+        } else {
+            Node::Or(id, combinator)
+        }
+    }
+
+    fn register_unary(
+        predicates: &mut Vec<Predicate>,
+        path: String,
+        name: &str,
+        child: &Node,
+    ) -> usize {
+        let cost = child
+            .predicate()
+            .map(|id| predicates[id].cost)
+            .unwrap_or(0.0);
+        Self::register(predicates, path, name.to_string(), cost)
+    }
+
+    fn compile_unary(
+        query: &MatchQuery,
+        path: String,
+        name: &str,
+        predicates: &mut Vec<Predicate>,
+    ) -> (usize, Box<Node>) {
+        let child = Self::compile(query, format!("{}.0", path), predicates);
+        let id = Self::register_unary(predicates, path, name, &child);
+        (id, Box::new(child))
+    }
+
+    fn compile(query: &MatchQuery, path: String, predicates: &mut Vec<Predicate>) -> Node {
+        match query {
+            MatchQuery::Idle => Node::Constant(true),
+            MatchQuery::And(queries) => Self::compile_combinator(queries, path, true, predicates),
+            MatchQuery::Or(queries) => Self::compile_combinator(queries, path, false, predicates),
+            MatchQuery::Not(query) => match Self::compile(query, format!("{}.0", path), predicates)
+            {
+                Node::Constant(value) => Node::Constant(!value),
+                child => {
+                    let id = Self::register_unary(predicates, path, "not", &child);
+                    Node::Not(id, Box::new(child))
+                }
+            },
+            MatchQuery::StopIfFalse(query) => {
+                let (id, child) = Self::compile_unary(query, path, "stop_if_false", predicates);
+                Node::StopIfFalse(id, child)
+            }
+            MatchQuery::StopIfTrue(query) => {
+                let (id, child) = Self::compile_unary(query, path, "stop_if_true", predicates);
+                Node::StopIfTrue(id, child)
+            }
+            leaf => {
+                let id = Self::register(predicates, path, leaf.to_json(), leaf_cost(leaf));
+                if is_plain(leaf) {
+                    Node::Plain(id, leaf.clone())
+                } else {
+                    Node::Contextual(id, leaf.clone())
+                }
+            }
+        }
+    }
+
+    fn count(&self, id: usize, result: ControlFlow<bool, bool>) -> ControlFlow<bool, bool> {
+        let predicate = &self.predicates[id];
+        predicate.evaluations.fetch_add(1, Ordering::Relaxed);
+        if let ControlFlow::Continue(true) | ControlFlow::Break(true) = result {
+            predicate.hits.fetch_add(1, Ordering::Relaxed);
+        }
+        result
+    }
+
+    fn evaluate(&self, node: &Node, ctx: &mut LazyContext) -> ControlFlow<bool, bool> {
+        match node {
+            Node::Constant(value) => ControlFlow::Continue(*value),
+            Node::Plain(id, query) => self.count(*id, query.execute(ctx.object, &mut ())),
+            Node::Contextual(id, query) => {
+                let object = ctx.object;
+                self.count(*id, query.execute(object, ctx.get()))
+            }
+            Node::And(id, combinator) => {
+                let order = combinator.order.read_recursive();
+                for i in order.iter() {
+                    match self.evaluate(&combinator.children[*i], ctx) {
+                        ControlFlow::Continue(true) => continue,
+                        res => return self.count(*id, res),
+                    }
+                }
+                self.count(*id, ControlFlow::Continue(true))
+            }
+            Node::Or(id, combinator) => {
+                let order = combinator.order.read_recursive();
+                for i in order.iter() {
+                    match self.evaluate(&combinator.children[*i], ctx) {
+                        ControlFlow::Continue(false) => continue,
+                        res => return self.count(*id, res),
+                    }
+                }
+                self.count(*id, ControlFlow::Continue(false))
+            }
+            Node::Not(id, child) => {
+                let res = match self.evaluate(child, ctx) {
+                    ControlFlow::Continue(x) => ControlFlow::Continue(!x),
+                    ControlFlow::Break(x) => ControlFlow::Break(!x),
+                };
+                self.count(*id, res)
+            }
+            Node::StopIfFalse(id, child) => {
+                let res = match self.evaluate(child, ctx) {
+                    ControlFlow::Continue(false) => ControlFlow::Break(false),
+                    res => res,
+                };
+                self.count(*id, res)
+            }
+            Node::StopIfTrue(id, child) => {
+                let res = match self.evaluate(child, ctx) {
+                    ControlFlow::Continue(true) => ControlFlow::Break(true),
+                    res => res,
+                };
+                self.count(*id, res)
+            }
+        }
+    }
+
+    pub fn execute(&self, o: &VideoObject) -> ControlFlow<bool, bool> {
+        let mut ctx = LazyContext {
+            object: o,
+            context: None,
+        };
+        let res = self.evaluate(&self.root, &mut ctx);
+        if (self.evaluations.fetch_add(1, Ordering::Relaxed) + 1) % REORDER_PERIOD == 0 {
+            self.optimize();
+        }
+        res
+    }
+
+    pub fn filter(&self, objs: &[BorrowedVideoObject]) -> Vec<BorrowedVideoObject> {
+        fiter_map_with_control_flow(objs.iter(), |o| o.with_object_ref(|o| self.execute(o)))
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+
+    pub fn partition(
+        &self,
+        objs: &[BorrowedVideoObject],
+    ) -> (Vec<BorrowedVideoObject>, Vec<BorrowedVideoObject>) {
+        let (a, b) =
+            partition_with_control_flow(objs.iter(), |o| o.with_object_ref(|o| self.execute(o)));
+        (
+            a.into_iter().cloned().collect(),
+            b.into_iter().cloned().collect(),
+        )
+    }
+
+    /// The expected cost of evaluating the child per evaluation deciding the result of the
+    /// combinator: `and` is decided by false, `or` by true.
+    ///
+    fn rank(&self, node: &Node, is_and: bool) -> f64 {
+        let Some(predicate) = node.predicate().map(|id| &self.predicates[id]) else {
+            return 0.0;
+        };
+        let pass_rate = predicate.pass_rate();
+        let decisive = if is_and { 1.0 - pass_rate } else { pass_rate };
+        predicate.cost / decisive.max(1e-3)
+    }
+
+    fn optimize_node(&self, node: &Node) {
+        match node {
+            Node::And(_, combinator) | Node::Or(_, combinator) => {
+                let is_and = matches!(node, Node::And(..));
+                combinator
+                    .children
+                    .iter()
+                    .for_each(|child| self.optimize_node(child));
+                if combinator.reorderable {
+                    let ranks = combinator
+                        .children
+                        .iter()
+                        .map(|child| self.rank(child, is_and))
+                        .collect::<Vec<_>>();
+                    let mut order = combinator.order.write();
+                    order.sort_by(|a, b| ranks[*a].total_cmp(&ranks[*b]));
+                }
+            }
+            Node::Not(_, child) | Node::StopIfFalse(_, child) | Node::StopIfTrue(_, child) => {
+                self.optimize_node(child)
+            }
+            Node::Constant(_) | Node::Plain(..) | Node::Contextual(..) => (),
+        }
+    }
+
+    /// Reorders the children of the combinators by the statistics collected so far. Called
+    /// automatically every few thousand evaluations.
+    ///
+    pub fn optimize(&self) {
+        self.optimize_node(&self.root);
+    }
+
+    /// The statistics of the predicates ordered by path.
+    ///
+    pub fn get_stats(&self) -> Vec<PredicateStats> {
+        let mut stats = self
+            .predicates
+            .iter()
+            .map(|p| PredicateStats {
+                path: p.path.clone(),
+                query: p.query.clone(),
+                evaluations: p.evaluations.load(Ordering::Relaxed),
+                hits: p.hits.load(Ordering::Relaxed),
+            })
+            .collect::<Vec<_>>();
+        stats.sort_by(|a, b| a.path.cmp(&b.path));
+        stats
+    }
+
+    /// Resets the statistics, the current order of the predicates is kept.
+    ///
+    pub fn reset_stats(&self) {
+        for predicate in &self.predicates {
+            predicate.evaluations.store(0, Ordering::Relaxed);
+            predicate.hits.store(0, Ordering::Relaxed);
+        }
+        self.evaluations.store(0, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::match_query::{and, eq, filter, not, or, stop_if_true, MatchQuery};
+    use crate::primitives::object::{BorrowedVideoObject, ObjectOperations};
+    use crate::test::gen_frame;
+
+    fn ids(objects: &[BorrowedVideoObject]) -> Vec<i64> {
+        let mut ids = objects.iter().map(|o| o.get_id()).collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn test_compiled_matches_interpreted() {
+        let frame = gen_frame();
+        let objects = frame.get_all_objects();
+        let queries = [
+            and![
+                MatchQuery::Idle,
+                and![
+                    MatchQuery::Namespace(eq("test2")),
+                    MatchQuery::ParentDefined
+                ],
+                MatchQuery::Label(eq("test"))
+            ],
+            or![
+                MatchQuery::Id(eq(0)),
+                not!(MatchQuery::ParentLabel(eq("test2"))),
+                MatchQuery::Label(eq("test2"))
+            ],
+            or![
+                stop_if_true!(MatchQuery::Id(eq(1))),
+                MatchQuery::Namespace(eq("test"))
+            ],
+            MatchQuery::And(vec![]),
+            MatchQuery::Or(vec![]),
+            not!(MatchQuery::Idle),
+        ];
+        for query in queries {
+            let compiled = query.compile();
+            assert_eq!(
+                ids(&compiled.filter(&objects)),
+                ids(&filter(&objects, &query)),
+                "{:?}",
+                query
+            );
+            compiled.optimize();
+            assert_eq!(
+                ids(&compiled.filter(&objects)),
+                ids(&filter(&objects, &query))
+            );
+        }
+    }
+
+    #[test]
+    fn test_reordering_by_selectivity() {
+        let frame = gen_frame();
+        let objects = frame.get_all_objects();
+        // the first predicate passes every object, the second rejects two of three
+        let query = and![MatchQuery::BoxWidth(eq(0.0)), MatchQuery::Label(eq("test"))];
+        let compiled = query.compile();
+        assert_eq!(ids(&compiled.filter(&objects)), vec![1]);
+        let stats = compiled.get_stats();
+        assert_eq!(
+            stats.iter().map(|s| s.path.as_str()).collect::<Vec<_>>(),
+            vec!["$", "$.0", "$.1"]
+        );
+        assert_eq!((stats[1].evaluations, stats[1].hits), (3, 3));
+        assert_eq!((stats[2].evaluations, stats[2].hits), (3, 1));
+        assert_eq!(stats[0].pass_rate(), Some(1.0 / 3.0));
+
+        compiled.optimize();
+        compiled.reset_stats();
+        assert_eq!(ids(&compiled.filter(&objects)), vec![1]);
+        let stats = compiled.get_stats();
+        // the label is evaluated first now, the other predicate only for its hit
+        assert_eq!(stats[2].evaluations, 3);
+        assert_eq!(stats[1].evaluations, 1);
+    }
+}
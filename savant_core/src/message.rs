@@ -1,14 +1,20 @@
 pub mod label_filter;
 
 use crate::otlp::PropagatedContext;
+use crate::primitives::audio_frame::AudioFrame;
+use crate::primitives::classification::DataClassification;
 use crate::primitives::eos::EndOfStream;
 use crate::primitives::frame::VideoFrameProxy;
 use crate::primitives::frame_batch::VideoFrameBatch;
 use crate::primitives::frame_update::VideoFrameUpdate;
+use crate::primitives::object::ObjectOperations;
 use crate::primitives::shutdown::Shutdown;
+use crate::primitives::telemetry_frame::TelemetryFrame;
 use crate::primitives::userdata::UserData;
 use crate::primitives::WithAttributes;
-use crate::protobuf::{deserialize, serialize};
+use crate::protobuf::{
+    content_hash, deserialize, serialize, serialize_canonical, serialize_with_profile,
+};
 use crate::trace;
 use lazy_static::lazy_static;
 use lru::LruCache;
@@ -78,6 +84,8 @@ impl SeqStore {
                 self.validate_seq_i_raw(&vf.inner.read().source_id, seq_id)
             }
             MessageEnvelope::UserData(ud) => self.validate_seq_i_raw(&ud.source_id, seq_id),
+            MessageEnvelope::AudioFrame(af) => self.validate_seq_i_raw(&af.source_id, seq_id),
+            MessageEnvelope::TelemetryFrame(tf) => self.validate_seq_i_raw(&tf.source_id, seq_id),
             _ => true,
         }
     }
@@ -105,6 +113,8 @@ pub(crate) enum MessageEnvelope {
     VideoFrameBatch(VideoFrameBatch),
     VideoFrameUpdate(VideoFrameUpdate),
     UserData(UserData),
+    AudioFrame(AudioFrame),
+    TelemetryFrame(TelemetryFrame),
     Shutdown(Shutdown),
     Unknown(String),
 }
@@ -189,6 +199,24 @@ impl Message {
         }
     }
 
+    pub fn audio_frame(mut frame: AudioFrame) -> Self {
+        let seq_id = generate_message_seq_id(frame.get_source_id());
+        frame.exclude_temporary_attributes();
+        Self {
+            meta: MessageMeta::new(seq_id),
+            payload: MessageEnvelope::AudioFrame(frame),
+        }
+    }
+
+    pub fn telemetry_frame(mut frame: TelemetryFrame) -> Self {
+        let seq_id = generate_message_seq_id(frame.get_source_id());
+        frame.exclude_temporary_attributes();
+        Self {
+            meta: MessageMeta::new(seq_id),
+            payload: MessageEnvelope::TelemetryFrame(frame),
+        }
+    }
+
     pub fn video_frame_batch(batch: &VideoFrameBatch) -> Self {
         let batch_copy = batch.clone();
         Self {
@@ -216,6 +244,12 @@ impl Message {
         &mut self.meta
     }
 
+    /// A stable hash of the message content, see [`crate::protobuf::content_hash`].
+    ///
+    pub fn content_hash(&self) -> u64 {
+        content_hash(self)
+    }
+
     pub fn get_labels(&self) -> Vec<String> {
         self.meta.routing_labels.clone()
     }
@@ -249,6 +283,12 @@ impl Message {
     pub fn is_video_frame_batch(&self) -> bool {
         matches!(self.payload, MessageEnvelope::VideoFrameBatch(_))
     }
+    pub fn is_audio_frame(&self) -> bool {
+        matches!(self.payload, MessageEnvelope::AudioFrame(_))
+    }
+    pub fn is_telemetry_frame(&self) -> bool {
+        matches!(self.payload, MessageEnvelope::TelemetryFrame(_))
+    }
     pub fn as_unknown(&self) -> Option<String> {
         match &self.payload {
             MessageEnvelope::Unknown(s) => Some(s.clone()),
@@ -293,6 +333,40 @@ impl Message {
             _ => None,
         }
     }
+    pub fn as_audio_frame(&self) -> Option<&AudioFrame> {
+        match &self.payload {
+            MessageEnvelope::AudioFrame(frame) => Some(frame),
+            _ => None,
+        }
+    }
+    pub fn as_telemetry_frame(&self) -> Option<&TelemetryFrame> {
+        match &self.payload {
+            MessageEnvelope::TelemetryFrame(frame) => Some(frame),
+            _ => None,
+        }
+    }
+
+    /// The highest classification of the frames and the objects of the message, sinks use it
+    /// to route the message, e.g. to a restricted storage.
+    ///
+    pub fn get_classification(&self) -> DataClassification {
+        match &self.payload {
+            MessageEnvelope::VideoFrame(frame) => frame.get_effective_classification(),
+            MessageEnvelope::VideoFrameBatch(batch) => batch
+                .frames()
+                .values()
+                .map(|f| f.get_effective_classification())
+                .max()
+                .unwrap_or_default(),
+            MessageEnvelope::VideoFrameUpdate(update) => update
+                .get_objects()
+                .iter()
+                .map(|(o, _)| o.get_classification())
+                .max()
+                .unwrap_or_default(),
+            _ => DataClassification::Public,
+        }
+    }
 }
 
 pub fn load_message(bytes: &[u8]) -> Message {
@@ -319,6 +393,20 @@ pub fn save_message(m: &Message) -> anyhow::Result<Vec<u8>> {
     Ok(serialize(m)?)
 }
 
+/// Saves the message with the deterministic encoding of [`serialize_canonical`], so equal
+/// messages produce equal bytes.
+///
+pub fn save_message_canonical(m: &Message) -> anyhow::Result<Vec<u8>> {
+    Ok(serialize_canonical(m)?)
+}
+
+/// Saves the message with the registered serialization profile, see
+/// [`crate::protobuf::SerializationProfile`].
+///
+pub fn save_message_with_profile(m: &Message, profile: &str) -> anyhow::Result<Vec<u8>> {
+    Ok(serialize_with_profile(m, profile)?)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::message::{load_message, save_message, validate_seq_id, Message};
@@ -26,6 +26,16 @@ pub struct Counter {
     values: HashMap<Vec<String>, u64>,
 }
 
+/// A counter of a fractional quantity like the seconds spent in a code section.
+///
+pub struct FloatCounter {
+    name: String,
+    description: Option<String>,
+    label_names: Vec<String>,
+    unit: Option<Unit>,
+    values: HashMap<Vec<String>, f64>,
+}
+
 pub struct Gauge {
     name: String,
     description: Option<String>,
@@ -34,12 +44,36 @@ pub struct Gauge {
     values: HashMap<Vec<String>, f64>,
 }
 
+/// The observations of a histogram with the same label values: the sum, the count and the
+/// number of observations falling into every bucket, not cumulative; the last bucket counts
+/// the observations above the largest bound.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistogramValue {
+    pub sum: f64,
+    pub count: u64,
+    pub buckets: Vec<u64>,
+}
+
+pub struct Histogram {
+    name: String,
+    description: Option<String>,
+    label_names: Vec<String>,
+    unit: Option<Unit>,
+    bounds: Vec<f64>,
+    values: HashMap<Vec<String>, HistogramValue>,
+}
+
 pub type SharedCounterFamily = Arc<Mutex<Counter>>;
+pub type SharedFloatCounterFamily = Arc<Mutex<FloatCounter>>;
 pub type SharedGaugeFamily = Arc<Mutex<Gauge>>;
+pub type SharedHistogramFamily = Arc<Mutex<Histogram>>;
 
 enum MetricType {
     Counter(SharedCounterFamily),
+    FloatCounter(SharedFloatCounterFamily),
     Gauge(SharedGaugeFamily),
+    Histogram(SharedHistogramFamily),
 }
 
 lazy_static! {
@@ -106,6 +140,55 @@ pub fn get_counter_family(name: &str) -> Option<SharedCounterFamily> {
     }
 }
 
+/// The names of the registered counter families.
+///
+pub fn get_counter_family_names() -> Vec<String> {
+    REGISTRY
+        .lock()
+        .iter()
+        .filter(|(_, metric)| matches!(metric, MetricType::Counter(_)))
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+pub fn new_float_counter(
+    name: &str,
+    description: Option<&str>,
+    label_names: &[&str],
+    unit: Option<Unit>,
+) -> SharedFloatCounterFamily {
+    let mut registry = REGISTRY.lock();
+    let counter = Arc::new(Mutex::new(FloatCounter {
+        name: name.to_string(),
+        description: description.map(|s| s.to_string()),
+        label_names: label_names.iter().map(|s| s.to_string()).collect(),
+        unit,
+        values: HashMap::new(),
+    }));
+    registry.insert(name.to_string(), MetricType::FloatCounter(counter.clone()));
+    counter
+}
+
+pub fn get_or_create_float_counter_family(
+    name: &str,
+    description: Option<&str>,
+    label_names: &[&str],
+    unit: Option<Unit>,
+) -> SharedFloatCounterFamily {
+    match get_float_counter_family(name) {
+        Some(counter) => counter,
+        None => new_float_counter(name, description, label_names, unit),
+    }
+}
+
+pub fn get_float_counter_family(name: &str) -> Option<SharedFloatCounterFamily> {
+    let registry = REGISTRY.lock();
+    match registry.get(name) {
+        Some(MetricType::FloatCounter(counter)) => Some(counter.clone()),
+        _ => None,
+    }
+}
+
 pub fn new_gauge(
     name: &str,
     description: Option<&str>,
@@ -144,6 +227,53 @@ pub fn get_gauge_family(name: &str) -> Option<SharedGaugeFamily> {
     }
 }
 
+/// Creates a histogram family with the upper bounds of the buckets, the bounds are sorted and
+/// deduplicated.
+///
+pub fn new_histogram(
+    name: &str,
+    description: Option<&str>,
+    label_names: &[&str],
+    unit: Option<Unit>,
+    bounds: &[f64],
+) -> SharedHistogramFamily {
+    let mut registry = REGISTRY.lock();
+    let mut bounds = bounds.to_vec();
+    bounds.sort_by(|a, b| a.total_cmp(b));
+    bounds.dedup();
+    let histogram = Arc::new(Mutex::new(Histogram {
+        name: name.to_string(),
+        description: description.map(|s| s.to_string()),
+        label_names: label_names.iter().map(|s| s.to_string()).collect(),
+        unit,
+        bounds,
+        values: HashMap::new(),
+    }));
+    registry.insert(name.to_string(), MetricType::Histogram(histogram.clone()));
+    histogram
+}
+
+pub fn get_or_create_histogram_family(
+    name: &str,
+    description: Option<&str>,
+    label_names: &[&str],
+    unit: Option<Unit>,
+    bounds: &[f64],
+) -> SharedHistogramFamily {
+    match get_histogram_family(name) {
+        Some(histogram) => histogram,
+        None => new_histogram(name, description, label_names, unit, bounds),
+    }
+}
+
+pub fn get_histogram_family(name: &str) -> Option<SharedHistogramFamily> {
+    let registry = REGISTRY.lock();
+    match registry.get(name) {
+        Some(MetricType::Histogram(histogram)) => Some(histogram.clone()),
+        _ => None,
+    }
+}
+
 pub fn delete_metric_family(name: &str) {
     let mut registry = REGISTRY.lock();
     registry.remove(name);
@@ -223,6 +353,73 @@ impl Counter {
     }
 }
 
+impl FloatCounter {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn get_label_names(&self) -> &[String] {
+        &self.label_names
+    }
+
+    pub fn get_unit(&self) -> &Option<Unit> {
+        &self.unit
+    }
+
+    pub fn inc(&mut self, increment: f64, label_values: &[&str]) -> anyhow::Result<f64> {
+        let labels = collect_labels(label_values);
+        if labels.len() != self.label_names.len() {
+            bail!("Invalid labels: {:?} != {:?}", &labels, &self.label_names);
+        }
+        let counter = self.values.entry(labels).or_insert(0.0);
+        let last_value = *counter;
+        *counter += increment;
+        Ok(last_value)
+    }
+
+    pub fn set(&mut self, value: f64, label_values: &[&str]) -> anyhow::Result<f64> {
+        let labels = collect_labels(label_values);
+        if labels.len() != self.label_names.len() {
+            bail!("Invalid labels: {:?} != {:?}", &labels, &self.label_names);
+        }
+        let counter = self.values.entry(labels).or_insert(value);
+        let last_value = *counter;
+        *counter = value;
+        Ok(last_value)
+    }
+
+    pub fn get(&self, label_values: &[&str]) -> anyhow::Result<Option<f64>> {
+        let labels = collect_labels(label_values);
+        if labels.len() != self.label_names.len() {
+            bail!("Invalid labels: {:?} != {:?}", &labels, &self.label_names);
+        }
+        Ok(self.values.get(&labels).cloned())
+    }
+
+    pub fn delete(&mut self, label_values: &[&str]) -> anyhow::Result<Option<f64>> {
+        let labels = collect_labels(label_values);
+        if labels.len() != self.label_names.len() {
+            bail!("Invalid labels: {:?} != {:?}", &labels, &self.label_names);
+        }
+        Ok(self.values.remove(&labels))
+    }
+
+    pub fn get_all(&self) -> &HashMap<Vec<String>, f64> {
+        &self.values
+    }
+
+    pub fn export(&self) -> Vec<(PrometheusLabels, f64)> {
+        self.values
+            .iter()
+            .map(|(labels, value)| (build_labels(&self.label_names, labels), *value))
+            .collect()
+    }
+}
+
 impl Gauge {
     pub fn get_name(&self) -> &str {
         &self.name
@@ -282,9 +479,95 @@ impl Gauge {
     }
 }
 
+impl Histogram {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn get_label_names(&self) -> &[String] {
+        &self.label_names
+    }
+
+    pub fn get_unit(&self) -> &Option<Unit> {
+        &self.unit
+    }
+
+    pub fn get_bounds(&self) -> &[f64] {
+        &self.bounds
+    }
+
+    pub fn observe(&mut self, value: f64, label_values: &[&str]) -> anyhow::Result<()> {
+        let labels = collect_labels(label_values);
+        if labels.len() != self.label_names.len() {
+            bail!("Invalid labels: {:?} != {:?}", &labels, &self.label_names);
+        }
+        let bucket = self.bounds.partition_point(|b| *b < value);
+        let buckets = self.bounds.len() + 1;
+        let histogram = self.values.entry(labels).or_insert_with(|| HistogramValue {
+            sum: 0.0,
+            count: 0,
+            buckets: vec![0; buckets],
+        });
+        histogram.sum += value;
+        histogram.count += 1;
+        histogram.buckets[bucket] += 1;
+        Ok(())
+    }
+
+    pub fn get(&self, label_values: &[&str]) -> anyhow::Result<Option<HistogramValue>> {
+        let labels = collect_labels(label_values);
+        if labels.len() != self.label_names.len() {
+            bail!("Invalid labels: {:?} != {:?}", &labels, &self.label_names);
+        }
+        Ok(self.values.get(&labels).cloned())
+    }
+
+    pub fn delete(&mut self, label_values: &[&str]) -> anyhow::Result<Option<HistogramValue>> {
+        let labels = collect_labels(label_values);
+        if labels.len() != self.label_names.len() {
+            bail!("Invalid labels: {:?} != {:?}", &labels, &self.label_names);
+        }
+        Ok(self.values.remove(&labels))
+    }
+
+    pub fn get_all(&self) -> &HashMap<Vec<String>, HistogramValue> {
+        &self.values
+    }
+
+    /// Returns the label sets with the sums, the counts and the buckets as the pairs of the
+    /// upper bound and the number of observations; the bound of the last bucket is `f64::MAX`.
+    ///
+    pub fn export(&self) -> Vec<(PrometheusLabels, f64, u64, Vec<(f64, u64)>)> {
+        self.values
+            .iter()
+            .map(|(labels, value)| {
+                let buckets = self
+                    .bounds
+                    .iter()
+                    .copied()
+                    .chain(std::iter::once(f64::MAX))
+                    .zip(value.buckets.iter().copied())
+                    .collect();
+                (
+                    build_labels(&self.label_names, labels),
+                    value.sum,
+                    value.count,
+                    buckets,
+                )
+            })
+            .collect()
+    }
+}
+
 pub enum ConstMetric {
     Counter(Family<PrometheusLabels, PrometheusCounter, PrometheusCounterFn>),
+    FloatCounter(Vec<(PrometheusLabels, f64)>),
     Gauge(Family<PrometheusLabels, PrometheusGauge, PrometheusGaugeFn>),
+    Histogram(Vec<(PrometheusLabels, f64, u64, Vec<(f64, u64)>)>),
 }
 
 pub struct MetricExport {
@@ -308,6 +591,15 @@ pub fn export_metrics() -> Vec<MetricExport> {
                     metric: ConstMetric::Counter(counter.export()),
                 }
             }
+            MetricType::FloatCounter(shared_counter) => {
+                let counter = shared_counter.lock();
+                MetricExport {
+                    name: name.clone(),
+                    description: counter.get_description().map(|s| s.to_string()),
+                    unit: counter.get_unit().clone(),
+                    metric: ConstMetric::FloatCounter(counter.export()),
+                }
+            }
             MetricType::Gauge(shared_gauge) => {
                 let gauge = shared_gauge.lock();
                 MetricExport {
@@ -317,6 +609,15 @@ pub fn export_metrics() -> Vec<MetricExport> {
                     metric: ConstMetric::Gauge(gauge.export()),
                 }
             }
+            MetricType::Histogram(shared_histogram) => {
+                let histogram = shared_histogram.lock();
+                MetricExport {
+                    name: name.clone(),
+                    description: histogram.get_description().map(|s| s.to_string()),
+                    unit: histogram.get_unit().clone(),
+                    metric: ConstMetric::Histogram(histogram.export()),
+                }
+            }
         })
         .collect()
 }
@@ -382,6 +683,30 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    #[serial_test::serial]
+    fn test_new_float_counter() -> anyhow::Result<()> {
+        let shared_counter = new_float_counter(
+            "test_float_counter_seconds",
+            Some("Test float counter"),
+            &["label1"],
+            None,
+        );
+        let mut counter = shared_counter.lock();
+        assert_eq!(counter.inc(0.25, &["a"])?, 0.0);
+        assert_eq!(counter.inc(0.5, &["a"])?, 0.25);
+        assert_eq!(counter.get(&["a"])?, Some(0.75));
+        assert_eq!(counter.set(2.5, &["a"])?, 0.75);
+        assert!(counter.inc(1.0, &[]).is_err());
+        assert_eq!(counter.export().len(), 1);
+        assert_eq!(counter.delete(&["a"])?, Some(2.5));
+        drop(counter);
+        assert!(get_float_counter_family("test_float_counter_seconds").is_some());
+        assert!(get_counter_family("test_float_counter_seconds").is_none());
+        delete_metric_family("test_float_counter_seconds");
+        Ok(())
+    }
+
     #[test]
     #[serial_test::serial]
     fn test_new_gauge() -> anyhow::Result<()> {
@@ -436,4 +761,35 @@ mod tests {
         delete_metric_family("test_gauge");
         Ok(())
     }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_new_histogram() -> anyhow::Result<()> {
+        let shared_histogram = new_histogram(
+            "test_histogram",
+            Some("Test histogram"),
+            &["label1"],
+            None,
+            &[1.0, 0.1, 1.0],
+        );
+        let mut histogram = shared_histogram.lock();
+        assert_eq!(histogram.get_bounds(), &[0.1, 1.0]);
+        histogram.observe(0.05, &["a"])?;
+        histogram.observe(0.1, &["a"])?;
+        histogram.observe(0.5, &["a"])?;
+        histogram.observe(5.0, &["a"])?;
+        assert!(histogram.observe(1.0, &[]).is_err());
+        let value = histogram.get(&["a"])?.unwrap();
+        assert_eq!(value.count, 4);
+        assert!((value.sum - 5.65).abs() < 1e-9);
+        assert_eq!(value.buckets, vec![2, 1, 1]);
+        let exported = histogram.export();
+        assert_eq!(exported[0].3, vec![(0.1, 2), (1.0, 1), (f64::MAX, 1)]);
+        assert!(histogram.delete(&["a"])?.is_some());
+        drop(histogram);
+        assert!(get_histogram_family("test_histogram").is_some());
+        assert!(get_counter_family("test_histogram").is_none());
+        delete_metric_family("test_histogram");
+        Ok(())
+    }
 }
@@ -1,6 +1,6 @@
 use crate::metrics::{export_metrics, ConstMetric};
 use prometheus_client::collector::Collector;
-use prometheus_client::encoding::{DescriptorEncoder, EncodeMetric};
+use prometheus_client::encoding::{DescriptorEncoder, EncodeMetric, NoLabelSet};
 use prometheus_client::metrics::MetricType;
 
 #[derive(Debug)]
@@ -23,6 +23,19 @@ impl Collector for SystemMetricCollector {
 
                     c.encode(metric_encoder)?;
                 }
+                ConstMetric::FloatCounter(c) => {
+                    let mut metric_encoder = encoder.encode_descriptor(
+                        &name,
+                        &desc_str,
+                        unit.as_ref(),
+                        MetricType::Counter,
+                    )?;
+                    for (labels, value) in c {
+                        metric_encoder
+                            .encode_family(&labels)?
+                            .encode_counter::<NoLabelSet, _, u64>(&value, None)?;
+                    }
+                }
                 ConstMetric::Gauge(g) => {
                     let metric_encoder = encoder.encode_descriptor(
                         &name,
@@ -32,6 +45,19 @@ impl Collector for SystemMetricCollector {
                     )?;
                     g.encode(metric_encoder)?;
                 }
+                ConstMetric::Histogram(h) => {
+                    let mut metric_encoder = encoder.encode_descriptor(
+                        &name,
+                        &desc_str,
+                        unit.as_ref(),
+                        MetricType::Histogram,
+                    )?;
+                    for (labels, sum, count, buckets) in h {
+                        metric_encoder
+                            .encode_family(&labels)?
+                            .encode_histogram::<NoLabelSet>(sum, count, &buckets, None)?;
+                    }
+                }
             }
         }
         Ok(())
@@ -1,4 +1,6 @@
-use crate::metrics::{get_or_create_counter_family, get_or_create_gauge_family};
+use crate::metrics::{
+    get_or_create_counter_family, get_or_create_float_counter_family, get_or_create_gauge_family,
+};
 use crate::rust::FrameProcessingStatRecordType;
 use crate::webserver::get_registered_pipelines;
 use log::debug;
@@ -28,6 +30,7 @@ impl PipelineMetricBuilder {
         let stage_performance_label_names = ["record_type", "stage_name"].as_slice();
         let stage_latency_label_names =
             ["record_type", "destination_stage_name", "source_stage_name"].as_slice();
+        let stage_rate_label_names = ["stage_name", "source_id"].as_slice();
 
         let registered_pipelines = get_registered_pipelines().await;
         debug!(
@@ -69,6 +72,12 @@ impl PipelineMetricBuilder {
                 .iter()
                 .map(|s| s.as_str())
                 .collect();
+            let adjusted_stage_rate_label_names =
+                adjust_labels(stage_rate_label_names, additional_label_names);
+            let asrln_refs: Vec<&str> = adjusted_stage_rate_label_names
+                .iter()
+                .map(|s| s.as_str())
+                .collect();
 
             let frame_counter = get_or_create_counter_family(
                 "frame_counter",
@@ -106,6 +115,48 @@ impl PipelineMetricBuilder {
                 &aspln_refs,
                 None,
             );
+            let stage_budget_overrun = get_or_create_counter_family(
+                "stage_budget_overrun",
+                Some("Number of frames or batches exceeding the stage processing budget"),
+                &aspln_refs,
+                None,
+            );
+            let stage_user_code_calls = get_or_create_counter_family(
+                "stage_user_code_calls",
+                Some("Number of measured calls of user code in the stage"),
+                &aspln_refs,
+                None,
+            );
+            let stage_user_code_time = get_or_create_float_counter_family(
+                "stage_user_code_seconds",
+                Some("Total time spent in user code of the stage, excluding the calls to the library and the GIL waits"),
+                &aspln_refs,
+                None,
+            );
+            let stage_gil_wait_time = get_or_create_float_counter_family(
+                "stage_gil_wait_seconds",
+                Some("Total time user code of the stage waited for the GIL held by other threads"),
+                &aspln_refs,
+                None,
+            );
+            let stage_rust_overhead_time = get_or_create_float_counter_family(
+                "stage_rust_overhead_seconds",
+                Some("Total time spent in the library calls made by user code of the stage"),
+                &aspln_refs,
+                None,
+            );
+            let stage_fps = get_or_create_gauge_family(
+                "stage_fps",
+                Some("Frames per second entering the stage over the sliding window"),
+                &asrln_refs,
+                None,
+            );
+            let stage_jitter = get_or_create_gauge_family(
+                "stage_jitter",
+                Some("Standard deviation of the intervals between the frames entering the stage in milliseconds"),
+                &asrln_refs,
+                None,
+            );
             let stage_min_latency = get_or_create_gauge_family(
                 "stage_min_latency",
                 Some("Minimum latency of the stage"),
@@ -167,6 +218,26 @@ impl PipelineMetricBuilder {
                 stage_batch_counter
                     .lock()
                     .set(sps.batch_counter as u64, &stage_performance_label_refs)?;
+                stage_budget_overrun.lock().set(
+                    sps.budget_overrun_counter as u64,
+                    &stage_performance_label_refs,
+                )?;
+                stage_user_code_calls.lock().set(
+                    sps.user_code_time.calls as u64,
+                    &stage_performance_label_refs,
+                )?;
+                stage_user_code_time.lock().set(
+                    sps.user_code_time.total.as_secs_f64(),
+                    &stage_performance_label_refs,
+                )?;
+                stage_rust_overhead_time.lock().set(
+                    sps.rust_overhead_time.total.as_secs_f64(),
+                    &stage_performance_label_refs,
+                )?;
+                stage_gil_wait_time.lock().set(
+                    sps.gil_wait_time.total.as_secs_f64(),
+                    &stage_performance_label_refs,
+                )?;
                 debug!(
                     "Building metrics for stage latencies: {}",
                     sls.latencies.len()
@@ -212,6 +283,25 @@ impl PipelineMetricBuilder {
                         .set(measurement.count as f64, &stage_latency_label_refs)?;
                 }
             }
+
+            debug!("Building metrics for stage rates");
+            for rate in p.rates() {
+                // the rate of all the sources of the stage is labelled with *
+                let stage_rate_labels = adjust_labels(
+                    &[&rate.stage_name, rate.source_id.as_deref().unwrap_or("*")],
+                    &additional_label_value_refs,
+                );
+                let stage_rate_label_refs = stage_rate_labels
+                    .iter()
+                    .map(|s| s.as_str())
+                    .collect::<Vec<&str>>();
+                stage_fps
+                    .lock()
+                    .set(rate.measurement.fps, &stage_rate_label_refs)?;
+                stage_jitter
+                    .lock()
+                    .set(rate.measurement.jitter_ms, &stage_rate_label_refs)?;
+            }
         }
         Ok(())
     }
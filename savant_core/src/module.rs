@@ -0,0 +1,382 @@
+use crate::message::Message;
+use crate::metrics::get_or_create_counter_family;
+use crate::pipeline::{Pipeline, PipelineConfiguration, PipelineStagePayloadType};
+use crate::primitives::frame::VideoFrameProxy;
+use crate::transport::zeromq::{
+    ReaderConfig, ReaderResult, SyncReader, SyncWriter, WriterConfig, WriterResult,
+};
+use crate::webserver::{
+    init_webserver_with_config, is_shutdown_set, set_shutdown_token, set_status, PipelineStatus,
+    WebserverConfig,
+};
+use anyhow::{bail, Context};
+use log::{debug, error, info, warn};
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+const MODULE_MESSAGES_METRIC: &str = "module_messages";
+
+/// The stage the frames are processed in.
+pub const PROCESSING_STAGE: &str = "process";
+
+fn default_receive_timeout_ms() -> i32 {
+    1000
+}
+
+/// The configuration of a module, loaded from a YAML or a JSON file:
+///
+/// ```yaml
+/// name: detector
+/// input: router+bind:ipc:///tmp/detector-in
+/// output: dealer+connect:ipc:///tmp/detector-out
+/// webserver_port: 8080
+/// shutdown_token: secret
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ModuleConfig {
+    pub name: String,
+    /// The ZeroMQ socket the messages are received from, e.g. `router+bind:ipc:///tmp/in`.
+    pub input: String,
+    /// The ZeroMQ socket the messages are sent to.
+    pub output: String,
+    /// The port of the webserver serving the metrics, the health and the shutdown endpoints,
+    /// the webserver is not started when it is not set.
+    #[serde(default)]
+    pub webserver_port: Option<u16>,
+    /// The token of the shutdown endpoint of the webserver.
+    #[serde(default)]
+    pub shutdown_token: Option<String>,
+    /// How long the reader waits for a message before checking for the shutdown.
+    #[serde(default = "default_receive_timeout_ms")]
+    pub receive_timeout_ms: i32,
+}
+
+impl ModuleConfig {
+    pub fn from_yaml(yaml: &str) -> anyhow::Result<Self> {
+        Ok(serde_yaml::from_str(yaml)?)
+    }
+
+    /// Loads the configuration, the `.json` files are parsed as JSON, the other files as
+    /// YAML.
+    ///
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        if path.extension().is_some_and(|e| e == "json") {
+            Ok(serde_json::from_str(&text)?)
+        } else {
+            Self::from_yaml(&text)
+        }
+    }
+}
+
+/// The code of a module: processes the frames in place, the runner takes care of the
+/// transport, the pipeline, the metrics and the shutdown.
+///
+pub trait FrameProcessor: Send {
+    /// Processes the frame, the frame is sent downstream after the call. An error drops the
+    /// frame.
+    ///
+    fn process(&mut self, frame: &VideoFrameProxy) -> anyhow::Result<()>;
+
+    /// Called when the source ends, before the end of stream is sent downstream.
+    ///
+    fn on_eos(&mut self, _source_id: &str) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// Stops the runner from another thread, e.g. a signal handler.
+///
+#[derive(Debug, Clone, Default)]
+pub struct ModuleStopHandle(Arc<AtomicBool>);
+
+impl ModuleStopHandle {
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// The counts of the messages handled by the runner.
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModuleReport {
+    pub processed: u64,
+    pub forwarded: u64,
+    pub failed: u64,
+}
+
+/// Runs a module: receives the messages from the input socket, processes the frames with
+/// the processor in the pipeline stage [`PROCESSING_STAGE`] and sends the frames and the
+/// other messages to the output socket. The webserver serving the metrics, the health and
+/// the shutdown endpoints is started when the port is configured.
+///
+/// The runner stops when the stop handle is triggered, the shutdown endpoint is called or a
+/// shutdown message is received; the shutdown message is forwarded before stopping. The
+/// messages are counted in the `module_messages` counter labelled with the module name and
+/// the outcome (`processed`, `forwarded`, `failed`).
+///
+pub struct ModuleRunner<P: FrameProcessor> {
+    config: ModuleConfig,
+    processor: P,
+    pipeline: Pipeline,
+    stop: ModuleStopHandle,
+    report: ModuleReport,
+}
+
+impl<P: FrameProcessor> ModuleRunner<P> {
+    pub fn new(config: ModuleConfig, processor: P) -> anyhow::Result<Self> {
+        let pipeline = Pipeline::new(
+            vec![(
+                PROCESSING_STAGE.to_string(),
+                PipelineStagePayloadType::Frame,
+                None,
+                None,
+            )],
+            PipelineConfiguration::default(),
+        )?;
+        pipeline.set_name(config.name.clone())?;
+        Ok(Self {
+            config,
+            processor,
+            pipeline,
+            stop: ModuleStopHandle::default(),
+            report: ModuleReport::default(),
+        })
+    }
+
+    pub fn get_pipeline(&self) -> &Pipeline {
+        &self.pipeline
+    }
+
+    pub fn stop_handle(&self) -> ModuleStopHandle {
+        self.stop.clone()
+    }
+
+    fn count(&mut self, outcome: &str) {
+        match outcome {
+            "processed" => self.report.processed += 1,
+            "forwarded" => self.report.forwarded += 1,
+            _ => self.report.failed += 1,
+        }
+        let res = get_or_create_counter_family(
+            MODULE_MESSAGES_METRIC,
+            Some("The number of messages handled by the module"),
+            &["module", "outcome"],
+            None,
+        )
+        .lock()
+        .inc(1, &[&self.config.name, outcome]);
+        if let Err(e) = res {
+            warn!(
+                target: "savant_rs::module",
+                "Failed to count the module message: {}", e
+            );
+        }
+    }
+
+    fn process_frame(&mut self, frame: VideoFrameProxy) -> anyhow::Result<()> {
+        let id = self.pipeline.add_frame(PROCESSING_STAGE, frame.clone())?;
+        let res = self.processor.process(&frame);
+        self.pipeline.delete(id)?;
+        res
+    }
+
+    /// Handles the message, returns the message to send downstream, `None` when it is
+    /// dropped.
+    ///
+    pub fn handle(&mut self, message: Message) -> Option<Message> {
+        let res = if let Some(frame) = message.as_video_frame() {
+            self.process_frame(frame).map(|_| "processed")
+        } else if let Some(eos) = message.as_end_of_stream() {
+            self.processor.on_eos(&eos.source_id).map(|_| "forwarded")
+        } else {
+            Ok("forwarded")
+        };
+        match res {
+            Ok(outcome) => {
+                self.count(outcome);
+                Some(message)
+            }
+            Err(e) => {
+                error!(
+                    target: "savant_rs::module",
+                    "Module {} failed to handle the message: {}", self.config.name, e
+                );
+                self.count("failed");
+                None
+            }
+        }
+    }
+
+    fn send(writer: &SyncWriter, topic: &[u8], message: &Message, data: &[Vec<u8>]) {
+        let topic = String::from_utf8_lossy(topic);
+        let data = data.iter().map(|d| d.as_slice()).collect::<Vec<_>>();
+        match writer.send_message(&topic, message, &data) {
+            Ok(WriterResult::Success { .. } | WriterResult::Ack { .. }) => {}
+            Ok(res) => warn!(
+                target: "savant_rs::module",
+                "The message for the topic {} is not delivered: {:?}", topic, res
+            ),
+            Err(e) => error!(
+                target: "savant_rs::module",
+                "Failed to send the message for the topic {}: {}", topic, e
+            ),
+        }
+    }
+
+    /// Runs the module until it is stopped, see [`ModuleRunner`].
+    ///
+    pub fn run(mut self) -> anyhow::Result<ModuleReport> {
+        if self.config.receive_timeout_ms <= 0 {
+            bail!("The receive timeout must be positive");
+        }
+        if let Some(port) = self.config.webserver_port {
+            init_webserver_with_config(WebserverConfig::new(port))?;
+            if let Some(token) = &self.config.shutdown_token {
+                set_shutdown_token(token.clone());
+            }
+        }
+        let reader = SyncReader::new(
+            &ReaderConfig::new()
+                .url(&self.config.input)?
+                .with_receive_timeout(self.config.receive_timeout_ms)?
+                .build()?,
+        )?;
+        let writer = SyncWriter::new(&WriterConfig::new().url(&self.config.output)?.build()?)?;
+        if self.config.webserver_port.is_some() {
+            set_status(PipelineStatus::Running)?;
+        }
+        info!(
+            target: "savant_rs::module",
+            "Module {} started: {} -> {}", self.config.name, self.config.input, self.config.output
+        );
+        let mut failure = None;
+        while !self.stop.is_stopped() && !is_shutdown_set() {
+            let res = match reader.receive() {
+                Ok(res) => res,
+                Err(e) => {
+                    error!(
+                        target: "savant_rs::module",
+                        "Module {} failed to receive a message: {}", self.config.name, e
+                    );
+                    failure = Some(e);
+                    break;
+                }
+            };
+            match res {
+                ReaderResult::Message {
+                    message,
+                    topic,
+                    data,
+                    ..
+                } => {
+                    let shutdown = message.is_shutdown();
+                    if let Some(message) = self.handle(*message) {
+                        Self::send(&writer, &topic, &message, &data);
+                    }
+                    if shutdown {
+                        info!(
+                            target: "savant_rs::module",
+                            "Module {} received the shutdown message", self.config.name
+                        );
+                        break;
+                    }
+                }
+                ReaderResult::Timeout => {}
+                res => debug!(target: "savant_rs::module", "Message skipped: {:?}", res),
+            }
+        }
+        info!(
+            target: "savant_rs::module",
+            "Module {} is stopping: {:?}", self.config.name, self.report
+        );
+        // the module is stopped and the sockets are closed even when receiving failed
+        let status = if self.config.webserver_port.is_some() {
+            set_status(PipelineStatus::Stopped)
+        } else {
+            Ok(())
+        };
+        let reader_shutdown = reader.shutdown();
+        let writer_shutdown = writer.shutdown();
+        self.pipeline.log_final_fps();
+        if let Some(e) = failure {
+            return Err(e);
+        }
+        status?;
+        reader_shutdown?;
+        writer_shutdown?;
+        Ok(self.report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::Message;
+    use crate::module::{FrameProcessor, ModuleConfig, ModuleReport, ModuleRunner};
+    use crate::primitives::eos::EndOfStream;
+    use crate::primitives::frame::VideoFrameProxy;
+    use crate::primitives::WithAttributes;
+    use crate::test::gen_frame;
+
+    struct Marker;
+
+    impl FrameProcessor for Marker {
+        fn process(&mut self, frame: &VideoFrameProxy) -> anyhow::Result<()> {
+            if frame.get_source_id() == "broken" {
+                anyhow::bail!("Broken source");
+            }
+            let mut frame = frame.clone();
+            frame.set_persistent_attribute("module", "processed", &None, false, vec![]);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_config() -> anyhow::Result<()> {
+        let config = ModuleConfig::from_yaml(
+            "name: detector\ninput: router+bind:ipc:///tmp/in\noutput: dealer+connect:ipc:///tmp/out\n",
+        )?;
+        assert_eq!(config.name, "detector");
+        assert_eq!(config.webserver_port, None);
+        assert_eq!(config.receive_timeout_ms, 1000);
+        assert!(ModuleConfig::from_yaml("name: detector").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_handle() -> anyhow::Result<()> {
+        let config = ModuleConfig::from_yaml(
+            "name: marker\ninput: router+bind:ipc:///tmp/in\noutput: dealer+connect:ipc:///tmp/out\n",
+        )?;
+        let mut runner = ModuleRunner::new(config, Marker)?;
+
+        let out = runner.handle(Message::video_frame(&gen_frame())).unwrap();
+        let frame = out.as_video_frame().unwrap();
+        assert!(frame.get_attribute("module", "processed").is_some());
+        assert_eq!(runner.get_pipeline().get_id_locations_len(), 0);
+
+        let mut broken = gen_frame();
+        broken.set_source_id("broken");
+        assert!(runner.handle(Message::video_frame(&broken)).is_none());
+        let eos = Message::end_of_stream(EndOfStream::new("test".to_string()));
+        assert!(runner.handle(eos).is_some());
+        assert_eq!(
+            runner.report,
+            ModuleReport {
+                processed: 1,
+                forwarded: 1,
+                failed: 1,
+            }
+        );
+        Ok(())
+    }
+}
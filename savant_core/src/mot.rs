@@ -0,0 +1,254 @@
+use crate::match_query::MatchQuery;
+use crate::primitives::frame::VideoFrameProxy;
+use crate::primitives::object::{ObjectOperations, VideoObject, VideoObjectBuilder};
+use crate::primitives::RBBox;
+use anyhow::{bail, Context, Result};
+use hashbrown::HashMap;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// A line of a MOTChallenge file describing one object of one frame:
+/// `frame,id,bb_left,bb_top,bb_width,bb_height,conf,x,y,z`. Frames are numbered from `1`,
+/// 2D results have `x`, `y`, `z` set to `-1`.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct MotRecord {
+    pub frame: i64,
+    pub id: i64,
+    pub left: f32,
+    pub top: f32,
+    pub width: f32,
+    pub height: f32,
+    pub confidence: f32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl MotRecord {
+    pub fn to_line(&self) -> String {
+        format!(
+            "{},{},{:.2},{:.2},{:.2},{:.2},{},{},{},{}",
+            self.frame,
+            self.id,
+            self.left,
+            self.top,
+            self.width,
+            self.height,
+            self.confidence,
+            self.x,
+            self.y,
+            self.z
+        )
+    }
+
+    /// Parses a line with 6 to 10 comma-separated fields, the missing fields are `-1`
+    /// (the confidence defaults to `1`).
+    ///
+    pub fn parse(line: &str) -> Result<Self> {
+        let fields = line.split(',').map(|f| f.trim()).collect::<Vec<_>>();
+        if fields.len() < 6 || fields.len() > 10 {
+            bail!("MOT line must have from 6 to 10 fields: {}", line)
+        }
+        let float = |i: usize, default: f32| -> Result<f32> {
+            match fields.get(i) {
+                Some(f) => f
+                    .parse::<f32>()
+                    .with_context(|| format!("Invalid MOT field {} in line: {}", i + 1, line)),
+                None => Ok(default),
+            }
+        };
+        let int = |i: usize| -> Result<i64> { Ok(float(i, 0.0)?.round() as i64) };
+        Ok(Self {
+            frame: int(0)?,
+            id: int(1)?,
+            left: float(2, 0.0)?,
+            top: float(3, 0.0)?,
+            width: float(4, 0.0)?,
+            height: float(5, 0.0)?,
+            confidence: float(6, 1.0)?,
+            x: float(7, -1.0)?,
+            y: float(8, -1.0)?,
+            z: float(9, -1.0)?,
+        })
+    }
+
+    pub fn get_bbox(&self) -> RBBox {
+        RBBox::ltwh(self.left, self.top, self.width, self.height)
+    }
+
+    /// Creates an object for evaluation: the box becomes both the detection and the track
+    /// box, the MOT id becomes the track id.
+    ///
+    pub fn to_object(&self, object_id: i64, namespace: &str, label: &str) -> VideoObject {
+        VideoObjectBuilder::default()
+            .id(object_id)
+            .namespace(namespace.to_string())
+            .label(label.to_string())
+            .detection_box(self.get_bbox())
+            .track_box(Some(self.get_bbox()))
+            .track_id(Some(self.id))
+            .confidence(Some(self.confidence))
+            .build()
+            .unwrap()
+    }
+}
+
+/// Parses a MOT file skipping empty lines and `#` comments.
+///
+pub fn parse_mot(text: &str) -> Result<Vec<MotRecord>> {
+    text.lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(MotRecord::parse)
+        .collect()
+}
+
+/// Groups the records by frame number and converts them to objects.
+///
+pub fn mot_to_objects(
+    records: &[MotRecord],
+    namespace: &str,
+    label: &str,
+) -> BTreeMap<i64, Vec<VideoObject>> {
+    let mut frames = BTreeMap::<i64, Vec<VideoObject>>::new();
+    for record in records {
+        let objects = frames.entry(record.frame).or_default();
+        let object = record.to_object(objects.len() as i64, namespace, label);
+        objects.push(object);
+    }
+    frames
+}
+
+/// Collects the track histories of the sources frame by frame. The frames of every source
+/// are numbered in the order they are added; only tracked objects matching the query are
+/// exported, with the track box when it is set and the detection box otherwise. Rotated
+/// boxes are replaced with their axis-aligned wrapping boxes.
+///
+#[derive(Debug)]
+pub struct MotExporter {
+    query: MatchQuery,
+    frame_counters: HashMap<String, i64>,
+    records: HashMap<String, Vec<MotRecord>>,
+}
+
+impl MotExporter {
+    pub fn new(query: MatchQuery) -> Self {
+        Self {
+            query,
+            frame_counters: HashMap::new(),
+            records: HashMap::new(),
+        }
+    }
+
+    /// Adds the tracked objects of the frame and returns the MOT frame number.
+    ///
+    pub fn add_frame(&mut self, frame: &VideoFrameProxy) -> i64 {
+        let source_id = frame.get_source_id();
+        let counter = self.frame_counters.entry(source_id.clone()).or_insert(0);
+        *counter += 1;
+        let frame_num = *counter;
+        let records = self.records.entry(source_id).or_default();
+        let mut objects = frame.access_objects(&self.query);
+        objects.sort_by_key(|o| o.get_id());
+        for object in objects {
+            let Some(track_id) = object.get_track_id() else {
+                continue;
+            };
+            let bbox = object
+                .get_track_box()
+                .unwrap_or_else(|| object.get_detection_box())
+                .get_wrapping_bbox();
+            let (left, top, width, height) =
+                bbox.as_ltwh().expect("Wrapping box is always axis-aligned");
+            records.push(MotRecord {
+                frame: frame_num,
+                id: track_id,
+                left,
+                top,
+                width,
+                height,
+                confidence: object.get_confidence().unwrap_or(1.0),
+                x: -1.0,
+                y: -1.0,
+                z: -1.0,
+            });
+        }
+        frame_num
+    }
+
+    pub fn get_sources(&self) -> Vec<String> {
+        let mut sources = self.records.keys().cloned().collect::<Vec<_>>();
+        sources.sort();
+        sources
+    }
+
+    pub fn get_records(&self, source_id: &str) -> &[MotRecord] {
+        self.records
+            .get(source_id)
+            .map(|r| r.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Renders the track history of the source in the MOT format.
+    ///
+    pub fn export(&self, source_id: &str) -> String {
+        let mut out = String::new();
+        for record in self.get_records(source_id) {
+            let _ = writeln!(out, "{}", record.to_line());
+        }
+        out
+    }
+
+    pub fn clear(&mut self, source_id: &str) {
+        self.frame_counters.remove(source_id);
+        self.records.remove(source_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::match_query::MatchQuery;
+    use crate::mot::{mot_to_objects, parse_mot, MotExporter, MotRecord};
+    use crate::primitives::object::{IdCollisionResolutionPolicy, ObjectOperations};
+    use crate::primitives::RBBox;
+    use crate::test::{gen_empty_frame, gen_object};
+
+    #[test]
+    fn test_export_and_parse() -> anyhow::Result<()> {
+        let mut exporter = MotExporter::new(MatchQuery::Idle);
+        for i in 0..2 {
+            let frame = gen_empty_frame();
+            let mut tracked = gen_object(1);
+            tracked.set_track_info(7, RBBox::ltwh(10.0 + i as f32, 20.0, 30.0, 40.0));
+            tracked.set_confidence(Some(0.5));
+            frame.add_object(tracked, IdCollisionResolutionPolicy::Error)?;
+            let mut untracked = gen_object(2);
+            untracked.clear_track_info();
+            frame.add_object(untracked, IdCollisionResolutionPolicy::Error)?;
+            assert_eq!(exporter.add_frame(&frame), i + 1);
+        }
+        let source = exporter.get_sources().pop().unwrap();
+        let text = exporter.export(&source);
+        assert_eq!(
+            text,
+            "1,7,10.00,20.00,30.00,40.00,0.5,-1,-1,-1\n2,7,11.00,20.00,30.00,40.00,0.5,-1,-1,-1\n"
+        );
+        let records = parse_mot(&format!("# comment\n{}", text))?;
+        assert_eq!(records, exporter.get_records(&source));
+        let frames = mot_to_objects(&records, "gt", "person");
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[&2][0].get_track_id(), Some(7));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_short_line() -> anyhow::Result<()> {
+        let record = MotRecord::parse("3, 1, 1, 2, 3, 4")?;
+        assert_eq!(record.confidence, 1.0);
+        assert_eq!(record.z, -1.0);
+        assert!(MotRecord::parse("1,2,3").is_err());
+        assert!(MotRecord::parse("1,2,a,4,5,6").is_err());
+        Ok(())
+    }
+}
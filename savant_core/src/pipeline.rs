@@ -1,28 +1,85 @@
 use std::sync::Arc;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
 use anyhow::Result;
+use crossbeam::channel::Receiver;
 use hashbrown::HashMap;
 use opentelemetry::Context;
 
 pub use implementation::PipelineConfiguration;
 pub use implementation::PipelineConfigurationBuilder;
+pub use implementation::MAX_SCANNED_FRAMES;
 
 use crate::match_query::MatchQuery;
+use crate::pipeline::admission::AdmissionFilter;
+use crate::pipeline::auto_batch::AutoBatchConfig;
+use crate::pipeline::circuit_breaker::{CircuitBreaker, Isolation};
+use crate::pipeline::debug_tap::DebugTap;
+use crate::pipeline::decimator::Decimator;
+use crate::pipeline::events::PipelineEvent;
+#[cfg(feature = "chaos")]
+use crate::pipeline::fault_injector::FaultInjector;
+use crate::pipeline::history::FrameHistory;
+use crate::pipeline::hooks::StageHook;
+use crate::pipeline::object_lifecycle::ObjectObserver;
+use crate::pipeline::rates::StageRate;
+use crate::pipeline::readiness::{PipelineHealth, StageReadiness, WarmupPolicy};
+use crate::pipeline::reaper::EvictedPayload;
+use crate::pipeline::result_cache::ResultCache;
+use crate::pipeline::source_config::SourceConfigResolver;
+use crate::pipeline::source_stats::SourceStatistics;
 use crate::pipeline::stage::PipelineStage;
+use crate::pipeline::stage_processor::StageProcessorVersion;
+use crate::pipeline::topology::{render_topology, PipelineTopology, TopologyFormat};
+use crate::pipeline::updaters::UpdaterScope;
+use crate::pipeline::user_code::UserCodeTime;
+use crate::pipeline::validation::FrameValidator;
+use crate::primitives::attribute_propagation::AttributePropagation;
 use crate::primitives::attribute_value::AttributeValue;
+use crate::primitives::audio_frame::AudioFrame;
+use crate::primitives::content_encoding::ContentEncoding;
 use crate::primitives::frame::VideoFrameProxy;
 use crate::primitives::frame_batch::VideoFrameBatch;
 use crate::primitives::frame_update::VideoFrameUpdate;
 use crate::primitives::object::BorrowedVideoObject;
+use crate::primitives::telemetry_frame::TelemetryFrame;
 use crate::webserver::{register_pipeline, unregister_pipeline};
 
 const MAX_TRACKED_STREAMS: usize = 8192; // defines how many streams are tracked for the frame ordering
 
+pub mod admission;
+pub mod auto_batch;
+pub mod best_shot;
+pub mod circuit_breaker;
+pub mod compat;
+pub mod contracts;
+pub mod debug_tap;
+pub mod decimator;
+pub mod events;
+#[cfg(feature = "chaos")]
+pub mod fault_injector;
+pub mod fixtures;
+pub mod history;
+pub mod hooks;
+pub mod motion;
+pub mod object_lifecycle;
+pub mod quality;
+pub mod rates;
+pub mod readiness;
+pub mod reaper;
+pub mod result_cache;
+pub mod source_config;
+pub mod source_stats;
 pub mod stage;
 pub mod stage_function_loader;
 pub mod stage_plugin_sample;
+pub mod stage_processor;
 pub mod stats;
+pub mod synchronizer;
+pub mod topology;
+pub mod updaters;
+pub mod user_code;
+pub mod validation;
 
 pub trait PipelineStageFunction: Send {
     fn set_pipeline(&mut self, pipeline: Pipeline);
@@ -34,9 +91,15 @@ pub trait PipelineStageFunction: Send {
         order: PipelineStageFunctionOrder,
         payload: &mut PipelinePayload,
     ) -> Result<()>;
+    /// Reports whether the function is ready for payloads, the pipeline holds or drops the
+    /// payloads sent to its stage until it is.
+    ///
+    fn get_readiness(&self) -> StageReadiness {
+        StageReadiness::Ready
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PipelineStageFunctionOrder {
     Ingress,
     Egress,
@@ -55,6 +118,8 @@ pub type PipelineStageFunctionFactory =
 pub enum PipelineStagePayloadType {
     Frame,
     Batch,
+    Audio,
+    Telemetry,
 }
 
 #[derive(Debug)]
@@ -73,6 +138,8 @@ pub enum PipelinePayload {
         Option<String>,
         Vec<SystemTime>,
     ),
+    Audio(AudioFrame, Context, Option<String>, SystemTime),
+    Telemetry(TelemetryFrame, Context, Option<String>, SystemTime),
 }
 
 #[derive(Clone, Default, Debug)]
@@ -119,6 +186,13 @@ impl Pipeline {
         self.0.log_final_fps()
     }
 
+    /// The sliding-window FPS and jitter of the frames entering the stages, for all the
+    /// frames of a stage and per source, in the order of the stages.
+    ///
+    pub fn rates(&self) -> Vec<StageRate> {
+        self.0.rates()
+    }
+
     pub fn memory_handle(&self) -> usize {
         self as *const Self as usize
     }
@@ -151,6 +225,277 @@ impl Pipeline {
         self.0.add_frame_update(frame_id, update)
     }
 
+    pub fn register_updater(&self, name: &str, namespaces: &[&str]) -> Result<()> {
+        self.0.register_updater(name, namespaces)
+    }
+
+    pub fn set_debug_tap(&self, stage_name: &str, tap: Option<DebugTap>) -> Result<()> {
+        self.0.set_debug_tap(stage_name, tap)
+    }
+
+    pub fn get_debug_tap(&self, stage_name: &str) -> Result<Option<Arc<DebugTap>>> {
+        self.0.get_debug_tap(stage_name)
+    }
+
+    pub fn set_object_observer(
+        &self,
+        stage_name: &str,
+        observer: Option<ObjectObserver>,
+    ) -> Result<()> {
+        self.0.set_object_observer(stage_name, observer)
+    }
+
+    pub fn get_object_observer(&self, stage_name: &str) -> Result<Option<Arc<ObjectObserver>>> {
+        self.0.get_object_observer(stage_name)
+    }
+
+    /// Registers the hook called on the payloads entering and leaving the stage under the
+    /// name unique in the stage.
+    ///
+    pub fn add_stage_hook(
+        &self,
+        stage_name: &str,
+        name: &str,
+        hook: Arc<dyn StageHook>,
+    ) -> Result<()> {
+        self.0.add_stage_hook(stage_name, name, hook)
+    }
+
+    pub fn remove_stage_hook(&self, stage_name: &str, name: &str) -> Result<bool> {
+        self.0.remove_stage_hook(stage_name, name)
+    }
+
+    pub fn get_stage_hooks(&self, stage_name: &str) -> Result<Vec<String>> {
+        self.0.get_stage_hooks(stage_name)
+    }
+
+    pub fn unregister_updater(&self, name: &str) -> Option<UpdaterScope> {
+        self.0.unregister_updater(name)
+    }
+
+    pub fn set_source_config_resolver(&self, resolver: Option<SourceConfigResolver>) {
+        self.0.set_source_config_resolver(resolver)
+    }
+
+    pub fn get_source_config_resolver(&self) -> Option<Arc<SourceConfigResolver>> {
+        self.0.get_source_config_resolver()
+    }
+
+    pub fn set_attribute_propagation(&self, propagation: Option<AttributePropagation>) {
+        self.0.set_attribute_propagation(propagation)
+    }
+
+    pub fn set_admission_filter(&self, filter: Option<AdmissionFilter>) {
+        self.0.set_admission_filter(filter)
+    }
+
+    pub fn get_admission_filter(&self) -> Option<Arc<AdmissionFilter>> {
+        self.0.get_admission_filter()
+    }
+
+    pub fn get_attribute_propagation(&self) -> Option<Arc<AttributePropagation>> {
+        self.0.get_attribute_propagation()
+    }
+
+    pub fn set_frame_history(&self, history: Option<FrameHistory>) {
+        self.0.set_frame_history(history)
+    }
+
+    pub fn get_frame_history(&self) -> Option<Arc<FrameHistory>> {
+        self.0.get_frame_history()
+    }
+
+    /// Installs the validator checking the invariants of the frames leaving the stages,
+    /// `None` disables the validation.
+    ///
+    pub fn set_frame_validator(&self, validator: Option<FrameValidator>) {
+        self.0.set_frame_validator(validator)
+    }
+
+    pub fn get_frame_validator(&self) -> Option<Arc<FrameValidator>> {
+        self.0.get_frame_validator()
+    }
+
+    /// Rebuilds the frame as it left the stage, see [`history::reconstruct_frame_at`].
+    ///
+    pub fn reconstruct_frame_at(
+        &self,
+        uuid: uuid::Uuid,
+        stage_name: &str,
+    ) -> Result<Option<VideoFrameProxy>> {
+        self.0.reconstruct_frame_at(uuid, stage_name)
+    }
+
+    /// Records the time user code spent processing the payloads of the stage, see
+    /// [`user_code::UserCodeSection`].
+    ///
+    pub fn record_user_code_time(
+        &self,
+        stage_name: &str,
+        ids: &[i64],
+        time: UserCodeTime,
+    ) -> Result<()> {
+        self.0.record_user_code_time(stage_name, ids, time)
+    }
+
+    pub fn set_decimator(&self, stage_name: &str, decimator: Option<Decimator>) -> Result<()> {
+        self.0.set_decimator(stage_name, decimator)
+    }
+
+    pub fn get_decimator(&self, stage_name: &str) -> Result<Option<Arc<Decimator>>> {
+        self.0.get_decimator(stage_name)
+    }
+
+    /// Installs the result cache of an idempotent frame stage, `None` removes the installed
+    /// cache.
+    ///
+    pub fn set_result_cache(&self, stage_name: &str, cache: Option<ResultCache>) -> Result<()> {
+        self.0.set_result_cache(stage_name, cache)
+    }
+
+    pub fn get_result_cache(&self, stage_name: &str) -> Result<Option<Arc<ResultCache>>> {
+        self.0.get_result_cache(stage_name)
+    }
+
+    /// Replays the cached results into the frames of the stage. Returns the ids of the frames
+    /// replayed, which the stage does not need to process, and the ids of the other frames.
+    ///
+    pub fn replay_cached(
+        &self,
+        stage_name: &str,
+        frame_ids: &[i64],
+    ) -> Result<(Vec<i64>, Vec<i64>)> {
+        self.0.replay_cached(stage_name, frame_ids)
+    }
+
+    /// Compresses the internal content of the frames entering the stage with the codec,
+    /// `None` stops the compression. The frames decompress the content on access.
+    ///
+    pub fn set_content_encoding(
+        &self,
+        stage_name: &str,
+        encoding: Option<ContentEncoding>,
+    ) -> Result<()> {
+        self.0.set_content_encoding(stage_name, encoding)
+    }
+
+    pub fn get_content_encoding(&self, stage_name: &str) -> Result<Option<ContentEncoding>> {
+        self.0.get_content_encoding(stage_name)
+    }
+
+    /// Replaces the ingress or egress function of the stage while the pipeline runs: waits
+    /// for the in-flight calls of the current function and swaps the functions atomically,
+    /// `None` removes the function. Returns the version of the installed function.
+    ///
+    pub fn replace_stage_processor(
+        &self,
+        stage_name: &str,
+        order: PipelineStageFunctionOrder,
+        processor: Option<Box<dyn PipelineStageFunction>>,
+    ) -> Result<StageProcessorVersion> {
+        self.0.replace_stage_processor(stage_name, order, processor)
+    }
+
+    pub fn get_stage_processor_version(
+        &self,
+        stage_name: &str,
+        order: PipelineStageFunctionOrder,
+    ) -> Result<StageProcessorVersion> {
+        self.0.get_stage_processor_version(stage_name, order)
+    }
+
+    /// Reports the readiness of the stage on behalf of its processor. Payloads are not sent
+    /// to a stage while it or any stage after it is warming up.
+    ///
+    pub fn set_stage_readiness(&self, stage_name: &str, readiness: StageReadiness) -> Result<()> {
+        self.0.set_stage_readiness(stage_name, readiness)
+    }
+
+    /// The readiness of the stage combined with the readiness of its functions.
+    ///
+    pub fn get_stage_readiness(&self, stage_name: &str) -> Result<StageReadiness> {
+        self.0.get_stage_readiness(stage_name)
+    }
+
+    /// Sets what happens to the payloads sent to the stage while the pipeline is not ready
+    /// for them: they are held in the previous stage or dropped.
+    ///
+    pub fn set_warmup_policy(&self, stage_name: &str, policy: WarmupPolicy) -> Result<()> {
+        self.0.set_warmup_policy(stage_name, policy)
+    }
+
+    pub fn get_warmup_policy(&self, stage_name: &str) -> Result<WarmupPolicy> {
+        self.0.get_warmup_policy(stage_name)
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.0.is_ready()
+    }
+
+    pub fn get_health(&self) -> PipelineHealth {
+        self.0.get_health()
+    }
+
+    /// Passes the frames of the stage through its decimator, the skipped frames are deleted.
+    /// Returns the ids of the kept frames and the root contexts of the deleted ones.
+    ///
+    pub fn decimate(
+        &self,
+        stage_name: &str,
+        frame_ids: &[i64],
+    ) -> Result<(Vec<i64>, HashMap<i64, Context>)> {
+        self.0.decimate(stage_name, frame_ids)
+    }
+
+    pub fn set_circuit_breaker(
+        &self,
+        stage_name: &str,
+        breaker: Option<CircuitBreaker>,
+    ) -> Result<()> {
+        self.0.set_circuit_breaker(stage_name, breaker)
+    }
+
+    pub fn get_circuit_breaker(&self, stage_name: &str) -> Result<Option<Arc<CircuitBreaker>>> {
+        self.0.get_circuit_breaker(stage_name)
+    }
+
+    /// Holds back the frames of the sources quarantined by the circuit breaker of the stage,
+    /// they are deleted or moved to the dead-letter stage.
+    ///
+    pub fn isolate(&self, stage_name: &str, frame_ids: &[i64]) -> Result<Isolation> {
+        self.0.isolate(stage_name, frame_ids)
+    }
+
+    #[cfg(feature = "chaos")]
+    pub fn set_fault_injector(
+        &self,
+        stage_name: &str,
+        injector: Option<FaultInjector>,
+    ) -> Result<()> {
+        self.0.set_fault_injector(stage_name, injector)
+    }
+
+    #[cfg(feature = "chaos")]
+    pub fn get_fault_injector(&self, stage_name: &str) -> Result<Option<Arc<FaultInjector>>> {
+        self.0.get_fault_injector(stage_name)
+    }
+
+    /// Passes the payloads of the stage through its fault injector, the dropped payloads are
+    /// deleted. Returns the ids of the kept payloads and the root contexts of the deleted ones.
+    ///
+    #[cfg(feature = "chaos")]
+    pub fn inject_faults(
+        &self,
+        stage_name: &str,
+        ids: &[i64],
+    ) -> Result<(Vec<i64>, HashMap<i64, Context>)> {
+        self.0.inject_faults(stage_name, ids)
+    }
+
+    pub fn get_updater(&self, name: &str) -> Option<UpdaterScope> {
+        self.0.get_updater(name)
+    }
+
     pub fn add_batched_frame_update(
         &self,
         batch_id: i64,
@@ -164,6 +509,20 @@ impl Pipeline {
         self.0.add_frame(stage_name, frame)
     }
 
+    /// Adds the frame with the priority, the frames with the higher priority are listed
+    /// first in [`Pipeline::get_stage_queue`]. The priority is kept when the frame moves
+    /// between the stages, a batch gets the highest priority of its frames and the frames
+    /// unpacked from a batch get the priority of the batch.
+    ///
+    pub fn add_frame_with_priority(
+        &self,
+        stage_name: &str,
+        frame: VideoFrameProxy,
+        priority: i32,
+    ) -> Result<i64> {
+        self.0.add_frame_with_priority(stage_name, frame, priority)
+    }
+
     pub fn add_frame_with_telemetry(
         &self,
         stage_name: &str,
@@ -174,14 +533,101 @@ impl Pipeline {
             .add_frame_with_telemetry(stage_name, frame, parent_ctx)
     }
 
+    pub fn add_audio_frame(&self, stage_name: &str, frame: AudioFrame) -> Result<i64> {
+        self.0.add_audio_frame(stage_name, frame)
+    }
+
+    pub fn get_audio_frame(&self, frame_id: i64) -> Result<(AudioFrame, Context)> {
+        self.0.get_audio_frame(frame_id)
+    }
+
+    pub fn add_telemetry_frame(&self, stage_name: &str, frame: TelemetryFrame) -> Result<i64> {
+        self.0.add_telemetry_frame(stage_name, frame)
+    }
+
+    pub fn get_telemetry_frame(&self, frame_id: i64) -> Result<(TelemetryFrame, Context)> {
+        self.0.get_telemetry_frame(frame_id)
+    }
+
     pub fn delete(&self, id: i64) -> Result<HashMap<i64, Context>> {
         self.0.delete(id)
     }
 
+    /// The frame TTL of the configuration, see [`PipelineConfiguration::frame_ttl`].
+    ///
+    pub fn get_frame_ttl(&self) -> Option<Duration> {
+        self.0.get_frame_ttl()
+    }
+
+    /// Subscribes to the changes of the payloads: the frames added, moved, packed, unpacked
+    /// and deleted and the updates applied, so the dashboards do not poll the queues. The
+    /// events are sent since the subscription, a subscriber is removed when the receiver is
+    /// dropped. A subscriber keeps at most [`events::EVENT_QUEUE_SIZE`] events, the later
+    /// events are dropped until it receives them.
+    ///
+    pub fn subscribe(&self) -> Receiver<PipelineEvent> {
+        self.0.subscribe()
+    }
+
+    /// Evicts the payloads which stay in their stages for the frame TTL or longer, e.g.
+    /// because the consumer of the stage died. The payloads are deleted as dropped, the root
+    /// spans of their frames get the `frame-evicted` event and are ended and the frames are
+    /// counted in the `pipeline_evicted_frames` counter labelled with the pipeline and the
+    /// stage. Does nothing without the TTL. Called in the background by
+    /// [`reaper::FrameReaper`].
+    ///
+    pub fn evict_stale_frames(&self) -> Vec<EvictedPayload> {
+        self.0.evict_stale_frames()
+    }
+
     pub fn get_stage_queue_len(&self, stage: &str) -> Result<usize> {
         self.0.get_stage_queue_len(stage)
     }
 
+    /// The ids of the payloads in the stage in the order they should be processed in: by
+    /// the priority, the higher first, and then in the order they entered the pipeline.
+    ///
+    pub fn get_stage_queue(&self, stage: &str) -> Result<Vec<i64>> {
+        self.0.get_stage_queue(stage)
+    }
+
+    /// Changes the priority of the payload in its current stage.
+    ///
+    pub fn set_priority(&self, id: i64, priority: i32) -> Result<()> {
+        self.0.set_priority(id, priority)
+    }
+
+    pub fn get_priority(&self, id: i64) -> Result<i32> {
+        self.0.get_priority(id)
+    }
+
+    pub fn get_topology(&self) -> PipelineTopology {
+        self.0.get_topology()
+    }
+
+    /// The edges of the stage with their destination stages, see
+    /// [`PipelineConfiguration::edges`].
+    ///
+    pub fn get_edges(&self, stage_name: &str) -> Result<Vec<(String, String)>> {
+        self.0.get_edges(stage_name)
+    }
+
+    /// Moves the payload along the edge of its stage, e.g. from a classifier to the branch
+    /// of the class. The frames moving to a batch stage are packed into a new batch and the
+    /// batches moving to a frame stage are unpacked. Returns the ids of the payloads in the
+    /// destination stage.
+    ///
+    pub fn route(&self, id: i64, edge_name: &str) -> Result<Vec<i64>> {
+        self.0.route(id, edge_name)
+    }
+
+    /// Renders the stages, the allowed transitions, the attached functions and the current
+    /// queue lengths in the DOT or Mermaid format.
+    ///
+    pub fn export_topology(&self, format: TopologyFormat) -> String {
+        render_topology(&[self.get_topology()], format)
+    }
+
     pub fn get_independent_frame(&self, frame_id: i64) -> Result<(VideoFrameProxy, Context)> {
         self.0.get_independent_frame(frame_id)
     }
@@ -218,6 +664,61 @@ impl Pipeline {
         self.0.move_and_unpack_batch(dest_stage_name, batch_id)
     }
 
+    /// Packs the independent frames of the source stage into the batches of the batch stage
+    /// when enough frames accumulate or the oldest frame waits for too long, instead of
+    /// calling [`Pipeline::move_and_pack_frames`]. The processing hints of the frames are
+    /// honored as described in [`AutoBatchConfig`]. The size is checked when the frames enter
+    /// the source stage, the latency by [`Pipeline::flush_auto_batches`], called in the
+    /// background by [`auto_batch::AutoBatchFlusher`]. A batch stage has one source stage
+    /// and a source stage feeds one batch stage.
+    ///
+    pub fn set_auto_batch(
+        &self,
+        batch_stage_name: &str,
+        source_stage_name: &str,
+        config: AutoBatchConfig,
+    ) -> Result<()> {
+        self.0
+            .set_auto_batch(batch_stage_name, source_stage_name, config)
+    }
+
+    pub fn clear_auto_batch(&self, batch_stage_name: &str) -> Result<bool> {
+        self.0.clear_auto_batch(batch_stage_name)
+    }
+
+    /// The source stage and the configuration of the auto-batching of the batch stage.
+    ///
+    pub fn get_auto_batch(
+        &self,
+        batch_stage_name: &str,
+    ) -> Result<Option<(String, AutoBatchConfig)>> {
+        self.0.get_auto_batch(batch_stage_name)
+    }
+
+    /// Packs the frames which waited for the latency of the auto-batching or, with `force`,
+    /// all the waiting frames. Returns the ids of the created batches. A batch stage failing
+    /// to accept the frames is logged and does not stop the flushing of the others.
+    ///
+    pub fn flush_auto_batches(&self, force: bool) -> Result<Vec<i64>> {
+        self.0.flush_auto_batches(force)
+    }
+
+    /// Moves the frames having objects matching the query to one stage and the rest to
+    /// another, so the next stage is chosen without pulling the frames into the user code.
+    /// Both destinations are checked, including their readiness, before any frame is moved.
+    /// Returns the ids of the matching and the other frames.
+    ///
+    pub fn move_matching(
+        &self,
+        matched_stage_name: &str,
+        unmatched_stage_name: &str,
+        query: &MatchQuery,
+        frame_ids: Vec<i64>,
+    ) -> Result<(Vec<i64>, Vec<i64>)> {
+        self.0
+            .move_matching(matched_stage_name, unmatched_stage_name, query, frame_ids)
+    }
+
     pub fn access_objects(
         &self,
         frame_id: i64,
@@ -226,10 +727,34 @@ impl Pipeline {
         self.0.access_objects(frame_id, query)
     }
 
+    /// The ingest rate, the latency and the drop rate of the sources over the rate window,
+    /// published to the upstream with [`source_stats::SourceStatsPublisher`].
+    ///
+    pub fn get_source_statistics(&self) -> Vec<SourceStatistics> {
+        self.0.get_source_statistics()
+    }
+
+    /// Evaluates the query on the objects of the frames in the pipeline without moving
+    /// them. Up to `max_frames` frames (at most [`MAX_SCANNED_FRAMES`]) with the lowest ids
+    /// are checked, one scan per [`PipelineConfiguration::frame_scan_interval`] is allowed.
+    /// Returns the frames having matching objects, ordered by the id.
+    ///
+    pub fn scan_frames(
+        &self,
+        query: &MatchQuery,
+        max_frames: usize,
+    ) -> Result<Vec<(i64, Vec<BorrowedVideoObject>)>> {
+        self.0.scan_frames(query, max_frames)
+    }
+
     pub fn get_id_locations_len(&self) -> usize {
         self.0.get_id_locations_len()
     }
 
+    pub fn get_root_spans_len(&self) -> usize {
+        self.0.get_root_spans_len()
+    }
+
     pub fn get_keyframe_history(&self, frame: &VideoFrameProxy) -> Option<Vec<(u128, i64)>> {
         self.0.get_keyframe_history(frame)
     }
@@ -245,30 +770,64 @@ pub(super) mod implementation {
     use std::collections::VecDeque;
     use std::num::NonZeroUsize;
     use std::sync::atomic::{AtomicI64, Ordering};
-    use std::sync::OnceLock;
-    use std::time::SystemTime;
+    use std::sync::{Arc, OnceLock};
+    use std::time::{Duration, Instant, SystemTime};
 
     use anyhow::{anyhow, bail, Result};
+    use crossbeam::channel::Receiver;
     use derive_builder::Builder;
     use hashbrown::HashMap;
     use lru::LruCache;
     use opentelemetry::trace::{SpanBuilder, TraceContextExt, Tracer};
     use opentelemetry::{Context, KeyValue};
 
+    use crate::clock::{self, Clock, ClockMode};
     use crate::get_tracer;
     use crate::match_query::MatchQuery;
+    use crate::metrics::get_or_create_counter_family;
+    use crate::pipeline::admission::AdmissionFilter;
+    use crate::pipeline::auto_batch::{AutoBatchConfig, AutoBatcher};
+    use crate::pipeline::circuit_breaker::{CircuitBreaker, Isolation, QuarantinePolicy};
+    use crate::pipeline::debug_tap::DebugTap;
+    use crate::pipeline::decimator::{DecimationStrategy, Decimator};
+    use crate::pipeline::events::{EventBus, PipelineEvent};
+    #[cfg(feature = "chaos")]
+    use crate::pipeline::fault_injector::FaultInjector;
+    use crate::pipeline::history::{reconstruct_frame_at, FrameHistory};
+    use crate::pipeline::hooks::StageHook;
+    use crate::pipeline::object_lifecycle::ObjectObserver;
+    use crate::pipeline::rates::StageRate;
+    use crate::pipeline::readiness::{
+        PipelineHealth, StageHealth, StageNotReady, StageReadiness, WarmupPolicy,
+    };
+    use crate::pipeline::reaper::{EvictedPayload, EVICTED_FRAMES_METRIC};
+    use crate::pipeline::result_cache::ResultCache;
+    use crate::pipeline::source_config::SourceConfigResolver;
+    use crate::pipeline::source_stats::{SourceStatistics, SourceStatsTracker};
     use crate::pipeline::stage::PipelineStage;
+    use crate::pipeline::stage_processor::StageProcessorVersion;
     use crate::pipeline::stats::{FrameProcessingStatRecord, Stats};
+    use crate::pipeline::topology::{transition, PipelineTopology, StageTopology};
+    use crate::pipeline::updaters::{UpdaterScope, UpdaterScopeError};
+    use crate::pipeline::user_code::UserCodeTime;
+    use crate::pipeline::validation::FrameValidator;
     use crate::pipeline::{
-        PipelinePayload, PipelineStageFunction, PipelineStagePayloadType, MAX_TRACKED_STREAMS,
+        PipelinePayload, PipelineStageFunction, PipelineStageFunctionOrder,
+        PipelineStagePayloadType, MAX_TRACKED_STREAMS,
     };
+    use crate::primitives::attribute_propagation::AttributePropagation;
+    use crate::primitives::content_encoding::ContentEncoding;
     use crate::primitives::frame::VideoFrameProxy;
     use crate::primitives::frame_batch::VideoFrameBatch;
     use crate::primitives::frame_update::VideoFrameUpdate;
-    use crate::primitives::object::BorrowedVideoObject;
+    use crate::primitives::object::{BorrowedVideoObject, OrphanPolicy};
     use crate::rwlock::SavantRwLock;
+    use uuid::Uuid;
 
     const DEFAULT_ROOT_SPAN_NAME: &str = "video_pipeline";
+    const DEFAULT_FRAME_SCAN_INTERVAL: Duration = Duration::from_secs(1);
+    /// The most frames checked by a scan, see [`Pipeline::scan_frames`].
+    pub const MAX_SCANNED_FRAMES: usize = 10_000;
 
     #[derive(Builder, Default, Debug, Clone)]
     pub struct PipelineConfiguration {
@@ -282,6 +841,40 @@ pub(super) mod implementation {
         pub collection_history: usize,
         #[builder(default = "60")]
         pub keyframe_history: usize,
+        /// Pairs of a stage name and an object namespace frozen when frames leave the stage,
+        /// `None` freezes the whole object set.
+        #[builder(default)]
+        pub freeze_objects: Vec<(String, Option<String>)>,
+        /// Triples of a stage name, a query and an orphan policy: the objects matching the
+        /// query are pruned when frames leave the stage.
+        #[builder(default)]
+        pub prune_objects: Vec<(String, MatchQuery, OrphanPolicy)>,
+        /// Pairs of a stage name and the expected time a payload spends in the stage.
+        #[builder(default)]
+        pub stage_budgets: Vec<(String, Duration)>,
+        /// The window of the stage rates, see [`crate::pipeline::rates::DEFAULT_RATE_WINDOW`]
+        /// when not set.
+        #[builder(default)]
+        pub rate_window: Option<Duration>,
+        /// The minimum time between the scans of the frames in the pipeline, see
+        /// [`Pipeline::scan_frames`].
+        #[builder(default = "DEFAULT_FRAME_SCAN_INTERVAL")]
+        pub frame_scan_interval: Duration,
+        /// Triples of a source stage, an edge name and a destination stage making the stages
+        /// a DAG: the payloads of a stage with the edges move only along them, see
+        /// [`Pipeline::route`], and a stage the edges lead to receives the payloads only
+        /// from the sources of the edges. The other stages pass the payloads to any stage
+        /// after them.
+        #[builder(default)]
+        pub edges: Vec<(String, String, String)>,
+        /// The longest time a payload stays in a stage, the payloads staying longer are
+        /// evicted by [`Pipeline::evict_stale_frames`]. `None` keeps the payloads forever.
+        #[builder(default)]
+        pub frame_ttl: Option<Duration>,
+        /// The clock mode of the pipeline, the pipeline gets its own clock advanced by its
+        /// frames only. `None` uses the process clock, see [`crate::clock::process_clock`].
+        #[builder(default)]
+        pub clock_mode: Option<ClockMode>,
     }
 
     #[derive(Debug)]
@@ -299,6 +892,18 @@ pub(super) mod implementation {
         root_span_name: OnceLock<String>,
         configuration: PipelineConfiguration,
         stats: Stats,
+        updaters: SavantRwLock<HashMap<String, UpdaterScope>>,
+        source_config: SavantRwLock<Option<Arc<SourceConfigResolver>>>,
+        attribute_propagation: SavantRwLock<Option<Arc<AttributePropagation>>>,
+        admission: SavantRwLock<Option<Arc<AdmissionFilter>>>,
+        history: SavantRwLock<Option<Arc<FrameHistory>>>,
+        validator: SavantRwLock<Option<Arc<FrameValidator>>>,
+        last_frame_scan: SavantRwLock<Option<Instant>>,
+        source_stats: SourceStatsTracker,
+        edges: HashMap<usize, Vec<(String, usize)>>,
+        auto_batchers: SavantRwLock<HashMap<usize, Arc<AutoBatcher>>>,
+        events: EventBus,
+        clock: Arc<Clock>,
     }
 
     impl Default for Pipeline {
@@ -323,6 +928,18 @@ pub(super) mod implementation {
                 root_span_name: OnceLock::new(),
                 configuration: PipelineConfiguration::default(),
                 stats: Stats::default(),
+                updaters: SavantRwLock::new(HashMap::new()),
+                source_config: SavantRwLock::new(None),
+                attribute_propagation: SavantRwLock::new(None),
+                admission: SavantRwLock::new(None),
+                history: SavantRwLock::new(None),
+                validator: SavantRwLock::new(None),
+                last_frame_scan: SavantRwLock::new(None),
+                source_stats: SourceStatsTracker::default(),
+                edges: HashMap::new(),
+                auto_batchers: SavantRwLock::new(HashMap::new()),
+                events: EventBus::default(),
+                clock: clock::process_clock(),
             }
         }
     }
@@ -360,13 +977,14 @@ pub(super) mod implementation {
                 bail!("Stage with name {} already exists", name)
             }
 
-            let stage = PipelineStage::new(
+            let mut stage = PipelineStage::new(
                 self.stages.len(),
                 name,
                 stage_type,
                 ingress_function,
                 egress_function,
             );
+            stage.clock = self.clock.clone();
             let stat = stage.get_stat();
             self.stats.add_stage_stats(stat);
             self.stages.push(stage);
@@ -383,23 +1001,154 @@ pub(super) mod implementation {
             )>,
             configuration: PipelineConfiguration,
         ) -> Result<Self> {
+            let clock = match configuration.clock_mode {
+                Some(mode) => Arc::new(Clock::new(mode)),
+                None => clock::process_clock(),
+            };
             let stats = Stats::new(
                 configuration.collection_history,
                 configuration.frame_period,
                 configuration.timestamp_period,
+                clock.clone(),
             );
             let mut pipeline = Self {
                 configuration,
                 stats,
+                clock,
                 ..Default::default()
             };
 
             for (name, stage_type, ingress_function, egress_function) in stages {
                 pipeline.add_stage(name, stage_type, ingress_function, egress_function)?;
             }
+            for (stage_name, namespace) in pipeline.configuration.freeze_objects.clone() {
+                let (index, _) = pipeline.find_stage(&stage_name, 0)?;
+                pipeline.stages[index].frozen_namespaces.push(namespace);
+            }
+            for (stage_name, query, policy) in pipeline.configuration.prune_objects.clone() {
+                let (index, _) = pipeline.find_stage(&stage_name, 0)?;
+                pipeline.stages[index].prune_rules.push((query, policy));
+            }
+            for (stage_name, budget) in pipeline.configuration.stage_budgets.clone() {
+                let (index, _) = pipeline.find_stage(&stage_name, 0)?;
+                pipeline.stages[index].budget = Some(budget);
+            }
+            if let Some(window) = pipeline.configuration.rate_window {
+                for stage in &pipeline.stages {
+                    stage.set_rate_window(window);
+                }
+                pipeline.source_stats = SourceStatsTracker::new(window);
+            }
+            for (from, name, to) in pipeline.configuration.edges.clone() {
+                pipeline.add_edge(&from, name, &to)?;
+            }
             Ok(pipeline)
         }
 
+        fn add_edge(&mut self, from: &str, name: String, to: &str) -> Result<()> {
+            let (from_index, from_stage) = self.find_stage(from, 0)?;
+            let (to_index, to_stage) = self.find_stage(to, from_index)?;
+            if to_index == from_index {
+                bail!("Edge {} of stage {} must lead to another stage", name, from)
+            }
+            if transition(&from_stage.stage_type, &to_stage.stage_type).is_none() {
+                bail!(
+                    "Edge {} cannot connect stage {} ({:?}) to stage {} ({:?})",
+                    name,
+                    from,
+                    from_stage.stage_type,
+                    to,
+                    to_stage.stage_type
+                )
+            }
+            let edges = self.edges.entry(from_index).or_default();
+            if edges.iter().any(|(n, _)| *n == name) {
+                bail!("Stage {} already has edge {}", from, name)
+            }
+            edges.push((name, to_index));
+            Ok(())
+        }
+
+        /// The edges of the stage with their destination stages.
+        ///
+        pub fn get_edges(&self, stage_name: &str) -> Result<Vec<(String, String)>> {
+            let (index, _) = self.find_stage(stage_name, 0)?;
+            Ok(self
+                .edges
+                .get(&index)
+                .map(|edges| {
+                    edges
+                        .iter()
+                        .map(|(name, to)| (name.clone(), self.stages[*to].name.clone()))
+                        .collect()
+                })
+                .unwrap_or_default())
+        }
+
+        /// Finds the stage the payloads of the source stage move to, the stage must be reached
+        /// by an edge when the source stage has the edges or the edges lead to the stage.
+        ///
+        fn find_destination(
+            &self,
+            dest_stage_name: &str,
+            source_index: usize,
+        ) -> Result<(usize, &PipelineStage)> {
+            let (dest_index, dest_stage) = self.find_stage(dest_stage_name, source_index)?;
+            if let Some(edges) = self.edges.get(&source_index) {
+                if !edges.iter().any(|(_, to)| *to == dest_index) {
+                    bail!(
+                        "Stage {} has no edge to stage {}",
+                        self.stages[source_index].name,
+                        dest_stage_name
+                    )
+                }
+            }
+            let mut sources = self
+                .edges
+                .iter()
+                .filter(|(_, edges)| edges.iter().any(|(_, to)| *to == dest_index))
+                .map(|(from, _)| *from)
+                .peekable();
+            if sources.peek().is_some() && !sources.any(|from| from == source_index) {
+                bail!(
+                    "Stage {} receives payloads only along its edges, not from stage {}",
+                    dest_stage_name,
+                    self.stages[source_index].name
+                )
+            }
+            Ok((dest_index, dest_stage))
+        }
+
+        /// Moves the payload along the edge of its stage. The frames moving to a batch stage
+        /// are packed into a new batch and the batches moving to a frame stage are unpacked.
+        /// Returns the ids of the payloads in the destination stage.
+        ///
+        pub fn route(&self, id: i64, edge_name: &str) -> Result<Vec<i64>> {
+            let source_index = self.get_stage_for_id(id)?;
+            let source_stage = &self.stages[source_index];
+            let Some(dest_index) = self.edges.get(&source_index).and_then(|edges| {
+                edges
+                    .iter()
+                    .find(|(name, _)| name == edge_name)
+                    .map(|(_, to)| *to)
+            }) else {
+                bail!("Stage {} has no edge {}", source_stage.name, edge_name)
+            };
+            let dest_stage = &self.stages[dest_index];
+            match (&source_stage.stage_type, &dest_stage.stage_type) {
+                (PipelineStagePayloadType::Frame, PipelineStagePayloadType::Batch) => {
+                    Ok(vec![self.move_and_pack_frames(&dest_stage.name, vec![id])?])
+                }
+                (PipelineStagePayloadType::Batch, PipelineStagePayloadType::Frame) => {
+                    self.move_and_unpack_batch(&dest_stage.name, id)
+                }
+                _ => {
+                    self.move_as_is(&dest_stage.name, vec![id])?;
+                    Ok(vec![id])
+                }
+            }
+        }
+
         pub fn get_stat_records(&self, max_n: usize) -> Vec<FrameProcessingStatRecord> {
             self.stats.get_records(max_n)
         }
@@ -412,10 +1161,35 @@ pub(super) mod implementation {
             self.stats.log_final_fps()
         }
 
+        pub fn rates(&self) -> Vec<StageRate> {
+            self.stages.iter().flat_map(|s| s.get_rates()).collect()
+        }
+
+        pub fn get_configuration(&self) -> &PipelineConfiguration {
+            &self.configuration
+        }
+
+        /// Up to `max_frames` frames of each stage, e.g. to inspect the metadata.
+        ///
+        pub fn sample_frames(&self, max_frames: usize) -> Vec<(String, VideoFrameProxy)> {
+            self.stages
+                .iter()
+                .flat_map(|s| {
+                    s.sample_frames(max_frames)
+                        .into_iter()
+                        .map(|f| (s.name.clone(), f))
+                })
+                .collect()
+        }
+
         pub fn get_id_locations_len(&self) -> usize {
             self.frame_locations.read().len()
         }
 
+        pub fn get_root_spans_len(&self) -> usize {
+            self.root_spans.read().len()
+        }
+
         pub fn set_root_span_name(&self, name: String) -> Result<()> {
             self.root_span_name.set(name).map_err(|last| {
                 anyhow::anyhow!(
@@ -551,6 +1325,15 @@ pub(super) mod implementation {
         }
 
         pub fn add_frame(&self, stage_name: &str, frame: VideoFrameProxy) -> Result<i64> {
+            self.add_frame_with_priority(stage_name, frame, 0)
+        }
+
+        pub fn add_frame_with_priority(
+            &self,
+            stage_name: &str,
+            frame: VideoFrameProxy,
+            priority: i32,
+        ) -> Result<i64> {
             let sampling_period = self.get_sampling_period();
             let next_frame = self.frame_counter.load(Ordering::SeqCst) + 1;
             let ctx = if *sampling_period <= 0 || next_frame % *sampling_period != 0 {
@@ -558,25 +1341,55 @@ pub(super) mod implementation {
             } else {
                 get_tracer().in_span(self.get_root_span_name().clone(), |cx| cx)
             };
-            self.add_frame_with_telemetry(stage_name, frame, ctx)
+            self.add_prioritized_frame(stage_name, frame, ctx, priority)
         }
 
         pub fn add_frame_with_telemetry(
+            &self,
+            stage_name: &str,
+            frame: VideoFrameProxy,
+            parent_ctx: Context,
+        ) -> Result<i64> {
+            self.add_prioritized_frame(stage_name, frame, parent_ctx, 0)
+        }
+
+        fn add_prioritized_frame(
             &self,
             stage_name: &str,
             mut frame: VideoFrameProxy,
             parent_ctx: Context,
+            priority: i32,
         ) -> Result<i64> {
-            if matches!(
+            if !matches!(
                 self.find_stage_type(stage_name, 0)?,
-                PipelineStagePayloadType::Batch
+                PipelineStagePayloadType::Frame
             ) {
-                bail!("Stage does not accept batched frames")
+                bail!("Stage does not accept independent frames")
+            }
+            self.check_readiness(self.find_stage(stage_name, 0)?.0, &[])?;
+            if let Some(filter) = self.get_admission_filter() {
+                if let Err(e) = filter.admit(&self.get_name().unwrap_or_default(), &frame) {
+                    self.source_stats
+                        .reject(&frame.get_source_id(), self.clock.now_ms());
+                    return Err(e);
+                }
+            }
+            if let Some(resolver) = self.get_source_config_resolver() {
+                let config = resolver.resolve(&frame.get_source_id())?;
+                if frame.get_geo_pose().is_none() {
+                    frame.set_geo_pose(config.get_geo_pose()?);
+                }
+                frame.set_source_config(Some(config));
+            }
+            if let Some(propagation) = self.get_attribute_propagation() {
+                frame.set_attribute_propagation(Some(propagation));
             }
+            self.clock.advance_to_frame(&frame);
 
             self.frame_counter.fetch_add(1, Ordering::SeqCst);
             let id_counter = self.id_counter.fetch_add(1, Ordering::SeqCst) + 1;
             let source_id = frame.get_source_id();
+            let source_id_for_stats = source_id.clone();
 
             if !parent_ctx.span().span_context().is_valid() {
                 self.root_spans
@@ -629,16 +1442,128 @@ pub(super) mod implementation {
 
             let ctx = self.get_stage_span(id_counter, format!("add/{}", stage_name));
             let frame_payload =
-                PipelinePayload::Frame(frame, Vec::new(), ctx, None, SystemTime::now());
+                PipelinePayload::Frame(frame, Vec::new(), ctx, None, self.clock.now());
 
             let (index, stage) = self.find_stage(stage_name, 0)?;
-            stage.add_frame_payload(id_counter, frame_payload)?;
+            self.source_stats
+                .admit(id_counter, &source_id_for_stats, self.clock.now_ms());
+            if let Some(payload) =
+                stage.add_prioritized_frame_payload(id_counter, frame_payload, priority)?
+            {
+                self.discard_dropped(stage, vec![(id_counter, payload)]);
+                bail!(
+                    "Frame {} is dropped when entering stage {}",
+                    id_counter,
+                    stage_name
+                )
+            }
             self.frame_locations.write().insert(id_counter, index);
 
             log::trace!(target: "savant_rs::pipeline", "Added frame {} to stage {}", id_counter, stage_name);
+            self.events.publish(|| PipelineEvent::FrameAdded {
+                id: id_counter,
+                stage: stage_name.to_string(),
+            });
+            self.trigger_auto_batch(index);
+            Ok(id_counter)
+        }
+
+        pub fn add_audio_frame(&self, stage_name: &str, frame: AudioFrame) -> Result<i64> {
+            let (index, stage) = self.find_stage(stage_name, 0)?;
+            if stage.stage_type != PipelineStagePayloadType::Audio {
+                bail!("Stage {} does not accept audio frames", stage_name)
+            }
+            self.check_readiness(index, &[])?;
+            let id_counter = self.start_sensor_payload();
+            let ctx = self.get_stage_span(id_counter, format!("add/{}", stage_name));
+            let dropped = stage.add_payloads([(
+                id_counter,
+                PipelinePayload::Audio(frame, ctx, None, self.clock.now()),
+            )])?;
+            if !dropped.is_empty() {
+                self.discard_dropped(stage, dropped);
+                bail!(
+                    "Audio frame {} is dropped when entering stage {}",
+                    id_counter,
+                    stage_name
+                )
+            }
+            self.frame_locations.write().insert(id_counter, index);
+
+            log::trace!(target: "savant_rs::pipeline", "Added audio frame {} to stage {}", id_counter, stage_name);
+            self.events.publish(|| PipelineEvent::FrameAdded {
+                id: id_counter,
+                stage: stage_name.to_string(),
+            });
             Ok(id_counter)
         }
 
+        pub fn add_telemetry_frame(&self, stage_name: &str, frame: TelemetryFrame) -> Result<i64> {
+            let (index, stage) = self.find_stage(stage_name, 0)?;
+            if stage.stage_type != PipelineStagePayloadType::Telemetry {
+                bail!("Stage {} does not accept telemetry frames", stage_name)
+            }
+            self.check_readiness(index, &[])?;
+            let id_counter = self.start_sensor_payload();
+            let ctx = self.get_stage_span(id_counter, format!("add/{}", stage_name));
+            let dropped = stage.add_payloads([(
+                id_counter,
+                PipelinePayload::Telemetry(frame, ctx, None, self.clock.now()),
+            )])?;
+            if !dropped.is_empty() {
+                self.discard_dropped(stage, dropped);
+                bail!(
+                    "Telemetry frame {} is dropped when entering stage {}",
+                    id_counter,
+                    stage_name
+                )
+            }
+            self.frame_locations.write().insert(id_counter, index);
+
+            log::trace!(target: "savant_rs::pipeline", "Added telemetry frame {} to stage {}", id_counter, stage_name);
+            self.events.publish(|| PipelineEvent::FrameAdded {
+                id: id_counter,
+                stage: stage_name.to_string(),
+            });
+            Ok(id_counter)
+        }
+
+        /// Allocates an id and a root span for an audio or telemetry frame.
+        ///
+        fn start_sensor_payload(&self) -> i64 {
+            let sampling_period = self.get_sampling_period();
+            let next_frame = self.frame_counter.fetch_add(1, Ordering::SeqCst) + 1;
+            let root_ctx = if *sampling_period <= 0 || next_frame % *sampling_period != 0 {
+                Context::default()
+            } else {
+                get_tracer().in_span(self.get_root_span_name().clone(), |cx| cx)
+            };
+            let id_counter = self.id_counter.fetch_add(1, Ordering::SeqCst) + 1;
+            self.root_spans.write().insert(id_counter, root_ctx);
+            id_counter
+        }
+
+        pub fn get_audio_frame(&self, frame_id: i64) -> Result<(AudioFrame, Context)> {
+            let stage = self.get_stage_for_id(frame_id)?;
+            if let Some(stage) = self.stages.get(stage) {
+                stage.get_audio_frame(frame_id)
+            } else {
+                bail!("Stage not found (when getting audio frame {})", frame_id)
+            }
+        }
+
+        pub fn get_telemetry_frame(&self, frame_id: i64) -> Result<(TelemetryFrame, Context)> {
+            let stage = self.get_stage_for_id(frame_id)?;
+            if let Some(stage) = self.stages.get(stage) {
+                stage.get_telemetry_frame(frame_id)
+            } else {
+                bail!(
+                    "Stage not found (when getting telemetry frame {})",
+                    frame_id
+                )
+            }
+        }
+
         pub fn get_keyframe_history(&self, frame: &VideoFrameProxy) -> Option<Vec<(u128, i64)>> {
             let mut keyframe_history = self.keyframe_history.write();
             keyframe_history
@@ -665,12 +1590,31 @@ pub(super) mod implementation {
         }
 
         pub fn delete(&self, id: i64) -> Result<HashMap<i64, Context>> {
+            self.remove_payload(id, false)
+        }
+
+        /// Deletes the payload the pipeline does not process further, the frames are counted
+        /// as dropped in the source statistics.
+        ///
+        fn drop_payload(&self, id: i64) -> Result<HashMap<i64, Context>> {
+            self.remove_payload(id, true)
+        }
+
+        fn remove_payload(&self, id: i64, dropped: bool) -> Result<HashMap<i64, Context>> {
             let stage = self
                 .frame_locations
                 .write()
                 .remove(&id)
                 .ok_or(anyhow::anyhow!("Object {} location not found", id))?;
+            self.remove_from_stage(id, stage, dropped)
+        }
 
+        fn remove_from_stage(
+            &self,
+            id: i64,
+            stage: usize,
+            dropped: bool,
+        ) -> Result<HashMap<i64, Context>> {
             self.stats.kick_off();
 
             if let Some(stage) = self.stages.get(stage) {
@@ -679,19 +1623,34 @@ pub(super) mod implementation {
                 if removed.is_none() {
                     bail!("Object {} is not found in the stage {}", id, stage.name)
                 }
+                self.events.publish(|| PipelineEvent::FrameDeleted {
+                    id,
+                    stage: stage.name.clone(),
+                    dropped,
+                });
 
                 let mut bind = self.root_spans.write();
                 match removed.unwrap() {
                     PipelinePayload::Frame(frame, _, ctx, _, _) => {
                         self.stats.register_frame(frame.get_object_count());
+                        self.source_stats
+                            .complete(&[id], dropped, self.clock.now_ms());
                         self.add_frame_json(&frame, &ctx);
                         ctx.span().end();
                         let root_ctx = bind.remove(&id).unwrap();
                         Ok(HashMap::from([(id, root_ctx)]))
                     }
-                    PipelinePayload::Batch(batch, _, contexts, _, _) => Ok({
-                        let mut bind = self.root_spans.write();
-                        contexts
+                    PipelinePayload::Audio(_, ctx, _, _)
+                    | PipelinePayload::Telemetry(_, ctx, _, _) => {
+                        self.stats.register_frame(0);
+                        ctx.span().end();
+                        let root_ctx = bind.remove(&id).unwrap();
+                        Ok(HashMap::from([(id, root_ctx)]))
+                    }
+                    PipelinePayload::Batch(batch, _, contexts, _, _) => {
+                        self.source_stats
+                            .complete(contexts.keys(), dropped, self.clock.now_ms());
+                        let root_contexts = contexts
                             .into_iter()
                             .map(|(frame_id, ctx)| {
                                 let frame_opt = batch.get(frame_id);
@@ -707,22 +1666,223 @@ pub(super) mod implementation {
                                     )
                                 }
                                 ctx.span().end();
-                                let root_ctx = bind.remove(&id).unwrap();
-                                Ok((id, root_ctx))
+                                let root_ctx = bind.remove(&frame_id).unwrap();
+                                Ok((frame_id, root_ctx))
                             })
-                            .collect::<Result<HashMap<_, _>, _>>()?
-                    }),
+                            .collect::<Result<HashMap<_, _>, _>>()?;
+                        drop(bind);
+                        // the frames of the batch are located in the stage of the batch
+                        let mut locations = self.frame_locations.write();
+                        for frame_id in root_contexts.keys() {
+                            locations.remove(frame_id);
+                        }
+                        Ok(root_contexts)
+                    }
                 }
             } else {
                 bail!("Stage ID={} not found (when removing object {})", stage, id)
             }
         }
 
+        /// Deletes the payloads dropped by the enter hooks or the fault injector of the stage,
+        /// the frames are counted as dropped in the source statistics.
+        ///
+        fn discard_dropped(&self, stage: &PipelineStage, dropped: Vec<(i64, PipelinePayload)>) {
+            for (id, payload) in dropped {
+                let (frame_ids, contexts): (Vec<_>, Vec<_>) = match payload {
+                    PipelinePayload::Frame(_, _, ctx, _, _)
+                    | PipelinePayload::Audio(_, ctx, _, _)
+                    | PipelinePayload::Telemetry(_, ctx, _, _) => (vec![id], vec![ctx]),
+                    PipelinePayload::Batch(_, _, contexts, _, _) => contexts.into_iter().unzip(),
+                };
+                for ctx in contexts {
+                    ctx.span().end();
+                }
+                {
+                    let mut locations = self.frame_locations.write();
+                    locations.remove(&id);
+                    for frame_id in &frame_ids {
+                        locations.remove(frame_id);
+                    }
+                }
+                {
+                    let mut root_spans = self.root_spans.write();
+                    for frame_id in &frame_ids {
+                        if let Some(root_ctx) = root_spans.remove(frame_id) {
+                            root_ctx.span().add_event(
+                                "frame-dropped",
+                                vec![KeyValue::new("stage", stage.name.clone())],
+                            );
+                            root_ctx.span().end();
+                        }
+                    }
+                }
+                self.source_stats
+                    .complete(&frame_ids, true, self.clock.now_ms());
+                log::debug!(
+                    target: "savant_rs::pipeline",
+                    "Payload {} (frames {:?}) is dropped when entering stage {}",
+                    id,
+                    frame_ids,
+                    stage.name
+                );
+                self.events.publish(|| PipelineEvent::FrameDeleted {
+                    id,
+                    stage: stage.name.clone(),
+                    dropped: true,
+                });
+            }
+        }
+
+        pub fn get_frame_ttl(&self) -> Option<Duration> {
+            self.configuration.frame_ttl
+        }
+
+        pub fn subscribe(&self) -> Receiver<PipelineEvent> {
+            self.events.subscribe()
+        }
+
+        pub fn evict_stale_frames(&self) -> Vec<EvictedPayload> {
+            let Some(ttl) = self.configuration.frame_ttl else {
+                return Vec::new();
+            };
+            let mut evicted = Vec::new();
+            for (index, stage) in self.stages.iter().enumerate() {
+                for (id, age) in stage.get_stale_payloads(ttl) {
+                    let contexts = match self.evict_payload(id, index) {
+                        Ok(Some(contexts)) => contexts,
+                        // the payload left the stage after the check
+                        Ok(None) => continue,
+                        Err(e) => {
+                            log::warn!(
+                                target: "savant_rs::pipeline::reaper",
+                                "Failed to evict payload {} from stage {}: {}",
+                                id,
+                                stage.name,
+                                e
+                            );
+                            continue;
+                        }
+                    };
+                    let mut frame_ids = Vec::with_capacity(contexts.len());
+                    for (frame_id, ctx) in contexts {
+                        ctx.span().add_event(
+                            "frame-evicted",
+                            vec![
+                                KeyValue::new("stage", stage.name.clone()),
+                                KeyValue::new("age_ms", age.as_millis() as i64),
+                            ],
+                        );
+                        ctx.span().end();
+                        frame_ids.push(frame_id);
+                    }
+                    frame_ids.sort_unstable();
+                    log::warn!(
+                        target: "savant_rs::pipeline::reaper",
+                        "Payload {} (frames {:?}) stayed in stage {} for {:?} and was evicted",
+                        id,
+                        frame_ids,
+                        stage.name,
+                        age
+                    );
+                    let res = get_or_create_counter_family(
+                        EVICTED_FRAMES_METRIC,
+                        Some("The number of frames evicted after staying in a stage for the TTL"),
+                        &["pipeline", "stage"],
+                        None,
+                    )
+                    .lock()
+                    .inc(
+                        frame_ids.len() as u64,
+                        &[&self.get_name().unwrap_or_default(), &stage.name],
+                    );
+                    if let Err(e) = res {
+                        log::warn!(
+                            target: "savant_rs::pipeline::reaper",
+                            "Failed to count the evicted frames: {}", e
+                        );
+                    }
+                    evicted.push(EvictedPayload {
+                        stage: stage.name.clone(),
+                        id,
+                        frame_ids,
+                        age,
+                    });
+                }
+            }
+            evicted
+        }
+
+        /// Deletes the payload if it is still in the stage, the frames are counted as dropped
+        /// in the source statistics.
+        ///
+        fn evict_payload(&self, id: i64, stage: usize) -> Result<Option<HashMap<i64, Context>>> {
+            {
+                let mut locations = self.frame_locations.write();
+                if locations.get(&id) != Some(&stage) {
+                    return Ok(None);
+                }
+                locations.remove(&id);
+            }
+            self.remove_from_stage(id, stage, true)
+                .map(Some)
+                .map_err(|e| {
+                    if self.stages[stage].contains(id) {
+                        self.frame_locations.write().insert(id, stage);
+                    } else if let Some(root_ctx) = self.root_spans.write().remove(&id) {
+                        // the payload is gone with the failed egress function
+                        root_ctx.span().end();
+                    }
+                    e
+                })
+        }
+
         pub fn get_stage_queue_len(&self, stage: &str) -> Result<usize> {
             let (_, stage) = self.find_stage(stage, 0)?;
             Ok(stage.len())
         }
 
+        pub fn get_stage_queue(&self, stage: &str) -> Result<Vec<i64>> {
+            let (_, stage) = self.find_stage(stage, 0)?;
+            Ok(stage.get_queue())
+        }
+
+        pub fn set_priority(&self, id: i64, priority: i32) -> Result<()> {
+            let stage = self.get_stage_for_id(id)?;
+            self.stages[stage].set_priority(id, priority)
+        }
+
+        pub fn get_priority(&self, id: i64) -> Result<i32> {
+            let stage = self.get_stage_for_id(id)?;
+            self.stages[stage]
+                .get_priority(id)
+                .ok_or_else(|| anyhow!("Payload {} not found in stage", id))
+        }
+
+        pub fn get_topology(&self) -> PipelineTopology {
+            PipelineTopology {
+                name: self.get_name(),
+                stages: self
+                    .stages
+                    .iter()
+                    .map(|s| StageTopology {
+                        name: s.name.clone(),
+                        stage_type: s.stage_type.clone(),
+                        has_ingress_function: s.has_ingress_function(),
+                        has_egress_function: s.has_egress_function(),
+                        queue_length: s.len(),
+                    })
+                    .collect(),
+                edges: self
+                    .edges
+                    .iter()
+                    .flat_map(|(from, edges)| {
+                        edges.iter().map(|(name, to)| (*from, name.clone(), *to))
+                    })
+                    .collect(),
+            }
+        }
+
         fn get_stage_for_id(&self, id: i64) -> Result<usize> {
             let bind = self.frame_locations.read();
             if let Some(stage) = bind.get(&id) {
@@ -784,10 +1944,595 @@ pub(super) mod implementation {
             }
         }
 
+        /// Registers the attribute namespaces (glob patterns) the updater may write. The
+        /// registration of an updater with the same name is replaced.
+        ///
+        pub fn register_updater(&self, name: &str, namespaces: &[&str]) -> Result<()> {
+            let scope = UpdaterScope::new(name, namespaces)?;
+            self.updaters.write().insert(name.to_string(), scope);
+            Ok(())
+        }
+
+        /// Installs the tap dumping frames entering the stage, `None` removes the installed
+        /// tap.
+        ///
+        pub fn set_debug_tap(&self, stage_name: &str, tap: Option<DebugTap>) -> Result<()> {
+            let (_, stage) = self.find_stage(stage_name, 0)?;
+            stage.set_debug_tap(tap);
+            Ok(())
+        }
+
+        pub fn get_debug_tap(&self, stage_name: &str) -> Result<Option<Arc<DebugTap>>> {
+            let (_, stage) = self.find_stage(stage_name, 0)?;
+            Ok(stage.get_debug_tap())
+        }
+
+        /// Installs the observer reporting the objects changed while the frames are in the
+        /// stage, `None` removes the installed observer.
+        ///
+        pub fn set_object_observer(
+            &self,
+            stage_name: &str,
+            observer: Option<ObjectObserver>,
+        ) -> Result<()> {
+            let (_, stage) = self.find_stage(stage_name, 0)?;
+            stage.set_object_observer(observer);
+            Ok(())
+        }
+
+        pub fn get_object_observer(&self, stage_name: &str) -> Result<Option<Arc<ObjectObserver>>> {
+            let (_, stage) = self.find_stage(stage_name, 0)?;
+            Ok(stage.get_object_observer())
+        }
+
+        pub fn add_stage_hook(
+            &self,
+            stage_name: &str,
+            name: &str,
+            hook: Arc<dyn StageHook>,
+        ) -> Result<()> {
+            let (_, stage) = self.find_stage(stage_name, 0)?;
+            stage.add_hook(name, hook)
+        }
+
+        pub fn remove_stage_hook(&self, stage_name: &str, name: &str) -> Result<bool> {
+            let (_, stage) = self.find_stage(stage_name, 0)?;
+            Ok(stage.remove_hook(name))
+        }
+
+        pub fn get_stage_hooks(&self, stage_name: &str) -> Result<Vec<String>> {
+            let (_, stage) = self.find_stage(stage_name, 0)?;
+            Ok(stage.get_hook_names())
+        }
+
+        pub fn unregister_updater(&self, name: &str) -> Option<UpdaterScope> {
+            self.updaters.write().remove(name)
+        }
+
+        /// Installs the resolver of the per-source configurations attached to the frames
+        /// when they are added, `None` removes the installed resolver.
+        ///
+        pub fn set_source_config_resolver(&self, resolver: Option<SourceConfigResolver>) {
+            *self.source_config.write() = resolver.map(Arc::new);
+        }
+
+        pub fn get_source_config_resolver(&self) -> Option<Arc<SourceConfigResolver>> {
+            self.source_config.read().clone()
+        }
+
+        /// Installs the attribute propagation attached to the frames when they are added,
+        /// `None` removes the installed propagation. The frames already in the pipeline keep
+        /// the propagation they were added with.
+        ///
+        pub fn set_attribute_propagation(&self, propagation: Option<AttributePropagation>) {
+            *self.attribute_propagation.write() = propagation.map(Arc::new);
+        }
+
+        pub fn get_attribute_propagation(&self) -> Option<Arc<AttributePropagation>> {
+            self.attribute_propagation.read().clone()
+        }
+
+        /// Installs the filter checked when the frames are added, `None` admits all frames.
+        ///
+        pub fn set_admission_filter(&self, filter: Option<AdmissionFilter>) {
+            *self.admission.write() = filter.map(Arc::new);
+        }
+
+        pub fn get_admission_filter(&self) -> Option<Arc<AdmissionFilter>> {
+            self.admission.read().clone()
+        }
+
+        /// Installs the history recording the frames leaving the stages, `None` stops the
+        /// recording. The journals written are kept.
+        ///
+        pub fn set_frame_history(&self, history: Option<FrameHistory>) {
+            let history = history.map(Arc::new);
+            for stage in &self.stages {
+                stage.set_history(history.clone());
+            }
+            *self.history.write() = history;
+        }
+
+        pub fn get_frame_history(&self) -> Option<Arc<FrameHistory>> {
+            self.history.read().clone()
+        }
+
+        pub fn set_frame_validator(&self, validator: Option<FrameValidator>) {
+            let validator = validator.map(Arc::new);
+            for stage in &self.stages {
+                stage.set_validator(validator.clone());
+            }
+            *self.validator.write() = validator;
+        }
+
+        pub fn get_frame_validator(&self) -> Option<Arc<FrameValidator>> {
+            self.validator.read().clone()
+        }
+
+        /// Rebuilds the frame as it left the stage from the journals of the directory of the
+        /// installed history, the ones of the past runs included.
+        ///
+        pub fn reconstruct_frame_at(
+            &self,
+            uuid: Uuid,
+            stage_name: &str,
+        ) -> Result<Option<VideoFrameProxy>> {
+            self.find_stage(stage_name, 0)?;
+            let Some(history) = self.get_frame_history() else {
+                bail!("The frame history is not installed");
+            };
+            history.flush();
+            reconstruct_frame_at(history.get_directory(), uuid, stage_name)
+        }
+
+        pub fn record_user_code_time(
+            &self,
+            stage_name: &str,
+            ids: &[i64],
+            time: UserCodeTime,
+        ) -> Result<()> {
+            let (_, stage) = self.find_stage(stage_name, 0)?;
+            stage.record_user_code_time(ids, time);
+            Ok(())
+        }
+
+        /// Installs the decimator of a frame stage, `None` removes the installed decimator.
+        /// The stage watched by the queue depth strategy must follow the decimator stage.
+        ///
+        pub fn set_decimator(&self, stage_name: &str, decimator: Option<Decimator>) -> Result<()> {
+            let (index, stage) = self.find_stage(stage_name, 0)?;
+            if stage.stage_type != PipelineStagePayloadType::Frame {
+                bail!(
+                    "Decimator requires a frame stage, stage {} is {:?}",
+                    stage_name,
+                    stage.stage_type
+                )
+            }
+            if let Some(DecimationStrategy::QueueDepth {
+                stage: downstream, ..
+            }) = decimator.as_ref().map(|d| d.get_strategy())
+            {
+                self.find_stage(downstream, index + 1)?;
+            }
+            stage.set_decimator(decimator);
+            Ok(())
+        }
+
+        pub fn get_decimator(&self, stage_name: &str) -> Result<Option<Arc<Decimator>>> {
+            let (_, stage) = self.find_stage(stage_name, 0)?;
+            Ok(stage.get_decimator())
+        }
+
+        pub fn set_result_cache(&self, stage_name: &str, cache: Option<ResultCache>) -> Result<()> {
+            let (_, stage) = self.find_stage(stage_name, 0)?;
+            if stage.stage_type != PipelineStagePayloadType::Frame {
+                bail!(
+                    "Result cache requires a frame stage, stage {} is {:?}",
+                    stage_name,
+                    stage.stage_type
+                )
+            }
+            stage.set_result_cache(cache);
+            Ok(())
+        }
+
+        pub fn get_result_cache(&self, stage_name: &str) -> Result<Option<Arc<ResultCache>>> {
+            let (_, stage) = self.find_stage(stage_name, 0)?;
+            Ok(stage.get_result_cache())
+        }
+
+        pub fn replay_cached(
+            &self,
+            stage_name: &str,
+            frame_ids: &[i64],
+        ) -> Result<(Vec<i64>, Vec<i64>)> {
+            let (index, stage) = self.find_stage(stage_name, 0)?;
+            let cache = stage
+                .get_result_cache()
+                .ok_or_else(|| anyhow!("Stage {} has no result cache", stage_name))?;
+            for (id, location) in self.get_stages_for_ids(frame_ids)? {
+                if location != index {
+                    bail!("Frame {} is not in the stage {}", id, stage_name)
+                }
+            }
+            let mut replayed = Vec::new();
+            let mut missed = Vec::new();
+            for id in frame_ids {
+                let (frame, _) = stage.get_independent_frame(*id)?;
+                if cache.replay(&frame)? {
+                    replayed.push(*id);
+                } else {
+                    missed.push(*id);
+                }
+            }
+            log::trace!(target: "savant_rs::pipeline", "Stage {} replayed cached results for {} of {} frames", stage_name, replayed.len(), frame_ids.len());
+            Ok((replayed, missed))
+        }
+
+        pub fn set_content_encoding(
+            &self,
+            stage_name: &str,
+            encoding: Option<ContentEncoding>,
+        ) -> Result<()> {
+            let (_, stage) = self.find_stage(stage_name, 0)?;
+            if !matches!(
+                stage.stage_type,
+                PipelineStagePayloadType::Frame | PipelineStagePayloadType::Batch
+            ) {
+                bail!(
+                    "Content encoding requires a frame or batch stage, stage {} is {:?}",
+                    stage_name,
+                    stage.stage_type
+                )
+            }
+            stage.set_content_encoding(encoding);
+            Ok(())
+        }
+
+        pub fn get_content_encoding(&self, stage_name: &str) -> Result<Option<ContentEncoding>> {
+            let (_, stage) = self.find_stage(stage_name, 0)?;
+            Ok(stage.get_content_encoding())
+        }
+
+        pub fn replace_stage_processor(
+            &self,
+            stage_name: &str,
+            order: PipelineStageFunctionOrder,
+            processor: Option<Box<dyn PipelineStageFunction>>,
+        ) -> Result<StageProcessorVersion> {
+            let (_, stage) = self.find_stage(stage_name, 0)?;
+            let (previous, version) = stage.get_processor(order).replace(processor);
+            log::info!(
+                target: "savant_rs::pipeline",
+                "Stage {} {:?} function replaced, version {}, previous function {}",
+                stage_name,
+                order,
+                version.version,
+                if previous.is_some() { "released" } else { "absent" }
+            );
+            Ok(version)
+        }
+
+        pub fn get_stage_processor_version(
+            &self,
+            stage_name: &str,
+            order: PipelineStageFunctionOrder,
+        ) -> Result<StageProcessorVersion> {
+            let (_, stage) = self.find_stage(stage_name, 0)?;
+            Ok(stage.get_processor(order).get_version())
+        }
+
+        pub fn set_stage_readiness(
+            &self,
+            stage_name: &str,
+            readiness: StageReadiness,
+        ) -> Result<()> {
+            let (_, stage) = self.find_stage(stage_name, 0)?;
+            if stage.get_readiness() != readiness {
+                log::info!(
+                    target: "savant_rs::pipeline",
+                    "Stage {} readiness changed to {:?}",
+                    stage_name,
+                    readiness
+                );
+            }
+            stage.set_readiness(readiness);
+            Ok(())
+        }
+
+        pub fn get_stage_readiness(&self, stage_name: &str) -> Result<StageReadiness> {
+            let (_, stage) = self.find_stage(stage_name, 0)?;
+            Ok(stage.get_readiness())
+        }
+
+        pub fn set_warmup_policy(&self, stage_name: &str, policy: WarmupPolicy) -> Result<()> {
+            let (_, stage) = self.find_stage(stage_name, 0)?;
+            stage.set_warmup_policy(policy);
+            Ok(())
+        }
+
+        pub fn get_warmup_policy(&self, stage_name: &str) -> Result<WarmupPolicy> {
+            let (_, stage) = self.find_stage(stage_name, 0)?;
+            Ok(stage.get_warmup_policy())
+        }
+
+        pub fn is_ready(&self) -> bool {
+            self.stages.iter().all(|s| s.get_readiness().is_ready())
+        }
+
+        pub fn get_health(&self) -> PipelineHealth {
+            let stages = self
+                .stages
+                .iter()
+                .map(|s| StageHealth {
+                    name: s.name.clone(),
+                    readiness: s.get_readiness(),
+                })
+                .collect::<Vec<_>>();
+            PipelineHealth {
+                name: self.get_name(),
+                ready: stages.iter().all(|s| s.readiness.is_ready()),
+                stages,
+            }
+        }
+
+        /// Checks that the destination stage and the stages after it are ready before the
+        /// payloads are sent to it. Otherwise, the payloads are held where they are or deleted
+        /// according to the warm-up policy of the destination stage, and [`StageNotReady`] is
+        /// returned.
+        ///
+        fn check_readiness(&self, dest_index: usize, ids: &[i64]) -> Result<()> {
+            let Some((stage, reason)) =
+                self.stages[dest_index..]
+                    .iter()
+                    .find_map(|s| match s.get_readiness() {
+                        StageReadiness::Ready => None,
+                        StageReadiness::WarmingUp(reason) => Some((s.name.clone(), reason)),
+                    })
+            else {
+                return Ok(());
+            };
+            let policy = self.stages[dest_index].get_warmup_policy();
+            let mut dropped = Vec::new();
+            if policy == WarmupPolicy::Drop {
+                for id in ids {
+                    self.drop_payload(*id)?;
+                    dropped.push(*id);
+                }
+            }
+            log::debug!(
+                target: "savant_rs::pipeline",
+                "Payloads {:?} are not sent to stage {}: stage {} is warming up ({}), policy {:?}",
+                ids,
+                self.stages[dest_index].name,
+                stage,
+                reason,
+                policy
+            );
+            Err(StageNotReady {
+                stage,
+                reason,
+                policy,
+                dropped,
+            }
+            .into())
+        }
+
+        /// Decides for each frame of the stage whether it is kept. The skipped frames are
+        /// deleted like with [`Pipeline::delete`], so their spans are ended and their
+        /// locations are released; the root contexts are returned to the caller.
+        ///
+        pub fn decimate(
+            &self,
+            stage_name: &str,
+            frame_ids: &[i64],
+        ) -> Result<(Vec<i64>, HashMap<i64, Context>)> {
+            let (index, stage) = self.find_stage(stage_name, 0)?;
+            let decimator = stage
+                .get_decimator()
+                .ok_or_else(|| anyhow!("Stage {} has no decimator", stage_name))?;
+            for (id, location) in self.get_stages_for_ids(frame_ids)? {
+                if location != index {
+                    bail!("Frame {} is not in the stage {}", id, stage_name)
+                }
+            }
+            let downstream_len = match decimator.get_strategy() {
+                DecimationStrategy::QueueDepth {
+                    stage: downstream, ..
+                } => self.find_stage(downstream, index + 1)?.1.len(),
+                _ => 0,
+            };
+
+            let mut kept = Vec::with_capacity(frame_ids.len());
+            let mut dropped = HashMap::new();
+            for id in frame_ids {
+                let (frame, _) = stage.get_independent_frame(*id)?;
+                // the kept frames are going to join the downstream queue
+                if decimator.keep(&frame, downstream_len + kept.len()) {
+                    kept.push(*id);
+                } else {
+                    dropped.extend(self.drop_payload(*id)?);
+                }
+            }
+            log::trace!(target: "savant_rs::pipeline", "Stage {} decimated {} of {} frames", stage_name, dropped.len(), frame_ids.len());
+            Ok((kept, dropped))
+        }
+
+        /// Installs the circuit breaker of a frame stage, `None` removes the installed
+        /// breaker. The dead-letter stage must follow the breaker stage.
+        ///
+        pub fn set_circuit_breaker(
+            &self,
+            stage_name: &str,
+            breaker: Option<CircuitBreaker>,
+        ) -> Result<()> {
+            let (index, stage) = self.find_stage(stage_name, 0)?;
+            if stage.stage_type != PipelineStagePayloadType::Frame {
+                bail!(
+                    "Circuit breaker requires a frame stage, stage {} is {:?}",
+                    stage_name,
+                    stage.stage_type
+                )
+            }
+            if let Some(QuarantinePolicy::DeadLetter(dead_letter)) =
+                breaker.as_ref().map(|b| &b.get_config().policy)
+            {
+                let (_, dead_letter_stage) = self.find_stage(dead_letter, index + 1)?;
+                if dead_letter_stage.stage_type != PipelineStagePayloadType::Frame {
+                    bail!(
+                        "The dead-letter stage {} must be a frame stage",
+                        dead_letter
+                    )
+                }
+            }
+            stage.set_circuit_breaker(breaker);
+            Ok(())
+        }
+
+        pub fn get_circuit_breaker(&self, stage_name: &str) -> Result<Option<Arc<CircuitBreaker>>> {
+            let (_, stage) = self.find_stage(stage_name, 0)?;
+            Ok(stage.get_circuit_breaker())
+        }
+
+        /// Asks the circuit breaker of the stage whether each frame passes. The frames of the
+        /// quarantined sources are deleted like with [`Pipeline::delete`] or moved as is to
+        /// the dead-letter stage.
+        ///
+        pub fn isolate(&self, stage_name: &str, frame_ids: &[i64]) -> Result<Isolation> {
+            let (index, stage) = self.find_stage(stage_name, 0)?;
+            let breaker = stage
+                .get_circuit_breaker()
+                .ok_or_else(|| anyhow!("Stage {} has no circuit breaker", stage_name))?;
+            for (id, location) in self.get_stages_for_ids(frame_ids)? {
+                if location != index {
+                    bail!("Frame {} is not in the stage {}", id, stage_name)
+                }
+            }
+            let mut isolation = Isolation::default();
+            for id in frame_ids {
+                let (frame, _) = stage.get_independent_frame(*id)?;
+                if breaker.allow(&frame.get_source_id()) {
+                    isolation.kept.push(*id);
+                    continue;
+                }
+                match &breaker.get_config().policy {
+                    QuarantinePolicy::Drop => isolation.dropped.extend(self.drop_payload(*id)?),
+                    QuarantinePolicy::DeadLetter(_) => isolation.routed.push(*id),
+                }
+            }
+            if let QuarantinePolicy::DeadLetter(dead_letter) = &breaker.get_config().policy {
+                if !isolation.routed.is_empty() {
+                    self.move_as_is(dead_letter, isolation.routed.clone())?;
+                }
+            }
+            Ok(isolation)
+        }
+
+        /// Installs the fault injector of a stage, `None` removes the installed injector.
+        ///
+        #[cfg(feature = "chaos")]
+        pub fn set_fault_injector(
+            &self,
+            stage_name: &str,
+            injector: Option<FaultInjector>,
+        ) -> Result<()> {
+            let (_, stage) = self.find_stage(stage_name, 0)?;
+            stage.set_fault_injector(injector);
+            Ok(())
+        }
+
+        #[cfg(feature = "chaos")]
+        pub fn get_fault_injector(&self, stage_name: &str) -> Result<Option<Arc<FaultInjector>>> {
+            let (_, stage) = self.find_stage(stage_name, 0)?;
+            Ok(stage.get_fault_injector())
+        }
+
+        /// The names of the stages having a fault injector with their injectors.
+        ///
+        #[cfg(feature = "chaos")]
+        pub fn get_fault_injectors(&self) -> Vec<(String, Arc<FaultInjector>)> {
+            self.stages
+                .iter()
+                .filter_map(|s| s.get_fault_injector().map(|i| (s.name.clone(), i)))
+                .collect()
+        }
+
+        /// Rolls the faults of each payload of the stage. The dropped payloads are deleted
+        /// like with [`Pipeline::delete`], the corrupted ones stay in the stage and the call
+        /// returns after the sum of the delays. Without an injector all payloads are kept.
+        ///
+        #[cfg(feature = "chaos")]
+        pub fn inject_faults(
+            &self,
+            stage_name: &str,
+            ids: &[i64],
+        ) -> Result<(Vec<i64>, HashMap<i64, Context>)> {
+            let (index, stage) = self.find_stage(stage_name, 0)?;
+            for (id, location) in self.get_stages_for_ids(ids)? {
+                if location != index {
+                    bail!("Payload {} is not in the stage {}", id, stage_name)
+                }
+            }
+            let Some(injector) = stage.get_fault_injector() else {
+                return Ok((ids.to_vec(), HashMap::new()));
+            };
+
+            let mut kept = Vec::with_capacity(ids.len());
+            let mut dropped = HashMap::new();
+            let mut delay = Duration::ZERO;
+            for id in ids {
+                let faults = injector.roll_faults();
+                if faults.drop {
+                    dropped.extend(self.drop_payload(*id)?);
+                    continue;
+                }
+                if faults.corrupt {
+                    stage.corrupt(*id)?;
+                }
+                delay += faults.delay.unwrap_or_default();
+                kept.push(*id);
+            }
+            log::debug!(target: "savant_rs::pipeline", "Stage {} faults: {} of {} payloads dropped, delayed by {} ms", stage_name, ids.len() - kept.len(), ids.len(), delay.as_millis());
+            if !delay.is_zero() {
+                std::thread::sleep(delay);
+            }
+            Ok((kept, dropped))
+        }
+
+        pub fn get_updater(&self, name: &str) -> Option<UpdaterScope> {
+            self.updaters.read().get(name).cloned()
+        }
+
+        /// Checks the update against the scope of its updater. When updaters are registered,
+        /// the updates without an updater are rejected, e.g. the deserialized ones.
+        ///
+        fn check_updater_scope(&self, update: &VideoFrameUpdate) -> Result<()> {
+            let updaters = self.updaters.read();
+            let Some(updater) = update.get_updater() else {
+                if updaters.is_empty() {
+                    return Ok(());
+                }
+                return Err(UpdaterScopeError::Anonymous.into());
+            };
+            let scope = updaters
+                .get(updater)
+                .ok_or_else(|| UpdaterScopeError::Unregistered(updater.to_string()))?;
+            scope.check(update)?;
+            Ok(())
+        }
+
+        /// Applies the updates of the payload. Updates produced by an updater are applied
+        /// only when they write the namespaces the updater is registered with.
+        ///
         pub fn apply_updates(&self, id: i64) -> Result<()> {
             let stage = self.get_stage_for_id(id)?;
             if let Some(stage) = self.stages.get(stage) {
-                stage.apply_updates(id)
+                stage.apply_checked_updates(id, |update| self.check_updater_scope(update))?;
+                self.events.publish(|| PipelineEvent::UpdateApplied {
+                    id,
+                    stage: stage.name.clone(),
+                });
+                Ok(())
             } else {
                 bail!(
                     "Stage ID={} not found (when applying updates to object {})",
@@ -854,22 +2599,23 @@ pub(super) mod implementation {
             log::trace!(
                 target: "savant_rs::pipeline", "Moving objects {:?} of type {:?} as is from stage {} to stage {}", 
                 object_ids, source_stage.stage_type, source_stage.name, dest_stage_name);
-            let (dest_index, dest_stage) = self.find_stage(dest_stage_name, source_index)?;
+            let (dest_index, dest_stage) = self.find_destination(dest_stage_name, source_index)?;
 
             if source_stage.stage_type != dest_stage.stage_type {
                 bail!("The source stage type for {} ({:?}) must be the same as the destination stage type for {} ({:?})", 
                     source_stage.name, source_stage.stage_type, dest_stage.name, dest_stage.stage_type)
             }
+            self.check_readiness(dest_index, &object_ids)?;
 
             let removed_objects = source_stage_opt
                 .as_ref()
                 .expect("Stage must be defined according to the previous check")
-                .delete_many(&object_ids)?;
+                .delete_many_prioritized(&object_ids)?;
 
             self.update_frame_locations(&object_ids, dest_index);
 
             let mut payloads = Vec::with_capacity(removed_objects.len());
-            for (id, payload) in removed_objects {
+            for (id, payload, priority) in removed_objects {
                 let payload = match payload {
                     PipelinePayload::Frame(frame, updates, ctx, source_index, time) => {
                         self.add_frame_json(&frame, &ctx);
@@ -893,11 +2639,31 @@ pub(super) mod implementation {
                         }
                         PipelinePayload::Batch(batch, updates, new_contexts, source_index, times)
                     }
+                    PipelinePayload::Audio(frame, ctx, source_index, time) => {
+                        ctx.span().end();
+                        let ctx = self.get_stage_span(id, format!("stage/{}", dest_stage_name));
+                        PipelinePayload::Audio(frame, ctx, source_index, time)
+                    }
+                    PipelinePayload::Telemetry(frame, ctx, source_index, time) => {
+                        ctx.span().end();
+                        let ctx = self.get_stage_span(id, format!("stage/{}", dest_stage_name));
+                        PipelinePayload::Telemetry(frame, ctx, source_index, time)
+                    }
                 };
-                payloads.push((id, payload));
+                payloads.push((id, payload, priority));
             }
 
-            dest_stage.add_payloads(payloads)?;
+            let dropped = dest_stage.add_prioritized_payloads(payloads)?;
+            let dropped_ids = dropped.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+            for id in object_ids.iter().filter(|id| !dropped_ids.contains(id)) {
+                self.events.publish(|| PipelineEvent::FrameMoved {
+                    id: *id,
+                    from: source_stage.name.clone(),
+                    to: dest_stage.name.clone(),
+                });
+            }
+            self.discard_dropped(dest_stage, dropped);
+            self.trigger_auto_batch(dest_index);
 
             Ok(())
         }
@@ -918,13 +2684,14 @@ pub(super) mod implementation {
             }
             let source_stage = source_stage_opt.unwrap();
             log::trace!(target: "savant_rs::pipeline", "Moving and packing frames {:?} from stage {} to stage {}", frame_ids, source_stage.name, dest_stage_name);
-            let (dest_index, dest_stage) = self.find_stage(dest_stage_name, source_index)?;
+            let (dest_index, dest_stage) = self.find_destination(dest_stage_name, source_index)?;
 
-            if matches!(source_stage.stage_type, PipelineStagePayloadType::Batch)
-                || matches!(dest_stage.stage_type, PipelineStagePayloadType::Frame)
+            if source_stage.stage_type != PipelineStagePayloadType::Frame
+                || dest_stage.stage_type != PipelineStagePayloadType::Batch
             {
                 bail!("Source stage {} must contain independent frames and destination stage must contain batched frames", source_stage.name)
             }
+            self.check_readiness(dest_index, &frame_ids)?;
 
             let batch_id = self.id_counter.fetch_add(1, Ordering::SeqCst) + 1;
 
@@ -938,12 +2705,14 @@ pub(super) mod implementation {
 
             let mut last_stage: Option<String> = None;
             let mut last_times: Vec<SystemTime> = Vec::with_capacity(batch.frames.len());
-            for id in frame_ids {
-                if let Some(payload) = source_stage_opt
+            let mut batch_priority: Option<i32> = None;
+            for &id in &frame_ids {
+                if let Some((payload, priority)) = source_stage_opt
                     .as_ref()
                     .expect("Stage must be defined according to the previous check")
-                    .delete(id)?
+                    .delete_prioritized(id)?
                 {
+                    batch_priority = Some(batch_priority.map_or(priority, |p| p.max(priority)));
                     match payload {
                         PipelinePayload::Frame(frame, updates, ctx, ls, lt) => {
                             last_stage = ls;
@@ -979,9 +2748,25 @@ pub(super) mod implementation {
 
             let payload =
                 PipelinePayload::Batch(batch, batch_updates, contexts, last_stage, last_times);
-            dest_stage.add_batch_payload(batch_id, payload)?;
+            if let Some(payload) = dest_stage.add_prioritized_batch_payload(
+                batch_id,
+                payload,
+                batch_priority.unwrap_or_default(),
+            )? {
+                self.discard_dropped(dest_stage, vec![(batch_id, payload)]);
+                bail!(
+                    "Batch {} is dropped when entering stage {}",
+                    batch_id,
+                    dest_stage_name
+                )
+            }
             self.frame_locations.write().insert(batch_id, dest_index);
             log::trace!(target: "savant_rs::pipeline", "Created batch {} to stage {}", batch_id, dest_stage_name);
+            self.events.publish(|| PipelineEvent::BatchCreated {
+                id: batch_id,
+                stage: dest_stage.name.clone(),
+                frame_ids,
+            });
             Ok(batch_id)
         }
 
@@ -1001,33 +2786,38 @@ pub(super) mod implementation {
             }
             let source_stage = source_stage_opt.unwrap();
             log::trace!(target: "savant_rs::pipeline", "Moving and unpacking batch {} from stage {} to stage {}", batch_id, source_stage.name, dest_stage_name);
-            let (dest_index, dest_stage) = self.find_stage(dest_stage_name, source_index)?;
+            let (dest_index, dest_stage) = self.find_destination(dest_stage_name, source_index)?;
 
-            if matches!(source_stage.stage_type, PipelineStagePayloadType::Frame)
-                || matches!(dest_stage.stage_type, PipelineStagePayloadType::Batch)
+            if source_stage.stage_type != PipelineStagePayloadType::Batch
+                || dest_stage.stage_type != PipelineStagePayloadType::Frame
             {
                 bail!("Source stage {} must contain batched frames and destination stage must contain independent frames", source_stage.name)
             }
+            self.check_readiness(dest_index, &[batch_id])?;
 
-            let (batch, updates, mut contexts, last_stage, last_times) = if let Some(payload) =
-                source_stage_opt
+            let (batch, updates, mut contexts, last_stage, last_times, priority) =
+                if let Some((payload, priority)) = source_stage_opt
                     .as_ref()
                     .expect("The stage must be defined according to the previous check")
-                    .delete(batch_id)?
-            {
-                match payload {
-                    PipelinePayload::Batch(batch, updates, contexts, last_stage, last_times) => {
-                        (batch, updates, contexts, last_stage, last_times)
+                    .delete_prioritized(batch_id)?
+                {
+                    match payload {
+                        PipelinePayload::Batch(
+                            batch,
+                            updates,
+                            contexts,
+                            last_stage,
+                            last_times,
+                        ) => (batch, updates, contexts, last_stage, last_times, priority),
+                        _ => bail!("Source stage {} must contain batch", source_stage.name),
                     }
-                    _ => bail!("Source stage {} must contain batch", source_stage.name),
-                }
-            } else {
-                bail!("Batch not found in source stage {}", source_stage.name)
-            };
+                } else {
+                    bail!("Batch not found in source stage {}", source_stage.name)
+                };
 
             self.frame_locations.write().remove(&batch_id);
 
-            let frame_ids = batch.frames.keys().cloned().collect::<Vec<_>>();
+            let mut frame_ids = batch.frames.keys().cloned().collect::<Vec<_>>();
             self.update_frame_locations(&frame_ids, dest_index);
 
             let mut payloads = HashMap::with_capacity(batch.frames.len());
@@ -1044,7 +2834,10 @@ pub(super) mod implementation {
                         Vec::new(),
                         ctx,
                         last_stage.clone(),
-                        last_times.first().unwrap_or(&SystemTime::now()).to_owned(),
+                        last_times
+                            .first()
+                            .copied()
+                            .unwrap_or_else(|| self.clock.now()),
                     ),
                 );
             }
@@ -1065,11 +2858,252 @@ pub(super) mod implementation {
                 }
             }
 
-            dest_stage.add_payloads(payloads)?;
+            let dropped = dest_stage.add_prioritized_payloads(
+                payloads
+                    .into_iter()
+                    .map(|(frame_id, payload)| (frame_id, payload, priority)),
+            )?;
+            frame_ids.retain(|id| !dropped.iter().any(|(dropped_id, _)| dropped_id == id));
+            self.events.publish(|| PipelineEvent::BatchUnpacked {
+                id: batch_id,
+                stage: dest_stage.name.clone(),
+                frame_ids: frame_ids.clone(),
+            });
+            self.discard_dropped(dest_stage, dropped);
+            self.trigger_auto_batch(dest_index);
 
             Ok(frame_ids)
         }
 
+        pub fn set_auto_batch(
+            &self,
+            batch_stage_name: &str,
+            source_stage_name: &str,
+            config: AutoBatchConfig,
+        ) -> Result<()> {
+            let (source, source_stage) = self.find_stage(source_stage_name, 0)?;
+            let (dest, dest_stage) = self.find_destination(batch_stage_name, source)?;
+            if source_stage.stage_type != PipelineStagePayloadType::Frame
+                || dest_stage.stage_type != PipelineStagePayloadType::Batch
+            {
+                bail!(
+                    "Source stage {} must contain independent frames and stage {} must contain batched frames",
+                    source_stage_name,
+                    batch_stage_name
+                )
+            }
+            let bypass = match &config.bypass {
+                Some(bypass_stage_name) => {
+                    let (bypass, bypass_stage) =
+                        self.find_destination(bypass_stage_name, source)?;
+                    // the bypassed frames would be moved back to the stage they are taken from
+                    if bypass == source {
+                        bail!(
+                            "Bypass stage {} must differ from the source stage",
+                            bypass_stage_name
+                        )
+                    }
+                    if bypass_stage.stage_type != PipelineStagePayloadType::Frame {
+                        bail!(
+                            "Bypass stage {} must contain independent frames",
+                            bypass_stage_name
+                        )
+                    }
+                    Some(bypass)
+                }
+                None => None,
+            };
+            let mut batchers = self.auto_batchers.write();
+            if batchers
+                .iter()
+                .any(|(d, b)| *d != dest && b.source == source)
+            {
+                bail!(
+                    "Stage {} already feeds the auto-batching of another stage",
+                    source_stage_name
+                )
+            }
+            batchers.insert(
+                dest,
+                Arc::new(AutoBatcher {
+                    source,
+                    bypass,
+                    config,
+                    lock: parking_lot::Mutex::new(()),
+                }),
+            );
+            Ok(())
+        }
+
+        pub fn clear_auto_batch(&self, batch_stage_name: &str) -> Result<bool> {
+            let (dest, _) = self.find_stage(batch_stage_name, 0)?;
+            Ok(self.auto_batchers.write().remove(&dest).is_some())
+        }
+
+        pub fn get_auto_batch(
+            &self,
+            batch_stage_name: &str,
+        ) -> Result<Option<(String, AutoBatchConfig)>> {
+            let (dest, _) = self.find_stage(batch_stage_name, 0)?;
+            Ok(self
+                .auto_batchers
+                .read()
+                .get(&dest)
+                .map(|b| (self.stages[b.source].name.clone(), b.config.clone())))
+        }
+
+        pub fn flush_auto_batches(&self, force: bool) -> Result<Vec<i64>> {
+            let batchers = self
+                .auto_batchers
+                .read()
+                .iter()
+                .map(|(dest, b)| (*dest, b.clone()))
+                .collect::<Vec<_>>();
+            let mut batches = Vec::new();
+            for (dest, batcher) in batchers {
+                match self.auto_batch(dest, &batcher, force) {
+                    Ok(packed) => batches.extend(packed),
+                    Err(e) => log::warn!(
+                        target: "savant_rs::pipeline::auto_batch",
+                        "Failed to pack the frames of stage {}: {}",
+                        self.stages[batcher.source].name,
+                        e
+                    ),
+                }
+            }
+            Ok(batches)
+        }
+
+        /// Packs the frames of the source stage of the batcher while the size or the latency
+        /// is reached, the frames with the higher priority first. The frames skipping the
+        /// inference are moved to the bypass stage first.
+        ///
+        fn auto_batch(&self, dest: usize, batcher: &AutoBatcher, force: bool) -> Result<Vec<i64>> {
+            let _guard = batcher.lock.lock();
+            let source_stage = &self.stages[batcher.source];
+            let dest_stage_name = &self.stages[dest].name;
+            let mut batches = Vec::new();
+            loop {
+                let mut queue = Vec::new();
+                let mut bypassed = Vec::new();
+                for id in source_stage.get_queue() {
+                    let (frame, _) = self.get_independent_frame(id)?;
+                    let hints = frame.get_processing_hints();
+                    if hints.skip_inference && batcher.bypass.is_some() {
+                        bypassed.push(id);
+                    } else {
+                        queue.push((id, hints.roi_list.len().max(1)));
+                    }
+                }
+                if let Some(bypass) = batcher.bypass.filter(|_| !bypassed.is_empty()) {
+                    self.move_as_is(&self.stages[bypass].name, bypassed)?;
+                }
+                if queue.is_empty() {
+                    break;
+                }
+                let regions = queue.iter().map(|(_, r)| r).sum::<usize>();
+                let full = regions >= batcher.config.max_size;
+                let expired = source_stage.get_oldest_entry().is_some_and(|entered| {
+                    self.clock.elapsed(entered) >= batcher.config.max_latency
+                });
+                if !(full || expired || force) {
+                    break;
+                }
+                let mut taken = 0;
+                let ids = queue
+                    .into_iter()
+                    .take_while(|(_, r)| {
+                        let take = taken < batcher.config.max_size;
+                        taken += r;
+                        take
+                    })
+                    .map(|(id, _)| id)
+                    .collect::<Vec<_>>();
+                batches.push(self.move_and_pack_frames(dest_stage_name, ids)?);
+            }
+            Ok(batches)
+        }
+
+        /// Checks the auto-batching fed by the stage the frames entered.
+        ///
+        fn trigger_auto_batch(&self, source: usize) {
+            let batcher = self
+                .auto_batchers
+                .read()
+                .iter()
+                .find(|(_, b)| b.source == source)
+                .map(|(dest, b)| (*dest, b.clone()));
+            let Some((dest, batcher)) = batcher else {
+                return;
+            };
+            if let Err(e) = self.auto_batch(dest, &batcher, false) {
+                log::warn!(
+                    target: "savant_rs::pipeline::auto_batch",
+                    "Failed to pack the frames of stage {}: {}",
+                    self.stages[source].name,
+                    e
+                );
+            }
+        }
+
+        pub fn move_matching(
+            &self,
+            matched_stage_name: &str,
+            unmatched_stage_name: &str,
+            query: &MatchQuery,
+            frame_ids: Vec<i64>,
+        ) -> Result<(Vec<i64>, Vec<i64>)> {
+            let source_index = self.check_ids_in_the_same_stage(&frame_ids)?;
+            let source_stage = &self.stages[source_index];
+            if source_stage.stage_type != PipelineStagePayloadType::Frame {
+                bail!(
+                    "Source stage {} must contain independent frames",
+                    source_stage.name
+                )
+            }
+            let mut dest_indices = Vec::with_capacity(2);
+            for dest_stage_name in [matched_stage_name, unmatched_stage_name] {
+                let (dest_index, dest_stage) =
+                    self.find_destination(dest_stage_name, source_index)?;
+                if dest_stage.stage_type != PipelineStagePayloadType::Frame {
+                    bail!(
+                        "Destination stage {} must contain independent frames",
+                        dest_stage_name
+                    )
+                }
+                dest_indices.push(dest_index);
+            }
+
+            let mut matched = Vec::new();
+            let mut unmatched = Vec::new();
+            for id in frame_ids {
+                let objects = source_stage.access_objects(id, query)?;
+                if objects.values().any(|objects| !objects.is_empty()) {
+                    matched.push(id);
+                } else {
+                    unmatched.push(id);
+                }
+            }
+            log::trace!(
+                target: "savant_rs::pipeline",
+                "Moving matching frames {:?} to stage {} and frames {:?} to stage {}",
+                matched, matched_stage_name, unmatched, unmatched_stage_name
+            );
+            // both destinations are checked before any frame is moved
+            for (dest_index, ids) in dest_indices.into_iter().zip([&matched, &unmatched]) {
+                if !ids.is_empty() {
+                    self.check_readiness(dest_index, ids)?;
+                }
+            }
+            if !matched.is_empty() {
+                self.move_as_is(matched_stage_name, matched.clone())?;
+            }
+            if !unmatched.is_empty() {
+                self.move_as_is(unmatched_stage_name, unmatched.clone())?;
+            }
+            Ok((matched, unmatched))
+        }
+
         pub fn access_objects(
             &self,
             frame_id: i64,
@@ -1080,10 +3114,58 @@ pub(super) mod implementation {
             if stage_opt.is_none() {
                 bail!("Stage ID={} not found", stage);
             }
-
-            stage_opt
-                .map(|stage| stage.access_objects(frame_id, query))
-                .unwrap()
+
+            stage_opt
+                .map(|stage| stage.access_objects(frame_id, query))
+                .unwrap()
+        }
+
+        pub fn get_source_statistics(&self) -> Vec<SourceStatistics> {
+            self.source_stats.measure(self.clock.now_ms())
+        }
+
+        /// Evaluates the query on the objects of the frames in the pipeline without moving
+        /// them, e.g. to check whether any frame in flight carries a label. Up to
+        /// `max_frames` frames (at most [`MAX_SCANNED_FRAMES`]) with the lowest ids are
+        /// checked, the query is evaluated without the locks of the stages. The scans are
+        /// limited to one per [`PipelineConfiguration::frame_scan_interval`], the scans
+        /// called more often fail.
+        ///
+        /// Returns the frames having matching objects, ordered by the id.
+        ///
+        pub fn scan_frames(
+            &self,
+            query: &MatchQuery,
+            max_frames: usize,
+        ) -> Result<Vec<(i64, Vec<BorrowedVideoObject>)>> {
+            {
+                let mut last_scan = self.last_frame_scan.write();
+                let interval = self.configuration.frame_scan_interval;
+                if let Some(elapsed) = last_scan.map(|t| t.elapsed()) {
+                    if elapsed < interval {
+                        bail!(
+                            "The frames were scanned {} ms ago, the scans are limited to one per {} ms",
+                            elapsed.as_millis(),
+                            interval.as_millis()
+                        );
+                    }
+                }
+                *last_scan = Some(Instant::now());
+            }
+            let mut frames = self
+                .stages
+                .iter()
+                .flat_map(|s| s.get_frames())
+                .collect::<Vec<_>>();
+            frames.sort_by_key(|(id, _)| *id);
+            frames.truncate(max_frames.min(MAX_SCANNED_FRAMES));
+            Ok(frames
+                .into_iter()
+                .filter_map(|(id, frame)| {
+                    let objects = frame.access_objects(query);
+                    (!objects.is_empty()).then_some((id, objects))
+                })
+                .collect())
         }
     }
 
@@ -1134,12 +3216,26 @@ pub(super) mod implementation {
 
         use opentelemetry::trace::TraceContextExt;
 
-        use crate::pipeline::implementation::{create_test_pipeline, PipelineStagePayloadType};
+        use crate::clock::{self, ClockMode};
+        use crate::match_query::{IntExpression, MatchQuery, StringExpression};
+        use crate::pipeline::decimator::{DecimationStrategy, Decimator};
+        use crate::pipeline::implementation::{
+            create_test_pipeline, Pipeline, PipelineConfiguration, PipelineConfigurationBuilder,
+            PipelineStagePayloadType,
+        };
+        use crate::pipeline::source_config::SourceConfigResolver;
+        use crate::pipeline::updaters::UpdaterScopeError;
+        use crate::pipeline::user_code::UserCodeTime;
         use crate::primitives::attribute_value::AttributeValue;
+        use crate::primitives::audio_frame::{AudioFrame, AudioSampleFormat};
+        use crate::primitives::frame::{VideoFrameContent, VideoFrameProxy};
         use crate::primitives::frame_update::VideoFrameUpdate;
+        use crate::primitives::frozen_objects::FrozenObjectsError;
+        use crate::primitives::object::{ObjectOperations, OrphanPolicy};
+        use crate::primitives::telemetry_frame::TelemetryFrame;
         use crate::primitives::{Attribute, WithAttributes};
         use crate::telemetry::{init, TelemetryConfiguration};
-        use crate::test::gen_frame;
+        use crate::test::{gen_empty_frame, gen_frame, gen_object};
 
         static INIT: Once = Once::new();
 
@@ -1191,6 +3287,116 @@ pub(super) mod implementation {
             Ok(())
         }
 
+        #[test]
+        fn test_scan_frames() -> anyhow::Result<()> {
+            let pipeline = create_test_pipeline()?;
+            let first = pipeline.add_frame("input", gen_frame())?;
+            let second = pipeline.add_frame("input", gen_empty_frame())?;
+            let third = pipeline.add_frame("input", gen_frame())?;
+            let batch = pipeline.move_and_pack_frames("proc1", vec![third])?;
+
+            let found = pipeline.scan_frames(&MatchQuery::Id(IntExpression::EQ(0)), 10)?;
+            let ids = found.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+            assert_eq!(ids, vec![first, third]);
+            assert_eq!(found[0].1.len(), 1);
+            // the scans are rate-limited
+            assert!(pipeline
+                .scan_frames(&MatchQuery::Id(IntExpression::EQ(0)), 10)
+                .is_err());
+
+            *pipeline.last_frame_scan.write() = None;
+            let found = pipeline.scan_frames(&MatchQuery::Id(IntExpression::EQ(0)), 2)?;
+            assert_eq!(found.len(), 1);
+            assert_eq!(pipeline.get_stage_queue_len("proc1")?, 1);
+            pipeline.delete(first)?;
+            pipeline.delete(second)?;
+            pipeline.delete(batch)?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_audio_frames() -> anyhow::Result<()> {
+            let pipeline = Pipeline::new(
+                vec![
+                    (
+                        "video".to_string(),
+                        PipelineStagePayloadType::Frame,
+                        None,
+                        None,
+                    ),
+                    (
+                        "audio-in".to_string(),
+                        PipelineStagePayloadType::Audio,
+                        None,
+                        None,
+                    ),
+                    (
+                        "audio-out".to_string(),
+                        PipelineStagePayloadType::Audio,
+                        None,
+                        None,
+                    ),
+                ],
+                PipelineConfiguration::default(),
+            )?;
+            let frame = AudioFrame::new(
+                "mic",
+                0,
+                AudioSampleFormat::S16,
+                16000,
+                1,
+                160,
+                VideoFrameContent::None,
+            );
+            assert!(pipeline.add_audio_frame("video", frame.clone()).is_err());
+            assert!(pipeline.add_frame("audio-in", gen_frame()).is_err());
+            let id = pipeline.add_audio_frame("audio-in", frame.clone())?;
+            pipeline.move_as_is("audio-out", vec![id])?;
+            assert_eq!(pipeline.get_stage_queue_len("audio-out")?, 1);
+            assert_eq!(pipeline.get_audio_frame(id)?.0, frame);
+            assert!(pipeline.move_and_pack_frames("video", vec![id]).is_err());
+            pipeline.delete(id)?;
+            assert_eq!(pipeline.get_stage_queue_len("audio-out")?, 0);
+            Ok(())
+        }
+
+        #[test]
+        fn test_telemetry_frames() -> anyhow::Result<()> {
+            let pipeline = Pipeline::new(
+                vec![
+                    (
+                        "radar".to_string(),
+                        PipelineStagePayloadType::Telemetry,
+                        None,
+                        None,
+                    ),
+                    (
+                        "fusion".to_string(),
+                        PipelineStagePayloadType::Telemetry,
+                        None,
+                        None,
+                    ),
+                    (
+                        "audio".to_string(),
+                        PipelineStagePayloadType::Audio,
+                        None,
+                        None,
+                    ),
+                ],
+                PipelineConfiguration::default(),
+            )?;
+            let frame = TelemetryFrame::new("radar-1", "radar", 0);
+            assert!(pipeline.add_frame("radar", gen_frame()).is_err());
+            let id = pipeline.add_telemetry_frame("radar", frame.clone())?;
+            assert!(pipeline.move_as_is("audio", vec![id]).is_err());
+            pipeline.move_as_is("fusion", vec![id])?;
+            assert_eq!(pipeline.get_telemetry_frame(id)?.0, frame);
+            assert!(pipeline.get_audio_frame(id).is_err());
+            pipeline.delete(id)?;
+            assert_eq!(pipeline.get_stage_queue_len("fusion")?, 0);
+            Ok(())
+        }
+
         #[test]
         fn test_frame_to_batch() -> anyhow::Result<()> {
             let pipeline = create_test_pipeline()?;
@@ -1249,6 +3455,197 @@ pub(super) mod implementation {
             Ok(())
         }
 
+        #[test]
+        fn test_delete_batch() -> anyhow::Result<()> {
+            let pipeline = create_test_pipeline()?;
+            let id1 = pipeline.add_frame("input", gen_frame())?;
+            let id2 = pipeline.add_frame("input", gen_frame())?;
+            let batch_id = pipeline.move_and_pack_frames("proc1", vec![id1, id2])?;
+            let mut ids = pipeline.delete(batch_id)?.into_keys().collect::<Vec<_>>();
+            ids.sort_unstable();
+            assert_eq!(ids, vec![id1, id2]);
+            assert_eq!(pipeline.get_stage_queue_len("proc1")?, 0);
+            assert_eq!(pipeline.get_id_locations_len(), 0);
+            assert_eq!(pipeline.get_root_spans_len(), 0);
+            Ok(())
+        }
+
+        #[test]
+        fn test_priorities() -> anyhow::Result<()> {
+            let pipeline = create_test_pipeline()?;
+            let replay = pipeline.add_frame("input", gen_frame())?;
+            let live = pipeline.add_frame_with_priority("input", gen_frame(), 10)?;
+            assert_eq!(pipeline.get_stage_queue("input")?, vec![live, replay]);
+
+            pipeline.move_as_is("output", vec![replay, live])?;
+            assert_eq!(pipeline.get_stage_queue("output")?, vec![live, replay]);
+            assert_eq!(pipeline.get_priority(live)?, 10);
+
+            let first = pipeline.add_frame("input", gen_frame())?;
+            let second = pipeline.add_frame_with_priority("input", gen_frame(), 5)?;
+            let low = pipeline.move_and_pack_frames("proc1", vec![first])?;
+            let high = pipeline.move_and_pack_frames("proc1", vec![second])?;
+            assert_eq!(pipeline.get_stage_queue("proc1")?, vec![high, low]);
+            pipeline.set_priority(low, 20)?;
+            assert_eq!(pipeline.get_stage_queue("proc1")?, vec![low, high]);
+            pipeline.move_as_is("proc2", vec![low])?;
+            pipeline.move_and_unpack_batch("output", low)?;
+            assert_eq!(
+                pipeline.get_stage_queue("output")?,
+                vec![first, live, replay]
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn test_route() -> anyhow::Result<()> {
+            let stage = |name: &str, stage_type| (name.to_string(), stage_type, None, None);
+            let stages = || {
+                vec![
+                    stage("classify", PipelineStagePayloadType::Frame),
+                    stage("person", PipelineStagePayloadType::Frame),
+                    stage("vehicle", PipelineStagePayloadType::Batch),
+                    stage("vehicle-out", PipelineStagePayloadType::Frame),
+                ]
+            };
+            let edge = |from: &str, name: &str, to: &str| {
+                (from.to_string(), name.to_string(), to.to_string())
+            };
+            let configuration = |edges: Vec<(String, String, String)>| {
+                PipelineConfigurationBuilder::default()
+                    .edges(edges)
+                    .build()
+                    .unwrap()
+            };
+            let pipeline = Pipeline::new(
+                stages(),
+                configuration(vec![
+                    edge("classify", "person", "person"),
+                    edge("classify", "vehicle", "vehicle"),
+                ]),
+            )?;
+            assert_eq!(
+                pipeline.get_edges("classify")?,
+                vec![
+                    ("person".to_string(), "person".to_string()),
+                    ("vehicle".to_string(), "vehicle".to_string())
+                ]
+            );
+
+            let person = pipeline.add_frame("classify", gen_frame())?;
+            let vehicle = pipeline.add_frame("classify", gen_frame())?;
+            assert_eq!(pipeline.route(person, "person")?, vec![person]);
+            let batch = pipeline.route(vehicle, "vehicle")?;
+            assert_eq!(pipeline.get_stage_queue_len("person")?, 1);
+            assert_eq!(pipeline.get_stage_queue_len("vehicle")?, 1);
+            assert!(pipeline.route(person, "vehicle").is_err());
+            // the branches are isolated, a stage the edges lead to accepts only their sources
+            assert!(pipeline
+                .move_and_pack_frames("vehicle", vec![person])
+                .is_err());
+            assert_eq!(pipeline.get_stage_queue_len("person")?, 1);
+
+            // the stages without the edges move the payloads forward as before
+            assert_eq!(
+                pipeline.move_and_unpack_batch("vehicle-out", batch[0])?,
+                vec![vehicle]
+            );
+            let other = pipeline.add_frame("classify", gen_frame())?;
+            assert!(pipeline.move_as_is("vehicle-out", vec![other]).is_err());
+
+            assert!(Pipeline::new(
+                stages(),
+                configuration(vec![edge("person", "back", "classify")])
+            )
+            .is_err());
+            assert!(Pipeline::new(
+                stages(),
+                configuration(vec![
+                    edge("classify", "same", "person"),
+                    edge("classify", "same", "vehicle-out")
+                ])
+            )
+            .is_err());
+            Ok(())
+        }
+
+        #[test]
+        fn test_move_matching() -> anyhow::Result<()> {
+            let pipeline = create_test_pipeline()?;
+            let with_objects = pipeline.add_frame("input", gen_frame())?;
+            let empty = pipeline.add_frame("input", gen_empty_frame())?;
+            let query = MatchQuery::Namespace(StringExpression::EQ("test".to_string()));
+            assert!(pipeline
+                .move_matching("output", "proc1", &query, vec![with_objects, empty])
+                .is_err());
+            assert_eq!(pipeline.get_stage_queue_len("input")?, 2);
+
+            let (matched, unmatched) =
+                pipeline.move_matching("output", "output", &query, vec![with_objects, empty])?;
+            assert_eq!(matched, vec![with_objects]);
+            assert_eq!(unmatched, vec![empty]);
+            assert_eq!(pipeline.get_stage_queue_len("output")?, 2);
+            Ok(())
+        }
+
+        #[test]
+        fn test_move_matching_to_distinct_stages() -> anyhow::Result<()> {
+            use crate::pipeline::readiness::StageReadiness;
+
+            let pipeline = Pipeline::new(
+                vec![
+                    (
+                        "input".to_string(),
+                        PipelineStagePayloadType::Frame,
+                        None,
+                        None,
+                    ),
+                    (
+                        "matched".to_string(),
+                        PipelineStagePayloadType::Frame,
+                        None,
+                        None,
+                    ),
+                    (
+                        "unmatched".to_string(),
+                        PipelineStagePayloadType::Frame,
+                        None,
+                        None,
+                    ),
+                ],
+                PipelineConfiguration::default(),
+            )?;
+            let with_objects = pipeline.add_frame("input", gen_frame())?;
+            let empty = pipeline.add_frame("input", gen_empty_frame())?;
+            let query = MatchQuery::Namespace(StringExpression::EQ("test".to_string()));
+
+            // nothing is moved when one of the destinations is not ready
+            pipeline.set_stage_readiness(
+                "unmatched",
+                StageReadiness::WarmingUp("loading".to_string()),
+            )?;
+            assert!(pipeline
+                .move_matching("matched", "unmatched", &query, vec![with_objects, empty])
+                .is_err());
+            assert_eq!(pipeline.get_stage_queue_len("input")?, 2);
+            assert_eq!(pipeline.get_stage_queue_len("matched")?, 0);
+
+            pipeline.set_stage_readiness("unmatched", StageReadiness::Ready)?;
+            let (matched, unmatched) = pipeline.move_matching(
+                "matched",
+                "unmatched",
+                &query,
+                vec![with_objects, empty],
+            )?;
+            assert_eq!(matched, vec![with_objects]);
+            assert_eq!(unmatched, vec![empty]);
+            assert_eq!(pipeline.get_stage_queue_len("matched")?, 1);
+            assert_eq!(pipeline.get_stage_queue_len("unmatched")?, 1);
+            assert_eq!(pipeline.get_stage_for_id(with_objects)?, 1);
+            assert_eq!(pipeline.get_stage_for_id(empty)?, 2);
+            Ok(())
+        }
+
         #[test]
         fn test_frame_to_frame() -> anyhow::Result<()> {
             let pipeline = create_test_pipeline()?;
@@ -1284,6 +3681,786 @@ pub(super) mod implementation {
             Ok(())
         }
 
+        #[test]
+        fn test_frozen_objects() -> anyhow::Result<()> {
+            let pipeline = Pipeline::new(
+                vec![
+                    (
+                        "detection".to_string(),
+                        PipelineStagePayloadType::Frame,
+                        None,
+                        None,
+                    ),
+                    (
+                        "tracking".to_string(),
+                        PipelineStagePayloadType::Frame,
+                        None,
+                        None,
+                    ),
+                ],
+                PipelineConfigurationBuilder::default()
+                    .freeze_objects(vec![(
+                        "detection".to_string(),
+                        Some("detector".to_string()),
+                    )])
+                    .build()?,
+            )?;
+            let id = pipeline.add_frame("detection", gen_empty_frame())?;
+            let mut object = gen_object(1);
+            object.set_namespace("detector");
+            let mut update = VideoFrameUpdate::default();
+            update.add_object(object.clone(), None);
+            pipeline.add_frame_update(id, update.clone())?;
+            pipeline.apply_updates(id)?;
+            pipeline.clear_updates(id)?;
+
+            pipeline.move_as_is("tracking", vec![id])?;
+            pipeline.add_frame_update(id, update)?;
+            let err = pipeline
+                .apply_updates(id)
+                .unwrap_err()
+                .downcast::<FrozenObjectsError>()?;
+            assert_eq!(err.violator, "tracking");
+            assert_eq!(err.stage, "detection");
+            let (frame, _) = pipeline.get_independent_frame(id)?;
+            assert_eq!(frame.get_all_objects().len(), 1);
+            Ok(())
+        }
+
+        #[test]
+        fn test_prune_on_egress() -> anyhow::Result<()> {
+            let pipeline = Pipeline::new(
+                vec![
+                    (
+                        "input".to_string(),
+                        PipelineStagePayloadType::Frame,
+                        None,
+                        None,
+                    ),
+                    (
+                        "output".to_string(),
+                        PipelineStagePayloadType::Frame,
+                        None,
+                        None,
+                    ),
+                ],
+                PipelineConfigurationBuilder::default()
+                    .prune_objects(vec![(
+                        "input".to_string(),
+                        MatchQuery::Id(IntExpression::EQ(0)),
+                        OrphanPolicy::Cascade,
+                    )])
+                    .build()?,
+            )?;
+            let id = pipeline.add_frame("input", gen_frame())?;
+            let (frame, _) = pipeline.get_independent_frame(id)?;
+            assert_eq!(frame.get_all_objects().len(), 3);
+            pipeline.move_as_is("output", vec![id])?;
+            assert!(frame.get_all_objects().is_empty());
+            Ok(())
+        }
+
+        #[test]
+        fn test_updater_scope() -> anyhow::Result<()> {
+            let pipeline = create_test_pipeline()?;
+            pipeline.register_updater("lpr", &["lpr*"])?;
+            let id = pipeline.add_frame("input", gen_frame())?;
+
+            let mut update = get_update();
+            update.set_updater(Some("lpr"));
+            pipeline.add_frame_update(id, update)?;
+            let err = pipeline
+                .apply_updates(id)
+                .unwrap_err()
+                .downcast::<UpdaterScopeError>()?;
+            assert!(matches!(err, UpdaterScopeError::OutOfScope { .. }));
+            pipeline.clear_updates(id)?;
+
+            let mut update = VideoFrameUpdate::default();
+            update.set_updater(Some("lpr"));
+            update.add_frame_attribute(Attribute::persistent("lpr", "plate", vec![], &None, false));
+            pipeline.add_frame_update(id, update.clone())?;
+            pipeline.apply_updates(id)?;
+            pipeline.clear_updates(id)?;
+
+            update.set_updater(Some("unknown"));
+            pipeline.add_frame_update(id, update.clone())?;
+            assert!(pipeline.apply_updates(id).is_err());
+            pipeline.clear_updates(id)?;
+
+            update.set_updater(None);
+            pipeline.add_frame_update(id, update)?;
+            let err = pipeline
+                .apply_updates(id)
+                .unwrap_err()
+                .downcast::<UpdaterScopeError>()?;
+            assert_eq!(err, UpdaterScopeError::Anonymous);
+
+            let (frame, _) = pipeline.get_independent_frame(id)?;
+            assert!(frame.get_attribute("lpr", "plate").is_some());
+            assert!(frame.get_attribute("update", "attribute").is_none());
+            Ok(())
+        }
+
+        #[test]
+        fn test_source_config() -> anyhow::Result<()> {
+            let pipeline = create_test_pipeline()?;
+            let id = pipeline.add_frame("input", gen_frame())?;
+            let (frame, _) = pipeline.get_independent_frame(id)?;
+            assert!(frame.get_source_config().is_none());
+
+            let resolver = SourceConfigResolver::new(
+                serde_json::json!({"threshold": 0.5}),
+                None,
+                Duration::from_secs(60),
+            )?;
+            resolver.set_override("test", serde_json::json!({"threshold": 0.8}))?;
+            pipeline.set_source_config_resolver(Some(resolver));
+            let id = pipeline.add_frame("input", gen_frame())?;
+            let (frame, _) = pipeline.get_independent_frame(id)?;
+            let config = frame.get_source_config().unwrap();
+            assert_eq!(config.get_source_id(), "test");
+            assert_eq!(config.get_f64("threshold"), Some(0.8));
+            Ok(())
+        }
+
+        #[test]
+        #[serial_test::serial]
+        fn test_stage_budget() -> anyhow::Result<()> {
+            let pipeline = Pipeline::new(
+                vec![
+                    (
+                        "input".to_string(),
+                        PipelineStagePayloadType::Frame,
+                        None,
+                        None,
+                    ),
+                    (
+                        "output".to_string(),
+                        PipelineStagePayloadType::Frame,
+                        None,
+                        None,
+                    ),
+                ],
+                PipelineConfigurationBuilder::default()
+                    .stage_budgets(vec![("input".to_string(), Duration::from_millis(5))])
+                    .clock_mode(Some(ClockMode::Backfill { origin_ms: 0 }))
+                    .build()?,
+            )?;
+            let frame_at = |pts_ms| {
+                let mut frame = gen_frame();
+                frame.set_time_base((1, 1000));
+                frame.set_pts(pts_ms);
+                frame
+            };
+            let fast = pipeline.add_frame("input", frame_at(0))?;
+            pipeline.move_as_is("output", vec![fast])?;
+            let slow = pipeline.add_frame("input", frame_at(1))?;
+            // the frame moves the time of the pipeline past the budget of the slow frame
+            let tick = pipeline.add_frame("output", frame_at(10))?;
+            pipeline.move_as_is("output", vec![slow])?;
+            for id in [fast, slow, tick] {
+                pipeline.delete(id)?;
+            }
+            assert_eq!(pipeline.stages[0].stat.lock().0.budget_overrun_counter, 1);
+            assert_eq!(pipeline.stages[1].stat.lock().0.budget_overrun_counter, 0);
+            Ok(())
+        }
+
+        #[test]
+        #[serial_test::serial]
+        fn test_pipeline_clock() -> anyhow::Result<()> {
+            let pipeline = Pipeline::new(
+                vec![
+                    (
+                        "input".to_string(),
+                        PipelineStagePayloadType::Frame,
+                        None,
+                        None,
+                    ),
+                    (
+                        "output".to_string(),
+                        PipelineStagePayloadType::Frame,
+                        None,
+                        None,
+                    ),
+                ],
+                PipelineConfigurationBuilder::default()
+                    .stage_budgets(vec![("input".to_string(), Duration::from_millis(5))])
+                    .clock_mode(Some(ClockMode::Backfill { origin_ms: 0 }))
+                    .build()?,
+            )?;
+            let mut first = gen_frame();
+            first.set_time_base((1, 1000));
+            first.set_pts(0);
+            let mut second = gen_frame();
+            second.set_time_base((1, 1000));
+            second.set_pts(1000);
+            let first = pipeline.add_frame("input", first)?;
+            // the second frame moves the time of the pipeline by a second
+            let second = pipeline.add_frame("input", second)?;
+            pipeline.move_as_is("output", vec![first, second])?;
+            assert_eq!(pipeline.stages[0].stat.lock().0.budget_overrun_counter, 1);
+            assert_eq!(pipeline.clock.now_ms(), 1000);
+            // the process clock is not affected
+            assert!(!clock::is_backfill());
+            pipeline.delete(first)?;
+            pipeline.delete(second)?;
+            Ok(())
+        }
+
+        #[test]
+        #[serial_test::serial]
+        fn test_rates() -> anyhow::Result<()> {
+            let pipeline = create_test_pipeline()?;
+            for _ in 0..3 {
+                pipeline.add_frame("input", gen_frame())?;
+            }
+            let rates = pipeline.rates();
+            let input = rates
+                .iter()
+                .filter(|r| r.stage_name == "input")
+                .collect::<Vec<_>>();
+            assert_eq!(input.len(), 2);
+            assert_eq!(input[0].source_id, None);
+            assert_eq!(input[0].measurement.frames, 3);
+            assert_eq!(input[1].source_id, Some(gen_frame().get_source_id()));
+            assert!(rates
+                .iter()
+                .filter(|r| r.stage_name != "input")
+                .all(|r| r.source_id.is_none() && r.measurement.frames == 0));
+            Ok(())
+        }
+
+        #[test]
+        fn test_record_user_code_time() -> anyhow::Result<()> {
+            let pipeline = create_test_pipeline()?;
+            let id = pipeline.add_frame("input", gen_frame())?;
+            let time = UserCodeTime {
+                total: Duration::from_millis(10),
+                rust: Duration::from_millis(3),
+                gil_wait: Duration::from_millis(1),
+            };
+            pipeline.record_user_code_time("input", &[id], time)?;
+            pipeline.record_user_code_time(
+                "input",
+                &[id],
+                UserCodeTime {
+                    total: Duration::from_millis(3),
+                    rust: Duration::from_millis(1),
+                    ..Default::default()
+                },
+            )?;
+            let stat = pipeline.stages[0].get_stat();
+            let stat = &stat.lock().0;
+            assert_eq!(stat.user_code_time.calls, 2);
+            assert_eq!(stat.user_code_time.total, Duration::from_millis(8));
+            assert_eq!(stat.user_code_time.max, Duration::from_millis(6));
+            assert_eq!(stat.rust_overhead_time.total, Duration::from_millis(4));
+            assert_eq!(stat.gil_wait_time.total, Duration::from_millis(1));
+            assert!(pipeline
+                .record_user_code_time("missing", &[id], time)
+                .is_err());
+            Ok(())
+        }
+
+        #[test]
+        fn test_decimate() -> anyhow::Result<()> {
+            let pipeline = create_test_pipeline()?;
+            assert!(pipeline
+                .set_decimator(
+                    "input",
+                    Some(Decimator::new(DecimationStrategy::QueueDepth {
+                        stage: "input".to_string(),
+                        max_len: 1,
+                    })?)
+                )
+                .is_err());
+            pipeline.set_decimator(
+                "input",
+                Some(Decimator::new(DecimationStrategy::EveryNth(2))?),
+            )?;
+            let ids = (0..4)
+                .map(|_| pipeline.add_frame("input", gen_frame()))
+                .collect::<Result<Vec<_>, _>>()?;
+            let (kept, dropped) = pipeline.decimate("input", &ids)?;
+            assert_eq!(kept, vec![ids[0], ids[2]]);
+            assert_eq!(dropped.len(), 2);
+            assert!(dropped.contains_key(&ids[1]));
+            assert_eq!(pipeline.get_id_locations_len(), 2);
+            assert_eq!(pipeline.root_spans.read().len(), 2);
+            assert!(pipeline.decimate("proc1", &kept).is_err());
+            Ok(())
+        }
+
+        #[test]
+        fn test_replace_stage_processor() -> anyhow::Result<()> {
+            use crate::pipeline::stage::PipelineStage;
+            use crate::pipeline::{
+                PipelinePayload, PipelineStageFunction, PipelineStageFunctionOrder,
+            };
+            use crate::primitives::attribute_value::AttributeValueVariant;
+            use std::sync::atomic::{AtomicBool, Ordering};
+            use std::sync::{Arc, Barrier};
+
+            struct Tagger {
+                pipeline: Option<crate::pipeline::Pipeline>,
+                version: i64,
+                started: Option<Arc<Barrier>>,
+                finished: Arc<AtomicBool>,
+            }
+
+            impl PipelineStageFunction for Tagger {
+                fn set_pipeline(&mut self, _: crate::pipeline::Pipeline) {}
+                fn get_pipeline(&self) -> &Option<crate::pipeline::Pipeline> {
+                    &self.pipeline
+                }
+                fn call(
+                    &self,
+                    _: i64,
+                    _: &PipelineStage,
+                    _: PipelineStageFunctionOrder,
+                    payload: &mut PipelinePayload,
+                ) -> anyhow::Result<()> {
+                    if let Some(started) = &self.started {
+                        started.wait();
+                        // keeps the call in flight while the replacement is queued
+                        sleep(Duration::from_millis(50));
+                    }
+                    if let PipelinePayload::Frame(frame, ..) = payload {
+                        frame.clone().set_persistent_attribute(
+                            "processor",
+                            "version",
+                            &None,
+                            false,
+                            vec![AttributeValue::integer(self.version, None)],
+                        );
+                    }
+                    self.finished.store(true, Ordering::SeqCst);
+                    Ok(())
+                }
+            }
+
+            fn tagged_version(pipeline: &Pipeline, id: i64) -> anyhow::Result<Option<i64>> {
+                let (frame, _) = pipeline.get_independent_frame(id)?;
+                Ok(frame.get_attribute("processor", "version").and_then(|a| {
+                    match a.values[0].get() {
+                        AttributeValueVariant::Integer(v) => Some(*v),
+                        _ => None,
+                    }
+                }))
+            }
+
+            let pipeline = Arc::new(create_test_pipeline()?);
+            let order = PipelineStageFunctionOrder::Ingress;
+            let version = pipeline.get_stage_processor_version("input", order)?;
+            assert_eq!(version.version, 0);
+            assert!(!version.installed);
+
+            let started = Arc::new(Barrier::new(2));
+            let finished = Arc::new(AtomicBool::new(false));
+            let version = pipeline.replace_stage_processor(
+                "input",
+                order,
+                Some(Box::new(Tagger {
+                    pipeline: None,
+                    version: 1,
+                    started: Some(started.clone()),
+                    finished: finished.clone(),
+                })),
+            )?;
+            assert_eq!(version.version, 1);
+            assert!(version.installed);
+            assert!(pipeline.get_topology().stages[0].has_ingress_function);
+
+            let adder = {
+                let pipeline = pipeline.clone();
+                std::thread::spawn(move || pipeline.add_frame("input", gen_frame()))
+            };
+            // the replacement starts while the previous function is in flight
+            started.wait();
+            pipeline.replace_stage_processor(
+                "input",
+                order,
+                Some(Box::new(Tagger {
+                    pipeline: None,
+                    version: 2,
+                    started: None,
+                    finished: Arc::new(AtomicBool::new(false)),
+                })),
+            )?;
+            // the replacement waits for the in-flight call of the previous function
+            assert!(finished.load(Ordering::SeqCst));
+            let first = adder.join().unwrap()?;
+            assert_eq!(tagged_version(&pipeline, first)?, Some(1));
+            let second = pipeline.add_frame("input", gen_frame())?;
+            assert_eq!(tagged_version(&pipeline, second)?, Some(2));
+
+            let version = pipeline.replace_stage_processor("input", order, None)?;
+            assert_eq!(version.version, 3);
+            assert!(!version.installed);
+            assert!(version.replaced_at.is_some());
+            let third = pipeline.add_frame("input", gen_frame())?;
+            assert_eq!(tagged_version(&pipeline, third)?, None);
+            assert!(pipeline
+                .replace_stage_processor("unknown", order, None)
+                .is_err());
+            Ok(())
+        }
+
+        #[test]
+        fn test_content_encoding() -> anyhow::Result<()> {
+            use crate::primitives::content_encoding::ContentEncoding;
+            use crate::primitives::raw_content::InternalFrame;
+
+            fn encoding(frame: &VideoFrameProxy) -> Option<ContentEncoding> {
+                match frame.get_content().as_ref() {
+                    VideoFrameContent::Internal(internal) => internal.get_encoding(),
+                    _ => None,
+                }
+            }
+
+            let pipeline = create_test_pipeline()?;
+            assert!(pipeline
+                .set_content_encoding("unknown", Some(ContentEncoding::Lz4))
+                .is_err());
+            pipeline.set_content_encoding("input", Some(ContentEncoding::Lz4))?;
+            pipeline.set_content_encoding("proc1", Some(ContentEncoding::Zstd))?;
+            assert_eq!(
+                pipeline.get_content_encoding("input")?,
+                Some(ContentEncoding::Lz4)
+            );
+
+            let mut frame = gen_frame();
+            let pixels = InternalFrame::new(vec![3; 1024]);
+            frame.set_content(VideoFrameContent::Internal(pixels.clone()));
+            let id = pipeline.add_frame("input", frame.clone())?;
+            assert_eq!(encoding(&frame), Some(ContentEncoding::Lz4));
+            let batch_id = pipeline.move_and_pack_frames("proc1", vec![id])?;
+            assert_eq!(encoding(&frame), Some(ContentEncoding::Zstd));
+            let (batch, _) = pipeline.get_batch(batch_id)?;
+            let (_, batched) = batch.frames.into_iter().next().unwrap();
+            assert_eq!(
+                batched.get_content().as_ref(),
+                &VideoFrameContent::Internal(pixels)
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn test_readiness() -> anyhow::Result<()> {
+            use crate::pipeline::readiness::{StageNotReady, StageReadiness, WarmupPolicy};
+            use crate::pipeline::stage::PipelineStage;
+            use crate::pipeline::{
+                PipelinePayload, PipelineStageFunction, PipelineStageFunctionOrder,
+            };
+            use std::sync::atomic::{AtomicBool, Ordering};
+            use std::sync::Arc;
+
+            struct ModelLoader {
+                pipeline: Option<crate::pipeline::Pipeline>,
+                loaded: Arc<AtomicBool>,
+            }
+
+            impl PipelineStageFunction for ModelLoader {
+                fn set_pipeline(&mut self, _: crate::pipeline::Pipeline) {}
+                fn get_pipeline(&self) -> &Option<crate::pipeline::Pipeline> {
+                    &self.pipeline
+                }
+                fn call(
+                    &self,
+                    _: i64,
+                    _: &PipelineStage,
+                    _: PipelineStageFunctionOrder,
+                    _: &mut PipelinePayload,
+                ) -> anyhow::Result<()> {
+                    Ok(())
+                }
+                fn get_readiness(&self) -> StageReadiness {
+                    if self.loaded.load(Ordering::SeqCst) {
+                        StageReadiness::Ready
+                    } else {
+                        StageReadiness::WarmingUp("loading model".to_string())
+                    }
+                }
+            }
+
+            fn not_ready(result: anyhow::Result<impl std::fmt::Debug>) -> StageNotReady {
+                result
+                    .unwrap_err()
+                    .downcast::<StageNotReady>()
+                    .expect("StageNotReady expected")
+            }
+
+            let pipeline = create_test_pipeline()?;
+            assert!(pipeline.is_ready());
+            let id = pipeline.add_frame("input", gen_frame())?;
+
+            let loaded = Arc::new(AtomicBool::new(false));
+            pipeline.replace_stage_processor(
+                "proc2",
+                PipelineStageFunctionOrder::Ingress,
+                Some(Box::new(ModelLoader {
+                    pipeline: None,
+                    loaded: loaded.clone(),
+                })),
+            )?;
+            assert!(!pipeline.is_ready());
+            let health = pipeline.get_health();
+            assert!(!health.ready);
+            assert_eq!(
+                health.stages[2].readiness,
+                StageReadiness::WarmingUp("loading model".to_string())
+            );
+
+            // proc1 is ready, but proc2 after it is not, so the frame is held in input
+            let error = not_ready(pipeline.move_and_pack_frames("proc1", vec![id]));
+            assert_eq!(error.stage, "proc2");
+            assert_eq!(error.policy, WarmupPolicy::Hold);
+            assert!(error.dropped.is_empty());
+            assert_eq!(pipeline.get_stage_queue_len("input")?, 1);
+            assert!(pipeline.add_frame("input", gen_frame()).is_err());
+
+            pipeline.set_warmup_policy("proc1", WarmupPolicy::Drop)?;
+            let error = not_ready(pipeline.move_and_pack_frames("proc1", vec![id]));
+            assert_eq!(error.dropped, vec![id]);
+            assert_eq!(pipeline.get_stage_queue_len("input")?, 0);
+
+            loaded.store(true, Ordering::SeqCst);
+            pipeline.set_stage_readiness(
+                "output",
+                StageReadiness::WarmingUp("syncing gallery".to_string()),
+            )?;
+            let error = not_ready(pipeline.add_frame("input", gen_frame()));
+            assert_eq!(error.stage, "output");
+            pipeline.set_stage_readiness("output", StageReadiness::Ready)?;
+            assert!(pipeline.is_ready());
+            let id = pipeline.add_frame("input", gen_frame())?;
+            pipeline.move_and_pack_frames("proc1", vec![id])?;
+            Ok(())
+        }
+
+        #[test]
+        fn test_result_cache() -> anyhow::Result<()> {
+            use crate::pipeline::result_cache::{ResultCache, ResultCacheConfig};
+            use crate::primitives::raw_content::InternalFrame;
+            use crate::primitives::RBBox;
+            use std::num::NonZeroUsize;
+
+            let pipeline = create_test_pipeline()?;
+            let cache = || {
+                ResultCache::new(ResultCacheConfig {
+                    capacity: NonZeroUsize::new(16).unwrap(),
+                    namespaces: vec!["detector".to_string()],
+                })
+            };
+            assert!(pipeline.set_result_cache("proc1", Some(cache()?)).is_err());
+            pipeline.set_result_cache("input", Some(cache()?))?;
+            let segment_frame = || {
+                let mut frame = gen_frame();
+                frame.set_content(VideoFrameContent::Internal(InternalFrame::new(vec![
+                    5;
+                    512
+                ])));
+                frame
+            };
+
+            let frame = segment_frame();
+            let id = pipeline.add_frame("input", frame.clone())?;
+            assert_eq!(pipeline.replay_cached("input", &[id])?, (vec![], vec![id]));
+            frame.create_object(
+                "detector",
+                "person",
+                None,
+                RBBox::new(1.0, 1.0, 1.0, 1.0, None),
+                None,
+                None,
+                None,
+                vec![],
+            )?;
+            pipeline.move_and_pack_frames("proc1", vec![id])?;
+
+            let frame = segment_frame();
+            let id = pipeline.add_frame("input", frame.clone())?;
+            assert_eq!(pipeline.replay_cached("input", &[id])?, (vec![id], vec![]));
+            assert!(frame
+                .get_all_objects()
+                .iter()
+                .any(|o| o.get_namespace() == "detector" && o.get_label() == "person"));
+            let stats = pipeline.get_result_cache("input")?.unwrap().get_stats();
+            assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+            Ok(())
+        }
+
+        #[test]
+        fn test_isolate() -> anyhow::Result<()> {
+            use crate::pipeline::circuit_breaker::{
+                CircuitBreaker, CircuitBreakerConfig, QuarantinePolicy,
+            };
+
+            let pipeline = create_test_pipeline()?;
+            let breaker = |policy| {
+                CircuitBreaker::new(CircuitBreakerConfig {
+                    failure_threshold: 1,
+                    quarantine: Duration::from_secs(60),
+                    policy,
+                })
+            };
+            assert!(pipeline
+                .set_circuit_breaker("proc1", Some(breaker(QuarantinePolicy::Drop)?))
+                .is_err());
+            assert!(pipeline
+                .set_circuit_breaker(
+                    "input",
+                    Some(breaker(QuarantinePolicy::DeadLetter("proc1".to_string()))?)
+                )
+                .is_err());
+            pipeline.set_circuit_breaker(
+                "input",
+                Some(breaker(QuarantinePolicy::DeadLetter("output".to_string()))?),
+            )?;
+            let mut broken = gen_frame();
+            broken.set_source_id("broken");
+            let ids = vec![
+                pipeline.add_frame("input", broken)?,
+                pipeline.add_frame("input", gen_frame())?,
+            ];
+            pipeline
+                .get_circuit_breaker("input")?
+                .unwrap()
+                .record_failure("broken");
+            let isolation = pipeline.isolate("input", &ids)?;
+            assert_eq!(isolation.kept, vec![ids[1]]);
+            assert_eq!(isolation.routed, vec![ids[0]]);
+            assert!(isolation.dropped.is_empty());
+            assert_eq!(pipeline.get_stage_queue_len("output")?, 1);
+
+            pipeline.set_circuit_breaker("input", Some(breaker(QuarantinePolicy::Drop)?))?;
+            let mut broken = gen_frame();
+            broken.set_source_id("broken");
+            let id = pipeline.add_frame("input", broken)?;
+            pipeline
+                .get_circuit_breaker("input")?
+                .unwrap()
+                .record_failure("broken");
+            let isolation = pipeline.isolate("input", &[id])?;
+            assert!(isolation.kept.is_empty());
+            assert!(isolation.dropped.contains_key(&id));
+            Ok(())
+        }
+
+        #[cfg(feature = "chaos")]
+        #[test]
+        fn test_inject_faults() -> anyhow::Result<()> {
+            use crate::pipeline::fault_injector::{FaultInjector, FaultInjectorConfig};
+            use crate::primitives::raw_content::InternalFrame;
+
+            let pipeline = create_test_pipeline()?;
+            let mut frame = gen_frame();
+            frame.set_content(VideoFrameContent::Internal(InternalFrame::new(vec![0; 4])));
+            let ids = vec![pipeline.add_frame("input", frame.clone())?];
+            assert_eq!(pipeline.inject_faults("input", &ids)?.0, ids);
+
+            pipeline.set_fault_injector(
+                "input",
+                Some(FaultInjector::new(FaultInjectorConfig {
+                    delay_probability: 1.0,
+                    delay_ms: 10,
+                    corrupt_probability: 1.0,
+                    ..FaultInjectorConfig::default()
+                })?),
+            )?;
+            let started = std::time::Instant::now();
+            let (kept, dropped) = pipeline.inject_faults("input", &ids)?;
+            assert!(started.elapsed() >= Duration::from_millis(10));
+            assert_eq!(kept, ids);
+            assert!(dropped.is_empty());
+            match frame.get_content().as_ref() {
+                VideoFrameContent::Internal(internal) => {
+                    assert_eq!(internal.get_data().ok(), Some(&[255; 4][..]))
+                }
+                _ => panic!("Internal content expected"),
+            }
+
+            pipeline.set_fault_injector(
+                "input",
+                Some(FaultInjector::new(FaultInjectorConfig {
+                    drop_probability: 1.0,
+                    ..FaultInjectorConfig::default()
+                })?),
+            )?;
+            let (kept, dropped) = pipeline.inject_faults("input", &ids)?;
+            assert!(kept.is_empty());
+            assert!(dropped.contains_key(&ids[0]));
+            assert_eq!(pipeline.get_id_locations_len(), 0);
+            assert_eq!(
+                pipeline
+                    .get_fault_injector("input")?
+                    .unwrap()
+                    .get_stats()
+                    .dropped,
+                1
+            );
+            Ok(())
+        }
+
+        #[cfg(feature = "chaos")]
+        #[test]
+        fn test_faults_on_ingress() -> anyhow::Result<()> {
+            use crate::pipeline::fault_injector::{FaultInjector, FaultInjectorConfig};
+            use crate::primitives::raw_content::InternalFrame;
+
+            let pipeline = create_test_pipeline()?;
+            pipeline.set_fault_injector(
+                "proc1",
+                Some(FaultInjector::new(FaultInjectorConfig {
+                    corrupt_probability: 1.0,
+                    ..FaultInjectorConfig::default()
+                })?),
+            )?;
+            pipeline.set_fault_injector(
+                "output",
+                Some(FaultInjector::new(FaultInjectorConfig {
+                    drop_probability: 1.0,
+                    ..FaultInjectorConfig::default()
+                })?),
+            )?;
+            let mut frame = gen_frame();
+            frame.set_content(VideoFrameContent::Internal(InternalFrame::new(vec![0; 4])));
+            let id = pipeline.add_frame("input", frame.clone())?;
+            let batch_id = pipeline.move_and_pack_frames("proc1", vec![id])?;
+            match frame.get_content().as_ref() {
+                VideoFrameContent::Internal(internal) => {
+                    assert_eq!(internal.get_data().ok(), Some(&[255; 4][..]))
+                }
+                _ => panic!("Internal content expected"),
+            }
+            assert_eq!(
+                pipeline
+                    .get_fault_injector("proc1")?
+                    .unwrap()
+                    .get_stats()
+                    .corrupted,
+                1
+            );
+
+            let frame_ids = pipeline.move_and_unpack_batch("output", batch_id)?;
+            assert!(frame_ids.is_empty());
+            assert_eq!(pipeline.get_stage_queue_len("output")?, 0);
+            assert_eq!(pipeline.get_id_locations_len(), 0);
+
+            pipeline.set_fault_injector(
+                "input",
+                Some(FaultInjector::new(FaultInjectorConfig {
+                    drop_probability: 1.0,
+                    ..FaultInjectorConfig::default()
+                })?),
+            )?;
+            assert!(pipeline.add_frame("input", gen_frame()).is_err());
+            assert_eq!(pipeline.get_id_locations_len(), 0);
+            Ok(())
+        }
+
         #[test]
         fn test_batch_update() -> anyhow::Result<()> {
             let pipeline = create_test_pipeline()?;
@@ -0,0 +1,266 @@
+use crate::metrics::get_or_create_counter_family;
+use crate::primitives::frame::VideoFrameProxy;
+use crossbeam::channel::{Receiver, Sender, TrySendError};
+use globset::{Glob, GlobMatcher};
+use log::{debug, warn};
+use serde::Deserialize;
+
+const REJECTED_FRAMES_METRIC: &str = "frames_rejected_at_ingest";
+
+/// How many rejections are kept for the receivers of the error channel, the newer ones are
+/// dropped when it is full.
+pub const REJECTION_QUEUE_SIZE: usize = 1024;
+
+/// The declarative rules checked by [`AdmissionFilter`], loaded from YAML or JSON:
+///
+/// ```yaml
+/// allowed_codecs: [h264, hevc]
+/// max_width: 3840
+/// max_height: 2160
+/// allow_sources: ["cam-*"]
+/// deny_sources: ["cam-test-*"]
+/// ```
+///
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct AdmissionRules {
+    /// The codecs of the frames admitted, compared case-insensitively. The frames without a
+    /// codec are matched as `raw`. All codecs are admitted when not set.
+    #[serde(default)]
+    pub allowed_codecs: Option<Vec<String>>,
+    #[serde(default)]
+    pub max_width: Option<i64>,
+    #[serde(default)]
+    pub max_height: Option<i64>,
+    /// The globs of the sources admitted, all sources are admitted when empty.
+    #[serde(default)]
+    pub allow_sources: Vec<String>,
+    /// The globs of the sources rejected, checked before the allowed ones.
+    #[serde(default)]
+    pub deny_sources: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RejectionReason {
+    Codec(Option<String>),
+    Resolution { width: i64, height: i64 },
+    SourceDenied,
+    SourceNotAllowed,
+}
+
+impl RejectionReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RejectionReason::Codec(_) => "codec",
+            RejectionReason::Resolution { .. } => "resolution",
+            RejectionReason::SourceDenied => "source_denied",
+            RejectionReason::SourceNotAllowed => "source_not_allowed",
+        }
+    }
+}
+
+impl std::fmt::Display for RejectionReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RejectionReason::Codec(codec) => write!(
+                f,
+                "codec {} is not allowed",
+                codec.as_deref().unwrap_or("raw")
+            ),
+            RejectionReason::Resolution { width, height } => {
+                write!(f, "resolution {}x{} exceeds the limit", width, height)
+            }
+            RejectionReason::SourceDenied => write!(f, "source is denied"),
+            RejectionReason::SourceNotAllowed => write!(f, "source is not allowed"),
+        }
+    }
+}
+
+/// A frame rejected at ingest, sent to the error channel of the filter.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdmissionRejection {
+    pub source_id: String,
+    pub frame_uuid: String,
+    pub reason: RejectionReason,
+}
+
+/// Stops the malformed or unauthorized streams when the frames are added to the pipeline,
+/// see [`crate::pipeline::Pipeline::set_admission_filter`]. A rejected frame is not added,
+/// the error is returned to the caller, counted in the `frames_rejected_at_ingest` counter
+/// labelled with the pipeline name and the reason, and sent to the error channel returned by
+/// [`AdmissionFilter::get_rejections`].
+///
+#[derive(Debug)]
+pub struct AdmissionFilter {
+    rules: AdmissionRules,
+    allow: Vec<GlobMatcher>,
+    deny: Vec<GlobMatcher>,
+    sender: Sender<AdmissionRejection>,
+    receiver: Receiver<AdmissionRejection>,
+}
+
+fn matchers(globs: &[String]) -> anyhow::Result<Vec<GlobMatcher>> {
+    globs
+        .iter()
+        .map(|g| Ok(Glob::new(g)?.compile_matcher()))
+        .collect()
+}
+
+impl AdmissionFilter {
+    pub fn new(rules: AdmissionRules) -> anyhow::Result<Self> {
+        if rules.max_width.is_some_and(|w| w <= 0) || rules.max_height.is_some_and(|h| h <= 0) {
+            anyhow::bail!("The maximum resolution must be positive");
+        }
+        let (sender, receiver) = crossbeam::channel::bounded(REJECTION_QUEUE_SIZE);
+        Ok(Self {
+            allow: matchers(&rules.allow_sources)?,
+            deny: matchers(&rules.deny_sources)?,
+            rules,
+            sender,
+            receiver,
+        })
+    }
+
+    pub fn from_yaml(yaml: &str) -> anyhow::Result<Self> {
+        Self::new(serde_yaml::from_str(yaml)?)
+    }
+
+    pub fn get_rules(&self) -> &AdmissionRules {
+        &self.rules
+    }
+
+    /// The error channel, the receivers share the rejections.
+    ///
+    pub fn get_rejections(&self) -> Receiver<AdmissionRejection> {
+        self.receiver.clone()
+    }
+
+    pub fn check(&self, frame: &VideoFrameProxy) -> Result<(), RejectionReason> {
+        let source_id = frame.get_source_id();
+        if self.deny.iter().any(|m| m.is_match(&source_id)) {
+            return Err(RejectionReason::SourceDenied);
+        }
+        if !self.allow.is_empty() && !self.allow.iter().any(|m| m.is_match(&source_id)) {
+            return Err(RejectionReason::SourceNotAllowed);
+        }
+        if let Some(codecs) = &self.rules.allowed_codecs {
+            let codec = frame.get_codec();
+            let name = codec.as_deref().unwrap_or("raw");
+            if !codecs.iter().any(|c| c.eq_ignore_ascii_case(name)) {
+                return Err(RejectionReason::Codec(codec));
+            }
+        }
+        let (width, height) = (frame.get_width(), frame.get_height());
+        if self.rules.max_width.is_some_and(|w| width > w)
+            || self.rules.max_height.is_some_and(|h| height > h)
+        {
+            return Err(RejectionReason::Resolution { width, height });
+        }
+        Ok(())
+    }
+
+    /// Checks the frame, reports the rejection and returns the error when the frame is not
+    /// admitted.
+    ///
+    pub(crate) fn admit(&self, pipeline: &str, frame: &VideoFrameProxy) -> anyhow::Result<()> {
+        let Err(reason) = self.check(frame) else {
+            return Ok(());
+        };
+        let res = get_or_create_counter_family(
+            REJECTED_FRAMES_METRIC,
+            Some("The number of frames rejected by the admission filter"),
+            &["pipeline", "reason"],
+            None,
+        )
+        .lock()
+        .inc(1, &[pipeline, reason.as_str()]);
+        if let Err(e) = res {
+            warn!(
+                target: "savant_rs::pipeline::admission",
+                "Failed to count the rejected frame: {}", e
+            );
+        }
+        let source_id = frame.get_source_id();
+        let message = format!(
+            "Frame {} of the source {} is rejected: {}",
+            frame.get_uuid(),
+            source_id,
+            reason
+        );
+        let rejection = AdmissionRejection {
+            source_id,
+            frame_uuid: frame.get_uuid().to_string(),
+            reason,
+        };
+        if let Err(TrySendError::Full(_)) = self.sender.try_send(rejection) {
+            debug!(
+                target: "savant_rs::pipeline::admission",
+                "The rejection queue is full, the rejection is dropped"
+            );
+        }
+        anyhow::bail!(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metrics::get_counter_family;
+    use crate::pipeline::admission::{AdmissionFilter, RejectionReason};
+    use crate::pipeline::{Pipeline, PipelineConfiguration, PipelineStagePayloadType};
+    use crate::test::gen_frame;
+
+    #[test]
+    #[serial_test::serial]
+    fn test_admission() -> anyhow::Result<()> {
+        let filter = AdmissionFilter::from_yaml(
+            "allowed_codecs: [H264]\nmax_width: 1280\nallow_sources: [\"cam-*\"]\ndeny_sources: [\"cam-test\"]\n",
+        )?;
+        let rejections = filter.get_rejections();
+        let pipeline = Pipeline::new(
+            vec![(
+                "input".to_string(),
+                PipelineStagePayloadType::Frame,
+                None,
+                None,
+            )],
+            PipelineConfiguration::default(),
+        )?;
+        pipeline.set_name("admission".to_string())?;
+        pipeline.set_admission_filter(Some(filter));
+
+        let mut frame = gen_frame();
+        frame.set_source_id("cam-1");
+        frame.set_codec(Some("h264".to_string()));
+        frame.set_width(1280);
+        pipeline.add_frame("input", frame.clone())?;
+
+        frame.set_width(1920);
+        assert!(pipeline.add_frame("input", frame.clone()).is_err());
+        frame.set_width(1280);
+        frame.set_codec(None);
+        assert!(pipeline.add_frame("input", frame.clone()).is_err());
+        frame.set_codec(Some("h264".to_string()));
+        frame.set_source_id("cam-test");
+        assert!(pipeline.add_frame("input", frame.clone()).is_err());
+        frame.set_source_id("lobby");
+        assert!(pipeline.add_frame("input", frame).is_err());
+        assert_eq!(pipeline.get_id_locations_len(), 1);
+
+        let reasons = rejections.try_iter().map(|r| r.reason).collect::<Vec<_>>();
+        assert_eq!(
+            reasons,
+            vec![
+                RejectionReason::Resolution {
+                    width: 1920,
+                    height: 720
+                },
+                RejectionReason::Codec(None),
+                RejectionReason::SourceDenied,
+                RejectionReason::SourceNotAllowed,
+            ]
+        );
+        let counter = get_counter_family("frames_rejected_at_ingest").unwrap();
+        assert_eq!(counter.lock().get(&["admission", "codec"])?, Some(1));
+        Ok(())
+    }
+}
@@ -0,0 +1,296 @@
+use crate::pipeline::implementation;
+use crate::pipeline::Pipeline;
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// The triggers of packing the independent frames waiting in a stage into a batch of the
+/// next batch stage, see [`Pipeline::set_auto_batch`]. The frames are packed when
+/// `max_size` inference regions accumulate or the oldest frame waits for `max_latency`, the
+/// frames with the higher priority first. A frame counts as the number of the regions of
+/// interest of its processing hints, or one region when none are set.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoBatchConfig {
+    pub max_size: usize,
+    pub max_latency: Duration,
+    /// The frame stage the frames with `skip_inference` hint are moved to as is instead of
+    /// being packed; without it they are packed with the other frames. It must differ from
+    /// the source stage.
+    pub bypass: Option<String>,
+}
+
+impl AutoBatchConfig {
+    pub fn new(max_size: usize, max_latency: Duration) -> anyhow::Result<Self> {
+        if max_size == 0 {
+            anyhow::bail!("The batch size must be greater than 0");
+        }
+        Ok(Self {
+            max_size,
+            max_latency,
+            bypass: None,
+        })
+    }
+
+    pub fn with_bypass(mut self, stage_name: &str) -> Self {
+        self.bypass = Some(stage_name.to_string());
+        self
+    }
+}
+
+/// The auto-batching of a batch stage, the lock serializes the flushes so the same frames
+/// are not packed twice.
+///
+#[derive(Debug)]
+pub(crate) struct AutoBatcher {
+    pub source: usize,
+    pub bypass: Option<usize>,
+    pub config: AutoBatchConfig,
+    pub lock: Mutex<()>,
+}
+
+/// Packs the frames waited for the latency of [`AutoBatchConfig`] in the background, the
+/// size trigger is checked when the frames enter the stages. The thread stops when the
+/// flusher or the pipeline is dropped.
+///
+pub struct AutoBatchFlusher {
+    shutdown: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl AutoBatchFlusher {
+    pub fn start(pipeline: &Pipeline, period: Duration) -> anyhow::Result<Self> {
+        if period.is_zero() {
+            anyhow::bail!("The flushing period must be greater than 0");
+        }
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let thread_shutdown = shutdown.clone();
+        let pipeline: Weak<implementation::Pipeline> = Arc::downgrade(&pipeline.0);
+        let thread = std::thread::spawn(move || loop {
+            std::thread::park_timeout(period);
+            if thread_shutdown.load(Ordering::Relaxed) {
+                break;
+            }
+            let Some(pipeline) = pipeline.upgrade() else {
+                break;
+            };
+            if let Err(e) = pipeline.flush_auto_batches(false) {
+                log::warn!(
+                    target: "savant_rs::pipeline::auto_batch",
+                    "Failed to flush the batches: {}",
+                    e
+                );
+            }
+        });
+        Ok(Self {
+            shutdown,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn stop(&mut self) {
+        let Some(thread) = self.thread.take() else {
+            return;
+        };
+        self.shutdown.store(true, Ordering::Relaxed);
+        thread.thread().unpark();
+        if thread.join().is_err() {
+            log::error!(
+                target: "savant_rs::pipeline::auto_batch",
+                "The batch flushing thread panicked"
+            );
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.thread.is_some()
+    }
+}
+
+impl Drop for AutoBatchFlusher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clock::ClockMode;
+    use crate::pipeline::auto_batch::{AutoBatchConfig, AutoBatchFlusher};
+    use crate::pipeline::{
+        Pipeline, PipelineConfiguration, PipelineConfigurationBuilder, PipelineStagePayloadType,
+    };
+    use crate::primitives::frame::VideoFrameProxy;
+    use crate::primitives::RBBox;
+    use crate::test::gen_frame;
+    use std::time::Duration;
+
+    fn frame_at(pts_ms: i64) -> VideoFrameProxy {
+        let mut frame = gen_frame();
+        frame.set_time_base((1, 1000));
+        frame.set_pts(pts_ms);
+        frame
+    }
+
+    #[test]
+    fn test_auto_batch() -> anyhow::Result<()> {
+        let pipeline = Pipeline::new(
+            vec![
+                (
+                    "input".to_string(),
+                    PipelineStagePayloadType::Frame,
+                    None,
+                    None,
+                ),
+                (
+                    "infer".to_string(),
+                    PipelineStagePayloadType::Batch,
+                    None,
+                    None,
+                ),
+                (
+                    "other".to_string(),
+                    PipelineStagePayloadType::Frame,
+                    None,
+                    None,
+                ),
+            ],
+            // the frames drive the time of the pipeline, so the latency does not depend on
+            // the scheduling of the test
+            PipelineConfigurationBuilder::default()
+                .clock_mode(Some(ClockMode::Backfill { origin_ms: 0 }))
+                .build()?,
+        )?;
+        assert!(AutoBatchConfig::new(0, Duration::from_millis(10)).is_err());
+        assert!(pipeline
+            .set_auto_batch("input", "infer", AutoBatchConfig::new(2, Duration::ZERO)?)
+            .is_err());
+        pipeline.set_auto_batch(
+            "infer",
+            "input",
+            AutoBatchConfig::new(2, Duration::from_millis(50))?,
+        )?;
+
+        // the size trigger
+        pipeline.add_frame("input", frame_at(0))?;
+        assert_eq!(pipeline.get_stage_queue_len("infer")?, 0);
+        pipeline.add_frame("input", frame_at(10))?;
+        assert_eq!(pipeline.get_stage_queue_len("input")?, 0);
+        assert_eq!(pipeline.get_stage_queue_len("infer")?, 1);
+
+        // the latency trigger
+        pipeline.add_frame("input", frame_at(20))?;
+        assert!(pipeline.flush_auto_batches(false)?.is_empty());
+        pipeline.add_frame("other", frame_at(60))?;
+        assert!(pipeline.flush_auto_batches(false)?.is_empty());
+        pipeline.add_frame("other", frame_at(70))?;
+        assert_eq!(pipeline.flush_auto_batches(false)?.len(), 1);
+        assert_eq!(pipeline.get_stage_queue_len("input")?, 0);
+        assert_eq!(pipeline.get_stage_queue_len("infer")?, 2);
+
+        assert!(AutoBatchFlusher::start(&pipeline, Duration::ZERO).is_err());
+        let mut flusher = AutoBatchFlusher::start(&pipeline, Duration::from_secs(60))?;
+        assert!(flusher.is_running());
+        flusher.stop();
+        assert!(!flusher.is_running());
+
+        pipeline.add_frame("input", frame_at(80))?;
+        assert_eq!(pipeline.flush_auto_batches(true)?.len(), 1);
+        assert!(pipeline.clear_auto_batch("infer")?);
+        assert!(pipeline.get_auto_batch("infer")?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_flush_failure() -> anyhow::Result<()> {
+        use crate::pipeline::readiness::StageReadiness;
+
+        let pipeline = Pipeline::new(
+            [
+                ("first_input", PipelineStagePayloadType::Frame),
+                ("first_infer", PipelineStagePayloadType::Batch),
+                ("second_input", PipelineStagePayloadType::Frame),
+                ("second_infer", PipelineStagePayloadType::Batch),
+            ]
+            .into_iter()
+            .map(|(s, t)| (s.to_string(), t, None, None))
+            .collect(),
+            PipelineConfiguration::default(),
+        )?;
+        let config = AutoBatchConfig::new(10, Duration::from_secs(60))?;
+        pipeline.set_auto_batch("first_infer", "first_input", config.clone())?;
+        pipeline.set_auto_batch("second_infer", "second_input", config)?;
+        pipeline.add_frame("first_input", gen_frame())?;
+        pipeline.add_frame("second_input", gen_frame())?;
+        pipeline.set_stage_readiness(
+            "first_infer",
+            StageReadiness::WarmingUp("loading".to_string()),
+        )?;
+
+        // the stage warming up does not stop the other batcher
+        assert_eq!(pipeline.flush_auto_batches(true)?.len(), 1);
+        assert_eq!(pipeline.get_stage_queue_len("first_input")?, 1);
+        assert_eq!(pipeline.get_stage_queue_len("second_infer")?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_auto_batch_hints() -> anyhow::Result<()> {
+        let pipeline = Pipeline::new(
+            vec![
+                (
+                    "input".to_string(),
+                    PipelineStagePayloadType::Frame,
+                    None,
+                    None,
+                ),
+                (
+                    "infer".to_string(),
+                    PipelineStagePayloadType::Batch,
+                    None,
+                    None,
+                ),
+                (
+                    "passthrough".to_string(),
+                    PipelineStagePayloadType::Frame,
+                    None,
+                    None,
+                ),
+            ],
+            PipelineConfiguration::default(),
+        )?;
+        let config = AutoBatchConfig::new(2, Duration::from_secs(3600))?;
+        assert!(pipeline
+            .set_auto_batch("infer", "input", config.clone().with_bypass("infer"))
+            .is_err());
+        assert!(pipeline
+            .set_auto_batch("infer", "input", config.clone().with_bypass("input"))
+            .is_err());
+        pipeline.set_auto_batch("infer", "input", config.with_bypass("passthrough"))?;
+
+        // the frames skipping the inference are not packed
+        let mut skipped = gen_frame();
+        let mut hints = skipped.get_processing_hints();
+        hints.skip_inference = true;
+        skipped.set_processing_hints(hints);
+        pipeline.add_frame("input", skipped)?;
+        assert_eq!(pipeline.get_stage_queue_len("input")?, 0);
+        assert_eq!(pipeline.get_stage_queue_len("passthrough")?, 1);
+        assert_eq!(pipeline.get_stage_queue_len("infer")?, 0);
+
+        // a frame counts as its regions of interest
+        let mut frame = gen_frame();
+        let mut hints = frame.get_processing_hints();
+        hints.roi_list = vec![
+            RBBox::ltwh(0.0, 0.0, 10.0, 10.0),
+            RBBox::ltwh(20.0, 20.0, 10.0, 10.0),
+        ];
+        frame.set_processing_hints(hints);
+        pipeline.add_frame("input", frame)?;
+        assert_eq!(pipeline.get_stage_queue_len("input")?, 0);
+        assert_eq!(pipeline.get_stage_queue_len("infer")?, 1);
+        Ok(())
+    }
+}
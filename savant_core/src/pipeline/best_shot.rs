@@ -0,0 +1,442 @@
+use crate::pipeline::motion::luminance;
+use crate::pipeline::quality::sharpness;
+use crate::pipeline::stage::PipelineStage;
+use crate::pipeline::{
+    Pipeline, PipelinePayload, PipelineStageFunction, PipelineStageFunctionOrder, PluginParams,
+};
+use crate::primitives::attribute_value::{AttributeValue, AttributeValueVariant};
+use crate::primitives::frame::{VideoFrameContent, VideoFrameProxy};
+use crate::primitives::object::{BorrowedVideoObject, ObjectOperations};
+use crate::primitives::userdata::UserData;
+use crate::primitives::{RBBox, WithAttributes};
+use anyhow::Result;
+use hashbrown::hash_map::Entry;
+use hashbrown::HashMap;
+use parking_lot::Mutex;
+use std::fmt::Debug;
+
+pub const DEFAULT_BEST_SHOT_NAMESPACE: &str = "best_shot";
+
+#[no_mangle]
+pub fn init_best_shot_selector(_: &str, params: PluginParams) -> *mut dyn PipelineStageFunction {
+    let selector = BestShotSelector::new(BestShotConfiguration::from(&params));
+    Box::into_raw(Box::new(selector))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BestShotConfiguration {
+    /// A track ends when it is missing in more consecutive frames of its source.
+    pub max_missing_frames: u64,
+    /// The weight of the box area share of the frame.
+    pub size_weight: f64,
+    pub confidence_weight: f64,
+    /// The weight of the crop sharpness, computed for raw frames only.
+    pub sharpness_weight: f64,
+    /// The sharpness scoring a half, e.g. the blur threshold of the quality estimator.
+    pub sharpness_scale: f64,
+    /// The weights of float object attributes scoring the crop, e.g. the frontal-ness
+    /// estimated by a pose model, by `(namespace, name)`.
+    pub attribute_weights: Vec<(String, String, f64)>,
+    pub namespace: String,
+}
+
+impl Default for BestShotConfiguration {
+    fn default() -> Self {
+        Self {
+            max_missing_frames: 30,
+            size_weight: 1.0,
+            confidence_weight: 1.0,
+            sharpness_weight: 1.0,
+            sharpness_scale: 100.0,
+            attribute_weights: Vec::new(),
+            namespace: DEFAULT_BEST_SHOT_NAMESPACE.to_string(),
+        }
+    }
+}
+
+impl From<&PluginParams> for BestShotConfiguration {
+    /// The attribute weights are the float parameters named `attribute:<namespace>/<name>`.
+    ///
+    fn from(params: &PluginParams) -> Self {
+        let defaults = Self::default();
+        let as_number = |value: &AttributeValueVariant| match value {
+            AttributeValueVariant::Float(v) => Some(*v),
+            AttributeValueVariant::Integer(v) => Some(*v as f64),
+            _ => None,
+        };
+        let number = |name: &str, default: f64| {
+            params
+                .params
+                .get(name)
+                .and_then(|v| as_number(&v.value))
+                .unwrap_or(default)
+        };
+        let mut attribute_weights = params
+            .params
+            .iter()
+            .filter_map(|(k, v)| {
+                let (namespace, name) = k.strip_prefix("attribute:")?.split_once('/')?;
+                Some((
+                    namespace.to_string(),
+                    name.to_string(),
+                    as_number(&v.value)?,
+                ))
+            })
+            .collect::<Vec<_>>();
+        attribute_weights.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+        Self {
+            max_missing_frames: number("max_missing_frames", defaults.max_missing_frames as f64)
+                .max(0.0) as u64,
+            size_weight: number("size_weight", defaults.size_weight),
+            confidence_weight: number("confidence_weight", defaults.confidence_weight),
+            sharpness_weight: number("sharpness_weight", defaults.sharpness_weight),
+            sharpness_scale: number("sharpness_scale", defaults.sharpness_scale),
+            attribute_weights,
+            namespace: match params.params.get("namespace").map(|v| &v.value) {
+                Some(AttributeValueVariant::String(v)) => v.clone(),
+                _ => defaults.namespace,
+            },
+        }
+    }
+}
+
+/// Scores the crop of a tracked object, the crop with the highest score over the lifetime of
+/// the track is its best shot. `luma` is the GRAY8 image of raw frames.
+///
+pub trait ShotScorer: Send + Sync + Debug {
+    fn score(
+        &self,
+        frame: &VideoFrameProxy,
+        luma: Option<&[u8]>,
+        object: &BorrowedVideoObject,
+    ) -> f64;
+}
+
+/// The weighted sum of the size, confidence, sharpness and attribute scores of the
+/// configuration.
+///
+#[derive(Debug)]
+pub struct DefaultShotScorer {
+    configuration: BestShotConfiguration,
+}
+
+impl DefaultShotScorer {
+    pub fn new(configuration: BestShotConfiguration) -> Self {
+        Self { configuration }
+    }
+
+    fn crop_sharpness(frame: &VideoFrameProxy, luma: &[u8], bbox: &RBBox) -> Option<f64> {
+        let (width, height) = (frame.get_width() as usize, frame.get_height() as usize);
+        let (left, top, w, h) = bbox.get_wrapping_bbox().as_ltwh().ok()?;
+        let x0 = (left.max(0.0) as usize).min(width);
+        let y0 = (top.max(0.0) as usize).min(height);
+        let x1 = ((left + w).max(0.0) as usize).min(width);
+        let y1 = ((top + h).max(0.0) as usize).min(height);
+        if x1 <= x0 || y1 <= y0 {
+            return None;
+        }
+        let crop = (y0..y1)
+            .flat_map(|y| &luma[y * width + x0..y * width + x1])
+            .copied()
+            .collect::<Vec<_>>();
+        Some(sharpness(&crop, x1 - x0, y1 - y0))
+    }
+}
+
+impl ShotScorer for DefaultShotScorer {
+    fn score(
+        &self,
+        frame: &VideoFrameProxy,
+        luma: Option<&[u8]>,
+        object: &BorrowedVideoObject,
+    ) -> f64 {
+        let c = &self.configuration;
+        let bbox = object
+            .get_track_box()
+            .unwrap_or_else(|| object.get_detection_box());
+        let frame_area = (frame.get_width() * frame.get_height()).max(1) as f64;
+        let mut score = c.size_weight * (bbox.get_area() as f64 / frame_area).min(1.0)
+            + c.confidence_weight * object.get_confidence().unwrap_or(1.0) as f64;
+        if c.sharpness_weight != 0.0 {
+            if let Some(sharpness) = luma.and_then(|l| Self::crop_sharpness(frame, l, &bbox)) {
+                score += c.sharpness_weight * sharpness / (sharpness + c.sharpness_scale);
+            }
+        }
+        for (namespace, name, weight) in &c.attribute_weights {
+            let value = object.get_attribute(namespace, name).and_then(|a| {
+                a.get_values().first().and_then(|v| match v.get() {
+                    AttributeValueVariant::Float(v) => Some(*v),
+                    _ => None,
+                })
+            });
+            score += weight * value.unwrap_or_default();
+        }
+        score
+    }
+}
+
+/// The best crop of an ended track.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct BestShot {
+    pub source_id: String,
+    pub track_id: i64,
+    pub frame_uuid: String,
+    pub pts: i64,
+    pub bbox: RBBox,
+    pub score: f64,
+}
+
+impl BestShot {
+    /// The values of the track attribute: the frame uuid, the PTS, the box and the score.
+    ///
+    pub fn to_attribute_values(&self) -> Vec<AttributeValue> {
+        vec![
+            AttributeValue::string(&self.frame_uuid, None),
+            AttributeValue::integer(self.pts, None),
+            AttributeValue::bbox(self.bbox.clone().into(), None),
+            AttributeValue::float(self.score, None),
+        ]
+    }
+
+    /// The event sent downstream, the best shot is the attribute named after the track.
+    ///
+    pub fn to_user_data(&self, namespace: &str) -> UserData {
+        let mut data = UserData::new(&self.source_id);
+        data.set_persistent_attribute(
+            namespace,
+            &self.track_id.to_string(),
+            &None,
+            false,
+            self.to_attribute_values(),
+        );
+        data
+    }
+}
+
+#[derive(Debug)]
+struct TrackState {
+    best: BestShot,
+    last_seen: u64,
+}
+
+#[derive(Debug, Default)]
+struct SourceState {
+    frames: u64,
+    tracks: HashMap<i64, TrackState>,
+}
+
+/// Scores the crops of the tracked objects over the lifetime of their tracks and selects
+/// the best shot of every track, e.g. the face or the plate to recognize or show. When a
+/// track ends, the best shot is written as the frame attribute named after the track id,
+/// see [`BestShot::to_attribute_values`]. The selector is a stage function, so it is
+/// attached as an ingress or egress function of a frame or batch stage; the tracks of a
+/// finished source are ended with [`Self::finish_source`], e.g. on the end of stream.
+///
+#[derive(Debug)]
+pub struct BestShotSelector {
+    pipeline: Option<Pipeline>,
+    configuration: BestShotConfiguration,
+    scorer: Box<dyn ShotScorer>,
+    sources: Mutex<HashMap<String, SourceState>>,
+}
+
+impl BestShotSelector {
+    pub fn new(configuration: BestShotConfiguration) -> Self {
+        let scorer = Box::new(DefaultShotScorer::new(configuration.clone()));
+        Self::with_scorer(configuration, scorer)
+    }
+
+    pub fn with_scorer(configuration: BestShotConfiguration, scorer: Box<dyn ShotScorer>) -> Self {
+        Self {
+            pipeline: None,
+            configuration,
+            scorer,
+            sources: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn get_configuration(&self) -> &BestShotConfiguration {
+        &self.configuration
+    }
+
+    /// Scores the tracked objects of the frame and returns the best shots of the tracks of
+    /// the source which ended.
+    ///
+    pub fn observe(&self, frame: &VideoFrameProxy) -> Vec<BestShot> {
+        let luma = match frame.get_content().as_ref() {
+            VideoFrameContent::Internal(data) => luminance(
+                data,
+                frame.get_width() as usize,
+                frame.get_height() as usize,
+            )
+            .ok(),
+            _ => None,
+        };
+        let source_id = frame.get_source_id();
+        let mut sources = self.sources.lock();
+        let source = sources.entry(source_id.clone()).or_default();
+        source.frames += 1;
+        let now = source.frames;
+        for object in frame.get_all_objects() {
+            let Some(track_id) = object.get_track_id() else {
+                continue;
+            };
+            let score = self.scorer.score(frame, luma.as_deref(), &object);
+            let shot = || BestShot {
+                source_id: source_id.clone(),
+                track_id,
+                frame_uuid: frame.get_uuid_as_string(),
+                pts: frame.get_pts(),
+                bbox: object
+                    .get_track_box()
+                    .unwrap_or_else(|| object.get_detection_box()),
+                score,
+            };
+            match source.tracks.entry(track_id) {
+                Entry::Occupied(mut e) => {
+                    let state = e.get_mut();
+                    state.last_seen = now;
+                    if score > state.best.score {
+                        state.best = shot();
+                    }
+                }
+                Entry::Vacant(e) => {
+                    e.insert(TrackState {
+                        best: shot(),
+                        last_seen: now,
+                    });
+                }
+            }
+        }
+        let max_missing = self.configuration.max_missing_frames;
+        let mut ended = source
+            .tracks
+            .extract_if(|_, t| now - t.last_seen > max_missing)
+            .map(|(_, t)| t.best)
+            .collect::<Vec<_>>();
+        ended.sort_by_key(|s| s.track_id);
+        ended
+    }
+
+    /// Ends the tracks of the source and returns their best shots.
+    ///
+    pub fn finish_source(&self, source_id: &str) -> Vec<BestShot> {
+        let mut ended = self
+            .sources
+            .lock()
+            .remove(source_id)
+            .map(|s| s.tracks.into_values().map(|t| t.best).collect::<Vec<_>>())
+            .unwrap_or_default();
+        ended.sort_by_key(|s| s.track_id);
+        ended
+    }
+
+    fn process(&self, frame: &mut VideoFrameProxy) {
+        for shot in self.observe(frame) {
+            frame.set_persistent_attribute(
+                &self.configuration.namespace,
+                &shot.track_id.to_string(),
+                &None,
+                false,
+                shot.to_attribute_values(),
+            );
+        }
+    }
+}
+
+impl PipelineStageFunction for BestShotSelector {
+    fn set_pipeline(&mut self, pipeline: Pipeline) {
+        self.pipeline = Some(pipeline);
+    }
+    fn get_pipeline(&self) -> &Option<Pipeline> {
+        &self.pipeline
+    }
+    fn call(
+        &self,
+        _: i64,
+        _: &PipelineStage,
+        _: PipelineStageFunctionOrder,
+        payload: &mut PipelinePayload,
+    ) -> Result<()> {
+        match payload {
+            PipelinePayload::Frame(frame, ..) => self.process(frame),
+            PipelinePayload::Batch(batch, ..) => {
+                for frame in batch.frames().values() {
+                    self.process(&mut frame.clone());
+                }
+            }
+            PipelinePayload::Audio(..) | PipelinePayload::Telemetry(..) => (),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::best_shot::{BestShotConfiguration, BestShotSelector};
+    use crate::primitives::attribute_value::AttributeValueVariant;
+    use crate::primitives::frame::VideoFrameProxy;
+    use crate::primitives::object::{
+        IdCollisionResolutionPolicy, ObjectOperations, VideoObjectBuilder,
+    };
+    use crate::primitives::{RBBox, WithAttributes};
+    use crate::test::gen_empty_frame;
+
+    fn frame_with_track(pts: i64, track: Option<(i64, f32)>) -> VideoFrameProxy {
+        let mut frame = gen_empty_frame();
+        frame.set_width(100);
+        frame.set_height(100);
+        frame.set_pts(pts);
+        if let Some((track_id, size)) = track {
+            let object = VideoObjectBuilder::default()
+                .id(0)
+                .namespace("detector".to_string())
+                .label("face".to_string())
+                .detection_box(RBBox::new(50.0, 50.0, size, size, None))
+                .confidence(Some(0.5))
+                .build()
+                .unwrap();
+            let mut object = frame
+                .add_object(object, IdCollisionResolutionPolicy::GenerateNewId)
+                .unwrap();
+            object.set_track_info(track_id, RBBox::new(50.0, 50.0, size, size, None));
+        }
+        frame
+    }
+
+    #[test]
+    fn test_best_shot() {
+        let selector = BestShotSelector::new(BestShotConfiguration {
+            max_missing_frames: 1,
+            ..Default::default()
+        });
+        assert!(selector
+            .observe(&frame_with_track(0, Some((7, 10.0))))
+            .is_empty());
+        let best = frame_with_track(1, Some((7, 40.0)));
+        assert!(selector.observe(&best).is_empty());
+        assert!(selector
+            .observe(&frame_with_track(2, Some((7, 20.0))))
+            .is_empty());
+        assert!(selector.observe(&frame_with_track(3, None)).is_empty());
+        let mut last = frame_with_track(4, None);
+        selector.process(&mut last);
+        let attribute = last.get_attribute("best_shot", "7").unwrap();
+        let values = attribute.get_values();
+        assert_eq!(
+            values[0].get(),
+            &AttributeValueVariant::String(best.get_uuid_as_string())
+        );
+        assert_eq!(values[1].get(), &AttributeValueVariant::Integer(1));
+
+        selector.observe(&frame_with_track(5, Some((8, 10.0))));
+        let shots = selector.finish_source("test");
+        assert_eq!(shots.len(), 1);
+        assert_eq!(shots[0].track_id, 8);
+        assert_eq!(shots[0].pts, 5);
+        let data = shots[0].to_user_data("best_shot");
+        assert!(data.get_attribute("best_shot", "8").is_some());
+        assert!(selector.finish_source("test").is_empty());
+    }
+}
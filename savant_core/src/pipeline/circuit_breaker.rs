@@ -0,0 +1,263 @@
+use crate::clock::{self, Clock};
+use hashbrown::HashMap;
+use opentelemetry::Context;
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+/// What happens to the frames of a quarantined source.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuarantinePolicy {
+    /// The frames are deleted from the pipeline.
+    Drop,
+    /// The frames are moved as is to the dead-letter stage, which must follow the breaker
+    /// stage and have the same type.
+    DeadLetter(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircuitBreakerConfig {
+    /// The number of consecutive failures of a source which quarantines it.
+    pub failure_threshold: u32,
+    /// How long a source stays quarantined before a probe frame is let through.
+    pub quarantine: Duration,
+    pub policy: QuarantinePolicy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CircuitState {
+    /// The frames pass, the consecutive failures are counted.
+    Closed { failures: u32 },
+    /// The source is quarantined until the time.
+    Open { until: SystemTime },
+    /// A probe frame passed at the time; its success closes the circuit, its failure opens it
+    /// again. When the outcome is not recorded within the quarantine, e.g. the probe frame is
+    /// evicted, the source is quarantined again.
+    HalfOpen { since: SystemTime },
+}
+
+/// The frames of an isolated stage: the ids of the frames which pass, the ids of the frames
+/// moved to the dead-letter stage and the root contexts of the deleted frames.
+///
+#[derive(Debug, Default)]
+pub struct Isolation {
+    pub kept: Vec<i64>,
+    pub routed: Vec<i64>,
+    pub dropped: HashMap<i64, Context>,
+}
+
+/// Quarantines the sources failing repeatedly in a stage, so a corrupted stream does not
+/// affect other sources. The breaker is installed with
+/// [`crate::pipeline::Pipeline::set_circuit_breaker`] and applied with
+/// [`crate::pipeline::Pipeline::isolate`]; the stage processor reports the outcome of every
+/// frame with [`CircuitBreaker::record_success`] and [`CircuitBreaker::record_failure`].
+/// The quarantine is measured by the clock of the pipeline the breaker is installed in.
+///
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    clock: Arc<Clock>,
+    sources: Mutex<HashMap<String, CircuitState>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> anyhow::Result<Self> {
+        if config.failure_threshold == 0 {
+            anyhow::bail!("The failure threshold must be greater than 0");
+        }
+        Ok(Self {
+            config,
+            clock: clock::process_clock(),
+            sources: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub(crate) fn set_clock(&mut self, clock: Arc<Clock>) {
+        self.clock = clock;
+    }
+
+    pub fn get_config(&self) -> &CircuitBreakerConfig {
+        &self.config
+    }
+
+    pub fn get_state(&self, source_id: &str) -> CircuitState {
+        self.sources
+            .lock()
+            .get(source_id)
+            .copied()
+            .unwrap_or(CircuitState::Closed { failures: 0 })
+    }
+
+    /// The sources which are quarantined or probed.
+    ///
+    pub fn get_quarantined(&self) -> Vec<String> {
+        let mut sources = self
+            .sources
+            .lock()
+            .iter()
+            .filter(|(_, s)| !matches!(s, CircuitState::Closed { .. }))
+            .map(|(source, _)| source.clone())
+            .collect::<Vec<_>>();
+        sources.sort();
+        sources
+    }
+
+    /// Decides whether a frame of the source passes. When the quarantine is over, a single
+    /// probe frame passes and the following frames are held back until its outcome is
+    /// recorded. A probe without an outcome for the quarantine period opens the circuit again.
+    ///
+    pub fn allow(&self, source_id: &str) -> bool {
+        let mut sources = self.sources.lock();
+        let Some(state) = sources.get_mut(source_id) else {
+            return true;
+        };
+        let now = self.clock.now();
+        match state {
+            CircuitState::Closed { .. } => true,
+            CircuitState::Open { until } if now >= *until => {
+                log::info!(target: "savant_rs::pipeline", "Probing quarantined source {}", source_id);
+                *state = CircuitState::HalfOpen { since: now };
+                true
+            }
+            CircuitState::HalfOpen { since }
+                if self.clock.elapsed(*since) >= self.config.quarantine =>
+            {
+                log::warn!(
+                    target: "savant_rs::pipeline",
+                    "The probe of source {} timed out, quarantined for {} ms",
+                    source_id,
+                    self.config.quarantine.as_millis()
+                );
+                *state = CircuitState::Open {
+                    until: now + self.config.quarantine,
+                };
+                false
+            }
+            CircuitState::Open { .. } | CircuitState::HalfOpen { .. } => false,
+        }
+    }
+
+    pub fn record_success(&self, source_id: &str) {
+        let mut sources = self.sources.lock();
+        if let Some(CircuitState::HalfOpen { .. }) = sources.get(source_id) {
+            log::info!(target: "savant_rs::pipeline", "Source {} recovered", source_id);
+        }
+        sources.remove(source_id);
+    }
+
+    pub fn record_failure(&self, source_id: &str) {
+        let mut sources = self.sources.lock();
+        let state = sources
+            .entry(source_id.to_string())
+            .or_insert(CircuitState::Closed { failures: 0 });
+        let failures = match state {
+            CircuitState::Closed { failures } => *failures + 1,
+            // a failure of a frame which passed before the quarantine keeps it as is
+            CircuitState::Open { .. } => return,
+            CircuitState::HalfOpen { .. } => self.config.failure_threshold,
+        };
+        if failures < self.config.failure_threshold {
+            *state = CircuitState::Closed { failures };
+            return;
+        }
+        log::warn!(
+            target: "savant_rs::pipeline",
+            "Source {} quarantined for {} ms after {} failures",
+            source_id,
+            self.config.quarantine.as_millis(),
+            failures
+        );
+        *state = CircuitState::Open {
+            until: self.clock.now() + self.config.quarantine,
+        };
+    }
+
+    /// Forgets the state of the source, e.g. when the stream is restarted.
+    ///
+    pub fn reset_source(&self, source_id: &str) {
+        self.sources.lock().remove(source_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clock::ClockMode;
+    use crate::pipeline::circuit_breaker::{
+        CircuitBreaker, CircuitBreakerConfig, CircuitState, QuarantinePolicy,
+    };
+    use crate::pipeline::{Pipeline, PipelineConfigurationBuilder, PipelineStagePayloadType};
+    use crate::test::gen_frame;
+    use std::time::Duration;
+
+    #[test]
+    fn test_quarantine_and_probe() -> anyhow::Result<()> {
+        let pipeline = Pipeline::new(
+            vec![(
+                "input".to_string(),
+                PipelineStagePayloadType::Frame,
+                None,
+                None,
+            )],
+            PipelineConfigurationBuilder::default()
+                .clock_mode(Some(ClockMode::Backfill { origin_ms: 0 }))
+                .build()?,
+        )?;
+        pipeline.set_circuit_breaker(
+            "input",
+            Some(CircuitBreaker::new(CircuitBreakerConfig {
+                failure_threshold: 2,
+                quarantine: Duration::from_millis(20),
+                policy: QuarantinePolicy::Drop,
+            })?),
+        )?;
+        let breaker = pipeline.get_circuit_breaker("input")?.unwrap();
+        // the frames move the time of the pipeline to their PTS
+        let advance_to = |pts_ms| {
+            let mut frame = gen_frame();
+            frame.set_time_base((1, 1000));
+            frame.set_pts(pts_ms);
+            pipeline.add_frame("input", frame)
+        };
+
+        breaker.record_failure("broken");
+        assert!(breaker.allow("broken"));
+        breaker.record_success("broken");
+        breaker.record_failure("broken");
+        assert_eq!(
+            breaker.get_state("broken"),
+            CircuitState::Closed { failures: 1 }
+        );
+        breaker.record_failure("broken");
+        assert!(!breaker.allow("broken"));
+        assert!(breaker.allow("healthy"));
+        assert_eq!(breaker.get_quarantined(), vec!["broken"]);
+
+        advance_to(19)?;
+        assert!(!breaker.allow("broken"));
+        advance_to(20)?;
+        assert!(breaker.allow("broken"));
+        assert!(!breaker.allow("broken"));
+        breaker.record_failure("broken");
+        assert!(matches!(
+            breaker.get_state("broken"),
+            CircuitState::Open { .. }
+        ));
+
+        advance_to(40)?;
+        assert!(breaker.allow("broken"));
+        // the outcome of the probe is lost, the source is quarantined again
+        advance_to(60)?;
+        assert!(!breaker.allow("broken"));
+        assert!(matches!(
+            breaker.get_state("broken"),
+            CircuitState::Open { .. }
+        ));
+        advance_to(80)?;
+        assert!(breaker.allow("broken"));
+        breaker.record_success("broken");
+        assert!(breaker.allow("broken"));
+        assert!(breaker.get_quarantined().is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,336 @@
+use crate::pipeline::{Pipeline, PipelineConfiguration, PipelineStagePayloadType};
+use crate::primitives::frame::VideoFrameProxy;
+use anyhow::{bail, Result};
+use hashbrown::HashSet;
+use lazy_static::lazy_static;
+use log::warn;
+use regex::Regex;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// How [`LegacyPipeline`] handles the legacy calls behaving differently on the current
+/// pipeline.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompatMode {
+    /// The call is ignored and a warning is logged once per difference.
+    #[default]
+    Warn,
+    /// The call is ignored silently.
+    Silent,
+    /// The call fails.
+    Strict,
+}
+
+/// A legacy behavior the current pipeline does not support.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LegacyDifference {
+    /// The stages are declared when the pipeline is created, a stage cannot be added after
+    /// the first frame. The call always fails.
+    LateStage,
+    /// The root span name and the sampling period are set once, before the first frame.
+    LateSetting,
+}
+
+impl LegacyDifference {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            LegacyDifference::LateStage => {
+                "a stage is added after the first frame, declare the stages before it"
+            }
+            LegacyDifference::LateSetting => {
+                "the root span name or the sampling period is changed after the first frame, \
+                 the first value is kept"
+            }
+        }
+    }
+}
+
+/// Runs the code written against the legacy incremental pipeline API on the current
+/// pipeline: the stages and the settings are collected until the first frame is added or
+/// [`LegacyPipeline::pipeline`] is called, then the pipeline is created with them. The later
+/// calls the current pipeline does not support are handled according to the [`CompatMode`].
+///
+pub struct LegacyPipeline {
+    name: String,
+    mode: CompatMode,
+    configuration: PipelineConfiguration,
+    stages: Vec<(String, PipelineStagePayloadType)>,
+    root_span_name: Option<String>,
+    sampling_period: Option<i64>,
+    pipeline: Option<Pipeline>,
+    differences: HashSet<LegacyDifference>,
+}
+
+impl LegacyPipeline {
+    pub fn new(name: &str, mode: CompatMode) -> Self {
+        Self {
+            name: name.to_string(),
+            mode,
+            configuration: PipelineConfiguration::default(),
+            stages: Vec::new(),
+            root_span_name: None,
+            sampling_period: None,
+            pipeline: None,
+            differences: HashSet::new(),
+        }
+    }
+
+    pub fn with_configuration(mut self, configuration: PipelineConfiguration) -> Self {
+        self.configuration = configuration;
+        self
+    }
+
+    pub fn is_created(&self) -> bool {
+        self.pipeline.is_some()
+    }
+
+    /// The differences the code hit so far.
+    ///
+    pub fn get_differences(&self) -> Vec<LegacyDifference> {
+        self.differences.iter().copied().collect()
+    }
+
+    fn report(&mut self, difference: LegacyDifference) -> Result<()> {
+        let first = self.differences.insert(difference);
+        match self.mode {
+            CompatMode::Strict => bail!("Pipeline {}: {}", self.name, difference.describe()),
+            CompatMode::Warn if first => {
+                warn!(
+                    target: "savant_rs::pipeline::compat",
+                    "Pipeline {}: {}", self.name, difference.describe()
+                );
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn add_stage(&mut self, name: &str, payload_type: PipelineStagePayloadType) -> Result<()> {
+        if self.is_created() {
+            self.report(LegacyDifference::LateStage)?;
+            bail!("The stage {} is added after the pipeline is created", name);
+        }
+        if self.stages.iter().any(|(n, _)| n == name) {
+            bail!("The stage {} already exists", name);
+        }
+        self.stages.push((name.to_string(), payload_type));
+        Ok(())
+    }
+
+    pub fn get_stage_type(&self, name: &str) -> Result<PipelineStagePayloadType> {
+        match self.stages.iter().find(|(n, _)| n == name) {
+            Some((_, payload_type)) => Ok(payload_type.clone()),
+            None => bail!("Stage {} not found", name),
+        }
+    }
+
+    pub fn set_root_span_name(&mut self, name: &str) -> Result<()> {
+        if self.is_created() {
+            return self.report(LegacyDifference::LateSetting);
+        }
+        self.root_span_name = Some(name.to_string());
+        Ok(())
+    }
+
+    pub fn set_sampling_period(&mut self, period: i64) -> Result<()> {
+        if self.is_created() {
+            return self.report(LegacyDifference::LateSetting);
+        }
+        self.sampling_period = Some(period);
+        Ok(())
+    }
+
+    /// The pipeline, created with the collected stages and settings on the first call.
+    ///
+    pub fn pipeline(&mut self) -> Result<&Pipeline> {
+        if self.pipeline.is_none() {
+            if self.stages.is_empty() {
+                bail!("The pipeline {} has no stages", self.name);
+            }
+            let stages = self
+                .stages
+                .iter()
+                .map(|(name, payload_type)| (name.clone(), payload_type.clone(), None, None))
+                .collect();
+            let pipeline = Pipeline::new(stages, self.configuration.clone())?;
+            pipeline.set_name(self.name.clone())?;
+            if let Some(name) = &self.root_span_name {
+                pipeline.set_root_span_name(name.clone())?;
+            }
+            if let Some(period) = self.sampling_period {
+                pipeline.set_sampling_period(period)?;
+            }
+            self.pipeline = Some(pipeline);
+        }
+        Ok(self.pipeline.as_ref().unwrap())
+    }
+
+    pub fn add_frame(&mut self, stage_name: &str, frame: VideoFrameProxy) -> Result<i64> {
+        self.pipeline()?.add_frame(stage_name, frame)
+    }
+}
+
+/// A use of the legacy pipeline API found in the source code.
+///
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MigrationFinding {
+    pub path: String,
+    pub line: usize,
+    pub rule: &'static str,
+    pub message: &'static str,
+}
+
+struct MigrationRule {
+    id: &'static str,
+    pattern: Regex,
+    message: &'static str,
+}
+
+lazy_static! {
+    static ref MIGRATION_RULES: Vec<MigrationRule> = vec![
+        MigrationRule {
+            id: "pipeline2-import",
+            pattern: Regex::new(r"savant_rs\.pipeline2\b|import\s+pipeline2\b").unwrap(),
+            message: "savant_rs.pipeline2 is an alias of savant_rs.pipeline, import the latter",
+        },
+        MigrationRule {
+            id: "add-stage",
+            pattern: Regex::new(r"\.add_stage\s*\(").unwrap(),
+            message: "the stages are declared when the pipeline is created, pass them to the \
+                      VideoPipeline constructor; Rust code may use \
+                      savant_core::pipeline::compat::LegacyPipeline",
+        },
+        MigrationRule {
+            id: "name-only-constructor",
+            pattern: Regex::new(r#"VideoPipeline\s*\(\s*["'][^"']*["']\s*\)"#).unwrap(),
+            message: "the pipeline is created with the name, the stages and the configuration",
+        },
+        MigrationRule {
+            id: "late-setting",
+            pattern: Regex::new(r"\.(root_span_name|sampling_period)\s*=[^=]").unwrap(),
+            message: "the root span name and the sampling period are set once, before the \
+                      first frame",
+        },
+    ];
+}
+
+/// Finds the uses of the legacy pipeline API in the source code.
+///
+pub fn scan_source(path: &str, source: &str) -> Vec<MigrationFinding> {
+    let mut findings = Vec::new();
+    for (n, line) in source.lines().enumerate() {
+        for rule in MIGRATION_RULES.iter() {
+            if rule.pattern.is_match(line) {
+                findings.push(MigrationFinding {
+                    path: path.to_string(),
+                    line: n + 1,
+                    rule: rule.id,
+                    message: rule.message,
+                });
+            }
+        }
+    }
+    findings
+}
+
+fn collect_sources(path: &Path, sources: &mut Vec<PathBuf>) -> Result<()> {
+    if path.is_dir() {
+        let mut entries = std::fs::read_dir(path)?
+            .map(|e| e.map(|e| e.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        entries.sort();
+        for entry in entries {
+            collect_sources(&entry, sources)?;
+        }
+    } else if path
+        .extension()
+        .is_some_and(|e| e == "py" || e == "pyi" || e == "rs")
+    {
+        sources.push(path.to_path_buf());
+    }
+    Ok(())
+}
+
+/// Scans the Python and Rust files in the paths, the directories are scanned recursively.
+///
+pub fn migration_report(paths: &[PathBuf]) -> Result<Vec<MigrationFinding>> {
+    let mut sources = Vec::new();
+    for path in paths {
+        if !path.exists() {
+            bail!("{} does not exist", path.display());
+        }
+        collect_sources(path, &mut sources)?;
+    }
+    let mut findings = Vec::new();
+    for source in sources {
+        let text = std::fs::read_to_string(&source)?;
+        findings.extend(scan_source(&source.display().to_string(), &text));
+    }
+    Ok(findings)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::compat::{scan_source, CompatMode, LegacyDifference, LegacyPipeline};
+    use crate::pipeline::PipelineStagePayloadType;
+    use crate::test::gen_frame;
+
+    #[test]
+    fn test_legacy_pipeline() -> anyhow::Result<()> {
+        let mut legacy = LegacyPipeline::new("legacy", CompatMode::Warn);
+        legacy.add_stage("input", PipelineStagePayloadType::Frame)?;
+        legacy.add_stage("output", PipelineStagePayloadType::Frame)?;
+        assert!(legacy
+            .add_stage("input", PipelineStagePayloadType::Batch)
+            .is_err());
+        legacy.set_root_span_name("first")?;
+        legacy.set_root_span_name("second")?;
+        assert!(!legacy.is_created());
+
+        let id = legacy.add_frame("input", gen_frame())?;
+        assert_eq!(legacy.pipeline()?.get_root_span_name(), "second");
+        legacy.pipeline()?.move_as_is("output", vec![id])?;
+
+        legacy.set_sampling_period(10)?;
+        assert_eq!(legacy.pipeline()?.get_sampling_period(), 0);
+        assert!(legacy
+            .add_stage("late", PipelineStagePayloadType::Frame)
+            .is_err());
+        assert_eq!(legacy.get_differences().len(), 2);
+
+        let mut strict = LegacyPipeline::new("strict", CompatMode::Strict);
+        strict.add_stage("input", PipelineStagePayloadType::Frame)?;
+        strict.pipeline()?;
+        assert!(strict.set_sampling_period(10).is_err());
+        assert_eq!(
+            strict.get_differences(),
+            vec![LegacyDifference::LateSetting]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_source() {
+        let source = "from savant_rs.pipeline2 import VideoPipeline\n\
+                      p = VideoPipeline('legacy')\n\
+                      p.add_stage('input', VideoPipelineStagePayloadType.Frame)\n\
+                      p.sampling_period = 10\n\
+                      if p.sampling_period == 10:\n";
+        let rules = scan_source("legacy.py", source)
+            .iter()
+            .map(|f| (f.line, f.rule))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rules,
+            vec![
+                (1, "pipeline2-import"),
+                (2, "name-only-constructor"),
+                (3, "add-stage"),
+                (4, "late-setting"),
+            ]
+        );
+    }
+}
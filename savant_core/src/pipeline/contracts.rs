@@ -0,0 +1,611 @@
+use crate::pipeline::fixtures::load_fixture;
+use crate::primitives::attribute_value::AttributeValueVariant;
+use crate::primitives::frame::VideoFrameProxy;
+use crate::primitives::object::ObjectOperations;
+use crate::primitives::unit::Unit;
+use crate::primitives::{Attribute, WithAttributes};
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// The kind of the attribute values declared by a contract.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValueKind {
+    Bytes,
+    String,
+    StringVector,
+    Integer,
+    IntegerVector,
+    Float,
+    FloatVector,
+    Boolean,
+    BooleanVector,
+    BBox,
+    BBoxVector,
+    Point,
+    PointVector,
+    Polygon,
+    PolygonVector,
+    Intersection,
+    TemporaryValue,
+}
+
+impl ValueKind {
+    /// The kind of the value, `None` for empty values which match any kind.
+    ///
+    pub fn of(value: &AttributeValueVariant) -> Option<Self> {
+        Some(match value {
+            AttributeValueVariant::Bytes(_, _) => ValueKind::Bytes,
+            AttributeValueVariant::String(_) => ValueKind::String,
+            AttributeValueVariant::StringVector(_) => ValueKind::StringVector,
+            AttributeValueVariant::Integer(_) => ValueKind::Integer,
+            AttributeValueVariant::IntegerVector(_) => ValueKind::IntegerVector,
+            AttributeValueVariant::Float(_) => ValueKind::Float,
+            AttributeValueVariant::FloatVector(_) => ValueKind::FloatVector,
+            AttributeValueVariant::Boolean(_) => ValueKind::Boolean,
+            AttributeValueVariant::BooleanVector(_) => ValueKind::BooleanVector,
+            AttributeValueVariant::BBox(_) => ValueKind::BBox,
+            AttributeValueVariant::BBoxVector(_) => ValueKind::BBoxVector,
+            AttributeValueVariant::Point(_) => ValueKind::Point,
+            AttributeValueVariant::PointVector(_) => ValueKind::PointVector,
+            AttributeValueVariant::Polygon(_) => ValueKind::Polygon,
+            AttributeValueVariant::PolygonVector(_) => ValueKind::PolygonVector,
+            AttributeValueVariant::Intersection(_) => ValueKind::Intersection,
+            AttributeValueVariant::TemporaryValue(_) => ValueKind::TemporaryValue,
+            AttributeValueVariant::None => return None,
+        })
+    }
+}
+
+/// Objects of the namespace with the label, any label when it is not set.
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObjectSpec {
+    pub namespace: String,
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+impl ObjectSpec {
+    fn matches(&self, namespace: &str, label: &str) -> bool {
+        self.namespace == namespace && self.label.as_deref().is_none_or(|l| l == label)
+    }
+
+    /// A producer emitting `self` may emit the objects the consumer requires with `other`.
+    ///
+    fn covers(&self, other: &ObjectSpec) -> bool {
+        self.namespace == other.namespace
+            && (self.label.is_none() || other.label.is_none() || self.label == other.label)
+    }
+}
+
+impl fmt::Display for ObjectSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}",
+            self.namespace,
+            self.label.as_deref().unwrap_or("*")
+        )
+    }
+}
+
+/// An attribute of the frame or, when the object is set, of the matching objects. Without
+/// the kind the values are not checked. With the unit the numeric values must be tagged
+/// with it.
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttributeSpec {
+    #[serde(default)]
+    pub object: Option<ObjectSpec>,
+    pub namespace: String,
+    pub name: String,
+    #[serde(default)]
+    pub kind: Option<ValueKind>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<Unit>,
+}
+
+impl AttributeSpec {
+    fn owner(&self) -> String {
+        match &self.object {
+            Some(object) => format!("objects {}", object),
+            None => "the frame".to_string(),
+        }
+    }
+
+    fn covers(&self, other: &AttributeSpec) -> bool {
+        self.namespace == other.namespace
+            && self.name == other.name
+            && match (&self.object, &other.object) {
+                (None, None) => true,
+                (Some(a), Some(b)) => a.covers(b),
+                _ => false,
+            }
+    }
+
+    fn wrong_kind(&self, attribute: &Attribute) -> Option<ValueKind> {
+        let expected = self.kind?;
+        attribute
+            .values
+            .iter()
+            .filter_map(|v| ValueKind::of(&v.value))
+            .find(|kind| *kind != expected)
+    }
+
+    fn wrong_unit(&self, attribute: &Attribute) -> Option<Option<Unit>> {
+        let expected = self.unit?;
+        attribute
+            .values
+            .iter()
+            .filter(|v| v.is_numeric())
+            .map(|v| v.unit)
+            .find(|unit| *unit != Some(expected))
+    }
+}
+
+fn unit_name(unit: &Option<Unit>) -> String {
+    unit.map(|u| u.to_string())
+        .unwrap_or_else(|| "no unit".to_string())
+}
+
+/// What a pipeline module emits (for producers) or requires (for consumers).
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModuleContract {
+    pub module: String,
+    #[serde(default)]
+    pub objects: Vec<ObjectSpec>,
+    #[serde(default)]
+    pub attributes: Vec<AttributeSpec>,
+}
+
+impl ModuleContract {
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn from_yaml(yaml: &str) -> anyhow::Result<Self> {
+        Ok(serde_yaml::from_str(yaml)?)
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Checks the frame against the requirements of a consumer: the frame attributes must be
+    /// present, the attributes of the matching objects must be present and of the declared
+    /// kind. Required objects are not checked, a frame may have no detections.
+    ///
+    pub fn check_frame(&self, frame: &VideoFrameProxy) -> Vec<ContractViolation> {
+        let mut violations = Vec::new();
+        let objects = frame.get_all_objects();
+        for spec in &self.attributes {
+            match &spec.object {
+                None => check_attribute(
+                    &self.module,
+                    spec,
+                    frame.get_uuid_as_string(),
+                    None,
+                    frame.get_attribute(&spec.namespace, &spec.name),
+                    &mut violations,
+                ),
+                Some(object_spec) => {
+                    for object in objects
+                        .iter()
+                        .filter(|o| object_spec.matches(&o.get_namespace(), &o.get_label()))
+                    {
+                        check_attribute(
+                            &self.module,
+                            spec,
+                            frame.get_uuid_as_string(),
+                            Some(object.get_id()),
+                            object.get_attribute(&spec.namespace, &spec.name),
+                            &mut violations,
+                        );
+                    }
+                }
+            }
+        }
+        violations
+    }
+
+    /// Checks the frames of a fixture captured by a debug tap, see [`Self::check_frame`].
+    ///
+    pub fn check_fixture(&self, path: impl AsRef<Path>) -> anyhow::Result<Vec<ContractViolation>> {
+        Ok(load_fixture(path)?
+            .iter()
+            .flat_map(|frame| self.check_frame(frame))
+            .collect())
+    }
+}
+
+fn check_attribute(
+    module: &str,
+    spec: &AttributeSpec,
+    frame: String,
+    object: Option<i64>,
+    attribute: Option<Attribute>,
+    violations: &mut Vec<ContractViolation>,
+) {
+    match attribute {
+        None => violations.push(ContractViolation::AbsentAttribute {
+            consumer: module.to_string(),
+            frame,
+            object,
+            namespace: spec.namespace.clone(),
+            name: spec.name.clone(),
+        }),
+        Some(attribute) => {
+            if let Some(found) = spec.wrong_kind(&attribute) {
+                violations.push(ContractViolation::WrongKind {
+                    consumer: module.to_string(),
+                    frame,
+                    object,
+                    namespace: spec.namespace.clone(),
+                    name: spec.name.clone(),
+                    expected: spec.kind.unwrap(),
+                    found,
+                });
+            }
+            if let Some(found) = spec.wrong_unit(&attribute) {
+                violations.push(ContractViolation::WrongUnit {
+                    consumer: module.to_string(),
+                    frame,
+                    object,
+                    namespace: spec.namespace.clone(),
+                    name: spec.name.clone(),
+                    expected: spec.unit.unwrap(),
+                    found,
+                });
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ContractViolation {
+    #[error("No producer emits objects {object} required by {consumer}")]
+    MissingObjects {
+        consumer: String,
+        object: ObjectSpec,
+    },
+    #[error("No producer emits attribute {namespace}/{name} of {owner} required by {consumer}")]
+    MissingAttribute {
+        consumer: String,
+        owner: String,
+        namespace: String,
+        name: String,
+    },
+    #[error("Producer {producer} emits attribute {namespace}/{name} as {found:?} while {consumer} requires {expected:?}")]
+    IncompatibleKind {
+        consumer: String,
+        producer: String,
+        namespace: String,
+        name: String,
+        expected: ValueKind,
+        found: ValueKind,
+    },
+    #[error("Producer {producer} emits attribute {namespace}/{name} in {} while {consumer} requires {expected}", unit_name(.found))]
+    IncompatibleUnit {
+        consumer: String,
+        producer: String,
+        namespace: String,
+        name: String,
+        expected: Unit,
+        found: Option<Unit>,
+    },
+    #[error("Frame {frame} (object {object:?}) lacks attribute {namespace}/{name} required by {consumer}")]
+    AbsentAttribute {
+        consumer: String,
+        frame: String,
+        object: Option<i64>,
+        namespace: String,
+        name: String,
+    },
+    #[error("Frame {frame} (object {object:?}) holds attribute {namespace}/{name} as {found:?} while {consumer} requires {expected:?}")]
+    WrongKind {
+        consumer: String,
+        frame: String,
+        object: Option<i64>,
+        namespace: String,
+        name: String,
+        expected: ValueKind,
+        found: ValueKind,
+    },
+    #[error("Frame {frame} (object {object:?}) holds attribute {namespace}/{name} in {} while {consumer} requires {expected}", unit_name(.found))]
+    WrongUnit {
+        consumer: String,
+        frame: String,
+        object: Option<i64>,
+        namespace: String,
+        name: String,
+        expected: Unit,
+        found: Option<Unit>,
+    },
+}
+
+/// Keeps the contracts of the producer and consumer modules and validates that the
+/// requirements of the consumers are met by what the producers declare to emit.
+///
+#[derive(Debug, Clone, Default)]
+pub struct ContractRegistry {
+    producers: Vec<ModuleContract>,
+    consumers: Vec<ModuleContract>,
+}
+
+fn register(contracts: &mut Vec<ModuleContract>, contract: ModuleContract) {
+    contracts.retain(|c| c.module != contract.module);
+    contracts.push(contract);
+}
+
+impl ContractRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers what the module emits, replacing the previous contract of the module.
+    ///
+    pub fn register_producer(&mut self, contract: ModuleContract) {
+        register(&mut self.producers, contract);
+    }
+
+    /// Registers what the module requires, replacing the previous contract of the module.
+    ///
+    pub fn register_consumer(&mut self, contract: ModuleContract) {
+        register(&mut self.consumers, contract);
+    }
+
+    pub fn get_producers(&self) -> &[ModuleContract] {
+        &self.producers
+    }
+
+    pub fn get_consumers(&self) -> &[ModuleContract] {
+        &self.consumers
+    }
+
+    pub fn get_consumer(&self, module: &str) -> Option<&ModuleContract> {
+        self.consumers.iter().find(|c| c.module == module)
+    }
+
+    fn check_contract(&self, consumer: &ModuleContract) -> Vec<ContractViolation> {
+        let mut violations = Vec::new();
+        for object in &consumer.objects {
+            if !self
+                .producers
+                .iter()
+                .flat_map(|p| &p.objects)
+                .any(|o| o.covers(object))
+            {
+                violations.push(ContractViolation::MissingObjects {
+                    consumer: consumer.module.clone(),
+                    object: object.clone(),
+                });
+            }
+        }
+        for attribute in &consumer.attributes {
+            let emitted = self
+                .producers
+                .iter()
+                .flat_map(|p| p.attributes.iter().map(move |a| (p, a)))
+                .filter(|(_, a)| a.covers(attribute))
+                .collect::<Vec<_>>();
+            if emitted.is_empty() {
+                violations.push(ContractViolation::MissingAttribute {
+                    consumer: consumer.module.clone(),
+                    owner: attribute.owner(),
+                    namespace: attribute.namespace.clone(),
+                    name: attribute.name.clone(),
+                });
+            }
+            for (producer, a) in emitted {
+                if let Some(found) = attribute
+                    .kind
+                    .and_then(|expected| a.kind.filter(|kind| *kind != expected))
+                {
+                    violations.push(ContractViolation::IncompatibleKind {
+                        consumer: consumer.module.clone(),
+                        producer: producer.module.clone(),
+                        namespace: attribute.namespace.clone(),
+                        name: attribute.name.clone(),
+                        expected: attribute.kind.unwrap(),
+                        found,
+                    });
+                }
+                if let Some(expected) = attribute.unit.filter(|unit| a.unit != Some(*unit)) {
+                    violations.push(ContractViolation::IncompatibleUnit {
+                        consumer: consumer.module.clone(),
+                        producer: producer.module.clone(),
+                        namespace: attribute.namespace.clone(),
+                        name: attribute.name.clone(),
+                        expected,
+                        found: a.unit,
+                    });
+                }
+            }
+        }
+        violations
+    }
+
+    /// Validates the requirements of the consumer against the registered producers.
+    ///
+    pub fn check(&self, consumer: &str) -> anyhow::Result<Vec<ContractViolation>> {
+        let Some(contract) = self.get_consumer(consumer) else {
+            bail!("Consumer {} is not registered", consumer);
+        };
+        Ok(self.check_contract(contract))
+    }
+
+    /// Validates the requirements of all registered consumers.
+    ///
+    pub fn check_all(&self) -> Vec<ContractViolation> {
+        self.consumers
+            .iter()
+            .flat_map(|c| self.check_contract(c))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::contracts::{
+        ContractRegistry, ContractViolation, ModuleContract, ValueKind,
+    };
+    use crate::primitives::attribute_value::AttributeValue;
+    use crate::primitives::unit::Unit;
+    use crate::primitives::WithAttributes;
+    use crate::test::gen_frame;
+
+    fn detector() -> ModuleContract {
+        ModuleContract::from_yaml(
+            r#"
+module: detector
+objects:
+  - namespace: peoplenet
+attributes:
+  - object: {namespace: peoplenet, label: person}
+    namespace: classifier
+    name: age
+    kind: integer
+  - namespace: detector
+    name: fps
+    kind: float
+"#,
+        )
+        .unwrap()
+    }
+
+    fn classifier() -> ModuleContract {
+        ModuleContract::from_yaml(
+            r#"
+module: tracker
+objects:
+  - namespace: peoplenet
+    label: person
+attributes:
+  - object: {namespace: peoplenet}
+    namespace: classifier
+    name: age
+    kind: float
+  - namespace: tracker
+    name: tracks
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_static_check() -> anyhow::Result<()> {
+        let mut registry = ContractRegistry::new();
+        registry.register_producer(detector());
+        registry.register_consumer(classifier());
+        assert!(registry.check("unknown").is_err());
+        let violations = registry.check("tracker")?;
+        assert_eq!(violations.len(), 2);
+        assert!(matches!(
+            &violations[0],
+            ContractViolation::IncompatibleKind {
+                expected: ValueKind::Float,
+                found: ValueKind::Integer,
+                ..
+            }
+        ));
+        assert!(matches!(
+            &violations[1],
+            ContractViolation::MissingAttribute { name, .. } if name == "tracks"
+        ));
+        assert_eq!(registry.check_all(), violations);
+        Ok(())
+    }
+
+    #[test]
+    fn test_frame_check() {
+        let contract = ModuleContract::from_json(
+            r#"{"module": "osd", "attributes": [
+                {"namespace": "system", "name": "missing"},
+                {"object": {"namespace": "test2"}, "namespace": "classifier", "name": "age", "kind": "integer"}
+            ]}"#,
+        )
+        .unwrap();
+        let mut frame = gen_frame();
+        for mut object in frame.get_all_objects() {
+            object.set_persistent_attribute(
+                "classifier",
+                "age",
+                &None,
+                false,
+                vec![AttributeValue::float(30.0, None)],
+            );
+        }
+        let violations = contract.check_frame(&frame);
+        assert!(matches!(
+            &violations[0],
+            ContractViolation::AbsentAttribute { object: None, name, .. } if name == "missing"
+        ));
+        // objects 1 and 2 are in the namespace, object 0 is not
+        assert_eq!(violations.len(), 3);
+        assert!(violations[1..].iter().all(|v| matches!(
+            v,
+            ContractViolation::WrongKind {
+                object: Some(1 | 2),
+                ..
+            }
+        )));
+        frame.set_persistent_attribute("system", "missing", &None, false, vec![]);
+        assert_eq!(contract.check_frame(&frame).len(), 2);
+    }
+
+    #[test]
+    fn test_units() -> anyhow::Result<()> {
+        let mut registry = ContractRegistry::new();
+        registry.register_producer(ModuleContract::from_yaml(
+            "module: speedometer\nattributes:\n  - {namespace: tracker, name: speed, kind: float, unit: km/h}\n",
+        )?);
+        let consumer = ModuleContract::from_yaml(
+            "module: alerts\nattributes:\n  - {namespace: tracker, name: speed, kind: float, unit: m/s}\n",
+        )?;
+        registry.register_consumer(consumer.clone());
+        let violations = registry.check("alerts")?;
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].to_string(),
+            "Producer speedometer emits attribute tracker/speed in km/h while alerts requires m/s"
+        );
+
+        let mut frame = gen_frame();
+        frame.set_persistent_attribute(
+            "tracker",
+            "speed",
+            &None,
+            false,
+            vec![AttributeValue::float(36.0, None).with_unit(Unit::KilometersPerHour)?],
+        );
+        let violations = consumer.check_frame(&frame);
+        assert!(matches!(
+            &violations[..],
+            [ContractViolation::WrongUnit {
+                expected: Unit::MetersPerSecond,
+                found: Some(Unit::KilometersPerHour),
+                ..
+            }]
+        ));
+        let speed = frame.get_attribute("tracker", "speed").unwrap().values[0]
+            .convert_to(Unit::MetersPerSecond)?;
+        frame.set_persistent_attribute("tracker", "speed", &None, false, vec![speed]);
+        assert!(consumer.check_frame(&frame).is_empty());
+        frame.set_persistent_attribute(
+            "tracker",
+            "speed",
+            &None,
+            false,
+            vec![AttributeValue::float(10.0, None)],
+        );
+        assert!(consumer.check_frame(&frame)[0]
+            .to_string()
+            .contains("in no unit"));
+        Ok(())
+    }
+}
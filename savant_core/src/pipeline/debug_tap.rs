@@ -0,0 +1,387 @@
+use crate::match_query::MatchQuery;
+use crate::message::Message;
+use crate::primitives::frame::VideoFrameProxy;
+use crate::protobuf::{serialize, MessageStreamWriter};
+use crate::webserver::kvs::synchronous::set_blob;
+use crate::webserver::kvs::KvsBlob;
+use crossbeam::channel::{Receiver, Sender, TrySendError};
+use log::{debug, error, warn};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+pub const CONTENT_TYPE_PROTOBUF: &str = "application/x-protobuf";
+
+/// The number of serialized frames waiting for the writer thread, the frames selected while
+/// the queue is full are dropped.
+pub const DEBUG_TAP_QUEUE_SIZE: usize = 64;
+
+/// Which frames entering the stage are dumped.
+///
+#[derive(Debug, Clone)]
+pub enum DebugTapSelector {
+    /// Every Nth frame, starting with the first one.
+    EveryNth(u64),
+    /// Frames having at least one object matching the query.
+    Query(MatchQuery),
+}
+
+/// Where the dumped frames go. Frames are serialized as messages, so the export redaction
+/// filter applies to them.
+///
+#[derive(Debug, Clone)]
+pub enum DebugTapSink {
+    /// A file per frame named `<stage>-<source_id>-<uuid>.pb` in the directory.
+    Directory(PathBuf),
+    /// A KVS blob `<namespace>/<stage>/<source_id>` holding the last dumped frame of the
+    /// source.
+    Kvs { namespace: String, ttl: Option<u64> },
+    /// A fixture file holding the first `max_frames` dumped frames as a message journal,
+    /// loaded by [`crate::pipeline::fixtures::load_fixture`]. The file is truncated when the
+    /// tap is created.
+    Fixture { path: PathBuf, max_frames: u64 },
+}
+
+/// Dumps frames entering a stage for debugging. The tap is installed and removed with
+/// [`crate::pipeline::Pipeline::set_debug_tap`] and can be paused without reinstalling.
+/// The selected frames are serialized when they enter the stage and written to the sink by a
+/// background thread, so the stage is not blocked on the sink. When the sink falls behind by
+/// [`DEBUG_TAP_QUEUE_SIZE`] frames, the selected frames are dropped and counted. Dump failures
+/// are logged and never fail the pipeline.
+///
+#[derive(Debug)]
+pub struct DebugTap {
+    selector: DebugTapSelector,
+    max_frames: Option<u64>,
+    enabled: AtomicBool,
+    seen: AtomicU64,
+    dumped: Arc<AtomicU64>,
+    dropped: AtomicU64,
+    sender: Sender<TapWrite>,
+}
+
+struct TapDump {
+    stage: String,
+    source_id: String,
+    uuid: String,
+    data: Vec<u8>,
+}
+
+enum TapWrite {
+    Dump(TapDump),
+    Flush(Sender<()>),
+}
+
+impl DebugTap {
+    pub fn new(selector: DebugTapSelector, sink: DebugTapSink) -> anyhow::Result<Self> {
+        Self::with_queue_size(selector, sink, DEBUG_TAP_QUEUE_SIZE)
+    }
+
+    fn with_queue_size(
+        selector: DebugTapSelector,
+        sink: DebugTapSink,
+        queue_size: usize,
+    ) -> anyhow::Result<Self> {
+        if let DebugTapSelector::EveryNth(0) = selector {
+            anyhow::bail!("The debug tap period must be greater than 0");
+        }
+        let mut max_frames = None;
+        match &sink {
+            DebugTapSink::Directory(dir) => std::fs::create_dir_all(dir)?,
+            DebugTapSink::Fixture {
+                path,
+                max_frames: n,
+            } => {
+                if *n == 0 {
+                    anyhow::bail!("The fixture must hold at least one frame");
+                }
+                if let Some(dir) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    std::fs::create_dir_all(dir)?;
+                }
+                std::fs::File::create(path)?;
+                max_frames = Some(*n);
+            }
+            DebugTapSink::Kvs { .. } => {}
+        }
+        let dumped = Arc::new(AtomicU64::new(0));
+        let (sender, receiver) = crossbeam::channel::bounded(queue_size);
+        let thread_dumped = dumped.clone();
+        // the thread writes the queued dumps and exits when the tap is dropped
+        std::thread::spawn(move || write_dumps(&sink, &thread_dumped, receiver));
+        Ok(Self {
+            selector,
+            max_frames,
+            enabled: AtomicBool::new(true),
+            seen: AtomicU64::new(0),
+            dumped,
+            dropped: AtomicU64::new(0),
+            sender,
+        })
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// The number of frames written to the sink, the frames still queued are not counted,
+    /// see [`DebugTap::flush`].
+    ///
+    pub fn get_dumped(&self) -> u64 {
+        self.dumped.load(Ordering::Relaxed)
+    }
+
+    /// The number of selected frames dropped because the writer thread fell behind.
+    ///
+    pub fn get_dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Waits until the queued frames are written to the sink.
+    ///
+    pub fn flush(&self) {
+        let (done, wait) = crossbeam::channel::bounded(1);
+        if self.sender.send(TapWrite::Flush(done)).is_ok() {
+            let _ = wait.recv();
+        }
+    }
+
+    fn is_selected(&self, frame: &VideoFrameProxy) -> bool {
+        match &self.selector {
+            DebugTapSelector::EveryNth(n) => self.seen.fetch_add(1, Ordering::Relaxed) % n == 0,
+            DebugTapSelector::Query(q) => !frame.access_objects(q).is_empty(),
+        }
+    }
+
+    /// Whether the fixture is full, the writer thread checks it again before appending a
+    /// frame, so the fixture never holds more frames than requested.
+    ///
+    fn is_full(&self) -> bool {
+        self.max_frames.is_some_and(|n| self.get_dumped() >= n)
+    }
+
+    fn encode(&self, stage: &str, frame: &VideoFrameProxy) -> anyhow::Result<TapDump> {
+        let message = Message::video_frame(frame);
+        let data = if self.max_frames.is_some() {
+            // the record is appended to the fixture as is
+            let mut writer = MessageStreamWriter::new(Vec::new());
+            writer.write_message(&message)?;
+            writer.into_inner()
+        } else {
+            serialize(&message)?
+        };
+        Ok(TapDump {
+            stage: stage.to_string(),
+            source_id: frame.get_source_id(),
+            uuid: frame.get_uuid_as_string(),
+            data,
+        })
+    }
+
+    pub(crate) fn observe(&self, stage: &str, frame: &VideoFrameProxy) {
+        if !self.is_enabled() || self.is_full() || !self.is_selected(frame) {
+            return;
+        }
+        let res = self.encode(stage, frame).and_then(|dump| {
+            match self.sender.try_send(TapWrite::Dump(dump)) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(_)) => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    warn!(
+                        target: "savant_rs::pipeline::debug_tap",
+                        "Debug tap of stage {} dropped frame {}, the sink falls behind",
+                        stage,
+                        frame.get_uuid_as_string()
+                    );
+                    Ok(())
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    anyhow::bail!("The debug tap writer is stopped")
+                }
+            }
+        });
+        if let Err(e) = res {
+            error!(
+                target: "savant_rs::pipeline::debug_tap",
+                "Debug tap of stage {} failed to dump frame {}: {}",
+                stage,
+                frame.get_uuid_as_string(),
+                e
+            );
+        }
+    }
+}
+
+fn write_dumps(sink: &DebugTapSink, dumped: &AtomicU64, receiver: Receiver<TapWrite>) {
+    for write in receiver {
+        let mut dump = match write {
+            TapWrite::Dump(dump) => dump,
+            TapWrite::Flush(done) => {
+                let _ = done.send(());
+                continue;
+            }
+        };
+        // the frames are appended by this thread only, so the capacity check does not race
+        if let DebugTapSink::Fixture { max_frames, .. } = sink {
+            if dumped.load(Ordering::Relaxed) >= *max_frames {
+                continue;
+            }
+        }
+        match write_dump(sink, &mut dump) {
+            Ok(()) => {
+                dumped.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => error!(
+                target: "savant_rs::pipeline::debug_tap",
+                "Debug tap of stage {} failed to dump frame {}: {}",
+                dump.stage, dump.uuid, e
+            ),
+        }
+    }
+}
+
+fn write_dump(sink: &DebugTapSink, dump: &mut TapDump) -> anyhow::Result<()> {
+    match sink {
+        DebugTapSink::Directory(dir) => {
+            let path = dir.join(format!(
+                "{}-{}-{}.pb",
+                dump.stage, dump.source_id, dump.uuid
+            ));
+            std::fs::write(&path, &dump.data)?;
+            debug!(
+                target: "savant_rs::pipeline::debug_tap",
+                "Debug tap of stage {} dumped {}",
+                dump.stage,
+                path.display()
+            );
+        }
+        DebugTapSink::Kvs { namespace, ttl } => {
+            let name = format!("{}/{}", dump.stage, dump.source_id);
+            set_blob(
+                namespace,
+                &name,
+                KvsBlob::new(CONTENT_TYPE_PROTOBUF, std::mem::take(&mut dump.data)),
+                *ttl,
+            )?;
+        }
+        DebugTapSink::Fixture { path, .. } => {
+            let mut file = OpenOptions::new().append(true).open(path)?;
+            file.write_all(&dump.data)?;
+            debug!(
+                target: "savant_rs::pipeline::debug_tap",
+                "Debug tap of stage {} dumped to {}",
+                dump.stage,
+                path.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::match_query::{MatchQuery, StringExpression};
+    use crate::pipeline::debug_tap::{DebugTap, DebugTapSelector, DebugTapSink, TapWrite};
+    use crate::protobuf::deserialize;
+    use crate::test::gen_frame;
+    use crate::utils::uuid_v7::incremental_uuid_v7;
+    use crate::webserver::kvs::synchronous::get_blob;
+
+    #[test]
+    fn test_every_nth_to_directory() -> anyhow::Result<()> {
+        assert!(DebugTap::new(
+            DebugTapSelector::EveryNth(0),
+            DebugTapSink::Kvs {
+                namespace: "debug".to_string(),
+                ttl: None
+            }
+        )
+        .is_err());
+        let dir = std::env::temp_dir().join(format!("savant-debug-tap-{}", incremental_uuid_v7()));
+        let tap = DebugTap::new(
+            DebugTapSelector::EveryNth(2),
+            DebugTapSink::Directory(dir.clone()),
+        )?;
+        for _ in 0..3 {
+            tap.observe("input", &gen_frame());
+        }
+        tap.set_enabled(false);
+        tap.observe("input", &gen_frame());
+        tap.flush();
+        assert_eq!(tap.get_dumped(), 2);
+        let files = std::fs::read_dir(&dir)?.collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(files.len(), 2);
+        let message = deserialize(&std::fs::read(files[0].path())?)?;
+        assert!(message.as_video_frame().is_some());
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_dropped_when_sink_falls_behind() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("savant-debug-tap-{}", incremental_uuid_v7()));
+        let tap = DebugTap::with_queue_size(
+            DebugTapSelector::EveryNth(1),
+            DebugTapSink::Directory(dir.clone()),
+            2,
+        )?;
+        // the writer thread blocks on the flush until it is received
+        let (done, wait) = crossbeam::channel::bounded(0);
+        tap.sender.send(TapWrite::Flush(done))?;
+        for _ in 0..3 {
+            tap.observe("input", &gen_frame());
+        }
+        assert!(tap.get_dropped() >= 1);
+        wait.recv()?;
+        tap.flush();
+        assert_eq!(tap.get_dumped() + tap.get_dropped(), 3);
+        assert_eq!(std::fs::read_dir(&dir)?.count() as u64, tap.get_dumped());
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_query_to_kvs() -> anyhow::Result<()> {
+        let tap = DebugTap::new(
+            DebugTapSelector::Query(MatchQuery::Label(StringExpression::EQ(
+                "no-such-label".to_string(),
+            ))),
+            DebugTapSink::Kvs {
+                namespace: "debug_tap_test".to_string(),
+                ttl: None,
+            },
+        )?;
+        let frame = gen_frame();
+        tap.observe("detector", &frame);
+        tap.flush();
+        assert_eq!(tap.get_dumped(), 0);
+
+        let tap = DebugTap::new(
+            DebugTapSelector::Query(MatchQuery::Idle),
+            DebugTapSink::Kvs {
+                namespace: "debug_tap_test".to_string(),
+                ttl: None,
+            },
+        )?;
+        tap.observe("detector", &frame);
+        tap.flush();
+        assert_eq!(tap.get_dumped(), 1);
+        let blob = get_blob(
+            "debug_tap_test",
+            &format!("detector/{}", frame.get_source_id()),
+        )
+        .unwrap();
+        let message = deserialize(&blob.data)?;
+        assert_eq!(
+            message.as_video_frame().unwrap().get_uuid(),
+            frame.get_uuid()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+use crate::primitives::frame::VideoFrameProxy;
+use hashbrown::HashMap;
+use parking_lot::Mutex;
+
+/// How the decimator selects the frames passed downstream.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecimationStrategy {
+    /// Every Nth frame of a source, starting with the first one.
+    EveryNth(u64),
+    /// At most the given number of frames per second of a source, measured by the frame PTS.
+    TargetFps(f64),
+    /// Frames are passed while the queue of the downstream stage is shorter than the limit.
+    QueueDepth { stage: String, max_len: usize },
+}
+
+#[derive(Debug, Default)]
+struct SourceState {
+    seen: u64,
+    last_kept: Option<f64>,
+}
+
+/// Decides which frames of the decimator stage are kept, the state is tracked per source.
+/// The decimator is installed with [`crate::pipeline::Pipeline::set_decimator`] and applied
+/// with [`crate::pipeline::Pipeline::decimate`] which deletes the skipped frames.
+///
+#[derive(Debug)]
+pub struct Decimator {
+    strategy: DecimationStrategy,
+    sources: Mutex<HashMap<String, SourceState>>,
+}
+
+impl Decimator {
+    pub fn new(strategy: DecimationStrategy) -> anyhow::Result<Self> {
+        match &strategy {
+            DecimationStrategy::EveryNth(0) => {
+                anyhow::bail!("The decimation period must be greater than 0")
+            }
+            DecimationStrategy::TargetFps(fps) if !fps.is_finite() || *fps <= 0.0 => {
+                anyhow::bail!("The target FPS must be positive, got {}", fps)
+            }
+            _ => (),
+        }
+        Ok(Self {
+            strategy,
+            sources: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn get_strategy(&self) -> &DecimationStrategy {
+        &self.strategy
+    }
+
+    /// Forgets the state of the source, e.g. when the stream is restarted.
+    ///
+    pub fn reset_source(&self, source_id: &str) {
+        self.sources.lock().remove(source_id);
+    }
+
+    /// Decides whether the frame is kept. `downstream_queue_len` is only used by the
+    /// [`DecimationStrategy::QueueDepth`] strategy.
+    ///
+    pub fn keep(&self, frame: &VideoFrameProxy, downstream_queue_len: usize) -> bool {
+        let mut sources = self.sources.lock();
+        let state = sources.entry(frame.get_source_id()).or_default();
+        state.seen += 1;
+        match &self.strategy {
+            DecimationStrategy::EveryNth(n) => (state.seen - 1) % n == 0,
+            DecimationStrategy::TargetFps(fps) => {
+                let (num, den) = frame.get_time_base();
+                let ts = frame.get_pts() as f64 * num as f64 / den as f64;
+                // a stream restart moves the PTS backwards, so the frame is kept
+                let keep = state
+                    .last_kept
+                    .is_none_or(|last| ts < last || ts - last >= 1.0 / fps);
+                if keep {
+                    state.last_kept = Some(ts);
+                }
+                keep
+            }
+            DecimationStrategy::QueueDepth { max_len, .. } => downstream_queue_len < *max_len,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::decimator::{DecimationStrategy, Decimator};
+    use crate::test::gen_frame;
+
+    #[test]
+    fn test_every_nth() -> anyhow::Result<()> {
+        assert!(Decimator::new(DecimationStrategy::EveryNth(0)).is_err());
+        let decimator = Decimator::new(DecimationStrategy::EveryNth(3))?;
+        let frame = gen_frame();
+        let kept = (0..7).filter(|_| decimator.keep(&frame, 0)).count();
+        assert_eq!(kept, 3);
+        decimator.reset_source(&frame.get_source_id());
+        assert!(decimator.keep(&frame, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_target_fps() -> anyhow::Result<()> {
+        assert!(Decimator::new(DecimationStrategy::TargetFps(0.0)).is_err());
+        let decimator = Decimator::new(DecimationStrategy::TargetFps(10.0))?;
+        let mut frame = gen_frame();
+        frame.set_time_base((1, 1000));
+        let kept = (0..30)
+            .filter(|i| {
+                frame.set_pts(i * 40);
+                decimator.keep(&frame, 0)
+            })
+            .count();
+        // 25 FPS for 1.2 seconds decimated to 10 FPS
+        assert_eq!(kept, 10);
+        frame.set_pts(0);
+        assert!(decimator.keep(&frame, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_queue_depth() -> anyhow::Result<()> {
+        let decimator = Decimator::new(DecimationStrategy::QueueDepth {
+            stage: "inference".to_string(),
+            max_len: 2,
+        })?;
+        let frame = gen_frame();
+        assert!(decimator.keep(&frame, 1));
+        assert!(!decimator.keep(&frame, 2));
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+use crossbeam::channel::{Receiver, Sender, TrySendError};
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The events kept for a subscriber which does not receive them, the later events are
+/// dropped.
+///
+pub const EVENT_QUEUE_SIZE: usize = 10_000;
+
+/// A change of the payloads in the pipeline, see [`crate::pipeline::Pipeline::subscribe`].
+/// The stages are identified by the names.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum PipelineEvent {
+    /// A frame, an audio or a telemetry frame is added to the stage.
+    FrameAdded { id: i64, stage: String },
+    /// A frame or a batch is moved as is.
+    FrameMoved { id: i64, from: String, to: String },
+    /// The frames are packed into the batch of the stage.
+    BatchCreated {
+        id: i64,
+        stage: String,
+        frame_ids: Vec<i64>,
+    },
+    /// The batch is unpacked into the frames of the stage.
+    BatchUnpacked {
+        id: i64,
+        stage: String,
+        frame_ids: Vec<i64>,
+    },
+    /// A frame or a batch leaves the pipeline, `dropped` when the pipeline does not process
+    /// it further, e.g. it is evicted.
+    FrameDeleted {
+        id: i64,
+        stage: String,
+        dropped: bool,
+    },
+    /// The updates of a frame or a batch are applied.
+    UpdateApplied { id: i64, stage: String },
+}
+
+/// Delivers the events to the subscribers, the subscribers whose receivers are dropped are
+/// removed when the next event is published.
+///
+#[derive(Debug, Default)]
+pub(crate) struct EventBus {
+    active: AtomicBool,
+    subscribers: Mutex<Vec<Sender<PipelineEvent>>>,
+}
+
+impl EventBus {
+    pub fn subscribe(&self) -> Receiver<PipelineEvent> {
+        let (sender, receiver) = crossbeam::channel::bounded(EVENT_QUEUE_SIZE);
+        let mut subscribers = self.subscribers.lock();
+        subscribers.push(sender);
+        self.active.store(true, Ordering::Relaxed);
+        receiver
+    }
+
+    /// Sends the event to the subscribers, the event is built only when there are
+    /// subscribers.
+    ///
+    pub fn publish<F>(&self, event: F)
+    where
+        F: FnOnce() -> PipelineEvent,
+    {
+        if !self.active.load(Ordering::Relaxed) {
+            return;
+        }
+        let event = event();
+        let mut subscribers = self.subscribers.lock();
+        subscribers.retain(|s| match s.try_send(event.clone()) {
+            Ok(_) => true,
+            Err(TrySendError::Full(_)) => {
+                log::debug!(
+                    target: "savant_rs::pipeline::events",
+                    "The event queue of a subscriber is full, the event is dropped"
+                );
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        });
+        self.active
+            .store(!subscribers.is_empty(), Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::events::PipelineEvent;
+    use crate::pipeline::{Pipeline, PipelineConfiguration, PipelineStagePayloadType};
+    use crate::test::gen_frame;
+
+    #[test]
+    fn test_subscribe() -> anyhow::Result<()> {
+        let pipeline = Pipeline::new(
+            vec![
+                (
+                    "input".to_string(),
+                    PipelineStagePayloadType::Frame,
+                    None,
+                    None,
+                ),
+                (
+                    "infer".to_string(),
+                    PipelineStagePayloadType::Batch,
+                    None,
+                    None,
+                ),
+                (
+                    "output".to_string(),
+                    PipelineStagePayloadType::Frame,
+                    None,
+                    None,
+                ),
+                (
+                    "sink".to_string(),
+                    PipelineStagePayloadType::Frame,
+                    None,
+                    None,
+                ),
+            ],
+            PipelineConfiguration::default(),
+        )?;
+        // the events before the subscription are not delivered
+        let early = pipeline.add_frame("input", gen_frame())?;
+        let events = pipeline.subscribe();
+        let id = pipeline.add_frame("input", gen_frame())?;
+        let batch_id = pipeline.move_and_pack_frames("infer", vec![early, id])?;
+        pipeline.apply_updates(batch_id)?;
+        let frame_ids = pipeline.move_and_unpack_batch("output", batch_id)?;
+        pipeline.move_as_is("sink", vec![id])?;
+        pipeline.delete(id)?;
+
+        let received = events.try_iter().collect::<Vec<_>>();
+        assert_eq!(
+            received,
+            vec![
+                PipelineEvent::FrameAdded {
+                    id,
+                    stage: "input".to_string()
+                },
+                PipelineEvent::BatchCreated {
+                    id: batch_id,
+                    stage: "infer".to_string(),
+                    frame_ids: vec![early, id]
+                },
+                PipelineEvent::UpdateApplied {
+                    id: batch_id,
+                    stage: "infer".to_string()
+                },
+                PipelineEvent::BatchUnpacked {
+                    id: batch_id,
+                    stage: "output".to_string(),
+                    frame_ids
+                },
+                PipelineEvent::FrameMoved {
+                    id,
+                    from: "output".to_string(),
+                    to: "sink".to_string()
+                },
+                PipelineEvent::FrameDeleted {
+                    id,
+                    stage: "sink".to_string(),
+                    dropped: false
+                },
+            ]
+        );
+
+        drop(events);
+        pipeline.delete(early)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,208 @@
+use crate::primitives::frame::{VideoFrameContent, VideoFrameProxy};
+use crate::primitives::raw_content::InternalFrame;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// The faults injected into the payloads of a stage, each fault is rolled independently for
+/// every payload with its probability.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct FaultInjectorConfig {
+    /// The probability of a payload being delayed by `delay_ms`.
+    #[serde(default)]
+    pub delay_probability: f64,
+    #[serde(default)]
+    pub delay_ms: u64,
+    /// The probability of a payload being deleted from the pipeline.
+    #[serde(default)]
+    pub drop_probability: f64,
+    /// The probability of the frames of a payload being corrupted: internal content has its
+    /// bytes inverted, other content is removed. Audio and telemetry payloads are never
+    /// corrupted.
+    #[serde(default)]
+    pub corrupt_probability: f64,
+}
+
+/// The faults rolled for a payload, a dropped payload is neither delayed nor corrupted.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Faults {
+    pub delay: Option<Duration>,
+    pub drop: bool,
+    pub corrupt: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct FaultInjectorStats {
+    pub delayed: u64,
+    pub dropped: u64,
+    pub corrupted: u64,
+}
+
+/// Injects faults into the payloads of a stage to verify the recovery logic of the pipeline.
+/// The injector is installed with [`crate::pipeline::Pipeline::set_fault_injector`] or with
+/// the `/chaos` webserver endpoints. It is applied to every payload entering the stage after
+/// the enter hooks: a dropped payload is deleted instead of entering the stage, a delayed one
+/// holds the stage for the delay. [`crate::pipeline::Pipeline::inject_faults`] applies it to
+/// the payloads already in the stage.
+///
+#[derive(Debug)]
+pub struct FaultInjector {
+    config: FaultInjectorConfig,
+    delayed: AtomicU64,
+    dropped: AtomicU64,
+    corrupted: AtomicU64,
+}
+
+impl FaultInjector {
+    pub fn new(config: FaultInjectorConfig) -> anyhow::Result<Self> {
+        for (name, p) in [
+            ("delay", config.delay_probability),
+            ("drop", config.drop_probability),
+            ("corrupt", config.corrupt_probability),
+        ] {
+            if !(0.0..=1.0).contains(&p) {
+                anyhow::bail!("The {} probability must be within [0, 1], got {}", name, p);
+            }
+        }
+        Ok(Self {
+            config,
+            delayed: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            corrupted: AtomicU64::new(0),
+        })
+    }
+
+    pub fn get_config(&self) -> &FaultInjectorConfig {
+        &self.config
+    }
+
+    /// The number of payloads affected by each fault.
+    ///
+    pub fn get_stats(&self) -> FaultInjectorStats {
+        FaultInjectorStats {
+            delayed: self.delayed.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            corrupted: self.corrupted.load(Ordering::Relaxed),
+        }
+    }
+
+    fn roll(p: f64) -> bool {
+        p > 0.0 && rand::thread_rng().gen_bool(p)
+    }
+
+    /// Rolls the faults of a payload and counts them.
+    ///
+    pub fn roll_faults(&self) -> Faults {
+        if Self::roll(self.config.drop_probability) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return Faults {
+                drop: true,
+                ..Faults::default()
+            };
+        }
+        let delay = (self.config.delay_ms > 0 && Self::roll(self.config.delay_probability))
+            .then(|| Duration::from_millis(self.config.delay_ms));
+        if delay.is_some() {
+            self.delayed.fetch_add(1, Ordering::Relaxed);
+        }
+        let corrupt = Self::roll(self.config.corrupt_probability);
+        if corrupt {
+            self.corrupted.fetch_add(1, Ordering::Relaxed);
+        }
+        Faults {
+            delay,
+            drop: false,
+            corrupt,
+        }
+    }
+}
+
+/// Corrupts the content of the frame: the bytes of internal content are inverted keeping
+/// the layout, other content and content which fails to decompress is removed.
+///
+pub(crate) fn corrupt_frame(frame: &VideoFrameProxy) {
+    let content = match frame.get_content().as_ref() {
+        VideoFrameContent::Internal(internal) => match internal.get_data() {
+            Ok(data) => {
+                let data = data.iter().map(|b| !b).collect::<Vec<_>>();
+                let corrupted = match internal.get_layout() {
+                    Some(layout) => InternalFrame::raw(data, layout.clone())
+                        .expect("The data size is not changed"),
+                    None => InternalFrame::new(data),
+                };
+                VideoFrameContent::Internal(corrupted)
+            }
+            Err(_) => VideoFrameContent::None,
+        },
+        _ => VideoFrameContent::None,
+    };
+    frame.clone().set_content(content);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::fault_injector::{corrupt_frame, FaultInjector, FaultInjectorConfig};
+    use crate::primitives::frame::{ExternalFrame, VideoFrameContent};
+    use crate::primitives::raw_content::InternalFrame;
+    use crate::test::gen_frame;
+    use std::time::Duration;
+
+    #[test]
+    fn test_roll_faults() -> anyhow::Result<()> {
+        assert!(FaultInjector::new(FaultInjectorConfig {
+            drop_probability: 1.5,
+            ..FaultInjectorConfig::default()
+        })
+        .is_err());
+        let injector = FaultInjector::new(FaultInjectorConfig {
+            delay_probability: 1.0,
+            delay_ms: 5,
+            corrupt_probability: 1.0,
+            ..FaultInjectorConfig::default()
+        })?;
+        let faults = injector.roll_faults();
+        assert_eq!(faults.delay, Some(Duration::from_millis(5)));
+        assert!(faults.corrupt && !faults.drop);
+
+        let injector = FaultInjector::new(FaultInjectorConfig {
+            drop_probability: 1.0,
+            corrupt_probability: 1.0,
+            ..FaultInjectorConfig::default()
+        })?;
+        assert!(injector.roll_faults().drop);
+        let stats = injector.get_stats();
+        assert_eq!((stats.dropped, stats.corrupted), (1, 0));
+
+        let config = serde_json::from_str::<FaultInjectorConfig>(r#"{"drop_probability": 0.1}"#)?;
+        assert_eq!(config.drop_probability, 0.1);
+        assert_eq!(config.delay_ms, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_corrupt_frame() {
+        let mut frame = gen_frame();
+        frame.set_content(VideoFrameContent::Internal(InternalFrame::new(vec![
+            0, 1, 255,
+        ])));
+        corrupt_frame(&frame);
+        match frame.get_content().as_ref() {
+            VideoFrameContent::Internal(internal) => {
+                assert_eq!(internal.get_data().ok(), Some(&[255, 254, 0][..]))
+            }
+            _ => panic!("Internal content expected"),
+        }
+        frame.set_content(VideoFrameContent::External(ExternalFrame::new(
+            "s3",
+            &Some("bucket/key"),
+        )));
+        corrupt_frame(&frame);
+        assert!(matches!(
+            frame.get_content().as_ref(),
+            VideoFrameContent::None
+        ));
+    }
+}
@@ -0,0 +1,194 @@
+use crate::primitives::frame::VideoFrameProxy;
+use crate::protobuf::MessageStreamReader;
+use anyhow::{bail, Context};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+/// Loads the frames of a fixture file captured by a debug tap with
+/// [`crate::pipeline::debug_tap::DebugTapSink::Fixture`].
+///
+pub fn load_fixture(path: impl AsRef<Path>) -> anyhow::Result<Vec<VideoFrameProxy>> {
+    let path = path.as_ref();
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open the fixture {}", path.display()))?;
+    let mut frames = Vec::new();
+    for message in MessageStreamReader::new(BufReader::new(file)) {
+        let message = message?;
+        match message.as_video_frame() {
+            Some(frame) => frames.push(frame),
+            None => bail!(
+                "The fixture {} holds a message which is not a video frame",
+                path.display()
+            ),
+        }
+    }
+    Ok(frames)
+}
+
+/// Generates the scaffolding of regression tests loading a fixture: the test checks the
+/// captured frames and leaves a placeholder for running the stage processor on them.
+///
+#[derive(Debug, Clone)]
+pub struct FixtureScaffold {
+    fixture: PathBuf,
+    stage: String,
+    test_name: String,
+    frames: usize,
+    source_ids: Vec<String>,
+}
+
+impl FixtureScaffold {
+    pub fn new(fixture: impl AsRef<Path>, stage: &str, test_name: &str) -> anyhow::Result<Self> {
+        let mut chars = test_name.chars();
+        if !chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            bail!("The test name must be an identifier: {}", test_name);
+        }
+        let fixture = fixture.as_ref().to_path_buf();
+        let frames = load_fixture(&fixture)?;
+        let mut source_ids = frames.iter().map(|f| f.get_source_id()).collect::<Vec<_>>();
+        source_ids.sort();
+        source_ids.dedup();
+        Ok(Self {
+            fixture,
+            stage: stage.to_string(),
+            test_name: test_name.to_string(),
+            frames: frames.len(),
+            source_ids,
+        })
+    }
+
+    pub fn get_frames(&self) -> usize {
+        self.frames
+    }
+
+    pub fn get_source_ids(&self) -> &[String] {
+        &self.source_ids
+    }
+
+    fn fixture_path(&self) -> String {
+        format!("{:?}", self.fixture.display().to_string())
+    }
+
+    pub fn rust_test(&self) -> String {
+        format!(
+            r#"use savant_core::pipeline::fixtures::load_fixture;
+
+/// Frames captured at the `{stage}` stage from the sources {sources:?}.
+#[test]
+fn {name}() -> anyhow::Result<()> {{
+    let frames = load_fixture({path})?;
+    assert_eq!(frames.len(), {frames});
+    for frame in frames {{
+        // run the `{stage}` stage processor on the frame and check the results
+        assert!(!frame.get_source_id().is_empty());
+    }}
+    Ok(())
+}}
+"#,
+            stage = self.stage,
+            sources = self.source_ids,
+            name = self.test_name,
+            path = self.fixture_path(),
+            frames = self.frames,
+        )
+    }
+
+    pub fn python_test(&self) -> String {
+        format!(
+            r#"from savant_rs.utils import load_fixture
+
+
+def {name}():
+    """Frames captured at the `{stage}` stage from the sources {sources:?}."""
+    frames = load_fixture({path})
+    assert len(frames) == {frames}
+    for frame in frames:
+        # run the `{stage}` stage processor on the frame and check the results
+        assert frame.source_id
+"#,
+            stage = self.stage,
+            sources = self.source_ids,
+            name = self.test_name,
+            path = self.fixture_path(),
+            frames = self.frames,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::debug_tap::{DebugTap, DebugTapSelector, DebugTapSink};
+    use crate::pipeline::fixtures::{load_fixture, FixtureScaffold};
+    use crate::test::gen_frame;
+    use crate::utils::uuid_v7::incremental_uuid_v7;
+
+    #[test]
+    fn test_capture_and_scaffold() -> anyhow::Result<()> {
+        let path = std::env::temp_dir()
+            .join(format!("savant-fixture-{}", incremental_uuid_v7()))
+            .join("detector.fixture");
+        let tap = DebugTap::new(
+            DebugTapSelector::EveryNth(1),
+            DebugTapSink::Fixture {
+                path: path.clone(),
+                max_frames: 2,
+            },
+        )?;
+        let frames = (0..3).map(|_| gen_frame()).collect::<Vec<_>>();
+        for frame in &frames {
+            tap.observe("detector", frame);
+        }
+        tap.flush();
+        assert_eq!(tap.get_dumped(), 2);
+
+        let loaded = load_fixture(&path)?;
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[1].get_uuid(), frames[1].get_uuid());
+        assert!(loaded[0].get_object(1).is_some());
+
+        assert!(FixtureScaffold::new(&path, "detector", "1st").is_err());
+        let scaffold = FixtureScaffold::new(&path, "detector", "test_detector")?;
+        assert_eq!(scaffold.get_frames(), 2);
+        assert_eq!(scaffold.get_source_ids(), &["test".to_string()]);
+        let rust = scaffold.rust_test();
+        assert!(rust.contains("fn test_detector()"));
+        assert!(rust.contains("assert_eq!(frames.len(), 2);"));
+        let python = scaffold.python_test();
+        assert!(python.contains("def test_detector():"));
+        assert!(python.contains(&format!("load_fixture({:?})", path.display().to_string())));
+        std::fs::remove_dir_all(path.parent().unwrap())?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_concurrent_capture() -> anyhow::Result<()> {
+        let path =
+            std::env::temp_dir().join(format!("savant-fixture-{}.fixture", incremental_uuid_v7()));
+        let tap = DebugTap::new(
+            DebugTapSelector::EveryNth(1),
+            DebugTapSink::Fixture {
+                path: path.clone(),
+                max_frames: 3,
+            },
+        )?;
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..4 {
+                        tap.observe("detector", &gen_frame());
+                    }
+                });
+            }
+        });
+        tap.flush();
+        assert_eq!(tap.get_dumped(), 3);
+        assert_eq!(load_fixture(&path)?.len(), 3);
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
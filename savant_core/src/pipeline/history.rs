@@ -0,0 +1,302 @@
+use crate::message::Message;
+use crate::primitives::frame::VideoFrameProxy;
+use crate::protobuf::{MessageStreamReader, MessageStreamWriter};
+use crate::utils::uuid_v7::incremental_uuid_v7;
+use anyhow::bail;
+use crossbeam::channel::{Receiver, Sender};
+use log::error;
+use savant_protobuf::generated;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
+use uuid::Uuid;
+
+/// The routing label of the history records naming the stage the frame left:
+/// `history-stage:<stage>`.
+pub const HISTORY_STAGE_LABEL: &str = "history-stage:";
+
+/// The extension of the history journals.
+pub const HISTORY_EXTENSION: &str = "history";
+
+/// Records the frames leaving the stages of the pipeline, so the state of a frame after any
+/// stage is reconstructed later with [`reconstruct_frame_at`], also after the process is
+/// restarted. Every run writes the journal `<run uuid>.history` to the directory, a record
+/// is the frame message labelled with [`HISTORY_STAGE_LABEL`] and the stage. Installed with
+/// [`crate::pipeline::Pipeline::set_frame_history`].
+///
+/// The records are taken before the export redaction, so the frames are reconstructed
+/// exactly; the journals hold the redacted attributes and must be protected like the frames
+/// themselves. The records are written by a background thread, so a stage never waits for
+/// the disk.
+///
+/// A record is written for every frame leaving every stage, so the history is meant for
+/// investigations rather than for the production load; the old journals are removed with
+/// [`crate::retention::RetentionManager`].
+///
+#[derive(Debug)]
+pub struct FrameHistory {
+    directory: PathBuf,
+    path: PathBuf,
+    sender: Option<Sender<HistoryWrite>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+#[derive(Debug)]
+enum HistoryWrite {
+    Record(Box<generated::Message>),
+    Flush(Sender<()>),
+}
+
+fn write_history(path: &Path, file: File, receiver: Receiver<HistoryWrite>) {
+    let mut writer = MessageStreamWriter::new(BufWriter::new(file));
+    let report = |e: &dyn std::fmt::Display| {
+        error!(
+            target: "savant_rs::pipeline::history",
+            "Failed to write the frame history {}: {}",
+            path.display(),
+            e
+        )
+    };
+    for write in receiver.iter() {
+        match write {
+            HistoryWrite::Record(record) => {
+                if let Err(e) = writer.write_unredacted(&record) {
+                    report(&e);
+                }
+                // the records queued meanwhile are written before the file is flushed
+                if !receiver.is_empty() {
+                    continue;
+                }
+            }
+            HistoryWrite::Flush(done) => {
+                if let Err(e) = writer.flush() {
+                    report(&e);
+                }
+                let _ = done.send(());
+                continue;
+            }
+        }
+        if let Err(e) = writer.flush() {
+            report(&e);
+        }
+    }
+    if let Err(e) = writer.flush() {
+        report(&e);
+    }
+}
+
+impl FrameHistory {
+    pub fn new(directory: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let directory = directory.as_ref().to_path_buf();
+        std::fs::create_dir_all(&directory)?;
+        let path = directory.join(format!("{}.{}", incremental_uuid_v7(), HISTORY_EXTENSION));
+        let file = File::create(&path)?;
+        let (sender, receiver) = crossbeam::channel::unbounded();
+        let thread_path = path.clone();
+        let thread = std::thread::spawn(move || write_history(&thread_path, file, receiver));
+        Ok(Self {
+            directory,
+            path,
+            sender: Some(sender),
+            thread: Some(thread),
+        })
+    }
+
+    pub fn get_directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// The journal of the current run.
+    ///
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    fn send(&self, write: HistoryWrite) -> bool {
+        self.sender
+            .as_ref()
+            .is_some_and(|sender| sender.send(write).is_ok())
+    }
+
+    /// Waits until the recorded frames are written to the journal.
+    ///
+    pub fn flush(&self) {
+        let (done, wait) = crossbeam::channel::bounded(1);
+        if self.send(HistoryWrite::Flush(done)) {
+            let _ = wait.recv();
+        }
+    }
+
+    pub(crate) fn record(&self, stage: &str, frame: &VideoFrameProxy) {
+        let mut message = Message::video_frame(frame);
+        message.set_labels(vec![format!("{}{}", HISTORY_STAGE_LABEL, stage)]);
+        // the conversion is the snapshot of the frame, the frame changes in the next stages
+        let record = Box::new(generated::Message::from(&message));
+        if !self.send(HistoryWrite::Record(record)) {
+            error!(
+                target: "savant_rs::pipeline::history",
+                "Failed to record frame {} leaving stage {}: the history writer is stopped",
+                frame.get_uuid_as_string(),
+                stage
+            );
+        }
+    }
+}
+
+impl Drop for FrameHistory {
+    fn drop(&mut self) {
+        // the thread writes the queued records and exits when the channel is closed
+        self.sender.take();
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                error!(
+                    target: "savant_rs::pipeline::history",
+                    "The frame history writer panicked"
+                );
+            }
+        }
+    }
+}
+
+fn record_stage(message: &Message) -> Option<String> {
+    message
+        .get_labels()
+        .iter()
+        .find_map(|l| l.strip_prefix(HISTORY_STAGE_LABEL).map(|s| s.to_string()))
+}
+
+/// The journals of the directory, the oldest run first.
+///
+fn history_journals(directory: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    if !directory.is_dir() {
+        bail!("{} is not a directory", directory.display());
+    }
+    let mut journals = std::fs::read_dir(directory)?
+        .map(|e| e.map(|e| e.path()))
+        .collect::<std::io::Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|p| p.extension().is_some_and(|e| e == HISTORY_EXTENSION))
+        .collect::<Vec<_>>();
+    // the names are UUIDv7, so they are ordered by the start of the run
+    journals.sort();
+    Ok(journals)
+}
+
+/// The stages the frame left in the order it left them, with the state of the frame after
+/// each of them. The frame is searched in the journals of the directory, a frame is
+/// recorded by a single run.
+///
+pub fn frame_lineage(
+    directory: &Path,
+    uuid: Uuid,
+) -> anyhow::Result<Vec<(String, VideoFrameProxy)>> {
+    for journal in history_journals(directory)?.into_iter().rev() {
+        let reader = MessageStreamReader::new(BufReader::new(File::open(&journal)?));
+        let mut lineage = Vec::new();
+        for message in reader {
+            let message = message?;
+            let Some(frame) = message.as_video_frame() else {
+                continue;
+            };
+            if frame.get_uuid() != uuid {
+                continue;
+            }
+            if let Some(stage) = record_stage(&message) {
+                lineage.push((stage, frame));
+            }
+        }
+        if !lineage.is_empty() {
+            return Ok(lineage);
+        }
+    }
+    Ok(Vec::new())
+}
+
+/// Rebuilds the frame, with its objects and attributes, as it left the stage in the run
+/// which recorded it. When the frame left the stage several times, the last state is
+/// returned. Returns `None` when the frame did not leave the stage.
+///
+pub fn reconstruct_frame_at(
+    directory: &Path,
+    uuid: Uuid,
+    stage: &str,
+) -> anyhow::Result<Option<VideoFrameProxy>> {
+    Ok(frame_lineage(directory, uuid)?
+        .into_iter()
+        .rev()
+        .find(|(s, _)| s == stage)
+        .map(|(_, frame)| frame))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::history::{frame_lineage, reconstruct_frame_at, FrameHistory};
+    use crate::pipeline::{Pipeline, PipelineConfiguration, PipelineStagePayloadType};
+    use crate::primitives::attribute_value::AttributeValue;
+    use crate::primitives::object::{IdCollisionResolutionPolicy, ObjectOperations};
+    use crate::primitives::WithAttributes;
+    use crate::protobuf::{set_export_filter, RedactionFilter};
+    use crate::test::{gen_frame, gen_object};
+    use crate::utils::uuid_v7::incremental_uuid_v7;
+
+    #[test]
+    #[serial_test::serial]
+    fn test_reconstruct_frame() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("savant-history-{}", incremental_uuid_v7()));
+        let stages = [
+            ("detect", PipelineStagePayloadType::Frame),
+            ("track", PipelineStagePayloadType::Batch),
+            ("output", PipelineStagePayloadType::Frame),
+        ]
+        .into_iter()
+        .map(|(s, t)| (s.to_string(), t, None, None))
+        .collect();
+        let pipeline = Pipeline::new(stages, PipelineConfiguration::default())?;
+        pipeline.set_frame_history(Some(FrameHistory::new(&dir)?));
+
+        let mut frame = gen_frame();
+        frame.set_persistent_attribute(
+            "history_test",
+            "secret",
+            &None,
+            false,
+            vec![AttributeValue::integer(1, None)],
+        );
+        let objects = frame.get_all_objects().len();
+        // the history keeps the frames as they are, the export redaction is not applied
+        set_export_filter(Some(RedactionFilter::new(&["history_test/*"])?));
+        let id = pipeline.add_frame("detect", frame.clone())?;
+        frame.add_object(gen_object(100), IdCollisionResolutionPolicy::Error)?;
+        let id = pipeline.move_and_pack_frames("track", vec![id])?;
+        frame.delete_objects_with_ids(&[0]);
+        let ids = pipeline.move_and_unpack_batch("output", id)?;
+        pipeline.delete(ids[0])?;
+        set_export_filter(None);
+
+        let detect = pipeline
+            .reconstruct_frame_at(frame.get_uuid(), "detect")?
+            .unwrap();
+        assert_eq!(detect.get_all_objects().len(), objects + 1);
+        assert!(detect.get_object(0).is_some());
+        assert!(detect.get_attribute("history_test", "secret").is_some());
+        assert!(pipeline
+            .reconstruct_frame_at(frame.get_uuid(), "missing")
+            .is_err());
+        // the history of a past run is reconstructed without the pipeline
+        drop(pipeline);
+
+        let track = reconstruct_frame_at(&dir, frame.get_uuid(), "track")?.unwrap();
+        assert_eq!(track.get_all_objects().len(), objects);
+        assert!(track.get_object(0).is_none());
+        assert!(reconstruct_frame_at(&dir, frame.get_uuid(), "output")?.is_some());
+
+        let stages = frame_lineage(&dir, frame.get_uuid())?
+            .into_iter()
+            .map(|(s, _)| s)
+            .collect::<Vec<_>>();
+        assert_eq!(stages, vec!["detect", "track", "output"]);
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+use crate::pipeline::PipelinePayload;
+
+/// The Rust code run on the payloads entering and leaving a stage, e.g. to stamp attributes,
+/// prune objects or check the metadata without passing the frames through the bindings.
+/// Unlike the ingress and egress functions, any number of hooks is registered per stage and
+/// they are added and removed while the pipeline runs, see
+/// [`crate::pipeline::Pipeline::add_stage_hook`].
+///
+/// `on_enter` is called after the ingress function and `on_exit` before the egress
+/// function, the hooks are called in the order of the registration. An enter hook filters
+/// the payloads: when it returns [`HookAction::Drop`], the later hooks are not called and
+/// the payload is deleted as dropped instead of entering the stage. The hooks are called
+/// under the lock of the stage payloads and must not call the pipeline.
+///
+pub trait StageHook: Send + Sync {
+    fn on_enter(&self, _payload: &mut PipelinePayload) -> HookAction {
+        HookAction::Keep
+    }
+    fn on_exit(&self, _payload: &mut PipelinePayload) {}
+}
+
+/// Whether the payload entering the stage is kept, see [`StageHook::on_enter`].
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookAction {
+    Keep,
+    Drop,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::hooks::{HookAction, StageHook};
+    use crate::pipeline::{
+        Pipeline, PipelineConfiguration, PipelinePayload, PipelineStagePayloadType,
+    };
+    use crate::primitives::WithAttributes;
+    use crate::test::gen_frame;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Default)]
+    struct Stamp(AtomicUsize);
+
+    impl StageHook for Stamp {
+        fn on_enter(&self, payload: &mut PipelinePayload) -> HookAction {
+            if let PipelinePayload::Frame(frame, ..) = payload {
+                frame.set_persistent_attribute("hooks", "stamp", &None, false, vec![]);
+            }
+            HookAction::Keep
+        }
+
+        fn on_exit(&self, _payload: &mut PipelinePayload) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_stage_hooks() -> anyhow::Result<()> {
+        let pipeline = Pipeline::new(
+            vec![
+                (
+                    "input".to_string(),
+                    PipelineStagePayloadType::Frame,
+                    None,
+                    None,
+                ),
+                (
+                    "output".to_string(),
+                    PipelineStagePayloadType::Frame,
+                    None,
+                    None,
+                ),
+            ],
+            PipelineConfiguration::default(),
+        )?;
+        let stamp = Arc::new(Stamp::default());
+        pipeline.add_stage_hook("output", "stamp", stamp.clone())?;
+        assert!(pipeline
+            .add_stage_hook("output", "stamp", stamp.clone())
+            .is_err());
+        assert_eq!(pipeline.get_stage_hooks("output")?, vec!["stamp"]);
+
+        let frame = gen_frame();
+        let id = pipeline.add_frame("input", frame.clone())?;
+        assert!(frame.get_attribute("hooks", "stamp").is_none());
+        pipeline.move_as_is("output", vec![id])?;
+        assert!(frame.get_attribute("hooks", "stamp").is_some());
+        pipeline.delete(id)?;
+        assert_eq!(stamp.0.load(Ordering::Relaxed), 1);
+
+        assert!(pipeline.remove_stage_hook("output", "stamp")?);
+        assert!(pipeline.get_stage_hooks("output")?.is_empty());
+        Ok(())
+    }
+
+    struct DropAll;
+
+    impl StageHook for DropAll {
+        fn on_enter(&self, _payload: &mut PipelinePayload) -> HookAction {
+            HookAction::Drop
+        }
+    }
+
+    #[test]
+    fn test_drop_in_hook() -> anyhow::Result<()> {
+        let pipeline = Pipeline::new(
+            vec![
+                (
+                    "input".to_string(),
+                    PipelineStagePayloadType::Frame,
+                    None,
+                    None,
+                ),
+                (
+                    "output".to_string(),
+                    PipelineStagePayloadType::Frame,
+                    None,
+                    None,
+                ),
+            ],
+            PipelineConfiguration::default(),
+        )?;
+        pipeline.add_stage_hook("output", "drop", Arc::new(DropAll))?;
+        let id = pipeline.add_frame("input", gen_frame())?;
+        pipeline.move_as_is("output", vec![id])?;
+        assert_eq!(pipeline.get_stage_queue_len("output")?, 0);
+        assert_eq!(pipeline.get_id_locations_len(), 0);
+        assert_eq!(pipeline.get_root_spans_len(), 0);
+        assert!(pipeline.delete(id).is_err());
+
+        pipeline.add_stage_hook("input", "drop", Arc::new(DropAll))?;
+        assert!(pipeline.add_frame("input", gen_frame()).is_err());
+        assert_eq!(pipeline.get_id_locations_len(), 0);
+        assert_eq!(pipeline.get_root_spans_len(), 0);
+        Ok(())
+    }
+}
@@ -0,0 +1,446 @@
+use crate::pipeline::stage::PipelineStage;
+use crate::pipeline::{
+    Pipeline, PipelinePayload, PipelineStageFunction, PipelineStageFunctionOrder, PluginParams,
+};
+use crate::primitives::attribute_value::{AttributeValue, AttributeValueVariant};
+use crate::primitives::frame::{VideoFrameContent, VideoFrameProxy};
+use crate::primitives::raw_content::InternalFrame;
+use crate::primitives::{RBBox, WithAttributes};
+use crate::transcoding::{transcode, Format};
+use anyhow::{bail, Result};
+use hashbrown::HashMap;
+use parking_lot::Mutex;
+
+pub const DEFAULT_MOTION_NAMESPACE: &str = "motion";
+pub const MOTION_PRESENT_ATTRIBUTE: &str = "motion_present";
+pub const MOTION_MASK_ATTRIBUTE: &str = "mask";
+pub const MOTION_AREA_ATTRIBUTE: &str = "area";
+
+#[no_mangle]
+pub fn init_motion_detector(_: &str, params: PluginParams) -> *mut dyn PipelineStageFunction {
+    let detector = MotionDetector::new(MotionDetectorConfiguration::from(&params));
+    Box::into_raw(Box::new(detector))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MotionDetectorConfiguration {
+    /// The side of the square cell in pixels, the background is modelled per cell.
+    pub cell_size: usize,
+    /// The difference of the mean luminance of a cell from the background to treat the cell
+    /// as moving.
+    pub threshold: f32,
+    /// How fast the background follows the scene, from `0` to `1`.
+    pub learning_rate: f32,
+    /// The minimal number of connected moving cells forming a region.
+    pub min_cells: usize,
+    /// Sets `skip_inference` of the frames without motion and the regions of interest of the
+    /// frames with motion.
+    pub gate_inference: bool,
+    pub namespace: String,
+}
+
+impl Default for MotionDetectorConfiguration {
+    fn default() -> Self {
+        Self {
+            cell_size: 16,
+            threshold: 12.0,
+            learning_rate: 0.05,
+            min_cells: 1,
+            gate_inference: true,
+            namespace: DEFAULT_MOTION_NAMESPACE.to_string(),
+        }
+    }
+}
+
+impl From<&PluginParams> for MotionDetectorConfiguration {
+    fn from(params: &PluginParams) -> Self {
+        let defaults = Self::default();
+        let number = |name: &str, default: f64| match params.params.get(name).map(|v| &v.value) {
+            Some(AttributeValueVariant::Float(v)) => *v,
+            Some(AttributeValueVariant::Integer(v)) => *v as f64,
+            _ => default,
+        };
+        Self {
+            cell_size: (number("cell_size", defaults.cell_size as f64) as usize).max(1),
+            threshold: number("threshold", defaults.threshold as f64) as f32,
+            learning_rate: (number("learning_rate", defaults.learning_rate as f64) as f32)
+                .clamp(0.0, 1.0),
+            min_cells: (number("min_cells", defaults.min_cells as f64) as usize).max(1),
+            gate_inference: match params.params.get("gate_inference").map(|v| &v.value) {
+                Some(AttributeValueVariant::Boolean(v)) => *v,
+                _ => defaults.gate_inference,
+            },
+            namespace: match params.params.get("namespace").map(|v| &v.value) {
+                Some(AttributeValueVariant::String(v)) => v.clone(),
+                _ => defaults.namespace,
+            },
+        }
+    }
+}
+
+/// The result of the detection for one frame. The mask holds one byte per cell, row by row,
+/// `1` for moving cells.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct MotionResult {
+    pub motion_present: bool,
+    pub rows: usize,
+    pub cols: usize,
+    pub mask: Vec<u8>,
+    pub regions: Vec<RBBox>,
+    /// The share of moving cells.
+    pub area: f64,
+}
+
+#[derive(Debug)]
+struct Background {
+    width: i64,
+    height: i64,
+    cells: Vec<f32>,
+}
+
+/// A CPU motion detector for raw frames (any pixel format of the raw layout; content without
+/// a layout is treated as GRAY8, RGB or RGBA derived from the content size). Every source
+/// has its own running-average background of cell luminance; connected moving cells form
+/// the regions of motion. The detector is a stage function, so it is attached as an ingress
+/// or egress function of a frame or batch stage.
+///
+#[derive(Debug)]
+pub struct MotionDetector {
+    pipeline: Option<Pipeline>,
+    configuration: MotionDetectorConfiguration,
+    backgrounds: Mutex<HashMap<String, Background>>,
+}
+
+pub(crate) fn luminance(content: &InternalFrame, width: usize, height: usize) -> Result<Vec<u8>> {
+    if let Some(layout) = content.get_layout() {
+        if layout.get_width() != width || layout.get_height() != height {
+            bail!(
+                "The {}x{} content layout does not match the {}x{} frame",
+                layout.get_width(),
+                layout.get_height(),
+                width,
+                height
+            )
+        }
+        return transcode(
+            &Format::Raw(layout.get_format()),
+            &Format::GRAY8,
+            &content.to_packed(),
+            width as i64,
+            height as i64,
+        );
+    }
+    let data = content.get_data()?;
+    let pixels = width * height;
+    if pixels == 0 {
+        bail!("Frame has no pixels")
+    }
+    match data.len() / pixels {
+        1 if data.len() == pixels => Ok(data.to_vec()),
+        channels @ (3 | 4) if data.len() == pixels * channels => Ok(data
+            .chunks_exact(channels)
+            .map(|p| ((p[0] as u32 * 77 + p[1] as u32 * 150 + p[2] as u32 * 29) >> 8) as u8)
+            .collect()),
+        _ => bail!(
+            "Content size {} does not match a raw {}x{} GRAY8, RGB or RGBA frame",
+            data.len(),
+            width,
+            height
+        ),
+    }
+}
+
+impl MotionDetector {
+    pub fn new(configuration: MotionDetectorConfiguration) -> Self {
+        Self {
+            pipeline: None,
+            configuration,
+            backgrounds: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn get_configuration(&self) -> &MotionDetectorConfiguration {
+        &self.configuration
+    }
+
+    /// Forgets the background of the source, e.g. when the stream restarts.
+    ///
+    pub fn reset(&self, source_id: &str) {
+        self.backgrounds.lock().remove(source_id);
+    }
+
+    fn cell_means(&self, luma: &[u8], width: usize, height: usize) -> (usize, usize, Vec<f32>) {
+        let cell = self.configuration.cell_size;
+        let cols = width.div_ceil(cell);
+        let rows = height.div_ceil(cell);
+        let mut sums = vec![0u64; rows * cols];
+        let mut counts = vec![0u32; rows * cols];
+        for (y, line) in luma.chunks_exact(width).enumerate() {
+            let row = y / cell;
+            for (x, v) in line.iter().enumerate() {
+                let index = row * cols + x / cell;
+                sums[index] += *v as u64;
+                counts[index] += 1;
+            }
+        }
+        let means = sums
+            .iter()
+            .zip(counts.iter())
+            .map(|(s, c)| *s as f32 / *c as f32)
+            .collect();
+        (rows, cols, means)
+    }
+
+    fn regions(
+        &self,
+        mask: &[u8],
+        rows: usize,
+        cols: usize,
+        width: i64,
+        height: i64,
+    ) -> Vec<RBBox> {
+        let cell = self.configuration.cell_size as i64;
+        let mut visited = vec![false; mask.len()];
+        let mut regions = Vec::new();
+        for (start, moving) in mask.iter().enumerate() {
+            if *moving == 0 || visited[start] {
+                continue;
+            }
+            visited[start] = true;
+            let mut stack = vec![start];
+            let (mut min_r, mut max_r, mut min_c, mut max_c) = (rows, 0, cols, 0);
+            let mut cells = 0;
+            while let Some(index) = stack.pop() {
+                let (r, c) = (index / cols, index % cols);
+                cells += 1;
+                min_r = min_r.min(r);
+                max_r = max_r.max(r);
+                min_c = min_c.min(c);
+                max_c = max_c.max(c);
+                let mut neighbours = Vec::with_capacity(4);
+                if r > 0 {
+                    neighbours.push(index - cols);
+                }
+                if r + 1 < rows {
+                    neighbours.push(index + cols);
+                }
+                if c > 0 {
+                    neighbours.push(index - 1);
+                }
+                if c + 1 < cols {
+                    neighbours.push(index + 1);
+                }
+                for n in neighbours {
+                    if mask[n] != 0 && !visited[n] {
+                        visited[n] = true;
+                        stack.push(n);
+                    }
+                }
+            }
+            if cells < self.configuration.min_cells {
+                continue;
+            }
+            let left = min_c as i64 * cell;
+            let top = min_r as i64 * cell;
+            let right = ((max_c as i64 + 1) * cell).min(width);
+            let bottom = ((max_r as i64 + 1) * cell).min(height);
+            regions.push(RBBox::ltwh(
+                left as f32,
+                top as f32,
+                (right - left) as f32,
+                (bottom - top) as f32,
+            ));
+        }
+        regions
+    }
+
+    /// Updates the background of the frame source and returns the motion of the frame. The
+    /// first frame of a source only initializes the background and has no motion.
+    ///
+    pub fn detect(&self, frame: &VideoFrameProxy) -> Result<MotionResult> {
+        let content = frame.get_content();
+        let VideoFrameContent::Internal(data) = content.as_ref() else {
+            bail!("Motion detection requires a frame with internal raw content")
+        };
+        let (width, height) = (frame.get_width(), frame.get_height());
+        let luma = luminance(data, width as usize, height as usize)?;
+        let (rows, cols, means) = self.cell_means(&luma, width as usize, height as usize);
+
+        let mut backgrounds = self.backgrounds.lock();
+        let background = backgrounds
+            .entry(frame.get_source_id())
+            .or_insert_with(|| Background {
+                width,
+                height,
+                cells: Vec::new(),
+            });
+        if background.width != width || background.height != height {
+            background.width = width;
+            background.height = height;
+            background.cells.clear();
+        }
+        if background.cells.is_empty() {
+            background.cells = means;
+            return Ok(MotionResult {
+                motion_present: false,
+                rows,
+                cols,
+                mask: vec![0; rows * cols],
+                regions: Vec::new(),
+                area: 0.0,
+            });
+        }
+
+        let rate = self.configuration.learning_rate;
+        let mask = background
+            .cells
+            .iter_mut()
+            .zip(means.iter())
+            .map(|(bg, mean)| {
+                let moving = (mean - *bg).abs() > self.configuration.threshold;
+                *bg += rate * (mean - *bg);
+                moving as u8
+            })
+            .collect::<Vec<_>>();
+        drop(backgrounds);
+
+        let regions = self.regions(&mask, rows, cols, width, height);
+        let area = mask.iter().filter(|m| **m != 0).count() as f64 / mask.len() as f64;
+        Ok(MotionResult {
+            motion_present: !regions.is_empty(),
+            rows,
+            cols,
+            mask,
+            regions,
+            area,
+        })
+    }
+
+    fn process(&self, frame: &mut VideoFrameProxy) -> Result<()> {
+        let result = self.detect(frame)?;
+        let namespace = &self.configuration.namespace;
+        frame.set_persistent_attribute(
+            namespace,
+            MOTION_PRESENT_ATTRIBUTE,
+            &None,
+            false,
+            vec![AttributeValue::boolean(result.motion_present, None)],
+        );
+        frame.set_persistent_attribute(
+            namespace,
+            MOTION_AREA_ATTRIBUTE,
+            &None,
+            false,
+            vec![AttributeValue::float(result.area, None)],
+        );
+        frame.set_persistent_attribute(
+            namespace,
+            MOTION_MASK_ATTRIBUTE,
+            &None,
+            false,
+            vec![AttributeValue::bytes(
+                &[result.rows as i64, result.cols as i64],
+                &result.mask,
+                None,
+            )],
+        );
+        if self.configuration.gate_inference {
+            let mut hints = frame.get_processing_hints();
+            hints.skip_inference = !result.motion_present;
+            hints.roi_list = result.regions;
+            frame.set_processing_hints(hints);
+        }
+        Ok(())
+    }
+}
+
+impl PipelineStageFunction for MotionDetector {
+    fn set_pipeline(&mut self, pipeline: Pipeline) {
+        self.pipeline = Some(pipeline);
+    }
+    fn get_pipeline(&self) -> &Option<Pipeline> {
+        &self.pipeline
+    }
+    fn call(
+        &self,
+        _: i64,
+        _: &PipelineStage,
+        _: PipelineStageFunctionOrder,
+        payload: &mut PipelinePayload,
+    ) -> Result<()> {
+        match payload {
+            PipelinePayload::Frame(frame, ..) => self.process(frame),
+            PipelinePayload::Batch(batch, ..) => {
+                for frame in batch.frames().values() {
+                    self.process(&mut frame.clone())?;
+                }
+                Ok(())
+            }
+            PipelinePayload::Audio(..) | PipelinePayload::Telemetry(..) => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::motion::{MotionDetector, MotionDetectorConfiguration};
+    use crate::pipeline::{Pipeline, PipelineConfiguration, PipelineStagePayloadType};
+    use crate::primitives::frame::{VideoFrameContent, VideoFrameProxy};
+    use crate::primitives::WithAttributes;
+    use crate::test::gen_frame;
+
+    fn gray_frame(pixels: Vec<u8>) -> VideoFrameProxy {
+        let mut frame = gen_frame();
+        frame.set_width(64);
+        frame.set_height(32);
+        frame.set_content(VideoFrameContent::Internal(pixels.into()));
+        frame
+    }
+
+    #[test]
+    fn test_detect() -> anyhow::Result<()> {
+        let detector = MotionDetector::new(MotionDetectorConfiguration::default());
+        let still = vec![10u8; 64 * 32];
+        assert!(!detector.detect(&gray_frame(still.clone()))?.motion_present);
+        assert!(!detector.detect(&gray_frame(still.clone()))?.motion_present);
+
+        let mut moved = still;
+        for y in 0..16 {
+            for x in 16..32 {
+                moved[y * 64 + x] = 200;
+            }
+        }
+        let result = detector.detect(&gray_frame(moved))?;
+        assert!(result.motion_present);
+        assert_eq!((result.rows, result.cols), (2, 4));
+        assert_eq!(result.mask, vec![0, 1, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(result.regions.len(), 1);
+        assert_eq!(result.regions[0].as_ltrb()?, (16.0, 0.0, 32.0, 16.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_stage_function() -> anyhow::Result<()> {
+        let pipeline = Pipeline::new(
+            vec![(
+                "motion".to_string(),
+                PipelineStagePayloadType::Frame,
+                Some(Box::new(MotionDetector::new(
+                    MotionDetectorConfiguration::default(),
+                ))),
+                None,
+            )],
+            PipelineConfiguration::default(),
+        )?;
+        let id = pipeline.add_frame("motion", gray_frame(vec![0; 64 * 32]))?;
+        let (frame, _) = pipeline.get_independent_frame(id)?;
+        let attribute = frame.get_attribute("motion", "motion_present").unwrap();
+        assert_eq!(
+            attribute.get_values()[0].get(),
+            &crate::primitives::attribute_value::AttributeValueVariant::Boolean(false)
+        );
+        assert!(frame.get_processing_hints().skip_inference);
+        Ok(())
+    }
+}
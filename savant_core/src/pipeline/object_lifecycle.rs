@@ -0,0 +1,271 @@
+use crate::primitives::frame::VideoFrameProxy;
+use crate::primitives::object::{ObjectAccess, ObjectOperations};
+use hashbrown::HashMap;
+use parking_lot::Mutex;
+use prost::Message as ProstMessage;
+use savant_protobuf::generated;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectEventKind {
+    Added,
+    Modified,
+    Deleted,
+}
+
+impl ObjectEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ObjectEventKind::Added => "added",
+            ObjectEventKind::Modified => "modified",
+            ObjectEventKind::Deleted => "deleted",
+        }
+    }
+}
+
+/// A change of an object of a frame made while the frame was in the stage.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectEvent {
+    pub kind: ObjectEventKind,
+    pub source_id: String,
+    pub frame_uuid: String,
+    pub object_id: i64,
+    pub namespace: String,
+    pub label: String,
+}
+
+/// Receives the object events of the frames leaving the stage, the events of the frames
+/// leaving together are delivered in one call. The callback is called without the locks of
+/// the stage.
+///
+pub trait ObjectLifecycleCallback: Send + Sync {
+    fn on_events(&self, stage: &str, events: Vec<ObjectEvent>);
+}
+
+#[derive(Debug, Clone)]
+struct ObjectState {
+    namespace: String,
+    label: String,
+    fingerprint: u64,
+}
+
+type FrameSnapshot = HashMap<i64, ObjectState>;
+
+fn snapshot(frame: &VideoFrameProxy) -> FrameSnapshot {
+    frame
+        .get_all_objects()
+        .into_iter()
+        .map(|object| {
+            let mut hasher = DefaultHasher::new();
+            object
+                .with_object_ref(|o| generated::VideoObject::from(o).encode_to_vec())
+                .hash(&mut hasher);
+            let state = ObjectState {
+                namespace: object.get_namespace(),
+                label: object.get_label(),
+                fingerprint: hasher.finish(),
+            };
+            (object.get_id(), state)
+        })
+        .collect()
+}
+
+/// Tracks the objects of the frames between entering and leaving a stage and reports the
+/// added, modified and deleted ones. The objects are fingerprinted when a frame enters the
+/// stage and compared when it leaves, so the observer costs a serialization of the objects
+/// twice per frame. Installed with [`crate::pipeline::Pipeline::set_object_observer`].
+///
+pub struct ObjectObserver {
+    callback: Arc<dyn ObjectLifecycleCallback>,
+    frames: Mutex<HashMap<u128, FrameSnapshot>>,
+    pending: Mutex<Vec<ObjectEvent>>,
+}
+
+impl std::fmt::Debug for ObjectObserver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ObjectObserver")
+            .field("frames", &self.frames.lock().len())
+            .finish()
+    }
+}
+
+impl ObjectObserver {
+    pub fn new(callback: Arc<dyn ObjectLifecycleCallback>) -> Self {
+        Self {
+            callback,
+            frames: Mutex::new(HashMap::new()),
+            pending: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn enter(&self, frame: &VideoFrameProxy) {
+        let snapshot = snapshot(frame);
+        self.frames.lock().insert(frame.get_uuid_u128(), snapshot);
+    }
+
+    /// Compares the objects with the ones the frame entered with, the events are delivered
+    /// by [`ObjectObserver::notify`].
+    ///
+    pub(crate) fn leave(&self, frame: &VideoFrameProxy) {
+        let Some(mut before) = self.frames.lock().remove(&frame.get_uuid_u128()) else {
+            return;
+        };
+        let source_id = frame.get_source_id();
+        let frame_uuid = frame.get_uuid_as_string();
+        let event = |kind, object_id, state: ObjectState| ObjectEvent {
+            kind,
+            source_id: source_id.clone(),
+            frame_uuid: frame_uuid.clone(),
+            object_id,
+            namespace: state.namespace,
+            label: state.label,
+        };
+        let mut events = Vec::new();
+        let mut after = snapshot(frame).into_iter().collect::<Vec<_>>();
+        after.sort_by_key(|(id, _)| *id);
+        for (id, state) in after {
+            match before.remove(&id) {
+                None => events.push(event(ObjectEventKind::Added, id, state)),
+                Some(previous) if previous.fingerprint != state.fingerprint => {
+                    events.push(event(ObjectEventKind::Modified, id, state))
+                }
+                Some(_) => {}
+            }
+        }
+        let mut deleted = before.into_iter().collect::<Vec<_>>();
+        deleted.sort_by_key(|(id, _)| *id);
+        for (id, state) in deleted {
+            events.push(event(ObjectEventKind::Deleted, id, state));
+        }
+        self.pending.lock().extend(events);
+    }
+
+    /// Delivers the pending events to the callback.
+    ///
+    pub(crate) fn notify(&self, stage: &str) {
+        let events = std::mem::take(&mut *self.pending.lock());
+        if !events.is_empty() {
+            self.callback.on_events(stage, events);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::object_lifecycle::{
+        ObjectEvent, ObjectEventKind, ObjectLifecycleCallback, ObjectObserver,
+    };
+    use crate::pipeline::{Pipeline, PipelineConfiguration, PipelineStagePayloadType};
+    use crate::primitives::object::{IdCollisionResolutionPolicy, ObjectOperations};
+    use crate::test::{gen_frame, gen_object};
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<(String, Vec<ObjectEvent>)>>);
+
+    impl ObjectLifecycleCallback for Recorder {
+        fn on_events(&self, stage: &str, events: Vec<ObjectEvent>) {
+            self.0.lock().push((stage.to_string(), events));
+        }
+    }
+
+    #[test]
+    fn test_object_events() -> anyhow::Result<()> {
+        let pipeline = Pipeline::new(
+            vec![
+                (
+                    "detect".to_string(),
+                    PipelineStagePayloadType::Frame,
+                    None,
+                    None,
+                ),
+                (
+                    "output".to_string(),
+                    PipelineStagePayloadType::Frame,
+                    None,
+                    None,
+                ),
+            ],
+            PipelineConfiguration::default(),
+        )?;
+        let recorder = Arc::new(Recorder::default());
+        pipeline.set_object_observer("detect", Some(ObjectObserver::new(recorder.clone())))?;
+
+        let frame = gen_frame();
+        let id = pipeline.add_frame("detect", frame.clone())?;
+        let mut modified = frame.get_object(0).unwrap();
+        modified.set_label("modified");
+        frame.delete_objects_with_ids(&[1]);
+        frame.add_object(gen_object(100), IdCollisionResolutionPolicy::Error)?;
+        pipeline.move_as_is("output", vec![id])?;
+        pipeline.delete(id)?;
+
+        let calls = recorder.0.lock();
+        assert_eq!(calls.len(), 1);
+        let (stage, events) = &calls[0];
+        assert_eq!(stage, "detect");
+        let kinds = events
+            .iter()
+            .map(|e| (e.kind, e.object_id))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                (ObjectEventKind::Modified, 0),
+                (ObjectEventKind::Added, 100),
+                (ObjectEventKind::Deleted, 1),
+            ]
+        );
+        assert_eq!(events[0].label, "modified");
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_egress() -> anyhow::Result<()> {
+        use crate::pipeline::stage::PipelineStage;
+        use crate::pipeline::{PipelinePayload, PipelineStageFunction, PipelineStageFunctionOrder};
+
+        struct Failing(Option<Pipeline>);
+
+        impl PipelineStageFunction for Failing {
+            fn set_pipeline(&mut self, _: Pipeline) {}
+            fn get_pipeline(&self) -> &Option<Pipeline> {
+                &self.0
+            }
+            fn call(
+                &self,
+                _: i64,
+                _: &PipelineStage,
+                _: PipelineStageFunctionOrder,
+                _: &mut PipelinePayload,
+            ) -> anyhow::Result<()> {
+                anyhow::bail!("Egress failed")
+            }
+        }
+
+        let pipeline = Pipeline::new(
+            vec![(
+                "detect".to_string(),
+                PipelineStagePayloadType::Frame,
+                None,
+                Some(Box::new(Failing(None))),
+            )],
+            PipelineConfiguration::default(),
+        )?;
+        let recorder = Arc::new(Recorder::default());
+        pipeline.set_object_observer("detect", Some(ObjectObserver::new(recorder.clone())))?;
+
+        let frame = gen_frame();
+        let id = pipeline.add_frame("detect", frame.clone())?;
+        frame.delete_objects_with_ids(&[1]);
+        assert!(pipeline.delete(id).is_err());
+        // the frame is removed, so it leaves the stage despite the failure
+        assert_eq!(recorder.0.lock().len(), 1);
+        assert_eq!(pipeline.get_id_locations_len(), 0);
+        Ok(())
+    }
+}
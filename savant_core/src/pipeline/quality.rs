@@ -0,0 +1,376 @@
+use crate::pipeline::motion::luminance;
+use crate::pipeline::stage::PipelineStage;
+use crate::pipeline::{
+    Pipeline, PipelinePayload, PipelineStageFunction, PipelineStageFunctionOrder, PluginParams,
+};
+use crate::primitives::attribute_value::{AttributeValue, AttributeValueVariant};
+use crate::primitives::frame::{VideoFrameContent, VideoFrameProxy};
+use crate::primitives::WithAttributes;
+use anyhow::{bail, Result};
+
+pub const DEFAULT_QUALITY_NAMESPACE: &str = "quality";
+pub const QUALITY_SHARPNESS_ATTRIBUTE: &str = "sharpness";
+pub const QUALITY_BLURRED_ATTRIBUTE: &str = "blurred";
+pub const QUALITY_BRIGHTNESS_ATTRIBUTE: &str = "brightness";
+pub const QUALITY_CONTRAST_ATTRIBUTE: &str = "contrast";
+pub const QUALITY_UNDEREXPOSED_ATTRIBUTE: &str = "underexposed";
+pub const QUALITY_OVEREXPOSED_ATTRIBUTE: &str = "overexposed";
+pub const QUALITY_HISTOGRAM_ATTRIBUTE: &str = "histogram";
+
+#[no_mangle]
+pub fn init_quality_estimator(_: &str, params: PluginParams) -> *mut dyn PipelineStageFunction {
+    let estimator = QualityEstimator::new(QualityEstimatorConfiguration::from(&params));
+    Box::into_raw(Box::new(estimator))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualityEstimatorConfiguration {
+    /// Computes the sharpness (the variance of the Laplacian of the luminance).
+    pub blur: bool,
+    /// Computes the brightness, contrast, exposure shares and the histogram.
+    pub exposure: bool,
+    /// The frame is blurred when the sharpness is below the threshold.
+    pub blur_threshold: f64,
+    /// Pixels with the luminance at or below the level are underexposed.
+    pub dark_level: u8,
+    /// Pixels with the luminance at or above the level are overexposed.
+    pub bright_level: u8,
+    /// The number of luminance histogram bins, from `1` to `256`.
+    pub histogram_bins: usize,
+    pub namespace: String,
+}
+
+impl Default for QualityEstimatorConfiguration {
+    fn default() -> Self {
+        Self {
+            blur: true,
+            exposure: true,
+            blur_threshold: 100.0,
+            dark_level: 16,
+            bright_level: 239,
+            histogram_bins: 16,
+            namespace: DEFAULT_QUALITY_NAMESPACE.to_string(),
+        }
+    }
+}
+
+impl From<&PluginParams> for QualityEstimatorConfiguration {
+    fn from(params: &PluginParams) -> Self {
+        let defaults = Self::default();
+        let number = |name: &str, default: f64| match params.params.get(name).map(|v| &v.value) {
+            Some(AttributeValueVariant::Float(v)) => *v,
+            Some(AttributeValueVariant::Integer(v)) => *v as f64,
+            _ => default,
+        };
+        let flag = |name: &str, default: bool| match params.params.get(name).map(|v| &v.value) {
+            Some(AttributeValueVariant::Boolean(v)) => *v,
+            _ => default,
+        };
+        Self {
+            blur: flag("blur", defaults.blur),
+            exposure: flag("exposure", defaults.exposure),
+            blur_threshold: number("blur_threshold", defaults.blur_threshold),
+            dark_level: number("dark_level", defaults.dark_level as f64).clamp(0.0, 255.0) as u8,
+            bright_level: number("bright_level", defaults.bright_level as f64).clamp(0.0, 255.0)
+                as u8,
+            histogram_bins: (number("histogram_bins", defaults.histogram_bins as f64) as usize)
+                .clamp(1, 256),
+            namespace: match params.params.get("namespace").map(|v| &v.value) {
+                Some(AttributeValueVariant::String(v)) => v.clone(),
+                _ => defaults.namespace,
+            },
+        }
+    }
+}
+
+/// The blur estimate of a frame.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlurEstimate {
+    /// The variance of the Laplacian, low values mean a defocused or blurred image.
+    pub sharpness: f64,
+    pub blurred: bool,
+}
+
+/// The luminance statistics of a frame, the shares are from `0` to `1`.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExposureEstimate {
+    /// The mean luminance.
+    pub brightness: f64,
+    /// The standard deviation of the luminance.
+    pub contrast: f64,
+    pub underexposed: f64,
+    pub overexposed: f64,
+    /// The shares of pixels per luminance bin.
+    pub histogram: Vec<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualityEstimate {
+    pub blur: Option<BlurEstimate>,
+    pub exposure: Option<ExposureEstimate>,
+}
+
+/// The variance of the Laplacian of the GRAY8 image.
+///
+pub(crate) fn sharpness(luma: &[u8], width: usize, height: usize) -> f64 {
+    let mut sum = 0.0;
+    let mut sum_sq = 0.0;
+    let mut count = 0usize;
+    for y in 1..height.saturating_sub(1) {
+        for x in 1..width.saturating_sub(1) {
+            let at = |x: usize, y: usize| luma[y * width + x] as f64;
+            let laplacian =
+                at(x - 1, y) + at(x + 1, y) + at(x, y - 1) + at(x, y + 1) - 4.0 * at(x, y);
+            sum += laplacian;
+            sum_sq += laplacian * laplacian;
+            count += 1;
+        }
+    }
+    if count == 0 {
+        0.0
+    } else {
+        let mean = sum / count as f64;
+        sum_sq / count as f64 - mean * mean
+    }
+}
+
+/// Estimates the image quality of raw frames (GRAY8, RGB or RGBA, the format is derived from
+/// the content size) and writes the estimates as frame attributes, so rules can react to a
+/// defocused or badly exposed camera. The estimator is a stage function, so it is attached
+/// as an ingress or egress function of a frame or batch stage. Frames with external or no
+/// content are passed without the estimates.
+///
+#[derive(Debug)]
+pub struct QualityEstimator {
+    pipeline: Option<Pipeline>,
+    configuration: QualityEstimatorConfiguration,
+}
+
+impl QualityEstimator {
+    pub fn new(configuration: QualityEstimatorConfiguration) -> Self {
+        Self {
+            pipeline: None,
+            configuration,
+        }
+    }
+
+    pub fn get_configuration(&self) -> &QualityEstimatorConfiguration {
+        &self.configuration
+    }
+
+    fn blur(&self, luma: &[u8], width: usize, height: usize) -> BlurEstimate {
+        let sharpness = sharpness(luma, width, height);
+        BlurEstimate {
+            sharpness,
+            blurred: sharpness < self.configuration.blur_threshold,
+        }
+    }
+
+    fn exposure(&self, luma: &[u8]) -> ExposureEstimate {
+        let mut counts = [0usize; 256];
+        luma.iter().for_each(|v| counts[*v as usize] += 1);
+        let total = luma.len() as f64;
+        let brightness = counts
+            .iter()
+            .enumerate()
+            .map(|(v, c)| v as f64 * *c as f64)
+            .sum::<f64>()
+            / total;
+        let variance = counts
+            .iter()
+            .enumerate()
+            .map(|(v, c)| (v as f64 - brightness).powi(2) * *c as f64)
+            .sum::<f64>()
+            / total;
+        let share = |range: &[usize]| range.iter().sum::<usize>() as f64 / total;
+        let bins = self.configuration.histogram_bins;
+        let mut histogram = vec![0usize; bins];
+        counts
+            .iter()
+            .enumerate()
+            .for_each(|(v, c)| histogram[v * bins / 256] += c);
+        ExposureEstimate {
+            brightness,
+            contrast: variance.sqrt(),
+            underexposed: share(&counts[..=self.configuration.dark_level as usize]),
+            overexposed: share(&counts[self.configuration.bright_level as usize..]),
+            histogram: histogram.iter().map(|c| *c as f64 / total).collect(),
+        }
+    }
+
+    /// Estimates the quality of the frame with the enabled estimators.
+    ///
+    pub fn estimate(&self, frame: &VideoFrameProxy) -> Result<QualityEstimate> {
+        let content = frame.get_content();
+        let VideoFrameContent::Internal(data) = content.as_ref() else {
+            bail!("Quality estimation requires a frame with internal raw content")
+        };
+        let (width, height) = (frame.get_width() as usize, frame.get_height() as usize);
+        let luma = luminance(data, width, height)?;
+        Ok(QualityEstimate {
+            blur: self
+                .configuration
+                .blur
+                .then(|| self.blur(&luma, width, height)),
+            exposure: self.configuration.exposure.then(|| self.exposure(&luma)),
+        })
+    }
+
+    fn process(&self, frame: &mut VideoFrameProxy) -> Result<()> {
+        if !matches!(frame.get_content().as_ref(), VideoFrameContent::Internal(_)) {
+            return Ok(());
+        }
+        let estimate = self.estimate(frame)?;
+        let namespace = &self.configuration.namespace;
+        let mut set = |name: &str, value: AttributeValue| {
+            frame.set_persistent_attribute(namespace, name, &None, false, vec![value]);
+        };
+        if let Some(blur) = estimate.blur {
+            set(
+                QUALITY_SHARPNESS_ATTRIBUTE,
+                AttributeValue::float(blur.sharpness, None),
+            );
+            set(
+                QUALITY_BLURRED_ATTRIBUTE,
+                AttributeValue::boolean(blur.blurred, None),
+            );
+        }
+        if let Some(exposure) = estimate.exposure {
+            set(
+                QUALITY_BRIGHTNESS_ATTRIBUTE,
+                AttributeValue::float(exposure.brightness, None),
+            );
+            set(
+                QUALITY_CONTRAST_ATTRIBUTE,
+                AttributeValue::float(exposure.contrast, None),
+            );
+            set(
+                QUALITY_UNDEREXPOSED_ATTRIBUTE,
+                AttributeValue::float(exposure.underexposed, None),
+            );
+            set(
+                QUALITY_OVEREXPOSED_ATTRIBUTE,
+                AttributeValue::float(exposure.overexposed, None),
+            );
+            set(
+                QUALITY_HISTOGRAM_ATTRIBUTE,
+                AttributeValue::float_vector(exposure.histogram, None),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl PipelineStageFunction for QualityEstimator {
+    fn set_pipeline(&mut self, pipeline: Pipeline) {
+        self.pipeline = Some(pipeline);
+    }
+    fn get_pipeline(&self) -> &Option<Pipeline> {
+        &self.pipeline
+    }
+    fn call(
+        &self,
+        _: i64,
+        _: &PipelineStage,
+        _: PipelineStageFunctionOrder,
+        payload: &mut PipelinePayload,
+    ) -> Result<()> {
+        match payload {
+            PipelinePayload::Frame(frame, ..) => self.process(frame),
+            PipelinePayload::Batch(batch, ..) => {
+                for frame in batch.frames().values() {
+                    self.process(&mut frame.clone())?;
+                }
+                Ok(())
+            }
+            PipelinePayload::Audio(..) | PipelinePayload::Telemetry(..) => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::quality::{QualityEstimator, QualityEstimatorConfiguration};
+    use crate::pipeline::{Pipeline, PipelineConfiguration, PipelineStagePayloadType};
+    use crate::primitives::attribute_value::AttributeValueVariant;
+    use crate::primitives::frame::{VideoFrameContent, VideoFrameProxy};
+    use crate::primitives::WithAttributes;
+    use crate::test::gen_frame;
+
+    fn gray_frame(pixels: Vec<u8>) -> VideoFrameProxy {
+        let mut frame = gen_frame();
+        frame.set_width(32);
+        frame.set_height(32);
+        frame.set_content(VideoFrameContent::Internal(pixels.into()));
+        frame
+    }
+
+    #[test]
+    fn test_estimate() -> anyhow::Result<()> {
+        let estimator = QualityEstimator::new(QualityEstimatorConfiguration::default());
+        let flat = estimator.estimate(&gray_frame(vec![0; 32 * 32]))?;
+        let blur = flat.blur.unwrap();
+        assert_eq!(blur.sharpness, 0.0);
+        assert!(blur.blurred);
+        let exposure = flat.exposure.unwrap();
+        assert_eq!(exposure.brightness, 0.0);
+        assert_eq!(exposure.underexposed, 1.0);
+        assert_eq!(exposure.histogram[0], 1.0);
+
+        // a checkerboard is as sharp as it gets, a half of its pixels is overexposed
+        let checkerboard = (0..32 * 32)
+            .map(|i| if (i % 32 + i / 32) % 2 == 0 { 0 } else { 255 })
+            .collect();
+        let sharp = estimator.estimate(&gray_frame(checkerboard))?;
+        assert!(!sharp.blur.unwrap().blurred);
+        let exposure = sharp.exposure.unwrap();
+        assert_eq!(exposure.brightness, 127.5);
+        assert_eq!(exposure.contrast, 127.5);
+        assert_eq!(exposure.overexposed, 0.5);
+        assert_eq!(exposure.histogram.len(), 16);
+
+        let estimator = QualityEstimator::new(QualityEstimatorConfiguration {
+            exposure: false,
+            ..Default::default()
+        });
+        assert!(estimator.estimate(&gray_frame(vec![0; 32])).is_err());
+        assert!(estimator
+            .estimate(&gray_frame(vec![0; 32 * 32]))?
+            .exposure
+            .is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_stage_function() -> anyhow::Result<()> {
+        let pipeline = Pipeline::new(
+            vec![(
+                "quality".to_string(),
+                PipelineStagePayloadType::Frame,
+                Some(Box::new(QualityEstimator::new(
+                    QualityEstimatorConfiguration::default(),
+                ))),
+                None,
+            )],
+            PipelineConfiguration::default(),
+        )?;
+        let id = pipeline.add_frame("quality", gray_frame(vec![128; 32 * 32 * 3]))?;
+        let (frame, _) = pipeline.get_independent_frame(id)?;
+        let attribute = frame.get_attribute("quality", "brightness").unwrap();
+        assert_eq!(
+            attribute.get_values()[0].get(),
+            &AttributeValueVariant::Float(128.0)
+        );
+        assert!(frame.get_attribute("quality", "blurred").is_some());
+
+        // frames without raw content are passed as is
+        let mut external = gen_frame();
+        external.set_content(VideoFrameContent::None);
+        let id = pipeline.add_frame("quality", external)?;
+        let (frame, _) = pipeline.get_independent_frame(id)?;
+        assert!(frame.get_attribute("quality", "brightness").is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use hashbrown::HashMap;
+
+pub const DEFAULT_RATE_WINDOW: Duration = Duration::from_secs(5);
+
+/// The frame rate of a stream over the window.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RateMeasurement {
+    /// The number of frames in the window.
+    pub frames: usize,
+    pub fps: f64,
+    /// The standard deviation of the intervals between the frames in milliseconds.
+    pub jitter_ms: f64,
+}
+
+/// Measures the frame rate of a stream over a sliding window of the pipeline time, see
+/// [`crate::clock::now_ms`]. The arrivals older than the window are dropped, so the memory
+/// is bounded by the rate of the stream.
+///
+#[derive(Debug, Clone)]
+pub struct RateMeter {
+    window_ms: u64,
+    started_ms: Option<u64>,
+    arrivals: VecDeque<u64>,
+}
+
+impl RateMeter {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window_ms: (window.as_millis() as u64).max(1),
+            started_ms: None,
+            arrivals: VecDeque::new(),
+        }
+    }
+
+    fn evict(&mut self, now_ms: u64) {
+        let oldest = now_ms.saturating_sub(self.window_ms);
+        while self.arrivals.front().is_some_and(|ts| *ts < oldest) {
+            self.arrivals.pop_front();
+        }
+    }
+
+    pub fn observe(&mut self, ts_ms: u64) {
+        self.started_ms.get_or_insert(ts_ms);
+        self.arrivals.push_back(ts_ms);
+        self.evict(ts_ms);
+    }
+
+    /// True when no frames arrived in the window.
+    ///
+    pub fn is_idle(&mut self, now_ms: u64) -> bool {
+        self.evict(now_ms);
+        self.arrivals.is_empty()
+    }
+
+    /// The rate over the window ending at the time. A meter running shorter than the window
+    /// measures the rate since the first frame.
+    ///
+    pub fn measure(&mut self, now_ms: u64) -> RateMeasurement {
+        self.evict(now_ms);
+        let frames = self.arrivals.len();
+        let Some(started_ms) = self.started_ms else {
+            return RateMeasurement::default();
+        };
+        let span_ms = now_ms.saturating_sub(started_ms).min(self.window_ms);
+        let fps = if span_ms == 0 {
+            0.0
+        } else {
+            frames as f64 * 1000.0 / span_ms as f64
+        };
+        let intervals = self
+            .arrivals
+            .iter()
+            .zip(self.arrivals.iter().skip(1))
+            .map(|(a, b)| b.saturating_sub(*a) as f64)
+            .collect::<Vec<_>>();
+        let jitter_ms = if intervals.len() < 2 {
+            0.0
+        } else {
+            let mean = intervals.iter().sum::<f64>() / intervals.len() as f64;
+            let variance =
+                intervals.iter().map(|i| (i - mean).powi(2)).sum::<f64>() / intervals.len() as f64;
+            variance.sqrt()
+        };
+        RateMeasurement {
+            frames,
+            fps,
+            jitter_ms,
+        }
+    }
+}
+
+/// The rate of the frames entering a stage, of a source when `source_id` is set, of all the
+/// sources otherwise.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageRate {
+    pub stage_name: String,
+    pub source_id: Option<String>,
+    pub measurement: RateMeasurement,
+}
+
+/// The rate meters of a stage, for all the frames and per source.
+///
+#[derive(Debug)]
+pub(crate) struct StageRateMeters {
+    window: Duration,
+    total: RateMeter,
+    sources: HashMap<String, RateMeter>,
+}
+
+impl StageRateMeters {
+    pub(crate) fn new(window: Duration) -> Self {
+        Self {
+            window,
+            total: RateMeter::new(window),
+            sources: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn observe(&mut self, source_id: &str, ts_ms: u64) {
+        self.total.observe(ts_ms);
+        match self.sources.get_mut(source_id) {
+            Some(meter) => meter.observe(ts_ms),
+            None => {
+                let mut meter = RateMeter::new(self.window);
+                meter.observe(ts_ms);
+                self.sources.insert(source_id.to_string(), meter);
+            }
+        }
+    }
+
+    /// The rates of the stage, the sources idle for the window are forgotten.
+    ///
+    pub(crate) fn measure(&mut self, stage_name: &str, now_ms: u64) -> Vec<StageRate> {
+        self.sources.retain(|_, meter| !meter.is_idle(now_ms));
+        let mut sources = self.sources.iter_mut().collect::<Vec<_>>();
+        sources.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let mut rates = vec![StageRate {
+            stage_name: stage_name.to_string(),
+            source_id: None,
+            measurement: self.total.measure(now_ms),
+        }];
+        rates.extend(sources.into_iter().map(|(source_id, meter)| StageRate {
+            stage_name: stage_name.to_string(),
+            source_id: Some(source_id.clone()),
+            measurement: meter.measure(now_ms),
+        }));
+        rates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::pipeline::rates::{RateMeter, StageRateMeters};
+
+    #[test]
+    fn test_rate_meter() {
+        let mut meter = RateMeter::new(Duration::from_secs(1));
+        for i in 0..50 {
+            // 25 FPS for 2 seconds
+            meter.observe(i * 40);
+        }
+        let rate = meter.measure(2000);
+        assert_eq!(rate.frames, 25);
+        assert_eq!(rate.fps, 25.0);
+        assert_eq!(rate.jitter_ms, 0.0);
+
+        let rate = meter.measure(2500);
+        assert_eq!(rate.frames, 12);
+        assert!(meter.is_idle(3100));
+        assert_eq!(meter.measure(3100).fps, 0.0);
+    }
+
+    #[test]
+    fn test_jitter() {
+        let mut meter = RateMeter::new(Duration::from_secs(10));
+        for ts in [0, 30, 80, 110, 160] {
+            meter.observe(ts);
+        }
+        let rate = meter.measure(200);
+        assert_eq!(rate.frames, 5);
+        assert_eq!(rate.fps, 25.0);
+        assert_eq!(rate.jitter_ms, 10.0);
+    }
+
+    #[test]
+    fn test_stage_rates() {
+        let mut meters = StageRateMeters::new(Duration::from_secs(1));
+        for i in 0..10 {
+            meters.observe("cam-1", i * 100);
+            if i < 2 {
+                meters.observe("cam-2", i * 100);
+            }
+        }
+        let rates = meters.measure("input", 1000);
+        assert_eq!(rates.len(), 3);
+        assert_eq!(rates[0].source_id, None);
+        assert_eq!(rates[0].measurement.frames, 12);
+        assert_eq!(rates[1].source_id.as_deref(), Some("cam-1"));
+        assert_eq!(rates[1].measurement.fps, 10.0);
+        let rates = meters.measure("input", 1150);
+        assert_eq!(rates.len(), 2);
+    }
+}
@@ -0,0 +1,92 @@
+use serde::Serialize;
+
+/// The readiness of a stage. A stage is warming up while its processor prepares resources
+/// needed for the payloads, like loading a model or synchronizing a gallery.
+///
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(tag = "state", content = "reason", rename_all = "snake_case")]
+pub enum StageReadiness {
+    #[default]
+    Ready,
+    /// The stage is not ready yet, with the human-readable reason.
+    WarmingUp(String),
+}
+
+impl StageReadiness {
+    pub fn is_ready(&self) -> bool {
+        matches!(self, StageReadiness::Ready)
+    }
+
+    /// The first warming up state of the two, so a stage is ready only when all its parts are.
+    ///
+    pub fn and(self, other: StageReadiness) -> StageReadiness {
+        match self {
+            StageReadiness::Ready => other,
+            warming_up => warming_up,
+        }
+    }
+}
+
+/// What happens to the payloads sent to a stage while it or a stage after it warms up.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum WarmupPolicy {
+    /// The payloads stay in the previous stage and can be sent again later, new frames are
+    /// not accepted.
+    #[default]
+    Hold,
+    /// The payloads are deleted from the pipeline.
+    Drop,
+}
+
+/// The error returned when payloads are sent to a stage while the pipeline is not ready for
+/// them. With [`WarmupPolicy::Drop`] the deleted payloads are listed in `dropped`.
+///
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("Stage {stage} is warming up ({reason}), {} payloads dropped", dropped.len())]
+pub struct StageNotReady {
+    /// The stage which is warming up, the destination or a stage after it.
+    pub stage: String,
+    pub reason: String,
+    pub policy: WarmupPolicy,
+    pub dropped: Vec<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StageHealth {
+    pub name: String,
+    pub readiness: StageReadiness,
+}
+
+/// The readiness of the pipeline stages reported by the `/health` endpoint.
+///
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PipelineHealth {
+    pub name: Option<String>,
+    pub ready: bool,
+    pub stages: Vec<StageHealth>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::readiness::StageReadiness;
+
+    #[test]
+    fn test_readiness_json() -> anyhow::Result<()> {
+        let warming_up = StageReadiness::WarmingUp("loading model".to_string());
+        assert_eq!(
+            serde_json::to_string(&warming_up)?,
+            r#"{"state":"warming_up","reason":"loading model"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&StageReadiness::Ready)?,
+            r#"{"state":"ready"}"#
+        );
+        assert_eq!(
+            StageReadiness::Ready.and(warming_up.clone()),
+            warming_up.clone()
+        );
+        assert!(!warming_up.and(StageReadiness::Ready).is_ready());
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+use crate::pipeline::implementation;
+use crate::pipeline::Pipeline;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+pub(crate) const EVICTED_FRAMES_METRIC: &str = "pipeline_evicted_frames";
+
+/// A payload which stayed in the stage longer than the frame TTL and was evicted.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvictedPayload {
+    pub stage: String,
+    pub id: i64,
+    /// The frames of the payload, the ids of the frames in the batch for a batch.
+    pub frame_ids: Vec<i64>,
+    /// The time the payload stayed in the stage.
+    pub age: Duration,
+}
+
+/// Receives the payloads evicted by [`FrameReaper`], the payloads evicted by one pass are
+/// delivered in one call. The callback is called without the locks of the pipeline.
+///
+pub trait EvictionCallback: Send + Sync {
+    fn on_evicted(&self, payloads: Vec<EvictedPayload>);
+}
+
+/// Evicts the payloads stuck in the stages longer than the frame TTL of the pipeline
+/// configuration in the background, see [`Pipeline::evict_stale_frames`]. The thread stops
+/// when the reaper or the pipeline is dropped.
+///
+pub struct FrameReaper {
+    shutdown: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FrameReaper {
+    pub fn start(
+        pipeline: &Pipeline,
+        period: Duration,
+        callback: Option<Arc<dyn EvictionCallback>>,
+    ) -> anyhow::Result<Self> {
+        if period.is_zero() {
+            anyhow::bail!("The eviction period must be greater than 0");
+        }
+        if pipeline.get_frame_ttl().is_none() {
+            anyhow::bail!("The pipeline has no frame TTL configured");
+        }
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let thread_shutdown = shutdown.clone();
+        let pipeline: Weak<implementation::Pipeline> = Arc::downgrade(&pipeline.0);
+        let thread = std::thread::spawn(move || loop {
+            std::thread::park_timeout(period);
+            if thread_shutdown.load(Ordering::Relaxed) {
+                break;
+            }
+            let Some(pipeline) = pipeline.upgrade() else {
+                break;
+            };
+            let evicted = pipeline.evict_stale_frames();
+            drop(pipeline);
+            if evicted.is_empty() {
+                continue;
+            }
+            if let Some(callback) = &callback {
+                callback.on_evicted(evicted);
+            }
+        });
+        Ok(Self {
+            shutdown,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn stop(&mut self) {
+        let Some(thread) = self.thread.take() else {
+            return;
+        };
+        self.shutdown.store(true, Ordering::Relaxed);
+        thread.thread().unpark();
+        if thread.join().is_err() {
+            log::error!(
+                target: "savant_rs::pipeline::reaper",
+                "The frame reaper thread panicked"
+            );
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.thread.is_some()
+    }
+}
+
+impl Drop for FrameReaper {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::reaper::{EvictedPayload, EvictionCallback, FrameReaper};
+    use crate::pipeline::{Pipeline, PipelineConfigurationBuilder, PipelineStagePayloadType};
+    use crate::test::gen_frame;
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct Collector(Mutex<Vec<EvictedPayload>>);
+
+    impl EvictionCallback for Collector {
+        fn on_evicted(&self, payloads: Vec<EvictedPayload>) {
+            self.0.lock().extend(payloads);
+        }
+    }
+
+    #[test]
+    fn test_evict_stale_frames() -> anyhow::Result<()> {
+        let pipeline = Pipeline::new(
+            vec![
+                (
+                    "input".to_string(),
+                    PipelineStagePayloadType::Frame,
+                    None,
+                    None,
+                ),
+                (
+                    "output".to_string(),
+                    PipelineStagePayloadType::Frame,
+                    None,
+                    None,
+                ),
+            ],
+            PipelineConfigurationBuilder::default()
+                .frame_ttl(Some(Duration::from_millis(50)))
+                .build()?,
+        )?;
+        let stale = pipeline.add_frame("input", gen_frame())?;
+        std::thread::sleep(Duration::from_millis(60));
+        let fresh = pipeline.add_frame("input", gen_frame())?;
+
+        let evicted = pipeline.evict_stale_frames();
+        assert_eq!(evicted.len(), 1);
+        assert_eq!(evicted[0].stage, "input");
+        assert_eq!(evicted[0].id, stale);
+        assert_eq!(evicted[0].frame_ids, vec![stale]);
+        assert_eq!(pipeline.get_id_locations_len(), 1);
+        assert!(pipeline.delete(stale).is_err());
+
+        let collector = Arc::new(Collector::default());
+        let mut reaper = FrameReaper::start(
+            &pipeline,
+            Duration::from_millis(10),
+            Some(collector.clone()),
+        )?;
+        pipeline.move_as_is("output", vec![fresh])?;
+        std::thread::sleep(Duration::from_millis(150));
+        reaper.stop();
+        assert!(!reaper.is_running());
+        let evicted = collector.0.lock().clone();
+        assert_eq!(evicted.len(), 1);
+        assert_eq!(evicted[0].stage, "output");
+        assert_eq!(evicted[0].id, fresh);
+        assert_eq!(pipeline.get_id_locations_len(), 0);
+        Ok(())
+    }
+}
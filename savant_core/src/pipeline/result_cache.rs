@@ -0,0 +1,284 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+use hashbrown::HashMap;
+use lru::LruCache;
+use parking_lot::Mutex;
+
+use crate::primitives::frame::{VideoFrameContent, VideoFrameProxy};
+use crate::primitives::object::{IdCollisionResolutionPolicy, ObjectOperations, VideoObject};
+use crate::primitives::{Attribute, WithAttributes};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResultCacheConfig {
+    /// The number of distinct frame contents whose results are kept.
+    pub capacity: NonZeroUsize,
+    /// The namespaces of the objects and frame attributes the stage produces. Only these
+    /// are cached and replayed.
+    pub namespaces: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResultCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+#[derive(Debug)]
+struct CachedResult {
+    attributes: Vec<Attribute>,
+    objects: Vec<VideoObject>,
+}
+
+#[derive(Debug)]
+struct CacheState {
+    results: LruCache<u64, Arc<CachedResult>>,
+    /// The content keys of the frames in the stage, taken when the frames enter it, so the
+    /// results are stored under the key of the input even if the stage changes the content.
+    pending: LruCache<u128, u64>,
+    hits: u64,
+    misses: u64,
+}
+
+/// Memoizes the results of an idempotent stage by the frame content: the objects and frame
+/// attributes the stage produced for a content are stored when the frame leaves the stage
+/// and replayed into frames with the same content, e.g. when a backfill job processes
+/// overlapping journal segments again. The cache is installed with
+/// [`crate::pipeline::Pipeline::set_result_cache`] and applied with
+/// [`crate::pipeline::Pipeline::replay_cached`] before the stage processes the frames.
+///
+#[derive(Debug)]
+pub struct ResultCache {
+    config: ResultCacheConfig,
+    state: Mutex<CacheState>,
+}
+
+impl ResultCache {
+    pub fn new(config: ResultCacheConfig) -> anyhow::Result<Self> {
+        if config.namespaces.is_empty() {
+            anyhow::bail!("The result cache requires the namespaces produced by the stage");
+        }
+        let state = CacheState {
+            results: LruCache::new(config.capacity),
+            pending: LruCache::new(config.capacity),
+            hits: 0,
+            misses: 0,
+        };
+        Ok(Self {
+            config,
+            state: Mutex::new(state),
+        })
+    }
+
+    pub fn get_config(&self) -> &ResultCacheConfig {
+        &self.config
+    }
+
+    pub fn get_stats(&self) -> ResultCacheStats {
+        let state = self.state.lock();
+        ResultCacheStats {
+            hits: state.hits,
+            misses: state.misses,
+            entries: state.results.len(),
+        }
+    }
+
+    pub fn clear(&self) {
+        let mut state = self.state.lock();
+        state.results.clear();
+        state.pending.clear();
+    }
+
+    /// The hash of the frame content and its geometry, `None` for frames without content
+    /// which are never cached.
+    ///
+    pub fn content_key(frame: &VideoFrameProxy) -> Option<u64> {
+        let mut hasher = DefaultHasher::new();
+        (frame.get_width(), frame.get_height(), frame.get_codec()).hash(&mut hasher);
+        match frame.get_content().as_ref() {
+            VideoFrameContent::Internal(content) => match content.try_get_data() {
+                Ok(data) => data.hash(&mut hasher),
+                Err(e) => {
+                    log::error!(
+                        target: "savant_rs::pipeline",
+                        "Result cache failed to access the content of frame {}: {}",
+                        frame.get_uuid_as_string(),
+                        e
+                    );
+                    return None;
+                }
+            },
+            VideoFrameContent::External(content) => {
+                (&content.method, &content.location).hash(&mut hasher)
+            }
+            VideoFrameContent::None => return None,
+        }
+        Some(hasher.finish())
+    }
+
+    fn produced(&self, namespace: &str) -> bool {
+        self.config.namespaces.iter().any(|n| n == namespace)
+    }
+
+    /// Takes the content key of the frame entering the stage.
+    ///
+    pub(crate) fn enter(&self, frame: &VideoFrameProxy) {
+        if let Some(key) = Self::content_key(frame) {
+            self.state.lock().pending.put(frame.get_uuid_u128(), key);
+        }
+    }
+
+    /// Stores the results of the frame leaving the stage unless its content is cached.
+    ///
+    pub(crate) fn leave(&self, frame: &VideoFrameProxy) {
+        let key = {
+            let mut state = self.state.lock();
+            match state.pending.pop(&frame.get_uuid_u128()) {
+                Some(key) if !state.results.contains(&key) => key,
+                _ => return,
+            }
+        };
+        let attributes = frame
+            .get_attributes()
+            .iter()
+            .filter(|(namespace, _)| self.produced(namespace))
+            .filter_map(|(namespace, name)| frame.get_attribute(namespace, name))
+            .collect();
+        let mut objects = frame
+            .get_all_objects()
+            .iter()
+            .filter(|o| self.produced(&o.get_namespace()))
+            .map(|o| {
+                let mut object = o.detached_copy();
+                object.parent_id = o.get_parent_id();
+                object
+            })
+            .collect::<Vec<_>>();
+        objects.sort_by_key(|o| o.id);
+        let result = Arc::new(CachedResult {
+            attributes,
+            objects,
+        });
+        self.state.lock().results.put(key, result);
+    }
+
+    /// Replays the cached results into the frame. Returns whether the content was cached;
+    /// the frames replayed do not need to be processed by the stage.
+    ///
+    pub fn replay(&self, frame: &VideoFrameProxy) -> anyhow::Result<bool> {
+        let result = {
+            let mut state = self.state.lock();
+            let key = match state.pending.get(&frame.get_uuid_u128()) {
+                Some(key) => Some(*key),
+                None => Self::content_key(frame),
+            };
+            match key.and_then(|k| state.results.get(&k).cloned()) {
+                Some(result) => {
+                    state.hits += 1;
+                    result
+                }
+                None => {
+                    state.misses += 1;
+                    return Ok(false);
+                }
+            }
+        };
+
+        let mut frame = frame.clone();
+        for attribute in &result.attributes {
+            frame.set_attribute(attribute.clone());
+        }
+        // the objects are ordered by id, so the produced parents get their new ids first
+        let mut ids = HashMap::with_capacity(result.objects.len());
+        for cached in &result.objects {
+            let mut object = cached.clone();
+            object.id = frame.get_max_object_id() + 1;
+            object.parent_id = cached
+                .parent_id
+                .map(|p| ids.get(&p).copied().unwrap_or(p))
+                .filter(|p| frame.object_exists(*p));
+            let added = frame.add_object(object, IdCollisionResolutionPolicy::GenerateNewId)?;
+            ids.insert(cached.id, added.get_id());
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use crate::pipeline::result_cache::{ResultCache, ResultCacheConfig};
+    use crate::primitives::attribute_value::AttributeValue;
+    use crate::primitives::frame::VideoFrameContent;
+    use crate::primitives::object::ObjectOperations;
+    use crate::primitives::raw_content::InternalFrame;
+    use crate::primitives::{RBBox, WithAttributes};
+    use crate::test::gen_frame;
+
+    #[test]
+    fn test_replay() -> anyhow::Result<()> {
+        let cache = ResultCache::new(ResultCacheConfig {
+            capacity: NonZeroUsize::new(4).unwrap(),
+            namespaces: vec!["detector".to_string()],
+        })?;
+        let content = || VideoFrameContent::Internal(InternalFrame::new(vec![1; 256]));
+
+        let mut processed = gen_frame();
+        processed.set_content(content());
+        cache.enter(&processed);
+        assert!(!cache.replay(&processed)?);
+        let car = processed.create_object(
+            "detector",
+            "car",
+            None,
+            RBBox::new(10.0, 10.0, 4.0, 4.0, None),
+            Some(0.9),
+            None,
+            None,
+            vec![],
+        )?;
+        processed.create_object(
+            "detector",
+            "plate",
+            Some(car.get_id()),
+            RBBox::new(10.0, 11.0, 2.0, 1.0, None),
+            None,
+            None,
+            None,
+            vec![],
+        )?;
+        processed.set_persistent_attribute(
+            "detector",
+            "model",
+            &None,
+            false,
+            vec![AttributeValue::string("yolo", None)],
+        );
+        cache.leave(&processed);
+
+        let mut duplicate = gen_frame();
+        duplicate.set_content(content());
+        cache.enter(&duplicate);
+        let object_count = duplicate.get_all_objects().len();
+        assert!(cache.replay(&duplicate)?);
+        let objects = duplicate.get_all_objects();
+        assert_eq!(objects.len(), object_count + 2);
+        let find = |label: &str| objects.iter().find(|o| o.get_label() == label).unwrap();
+        assert_eq!(find("plate").get_parent_id(), Some(find("car").get_id()));
+        assert!(duplicate.get_attribute("detector", "model").is_some());
+
+        let mut other = gen_frame();
+        other.set_content(VideoFrameContent::Internal(InternalFrame::new(vec![
+            2;
+            256
+        ])));
+        assert!(!cache.replay(&other)?);
+        let stats = cache.get_stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 2, 1));
+        Ok(())
+    }
+}
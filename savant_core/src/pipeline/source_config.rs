@@ -0,0 +1,349 @@
+use crate::primitives::geo::GeoPosition;
+use crate::rwlock::SavantRwLock;
+use crate::webserver::kvs::synchronous::get_blob;
+use anyhow::{anyhow, bail, Context};
+use hashbrown::HashMap;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Where the per-source overrides are looked up.
+///
+#[derive(Debug, Clone)]
+pub enum SourceConfigProvider {
+    /// A `<source_id>.yaml`, `<source_id>.yml` or `<source_id>.json` file in the directory.
+    Directory(PathBuf),
+    /// A JSON KVS blob `<namespace>/<source_id>`.
+    Kvs { namespace: String },
+}
+
+impl SourceConfigProvider {
+    fn load(&self, source_id: &str) -> anyhow::Result<Option<Value>> {
+        match self {
+            SourceConfigProvider::Directory(dir) => {
+                // the source id comes from the stream, it must not escape the directory
+                if source_id.is_empty()
+                    || source_id.contains(['/', '\\', '\0'])
+                    || source_id.contains("..")
+                {
+                    bail!("Invalid source id {:?} for a file lookup", source_id);
+                }
+                for extension in ["yaml", "yml", "json"] {
+                    let path = dir.join(format!("{}.{}", source_id, extension));
+                    if !path.is_file() {
+                        continue;
+                    }
+                    let text = std::fs::read_to_string(&path)
+                        .with_context(|| format!("Failed to read {}", path.display()))?;
+                    let value = if extension == "json" {
+                        serde_json::from_str(&text)?
+                    } else {
+                        serde_yaml::from_str(&text)?
+                    };
+                    return Ok(Some(value));
+                }
+                Ok(None)
+            }
+            SourceConfigProvider::Kvs { namespace } => get_blob(namespace, source_id)
+                .map(|blob| blob.as_json().map_err(|e| anyhow!(e)))
+                .transpose(),
+        }
+    }
+}
+
+/// The read-only configuration of a source: the global defaults with the source overrides
+/// applied. Attached to the frames by the pipeline when they are added.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceConfig {
+    source_id: String,
+    values: Map<String, Value>,
+}
+
+impl SourceConfig {
+    pub fn get_source_id(&self) -> &str {
+        &self.source_id
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.values.get(key)
+    }
+
+    /// Looks up a nested value by a `/`-separated path like `zones/entrance`.
+    ///
+    pub fn get_path(&self, path: &str) -> Option<&Value> {
+        let mut parts = path.split('/');
+        let first = self.values.get(parts.next()?)?;
+        parts.try_fold(first, |value, part| value.as_object()?.get(part))
+    }
+
+    pub fn get_f64(&self, path: &str) -> Option<f64> {
+        self.get_path(path).and_then(Value::as_f64)
+    }
+
+    pub fn get_bool(&self, path: &str) -> Option<bool> {
+        self.get_path(path).and_then(Value::as_bool)
+    }
+
+    pub fn get_str(&self, path: &str) -> Option<&str> {
+        self.get_path(path).and_then(Value::as_str)
+    }
+
+    /// The pose of the camera of the source: the `geo` key holding an object with the
+    /// `latitude`, `longitude`, optional `altitude` and optional `heading`.
+    ///
+    pub fn get_geo_pose(&self) -> anyhow::Result<Option<GeoPosition>> {
+        let Some(value) = self.values.get("geo") else {
+            return Ok(None);
+        };
+        let pose = GeoPosition::deserialize(value)
+            .with_context(|| format!("Invalid geo pose of the source {}", self.source_id))?;
+        pose.validate()?;
+        Ok(Some(pose))
+    }
+
+    pub fn keys(&self) -> Vec<String> {
+        self.values.keys().cloned().collect()
+    }
+
+    pub fn as_json(&self) -> Value {
+        Value::Object(self.values.clone())
+    }
+}
+
+fn as_object(value: Value, what: &str) -> anyhow::Result<Map<String, Value>> {
+    match value {
+        Value::Object(map) => Ok(map),
+        Value::Null => Ok(Map::new()),
+        _ => bail!("The {} must be an object", what),
+    }
+}
+
+/// Applies the overlay to the target: objects are merged recursively, `null` removes the
+/// key, other values replace the target values.
+///
+fn merge(target: &mut Map<String, Value>, overlay: Map<String, Value>) {
+    for (key, value) in overlay {
+        match (target.get_mut(&key), value) {
+            (_, Value::Null) => {
+                target.remove(&key);
+            }
+            (Some(Value::Object(current)), Value::Object(value)) => merge(current, value),
+            (_, value) => {
+                target.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Resolves the configuration of a source from the global defaults, the provider overrides
+/// and the in-memory overrides, applied in this order. Resolved configurations are cached
+/// for the TTL, the provider is not queried again until they expire or are invalidated.
+///
+#[derive(Debug)]
+pub struct SourceConfigResolver {
+    defaults: Map<String, Value>,
+    provider: Option<SourceConfigProvider>,
+    ttl: Duration,
+    overrides: SavantRwLock<HashMap<String, Map<String, Value>>>,
+    cache: SavantRwLock<HashMap<String, (Instant, Arc<SourceConfig>)>>,
+}
+
+impl SourceConfigResolver {
+    pub fn new(
+        defaults: Value,
+        provider: Option<SourceConfigProvider>,
+        ttl: Duration,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            defaults: as_object(defaults, "default configuration")?,
+            provider,
+            ttl,
+            overrides: SavantRwLock::new(HashMap::new()),
+            cache: SavantRwLock::new(HashMap::new()),
+        })
+    }
+
+    pub fn get_provider(&self) -> &Option<SourceConfigProvider> {
+        &self.provider
+    }
+
+    pub fn get_ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Sets the in-memory overrides of the source, applied over the provider overrides.
+    ///
+    pub fn set_override(&self, source_id: &str, overrides: Value) -> anyhow::Result<()> {
+        let overrides = as_object(overrides, "source configuration")?;
+        self.overrides
+            .write()
+            .insert(source_id.to_string(), overrides);
+        self.invalidate(Some(source_id));
+        Ok(())
+    }
+
+    pub fn clear_override(&self, source_id: &str) {
+        self.overrides.write().remove(source_id);
+        self.invalidate(Some(source_id));
+    }
+
+    /// Drops the cached configuration of the source, `None` drops all cached configurations.
+    ///
+    pub fn invalidate(&self, source_id: Option<&str>) {
+        let mut cache = self.cache.write();
+        match source_id {
+            Some(source_id) => {
+                cache.remove(source_id);
+            }
+            None => cache.clear(),
+        }
+    }
+
+    pub fn resolve(&self, source_id: &str) -> anyhow::Result<Arc<SourceConfig>> {
+        if let Some((resolved_at, config)) = self.cache.read().get(source_id) {
+            if resolved_at.elapsed() < self.ttl {
+                return Ok(config.clone());
+            }
+        }
+        let mut values = self.defaults.clone();
+        if let Some(provider) = &self.provider {
+            if let Some(value) = provider.load(source_id).with_context(|| {
+                format!("Failed to load the configuration of source {}", source_id)
+            })? {
+                merge(&mut values, as_object(value, "source configuration")?);
+            }
+        }
+        if let Some(overrides) = self.overrides.read().get(source_id) {
+            merge(&mut values, overrides.clone());
+        }
+        let config = Arc::new(SourceConfig {
+            source_id: source_id.to_string(),
+            values,
+        });
+        self.cache
+            .write()
+            .insert(source_id.to_string(), (Instant::now(), config.clone()));
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::source_config::{SourceConfigProvider, SourceConfigResolver};
+    use crate::utils::uuid_v7::incremental_uuid_v7;
+    use crate::webserver::kvs::synchronous::set_blob;
+    use crate::webserver::kvs::KvsBlob;
+    use serde_json::json;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn defaults() -> serde_json::Value {
+        json!({
+            "threshold": 0.5,
+            "models": {"detector": true, "classifier": true},
+            "zones": {"entrance": [[0, 0], [10, 0], [10, 10]]}
+        })
+    }
+
+    #[test]
+    fn test_directory_overlay() -> anyhow::Result<()> {
+        let dir =
+            std::env::temp_dir().join(format!("savant-source-config-{}", incremental_uuid_v7()));
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(
+            dir.join("cam-1.yaml"),
+            "threshold: 0.7\nmodels:\n  classifier: false\nzones: null\n",
+        )?;
+        std::fs::write(dir.join("cam-2.json"), "[1, 2]")?;
+        let resolver = SourceConfigResolver::new(
+            defaults(),
+            Some(SourceConfigProvider::Directory(dir.clone())),
+            Duration::from_secs(60),
+        )?;
+
+        let config = resolver.resolve("cam-1")?;
+        assert_eq!(config.get_source_id(), "cam-1");
+        assert_eq!(config.get_f64("threshold"), Some(0.7));
+        assert_eq!(config.get_bool("models/detector"), Some(true));
+        assert_eq!(config.get_bool("models/classifier"), Some(false));
+        assert!(config.get("zones").is_none());
+
+        let config = resolver.resolve("cam-3")?;
+        assert_eq!(config.get_f64("threshold"), Some(0.5));
+        assert!(config.get_path("zones/entrance").is_some());
+        assert!(resolver.resolve("cam-2").is_err());
+        assert!(config.get_geo_pose()?.is_none());
+
+        let outside = dir.with_extension("yaml");
+        std::fs::write(&outside, "threshold: 0.9\n")?;
+        let escaping = format!("../{}", dir.file_name().unwrap().to_string_lossy());
+        assert!(resolver.resolve(&escaping).is_err());
+        assert!(resolver.resolve(&dir.to_string_lossy()).is_err());
+        assert!(resolver.resolve("cam\\..\\cam-1").is_err());
+        assert!(resolver.resolve("cam-1\0").is_err());
+        std::fs::remove_file(&outside)?;
+
+        resolver.set_override(
+            "cam-3",
+            json!({"geo": {"latitude": 55.75, "longitude": 37.61, "heading": 270.0}}),
+        )?;
+        let pose = resolver.resolve("cam-3")?.get_geo_pose()?.unwrap();
+        assert_eq!(pose.altitude, 0.0);
+        assert_eq!(pose.heading, Some(270.0));
+        resolver.set_override(
+            "cam-3",
+            json!({"geo": {"latitude": 95.0, "longitude": 0.0}}),
+        )?;
+        assert!(resolver.resolve("cam-3")?.get_geo_pose().is_err());
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_kvs_overlay_and_cache() -> anyhow::Result<()> {
+        let namespace = format!("source_config_test_{}", incremental_uuid_v7());
+        set_blob(
+            &namespace,
+            "cam-1",
+            KvsBlob::json(&json!({"threshold": 0.9})),
+            None,
+        )?;
+        let resolver = SourceConfigResolver::new(
+            defaults(),
+            Some(SourceConfigProvider::Kvs {
+                namespace: namespace.clone(),
+            }),
+            Duration::from_secs(60),
+        )?;
+        let first = resolver.resolve("cam-1")?;
+        assert_eq!(first.get_f64("threshold"), Some(0.9));
+
+        // the cached configuration is served until invalidated
+        set_blob(
+            &namespace,
+            "cam-1",
+            KvsBlob::json(&json!({"threshold": 0.1})),
+            None,
+        )?;
+        assert!(Arc::ptr_eq(&first, &resolver.resolve("cam-1")?));
+        resolver.invalidate(None);
+        assert_eq!(resolver.resolve("cam-1")?.get_f64("threshold"), Some(0.1));
+
+        resolver.set_override("cam-1", json!({"models": {"detector": false}}))?;
+        let config = resolver.resolve("cam-1")?;
+        assert_eq!(config.get_f64("threshold"), Some(0.1));
+        assert_eq!(config.get_bool("models/detector"), Some(false));
+        assert_eq!(config.get_bool("models/classifier"), Some(true));
+        resolver.clear_override("cam-1");
+        assert_eq!(
+            resolver.resolve("cam-1")?.get_bool("models/detector"),
+            Some(true)
+        );
+        assert!(resolver.set_override("cam-1", json!(1)).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,363 @@
+use crate::message::Message;
+use crate::pipeline::implementation;
+use crate::pipeline::rates::RateMeter;
+use crate::pipeline::Pipeline;
+use crate::primitives::attribute_value::{AttributeValue, AttributeValueVariant};
+use crate::primitives::userdata::UserData;
+use crate::primitives::{Attribute, WithAttributes};
+use crate::transport::zeromq::SyncWriter;
+use hashbrown::HashMap;
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// The namespace of the attribute carried by the source statistics messages.
+pub const SOURCE_STATS_NAMESPACE: &str = "pipeline";
+/// The name of the attribute carried by the source statistics messages, its values are the
+/// ingest rate, the latency, the drop rate and the allowed ingest rate.
+pub const SOURCE_STATS_ATTRIBUTE: &str = "source_stats";
+
+/// The weight of the latest frame in the smoothed latency.
+const LATENCY_SMOOTHING: f64 = 0.2;
+
+/// The processing statistics of a source over the rate window of the pipeline, published to
+/// the upstream so the encoders adapt the bitrate or the frame rate to the backpressure of
+/// the analytics.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceStatistics {
+    pub source_id: String,
+    /// The rate of the frames offered to the pipeline, the rejected ones included.
+    pub ingest_fps: f64,
+    /// The time the recent frames spent in the pipeline, smoothed exponentially.
+    pub latency_ms: f64,
+    /// The part of the offered frames rejected at ingest or dropped by the stages.
+    pub drop_rate: f64,
+    /// The rate of the frames the pipeline processed without dropping while it was dropping
+    /// frames, `None` when no frames were dropped in the window.
+    pub allowed_fps: Option<f64>,
+}
+
+impl SourceStatistics {
+    /// The user data message of the source carrying the
+    /// [`SOURCE_STATS_NAMESPACE`]/[`SOURCE_STATS_ATTRIBUTE`] attribute.
+    ///
+    pub fn to_message(&self) -> Message {
+        let mut user_data = UserData::new(&self.source_id);
+        user_data.set_attribute(Attribute::persistent(
+            SOURCE_STATS_NAMESPACE,
+            SOURCE_STATS_ATTRIBUTE,
+            vec![
+                AttributeValue::float(self.ingest_fps, None),
+                AttributeValue::float(self.latency_ms, None),
+                AttributeValue::float(self.drop_rate, None),
+                match self.allowed_fps {
+                    Some(fps) => AttributeValue::float(fps, None),
+                    None => AttributeValue::none(),
+                },
+            ],
+            &None,
+            false,
+        ));
+        Message::user_data(user_data)
+    }
+
+    /// Recognizes a source statistics message.
+    ///
+    pub fn from_message(message: &Message) -> Option<Self> {
+        let user_data = message.as_user_data()?;
+        let attribute = user_data.get_attribute(SOURCE_STATS_NAMESPACE, SOURCE_STATS_ATTRIBUTE)?;
+        let values = attribute
+            .get_values()
+            .iter()
+            .map(|v| match v.get() {
+                AttributeValueVariant::Float(f) => Some(Some(*f)),
+                AttributeValueVariant::None => Some(None),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let [Some(ingest_fps), Some(latency_ms), Some(drop_rate), allowed_fps] = values[..] else {
+            return None;
+        };
+        Some(Self {
+            source_id: user_data.get_source_id().to_string(),
+            ingest_fps,
+            latency_ms,
+            drop_rate,
+            allowed_fps,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct SourceState {
+    offered: RateMeter,
+    dropped: RateMeter,
+    completed: RateMeter,
+    latency_ms: Option<f64>,
+}
+
+impl SourceState {
+    fn new(window: Duration) -> Self {
+        Self {
+            offered: RateMeter::new(window),
+            dropped: RateMeter::new(window),
+            completed: RateMeter::new(window),
+            latency_ms: None,
+        }
+    }
+}
+
+/// Tracks the frames of the sources from the ingest to the deletion.
+///
+#[derive(Debug)]
+pub(crate) struct SourceStatsTracker {
+    window: Duration,
+    sources: Mutex<HashMap<String, SourceState>>,
+    /// The source and the time the frames in the pipeline were admitted.
+    in_flight: Mutex<HashMap<i64, (String, u64)>>,
+}
+
+impl Default for SourceStatsTracker {
+    fn default() -> Self {
+        Self::new(crate::pipeline::rates::DEFAULT_RATE_WINDOW)
+    }
+}
+
+impl SourceStatsTracker {
+    pub(crate) fn new(window: Duration) -> Self {
+        Self {
+            window,
+            sources: Mutex::new(HashMap::new()),
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn with_source<F>(&self, source_id: &str, f: F)
+    where
+        F: FnOnce(&mut SourceState),
+    {
+        let mut sources = self.sources.lock();
+        match sources.get_mut(source_id) {
+            Some(state) => f(state),
+            None => {
+                let mut state = SourceState::new(self.window);
+                f(&mut state);
+                sources.insert(source_id.to_string(), state);
+            }
+        }
+    }
+
+    pub(crate) fn admit(&self, id: i64, source_id: &str, now_ms: u64) {
+        self.with_source(source_id, |s| s.offered.observe(now_ms));
+        self.in_flight
+            .lock()
+            .insert(id, (source_id.to_string(), now_ms));
+    }
+
+    pub(crate) fn reject(&self, source_id: &str, now_ms: u64) {
+        self.with_source(source_id, |s| {
+            s.offered.observe(now_ms);
+            s.dropped.observe(now_ms);
+        });
+    }
+
+    /// Accounts the frames leaving the pipeline, the ids of the payloads which are not
+    /// frames are ignored.
+    ///
+    pub(crate) fn complete<'a>(
+        &self,
+        ids: impl IntoIterator<Item = &'a i64>,
+        dropped: bool,
+        now_ms: u64,
+    ) {
+        let left = {
+            let mut in_flight = self.in_flight.lock();
+            ids.into_iter()
+                .filter_map(|id| in_flight.remove(id))
+                .collect::<Vec<_>>()
+        };
+        for (source_id, admitted_ms) in left {
+            self.with_source(&source_id, |s| {
+                if dropped {
+                    s.dropped.observe(now_ms);
+                    return;
+                }
+                s.completed.observe(now_ms);
+                let latency = now_ms.saturating_sub(admitted_ms) as f64;
+                s.latency_ms = Some(match s.latency_ms {
+                    Some(l) => l + LATENCY_SMOOTHING * (latency - l),
+                    None => latency,
+                });
+            });
+        }
+    }
+
+    /// The statistics of the sources ordered by the source, the sources idle for the window
+    /// are forgotten.
+    ///
+    pub(crate) fn measure(&self, now_ms: u64) -> Vec<SourceStatistics> {
+        let in_flight = self
+            .in_flight
+            .lock()
+            .values()
+            .map(|(s, _)| s.clone())
+            .collect::<hashbrown::HashSet<_>>();
+        let mut sources = self.sources.lock();
+        sources.retain(|source_id, s| !s.offered.is_idle(now_ms) || in_flight.contains(source_id));
+        let mut stats = sources
+            .iter_mut()
+            .map(|(source_id, s)| {
+                let offered = s.offered.measure(now_ms);
+                let dropped = s.dropped.measure(now_ms);
+                let completed = s.completed.measure(now_ms);
+                let drop_rate = if offered.frames == 0 {
+                    0.0
+                } else {
+                    (dropped.frames as f64 / offered.frames as f64).min(1.0)
+                };
+                SourceStatistics {
+                    source_id: source_id.clone(),
+                    ingest_fps: offered.fps,
+                    latency_ms: s.latency_ms.unwrap_or_default(),
+                    drop_rate,
+                    allowed_fps: (dropped.frames > 0).then_some(completed.fps),
+                }
+            })
+            .collect::<Vec<_>>();
+        stats.sort_by(|a, b| a.source_id.cmp(&b.source_id));
+        stats
+    }
+}
+
+/// Sends the statistics of the sources of the pipeline to the control topic every period,
+/// a message per source (see [`SourceStatistics::to_message`]). The publisher stops when it
+/// is dropped or the pipeline is dropped. The failed sends are logged.
+///
+pub struct SourceStatsPublisher {
+    shutdown: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl SourceStatsPublisher {
+    pub fn start(
+        pipeline: &Pipeline,
+        writer: SyncWriter,
+        topic: &str,
+        period: Duration,
+    ) -> anyhow::Result<Self> {
+        if period.is_zero() {
+            anyhow::bail!("The publishing period must be greater than 0");
+        }
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let thread_shutdown = shutdown.clone();
+        let pipeline: Weak<implementation::Pipeline> = Arc::downgrade(&pipeline.0);
+        let topic = topic.to_string();
+        let thread = std::thread::spawn(move || loop {
+            std::thread::park_timeout(period);
+            if thread_shutdown.load(Ordering::Relaxed) {
+                break;
+            }
+            let Some(pipeline) = pipeline.upgrade() else {
+                break;
+            };
+            let stats = pipeline.get_source_statistics();
+            drop(pipeline);
+            for s in stats {
+                if let Err(e) = writer.send_message(&topic, &s.to_message(), &[]) {
+                    log::warn!(
+                        target: "savant_rs::pipeline::source_stats",
+                        "Failed to publish the statistics of the source {}: {}",
+                        s.source_id,
+                        e
+                    );
+                }
+            }
+        });
+        Ok(Self {
+            shutdown,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn stop(&mut self) {
+        let Some(thread) = self.thread.take() else {
+            return;
+        };
+        self.shutdown.store(true, Ordering::Relaxed);
+        thread.thread().unpark();
+        if thread.join().is_err() {
+            log::error!(
+                target: "savant_rs::pipeline::source_stats",
+                "The source statistics thread panicked"
+            );
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.thread.is_some()
+    }
+}
+
+impl Drop for SourceStatsPublisher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::source_stats::{SourceStatistics, SourceStatsTracker};
+    use crate::pipeline::{Pipeline, PipelineConfiguration, PipelineStagePayloadType};
+    use crate::test::gen_frame;
+    use std::time::Duration;
+
+    #[test]
+    fn test_source_statistics() {
+        let tracker = SourceStatsTracker::new(Duration::from_secs(1));
+        for i in 0..10 {
+            let now = i * 100;
+            tracker.admit(i as i64, "cam-1", now);
+            tracker.complete(&[i as i64], i % 5 == 4, now + 40);
+        }
+        tracker.reject("cam-2", 500);
+        let stats = tracker.measure(1000);
+        assert_eq!(stats.len(), 2);
+        let cam = &stats[0];
+        assert_eq!(cam.source_id, "cam-1");
+        assert_eq!(cam.ingest_fps, 10.0);
+        assert_eq!(cam.latency_ms, 40.0);
+        assert_eq!(cam.drop_rate, 0.2);
+        // 8 frames completed since the first one at 40 ms
+        assert!((cam.allowed_fps.unwrap() - 8.0 / 0.96).abs() < 1e-9);
+        assert_eq!(stats[1].drop_rate, 1.0);
+
+        let message = cam.to_message();
+        assert_eq!(SourceStatistics::from_message(&message).as_ref(), Some(cam));
+        assert!(tracker.measure(3000).is_empty());
+    }
+
+    #[test]
+    fn test_pipeline_source_statistics() -> anyhow::Result<()> {
+        let pipeline = Pipeline::new(
+            vec![(
+                "input".to_string(),
+                PipelineStagePayloadType::Frame,
+                None,
+                None,
+            )],
+            PipelineConfiguration::default(),
+        )?;
+        let frame = gen_frame();
+        let id = pipeline.add_frame("input", frame.clone())?;
+        pipeline.delete(id)?;
+        let stats = pipeline.get_source_statistics();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].source_id, frame.get_source_id());
+        assert_eq!(stats[0].drop_rate, 0.0);
+        assert!(stats[0].allowed_fps.is_none());
+        Ok(())
+    }
+}
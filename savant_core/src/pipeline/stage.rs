@@ -1,23 +1,41 @@
 use std::fmt::{Debug, Formatter};
 use std::sync::Arc;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
 use anyhow::bail;
 use hashbrown::{HashMap, HashSet};
 use opentelemetry::trace::TraceContextExt;
-use opentelemetry::Context;
+use opentelemetry::{Context, KeyValue};
 use parking_lot::Mutex;
 
+use crate::clock::{self, Clock};
 use crate::match_query::MatchQuery;
+use crate::pipeline::circuit_breaker::CircuitBreaker;
+use crate::pipeline::debug_tap::DebugTap;
+use crate::pipeline::decimator::Decimator;
+#[cfg(feature = "chaos")]
+use crate::pipeline::fault_injector::{corrupt_frame, FaultInjector};
+use crate::pipeline::history::FrameHistory;
+use crate::pipeline::hooks::{HookAction, StageHook};
 use crate::pipeline::implementation::Pipeline;
+use crate::pipeline::object_lifecycle::ObjectObserver;
+use crate::pipeline::rates::{StageRate, StageRateMeters, DEFAULT_RATE_WINDOW};
+use crate::pipeline::readiness::{StageReadiness, WarmupPolicy};
+use crate::pipeline::result_cache::ResultCache;
+use crate::pipeline::stage_processor::StageProcessor;
 use crate::pipeline::stats::{StageLatencyStat, StageProcessingStat, StageStats};
+use crate::pipeline::user_code::UserCodeTime;
+use crate::pipeline::validation::FrameValidator;
 use crate::pipeline::{
     PipelinePayload, PipelineStageFunction, PipelineStageFunctionOrder, PipelineStagePayloadType,
 };
+use crate::primitives::audio_frame::AudioFrame;
+use crate::primitives::content_encoding::ContentEncoding;
 use crate::primitives::frame::VideoFrameProxy;
 use crate::primitives::frame_batch::VideoFrameBatch;
 use crate::primitives::frame_update::VideoFrameUpdate;
-use crate::primitives::object::BorrowedVideoObject;
+use crate::primitives::object::{BorrowedVideoObject, OrphanPolicy};
+use crate::primitives::telemetry_frame::TelemetryFrame;
 use crate::rwlock::SavantRwLock;
 
 pub struct PipelineStage {
@@ -26,20 +44,64 @@ pub struct PipelineStage {
     pub stage_type: PipelineStagePayloadType,
     pub payload: SavantRwLock<HashMap<i64, PipelinePayload>>,
     pub stat: StageStats,
-    ingress_function: Option<Box<dyn PipelineStageFunction>>,
-    egress_function: Option<Box<dyn PipelineStageFunction>>,
+    /// The object namespaces frozen when frames leave the stage, `None` freezes all objects.
+    pub frozen_namespaces: Vec<Option<String>>,
+    /// The objects pruned from frames leaving the stage, before the objects are frozen.
+    pub prune_rules: Vec<(MatchQuery, OrphanPolicy)>,
+    /// The expected time a payload spends in the stage. Payloads leaving the stage later are
+    /// counted as budget overruns and marked with a span event.
+    pub budget: Option<Duration>,
+    /// The clock of the pipeline measuring the latencies, the budgets and the rates.
+    pub clock: Arc<Clock>,
+    /// The priorities of the payloads which are not 0, updated under the lock of the payloads.
+    priorities: Mutex<HashMap<i64, i32>>,
+    debug_tap: SavantRwLock<Option<Arc<DebugTap>>>,
+    object_observer: SavantRwLock<Option<Arc<ObjectObserver>>>,
+    hooks: SavantRwLock<Vec<(String, Arc<dyn StageHook>)>>,
+    history: SavantRwLock<Option<Arc<FrameHistory>>>,
+    validator: SavantRwLock<Option<Arc<FrameValidator>>>,
+    decimator: SavantRwLock<Option<Arc<Decimator>>>,
+    circuit_breaker: SavantRwLock<Option<Arc<CircuitBreaker>>>,
+    content_encoding: SavantRwLock<Option<ContentEncoding>>,
+    result_cache: SavantRwLock<Option<Arc<ResultCache>>>,
+    readiness: SavantRwLock<StageReadiness>,
+    warmup_policy: SavantRwLock<WarmupPolicy>,
+    rates: Mutex<StageRateMeters>,
+    #[cfg(feature = "chaos")]
+    fault_injector: SavantRwLock<Option<Arc<FaultInjector>>>,
+    ingress_function: StageProcessor,
+    egress_function: StageProcessor,
 }
 
 impl Debug for PipelineStage {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("PipelineStage")
-            .field("id", &self.id)
+        let mut s = f.debug_struct("PipelineStage");
+        s.field("id", &self.id)
             .field("name", &self.name)
             .field("stage_type", &self.stage_type)
             .field("payload", &self.payload)
             .field("stat", &self.stat)
-            .field("ingress_function", &self.ingress_function.is_some())
-            .field("egress_function", &self.egress_function.is_some())
+            .field("frozen_namespaces", &self.frozen_namespaces)
+            .field("prune_rules", &self.prune_rules)
+            .field("budget", &self.budget)
+            .field("clock", &self.clock)
+            .field("priorities", &self.priorities)
+            .field("debug_tap", &self.debug_tap)
+            .field("object_observer", &self.object_observer)
+            .field("hooks", &self.get_hook_names())
+            .field("history", &self.history)
+            .field("validator", &self.validator)
+            .field("decimator", &self.decimator)
+            .field("circuit_breaker", &self.circuit_breaker)
+            .field("content_encoding", &self.content_encoding)
+            .field("result_cache", &self.result_cache)
+            .field("readiness", &self.readiness)
+            .field("warmup_policy", &self.warmup_policy)
+            .field("rates", &self.rates);
+        #[cfg(feature = "chaos")]
+        s.field("fault_injector", &self.fault_injector);
+        s.field("ingress_function", &self.ingress_function)
+            .field("egress_function", &self.egress_function)
             .finish()
     }
 }
@@ -61,8 +123,27 @@ impl PipelineStage {
                 StageProcessingStat::new(name.clone()),
                 StageLatencyStat::new(name),
             ))),
-            ingress_function,
-            egress_function,
+            frozen_namespaces: Vec::new(),
+            prune_rules: Vec::new(),
+            budget: None,
+            clock: clock::process_clock(),
+            priorities: Mutex::new(HashMap::new()),
+            debug_tap: SavantRwLock::new(None),
+            object_observer: SavantRwLock::new(None),
+            hooks: SavantRwLock::new(Vec::new()),
+            history: SavantRwLock::new(None),
+            validator: SavantRwLock::new(None),
+            decimator: SavantRwLock::new(None),
+            circuit_breaker: SavantRwLock::new(None),
+            content_encoding: SavantRwLock::new(None),
+            result_cache: SavantRwLock::new(None),
+            readiness: SavantRwLock::new(StageReadiness::Ready),
+            warmup_policy: SavantRwLock::new(WarmupPolicy::Hold),
+            rates: Mutex::new(StageRateMeters::new(DEFAULT_RATE_WINDOW)),
+            #[cfg(feature = "chaos")]
+            fault_injector: SavantRwLock::new(None),
+            ingress_function: StageProcessor::new(ingress_function),
+            egress_function: StageProcessor::new(egress_function),
         }
     }
 
@@ -70,6 +151,21 @@ impl PipelineStage {
         self.stat.clone()
     }
 
+    pub fn has_ingress_function(&self) -> bool {
+        self.ingress_function.is_installed()
+    }
+
+    pub fn has_egress_function(&self) -> bool {
+        self.egress_function.is_installed()
+    }
+
+    pub fn get_processor(&self, order: PipelineStageFunctionOrder) -> &StageProcessor {
+        match order {
+            PipelineStageFunctionOrder::Ingress => &self.ingress_function,
+            PipelineStageFunctionOrder::Egress => &self.egress_function,
+        }
+    }
+
     fn with_payload_item_mut<F, T>(&self, id: i64, f: F) -> anyhow::Result<T>
     where
         F: FnOnce(&mut PipelinePayload) -> T,
@@ -140,6 +236,12 @@ impl PipelineStage {
         stat_bind.0.object_counter += f.get_object_count();
     }
 
+    fn update_processing_stats_for_sensor_frame(&self) {
+        let mut stat_bind = self.stat.lock();
+        stat_bind.0.frame_counter += 1;
+        stat_bind.0.queue_length += 1;
+    }
+
     fn update_processing_stats_for_batch(&self, b: &VideoFrameBatch) {
         let mut stat_bind = self.stat.lock();
         stat_bind.0.batch_counter += 1;
@@ -152,27 +254,438 @@ impl PipelineStage {
             .sum::<usize>();
     }
 
-    pub fn add_payloads<I>(&self, payloads: I) -> anyhow::Result<()>
+    fn for_each_frame<F>(payload: &PipelinePayload, f: F)
     where
-        I: IntoIterator<Item = (i64, PipelinePayload)>,
+        F: Fn(&VideoFrameProxy),
     {
-        self.with_payload_mut(|bind| {
-            for (id, mut payload) in payloads {
-                if let Some(ingress_function) = &self.ingress_function {
-                    ingress_function.call(
-                        id,
-                        self,
-                        PipelineStageFunctionOrder::Ingress,
-                        &mut payload,
-                    )?;
+        match payload {
+            PipelinePayload::Frame(frame, ..) => f(frame),
+            PipelinePayload::Batch(batch, ..) => batch.frames.values().for_each(f),
+            PipelinePayload::Audio(..) | PipelinePayload::Telemetry(..) => (),
+        }
+    }
+
+    pub fn set_debug_tap(&self, tap: Option<DebugTap>) {
+        *self.debug_tap.write() = tap.map(Arc::new);
+    }
+
+    pub fn get_debug_tap(&self) -> Option<Arc<DebugTap>> {
+        self.debug_tap.read().clone()
+    }
+
+    pub fn set_object_observer(&self, observer: Option<ObjectObserver>) {
+        *self.object_observer.write() = observer.map(Arc::new);
+    }
+
+    pub fn get_object_observer(&self) -> Option<Arc<ObjectObserver>> {
+        self.object_observer.read().clone()
+    }
+
+    /// Delivers the object events and the invariant violations of the frames which left the
+    /// stage, called without the lock of the payloads.
+    ///
+    fn notify_observers(&self) {
+        if let Some(observer) = self.get_object_observer() {
+            observer.notify(&self.name);
+        }
+        if let Some(validator) = self.validator.read().clone() {
+            validator.notify(&self.name);
+        }
+    }
+
+    /// Registers the hook under the name, the names are unique in the stage.
+    ///
+    pub fn add_hook(&self, name: &str, hook: Arc<dyn StageHook>) -> anyhow::Result<()> {
+        let mut hooks = self.hooks.write();
+        if hooks.iter().any(|(n, _)| n == name) {
+            bail!("Stage {} already has hook {}", self.name, name)
+        }
+        hooks.push((name.to_string(), hook));
+        Ok(())
+    }
+
+    pub fn remove_hook(&self, name: &str) -> bool {
+        let mut hooks = self.hooks.write();
+        let len = hooks.len();
+        hooks.retain(|(n, _)| n != name);
+        hooks.len() != len
+    }
+
+    /// The names of the hooks in the order they are called.
+    ///
+    pub fn get_hook_names(&self) -> Vec<String> {
+        self.hooks.read().iter().map(|(n, _)| n.clone()).collect()
+    }
+
+    /// Calls the enter hooks until one of them drops the payload.
+    ///
+    fn call_enter_hooks(&self, id: i64, payload: &mut PipelinePayload) -> HookAction {
+        for (name, hook) in self.hooks.read().iter() {
+            if hook.on_enter(payload) == HookAction::Drop {
+                log::debug!(
+                    target: "savant_rs::pipeline::hooks",
+                    "Payload {} is dropped by hook {} of stage {}",
+                    id,
+                    name,
+                    self.name
+                );
+                return HookAction::Drop;
+            }
+        }
+        HookAction::Keep
+    }
+
+    /// Decides whether the payload enters the stage: the enter hooks are called, then the
+    /// faults of the fault injector are applied to the kept payload. The injected delay is
+    /// returned, the caller sleeps after releasing the payloads, see
+    /// [`PipelineStage::sleep_injected`].
+    ///
+    fn admit(&self, id: i64, payload: &mut PipelinePayload) -> (HookAction, Duration) {
+        if self.call_enter_hooks(id, payload) == HookAction::Drop {
+            return (HookAction::Drop, Duration::ZERO);
+        }
+        #[cfg(feature = "chaos")]
+        if let Some(injector) = self.get_fault_injector() {
+            let faults = injector.roll_faults();
+            if faults.drop {
+                log::debug!(
+                    target: "savant_rs::pipeline",
+                    "Payload {} is dropped by the fault injector of stage {}",
+                    id,
+                    self.name
+                );
+                return (HookAction::Drop, Duration::ZERO);
+            }
+            if faults.corrupt {
+                Self::for_each_frame(payload, corrupt_frame);
+            }
+            return (HookAction::Keep, faults.delay.unwrap_or_default());
+        }
+        (HookAction::Keep, Duration::ZERO)
+    }
+
+    /// Sleeps for the delay injected on admission. The payloads are not locked, so the delay
+    /// stalls only the caller, not the other users of the stage.
+    ///
+    fn sleep_injected(delay: Duration) {
+        if !delay.is_zero() {
+            std::thread::sleep(delay);
+        }
+    }
+
+    fn call_exit_hooks(&self, payload: &mut PipelinePayload) {
+        for (_, hook) in self.hooks.read().iter() {
+            hook.on_exit(payload);
+        }
+    }
+
+    pub(crate) fn set_history(&self, history: Option<Arc<FrameHistory>>) {
+        *self.history.write() = history;
+    }
+
+    pub(crate) fn set_validator(&self, validator: Option<Arc<FrameValidator>>) {
+        *self.validator.write() = validator;
+    }
+
+    pub fn set_decimator(&self, decimator: Option<Decimator>) {
+        *self.decimator.write() = decimator.map(Arc::new);
+    }
+
+    pub fn get_decimator(&self) -> Option<Arc<Decimator>> {
+        self.decimator.read().clone()
+    }
+
+    pub fn set_circuit_breaker(&self, breaker: Option<CircuitBreaker>) {
+        *self.circuit_breaker.write() = breaker.map(|mut b| {
+            b.set_clock(self.clock.clone());
+            Arc::new(b)
+        });
+    }
+
+    pub fn get_circuit_breaker(&self) -> Option<Arc<CircuitBreaker>> {
+        self.circuit_breaker.read().clone()
+    }
+
+    pub fn set_content_encoding(&self, encoding: Option<ContentEncoding>) {
+        *self.content_encoding.write() = encoding;
+    }
+
+    pub fn get_content_encoding(&self) -> Option<ContentEncoding> {
+        *self.content_encoding.read()
+    }
+
+    pub fn set_result_cache(&self, cache: Option<ResultCache>) {
+        *self.result_cache.write() = cache.map(Arc::new);
+    }
+
+    pub fn get_result_cache(&self) -> Option<Arc<ResultCache>> {
+        self.result_cache.read().clone()
+    }
+
+    /// Sets the readiness reported on behalf of the stage, e.g. by the code loading its model
+    /// outside of the stage functions.
+    ///
+    pub fn set_readiness(&self, readiness: StageReadiness) {
+        *self.readiness.write() = readiness;
+    }
+
+    /// The stage is ready when the reported readiness and both stage functions are ready.
+    ///
+    pub fn get_readiness(&self) -> StageReadiness {
+        self.readiness
+            .read()
+            .clone()
+            .and(self.ingress_function.get_readiness())
+            .and(self.egress_function.get_readiness())
+    }
+
+    pub fn set_warmup_policy(&self, policy: WarmupPolicy) {
+        *self.warmup_policy.write() = policy;
+    }
+
+    pub fn get_warmup_policy(&self) -> WarmupPolicy {
+        *self.warmup_policy.read()
+    }
+
+    /// Sets the window of the rate meters, the measured rates are reset.
+    ///
+    pub fn set_rate_window(&self, window: Duration) {
+        *self.rates.lock() = StageRateMeters::new(window);
+    }
+
+    /// The rate of the frames entering the stage and of the frames of each source.
+    ///
+    pub fn get_rates(&self) -> Vec<StageRate> {
+        self.rates.lock().measure(&self.name, self.clock.now_ms())
+    }
+
+    #[cfg(feature = "chaos")]
+    pub fn set_fault_injector(&self, injector: Option<FaultInjector>) {
+        *self.fault_injector.write() = injector.map(Arc::new);
+    }
+
+    #[cfg(feature = "chaos")]
+    pub fn get_fault_injector(&self) -> Option<Arc<FaultInjector>> {
+        self.fault_injector.read().clone()
+    }
+
+    /// Corrupts the content of the frames of the payload.
+    ///
+    #[cfg(feature = "chaos")]
+    pub(crate) fn corrupt(&self, id: i64) -> anyhow::Result<()> {
+        self.with_payload_item(id, |payload| Self::for_each_frame(payload, corrupt_frame))
+    }
+
+    fn enter(&self, payload: &PipelinePayload) {
+        let tap = self.get_debug_tap();
+        let encoding = self.get_content_encoding();
+        let cache = self.get_result_cache();
+        let observer = self.get_object_observer();
+        let now = self.clock.now_ms();
+        Self::for_each_frame(payload, |frame| {
+            frame.set_stage(Some(self.name.clone()));
+            self.rates.lock().observe(&frame.get_source_id(), now);
+            if let Some(cache) = &cache {
+                cache.enter(frame);
+            }
+            if let Some(encoding) = encoding {
+                if let Err(e) = frame.clone().compress_content(encoding) {
+                    log::error!(
+                        target: "savant_rs::pipeline",
+                        "Stage {} failed to compress the content of frame {}: {}",
+                        self.name,
+                        frame.get_uuid_as_string(),
+                        e
+                    );
+                }
+            }
+            if let Some(tap) = &tap {
+                tap.observe(&self.name, frame);
+            }
+            if let Some(observer) = &observer {
+                observer.enter(frame);
+            }
+        });
+    }
+
+    fn leave(&self, payload: &PipelinePayload) {
+        let cache = self.get_result_cache();
+        let observer = self.get_object_observer();
+        let history = self.history.read().clone();
+        Self::for_each_frame(payload, |frame| {
+            for (query, policy) in &self.prune_rules {
+                if let Err(e) = frame.prune(query, *policy) {
+                    log::error!(
+                        target: "savant_rs::pipeline",
+                        "Stage {} failed to prune objects of frame {}: {}",
+                        self.name,
+                        frame.get_uuid_as_string(),
+                        e
+                    );
+                }
+            }
+            if let Some(observer) = &observer {
+                observer.leave(frame);
+            }
+            if let Some(cache) = &cache {
+                cache.leave(frame);
+            }
+            for namespace in &self.frozen_namespaces {
+                frame.freeze_objects(namespace.as_deref(), &self.name);
+            }
+            if let Some(history) = &history {
+                history.record(&self.name, frame);
+            }
+            frame.set_stage(None);
+        });
+        self.check_budget(payload);
+        self.validate(payload);
+    }
+
+    /// Checks the invariants of the frames leaving the stage and adds the
+    /// `invariant-violation` events to the spans of the frames breaking them.
+    ///
+    fn validate(&self, payload: &PipelinePayload) {
+        let Some(validator) = self.validator.read().clone() else {
+            return;
+        };
+        let frames = match payload {
+            PipelinePayload::Frame(frame, _, ctx, _, _) => vec![(frame, Some(ctx))],
+            PipelinePayload::Batch(batch, _, contexts, _, _) => batch
+                .frames
+                .iter()
+                .map(|(id, frame)| (frame, contexts.get(id)))
+                .collect(),
+            PipelinePayload::Audio(..) | PipelinePayload::Telemetry(..) => return,
+        };
+        for (frame, ctx) in frames {
+            let violations = validator.check(&self.name, frame);
+            let Some(ctx) = ctx else {
+                continue;
+            };
+            for v in violations {
+                ctx.span().add_event(
+                    "invariant-violation",
+                    vec![
+                        KeyValue::new("stage", self.name.clone()),
+                        KeyValue::new("kind", v.kind()),
+                        KeyValue::new("message", v.to_string()),
+                    ],
+                );
+            }
+        }
+    }
+
+    /// Counts the payload as a budget overrun when it spent more than the budget in the
+    /// stage and adds the `budget-overrun` event to the spans of its frames.
+    ///
+    fn check_budget(&self, payload: &PipelinePayload) {
+        let Some(budget) = self.budget else {
+            return;
+        };
+        let (entered, contexts) = match payload {
+            PipelinePayload::Frame(_, _, ctx, _, entered)
+            | PipelinePayload::Audio(_, ctx, _, entered)
+            | PipelinePayload::Telemetry(_, ctx, _, entered) => (*entered, vec![ctx]),
+            PipelinePayload::Batch(_, _, contexts, _, entered) => match entered.first() {
+                Some(entered) => (*entered, contexts.values().collect()),
+                None => return,
+            },
+        };
+        let elapsed = self.clock.elapsed(entered);
+        if elapsed <= budget {
+            return;
+        }
+        self.stat.lock().0.budget_overrun_counter += 1;
+        log::debug!(
+            target: "savant_rs::pipeline",
+            "Stage {} budget overrun: {} micros spent, {} micros allowed",
+            self.name,
+            elapsed.as_micros(),
+            budget.as_micros()
+        );
+        for ctx in contexts {
+            ctx.span().add_event(
+                "budget-overrun",
+                vec![
+                    KeyValue::new("stage", self.name.clone()),
+                    KeyValue::new("budget_us", budget.as_micros() as i64),
+                    KeyValue::new("elapsed_us", elapsed.as_micros() as i64),
+                ],
+            );
+        }
+    }
+
+    /// Records the time of the user code which processed the payloads and adds the
+    /// `user-code` event to the spans of their frames. The payloads which are not in the
+    /// stage are skipped.
+    ///
+    pub fn record_user_code_time(&self, ids: &[i64], time: UserCodeTime) {
+        {
+            let mut stat = self.stat.lock();
+            stat.0.user_code_time.record(time.user());
+            stat.0.rust_overhead_time.record(time.rust);
+            stat.0.gil_wait_time.record(time.gil_wait);
+        }
+        self.with_payload(|payloads| {
+            for payload in ids.iter().filter_map(|id| payloads.get(id)) {
+                let contexts = match payload {
+                    PipelinePayload::Frame(_, _, ctx, _, _)
+                    | PipelinePayload::Audio(_, ctx, _, _)
+                    | PipelinePayload::Telemetry(_, ctx, _, _) => vec![ctx],
+                    PipelinePayload::Batch(_, _, contexts, _, _) => contexts.values().collect(),
+                };
+                for ctx in contexts {
+                    ctx.span().add_event(
+                        "user-code",
+                        vec![
+                            KeyValue::new("stage", self.name.clone()),
+                            KeyValue::new("user_us", time.user().as_micros() as i64),
+                            KeyValue::new("rust_us", time.rust.as_micros() as i64),
+                            KeyValue::new("gil_wait_us", time.gil_wait.as_micros() as i64),
+                        ],
+                    );
                 }
+            }
+        });
+    }
+
+    /// Adds the payloads, returns the payloads dropped by the hooks or the fault injector.
+    ///
+    pub fn add_payloads<I>(&self, payloads: I) -> anyhow::Result<Vec<(i64, PipelinePayload)>>
+    where
+        I: IntoIterator<Item = (i64, PipelinePayload)>,
+    {
+        self.add_prioritized_payloads(payloads.into_iter().map(|(id, p)| (id, p, 0)))
+    }
+
+    /// Adds the payloads with their priorities, see [`PipelineStage::get_queue`]. Returns the
+    /// payloads dropped by the hooks or the fault injector.
+    ///
+    pub fn add_prioritized_payloads<I>(
+        &self,
+        payloads: I,
+    ) -> anyhow::Result<Vec<(i64, PipelinePayload)>>
+    where
+        I: IntoIterator<Item = (i64, PipelinePayload, i32)>,
+    {
+        let mut delay = Duration::ZERO;
+        let res = self.with_payload_mut(|bind| {
+            let mut dropped = Vec::new();
+            for (id, mut payload, priority) in payloads {
+                self.ingress_function.call(
+                    id,
+                    self,
+                    PipelineStageFunctionOrder::Ingress,
+                    &mut payload,
+                )?;
                 if bind.contains_key(&id) {
                     bail!("Payload {} already exists", id)
                 }
-                let payload = match payload {
+                let mut payload = match payload {
                     PipelinePayload::Frame(f, updates, context, last_stage, last_time) => {
-                        if self.stage_type == PipelineStagePayloadType::Batch {
-                            bail!("Payload must be a batch")
+                        if self.stage_type != PipelineStagePayloadType::Frame {
+                            bail!("Payload must be a {:?}", self.stage_type)
                         } else {
                             self.update_processing_stats_for_frame(&f);
                             self.update_latency_stats(last_stage, vec![last_time]);
@@ -182,12 +695,12 @@ impl PipelineStage {
                             updates,
                             context,
                             Some(self.name.clone()),
-                            SystemTime::now(),
+                            self.clock.now(),
                         )
                     }
                     PipelinePayload::Batch(b, updates, contexts, last_stage, last_times) => {
-                        if self.stage_type == PipelineStagePayloadType::Frame {
-                            bail!("Payload must be a frame")
+                        if self.stage_type != PipelineStagePayloadType::Batch {
+                            bail!("Payload must be a {:?}", self.stage_type)
                         } else {
                             self.update_processing_stats_for_batch(&b);
                             self.update_latency_stats(last_stage, last_times);
@@ -197,52 +710,135 @@ impl PipelineStage {
                             updates,
                             contexts,
                             Some(self.name.clone()),
-                            vec![SystemTime::now()],
+                            vec![self.clock.now()],
+                        )
+                    }
+                    PipelinePayload::Audio(f, context, last_stage, last_time) => {
+                        if self.stage_type != PipelineStagePayloadType::Audio {
+                            bail!("Payload must be a {:?}", self.stage_type)
+                        } else {
+                            self.update_processing_stats_for_sensor_frame();
+                            self.update_latency_stats(last_stage, vec![last_time]);
+                        }
+                        PipelinePayload::Audio(
+                            f,
+                            context,
+                            Some(self.name.clone()),
+                            self.clock.now(),
+                        )
+                    }
+                    PipelinePayload::Telemetry(f, context, last_stage, last_time) => {
+                        if self.stage_type != PipelineStagePayloadType::Telemetry {
+                            bail!("Payload must be a {:?}", self.stage_type)
+                        } else {
+                            self.update_processing_stats_for_sensor_frame();
+                            self.update_latency_stats(last_stage, vec![last_time]);
+                        }
+                        PipelinePayload::Telemetry(
+                            f,
+                            context,
+                            Some(self.name.clone()),
+                            self.clock.now(),
                         )
                     }
                 };
+                let (action, injected) = self.admit(id, &mut payload);
+                delay += injected;
+                if action == HookAction::Drop {
+                    dropped.push((id, payload));
+                    continue;
+                }
+                self.enter(&payload);
                 bind.insert(id, payload);
+                self.store_priority(id, priority);
             }
-            Ok(())
-        })
+            Ok(dropped)
+        });
+        Self::sleep_injected(delay);
+        res
     }
 
-    pub fn add_frame_payload(&self, frame_id: i64, payload: PipelinePayload) -> anyhow::Result<()> {
-        self.with_payload_mut(|bind| {
+    /// Adds the frame, returns the frame when it is dropped by the hooks or the fault
+    /// injector.
+    ///
+    pub fn add_frame_payload(
+        &self,
+        frame_id: i64,
+        payload: PipelinePayload,
+    ) -> anyhow::Result<Option<PipelinePayload>> {
+        self.add_prioritized_frame_payload(frame_id, payload, 0)
+    }
+
+    pub fn add_prioritized_frame_payload(
+        &self,
+        frame_id: i64,
+        payload: PipelinePayload,
+        priority: i32,
+    ) -> anyhow::Result<Option<PipelinePayload>> {
+        let mut delay = Duration::ZERO;
+        let res = self.with_payload_mut(|bind| {
             if bind.contains_key(&frame_id) {
                 bail!("Frame {} already exists", frame_id)
             }
             match payload {
-                PipelinePayload::Batch(_, _, _, _, _) => {
+                PipelinePayload::Batch(..)
+                | PipelinePayload::Audio(..)
+                | PipelinePayload::Telemetry(..) => {
                     bail!("Payload must be a frame")
                 }
                 PipelinePayload::Frame(f, u, c, last_stage, last_time) => {
                     self.update_processing_stats_for_frame(&f);
                     self.update_latency_stats(last_stage, vec![last_time]);
                     let mut payload =
-                        PipelinePayload::Frame(f, u, c, Some(self.name.clone()), SystemTime::now());
-                    if let Some(ingress_function) = &self.ingress_function {
-                        ingress_function.call(
-                            frame_id,
-                            self,
-                            PipelineStageFunctionOrder::Ingress,
-                            &mut payload,
-                        )?;
+                        PipelinePayload::Frame(f, u, c, Some(self.name.clone()), self.clock.now());
+                    self.ingress_function.call(
+                        frame_id,
+                        self,
+                        PipelineStageFunctionOrder::Ingress,
+                        &mut payload,
+                    )?;
+                    let (action, injected) = self.admit(frame_id, &mut payload);
+                    delay = injected;
+                    if action == HookAction::Drop {
+                        return Ok(Some(payload));
                     }
+                    self.enter(&payload);
                     bind.insert(frame_id, payload);
+                    self.store_priority(frame_id, priority);
                 }
             }
-            Ok(())
-        })
+            Ok(None)
+        });
+        Self::sleep_injected(delay);
+        res
+    }
+
+    /// Adds the batch, returns the batch when it is dropped by the hooks or the fault
+    /// injector.
+    ///
+    pub fn add_batch_payload(
+        &self,
+        batch_id: i64,
+        payload: PipelinePayload,
+    ) -> anyhow::Result<Option<PipelinePayload>> {
+        self.add_prioritized_batch_payload(batch_id, payload, 0)
     }
 
-    pub fn add_batch_payload(&self, batch_id: i64, payload: PipelinePayload) -> anyhow::Result<()> {
-        self.with_payload_mut(|bind| {
+    pub fn add_prioritized_batch_payload(
+        &self,
+        batch_id: i64,
+        payload: PipelinePayload,
+        priority: i32,
+    ) -> anyhow::Result<Option<PipelinePayload>> {
+        let mut delay = Duration::ZERO;
+        let res = self.with_payload_mut(|bind| {
             if bind.contains_key(&batch_id) {
                 bail!("Batch {} already exists", batch_id)
             }
             match payload {
-                PipelinePayload::Frame(_, _, _, _, _) => {
+                PipelinePayload::Frame(..)
+                | PipelinePayload::Audio(..)
+                | PipelinePayload::Telemetry(..) => {
                     bail!("Payload must be a batch")
                 }
                 PipelinePayload::Batch(b, u, c, last_stage, last_times) => {
@@ -253,59 +849,137 @@ impl PipelineStage {
                         u,
                         c,
                         Some(self.name.clone()),
-                        vec![SystemTime::now()],
+                        vec![self.clock.now()],
                     );
-                    if let Some(ingress_function) = &self.ingress_function {
-                        ingress_function.call(
-                            batch_id,
-                            self,
-                            PipelineStageFunctionOrder::Ingress,
-                            &mut payload,
-                        )?;
+                    self.ingress_function.call(
+                        batch_id,
+                        self,
+                        PipelineStageFunctionOrder::Ingress,
+                        &mut payload,
+                    )?;
+                    let (action, injected) = self.admit(batch_id, &mut payload);
+                    delay = injected;
+                    if action == HookAction::Drop {
+                        return Ok(Some(payload));
                     }
+                    self.enter(&payload);
                     bind.insert(batch_id, payload);
+                    self.store_priority(batch_id, priority);
                 }
             }
-            Ok(())
-        })
+            Ok(None)
+        });
+        Self::sleep_injected(delay);
+        res
     }
 
     pub fn delete(&self, id: i64) -> anyhow::Result<Option<PipelinePayload>> {
-        self.with_payload_mut(|bind| {
+        Ok(self.delete_prioritized(id)?.map(|(payload, _)| payload))
+    }
+
+    /// Deletes the payload and returns it with its priority.
+    ///
+    pub fn delete_prioritized(&self, id: i64) -> anyhow::Result<Option<(PipelinePayload, i32)>> {
+        let res = self.with_payload_mut(|bind| {
             let mut res = bind.remove(&id);
-            if let Some(egress_function) = &self.egress_function {
-                if let Some(payload) = res.as_mut() {
-                    egress_function.call(id, self, PipelineStageFunctionOrder::Egress, payload)?;
-                }
-            }
-            if res.is_some() {
+            let mut egress = Ok(());
+            if let Some(payload) = res.as_mut() {
+                self.call_exit_hooks(payload);
+                egress = self.egress_function.call(
+                    id,
+                    self,
+                    PipelineStageFunctionOrder::Egress,
+                    payload,
+                );
+                // the payload is removed, so it leaves the stage even when the egress fails
+                self.leave(payload);
                 let mut stats_bind = self.stat.lock();
                 stats_bind.0.queue_length = bind.len();
             }
-            Ok(res)
-        })
+            let priority = self.priorities.lock().remove(&id).unwrap_or(0);
+            egress?;
+            Ok(res.map(|payload| (payload, priority)))
+        });
+        self.notify_observers();
+        res
     }
 
     pub fn delete_many(&self, ids: &[i64]) -> anyhow::Result<Vec<(i64, PipelinePayload)>> {
-        self.with_payload_mut(|bind| {
+        Ok(self
+            .delete_many_prioritized(ids)?
+            .into_iter()
+            .map(|(id, payload, _)| (id, payload))
+            .collect())
+    }
+
+    /// Deletes the payloads and returns them with their priorities.
+    ///
+    pub fn delete_many_prioritized(
+        &self,
+        ids: &[i64],
+    ) -> anyhow::Result<Vec<(i64, PipelinePayload, i32)>> {
+        let res = self.with_payload_mut(|bind| {
             let mut removed = Vec::with_capacity(ids.len());
             for id in ids {
                 let v = bind.remove(id);
                 if let Some(mut p) = v {
-                    if let Some(egress_function) = &self.egress_function {
-                        egress_function.call(
-                            *id,
-                            self,
-                            PipelineStageFunctionOrder::Egress,
-                            &mut p,
-                        )?;
+                    self.call_exit_hooks(&mut p);
+                    let egress = self.egress_function.call(
+                        *id,
+                        self,
+                        PipelineStageFunctionOrder::Egress,
+                        &mut p,
+                    );
+                    self.leave(&p);
+                    let priority = self.priorities.lock().remove(id).unwrap_or(0);
+                    if let Err(e) = egress {
+                        self.stat.lock().0.queue_length = bind.len();
+                        return Err(e);
                     }
-                    removed.push((*id, p));
+                    removed.push((*id, p, priority));
                 }
             }
             let mut stats_bind = self.stat.lock();
             stats_bind.0.queue_length = bind.len();
             Ok(removed)
+        });
+        self.notify_observers();
+        res
+    }
+
+    /// Up to `max_frames` frames of the payloads in the stage, in no particular order.
+    ///
+    pub fn sample_frames(&self, max_frames: usize) -> Vec<VideoFrameProxy> {
+        self.with_payload(|payloads| {
+            payloads
+                .values()
+                .flat_map(|payload| match payload {
+                    PipelinePayload::Frame(frame, ..) => vec![frame.clone()],
+                    PipelinePayload::Batch(batch, ..) => batch.frames.values().cloned().collect(),
+                    PipelinePayload::Audio(..) | PipelinePayload::Telemetry(..) => vec![],
+                })
+                .take(max_frames)
+                .collect()
+        })
+    }
+
+    /// The frames of the payloads in the stage with their ids, the frames of the batches
+    /// are listed with the ids they have in the batches.
+    ///
+    pub fn get_frames(&self) -> Vec<(i64, VideoFrameProxy)> {
+        self.with_payload(|payloads| {
+            payloads
+                .iter()
+                .flat_map(|(id, payload)| match payload {
+                    PipelinePayload::Frame(frame, ..) => vec![(*id, frame.clone())],
+                    PipelinePayload::Batch(batch, ..) => batch
+                        .frames
+                        .iter()
+                        .map(|(id, frame)| (*id, frame.clone()))
+                        .collect(),
+                    PipelinePayload::Audio(..) | PipelinePayload::Telemetry(..) => vec![],
+                })
+                .collect()
         })
     }
 
@@ -313,10 +987,98 @@ impl PipelineStage {
         self.with_payload(|bind| bind.len())
     }
 
+    pub fn contains(&self, id: i64) -> bool {
+        self.with_payload(|bind| bind.contains_key(&id))
+    }
+
     pub fn is_empty(&self) -> bool {
         self.with_payload(|bind| bind.is_empty())
     }
 
+    /// The time the payload which entered the stage first has entered it.
+    ///
+    pub fn get_oldest_entry(&self) -> Option<SystemTime> {
+        self.with_payload(|bind| {
+            bind.values()
+                .filter_map(|payload| match payload {
+                    PipelinePayload::Frame(_, _, _, _, entered)
+                    | PipelinePayload::Audio(_, _, _, entered)
+                    | PipelinePayload::Telemetry(_, _, _, entered) => Some(*entered),
+                    PipelinePayload::Batch(_, _, _, _, entered) => entered.first().copied(),
+                })
+                .min()
+        })
+    }
+
+    /// The payloads which stay in the stage for the TTL or longer and for how long they
+    /// stay, the oldest first.
+    ///
+    pub fn get_stale_payloads(&self, ttl: Duration) -> Vec<(i64, Duration)> {
+        let mut stale = self.with_payload(|bind| {
+            bind.iter()
+                .filter_map(|(id, payload)| {
+                    let entered = match payload {
+                        PipelinePayload::Frame(_, _, _, _, entered)
+                        | PipelinePayload::Audio(_, _, _, entered)
+                        | PipelinePayload::Telemetry(_, _, _, entered) => Some(*entered),
+                        PipelinePayload::Batch(_, _, _, _, entered) => entered.first().copied(),
+                    }?;
+                    let age = self.clock.elapsed(entered);
+                    (age >= ttl).then_some((*id, age))
+                })
+                .collect::<Vec<_>>()
+        });
+        stale.sort_by(|(a, a_age), (b, b_age)| b_age.cmp(a_age).then(a.cmp(b)));
+        stale
+    }
+
+    fn store_priority(&self, id: i64, priority: i32) {
+        let mut priorities = self.priorities.lock();
+        if priority == 0 {
+            priorities.remove(&id);
+        } else {
+            priorities.insert(id, priority);
+        }
+    }
+
+    /// Changes the priority of the payload in the stage, the payloads with the higher
+    /// priority are processed first. The default priority is 0.
+    ///
+    pub fn set_priority(&self, id: i64, priority: i32) -> anyhow::Result<()> {
+        self.with_payload(|bind| {
+            if !bind.contains_key(&id) {
+                bail!("Payload {} not found in stage", id)
+            }
+            self.store_priority(id, priority);
+            Ok(())
+        })
+    }
+
+    /// The priority of the payload, `None` if the payload is not in the stage.
+    ///
+    pub fn get_priority(&self, id: i64) -> Option<i32> {
+        self.with_payload(|bind| {
+            bind.contains_key(&id)
+                .then(|| self.priorities.lock().get(&id).copied().unwrap_or(0))
+        })
+    }
+
+    /// The ids of the payloads in the order they should be processed in: by the priority,
+    /// the higher first, and then by the id, so the payloads of the same priority keep the
+    /// order they entered the pipeline in.
+    ///
+    pub fn get_queue(&self) -> Vec<i64> {
+        self.with_payload(|bind| {
+            let priorities = self.priorities.lock();
+            let mut queue = bind
+                .keys()
+                .map(|id| (priorities.get(id).copied().unwrap_or(0), *id))
+                .collect::<Vec<_>>();
+            queue.sort_by(|(pa, ia), (pb, ib)| pb.cmp(pa).then(ia.cmp(ib)));
+            queue.into_iter().map(|(_, id)| id).collect()
+        })
+    }
+
     pub fn get_independent_frame(
         &self,
         frame_id: i64,
@@ -327,6 +1089,20 @@ impl PipelineStage {
         })?
     }
 
+    pub fn get_audio_frame(&self, frame_id: i64) -> anyhow::Result<(AudioFrame, Context)> {
+        self.with_payload_item(frame_id, |payload| match payload {
+            PipelinePayload::Audio(frame, ctx, _, _) => Ok((frame.clone(), ctx.clone())),
+            _ => bail!("Payload must be an audio frame"),
+        })?
+    }
+
+    pub fn get_telemetry_frame(&self, frame_id: i64) -> anyhow::Result<(TelemetryFrame, Context)> {
+        self.with_payload_item(frame_id, |payload| match payload {
+            PipelinePayload::Telemetry(frame, ctx, _, _) => Ok((frame.clone(), ctx.clone())),
+            _ => bail!("Payload must be a telemetry frame"),
+        })?
+    }
+
     pub fn get_batched_frame(
         &self,
         batch_id: i64,
@@ -360,7 +1136,25 @@ impl PipelineStage {
     }
 
     pub fn apply_updates(&self, id: i64) -> anyhow::Result<()> {
+        self.apply_checked_updates(id, |_| Ok(()))
+    }
+
+    /// Applies the updates when all of them pass the check, otherwise nothing is applied.
+    ///
+    pub fn apply_checked_updates<F>(&self, id: i64, check: F) -> anyhow::Result<()>
+    where
+        F: Fn(&VideoFrameUpdate) -> anyhow::Result<()>,
+    {
         self.with_payload_item_mut(id, |payload| {
+            match payload {
+                PipelinePayload::Frame(_, updates, _, _, _) => {
+                    updates.iter().try_for_each(&check)?;
+                }
+                PipelinePayload::Batch(_, updates, _, _, _) => {
+                    updates.iter().try_for_each(|(_, update)| check(update))?;
+                }
+                PipelinePayload::Audio(..) | PipelinePayload::Telemetry(..) => (),
+            }
             match payload {
                 PipelinePayload::Frame(frame, updates, ctx, _, _) => {
                     let _span =
@@ -382,6 +1176,7 @@ impl PipelineStage {
                         }
                     }
                 }
+                PipelinePayload::Audio(..) | PipelinePayload::Telemetry(..) => (),
             }
             Ok(())
         })?
@@ -410,6 +1205,7 @@ impl PipelineStage {
                     updates.clear();
                     contexts.iter().for_each(|cx| cx.span().end());
                 }
+                PipelinePayload::Audio(..) | PipelinePayload::Telemetry(..) => (),
             }
             Ok(())
         })?
@@ -437,6 +1233,7 @@ impl PipelineStage {
                 contexts.into_iter().for_each(|ctx| ctx.span().end());
                 res
             }
+            PipelinePayload::Audio(..) | PipelinePayload::Telemetry(..) => Ok(HashMap::new()),
         })?
     }
     fn update_latency_stats(&self, last_stage: Option<String>, last_times: Vec<SystemTime>) {
@@ -445,7 +1242,7 @@ impl PipelineStage {
             for lt in last_times {
                 stat_bind
                     .1
-                    .record_latency(last_stage.clone(), lt.elapsed().unwrap_or_default());
+                    .record_latency(last_stage.clone(), self.clock.elapsed(lt));
             }
         }
     }
@@ -731,6 +1528,42 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_priorities() -> Result<()> {
+        let stage = get_frame_stage();
+        let payload = || {
+            PipelinePayload::Frame(
+                gen_frame(),
+                Vec::default(),
+                Context::default(),
+                None,
+                SystemTime::now(),
+            )
+        };
+        stage.add_frame_payload(1, payload())?;
+        stage.add_prioritized_frame_payload(2, payload(), 10)?;
+        stage.add_prioritized_payloads(vec![(3, payload(), 10), (4, payload(), -1)])?;
+        assert_eq!(stage.get_queue(), vec![2, 3, 1, 4]);
+        assert_eq!(stage.get_priority(2), Some(10));
+        assert_eq!(stage.get_priority(5), None);
+
+        stage.set_priority(1, 20)?;
+        assert!(stage.set_priority(5, 20).is_err());
+        assert_eq!(stage.get_queue(), vec![1, 2, 3, 4]);
+
+        let removed = stage.delete_many_prioritized(&[1, 4])?;
+        assert_eq!(
+            removed
+                .iter()
+                .map(|(id, _, p)| (*id, *p))
+                .collect::<Vec<_>>(),
+            vec![(1, 20), (4, -1)]
+        );
+        assert!(matches!(stage.delete_prioritized(3)?, Some((_, 10))));
+        assert_eq!(stage.get_queue(), vec![2]);
+        Ok(())
+    }
+
     #[test]
     fn test_len() -> Result<()> {
         let stage = get_frame_stage();
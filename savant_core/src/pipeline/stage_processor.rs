@@ -0,0 +1,106 @@
+use std::fmt::{Debug, Formatter};
+use std::time::SystemTime;
+
+use crate::pipeline::readiness::StageReadiness;
+use crate::pipeline::stage::PipelineStage;
+use crate::pipeline::{PipelinePayload, PipelineStageFunction, PipelineStageFunctionOrder};
+use crate::rwlock::SavantRwLock;
+
+/// The version of the ingress or egress function of a stage. The function the stage was
+/// created with has version 0, every replacement increments the version.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageProcessorVersion {
+    pub version: u64,
+    /// Whether the stage has the function, replacing it with `None` removes it.
+    pub installed: bool,
+    /// The time of the last replacement, `None` for the initial function.
+    pub replaced_at: Option<SystemTime>,
+}
+
+struct Slot {
+    function: Option<Box<dyn PipelineStageFunction>>,
+    version: StageProcessorVersion,
+}
+
+/// The slot of a stage function which can be replaced while the pipeline runs. The calls hold
+/// the slot for reading, so the replacement waits for the in-flight calls to complete and the
+/// payloads are processed either by the old or by the new function, never by both. The lock is
+/// fair: a waiting replacement blocks the new calls, so a steady stream of calls cannot starve
+/// it. The queries of the version and the readiness do not wait for a pending replacement, so
+/// a function may query its own stage.
+///
+pub struct StageProcessor(SavantRwLock<Slot>);
+
+impl Debug for StageProcessor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("StageProcessor")
+            .field(&self.get_version())
+            .finish()
+    }
+}
+
+impl StageProcessor {
+    pub fn new(function: Option<Box<dyn PipelineStageFunction>>) -> Self {
+        let version = StageProcessorVersion {
+            version: 0,
+            installed: function.is_some(),
+            replaced_at: None,
+        };
+        Self(SavantRwLock::new(Slot { function, version }))
+    }
+
+    pub fn is_installed(&self) -> bool {
+        self.0.read_recursive().function.is_some()
+    }
+
+    pub fn get_version(&self) -> StageProcessorVersion {
+        self.0.read_recursive().version.clone()
+    }
+
+    pub fn get_readiness(&self) -> StageReadiness {
+        match &self.0.read_recursive().function {
+            Some(function) => function.get_readiness(),
+            None => StageReadiness::Ready,
+        }
+    }
+
+    /// Calls the function if the stage has one. The function may call the pipeline again,
+    /// but must neither replace the functions of its own stage nor pass payloads through it,
+    /// the nested call would wait for a pending replacement forever.
+    ///
+    pub fn call(
+        &self,
+        id: i64,
+        stage: &PipelineStage,
+        order: PipelineStageFunctionOrder,
+        payload: &mut PipelinePayload,
+    ) -> anyhow::Result<()> {
+        let slot = self.0.read();
+        match &slot.function {
+            Some(function) => function.call(id, stage, order, payload),
+            None => Ok(()),
+        }
+    }
+
+    /// Waits for the in-flight calls, swaps the function and returns the previous one with
+    /// the new version.
+    ///
+    pub fn replace(
+        &self,
+        function: Option<Box<dyn PipelineStageFunction>>,
+    ) -> (
+        Option<Box<dyn PipelineStageFunction>>,
+        StageProcessorVersion,
+    ) {
+        let mut slot = self.0.write();
+        let version = StageProcessorVersion {
+            version: slot.version.version + 1,
+            installed: function.is_some(),
+            replaced_at: Some(SystemTime::now()),
+        };
+        slot.version = version.clone();
+        let previous = std::mem::replace(&mut slot.function, function);
+        (previous, version)
+    }
+}
@@ -6,6 +6,8 @@ use hashbrown::HashMap;
 use log::info;
 use parking_lot::{Mutex, MutexGuard};
 
+use crate::clock::Clock;
+
 #[cfg(test)]
 #[derive(Default, Debug)]
 struct TimeCounter {
@@ -14,6 +16,10 @@ struct TimeCounter {
 
 #[cfg(test)]
 impl TimeCounter {
+    fn new(_clock: Arc<Clock>) -> Self {
+        Self::default()
+    }
+
     pub fn update_time(&mut self, time: i64) {
         self.current_time = time;
     }
@@ -24,16 +30,24 @@ impl TimeCounter {
 }
 
 #[cfg(not(test))]
-#[derive(Default, Debug)]
-struct TimeCounter;
+#[derive(Debug)]
+struct TimeCounter(Arc<Clock>);
+
+#[cfg(not(test))]
+impl Default for TimeCounter {
+    fn default() -> Self {
+        Self(crate::clock::process_clock())
+    }
+}
 
 #[cfg(not(test))]
 impl TimeCounter {
+    fn new(clock: Arc<Clock>) -> Self {
+        Self(clock)
+    }
+
     pub fn get_current_time(&self) -> i64 {
-        std::time::SystemTime::now()
-            .duration_since(std::time::SystemTime::UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as i64
+        self.0.now_ms() as i64
     }
 }
 
@@ -51,6 +65,30 @@ pub struct StageProcessingStat {
     pub frame_counter: usize,
     pub object_counter: usize,
     pub batch_counter: usize,
+    /// The number of payloads which spent more than the stage budget in the stage.
+    pub budget_overrun_counter: usize,
+    /// The time spent in user code processing the payloads of the stage, see
+    /// [`crate::pipeline::user_code::UserCodeSection`].
+    pub user_code_time: ExecutionTimeStat,
+    /// The time spent in the calls into the library made by the user code.
+    pub rust_overhead_time: ExecutionTimeStat,
+    /// The time the user code waited for the GIL held by other threads.
+    pub gil_wait_time: ExecutionTimeStat,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ExecutionTimeStat {
+    pub calls: usize,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+impl ExecutionTimeStat {
+    pub fn record(&mut self, elapsed: Duration) {
+        self.calls += 1;
+        self.total += elapsed;
+        self.max = std::cmp::max(self.max, elapsed);
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -122,12 +160,13 @@ impl StageProcessingStat {
 
     pub fn log_stats(&self) {
         info!(
-            "📊 {:<32} > queue {:>8}, frames {:>8}, objects {:>8}, batches {:>8}",
+            "📊 {:<32} > queue {:>8}, frames {:>8}, objects {:>8}, batches {:>8}, overruns {:>8}",
             self.stage_name,
             self.queue_length,
             self.frame_counter,
             self.object_counter,
             self.batch_counter,
+            self.budget_overrun_counter,
         );
     }
 }
@@ -312,7 +351,7 @@ pub struct Stats {
 
 impl Default for Stats {
     fn default() -> Self {
-        Stats::new(100, Some(1000), Some(1000))
+        Stats::new(100, Some(1000), Some(1000), crate::clock::process_clock())
     }
 }
 
@@ -367,11 +406,12 @@ impl Stats {
         stats_history: usize,
         frame_period: Option<i64>,
         timestamp_period: Option<i64>,
+        clock: Arc<Clock>,
     ) -> Self {
-        let generator = Arc::new(Mutex::new(StatsGenerator::new(
-            frame_period,
-            timestamp_period,
-        )));
+        let generator = Arc::new(Mutex::new(StatsGenerator {
+            time_counter: TimeCounter::new(clock),
+            ..StatsGenerator::new(frame_period, timestamp_period)
+        }));
         let collector = Arc::new(Mutex::new(StatsCollector::new(stats_history)));
         let shutdown = Arc::new(OnceLock::new());
         let stage_stats = Arc::new(Mutex::new(Vec::new()));
@@ -0,0 +1,302 @@
+use crate::pipeline::{Pipeline, PipelineStagePayloadType};
+use anyhow::{bail, Result};
+use derive_builder::Builder;
+use hashbrown::HashMap;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+
+#[derive(Builder, Debug, Clone)]
+pub struct SynchronizerConfiguration {
+    /// The source ids of the streams to align, every bundle contains one frame per stream.
+    pub streams: Vec<String>,
+    /// The maximum difference between the timestamps of the frames of one bundle.
+    #[builder(default = "20_000_000")]
+    pub tolerance_ns: i64,
+    /// The maximum number of frames waiting for a match per stream, the oldest frame is
+    /// dropped when the limit is exceeded.
+    #[builder(default = "64")]
+    pub max_pending: usize,
+}
+
+/// The result of pushing a frame to the synchronizer.
+///
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SynchronizerOutput {
+    /// The ids of the batches packed from the aligned frames.
+    pub batches: Vec<i64>,
+    /// The ids of the frames which cannot be aligned anymore, they are removed from the
+    /// pipeline.
+    pub dropped: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PendingFrame {
+    id: i64,
+    ts_ns: i64,
+}
+
+/// Aligns frames of several streams by timestamp. The frames are added to a frame stage as
+/// usual and pushed to the synchronizer; when every stream has a frame within the tolerance,
+/// the frames are packed into a batch in the destination stage. Frames of every stream must
+/// arrive in the timestamp order.
+///
+#[derive(Debug)]
+pub struct StreamSynchronizer {
+    pipeline: Pipeline,
+    dest_stage: String,
+    configuration: SynchronizerConfiguration,
+    pending: Mutex<HashMap<String, VecDeque<PendingFrame>>>,
+}
+
+fn timestamp_ns(pts: i64, time_base: (i32, i32)) -> i64 {
+    if time_base.1 == 0 {
+        return pts;
+    }
+    (pts as i128 * time_base.0 as i128 * 1_000_000_000 / time_base.1 as i128) as i64
+}
+
+impl StreamSynchronizer {
+    pub fn new(
+        pipeline: Pipeline,
+        dest_stage: &str,
+        configuration: SynchronizerConfiguration,
+    ) -> Result<Self> {
+        if pipeline.get_stage_type(dest_stage)? != PipelineStagePayloadType::Batch {
+            bail!(
+                "Synchronizer destination stage {} must be a batch stage",
+                dest_stage
+            )
+        }
+        if configuration.streams.len() < 2 {
+            bail!("Synchronizer requires at least two streams")
+        }
+        if configuration.tolerance_ns < 0 {
+            bail!("Synchronizer tolerance must be non-negative")
+        }
+        let pending = configuration
+            .streams
+            .iter()
+            .map(|s| {
+                (
+                    s.clone(),
+                    VecDeque::with_capacity(configuration.max_pending),
+                )
+            })
+            .collect();
+        Ok(Self {
+            pipeline,
+            dest_stage: dest_stage.to_string(),
+            configuration,
+            pending: Mutex::new(pending),
+        })
+    }
+
+    pub fn get_configuration(&self) -> &SynchronizerConfiguration {
+        &self.configuration
+    }
+
+    pub fn get_pending_len(&self, stream: &str) -> usize {
+        self.pending
+            .lock()
+            .get(stream)
+            .map(|q| q.len())
+            .unwrap_or(0)
+    }
+
+    /// Registers the frame already added to the pipeline and emits the bundles which became
+    /// complete. A frame already waiting for a match is rejected. When a bundle fails to be
+    /// packed, the pending frames are restored except the frames of the batches packed before
+    /// the failure.
+    ///
+    pub fn push(&self, frame_id: i64) -> Result<SynchronizerOutput> {
+        let (frame, _) = self.pipeline.get_independent_frame(frame_id)?;
+        let source_id = frame.get_source_id();
+        let ts_ns = timestamp_ns(frame.get_pts(), frame.get_time_base());
+
+        let mut output = SynchronizerOutput::default();
+        let mut pending = self.pending.lock();
+        if pending.values().flatten().any(|f| f.id == frame_id) {
+            bail!("Frame {} is already pushed to the synchronizer", frame_id)
+        }
+        let Some(queue) = pending.get_mut(&source_id) else {
+            bail!("Stream {} is not synchronized", source_id)
+        };
+        if let Some(last) = queue.back() {
+            if last.ts_ns > ts_ns {
+                bail!(
+                    "Frame {} of stream {} is out of order: {} < {}",
+                    frame_id,
+                    source_id,
+                    ts_ns,
+                    last.ts_ns
+                )
+            }
+        }
+        let snapshot = pending.clone();
+        let queue = pending.get_mut(&source_id).unwrap();
+        queue.push_back(PendingFrame {
+            id: frame_id,
+            ts_ns,
+        });
+        if queue.len() > self.configuration.max_pending {
+            output.dropped.extend(queue.pop_front().map(|f| f.id));
+        }
+
+        let mut packed = Vec::new();
+        loop {
+            if pending.values().any(|q| q.is_empty()) {
+                break;
+            }
+            let newest = pending
+                .values()
+                .map(|q| q.front().unwrap().ts_ns)
+                .max()
+                .unwrap();
+            let mut stale = false;
+            for queue in pending.values_mut() {
+                while let Some(f) = queue.front() {
+                    if f.ts_ns < newest - self.configuration.tolerance_ns {
+                        output.dropped.push(f.id);
+                        queue.pop_front();
+                        stale = true;
+                    } else {
+                        break;
+                    }
+                }
+            }
+            if stale {
+                continue;
+            }
+            let bundle = self
+                .configuration
+                .streams
+                .iter()
+                .map(|s| pending.get_mut(s).unwrap().pop_front().unwrap().id)
+                .collect::<Vec<_>>();
+            match self
+                .pipeline
+                .move_and_pack_frames(&self.dest_stage, bundle.clone())
+            {
+                Ok(batch_id) => {
+                    output.batches.push(batch_id);
+                    packed.extend(bundle);
+                }
+                Err(e) => {
+                    *pending = snapshot;
+                    for queue in pending.values_mut() {
+                        queue.retain(|f| !packed.contains(&f.id));
+                    }
+                    return Err(e.context(format!(
+                        "Failed to pack the bundle, the batches {:?} are packed",
+                        output.batches
+                    )));
+                }
+            }
+        }
+        drop(pending);
+
+        for id in &output.dropped {
+            self.pipeline.delete(*id)?;
+        }
+        Ok(output)
+    }
+
+    /// Removes all frames waiting for a match from the pipeline, e.g. when a stream ends.
+    ///
+    pub fn flush(&self) -> Result<Vec<i64>> {
+        let dropped = self
+            .pending
+            .lock()
+            .values_mut()
+            .flat_map(|q| q.drain(..).map(|f| f.id))
+            .collect::<Vec<_>>();
+        for id in &dropped {
+            self.pipeline.delete(*id)?;
+        }
+        Ok(dropped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::synchronizer::{
+        StreamSynchronizer, SynchronizerConfigurationBuilder, SynchronizerOutput,
+    };
+    use crate::pipeline::{Pipeline, PipelineConfiguration, PipelineStagePayloadType};
+    use crate::test::gen_frame;
+
+    fn frame(source_id: &str, pts_ms: i64) -> crate::primitives::frame::VideoFrameProxy {
+        let mut f = gen_frame();
+        f.set_source_id(source_id);
+        f.set_time_base((1, 1000));
+        f.set_pts(pts_ms);
+        f
+    }
+
+    #[test]
+    fn test_alignment() -> anyhow::Result<()> {
+        let pipeline = Pipeline::new(
+            vec![
+                (
+                    "input".to_string(),
+                    PipelineStagePayloadType::Frame,
+                    None,
+                    None,
+                ),
+                (
+                    "aligned".to_string(),
+                    PipelineStagePayloadType::Batch,
+                    None,
+                    None,
+                ),
+            ],
+            PipelineConfiguration::default(),
+        )?;
+        let sync = StreamSynchronizer::new(
+            pipeline.clone(),
+            "aligned",
+            SynchronizerConfigurationBuilder::default()
+                .streams(vec!["visual".to_string(), "thermal".to_string()])
+                .tolerance_ns(10_000_000)
+                .build()?,
+        )?;
+        assert!(StreamSynchronizer::new(
+            pipeline.clone(),
+            "input",
+            sync.get_configuration().clone()
+        )
+        .is_err());
+
+        let v0 = pipeline.add_frame("input", frame("visual", 0))?;
+        assert_eq!(sync.push(v0)?, SynchronizerOutput::default());
+        let v40 = pipeline.add_frame("input", frame("visual", 40))?;
+        sync.push(v40)?;
+        // the thermal frame matches the second visual frame, the first one is dropped
+        let t35 = pipeline.add_frame("input", frame("thermal", 35))?;
+        let out = sync.push(t35)?;
+        assert_eq!(out.dropped, vec![v0]);
+        assert_eq!(out.batches.len(), 1);
+        let (batch, _) = pipeline.get_batch(out.batches[0])?;
+        assert_eq!(batch.frames().len(), 2);
+        assert_eq!(pipeline.get_stage_queue_len("input")?, 0);
+
+        let t80 = pipeline.add_frame("input", frame("thermal", 80))?;
+        sync.push(t80)?;
+        assert!(sync.push(t80).is_err());
+        assert_eq!(sync.get_pending_len("thermal"), 1);
+        assert_eq!(sync.flush()?, vec![t80]);
+        assert_eq!(pipeline.get_stage_queue_len("input")?, 0);
+
+        // the thermal frame is removed behind the synchronizer, the bundle fails to be
+        // packed and the pending frames are restored
+        let t120 = pipeline.add_frame("input", frame("thermal", 120))?;
+        sync.push(t120)?;
+        pipeline.delete(t120)?;
+        let v125 = pipeline.add_frame("input", frame("visual", 125))?;
+        assert!(sync.push(v125).is_err());
+        assert_eq!(sync.get_pending_len("thermal"), 1);
+        assert_eq!(sync.get_pending_len("visual"), 0);
+        assert_eq!(pipeline.get_stage_queue_len("input")?, 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,251 @@
+use crate::pipeline::PipelineStagePayloadType;
+use anyhow::bail;
+use std::fmt::Write;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TopologyFormat {
+    Dot,
+    Mermaid,
+}
+
+impl FromStr for TopologyFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "dot" => Ok(TopologyFormat::Dot),
+            "mermaid" => Ok(TopologyFormat::Mermaid),
+            _ => bail!("Unknown topology format {}, expected dot or mermaid", s),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StageTopology {
+    pub name: String,
+    pub stage_type: PipelineStagePayloadType,
+    pub has_ingress_function: bool,
+    pub has_egress_function: bool,
+    pub queue_length: usize,
+}
+
+impl StageTopology {
+    fn label(&self) -> String {
+        let mut label = format!(
+            "{} [{}]\\nqueue: {}",
+            self.name,
+            match self.stage_type {
+                PipelineStagePayloadType::Frame => "frame",
+                PipelineStagePayloadType::Batch => "batch",
+                PipelineStagePayloadType::Audio => "audio",
+                PipelineStagePayloadType::Telemetry => "telemetry",
+            },
+            self.queue_length
+        );
+        let functions = [
+            (self.has_ingress_function, "ingress"),
+            (self.has_egress_function, "egress"),
+        ]
+        .into_iter()
+        .filter_map(|(present, name)| present.then_some(name))
+        .collect::<Vec<_>>();
+        if !functions.is_empty() {
+            label.push_str(&format!("\\nfunctions: {}", functions.join(", ")));
+        }
+        label
+    }
+}
+
+/// A snapshot of the pipeline stages. Payloads move only forward, so a stage can pass
+/// payloads to any stage after it: between stages of the same type payloads are moved as is,
+/// frames are packed into batches and batches are unpacked into frames, audio and telemetry
+/// frames only move between stages of their own type. The transitions to the next stage are rendered with solid edges, the transitions skipping
+/// stages with dashed edges. A stage with the declared edges passes payloads only along
+/// them and a stage the declared edges lead to receives payloads only along them, the edges
+/// are labelled with their names.
+///
+#[derive(Debug, Clone)]
+pub struct PipelineTopology {
+    pub name: Option<String>,
+    pub stages: Vec<StageTopology>,
+    /// The declared edges as the source stage index, the edge name and the destination stage
+    /// index.
+    pub edges: Vec<(usize, String, usize)>,
+}
+
+pub(crate) fn transition(
+    from: &PipelineStagePayloadType,
+    to: &PipelineStagePayloadType,
+) -> Option<&'static str> {
+    match (from, to) {
+        (PipelineStagePayloadType::Frame, PipelineStagePayloadType::Batch) => Some("pack"),
+        (PipelineStagePayloadType::Batch, PipelineStagePayloadType::Frame) => Some("unpack"),
+        (a, b) if a == b => Some("as is"),
+        _ => None,
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('"', "\\\"")
+}
+
+impl PipelineTopology {
+    fn title(&self, index: usize) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("pipeline {}", index))
+    }
+
+    fn transitions(&self) -> Vec<(usize, usize, String)> {
+        let stages = &self.stages;
+        let mut transitions = Vec::new();
+        for from in 0..stages.len() {
+            let edges = self
+                .edges
+                .iter()
+                .filter(|(f, _, _)| *f == from)
+                .collect::<Vec<_>>();
+            for to in from + 1..stages.len() {
+                let Some(op) = transition(&stages[from].stage_type, &stages[to].stage_type) else {
+                    continue;
+                };
+                if edges.is_empty() {
+                    transitions.push((from, to, op.to_string()));
+                } else {
+                    transitions.extend(
+                        edges
+                            .iter()
+                            .filter(|(_, _, t)| *t == to)
+                            .map(|(_, name, _)| (from, to, format!("{}: {}", name, op))),
+                    );
+                }
+            }
+        }
+        transitions
+    }
+
+    fn write_dot(&self, index: usize, out: &mut String) {
+        let _ = writeln!(out, "  subgraph cluster_p{} {{", index);
+        let _ = writeln!(out, "    label=\"{}\";", escape(&self.title(index)));
+        for (i, stage) in self.stages.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "    p{}_s{} [shape={}, label=\"{}\"];",
+                index,
+                i,
+                match stage.stage_type {
+                    PipelineStagePayloadType::Frame => "box",
+                    PipelineStagePayloadType::Batch => "box3d",
+                    PipelineStagePayloadType::Audio => "ellipse",
+                    PipelineStagePayloadType::Telemetry => "hexagon",
+                },
+                escape(&stage.label())
+            );
+        }
+        for (from, to, op) in self.transitions() {
+            let _ = writeln!(
+                out,
+                "    p{}_s{} -> p{}_s{} [label=\"{}\"{}];",
+                index,
+                from,
+                index,
+                to,
+                op,
+                if to == from + 1 { "" } else { ", style=dashed" }
+            );
+        }
+        let _ = writeln!(out, "  }}");
+    }
+
+    fn write_mermaid(&self, index: usize, out: &mut String) {
+        let _ = writeln!(
+            out,
+            "  subgraph p{}[\"{}\"]",
+            index,
+            self.title(index).replace('"', "#quot;")
+        );
+        for (i, stage) in self.stages.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "    p{}_s{}[\"{}\"]",
+                index,
+                i,
+                stage.label().replace('"', "#quot;").replace("\\n", "<br/>")
+            );
+        }
+        for (from, to, op) in self.transitions() {
+            let _ = writeln!(
+                out,
+                "    p{}_s{} {}|{}| p{}_s{}",
+                index,
+                from,
+                if to == from + 1 { "-->" } else { "-.->" },
+                op,
+                index,
+                to
+            );
+        }
+        let _ = writeln!(out, "  end");
+    }
+}
+
+/// Renders the pipelines as a single graph with one cluster per pipeline.
+///
+pub fn render_topology(pipelines: &[PipelineTopology], format: TopologyFormat) -> String {
+    let mut out = String::new();
+    match format {
+        TopologyFormat::Dot => {
+            out.push_str("digraph pipelines {\n  rankdir=LR;\n");
+            for (i, p) in pipelines.iter().enumerate() {
+                p.write_dot(i, &mut out);
+            }
+            out.push_str("}\n");
+        }
+        TopologyFormat::Mermaid => {
+            out.push_str("flowchart LR\n");
+            for (i, p) in pipelines.iter().enumerate() {
+                p.write_mermaid(i, &mut out);
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::implementation::create_test_pipeline;
+    use crate::pipeline::topology::{render_topology, TopologyFormat};
+    use crate::test::gen_frame;
+
+    #[test]
+    fn test_export_topology() -> anyhow::Result<()> {
+        let pipeline = create_test_pipeline()?;
+        pipeline.add_frame("input", gen_frame())?;
+        let topology = pipeline.get_topology();
+        assert_eq!(topology.stages.len(), 4);
+        assert_eq!(topology.stages[0].queue_length, 1);
+
+        let dot = render_topology(&[topology.clone()], TopologyFormat::Dot);
+        assert!(dot.starts_with("digraph"));
+        assert!(dot.contains("input [frame]\\nqueue: 1"));
+        assert!(dot.contains("p0_s0 -> p0_s1 [label=\"pack\"]"));
+        assert!(dot.contains("p0_s0 -> p0_s3 [label=\"as is\", style=dashed]"));
+
+        let mermaid = render_topology(&[topology], TopologyFormat::Mermaid);
+        assert!(mermaid.starts_with("flowchart LR"));
+        assert!(mermaid.contains("p0_s2 -->|unpack| p0_s3"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_edges() -> anyhow::Result<()> {
+        let mut topology = create_test_pipeline()?.get_topology();
+        topology.edges = vec![(0, "people".to_string(), 3)];
+        let dot = render_topology(&[topology], TopologyFormat::Dot);
+        assert!(dot.contains("p0_s0 -> p0_s3 [label=\"people: as is\", style=dashed]"));
+        assert!(!dot.contains("p0_s0 -> p0_s1"));
+        assert!(dot.contains("p0_s1 -> p0_s2 [label=\"as is\"]"));
+        Ok(())
+    }
+}
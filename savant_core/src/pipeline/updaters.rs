@@ -0,0 +1,138 @@
+use crate::primitives::frame_update::VideoFrameUpdate;
+use crate::primitives::Attribute;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum UpdaterScopeError {
+    #[error("Updater '{0}' is not registered in the pipeline")]
+    Unregistered(String),
+    #[error("The update has no updater, but the pipeline limits the updaters to their scopes")]
+    Anonymous,
+    #[error("Updater '{updater}' is not allowed to write the attribute '{namespace}/{name}' of {target}, allowed namespaces: {allowed:?}")]
+    OutOfScope {
+        updater: String,
+        namespace: String,
+        name: String,
+        /// `the frame` or the object the attribute is written to.
+        target: String,
+        allowed: Vec<String>,
+    },
+}
+
+/// The attribute namespaces an updater is allowed to write, as glob patterns. Updates
+/// produced by the updater (see [`VideoFrameUpdate::set_updater`]) are rejected when they
+/// carry attributes of other namespaces. Once an updater is registered in a pipeline, the
+/// updates without an updater are rejected as well.
+///
+#[derive(Debug, Clone)]
+pub struct UpdaterScope {
+    name: String,
+    namespaces: Vec<String>,
+    matcher: GlobSet,
+}
+
+impl UpdaterScope {
+    pub fn new(name: &str, namespaces: &[&str]) -> anyhow::Result<Self> {
+        let mut builder = GlobSetBuilder::new();
+        for ns in namespaces {
+            builder.add(Glob::new(ns)?);
+        }
+        Ok(Self {
+            name: name.to_string(),
+            namespaces: namespaces.iter().map(|ns| ns.to_string()).collect(),
+            matcher: builder.build()?,
+        })
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_namespaces(&self) -> &[String] {
+        &self.namespaces
+    }
+
+    pub fn allows(&self, namespace: &str) -> bool {
+        self.matcher.is_match(namespace)
+    }
+
+    fn check_attribute(
+        &self,
+        attribute: &Attribute,
+        target: &str,
+    ) -> Result<(), UpdaterScopeError> {
+        if self.allows(&attribute.namespace) {
+            Ok(())
+        } else {
+            Err(UpdaterScopeError::OutOfScope {
+                updater: self.name.clone(),
+                namespace: attribute.namespace.clone(),
+                name: attribute.name.clone(),
+                target: target.to_string(),
+                allowed: self.namespaces.clone(),
+            })
+        }
+    }
+
+    /// Checks the frame attributes, the object attributes and the attributes of the new
+    /// objects carried by the update.
+    ///
+    pub fn check(&self, update: &VideoFrameUpdate) -> Result<(), UpdaterScopeError> {
+        for attribute in update.get_frame_attributes() {
+            self.check_attribute(attribute, "the frame")?;
+        }
+        for (id, attribute) in update.get_object_attributes() {
+            self.check_attribute(attribute, &format!("the object {}", id))?;
+        }
+        for (object, _) in update.get_objects() {
+            for attribute in &object.attributes {
+                self.check_attribute(
+                    attribute,
+                    &format!("the new object {}/{}", object.namespace, object.label),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::updaters::{UpdaterScope, UpdaterScopeError};
+    use crate::primitives::frame_update::VideoFrameUpdate;
+    use crate::primitives::Attribute;
+
+    #[test]
+    fn test_scope() -> anyhow::Result<()> {
+        let scope = UpdaterScope::new("lpr", &["lpr", "lpr.*"])?;
+        assert!(scope.allows("lpr.ocr"));
+        assert!(!scope.allows("tracker"));
+
+        let mut update = VideoFrameUpdate::default();
+        update.add_frame_attribute(Attribute::persistent("lpr", "plates", vec![], &None, false));
+        update.add_object_attribute(
+            1,
+            Attribute::persistent("lpr.ocr", "text", vec![], &None, false),
+        );
+        scope.check(&update)?;
+
+        update.add_object_attribute(
+            2,
+            Attribute::persistent("tracker", "id", vec![], &None, false),
+        );
+        match scope.check(&update) {
+            Err(UpdaterScopeError::OutOfScope {
+                updater,
+                namespace,
+                target,
+                ..
+            }) => {
+                assert_eq!(updater, "lpr");
+                assert_eq!(namespace, "tracker");
+                assert_eq!(target, "the object 2");
+            }
+            r => panic!("Unexpected result: {:?}", r),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+use crate::pipeline::Pipeline;
+
+thread_local! {
+    static RUST_TIME: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+    static GIL_WAIT_TIME: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+}
+
+/// Adds the time the calling thread spent in Rust on behalf of user code. The Python bindings
+/// call it around the calls into the library, so the sections of user code exclude it.
+///
+pub fn account_rust_time(elapsed: Duration) {
+    RUST_TIME.with(|t| t.set(t.get().saturating_add(elapsed)));
+}
+
+/// The total time the calling thread spent in Rust, see [`account_rust_time`].
+///
+pub fn get_rust_time() -> Duration {
+    RUST_TIME.with(|t| t.get())
+}
+
+/// Adds the time the calling thread waited for the GIL held by other threads. The Python
+/// bindings call it when they take the GIL back after a call which released it.
+///
+pub fn account_gil_wait_time(elapsed: Duration) {
+    GIL_WAIT_TIME.with(|t| t.set(t.get().saturating_add(elapsed)));
+}
+
+/// The total time the calling thread waited for the GIL, see [`account_gil_wait_time`].
+///
+pub fn get_gil_wait_time() -> Duration {
+    GIL_WAIT_TIME.with(|t| t.get())
+}
+
+/// The time of a section of user code split into the time spent in the calls into the
+/// library, the time spent waiting for the GIL and the rest.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct UserCodeTime {
+    pub total: Duration,
+    pub rust: Duration,
+    pub gil_wait: Duration,
+}
+
+impl UserCodeTime {
+    pub fn user(&self) -> Duration {
+        self.total
+            .saturating_sub(self.rust)
+            .saturating_sub(self.gil_wait)
+    }
+}
+
+/// Measures a callback of user code processing the payloads of a stage, e.g. a Python
+/// function handling the frames. The calls into the library made by the callback on the
+/// same thread are counted as Rust overhead and the time the thread waits to take the GIL
+/// back from other threads is counted separately, the work done by the threads the callback
+/// starts is counted as user code.
+///
+/// The time is aggregated per stage in
+/// [`crate::pipeline::stats::StageProcessingStat::user_code_time`],
+/// [`crate::pipeline::stats::StageProcessingStat::rust_overhead_time`] and
+/// [`crate::pipeline::stats::StageProcessingStat::gil_wait_time`], and added as the
+/// `user-code` event to the spans of the payloads, so the memory used does not grow with the
+/// number of calls.
+///
+#[derive(Debug)]
+pub struct UserCodeSection {
+    stage_name: String,
+    ids: Vec<i64>,
+    started: Instant,
+    rust_time_at_start: Duration,
+    gil_wait_time_at_start: Duration,
+}
+
+impl UserCodeSection {
+    /// Starts the section for the payloads of the stage, the ids are the frame or batch ids
+    /// of the payloads processed by the callback.
+    ///
+    pub fn start(stage_name: &str, ids: Vec<i64>) -> Self {
+        Self {
+            stage_name: stage_name.to_string(),
+            ids,
+            started: Instant::now(),
+            rust_time_at_start: get_rust_time(),
+            gil_wait_time_at_start: get_gil_wait_time(),
+        }
+    }
+
+    pub fn get_stage_name(&self) -> &str {
+        &self.stage_name
+    }
+
+    pub fn elapsed(&self) -> UserCodeTime {
+        UserCodeTime {
+            total: self.started.elapsed(),
+            rust: get_rust_time().saturating_sub(self.rust_time_at_start),
+            gil_wait: get_gil_wait_time().saturating_sub(self.gil_wait_time_at_start),
+        }
+    }
+
+    /// Ends the section and records the time in the stage of the pipeline.
+    ///
+    pub fn finish(self, pipeline: &Pipeline) -> anyhow::Result<UserCodeTime> {
+        let time = self.elapsed();
+        pipeline.record_user_code_time(&self.stage_name, &self.ids, time)?;
+        Ok(time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::pipeline::user_code::{account_gil_wait_time, account_rust_time, UserCodeSection};
+    use crate::pipeline::{Pipeline, PipelineConfiguration, PipelineStagePayloadType};
+    use crate::test::gen_frame;
+
+    #[test]
+    fn test_user_code_section() -> anyhow::Result<()> {
+        let pipeline = Pipeline::new(
+            vec![(
+                "input".to_string(),
+                PipelineStagePayloadType::Frame,
+                None,
+                None,
+            )],
+            PipelineConfiguration::default(),
+        )?;
+        let id = pipeline.add_frame("input", gen_frame())?;
+
+        let section = UserCodeSection::start("input", vec![id]);
+        std::thread::sleep(Duration::from_millis(10));
+        account_rust_time(Duration::from_millis(4));
+        account_gil_wait_time(Duration::from_millis(3));
+        let time = section.finish(&pipeline)?;
+        assert_eq!(time.rust, Duration::from_millis(4));
+        assert_eq!(time.gil_wait, Duration::from_millis(3));
+        assert!(time.user() >= Duration::from_millis(3));
+        assert_eq!(time.user(), time.total - time.rust - time.gil_wait);
+        assert!(UserCodeSection::start("missing", vec![])
+            .finish(&pipeline)
+            .is_err());
+        Ok(())
+    }
+}
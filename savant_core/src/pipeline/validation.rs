@@ -0,0 +1,450 @@
+use crate::metrics::get_or_create_counter_family;
+use crate::pipeline::contracts::{ContractViolation, ModuleContract};
+use crate::primitives::frame::VideoFrameProxy;
+use crate::primitives::object::ObjectOperations;
+use crate::primitives::RBBox;
+use hashbrown::{HashMap, HashSet};
+use log::warn;
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+const INVARIANT_VIOLATIONS_METRIC: &str = "frame_invariant_violations";
+
+/// An invariant the frame leaving a stage breaks.
+///
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum InvariantViolation {
+    #[error("Object {object} refers to parent {parent} which is not in the frame")]
+    MissingParent { object: i64, parent: i64 },
+    #[error("Object {object} has the {kind} box which is not finite or has a negative size")]
+    InvalidBox { object: i64, kind: &'static str },
+    #[error("Object {object} has the {kind} box ({left}, {top}, {right}, {bottom}) out of the frame bounds")]
+    BoxOutOfBounds {
+        object: i64,
+        kind: &'static str,
+        left: f32,
+        top: f32,
+        right: f32,
+        bottom: f32,
+    },
+    #[error(
+        "Objects {first} and {second} of {namespace}/{label} have the same track id {track_id}"
+    )]
+    DuplicateTrackId {
+        namespace: String,
+        label: String,
+        track_id: i64,
+        first: i64,
+        second: i64,
+    },
+    #[error("Object {object} has the track box without the track id")]
+    TrackBoxWithoutId { object: i64 },
+    #[error("{0}")]
+    Contract(ContractViolation),
+}
+
+impl InvariantViolation {
+    /// The kind of the violation in the metrics and the span events.
+    ///
+    pub fn kind(&self) -> &'static str {
+        match self {
+            InvariantViolation::MissingParent { .. } => "missing_parent",
+            InvariantViolation::InvalidBox { .. } => "invalid_box",
+            InvariantViolation::BoxOutOfBounds { .. } => "box_out_of_bounds",
+            InvariantViolation::DuplicateTrackId { .. } => "duplicate_track_id",
+            InvariantViolation::TrackBoxWithoutId { .. } => "track_box_without_id",
+            InvariantViolation::Contract(_) => "contract",
+        }
+    }
+}
+
+/// A violation found in a frame leaving the stage.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationEvent {
+    pub stage: String,
+    pub source_id: String,
+    pub frame_uuid: String,
+    pub violation: InvariantViolation,
+}
+
+/// Receives the violations found in the frames leaving a stage, the events of the frames
+/// leaving together are delivered in one call. The callback is called without the locks of
+/// the stage.
+///
+pub trait ValidationCallback: Send + Sync {
+    fn on_violations(&self, stage: &str, events: Vec<ValidationEvent>);
+}
+
+/// The invariants checked by [`FrameValidator`].
+///
+#[derive(Debug, Clone)]
+pub struct FrameValidatorConfig {
+    /// Every n-th frame leaving a stage is checked, 1 checks all the frames (e.g. in the
+    /// debug builds), a larger value samples the frames in the production.
+    pub sample_every: u64,
+    /// The boxes must lie within the frame extended by the margin in pixels, `None` does not
+    /// check the bounds.
+    pub bounds_margin: Option<f32>,
+    /// The attribute contracts the frames leaving the stages must pass, by the stage, see
+    /// [`ModuleContract::check_frame`].
+    pub contracts: HashMap<String, ModuleContract>,
+}
+
+impl Default for FrameValidatorConfig {
+    fn default() -> Self {
+        Self {
+            sample_every: 1,
+            bounds_margin: Some(0.0),
+            contracts: HashMap::new(),
+        }
+    }
+}
+
+/// Checks the invariants of the frames at the stage boundaries: the parents of the objects
+/// are in the frame, the boxes are finite and within the frame bounds, the track ids are
+/// unique among the objects of the same namespace and label and the attributes pass the
+/// contracts of the stages. The frames are checked when they leave a stage, so the stage
+/// which broke an invariant is the one reported. Installed for all the stages with
+/// [`crate::pipeline::Pipeline::set_frame_validator`].
+///
+/// Every violation is logged, counted in the `frame_invariant_violations` counter labelled
+/// with the stage and the kind, added as the `invariant-violation` event to the span of the
+/// frame and passed to the callback.
+///
+pub struct FrameValidator {
+    config: FrameValidatorConfig,
+    callback: Option<Arc<dyn ValidationCallback>>,
+    counter: AtomicU64,
+    /// The events waiting for the delivery, by the stage.
+    pending: Mutex<HashMap<String, Vec<ValidationEvent>>>,
+}
+
+impl std::fmt::Debug for FrameValidator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FrameValidator")
+            .field("config", &self.config)
+            .field("counter", &self.counter)
+            .finish()
+    }
+}
+
+fn check_box(
+    object: i64,
+    kind: &'static str,
+    bbox: &RBBox,
+    bounds: Option<(f32, f32, f32)>,
+    violations: &mut Vec<InvariantViolation>,
+) {
+    let finite = [
+        bbox.get_xc(),
+        bbox.get_yc(),
+        bbox.get_width(),
+        bbox.get_height(),
+        bbox.get_angle().unwrap_or_default(),
+    ]
+    .iter()
+    .all(|v| v.is_finite());
+    if !finite || bbox.get_width() < 0.0 || bbox.get_height() < 0.0 {
+        violations.push(InvariantViolation::InvalidBox { object, kind });
+        return;
+    }
+    let Some((width, height, margin)) = bounds else {
+        return;
+    };
+    let Ok((left, top, right, bottom)) = bbox.get_wrapping_bbox().as_ltrb() else {
+        return;
+    };
+    if left < -margin || top < -margin || right > width + margin || bottom > height + margin {
+        violations.push(InvariantViolation::BoxOutOfBounds {
+            object,
+            kind,
+            left,
+            top,
+            right,
+            bottom,
+        });
+    }
+}
+
+impl FrameValidator {
+    pub fn new(
+        config: FrameValidatorConfig,
+        callback: Option<Arc<dyn ValidationCallback>>,
+    ) -> anyhow::Result<Self> {
+        if config.sample_every == 0 {
+            anyhow::bail!("The sampling interval must be greater than 0");
+        }
+        Ok(Self {
+            config,
+            callback,
+            counter: AtomicU64::new(0),
+            pending: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn get_config(&self) -> &FrameValidatorConfig {
+        &self.config
+    }
+
+    /// Checks the invariants of the frame as it leaves the stage regardless of the sampling.
+    ///
+    pub fn validate(&self, stage: &str, frame: &VideoFrameProxy) -> Vec<InvariantViolation> {
+        let mut violations = Vec::new();
+        let mut objects = frame.get_all_objects();
+        objects.sort_by_key(|o| o.get_id());
+        let ids = objects.iter().map(|o| o.get_id()).collect::<HashSet<_>>();
+        let bounds = self
+            .config
+            .bounds_margin
+            .map(|margin| (frame.get_width() as f32, frame.get_height() as f32, margin));
+        let mut tracks = HashMap::new();
+        for o in &objects {
+            let id = o.get_id();
+            if let Some(parent) = o.get_parent_id() {
+                if !ids.contains(&parent) {
+                    violations.push(InvariantViolation::MissingParent { object: id, parent });
+                }
+            }
+            check_box(
+                id,
+                "detection",
+                &o.get_detection_box(),
+                bounds,
+                &mut violations,
+            );
+            match (o.get_track_id(), o.get_track_box()) {
+                (None, Some(_)) => {
+                    violations.push(InvariantViolation::TrackBoxWithoutId { object: id })
+                }
+                (Some(track_id), track_box) => {
+                    if let Some(track_box) = track_box {
+                        check_box(id, "track", &track_box, bounds, &mut violations);
+                    }
+                    let (namespace, label) = (o.get_namespace(), o.get_label());
+                    let key = (namespace.clone(), label.clone(), track_id);
+                    if let Some(first) = tracks.insert(key, id) {
+                        violations.push(InvariantViolation::DuplicateTrackId {
+                            namespace,
+                            label,
+                            track_id,
+                            first,
+                            second: id,
+                        });
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+        if let Some(contract) = self.config.contracts.get(stage) {
+            violations.extend(
+                contract
+                    .check_frame(frame)
+                    .into_iter()
+                    .map(InvariantViolation::Contract),
+            );
+        }
+        violations
+    }
+
+    fn count(&self, stage: &str, kind: &str) {
+        let res = get_or_create_counter_family(
+            INVARIANT_VIOLATIONS_METRIC,
+            Some("The number of the frame invariant violations found at the stage boundaries"),
+            &["stage", "kind"],
+            None,
+        )
+        .lock()
+        .inc(1, &[stage, kind]);
+        if let Err(e) = res {
+            warn!(
+                target: "savant_rs::pipeline::validation",
+                "Failed to count the invariant violation: {}", e
+            );
+        }
+    }
+
+    /// Checks the sampled frame leaving the stage, the violations are logged, counted and
+    /// kept for [`FrameValidator::notify`].
+    ///
+    pub(crate) fn check(&self, stage: &str, frame: &VideoFrameProxy) -> Vec<InvariantViolation> {
+        if self.counter.fetch_add(1, Ordering::Relaxed) % self.config.sample_every != 0 {
+            return Vec::new();
+        }
+        let violations = self.validate(stage, frame);
+        if violations.is_empty() {
+            return violations;
+        }
+        let source_id = frame.get_source_id();
+        let frame_uuid = frame.get_uuid_as_string();
+        for v in &violations {
+            warn!(
+                target: "savant_rs::pipeline::validation",
+                "Frame {} of source {} leaving stage {}: {}", frame_uuid, source_id, stage, v
+            );
+            self.count(stage, v.kind());
+        }
+        if self.callback.is_some() {
+            self.pending
+                .lock()
+                .entry_ref(stage)
+                .or_default()
+                .extend(violations.iter().map(|v| ValidationEvent {
+                    stage: stage.to_string(),
+                    source_id: source_id.clone(),
+                    frame_uuid: frame_uuid.clone(),
+                    violation: v.clone(),
+                }));
+        }
+        violations
+    }
+
+    /// Delivers the pending events of the stage to the callback.
+    ///
+    pub(crate) fn notify(&self, stage: &str) {
+        let Some(callback) = &self.callback else {
+            return;
+        };
+        let events = self.pending.lock().remove(stage);
+        if let Some(events) = events.filter(|e| !e.is_empty()) {
+            callback.on_violations(stage, events);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::validation::{
+        FrameValidator, FrameValidatorConfig, InvariantViolation, ValidationCallback,
+        ValidationEvent,
+    };
+    use crate::pipeline::{Pipeline, PipelineConfiguration, PipelineStagePayloadType};
+    use crate::primitives::object::{IdCollisionResolutionPolicy, ObjectAccess, ObjectOperations};
+    use crate::primitives::RBBox;
+    use crate::test::{gen_frame, gen_object};
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<ValidationEvent>>);
+
+    impl ValidationCallback for Recorder {
+        fn on_violations(&self, stage: &str, events: Vec<ValidationEvent>) {
+            assert!(events.iter().all(|e| e.stage == stage));
+            self.0.lock().extend(events);
+        }
+    }
+
+    #[test]
+    fn test_validate_frame() -> anyhow::Result<()> {
+        let validator = FrameValidator::new(FrameValidatorConfig::default(), None)?;
+        let frame = gen_frame();
+        assert!(validator.validate("detect", &frame).is_empty());
+
+        let mut object = gen_object(10);
+        object.set_detection_box(RBBox::new(f32::NAN, 0.0, 10.0, 10.0, None));
+        frame.add_object(object, IdCollisionResolutionPolicy::Error)?;
+        let mut object = gen_object(11);
+        object.set_detection_box(RBBox::new(1275.0, 10.0, 20.0, 10.0, None));
+        object.set_track_info(10, RBBox::new(100.0, 100.0, 10.0, 10.0, None));
+        frame.add_object(object, IdCollisionResolutionPolicy::Error)?;
+        frame
+            .get_object(1)
+            .unwrap()
+            .with_object_mut(|o| o.parent_id = Some(99));
+        let kinds = validator
+            .validate("detect", &frame)
+            .iter()
+            .map(|v| v.kind())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                "missing_parent",
+                "invalid_box",
+                "box_out_of_bounds",
+                "duplicate_track_id"
+            ]
+        );
+        assert!(FrameValidator::new(
+            FrameValidatorConfig {
+                sample_every: 0,
+                ..Default::default()
+            },
+            None
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_notify_by_stage() -> anyhow::Result<()> {
+        let recorder = Arc::new(Recorder::default());
+        let validator =
+            FrameValidator::new(FrameValidatorConfig::default(), Some(recorder.clone()))?;
+        let frame = gen_frame();
+        frame
+            .get_object(0)
+            .unwrap()
+            .set_detection_box(RBBox::new(f32::NAN, 0.0, 10.0, 10.0, None));
+        assert_eq!(validator.check("detect", &frame).len(), 1);
+        // the events of a stage are not delivered for another one
+        validator.notify("track");
+        assert!(recorder.0.lock().is_empty());
+        validator.notify("detect");
+        assert_eq!(recorder.0.lock().len(), 1);
+        validator.notify("detect");
+        assert_eq!(recorder.0.lock().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pipeline_validation() -> anyhow::Result<()> {
+        let pipeline = Pipeline::new(
+            vec![
+                (
+                    "detect".to_string(),
+                    PipelineStagePayloadType::Frame,
+                    None,
+                    None,
+                ),
+                (
+                    "output".to_string(),
+                    PipelineStagePayloadType::Frame,
+                    None,
+                    None,
+                ),
+            ],
+            PipelineConfiguration::default(),
+        )?;
+        let recorder = Arc::new(Recorder::default());
+        pipeline.set_frame_validator(Some(FrameValidator::new(
+            FrameValidatorConfig::default(),
+            Some(recorder.clone()),
+        )?));
+        let frame = gen_frame();
+        let id = pipeline.add_frame("detect", frame.clone())?;
+        frame.get_object(0).unwrap().set_detection_box(RBBox::new(
+            f32::INFINITY,
+            0.0,
+            10.0,
+            10.0,
+            None,
+        ));
+        pipeline.move_as_is("output", vec![id])?;
+        {
+            let events = recorder.0.lock();
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].stage, "detect");
+            assert_eq!(events[0].source_id, frame.get_source_id());
+            assert!(matches!(
+                events[0].violation,
+                InvariantViolation::InvalidBox { object: 0, .. }
+            ));
+        }
+        pipeline.delete(id)?;
+        assert_eq!(recorder.0.lock().len(), 2);
+        Ok(())
+    }
+}
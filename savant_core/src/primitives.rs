@@ -8,15 +8,32 @@ pub mod bbox;
 pub use bbox::*;
 
 pub mod any_object;
+pub mod attribute_propagation;
 pub mod attribute_set;
 pub mod attribute_value;
+pub mod audio_frame;
+pub mod batch_tensor;
+pub mod classification;
+pub mod content_encoding;
 pub mod eos;
 pub mod frame;
 pub mod frame_batch;
+pub mod frame_delta;
+pub mod frame_merge;
 pub mod frame_update;
+pub mod frozen_objects;
+pub mod geo;
 pub mod object;
+pub mod object_fusion;
+pub mod object_payload;
+pub mod processing_hints;
+pub mod query_cache;
+pub mod raw_content;
+pub mod representation;
 pub mod segment;
 pub mod shutdown;
+pub mod telemetry_frame;
+pub mod unit;
 pub mod userdata;
 
 pub use segment::*;
@@ -25,9 +42,14 @@ pub mod rust {
     pub use super::attribute::Attribute;
     pub use super::attribute_set::AttributeSet;
     pub use super::attribute_value::AttributeValue;
+    pub use super::attribute_value::AttributeValues;
+    pub use super::audio_frame::AudioFrame;
+    pub use super::audio_frame::AudioSampleFormat;
     pub use super::bbox::BBoxMetricType;
     pub use super::bbox::RBBox;
     pub use super::bbox::RBBoxData;
+    pub use super::classification::DataClassification;
+    pub use super::content_encoding::ContentEncoding;
     pub use super::eos::EndOfStream;
     pub use super::frame::BelongingVideoFrame;
     pub use super::frame::VideoFrameContent;
@@ -35,19 +57,30 @@ pub mod rust {
     pub use super::frame::VideoFrameTranscodingMethod;
     pub use super::frame::VideoFrameTransformation;
     pub use super::frame_batch::VideoFrameBatch;
+    pub use super::frame_delta::{DeltaDecoder, DeltaEncoder};
+    pub use super::frame_merge::{FrameMergePolicy, ObjectMergePolicy};
     pub use super::frame_update::VideoFrameUpdate;
+    pub use super::frozen_objects::FrozenObjects;
+    pub use super::geo::{Enu, GeoPosition};
     pub use super::object::BorrowedVideoObject;
     pub use super::object::VideoObject;
     pub use super::object::VideoObjectBBoxTransformation;
     pub use super::object::VideoObjectBuilder;
+    pub use super::object_payload::ObjectPayload;
     pub use super::point::Point;
     pub use super::polygonal_area::PolygonalArea;
+    pub use super::processing_hints::ProcessingHints;
+    pub use super::raw_content::{InternalFrame, PixelFormat, Plane, RawContentError, RawLayout};
+    pub use super::representation::ContentRepresentation;
     pub use super::segment::Intersection;
     pub use super::segment::IntersectionKind;
     pub use super::segment::Segment;
     pub use super::shutdown::Shutdown;
+    pub use super::telemetry_frame::TelemetryFrame;
+    pub use super::unit::Unit;
     pub use super::userdata::UserData;
     pub use crate::message::Message;
     pub use crate::primitives::frame::ExternalFrame;
     pub use crate::primitives::object::IdCollisionResolutionPolicy;
+    pub use crate::primitives::object::OrphanPolicy;
 }
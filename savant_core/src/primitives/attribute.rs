@@ -1,11 +1,13 @@
+use crate::clock::now_ms;
 use crate::json_api::ToSerdeJsonValue;
-use crate::primitives::attribute_value::AttributeValue;
+use crate::primitives::attribute_value::{AttributeValue, AttributeValues};
 use std::mem;
-use std::sync::Arc;
+use std::time::Duration;
 
 /// Attribute represents a specific knowledge about certain entity. The attribute is identified by ``(creator, label)`` pair which is unique within the entity.
 /// The attribute value is a list of values, each of which has a confidence score. The attribute may include additional information in the form of a hint.
 /// There are two kinds of attributes: persistent and non-persistent. Persistent attributes are serialized, while non-persistent are not.
+/// An attribute may expire: after the expiry it is invisible to the accessors and is not serialized.
 ///
 /// The list nature of attribute values is used to represent complex values of the same attribute.
 /// For example, the attribute ``(person_profiler, bio)`` may include values in the form ``["Age", 32, "Gender", None, "Height", 186]``. Each element of the
@@ -18,17 +20,21 @@ pub struct Attribute {
     pub namespace: String,
     pub name: String,
     #[builder(setter(custom))]
-    pub values: Arc<Vec<AttributeValue>>,
+    pub values: AttributeValues,
     pub hint: Option<String>,
     #[builder(default = "true")]
     pub is_persistent: bool,
     #[builder(default = "false")]
     pub is_hidden: bool,
+    /// The expiry in milliseconds since the UNIX epoch.
+    #[builder(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
 }
 
 impl AttributeBuilder {
     pub fn values(&mut self, vals: Vec<AttributeValue>) -> &mut Self {
-        self.values = Some(Arc::new(vals));
+        self.values = Some(vals.into());
         self
     }
 }
@@ -194,7 +200,7 @@ impl Attribute {
     /// List[:class:`AttributeValue`]
     ///   The values of the attribute.
     ///
-    pub fn get_values(&self) -> &Vec<AttributeValue> {
+    pub fn get_values(&self) -> &[AttributeValue] {
         &self.values
     }
 
@@ -228,7 +234,59 @@ impl Attribute {
     ///   The values of the attribute.
     ///
     pub fn set_values(&mut self, values: Vec<AttributeValue>) {
-        self.values = Arc::new(values);
+        self.values = values.into();
+    }
+
+    /// Returns the expiry of the attribute.
+    ///
+    /// Returns
+    /// -------
+    /// int or None
+    ///   The expiry in milliseconds since the UNIX epoch or ``None`` if the attribute does not expire.
+    ///
+    pub fn get_expires_at(&self) -> Option<u64> {
+        self.expires_at
+    }
+
+    /// Sets the absolute expiry of the attribute.
+    ///
+    /// Parameters
+    /// ----------
+    /// expires_at : int or None
+    ///   The expiry in milliseconds since the UNIX epoch or ``None`` to never expire.
+    ///
+    pub fn set_expires_at(&mut self, expires_at: Option<u64>) {
+        self.expires_at = expires_at;
+    }
+
+    /// Sets the expiry of the attribute relative to the current time.
+    ///
+    /// Parameters
+    /// ----------
+    /// ttl : Duration
+    ///   The time the attribute stays alive.
+    ///
+    pub fn set_ttl(&mut self, ttl: Duration) {
+        self.expires_at = Some(now_ms().saturating_add(ttl.as_millis() as u64));
+    }
+
+    /// Returns ``True`` if the attribute expired at the moment in milliseconds since the
+    /// UNIX epoch.
+    ///
+    pub fn is_expired_at(&self, now_ms: u64) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= now_ms)
+    }
+
+    /// Returns ``True`` if the attribute has expired.
+    ///
+    /// Returns
+    /// -------
+    /// bool
+    ///   ``True`` if the attribute has expired, ``False`` otherwise.
+    ///
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.is_some() && self.is_expired_at(now_ms())
     }
 
     pub fn to_json(&self) -> anyhow::Result<String> {
@@ -282,7 +340,7 @@ pub trait WithAttributes: Send {
             attributes
                 .iter()
                 .filter_map(|a| {
-                    if a.is_hidden {
+                    if a.is_hidden || a.is_expired() {
                         None
                     } else {
                         Some((a.namespace.clone(), a.name.clone()))
@@ -296,7 +354,7 @@ pub trait WithAttributes: Send {
         self.with_attributes_ref(|attributes| {
             attributes
                 .iter()
-                .find(|a| a.namespace == namespace && a.name == name)
+                .find(|a| a.namespace == namespace && a.name == name && !a.is_expired())
                 .cloned()
         })
     }
@@ -305,7 +363,7 @@ pub trait WithAttributes: Send {
         self.with_attributes_ref(|attributes| {
             attributes
                 .iter()
-                .any(|a| a.namespace == namespace && a.name == name)
+                .any(|a| a.namespace == namespace && a.name == name && !a.is_expired())
         })
     }
 
@@ -333,6 +391,16 @@ pub trait WithAttributes: Send {
         })
     }
 
+    /// Removes the expired attributes, returns the number of removed attributes.
+    ///
+    fn purge_expired_attributes(&mut self) -> usize {
+        self.with_attributes_mut(|attributes| {
+            let before = attributes.len();
+            attributes.retain(|a| !a.is_expired());
+            before - attributes.len()
+        })
+    }
+
     fn clear_attributes(&mut self) {
         self.with_attributes_mut(|attributes| attributes.clear())
     }
@@ -348,7 +416,7 @@ pub trait WithAttributes: Send {
             attributes
                 .iter()
                 .filter_map(|a| {
-                    if a.namespace == namespace {
+                    if a.namespace == namespace && !a.is_expired() {
                         Some((a.namespace.clone(), a.name.clone()))
                     } else {
                         None
@@ -369,7 +437,7 @@ pub trait WithAttributes: Send {
             attributes
                 .iter()
                 .filter_map(|a| {
-                    if names.contains(&a.name.as_str()) {
+                    if names.contains(&a.name.as_str()) && !a.is_expired() {
                         Some((a.namespace.clone(), a.name.clone()))
                     } else {
                         None
@@ -390,7 +458,7 @@ pub trait WithAttributes: Send {
             attributes
                 .iter()
                 .filter_map(|a| {
-                    if hints.contains(&&a.hint.as_deref()) {
+                    if hints.contains(&&a.hint.as_deref()) && !a.is_expired() {
                         Some((a.namespace.clone(), a.name.clone()))
                     } else {
                         None
@@ -430,6 +498,7 @@ mod tests {
     use crate::primitives::attribute_value::{AttributeValue, AttributeValueVariant};
     use crate::primitives::{Attribute, WithAttributes};
     use std::mem;
+    use std::time::Duration;
 
     #[derive(Default, Clone)]
     struct AttrStor {
@@ -523,6 +592,27 @@ mod tests {
         assert_eq!(t.attributes.len(), 0);
     }
 
+    #[test]
+    fn test_expired_attributes() {
+        let mut expired = Attribute::new("osd", "skip", vec![], &None, true, false);
+        expired.set_expires_at(Some(1));
+        assert!(expired.is_expired());
+        let mut alive = Attribute::new("osd", "draw", vec![], &None, true, false);
+        alive.set_ttl(Duration::from_secs(60));
+        assert!(!alive.is_expired());
+        assert!(alive.is_expired_at(alive.get_expires_at().unwrap()));
+
+        let mut t = AttrStor::default();
+        t.set_attribute(expired);
+        t.set_attribute(alive);
+        assert!(t.get_attribute("osd", "skip").is_none());
+        assert!(!t.contains_attribute("osd", "skip"));
+        assert_eq!(t.get_attributes(), vec![("osd".into(), "draw".into())]);
+        assert_eq!(t.find_attributes_with_ns("osd").len(), 1);
+        assert_eq!(t.purge_expired_attributes(), 1);
+        assert_eq!(t.attributes.len(), 1);
+    }
+
     #[test]
     fn test_delete_attribute() {
         let attribute = Attribute::new("system", "test", vec![], &None, true, false);
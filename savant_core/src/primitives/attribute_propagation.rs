@@ -0,0 +1,217 @@
+use crate::primitives::attribute_value::{AttributeValue, AttributeValueVariant};
+use crate::primitives::frame::VideoFrameProxy;
+use crate::primitives::object::VideoObject;
+use crate::primitives::userdata::UserData;
+use crate::primitives::{Attribute, WithAttributes};
+
+/// How a frame attribute is passed to the derived objects and events.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum PropagationMode {
+    /// The attribute is copied with its values.
+    #[default]
+    Copy,
+    /// The attribute is passed with a single string value, the uuid of the frame holding the
+    /// values, so large values are not duplicated and the consumers resolve them against
+    /// the frame. The attribute gets the [`REFERENCE_HINT`] hint, see [`frame_reference`].
+    Reference,
+}
+
+/// The hint of the attributes passed by reference, it tells the reference from an attribute
+/// which really holds a single string value.
+///
+pub const REFERENCE_HINT: &str = "savant.frame_reference";
+
+/// Returns the uuid of the frame holding the values when the attribute is passed by
+/// reference, `None` for the attributes holding their values.
+///
+pub fn frame_reference(attribute: &Attribute) -> Option<&str> {
+    if attribute.hint.as_deref() != Some(REFERENCE_HINT) {
+        return None;
+    }
+    match attribute.values.as_slice() {
+        [AttributeValue {
+            value: AttributeValueVariant::String(uuid),
+            ..
+        }] => Some(uuid.as_str()),
+        _ => None,
+    }
+}
+
+/// Selects the frame attributes passed to the objects created in the frame and to the
+/// events derived from it.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropagationRule {
+    pub namespace: String,
+    /// The name of the attribute, `None` selects all the attributes of the namespace.
+    pub name: Option<String>,
+    pub mode: PropagationMode,
+    pub to_objects: bool,
+    pub to_events: bool,
+}
+
+impl PropagationRule {
+    pub fn new(namespace: &str, name: Option<&str>, mode: PropagationMode) -> Self {
+        Self {
+            namespace: namespace.to_string(),
+            name: name.map(String::from),
+            mode,
+            to_objects: true,
+            to_events: true,
+        }
+    }
+
+    fn matches(&self, attribute: &Attribute) -> bool {
+        attribute.namespace == self.namespace
+            && self.name.as_ref().is_none_or(|n| n == &attribute.name)
+    }
+}
+
+/// The inheritance of the frame attributes like the location, the tenant or the camera group:
+/// the attributes selected by the rules are set on the objects added to the frame and on the
+/// events created with [`VideoFrameProxy::create_user_data`], so the processors do not forward
+/// them manually. The attributes the object or the event already has are not replaced.
+///
+/// The propagation is attached to the frames by the pipeline, see
+/// [`crate::pipeline::Pipeline::set_attribute_propagation`], or directly with
+/// [`VideoFrameProxy::set_attribute_propagation`].
+///
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AttributePropagation {
+    rules: Vec<PropagationRule>,
+}
+
+impl AttributePropagation {
+    pub fn new(rules: Vec<PropagationRule>) -> Self {
+        Self { rules }
+    }
+
+    pub fn get_rules(&self) -> &[PropagationRule] {
+        &self.rules
+    }
+
+    /// The attributes passed from the frame, the first matching rule decides the mode.
+    ///
+    fn propagated(
+        &self,
+        frame_uuid: &str,
+        attributes: &[Attribute],
+        selected: impl Fn(&PropagationRule) -> bool,
+    ) -> Vec<Attribute> {
+        attributes
+            .iter()
+            .filter_map(|attribute| {
+                let rule = self
+                    .rules
+                    .iter()
+                    .find(|r| selected(r) && r.matches(attribute))?;
+                let mut attribute = attribute.clone();
+                if rule.mode == PropagationMode::Reference {
+                    attribute.set_values(vec![AttributeValue::string(frame_uuid, None)]);
+                    attribute.set_hint(Some(REFERENCE_HINT.to_string()));
+                }
+                Some(attribute)
+            })
+            .collect()
+    }
+
+    pub(crate) fn apply_to_object(
+        &self,
+        frame_uuid: &str,
+        frame_attributes: &[Attribute],
+        object: &mut VideoObject,
+    ) {
+        for attribute in self.propagated(frame_uuid, frame_attributes, |r| r.to_objects) {
+            if !object.contains_attribute(&attribute.namespace, &attribute.name) {
+                object.set_attribute(attribute);
+            }
+        }
+    }
+
+    pub fn apply_to_user_data(&self, frame: &VideoFrameProxy, data: &mut UserData) {
+        let frame_attributes = frame.with_attributes_ref(|a| a.clone());
+        let frame_uuid = frame.get_uuid_as_string();
+        for attribute in self.propagated(&frame_uuid, &frame_attributes, |r| r.to_events) {
+            if !data.contains_attribute(&attribute.namespace, &attribute.name) {
+                data.set_attribute(attribute);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::primitives::attribute_propagation::{
+        frame_reference, AttributePropagation, PropagationMode, PropagationRule,
+    };
+    use crate::primitives::attribute_value::AttributeValue;
+    use crate::primitives::object::ObjectOperations;
+    use crate::primitives::{RBBox, WithAttributes};
+    use crate::test::gen_frame;
+
+    #[test]
+    fn test_propagation() -> anyhow::Result<()> {
+        let mut frame = gen_frame();
+        frame.set_persistent_attribute(
+            "site",
+            "location",
+            &None,
+            false,
+            vec![AttributeValue::string("gate-3", None)],
+        );
+        frame.set_persistent_attribute(
+            "site",
+            "tenant",
+            &None,
+            false,
+            vec![AttributeValue::string("acme", None)],
+        );
+        let mut tenant = PropagationRule::new("site", Some("tenant"), PropagationMode::Reference);
+        tenant.to_events = false;
+        frame.set_attribute_propagation(Some(Arc::new(AttributePropagation::new(vec![
+            tenant,
+            PropagationRule::new("site", None, PropagationMode::Copy),
+        ]))));
+
+        let object = frame.create_object(
+            "detector",
+            "car",
+            None,
+            RBBox::new(10.0, 10.0, 4.0, 4.0, None),
+            None,
+            None,
+            None,
+            vec![],
+        )?;
+        let location = object.get_attribute("site", "location").unwrap();
+        assert_eq!(
+            location.get_values(),
+            &[AttributeValue::string("gate-3", None)]
+        );
+        let tenant = object.get_attribute("site", "tenant").unwrap();
+        assert_eq!(
+            tenant.get_values(),
+            &[AttributeValue::string(&frame.get_uuid_as_string(), None)]
+        );
+        assert_eq!(
+            frame_reference(&tenant),
+            Some(frame.get_uuid_as_string().as_str())
+        );
+        // a copied single string value is not a reference
+        assert!(frame_reference(&location).is_none());
+
+        let event = frame.create_user_data();
+        assert_eq!(event.source_id, frame.get_source_id());
+        let event_tenant = event.get_attribute("site", "tenant").unwrap();
+        assert_eq!(
+            event_tenant.get_values(),
+            &[AttributeValue::string("acme", None)]
+        );
+        assert!(frame_reference(&event_tenant).is_none());
+        assert!(event.get_attribute("site", "location").is_some());
+        Ok(())
+    }
+}
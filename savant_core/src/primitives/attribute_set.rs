@@ -38,6 +38,21 @@ impl AttributeSet {
     pub fn json_pretty(&self) -> String {
         serde_json::to_string_pretty(&self.to_serde_json_value()).unwrap()
     }
+
+    /// Parses the set from the document produced by [`AttributeSet::json`], a list of
+    /// attributes or a single attribute.
+    ///
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        let value: Value = serde_json::from_str(json)?;
+        let attributes = match value {
+            Value::Array(_) => serde_json::from_value(value)?,
+            Value::Object(mut map) if map.len() == 1 && map.contains_key("attributes") => {
+                serde_json::from_value(map.remove("attributes").unwrap())?
+            }
+            _ => vec![serde_json::from_value(value)?],
+        };
+        Ok(Self { attributes })
+    }
 }
 
 impl WithAttributes for AttributeSet {
@@ -55,3 +70,27 @@ impl WithAttributes for AttributeSet {
         f(&mut self.attributes)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::attribute_set::AttributeSet;
+    use crate::primitives::attribute_value::AttributeValue;
+    use crate::primitives::Attribute;
+
+    #[test]
+    fn test_json_roundtrip() -> anyhow::Result<()> {
+        let attribute = Attribute::persistent(
+            "ns",
+            "name",
+            vec![AttributeValue::integer(1, None)],
+            &None,
+            false,
+        );
+        let set = AttributeSet::from(vec![attribute.clone()]);
+        assert_eq!(AttributeSet::from_json(&set.json())?, set);
+        assert_eq!(AttributeSet::from_json(&attribute.to_json()?)?, set);
+        let list = serde_json::to_string(&vec![attribute])?;
+        assert_eq!(AttributeSet::from_json(&list)?, set);
+        Ok(())
+    }
+}
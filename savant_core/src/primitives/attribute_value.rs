@@ -1,5 +1,10 @@
 use crate::primitives::any_object::AnyObject;
+use crate::primitives::unit::Unit;
 use crate::primitives::{Intersection, Point, PolygonalArea, RBBoxData};
+use anyhow::bail;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::ops::Deref;
+use std::sync::Arc;
 
 #[derive(Debug, PartialEq, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub enum AttributeValueVariant {
@@ -28,11 +33,18 @@ pub enum AttributeValueVariant {
 pub struct AttributeValue {
     pub confidence: Option<f32>,
     pub value: AttributeValueVariant,
+    /// The unit of a numeric value.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<Unit>,
 }
 
 impl AttributeValue {
     pub fn new(value: AttributeValueVariant, confidence: Option<f32>) -> Self {
-        Self { confidence, value }
+        Self {
+            confidence,
+            value,
+            unit: None,
+        }
     }
 
     pub fn float(value: f64, confidence: Option<f32>) -> Self {
@@ -114,6 +126,75 @@ impl AttributeValue {
         &self.value
     }
 
+    /// Integers, floats and their vectors, the values which may have a unit.
+    ///
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self.value,
+            AttributeValueVariant::Integer(_)
+                | AttributeValueVariant::IntegerVector(_)
+                | AttributeValueVariant::Float(_)
+                | AttributeValueVariant::FloatVector(_)
+        )
+    }
+
+    pub fn get_unit(&self) -> Option<Unit> {
+        self.unit
+    }
+
+    /// Tags the value with the unit, only numeric values have units.
+    ///
+    pub fn set_unit(&mut self, unit: Option<Unit>) -> anyhow::Result<()> {
+        if unit.is_some() && !self.is_numeric() {
+            bail!(
+                "Only numeric values have units, the value is {:?}",
+                self.value
+            );
+        }
+        self.unit = unit;
+        Ok(())
+    }
+
+    pub fn with_unit(mut self, unit: Unit) -> anyhow::Result<Self> {
+        self.set_unit(Some(unit))?;
+        Ok(self)
+    }
+
+    /// Converts the value to the unit, the integers become floats unless the unit is the same.
+    ///
+    pub fn convert_to(&self, unit: Unit) -> anyhow::Result<Self> {
+        let Some(from) = self.unit else {
+            bail!("The value has no unit to convert from");
+        };
+        if from == unit {
+            return Ok(self.clone());
+        }
+        let value = match &self.value {
+            AttributeValueVariant::Integer(v) => {
+                AttributeValueVariant::Float(from.convert(*v as f64, unit)?)
+            }
+            AttributeValueVariant::Float(v) => {
+                AttributeValueVariant::Float(from.convert(*v, unit)?)
+            }
+            AttributeValueVariant::IntegerVector(v) => AttributeValueVariant::FloatVector(
+                v.iter()
+                    .map(|v| from.convert(*v as f64, unit))
+                    .collect::<anyhow::Result<_>>()?,
+            ),
+            AttributeValueVariant::FloatVector(v) => AttributeValueVariant::FloatVector(
+                v.iter()
+                    .map(|v| from.convert(*v, unit))
+                    .collect::<anyhow::Result<_>>()?,
+            ),
+            value => bail!("Cannot convert {:?}, only numeric values have units", value),
+        };
+        Ok(Self {
+            confidence: self.confidence,
+            value,
+            unit: Some(unit),
+        })
+    }
+
     pub fn to_json(&self) -> anyhow::Result<String> {
         Ok(serde_json::to_string(self)?)
     }
@@ -122,3 +203,154 @@ impl AttributeValue {
         Ok(serde_json::from_str(json)?)
     }
 }
+
+/// Storage for the values of an attribute.
+///
+/// Most attributes carry exactly one value, so the single value is kept inline without
+/// any heap allocation for the container. Multiple values are kept in a shared vector,
+/// which makes cloning of attributes cheap. The storage dereferences to a slice, so the
+/// representation is transparent for readers.
+///
+#[derive(Debug, Clone, Default)]
+pub enum AttributeValues {
+    #[default]
+    Empty,
+    One(AttributeValue),
+    Many(Arc<Vec<AttributeValue>>),
+}
+
+impl AttributeValues {
+    pub fn as_slice(&self) -> &[AttributeValue] {
+        match self {
+            AttributeValues::Empty => &[],
+            AttributeValues::One(v) => std::slice::from_ref(v),
+            AttributeValues::Many(v) => v.as_slice(),
+        }
+    }
+
+    pub fn to_vec(&self) -> Vec<AttributeValue> {
+        self.as_slice().to_vec()
+    }
+
+    pub fn is_inline(&self) -> bool {
+        !matches!(self, AttributeValues::Many(_))
+    }
+}
+
+impl Deref for AttributeValues {
+    type Target = [AttributeValue];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl PartialEq for AttributeValues {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl From<Vec<AttributeValue>> for AttributeValues {
+    fn from(mut values: Vec<AttributeValue>) -> Self {
+        match values.len() {
+            0 => AttributeValues::Empty,
+            1 => AttributeValues::One(values.pop().unwrap()),
+            _ => AttributeValues::Many(Arc::new(values)),
+        }
+    }
+}
+
+impl From<AttributeValue> for AttributeValues {
+    fn from(value: AttributeValue) -> Self {
+        AttributeValues::One(value)
+    }
+}
+
+impl FromIterator<AttributeValue> for AttributeValues {
+    fn from_iter<T: IntoIterator<Item = AttributeValue>>(iter: T) -> Self {
+        iter.into_iter().collect::<Vec<_>>().into()
+    }
+}
+
+impl Serialize for AttributeValues {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.as_slice().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AttributeValues {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Vec::<AttributeValue>::deserialize(deserializer)?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::attribute_value::{
+        AttributeValue, AttributeValueVariant, AttributeValues,
+    };
+    use crate::primitives::unit::Unit;
+
+    #[test]
+    fn test_inline_storage() {
+        let values = AttributeValues::from(vec![]);
+        assert!(matches!(values, AttributeValues::Empty));
+        assert!(values.is_empty());
+
+        let values = AttributeValues::from(vec![AttributeValue::integer(1, None)]);
+        assert!(matches!(values, AttributeValues::One(_)));
+        assert_eq!(values.len(), 1);
+
+        let values = AttributeValues::from(vec![
+            AttributeValue::integer(1, None),
+            AttributeValue::integer(2, None),
+        ]);
+        assert!(!values.is_inline());
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[1], AttributeValue::integer(2, None));
+    }
+
+    #[test]
+    fn test_serde_transparent() {
+        let values = AttributeValues::from(vec![AttributeValue::float(1.0, Some(0.5))]);
+        let json = serde_json::to_string(&values).unwrap();
+        let vec_json = serde_json::to_string(&vec![AttributeValue::float(1.0, Some(0.5))]).unwrap();
+        assert_eq!(json, vec_json);
+        let restored: AttributeValues = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, values);
+        assert!(restored.is_inline());
+    }
+
+    #[test]
+    fn test_units() -> anyhow::Result<()> {
+        let speed = AttributeValue::integer(36, None).with_unit(Unit::KilometersPerHour)?;
+        let converted = speed.convert_to(Unit::MetersPerSecond)?;
+        assert_eq!(converted.get_unit(), Some(Unit::MetersPerSecond));
+        assert!(
+            matches!(converted.get(), AttributeValueVariant::Float(v) if (v - 10.0).abs() < 1e-9)
+        );
+        assert_eq!(speed.convert_to(Unit::KilometersPerHour)?, speed);
+        assert!(speed.convert_to(Unit::Second).is_err());
+        assert!(AttributeValue::float(1.0, None)
+            .convert_to(Unit::Meter)
+            .is_err());
+        assert!(AttributeValue::string("fast", None)
+            .with_unit(Unit::Meter)
+            .is_err());
+
+        let json = speed.to_json()?;
+        assert!(json.contains(r#""unit":"km/h""#));
+        assert_eq!(AttributeValue::from_json(&json)?, speed);
+        assert!(!AttributeValue::integer(36, None)
+            .to_json()?
+            .contains("unit"));
+        Ok(())
+    }
+}
@@ -0,0 +1,225 @@
+use crate::json_api::ToSerdeJsonValue;
+use crate::primitives::frame::VideoFrameContent;
+use crate::primitives::{Attribute, WithAttributes};
+use crate::utils::uuid_v7::frame_uuid;
+use anyhow::bail;
+use derive_builder::Builder;
+use serde_json::Value;
+use std::str::FromStr;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum AudioSampleFormat {
+    U8,
+    S16,
+    S32,
+    F32,
+    F64,
+}
+
+impl AudioSampleFormat {
+    pub fn bytes_per_sample(&self) -> usize {
+        match self {
+            AudioSampleFormat::U8 => 1,
+            AudioSampleFormat::S16 => 2,
+            AudioSampleFormat::S32 | AudioSampleFormat::F32 => 4,
+            AudioSampleFormat::F64 => 8,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AudioSampleFormat::U8 => "u8",
+            AudioSampleFormat::S16 => "s16",
+            AudioSampleFormat::S32 => "s32",
+            AudioSampleFormat::F32 => "f32",
+            AudioSampleFormat::F64 => "f64",
+        }
+    }
+}
+
+impl FromStr for AudioSampleFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "u8" => AudioSampleFormat::U8,
+            "s16" => AudioSampleFormat::S16,
+            "s32" => AudioSampleFormat::S32,
+            "f32" => AudioSampleFormat::F32,
+            "f64" => AudioSampleFormat::F64,
+            _ => bail!("Unknown audio sample format {}", s),
+        })
+    }
+}
+
+/// A chunk of an audio stream. Samples are interleaved, the content is stored the same way as
+/// the content of video frames: inline, as a reference to an external storage or not at all.
+///
+#[derive(Builder, Debug, Clone, PartialEq)]
+#[builder(default)]
+pub struct AudioFrame {
+    pub source_id: String,
+    pub uuid: u128,
+    pub pts: i64,
+    pub dts: Option<i64>,
+    pub duration: Option<i64>,
+    pub time_base: (i32, i32),
+    pub sample_format: AudioSampleFormat,
+    pub sample_rate: u32,
+    pub channels: u32,
+    /// The number of samples per channel.
+    pub samples: u32,
+    pub codec: Option<String>,
+    pub content: Arc<VideoFrameContent>,
+    #[builder(setter(skip))]
+    pub attributes: Vec<Attribute>,
+}
+
+impl Default for AudioFrame {
+    fn default() -> Self {
+        Self {
+            source_id: String::new(),
+            uuid: frame_uuid().as_u128(),
+            pts: 0,
+            dts: None,
+            duration: None,
+            time_base: (1, 1000000),
+            sample_format: AudioSampleFormat::S16,
+            sample_rate: 48000,
+            channels: 1,
+            samples: 0,
+            codec: None,
+            content: Arc::new(VideoFrameContent::None),
+            attributes: Vec::new(),
+        }
+    }
+}
+
+impl AudioFrame {
+    pub fn new(
+        source_id: &str,
+        pts: i64,
+        sample_format: AudioSampleFormat,
+        sample_rate: u32,
+        channels: u32,
+        samples: u32,
+        content: VideoFrameContent,
+    ) -> Self {
+        Self {
+            source_id: source_id.to_string(),
+            pts,
+            sample_format,
+            sample_rate,
+            channels,
+            samples,
+            content: Arc::new(content),
+            ..Default::default()
+        }
+    }
+
+    pub fn get_uuid(&self) -> Uuid {
+        Uuid::from_u128(self.uuid)
+    }
+
+    pub fn get_source_id(&self) -> &str {
+        &self.source_id
+    }
+
+    pub fn get_content(&self) -> Arc<VideoFrameContent> {
+        self.content.clone()
+    }
+
+    pub fn set_content(&mut self, content: VideoFrameContent) {
+        self.content = Arc::new(content);
+    }
+
+    /// The size of raw interleaved samples in bytes.
+    ///
+    pub fn get_raw_size(&self) -> usize {
+        self.samples as usize * self.channels as usize * self.sample_format.bytes_per_sample()
+    }
+
+    /// The duration of the frame in nanoseconds computed from the number of samples.
+    ///
+    pub fn get_sample_duration_ns(&self) -> Option<i64> {
+        if self.sample_rate == 0 {
+            return None;
+        }
+        Some(self.samples as i64 * 1_000_000_000 / self.sample_rate as i64)
+    }
+
+    pub fn json(&self) -> String {
+        serde_json::to_string(&self.to_serde_json_value()).unwrap()
+    }
+}
+
+impl ToSerdeJsonValue for AudioFrame {
+    fn to_serde_json_value(&self) -> Value {
+        serde_json::json!({
+            "type": "AudioFrame",
+            "source_id": self.source_id,
+            "uuid": self.get_uuid().to_string(),
+            "pts": self.pts,
+            "dts": self.dts,
+            "duration": self.duration,
+            "time_base": self.time_base,
+            "sample_format": self.sample_format.as_str(),
+            "sample_rate": self.sample_rate,
+            "channels": self.channels,
+            "samples": self.samples,
+            "codec": self.codec,
+            "content": self.content.to_serde_json_value(),
+            "attributes": self.attributes.iter().filter_map(|v| if v.is_hidden { None } else { Some(v.to_serde_json_value()) }).collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl WithAttributes for AudioFrame {
+    fn with_attributes_ref<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&Vec<Attribute>) -> R,
+    {
+        f(&self.attributes)
+    }
+
+    fn with_attributes_mut<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Vec<Attribute>) -> R,
+    {
+        f(&mut self.attributes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::audio_frame::{AudioFrame, AudioFrameBuilder, AudioSampleFormat};
+    use crate::primitives::frame::VideoFrameContent;
+
+    #[test]
+    fn test_raw_size() {
+        let frame = AudioFrame::new(
+            "mic",
+            0,
+            AudioSampleFormat::S16,
+            16000,
+            2,
+            1600,
+            VideoFrameContent::Internal(vec![0; 6400].into()),
+        );
+        assert_eq!(frame.get_raw_size(), 6400);
+        assert_eq!(frame.get_sample_duration_ns(), Some(100_000_000));
+    }
+
+    #[test]
+    fn test_builder() {
+        let frame = AudioFrameBuilder::default()
+            .source_id("mic".to_string())
+            .sample_format(AudioSampleFormat::F32)
+            .build()
+            .unwrap();
+        assert_eq!(frame.sample_rate, 48000);
+        assert_eq!(frame.sample_format, AudioSampleFormat::F32);
+    }
+}
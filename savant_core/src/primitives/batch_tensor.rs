@@ -0,0 +1,407 @@
+use crate::primitives::frame::{VideoFrameContent, VideoFrameProxy, VideoFrameTransformation};
+use crate::primitives::frame_batch::VideoFrameBatch;
+use crate::primitives::object::VideoObjectBBoxTransformation;
+use crate::primitives::raw_content::PixelFormat;
+use crate::transcoding::{transcode, Format};
+use anyhow::{bail, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TensorLayout {
+    #[default]
+    Nchw,
+    Nhwc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TensorDtype {
+    U8,
+    #[default]
+    F32,
+}
+
+/// The per-channel normalization of `F32` tensors: `(pixel * scale - mean) / std`. The
+/// empty mean and std are zeros and ones, `U8` tensors are not normalized.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Normalization {
+    pub scale: f32,
+    pub mean: Vec<f32>,
+    pub std: Vec<f32>,
+}
+
+impl Default for Normalization {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            mean: Vec::new(),
+            std: Vec::new(),
+        }
+    }
+}
+
+impl Normalization {
+    fn apply(&self, channel: usize, value: u8) -> f32 {
+        let mean = self.mean.get(channel).copied().unwrap_or(0.0);
+        let std = self.std.get(channel).copied().unwrap_or(1.0);
+        (value as f32 * self.scale - mean) / std
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResizeMode {
+    /// The frame is scaled to the tensor size ignoring the aspect ratio.
+    Stretch,
+    /// The frame is scaled keeping the aspect ratio and centered, the rest is padded.
+    #[default]
+    Letterbox,
+}
+
+/// How the frames are prepared for the model input.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreprocessingSpec {
+    pub width: usize,
+    pub height: usize,
+    /// The pixel format of the tensor channels: `Gray8`, `Rgb8`, `Bgr8`, `Rgba8` or `Bgra8`.
+    pub format: PixelFormat,
+    pub resize: ResizeMode,
+    /// The value of the padded pixels before the normalization.
+    pub padding: u8,
+}
+
+impl PreprocessingSpec {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            format: PixelFormat::Rgb8,
+            resize: ResizeMode::default(),
+            padding: 0,
+        }
+    }
+}
+
+/// How a frame is placed in the tensor: tensor coordinates are frame coordinates scaled and
+/// shifted by the padding.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct TensorFrameTransform {
+    pub frame_id: i64,
+    pub frame_width: usize,
+    pub frame_height: usize,
+    pub scale_x: f32,
+    pub scale_y: f32,
+    pub pad_left: usize,
+    pub pad_top: usize,
+    pub scaled_width: usize,
+    pub scaled_height: usize,
+}
+
+impl TensorFrameTransform {
+    fn new(frame_id: i64, width: usize, height: usize, spec: &PreprocessingSpec) -> Self {
+        let (scale_x, scale_y) = (
+            spec.width as f32 / width as f32,
+            spec.height as f32 / height as f32,
+        );
+        let (scale_x, scale_y) = match spec.resize {
+            ResizeMode::Stretch => (scale_x, scale_y),
+            ResizeMode::Letterbox => (scale_x.min(scale_y), scale_x.min(scale_y)),
+        };
+        let scaled_width = ((width as f32 * scale_x).round() as usize).clamp(1, spec.width);
+        let scaled_height = ((height as f32 * scale_y).round() as usize).clamp(1, spec.height);
+        Self {
+            frame_id,
+            frame_width: width,
+            frame_height: height,
+            scale_x,
+            scale_y,
+            pad_left: (spec.width - scaled_width) / 2,
+            pad_top: (spec.height - scaled_height) / 2,
+            scaled_width,
+            scaled_height,
+        }
+    }
+
+    /// Maps a point of the tensor to the frame.
+    ///
+    pub fn to_frame(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            (x - self.pad_left as f32) / self.scale_x,
+            (y - self.pad_top as f32) / self.scale_y,
+        )
+    }
+
+    /// The operations mapping boxes in the tensor coordinates to the frame, to be used with
+    /// [`VideoFrameProxy::transform_geometry`] or the object transformations.
+    ///
+    pub fn to_frame_operations(&self) -> Vec<VideoObjectBBoxTransformation> {
+        vec![
+            VideoObjectBBoxTransformation::Shift(-(self.pad_left as f32), -(self.pad_top as f32)),
+            VideoObjectBBoxTransformation::Scale(1.0 / self.scale_x, 1.0 / self.scale_y),
+        ]
+    }
+
+    /// The transformations in the terms of the frame transformation log.
+    ///
+    pub fn to_frame_transformations(
+        &self,
+        spec: &PreprocessingSpec,
+    ) -> Vec<VideoFrameTransformation> {
+        vec![
+            VideoFrameTransformation::InitialSize(
+                self.frame_width as u64,
+                self.frame_height as u64,
+            ),
+            VideoFrameTransformation::Scale(self.scaled_width as u64, self.scaled_height as u64),
+            VideoFrameTransformation::Padding(
+                self.pad_left as u64,
+                self.pad_top as u64,
+                (spec.width - self.scaled_width - self.pad_left) as u64,
+                (spec.height - self.scaled_height - self.pad_top) as u64,
+            ),
+            VideoFrameTransformation::ResultingSize(spec.width as u64, spec.height as u64),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TensorData {
+    U8(Vec<u8>),
+    F32(Vec<f32>),
+}
+
+/// The contiguous tensor of a batch, the frames follow in the ascending order of their ids.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchTensor {
+    pub data: TensorData,
+    pub layout: TensorLayout,
+    /// The dimensions in the order of the layout.
+    pub shape: [usize; 4],
+    pub transforms: Vec<TensorFrameTransform>,
+}
+
+impl BatchTensor {
+    /// The native-endian bytes of the tensor elements.
+    ///
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match &self.data {
+            TensorData::U8(data) => data.clone(),
+            TensorData::F32(data) => data.iter().flat_map(|v| v.to_ne_bytes()).collect(),
+        }
+    }
+}
+
+/// The pixels of the frame converted to the spec format.
+///
+fn frame_pixels(frame: &VideoFrameProxy, format: PixelFormat) -> Result<(Vec<u8>, usize, usize)> {
+    let content = frame.get_content();
+    let VideoFrameContent::Internal(internal) = content.as_ref() else {
+        bail!(
+            "Frame {} has no internal content",
+            frame.get_uuid_as_string()
+        )
+    };
+    let Some(current) = frame.get_format() else {
+        bail!("Frame {} has no codec", frame.get_uuid_as_string())
+    };
+    let (width, height) = (frame.get_width(), frame.get_height());
+    let pixels = transcode(
+        &current,
+        &Format::Raw(format),
+        &internal.to_packed(),
+        width,
+        height,
+    )?;
+    Ok((pixels, usize::try_from(width)?, usize::try_from(height)?))
+}
+
+/// Bilinear sampling of the channel at the fractional position.
+///
+fn sample(
+    pixels: &[u8],
+    width: usize,
+    height: usize,
+    channels: usize,
+    x: f32,
+    y: f32,
+    c: usize,
+) -> u8 {
+    let x = x.clamp(0.0, (width - 1) as f32);
+    let y = y.clamp(0.0, (height - 1) as f32);
+    let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+    let at = |x: usize, y: usize| pixels[(y * width + x) * channels + c] as f32;
+    let top = at(x0, y0) * (1.0 - fx) + at(x1, y0) * fx;
+    let bottom = at(x0, y1) * (1.0 - fx) + at(x1, y1) * fx;
+    (top * (1.0 - fy) + bottom * fy).round() as u8
+}
+
+impl VideoFrameBatch {
+    /// Assembles the tensor of the batch frames: the raw content of every frame is converted
+    /// to the spec format, resized and normalized. The transforms of the frames map the model
+    /// outputs back to the frames.
+    ///
+    pub fn to_tensor(
+        &self,
+        spec: &PreprocessingSpec,
+        layout: TensorLayout,
+        dtype: TensorDtype,
+        normalization: &Normalization,
+    ) -> Result<BatchTensor> {
+        let Some(channels) = spec.format.bytes_per_pixel() else {
+            bail!(
+                "The tensor format must have a single plane, {} is planar",
+                spec.format
+            )
+        };
+        if spec.width == 0 || spec.height == 0 {
+            bail!("The tensor size must be positive")
+        }
+        let mut ids = self.frames.keys().copied().collect::<Vec<_>>();
+        ids.sort();
+        let (w, h) = (spec.width, spec.height);
+        let frame_size = w * h * channels;
+        let mut values = vec![spec.padding; ids.len() * frame_size];
+        let mut transforms = Vec::with_capacity(ids.len());
+        for (n, id) in ids.iter().enumerate() {
+            let (pixels, width, height) = frame_pixels(&self.frames[id], spec.format)?;
+            if width == 0 || height == 0 {
+                bail!("Frame {} has no pixels", id)
+            }
+            let transform = TensorFrameTransform::new(*id, width, height, spec);
+            let tensor = &mut values[n * frame_size..(n + 1) * frame_size];
+            for ty in 0..transform.scaled_height {
+                let sy = (ty as f32 + 0.5) / transform.scale_y - 0.5;
+                for tx in 0..transform.scaled_width {
+                    let sx = (tx as f32 + 0.5) / transform.scale_x - 0.5;
+                    let (x, y) = (tx + transform.pad_left, ty + transform.pad_top);
+                    for c in 0..channels {
+                        let index = match layout {
+                            TensorLayout::Nchw => (c * h + y) * w + x,
+                            TensorLayout::Nhwc => (y * w + x) * channels + c,
+                        };
+                        tensor[index] = sample(&pixels, width, height, channels, sx, sy, c);
+                    }
+                }
+            }
+            transforms.push(transform);
+        }
+        let shape = match layout {
+            TensorLayout::Nchw => [ids.len(), channels, h, w],
+            TensorLayout::Nhwc => [ids.len(), h, w, channels],
+        };
+        let data = match dtype {
+            TensorDtype::U8 => TensorData::U8(values),
+            TensorDtype::F32 => TensorData::F32(
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| {
+                        let c = match layout {
+                            TensorLayout::Nchw => (i / (w * h)) % channels,
+                            TensorLayout::Nhwc => i % channels,
+                        };
+                        normalization.apply(c, *v)
+                    })
+                    .collect(),
+            ),
+        };
+        Ok(BatchTensor {
+            data,
+            layout,
+            shape,
+            transforms,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::batch_tensor::{
+        Normalization, PreprocessingSpec, ResizeMode, TensorData, TensorDtype, TensorLayout,
+    };
+    use crate::primitives::frame::{VideoFrameContent, VideoFrameProxy};
+    use crate::primitives::frame_batch::VideoFrameBatch;
+    use crate::primitives::raw_content::PixelFormat;
+    use crate::test::gen_frame;
+
+    fn raw_frame(width: i64, height: i64, rgb: [u8; 3]) -> VideoFrameProxy {
+        let mut frame = gen_frame();
+        frame.set_width(width);
+        frame.set_height(height);
+        frame.set_codec(Some(PixelFormat::Rgb8.codec()));
+        frame.set_content(VideoFrameContent::Internal(
+            rgb.repeat((width * height) as usize).into(),
+        ));
+        frame
+    }
+
+    #[test]
+    fn test_letterbox_nchw() -> anyhow::Result<()> {
+        let mut batch = VideoFrameBatch::new();
+        batch.add(2, raw_frame(4, 2, [10, 20, 30]));
+        batch.add(1, raw_frame(2, 2, [1, 2, 3]));
+        let mut spec = PreprocessingSpec::new(4, 4);
+        spec.padding = 255;
+        let tensor = batch.to_tensor(
+            &spec,
+            TensorLayout::Nchw,
+            TensorDtype::U8,
+            &Normalization::default(),
+        )?;
+        assert_eq!(tensor.shape, [2, 3, 4, 4]);
+        let TensorData::U8(data) = &tensor.data else {
+            panic!("U8 data expected")
+        };
+        // the square frame fills the tensor, the wide one is padded at the top and bottom
+        assert!(data[..16].iter().all(|v| *v == 1));
+        let second_red = &data[48..64];
+        assert_eq!(second_red[..4], [255; 4]);
+        assert_eq!(second_red[4..12], [10; 8]);
+        assert_eq!(second_red[12..], [255; 4]);
+
+        let transform = &tensor.transforms[1];
+        assert_eq!(transform.frame_id, 2);
+        assert_eq!((transform.pad_left, transform.pad_top), (0, 1));
+        assert_eq!(transform.to_frame(4.0, 3.0), (4.0, 2.0));
+        assert_eq!(transform.to_frame_transformations(&spec).len(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_stretch_nhwc_normalized() -> anyhow::Result<()> {
+        let mut batch = VideoFrameBatch::new();
+        batch.add(1, raw_frame(4, 2, [255, 0, 51]));
+        let spec = PreprocessingSpec {
+            format: PixelFormat::Bgr8,
+            resize: ResizeMode::Stretch,
+            ..PreprocessingSpec::new(2, 2)
+        };
+        let normalization = Normalization {
+            scale: 1.0 / 255.0,
+            mean: vec![0.0, 0.0, 0.5],
+            std: vec![1.0, 1.0, 0.5],
+        };
+        let tensor =
+            batch.to_tensor(&spec, TensorLayout::Nhwc, TensorDtype::F32, &normalization)?;
+        assert_eq!(tensor.shape, [1, 2, 2, 3]);
+        let TensorData::F32(data) = &tensor.data else {
+            panic!("F32 data expected")
+        };
+        assert!((data[0] - 0.2).abs() < 1e-6);
+        assert_eq!(data[1], 0.0);
+        assert!((data[2] - 1.0).abs() < 1e-6);
+        assert_eq!(tensor.to_bytes().len(), 12 * 4);
+
+        let spec = PreprocessingSpec {
+            format: PixelFormat::Nv12,
+            ..spec
+        };
+        assert!(batch
+            .to_tensor(&spec, TensorLayout::Nhwc, TensorDtype::F32, &normalization)
+            .is_err());
+        Ok(())
+    }
+}
@@ -10,7 +10,7 @@ use geo::{Area, BooleanOps};
 use lazy_static::lazy_static;
 use std::f32::consts::PI;
 use std::fmt;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 
 pub const BBOX_ELEMENT_UNDEFINED: f32 = 3.402_823_5e38_f32;
@@ -41,6 +41,9 @@ pub struct RBBoxData {
     pub height: AtomicF32,
     pub angle: AtomicF32,
     pub has_modifications: AtomicBool,
+    /// The number of the modifications, unlike the flag it is never reset.
+    #[serde(skip)]
+    pub modification_count: AtomicU64,
 }
 
 impl RBBoxData {
@@ -52,6 +55,7 @@ impl RBBoxData {
             height: height.into(),
             angle: angle.unwrap_or(BBOX_ELEMENT_UNDEFINED).into(),
             has_modifications: false.into(),
+            modification_count: 0.into(),
         }
     }
 }
@@ -76,6 +80,7 @@ impl Clone for RBBoxData {
     fn clone(&self) -> Self {
         Self {
             has_modifications: AtomicBool::new(self.has_modifications.load(Ordering::SeqCst)),
+            modification_count: AtomicU64::new(self.modification_count.load(Ordering::SeqCst)),
             xc: self.xc.clone(),
             yc: self.yc.clone(),
             width: self.width.clone(),
@@ -193,6 +198,15 @@ impl RBBox {
     }
     pub fn set_modifications(&self, value: bool) {
         self.0.has_modifications.store(value, Ordering::SeqCst);
+        if value {
+            self.0.modification_count.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+    /// The number of the modifications of the box, it grows with every change even if the
+    /// modification flag is reset.
+    ///
+    pub fn get_modification_count(&self) -> u64 {
+        self.0.modification_count.load(Ordering::SeqCst)
     }
     pub fn set_xc(&self, xc: f32) {
         self.0.xc.set(xc);
@@ -226,6 +240,7 @@ impl RBBox {
             height: height.into(),
             angle: angle.unwrap_or(BBOX_ELEMENT_UNDEFINED).into(),
             has_modifications: AtomicBool::new(false),
+            modification_count: AtomicU64::new(0),
         })
     }
     pub fn shift(&self, dx: f32, dy: f32) {
@@ -1,4 +1,6 @@
 use crate::primitives::{BBoxMetricType, RBBox};
+use crate::simd::{iou_one_to_many, BoxColumns};
+use crate::EPS;
 use geo::{Area, BooleanOps, MultiPolygon};
 use rayon::iter::ParallelIterator;
 use rayon::prelude::IntoParallelRefIterator;
@@ -65,7 +67,46 @@ pub fn calculate_union_area(bboxes: &[&RBBox]) -> MultiPolygon<f64> {
     union
 }
 
-pub fn associate_bboxes(
+fn axis_aligned_ltrb(bbox: &RBBox) -> Option<[f32; 4]> {
+    let (left, top, right, bottom) = bbox.as_ltrb().ok()?;
+    Some([left, top, right, bottom])
+}
+
+/// Associates axis-aligned boxes by IoU with the vectorized kernel. Boxes without area are
+/// not associated, like in [`RBBox::iou`].
+///
+fn associate_axis_aligned(
+    candidates: &[&RBBox],
+    owners: &[&RBBox],
+    threshold: f32,
+) -> Option<HashMap<usize, Vec<(usize, f32)>>> {
+    let mut owner_columns = BoxColumns::with_capacity(owners.len());
+    for o in owners {
+        owner_columns.push(axis_aligned_ltrb(o)?, o.get_area());
+    }
+    let candidate_ltrbs = candidates
+        .iter()
+        .map(|c| axis_aligned_ltrb(c))
+        .collect::<Option<Vec<_>>>()?;
+    let mut associations = HashMap::new();
+    let mut ious = vec![0.0; owners.len()];
+    for (ci, (c, ltrb)) in candidates.iter().zip(candidate_ltrbs).enumerate() {
+        let matched = associations.entry(ci).or_insert_with(Vec::new);
+        let area = c.get_area();
+        if area < EPS {
+            continue;
+        }
+        iou_one_to_many(ltrb, area, &owner_columns, &mut ious);
+        for (co, (o, iou)) in owners.iter().zip(&ious).enumerate() {
+            if o.get_area() >= EPS && *iou > threshold {
+                matched.push((co, *iou));
+            }
+        }
+    }
+    Some(associations)
+}
+
+fn associate_each(
     candidates: &[&RBBox],
     owners: &[&RBBox],
     metric: BBoxMetricType,
@@ -87,6 +128,20 @@ pub fn associate_bboxes(
             }
         }
     }
+    associations
+}
+
+pub fn associate_bboxes(
+    candidates: &[&RBBox],
+    owners: &[&RBBox],
+    metric: BBoxMetricType,
+    threshold: f32,
+) -> HashMap<usize, Vec<(usize, f32)>> {
+    let mut associations = match metric {
+        BBoxMetricType::IoU => associate_axis_aligned(candidates, owners, threshold),
+        _ => None,
+    }
+    .unwrap_or_else(|| associate_each(candidates, owners, metric, threshold));
     for (_, v) in associations.iter_mut() {
         v.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
     }
@@ -177,4 +232,18 @@ mod tests {
         assert!(matches!(lp2_associations.as_slice(), [(1, _), (0, _)]));
         assert!(lp3_associations.is_empty());
     }
+
+    #[test]
+    fn test_associate_axis_aligned_matches_each() {
+        let boxes = (0..20)
+            .map(|i| RBBox::ltrb(i as f32 * 2.0, 0.0, i as f32 * 2.0 + 5.0, 4.0))
+            .collect::<Vec<_>>();
+        let boxes = boxes.iter().collect::<Vec<_>>();
+        let fast = super::associate_axis_aligned(&boxes, &boxes, 0.1).unwrap();
+        let each = super::associate_each(&boxes, &boxes, BBoxMetricType::IoU, 0.1);
+        assert_eq!(fast, each);
+
+        let rotated = RBBox::new(10.0, 2.0, 5.0, 4.0, Some(30.0));
+        assert!(super::associate_axis_aligned(&[&rotated], &boxes, 0.1).is_none());
+    }
 }
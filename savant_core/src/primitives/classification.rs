@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// The sensitivity of the data held by a frame or an object, set by the processors which
+/// produce the data (e.g. a face detector classifies its objects as [`DataClassification::Pii`]).
+/// The levels are ordered, the data classified above the level allowed for a sink is redacted
+/// or rejected on serialization, see [`crate::protobuf::ClassificationPolicy`].
+///
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum DataClassification {
+    #[default]
+    Public,
+    Internal,
+    /// Personally identifiable information.
+    Pii,
+}
+
+impl DataClassification {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataClassification::Public => "public",
+            DataClassification::Internal => "internal",
+            DataClassification::Pii => "pii",
+        }
+    }
+
+    pub fn is_public(&self) -> bool {
+        *self == DataClassification::Public
+    }
+
+    pub(crate) fn to_level(self) -> u32 {
+        match self {
+            DataClassification::Public => 0,
+            DataClassification::Internal => 1,
+            DataClassification::Pii => 2,
+        }
+    }
+
+    /// The unknown levels of newer peers are treated as the most restrictive one.
+    ///
+    pub(crate) fn from_level(level: u32) -> Self {
+        match level {
+            0 => DataClassification::Public,
+            1 => DataClassification::Internal,
+            _ => DataClassification::Pii,
+        }
+    }
+}
+
+impl Display for DataClassification {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DataClassification {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "public" => Ok(DataClassification::Public),
+            "internal" => Ok(DataClassification::Internal),
+            "pii" => Ok(DataClassification::Pii),
+            _ => anyhow::bail!("Unknown data classification: {}", s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::classification::DataClassification;
+
+    #[test]
+    fn test_classification_order() -> anyhow::Result<()> {
+        assert!(DataClassification::Public < DataClassification::Internal);
+        assert!(DataClassification::Internal < DataClassification::Pii);
+        assert_eq!(
+            "PII".parse::<DataClassification>()?,
+            DataClassification::Pii
+        );
+        assert!("secret".parse::<DataClassification>().is_err());
+        assert_eq!(DataClassification::from_level(7), DataClassification::Pii);
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+use crate::primitives::raw_content::RawContentError;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The zstd level used for the frame content, a balance between the speed and the ratio
+/// suitable for compressing frames on every stage.
+///
+const ZSTD_LEVEL: i32 = 3;
+
+/// The default maximum decoded size of compressed content, enough for an 8K RGBA frame.
+///
+pub const DEFAULT_MAX_DECODED_CONTENT_SIZE: usize = 256 * 1024 * 1024;
+
+static MAX_DECODED_CONTENT_SIZE: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_DECODED_CONTENT_SIZE);
+
+/// Sets the maximum decoded size of compressed content, the content claiming a larger size
+/// is rejected before the buffer is allocated.
+///
+pub fn set_max_decoded_content_size(size: usize) {
+    MAX_DECODED_CONTENT_SIZE.store(size, Ordering::Relaxed);
+}
+
+pub fn get_max_decoded_content_size() -> usize {
+    MAX_DECODED_CONTENT_SIZE.load(Ordering::Relaxed)
+}
+
+/// The compression codec of the internal frame content. Compressed content carries the
+/// encoding tag and the decoded size, the bytes are decompressed on the first access.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContentEncoding {
+    /// Fast compression for the content moved between the stages.
+    Lz4,
+    /// Better ratio for the content stored in journals or sent over slow links.
+    Zstd,
+}
+
+impl ContentEncoding {
+    pub const ALL: [ContentEncoding; 2] = [ContentEncoding::Lz4, ContentEncoding::Zstd];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ContentEncoding::Lz4 => "lz4",
+            ContentEncoding::Zstd => "zstd",
+        }
+    }
+
+    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>, RawContentError> {
+        match self {
+            ContentEncoding::Lz4 => Ok(lz4_flex::block::compress(data)),
+            ContentEncoding::Zstd => zstd::bulk::compress(data, ZSTD_LEVEL)
+                .map_err(|e| RawContentError::ContentCodec(*self, e.to_string())),
+        }
+    }
+
+    /// Decompresses the data, the result must be exactly of the decoded size, which must not
+    /// exceed [`get_max_decoded_content_size`].
+    ///
+    pub fn decompress(&self, data: &[u8], size: usize) -> Result<Vec<u8>, RawContentError> {
+        let max = get_max_decoded_content_size();
+        if size > max {
+            return Err(RawContentError::ContentCodec(
+                *self,
+                format!("the decoded size {} exceeds the maximum {}", size, max),
+            ));
+        }
+        let decoded = match self {
+            ContentEncoding::Lz4 => lz4_flex::block::decompress(data, size)
+                .map_err(|e| RawContentError::ContentCodec(*self, e.to_string()))?,
+            ContentEncoding::Zstd => zstd::bulk::decompress(data, size)
+                .map_err(|e| RawContentError::ContentCodec(*self, e.to_string()))?,
+        };
+        if decoded.len() != size {
+            return Err(RawContentError::ContentCodec(
+                *self,
+                format!("{} bytes decoded, {} expected", decoded.len(), size),
+            ));
+        }
+        Ok(decoded)
+    }
+}
+
+impl FromStr for ContentEncoding {
+    type Err = RawContentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ContentEncoding::ALL
+            .into_iter()
+            .find(|e| e.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| RawContentError::UnknownContentEncoding(s.to_string()))
+    }
+}
+
+impl fmt::Display for ContentEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::content_encoding::{get_max_decoded_content_size, ContentEncoding};
+    use crate::primitives::raw_content::RawContentError;
+
+    #[test]
+    fn test_codecs() -> anyhow::Result<()> {
+        let data = (0..4096).map(|i| (i / 64) as u8).collect::<Vec<_>>();
+        for encoding in ContentEncoding::ALL {
+            let compressed = encoding.compress(&data)?;
+            assert!(compressed.len() < data.len() / 4);
+            assert_eq!(encoding.decompress(&compressed, data.len())?, data);
+            assert!(matches!(
+                encoding.decompress(&compressed, data.len() + 1),
+                Err(RawContentError::ContentCodec(..))
+            ));
+            assert_eq!(encoding.name().parse::<ContentEncoding>()?, encoding);
+        }
+        assert!("gzip".parse::<ContentEncoding>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decoded_size_limit() -> anyhow::Result<()> {
+        let compressed = ContentEncoding::Lz4.compress(&[0; 64])?;
+        assert!(matches!(
+            ContentEncoding::Lz4.decompress(&compressed, get_max_decoded_content_size() + 1),
+            Err(RawContentError::ContentCodec(..))
+        ));
+        Ok(())
+    }
+}
@@ -2,24 +2,39 @@ use crate::draw::DrawLabelKind;
 use crate::json_api::ToSerdeJsonValue;
 use crate::match_query::{and, IntExpression, MatchQuery, StringExpression};
 use crate::message::Message;
-use crate::primitives::frame_update::VideoFrameUpdate;
+use crate::pipeline::source_config::SourceConfig;
+use crate::primitives::attribute_propagation::AttributePropagation;
+use crate::primitives::classification::DataClassification;
+use crate::primitives::content_encoding::ContentEncoding;
+use crate::primitives::frame_merge::{merge_attributes, FrameMergePolicy, ObjectMergePolicy};
+use crate::primitives::frame_update::{AttributeUpdatePolicy, VideoFrameUpdate};
+use crate::primitives::frozen_objects::{check_objects_mutable, FrozenObjects, FrozenObjectsError};
+use crate::primitives::geo::GeoPosition;
 use crate::primitives::object::private::{
     SealedObjectOperations, SealedWithFrame, SealedWithParent,
 };
 use crate::primitives::object::{
-    BorrowedVideoObject, IdCollisionResolutionPolicy, ObjectAccess, ObjectOperations, VideoObject,
-    VideoObjectBBoxTransformation, VideoObjectBuilder,
+    BorrowedVideoObject, IdCollisionResolutionPolicy, ObjectAccess, ObjectOperations, OrphanPolicy,
+    VideoObject, VideoObjectBBoxTransformation, VideoObjectBuilder,
 };
+use crate::primitives::processing_hints::ProcessingHints;
+use crate::primitives::query_cache::{QueryCache, QueryCacheStats};
+use crate::primitives::raw_content::{InternalFrame, RawLayout};
+use crate::primitives::representation::ContentRepresentation;
+use crate::primitives::userdata::UserData;
 use crate::primitives::{Attribute, RBBox, WithAttributes};
 use crate::rwlock::{SavantArcRwLock, SavantRwLock};
 use crate::trace;
+use crate::transcoding::{transcode, Format, TranscodingError};
 use crate::utils::iter::fiter_map_with_control_flow;
-use crate::utils::uuid_v7::incremental_uuid_v7;
+use crate::utils::uuid_v7::{frame_uuid, uuid_v7_timestamp_ms};
 use crate::version;
 use anyhow::{anyhow, bail};
 use derive_builder::Builder;
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
+use parking_lot::RwLockWriteGuard;
 use serde_json::Value;
+use std::collections::BTreeMap;
 use std::fmt::{Debug, Formatter};
 use std::hash::{Hash, Hasher};
 use std::mem;
@@ -70,7 +85,7 @@ impl ExternalFrame {
 #[derive(Debug, PartialEq, Clone)]
 pub enum VideoFrameContent {
     External(ExternalFrame),
-    Internal(Vec<u8>),
+    Internal(InternalFrame),
     None,
 }
 
@@ -143,9 +158,38 @@ pub struct VideoFrame {
     #[builder(setter(skip))]
     pub attributes: Vec<Attribute>,
     #[builder(setter(skip))]
+    pub processing_hints: ProcessingHints,
+    /// The pose of the camera of the source.
+    #[builder(setter(skip))]
+    pub geo_pose: Option<GeoPosition>,
+    /// The sensitivity of the frame data, the objects inherit it.
+    #[builder(setter(skip))]
+    pub classification: DataClassification,
+    /// The additional content representations by key, the primary content is not among them.
+    #[builder(setter(skip))]
+    pub(crate) representations: BTreeMap<String, ContentRepresentation>,
+    #[builder(setter(skip))]
     pub(crate) objects: HashMap<i64, VideoObject>,
     #[builder(setter(skip))]
     pub(crate) max_object_id: i64,
+    #[builder(setter(skip))]
+    pub(crate) frozen_objects: Vec<FrozenObjects>,
+    /// The pipeline stage holding the frame, reported when frozen objects are mutated.
+    #[builder(setter(skip))]
+    pub(crate) stage: Option<String>,
+    /// The configuration of the source resolved by the pipeline, not serialized.
+    #[builder(setter(skip))]
+    pub(crate) source_config: Option<Arc<SourceConfig>>,
+    /// The cache of the query results, `None` unless enabled for the frame.
+    #[builder(setter(skip))]
+    pub(crate) query_cache: Option<QueryCache>,
+    /// The number of the modifications of the frame, the cached query results are valid
+    /// within one generation.
+    #[builder(setter(skip))]
+    pub(crate) generation: u64,
+    /// The frame attributes passed to the objects and events derived from the frame.
+    #[builder(setter(skip))]
+    pub(crate) attribute_propagation: Option<Arc<AttributePropagation>>,
 }
 
 const DEFAULT_TRANSFORMATIONS_COUNT: usize = 4;
@@ -158,7 +202,7 @@ impl Default for VideoFrame {
             previous_frame_seq_id: None,
             previous_keyframe: None,
             source_id: String::new(),
-            uuid: incremental_uuid_v7().as_u128(),
+            uuid: frame_uuid().as_u128(),
             creation_timestamp_ns: SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .unwrap()
@@ -176,8 +220,18 @@ impl Default for VideoFrame {
             content: Arc::new(VideoFrameContent::None),
             transformations: Vec::with_capacity(DEFAULT_TRANSFORMATIONS_COUNT),
             attributes: Vec::with_capacity(DEFAULT_ATTRIBUTES_COUNT),
+            processing_hints: ProcessingHints::default(),
+            geo_pose: None,
+            classification: DataClassification::default(),
+            representations: BTreeMap::new(),
             objects: HashMap::with_capacity(DEFAULT_OBJECTS_COUNT),
             max_object_id: 0,
+            frozen_objects: Vec::new(),
+            stage: None,
+            source_config: None,
+            query_cache: None,
+            generation: 0,
+            attribute_propagation: None,
         }
     }
 }
@@ -196,7 +250,7 @@ impl ToSerdeJsonValue for VideoFrame {
             .iter()
             .map(|o| o.to_serde_json_value())
             .collect::<Vec<_>>();
-        serde_json::json!(
+        let mut value = serde_json::json!(
             {
                 "previous_frame_seq_id": self.previous_frame_seq_id,
                 "previous_keyframe": previous_keyframe,
@@ -217,10 +271,28 @@ impl ToSerdeJsonValue for VideoFrame {
                 "duration": self.duration,
                 "content": self.content.to_serde_json_value(),
                 "transformations": self.transformations.iter().map(|t| t.to_serde_json_value()).collect::<Vec<_>>(),
-                "attributes": self.attributes.iter().filter_map(|v| if v.is_hidden { None } else { Some(v.to_serde_json_value()) }).collect::<Vec<_>>(),
+                "attributes": self.attributes.iter().filter_map(|v| if v.is_hidden || v.is_expired() { None } else { Some(v.to_serde_json_value()) }).collect::<Vec<_>>(),
                 "objects": objects,
             }
-        )
+        );
+        if !self.processing_hints.is_default() {
+            value["processing_hints"] = serde_json::json!(self.processing_hints);
+        }
+        if let Some(geo_pose) = &self.geo_pose {
+            value["geo_pose"] = serde_json::json!(geo_pose);
+        }
+        if !self.classification.is_public() {
+            value["classification"] = serde_json::json!(self.classification);
+        }
+        if !self.representations.is_empty() {
+            value["representations"] = self
+                .representations
+                .iter()
+                .map(|(key, r)| (key.clone(), r.to_serde_json_value()))
+                .collect::<serde_json::Map<_, _>>()
+                .into();
+        }
+        value
     }
 }
 
@@ -261,6 +333,16 @@ impl VideoFrame {
         &mut self.objects
     }
 
+    /// The total number of the modifications of the object boxes, it changes whenever a box
+    /// is modified in place.
+    ///
+    pub(crate) fn box_modification_count(&self) -> u64 {
+        self.objects
+            .values()
+            .flat_map(|o| std::iter::once(&o.detection_box).chain(o.track_box.as_ref()))
+            .fold(0, |total, b| total.wrapping_add(b.get_modification_count()))
+    }
+
     pub fn smart_copy(&self) -> Self {
         let mut frame = self.clone();
         frame.objects.clear();
@@ -279,6 +361,19 @@ impl VideoFrame {
         });
     }
 
+    pub(crate) fn check_objects_mutable(
+        &self,
+        namespace: &str,
+        operation: &str,
+    ) -> Result<(), FrozenObjectsError> {
+        check_objects_mutable(
+            &self.frozen_objects,
+            self.stage.as_deref(),
+            namespace,
+            operation,
+        )
+    }
+
     pub fn restore_all_temporary_attributes(
         &mut self,
         frame_attributes: Vec<Attribute>,
@@ -360,7 +455,7 @@ impl WithAttributes for VideoFrameProxy {
     where
         F: FnOnce(&mut Vec<Attribute>) -> R,
     {
-        let mut bind = trace!(self.inner.write());
+        let mut bind = self.write();
         f(&mut bind.attributes)
     }
 }
@@ -378,7 +473,7 @@ impl VideoFrameProxy {
         inner.stream_compatibility_hash()
     }
     pub fn exclude_all_temporary_attributes(&self) {
-        let mut inner = trace!(self.inner.write());
+        let mut inner = self.write();
         inner.exclude_all_temporary_attributes()
     }
 
@@ -391,6 +486,17 @@ impl VideoFrameProxy {
         self as *const Self as usize
     }
 
+    /// Returns the address of the shared frame state. Unlike [`Self::memory_handle`], all
+    /// proxies pointing to the same frame return the same value.
+    ///
+    pub fn shared_handle(&self) -> usize {
+        Arc::as_ptr(&self.inner.0) as usize
+    }
+
+    pub fn is_same_frame(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner.0, &other.inner.0)
+    }
+
     pub fn transform_geometry(&self, ops: &Vec<VideoObjectBBoxTransformation>) {
         let objs = self.get_all_objects();
         for mut obj in objs {
@@ -406,10 +512,7 @@ impl VideoFrameProxy {
     }
 
     pub fn prepare_after_load(&self) {
-        let objects = self.get_all_objects();
-        for mut o in objects {
-            o.attach_to_video_frame(self.clone());
-        }
+        self.fix_object_owned_frame();
     }
 
     pub(crate) fn get_inner(&self) -> SavantArcRwLock<Box<VideoFrame>> {
@@ -433,16 +536,65 @@ impl VideoFrameProxy {
             .collect()
     }
 
+    /// Takes the write lock to modify the frame, starting a new generation of the frame.
+    ///
+    pub(crate) fn write(&self) -> RwLockWriteGuard<'_, Box<VideoFrame>> {
+        let mut inner = trace!(self.inner.write());
+        inner.generation = inner.generation.wrapping_add(1);
+        inner
+    }
+
     pub fn access_objects(&self, q: &MatchQuery) -> Vec<BorrowedVideoObject> {
         let inner = trace!(self.inner.read_recursive());
+        let mut pending = None;
+        if let Some(cache) = &inner.query_cache {
+            let key = q.to_json();
+            let generation = inner.generation;
+            let box_modifications = inner.box_modification_count();
+            if let Some(ids) = cache.get(&key, generation, box_modifications) {
+                return ids
+                    .into_iter()
+                    .map(|id| BorrowedVideoObject(self.into(), id))
+                    .collect();
+            }
+            pending = Some((key, generation, box_modifications));
+        }
         let objects = inner.objects.values().cloned().collect::<Vec<_>>();
         drop(inner);
-        fiter_map_with_control_flow(objects, |o| q.execute_with_new_context(o))
+        let ids = fiter_map_with_control_flow(objects, |o| q.execute_with_new_context(o))
             .iter()
-            .map(|o| BorrowedVideoObject(self.into(), o.get_id()))
+            .map(|o| o.get_id())
+            .collect::<Vec<_>>();
+        if let Some((key, generation, box_modifications)) = pending {
+            // the results are stored only if the frame has not been modified meanwhile
+            let inner = trace!(self.inner.read_recursive());
+            if let Some(cache) = &inner.query_cache {
+                if inner.generation == generation
+                    && inner.box_modification_count() == box_modifications
+                {
+                    cache.put(key, generation, box_modifications, ids.clone());
+                }
+            }
+        }
+        ids.into_iter()
+            .map(|id| BorrowedVideoObject(self.into(), id))
             .collect()
     }
 
+    /// Enables or disables the cache of the [`Self::access_objects`] results. While enabled,
+    /// a query evaluated again against the unmodified frame returns the stored objects; any
+    /// modification of the frame, its objects or their boxes invalidates the results.
+    ///
+    pub fn set_query_cache(&self, enabled: bool) {
+        let mut inner = self.write();
+        inner.query_cache = enabled.then(QueryCache::default);
+    }
+
+    pub fn get_query_cache_stats(&self) -> Option<QueryCacheStats> {
+        let inner = trace!(self.inner.read_recursive());
+        inner.query_cache.as_ref().map(|c| c.get_stats())
+    }
+
     pub fn get_json(&self) -> String {
         serde_json::to_string(&self.to_serde_json_value()).unwrap()
     }
@@ -467,8 +619,29 @@ impl VideoFrameProxy {
             .collect()
     }
 
+    /// Removes the objects with the ids. Nothing is removed when an object belongs to a
+    /// frozen namespace, the violation is logged.
+    ///
     pub fn delete_objects_with_ids(&self, ids: &[i64]) -> Vec<VideoObject> {
-        let mut inner = trace!(self.inner.write());
+        self.try_delete_objects_with_ids(ids).unwrap_or_else(|e| {
+            log::error!(target: "savant_rs::frozen_objects", "{}", e);
+            Vec::new()
+        })
+    }
+
+    /// Removes the objects with the ids, fails without removing anything when an object
+    /// belongs to a frozen namespace.
+    ///
+    pub fn try_delete_objects_with_ids(
+        &self,
+        ids: &[i64],
+    ) -> Result<Vec<VideoObject>, FrozenObjectsError> {
+        let mut inner = self.write();
+        for id in ids {
+            if let Some(object) = inner.objects.get(id) {
+                inner.check_objects_mutable(&object.namespace, "delete")?;
+            }
+        }
         let objects = mem::take(&mut inner.objects);
         let (mut retained, removed): (HashMap<i64, VideoObject>, HashMap<i64, VideoObject>) =
             objects.into_iter().partition(|(id, _)| !ids.contains(id));
@@ -483,14 +656,14 @@ impl VideoFrameProxy {
         inner.objects = retained;
         drop(inner);
 
-        removed
+        Ok(removed
             .into_values()
             .map(|mut o| {
                 o.parent_id = None;
                 o.frame = None;
                 o
             })
-            .collect()
+            .collect())
     }
 
     pub fn object_exists(&self, id: i64) -> bool {
@@ -504,16 +677,188 @@ impl VideoFrameProxy {
         self.delete_objects_with_ids(&ids)
     }
 
+    /// Removes the objects matching the query, fails without removing anything when an
+    /// object belongs to a frozen namespace.
+    ///
+    pub fn try_delete_objects(
+        &self,
+        q: &MatchQuery,
+    ) -> Result<Vec<VideoObject>, FrozenObjectsError> {
+        let objs = self.access_objects(q);
+        let ids = objs.iter().map(|o| o.get_id()).collect::<Vec<_>>();
+        self.try_delete_objects_with_ids(&ids)
+    }
+
+    /// Removes the objects matching the query and fixes up the parents of the retained
+    /// objects according to the policy, so no object refers to a removed parent. The removal
+    /// and the fix-ups happen under a single lock; nothing is changed when a removed object
+    /// belongs to a frozen namespace.
+    ///
+    pub fn prune(&self, q: &MatchQuery, policy: OrphanPolicy) -> anyhow::Result<Vec<VideoObject>> {
+        let matched = self.access_objects(q);
+        let mut inner = self.write();
+        let mut removed = matched
+            .iter()
+            .map(|o| o.get_id())
+            .filter(|id| inner.objects.contains_key(id))
+            .collect::<HashSet<_>>();
+        let is_orphan = |o: &VideoObject, removed: &HashSet<i64>| {
+            !removed.contains(&o.id) && o.parent_id.is_some_and(|p| removed.contains(&p))
+        };
+
+        if policy == OrphanPolicy::Cascade {
+            loop {
+                let descendants = inner
+                    .objects
+                    .values()
+                    .filter(|o| is_orphan(o, &removed))
+                    .map(|o| o.id)
+                    .collect::<Vec<_>>();
+                if descendants.is_empty() {
+                    break;
+                }
+                removed.extend(descendants);
+            }
+        }
+
+        for id in &removed {
+            inner.check_objects_mutable(&inner.objects[id].namespace, "prune")?;
+        }
+
+        let new_parents = inner
+            .objects
+            .values()
+            .filter(|o| is_orphan(o, &removed))
+            .map(|o| {
+                let mut parent = o.parent_id;
+                if policy == OrphanPolicy::Reparent {
+                    // bounded by the number of removed objects to tolerate parent cycles
+                    for _ in 0..=removed.len() {
+                        match parent {
+                            Some(id) if removed.contains(&id) => {
+                                parent = inner.objects.get(&id).and_then(|p| p.parent_id);
+                            }
+                            _ => break,
+                        }
+                    }
+                }
+                (o.id, parent.filter(|id| !removed.contains(id)))
+            })
+            .collect::<Vec<_>>();
+        for (id, parent) in new_parents {
+            inner.objects.get_mut(&id).unwrap().parent_id = parent;
+        }
+
+        let mut removed = removed.into_iter().collect::<Vec<_>>();
+        removed.sort_unstable();
+        Ok(removed
+            .into_iter()
+            .filter_map(|id| inner.objects.remove(&id))
+            .map(|mut o| {
+                o.parent_id = None;
+                o.frame = None;
+                o
+            })
+            .collect())
+    }
+
+    /// Merges the objects and the attributes of the other frame, e.g. a copy of the frame
+    /// annotated by a parallel branch. The added objects get new ids and their parents are
+    /// remapped accordingly. Nothing is changed when the merge fails.
+    ///
+    /// Returns the mapping of the object ids of the other frame to the ids in the frame.
+    ///
+    pub fn merge_from(
+        &self,
+        other: &VideoFrameProxy,
+        policy: &FrameMergePolicy,
+    ) -> anyhow::Result<HashMap<i64, i64>> {
+        if self.is_same_frame(other) {
+            bail!("The frame cannot be merged with itself");
+        }
+        let other_inner = trace!(other.inner.read_recursive());
+        let mut other_objects = other_inner.objects.clone();
+        let other_attributes = other_inner.attributes.clone();
+        drop(other_inner);
+        let mut other_ids = other_objects.keys().copied().collect::<Vec<_>>();
+        other_ids.sort_unstable();
+
+        let mut inner = self.write();
+        if let AttributeUpdatePolicy::Error = policy.frame_attribute_policy {
+            if let Some(attr) = other_attributes
+                .iter()
+                .find(|a| inner.contains_attribute(&a.namespace, &a.name))
+            {
+                bail!(
+                    "Attribute with name '{}' created by '{}' already exists in the frame.",
+                    attr.name,
+                    attr.namespace
+                );
+            }
+        }
+
+        let mut objects = inner.objects.clone();
+        let mut max_object_id = objects.keys().copied().fold(inner.max_object_id, i64::max);
+        let mut mapping = HashMap::with_capacity(other_ids.len());
+        let mut merged = HashSet::new();
+        for id in &other_ids {
+            let object = &other_objects[id];
+            let same = policy.object_policy == ObjectMergePolicy::MergeSameObjects
+                && objects
+                    .get(id)
+                    .is_some_and(|o| o.namespace == object.namespace && o.label == object.label);
+            if same {
+                merged.insert(*id);
+                mapping.insert(*id, *id);
+            } else {
+                max_object_id += 1;
+                mapping.insert(*id, max_object_id);
+            }
+        }
+
+        for id in other_ids {
+            let mut object = other_objects.remove(&id).unwrap();
+            if merged.contains(&id) {
+                inner.check_objects_mutable(&object.namespace, "merge attributes of")?;
+                merge_attributes(
+                    objects.get_mut(&id).unwrap(),
+                    &object.attributes,
+                    &policy.object_attribute_policy,
+                    &format!("the object with ID {}", id),
+                )?;
+            } else {
+                inner.check_objects_mutable(&object.namespace, "add")?;
+                object.id = mapping[&id];
+                object.parent_id = object.parent_id.and_then(|p| mapping.get(&p).copied());
+                object.frame = Some(self.into());
+                objects.insert(object.id, object);
+            }
+        }
+
+        inner.objects = objects;
+        inner.max_object_id = max_object_id;
+        merge_attributes(
+            &mut **inner,
+            &other_attributes,
+            &policy.frame_attribute_policy,
+            "the frame",
+        )?;
+        Ok(mapping)
+    }
+
     pub fn get_object(&self, id: i64) -> Option<BorrowedVideoObject> {
         let inner = trace!(self.inner.read_recursive());
         let obj = inner.objects.get(&id);
         obj.map(|_| BorrowedVideoObject(self.into(), id))
     }
 
+    /// Attaches the objects to the frame, frozen objects included.
+    ///
     fn fix_object_owned_frame(&self) {
-        self.get_all_objects()
-            .into_iter()
-            .for_each(|mut o| o.attach_to_video_frame(self.clone()));
+        let mut inner = self.write();
+        for o in inner.objects.values_mut() {
+            o.frame = Some(self.into());
+        }
     }
 
     pub fn set_draw_label(&self, q: &MatchQuery, label: DrawLabelKind) {
@@ -573,7 +918,9 @@ impl VideoFrameProxy {
     pub fn clear_parent(&self, q: &MatchQuery) -> Vec<BorrowedVideoObject> {
         let mut objects = self.access_objects(q);
         objects.iter_mut().for_each(|o| {
-            o.set_parent(None).unwrap();
+            if let Err(e) = o.set_parent(None) {
+                log::error!(target: "savant_rs::frozen_objects", "{}", e);
+            }
         });
         objects
     }
@@ -635,7 +982,17 @@ impl VideoFrameProxy {
 
         let object_id = object.get_id();
         let new_id = self.get_max_object_id() + 1;
-        let mut inner = trace!(self.inner.write());
+        let mut inner = self.write();
+        inner.check_objects_mutable(&object.namespace, "add")?;
+        if let (Some(existing), IdCollisionResolutionPolicy::Overwrite) =
+            (inner.objects.get(&object_id), &policy)
+        {
+            inner.check_objects_mutable(&existing.namespace, "overwrite")?;
+        }
+        if let Some(propagation) = inner.attribute_propagation.clone() {
+            let frame_uuid = Uuid::from_u128(inner.uuid).to_string();
+            propagation.apply_to_object(&frame_uuid, &inner.attributes, &mut object);
+        }
         object.attach_to_video_frame(self.clone());
         let assigned_object_id = if inner.objects.contains_key(&object_id) {
             match policy {
@@ -669,6 +1026,39 @@ impl VideoFrameProxy {
         inner.max_object_id
     }
 
+    /// Freezes the objects of the namespace, or all objects when the namespace is `None`,
+    /// after the stage. Freezing is permanent for the frame.
+    ///
+    pub fn freeze_objects(&self, namespace: Option<&str>, stage: &str) {
+        let frozen = FrozenObjects::new(namespace, stage);
+        let mut inner = self.write();
+        if !inner.frozen_objects.contains(&frozen) {
+            inner.frozen_objects.push(frozen);
+        }
+    }
+
+    pub fn get_frozen_objects(&self) -> Vec<FrozenObjects> {
+        trace!(self.inner.read_recursive()).frozen_objects.clone()
+    }
+
+    /// Fails when the object exists and belongs to a frozen namespace.
+    ///
+    pub(crate) fn check_object_mutable(
+        &self,
+        id: i64,
+        operation: &str,
+    ) -> Result<(), FrozenObjectsError> {
+        let inner = trace!(self.inner.read_recursive());
+        match inner.objects.get(&id) {
+            Some(object) => inner.check_objects_mutable(&object.namespace, operation),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn set_stage(&self, stage: Option<String>) {
+        self.write().stage = stage;
+    }
+
     pub(crate) fn update_objects(&self, update: &VideoFrameUpdate) -> anyhow::Result<()> {
         use crate::primitives::frame_update::ObjectUpdatePolicy::*;
         let other_inner = update.objects.clone();
@@ -734,14 +1124,14 @@ impl VideoFrameProxy {
         use crate::primitives::frame_update::AttributeUpdatePolicy::*;
         match &update.frame_attribute_policy {
             ReplaceWithForeign => {
-                let mut inner = trace!(self.inner.write());
+                let mut inner = self.write();
                 let other_inner = update.get_frame_attributes().clone();
                 other_inner.iter().for_each(|a| {
                     inner.set_attribute(a.clone());
                 });
             }
             KeepOwn => {
-                let mut inner = trace!(self.inner.write());
+                let mut inner = self.write();
                 let other_inner = update.get_frame_attributes();
                 for attr in other_inner {
                     if inner.get_attribute(&attr.namespace, &attr.name).is_none() {
@@ -750,7 +1140,7 @@ impl VideoFrameProxy {
                 }
             }
             Error => {
-                let mut inner = trace!(self.inner.write());
+                let mut inner = self.write();
                 let other_inner = update.get_frame_attributes().clone();
                 for attr in other_inner {
                     let key = (attr.namespace.clone(), attr.name.clone());
@@ -801,7 +1191,26 @@ impl VideoFrameProxy {
         Ok(())
     }
 
+    fn check_update_allowed(&self, update: &VideoFrameUpdate) -> Result<(), FrozenObjectsError> {
+        let inner = trace!(self.inner.read_recursive());
+        if inner.frozen_objects.is_empty() {
+            return Ok(());
+        }
+        for (id, _) in update.get_object_attributes() {
+            if let Some(object) = inner.objects.get(id) {
+                inner.check_objects_mutable(&object.namespace, "update attributes of")?;
+            }
+        }
+        for (object, _) in &update.objects {
+            inner.check_objects_mutable(&object.namespace, "add")?;
+        }
+        Ok(())
+    }
+
+    /// Applies the update. Nothing is applied when the update touches frozen objects.
+    ///
     pub fn update(&self, update: &VideoFrameUpdate) -> anyhow::Result<()> {
+        self.check_update_allowed(update)?;
         self.update_frame_attributes(update)?;
         self.update_object_attributes(update)?;
         self.update_objects(update)?;
@@ -853,7 +1262,7 @@ impl VideoFrameProxy {
     }
 
     pub(crate) fn set_previous_frame_seq_id(&mut self, previous_frame_seq_id: Option<i64>) {
-        let mut inner = trace!(self.inner.write());
+        let mut inner = self.write();
         inner.previous_frame_seq_id = previous_frame_seq_id;
     }
 
@@ -867,17 +1276,53 @@ impl VideoFrameProxy {
     }
 
     pub(crate) fn set_previous_keyframe(&mut self, previous_keyframe: Option<u128>) {
-        let mut inner = trace!(self.inner.write());
+        let mut inner = self.write();
         inner.previous_keyframe = previous_keyframe;
     }
 
+    /// The configuration of the source resolved when the frame was added to the pipeline.
+    ///
+    pub fn get_source_config(&self) -> Option<Arc<SourceConfig>> {
+        trace!(self.inner.read_recursive()).source_config.clone()
+    }
+
+    pub(crate) fn set_source_config(&mut self, source_config: Option<Arc<SourceConfig>>) {
+        let mut inner = self.write();
+        inner.source_config = source_config;
+    }
+
+    /// Sets the frame attributes passed to the objects added to the frame and to the events
+    /// created from it, `None` disables the propagation.
+    ///
+    pub fn set_attribute_propagation(&self, propagation: Option<Arc<AttributePropagation>>) {
+        let mut inner = self.write();
+        inner.attribute_propagation = propagation;
+    }
+
+    pub fn get_attribute_propagation(&self) -> Option<Arc<AttributePropagation>> {
+        trace!(self.inner.read_recursive())
+            .attribute_propagation
+            .clone()
+    }
+
+    /// Creates an event of the frame source carrying the frame attributes selected by the
+    /// attribute propagation.
+    ///
+    pub fn create_user_data(&self) -> UserData {
+        let mut data = UserData::new(&self.get_source_id());
+        if let Some(propagation) = self.get_attribute_propagation() {
+            propagation.apply_to_user_data(self, &mut data);
+        }
+        data
+    }
+
     pub fn set_source_id(&mut self, source_id: &str) {
-        let mut inner = trace!(self.inner.write());
+        let mut inner = self.write();
         inner.source_id = source_id.to_string();
     }
 
     pub fn set_time_base(&mut self, time_base: (i32, i32)) {
-        let mut inner = trace!(self.inner.write());
+        let mut inner = self.write();
         inner.time_base = time_base;
     }
     pub fn get_time_base(&self) -> (i32, i32) {
@@ -896,12 +1341,19 @@ impl VideoFrameProxy {
         self.get_uuid().to_string()
     }
 
+    /// The creation time in milliseconds since the UNIX epoch carried by the uuid, when the
+    /// frame has a version 7 uuid.
+    ///
+    pub fn get_uuid_timestamp_ms(&self) -> Option<u64> {
+        uuid_v7_timestamp_ms(&self.get_uuid())
+    }
+
     pub fn get_creation_timestamp_ns(&self) -> u128 {
         trace!(self.inner.read_recursive()).creation_timestamp_ns
     }
 
     pub fn set_creation_timestamp_ns(&mut self, creation_timestamp_ns: u128) {
-        let mut inner = trace!(self.inner.write());
+        let mut inner = self.write();
         inner.creation_timestamp_ns = creation_timestamp_ns;
     }
 
@@ -910,7 +1362,7 @@ impl VideoFrameProxy {
     }
     pub fn set_pts(&mut self, pts: i64) {
         assert!(pts >= 0, "pts must be greater than or equal to 0");
-        let mut inner = trace!(self.inner.write());
+        let mut inner = self.write();
         inner.pts = pts;
     }
 
@@ -919,7 +1371,7 @@ impl VideoFrameProxy {
     }
 
     pub fn set_framerate(&mut self, framerate: &str) {
-        let mut inner = trace!(self.inner.write());
+        let mut inner = self.write();
         inner.framerate = framerate.to_string();
     }
 
@@ -929,7 +1381,7 @@ impl VideoFrameProxy {
 
     pub fn set_width(&mut self, width: i64) {
         assert!(width > 0, "width must be greater than 0");
-        let mut inner = trace!(self.inner.write());
+        let mut inner = self.write();
         inner.width = width;
     }
 
@@ -939,7 +1391,7 @@ impl VideoFrameProxy {
 
     pub fn set_height(&mut self, height: i64) {
         assert!(height > 0, "height must be greater than 0");
-        let mut inner = trace!(self.inner.write());
+        let mut inner = self.write();
         inner.height = height;
     }
 
@@ -953,7 +1405,7 @@ impl VideoFrameProxy {
             dts.is_none() || dts.unwrap() >= 0,
             "dts must be greater than or equal to 0"
         );
-        let mut inner = trace!(self.inner.write());
+        let mut inner = self.write();
         inner.dts = dts;
     }
 
@@ -967,7 +1419,7 @@ impl VideoFrameProxy {
             duration.is_none() || duration.unwrap() >= 0,
             "duration must be greater than or equal to 0"
         );
-        let mut inner = trace!(self.inner.write());
+        let mut inner = self.write();
         inner.duration = duration;
     }
 
@@ -977,7 +1429,7 @@ impl VideoFrameProxy {
     }
 
     pub fn set_transcoding_method(&mut self, transcoding_method: VideoFrameTranscodingMethod) {
-        let mut inner = trace!(self.inner.write());
+        let mut inner = self.write();
         inner.transcoding_method = transcoding_method;
     }
 
@@ -987,17 +1439,17 @@ impl VideoFrameProxy {
     }
 
     pub fn set_codec(&mut self, codec: Option<String>) {
-        let mut inner = trace!(self.inner.write());
+        let mut inner = self.write();
         inner.codec = codec;
     }
 
     pub fn clear_transformations(&mut self) {
-        let mut inner = trace!(self.inner.write());
+        let mut inner = self.write();
         inner.transformations.clear();
     }
 
     pub fn add_transformation(&mut self, transformation: VideoFrameTransformation) {
-        let mut inner = trace!(self.inner.write());
+        let mut inner = self.write();
         inner.transformations.push(transformation);
     }
 
@@ -1012,23 +1464,220 @@ impl VideoFrameProxy {
     }
 
     pub fn set_keyframe(&mut self, keyframe: Option<bool>) {
-        let mut inner = trace!(self.inner.write());
+        let mut inner = self.write();
         inner.keyframe = keyframe;
     }
 
+    pub fn get_processing_hints(&self) -> ProcessingHints {
+        let inner = trace!(self.inner.read_recursive());
+        inner.processing_hints.clone()
+    }
+
+    pub fn set_processing_hints(&mut self, hints: ProcessingHints) {
+        let mut inner = self.write();
+        inner.processing_hints = hints;
+    }
+
+    pub fn get_geo_pose(&self) -> Option<GeoPosition> {
+        let inner = trace!(self.inner.read_recursive());
+        inner.geo_pose
+    }
+
+    /// Sets the pose of the camera of the source, the pipelines set it from the `geo` key of
+    /// the source configuration when the frame has none.
+    ///
+    pub fn set_geo_pose(&mut self, pose: Option<GeoPosition>) {
+        let mut inner = self.write();
+        inner.geo_pose = pose;
+    }
+
+    pub fn get_classification(&self) -> DataClassification {
+        let inner = trace!(self.inner.read_recursive());
+        inner.classification
+    }
+
+    /// Classifies the frame data, the classification applies to the content, the attributes
+    /// and the objects of the frame. The classification is not changed while the frame has
+    /// frozen objects, see [`VideoFrameProxy::freeze_objects`].
+    ///
+    pub fn set_classification(&mut self, classification: DataClassification) {
+        if let Err(e) = self.try_set_classification(classification) {
+            log::error!(target: "savant_rs::frozen_objects", "{}", e);
+        }
+    }
+
+    /// Classifies the frame data, fails when the classification changes while the frame has
+    /// frozen objects.
+    ///
+    pub fn try_set_classification(
+        &mut self,
+        classification: DataClassification,
+    ) -> Result<(), FrozenObjectsError> {
+        let mut inner = self.write();
+        if inner.classification == classification {
+            return Ok(());
+        }
+        for object in inner.objects.values() {
+            inner.check_objects_mutable(&object.namespace, "reclassify")?;
+        }
+        inner.classification = classification;
+        Ok(())
+    }
+
+    /// The highest classification of the frame and its objects, sinks use it to route the
+    /// frame, e.g. to a restricted storage.
+    ///
+    pub fn get_effective_classification(&self) -> DataClassification {
+        let inner = trace!(self.inner.read_recursive());
+        inner
+            .objects
+            .values()
+            .map(|o| o.classification)
+            .fold(inner.classification, DataClassification::max)
+    }
+
+    /// Sets the content representation under the key, replacing the previous one.
+    ///
+    pub fn set_representation(&mut self, key: &str, representation: ContentRepresentation) {
+        let mut inner = self.write();
+        inner
+            .representations
+            .insert(key.to_string(), representation);
+    }
+
+    pub fn get_representation(&self, key: &str) -> Option<ContentRepresentation> {
+        let inner = trace!(self.inner.read_recursive());
+        inner.representations.get(key).cloned()
+    }
+
+    pub fn delete_representation(&mut self, key: &str) -> Option<ContentRepresentation> {
+        let mut inner = self.write();
+        inner.representations.remove(key)
+    }
+
+    pub fn get_representation_keys(&self) -> Vec<String> {
+        let inner = trace!(self.inner.read_recursive());
+        inner.representations.keys().cloned().collect()
+    }
+
+    /// Keeps only the representations with the listed keys.
+    ///
+    pub fn retain_representations(&mut self, keys: &[&str]) {
+        let mut inner = self.write();
+        inner
+            .representations
+            .retain(|key, _| keys.contains(&key.as_str()));
+    }
+
     pub fn get_content(&self) -> Arc<VideoFrameContent> {
         let inner = trace!(self.inner.read_recursive());
         inner.content.clone()
     }
 
     pub fn set_content(&mut self, content: VideoFrameContent) {
-        let mut inner = trace!(self.inner.write());
+        let mut inner = self.write();
         inner.content = Arc::new(content);
     }
 
+    /// The format of the internal content: the pixel format of the raw layout or the format
+    /// derived from the codec, `None` for external or missing content and frames without a
+    /// codec.
+    ///
+    pub fn get_format(&self) -> Option<Format> {
+        let inner = trace!(self.inner.read_recursive());
+        match inner.content.as_ref() {
+            VideoFrameContent::Internal(content) => match content.get_layout() {
+                Some(layout) => Some(Format::Raw(layout.get_format())),
+                None => inner.codec.as_deref().map(Format::from_codec),
+            },
+            _ => None,
+        }
+    }
+
+    /// Transcodes the internal content to the format with the registered transcoders and
+    /// updates the codec, does nothing when the content is already in the format. Raw
+    /// results get the packed layout.
+    ///
+    pub fn ensure_format(&mut self, format: Format) -> anyhow::Result<()> {
+        let (content, width, height) = {
+            let inner = trace!(self.inner.read_recursive());
+            (inner.content.clone(), inner.width, inner.height)
+        };
+        let VideoFrameContent::Internal(internal) = content.as_ref() else {
+            return Err(TranscodingError::UnsupportedContent.into());
+        };
+        let current = self.get_format().ok_or(TranscodingError::UnknownFormat)?;
+        if current == format {
+            return Ok(());
+        }
+        let data = transcode(&current, &format, &internal.to_packed(), width, height)?;
+        let internal = match &format {
+            Format::Raw(pixel_format) => InternalFrame::raw(
+                data,
+                RawLayout::packed(
+                    *pixel_format,
+                    usize::try_from(width)?,
+                    usize::try_from(height)?,
+                )?,
+            )?,
+            Format::Encoded(_) => InternalFrame::new(data),
+        };
+        let mut inner = self.write();
+        inner.content = Arc::new(VideoFrameContent::Internal(internal));
+        inner.codec = Some(format.codec());
+        Ok(())
+    }
+
+    /// Compresses the internal content with the codec, the frame keeps the compressed bytes
+    /// and decompresses them on the first access. Other content is left as is.
+    ///
+    pub fn compress_content(&mut self, encoding: ContentEncoding) -> anyhow::Result<()> {
+        let content = self.get_content();
+        let VideoFrameContent::Internal(internal) = content.as_ref() else {
+            return Ok(());
+        };
+        if internal.get_encoding() == Some(encoding) {
+            return Ok(());
+        }
+        let compressed = internal.compress(encoding)?;
+        self.set_content(VideoFrameContent::Internal(compressed));
+        Ok(())
+    }
+
+    /// Replaces compressed internal content with the decoded bytes.
+    ///
+    pub fn decompress_content(&mut self) -> anyhow::Result<()> {
+        let content = self.get_content();
+        let VideoFrameContent::Internal(internal) = content.as_ref() else {
+            return Ok(());
+        };
+        if internal.get_encoding().is_none() {
+            return Ok(());
+        }
+        let decompressed = internal.decompress()?;
+        self.set_content(VideoFrameContent::Internal(decompressed));
+        Ok(())
+    }
+
+    /// Removes all objects. Nothing is removed when an object belongs to a frozen namespace,
+    /// the violation is logged.
+    ///
     pub fn clear_objects(&self) {
-        let mut frame = trace!(self.inner.write());
+        if let Err(e) = self.try_clear_objects() {
+            log::error!(target: "savant_rs::frozen_objects", "{}", e);
+        }
+    }
+
+    /// Removes all objects, fails without removing anything when an object belongs to a
+    /// frozen namespace.
+    ///
+    pub fn try_clear_objects(&self) -> Result<(), FrozenObjectsError> {
+        let mut frame = self.write();
+        for object in frame.objects.values() {
+            frame.check_objects_mutable(&object.namespace, "delete")?;
+        }
         frame.objects.clear();
+        Ok(())
     }
 
     // pub fn check_frame_fit(
@@ -1062,10 +1711,10 @@ impl VideoFrameProxy {
 #[cfg(test)]
 mod tests {
     use crate::draw::DrawLabelKind;
-    use crate::match_query::{eq, one_of, MatchQuery};
+    use crate::match_query::{eq, gt, one_of, MatchQuery};
     use crate::primitives::object::private::{SealedWithFrame, SealedWithParent};
     use crate::primitives::object::{
-        IdCollisionResolutionPolicy, ObjectOperations, VideoObjectBuilder,
+        IdCollisionResolutionPolicy, ObjectOperations, OrphanPolicy, VideoObjectBuilder,
     };
     use crate::primitives::{RBBox, WithAttributes};
     use crate::test::{gen_empty_frame, gen_frame, gen_object, s};
@@ -1139,6 +1788,38 @@ mod tests {
         assert!(o.get_parent().is_none());
     }
 
+    #[test]
+    fn test_prune() -> anyhow::Result<()> {
+        let f = gen_frame();
+        f.set_parent_by_id(2, 1)?;
+        let removed = f.prune(&MatchQuery::Id(eq(1)), OrphanPolicy::Reparent)?;
+        assert_eq!(removed.len(), 1);
+        assert_eq!(f.get_object(2).unwrap().get_parent_id(), Some(0));
+
+        let f = gen_frame();
+        f.set_parent_by_id(2, 1)?;
+        f.prune(&MatchQuery::Id(eq(1)), OrphanPolicy::Detach)?;
+        assert!(f.get_object(2).unwrap().get_parent().is_none());
+
+        let f = gen_frame();
+        f.set_parent_by_id(2, 1)?;
+        let removed = f.prune(&MatchQuery::Id(eq(0)), OrphanPolicy::Cascade)?;
+        assert_eq!(
+            removed.iter().map(|o| o.get_id()).collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        assert!(removed.iter().all(|o| o.get_frame().is_none()));
+        assert!(f.get_all_objects().is_empty());
+
+        let f = gen_frame();
+        f.freeze_objects(Some("test2"), "detection");
+        assert!(f
+            .prune(&MatchQuery::Id(eq(0)), OrphanPolicy::Cascade)
+            .is_err());
+        assert_eq!(f.get_all_objects().len(), 3);
+        Ok(())
+    }
+
     #[test]
     fn test_delete_all_objects() {
         let f = gen_frame();
@@ -1366,4 +2047,51 @@ mod tests {
         let objs = frame.get_all_objects();
         assert_eq!(objs.len(), 1);
     }
+
+    #[test]
+    fn test_shared_handle() {
+        let frame = gen_frame();
+        let same = frame.clone();
+        let copy = frame.smart_copy();
+        assert!(frame.is_same_frame(&same));
+        assert_eq!(frame.shared_handle(), same.shared_handle());
+        assert!(!frame.is_same_frame(&copy));
+        assert_ne!(frame.shared_handle(), copy.shared_handle());
+
+        let o1 = frame.get_object(1).unwrap();
+        let o2 = same.get_object(1).unwrap();
+        assert_eq!(o1.shared_handle(), o2.shared_handle());
+        assert_ne!(
+            o1.shared_handle(),
+            copy.get_object(1).unwrap().shared_handle()
+        );
+    }
+
+    #[test]
+    fn test_query_cache() {
+        let frame = gen_frame();
+        let q = MatchQuery::BoxWidth(gt(100.0));
+        assert!(frame.get_query_cache_stats().is_none());
+        frame.set_query_cache(true);
+
+        let matched = frame.access_objects(&q).len();
+        assert_eq!(frame.access_objects(&q).len(), matched);
+        let stats = frame.get_query_cache_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+
+        // the box is changed in place, without locking the frame
+        let object = frame.get_object(0).unwrap();
+        object.get_detection_box().set_width(1000.0);
+        assert!(frame.access_objects(&q).iter().any(|o| o.get_id() == 0));
+        assert_eq!(frame.get_query_cache_stats().unwrap().misses, 2);
+
+        let mut object = frame.get_object(1).unwrap();
+        object.set_detection_box(RBBox::new(0.0, 0.0, 500.0, 500.0, None));
+        assert!(frame.access_objects(&q).iter().any(|o| o.get_id() == 1));
+        let stats = frame.get_query_cache_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 3, 1));
+
+        frame.set_query_cache(false);
+        assert!(frame.get_query_cache_stats().is_none());
+    }
 }
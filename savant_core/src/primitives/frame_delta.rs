@@ -0,0 +1,395 @@
+use crate::primitives::attribute_value::{AttributeValue, AttributeValueVariant};
+use crate::primitives::frame::VideoFrameProxy;
+use crate::primitives::object::VideoObject;
+use crate::primitives::{Attribute, WithAttributes};
+use crate::trace;
+use anyhow::bail;
+use hashbrown::{HashMap, HashSet};
+use parking_lot::Mutex;
+use savant_protobuf::generated;
+use uuid::Uuid;
+
+/// The namespace of the hidden frame attribute which marks a delta frame.
+///
+pub const DELTA_NAMESPACE: &str = "savant.delta";
+pub const DELTA_ATTRIBUTE: &str = "base";
+
+/// What a delta frame carries besides the changed objects and attributes.
+///
+#[derive(Debug, Clone, PartialEq, Default)]
+struct DeltaHeader {
+    base: u128,
+    deleted_objects: Vec<i64>,
+    deleted_attributes: Vec<(String, String)>,
+    /// The parents of the carried objects, the parents may be unchanged and thus absent.
+    parents: Vec<(i64, i64)>,
+}
+
+impl DeltaHeader {
+    fn to_attribute(&self) -> Attribute {
+        let (namespaces, names) = self.deleted_attributes.iter().cloned().unzip();
+        Attribute::persistent(
+            DELTA_NAMESPACE,
+            DELTA_ATTRIBUTE,
+            vec![
+                AttributeValue::string(&Uuid::from_u128(self.base).to_string(), None),
+                AttributeValue::integer_vector(self.deleted_objects.clone(), None),
+                AttributeValue::string_vector(namespaces, None),
+                AttributeValue::string_vector(names, None),
+                AttributeValue::integer_vector(
+                    self.parents.iter().flat_map(|(c, p)| [*c, *p]).collect(),
+                    None,
+                ),
+            ],
+            &None,
+            true,
+        )
+    }
+
+    fn from_attribute(attribute: &Attribute) -> anyhow::Result<Self> {
+        use AttributeValueVariant as V;
+        let values = attribute
+            .get_values()
+            .iter()
+            .map(|v| &v.value)
+            .collect::<Vec<_>>();
+        match values.as_slice() {
+            [V::String(base), V::IntegerVector(deleted_objects), V::StringVector(namespaces), V::StringVector(names), V::IntegerVector(parents)]
+                if namespaces.len() == names.len() && parents.len() % 2 == 0 =>
+            {
+                Ok(Self {
+                    base: Uuid::parse_str(base)?.as_u128(),
+                    deleted_objects: deleted_objects.clone(),
+                    deleted_attributes: namespaces
+                        .iter()
+                        .cloned()
+                        .zip(names.iter().cloned())
+                        .collect(),
+                    parents: parents.chunks(2).map(|p| (p[0], p[1])).collect(),
+                })
+            }
+            _ => bail!("The delta frame header is malformed"),
+        }
+    }
+}
+
+fn same_object(left: &VideoObject, right: &VideoObject) -> bool {
+    let canonical = |o: &VideoObject| {
+        let mut o = generated::VideoObject::from(o);
+        o.attributes
+            .sort_by(|a, b| (&a.namespace, &a.name).cmp(&(&b.namespace, &b.name)));
+        o
+    };
+    canonical(left) == canonical(right)
+}
+
+/// Whether the frame is a delta frame produced by [`DeltaEncoder`].
+///
+pub fn is_delta_frame(frame: &VideoFrameProxy) -> bool {
+    frame.contains_attribute(DELTA_NAMESPACE, DELTA_ATTRIBUTE)
+}
+
+#[derive(Debug)]
+struct EncoderState {
+    base: VideoFrameProxy,
+    since_keyframe: u64,
+}
+
+/// Encodes the frames of a source relative to the previous frame of the source: a delta
+/// frame keeps the frame fields and content but carries only the new and changed objects and
+/// attributes. Every `keyframe_interval`-th frame is passed as is, so a receiver can join the
+/// stream. The frames are restored with [`DeltaDecoder`].
+///
+#[derive(Debug)]
+pub struct DeltaEncoder {
+    keyframe_interval: u64,
+    sources: Mutex<HashMap<String, EncoderState>>,
+}
+
+impl DeltaEncoder {
+    pub fn new(keyframe_interval: u64) -> anyhow::Result<Self> {
+        if keyframe_interval == 0 {
+            bail!("The keyframe interval must be greater than 0");
+        }
+        Ok(Self {
+            keyframe_interval,
+            sources: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn get_keyframe_interval(&self) -> u64 {
+        self.keyframe_interval
+    }
+
+    /// Makes the next frame of the source a keyframe, e.g. when a receiver lost the stream.
+    ///
+    pub fn reset_source(&self, source_id: &str) {
+        self.sources.lock().remove(source_id);
+    }
+
+    /// Encodes the frame. A keyframe is the frame itself, a delta frame is a new frame.
+    ///
+    pub fn encode(&self, frame: &VideoFrameProxy) -> anyhow::Result<VideoFrameProxy> {
+        if is_delta_frame(frame) {
+            bail!("The frame {} is already delta-encoded", frame.get_uuid());
+        }
+        let snapshot = frame.smart_copy();
+        let mut sources = self.sources.lock();
+        let source_id = frame.get_source_id();
+        match sources.get_mut(&source_id) {
+            Some(state) if state.since_keyframe < self.keyframe_interval => {
+                let delta = Self::diff(&state.base, &snapshot);
+                state.base = snapshot;
+                state.since_keyframe += 1;
+                Ok(delta)
+            }
+            _ => {
+                sources.insert(
+                    source_id,
+                    EncoderState {
+                        base: snapshot,
+                        since_keyframe: 1,
+                    },
+                );
+                Ok(frame.clone())
+            }
+        }
+    }
+
+    fn diff(base: &VideoFrameProxy, frame: &VideoFrameProxy) -> VideoFrameProxy {
+        let delta = frame.smart_copy();
+        let base_inner = trace!(base.inner.read_recursive());
+        let mut inner = trace!(delta.inner.write());
+
+        let mut header = DeltaHeader {
+            base: base_inner.uuid,
+            ..Default::default()
+        };
+        header.deleted_objects = base_inner
+            .objects
+            .keys()
+            .filter(|id| !inner.objects.contains_key(*id))
+            .copied()
+            .collect();
+        header.deleted_objects.sort_unstable();
+        inner.objects.retain(|id, o| {
+            base_inner
+                .objects
+                .get(id)
+                .is_none_or(|b| !same_object(b, o))
+        });
+        for o in inner.objects.values_mut() {
+            if let Some(parent) = o.parent_id.take() {
+                header.parents.push((o.id, parent));
+            }
+        }
+        header.parents.sort_unstable();
+
+        header.deleted_attributes = base_inner
+            .attributes
+            .iter()
+            .filter(|a| !inner.contains_attribute(&a.namespace, &a.name))
+            .map(|a| (a.namespace.clone(), a.name.clone()))
+            .collect();
+        inner
+            .attributes
+            .retain(|a| !base_inner.attributes.contains(a));
+        inner.attributes.push(header.to_attribute());
+        drop(inner);
+        delta
+    }
+}
+
+/// Restores the frames encoded by [`DeltaEncoder`], keeping the last restored frame of every
+/// source. A delta frame fails to decode when the previous frame of the source was lost.
+///
+#[derive(Debug, Default)]
+pub struct DeltaDecoder {
+    sources: Mutex<HashMap<String, VideoFrameProxy>>,
+}
+
+impl DeltaDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reset_source(&self, source_id: &str) {
+        self.sources.lock().remove(source_id);
+    }
+
+    /// Decodes the frame. A keyframe is returned as is, a delta frame is restored into a new
+    /// frame.
+    ///
+    pub fn decode(&self, frame: &VideoFrameProxy) -> anyhow::Result<VideoFrameProxy> {
+        let mut sources = self.sources.lock();
+        let source_id = frame.get_source_id();
+        let Some(header) = frame.get_attribute(DELTA_NAMESPACE, DELTA_ATTRIBUTE) else {
+            sources.insert(source_id, frame.smart_copy());
+            return Ok(frame.clone());
+        };
+        let header = DeltaHeader::from_attribute(&header)?;
+        let Some(base) = sources.get(&source_id) else {
+            bail!(
+                "The delta frame {} of source {} has no base frame, a keyframe is required",
+                frame.get_uuid(),
+                source_id
+            );
+        };
+        if base.get_uuid_u128() != header.base {
+            bail!(
+                "The delta frame {} of source {} refers to the frame {}, but the last frame is {}",
+                frame.get_uuid(),
+                source_id,
+                Uuid::from_u128(header.base),
+                base.get_uuid()
+            );
+        }
+
+        let restored = frame.smart_copy();
+        let base_inner = trace!(base.inner.read_recursive());
+        let mut inner = trace!(restored.inner.write());
+        inner
+            .attributes
+            .retain(|a| a.namespace != DELTA_NAMESPACE || a.name != DELTA_ATTRIBUTE);
+        let deleted_attributes = header
+            .deleted_attributes
+            .iter()
+            .map(|(ns, n)| (ns.as_str(), n.as_str()))
+            .collect::<HashSet<_>>();
+        let mut attributes = base_inner
+            .attributes
+            .iter()
+            .filter(|a| {
+                !deleted_attributes.contains(&(a.namespace.as_str(), a.name.as_str()))
+                    && !inner.contains_attribute(&a.namespace, &a.name)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        attributes.append(&mut inner.attributes);
+        inner.attributes = attributes;
+
+        for (id, o) in &base_inner.objects {
+            if header.deleted_objects.contains(id) || inner.objects.contains_key(id) {
+                continue;
+            }
+            let mut copy = o.detached_copy();
+            copy.parent_id = o.parent_id;
+            copy.frame = Some((&restored).into());
+            inner.objects.insert(*id, copy);
+        }
+        for (child, parent) in &header.parents {
+            match inner.objects.get_mut(child) {
+                Some(o) => o.parent_id = Some(*parent),
+                None => bail!(
+                    "The delta frame sets the parent of the missing object {}",
+                    child
+                ),
+            }
+        }
+        if let Some(id) = inner
+            .objects
+            .values()
+            .filter_map(|o| o.parent_id)
+            .find(|p| !inner.objects.contains_key(p))
+        {
+            bail!("The restored frame misses the parent object {}", id);
+        }
+        inner.max_object_id = inner
+            .objects
+            .keys()
+            .copied()
+            .fold(inner.max_object_id, i64::max);
+        drop(inner);
+        drop(base_inner);
+
+        sources.insert(source_id, restored.smart_copy());
+        Ok(restored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::attribute_value::AttributeValue;
+    use crate::primitives::frame::VideoFrameProxy;
+    use crate::primitives::frame_delta::{is_delta_frame, DeltaDecoder, DeltaEncoder};
+    use crate::primitives::object::{IdCollisionResolutionPolicy, ObjectOperations};
+    use crate::primitives::{Attribute, WithAttributes};
+    use crate::protobuf::{from_pb, ToProtobuf};
+    use crate::test::{gen_frame, gen_object};
+
+    fn transfer(frame: &VideoFrameProxy) -> anyhow::Result<VideoFrameProxy> {
+        let bytes = frame.to_pb()?;
+        Ok(from_pb::<
+            savant_protobuf::generated::VideoFrame,
+            VideoFrameProxy,
+        >(&bytes)?)
+    }
+
+    #[test]
+    fn test_delta_round_trip() -> anyhow::Result<()> {
+        assert!(DeltaEncoder::new(0).is_err());
+        let encoder = DeltaEncoder::new(3)?;
+        let decoder = DeltaDecoder::new();
+        let mut frame = gen_frame();
+
+        let key = encoder.encode(&frame)?;
+        assert!(!is_delta_frame(&key));
+        decoder.decode(&transfer(&key)?)?;
+
+        // one object changes, one object disappears, one attribute is added and a new child
+        // refers to the unchanged parent object
+        frame.get_object(2).unwrap().set_label("changed");
+        frame.delete_objects_with_ids(&[1]);
+        frame.set_attribute(Attribute::persistent(
+            "scene",
+            "state",
+            vec![AttributeValue::integer(1, None)],
+            &None,
+            false,
+        ));
+        let mut child = gen_object(10);
+        child.parent_id = Some(0);
+        frame.add_object(child, IdCollisionResolutionPolicy::Error)?;
+
+        let delta = encoder.encode(&frame)?;
+        assert!(is_delta_frame(&delta));
+        assert_eq!(delta.get_all_objects().len(), 2);
+        let restored = decoder.decode(&transfer(&delta)?)?;
+        assert!(!is_delta_frame(&restored));
+        assert_eq!(
+            restored.get_all_objects().len(),
+            frame.get_all_objects().len()
+        );
+        assert!(restored.get_object(1).is_none());
+        assert_eq!(restored.get_object(2).unwrap().get_label(), "changed");
+        assert_eq!(restored.get_object(2).unwrap().get_parent_id(), Some(0));
+        assert_eq!(restored.get_object(10).unwrap().get_parent_id(), Some(0));
+        assert!(restored.get_attribute("scene", "state").is_some());
+        assert_eq!(
+            restored.get_attributes().len(),
+            frame.get_attributes().len()
+        );
+
+        // the frame deletes the attribute, then the interval forces a keyframe
+        frame.delete_attribute("scene", "state");
+        let restored = decoder.decode(&transfer(&encoder.encode(&frame)?)?)?;
+        assert!(restored.get_attribute("scene", "state").is_none());
+        assert!(!is_delta_frame(&encoder.encode(&frame)?));
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_base() -> anyhow::Result<()> {
+        let encoder = DeltaEncoder::new(10)?;
+        let frame = gen_frame();
+        encoder.encode(&frame)?;
+        let delta = encoder.encode(&frame)?;
+        assert!(encoder.encode(&delta).is_err());
+
+        let decoder = DeltaDecoder::new();
+        assert!(decoder.decode(&delta).is_err());
+        decoder.decode(&gen_frame())?;
+        assert!(decoder.decode(&delta).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+use crate::primitives::frame_update::AttributeUpdatePolicy;
+use crate::primitives::{Attribute, WithAttributes};
+use anyhow::bail;
+
+/// How the objects of the other frame are merged into the frame.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ObjectMergePolicy {
+    /// Objects with the same id, namespace and label are the same object and only their
+    /// attributes are merged. The other objects are added with new ids.
+    #[default]
+    MergeSameObjects,
+    /// All objects of the other frame are added with new ids.
+    AddForeignObjects,
+}
+
+/// The policies of [`crate::primitives::frame::VideoFrameProxy::merge_from`], used to unify
+/// copies of a frame annotated by parallel branches.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameMergePolicy {
+    pub object_policy: ObjectMergePolicy,
+    pub frame_attribute_policy: AttributeUpdatePolicy,
+    pub object_attribute_policy: AttributeUpdatePolicy,
+}
+
+impl Default for FrameMergePolicy {
+    fn default() -> Self {
+        Self {
+            object_policy: ObjectMergePolicy::default(),
+            frame_attribute_policy: AttributeUpdatePolicy::ReplaceWithForeign,
+            object_attribute_policy: AttributeUpdatePolicy::ReplaceWithForeign,
+        }
+    }
+}
+
+/// Merges the attributes into the target according to the policy. With
+/// [`AttributeUpdatePolicy::Error`] the target is not changed when an attribute collides.
+///
+pub(crate) fn merge_attributes<T: WithAttributes>(
+    target: &mut T,
+    attributes: &[Attribute],
+    policy: &AttributeUpdatePolicy,
+    owner: &str,
+) -> anyhow::Result<()> {
+    if let AttributeUpdatePolicy::Error = policy {
+        if let Some(attr) = attributes
+            .iter()
+            .find(|a| target.contains_attribute(&a.namespace, &a.name))
+        {
+            bail!(
+                "Attribute with name '{}.{}' already exists in {}.",
+                attr.namespace,
+                attr.name,
+                owner
+            );
+        }
+    }
+    for attr in attributes {
+        if let AttributeUpdatePolicy::KeepOwn = policy {
+            if target.contains_attribute(&attr.namespace, &attr.name) {
+                continue;
+            }
+        }
+        target.set_attribute(attr.clone());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::attribute_value::{AttributeValue, AttributeValueVariant};
+    use crate::primitives::frame_merge::{FrameMergePolicy, ObjectMergePolicy};
+    use crate::primitives::frame_update::AttributeUpdatePolicy;
+    use crate::primitives::object::{IdCollisionResolutionPolicy, ObjectOperations};
+    use crate::primitives::{Attribute, WithAttributes};
+    use crate::test::{gen_frame, gen_object};
+
+    fn branch_attribute(name: &str, value: i64) -> Attribute {
+        Attribute::persistent(
+            "branch",
+            name,
+            vec![AttributeValue::integer(value, None)],
+            &None,
+            false,
+        )
+    }
+
+    #[test]
+    fn test_merge_same_objects() -> anyhow::Result<()> {
+        let mut frame = gen_frame();
+        let mut copy = frame.smart_copy();
+
+        // both branches annotate the shared object and add an object with the same id
+        frame
+            .get_object(1)
+            .unwrap()
+            .set_attribute(branch_attribute("left", 1));
+        copy.get_object(1)
+            .unwrap()
+            .set_attribute(branch_attribute("right", 2));
+        let mut left = gen_object(3);
+        left.set_namespace("left");
+        frame.add_object(left, IdCollisionResolutionPolicy::Error)?;
+        let mut right = gen_object(3);
+        right.set_namespace("right");
+        copy.add_object(right, IdCollisionResolutionPolicy::Error)?;
+        let mut child = gen_object(4);
+        child.set_namespace("right");
+        child.parent_id = Some(3);
+        copy.add_object(child, IdCollisionResolutionPolicy::Error)?;
+        copy.set_attribute(branch_attribute("right", 2));
+
+        let mapping = frame.merge_from(&copy, &FrameMergePolicy::default())?;
+        assert_eq!(mapping[&1], 1);
+        assert_eq!(mapping[&3], 4);
+        assert_eq!(mapping[&4], 5);
+        assert_eq!(frame.get_all_objects().len(), 6);
+        let shared = frame.get_object(1).unwrap();
+        assert!(shared.get_attribute("branch", "left").is_some());
+        assert!(shared.get_attribute("branch", "right").is_some());
+        assert_eq!(frame.get_object(4).unwrap().get_namespace(), "right");
+        assert_eq!(frame.get_object(5).unwrap().get_parent_id(), Some(4));
+        assert!(frame.get_attribute("branch", "right").is_some());
+        assert_eq!(frame.get_max_object_id(), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_errors() -> anyhow::Result<()> {
+        let mut frame = gen_frame();
+        assert!(frame
+            .merge_from(&frame, &FrameMergePolicy::default())
+            .is_err());
+
+        let mut copy = frame.smart_copy();
+        frame.set_attribute(branch_attribute("shared", 1));
+        copy.set_attribute(branch_attribute("shared", 2));
+        let mut other = gen_object(10);
+        other.set_namespace("right");
+        copy.add_object(other, IdCollisionResolutionPolicy::Error)?;
+        let policy = FrameMergePolicy {
+            object_policy: ObjectMergePolicy::AddForeignObjects,
+            frame_attribute_policy: AttributeUpdatePolicy::Error,
+            object_attribute_policy: AttributeUpdatePolicy::KeepOwn,
+        };
+        assert!(frame.merge_from(&copy, &policy).is_err());
+        assert_eq!(frame.get_all_objects().len(), 3);
+
+        let policy = FrameMergePolicy {
+            frame_attribute_policy: AttributeUpdatePolicy::KeepOwn,
+            ..policy
+        };
+        let mapping = frame.merge_from(&copy, &policy)?;
+        assert_eq!(mapping.len(), 4);
+        assert_eq!(frame.get_all_objects().len(), 7);
+        let value = frame.get_attribute("branch", "shared").unwrap();
+        assert_eq!(
+            value.get_values()[0].value,
+            AttributeValueVariant::Integer(1)
+        );
+        Ok(())
+    }
+}
@@ -30,6 +30,10 @@ pub struct VideoFrameUpdate {
     pub(crate) object_attribute_policy: AttributeUpdatePolicy,
     #[serde(skip)]
     pub(crate) object_policy: ObjectUpdatePolicy,
+    /// The name of the updater producing the update, its writes are limited to the
+    /// namespaces the updater is registered with in the pipeline.
+    #[serde(default)]
+    pub(crate) updater: Option<String>,
 }
 
 impl Default for VideoFrameUpdate {
@@ -41,6 +45,7 @@ impl Default for VideoFrameUpdate {
             object_policy: ObjectUpdatePolicy::ErrorIfLabelsCollide,
             frame_attribute_policy: AttributeUpdatePolicy::Error,
             object_attribute_policy: AttributeUpdatePolicy::Error,
+            updater: None,
         }
     }
 }
@@ -78,6 +83,14 @@ impl VideoFrameUpdate {
         self.object_attribute_policy.clone()
     }
 
+    pub fn set_updater(&mut self, updater: Option<&str>) {
+        self.updater = updater.map(String::from);
+    }
+
+    pub fn get_updater(&self) -> Option<&str> {
+        self.updater.as_deref()
+    }
+
     pub fn add_frame_attribute(&mut self, attribute: Attribute) {
         self.frame_attributes.push(attribute);
     }
@@ -0,0 +1,211 @@
+/// A part of the frame object set frozen after a pipeline stage: the objects of the namespace
+/// or, when the namespace is not set, the whole object set. Frozen objects cannot be added,
+/// replaced or updated, so downstream stages cannot break the results of upstream teams.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FrozenObjects {
+    pub namespace: Option<String>,
+    pub stage: String,
+}
+
+impl FrozenObjects {
+    pub fn new(namespace: Option<&str>, stage: &str) -> Self {
+        Self {
+            namespace: namespace.map(String::from),
+            stage: stage.to_string(),
+        }
+    }
+
+    pub fn covers(&self, namespace: &str) -> bool {
+        self.namespace.as_deref().is_none_or(|ns| ns == namespace)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error(
+    "{violator} attempted to {operation} an object of namespace '{namespace}' frozen after the stage '{stage}'"
+)]
+pub struct FrozenObjectsError {
+    /// The stage holding the frame when the mutation was attempted, or `unknown` for frames
+    /// outside of a pipeline.
+    pub violator: String,
+    pub operation: String,
+    pub namespace: String,
+    /// The stage after which the objects were frozen.
+    pub stage: String,
+}
+
+pub(crate) const UNKNOWN_VIOLATOR: &str = "unknown";
+
+/// Fails when the objects of the namespace are frozen, the violator is the stage holding the
+/// frame. Every mutation of the frame objects passes the check.
+///
+pub(crate) fn check_objects_mutable(
+    frozen_objects: &[FrozenObjects],
+    violator: Option<&str>,
+    namespace: &str,
+    operation: &str,
+) -> Result<(), FrozenObjectsError> {
+    match frozen_objects.iter().find(|f| f.covers(namespace)) {
+        Some(frozen) => Err(FrozenObjectsError {
+            violator: violator.unwrap_or(UNKNOWN_VIOLATOR).to_string(),
+            operation: operation.to_string(),
+            namespace: namespace.to_string(),
+            stage: frozen.stage.clone(),
+        }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::classification::DataClassification;
+    use crate::primitives::frame_update::{ObjectUpdatePolicy, VideoFrameUpdate};
+    use crate::primitives::frozen_objects::{FrozenObjects, FrozenObjectsError};
+    use crate::primitives::object::private::SealedWithParent;
+    use crate::primitives::object::{IdCollisionResolutionPolicy, ObjectOperations};
+    use crate::primitives::RBBox;
+    use crate::test::{gen_empty_frame, gen_object};
+
+    #[test]
+    fn test_frozen_namespace() -> anyhow::Result<()> {
+        let frame = gen_empty_frame();
+        frame.create_object(
+            "detector",
+            "person",
+            None,
+            RBBox::ltwh(0.0, 0.0, 10.0, 10.0),
+            None,
+            None,
+            None,
+            vec![],
+        )?;
+        frame.freeze_objects(Some("detector"), "detection");
+        frame.freeze_objects(Some("detector"), "detection");
+        assert_eq!(
+            frame.get_frozen_objects(),
+            vec![FrozenObjects::new(Some("detector"), "detection")]
+        );
+
+        let mut foreign = gen_object(100);
+        foreign.set_namespace("detector");
+        let err = frame
+            .add_object(foreign.clone(), IdCollisionResolutionPolicy::GenerateNewId)
+            .unwrap_err()
+            .downcast::<FrozenObjectsError>()?;
+        assert_eq!(err.stage, "detection");
+        assert_eq!(err.violator, "unknown");
+
+        let mut update = VideoFrameUpdate::default();
+        update.set_object_policy(ObjectUpdatePolicy::ReplaceSameLabelObjects);
+        foreign.set_label("person");
+        update.add_object(foreign, None);
+        assert!(frame.update(&update).is_err());
+        assert_eq!(frame.get_all_objects().len(), 1);
+
+        let mut other = gen_object(101);
+        other.set_namespace("classifier");
+        frame.add_object(other, IdCollisionResolutionPolicy::GenerateNewId)?;
+
+        frame.freeze_objects(None, "classification");
+        let mut late = gen_object(102);
+        late.set_namespace("tracker");
+        assert!(frame
+            .add_object(late, IdCollisionResolutionPolicy::GenerateNewId)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_frozen_object_mutations() -> anyhow::Result<()> {
+        let frame = gen_empty_frame();
+        let mut frozen = frame.create_object(
+            "detector",
+            "person",
+            None,
+            RBBox::ltwh(0.0, 0.0, 10.0, 10.0),
+            None,
+            None,
+            None,
+            vec![],
+        )?;
+        let mut free = frame.create_object(
+            "classifier",
+            "face",
+            None,
+            RBBox::ltwh(0.0, 0.0, 5.0, 5.0),
+            None,
+            None,
+            None,
+            vec![],
+        )?;
+        frame.freeze_objects(Some("detector"), "detection");
+
+        frozen.set_label("car");
+        assert_eq!(frozen.get_label(), "person");
+        free.set_label("eye");
+        assert_eq!(free.get_label(), "eye");
+        free.set_namespace("detector");
+        assert_eq!(free.get_namespace(), "classifier");
+
+        frozen.set_classification(DataClassification::Pii);
+        assert_eq!(frozen.get_classification(), DataClassification::Public);
+        free.set_classification(DataClassification::Pii);
+        assert_eq!(free.get_classification(), DataClassification::Pii);
+        let mut owner = frame.clone();
+        owner.set_classification(DataClassification::Internal);
+        assert_eq!(frame.get_classification(), DataClassification::Public);
+
+        assert!(frozen.set_parent(Some(free.get_id())).is_err());
+        assert!(frozen.get_parent_id().is_none());
+        free.set_parent(Some(frozen.get_id()))?;
+
+        assert!(frame.delete_objects_with_ids(&[frozen.get_id()]).is_empty());
+        frame.clear_objects();
+        assert_eq!(frame.get_all_objects().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_frozen_object_errors() -> anyhow::Result<()> {
+        let frame = gen_empty_frame();
+        let mut frozen = frame.create_object(
+            "detector",
+            "person",
+            None,
+            RBBox::ltwh(0.0, 0.0, 10.0, 10.0),
+            None,
+            None,
+            None,
+            vec![],
+        )?;
+        frame.freeze_objects(Some("detector"), "detection");
+        frame.set_stage(Some("tracking".to_string()));
+
+        let err = frozen
+            .try_with_object_mut(|o| o.set_label("car"))
+            .unwrap_err();
+        assert_eq!(err.stage, "detection");
+        assert_eq!(err.violator, "tracking");
+        assert_eq!(frozen.get_label(), "person");
+        assert!(frozen
+            .try_with_attributes_mut(|attributes| attributes.clear())
+            .is_err());
+        assert!(frame
+            .try_delete_objects_with_ids(&[frozen.get_id()])
+            .is_err());
+        assert!(frame.try_clear_objects().is_err());
+        let mut owner = frame.clone();
+        assert!(owner
+            .try_set_classification(DataClassification::Internal)
+            .is_err());
+        assert_eq!(frame.get_all_objects().len(), 1);
+
+        let detection_box = frozen.get_detection_box();
+        detection_box.set_xc(100.0);
+        assert_eq!(frozen.get_detection_box().get_xc(), 5.0);
+        frozen.set_track_info(1, RBBox::ltwh(0.0, 0.0, 10.0, 10.0));
+        assert!(frozen.get_track_box().is_none());
+        Ok(())
+    }
+}
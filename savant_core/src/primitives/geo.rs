@@ -0,0 +1,164 @@
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+
+/// The semi-major axis of the WGS84 ellipsoid in meters.
+const WGS84_A: f64 = 6_378_137.0;
+/// The flattening of the WGS84 ellipsoid.
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+/// The squared first eccentricity of the WGS84 ellipsoid.
+const WGS84_E2: f64 = WGS84_F * (2.0 - WGS84_F);
+
+/// A WGS84 position: the position of a detected object or, with the heading, the pose of
+/// the camera of a source.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GeoPosition {
+    /// The latitude in degrees, `[-90, 90]`.
+    pub latitude: f64,
+    /// The longitude in degrees, `[-180, 180]`.
+    pub longitude: f64,
+    /// The height above the ellipsoid in meters.
+    #[serde(default)]
+    pub altitude: f64,
+    /// The direction in degrees clockwise from the true north, `[0, 360)`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heading: Option<f64>,
+}
+
+/// Local east-north-up coordinates in meters relative to an origin position.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Enu {
+    pub east: f64,
+    pub north: f64,
+    pub up: f64,
+}
+
+impl GeoPosition {
+    pub fn new(
+        latitude: f64,
+        longitude: f64,
+        altitude: f64,
+        heading: Option<f64>,
+    ) -> anyhow::Result<Self> {
+        let position = Self {
+            latitude,
+            longitude,
+            altitude,
+            heading,
+        };
+        position.validate()?;
+        Ok(position)
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !(-90.0..=90.0).contains(&self.latitude) {
+            bail!("The latitude {} is out of [-90, 90]", self.latitude);
+        }
+        if !(-180.0..=180.0).contains(&self.longitude) {
+            bail!("The longitude {} is out of [-180, 180]", self.longitude);
+        }
+        if !self.altitude.is_finite() {
+            bail!("The altitude {} is not finite", self.altitude);
+        }
+        if let Some(heading) = self.heading {
+            if !(0.0..360.0).contains(&heading) {
+                bail!("The heading {} is out of [0, 360)", heading);
+            }
+        }
+        Ok(())
+    }
+
+    /// The earth-centered, earth-fixed coordinates in meters.
+    ///
+    pub fn to_ecef(&self) -> (f64, f64, f64) {
+        let (lat, lon) = (self.latitude.to_radians(), self.longitude.to_radians());
+        let n = WGS84_A / (1.0 - WGS84_E2 * lat.sin().powi(2)).sqrt();
+        (
+            (n + self.altitude) * lat.cos() * lon.cos(),
+            (n + self.altitude) * lat.cos() * lon.sin(),
+            (n * (1.0 - WGS84_E2) + self.altitude) * lat.sin(),
+        )
+    }
+
+    /// The position of the earth-centered, earth-fixed coordinates, without the heading.
+    ///
+    pub fn from_ecef(x: f64, y: f64, z: f64) -> Self {
+        let p = x.hypot(y);
+        let mut lat = z.atan2(p * (1.0 - WGS84_E2));
+        let mut altitude = 0.0;
+        for _ in 0..10 {
+            let n = WGS84_A / (1.0 - WGS84_E2 * lat.sin().powi(2)).sqrt();
+            altitude = p * lat.cos() + z * lat.sin() - WGS84_A * WGS84_A / n;
+            lat = z.atan2(p * (1.0 - WGS84_E2 * n / (n + altitude)));
+        }
+        Self {
+            latitude: lat.to_degrees(),
+            longitude: y.atan2(x).to_degrees(),
+            altitude,
+            heading: None,
+        }
+    }
+
+    /// The coordinates of the position in the local east-north-up frame of the origin.
+    ///
+    pub fn to_enu(&self, origin: &GeoPosition) -> Enu {
+        let (x, y, z) = self.to_ecef();
+        let (x0, y0, z0) = origin.to_ecef();
+        let (dx, dy, dz) = (x - x0, y - y0, z - z0);
+        let (lat, lon) = (origin.latitude.to_radians(), origin.longitude.to_radians());
+        Enu {
+            east: -lon.sin() * dx + lon.cos() * dy,
+            north: -lat.sin() * lon.cos() * dx - lat.sin() * lon.sin() * dy + lat.cos() * dz,
+            up: lat.cos() * lon.cos() * dx + lat.cos() * lon.sin() * dy + lat.sin() * dz,
+        }
+    }
+
+    /// The position of the local east-north-up coordinates of the origin, without the
+    /// heading.
+    ///
+    pub fn from_enu(origin: &GeoPosition, enu: &Enu) -> Self {
+        let (x0, y0, z0) = origin.to_ecef();
+        let (lat, lon) = (origin.latitude.to_radians(), origin.longitude.to_radians());
+        let Enu { east, north, up } = *enu;
+        Self::from_ecef(
+            x0 - lon.sin() * east - lat.sin() * lon.cos() * north + lat.cos() * lon.cos() * up,
+            y0 + lon.cos() * east - lat.sin() * lon.sin() * north + lat.cos() * lon.sin() * up,
+            z0 + lat.cos() * north + lat.sin() * up,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::geo::{Enu, GeoPosition};
+
+    #[test]
+    fn test_enu_roundtrip() -> anyhow::Result<()> {
+        let origin = GeoPosition::new(55.7558, 37.6173, 150.0, Some(90.0))?;
+        let enu = Enu {
+            east: 120.0,
+            north: -45.5,
+            up: 3.0,
+        };
+        let position = GeoPosition::from_enu(&origin, &enu);
+        assert!(position.heading.is_none());
+        let restored = position.to_enu(&origin);
+        assert!((restored.east - enu.east).abs() < 1e-6);
+        assert!((restored.north - enu.north).abs() < 1e-6);
+        assert!((restored.up - enu.up).abs() < 1e-6);
+
+        // 0.001 degree of latitude is about 111 m to the north
+        let north = GeoPosition::new(55.7568, 37.6173, 150.0, None)?.to_enu(&origin);
+        assert!(north.east.abs() < 1e-6);
+        assert!((north.north - 111.3).abs() < 0.5);
+
+        let origin = origin.to_enu(&origin);
+        assert!(origin.east.abs() < 1e-9 && origin.north.abs() < 1e-9);
+
+        assert!(GeoPosition::new(91.0, 0.0, 0.0, None).is_err());
+        assert!(GeoPosition::new(0.0, 181.0, 0.0, None).is_err());
+        assert!(GeoPosition::new(0.0, 0.0, 0.0, Some(360.0)).is_err());
+        Ok(())
+    }
+}
@@ -3,10 +3,14 @@ use serde_json::Value;
 use std::fmt::Debug;
 
 use crate::json_api::ToSerdeJsonValue;
+use crate::primitives::classification::DataClassification;
 use crate::primitives::frame::{BelongingVideoFrame, VideoFrameProxy};
+use crate::primitives::frozen_objects::{check_objects_mutable, FrozenObjectsError};
+use crate::primitives::geo::GeoPosition;
 use crate::primitives::object::private::{
     SealedObjectOperations, SealedWithFrame, SealedWithParent,
 };
+use crate::primitives::object_payload::ObjectPayload;
 use crate::primitives::{Attribute, RBBox, WithAttributes};
 
 use super::bbox::BBOX_UNDEFINED;
@@ -31,6 +35,19 @@ pub enum IdCollisionResolutionPolicy {
     Error,
 }
 
+/// What happens to the descendants of removed objects when the frame is pruned.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OrphanPolicy {
+    /// The children of removed objects lose their parent.
+    #[default]
+    Detach,
+    /// The children of removed objects are attached to the nearest retained ancestor.
+    Reparent,
+    /// The descendants of removed objects are removed too.
+    Cascade,
+}
+
 #[derive(Debug, derive_builder::Builder, serde::Serialize, serde::Deserialize)]
 pub struct VideoObject {
     pub(crate) id: i64,
@@ -54,6 +71,15 @@ pub struct VideoObject {
     #[builder(default)]
     pub(crate) label_id: Option<i64>,
     #[builder(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) geo_position: Option<GeoPosition>,
+    #[builder(default)]
+    #[serde(default, skip_serializing_if = "DataClassification::is_public")]
+    pub(crate) classification: DataClassification,
+    #[builder(default)]
+    #[serde(skip_deserializing, skip_serializing)]
+    pub(crate) payload: Option<ObjectPayload>,
+    #[builder(default)]
     #[serde(skip_deserializing, skip_serializing)]
     pub(crate) frame: Option<BelongingVideoFrame>,
 }
@@ -73,6 +99,10 @@ impl Clone for VideoObject {
             track_box: self.track_box.as_ref().map(|tb| tb.copy()),
             namespace_id: self.namespace_id,
             label_id: self.label_id,
+            geo_position: self.geo_position,
+            classification: self.classification,
+            // the payload is copied explicitly, see `ObjectOperations::copy_payload_to`
+            payload: None,
             frame: self.frame.clone(),
         }
     }
@@ -95,6 +125,9 @@ impl Default for VideoObject {
             track_box: None,
             namespace_id: None,
             label_id: None,
+            geo_position: None,
+            classification: DataClassification::Public,
+            payload: None,
             frame: None,
         }
     }
@@ -125,6 +158,101 @@ impl ToSerdeJsonValue for VideoObject {
 #[derive(Debug, Clone)]
 pub struct BorrowedVideoObject(pub(crate) BelongingVideoFrame, pub(crate) i64);
 
+impl BorrowedVideoObject {
+    /// Returns the shared handle of the owning frame and the object id. Borrowed objects
+    /// referring to the same object of the same frame return the same value.
+    ///
+    pub fn shared_handle(&self) -> (usize, i64) {
+        (self.0.inner.as_ptr() as usize, self.1)
+    }
+
+    /// Changes the object, fails when the object is frozen or is moved into a frozen
+    /// namespace, see [`crate::primitives::frame::VideoFrameProxy::freeze_objects`]. The
+    /// closure is not called for a frozen object, the namespace of an object moved into a
+    /// frozen namespace is restored.
+    ///
+    pub fn try_with_object_mut<F, R>(&mut self, f: F) -> Result<R, FrozenObjectsError>
+    where
+        F: FnOnce(&mut VideoObject) -> R,
+    {
+        let frame = <&BelongingVideoFrame as Into<VideoFrameProxy>>::into(&self.0);
+        let mut frame = frame.write();
+        let frame = &mut **frame;
+        let uuid = frame.uuid;
+        let object = frame
+            .objects
+            .get_mut(&self.1)
+            .unwrap_or_else(|| panic!("Object {} not found in the frame {}", self.1, uuid));
+        if frame.frozen_objects.is_empty() {
+            return Ok(f(object));
+        }
+        check_objects_mutable(
+            &frame.frozen_objects,
+            frame.stage.as_deref(),
+            &object.namespace,
+            "modify",
+        )?;
+        let namespace = object.namespace.clone();
+        let res = f(object);
+        if object.namespace != namespace {
+            if let Err(e) = check_objects_mutable(
+                &frame.frozen_objects,
+                frame.stage.as_deref(),
+                &object.namespace,
+                "add",
+            ) {
+                object.namespace = namespace;
+                return Err(e);
+            }
+        }
+        Ok(res)
+    }
+
+    /// Changes the attributes of the object, fails when the object is frozen, see
+    /// [`crate::primitives::frame::VideoFrameProxy::freeze_objects`]. The closure is not
+    /// called for a frozen object.
+    ///
+    pub fn try_with_attributes_mut<F, R>(&mut self, f: F) -> Result<R, FrozenObjectsError>
+    where
+        F: FnOnce(&mut Vec<Attribute>) -> R,
+    {
+        let frame = <&BelongingVideoFrame as Into<VideoFrameProxy>>::into(&self.0);
+        let mut frame = frame.write();
+        let frame = &mut **frame;
+        let uuid = frame.uuid;
+        let object = frame
+            .objects
+            .get_mut(&self.1)
+            .unwrap_or_else(|| panic!("Object {} not found in the frame {}", self.1, uuid));
+        check_objects_mutable(
+            &frame.frozen_objects,
+            frame.stage.as_deref(),
+            &object.namespace,
+            "update attributes of",
+        )?;
+        Ok(f(&mut object.attributes))
+    }
+
+    /// Returns the box selected by the closure. The box is shared with the object unless the
+    /// object is frozen, the box setters bypass the freeze check.
+    ///
+    fn with_box_ref<F>(&self, f: F) -> Option<RBBox>
+    where
+        F: FnOnce(&VideoObject) -> Option<&RBBox>,
+    {
+        let frame = <&BelongingVideoFrame as Into<VideoFrameProxy>>::into(&self.0);
+        let frame = frame.inner.read_recursive();
+        let object = frame
+            .objects
+            .get(&self.1)
+            .unwrap_or_else(|| panic!("Object {} not found in the frame {}", self.1, frame.uuid));
+        let frozen = frame
+            .check_objects_mutable(&object.namespace, "modify")
+            .is_err();
+        f(object).map(|b| if frozen { b.copy() } else { b.clone() })
+    }
+}
+
 impl ToSerdeJsonValue for BorrowedVideoObject {
     fn to_serde_json_value(&self) -> Value {
         self.with_object_ref(|o| o.to_serde_json_value())
@@ -161,18 +289,21 @@ impl WithAttributes for BorrowedVideoObject {
         f(&object.attributes)
     }
 
+    /// The attributes of a frozen object are changed in a copy which is discarded and the
+    /// violation is logged, see [`BorrowedVideoObject::try_with_attributes_mut`].
+    ///
     fn with_attributes_mut<F, R>(&mut self, f: F) -> R
     where
         F: FnOnce(&mut Vec<Attribute>) -> R,
     {
-        let frame = <&BelongingVideoFrame as Into<VideoFrameProxy>>::into(&self.0);
-        let mut frame = frame.inner.0.write();
-        let uuid = frame.uuid;
-        let object = frame
-            .objects
-            .get_mut(&self.1)
-            .unwrap_or_else(|| panic!("Object {} not found in the frame {}", self.1, uuid));
-        f(&mut object.attributes)
+        let mut f = Some(f);
+        match self.try_with_attributes_mut(|attributes| (f.take().unwrap())(attributes)) {
+            Ok(res) => res,
+            Err(e) => {
+                log::error!(target: "savant_rs::frozen_objects", "{}", e);
+                (f.take().unwrap())(&mut self.with_attributes_ref(|a| a.clone()))
+            }
+        }
     }
 }
 
@@ -209,6 +340,9 @@ pub(crate) mod private {
             }
         }
         fn set_parent(&mut self, parent_opt: Option<i64>) -> anyhow::Result<()> {
+            if let Some(frame) = self.get_frame() {
+                frame.check_object_mutable(self.get_id(), "set the parent of")?;
+            }
             if let Some(parent) = parent_opt {
                 if self.get_frame().is_none() {
                     bail!("Cannot set parent to the object detached from a frame");
@@ -386,6 +520,50 @@ where
         });
     }
 
+    fn get_geo_position(&self) -> Option<GeoPosition> {
+        self.with_object_ref(|o| o.geo_position)
+    }
+
+    fn set_geo_position(&mut self, position: Option<GeoPosition>) {
+        self.with_object_mut(|o| o.geo_position = position);
+    }
+
+    /// The classification of the object data, the frame classification applies too.
+    ///
+    fn get_classification(&self) -> DataClassification {
+        self.with_object_ref(|o| o.classification)
+    }
+
+    fn set_classification(&mut self, classification: DataClassification) {
+        self.with_object_mut(|o| o.classification = classification);
+    }
+
+    fn get_payload(&self) -> Option<ObjectPayload> {
+        self.with_object_ref(|o| o.payload.clone())
+    }
+
+    fn set_payload(&mut self, payload: Option<ObjectPayload>) {
+        self.with_object_mut(|o| o.payload = payload);
+    }
+
+    /// Removes the payload from the object and returns it.
+    ///
+    fn take_payload(&mut self) -> Option<ObjectPayload> {
+        self.with_object_mut(|o| o.payload.take())
+    }
+
+    /// Copies the payload to the other object, the copies share the data.
+    ///
+    fn copy_payload_to<O: ObjectOperations>(&self, other: &mut O) {
+        other.set_payload(self.get_payload());
+    }
+
+    /// Moves the payload to the other object.
+    ///
+    fn transfer_payload_to<O: ObjectOperations>(&mut self, other: &mut O) {
+        other.set_payload(self.take_payload());
+    }
+
     fn set_draw_label(&mut self, draw_label: Option<String>) {
         self.with_object_mut(|o| o.draw_label = draw_label);
     }
@@ -413,7 +591,20 @@ where
 }
 
 impl ObjectOperations for VideoObject {}
-impl ObjectOperations for BorrowedVideoObject {}
+
+impl ObjectOperations for BorrowedVideoObject {
+    /// The box of a frozen object is a detached copy, changing it does not change the object.
+    ///
+    fn get_detection_box(&self) -> RBBox {
+        self.with_box_ref(|o| Some(&o.detection_box)).unwrap()
+    }
+
+    /// The box of a frozen object is a detached copy, changing it does not change the object.
+    ///
+    fn get_track_box(&self) -> Option<RBBox> {
+        self.with_box_ref(|o| o.track_box.as_ref())
+    }
+}
 
 impl ObjectAccess for VideoObject {
     fn with_object_ref<F, R>(&self, f: F) -> R
@@ -445,18 +636,19 @@ impl ObjectAccess for BorrowedVideoObject {
         f(object)
     }
 
+    /// A frozen object is changed in a copy which is discarded and the violation is logged,
+    /// see [`BorrowedVideoObject::try_with_object_mut`].
+    ///
     fn with_object_mut<F, R>(&mut self, f: F) -> R
     where
         F: FnOnce(&mut VideoObject) -> R,
     {
-        let frame = <&BelongingVideoFrame as Into<VideoFrameProxy>>::into(&self.0);
-        let mut frame = frame.inner.0.write();
-        let uuid = frame.uuid;
-        let object = frame
-            .objects
-            .get_mut(&self.1)
-            .unwrap_or_else(|| panic!("Object {} not found in the frame {}", self.1, uuid));
-        f(object)
+        let mut f = Some(f);
+        let mut res = None;
+        if let Err(e) = self.try_with_object_mut(|o| res = Some((f.take().unwrap())(o))) {
+            log::error!(target: "savant_rs::frozen_objects", "{}", e);
+        }
+        res.unwrap_or_else(|| (f.take().unwrap())(&mut self.with_object_ref(|o| o.clone())))
     }
 }
 
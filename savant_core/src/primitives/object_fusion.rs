@@ -0,0 +1,338 @@
+use crate::primitives::object::{ObjectOperations, VideoObject};
+use crate::primitives::RBBox;
+use anyhow::bail;
+use hashbrown::HashMap;
+
+/// The parameters of [`fuse_objects`].
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoxFusionConfig {
+    /// The IoU with the fused box of a cluster above which an object joins the cluster.
+    pub iou_threshold: f32,
+    /// The number of the models or frames the objects come from. The confidence of a cluster
+    /// found by fewer of them is lowered proportionally.
+    pub sources: usize,
+    /// Whether the objects with different namespaces or labels are fused.
+    pub cross_label: bool,
+    /// The confidence of the objects without one.
+    pub default_confidence: f32,
+}
+
+impl Default for BoxFusionConfig {
+    fn default() -> Self {
+        Self {
+            iou_threshold: 0.55,
+            sources: 1,
+            cross_label: false,
+            default_confidence: 1.0,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Cluster {
+    members: Vec<usize>,
+    namespace: String,
+    label: String,
+    track_id: Option<(i64, usize)>,
+    weight: f32,
+    confidence: f32,
+    xc: f32,
+    yc: f32,
+    width: f32,
+    height: f32,
+    angle: Option<f32>,
+}
+
+impl Cluster {
+    fn add(&mut self, index: usize, bbox: &RBBox, confidence: f32) {
+        let w = confidence.max(f32::EPSILON);
+        self.members.push(index);
+        self.weight += w;
+        self.confidence += confidence;
+        self.xc += w * bbox.get_xc();
+        self.yc += w * bbox.get_yc();
+        self.width += w * bbox.get_width();
+        self.height += w * bbox.get_height();
+        self.angle = match (self.angle, bbox.get_angle()) {
+            (Some(a), Some(b)) => Some(a + w * b),
+            _ => None,
+        };
+    }
+
+    fn fused_box(&self) -> RBBox {
+        RBBox::new(
+            self.xc / self.weight,
+            self.yc / self.weight,
+            self.width / self.weight,
+            self.height / self.weight,
+            self.angle.map(|a| a / self.weight),
+        )
+    }
+}
+
+/// Fuses the overlapping objects detected by several models or in several frames with the
+/// weighted box fusion: the objects are clustered by the IoU in the order of the confidence
+/// and the box of a cluster is the average of the boxes weighted by the confidence. Unlike
+/// the non-maximum suppression, the boxes of all the detectors contribute to the result.
+///
+/// The tracks are respected: the objects with the same track id are fused regardless of the
+/// IoU and the objects with different track ids are never fused.
+///
+/// A fused object is a detached copy of the most confident object of the cluster, with its
+/// id and attributes, the fused box and the average confidence of the cluster scaled by the
+/// part of [`BoxFusionConfig::sources`] which found it. The objects are returned in the
+/// order of the confidence of their most confident member.
+///
+pub fn fuse_objects<O: ObjectOperations>(
+    objects: &[O],
+    config: &BoxFusionConfig,
+) -> anyhow::Result<Vec<VideoObject>> {
+    if config.sources == 0 {
+        bail!("The number of the sources must be greater than 0");
+    }
+    let confidences = objects
+        .iter()
+        .map(|o| o.get_confidence().unwrap_or(config.default_confidence))
+        .collect::<Vec<_>>();
+    let mut order = (0..objects.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| confidences[*b].total_cmp(&confidences[*a]));
+
+    let mut clusters: Vec<Cluster> = Vec::new();
+    for index in order {
+        let object = &objects[index];
+        let bbox = object.get_detection_box();
+        let namespace = object.get_namespace();
+        let label = object.get_label();
+        let track_id = object.get_track_id();
+        let mut best: Option<(usize, f32)> = None;
+        for (ci, cluster) in clusters.iter().enumerate() {
+            if !config.cross_label && (cluster.namespace != namespace || cluster.label != label) {
+                continue;
+            }
+            match (cluster.track_id, track_id) {
+                (Some((a, _)), Some(b)) if a == b => {
+                    best = Some((ci, f32::INFINITY));
+                    break;
+                }
+                (Some(_), Some(_)) => continue,
+                _ => {}
+            }
+            let Ok(iou) = cluster.fused_box().iou(&bbox) else {
+                continue;
+            };
+            if iou > config.iou_threshold && best.is_none_or(|(_, b)| iou > b) {
+                best = Some((ci, iou));
+            }
+        }
+        let cluster = match best {
+            Some((ci, _)) => &mut clusters[ci],
+            None => {
+                clusters.push(Cluster {
+                    members: Vec::new(),
+                    namespace,
+                    label,
+                    track_id: None,
+                    weight: 0.0,
+                    confidence: 0.0,
+                    xc: 0.0,
+                    yc: 0.0,
+                    width: 0.0,
+                    height: 0.0,
+                    angle: Some(0.0),
+                });
+                clusters.last_mut().unwrap()
+            }
+        };
+        if cluster.track_id.is_none() {
+            cluster.track_id = track_id.map(|t| (t, index));
+        }
+        cluster.add(index, &bbox, confidences[index]);
+    }
+
+    Ok(clusters
+        .into_iter()
+        .map(|cluster| {
+            let representative = cluster.members[0];
+            let mut fused = objects[representative].detached_copy();
+            fused.set_detection_box(cluster.fused_box());
+            let members = cluster.members.len();
+            let confidence = cluster.confidence / members as f32
+                * members.min(config.sources) as f32
+                / config.sources as f32;
+            fused.set_confidence(Some(confidence));
+            if let Some((track_id, owner)) = cluster.track_id {
+                if owner != representative {
+                    match objects[owner].get_track_box() {
+                        Some(track_box) => fused.set_track_info(track_id, track_box.copy()),
+                        None => fused.set_track_id(Some(track_id)),
+                    }
+                }
+            }
+            fused
+        })
+        .collect())
+}
+
+/// Keeps the tracked objects alive through the frames the detectors miss them in, with the
+/// confidence decaying by the factor for every missed frame, so a short miss does not break
+/// the downstream logic relying on the presence of the object. A track is dropped when it
+/// is missed for more than `max_missed` frames or its confidence falls below
+/// `min_confidence`. The objects without a track id are ignored. An instance serves a
+/// single source.
+///
+#[derive(Debug)]
+pub struct ConfidenceDecay {
+    factor: f32,
+    min_confidence: f32,
+    max_missed: u32,
+    tracks: HashMap<i64, (VideoObject, u32)>,
+}
+
+impl ConfidenceDecay {
+    pub fn new(factor: f32, min_confidence: f32, max_missed: u32) -> anyhow::Result<Self> {
+        if !(factor > 0.0 && factor <= 1.0) {
+            bail!("The decay factor must be in (0, 1], got {}", factor);
+        }
+        Ok(Self {
+            factor,
+            min_confidence,
+            max_missed,
+            tracks: HashMap::new(),
+        })
+    }
+
+    /// Accounts the objects detected in the frame and returns the detached copies of the
+    /// tracked objects missed in it with the decayed confidence, in the order of the track
+    /// ids. The returned objects are meant to be added to the frame.
+    ///
+    pub fn update<O: ObjectOperations>(&mut self, objects: &[O]) -> Vec<VideoObject> {
+        let mut seen = hashbrown::HashSet::new();
+        for object in objects {
+            if let Some(track_id) = object.get_track_id() {
+                seen.insert(track_id);
+                self.tracks.insert(track_id, (object.detached_copy(), 0));
+            }
+        }
+        let (factor, min_confidence, max_missed) =
+            (self.factor, self.min_confidence, self.max_missed);
+        let mut decayed = Vec::new();
+        self.tracks.retain(|track_id, (object, missed)| {
+            if seen.contains(track_id) {
+                return true;
+            }
+            *missed += 1;
+            if *missed > max_missed {
+                return false;
+            }
+            let confidence = object.get_confidence().unwrap_or(1.0) * factor.powi(*missed as i32);
+            if confidence < min_confidence {
+                return false;
+            }
+            let mut copy = object.clone();
+            copy.set_confidence(Some(confidence));
+            decayed.push((*track_id, copy));
+            true
+        });
+        decayed.sort_by_key(|(track_id, _)| *track_id);
+        decayed.into_iter().map(|(_, o)| o).collect()
+    }
+
+    /// The number of the tracks remembered.
+    ///
+    pub fn len(&self) -> usize {
+        self.tracks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
+    /// Forgets the tracks, e.g. when the stream restarts.
+    ///
+    pub fn reset(&mut self) {
+        self.tracks.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::object::{ObjectOperations, VideoObject};
+    use crate::primitives::object_fusion::{fuse_objects, BoxFusionConfig, ConfidenceDecay};
+    use crate::primitives::RBBox;
+
+    fn detection(id: i64, bbox: RBBox, confidence: f32, track_id: Option<i64>) -> VideoObject {
+        VideoObject {
+            id,
+            namespace: "detector".to_string(),
+            label: "person".to_string(),
+            detection_box: bbox,
+            confidence: Some(confidence),
+            track_id,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_fuse_objects() -> anyhow::Result<()> {
+        let objects = vec![
+            detection(0, RBBox::ltrb(0.0, 0.0, 10.0, 10.0), 0.9, Some(5)),
+            detection(1, RBBox::ltrb(2.0, 0.0, 12.0, 10.0), 0.3, None),
+            detection(2, RBBox::ltrb(100.0, 0.0, 110.0, 10.0), 0.8, Some(7)),
+            // far from the track but with its id
+            detection(3, RBBox::ltrb(200.0, 0.0, 210.0, 10.0), 0.4, Some(7)),
+            // overlaps the first one but is another track
+            detection(4, RBBox::ltrb(0.0, 1.0, 10.0, 11.0), 0.5, Some(8)),
+        ];
+        let config = BoxFusionConfig {
+            sources: 2,
+            ..Default::default()
+        };
+        let fused = fuse_objects(&objects, &config)?;
+        assert_eq!(fused.len(), 3);
+
+        assert_eq!(fused[0].get_id(), 0);
+        assert_eq!(fused[0].get_track_id(), Some(5));
+        let bbox = fused[0].get_detection_box();
+        assert!((bbox.get_xc() - (0.9 * 5.0 + 0.3 * 7.0) / 1.2).abs() < 1e-4);
+        assert!((fused[0].get_confidence().unwrap() - 0.6).abs() < 1e-6);
+
+        assert_eq!(fused[1].get_track_id(), Some(7));
+        assert!((fused[1].get_confidence().unwrap() - 0.6).abs() < 1e-6);
+
+        assert_eq!(fused[2].get_track_id(), Some(8));
+        assert!((fused[2].get_confidence().unwrap() - 0.25).abs() < 1e-6);
+
+        let config = BoxFusionConfig {
+            sources: 0,
+            ..Default::default()
+        };
+        assert!(fuse_objects(&objects, &config).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_confidence_decay() -> anyhow::Result<()> {
+        let mut decay = ConfidenceDecay::new(0.5, 0.2, 3)?;
+        let object = detection(0, RBBox::ltrb(0.0, 0.0, 10.0, 10.0), 0.8, Some(1));
+        let untracked = detection(1, RBBox::ltrb(0.0, 0.0, 10.0, 10.0), 0.8, None);
+        assert!(decay.update(&[object.clone(), untracked]).is_empty());
+        assert_eq!(decay.len(), 1);
+
+        let missed = decay.update::<VideoObject>(&[]);
+        assert_eq!(missed.len(), 1);
+        assert_eq!(missed[0].get_confidence(), Some(0.4));
+        assert_eq!(
+            decay.update::<VideoObject>(&[])[0].get_confidence(),
+            Some(0.2)
+        );
+        // 0.1 is below the minimal confidence
+        assert!(decay.update::<VideoObject>(&[]).is_empty());
+        assert!(decay.is_empty());
+
+        decay.update(&[object]);
+        assert_eq!(decay.update::<VideoObject>(&[]).len(), 1);
+        assert!(ConfidenceDecay::new(1.5, 0.2, 3).is_err());
+        Ok(())
+    }
+}
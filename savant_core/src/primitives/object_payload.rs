@@ -0,0 +1,110 @@
+use anyhow::bail;
+use std::sync::Arc;
+
+/// The largest payload an object carries, 16 MiB.
+pub const MAX_OBJECT_PAYLOAD_SIZE: usize = 16 * 1024 * 1024;
+
+/// An opaque binary artifact of an object, e.g. a feature map or a crop, passed between the
+/// stages. The payload is immutable, so the copies share the data.
+///
+/// The payload is never copied with the object implicitly: a cloned or detached object has
+/// no payload, it is copied or moved to another object explicitly. It is not serialized
+/// unless [`ObjectPayload::with_serialization`] enables it.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectPayload {
+    content_type: String,
+    data: Arc<[u8]>,
+    serialized: bool,
+}
+
+impl ObjectPayload {
+    pub fn new(content_type: &str, data: Vec<u8>) -> anyhow::Result<Self> {
+        if content_type.is_empty() {
+            bail!("The payload content type must not be empty");
+        }
+        if data.len() > MAX_OBJECT_PAYLOAD_SIZE {
+            bail!(
+                "The payload size {} exceeds the limit of {} bytes",
+                data.len(),
+                MAX_OBJECT_PAYLOAD_SIZE
+            );
+        }
+        Ok(Self {
+            content_type: content_type.to_string(),
+            data: data.into(),
+            serialized: false,
+        })
+    }
+
+    /// Whether the payload is serialized with the object.
+    ///
+    pub fn with_serialization(mut self, serialized: bool) -> Self {
+        self.serialized = serialized;
+        self
+    }
+
+    pub fn get_content_type(&self) -> &str {
+        &self.content_type
+    }
+
+    pub fn get_data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn is_serialized(&self) -> bool {
+        self.serialized
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::object::ObjectOperations;
+    use crate::primitives::object_payload::{ObjectPayload, MAX_OBJECT_PAYLOAD_SIZE};
+    use crate::test::gen_frame;
+
+    #[test]
+    fn test_payload_lifecycle() -> anyhow::Result<()> {
+        assert!(ObjectPayload::new("", vec![1]).is_err());
+        assert!(ObjectPayload::new("raw", vec![0; MAX_OBJECT_PAYLOAD_SIZE + 1]).is_err());
+
+        let frame = gen_frame();
+        let mut source = frame.get_object(0).unwrap();
+        let mut target = frame.get_object(1).unwrap();
+        source.set_payload(Some(ObjectPayload::new("image/jpeg", vec![1, 2, 3])?));
+        // the object borrowed again sees the payload
+        assert_eq!(
+            frame
+                .get_object(0)
+                .unwrap()
+                .get_payload()
+                .unwrap()
+                .get_data(),
+            &[1, 2, 3]
+        );
+        // implicit copies do not carry the payload
+        assert!(source.detached_copy().get_payload().is_none());
+
+        source.copy_payload_to(&mut target);
+        assert!(source.get_payload().is_some());
+        assert_eq!(target.get_payload(), source.get_payload());
+
+        target.set_payload(None);
+        source.transfer_payload_to(&mut target);
+        assert!(source.get_payload().is_none());
+        assert_eq!(
+            target.take_payload().unwrap().get_content_type(),
+            "image/jpeg"
+        );
+        assert!(target.get_payload().is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+use crate::primitives::RBBox;
+use serde::{Deserialize, Serialize};
+
+/// Hints telling downstream stages how much work a frame deserves. They are set by cheap
+/// front-ends (e.g. motion detection) and honored by batching and inference stages.
+///
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProcessingHints {
+    /// The frame does not need inference, it is only passed through.
+    pub skip_inference: bool,
+    /// The regions which need inference, the whole frame when empty.
+    pub roi_list: Vec<RBBox>,
+    /// The allowed degradation of processing, `0` is the full quality; stages choose what a
+    /// level means to them (a lighter model, a lower resolution, etc.).
+    pub degrade_level: u8,
+}
+
+impl ProcessingHints {
+    pub fn is_default(&self) -> bool {
+        !self.skip_inference && self.roi_list.is_empty() && self.degrade_level == 0
+    }
+
+    /// The regions an inference stage must process for a frame of the given size: nothing
+    /// when inference is skipped, the regions of interest when they are set, otherwise the
+    /// whole frame.
+    ///
+    pub fn inference_regions(&self, width: i64, height: i64) -> Vec<RBBox> {
+        if self.skip_inference {
+            Vec::new()
+        } else if self.roi_list.is_empty() {
+            vec![RBBox::ltwh(0.0, 0.0, width as f32, height as f32)]
+        } else {
+            self.roi_list.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::processing_hints::ProcessingHints;
+    use crate::primitives::RBBox;
+
+    #[test]
+    fn test_inference_regions() {
+        let mut hints = ProcessingHints::default();
+        assert!(hints.is_default());
+        assert_eq!(
+            hints.inference_regions(1280, 720),
+            vec![RBBox::ltwh(0.0, 0.0, 1280.0, 720.0)]
+        );
+        hints.roi_list = vec![RBBox::ltwh(10.0, 10.0, 100.0, 100.0)];
+        assert_eq!(hints.inference_regions(1280, 720), hints.roi_list);
+        hints.skip_inference = true;
+        assert!(hints.inference_regions(1280, 720).is_empty());
+        assert!(!hints.is_default());
+    }
+}
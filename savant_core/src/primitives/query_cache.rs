@@ -0,0 +1,93 @@
+use hashbrown::HashMap;
+use parking_lot::Mutex;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct QueryCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    /// The generation of the frame the results were computed for.
+    generation: u64,
+    /// The total number of the modifications of the object boxes, the boxes are changed
+    /// without locking the frame, so the generation does not cover them.
+    box_modifications: u64,
+    results: HashMap<String, Vec<i64>>,
+    hits: u64,
+    misses: u64,
+}
+
+/// The results of the queries evaluated against a frame, keyed by the query. The results
+/// are valid while the frame and the boxes of its objects are not modified, any change
+/// drops all of them. The queries cached must not depend on anything but the frame, e.g. a
+/// user-defined predicate reading external state must not be used with the cache.
+///
+/// The cache is enabled per frame with
+/// [`crate::primitives::frame::VideoFrameProxy::set_query_cache`] and is not copied with
+/// the frame.
+///
+#[derive(Debug, Default)]
+pub struct QueryCache(Mutex<CacheState>);
+
+impl Clone for QueryCache {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl QueryCache {
+    pub fn get_stats(&self) -> QueryCacheStats {
+        let state = self.0.lock();
+        QueryCacheStats {
+            hits: state.hits,
+            misses: state.misses,
+            entries: state.results.len(),
+        }
+    }
+
+    fn validate(state: &mut CacheState, generation: u64, box_modifications: u64) {
+        if (state.generation, state.box_modifications) != (generation, box_modifications) {
+            state.results.clear();
+            state.generation = generation;
+            state.box_modifications = box_modifications;
+        }
+    }
+
+    /// The ids of the objects matching the query if the frame is not modified since they
+    /// were stored.
+    ///
+    pub(crate) fn get(
+        &self,
+        query: &str,
+        generation: u64,
+        box_modifications: u64,
+    ) -> Option<Vec<i64>> {
+        let mut state = self.0.lock();
+        Self::validate(&mut state, generation, box_modifications);
+        match state.results.get(query).cloned() {
+            Some(ids) => {
+                state.hits += 1;
+                Some(ids)
+            }
+            None => {
+                state.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub(crate) fn put(
+        &self,
+        query: String,
+        generation: u64,
+        box_modifications: u64,
+        ids: Vec<i64>,
+    ) {
+        let mut state = self.0.lock();
+        Self::validate(&mut state, generation, box_modifications);
+        state.results.insert(query, ids);
+    }
+}
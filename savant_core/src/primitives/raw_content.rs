@@ -0,0 +1,774 @@
+use crate::primitives::content_encoding::ContentEncoding;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+const RAW_CODEC_PREFIX: &str = "raw-";
+
+/// The pixel format of raw frame content. Raw frames carry the format in the codec as
+/// `raw-<name>`, like `raw-rgb8` or `raw-nv12`.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PixelFormat {
+    Gray8,
+    Rgb8,
+    Bgr8,
+    Rgba8,
+    Bgra8,
+    /// A luma plane followed by an interleaved UV plane subsampled by two in both directions.
+    Nv12,
+    /// A luma plane followed by U and V planes subsampled by two in both directions.
+    I420,
+}
+
+impl PixelFormat {
+    pub const ALL: [PixelFormat; 7] = [
+        PixelFormat::Gray8,
+        PixelFormat::Rgb8,
+        PixelFormat::Bgr8,
+        PixelFormat::Rgba8,
+        PixelFormat::Bgra8,
+        PixelFormat::Nv12,
+        PixelFormat::I420,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PixelFormat::Gray8 => "gray8",
+            PixelFormat::Rgb8 => "rgb8",
+            PixelFormat::Bgr8 => "bgr8",
+            PixelFormat::Rgba8 => "rgba8",
+            PixelFormat::Bgra8 => "bgra8",
+            PixelFormat::Nv12 => "nv12",
+            PixelFormat::I420 => "i420",
+        }
+    }
+
+    pub fn codec(&self) -> String {
+        format!("{}{}", RAW_CODEC_PREFIX, self.name())
+    }
+
+    /// Parses the codec of a raw frame, `None` for other codecs.
+    ///
+    pub fn from_codec(codec: &str) -> Option<Self> {
+        codec
+            .to_ascii_lowercase()
+            .strip_prefix(RAW_CODEC_PREFIX)
+            .and_then(|name| name.parse().ok())
+    }
+
+    /// The number of bytes per pixel of the single-plane formats, `None` for planar formats.
+    ///
+    pub fn bytes_per_pixel(&self) -> Option<usize> {
+        match self {
+            PixelFormat::Gray8 => Some(1),
+            PixelFormat::Rgb8 | PixelFormat::Bgr8 => Some(3),
+            PixelFormat::Rgba8 | PixelFormat::Bgra8 => Some(4),
+            PixelFormat::Nv12 | PixelFormat::I420 => None,
+        }
+    }
+
+    pub fn planes(&self) -> usize {
+        match self {
+            PixelFormat::Nv12 => 2,
+            PixelFormat::I420 => 3,
+            _ => 1,
+        }
+    }
+
+    /// The number of meaningful bytes in a row and the number of rows of the plane, fails when
+    /// the row size overflows.
+    ///
+    pub fn plane_size(
+        &self,
+        plane: usize,
+        width: usize,
+        height: usize,
+    ) -> Result<(usize, usize), RawContentError> {
+        let (chroma_width, chroma_height) = (width.div_ceil(2), height.div_ceil(2));
+        let (row_bytes, rows) = match (self, plane) {
+            (PixelFormat::Nv12, 1) => (chroma_width.checked_mul(2), chroma_height),
+            (PixelFormat::I420, 1 | 2) => (Some(chroma_width), chroma_height),
+            (PixelFormat::Nv12 | PixelFormat::I420, _) => (Some(width), height),
+            (packed, _) => (
+                width.checked_mul(packed.bytes_per_pixel().unwrap_or(1)),
+                height,
+            ),
+        };
+        row_bytes
+            .map(|row_bytes| (row_bytes, rows))
+            .ok_or(RawContentError::LayoutOverflow)
+    }
+}
+
+impl Display for PixelFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for PixelFormat {
+    type Err = RawContentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PixelFormat::ALL
+            .into_iter()
+            .find(|f| f.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| RawContentError::UnknownPixelFormat(s.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum RawContentError {
+    #[error("Unknown pixel format '{0}'")]
+    UnknownPixelFormat(String),
+    #[error("The {format} format has {expected} planes, {actual} are described")]
+    PlaneCount {
+        format: PixelFormat,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("The stride {stride} of plane {plane} is less than its row size {row_bytes}")]
+    StrideTooSmall {
+        plane: usize,
+        stride: usize,
+        row_bytes: usize,
+    },
+    #[error("Content size {size} is less than {required} bytes required by the layout")]
+    SizeMismatch { size: usize, required: usize },
+    #[error("The layout describes a buffer larger than the address space")]
+    LayoutOverflow,
+    #[error("The content has no plane {0}")]
+    NoPlane(usize),
+    #[error("Unknown content encoding '{0}'")]
+    UnknownContentEncoding(String),
+    #[error("The {0} content codec failed: {1}")]
+    ContentCodec(ContentEncoding, String),
+}
+
+/// The placement of a plane in the content buffer.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Plane {
+    pub offset: usize,
+    /// The distance in bytes between the starts of two consecutive rows.
+    pub stride: usize,
+}
+
+/// Describes how raw pixels are laid out in the content buffer.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawLayout {
+    format: PixelFormat,
+    width: usize,
+    height: usize,
+    planes: Vec<Plane>,
+}
+
+impl RawLayout {
+    pub fn new(
+        format: PixelFormat,
+        width: usize,
+        height: usize,
+        planes: Vec<Plane>,
+    ) -> Result<Self, RawContentError> {
+        if planes.len() != format.planes() {
+            return Err(RawContentError::PlaneCount {
+                format,
+                expected: format.planes(),
+                actual: planes.len(),
+            });
+        }
+        for (index, plane) in planes.iter().enumerate() {
+            let (row_bytes, _) = format.plane_size(index, width, height)?;
+            if plane.stride < row_bytes {
+                return Err(RawContentError::StrideTooSmall {
+                    plane: index,
+                    stride: plane.stride,
+                    row_bytes,
+                });
+            }
+        }
+        let layout = Self {
+            format,
+            width,
+            height,
+            planes,
+        };
+        layout.required_size()?;
+        Ok(layout)
+    }
+
+    /// The layout without padding: the rows and the planes follow each other. Fails when the
+    /// planes do not fit the address space.
+    ///
+    pub fn packed(
+        format: PixelFormat,
+        width: usize,
+        height: usize,
+    ) -> Result<Self, RawContentError> {
+        let mut offset = 0usize;
+        let mut planes = Vec::with_capacity(format.planes());
+        for index in 0..format.planes() {
+            let (row_bytes, rows) = format.plane_size(index, width, height)?;
+            planes.push(Plane {
+                offset,
+                stride: row_bytes,
+            });
+            offset = row_bytes
+                .checked_mul(rows)
+                .and_then(|size| offset.checked_add(size))
+                .ok_or(RawContentError::LayoutOverflow)?;
+        }
+        Ok(Self {
+            format,
+            width,
+            height,
+            planes,
+        })
+    }
+
+    pub fn get_format(&self) -> PixelFormat {
+        self.format
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get_planes(&self) -> &[Plane] {
+        &self.planes
+    }
+
+    pub fn is_packed(&self) -> bool {
+        Self::packed(self.format, self.width, self.height).is_ok_and(|packed| *self == packed)
+    }
+
+    /// The minimal size of a buffer holding all planes, fails when the size overflows.
+    ///
+    pub fn required_size(&self) -> Result<usize, RawContentError> {
+        let mut required = 0;
+        for (index, plane) in self.planes.iter().enumerate() {
+            let (row_bytes, rows) = self.format.plane_size(index, self.width, self.height)?;
+            if rows == 0 {
+                continue;
+            }
+            let end = plane
+                .stride
+                .checked_mul(rows - 1)
+                .and_then(|size| size.checked_add(plane.offset))
+                .and_then(|size| size.checked_add(row_bytes))
+                .ok_or(RawContentError::LayoutOverflow)?;
+            required = required.max(end);
+        }
+        Ok(required)
+    }
+}
+
+/// Read access to the rows of a plane.
+///
+#[derive(Debug, Clone, Copy)]
+pub struct PlaneView<'a> {
+    data: &'a [u8],
+    stride: usize,
+    row_bytes: usize,
+    rows: usize,
+}
+
+impl<'a> PlaneView<'a> {
+    pub fn get_stride(&self) -> usize {
+        self.stride
+    }
+
+    pub fn get_row_bytes(&self) -> usize {
+        self.row_bytes
+    }
+
+    pub fn get_rows(&self) -> usize {
+        self.rows
+    }
+
+    /// The meaningful bytes of the row, without the padding.
+    ///
+    pub fn row(&self, y: usize) -> Option<&'a [u8]> {
+        (y < self.rows).then(|| &self.data[y * self.stride..y * self.stride + self.row_bytes])
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &'a [u8]> + '_ {
+        (0..self.rows).filter_map(|y| self.row(y))
+    }
+}
+
+/// Write access to the rows of a plane.
+///
+#[derive(Debug)]
+pub struct PlaneViewMut<'a> {
+    data: &'a mut [u8],
+    stride: usize,
+    row_bytes: usize,
+    rows: usize,
+}
+
+impl PlaneViewMut<'_> {
+    pub fn get_rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> Option<&mut [u8]> {
+        (y < self.rows).then(|| &mut self.data[y * self.stride..y * self.stride + self.row_bytes])
+    }
+}
+
+/// The content stored in the frame: the bytes and, for raw pixels, their layout. Encoded
+/// content and content of unknown layout has no layout. Compressed content keeps the
+/// compressed bytes and decompresses them on the first access to the data.
+///
+#[derive(Debug, Clone, Default)]
+pub struct InternalFrame {
+    data: Vec<u8>,
+    layout: Option<RawLayout>,
+    compressed: Option<Compressed>,
+}
+
+/// The codec and the decoded size of compressed content, the decoded bytes are cached.
+///
+#[derive(Debug, Clone)]
+struct Compressed {
+    encoding: ContentEncoding,
+    size: usize,
+    decoded: OnceLock<Vec<u8>>,
+}
+
+impl PartialEq for InternalFrame {
+    fn eq(&self, other: &Self) -> bool {
+        // content which fails to decompress is not equal to any content
+        self.layout == other.layout
+            && matches!((self.get_data(), other.get_data()), (Ok(a), Ok(b)) if a == b)
+    }
+}
+
+impl From<Vec<u8>> for InternalFrame {
+    fn from(data: Vec<u8>) -> Self {
+        Self::new(data)
+    }
+}
+
+/// The packed layout of the raw codec, `None` when the codec is not raw or the size does not
+/// match the frame size.
+///
+fn codec_layout(codec: Option<&str>, width: i64, height: i64, size: usize) -> Option<RawLayout> {
+    codec
+        .and_then(PixelFormat::from_codec)
+        .zip(usize::try_from(width).ok())
+        .zip(usize::try_from(height).ok())
+        .and_then(|((format, width), height)| RawLayout::packed(format, width, height).ok())
+        .filter(|layout| layout.required_size() == Ok(size))
+}
+
+impl InternalFrame {
+    pub fn new(data: Vec<u8>) -> Self {
+        Self {
+            data,
+            layout: None,
+            compressed: None,
+        }
+    }
+
+    /// Raw pixels, the buffer must hold all planes of the layout.
+    ///
+    pub fn raw(data: Vec<u8>, layout: RawLayout) -> Result<Self, RawContentError> {
+        let required = layout.required_size()?;
+        if data.len() < required {
+            return Err(RawContentError::SizeMismatch {
+                size: data.len(),
+                required,
+            });
+        }
+        Ok(Self {
+            data,
+            layout: Some(layout),
+            compressed: None,
+        })
+    }
+
+    /// The content with the packed layout derived from the raw codec and the frame size, the
+    /// content stays without a layout when the codec is not raw or the size does not match.
+    ///
+    pub fn from_codec(data: Vec<u8>, codec: Option<&str>, width: i64, height: i64) -> Self {
+        let layout = codec_layout(codec, width, height, data.len());
+        Self {
+            data,
+            layout,
+            compressed: None,
+        }
+    }
+
+    /// Compressed content of the decoded size, the layout describes the decoded bytes.
+    ///
+    pub fn compressed(
+        data: Vec<u8>,
+        encoding: ContentEncoding,
+        size: usize,
+        layout: Option<RawLayout>,
+    ) -> Result<Self, RawContentError> {
+        if let Some(layout) = &layout {
+            let required = layout.required_size()?;
+            if size < required {
+                return Err(RawContentError::SizeMismatch { size, required });
+            }
+        }
+        Ok(Self {
+            data,
+            layout,
+            compressed: Some(Compressed {
+                encoding,
+                size,
+                decoded: OnceLock::new(),
+            }),
+        })
+    }
+
+    /// Compressed content with the packed layout derived from the raw codec and the frame
+    /// size, like [`InternalFrame::from_codec`].
+    ///
+    pub fn compressed_from_codec(
+        data: Vec<u8>,
+        encoding: ContentEncoding,
+        size: usize,
+        codec: Option<&str>,
+        width: i64,
+        height: i64,
+    ) -> Self {
+        Self {
+            data,
+            layout: codec_layout(codec, width, height, size),
+            compressed: Some(Compressed {
+                encoding,
+                size,
+                decoded: OnceLock::new(),
+            }),
+        }
+    }
+
+    /// The content compressed with the codec. Raw pixels are packed before the compression
+    /// and get the packed layout, content already compressed with the codec is cloned.
+    ///
+    pub fn compress(&self, encoding: ContentEncoding) -> Result<Self, RawContentError> {
+        if self.get_encoding() == Some(encoding) {
+            return Ok(self.clone());
+        }
+        let packed = self.try_to_packed()?;
+        let layout = self
+            .layout
+            .as_ref()
+            .map(|l| RawLayout::packed(l.format, l.width, l.height))
+            .transpose()?;
+        Self::compressed(encoding.compress(&packed)?, encoding, packed.len(), layout)
+    }
+
+    /// The uncompressed content, content which is not compressed is cloned.
+    ///
+    pub fn decompress(&self) -> Result<Self, RawContentError> {
+        let data = self.get_data()?.to_vec();
+        Ok(Self {
+            data,
+            layout: self.layout.clone(),
+            compressed: None,
+        })
+    }
+
+    /// The codec of compressed content.
+    ///
+    pub fn get_encoding(&self) -> Option<ContentEncoding> {
+        self.compressed.as_ref().map(|c| c.encoding)
+    }
+
+    /// The compressed bytes of compressed content.
+    ///
+    pub fn get_compressed_data(&self) -> Option<&[u8]> {
+        self.compressed.as_ref().map(|_| self.data.as_slice())
+    }
+
+    /// The decoded bytes, compressed content is decompressed on the first access.
+    ///
+    pub fn get_data(&self) -> Result<&[u8], RawContentError> {
+        let Some(compressed) = &self.compressed else {
+            return Ok(&self.data);
+        };
+        if let Some(decoded) = compressed.decoded.get() {
+            return Ok(decoded);
+        }
+        let decoded = compressed
+            .encoding
+            .decompress(&self.data, compressed.size)?;
+        Ok(compressed.decoded.get_or_init(|| decoded))
+    }
+
+    pub fn into_data(self) -> Result<Vec<u8>, RawContentError> {
+        match self.compressed {
+            None => Ok(self.data),
+            Some(_) => Ok(self.get_data()?.to_vec()),
+        }
+    }
+
+    pub fn get_layout(&self) -> Option<&RawLayout> {
+        self.layout.as_ref()
+    }
+
+    /// The size of the decoded bytes.
+    ///
+    pub fn len(&self) -> usize {
+        match &self.compressed {
+            Some(compressed) => compressed.size,
+            None => self.data.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Replaces compressed content with its decoded bytes.
+    ///
+    fn decompress_in_place(&mut self) -> Result<(), RawContentError> {
+        if self.compressed.is_some() {
+            self.data = self.get_data()?.to_vec();
+            self.compressed = None;
+        }
+        Ok(())
+    }
+
+    fn plane_geometry(&self, index: usize) -> Result<(Plane, usize, usize), RawContentError> {
+        let layout = self
+            .layout
+            .as_ref()
+            .ok_or(RawContentError::NoPlane(index))?;
+        let plane = *layout
+            .planes
+            .get(index)
+            .ok_or(RawContentError::NoPlane(index))?;
+        let (row_bytes, rows) = layout
+            .format
+            .plane_size(index, layout.width, layout.height)?;
+        Ok((plane, row_bytes, rows))
+    }
+
+    pub fn plane(&self, index: usize) -> Result<PlaneView<'_>, RawContentError> {
+        let (plane, row_bytes, rows) = self.plane_geometry(index)?;
+        Ok(PlaneView {
+            data: &self.get_data()?[plane.offset..],
+            stride: plane.stride,
+            row_bytes,
+            rows,
+        })
+    }
+
+    /// The mutable view of the plane, compressed content is decompressed for good.
+    ///
+    pub fn plane_mut(&mut self, index: usize) -> Result<PlaneViewMut<'_>, RawContentError> {
+        let (plane, row_bytes, rows) = self.plane_geometry(index)?;
+        self.decompress_in_place()?;
+        Ok(PlaneViewMut {
+            data: &mut self.data[plane.offset..],
+            stride: plane.stride,
+            row_bytes,
+            rows,
+        })
+    }
+
+    /// The decoded bytes with the padding between the rows and the planes removed. Content
+    /// without a layout is returned as is.
+    ///
+    pub fn try_to_packed(&self) -> Result<Vec<u8>, RawContentError> {
+        let data = self.get_data()?;
+        let Some(layout) = &self.layout else {
+            return Ok(data.to_vec());
+        };
+        if layout.is_packed() {
+            return Ok(data[..layout.required_size()?].to_vec());
+        }
+        let packed = RawLayout::packed(layout.format, layout.width, layout.height)?;
+        let mut res = Vec::with_capacity(packed.required_size()?);
+        for index in 0..layout.planes.len() {
+            self.plane(index)?
+                .rows()
+                .for_each(|row| res.extend_from_slice(row));
+        }
+        Ok(res)
+    }
+
+    /// Like [`InternalFrame::try_to_packed`], compressed content which fails to decompress is
+    /// logged and reads as empty.
+    ///
+    pub fn to_packed(&self) -> Vec<u8> {
+        self.try_to_packed().unwrap_or_else(|e| {
+            log::error!(
+                target: "savant_rs::raw_content",
+                "Failed to pack the frame content: {}",
+                e
+            );
+            Vec::new()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::content_encoding::ContentEncoding;
+    use crate::primitives::raw_content::{
+        InternalFrame, PixelFormat, Plane, RawContentError, RawLayout,
+    };
+
+    #[test]
+    fn test_packed_layout() -> anyhow::Result<()> {
+        let layout = RawLayout::packed(PixelFormat::Nv12, 5, 3)?;
+        assert_eq!(
+            layout.get_planes(),
+            &[
+                Plane {
+                    offset: 0,
+                    stride: 5
+                },
+                Plane {
+                    offset: 15,
+                    stride: 6
+                }
+            ]
+        );
+        assert_eq!(layout.required_size()?, 27);
+        assert!(layout.is_packed());
+        assert_eq!(PixelFormat::from_codec("RAW-NV12"), Some(PixelFormat::Nv12));
+        assert_eq!(PixelFormat::from_codec("h264"), None);
+        assert_eq!(PixelFormat::I420.codec(), "raw-i420");
+        Ok(())
+    }
+
+    #[test]
+    fn test_layout_overflow() {
+        assert_eq!(
+            RawLayout::packed(PixelFormat::Rgba8, usize::MAX / 2, 1),
+            Err(RawContentError::LayoutOverflow)
+        );
+        assert_eq!(
+            RawLayout::packed(PixelFormat::Gray8, usize::MAX, 2),
+            Err(RawContentError::LayoutOverflow)
+        );
+        assert_eq!(
+            RawLayout::new(
+                PixelFormat::Gray8,
+                1,
+                3,
+                vec![Plane {
+                    offset: 0,
+                    stride: usize::MAX / 2 + 1
+                }]
+            ),
+            Err(RawContentError::LayoutOverflow)
+        );
+        let frame = InternalFrame::from_codec(vec![0; 12], Some("raw-rgb8"), i64::MAX, 2);
+        assert!(frame.get_layout().is_none());
+    }
+
+    #[test]
+    fn test_strided_planes() -> anyhow::Result<()> {
+        assert!(matches!(
+            RawLayout::new(
+                PixelFormat::Rgb8,
+                2,
+                2,
+                vec![Plane {
+                    offset: 0,
+                    stride: 4
+                }]
+            ),
+            Err(RawContentError::StrideTooSmall { .. })
+        ));
+        // 2x2 RGB rows padded to 8 bytes
+        let layout = RawLayout::new(
+            PixelFormat::Rgb8,
+            2,
+            2,
+            vec![Plane {
+                offset: 0,
+                stride: 8,
+            }],
+        )?;
+        assert_eq!(layout.required_size()?, 14);
+        assert!(InternalFrame::raw(vec![0; 13], layout.clone()).is_err());
+        let data = (0..16).collect::<Vec<u8>>();
+        let mut frame = InternalFrame::raw(data, layout)?;
+        let plane = frame.plane(0)?;
+        assert_eq!(plane.get_rows(), 2);
+        assert_eq!(plane.row(1), Some(&[8, 9, 10, 11, 12, 13][..]));
+        assert!(plane.row(2).is_none());
+        assert!(frame.plane(1).is_err());
+        assert_eq!(
+            frame.to_packed(),
+            vec![0, 1, 2, 3, 4, 5, 8, 9, 10, 11, 12, 13]
+        );
+
+        frame.plane_mut(0)?.row_mut(0).unwrap().fill(0);
+        assert_eq!(&frame.get_data()?[..8], &[0, 0, 0, 0, 0, 0, 6, 7]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_codec() {
+        let frame = InternalFrame::from_codec(vec![0; 12], Some("raw-rgb8"), 2, 2);
+        assert_eq!(
+            frame.get_layout().map(|l| l.get_format()),
+            Some(PixelFormat::Rgb8)
+        );
+        let frame = InternalFrame::from_codec(vec![0; 11], Some("raw-rgb8"), 2, 2);
+        assert!(frame.get_layout().is_none());
+        let frame = InternalFrame::from_codec(vec![0; 12], Some("jpeg"), 2, 2);
+        assert!(frame.get_layout().is_none());
+    }
+
+    #[test]
+    fn test_compressed() -> anyhow::Result<()> {
+        // 2x2 RGB rows padded to 8 bytes
+        let layout = RawLayout::new(
+            PixelFormat::Rgb8,
+            2,
+            2,
+            vec![Plane {
+                offset: 0,
+                stride: 8,
+            }],
+        )?;
+        let frame = InternalFrame::raw((0..16).collect(), layout)?;
+        let compressed = frame.compress(ContentEncoding::Zstd)?;
+        assert_eq!(compressed.get_encoding(), Some(ContentEncoding::Zstd));
+        assert!(compressed.get_layout().unwrap().is_packed());
+        assert_eq!(compressed.len(), 12);
+        assert_eq!(compressed.get_data()?, frame.to_packed().as_slice());
+        assert_eq!(
+            compressed.plane(0)?.row(1),
+            Some(&[8, 9, 10, 11, 12, 13][..])
+        );
+
+        let mut decompressed = compressed.decompress()?;
+        assert!(decompressed.get_encoding().is_none());
+        assert_eq!(decompressed, compressed);
+
+        let mut lz4 = compressed.compress(ContentEncoding::Lz4)?;
+        assert_eq!(lz4.get_encoding(), Some(ContentEncoding::Lz4));
+        lz4.plane_mut(0)?.row_mut(0).unwrap().fill(0);
+        assert!(lz4.get_encoding().is_none());
+        decompressed.plane_mut(0)?.row_mut(0).unwrap().fill(0);
+        assert_eq!(lz4, decompressed);
+
+        let broken = InternalFrame::compressed(vec![1, 2, 3], ContentEncoding::Lz4, 12, None)?;
+        assert!(matches!(
+            broken.get_data(),
+            Err(RawContentError::ContentCodec(ContentEncoding::Lz4, _))
+        ));
+        assert_ne!(broken, broken.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use crate::json_api::ToSerdeJsonValue;
+use crate::primitives::frame::VideoFrameContent;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// An additional picture of the frame (a half resolution raw copy, a thumbnail, etc.)
+/// carried next to the primary content under a representation key. Consumers pick the
+/// representation they need instead of converting the primary content, sinks choose which
+/// representations they send.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentRepresentation {
+    pub codec: Option<String>,
+    pub width: i64,
+    pub height: i64,
+    pub content: Arc<VideoFrameContent>,
+}
+
+impl ContentRepresentation {
+    pub fn new(content: VideoFrameContent, width: i64, height: i64, codec: Option<String>) -> Self {
+        Self {
+            codec,
+            width,
+            height,
+            content: Arc::new(content),
+        }
+    }
+}
+
+impl ToSerdeJsonValue for ContentRepresentation {
+    fn to_serde_json_value(&self) -> Value {
+        serde_json::json!({
+            "codec": self.codec,
+            "width": self.width,
+            "height": self.height,
+            "content": self.content.to_serde_json_value(),
+        })
+    }
+}
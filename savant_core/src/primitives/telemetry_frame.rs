@@ -0,0 +1,102 @@
+use crate::json_api::ToSerdeJsonValue;
+use crate::primitives::{Attribute, WithAttributes};
+use crate::utils::uuid_v7::frame_uuid;
+use serde_json::Value;
+use uuid::Uuid;
+
+/// A record of a non-video sensor (radar, lidar, GPS, IMU, etc.). The measurements are kept
+/// as typed attributes, the sensor kind tells consumers how to interpret them.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct TelemetryFrame {
+    pub source_id: String,
+    pub sensor: String,
+    pub uuid: u128,
+    pub pts: i64,
+    pub time_base: (i32, i32),
+    pub attributes: Vec<Attribute>,
+}
+
+const DEFAULT_ATTRIBUTES_COUNT: usize = 4;
+
+impl TelemetryFrame {
+    pub fn new(source_id: &str, sensor: &str, pts: i64) -> Self {
+        Self {
+            source_id: source_id.to_string(),
+            sensor: sensor.to_string(),
+            uuid: frame_uuid().as_u128(),
+            pts,
+            time_base: (1, 1000000),
+            attributes: Vec::with_capacity(DEFAULT_ATTRIBUTES_COUNT),
+        }
+    }
+
+    pub fn get_uuid(&self) -> Uuid {
+        Uuid::from_u128(self.uuid)
+    }
+
+    pub fn get_source_id(&self) -> &str {
+        &self.source_id
+    }
+
+    pub fn get_sensor(&self) -> &str {
+        &self.sensor
+    }
+
+    pub fn json(&self) -> String {
+        serde_json::to_string(&self.to_serde_json_value()).unwrap()
+    }
+}
+
+impl ToSerdeJsonValue for TelemetryFrame {
+    fn to_serde_json_value(&self) -> Value {
+        serde_json::json!({
+            "type": "TelemetryFrame",
+            "source_id": self.source_id,
+            "sensor": self.sensor,
+            "uuid": self.get_uuid().to_string(),
+            "pts": self.pts,
+            "time_base": self.time_base,
+            "attributes": self.attributes.iter().filter_map(|v| if v.is_hidden { None } else { Some(v.to_serde_json_value()) }).collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl WithAttributes for TelemetryFrame {
+    fn with_attributes_ref<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&Vec<Attribute>) -> R,
+    {
+        f(&self.attributes)
+    }
+
+    fn with_attributes_mut<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Vec<Attribute>) -> R,
+    {
+        f(&mut self.attributes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::attribute_value::AttributeValue;
+    use crate::primitives::telemetry_frame::TelemetryFrame;
+    use crate::primitives::{Attribute, WithAttributes};
+
+    #[test]
+    fn test_json() {
+        let mut frame = TelemetryFrame::new("lidar-1", "lidar", 10);
+        frame.set_attribute(Attribute::persistent(
+            "lidar",
+            "range",
+            vec![AttributeValue::float(12.5, Some(0.9))],
+            &None,
+            false,
+        ));
+        frame.set_attribute(Attribute::persistent("lidar", "raw", vec![], &None, true));
+        let v: serde_json::Value = serde_json::from_str(&frame.json()).unwrap();
+        assert_eq!(v["sensor"], "lidar");
+        assert_eq!(v["attributes"].as_array().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,189 @@
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// The physical quantity measured by a unit, values convert only between the units of the
+/// same dimension.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dimension {
+    Length,
+    Speed,
+    Time,
+    Angle,
+    Mass,
+    Ratio,
+    Pixels,
+}
+
+/// The unit of a numeric attribute value, serialized as its symbol, e.g. `km/h`.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Unit {
+    Meter,
+    Kilometer,
+    Centimeter,
+    Millimeter,
+    Foot,
+    Mile,
+    MetersPerSecond,
+    KilometersPerHour,
+    MilesPerHour,
+    Knot,
+    Second,
+    Millisecond,
+    Microsecond,
+    Minute,
+    Hour,
+    Degree,
+    Radian,
+    Kilogram,
+    Gram,
+    Ratio,
+    Percent,
+    Pixel,
+}
+
+const UNITS: [Unit; 22] = [
+    Unit::Meter,
+    Unit::Kilometer,
+    Unit::Centimeter,
+    Unit::Millimeter,
+    Unit::Foot,
+    Unit::Mile,
+    Unit::MetersPerSecond,
+    Unit::KilometersPerHour,
+    Unit::MilesPerHour,
+    Unit::Knot,
+    Unit::Second,
+    Unit::Millisecond,
+    Unit::Microsecond,
+    Unit::Minute,
+    Unit::Hour,
+    Unit::Degree,
+    Unit::Radian,
+    Unit::Kilogram,
+    Unit::Gram,
+    Unit::Ratio,
+    Unit::Percent,
+    Unit::Pixel,
+];
+
+impl Unit {
+    /// The symbol, the dimension and the factor converting the unit to the base unit of the
+    /// dimension (meters, m/s, seconds, radians, kilograms, ratio, pixels).
+    ///
+    fn spec(self) -> (&'static str, Dimension, f64) {
+        match self {
+            Unit::Meter => ("m", Dimension::Length, 1.0),
+            Unit::Kilometer => ("km", Dimension::Length, 1000.0),
+            Unit::Centimeter => ("cm", Dimension::Length, 0.01),
+            Unit::Millimeter => ("mm", Dimension::Length, 0.001),
+            Unit::Foot => ("ft", Dimension::Length, 0.3048),
+            Unit::Mile => ("mi", Dimension::Length, 1609.344),
+            Unit::MetersPerSecond => ("m/s", Dimension::Speed, 1.0),
+            Unit::KilometersPerHour => ("km/h", Dimension::Speed, 1000.0 / 3600.0),
+            Unit::MilesPerHour => ("mph", Dimension::Speed, 1609.344 / 3600.0),
+            Unit::Knot => ("kn", Dimension::Speed, 1852.0 / 3600.0),
+            Unit::Second => ("s", Dimension::Time, 1.0),
+            Unit::Millisecond => ("ms", Dimension::Time, 0.001),
+            Unit::Microsecond => ("us", Dimension::Time, 0.000_001),
+            Unit::Minute => ("min", Dimension::Time, 60.0),
+            Unit::Hour => ("h", Dimension::Time, 3600.0),
+            Unit::Degree => ("deg", Dimension::Angle, std::f64::consts::PI / 180.0),
+            Unit::Radian => ("rad", Dimension::Angle, 1.0),
+            Unit::Kilogram => ("kg", Dimension::Mass, 1.0),
+            Unit::Gram => ("g", Dimension::Mass, 0.001),
+            Unit::Ratio => ("ratio", Dimension::Ratio, 1.0),
+            Unit::Percent => ("%", Dimension::Ratio, 0.01),
+            Unit::Pixel => ("px", Dimension::Pixels, 1.0),
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        self.spec().0
+    }
+
+    pub fn dimension(self) -> Dimension {
+        self.spec().1
+    }
+
+    /// Converts the value measured in the unit to the target unit.
+    ///
+    pub fn convert(self, value: f64, to: Unit) -> anyhow::Result<f64> {
+        if self == to {
+            return Ok(value);
+        }
+        let (from_symbol, from_dimension, from_factor) = self.spec();
+        let (to_symbol, to_dimension, to_factor) = to.spec();
+        if from_dimension != to_dimension {
+            bail!(
+                "Cannot convert {} ({:?}) to {} ({:?})",
+                from_symbol,
+                from_dimension,
+                to_symbol,
+                to_dimension
+            );
+        }
+        Ok(value * from_factor / to_factor)
+    }
+}
+
+impl FromStr for Unit {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match UNITS.iter().find(|u| u.symbol() == s) {
+            Some(unit) => Ok(*unit),
+            None => bail!(
+                "Unknown unit `{}`, expected one of {}",
+                s,
+                UNITS.map(Unit::symbol).join(", ")
+            ),
+        }
+    }
+}
+
+impl TryFrom<String> for Unit {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Unit> for String {
+    fn from(unit: Unit) -> Self {
+        unit.symbol().to_string()
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::unit::{Dimension, Unit};
+
+    #[test]
+    fn test_convert() -> anyhow::Result<()> {
+        assert!(
+            (Unit::MetersPerSecond.convert(10.0, Unit::KilometersPerHour)? - 36.0).abs() < 1e-9
+        );
+        assert!((Unit::Kilometer.convert(1.5, Unit::Meter)? - 1500.0).abs() < 1e-9);
+        assert!((Unit::Degree.convert(180.0, Unit::Radian)? - std::f64::consts::PI).abs() < 1e-9);
+        assert!(Unit::Second.convert(1.0, Unit::Meter).is_err());
+
+        assert_eq!("km/h".parse::<Unit>()?, Unit::KilometersPerHour);
+        assert!("furlong".parse::<Unit>().is_err());
+        assert_eq!(Unit::Percent.dimension(), Dimension::Ratio);
+        assert_eq!(serde_json::to_string(&Unit::MetersPerSecond)?, r#""m/s""#);
+        assert_eq!(serde_json::from_str::<Unit>(r#""ms""#)?, Unit::Millisecond);
+        Ok(())
+    }
+}
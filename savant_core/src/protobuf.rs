@@ -1,13 +1,42 @@
 use crate::message::{Message, MessageEnvelope, MessageMeta};
 use crate::otlp::PropagatedContext;
+use crate::primitives::content_encoding::ContentEncoding;
 use savant_protobuf::generated;
 
+#[cfg(test)]
+mod compatibility;
+mod encrypted_stream;
+mod lazy;
 mod serialize;
+mod stream;
 
-pub use generated::{UserData, VideoFrame, VideoFrameBatch, VideoFrameUpdate, VideoObject};
+pub use encrypted_stream::{
+    EncryptedMessageStreamReader, EncryptedMessageStreamWriter, EnvKeyResolver, KeyResolver,
+    MasterKey, RecordLocation, SegmentMetadata,
+};
+pub use generated::{
+    Attribute, UserData, VideoFrame, VideoFrameBatch, VideoFrameUpdate, VideoObject,
+};
+pub use lazy::LazyVideoFrame;
+pub use serialize::classification::{
+    get_classification_policy, set_classification_policy, ClassificationAction,
+    ClassificationPolicy,
+};
 pub use serialize::from_pb;
+pub use serialize::ingestion::{
+    get_ingestion_policy, set_ingestion_policy, CollisionAction, Ingest, IngestionPolicy,
+};
+pub use serialize::profiles::{
+    get_serialization_profile, get_serialization_profiles, register_serialization_profile,
+    unregister_serialization_profile, SerializationProfile,
+};
+pub(crate) use serialize::redaction::redact_frame_for_export;
+pub use serialize::redaction::{get_export_filter, set_export_filter, RedactionFilter};
 pub use serialize::Error;
 pub use serialize::ToProtobuf;
+pub use stream::{
+    BatchStreamReader, BatchStreamWriter, MessageStreamReader, MessageStreamWriter, MAX_RECORD_SIZE,
+};
 
 impl From<&Message> for generated::Message {
     fn from(m: &Message) -> Self {
@@ -51,15 +80,103 @@ impl TryFrom<&generated::Message> for Message {
 
 pub fn serialize(m: &Message) -> Result<Vec<u8>, Error> {
     use prost::Message as ProstMessage;
-    let message = generated::Message::from(m);
+    let mut message = generated::Message::from(m);
+    serialize::redaction::redact_for_export(&mut message)?;
     let mut buf = Vec::new();
     message.encode(&mut buf)?;
     Ok(buf)
 }
 
-pub fn deserialize(bytes: &[u8]) -> Result<Message, Error> {
+/// Serializes the message for a sink with its own classification policy instead of the
+/// process-wide one, e.g. a sink writing to a restricted storage accepts the data other sinks
+/// redact. The export filter is applied.
+///
+pub fn serialize_with_policy(m: &Message, policy: &ClassificationPolicy) -> Result<Vec<u8>, Error> {
+    use prost::Message as ProstMessage;
+    let mut message = generated::Message::from(m);
+    serialize::redaction::redact_with_policy(&mut message, Some(policy))?;
+    let mut buf = Vec::new();
+    message.encode(&mut buf)?;
+    Ok(buf)
+}
+
+/// Serializes the message keeping only the listed content representations of the frames, so
+/// every sink sends the representations its consumers need. The primary content is always
+/// sent.
+///
+pub fn serialize_with_representations(
+    m: &Message,
+    representations: &[String],
+) -> Result<Vec<u8>, Error> {
+    serialize_for_sink(m, Some(representations), None)
+}
+
+/// Serializes the message for a sink: keeps only the listed content representations when
+/// they are given and compresses the uncompressed internal content of the frames with the
+/// codec when it is given. The frames are not modified.
+///
+pub fn serialize_for_sink(
+    m: &Message,
+    representations: Option<&[String]>,
+    content_encoding: Option<ContentEncoding>,
+) -> Result<Vec<u8>, Error> {
+    use prost::Message as ProstMessage;
+    let mut message = generated::Message::from(m);
+    serialize::redaction::redact_for_export(&mut message)?;
+    if let Some(representations) = representations {
+        serialize::representations::select_representations(&mut message, representations)?;
+    }
+    if let Some(encoding) = content_encoding {
+        serialize::content_encoding::compress_content(&mut message, encoding)?;
+    }
+    let mut buf = Vec::new();
+    message.encode(&mut buf)?;
+    Ok(buf)
+}
+
+/// Serializes the message with the registered profile selecting the objects, the attributes
+/// and the content representations the sink receives. The export redaction is applied first.
+///
+pub fn serialize_with_profile(m: &Message, profile: &str) -> Result<Vec<u8>, Error> {
     use prost::Message as ProstMessage;
-    let message = generated::Message::decode(bytes)?;
+    let Some(profile) = get_serialization_profile(profile) else {
+        return Err(Error::UnknownSerializationProfile(profile.to_string()));
+    };
+    let mut message = generated::Message::from(m);
+    serialize::redaction::redact_for_export(&mut message)?;
+    profile.apply(&mut message)?;
+    let mut buf = Vec::new();
+    message.encode(&mut buf)?;
+    Ok(buf)
+}
+
+/// Serializes the message so that equal messages produce equal bytes: the objects are ordered
+/// by id, the attributes by namespace and name, and the map entries by key. The result is
+/// decoded with [`deserialize`] like the regular encoding.
+///
+pub fn serialize_canonical(m: &Message) -> Result<Vec<u8>, Error> {
+    let mut message = generated::Message::from(m);
+    serialize::redaction::redact_for_export(&mut message)?;
+    Ok(serialize::canonical::encode_canonical(message))
+}
+
+/// A hash of the canonical encoding of the message content which is stable across processes
+/// and releases. The sequence id, routing labels and span context are not hashed, neither is
+/// the export redaction applied.
+///
+pub fn content_hash(m: &Message) -> u64 {
+    let message = generated::Message {
+        content: Some(m.payload().into()),
+        ..Default::default()
+    };
+    serialize::canonical::stable_hash(&serialize::canonical::encode_canonical(message))
+}
+
+/// Deserializes the message, the ingestion policy is applied to the attributes of the
+/// locally-managed namespaces.
+///
+pub fn deserialize(bytes: &[u8]) -> Result<Message, Error> {
+    let message = serialize::ingestion::decode_ingested::<generated::Message>(bytes)?;
     let m = Message::try_from(&message)?;
     Ok(m)
 }
@@ -0,0 +1,188 @@
+// Schema-evolution checks for the generated protobuf messages.
+//
+// The top-level field layout of fully populated messages is compared against the golden file
+// `assets/protobuf/field_numbers.txt`, so renumbered or removed fields in `savant_protobuf`
+// fail the build before a release. Messages recorded by previous releases are kept in
+// `assets/protobuf/corpus` and must still decode with the current code.
+//
+// Both the golden file and the corpus are committed, a missing golden file or an empty corpus
+// fails the check. Set `SAVANT_UPDATE_PROTOBUF_GOLDEN=1` to write the golden file after an
+// intentional schema change and `SAVANT_RECORD_PROTOBUF_CORPUS=1` to add the messages of the
+// current release to the corpus.
+
+use crate::message::Message;
+use crate::primitives::frame::{VideoFrameContent, VideoFrameProxy, VideoFrameTransformation};
+use crate::primitives::frame_update::VideoFrameUpdate;
+use crate::primitives::rust::{EndOfStream, UserData};
+use crate::primitives::{Attribute, WithAttributes};
+use crate::protobuf::{deserialize, serialize};
+use crate::test::{gen_frame, gen_object};
+use crate::version;
+use prost::encoding::{decode_key, skip_field, DecodeContext};
+use prost::Message as ProstMessage;
+use savant_protobuf::generated;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+const GOLDEN_UPDATE_ENV: &str = "SAVANT_UPDATE_PROTOBUF_GOLDEN";
+const CORPUS_RECORD_ENV: &str = "SAVANT_RECORD_PROTOBUF_CORPUS";
+
+fn assets_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("assets")
+        .join("protobuf")
+}
+
+fn layout<T: ProstMessage>(name: &str, message: &T) -> Vec<String> {
+    let bytes = message.encode_to_vec();
+    let mut buf = bytes.as_slice();
+    let mut fields = BTreeSet::new();
+    while !buf.is_empty() {
+        let (tag, wire_type) = decode_key(&mut buf).unwrap();
+        skip_field(wire_type, tag, &mut buf, DecodeContext::default()).unwrap();
+        fields.insert((tag, format!("{:?}", wire_type)));
+    }
+    fields
+        .into_iter()
+        .map(|(tag, wire_type)| format!("{} {} {}", name, tag, wire_type))
+        .collect()
+}
+
+fn populated_frame() -> VideoFrameProxy {
+    let mut frame = gen_frame();
+    frame.set_dts(Some(1));
+    frame.set_duration(Some(1));
+    frame.set_time_base((1, 1000));
+    frame.set_codec(Some("h264".to_string()));
+    frame.set_keyframe(Some(true));
+    frame.set_previous_frame_seq_id(Some(1));
+    frame.set_previous_keyframe(Some(frame.get_uuid_u128()));
+    frame.add_transformation(VideoFrameTransformation::InitialSize(1920, 1080));
+    frame.set_content(VideoFrameContent::Internal(vec![0; 16].into()));
+    frame
+}
+
+fn populated_messages() -> Vec<(&'static str, Message)> {
+    let frame = populated_frame();
+
+    let mut update = VideoFrameUpdate::default();
+    update.add_frame_attribute(Attribute::persistent(
+        "update",
+        "frame",
+        vec![],
+        &None,
+        false,
+    ));
+    update.add_object_attribute(
+        1,
+        Attribute::persistent("update", "object", vec![], &None, false),
+    );
+    update.add_object(gen_object(10), Some(1));
+
+    let mut user_data = UserData::new("source");
+    user_data.set_attribute(Attribute::persistent("user", "data", vec![], &None, false));
+
+    let mut video_frame = Message::video_frame(&frame);
+    video_frame.set_labels(vec!["label".to_string()]);
+
+    vec![
+        ("video_frame", video_frame),
+        ("video_frame_update", Message::video_frame_update(update)),
+        ("user_data", Message::user_data(user_data)),
+        (
+            "end_of_stream",
+            Message::end_of_stream(EndOfStream::new("source".to_string())),
+        ),
+    ]
+}
+
+fn current_layout() -> String {
+    let frame = populated_frame();
+    let object = gen_object(1);
+    let mut lines = layout("VideoFrame", &generated::VideoFrame::from(&frame));
+    lines.extend(layout(
+        "VideoObject",
+        &generated::VideoObject::from(&object),
+    ));
+    for (name, m) in populated_messages() {
+        lines.extend(layout(
+            &format!("Message[{}]", name),
+            &generated::Message::from(&m),
+        ));
+    }
+    lines.join("\n") + "\n"
+}
+
+#[test]
+fn test_field_numbers_match_golden_file() {
+    let path = assets_dir().join("field_numbers.txt");
+    let current = current_layout();
+    if std::env::var(GOLDEN_UPDATE_ENV).is_ok() {
+        std::fs::create_dir_all(assets_dir()).unwrap();
+        std::fs::write(&path, &current).unwrap();
+        return;
+    }
+    let golden = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "Failed to read the golden file {}: {}, set {} to create it",
+            path.display(),
+            e,
+            GOLDEN_UPDATE_ENV
+        )
+    });
+    assert_eq!(
+        golden, current,
+        "Protobuf field layout changed, set {} to accept the change intentionally",
+        GOLDEN_UPDATE_ENV
+    );
+}
+
+#[test]
+fn test_previous_releases_corpus_decodes() {
+    let corpus = assets_dir().join("corpus");
+    if std::env::var(CORPUS_RECORD_ENV).is_ok() {
+        std::fs::create_dir_all(&corpus).unwrap();
+        for (name, m) in populated_messages() {
+            let path = corpus.join(format!("{}-{}.pb", version(), name));
+            std::fs::write(path, serialize(&m).unwrap()).unwrap();
+        }
+    }
+    let entries = std::fs::read_dir(&corpus).unwrap_or_else(|e| {
+        panic!(
+            "Failed to read the corpus {}: {}, set {} to record it",
+            corpus.display(),
+            e,
+            CORPUS_RECORD_ENV
+        )
+    });
+    let mut decoded = 0;
+    for entry in entries {
+        let path = entry.unwrap().path();
+        if path.extension().and_then(|e| e.to_str()) != Some("pb") {
+            continue;
+        }
+        decoded += 1;
+        let bytes = std::fs::read(&path).unwrap();
+        let m = deserialize(&bytes)
+            .unwrap_or_else(|e| panic!("Failed to decode {}: {}", path.display(), e));
+        let stem = path.file_stem().unwrap().to_string_lossy().to_string();
+        let kind_matches = match stem.split_once('-').map(|(_, kind)| kind) {
+            Some("video_frame") => m.is_video_frame(),
+            Some("video_frame_update") => m.is_video_frame_update(),
+            Some("user_data") => m.is_user_data(),
+            Some("end_of_stream") => m.is_end_of_stream(),
+            _ => true,
+        };
+        assert!(
+            kind_matches,
+            "Unexpected message kind in {}",
+            path.display()
+        );
+    }
+    assert!(
+        decoded > 0,
+        "No recorded messages found in {}, set {} to record them",
+        corpus.display(),
+        CORPUS_RECORD_ENV
+    );
+}
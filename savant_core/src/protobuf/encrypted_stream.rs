@@ -0,0 +1,712 @@
+use crate::message::Message;
+use crate::protobuf::serialize::ingestion::decode_ingested;
+use crate::protobuf::serialize::redaction::redact_for_export;
+use crate::protobuf::serialize::Error;
+use crate::protobuf::stream::{read_record, write_record, MAX_RECORD_SIZE};
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{anyhow, bail};
+use hashbrown::HashMap;
+use prost::Message as ProstMessage;
+use rand::RngCore;
+use savant_protobuf::generated;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use zeroize::{Zeroize, Zeroizing};
+
+/// The first bytes of an encrypted journal.
+const MAGIC: &[u8; 8] = b"SVJENC01";
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+const NONCE_PREFIX_LEN: usize = 4;
+
+/// A 256-bit AES key wrapping the keys of the journal segments. The key is wiped from the
+/// memory when dropped.
+///
+#[derive(Clone, PartialEq, Eq)]
+pub struct MasterKey([u8; KEY_LEN]);
+
+impl Drop for MasterKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl MasterKey {
+    pub fn new(key: [u8; KEY_LEN]) -> Self {
+        Self(key)
+    }
+
+    pub fn generate() -> Self {
+        let mut key = Self([0u8; KEY_LEN]);
+        rand::thread_rng().fill_bytes(&mut key.0);
+        key
+    }
+
+    /// Parses the key from 64 hex digits.
+    ///
+    pub fn from_hex(hex: &str) -> anyhow::Result<Self> {
+        let hex = hex.trim();
+        if hex.len() != KEY_LEN * 2 || !hex.is_ascii() {
+            bail!("The master key must be {} hex digits", KEY_LEN * 2);
+        }
+        let mut key = Self([0u8; KEY_LEN]);
+        for (i, byte) in key.0.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+                .map_err(|_| anyhow!("The master key must be {} hex digits", KEY_LEN * 2))?;
+        }
+        Ok(key)
+    }
+
+    pub fn to_hex(&self) -> String {
+        self.0.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.0))
+    }
+}
+
+impl fmt::Debug for MasterKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MasterKey(..)")
+    }
+}
+
+/// Resolves the master keys by their ids, e.g. from the environment or a KMS. The key id is
+/// recorded in every segment, so the keys may be rotated between the segments.
+///
+pub trait KeyResolver: Send + Sync {
+    fn resolve(&self, key_id: &str) -> anyhow::Result<MasterKey>;
+}
+
+impl<F> KeyResolver for F
+where
+    F: Fn(&str) -> anyhow::Result<MasterKey> + Send + Sync,
+{
+    fn resolve(&self, key_id: &str) -> anyhow::Result<MasterKey> {
+        self(key_id)
+    }
+}
+
+/// Reads the master keys as hex digits from the environment variables `<prefix><KEY_ID>`,
+/// the key id is upper-cased and its characters other than letters and digits are replaced
+/// with `_`, e.g. `SAVANT_JOURNAL_KEY_SITE_1` for the key `site-1`.
+///
+#[derive(Debug, Clone)]
+pub struct EnvKeyResolver {
+    prefix: String,
+}
+
+impl Default for EnvKeyResolver {
+    fn default() -> Self {
+        Self::new("SAVANT_JOURNAL_KEY_")
+    }
+}
+
+impl EnvKeyResolver {
+    pub fn new(prefix: &str) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+        }
+    }
+
+    pub fn variable(&self, key_id: &str) -> String {
+        let id = key_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_uppercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+        format!("{}{}", self.prefix, id)
+    }
+}
+
+impl KeyResolver for EnvKeyResolver {
+    fn resolve(&self, key_id: &str) -> anyhow::Result<MasterKey> {
+        let variable = self.variable(key_id);
+        let value = std::env::var(&variable)
+            .map_err(|_| anyhow!("The master key {} is not set in {}", key_id, variable))?;
+        MasterKey::from_hex(&value)
+    }
+}
+
+/// The authenticated metadata of a segment, a modified metadata fails the decryption of the
+/// segment key.
+///
+#[derive(Clone, PartialEq, prost::Message)]
+struct SegmentMetadataRecord {
+    #[prost(uint64, tag = "1")]
+    segment: u64,
+    #[prost(string, tag = "2")]
+    key_id: String,
+    #[prost(bytes = "vec", tag = "3")]
+    nonce_prefix: Vec<u8>,
+    #[prost(uint64, tag = "4")]
+    created_ms: u64,
+    #[prost(btree_map = "string, string", tag = "5")]
+    metadata: BTreeMap<String, String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct SegmentHeaderRecord {
+    /// The encoded [`SegmentMetadataRecord`], kept as is because it is the associated data
+    /// of the wrapped key.
+    #[prost(bytes = "vec", tag = "1")]
+    metadata: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    key_nonce: Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    wrapped_key: Vec<u8>,
+}
+
+/// A record of the journal: a segment header or an encrypted message of the last segment.
+///
+#[derive(Clone, PartialEq, prost::Message)]
+struct JournalRecord {
+    #[prost(message, optional, tag = "1")]
+    segment: Option<SegmentHeaderRecord>,
+    #[prost(uint64, tag = "2")]
+    index: u64,
+    #[prost(bytes = "vec", tag = "3")]
+    ciphertext: Vec<u8>,
+}
+
+/// The metadata of a journal segment.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentMetadata {
+    /// The number of the segment in the journal.
+    pub segment: u64,
+    pub key_id: String,
+    /// The creation time in milliseconds since the UNIX epoch.
+    pub created_ms: u64,
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// Where a message is stored in an encrypted journal: the offsets of its segment header and
+/// of the message record. A message is decrypted at its location without the preceding
+/// messages, see [`EncryptedMessageStreamReader::read_at`].
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RecordLocation {
+    pub segment_offset: u64,
+    pub record_offset: u64,
+}
+
+fn crypto_error(what: &str) -> Error {
+    Error::Encryption(what.to_string())
+}
+
+/// The nonce of a message is the segment nonce prefix and the message index, the keys are
+/// unique to the segments so the nonces are never reused.
+///
+fn record_nonce(prefix: &[u8], index: u64) -> [u8; NONCE_LEN] {
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce[..NONCE_PREFIX_LEN].copy_from_slice(prefix);
+    nonce[NONCE_PREFIX_LEN..].copy_from_slice(&index.to_be_bytes());
+    nonce
+}
+
+/// The associated data of a message binds it to its position, so the records cannot be
+/// reordered or moved between the segments. The sequential reader authenticates the records
+/// with the sequence numbers it expects, so dropped or replayed records fail as well.
+///
+fn record_aad(segment: u64, index: u64) -> [u8; 16] {
+    let mut aad = [0u8; 16];
+    aad[..8].copy_from_slice(&segment.to_be_bytes());
+    aad[8..].copy_from_slice(&index.to_be_bytes());
+    aad
+}
+
+struct Segment {
+    number: u64,
+    offset: u64,
+    cipher: Aes256Gcm,
+    nonce_prefix: Vec<u8>,
+    records: u64,
+    metadata: SegmentMetadata,
+}
+
+impl Segment {
+    fn open(
+        header: &SegmentHeaderRecord,
+        offset: u64,
+        resolver: &dyn KeyResolver,
+        keys: &mut HashMap<String, MasterKey>,
+    ) -> Result<Self, Error> {
+        let record = SegmentMetadataRecord::decode(header.metadata.as_slice())?;
+        if record.nonce_prefix.len() != NONCE_PREFIX_LEN || header.key_nonce.len() != NONCE_LEN {
+            return Err(crypto_error("Malformed segment header"));
+        }
+        let master_key = match keys.get(&record.key_id) {
+            Some(key) => key.clone(),
+            None => {
+                let key = resolver
+                    .resolve(&record.key_id)
+                    .map_err(|e| Error::Encryption(e.to_string()))?;
+                keys.insert(record.key_id.clone(), key.clone());
+                key
+            }
+        };
+        let key = Zeroizing::new(
+            master_key
+                .cipher()
+                .decrypt(
+                    Nonce::from_slice(&header.key_nonce),
+                    Payload {
+                        msg: &header.wrapped_key,
+                        aad: &header.metadata,
+                    },
+                )
+                .map_err(|_| {
+                    crypto_error(
+                        "Failed to unwrap the segment key, the key or the metadata is wrong",
+                    )
+                })?,
+        );
+        let cipher =
+            Aes256Gcm::new_from_slice(&key).map_err(|_| crypto_error("Malformed segment key"))?;
+        Ok(Self {
+            number: record.segment,
+            offset,
+            cipher,
+            nonce_prefix: record.nonce_prefix,
+            records: 0,
+            metadata: SegmentMetadata {
+                segment: record.segment,
+                key_id: record.key_id,
+                created_ms: record.created_ms,
+                metadata: record.metadata,
+            },
+        })
+    }
+
+    /// Decrypts the record as the message with the index in the segment.
+    ///
+    fn decrypt(&self, record: &JournalRecord, index: u64) -> Result<Message, Error> {
+        let nonce = record_nonce(&self.nonce_prefix, index);
+        let plaintext = self
+            .cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &record.ciphertext,
+                    aad: &record_aad(self.number, index),
+                },
+            )
+            .map_err(|_| crypto_error("Failed to decrypt a record, the journal is corrupted"))?;
+        Message::try_from(&decode_ingested::<generated::Message>(
+            plaintext.as_slice(),
+        )?)
+    }
+}
+
+/// Writes messages as an encrypted journal. The journal is split into segments, every
+/// segment has its own random AES-256-GCM key stored wrapped by the master key next to the
+/// authenticated segment metadata. Every message is encrypted separately, so it can be
+/// read at its location without decrypting the preceding ones.
+///
+pub struct EncryptedMessageStreamWriter<W: Write> {
+    writer: W,
+    buf: Vec<u8>,
+    key_id: String,
+    master_key: MasterKey,
+    max_segment_records: u64,
+    metadata: BTreeMap<String, String>,
+    segment: Option<Segment>,
+    segments: u64,
+    position: u64,
+}
+
+impl<W: Write> EncryptedMessageStreamWriter<W> {
+    /// Creates the writer, the master key is resolved once. A new segment with a new key is
+    /// started every `max_segment_records` messages.
+    ///
+    pub fn new(
+        mut writer: W,
+        key_id: &str,
+        resolver: &dyn KeyResolver,
+        max_segment_records: u64,
+    ) -> Result<Self, Error> {
+        if max_segment_records == 0 {
+            return Err(crypto_error("The segment must hold at least one record"));
+        }
+        let master_key = resolver
+            .resolve(key_id)
+            .map_err(|e| Error::Encryption(e.to_string()))?;
+        writer.write_all(MAGIC)?;
+        Ok(Self {
+            writer,
+            buf: Vec::new(),
+            key_id: key_id.to_string(),
+            master_key,
+            max_segment_records,
+            metadata: BTreeMap::new(),
+            segment: None,
+            segments: 0,
+            position: MAGIC.len() as u64,
+        })
+    }
+
+    fn write(&mut self, record: &JournalRecord) -> Result<u64, Error> {
+        let offset = self.position;
+        write_record(&mut self.writer, &mut self.buf, record)?;
+        self.position += self.buf.len() as u64;
+        Ok(offset)
+    }
+
+    /// Starts a new segment with a new key, the metadata is kept for the following segments.
+    ///
+    pub fn start_segment(&mut self, metadata: BTreeMap<String, String>) -> Result<(), Error> {
+        self.metadata = metadata;
+        let mut rng = rand::thread_rng();
+        let mut key = Zeroizing::new([0u8; KEY_LEN]);
+        let mut key_nonce = [0u8; NONCE_LEN];
+        let mut nonce_prefix = vec![0u8; NONCE_PREFIX_LEN];
+        rng.fill_bytes(&mut key[..]);
+        rng.fill_bytes(&mut key_nonce);
+        rng.fill_bytes(&mut nonce_prefix);
+
+        let metadata = SegmentMetadata {
+            segment: self.segments,
+            key_id: self.key_id.clone(),
+            created_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+            metadata: self.metadata.clone(),
+        };
+        let encoded = SegmentMetadataRecord {
+            segment: metadata.segment,
+            key_id: metadata.key_id.clone(),
+            nonce_prefix: nonce_prefix.clone(),
+            created_ms: metadata.created_ms,
+            metadata: metadata.metadata.clone(),
+        }
+        .encode_to_vec();
+        let wrapped_key = self
+            .master_key
+            .cipher()
+            .encrypt(
+                Nonce::from_slice(&key_nonce),
+                Payload {
+                    msg: &key[..],
+                    aad: &encoded,
+                },
+            )
+            .map_err(|_| crypto_error("Failed to wrap the segment key"))?;
+        let offset = self.write(&JournalRecord {
+            segment: Some(SegmentHeaderRecord {
+                metadata: encoded,
+                key_nonce: key_nonce.to_vec(),
+                wrapped_key,
+            }),
+            index: 0,
+            ciphertext: Vec::new(),
+        })?;
+        self.segment = Some(Segment {
+            number: self.segments,
+            offset,
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key[..])),
+            nonce_prefix,
+            records: 0,
+            metadata,
+        });
+        self.segments += 1;
+        Ok(())
+    }
+
+    pub fn write_message(&mut self, m: &Message) -> Result<RecordLocation, Error> {
+        if self
+            .segment
+            .as_ref()
+            .is_none_or(|s| s.records >= self.max_segment_records)
+        {
+            self.start_segment(self.metadata.clone())?;
+        }
+        let mut record = generated::Message::from(m);
+        redact_for_export(&mut record)?;
+        let plaintext = record.encode_to_vec();
+        let segment = self.segment.as_mut().unwrap();
+        let index = segment.records;
+        let nonce = record_nonce(&segment.nonce_prefix, index);
+        let ciphertext = segment
+            .cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &plaintext,
+                    aad: &record_aad(segment.number, index),
+                },
+            )
+            .map_err(|_| crypto_error("Failed to encrypt a record"))?;
+        segment.records += 1;
+        let segment_offset = segment.offset;
+        let record_offset = self.write(&JournalRecord {
+            segment: None,
+            index,
+            ciphertext,
+        })?;
+        Ok(RecordLocation {
+            segment_offset,
+            record_offset,
+        })
+    }
+
+    pub fn get_segment_metadata(&self) -> Option<&SegmentMetadata> {
+        self.segment.as_ref().map(|s| &s.metadata)
+    }
+
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads messages from an encrypted journal, see [`EncryptedMessageStreamWriter`]. The
+/// master keys are resolved by the ids recorded in the segments and cached. The sequential
+/// reading requires the segments and the records to follow each other without gaps.
+///
+pub struct EncryptedMessageStreamReader<R: Read> {
+    reader: R,
+    buf: Vec<u8>,
+    resolver: Arc<dyn KeyResolver>,
+    keys: HashMap<String, MasterKey>,
+    segment: Option<Segment>,
+    started: bool,
+    max_record_size: usize,
+}
+
+impl<R: Read> EncryptedMessageStreamReader<R> {
+    pub fn new(reader: R, resolver: Arc<dyn KeyResolver>) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+            resolver,
+            keys: HashMap::new(),
+            segment: None,
+            started: false,
+            max_record_size: MAX_RECORD_SIZE,
+        }
+    }
+
+    /// Limits the size of the records read, a larger record fails the reading.
+    ///
+    pub fn set_max_record_size(&mut self, max_record_size: usize) {
+        self.max_record_size = max_record_size;
+    }
+
+    fn check_magic(&mut self) -> Result<(), Error> {
+        let mut magic = [0u8; MAGIC.len()];
+        self.reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(crypto_error("Not an encrypted journal"));
+        }
+        Ok(())
+    }
+
+    pub fn read_message(&mut self) -> Result<Option<Message>, Error> {
+        if !self.started {
+            self.check_magic()?;
+            self.started = true;
+        }
+        loop {
+            let Some(record) = read_record::<_, JournalRecord>(
+                &mut self.reader,
+                &mut self.buf,
+                self.max_record_size,
+            )?
+            else {
+                return Ok(None);
+            };
+            match &record.segment {
+                Some(header) => {
+                    let segment = Segment::open(header, 0, self.resolver.as_ref(), &mut self.keys)?;
+                    let expected = self.segment.as_ref().map_or(0, |s| s.number + 1);
+                    if segment.number != expected {
+                        return Err(crypto_error("A segment of the journal is missing"));
+                    }
+                    self.segment = Some(segment);
+                }
+                None => match &mut self.segment {
+                    Some(segment) => {
+                        let index = segment.records;
+                        segment.records += 1;
+                        return segment.decrypt(&record, index).map(Some);
+                    }
+                    None => return Err(crypto_error("A record precedes the segment header")),
+                },
+            }
+        }
+    }
+
+    /// The metadata of the segment of the last read message.
+    ///
+    pub fn get_segment_metadata(&self) -> Option<&SegmentMetadata> {
+        self.segment.as_ref().map(|s| &s.metadata)
+    }
+}
+
+impl<R: Read + Seek> EncryptedMessageStreamReader<R> {
+    fn read_record_at(&mut self, offset: u64) -> Result<JournalRecord, Error> {
+        self.reader.seek(SeekFrom::Start(offset))?;
+        read_record::<_, JournalRecord>(&mut self.reader, &mut self.buf, self.max_record_size)?
+            .ok_or(Error::UnexpectedEndOfStream)
+    }
+
+    /// Reads the message at the location returned by the writer, only the segment header
+    /// and the message record are read. The sequential reading continues after the message.
+    ///
+    pub fn read_at(&mut self, location: RecordLocation) -> Result<Message, Error> {
+        self.started = true;
+        if self
+            .segment
+            .as_ref()
+            .is_none_or(|s| s.offset != location.segment_offset)
+        {
+            let header = self
+                .read_record_at(location.segment_offset)?
+                .segment
+                .ok_or_else(|| crypto_error("No segment header at the offset"))?;
+            self.segment = Some(Segment::open(
+                &header,
+                location.segment_offset,
+                self.resolver.as_ref(),
+                &mut self.keys,
+            )?);
+        }
+        let record = self.read_record_at(location.record_offset)?;
+        if record.segment.is_some() {
+            return Err(crypto_error("No message record at the offset"));
+        }
+        let segment = self.segment.as_mut().unwrap();
+        let message = segment.decrypt(&record, record.index)?;
+        // the sequential reading expects the following record
+        segment.records = record.index + 1;
+        Ok(message)
+    }
+}
+
+impl<R: Read> Iterator for EncryptedMessageStreamReader<R> {
+    type Item = Result<Message, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_message().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::Message;
+    use crate::primitives::rust::EndOfStream;
+    use crate::protobuf::encrypted_stream::{
+        EncryptedMessageStreamReader, EncryptedMessageStreamWriter, EnvKeyResolver, KeyResolver,
+        MasterKey,
+    };
+    use crate::protobuf::serialize::Error;
+    use crate::test::gen_frame;
+    use std::collections::BTreeMap;
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_encrypted_journal() -> anyhow::Result<()> {
+        let master_key = MasterKey::generate();
+        let key = master_key.clone();
+        let resolver: Arc<dyn KeyResolver> =
+            Arc::new(move |id: &str| -> anyhow::Result<MasterKey> {
+                anyhow::ensure!(id == "site-1", "Unknown key {}", id);
+                Ok(key.clone())
+            });
+        let mut writer =
+            EncryptedMessageStreamWriter::new(Vec::new(), "site-1", resolver.as_ref(), 2)?;
+        writer.start_segment(BTreeMap::from([("camera".to_string(), "gate".to_string())]))?;
+        let mut frames = Vec::new();
+        let mut locations = Vec::new();
+        for _ in 0..5 {
+            let frame = gen_frame();
+            locations.push(writer.write_message(&Message::video_frame(&frame))?);
+            frames.push(frame);
+        }
+        writer.write_message(&Message::end_of_stream(EndOfStream::new("test".into())))?;
+        let bytes = writer.into_inner();
+
+        let mut reader = EncryptedMessageStreamReader::new(bytes.as_slice(), resolver.clone());
+        let messages = reader.by_ref().collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(messages.len(), 6);
+        assert!(messages[5].is_end_of_stream());
+        let metadata = reader.get_segment_metadata().unwrap();
+        assert_eq!(metadata.segment, 2);
+        assert_eq!(metadata.metadata["camera"], "gate");
+
+        let mut reader = EncryptedMessageStreamReader::new(Cursor::new(&bytes), resolver.clone());
+        for i in [3, 0, 4] {
+            let frame = reader.read_at(locations[i])?.as_video_frame().unwrap();
+            assert_eq!(frame.get_uuid(), frames[i].get_uuid());
+        }
+
+        // the plaintext is not in the journal
+        let source_id = frames[0].get_source_id();
+        assert!(!bytes
+            .windows(source_id.len())
+            .any(|w| w == source_id.as_bytes()));
+
+        // a tampered record fails the authentication
+        let mut tampered = bytes.clone();
+        // the last byte of the second record, the tag of its ciphertext
+        tampered[locations[2].segment_offset as usize - 1] ^= 1;
+        let mut reader =
+            EncryptedMessageStreamReader::new(Cursor::new(&tampered), resolver.clone());
+        assert!(reader.read_at(locations[0]).is_ok());
+        assert!(matches!(
+            reader.read_at(locations[1]),
+            Err(Error::Encryption(_))
+        ));
+
+        // a dropped record breaks the sequence
+        let mut dropped = bytes[..locations[2].record_offset as usize].to_vec();
+        dropped.extend_from_slice(&bytes[locations[3].record_offset as usize..]);
+        let mut reader = EncryptedMessageStreamReader::new(dropped.as_slice(), resolver.clone());
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.next().unwrap().is_ok());
+        assert!(matches!(reader.next(), Some(Err(Error::Encryption(_)))));
+
+        let mut reader = EncryptedMessageStreamReader::new(bytes.as_slice(), resolver.clone());
+        reader.set_max_record_size(16);
+        assert!(matches!(
+            reader.next(),
+            Some(Err(Error::RecordTooLarge(_, 16)))
+        ));
+
+        let wrong: Arc<dyn KeyResolver> =
+            Arc::new(|_: &str| -> anyhow::Result<MasterKey> { Ok(MasterKey::generate()) });
+        let mut reader = EncryptedMessageStreamReader::new(bytes.as_slice(), wrong);
+        assert!(matches!(reader.next(), Some(Err(Error::Encryption(_)))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_env_key_resolver() -> anyhow::Result<()> {
+        let resolver = EnvKeyResolver::default();
+        let variable = resolver.variable("test-env-resolver.1");
+        assert_eq!(variable, "SAVANT_JOURNAL_KEY_TEST_ENV_RESOLVER_1");
+        let key = MasterKey::generate();
+        std::env::set_var(&variable, key.to_hex());
+        assert_eq!(resolver.resolve("test-env-resolver.1")?, key);
+        assert!(resolver.resolve("test-env-resolver-missing").is_err());
+        assert!(MasterKey::from_hex("abc").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,276 @@
+use crate::primitives::frame::VideoFrameProxy;
+use crate::primitives::object::VideoObject;
+use crate::primitives::Attribute;
+use crate::protobuf::serialize::carrier::attribute_record;
+use crate::protobuf::serialize::ingestion::decode_ingested;
+use crate::protobuf::serialize::Error;
+use lazy_static::lazy_static;
+use prost::encoding::{decode_key, skip_field, DecodeContext};
+use prost::Message as ProstMessage;
+use savant_protobuf::generated;
+use std::ops::Range;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+/// Field numbers of the heavy sections of the generated frame message. They are discovered
+/// by encoding probe messages, so the lazy frame follows the schema of `savant_protobuf`
+/// without duplicating its field numbers.
+///
+struct SectionTags {
+    attributes: u32,
+    objects: u32,
+    /// The tag of the internal content, the other content variants have their own tags.
+    internal_content: u32,
+}
+
+fn probe_tag(probe: generated::VideoFrame) -> u32 {
+    let bytes = probe.encode_to_vec();
+    let mut buf = bytes.as_slice();
+    let (tag, _) = decode_key(&mut buf).expect("Probe message must contain a field");
+    tag
+}
+
+lazy_static! {
+    static ref SECTION_TAGS: SectionTags = SectionTags {
+        attributes: probe_tag(generated::VideoFrame {
+            attributes: vec![generated::Attribute::default()],
+            ..Default::default()
+        }),
+        objects: probe_tag(generated::VideoFrame {
+            objects: vec![generated::VideoObject::default()],
+            ..Default::default()
+        }),
+        internal_content: probe_tag(generated::VideoFrame {
+            content: Some(generated::video_frame::Content::Internal(Vec::new())),
+            ..Default::default()
+        }),
+    };
+}
+
+#[derive(Debug, Default)]
+struct Sections {
+    header: Vec<Range<usize>>,
+    attributes: Vec<Range<usize>>,
+    objects: Vec<Range<usize>>,
+}
+
+fn scan_sections(bytes: &[u8]) -> Result<Sections, Error> {
+    let tags = &*SECTION_TAGS;
+    let mut sections = Sections::default();
+    let mut buf = bytes;
+    while !buf.is_empty() {
+        let start = bytes.len() - buf.len();
+        let (tag, wire_type) = decode_key(&mut buf)?;
+        skip_field(wire_type, tag, &mut buf, DecodeContext::default())?;
+        let range = start..bytes.len() - buf.len();
+        match tag {
+            t if t == tags.attributes => sections.attributes.push(range),
+            t if t == tags.objects => sections.objects.push(range),
+            t if t == tags.internal_content => (),
+            // the external and the none content are small, they are decoded with the header
+            _ => sections.header.push(range),
+        }
+    }
+    Ok(sections)
+}
+
+fn decode_section(bytes: &[u8], ranges: &[Range<usize>]) -> Result<generated::VideoFrame, Error> {
+    let mut section = Vec::with_capacity(ranges.iter().map(|r| r.len()).sum());
+    for r in ranges {
+        section.extend_from_slice(&bytes[r.clone()]);
+    }
+    decode_ingested::<generated::VideoFrame>(section.as_slice())
+}
+
+/// A video frame which keeps the encoded protobuf bytes and decodes them per section on first
+/// access. Header fields (source, timestamps, geometry) are decoded when the frame is created,
+/// attributes and objects are decoded only when requested, the internal content is decoded
+/// only when the whole frame is materialized; the external and the none content are decoded
+/// with the header. Components which only route frames can forward
+/// [`LazyVideoFrame::bytes`] without paying the full decoding cost.
+///
+#[derive(Debug)]
+pub struct LazyVideoFrame {
+    bytes: Vec<u8>,
+    header: generated::VideoFrame,
+    attribute_ranges: Vec<Range<usize>>,
+    object_ranges: Vec<Range<usize>>,
+    attributes: OnceLock<Vec<Attribute>>,
+    objects: OnceLock<Vec<VideoObject>>,
+}
+
+impl LazyVideoFrame {
+    pub fn new(bytes: Vec<u8>) -> Result<Self, Error> {
+        let sections = scan_sections(&bytes)?;
+        let header = decode_section(&bytes, &sections.header)?;
+        Uuid::from_str(&header.uuid)?;
+        Ok(Self {
+            bytes,
+            header,
+            attribute_ranges: sections.attributes,
+            object_ranges: sections.objects,
+            attributes: OnceLock::new(),
+            objects: OnceLock::new(),
+        })
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn get_source_id(&self) -> &str {
+        &self.header.source_id
+    }
+
+    pub fn get_uuid(&self) -> Uuid {
+        Uuid::from_str(&self.header.uuid).expect("UUID is validated on creation")
+    }
+
+    pub fn get_pts(&self) -> i64 {
+        self.header.pts
+    }
+
+    pub fn get_dts(&self) -> Option<i64> {
+        self.header.dts
+    }
+
+    pub fn get_keyframe(&self) -> Option<bool> {
+        self.header.keyframe
+    }
+
+    pub fn get_width(&self) -> i64 {
+        self.header.width
+    }
+
+    pub fn get_height(&self) -> i64 {
+        self.header.height
+    }
+
+    pub fn get_codec(&self) -> Option<&str> {
+        self.header.codec.as_deref()
+    }
+
+    pub fn is_attributes_decoded(&self) -> bool {
+        self.attributes.get().is_some()
+    }
+
+    pub fn is_objects_decoded(&self) -> bool {
+        self.objects.get().is_some()
+    }
+
+    pub fn get_attributes(&self) -> Result<&[Attribute], Error> {
+        if let Some(attributes) = self.attributes.get() {
+            return Ok(attributes);
+        }
+        let section = decode_section(&self.bytes, &self.attribute_ranges)?;
+        let attributes = section
+            .attributes
+            .iter()
+            // the hidden carriers are decoded into the frame, never exposed as attributes
+            .filter(|a| attribute_record(a).is_none())
+            .map(Attribute::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self.attributes.get_or_init(|| attributes))
+    }
+
+    pub fn get_objects(&self) -> Result<&[VideoObject], Error> {
+        if let Some(objects) = self.objects.get() {
+            return Ok(objects);
+        }
+        let section = decode_section(&self.bytes, &self.object_ranges)?;
+        let objects = section
+            .objects
+            .iter()
+            .map(VideoObject::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self.objects.get_or_init(|| objects))
+    }
+
+    /// Decodes the whole frame.
+    ///
+    pub fn materialize(&self) -> Result<VideoFrameProxy, Error> {
+        let frame = decode_ingested::<generated::VideoFrame>(self.bytes.as_slice())?;
+        VideoFrameProxy::try_from(&frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json_api::ToSerdeJsonValue;
+    use crate::primitives::attribute_value::AttributeValue;
+    use crate::primitives::frame::{ExternalFrame, VideoFrameContent};
+    use crate::primitives::{Attribute, WithAttributes};
+    use crate::protobuf::lazy::LazyVideoFrame;
+    use crate::protobuf::ToProtobuf;
+    use crate::test::gen_frame;
+
+    #[test]
+    fn test_lazy_header() {
+        let frame = gen_frame();
+        let lazy = LazyVideoFrame::new(frame.to_pb().unwrap()).unwrap();
+        assert_eq!(lazy.get_source_id(), frame.get_source_id());
+        assert_eq!(lazy.get_pts(), frame.get_pts());
+        assert_eq!(lazy.get_uuid(), frame.get_uuid());
+        assert!(!lazy.is_attributes_decoded());
+        assert!(!lazy.is_objects_decoded());
+    }
+
+    #[test]
+    fn test_lazy_sections() {
+        let frame = gen_frame();
+        let lazy = LazyVideoFrame::new(frame.to_pb().unwrap()).unwrap();
+        let objects = lazy.get_objects().unwrap();
+        assert_eq!(objects.len(), frame.get_all_objects().len());
+        assert!(lazy.is_objects_decoded());
+        assert!(!lazy.is_attributes_decoded());
+        let attributes = lazy.get_attributes().unwrap();
+        assert_eq!(attributes.len(), frame.get_attributes().len());
+    }
+
+    #[test]
+    fn test_lazy_hidden_carriers() {
+        let mut frame = gen_frame();
+        let mut osd = Attribute::persistent(
+            "osd",
+            "skip",
+            vec![AttributeValue::boolean(true, None)],
+            &None,
+            false,
+        );
+        osd.set_ttl(std::time::Duration::from_secs(60));
+        frame.set_attribute(osd);
+        frame.freeze_objects(None, "detection");
+        let lazy = LazyVideoFrame::new(frame.to_pb().unwrap()).unwrap();
+        let attributes = lazy.get_attributes().unwrap();
+        assert_eq!(attributes.len(), frame.get_attributes().len());
+    }
+
+    #[test]
+    fn test_materialize() {
+        let frame = gen_frame();
+        let lazy = LazyVideoFrame::new(frame.to_pb().unwrap()).unwrap();
+        let restored = lazy.materialize().unwrap();
+        assert_eq!(frame.to_serde_json_value(), restored.to_serde_json_value());
+    }
+
+    #[test]
+    fn test_content_variants() {
+        for content in [
+            VideoFrameContent::Internal(vec![1, 2, 3].into()),
+            VideoFrameContent::External(ExternalFrame::new("s3", &Some("s3://bucket/key"))),
+            VideoFrameContent::None,
+        ] {
+            let mut frame = gen_frame();
+            frame.set_content(content);
+            let lazy = LazyVideoFrame::new(frame.to_pb().unwrap()).unwrap();
+            assert_eq!(lazy.get_source_id(), frame.get_source_id());
+            let restored = lazy.materialize().unwrap();
+            assert_eq!(frame.to_serde_json_value(), restored.to_serde_json_value());
+        }
+    }
+}
@@ -9,11 +9,27 @@ use savant_protobuf::generated;
 use std::convert::Infallible;
 
 mod attribute;
+mod attribute_expiry;
 mod attribute_set;
+mod attribute_units;
+mod audio_frame;
 mod bounding_box;
+pub(crate) mod canonical;
+pub(crate) mod carrier;
+pub(crate) mod classification;
+pub(crate) mod content_encoding;
+mod frozen_objects;
+mod geo;
+pub(crate) mod ingestion;
 mod intersection_kind;
 mod message_envelope;
+mod object_payload;
 mod polygonal_area;
+mod processing_hints;
+pub(crate) mod profiles;
+pub(crate) mod redaction;
+pub(crate) mod representations;
+mod telemetry_frame;
 mod user_data;
 mod video_frame;
 mod video_frame_batch;
@@ -35,6 +51,34 @@ pub enum Error {
     InvalidVideoFrameParentObject(i64),
     #[error("Failed to convert protobuf enum balue to Rust enum value: {0}")]
     EnumConversionError(i32),
+    #[error("I/O error while streaming protobuf records: {0}")]
+    Io(std::io::Error),
+    #[error("Stream ended in the middle of a protobuf record")]
+    UnexpectedEndOfStream,
+    #[error("The stream record of {0} bytes exceeds the limit of {1} bytes")]
+    RecordTooLarge(usize, usize),
+    #[error("Unknown audio sample format: {0}")]
+    UnknownAudioSampleFormat(String),
+    #[error("Journal encryption error: {0}")]
+    Encryption(String),
+    #[error("Unknown content encoding: {0}")]
+    UnknownContentEncoding(String),
+    #[error("Content compression error: {0}")]
+    ContentEncoding(String),
+    #[error("The {0} classified as {1} is not allowed for export")]
+    ClassificationRejected(String, String),
+    #[error("The incoming attribute {0}/{1} is in a locally-managed namespace")]
+    NamespaceCollision(String, String),
+    #[error("Invalid object payload: {0}")]
+    InvalidObjectPayload(String),
+    #[error("Unknown serialization profile: {0}")]
+    UnknownSerializationProfile(String),
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
 }
 
 impl From<uuid::Error> for Error {
@@ -77,13 +121,16 @@ where
     }
 }
 
+/// Decodes the value, the ingestion policy is applied to the attributes of the
+/// locally-managed namespaces.
+///
 pub fn from_pb<T, U>(bytes: &[u8]) -> Result<U, Error>
 where
-    T: prost::Message + Default,
+    T: prost::Message + Default + ingestion::Ingest,
     U: for<'a> TryFrom<&'a T>,
     Error: for<'a> From<<U as TryFrom<&'a T>>::Error>,
 {
-    let pb = T::decode(bytes)?;
+    let pb = ingestion::decode_ingested::<T>(bytes)?;
     let obj = U::try_from(&pb)?;
     Ok(obj)
 }
@@ -4,7 +4,6 @@ use crate::primitives::{Attribute, IntersectionKind, RBBox};
 use crate::protobuf::serialize;
 use prost::UnknownEnumValue;
 use savant_protobuf::generated;
-use std::sync::Arc;
 
 impl From<&AttributeValueVariant> for generated::attribute_value::Value {
     fn from(value: &AttributeValueVariant) -> Self {
@@ -214,10 +213,10 @@ impl From<&AttributeValue> for generated::AttributeValue {
 impl TryFrom<&generated::AttributeValue> for AttributeValue {
     type Error = serialize::Error;
     fn try_from(value: &generated::AttributeValue) -> Result<Self, Self::Error> {
-        Ok(AttributeValue {
-            confidence: value.confidence,
-            value: AttributeValueVariant::try_from(value.value.as_ref().unwrap())?,
-        })
+        Ok(AttributeValue::new(
+            AttributeValueVariant::try_from(value.value.as_ref().unwrap())?,
+            value.confidence,
+        ))
     }
 }
 
@@ -240,16 +239,16 @@ impl TryFrom<&generated::Attribute> for Attribute {
         Ok(Attribute {
             namespace: value.namespace.clone(),
             name: value.name.clone(),
-            values: Arc::new(
-                value
-                    .values
-                    .iter()
-                    .map(|v| v.try_into())
-                    .collect::<Result<Vec<_>, _>>()?,
-            ),
+            values: value
+                .values
+                .iter()
+                .map(|v| v.try_into())
+                .collect::<Result<Vec<_>, _>>()?
+                .into(),
             hint: value.hint.clone(),
             is_persistent: value.is_persistent,
             is_hidden: value.is_hidden,
+            expires_at: None,
         })
     }
 }
@@ -259,7 +258,6 @@ mod tests {
     use crate::primitives::attribute_value::{AttributeValue, AttributeValueVariant};
     use crate::primitives::{Attribute, IntersectionKind};
     use savant_protobuf::generated;
-    use std::sync::Arc;
 
     #[test]
     fn test_attribute_value_variant_bytes() {
@@ -668,10 +666,10 @@ mod tests {
 
     #[test]
     fn test_attribute_value() {
-        let av = AttributeValue {
-            confidence: Some(0.5),
-            value: AttributeValueVariant::String("string".to_string()),
-        };
+        let av = AttributeValue::new(
+            AttributeValueVariant::String("string".to_string()),
+            Some(0.5),
+        );
         assert_eq!(
             av,
             AttributeValue::try_from(&generated::AttributeValue {
@@ -702,13 +700,15 @@ mod tests {
         let a = Attribute {
             namespace: "namespace".to_string(),
             name: "name".to_string(),
-            values: Arc::new(vec![AttributeValue {
-                confidence: Some(0.5),
-                value: AttributeValueVariant::String("string".to_string()),
-            }]),
+            values: vec![AttributeValue::new(
+                AttributeValueVariant::String("string".to_string()),
+                Some(0.5),
+            )]
+            .into(),
             hint: Some("hint".to_string()),
             is_persistent: true,
             is_hidden: false,
+            expires_at: None,
         };
         assert_eq!(
             a,
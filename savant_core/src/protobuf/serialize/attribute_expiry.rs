@@ -0,0 +1,166 @@
+use crate::primitives::frame::VideoFrame;
+use crate::primitives::object::VideoObject;
+use crate::primitives::Attribute;
+use crate::protobuf::serialize;
+use crate::protobuf::serialize::carrier::{attribute_record, record_attribute};
+use hashbrown::HashMap;
+use prost::Message as ProstMessage;
+use savant_protobuf::generated;
+
+pub(crate) const ATTRIBUTE_EXPIRY_KIND: &str = "attribute_expiry";
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct AttributeExpiryRecord {
+    #[prost(int64, optional, tag = "1")]
+    pub object_id: Option<i64>,
+    #[prost(string, tag = "2")]
+    pub namespace: String,
+    #[prost(string, tag = "3")]
+    pub name: String,
+    #[prost(uint64, tag = "4")]
+    pub expires_at: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct AttributeExpiriesRecord {
+    #[prost(message, repeated, tag = "1")]
+    pub expiries: Vec<AttributeExpiryRecord>,
+}
+
+fn expiry_records<'a>(
+    object_id: Option<i64>,
+    attributes: &'a [Attribute],
+) -> impl Iterator<Item = AttributeExpiryRecord> + 'a {
+    attributes
+        .iter()
+        .filter(|a| !a.is_expired())
+        .filter_map(move |a| {
+            a.expires_at.map(|expires_at| AttributeExpiryRecord {
+                object_id,
+                namespace: a.namespace.clone(),
+                name: a.name.clone(),
+                expires_at,
+            })
+        })
+}
+
+/// The hidden attribute carrying the expiry of the frame and object attributes, `None` when
+/// no attribute expires. Already expired attributes are not serialized at all.
+///
+pub(crate) fn attribute_expiry_attribute(frame: &VideoFrame) -> Option<generated::Attribute> {
+    let mut expiries = expiry_records(None, &frame.attributes)
+        .chain(
+            frame
+                .objects
+                .values()
+                .flat_map(|o| expiry_records(Some(o.id), &o.attributes)),
+        )
+        .collect::<Vec<_>>();
+    if expiries.is_empty() {
+        return None;
+    }
+    expiries.sort_by_key(|e| e.object_id);
+    let record = AttributeExpiriesRecord { expiries };
+    Some(record_attribute(
+        ATTRIBUTE_EXPIRY_KIND,
+        record.encode_to_vec(),
+    ))
+}
+
+/// Decodes the expiries if the attribute carries them.
+///
+pub(crate) fn attribute_expiry_from_attribute(
+    attribute: &generated::Attribute,
+) -> Option<Result<Vec<AttributeExpiryRecord>, serialize::Error>> {
+    match attribute_record(attribute) {
+        Some((ATTRIBUTE_EXPIRY_KIND, data)) => Some(
+            AttributeExpiriesRecord::decode(data)
+                .map(|r| r.expiries)
+                .map_err(serialize::Error::from),
+        ),
+        _ => None,
+    }
+}
+
+/// Restores the expiry of the deserialized frame and object attributes.
+///
+pub(crate) fn apply_attribute_expiry(
+    expiries: &[AttributeExpiryRecord],
+    attributes: &mut [Attribute],
+    objects: &mut HashMap<i64, VideoObject>,
+) {
+    for expiry in expiries {
+        let attributes = match expiry.object_id {
+            Some(id) => match objects.get_mut(&id) {
+                Some(object) => object.attributes.as_mut_slice(),
+                None => continue,
+            },
+            None => &mut *attributes,
+        };
+        if let Some(attribute) = attributes
+            .iter_mut()
+            .find(|a| a.namespace == expiry.namespace && a.name == expiry.name)
+        {
+            attribute.expires_at = Some(expiry.expires_at);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::Message;
+    use crate::primitives::attribute_value::AttributeValue;
+    use crate::primitives::{Attribute, WithAttributes};
+    use crate::protobuf::{deserialize, serialize};
+    use crate::test::gen_frame;
+    use std::time::Duration;
+
+    #[test]
+    fn test_attribute_expiry_roundtrip() -> anyhow::Result<()> {
+        let mut frame = gen_frame();
+        let mut osd = Attribute::persistent(
+            "osd",
+            "skip",
+            vec![AttributeValue::boolean(true, None)],
+            &None,
+            false,
+        );
+        osd.set_ttl(Duration::from_secs(60));
+        frame.set_attribute(osd.clone());
+        let mut expired = osd.clone();
+        expired.name = "expired".to_string();
+        expired.set_expires_at(Some(1));
+        frame.set_attribute(expired);
+        let mut object = frame.get_object(1).unwrap();
+        object.set_attribute(osd.clone());
+
+        assert!(frame.get_attribute("osd", "expired").is_none());
+        assert!(!frame.contains_attribute("osd", "expired"));
+
+        let restored = deserialize(&serialize(&Message::video_frame(&frame))?)?;
+        let mut restored = restored.as_video_frame().unwrap();
+        assert_eq!(
+            restored
+                .get_attribute("osd", "skip")
+                .and_then(|a| a.get_expires_at()),
+            osd.get_expires_at()
+        );
+        assert_eq!(
+            restored
+                .get_object(1)
+                .unwrap()
+                .get_attribute("osd", "skip")
+                .and_then(|a| a.get_expires_at()),
+            osd.get_expires_at()
+        );
+        assert!(restored
+            .get_object(2)
+            .unwrap()
+            .get_attribute("osd", "skip")
+            .is_none());
+        // the expired attribute is not sent
+        assert!(restored.delete_attribute("osd", "expired").is_none());
+        assert_eq!(frame.purge_expired_attributes(), 1);
+        Ok(())
+    }
+}
@@ -8,6 +8,7 @@ impl From<&AttributeSet> for generated::AttributeSet {
         let attributes = ud
             .attributes
             .iter()
+            .filter(|a| !a.is_expired())
             .map(generated::Attribute::from)
             .collect();
 
@@ -0,0 +1,168 @@
+use crate::primitives::frame::VideoFrame;
+use crate::primitives::object::VideoObject;
+use crate::primitives::unit::Unit;
+use crate::primitives::Attribute;
+use crate::protobuf::serialize;
+use crate::protobuf::serialize::carrier::{attribute_record, record_attribute};
+use hashbrown::HashMap;
+use prost::Message as ProstMessage;
+use savant_protobuf::generated;
+
+pub(crate) const ATTRIBUTE_UNITS_KIND: &str = "attribute_units";
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct AttributeUnitRecord {
+    #[prost(int64, optional, tag = "1")]
+    pub object_id: Option<i64>,
+    #[prost(string, tag = "2")]
+    pub namespace: String,
+    #[prost(string, tag = "3")]
+    pub name: String,
+    #[prost(uint32, tag = "4")]
+    pub index: u32,
+    #[prost(string, tag = "5")]
+    pub unit: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct AttributeUnitsRecord {
+    #[prost(message, repeated, tag = "1")]
+    pub units: Vec<AttributeUnitRecord>,
+}
+
+fn unit_records<'a>(
+    object_id: Option<i64>,
+    attributes: &'a [Attribute],
+) -> impl Iterator<Item = AttributeUnitRecord> + 'a {
+    attributes
+        .iter()
+        .filter(|a| a.is_persistent && !a.is_expired())
+        .flat_map(move |a| {
+            a.values.iter().enumerate().filter_map(move |(index, v)| {
+                v.unit.map(|unit| AttributeUnitRecord {
+                    object_id,
+                    namespace: a.namespace.clone(),
+                    name: a.name.clone(),
+                    index: index as u32,
+                    unit: unit.symbol().to_string(),
+                })
+            })
+        })
+}
+
+/// The hidden attribute carrying the units of the frame and object attribute values, `None`
+/// when no value has a unit.
+///
+pub(crate) fn attribute_units_attribute(frame: &VideoFrame) -> Option<generated::Attribute> {
+    let mut units = unit_records(None, &frame.attributes)
+        .chain(
+            frame
+                .objects
+                .values()
+                .flat_map(|o| unit_records(Some(o.id), &o.attributes)),
+        )
+        .collect::<Vec<_>>();
+    if units.is_empty() {
+        return None;
+    }
+    units.sort_by_key(|u| u.object_id);
+    let record = AttributeUnitsRecord { units };
+    Some(record_attribute(
+        ATTRIBUTE_UNITS_KIND,
+        record.encode_to_vec(),
+    ))
+}
+
+/// Decodes the units if the attribute carries them.
+///
+pub(crate) fn attribute_units_from_attribute(
+    attribute: &generated::Attribute,
+) -> Option<Result<Vec<AttributeUnitRecord>, serialize::Error>> {
+    match attribute_record(attribute) {
+        Some((ATTRIBUTE_UNITS_KIND, data)) => Some(
+            AttributeUnitsRecord::decode(data)
+                .map(|r| r.units)
+                .map_err(serialize::Error::from),
+        ),
+        _ => None,
+    }
+}
+
+/// Restores the units of the deserialized frame and object attribute values, the units
+/// unknown to this version are dropped.
+///
+pub(crate) fn apply_attribute_units(
+    units: &[AttributeUnitRecord],
+    attributes: &mut [Attribute],
+    objects: &mut HashMap<i64, VideoObject>,
+) {
+    for record in units {
+        let Ok(unit) = record.unit.parse::<Unit>() else {
+            continue;
+        };
+        let attributes = match record.object_id {
+            Some(id) => match objects.get_mut(&id) {
+                Some(object) => object.attributes.as_mut_slice(),
+                None => continue,
+            },
+            None => &mut *attributes,
+        };
+        if let Some(attribute) = attributes
+            .iter_mut()
+            .find(|a| a.namespace == record.namespace && a.name == record.name)
+        {
+            let mut values = attribute.values.to_vec();
+            if let Some(value) = values.get_mut(record.index as usize) {
+                value.unit = Some(unit);
+                attribute.values = values.into();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::Message;
+    use crate::primitives::attribute_value::AttributeValue;
+    use crate::primitives::unit::Unit;
+    use crate::primitives::WithAttributes;
+    use crate::protobuf::{deserialize, serialize};
+    use crate::test::gen_frame;
+
+    #[test]
+    fn test_attribute_units_roundtrip() -> anyhow::Result<()> {
+        let frame = gen_frame();
+        let attribute_count = frame.get_attributes().len();
+        let mut object = frame.get_object(1).unwrap();
+        object.set_persistent_attribute(
+            "tracker",
+            "speed",
+            &None,
+            false,
+            vec![
+                AttributeValue::float(12.5, None).with_unit(Unit::KilometersPerHour)?,
+                AttributeValue::float(90.0, None),
+                AttributeValue::float(3.0, None).with_unit(Unit::MetersPerSecond)?,
+            ],
+        );
+
+        let restored = deserialize(&serialize(&Message::video_frame(&frame))?)?;
+        let restored = restored.as_video_frame().unwrap();
+        assert_eq!(restored.get_attributes().len(), attribute_count);
+        let speed = restored
+            .get_object(1)
+            .unwrap()
+            .get_attribute("tracker", "speed")
+            .unwrap();
+        let units = speed.values.iter().map(|v| v.unit).collect::<Vec<_>>();
+        assert_eq!(
+            units,
+            vec![
+                Some(Unit::KilometersPerHour),
+                None,
+                Some(Unit::MetersPerSecond)
+            ]
+        );
+        Ok(())
+    }
+}
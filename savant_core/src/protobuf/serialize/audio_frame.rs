@@ -0,0 +1,158 @@
+use crate::primitives::audio_frame::{AudioFrame, AudioSampleFormat};
+use crate::primitives::frame::VideoFrameContent;
+use crate::primitives::Attribute;
+use crate::protobuf::serialize;
+use crate::protobuf::serialize::carrier::wrap_record;
+use prost::Message as ProstMessage;
+use savant_protobuf::generated;
+use std::str::FromStr;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub(crate) const AUDIO_FRAME_KIND: &str = "audio_frame";
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct AudioFrameRecord {
+    #[prost(string, tag = "1")]
+    pub source_id: String,
+    #[prost(string, tag = "2")]
+    pub uuid: String,
+    #[prost(int64, tag = "3")]
+    pub pts: i64,
+    #[prost(int64, optional, tag = "4")]
+    pub dts: Option<i64>,
+    #[prost(int64, optional, tag = "5")]
+    pub duration: Option<i64>,
+    #[prost(int32, tag = "6")]
+    pub time_base_numerator: i32,
+    #[prost(int32, tag = "7")]
+    pub time_base_denominator: i32,
+    #[prost(string, tag = "8")]
+    pub sample_format: String,
+    #[prost(uint32, tag = "9")]
+    pub sample_rate: u32,
+    #[prost(uint32, tag = "10")]
+    pub channels: u32,
+    #[prost(uint32, tag = "11")]
+    pub samples: u32,
+    #[prost(string, optional, tag = "12")]
+    pub codec: Option<String>,
+    #[prost(message, optional, tag = "13")]
+    pub external: Option<generated::ExternalFrame>,
+    #[prost(bytes = "vec", optional, tag = "14")]
+    pub internal: Option<Vec<u8>>,
+    #[prost(message, repeated, tag = "15")]
+    pub attributes: Vec<generated::Attribute>,
+}
+
+impl From<&AudioFrame> for AudioFrameRecord {
+    fn from(frame: &AudioFrame) -> Self {
+        let (external, internal) = match &*frame.content {
+            VideoFrameContent::External(e) => (
+                Some(generated::ExternalFrame {
+                    method: e.method.clone(),
+                    location: e.location.clone(),
+                }),
+                None,
+            ),
+            VideoFrameContent::Internal(data) => (None, Some(data.to_packed())),
+            VideoFrameContent::None => (None, None),
+        };
+        AudioFrameRecord {
+            source_id: frame.source_id.clone(),
+            uuid: Uuid::from_u128(frame.uuid).to_string(),
+            pts: frame.pts,
+            dts: frame.dts,
+            duration: frame.duration,
+            time_base_numerator: frame.time_base.0,
+            time_base_denominator: frame.time_base.1,
+            sample_format: frame.sample_format.as_str().to_string(),
+            sample_rate: frame.sample_rate,
+            channels: frame.channels,
+            samples: frame.samples,
+            codec: frame.codec.clone(),
+            external,
+            internal,
+            attributes: frame
+                .attributes
+                .iter()
+                .filter(|a| a.is_persistent && !a.is_expired())
+                .map(generated::Attribute::from)
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<&AudioFrameRecord> for AudioFrame {
+    type Error = serialize::Error;
+
+    fn try_from(value: &AudioFrameRecord) -> Result<Self, Self::Error> {
+        let content = match (&value.external, &value.internal) {
+            (Some(e), _) => VideoFrameContent::External(crate::primitives::frame::ExternalFrame {
+                method: e.method.clone(),
+                location: e.location.clone(),
+            }),
+            (None, Some(data)) => VideoFrameContent::Internal(data.clone().into()),
+            (None, None) => VideoFrameContent::None,
+        };
+        Ok(AudioFrame {
+            source_id: value.source_id.clone(),
+            uuid: Uuid::from_str(&value.uuid)?.as_u128(),
+            pts: value.pts,
+            dts: value.dts,
+            duration: value.duration,
+            time_base: (value.time_base_numerator, value.time_base_denominator),
+            sample_format: AudioSampleFormat::from_str(&value.sample_format).map_err(|_| {
+                serialize::Error::UnknownAudioSampleFormat(value.sample_format.clone())
+            })?,
+            sample_rate: value.sample_rate,
+            channels: value.channels,
+            samples: value.samples,
+            codec: value.codec.clone(),
+            content: Arc::new(content),
+            attributes: value
+                .attributes
+                .iter()
+                .map(Attribute::try_from)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl From<&AudioFrame> for generated::UserData {
+    fn from(frame: &AudioFrame) -> Self {
+        let record = AudioFrameRecord::from(frame).encode_to_vec();
+        wrap_record(&frame.source_id, AUDIO_FRAME_KIND, record)
+    }
+}
+
+pub(crate) fn decode_audio_frame(data: &[u8]) -> Result<AudioFrame, serialize::Error> {
+    AudioFrame::try_from(&AudioFrameRecord::decode(data)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::Message;
+    use crate::primitives::audio_frame::{AudioFrame, AudioSampleFormat};
+    use crate::primitives::frame::VideoFrameContent;
+    use crate::primitives::{Attribute, WithAttributes};
+    use crate::protobuf::{deserialize, serialize};
+
+    #[test]
+    fn test_audio_frame_message() {
+        let mut frame = AudioFrame::new(
+            "mic",
+            100,
+            AudioSampleFormat::F32,
+            44100,
+            2,
+            441,
+            VideoFrameContent::Internal(vec![1; 441 * 2 * 4].into()),
+        );
+        frame.set_attribute(Attribute::persistent("a", "b", vec![], &None, false));
+        let m = Message::audio_frame(frame.clone());
+        let restored = deserialize(&serialize(&m).unwrap()).unwrap();
+        assert!(restored.is_audio_frame());
+        assert_eq!(restored.as_audio_frame().unwrap(), &frame);
+    }
+}
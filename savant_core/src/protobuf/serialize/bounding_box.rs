@@ -28,6 +28,7 @@ impl From<&generated::BoundingBox> for RBBoxData {
             height: value.height.into(),
             angle: value.angle.unwrap_or(BBOX_ELEMENT_UNDEFINED).into(),
             has_modifications: false.into(),
+            modification_count: 0.into(),
         }
     }
 }
@@ -0,0 +1,142 @@
+use prost::Message as ProstMessage;
+use savant_protobuf::generated;
+use std::mem;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// FNV-1a, the hash must not depend on the Rust release or the process.
+///
+pub(crate) fn stable_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+fn sort_attributes(attributes: &mut [generated::Attribute]) {
+    attributes.sort_by(|a, b| (&a.namespace, &a.name).cmp(&(&b.namespace, &b.name)));
+}
+
+fn canonicalize_frame(frame: &mut generated::VideoFrame) {
+    sort_attributes(&mut frame.attributes);
+    frame.objects.sort_by_key(|o| o.id);
+    for object in &mut frame.objects {
+        sort_attributes(&mut object.attributes);
+    }
+}
+
+/// Orders the repeated fields whose order carries no meaning: the objects by id and the
+/// attributes by namespace and name. Updates keep their order because it is applied as is.
+///
+pub(crate) fn canonicalize_content(content: &mut generated::message::Content) {
+    match content {
+        generated::message::Content::VideoFrame(frame) => canonicalize_frame(frame),
+        generated::message::Content::VideoFrameBatch(batch) => {
+            batch.batch.values_mut().for_each(canonicalize_frame)
+        }
+        generated::message::Content::UserData(user_data) => {
+            sort_attributes(&mut user_data.attributes)
+        }
+        _ => (),
+    }
+}
+
+/// Encodes the message with the map entries ordered by key. Protobuf merges concatenated
+/// encodings of a message, so the maps are emptied and every entry is appended as a separate
+/// message holding just the entry; the result decodes to the original message.
+///
+pub(crate) fn encode_canonical(mut message: generated::Message) -> Vec<u8> {
+    if let Some(content) = message.content.as_mut() {
+        canonicalize_content(content);
+    }
+    let mut context = mem::take(&mut message.propagated_context)
+        .into_iter()
+        .collect::<Vec<_>>();
+    context.sort();
+    let mut frames = match message.content.as_mut() {
+        Some(generated::message::Content::VideoFrameBatch(batch)) => {
+            mem::take(&mut batch.batch).into_iter().collect::<Vec<_>>()
+        }
+        _ => Vec::new(),
+    };
+    frames.sort_by_key(|(id, _)| *id);
+
+    let mut buf = message.encode_to_vec();
+    for entry in context {
+        generated::Message {
+            propagated_context: [entry].into_iter().collect(),
+            ..Default::default()
+        }
+        .encode_raw(&mut buf);
+    }
+    for entry in frames {
+        generated::Message {
+            content: Some(generated::message::Content::VideoFrameBatch(
+                generated::VideoFrameBatch {
+                    batch: [entry].into_iter().collect(),
+                },
+            )),
+            ..Default::default()
+        }
+        .encode_raw(&mut buf);
+    }
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::Message;
+    use crate::otlp::PropagatedContext;
+    use crate::primitives::frame_batch::VideoFrameBatch;
+    use crate::primitives::{Attribute, WithAttributes};
+    use crate::protobuf::serialize::canonical::stable_hash;
+    use crate::protobuf::{deserialize, serialize_canonical};
+    use crate::test::gen_frame;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_stable_hash() {
+        assert_eq!(stable_hash(b""), 0xcbf29ce484222325);
+        assert_eq!(stable_hash(b"a"), 0xaf63dc4c8601ec8c);
+    }
+
+    #[test]
+    fn test_canonical_frame() -> anyhow::Result<()> {
+        let mut left = gen_frame();
+        left.set_attribute(Attribute::persistent("a", "first", vec![], &None, false));
+        left.set_attribute(Attribute::persistent("b", "second", vec![], &None, false));
+        let mut right = left.smart_copy();
+        // the removal swaps the last attribute in, so the order differs from the left frame
+        let first = right.delete_attribute("a", "first").unwrap();
+        right.set_attribute(first);
+
+        let left = Message::video_frame(&left);
+        let mut right = Message::video_frame(&right);
+        right.meta_mut().seq_id = left.meta().seq_id;
+        assert_eq!(serialize_canonical(&left)?, serialize_canonical(&right)?);
+        assert_eq!(left.content_hash(), right.content_hash());
+        Ok(())
+    }
+
+    #[test]
+    fn test_canonical_batch() -> anyhow::Result<()> {
+        let mut batch = VideoFrameBatch::new();
+        for id in 0..16 {
+            batch.add(id, gen_frame());
+        }
+        let mut message = Message::video_frame_batch(&batch);
+        message.set_span_context(PropagatedContext(HashMap::from([
+            ("traceparent".to_string(), "00-01-02-01".to_string()),
+            ("tracestate".to_string(), "".to_string()),
+        ])));
+        let bytes = serialize_canonical(&message)?;
+        assert_eq!(bytes, serialize_canonical(&message)?);
+
+        let restored = deserialize(&bytes)?;
+        assert_eq!(restored.get_span_context().0.len(), 2);
+        let restored_batch = restored.as_video_frame_batch().unwrap();
+        assert_eq!(restored_batch.frames().len(), 16);
+        assert_eq!(restored.content_hash(), message.content_hash());
+        Ok(())
+    }
+}
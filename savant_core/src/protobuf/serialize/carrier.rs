@@ -0,0 +1,62 @@
+use savant_protobuf::generated;
+
+// The message schema of `savant_protobuf` has no audio frames, telemetry frames or frame
+// processing hints, so they travel as encoded records held by reserved hidden attributes.
+// Audio and telemetry frames are sent as user data of the same source holding such an
+// attribute, peers which do not know them see ordinary user data.
+//
+// A carrier attribute is added only when the carried value differs from its default, so the
+// messages of the frames not using a feature are encoded exactly as before the feature, and
+// peers which do not know a carrier see a hidden attribute they ignore.
+pub(crate) const CARRIER_NAMESPACE: &str = "savant";
+
+pub(crate) fn record_attribute(kind: &str, record: Vec<u8>) -> generated::Attribute {
+    generated::Attribute {
+        namespace: CARRIER_NAMESPACE.to_string(),
+        name: kind.to_string(),
+        hint: None,
+        is_persistent: true,
+        values: vec![generated::AttributeValue {
+            confidence: None,
+            value: Some(generated::attribute_value::Value::Bytes(
+                generated::BytesAttributeValueVariant {
+                    dims: vec![record.len() as i64],
+                    data: record,
+                },
+            )),
+        }],
+        is_hidden: true,
+    }
+}
+
+/// Returns the kind and the encoded record held by the attribute, `None` for ordinary
+/// attributes.
+///
+pub(crate) fn attribute_record(attribute: &generated::Attribute) -> Option<(&str, &[u8])> {
+    if attribute.namespace != CARRIER_NAMESPACE || !attribute.is_hidden {
+        return None;
+    }
+    match attribute.values.first().and_then(|v| v.value.as_ref()) {
+        Some(generated::attribute_value::Value::Bytes(bytes)) => {
+            Some((attribute.name.as_str(), bytes.data.as_slice()))
+        }
+        _ => None,
+    }
+}
+
+pub(crate) fn wrap_record(source_id: &str, kind: &str, record: Vec<u8>) -> generated::UserData {
+    generated::UserData {
+        source_id: source_id.to_string(),
+        attributes: vec![record_attribute(kind, record)],
+    }
+}
+
+/// Returns the kind and the encoded record carried by the user data, `None` for ordinary
+/// user data.
+///
+pub(crate) fn unwrap_record(ud: &generated::UserData) -> Option<(&str, &[u8])> {
+    match ud.attributes.as_slice() {
+        [attribute] => attribute_record(attribute),
+        _ => None,
+    }
+}
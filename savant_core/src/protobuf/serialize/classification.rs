@@ -0,0 +1,304 @@
+use crate::metrics::get_or_create_counter_family;
+use crate::primitives::classification::DataClassification;
+use crate::protobuf::serialize;
+use crate::protobuf::serialize::carrier::{attribute_record, record_attribute};
+use crate::protobuf::serialize::Error;
+use hashbrown::HashSet;
+use lazy_static::lazy_static;
+use log::warn;
+use parking_lot::RwLock;
+use prost::Message as ProstMessage;
+use savant_protobuf::generated;
+
+pub(crate) const CLASSIFICATION_KIND: &str = "classification";
+
+const CLASSIFIED_ITEMS_METRIC: &str = "classified_items_exported";
+
+lazy_static! {
+    static ref CLASSIFICATION_POLICY: RwLock<Option<ClassificationPolicy>> = RwLock::new(None);
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct ClassificationRecord {
+    #[prost(uint32, tag = "1")]
+    pub level: u32,
+}
+
+/// The hidden attribute carrying the classification of a frame or an object, `None` for
+/// public data.
+///
+pub(crate) fn classification_attribute(
+    classification: DataClassification,
+) -> Option<generated::Attribute> {
+    if classification.is_public() {
+        return None;
+    }
+    let record = ClassificationRecord {
+        level: classification.to_level(),
+    }
+    .encode_to_vec();
+    Some(record_attribute(CLASSIFICATION_KIND, record))
+}
+
+/// Decodes the classification if the attribute carries it.
+///
+pub(crate) fn classification_from_attribute(
+    attribute: &generated::Attribute,
+) -> Option<Result<DataClassification, serialize::Error>> {
+    match attribute_record(attribute) {
+        Some((CLASSIFICATION_KIND, data)) => Some(
+            ClassificationRecord::decode(data)
+                .map(|r| DataClassification::from_level(r.level))
+                .map_err(serialize::Error::from),
+        ),
+        _ => None,
+    }
+}
+
+/// What happens to the frames and the objects classified above the allowed level.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClassificationAction {
+    /// The objects are removed, their children lose the parent. The frames lose the content,
+    /// the attributes and the objects, the rest of the frame (e.g. the timestamps) is kept,
+    /// so the receivers see the stream without gaps.
+    #[default]
+    Redact,
+    /// The message is not serialized, [`Error::ClassificationRejected`] is returned.
+    Reject,
+}
+
+/// The classification level a sink accepts. Every frame and object classified above the
+/// public level is counted in the `classified_items_exported` counter labelled with the kind
+/// (`frame`, `object`), the classification and the action taken (`exported`, `redacted`,
+/// `rejected`).
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClassificationPolicy {
+    /// The highest classification exported as is.
+    pub allowed: DataClassification,
+    pub action: ClassificationAction,
+}
+
+impl ClassificationPolicy {
+    pub fn new(allowed: DataClassification, action: ClassificationAction) -> Self {
+        Self { allowed, action }
+    }
+
+    /// Exports everything, only counts the classified data.
+    ///
+    pub fn unrestricted() -> Self {
+        Self::new(DataClassification::Pii, ClassificationAction::Redact)
+    }
+
+    fn count(&self, kind: &str, classification: DataClassification, action: &str) {
+        let res = get_or_create_counter_family(
+            CLASSIFIED_ITEMS_METRIC,
+            Some("The number of classified frames and objects passed through serialization"),
+            &["kind", "classification", "action"],
+            None,
+        )
+        .lock()
+        .inc(1, &[kind, classification.as_str(), action]);
+        if let Err(e) = res {
+            warn!(
+                target: "savant_rs::classification",
+                "Failed to count the classified {}: {}", kind, e
+            );
+        }
+    }
+
+    /// Decides on the item and counts the decision, `true` when the item must be removed.
+    ///
+    fn enforce(&self, kind: &str, classification: DataClassification) -> Result<bool, Error> {
+        if classification.is_public() {
+            return Ok(false);
+        }
+        if classification <= self.allowed {
+            self.count(kind, classification, "exported");
+            return Ok(false);
+        }
+        match self.action {
+            ClassificationAction::Redact => {
+                self.count(kind, classification, "redacted");
+                Ok(true)
+            }
+            ClassificationAction::Reject => {
+                self.count(kind, classification, "rejected");
+                Err(Error::ClassificationRejected(
+                    kind.to_string(),
+                    classification.to_string(),
+                ))
+            }
+        }
+    }
+
+    fn enforce_objects(
+        &self,
+        objects: &mut Vec<generated::VideoObject>,
+        inherited: DataClassification,
+    ) -> Result<(), Error> {
+        let mut removed = HashSet::new();
+        for object in objects.iter() {
+            let classification = attributes_classification(&object.attributes)?.max(inherited);
+            if self.enforce("object", classification)? {
+                removed.insert(object.id);
+            }
+        }
+        if removed.is_empty() {
+            return Ok(());
+        }
+        objects.retain(|o| !removed.contains(&o.id));
+        for object in objects.iter_mut() {
+            if object.parent_id.is_some_and(|id| removed.contains(&id)) {
+                object.parent_id = None;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn enforce_frame(&self, frame: &mut generated::VideoFrame) -> Result<(), Error> {
+        let classification = attributes_classification(&frame.attributes)?;
+        if self.enforce("frame", classification)? {
+            frame.content = Some(generated::video_frame::Content::None(
+                generated::NoneFrame {},
+            ));
+            frame
+                .attributes
+                .retain(|a| classification_from_attribute(a).is_some());
+            frame.objects.clear();
+            return Ok(());
+        }
+        self.enforce_objects(&mut frame.objects, classification)
+    }
+
+    pub fn enforce_message(&self, message: &mut generated::Message) -> Result<(), Error> {
+        match &mut message.content {
+            Some(generated::message::Content::VideoFrame(frame)) => self.enforce_frame(frame),
+            Some(generated::message::Content::VideoFrameBatch(batch)) => {
+                for frame in batch.batch.values_mut() {
+                    self.enforce_frame(frame)?;
+                }
+                Ok(())
+            }
+            Some(generated::message::Content::VideoFrameUpdate(update)) => {
+                let mut objects = Vec::with_capacity(update.objects.len());
+                for object in update.objects.drain(..) {
+                    let classification = match &object.object {
+                        Some(o) => attributes_classification(&o.attributes)?,
+                        None => DataClassification::Public,
+                    };
+                    if !self.enforce("object", classification)? {
+                        objects.push(object);
+                    }
+                }
+                update.objects = objects;
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn attributes_classification(
+    attributes: &[generated::Attribute],
+) -> Result<DataClassification, Error> {
+    attributes
+        .iter()
+        .find_map(classification_from_attribute)
+        .unwrap_or(Ok(DataClassification::Public))
+}
+
+/// Sets the policy applied to every message leaving the process through serialization and
+/// message journals, `None` exports everything without counting.
+///
+pub fn set_classification_policy(policy: Option<ClassificationPolicy>) {
+    *CLASSIFICATION_POLICY.write() = policy;
+}
+
+pub fn get_classification_policy() -> Option<ClassificationPolicy> {
+    *CLASSIFICATION_POLICY.read()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::Message;
+    use crate::metrics::get_counter_family;
+    use crate::primitives::classification::DataClassification;
+    use crate::primitives::frame::VideoFrameProxy;
+    use crate::primitives::object::ObjectOperations;
+    use crate::primitives::WithAttributes;
+    use crate::protobuf::serialize::classification::{ClassificationAction, ClassificationPolicy};
+    use crate::protobuf::{from_pb, Error, ToProtobuf};
+    use crate::test::gen_frame;
+    use savant_protobuf::generated;
+
+    #[test]
+    fn test_classification_roundtrip() {
+        let mut frame = gen_frame();
+        let attribute_count = frame.get_attributes().len();
+        frame.set_classification(DataClassification::Internal);
+        frame
+            .get_object(1)
+            .unwrap()
+            .set_classification(DataClassification::Pii);
+        assert_eq!(
+            frame.get_effective_classification(),
+            DataClassification::Pii
+        );
+        let bytes = frame.to_pb().unwrap();
+        let restored = from_pb::<generated::VideoFrame, VideoFrameProxy>(&bytes).unwrap();
+        assert_eq!(restored.get_classification(), DataClassification::Internal);
+        assert_eq!(
+            restored.get_object(1).unwrap().get_classification(),
+            DataClassification::Pii
+        );
+        assert_eq!(
+            restored.get_object(0).unwrap().get_classification(),
+            DataClassification::Public
+        );
+        assert_eq!(restored.get_attributes().len(), attribute_count);
+    }
+
+    #[test]
+    fn test_enforce_policy() -> anyhow::Result<()> {
+        let mut frame = gen_frame();
+        frame
+            .get_object(0)
+            .unwrap()
+            .set_classification(DataClassification::Pii);
+        let internal =
+            ClassificationPolicy::new(DataClassification::Internal, ClassificationAction::Redact);
+
+        let mut message = generated::Message::from(&Message::video_frame(&frame));
+        internal.enforce_message(&mut message)?;
+        let restored = Message::try_from(&message)?;
+        let restored = restored.as_video_frame().unwrap();
+        assert!(restored.get_object(0).is_none());
+        // the children of the removed object are detached
+        assert_eq!(restored.get_object(1).unwrap().get_parent_id(), None);
+        assert_eq!(restored.get_object(2).unwrap().get_parent_id(), None);
+
+        frame.set_classification(DataClassification::Pii);
+        let mut message = generated::Message::from(&Message::video_frame(&frame));
+        internal.enforce_message(&mut message)?;
+        let restored = Message::try_from(&message)?;
+        let restored = restored.as_video_frame().unwrap();
+        assert!(restored.get_all_objects().is_empty());
+        assert!(restored.get_attributes().is_empty());
+        assert_eq!(restored.get_classification(), DataClassification::Pii);
+
+        let reject =
+            ClassificationPolicy::new(DataClassification::Public, ClassificationAction::Reject);
+        let mut message = generated::Message::from(&Message::video_frame(&frame));
+        assert!(matches!(
+            reject.enforce_message(&mut message),
+            Err(Error::ClassificationRejected(_, _))
+        ));
+
+        let counter = get_counter_family("classified_items_exported").unwrap();
+        assert!(counter.lock().get(&["frame", "pii", "redacted"])?.is_some());
+        assert!(counter.lock().get(&["frame", "pii", "rejected"])?.is_some());
+        Ok(())
+    }
+}
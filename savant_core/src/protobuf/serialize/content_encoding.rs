@@ -0,0 +1,206 @@
+use crate::primitives::content_encoding::{get_max_decoded_content_size, ContentEncoding};
+use crate::primitives::frame::VideoFrameContent;
+use crate::primitives::raw_content::InternalFrame;
+use crate::protobuf::serialize;
+use crate::protobuf::serialize::carrier::{attribute_record, record_attribute};
+use prost::Message as ProstMessage;
+use savant_protobuf::generated;
+
+pub(crate) const CONTENT_ENCODING_KIND: &str = "content_encoding";
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct ContentEncodingRecord {
+    #[prost(string, tag = "1")]
+    pub encoding: String,
+    #[prost(uint64, tag = "2")]
+    pub size: u64,
+}
+
+impl ContentEncodingRecord {
+    pub(crate) fn new(encoding: ContentEncoding, size: usize) -> Self {
+        Self {
+            encoding: encoding.name().to_string(),
+            size: size as u64,
+        }
+    }
+
+    /// The codec and the decoded size, the size above [`get_max_decoded_content_size`] is
+    /// rejected, so a forged record does not make the decoder allocate it.
+    ///
+    pub(crate) fn decode_encoding(&self) -> Result<(ContentEncoding, usize), serialize::Error> {
+        let encoding = self
+            .encoding
+            .parse()
+            .map_err(|_| serialize::Error::UnknownContentEncoding(self.encoding.clone()))?;
+        let max = get_max_decoded_content_size();
+        match usize::try_from(self.size) {
+            Ok(size) if size <= max => Ok((encoding, size)),
+            _ => Err(serialize::Error::ContentEncoding(format!(
+                "The decoded size {} exceeds the maximum {}",
+                self.size, max
+            ))),
+        }
+    }
+}
+
+/// The compressed bytes sent as they are: the raw layout is not serialized, so the content
+/// with a padded layout is sent decoded and packed.
+///
+pub(crate) fn wire_compressed(content: &InternalFrame) -> Option<(ContentEncoding, &[u8])> {
+    if content.get_layout().is_some_and(|l| !l.is_packed()) {
+        return None;
+    }
+    content.get_encoding().zip(content.get_compressed_data())
+}
+
+/// The hidden attribute carrying the codec and the decoded size of compressed frame content,
+/// `None` for uncompressed content.
+///
+pub(crate) fn content_encoding_attribute(
+    content: &VideoFrameContent,
+) -> Option<generated::Attribute> {
+    let VideoFrameContent::Internal(content) = content else {
+        return None;
+    };
+    let (encoding, _) = wire_compressed(content)?;
+    let record = ContentEncodingRecord::new(encoding, content.len());
+    Some(record_attribute(
+        CONTENT_ENCODING_KIND,
+        record.encode_to_vec(),
+    ))
+}
+
+/// Decodes the codec and the decoded size if the attribute carries them.
+///
+pub(crate) fn content_encoding_from_attribute(
+    attribute: &generated::Attribute,
+) -> Option<Result<(ContentEncoding, usize), serialize::Error>> {
+    match attribute_record(attribute) {
+        Some((CONTENT_ENCODING_KIND, data)) => Some(
+            ContentEncodingRecord::decode(data)
+                .map_err(serialize::Error::from)
+                .and_then(|r| r.decode_encoding()),
+        ),
+        _ => None,
+    }
+}
+
+fn compress_frame_content(
+    frame: &mut generated::VideoFrame,
+    encoding: ContentEncoding,
+) -> Result<(), serialize::Error> {
+    if frame
+        .attributes
+        .iter()
+        .any(|a| content_encoding_from_attribute(a).is_some())
+    {
+        return Ok(());
+    }
+    let Some(generated::video_frame::Content::Internal(data)) = &mut frame.content else {
+        return Ok(());
+    };
+    if data.is_empty() {
+        return Ok(());
+    }
+    let compressed = encoding
+        .compress(data)
+        .map_err(|e| serialize::Error::ContentEncoding(e.to_string()))?;
+    let record = ContentEncodingRecord::new(encoding, data.len());
+    *data = compressed;
+    frame.attributes.push(record_attribute(
+        CONTENT_ENCODING_KIND,
+        record.encode_to_vec(),
+    ));
+    Ok(())
+}
+
+/// Compresses the uncompressed internal content of the frames in the message converted for
+/// sending, the content already compressed keeps its codec.
+///
+pub(crate) fn compress_content(
+    message: &mut generated::Message,
+    encoding: ContentEncoding,
+) -> Result<(), serialize::Error> {
+    match &mut message.content {
+        Some(generated::message::Content::VideoFrame(frame)) => {
+            compress_frame_content(frame, encoding)
+        }
+        Some(generated::message::Content::VideoFrameBatch(batch)) => {
+            for frame in batch.batch.values_mut() {
+                compress_frame_content(frame, encoding)?;
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::Message;
+    use crate::primitives::content_encoding::ContentEncoding;
+    use crate::primitives::frame::VideoFrameContent;
+    use crate::primitives::raw_content::{InternalFrame, PixelFormat, RawLayout};
+    use crate::primitives::WithAttributes;
+    use crate::protobuf::serialize::content_encoding::ContentEncodingRecord;
+    use crate::protobuf::{deserialize, serialize, serialize_for_sink};
+    use crate::test::gen_frame;
+
+    fn internal(message: &Message) -> InternalFrame {
+        let frame = message.as_video_frame().unwrap();
+        match frame.get_content().as_ref() {
+            VideoFrameContent::Internal(content) => content.clone(),
+            _ => panic!("Internal content expected"),
+        }
+    }
+
+    #[test]
+    fn test_compressed_content_roundtrip() -> anyhow::Result<()> {
+        let mut frame = gen_frame();
+        let attribute_count = frame.get_attributes().len();
+        frame.set_width(64);
+        frame.set_height(32);
+        frame.set_codec(Some(PixelFormat::Rgb8.codec()));
+        let pixels = InternalFrame::raw(
+            vec![7; 64 * 32 * 3],
+            RawLayout::packed(PixelFormat::Rgb8, 64, 32)?,
+        )?;
+        frame.set_content(VideoFrameContent::Internal(pixels.clone()));
+        frame.compress_content(ContentEncoding::Lz4)?;
+
+        let restored = deserialize(&serialize(&Message::video_frame(&frame))?)?;
+        let content = internal(&restored);
+        assert_eq!(content.get_encoding(), Some(ContentEncoding::Lz4));
+        assert_eq!(
+            content.get_layout().map(|l| l.get_format()),
+            Some(PixelFormat::Rgb8)
+        );
+        assert_eq!(content, pixels);
+        assert_eq!(
+            restored.as_video_frame().unwrap().get_attributes().len(),
+            attribute_count
+        );
+
+        frame.set_content(VideoFrameContent::Internal(pixels.clone()));
+        let plain = serialize(&Message::video_frame(&frame))?;
+        let compressed = serialize_for_sink(
+            &Message::video_frame(&frame),
+            None,
+            Some(ContentEncoding::Zstd),
+        )?;
+        assert!(compressed.len() < plain.len());
+        let content = internal(&deserialize(&compressed)?);
+        assert_eq!(content.get_encoding(), Some(ContentEncoding::Zstd));
+        assert_eq!(content, pixels);
+        Ok(())
+    }
+
+    #[test]
+    fn test_forged_decoded_size() {
+        let record = ContentEncodingRecord {
+            encoding: ContentEncoding::Zstd.name().to_string(),
+            size: u64::MAX,
+        };
+        assert!(record.decode_encoding().is_err());
+    }
+}
@@ -0,0 +1,100 @@
+use crate::primitives::frozen_objects::FrozenObjects;
+use crate::protobuf::serialize;
+use crate::protobuf::serialize::carrier::{attribute_record, record_attribute};
+use prost::Message as ProstMessage;
+use savant_protobuf::generated;
+
+pub(crate) const FROZEN_OBJECTS_KIND: &str = "frozen_objects";
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct FrozenNamespaceRecord {
+    /// All objects are frozen when not set.
+    #[prost(string, optional, tag = "1")]
+    pub namespace: Option<String>,
+    #[prost(string, tag = "2")]
+    pub stage: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct FrozenObjectsRecord {
+    #[prost(message, repeated, tag = "1")]
+    pub frozen: Vec<FrozenNamespaceRecord>,
+}
+
+/// The hidden attribute carrying the frozen parts of the object set, `None` when no objects
+/// are frozen.
+///
+pub(crate) fn frozen_objects_attribute(frozen: &[FrozenObjects]) -> Option<generated::Attribute> {
+    if frozen.is_empty() {
+        return None;
+    }
+    let record = FrozenObjectsRecord {
+        frozen: frozen
+            .iter()
+            .map(|f| FrozenNamespaceRecord {
+                namespace: f.namespace.clone(),
+                stage: f.stage.clone(),
+            })
+            .collect(),
+    };
+    Some(record_attribute(
+        FROZEN_OBJECTS_KIND,
+        record.encode_to_vec(),
+    ))
+}
+
+/// Decodes the frozen parts of the object set if the attribute carries them.
+///
+pub(crate) fn frozen_objects_from_attribute(
+    attribute: &generated::Attribute,
+) -> Option<Result<Vec<FrozenObjects>, serialize::Error>> {
+    match attribute_record(attribute) {
+        Some((FROZEN_OBJECTS_KIND, data)) => Some(
+            FrozenObjectsRecord::decode(data)
+                .map(|r| {
+                    r.frozen
+                        .into_iter()
+                        .map(|f| FrozenObjects {
+                            namespace: f.namespace,
+                            stage: f.stage,
+                        })
+                        .collect()
+                })
+                .map_err(serialize::Error::from),
+        ),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::Message;
+    use crate::primitives::frozen_objects::FrozenObjects;
+    use crate::primitives::object::IdCollisionResolutionPolicy;
+    use crate::primitives::WithAttributes;
+    use crate::protobuf::{deserialize, serialize};
+    use crate::test::{gen_frame, gen_object};
+
+    #[test]
+    fn test_frozen_objects_roundtrip() -> anyhow::Result<()> {
+        let frame = gen_frame();
+        let attribute_count = frame.get_attributes().len();
+        frame.freeze_objects(Some("detector"), "detection");
+        frame.freeze_objects(None, "classification");
+
+        let restored = deserialize(&serialize(&Message::video_frame(&frame))?)?;
+        let restored = restored.as_video_frame().unwrap();
+        assert_eq!(
+            restored.get_frozen_objects(),
+            vec![
+                FrozenObjects::new(Some("detector"), "detection"),
+                FrozenObjects::new(None, "classification"),
+            ]
+        );
+        assert_eq!(restored.get_attributes().len(), attribute_count);
+        assert!(restored
+            .add_object(gen_object(100), IdCollisionResolutionPolicy::GenerateNewId)
+            .is_err());
+        Ok(())
+    }
+}
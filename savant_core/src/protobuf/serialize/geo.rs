@@ -0,0 +1,98 @@
+use crate::primitives::geo::GeoPosition;
+use crate::protobuf::serialize;
+use crate::protobuf::serialize::carrier::{attribute_record, record_attribute};
+use prost::Message as ProstMessage;
+use savant_protobuf::generated;
+
+pub(crate) const GEO_POSITION_KIND: &str = "geo_position";
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct GeoPositionRecord {
+    #[prost(double, tag = "1")]
+    pub latitude: f64,
+    #[prost(double, tag = "2")]
+    pub longitude: f64,
+    #[prost(double, tag = "3")]
+    pub altitude: f64,
+    #[prost(double, optional, tag = "4")]
+    pub heading: Option<f64>,
+}
+
+impl From<&GeoPosition> for GeoPositionRecord {
+    fn from(position: &GeoPosition) -> Self {
+        GeoPositionRecord {
+            latitude: position.latitude,
+            longitude: position.longitude,
+            altitude: position.altitude,
+            heading: position.heading,
+        }
+    }
+}
+
+impl From<&GeoPositionRecord> for GeoPosition {
+    fn from(value: &GeoPositionRecord) -> Self {
+        GeoPosition {
+            latitude: value.latitude,
+            longitude: value.longitude,
+            altitude: value.altitude,
+            heading: value.heading,
+        }
+    }
+}
+
+/// The hidden attribute carrying the camera pose of a frame or the position of an object,
+/// `None` without the position.
+///
+pub(crate) fn geo_position_attribute(
+    position: Option<&GeoPosition>,
+) -> Option<generated::Attribute> {
+    let record = GeoPositionRecord::from(position?).encode_to_vec();
+    Some(record_attribute(GEO_POSITION_KIND, record))
+}
+
+/// Decodes the position if the attribute carries it.
+///
+pub(crate) fn geo_position_from_attribute(
+    attribute: &generated::Attribute,
+) -> Option<Result<GeoPosition, serialize::Error>> {
+    match attribute_record(attribute) {
+        Some((GEO_POSITION_KIND, data)) => Some(
+            GeoPositionRecord::decode(data)
+                .map(|r| GeoPosition::from(&r))
+                .map_err(serialize::Error::from),
+        ),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::frame::VideoFrameProxy;
+    use crate::primitives::geo::GeoPosition;
+    use crate::primitives::object::ObjectOperations;
+    use crate::primitives::WithAttributes;
+    use crate::protobuf::{from_pb, ToProtobuf};
+    use crate::test::gen_frame;
+    use savant_protobuf::generated;
+
+    #[test]
+    fn test_geo_position_roundtrip() {
+        let mut frame = gen_frame();
+        let attribute_count = frame.get_attributes().len();
+        let pose = GeoPosition::new(55.7558, 37.6173, 150.0, Some(90.0)).unwrap();
+        let position = GeoPosition::new(55.7559, 37.6175, 148.5, None).unwrap();
+        frame.set_geo_pose(Some(pose));
+        frame
+            .get_object(1)
+            .unwrap()
+            .set_geo_position(Some(position));
+        let bytes = frame.to_pb().unwrap();
+        let restored = from_pb::<generated::VideoFrame, VideoFrameProxy>(&bytes).unwrap();
+        assert_eq!(restored.get_geo_pose(), Some(pose));
+        assert_eq!(restored.get_attributes().len(), attribute_count);
+        let object = restored.get_object(1).unwrap();
+        assert_eq!(object.get_geo_position(), Some(position));
+        assert!(object.get_attributes().is_empty());
+        assert!(restored.get_object(2).unwrap().get_geo_position().is_none());
+    }
+}
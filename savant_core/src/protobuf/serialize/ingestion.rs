@@ -0,0 +1,388 @@
+use crate::metrics::get_or_create_counter_family;
+use crate::protobuf::serialize::audio_frame::{AudioFrameRecord, AUDIO_FRAME_KIND};
+use crate::protobuf::serialize::carrier::{attribute_record, unwrap_record, wrap_record};
+use crate::protobuf::serialize::telemetry_frame::{TelemetryFrameRecord, TELEMETRY_FRAME_KIND};
+use crate::protobuf::serialize::Error;
+use globset::{Glob, GlobMatcher};
+use lazy_static::lazy_static;
+use log::warn;
+use parking_lot::RwLock;
+use prost::Message as ProstMessage;
+use savant_protobuf::generated;
+use std::sync::Arc;
+
+const NAMESPACE_COLLISIONS_METRIC: &str = "ingested_namespace_collisions";
+
+lazy_static! {
+    static ref INGESTION_POLICY: RwLock<Option<Arc<IngestionPolicy>>> = RwLock::new(None);
+}
+
+/// What happens to the incoming attributes in the locally-managed namespaces.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CollisionAction {
+    /// The namespace gets the prefix, e.g. `tracker` becomes `upstream.tracker` with the
+    /// `upstream.` prefix, so the values stay available without overwriting the local ones.
+    Rename(String),
+    /// The attributes are removed.
+    Drop,
+    /// The message is not deserialized, [`Error::NamespaceCollision`] is returned.
+    Reject,
+}
+
+/// The namespaces managed by the local modules, given as globs (e.g. `tracker`, `lpr*`), and
+/// the action taken for the incoming attributes in them. Every collision is counted in the
+/// `ingested_namespace_collisions` counter labelled with the matching glob and the action
+/// (`renamed`, `dropped`, `rejected`). The reserved hidden attributes are never touched.
+///
+/// The policy is applied wherever the incoming data is decoded: [`crate::protobuf::deserialize`],
+/// [`crate::protobuf::from_pb`], [`crate::protobuf::LazyVideoFrame`] and the stream and the
+/// encrypted journal readers.
+///
+#[derive(Debug, Clone)]
+pub struct IngestionPolicy {
+    namespaces: Vec<String>,
+    matchers: Vec<GlobMatcher>,
+    action: CollisionAction,
+}
+
+impl IngestionPolicy {
+    pub fn new(namespaces: &[&str], action: CollisionAction) -> anyhow::Result<Self> {
+        if let CollisionAction::Rename(prefix) = &action {
+            if prefix.is_empty() {
+                anyhow::bail!("The rename prefix must not be empty");
+            }
+        }
+        let matchers = namespaces
+            .iter()
+            .map(|ns| Ok(Glob::new(ns)?.compile_matcher()))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self {
+            namespaces: namespaces.iter().map(|ns| ns.to_string()).collect(),
+            matchers,
+            action,
+        })
+    }
+
+    pub fn get_namespaces(&self) -> &[String] {
+        &self.namespaces
+    }
+
+    pub fn get_action(&self) -> &CollisionAction {
+        &self.action
+    }
+
+    pub fn is_managed(&self, namespace: &str) -> bool {
+        self.managed_by(namespace).is_some()
+    }
+
+    /// The glob the namespace matches, the first one when it matches several.
+    ///
+    fn managed_by(&self, namespace: &str) -> Option<&str> {
+        self.matchers
+            .iter()
+            .position(|m| m.is_match(namespace))
+            .map(|i| self.namespaces[i].as_str())
+    }
+
+    fn count(&self, glob: &str, action: &str) {
+        let res = get_or_create_counter_family(
+            NAMESPACE_COLLISIONS_METRIC,
+            Some("The number of incoming attributes in the locally-managed namespaces"),
+            &["glob", "action"],
+            None,
+        )
+        .lock()
+        .inc(1, &[glob, action]);
+        if let Err(e) = res {
+            warn!(
+                target: "savant_rs::ingestion",
+                "Failed to count the namespace collision: {}", e
+            );
+        }
+    }
+
+    /// Applies the action to the attribute, `false` when the attribute must be removed.
+    ///
+    fn resolve(&self, attribute: &mut generated::Attribute) -> Result<bool, Error> {
+        if attribute_record(attribute).is_some() {
+            return Ok(true);
+        }
+        let Some(glob) = self.managed_by(&attribute.namespace) else {
+            return Ok(true);
+        };
+        match &self.action {
+            CollisionAction::Rename(prefix) => {
+                self.count(glob, "renamed");
+                attribute.namespace = format!("{}{}", prefix, attribute.namespace);
+                Ok(true)
+            }
+            CollisionAction::Drop => {
+                self.count(glob, "dropped");
+                Ok(false)
+            }
+            CollisionAction::Reject => {
+                self.count(glob, "rejected");
+                Err(Error::NamespaceCollision(
+                    attribute.namespace.clone(),
+                    attribute.name.clone(),
+                ))
+            }
+        }
+    }
+
+    fn resolve_attributes(&self, attributes: &mut Vec<generated::Attribute>) -> Result<(), Error> {
+        let mut kept = Vec::with_capacity(attributes.len());
+        for mut attribute in attributes.drain(..) {
+            if self.resolve(&mut attribute)? {
+                kept.push(attribute);
+            }
+        }
+        *attributes = kept;
+        Ok(())
+    }
+
+    fn resolve_frame(&self, frame: &mut generated::VideoFrame) -> Result<(), Error> {
+        self.resolve_attributes(&mut frame.attributes)?;
+        for object in &mut frame.objects {
+            self.resolve_attributes(&mut object.attributes)?;
+        }
+        Ok(())
+    }
+
+    fn resolve_user_data(&self, ud: &mut generated::UserData) -> Result<(), Error> {
+        match unwrap_record(ud) {
+            Some((AUDIO_FRAME_KIND, data)) => {
+                let mut record = AudioFrameRecord::decode(data)?;
+                self.resolve_attributes(&mut record.attributes)?;
+                *ud = wrap_record(&ud.source_id, AUDIO_FRAME_KIND, record.encode_to_vec());
+            }
+            Some((TELEMETRY_FRAME_KIND, data)) => {
+                let mut record = TelemetryFrameRecord::decode(data)?;
+                self.resolve_attributes(&mut record.attributes)?;
+                *ud = wrap_record(&ud.source_id, TELEMETRY_FRAME_KIND, record.encode_to_vec());
+            }
+            _ => self.resolve_attributes(&mut ud.attributes)?,
+        }
+        Ok(())
+    }
+
+    pub fn apply(&self, message: &mut generated::Message) -> Result<(), Error> {
+        message.ingest(self)
+    }
+}
+
+/// The decoded protobuf values carrying attributes, the ingestion policy is applied to them
+/// before they are converted.
+///
+pub trait Ingest {
+    fn ingest(&mut self, policy: &IngestionPolicy) -> Result<(), Error>;
+}
+
+impl Ingest for generated::Message {
+    fn ingest(&mut self, policy: &IngestionPolicy) -> Result<(), Error> {
+        match &mut self.content {
+            Some(generated::message::Content::VideoFrame(frame)) => frame.ingest(policy),
+            Some(generated::message::Content::VideoFrameBatch(batch)) => batch.ingest(policy),
+            Some(generated::message::Content::VideoFrameUpdate(update)) => update.ingest(policy),
+            Some(generated::message::Content::UserData(ud)) => ud.ingest(policy),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Ingest for generated::VideoFrame {
+    fn ingest(&mut self, policy: &IngestionPolicy) -> Result<(), Error> {
+        policy.resolve_frame(self)
+    }
+}
+
+impl Ingest for generated::VideoFrameBatch {
+    fn ingest(&mut self, policy: &IngestionPolicy) -> Result<(), Error> {
+        for frame in self.batch.values_mut() {
+            policy.resolve_frame(frame)?;
+        }
+        Ok(())
+    }
+}
+
+impl Ingest for generated::VideoFrameUpdate {
+    fn ingest(&mut self, policy: &IngestionPolicy) -> Result<(), Error> {
+        policy.resolve_attributes(&mut self.frame_attributes)?;
+        let mut kept = Vec::with_capacity(self.object_attributes.len());
+        for mut oa in self.object_attributes.drain(..) {
+            let keep = match oa.attribute.as_mut() {
+                Some(attribute) => policy.resolve(attribute)?,
+                None => true,
+            };
+            if keep {
+                kept.push(oa);
+            }
+        }
+        self.object_attributes = kept;
+        for object in &mut self.objects {
+            policy.resolve_attributes(&mut object.attributes)?;
+        }
+        Ok(())
+    }
+}
+
+impl Ingest for generated::VideoObject {
+    fn ingest(&mut self, policy: &IngestionPolicy) -> Result<(), Error> {
+        policy.resolve_attributes(&mut self.attributes)
+    }
+}
+
+impl Ingest for generated::UserData {
+    fn ingest(&mut self, policy: &IngestionPolicy) -> Result<(), Error> {
+        policy.resolve_user_data(self)
+    }
+}
+
+impl Ingest for generated::AttributeSet {
+    fn ingest(&mut self, policy: &IngestionPolicy) -> Result<(), Error> {
+        policy.resolve_attributes(&mut self.attributes)
+    }
+}
+
+/// A single attribute cannot be removed, so the dropped one is rejected.
+///
+impl Ingest for generated::Attribute {
+    fn ingest(&mut self, policy: &IngestionPolicy) -> Result<(), Error> {
+        if policy.resolve(self)? {
+            Ok(())
+        } else {
+            Err(Error::NamespaceCollision(
+                self.namespace.clone(),
+                self.name.clone(),
+            ))
+        }
+    }
+}
+
+/// Sets the policy applied to every message deserialized by the process, `None` accepts the
+/// attributes in all namespaces as is.
+///
+pub fn set_ingestion_policy(policy: Option<IngestionPolicy>) {
+    *INGESTION_POLICY.write() = policy.map(Arc::new);
+}
+
+pub fn get_ingestion_policy() -> Option<Arc<IngestionPolicy>> {
+    INGESTION_POLICY.read().clone()
+}
+
+pub(crate) fn apply_ingestion_policy<T: Ingest>(value: &mut T) -> Result<(), Error> {
+    match get_ingestion_policy() {
+        Some(policy) => value.ingest(&policy),
+        None => Ok(()),
+    }
+}
+
+/// Decodes the incoming value and applies the ingestion policy to it.
+///
+pub(crate) fn decode_ingested<T>(bytes: &[u8]) -> Result<T, Error>
+where
+    T: ProstMessage + Default + Ingest,
+{
+    let mut value = T::decode(bytes)?;
+    apply_ingestion_policy(&mut value)?;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::Message;
+    use crate::metrics::get_counter_family;
+    use crate::primitives::frame::VideoFrameProxy;
+    use crate::primitives::{Attribute, WithAttributes};
+    use crate::protobuf::serialize::ingestion::{
+        set_ingestion_policy, CollisionAction, IngestionPolicy,
+    };
+    use crate::protobuf::{deserialize, from_pb, serialize, Error, LazyVideoFrame, ToProtobuf};
+    use crate::test::gen_frame;
+    use savant_protobuf::generated;
+
+    fn ingest(policy: &IngestionPolicy) -> Result<Message, Error> {
+        let frame = gen_frame();
+        let mut message = generated::Message::from(&Message::video_frame(&frame));
+        policy.apply(&mut message)?;
+        Message::try_from(&message)
+    }
+
+    #[test]
+    fn test_ingestion_policy() -> anyhow::Result<()> {
+        let rename = IngestionPolicy::new(
+            &["system*"],
+            CollisionAction::Rename("upstream.".to_string()),
+        )?;
+        let restored = ingest(&rename)?;
+        let frame = restored.as_video_frame().unwrap();
+        assert!(frame.get_attribute("system", "test").is_none());
+        assert!(frame.get_attribute("upstream.system", "test").is_some());
+        assert!(frame.get_attribute("upstream.system2", "test2").is_some());
+        assert!(frame.get_attribute("test", "test").is_some());
+
+        let drop = IngestionPolicy::new(&["system"], CollisionAction::Drop)?;
+        let restored = ingest(&drop)?;
+        let frame = restored.as_video_frame().unwrap();
+        assert!(frame.get_attribute("system", "test").is_none());
+        assert!(frame.get_attribute("system2", "test2").is_some());
+
+        let reject = IngestionPolicy::new(&["system2"], CollisionAction::Reject)?;
+        assert!(matches!(
+            ingest(&reject),
+            Err(Error::NamespaceCollision(ns, name)) if ns == "system2" && name == "test2"
+        ));
+
+        assert!(IngestionPolicy::new(&["system"], CollisionAction::Rename(String::new())).is_err());
+
+        // the collisions are counted by the glob, not by the incoming namespace
+        let counter = get_counter_family("ingested_namespace_collisions").unwrap();
+        assert!(counter.lock().get(&["system", "dropped"])?.is_some());
+        assert!(counter.lock().get(&["system*", "renamed"])?.is_some());
+        assert!(counter.lock().get(&["system2", "renamed"])?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_ingestion_decoders() -> anyhow::Result<()> {
+        let mut frame = gen_frame();
+        frame.set_attribute(Attribute::persistent(
+            "ingestion-test",
+            "local",
+            vec![],
+            &None,
+            false,
+        ));
+        let bytes = frame.to_pb()?;
+        let message = serialize(&Message::video_frame(&frame))?;
+        set_ingestion_policy(Some(IngestionPolicy::new(
+            &["ingestion-test"],
+            CollisionAction::Drop,
+        )?));
+
+        let restored = from_pb::<generated::VideoFrame, VideoFrameProxy>(&bytes);
+        let lazy = LazyVideoFrame::new(bytes);
+        let deserialized = deserialize(&message);
+        set_ingestion_policy(None);
+
+        assert!(restored?.get_attribute("ingestion-test", "local").is_none());
+        let lazy = lazy?;
+        assert!(lazy
+            .get_attributes()?
+            .iter()
+            .all(|a| a.namespace != "ingestion-test"));
+        assert!(lazy
+            .materialize()?
+            .get_attribute("ingestion-test", "local")
+            .is_none());
+        let deserialized = deserialized?;
+        assert!(deserialized
+            .as_video_frame()
+            .unwrap()
+            .get_attribute("ingestion-test", "local")
+            .is_none());
+        Ok(())
+    }
+}
@@ -5,6 +5,9 @@ use crate::primitives::frame_batch::VideoFrameBatch;
 use crate::primitives::frame_update::VideoFrameUpdate;
 use crate::primitives::rust::{Shutdown, UserData};
 use crate::protobuf::serialize;
+use crate::protobuf::serialize::audio_frame::{decode_audio_frame, AUDIO_FRAME_KIND};
+use crate::protobuf::serialize::carrier::unwrap_record;
+use crate::protobuf::serialize::telemetry_frame::{decode_telemetry_frame, TELEMETRY_FRAME_KIND};
 use savant_protobuf::generated;
 
 impl From<&MessageEnvelope> for generated::message::Content {
@@ -24,6 +27,8 @@ impl From<&MessageEnvelope> for generated::message::Content {
                 generated::message::Content::VideoFrameUpdate(vfu.into())
             }
             MessageEnvelope::UserData(ud) => generated::message::Content::UserData(ud.into()),
+            MessageEnvelope::AudioFrame(af) => generated::message::Content::UserData(af.into()),
+            MessageEnvelope::TelemetryFrame(tf) => generated::message::Content::UserData(tf.into()),
             MessageEnvelope::Shutdown(s) => {
                 generated::message::Content::Shutdown(generated::Shutdown {
                     auth: s.get_auth().to_string(),
@@ -55,9 +60,15 @@ impl TryFrom<&generated::message::Content> for MessageEnvelope {
             generated::message::Content::VideoFrameUpdate(vfu) => {
                 MessageEnvelope::VideoFrameUpdate(VideoFrameUpdate::try_from(vfu)?)
             }
-            generated::message::Content::UserData(ud) => {
-                MessageEnvelope::UserData(UserData::try_from(ud)?)
-            }
+            generated::message::Content::UserData(ud) => match unwrap_record(ud) {
+                Some((AUDIO_FRAME_KIND, data)) => {
+                    MessageEnvelope::AudioFrame(decode_audio_frame(data)?)
+                }
+                Some((TELEMETRY_FRAME_KIND, data)) => {
+                    MessageEnvelope::TelemetryFrame(decode_telemetry_frame(data)?)
+                }
+                _ => MessageEnvelope::UserData(UserData::try_from(ud)?),
+            },
             generated::message::Content::Shutdown(s) => {
                 MessageEnvelope::Shutdown(Shutdown::new(&s.auth))
             }
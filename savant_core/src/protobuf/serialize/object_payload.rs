@@ -0,0 +1,73 @@
+use crate::primitives::object_payload::ObjectPayload;
+use crate::protobuf::serialize;
+use crate::protobuf::serialize::carrier::{attribute_record, record_attribute};
+use prost::Message as ProstMessage;
+use savant_protobuf::generated;
+
+pub(crate) const OBJECT_PAYLOAD_KIND: &str = "object_payload";
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct ObjectPayloadRecord {
+    #[prost(string, tag = "1")]
+    pub content_type: String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub data: Vec<u8>,
+}
+
+/// The hidden attribute carrying the payload of an object, `None` without the payload or
+/// when its serialization is not enabled.
+///
+pub(crate) fn object_payload_attribute(
+    payload: Option<&ObjectPayload>,
+) -> Option<generated::Attribute> {
+    let payload = payload.filter(|p| p.is_serialized())?;
+    let record = ObjectPayloadRecord {
+        content_type: payload.get_content_type().to_string(),
+        data: payload.get_data().to_vec(),
+    }
+    .encode_to_vec();
+    Some(record_attribute(OBJECT_PAYLOAD_KIND, record))
+}
+
+/// Decodes the payload if the attribute carries it, the payload stays serialized.
+///
+pub(crate) fn object_payload_from_attribute(
+    attribute: &generated::Attribute,
+) -> Option<Result<ObjectPayload, serialize::Error>> {
+    match attribute_record(attribute) {
+        Some((OBJECT_PAYLOAD_KIND, data)) => Some(
+            ObjectPayloadRecord::decode(data)
+                .map_err(serialize::Error::from)
+                .and_then(|r| {
+                    ObjectPayload::new(&r.content_type, r.data)
+                        .map(|p| p.with_serialization(true))
+                        .map_err(|e| serialize::Error::InvalidObjectPayload(e.to_string()))
+                }),
+        ),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::object::{ObjectOperations, VideoObject};
+    use crate::primitives::object_payload::ObjectPayload;
+    use crate::primitives::WithAttributes;
+    use crate::test::gen_object;
+    use savant_protobuf::generated;
+
+    #[test]
+    fn test_payload_serialization() -> anyhow::Result<()> {
+        let mut obj = gen_object(1);
+        obj.set_payload(Some(ObjectPayload::new("tensor/f32", vec![0; 16])?));
+        let restored = VideoObject::try_from(&generated::VideoObject::from(&obj))?;
+        assert!(restored.get_payload().is_none());
+
+        let payload = ObjectPayload::new("tensor/f32", vec![0; 16])?.with_serialization(true);
+        obj.set_payload(Some(payload.clone()));
+        let restored = VideoObject::try_from(&generated::VideoObject::from(&obj))?;
+        assert_eq!(restored.get_payload(), Some(payload));
+        assert_eq!(restored.get_attributes(), obj.get_attributes());
+        Ok(())
+    }
+}
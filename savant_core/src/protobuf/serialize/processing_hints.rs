@@ -0,0 +1,95 @@
+use crate::primitives::processing_hints::ProcessingHints;
+use crate::primitives::RBBox;
+use crate::protobuf::serialize;
+use crate::protobuf::serialize::carrier::{attribute_record, record_attribute};
+use prost::Message as ProstMessage;
+use savant_protobuf::generated;
+
+pub(crate) const PROCESSING_HINTS_KIND: &str = "processing_hints";
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct ProcessingHintsRecord {
+    #[prost(bool, tag = "1")]
+    pub skip_inference: bool,
+    #[prost(message, repeated, tag = "2")]
+    pub roi_list: Vec<generated::BoundingBox>,
+    #[prost(uint32, tag = "3")]
+    pub degrade_level: u32,
+}
+
+impl From<&ProcessingHints> for ProcessingHintsRecord {
+    fn from(hints: &ProcessingHints) -> Self {
+        ProcessingHintsRecord {
+            skip_inference: hints.skip_inference,
+            roi_list: hints
+                .roi_list
+                .iter()
+                .map(generated::BoundingBox::from)
+                .collect(),
+            degrade_level: hints.degrade_level as u32,
+        }
+    }
+}
+
+impl From<&ProcessingHintsRecord> for ProcessingHints {
+    fn from(value: &ProcessingHintsRecord) -> Self {
+        ProcessingHints {
+            skip_inference: value.skip_inference,
+            roi_list: value.roi_list.iter().map(RBBox::from).collect(),
+            degrade_level: value.degrade_level.min(u8::MAX as u32) as u8,
+        }
+    }
+}
+
+/// The hidden attribute carrying the hints of a frame, `None` for default hints.
+///
+pub(crate) fn processing_hints_attribute(hints: &ProcessingHints) -> Option<generated::Attribute> {
+    if hints.is_default() {
+        return None;
+    }
+    let record = ProcessingHintsRecord::from(hints).encode_to_vec();
+    Some(record_attribute(PROCESSING_HINTS_KIND, record))
+}
+
+/// Decodes the hints if the attribute carries them.
+///
+pub(crate) fn processing_hints_from_attribute(
+    attribute: &generated::Attribute,
+) -> Option<Result<ProcessingHints, serialize::Error>> {
+    match attribute_record(attribute) {
+        Some((PROCESSING_HINTS_KIND, data)) => Some(
+            ProcessingHintsRecord::decode(data)
+                .map(|r| ProcessingHints::from(&r))
+                .map_err(serialize::Error::from),
+        ),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::frame::VideoFrameProxy;
+    use crate::primitives::processing_hints::ProcessingHints;
+    use crate::primitives::{RBBox, WithAttributes};
+    use crate::protobuf::{from_pb, ToProtobuf};
+    use crate::test::gen_frame;
+    use savant_protobuf::generated;
+
+    #[test]
+    fn test_processing_hints_roundtrip() {
+        let mut frame = gen_frame();
+        let attribute_count = frame.get_attributes().len();
+        frame.set_processing_hints(ProcessingHints {
+            skip_inference: false,
+            roi_list: vec![RBBox::ltwh(0.0, 0.0, 64.0, 48.0)],
+            degrade_level: 2,
+        });
+        let bytes = frame.to_pb().unwrap();
+        let restored = from_pb::<generated::VideoFrame, VideoFrameProxy>(&bytes).unwrap();
+        assert_eq!(
+            restored.get_processing_hints(),
+            frame.get_processing_hints()
+        );
+        assert_eq!(restored.get_attributes().len(), attribute_count);
+    }
+}
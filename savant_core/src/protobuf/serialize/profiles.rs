@@ -0,0 +1,380 @@
+use crate::primitives::content_encoding::ContentEncoding;
+use crate::protobuf::serialize::carrier::attribute_record;
+use crate::protobuf::serialize::Error;
+use crate::protobuf::serialize::{content_encoding, representations};
+use globset::{Glob, GlobMatcher};
+use hashbrown::{HashMap, HashSet};
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+use savant_protobuf::generated;
+use std::sync::Arc;
+
+lazy_static! {
+    static ref SERIALIZATION_PROFILES: RwLock<HashMap<String, Arc<SerializationProfile>>> =
+        RwLock::new(HashMap::new());
+}
+
+/// `namespace/name` glob patterns.
+///
+#[derive(Debug, Clone)]
+struct Patterns(Vec<(GlobMatcher, GlobMatcher)>);
+
+impl Patterns {
+    fn new(patterns: &[&str]) -> anyhow::Result<Self> {
+        let mut matchers = Vec::with_capacity(patterns.len());
+        for pattern in patterns {
+            let Some((namespace, name)) = pattern.split_once('/') else {
+                anyhow::bail!("Pattern must have the form namespace/name: {}", pattern)
+            };
+            matchers.push((
+                Glob::new(namespace)?.compile_matcher(),
+                Glob::new(name)?.compile_matcher(),
+            ));
+        }
+        Ok(Self(matchers))
+    }
+
+    fn is_match(&self, namespace: &str, name: &str) -> bool {
+        self.0
+            .iter()
+            .any(|(ns, n)| ns.is_match(namespace) && n.is_match(name))
+    }
+}
+
+/// Included and excluded items, an item is kept when it matches the included patterns (if
+/// they are set) and does not match the excluded ones.
+///
+#[derive(Debug, Clone, Default)]
+struct Selection {
+    include: Option<Patterns>,
+    exclude: Option<Patterns>,
+}
+
+impl Selection {
+    fn new(include: Option<&[&str]>, exclude: &[&str]) -> anyhow::Result<Self> {
+        Ok(Self {
+            include: include.map(Patterns::new).transpose()?,
+            exclude: if exclude.is_empty() {
+                None
+            } else {
+                Some(Patterns::new(exclude)?)
+            },
+        })
+    }
+
+    fn is_selected(&self, namespace: &str, name: &str) -> bool {
+        self.include
+            .as_ref()
+            .is_none_or(|p| p.is_match(namespace, name))
+            && !self
+                .exclude
+                .as_ref()
+                .is_some_and(|p| p.is_match(namespace, name))
+    }
+}
+
+/// A named set of the parts of the messages a sink receives, so a metrics sink gets small
+/// messages while a recorder gets the full fidelity without stripping the messages in every
+/// sink. The objects are selected by the `namespace/label` glob patterns, the attributes of
+/// the frames, the objects and the user data by the `namespace/name` ones. The children of
+/// the removed objects lose the parent. The reserved hidden attributes (e.g. the processing
+/// hints) and the attributes of the audio and telemetry frames are kept. The profile is
+/// applied after the export redaction.
+///
+/// The profiles are registered with [`register_serialization_profile`] and selected by the
+/// name with [`crate::protobuf::serialize_with_profile`] or in the writer configuration.
+///
+#[derive(Debug, Clone)]
+pub struct SerializationProfile {
+    name: String,
+    objects: Selection,
+    attributes: Selection,
+    representations: Option<Vec<String>>,
+    content_encoding: Option<ContentEncoding>,
+}
+
+impl SerializationProfile {
+    /// The profile keeping everything.
+    ///
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            objects: Selection::default(),
+            attributes: Selection::default(),
+            representations: None,
+            content_encoding: None,
+        }
+    }
+
+    /// Selects the objects by the `namespace/label` patterns, `include` set to an empty list
+    /// removes all the objects.
+    ///
+    pub fn with_objects(
+        mut self,
+        include: Option<&[&str]>,
+        exclude: &[&str],
+    ) -> anyhow::Result<Self> {
+        self.objects = Selection::new(include, exclude)?;
+        Ok(self)
+    }
+
+    /// Selects the attributes by the `namespace/name` patterns.
+    ///
+    pub fn with_attributes(
+        mut self,
+        include: Option<&[&str]>,
+        exclude: &[&str],
+    ) -> anyhow::Result<Self> {
+        self.attributes = Selection::new(include, exclude)?;
+        Ok(self)
+    }
+
+    /// Keeps only the listed content representations, the primary content is always kept.
+    ///
+    pub fn with_representations(mut self, representations: Option<Vec<String>>) -> Self {
+        self.representations = representations;
+        self
+    }
+
+    /// Compresses the uncompressed internal content of the frames.
+    ///
+    pub fn with_content_encoding(mut self, content_encoding: Option<ContentEncoding>) -> Self {
+        self.content_encoding = content_encoding;
+        self
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn select_attributes(&self, attributes: &mut Vec<generated::Attribute>) {
+        attributes.retain(|a| {
+            attribute_record(a).is_some() || self.attributes.is_selected(&a.namespace, &a.name)
+        });
+    }
+
+    fn select_objects(&self, objects: &mut Vec<generated::VideoObject>) {
+        let mut removed = HashSet::new();
+        objects.retain(|o| {
+            let selected = self.objects.is_selected(&o.namespace, &o.label);
+            if !selected {
+                removed.insert(o.id);
+            }
+            selected
+        });
+        for object in objects.iter_mut() {
+            if object.parent_id.is_some_and(|id| removed.contains(&id)) {
+                object.parent_id = None;
+            }
+            self.select_attributes(&mut object.attributes);
+        }
+    }
+
+    fn select_frame(&self, frame: &mut generated::VideoFrame) {
+        self.select_attributes(&mut frame.attributes);
+        self.select_objects(&mut frame.objects);
+    }
+
+    /// Removes the parts of the message not selected by the profile and compresses the
+    /// content.
+    ///
+    pub fn apply(&self, message: &mut generated::Message) -> Result<(), Error> {
+        match &mut message.content {
+            Some(generated::message::Content::VideoFrame(frame)) => self.select_frame(frame),
+            Some(generated::message::Content::VideoFrameBatch(batch)) => {
+                for frame in batch.batch.values_mut() {
+                    self.select_frame(frame);
+                }
+            }
+            Some(generated::message::Content::VideoFrameUpdate(update)) => {
+                self.select_attributes(&mut update.frame_attributes);
+                let mut removed = HashSet::new();
+                update.objects.retain(|o| match &o.object {
+                    Some(object) => {
+                        let selected = self.objects.is_selected(&object.namespace, &object.label);
+                        if !selected {
+                            removed.insert(object.id);
+                        }
+                        selected
+                    }
+                    None => true,
+                });
+                for object in update.objects.iter_mut() {
+                    if object.parent_id.is_some_and(|id| removed.contains(&id)) {
+                        object.parent_id = None;
+                    }
+                    if let Some(object) = object.object.as_mut() {
+                        self.select_attributes(&mut object.attributes);
+                    }
+                }
+                update.object_attributes.retain(|oa| {
+                    !removed.contains(&oa.object_id)
+                        && oa
+                            .attribute
+                            .as_ref()
+                            .map(|a| {
+                                attribute_record(a).is_some()
+                                    || self.attributes.is_selected(&a.namespace, &a.name)
+                            })
+                            .unwrap_or(true)
+                });
+            }
+            Some(generated::message::Content::UserData(ud)) => {
+                self.select_attributes(&mut ud.attributes)
+            }
+            _ => {}
+        }
+        if let Some(representations) = &self.representations {
+            representations::select_representations(message, representations)?;
+        }
+        if let Some(encoding) = self.content_encoding {
+            content_encoding::compress_content(message, encoding)?;
+        }
+        Ok(())
+    }
+}
+
+/// Registers the profile under its name, replacing the profile with the same name.
+///
+pub fn register_serialization_profile(profile: SerializationProfile) {
+    SERIALIZATION_PROFILES
+        .write()
+        .insert(profile.name.clone(), Arc::new(profile));
+}
+
+pub fn unregister_serialization_profile(name: &str) -> bool {
+    SERIALIZATION_PROFILES.write().remove(name).is_some()
+}
+
+pub fn get_serialization_profile(name: &str) -> Option<Arc<SerializationProfile>> {
+    SERIALIZATION_PROFILES.read().get(name).cloned()
+}
+
+/// The names of the registered profiles, sorted.
+///
+pub fn get_serialization_profiles() -> Vec<String> {
+    let mut names = SERIALIZATION_PROFILES
+        .read()
+        .keys()
+        .cloned()
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::Message;
+    use crate::primitives::object::ObjectOperations;
+    use crate::primitives::WithAttributes;
+    use crate::protobuf::serialize::profiles::{
+        get_serialization_profiles, register_serialization_profile,
+        unregister_serialization_profile, SerializationProfile,
+    };
+    use crate::protobuf::serialize::Error;
+    use crate::protobuf::{deserialize, serialize, serialize_with_profile};
+    use crate::test::gen_frame;
+
+    #[test]
+    fn test_serialization_profile() -> anyhow::Result<()> {
+        assert!(SerializationProfile::new("bad")
+            .with_objects(None, &["no_separator"])
+            .is_err());
+        let frame = gen_frame();
+        frame.set_persistent_attribute("metrics", "fps", &None, false, vec![]);
+        frame.set_persistent_attribute("reid", "embedding", &None, false, vec![]);
+        let message = Message::video_frame(&frame);
+
+        register_serialization_profile(
+            SerializationProfile::new("profile-test-metrics")
+                .with_objects(Some(&[]), &[])?
+                .with_attributes(Some(&["metrics/*"]), &[])?,
+        );
+        register_serialization_profile(
+            SerializationProfile::new("profile-test-recorder")
+                .with_objects(None, &["test/*"])?
+                .with_attributes(None, &["reid/*"])?,
+        );
+        assert!(get_serialization_profiles().contains(&"profile-test-metrics".to_string()));
+
+        let metrics = deserialize(&serialize_with_profile(&message, "profile-test-metrics")?)?;
+        let metrics = metrics.as_video_frame().unwrap();
+        assert!(metrics.get_all_objects().is_empty());
+        assert!(metrics.get_attribute("metrics", "fps").is_some());
+        assert!(metrics.get_attribute("reid", "embedding").is_none());
+
+        let recorder = deserialize(&serialize_with_profile(&message, "profile-test-recorder")?)?;
+        let recorder = recorder.as_video_frame().unwrap();
+        let objects = recorder.get_all_objects();
+        // the children of the removed parent are kept without it
+        assert_eq!(objects.len(), 2);
+        assert!(objects.iter().all(|o| o.get_parent_id().is_none()));
+        assert!(recorder.get_attribute("metrics", "fps").is_some());
+        assert!(recorder.get_attribute("reid", "embedding").is_none());
+
+        // the message itself is untouched
+        let full = deserialize(&serialize(&message)?)?;
+        assert_eq!(
+            full.as_video_frame().unwrap().get_all_objects().len(),
+            frame.get_all_objects().len()
+        );
+
+        assert!(unregister_serialization_profile("profile-test-metrics"));
+        assert!(unregister_serialization_profile("profile-test-recorder"));
+        assert!(matches!(
+            serialize_with_profile(&message, "profile-test-metrics"),
+            Err(Error::UnknownSerializationProfile(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_profile() -> anyhow::Result<()> {
+        use crate::primitives::frame_update::VideoFrameUpdate;
+        use crate::protobuf::serialize::carrier::{attribute_record, record_attribute};
+        use crate::test::gen_object;
+        use savant_protobuf::generated;
+
+        let mut update = VideoFrameUpdate::default();
+        let mut other = gen_object(1);
+        other.namespace = "other".to_string();
+        update.add_object(other, None);
+        update.add_object(gen_object(2), Some(1));
+        update.add_object(gen_object(3), Some(4));
+        for id in [1, 2] {
+            let attribute = gen_object(id).get_attribute("some", "attribute").unwrap();
+            update.add_object_attribute(id, attribute);
+        }
+        let mut message = generated::Message::from(&Message::video_frame_update(update));
+        let Some(generated::message::Content::VideoFrameUpdate(u)) = &mut message.content else {
+            unreachable!()
+        };
+        u.object_attributes.push(generated::ObjectAttribute {
+            object_id: 2,
+            attribute: Some(record_attribute("hint", vec![1])),
+        });
+
+        SerializationProfile::new("profile-test-update")
+            .with_objects(None, &["other/*"])?
+            .with_attributes(Some(&["some/*"]), &[])?
+            .apply(&mut message)?;
+        let Some(generated::message::Content::VideoFrameUpdate(u)) = &message.content else {
+            unreachable!()
+        };
+        assert_eq!(
+            u.objects
+                .iter()
+                .map(|o| (o.object.as_ref().unwrap().id, o.parent_id))
+                .collect::<Vec<_>>(),
+            vec![(2, None), (3, Some(4))]
+        );
+        // the attributes of the removed objects are dropped, the carrier records are kept
+        assert!(u.object_attributes.iter().all(|oa| oa.object_id == 2));
+        assert_eq!(u.object_attributes.len(), 2);
+        assert!(u
+            .object_attributes
+            .iter()
+            .any(|oa| attribute_record(oa.attribute.as_ref().unwrap()).is_some()));
+        Ok(())
+    }
+}
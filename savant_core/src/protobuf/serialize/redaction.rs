@@ -0,0 +1,256 @@
+use crate::protobuf::serialize::audio_frame::{AudioFrameRecord, AUDIO_FRAME_KIND};
+use crate::protobuf::serialize::carrier::{attribute_record, unwrap_record, wrap_record};
+use crate::protobuf::serialize::classification::{get_classification_policy, ClassificationPolicy};
+use crate::protobuf::serialize::telemetry_frame::{TelemetryFrameRecord, TELEMETRY_FRAME_KIND};
+use crate::protobuf::serialize::Error;
+use globset::{Glob, GlobMatcher};
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+use prost::Message as ProstMessage;
+use savant_protobuf::generated;
+use std::sync::Arc;
+
+lazy_static! {
+    static ref EXPORT_FILTER: RwLock<Option<Arc<RedactionFilter>>> = RwLock::new(None);
+}
+
+/// Strips attributes matching any of the `namespace/name` glob patterns (e.g.
+/// `*/face_embedding`, `lpr/*`) from messages. The reserved hidden attributes carrying the
+/// frame processing hints and the audio and telemetry frames are never stripped.
+///
+#[derive(Debug, Clone)]
+pub struct RedactionFilter {
+    patterns: Vec<String>,
+    matchers: Vec<(GlobMatcher, GlobMatcher)>,
+}
+
+impl RedactionFilter {
+    pub fn new(patterns: &[&str]) -> anyhow::Result<Self> {
+        let mut matchers = Vec::with_capacity(patterns.len());
+        for pattern in patterns {
+            let Some((namespace, name)) = pattern.split_once('/') else {
+                anyhow::bail!(
+                    "Redaction pattern must have the form namespace/name: {}",
+                    pattern
+                )
+            };
+            matchers.push((
+                Glob::new(namespace)?.compile_matcher(),
+                Glob::new(name)?.compile_matcher(),
+            ));
+        }
+        Ok(Self {
+            patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            matchers,
+        })
+    }
+
+    pub fn get_patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    pub fn is_redacted(&self, namespace: &str, name: &str) -> bool {
+        self.matchers
+            .iter()
+            .any(|(ns, n)| ns.is_match(namespace) && n.is_match(name))
+    }
+
+    fn redact_attributes(&self, attributes: &mut Vec<generated::Attribute>) {
+        attributes
+            .retain(|a| attribute_record(a).is_some() || !self.is_redacted(&a.namespace, &a.name));
+    }
+
+    pub(crate) fn redact_frame(&self, frame: &mut generated::VideoFrame) {
+        self.redact_attributes(&mut frame.attributes);
+        for object in &mut frame.objects {
+            self.redact_attributes(&mut object.attributes);
+        }
+    }
+
+    fn redact_user_data(&self, ud: &mut generated::UserData) -> Result<(), Error> {
+        match unwrap_record(ud) {
+            Some((AUDIO_FRAME_KIND, data)) => {
+                let mut record = AudioFrameRecord::decode(data)?;
+                self.redact_attributes(&mut record.attributes);
+                *ud = wrap_record(&ud.source_id, AUDIO_FRAME_KIND, record.encode_to_vec());
+            }
+            Some((TELEMETRY_FRAME_KIND, data)) => {
+                let mut record = TelemetryFrameRecord::decode(data)?;
+                self.redact_attributes(&mut record.attributes);
+                *ud = wrap_record(&ud.source_id, TELEMETRY_FRAME_KIND, record.encode_to_vec());
+            }
+            _ => self.redact_attributes(&mut ud.attributes),
+        }
+        Ok(())
+    }
+
+    pub fn redact_message(&self, message: &mut generated::Message) -> Result<(), Error> {
+        match &mut message.content {
+            Some(generated::message::Content::VideoFrame(frame)) => self.redact_frame(frame),
+            Some(generated::message::Content::VideoFrameBatch(batch)) => {
+                for frame in batch.batch.values_mut() {
+                    self.redact_frame(frame);
+                }
+            }
+            Some(generated::message::Content::VideoFrameUpdate(update)) => {
+                self.redact_attributes(&mut update.frame_attributes);
+                update.object_attributes.retain(|oa| {
+                    oa.attribute
+                        .as_ref()
+                        .map(|a| {
+                            attribute_record(a).is_some()
+                                || !self.is_redacted(&a.namespace, &a.name)
+                        })
+                        .unwrap_or(true)
+                });
+                for object in &mut update.objects {
+                    self.redact_attributes(&mut object.attributes);
+                }
+            }
+            Some(generated::message::Content::UserData(ud)) => self.redact_user_data(ud)?,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Sets the filter applied to every message leaving the process through serialization and
+/// message journals, `None` disables redaction.
+///
+pub fn set_export_filter(filter: Option<RedactionFilter>) {
+    *EXPORT_FILTER.write() = filter.map(Arc::new);
+}
+
+pub fn get_export_filter() -> Option<Arc<RedactionFilter>> {
+    EXPORT_FILTER.read().clone()
+}
+
+/// Applies the classification policy and the export filter to the message converted for
+/// sending.
+///
+pub(crate) fn redact_for_export(message: &mut generated::Message) -> Result<(), Error> {
+    redact_with_policy(message, get_classification_policy().as_ref())
+}
+
+/// Applies the classification policy of a sink instead of the process-wide one and the export
+/// filter.
+///
+pub(crate) fn redact_with_policy(
+    message: &mut generated::Message,
+    policy: Option<&ClassificationPolicy>,
+) -> Result<(), Error> {
+    if let Some(policy) = policy {
+        policy.enforce_message(message)?;
+    }
+    match get_export_filter() {
+        Some(filter) => filter.redact_message(message),
+        None => Ok(()),
+    }
+}
+
+pub(crate) fn redact_frame_for_export(frame: &mut generated::VideoFrame) -> Result<(), Error> {
+    if let Some(policy) = get_classification_policy() {
+        policy.enforce_frame(frame)?;
+    }
+    if let Some(filter) = get_export_filter() {
+        filter.redact_frame(frame);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::Message;
+    use crate::primitives::attribute::WithAttributes;
+    use crate::primitives::attribute_value::AttributeValue;
+    use crate::primitives::frame_update::VideoFrameUpdate;
+    use crate::primitives::telemetry_frame::TelemetryFrame;
+    use crate::primitives::Attribute;
+    use crate::protobuf::serialize::carrier::{attribute_record, record_attribute};
+    use crate::protobuf::serialize::redaction::{
+        get_export_filter, set_export_filter, RedactionFilter,
+    };
+    use crate::protobuf::{deserialize, serialize};
+    use crate::test::gen_frame;
+    use savant_protobuf::generated;
+
+    #[test]
+    fn test_redact_frame() -> anyhow::Result<()> {
+        assert!(RedactionFilter::new(&["no_separator"]).is_err());
+        let filter = RedactionFilter::new(&["*/face_embedding", "lpr/*"])?;
+        let mut frame = gen_frame();
+        frame.set_persistent_attribute("reid", "face_embedding", &None, false, vec![]);
+        frame.set_persistent_attribute("lpr", "plate", &None, false, vec![]);
+        frame.set_persistent_attribute("reid", "body_embedding", &None, false, vec![]);
+        let mut object = frame.get_object(0).unwrap();
+        object.set_persistent_attribute("lpr", "plate", &None, false, vec![]);
+
+        let mut message = generated::Message::from(&Message::video_frame(&frame));
+        filter.redact_message(&mut message)?;
+        let restored = Message::try_from(&message)?;
+        let restored = restored.as_video_frame().unwrap();
+        assert!(restored.get_attribute("reid", "body_embedding").is_some());
+        assert!(restored.get_attribute("reid", "face_embedding").is_none());
+        assert!(restored.get_attribute("lpr", "plate").is_none());
+        assert!(restored
+            .get_object(0)
+            .unwrap()
+            .get_attribute("lpr", "plate")
+            .is_none());
+        // the source frame is left intact
+        assert!(frame.get_attribute("lpr", "plate").is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_redact_update_keeps_carrier_records() -> anyhow::Result<()> {
+        let mut update = VideoFrameUpdate::default();
+        update.add_object_attribute(
+            1,
+            Attribute::persistent("lpr", "plate", vec![], &None, false),
+        );
+        let mut message = generated::Message::from(&Message::video_frame_update(update));
+        let Some(generated::message::Content::VideoFrameUpdate(u)) = &mut message.content else {
+            unreachable!()
+        };
+        u.object_attributes.push(generated::ObjectAttribute {
+            object_id: 1,
+            attribute: Some(record_attribute("hint", vec![1])),
+        });
+
+        // the pattern matches the carrier attribute too
+        RedactionFilter::new(&["*/*"])?.redact_message(&mut message)?;
+        let Some(generated::message::Content::VideoFrameUpdate(u)) = &message.content else {
+            unreachable!()
+        };
+        assert_eq!(u.object_attributes.len(), 1);
+        assert!(attribute_record(u.object_attributes[0].attribute.as_ref().unwrap()).is_some());
+        Ok(())
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_export_filter() -> anyhow::Result<()> {
+        let mut telemetry = TelemetryFrame::new("lidar", "front", 0);
+        telemetry.set_attribute(Attribute::persistent(
+            "redaction_test",
+            "secret",
+            vec![AttributeValue::integer(1, None)],
+            &None,
+            false,
+        ));
+        let message = Message::telemetry_frame(telemetry);
+
+        set_export_filter(Some(RedactionFilter::new(&["redaction_test/*"])?));
+        assert!(get_export_filter().is_some());
+        let redacted = deserialize(&serialize(&message)?)?;
+        set_export_filter(None);
+        let restored = deserialize(&serialize(&message)?)?;
+
+        let redacted = redacted.as_telemetry_frame().unwrap();
+        assert!(redacted.get_attribute("redaction_test", "secret").is_none());
+        let restored = restored.as_telemetry_frame().unwrap();
+        assert!(restored.get_attribute("redaction_test", "secret").is_some());
+        Ok(())
+    }
+}
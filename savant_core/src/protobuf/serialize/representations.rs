@@ -0,0 +1,277 @@
+use crate::primitives::frame::{ExternalFrame, VideoFrameContent};
+use crate::primitives::raw_content::InternalFrame;
+use crate::primitives::representation::ContentRepresentation;
+use crate::protobuf::serialize;
+use crate::protobuf::serialize::carrier::{attribute_record, record_attribute};
+use crate::protobuf::serialize::content_encoding::{wire_compressed, ContentEncodingRecord};
+use prost::Message as ProstMessage;
+use savant_protobuf::generated;
+use std::collections::BTreeMap;
+
+pub(crate) const REPRESENTATIONS_KIND: &str = "representations";
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct ContentRepresentationRecord {
+    #[prost(string, tag = "1")]
+    pub key: String,
+    #[prost(string, optional, tag = "2")]
+    pub codec: Option<String>,
+    #[prost(int64, tag = "3")]
+    pub width: i64,
+    #[prost(int64, tag = "4")]
+    pub height: i64,
+    #[prost(bytes = "vec", optional, tag = "5")]
+    pub internal: Option<Vec<u8>>,
+    #[prost(message, optional, tag = "6")]
+    pub external: Option<generated::ExternalFrame>,
+    #[prost(message, optional, tag = "7")]
+    pub encoding: Option<ContentEncodingRecord>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct ContentRepresentationsRecord {
+    #[prost(message, repeated, tag = "1")]
+    pub representations: Vec<ContentRepresentationRecord>,
+}
+
+impl ContentRepresentationRecord {
+    fn new(key: &str, representation: &ContentRepresentation) -> Self {
+        let (internal, external, encoding) = match representation.content.as_ref() {
+            // the raw layout is not serialized, the content is always packed
+            VideoFrameContent::Internal(content) => match wire_compressed(content) {
+                Some((encoding, compressed)) => (
+                    Some(compressed.to_vec()),
+                    None,
+                    Some(ContentEncodingRecord::new(encoding, content.len())),
+                ),
+                None => (Some(content.to_packed()), None, None),
+            },
+            VideoFrameContent::External(e) => (
+                None,
+                Some(generated::ExternalFrame {
+                    method: e.method.clone(),
+                    location: e.location.clone(),
+                }),
+                None,
+            ),
+            VideoFrameContent::None => (None, None, None),
+        };
+        Self {
+            key: key.to_string(),
+            codec: representation.codec.clone(),
+            width: representation.width,
+            height: representation.height,
+            internal,
+            external,
+            encoding,
+        }
+    }
+
+    fn to_representation(&self) -> Result<ContentRepresentation, serialize::Error> {
+        let encoding = self
+            .encoding
+            .as_ref()
+            .map(|e| e.decode_encoding())
+            .transpose()?;
+        let content = match (&self.internal, &self.external) {
+            (Some(data), _) => VideoFrameContent::Internal(match encoding {
+                Some((encoding, size)) => InternalFrame::compressed_from_codec(
+                    data.clone(),
+                    encoding,
+                    size,
+                    self.codec.as_deref(),
+                    self.width,
+                    self.height,
+                ),
+                None => InternalFrame::from_codec(
+                    data.clone(),
+                    self.codec.as_deref(),
+                    self.width,
+                    self.height,
+                ),
+            }),
+            (None, Some(e)) => VideoFrameContent::External(ExternalFrame {
+                method: e.method.clone(),
+                location: e.location.clone(),
+            }),
+            (None, None) => VideoFrameContent::None,
+        };
+        Ok(ContentRepresentation::new(
+            content,
+            self.width,
+            self.height,
+            self.codec.clone(),
+        ))
+    }
+}
+
+/// The hidden attribute carrying the content representations of a frame, `None` when the
+/// frame has none.
+///
+pub(crate) fn representations_attribute(
+    representations: &BTreeMap<String, ContentRepresentation>,
+) -> Option<generated::Attribute> {
+    if representations.is_empty() {
+        return None;
+    }
+    let record = ContentRepresentationsRecord {
+        representations: representations
+            .iter()
+            .map(|(key, r)| ContentRepresentationRecord::new(key, r))
+            .collect(),
+    };
+    Some(record_attribute(
+        REPRESENTATIONS_KIND,
+        record.encode_to_vec(),
+    ))
+}
+
+/// Decodes the representations if the attribute carries them.
+///
+pub(crate) fn representations_from_attribute(
+    attribute: &generated::Attribute,
+) -> Option<Result<BTreeMap<String, ContentRepresentation>, serialize::Error>> {
+    match attribute_record(attribute) {
+        Some((REPRESENTATIONS_KIND, data)) => Some(
+            ContentRepresentationsRecord::decode(data)
+                .map_err(serialize::Error::from)
+                .and_then(|record| {
+                    record
+                        .representations
+                        .iter()
+                        .map(|r| Ok((r.key.clone(), r.to_representation()?)))
+                        .collect()
+                }),
+        ),
+        _ => None,
+    }
+}
+
+fn select_frame_representations(
+    frame: &mut generated::VideoFrame,
+    keys: &[String],
+) -> Result<(), serialize::Error> {
+    let Some((position, data)) = frame
+        .attributes
+        .iter()
+        .enumerate()
+        .find_map(|(position, a)| match attribute_record(a) {
+            Some((REPRESENTATIONS_KIND, data)) => Some((position, data)),
+            _ => None,
+        })
+    else {
+        return Ok(());
+    };
+    let mut record = ContentRepresentationsRecord::decode(data)?;
+    record.representations.retain(|r| keys.contains(&r.key));
+    if record.representations.is_empty() {
+        frame.attributes.remove(position);
+    } else {
+        frame.attributes[position] = record_attribute(REPRESENTATIONS_KIND, record.encode_to_vec());
+    }
+    Ok(())
+}
+
+/// Drops the content representations of the frames in the message converted for sending
+/// which are not listed, the primary content is always kept.
+///
+pub(crate) fn select_representations(
+    message: &mut generated::Message,
+    keys: &[String],
+) -> Result<(), serialize::Error> {
+    match &mut message.content {
+        Some(generated::message::Content::VideoFrame(frame)) => {
+            select_frame_representations(frame, keys)
+        }
+        Some(generated::message::Content::VideoFrameBatch(batch)) => {
+            for frame in batch.batch.values_mut() {
+                select_frame_representations(frame, keys)?;
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::Message;
+    use crate::primitives::frame::{ExternalFrame, VideoFrameContent};
+    use crate::primitives::raw_content::{InternalFrame, PixelFormat, RawLayout};
+    use crate::primitives::representation::ContentRepresentation;
+    use crate::primitives::WithAttributes;
+    use crate::protobuf::{deserialize, serialize, serialize_with_representations};
+    use crate::test::gen_frame;
+
+    #[test]
+    fn test_representations_roundtrip() -> anyhow::Result<()> {
+        let mut frame = gen_frame();
+        let thumbnail = InternalFrame::raw(
+            vec![7; 4 * 2 * 3],
+            RawLayout::packed(PixelFormat::Rgb8, 4, 2)?,
+        )?;
+        frame.set_representation(
+            "thumbnail",
+            ContentRepresentation::new(
+                VideoFrameContent::Internal(thumbnail),
+                4,
+                2,
+                Some(PixelFormat::Rgb8.codec()),
+            ),
+        );
+        frame.set_representation(
+            "full",
+            ContentRepresentation::new(
+                VideoFrameContent::External(ExternalFrame::new("s3", &Some("bucket/key"))),
+                1920,
+                1080,
+                Some("h264".to_string()),
+            ),
+        );
+        let attributes = frame.get_attributes();
+
+        let restored = deserialize(&serialize(&Message::video_frame(&frame))?)?;
+        let restored = restored.as_video_frame().unwrap();
+        assert_eq!(
+            restored.get_representation_keys(),
+            vec!["full", "thumbnail"]
+        );
+        assert_eq!(
+            restored.get_representation("full"),
+            frame.get_representation("full")
+        );
+        let thumbnail = restored.get_representation("thumbnail").unwrap();
+        assert_eq!((thumbnail.width, thumbnail.height), (4, 2));
+        match thumbnail.content.as_ref() {
+            VideoFrameContent::Internal(content) => {
+                assert_eq!(content.get_data().ok(), Some(&[7; 24][..]));
+                assert_eq!(
+                    content.get_layout().map(|l| l.get_format()),
+                    Some(PixelFormat::Rgb8)
+                );
+            }
+            _ => panic!("Internal content expected"),
+        }
+        assert_eq!(restored.get_attributes(), attributes);
+
+        let selected = deserialize(&serialize_with_representations(
+            &Message::video_frame(&frame),
+            &["thumbnail".to_string()],
+        )?)?;
+        assert_eq!(
+            selected.as_video_frame().unwrap().get_representation_keys(),
+            vec!["thumbnail"]
+        );
+        let selected = deserialize(&serialize_with_representations(
+            &Message::video_frame(&frame),
+            &[],
+        )?)?;
+        assert!(selected
+            .as_video_frame()
+            .unwrap()
+            .get_representation_keys()
+            .is_empty());
+        assert_eq!(frame.get_representation_keys().len(), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use crate::primitives::telemetry_frame::TelemetryFrame;
+use crate::primitives::Attribute;
+use crate::protobuf::serialize;
+use crate::protobuf::serialize::carrier::wrap_record;
+use prost::Message as ProstMessage;
+use savant_protobuf::generated;
+use std::str::FromStr;
+use uuid::Uuid;
+
+pub(crate) const TELEMETRY_FRAME_KIND: &str = "telemetry_frame";
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct TelemetryFrameRecord {
+    #[prost(string, tag = "1")]
+    pub source_id: String,
+    #[prost(string, tag = "2")]
+    pub sensor: String,
+    #[prost(string, tag = "3")]
+    pub uuid: String,
+    #[prost(int64, tag = "4")]
+    pub pts: i64,
+    #[prost(int32, tag = "5")]
+    pub time_base_numerator: i32,
+    #[prost(int32, tag = "6")]
+    pub time_base_denominator: i32,
+    #[prost(message, repeated, tag = "7")]
+    pub attributes: Vec<generated::Attribute>,
+}
+
+impl From<&TelemetryFrame> for TelemetryFrameRecord {
+    fn from(frame: &TelemetryFrame) -> Self {
+        TelemetryFrameRecord {
+            source_id: frame.source_id.clone(),
+            sensor: frame.sensor.clone(),
+            uuid: Uuid::from_u128(frame.uuid).to_string(),
+            pts: frame.pts,
+            time_base_numerator: frame.time_base.0,
+            time_base_denominator: frame.time_base.1,
+            attributes: frame
+                .attributes
+                .iter()
+                .filter(|a| a.is_persistent)
+                .map(generated::Attribute::from)
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<&TelemetryFrameRecord> for TelemetryFrame {
+    type Error = serialize::Error;
+
+    fn try_from(value: &TelemetryFrameRecord) -> Result<Self, Self::Error> {
+        Ok(TelemetryFrame {
+            source_id: value.source_id.clone(),
+            sensor: value.sensor.clone(),
+            uuid: Uuid::from_str(&value.uuid)?.as_u128(),
+            pts: value.pts,
+            time_base: (value.time_base_numerator, value.time_base_denominator),
+            attributes: value
+                .attributes
+                .iter()
+                .map(Attribute::try_from)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl From<&TelemetryFrame> for generated::UserData {
+    fn from(frame: &TelemetryFrame) -> Self {
+        let record = TelemetryFrameRecord::from(frame).encode_to_vec();
+        wrap_record(&frame.source_id, TELEMETRY_FRAME_KIND, record)
+    }
+}
+
+pub(crate) fn decode_telemetry_frame(data: &[u8]) -> Result<TelemetryFrame, serialize::Error> {
+    TelemetryFrame::try_from(&TelemetryFrameRecord::decode(data)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::Message;
+    use crate::primitives::attribute_value::AttributeValue;
+    use crate::primitives::telemetry_frame::TelemetryFrame;
+    use crate::primitives::{Attribute, WithAttributes};
+    use crate::protobuf::{deserialize, serialize};
+
+    #[test]
+    fn test_telemetry_frame_message() {
+        let mut frame = TelemetryFrame::new("gps-1", "gps", 1000);
+        frame.set_attribute(Attribute::persistent(
+            "gps",
+            "position",
+            vec![
+                AttributeValue::float(52.52, None),
+                AttributeValue::float(13.40, None),
+            ],
+            &None,
+            false,
+        ));
+        let m = Message::telemetry_frame(frame.clone());
+        let restored = deserialize(&serialize(&m).unwrap()).unwrap();
+        assert!(restored.is_telemetry_frame());
+        assert_eq!(restored.as_telemetry_frame().unwrap(), &frame);
+    }
+}
@@ -1,13 +1,39 @@
+use crate::primitives::classification::DataClassification;
 use crate::primitives::frame::{
     VideoFrame, VideoFrameContent, VideoFrameProxy, VideoFrameTranscodingMethod,
     VideoFrameTransformation,
 };
 use crate::primitives::object::VideoObject;
+use crate::primitives::processing_hints::ProcessingHints;
+use crate::primitives::raw_content::InternalFrame;
 use crate::primitives::Attribute;
+use crate::protobuf::serialize::attribute_expiry::{
+    apply_attribute_expiry, attribute_expiry_attribute, attribute_expiry_from_attribute,
+};
+use crate::protobuf::serialize::attribute_units::{
+    apply_attribute_units, attribute_units_attribute, attribute_units_from_attribute,
+};
+use crate::protobuf::serialize::classification::{
+    classification_attribute, classification_from_attribute,
+};
+use crate::protobuf::serialize::content_encoding::{
+    content_encoding_attribute, content_encoding_from_attribute,
+};
+use crate::protobuf::serialize::frozen_objects::{
+    frozen_objects_attribute, frozen_objects_from_attribute,
+};
+use crate::protobuf::serialize::geo::{geo_position_attribute, geo_position_from_attribute};
+use crate::protobuf::serialize::processing_hints::{
+    processing_hints_attribute, processing_hints_from_attribute,
+};
+use crate::protobuf::serialize::representations::{
+    representations_attribute, representations_from_attribute,
+};
 use crate::protobuf::serialize::Error;
 use hashbrown::{HashMap, HashSet};
 use prost::UnknownEnumValue;
 use savant_protobuf::generated;
+use std::collections::BTreeMap;
 use std::str::FromStr;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -54,8 +80,16 @@ impl From<&Box<VideoFrame>> for generated::VideoFrame {
             attributes: video_frame
                 .attributes
                 .iter()
-                .filter(|a| a.is_persistent)
+                .filter(|a| a.is_persistent && !a.is_expired())
                 .map(|a| a.into())
+                .chain(processing_hints_attribute(&video_frame.processing_hints))
+                .chain(geo_position_attribute(video_frame.geo_pose.as_ref()))
+                .chain(classification_attribute(video_frame.classification))
+                .chain(attribute_expiry_attribute(video_frame))
+                .chain(attribute_units_attribute(video_frame))
+                .chain(representations_attribute(&video_frame.representations))
+                .chain(content_encoding_attribute(&video_frame.content))
+                .chain(frozen_objects_attribute(&video_frame.frozen_objects))
                 .collect(),
             objects,
             content: Some((&*video_frame.content).into()),
@@ -78,17 +112,44 @@ impl TryFrom<&generated::VideoFrame> for VideoFrame {
             .map(VideoFrameTransformation::try_from)
             .collect::<Result<Vec<VideoFrameTransformation>, _>>()?;
 
-        let attributes = value
-            .attributes
-            .iter()
-            .map(Attribute::try_from)
-            .collect::<Result<Vec<Attribute>, _>>()?;
+        let mut processing_hints = ProcessingHints::default();
+        let mut geo_pose = None;
+        let mut classification = DataClassification::default();
+        let mut representations = BTreeMap::new();
+        let mut expiries = Vec::new();
+        let mut units = Vec::new();
+        let mut content_encoding = None;
+        let mut frozen_objects = Vec::new();
+        let mut attributes = Vec::with_capacity(value.attributes.len());
+        for attribute in &value.attributes {
+            if let Some(hints) = processing_hints_from_attribute(attribute) {
+                processing_hints = hints?;
+            } else if let Some(pose) = geo_position_from_attribute(attribute) {
+                geo_pose = Some(pose?);
+            } else if let Some(decoded) = classification_from_attribute(attribute) {
+                classification = decoded?;
+            } else if let Some(decoded) = representations_from_attribute(attribute) {
+                representations = decoded?;
+            } else if let Some(decoded) = attribute_expiry_from_attribute(attribute) {
+                expiries = decoded?;
+            } else if let Some(decoded) = attribute_units_from_attribute(attribute) {
+                units = decoded?;
+            } else if let Some(decoded) = content_encoding_from_attribute(attribute) {
+                content_encoding = Some(decoded?);
+            } else if let Some(decoded) = frozen_objects_from_attribute(attribute) {
+                frozen_objects = decoded?;
+            } else {
+                attributes.push(Attribute::try_from(attribute)?);
+            }
+        }
 
-        let objects = value
+        let mut objects = value
             .objects
             .iter()
             .map(|o| VideoObject::try_from(o).map(|vo| (vo.id, vo)))
             .collect::<Result<HashMap<i64, _>, _>>()?;
+        apply_attribute_expiry(&expiries, &mut attributes, &mut objects);
+        apply_attribute_units(&units, &mut attributes, &mut objects);
 
         let object_parents = value
             .objects
@@ -130,11 +191,42 @@ impl TryFrom<&generated::VideoFrame> for VideoFrame {
             pts: value.pts,
             dts: value.dts,
             duration: value.duration,
-            content: Arc::new(VideoFrameContent::from(value.content.as_ref().unwrap())),
+            content: Arc::new(match value.content.as_ref().unwrap() {
+                // the raw layout is not serialized, the content is always packed
+                generated::video_frame::Content::Internal(data) => match content_encoding {
+                    Some((encoding, size)) => {
+                        VideoFrameContent::Internal(InternalFrame::compressed_from_codec(
+                            data.clone(),
+                            encoding,
+                            size,
+                            value.codec.as_deref(),
+                            value.width,
+                            value.height,
+                        ))
+                    }
+                    None => VideoFrameContent::Internal(InternalFrame::from_codec(
+                        data.clone(),
+                        value.codec.as_deref(),
+                        value.width,
+                        value.height,
+                    )),
+                },
+                content => VideoFrameContent::from(content),
+            }),
             transformations,
             attributes,
+            processing_hints,
+            geo_pose,
+            classification,
+            representations,
             objects,
             max_object_id,
+            frozen_objects,
+            stage: None,
+            source_config: None,
+            query_cache: None,
+            generation: 0,
+            attribute_propagation: None,
         })
     }
 }
@@ -150,7 +242,8 @@ impl TryFrom<&generated::VideoFrame> for VideoFrameProxy {
 #[cfg(test)]
 mod tests {
     use crate::json_api::ToSerdeJsonValue;
-    use crate::primitives::frame::VideoFrameProxy;
+    use crate::primitives::frame::{VideoFrameContent, VideoFrameProxy};
+    use crate::primitives::raw_content::{InternalFrame, PixelFormat, Plane, RawLayout};
     use crate::test::gen_frame;
     use savant_protobuf::generated;
 
@@ -164,4 +257,36 @@ mod tests {
         assert_eq!(restored.inner.read().creation_timestamp_ns, pattern);
         assert_eq!(frame.to_serde_json_value(), restored.to_serde_json_value());
     }
+
+    #[test]
+    fn test_raw_layout() -> anyhow::Result<()> {
+        let mut frame = gen_frame();
+        frame.set_width(2);
+        frame.set_height(2);
+        frame.set_codec(Some(PixelFormat::Gray8.codec()));
+        let layout = RawLayout::new(
+            PixelFormat::Gray8,
+            2,
+            2,
+            vec![Plane {
+                offset: 0,
+                stride: 4,
+            }],
+        )?;
+        frame.set_content(VideoFrameContent::Internal(InternalFrame::raw(
+            vec![1, 2, 0, 0, 3, 4, 0, 0],
+            layout,
+        )?));
+        let restored = VideoFrameProxy::try_from(&generated::VideoFrame::from(&frame))?;
+        let content = restored.get_content();
+        let VideoFrameContent::Internal(content) = content.as_ref() else {
+            panic!("Internal content expected")
+        };
+        assert_eq!(content.get_data().ok(), Some(&[1, 2, 3, 4][..]));
+        assert_eq!(
+            content.get_layout(),
+            Some(&RawLayout::packed(PixelFormat::Gray8, 2, 2)?)
+        );
+        Ok(())
+    }
 }
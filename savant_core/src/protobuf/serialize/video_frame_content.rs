@@ -1,4 +1,5 @@
 use crate::primitives::frame::VideoFrameContent;
+use crate::protobuf::serialize::content_encoding::wire_compressed;
 use savant_protobuf::generated;
 
 impl From<&VideoFrameContent> for generated::video_frame::Content {
@@ -10,9 +11,13 @@ impl From<&VideoFrameContent> for generated::video_frame::Content {
                     location: e.location.clone(),
                 })
             }
-            VideoFrameContent::Internal(data) => {
-                generated::video_frame::Content::Internal(data.clone())
-            }
+            // compressed content is sent as is, the codec travels in a hidden attribute
+            VideoFrameContent::Internal(data) => match wire_compressed(data) {
+                Some((_, compressed)) => {
+                    generated::video_frame::Content::Internal(compressed.to_vec())
+                }
+                None => generated::video_frame::Content::Internal(data.to_packed()),
+            },
             VideoFrameContent::None => {
                 generated::video_frame::Content::None(generated::NoneFrame {})
             }
@@ -30,7 +35,7 @@ impl From<&generated::video_frame::Content> for VideoFrameContent {
                 })
             }
             generated::video_frame::Content::Internal(data) => {
-                VideoFrameContent::Internal(data.clone())
+                VideoFrameContent::Internal(data.clone().into())
             }
             generated::video_frame::Content::None(_) => VideoFrameContent::None,
         }
@@ -57,7 +62,7 @@ mod tests {
             ))
         );
         assert_eq!(
-            VideoFrameContent::Internal(vec![1, 2, 3]),
+            VideoFrameContent::Internal(vec![1, 2, 3].into()),
             VideoFrameContent::from(&generated::video_frame::Content::Internal(vec![1, 2, 3]))
         );
         assert_eq!(
@@ -82,7 +87,9 @@ mod tests {
 
         assert_eq!(
             generated::video_frame::Content::Internal(vec![1, 2, 3]),
-            generated::video_frame::Content::from(&VideoFrameContent::Internal(vec![1, 2, 3]))
+            generated::video_frame::Content::from(&VideoFrameContent::Internal(
+                vec![1, 2, 3].into()
+            ))
         );
 
         assert_eq!(
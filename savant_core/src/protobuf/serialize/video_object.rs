@@ -1,14 +1,27 @@
+use crate::primitives::classification::DataClassification;
 use crate::primitives::object::{ObjectOperations, VideoObject};
-use crate::primitives::{Attribute, RBBox, WithAttributes};
+use crate::primitives::{Attribute, RBBox};
 use crate::protobuf::serialize;
+use crate::protobuf::serialize::classification::{
+    classification_attribute, classification_from_attribute,
+};
+use crate::protobuf::serialize::geo::{geo_position_attribute, geo_position_from_attribute};
+use crate::protobuf::serialize::object_payload::{
+    object_payload_attribute, object_payload_from_attribute,
+};
 use savant_protobuf::generated;
 
 impl From<&VideoObject> for generated::VideoObject {
     fn from(vop: &VideoObject) -> Self {
+        // one pass over the attributes, an attribute expiring in between is not looked up
         let attributes = vop
-            .get_attributes()
+            .attributes
             .iter()
-            .map(|(ns, l)| generated::Attribute::from(&vop.get_attribute(ns, l).unwrap()))
+            .filter(|a| !a.is_hidden && !a.is_expired())
+            .map(generated::Attribute::from)
+            .chain(geo_position_attribute(vop.geo_position.as_ref()))
+            .chain(classification_attribute(vop.classification))
+            .chain(object_payload_attribute(vop.payload.as_ref()))
             .collect();
 
         generated::VideoObject {
@@ -45,12 +58,21 @@ impl From<&(VideoObject, Option<i64>)> for GeneratedVideoObjectWithForeignParent
 impl TryFrom<&generated::VideoObject> for VideoObject {
     type Error = serialize::Error;
     fn try_from(obj: &generated::VideoObject) -> Result<Self, Self::Error> {
-        let attributes = obj
-            .attributes
-            .iter()
-            .filter(|a| a.is_persistent)
-            .map(Attribute::try_from)
-            .collect::<Result<Vec<Attribute>, _>>()?;
+        let mut geo_position = None;
+        let mut classification = DataClassification::default();
+        let mut payload = None;
+        let mut attributes = Vec::with_capacity(obj.attributes.len());
+        for attribute in obj.attributes.iter().filter(|a| a.is_persistent) {
+            if let Some(position) = geo_position_from_attribute(attribute) {
+                geo_position = Some(position?);
+            } else if let Some(decoded) = classification_from_attribute(attribute) {
+                classification = decoded?;
+            } else if let Some(decoded) = object_payload_from_attribute(attribute) {
+                payload = Some(decoded?);
+            } else {
+                attributes.push(Attribute::try_from(attribute)?);
+            }
+        }
 
         Ok(VideoObject {
             id: obj.id,
@@ -65,6 +87,9 @@ impl TryFrom<&generated::VideoObject> for VideoObject {
             track_id: obj.track_id,
             namespace_id: None,
             label_id: None,
+            geo_position,
+            classification,
+            payload,
             frame: None,
         })
     }
@@ -117,4 +142,20 @@ mod tests {
             persistent_attr
         );
     }
+
+    #[test]
+    fn test_object_with_expired_attribute() {
+        let mut obj = gen_object(1);
+        let mut expired = Attribute::persistent(
+            "osd",
+            "expired",
+            vec![AttributeValue::boolean(true, None)],
+            &None,
+            false,
+        );
+        expired.set_expires_at(Some(1));
+        obj.set_attribute(expired);
+        let serialized = generated::VideoObject::from(&obj);
+        assert!(serialized.attributes.iter().all(|a| a.name != "expired"));
+    }
 }
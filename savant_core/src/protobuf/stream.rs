@@ -0,0 +1,316 @@
+use crate::message::Message;
+use crate::primitives::frame::VideoFrameProxy;
+use crate::primitives::frame_batch::VideoFrameBatch;
+use crate::protobuf::serialize::ingestion::apply_ingestion_policy;
+use crate::protobuf::serialize::redaction::{redact_for_export, redact_frame_for_export};
+use crate::protobuf::serialize::Error;
+use prost::Message as ProstMessage;
+use savant_protobuf::generated;
+use std::io::{Read, Write};
+
+const MAX_VARINT_LEN: usize = 10;
+
+/// The largest record accepted by the stream readers, a larger length prefix is treated as
+/// corruption instead of being allocated.
+///
+pub const MAX_RECORD_SIZE: usize = 1 << 30;
+
+fn read_length_delimiter<R: Read>(reader: &mut R) -> Result<Option<usize>, Error> {
+    let mut value: u64 = 0;
+    for i in 0..MAX_VARINT_LEN {
+        let mut byte = [0u8; 1];
+        let read = reader.read(&mut byte)?;
+        if read == 0 {
+            return if i == 0 {
+                Ok(None)
+            } else {
+                Err(Error::UnexpectedEndOfStream)
+            };
+        }
+        value |= u64::from(byte[0] & 0x7f) << (7 * i);
+        if byte[0] & 0x80 == 0 {
+            return Ok(Some(value as usize));
+        }
+    }
+    Err(Error::ProstDecode(prost::DecodeError::new(
+        "invalid length delimiter",
+    )))
+}
+
+pub(super) fn read_record<R: Read, T: ProstMessage + Default>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    max_len: usize,
+) -> Result<Option<T>, Error> {
+    let len = match read_length_delimiter(reader)? {
+        Some(len) => len,
+        None => return Ok(None),
+    };
+    if len > max_len {
+        return Err(Error::RecordTooLarge(len, max_len));
+    }
+    buf.clear();
+    // the buffer grows with the bytes actually read, not with the declared length
+    reader.take(len as u64).read_to_end(buf)?;
+    if buf.len() < len {
+        return Err(Error::UnexpectedEndOfStream);
+    }
+    Ok(Some(T::decode(buf.as_slice())?))
+}
+
+pub(super) fn write_record<W: Write, T: ProstMessage>(
+    writer: &mut W,
+    buf: &mut Vec<u8>,
+    record: &T,
+) -> Result<(), Error> {
+    buf.clear();
+    record.encode_length_delimited(buf)?;
+    writer.write_all(buf)?;
+    Ok(())
+}
+
+/// Writes batch frames one by one. Every record is a length-delimited protobuf batch
+/// holding exactly one frame, so the whole batch is never materialized as a single message
+/// and the memory peak is bound by the largest frame rather than by the sum of all frames.
+///
+pub struct BatchStreamWriter<W: Write> {
+    writer: W,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> BatchStreamWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            buf: Vec::new(),
+        }
+    }
+
+    pub fn write_frame(&mut self, id: i64, frame: &VideoFrameProxy) -> Result<(), Error> {
+        let mut frame = generated::VideoFrame::from(frame);
+        redact_frame_for_export(&mut frame)?;
+        let record = generated::VideoFrameBatch {
+            batch: [(id, frame)].into_iter().collect(),
+        };
+        write_record(&mut self.writer, &mut self.buf, &record)
+    }
+
+    pub fn write_batch(&mut self, batch: &VideoFrameBatch) -> Result<(), Error> {
+        for (id, frame) in batch.frames() {
+            self.write_frame(*id, frame)?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads batch frames one by one. The reader is an iterator of ``(id, frame)`` pairs.
+///
+pub struct BatchStreamReader<R: Read> {
+    reader: R,
+    buf: Vec<u8>,
+    pending: Vec<(i64, generated::VideoFrame)>,
+}
+
+impl<R: Read> BatchStreamReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn read_frame(&mut self) -> Result<Option<(i64, VideoFrameProxy)>, Error> {
+        loop {
+            if let Some((id, frame)) = self.pending.pop() {
+                return Ok(Some((id, VideoFrameProxy::try_from(&frame)?)));
+            }
+            match read_record::<_, generated::VideoFrameBatch>(
+                &mut self.reader,
+                &mut self.buf,
+                MAX_RECORD_SIZE,
+            )? {
+                Some(mut record) => {
+                    apply_ingestion_policy(&mut record)?;
+                    self.pending.extend(record.batch)
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+
+    pub fn read_batch(mut self) -> Result<VideoFrameBatch, Error> {
+        let mut batch = VideoFrameBatch::new();
+        while let Some((id, frame)) = self.read_frame()? {
+            batch.add(id, frame);
+        }
+        Ok(batch)
+    }
+}
+
+impl<R: Read> Iterator for BatchStreamReader<R> {
+    type Item = Result<(i64, VideoFrameProxy), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_frame().transpose()
+    }
+}
+
+/// Writes messages as a journal of length-delimited records.
+///
+pub struct MessageStreamWriter<W: Write> {
+    writer: W,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> MessageStreamWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            buf: Vec::new(),
+        }
+    }
+
+    pub fn write_message(&mut self, m: &Message) -> Result<(), Error> {
+        let mut record = generated::Message::from(m);
+        redact_for_export(&mut record)?;
+        write_record(&mut self.writer, &mut self.buf, &record)
+    }
+
+    /// Writes the converted message as is, the export redaction is not applied.
+    ///
+    pub(crate) fn write_unredacted(&mut self, record: &generated::Message) -> Result<(), Error> {
+        write_record(&mut self.writer, &mut self.buf, record)
+    }
+
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads messages from a journal of length-delimited records.
+///
+pub struct MessageStreamReader<R: Read> {
+    reader: R,
+    buf: Vec<u8>,
+}
+
+impl<R: Read> MessageStreamReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+        }
+    }
+
+    pub fn read_message(&mut self) -> Result<Option<Message>, Error> {
+        match read_record::<_, generated::Message>(
+            &mut self.reader,
+            &mut self.buf,
+            MAX_RECORD_SIZE,
+        )? {
+            Some(mut record) => {
+                apply_ingestion_policy(&mut record)?;
+                Ok(Some(Message::try_from(&record)?))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl<R: Read> Iterator for MessageStreamReader<R> {
+    type Item = Result<Message, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_message().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::Message;
+    use crate::primitives::frame_batch::VideoFrameBatch;
+    use crate::primitives::rust::EndOfStream;
+    use crate::protobuf::serialize::Error;
+    use crate::protobuf::stream::{
+        BatchStreamReader, BatchStreamWriter, MessageStreamReader, MessageStreamWriter,
+        MAX_RECORD_SIZE,
+    };
+    use crate::test::gen_frame;
+
+    #[test]
+    fn test_batch_roundtrip() {
+        let mut batch = VideoFrameBatch::new();
+        batch.add(1, gen_frame());
+        batch.add(2, gen_frame());
+        let mut writer = BatchStreamWriter::new(Vec::new());
+        writer.write_batch(&batch).unwrap();
+        let bytes = writer.into_inner();
+
+        let restored = BatchStreamReader::new(bytes.as_slice())
+            .read_batch()
+            .unwrap();
+        assert_eq!(restored.frames().len(), 2);
+        for (id, frame) in batch.frames() {
+            let restored_frame = restored.get(*id).unwrap();
+            assert_eq!(frame.get_uuid(), restored_frame.get_uuid());
+            assert_eq!(
+                frame.get_all_objects().len(),
+                restored_frame.get_all_objects().len()
+            );
+        }
+    }
+
+    #[test]
+    fn test_truncated_batch() {
+        let mut writer = BatchStreamWriter::new(Vec::new());
+        writer.write_frame(1, &gen_frame()).unwrap();
+        let mut bytes = writer.into_inner();
+        bytes.truncate(bytes.len() - 1);
+        let res = BatchStreamReader::new(bytes.as_slice()).next().unwrap();
+        assert!(matches!(res, Err(Error::UnexpectedEndOfStream)));
+    }
+
+    #[test]
+    fn test_oversized_record() {
+        // the length prefix of a 2^40 byte record followed by nothing
+        let bytes = [0x80, 0x80, 0x80, 0x80, 0x80, 0x20];
+        let res = BatchStreamReader::new(bytes.as_slice()).next().unwrap();
+        assert!(matches!(
+            res,
+            Err(Error::RecordTooLarge(_, MAX_RECORD_SIZE))
+        ));
+    }
+
+    #[test]
+    fn test_message_journal() {
+        let mut writer = MessageStreamWriter::new(Vec::new());
+        writer
+            .write_message(&Message::video_frame(&gen_frame()))
+            .unwrap();
+        writer
+            .write_message(&Message::end_of_stream(EndOfStream::new("test".into())))
+            .unwrap();
+        let bytes = writer.into_inner();
+        let messages = MessageStreamReader::new(bytes.as_slice())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].is_video_frame());
+        assert!(messages[1].is_end_of_stream());
+    }
+}
@@ -0,0 +1,246 @@
+use crate::clip::get_clip_journal;
+use crate::metrics::get_or_create_counter_family;
+use anyhow::bail;
+use log::warn;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+const DISPOSITIONS_METRIC: &str = "frame_dispositions_reported";
+
+/// Serializes the appends, so the concurrent reports do not interleave.
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+/// The outcome of a frame in a sink.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportStatus {
+    Exported,
+    Skipped,
+    Failed,
+}
+
+impl ExportStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportStatus::Exported => "exported",
+            ExportStatus::Skipped => "skipped",
+            ExportStatus::Failed => "failed",
+        }
+    }
+}
+
+impl std::str::FromStr for ExportStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "exported" => ExportStatus::Exported,
+            "skipped" => ExportStatus::Skipped,
+            "failed" => ExportStatus::Failed,
+            _ => bail!("Unknown export status: {}", s),
+        })
+    }
+}
+
+/// The final disposition of a frame reported by a sink.
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Disposition {
+    pub frame_uuid: String,
+    pub sink: String,
+    pub status: ExportStatus,
+    /// Where the sink stored the frame or the results, e.g. a file path or an object key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stored_path: Option<String>,
+    /// The events the frame contributed to.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub event_ids: Vec<String>,
+    /// The time of the report in milliseconds since the UNIX epoch.
+    pub reported_at_ms: u64,
+}
+
+impl Disposition {
+    pub fn new(frame_uuid: Uuid, sink: &str, status: ExportStatus) -> Self {
+        Self {
+            frame_uuid: frame_uuid.to_string(),
+            sink: sink.to_string(),
+            status,
+            stored_path: None,
+            event_ids: Vec::new(),
+            reported_at_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+        }
+    }
+
+    pub fn with_stored_path(mut self, stored_path: &str) -> Self {
+        self.stored_path = Some(stored_path.to_string());
+        self
+    }
+
+    pub fn with_event_ids(mut self, event_ids: Vec<String>) -> Self {
+        self.event_ids = event_ids;
+        self
+    }
+}
+
+/// The index of the dispositions of the frames recorded in the journal, a file of JSON lines
+/// next to it: `<journal>.dispositions.jsonl`.
+///
+pub fn disposition_index_path(journal: &Path) -> PathBuf {
+    let mut name = journal.as_os_str().to_os_string();
+    name.push(".dispositions.jsonl");
+    PathBuf::from(name)
+}
+
+/// Appends the disposition to the index of the journal.
+///
+pub fn append_disposition(journal: &Path, disposition: &Disposition) -> anyhow::Result<()> {
+    let line = serde_json::to_string(disposition)?;
+    let _guard = INDEX_LOCK.lock();
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(disposition_index_path(journal))?;
+    writeln!(file, "{}", line)?;
+    let res = get_or_create_counter_family(
+        DISPOSITIONS_METRIC,
+        Some("The number of frame dispositions reported by the sinks"),
+        &["sink", "status"],
+        None,
+    )
+    .lock()
+    .inc(1, &[&disposition.sink, disposition.status.as_str()]);
+    if let Err(e) = res {
+        warn!(
+            target: "savant_rs::provenance",
+            "Failed to count the disposition: {}", e
+        );
+    }
+    Ok(())
+}
+
+/// Appends the disposition reported by a sink to the index of the recorded journal (see
+/// [`crate::clip::set_clip_journal`]), so the path of the frame from the source to the
+/// storage is reconstructed later with [`frame_provenance`].
+///
+pub fn report_disposition(disposition: &Disposition) -> anyhow::Result<()> {
+    let Some(journal) = get_clip_journal() else {
+        bail!("No clip journal is set");
+    };
+    append_disposition(&journal, disposition)
+}
+
+/// Reads the dispositions from the index of the journal in the order of the reports, only
+/// the ones of the frame when it is given. A journal without reports has no index. The lines
+/// which are not dispositions, e.g. the one torn by a crash while it was appended, are
+/// skipped with a warning.
+///
+pub fn read_dispositions(
+    journal: &Path,
+    frame_uuid: Option<Uuid>,
+) -> anyhow::Result<Vec<Disposition>> {
+    let path = disposition_index_path(journal);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let frame_uuid = frame_uuid.map(|u| u.to_string());
+    let mut dispositions = Vec::new();
+    for (n, line) in BufReader::new(File::open(&path)?).lines().enumerate() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
+                warn!(
+                    target: "savant_rs::provenance",
+                    "Skipping the malformed line {} of {}: {}", n + 1, path.display(), e
+                );
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let disposition: Disposition = match serde_json::from_str(&line) {
+            Ok(disposition) => disposition,
+            Err(e) => {
+                warn!(
+                    target: "savant_rs::provenance",
+                    "Skipping the malformed line {} of {}: {}", n + 1, path.display(), e
+                );
+                continue;
+            }
+        };
+        if frame_uuid
+            .as_ref()
+            .is_none_or(|u| u == &disposition.frame_uuid)
+        {
+            dispositions.push(disposition);
+        }
+    }
+    Ok(dispositions)
+}
+
+/// The dispositions of the frame reported to the recorded journal.
+///
+pub fn frame_provenance(frame_uuid: Uuid) -> anyhow::Result<Vec<Disposition>> {
+    let Some(journal) = get_clip_journal() else {
+        bail!("No clip journal is set");
+    };
+    read_dispositions(&journal, Some(frame_uuid))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metrics::get_counter_family;
+    use crate::provenance::{
+        append_disposition, disposition_index_path, read_dispositions, Disposition, ExportStatus,
+    };
+    use crate::utils::uuid_v7::incremental_uuid_v7;
+
+    #[test]
+    fn test_dispositions() -> anyhow::Result<()> {
+        let journal =
+            std::env::temp_dir().join(format!("savant-provenance-{}", incremental_uuid_v7()));
+        assert!(read_dispositions(&journal, None)?.is_empty());
+
+        let (first, second) = (incremental_uuid_v7(), incremental_uuid_v7());
+        let stored = Disposition::new(first, "s3", ExportStatus::Exported)
+            .with_stored_path("bucket/frames/1.jpeg")
+            .with_event_ids(vec!["intrusion-1".to_string()]);
+        append_disposition(&journal, &stored)?;
+        append_disposition(
+            &journal,
+            &Disposition::new(second, "s3", ExportStatus::Skipped),
+        )?;
+        append_disposition(
+            &journal,
+            &Disposition::new(first, "kafka", ExportStatus::Failed),
+        )?;
+
+        assert_eq!(read_dispositions(&journal, None)?.len(), 3);
+        let provenance = read_dispositions(&journal, Some(first))?;
+        assert_eq!(provenance.len(), 2);
+        assert_eq!(provenance[0], stored);
+        assert_eq!(provenance[1].sink, "kafka");
+
+        // a torn line does not hide the other dispositions
+        let index = disposition_index_path(&journal);
+        let mut content = std::fs::read(&index)?;
+        content.extend_from_slice(b"{\"frame_uuid\": \"");
+        std::fs::write(&index, content)?;
+        assert_eq!(read_dispositions(&journal, None)?.len(), 3);
+
+        let counter = get_counter_family("frame_dispositions_reported").unwrap();
+        assert!(counter.lock().get(&["s3", "exported"])?.is_some());
+        std::fs::remove_file(disposition_index_path(&journal))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,3 @@
+pub mod gallery;
+#[cfg(feature = "hnsw")]
+pub mod hnsw;
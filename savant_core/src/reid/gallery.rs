@@ -0,0 +1,417 @@
+use crate::clock::now_ms;
+use anyhow::{bail, Result};
+use hashbrown::HashMap;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GalleryConfiguration {
+    /// The dimension of embeddings.
+    pub dimension: usize,
+    /// The identities which are not updated for longer are evicted.
+    pub ttl: Option<Duration>,
+}
+
+/// An identity of the gallery. The centroid is the normalized mean of the normalized
+/// embeddings added for the identity.
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GalleryIdentity {
+    pub id: i64,
+    pub centroid: Vec<f32>,
+    pub samples: u64,
+    /// The time of the last update in milliseconds since the UNIX epoch.
+    pub updated_at_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GalleryMatch {
+    pub id: i64,
+    pub similarity: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GallerySnapshot {
+    pub dimension: usize,
+    pub identities: Vec<GalleryIdentity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<serde_json::Value>,
+}
+
+/// Stores and restores gallery snapshots, implement it to keep galleries in a custom
+/// storage.
+///
+pub trait GalleryPersistence {
+    fn save(&self, snapshot: &GallerySnapshot) -> Result<()>;
+    fn load(&self) -> Result<Option<GallerySnapshot>>;
+}
+
+/// Keeps the snapshot as a JSON file.
+///
+#[derive(Debug, Clone)]
+pub struct JsonFilePersistence {
+    pub path: PathBuf,
+}
+
+impl GalleryPersistence for JsonFilePersistence {
+    fn save(&self, snapshot: &GallerySnapshot) -> Result<()> {
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(snapshot)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    fn load(&self) -> Result<Option<GallerySnapshot>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_slice(&std::fs::read(&self.path)?)?))
+    }
+}
+
+pub(crate) fn normalized(v: &[f32]) -> Vec<f32> {
+    let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        return v.to_vec();
+    }
+    v.iter().map(|x| x / norm).collect()
+}
+
+pub(crate) fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+}
+
+/// A nearest-neighbour index over the identity centroids. The gallery searches the
+/// centroids exhaustively unless an index is set; approximate indexes keep the query latency
+/// low for large galleries. Vectors passed to the index are normalized, so the cosine
+/// similarity is their dot product.
+///
+pub trait EmbeddingIndex: Send + Sync + std::fmt::Debug {
+    /// Inserts the vector of the identity, replacing the previous one.
+    fn insert(&mut self, id: i64, vector: &[f32]);
+    fn remove(&mut self, id: i64);
+    fn clear(&mut self);
+    /// Returns up to `k` identities most similar to the vector, the most similar first.
+    fn search(&self, vector: &[f32], k: usize) -> Vec<GalleryMatch>;
+    /// The serializable state of the index, saved with the gallery to avoid rebuilding the
+    /// index on load. `None` means the index is rebuilt from the centroids.
+    fn snapshot(&self) -> Result<Option<serde_json::Value>> {
+        Ok(None)
+    }
+    fn restore(&mut self, snapshot: serde_json::Value) -> Result<()>;
+}
+
+#[derive(Debug, Default)]
+struct GalleryState {
+    identities: HashMap<i64, GalleryIdentity>,
+    index: Option<Box<dyn EmbeddingIndex>>,
+}
+
+impl GalleryState {
+    fn set(&mut self, identity: GalleryIdentity) {
+        if let Some(index) = &mut self.index {
+            index.insert(identity.id, &identity.centroid);
+        }
+        self.identities.insert(identity.id, identity);
+    }
+
+    fn remove(&mut self, id: i64) -> Option<GalleryIdentity> {
+        let removed = self.identities.remove(&id);
+        if removed.is_some() {
+            if let Some(index) = &mut self.index {
+                index.remove(id);
+            }
+        }
+        removed
+    }
+}
+
+/// A gallery of identities for re-identification: maps identity ids to embedding centroids
+/// and finds the identities most similar to an embedding by cosine similarity.
+///
+#[derive(Debug)]
+pub struct ReidGallery {
+    configuration: GalleryConfiguration,
+    state: RwLock<GalleryState>,
+}
+
+impl ReidGallery {
+    pub fn new(configuration: GalleryConfiguration) -> Result<Self> {
+        if configuration.dimension == 0 {
+            bail!("Gallery embedding dimension must be positive")
+        }
+        Ok(Self {
+            configuration,
+            state: RwLock::new(GalleryState::default()),
+        })
+    }
+
+    /// Creates a gallery searching the centroids with the index.
+    ///
+    pub fn with_index(
+        configuration: GalleryConfiguration,
+        mut index: Box<dyn EmbeddingIndex>,
+    ) -> Result<Self> {
+        let gallery = Self::new(configuration)?;
+        index.clear();
+        gallery.state.write().index = Some(index);
+        Ok(gallery)
+    }
+
+    pub fn get_configuration(&self) -> &GalleryConfiguration {
+        &self.configuration
+    }
+
+    fn check_dimension(&self, embedding: &[f32]) -> Result<()> {
+        if embedding.len() != self.configuration.dimension {
+            bail!(
+                "Embedding dimension {} does not match the gallery dimension {}",
+                embedding.len(),
+                self.configuration.dimension
+            )
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.read().identities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.state.read().identities.is_empty()
+    }
+
+    /// Sets the centroid of the identity to the embedding, replacing the previous samples.
+    ///
+    pub fn add(&self, id: i64, embedding: &[f32]) -> Result<()> {
+        self.check_dimension(embedding)?;
+        self.state.write().set(GalleryIdentity {
+            id,
+            centroid: normalized(embedding),
+            samples: 1,
+            updated_at_ms: now_ms(),
+        });
+        Ok(())
+    }
+
+    /// Adds a sample of the identity to its centroid, creates the identity when it does not
+    /// exist. Returns the number of samples of the identity.
+    ///
+    pub fn update(&self, id: i64, embedding: &[f32]) -> Result<u64> {
+        self.check_dimension(embedding)?;
+        let sample = normalized(embedding);
+        let mut state = self.state.write();
+        let identity = match state.identities.get(&id) {
+            Some(identity) => {
+                let n = identity.samples as f32;
+                let mean = identity
+                    .centroid
+                    .iter()
+                    .zip(sample.iter())
+                    .map(|(c, s)| (c * n + s) / (n + 1.0))
+                    .collect::<Vec<_>>();
+                GalleryIdentity {
+                    id,
+                    centroid: normalized(&mean),
+                    samples: identity.samples + 1,
+                    updated_at_ms: now_ms(),
+                }
+            }
+            None => GalleryIdentity {
+                id,
+                centroid: sample,
+                samples: 1,
+                updated_at_ms: now_ms(),
+            },
+        };
+        let samples = identity.samples;
+        state.set(identity);
+        Ok(samples)
+    }
+
+    pub fn get(&self, id: i64) -> Option<GalleryIdentity> {
+        self.state.read().identities.get(&id).cloned()
+    }
+
+    pub fn remove(&self, id: i64) -> Option<GalleryIdentity> {
+        self.state.write().remove(id)
+    }
+
+    /// Removes the identities not updated within the TTL and returns their ids.
+    ///
+    pub fn evict_expired(&self) -> Vec<i64> {
+        let Some(ttl) = self.configuration.ttl else {
+            return Vec::new();
+        };
+        let deadline = now_ms().saturating_sub(ttl.as_millis() as u64);
+        let mut state = self.state.write();
+        let expired = state
+            .identities
+            .values()
+            .filter(|identity| identity.updated_at_ms < deadline)
+            .map(|identity| identity.id)
+            .collect::<Vec<_>>();
+        for id in &expired {
+            state.remove(*id);
+        }
+        expired
+    }
+
+    /// Returns up to `k` identities most similar to the embedding, the most similar first.
+    /// Expired identities are evicted before the search.
+    ///
+    pub fn query(&self, embedding: &[f32], k: usize) -> Result<Vec<GalleryMatch>> {
+        self.check_dimension(embedding)?;
+        self.evict_expired();
+        let query = normalized(embedding);
+        let state = self.state.read();
+        if let Some(index) = &state.index {
+            return Ok(index.search(&query, k));
+        }
+        let mut matches = state
+            .identities
+            .values()
+            .map(|identity| GalleryMatch {
+                id: identity.id,
+                similarity: dot(&query, &identity.centroid),
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+        matches.truncate(k);
+        Ok(matches)
+    }
+
+    pub fn snapshot(&self) -> Result<GallerySnapshot> {
+        let state = self.state.read();
+        let mut identities = state.identities.values().cloned().collect::<Vec<_>>();
+        identities.sort_by_key(|i| i.id);
+        let index = match &state.index {
+            Some(index) => index.snapshot()?,
+            None => None,
+        };
+        Ok(GallerySnapshot {
+            dimension: self.configuration.dimension,
+            identities,
+            index,
+        })
+    }
+
+    /// Replaces the identities with the ones of the snapshot. The index is restored from the
+    /// snapshot when the snapshot has it, otherwise it is rebuilt. A snapshot with centroids
+    /// of another dimension is rejected.
+    ///
+    pub fn restore(&self, snapshot: GallerySnapshot) -> Result<()> {
+        if snapshot.dimension != self.configuration.dimension {
+            bail!(
+                "Snapshot dimension {} does not match the gallery dimension {}",
+                snapshot.dimension,
+                self.configuration.dimension
+            )
+        }
+        if let Some(identity) = snapshot
+            .identities
+            .iter()
+            .find(|i| i.centroid.len() != self.configuration.dimension)
+        {
+            bail!(
+                "Snapshot identity {} has the centroid dimension {}, expected {}",
+                identity.id,
+                identity.centroid.len(),
+                self.configuration.dimension
+            )
+        }
+        let mut state = self.state.write();
+        state.identities = snapshot.identities.into_iter().map(|i| (i.id, i)).collect();
+        let GalleryState { identities, index } = &mut *state;
+        if let Some(index) = index {
+            match snapshot.index {
+                Some(index_snapshot) => index.restore(index_snapshot)?,
+                None => {
+                    index.clear();
+                    for identity in identities.values() {
+                        index.insert(identity.id, &identity.centroid);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, persistence: &dyn GalleryPersistence) -> Result<()> {
+        persistence.save(&self.snapshot()?)
+    }
+
+    /// Restores the gallery from the persistence, returns `false` when there is no snapshot.
+    ///
+    pub fn load(&self, persistence: &dyn GalleryPersistence) -> Result<bool> {
+        match persistence.load()? {
+            Some(snapshot) => {
+                self.restore(snapshot)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::reid::gallery::{GalleryConfiguration, JsonFilePersistence, ReidGallery};
+    use crate::utils::uuid_v7::incremental_uuid_v7;
+    use std::time::Duration;
+
+    fn gallery(ttl: Option<Duration>) -> ReidGallery {
+        ReidGallery::new(GalleryConfiguration { dimension: 3, ttl }).unwrap()
+    }
+
+    #[test]
+    fn test_query() -> anyhow::Result<()> {
+        let gallery = gallery(None);
+        gallery.add(1, &[1.0, 0.0, 0.0])?;
+        gallery.add(2, &[0.0, 1.0, 0.0])?;
+        assert_eq!(gallery.update(2, &[0.0, 1.0, 1.0])?, 2);
+        assert!(gallery.add(3, &[1.0, 0.0]).is_err());
+
+        let matches = gallery.query(&[0.0, 2.0, 0.5], 2)?;
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].id, 2);
+        assert!(matches[0].similarity > matches[1].similarity);
+        assert_eq!(gallery.query(&[1.0, 0.0, 0.0], 1)?[0].id, 1);
+        Ok(())
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_ttl() -> anyhow::Result<()> {
+        let gallery = gallery(Some(Duration::from_millis(20)));
+        gallery.add(1, &[1.0, 0.0, 0.0])?;
+        std::thread::sleep(Duration::from_millis(40));
+        gallery.add(2, &[0.0, 1.0, 0.0])?;
+        assert_eq!(gallery.evict_expired(), vec![1]);
+        assert_eq!(gallery.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_persistence() -> anyhow::Result<()> {
+        let persistence = JsonFilePersistence {
+            path: std::env::temp_dir().join(format!("gallery-{}.json", incremental_uuid_v7())),
+        };
+        let source = gallery(None);
+        let restored = gallery(None);
+        assert!(!restored.load(&persistence)?);
+        source.add(1, &[1.0, 2.0, 3.0])?;
+        source.save(&persistence)?;
+        assert!(restored.load(&persistence)?);
+        assert_eq!(restored.snapshot()?, source.snapshot()?);
+        std::fs::remove_file(&persistence.path)?;
+
+        let mut mismatched = source.snapshot()?;
+        mismatched.identities[0].centroid.pop();
+        assert!(restored.restore(mismatched).is_err());
+        assert_eq!(restored.snapshot()?, source.snapshot()?);
+        Ok(())
+    }
+}
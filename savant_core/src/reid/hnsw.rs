@@ -0,0 +1,414 @@
+use crate::reid::gallery::{dot, EmbeddingIndex, GalleryMatch};
+use anyhow::{bail, Result};
+use hashbrown::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HnswParameters {
+    /// The maximum number of neighbours of a node on the upper layers, the bottom layer
+    /// keeps twice as many.
+    pub m: usize,
+    /// The number of candidates considered when a node is inserted.
+    pub ef_construction: usize,
+    /// The number of candidates considered by a search; larger values improve the recall
+    /// at the cost of latency.
+    pub ef_search: usize,
+    /// The seed of the level generator, the same insertions with the same seed build the
+    /// same graph.
+    pub seed: u64,
+}
+
+impl Default for HnswParameters {
+    fn default() -> Self {
+        Self {
+            m: 16,
+            ef_construction: 100,
+            ef_search: 64,
+            seed: 0x5eed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Node {
+    id: i64,
+    vector: Vec<f32>,
+    /// The neighbours of the node on every layer it belongs to, the bottom layer first.
+    neighbours: Vec<Vec<usize>>,
+    deleted: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Graph {
+    nodes: Vec<Node>,
+    entry: Option<usize>,
+    deleted: usize,
+    #[serde(skip)]
+    positions: HashMap<i64, usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Scored(f32, usize);
+
+impl PartialEq for Scored {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0).then(self.1.cmp(&other.1))
+    }
+}
+
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e3779b97f4a7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+/// An approximate nearest-neighbour index based on hierarchical navigable small world
+/// graphs. Nodes are inserted incrementally; removed nodes stay in the graph as tombstones
+/// keeping it connected until more than half of the nodes are removed, then the graph is
+/// rebuilt from the remaining ones.
+///
+#[derive(Debug, Clone)]
+pub struct HnswIndex {
+    parameters: HnswParameters,
+    graph: Graph,
+}
+
+impl HnswIndex {
+    pub fn new(parameters: HnswParameters) -> Result<Self> {
+        if parameters.m < 2 {
+            bail!("HNSW parameter m must be at least 2")
+        }
+        if parameters.ef_construction == 0 || parameters.ef_search == 0 {
+            bail!("HNSW ef parameters must be positive")
+        }
+        Ok(Self {
+            parameters,
+            graph: Graph::default(),
+        })
+    }
+
+    pub fn get_parameters(&self) -> &HnswParameters {
+        &self.parameters
+    }
+
+    pub fn set_ef_search(&mut self, ef_search: usize) {
+        self.parameters.ef_search = ef_search.max(1);
+    }
+
+    /// The number of live nodes.
+    ///
+    pub fn len(&self) -> usize {
+        self.graph.nodes.len() - self.graph.deleted
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn max_neighbours(&self, layer: usize) -> usize {
+        if layer == 0 {
+            self.parameters.m * 2
+        } else {
+            self.parameters.m
+        }
+    }
+
+    fn random_level(&self) -> usize {
+        let x = splitmix64(self.parameters.seed ^ self.graph.nodes.len() as u64);
+        let uniform = ((x >> 11) as f64 + 1.0) / (1u64 << 53) as f64;
+        (-uniform.ln() / (self.parameters.m as f64).ln()) as usize
+    }
+
+    fn similarity(&self, vector: &[f32], node: usize) -> f32 {
+        dot(vector, &self.graph.nodes[node].vector)
+    }
+
+    /// Returns up to `ef` nodes closest to the vector on the layer, the closest first.
+    ///
+    fn search_layer(
+        &self,
+        vector: &[f32],
+        entries: &[usize],
+        ef: usize,
+        layer: usize,
+    ) -> Vec<Scored> {
+        let mut visited = entries.iter().copied().collect::<HashSet<_>>();
+        let mut candidates = BinaryHeap::new();
+        let mut results = BinaryHeap::new();
+        for &e in entries {
+            let scored = Scored(self.similarity(vector, e), e);
+            candidates.push(scored);
+            results.push(Reverse(scored));
+        }
+        while results.len() > ef {
+            results.pop();
+        }
+        while let Some(candidate) = candidates.pop() {
+            let worst = results.peek().map(|r| r.0 .0).unwrap_or(f32::MIN);
+            if candidate.0 < worst && results.len() >= ef {
+                break;
+            }
+            let Some(neighbours) = self.graph.nodes[candidate.1].neighbours.get(layer) else {
+                continue;
+            };
+            for &n in neighbours {
+                if !visited.insert(n) {
+                    continue;
+                }
+                let scored = Scored(self.similarity(vector, n), n);
+                let worst = results.peek().map(|r| r.0 .0).unwrap_or(f32::MIN);
+                if results.len() < ef || scored.0 > worst {
+                    candidates.push(scored);
+                    results.push(Reverse(scored));
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+        let mut results = results.into_iter().map(|r| r.0).collect::<Vec<_>>();
+        results.sort_by(|a, b| b.cmp(a));
+        results
+    }
+
+    /// Descends greedily from the entry point to the layer above `layer`.
+    ///
+    fn descend(&self, vector: &[f32], entry: usize, layer: usize) -> usize {
+        let top = self.graph.nodes[entry].neighbours.len() - 1;
+        let mut current = entry;
+        for l in (layer + 1..=top).rev() {
+            current = self.search_layer(vector, &[current], 1, l)[0].1;
+        }
+        current
+    }
+
+    fn connect(&mut self, node: usize, neighbour: usize, layer: usize) {
+        let max = self.max_neighbours(layer);
+        let links = &mut self.graph.nodes[neighbour].neighbours[layer];
+        links.push(node);
+        if links.len() <= max {
+            return;
+        }
+        let base = &self.graph.nodes[neighbour].vector;
+        let mut scored = self.graph.nodes[neighbour].neighbours[layer]
+            .iter()
+            .map(|&n| Scored(dot(base, &self.graph.nodes[n].vector), n))
+            .collect::<Vec<_>>();
+        scored.sort_by(|a, b| b.cmp(a));
+        scored.truncate(max);
+        self.graph.nodes[neighbour].neighbours[layer] = scored.into_iter().map(|s| s.1).collect();
+    }
+
+    fn insert_node(&mut self, id: i64, vector: &[f32]) {
+        let level = self.random_level();
+        let node = self.graph.nodes.len();
+        self.graph.nodes.push(Node {
+            id,
+            vector: vector.to_vec(),
+            neighbours: vec![Vec::new(); level + 1],
+            deleted: false,
+        });
+        self.graph.positions.insert(id, node);
+        let Some(entry) = self.graph.entry else {
+            self.graph.entry = Some(node);
+            return;
+        };
+        let top = self.graph.nodes[entry].neighbours.len() - 1;
+        let mut entries = vec![self.descend(vector, entry, level.min(top))];
+        for layer in (0..=level.min(top)).rev() {
+            let candidates =
+                self.search_layer(vector, &entries, self.parameters.ef_construction, layer);
+            let neighbours = candidates
+                .iter()
+                .take(self.max_neighbours(layer))
+                .map(|s| s.1)
+                .collect::<Vec<_>>();
+            for &n in &neighbours {
+                self.connect(node, n, layer);
+            }
+            self.graph.nodes[node].neighbours[layer] = neighbours;
+            entries = candidates.into_iter().map(|s| s.1).collect();
+        }
+        if level > top {
+            self.graph.entry = Some(node);
+        }
+    }
+
+    fn rebuild(&mut self) {
+        let live = std::mem::take(&mut self.graph.nodes)
+            .into_iter()
+            .filter(|n| !n.deleted)
+            .collect::<Vec<_>>();
+        self.graph = Graph::default();
+        for node in live {
+            self.insert_node(node.id, &node.vector);
+        }
+    }
+}
+
+impl EmbeddingIndex for HnswIndex {
+    fn insert(&mut self, id: i64, vector: &[f32]) {
+        self.remove(id);
+        self.insert_node(id, vector);
+    }
+
+    fn remove(&mut self, id: i64) {
+        let Some(node) = self.graph.positions.remove(&id) else {
+            return;
+        };
+        self.graph.nodes[node].deleted = true;
+        self.graph.deleted += 1;
+        if self.graph.deleted * 2 > self.graph.nodes.len() {
+            self.rebuild();
+        }
+    }
+
+    fn clear(&mut self) {
+        self.graph = Graph::default();
+    }
+
+    fn search(&self, vector: &[f32], k: usize) -> Vec<GalleryMatch> {
+        let Some(entry) = self.graph.entry else {
+            return Vec::new();
+        };
+        if k == 0 || self.is_empty() {
+            return Vec::new();
+        }
+        let entry = self.descend(vector, entry, 0);
+        // tombstones occupy candidate slots, widen the search to compensate
+        let ef = self.parameters.ef_search.max(k) + self.graph.deleted.min(k);
+        self.search_layer(vector, &[entry], ef, 0)
+            .into_iter()
+            .filter(|s| !self.graph.nodes[s.1].deleted)
+            .take(k)
+            .map(|s| GalleryMatch {
+                id: self.graph.nodes[s.1].id,
+                similarity: s.0,
+            })
+            .collect()
+    }
+
+    fn snapshot(&self) -> Result<Option<serde_json::Value>> {
+        Ok(Some(serde_json::to_value(&self.graph)?))
+    }
+
+    fn restore(&mut self, snapshot: serde_json::Value) -> Result<()> {
+        let mut graph: Graph = serde_json::from_value(snapshot)?;
+        if graph.entry.is_some_and(|e| e >= graph.nodes.len())
+            || graph
+                .nodes
+                .iter()
+                .flat_map(|n| n.neighbours.iter().flatten())
+                .any(|&n| n >= graph.nodes.len())
+        {
+            bail!("HNSW snapshot references missing nodes")
+        }
+        let tombstones = graph.nodes.iter().filter(|n| n.deleted).count();
+        if graph.deleted != tombstones {
+            bail!(
+                "HNSW snapshot counts {} deleted nodes, the graph has {}",
+                graph.deleted,
+                tombstones
+            )
+        }
+        graph.positions = graph
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| !n.deleted)
+            .map(|(i, n)| (n.id, i))
+            .collect();
+        self.graph = graph;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::reid::gallery::{EmbeddingIndex, GalleryConfiguration, ReidGallery};
+    use crate::reid::hnsw::{HnswIndex, HnswParameters};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    const DIMENSION: usize = 32;
+
+    fn gallery(rng: &mut StdRng, size: i64) -> anyhow::Result<ReidGallery> {
+        let gallery = ReidGallery::with_index(
+            GalleryConfiguration {
+                dimension: DIMENSION,
+                ttl: None,
+            },
+            Box::new(HnswIndex::new(HnswParameters::default())?),
+        )?;
+        for id in 0..size {
+            let v = (0..DIMENSION)
+                .map(|_| rng.gen_range(-1.0..1.0))
+                .collect::<Vec<f32>>();
+            gallery.add(id, &v)?;
+        }
+        Ok(gallery)
+    }
+
+    #[test]
+    fn test_recall() -> anyhow::Result<()> {
+        let mut rng = StdRng::seed_from_u64(1);
+        let g = gallery(&mut rng, 2000)?;
+        let mut found = 0;
+        for id in 0..2000 {
+            let centroid = g.get(id).unwrap().centroid;
+            if g.query(&centroid, 1)?.first().map(|m| m.id) == Some(id) {
+                found += 1;
+            }
+        }
+        assert!(found >= 1960, "recall is too low: {}", found);
+
+        for id in 0..1500 {
+            g.remove(id);
+        }
+        let centroid = g.get(1700).unwrap().centroid;
+        let matches = g.query(&centroid, 5)?;
+        assert_eq!(matches[0].id, 1700);
+        assert!(matches.iter().all(|m| m.id >= 1500));
+        Ok(())
+    }
+
+    #[test]
+    fn test_snapshot() -> anyhow::Result<()> {
+        let mut rng = StdRng::seed_from_u64(2);
+        let source = gallery(&mut rng, 300)?;
+        source.remove(10);
+        let snapshot = source.snapshot()?;
+        assert!(snapshot.index.is_some());
+
+        let restored = gallery(&mut rng, 0)?;
+        restored.restore(snapshot.clone())?;
+        let centroid = source.get(20).unwrap().centroid;
+        assert_eq!(source.query(&centroid, 3)?, restored.query(&centroid, 3)?);
+
+        let mut index = HnswIndex::new(HnswParameters::default())?;
+        let mut broken = snapshot.index.clone().unwrap();
+        broken["entry"] = serde_json::json!(100_000);
+        assert!(index.restore(broken).is_err());
+        let mut broken = snapshot.index.clone().unwrap();
+        broken["deleted"] = serde_json::json!(100_000);
+        assert!(index.restore(broken).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,588 @@
+use crate::message::Message;
+use crate::primitives::attribute_value::AttributeValue;
+use crate::primitives::frame::VideoFrameProxy;
+use crate::primitives::object::{BorrowedVideoObject, ObjectOperations};
+use crate::primitives::WithAttributes;
+use crate::protobuf::{MessageStreamReader, MessageStreamWriter};
+use crate::transport::zeromq::reader::ReaderResult;
+use crate::transport::zeromq::{SyncReader, SyncWriter};
+use anyhow::{bail, Context};
+use hashbrown::{HashMap, HashSet};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// How the objects of compared frames are paired and when a paired object is moved.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ComparisonTolerance {
+    /// The minimum IoU of objects with the same namespace and label to pair them.
+    pub match_iou: f32,
+    /// Paired objects with a lower IoU are reported as moved.
+    pub move_iou: f32,
+}
+
+impl Default for ComparisonTolerance {
+    fn default() -> Self {
+        Self {
+            match_iou: 0.3,
+            move_iou: 0.9,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ObjectSummary {
+    pub id: i64,
+    pub namespace: String,
+    pub label: String,
+    /// The detection box as left, top, width and height.
+    pub bbox: [f32; 4],
+}
+
+impl ObjectSummary {
+    fn new(object: &BorrowedVideoObject) -> Self {
+        let bbox = object.get_detection_box();
+        let (left, top, width, height) = bbox.as_ltwh().unwrap_or_default();
+        Self {
+            id: object.get_id(),
+            namespace: object.get_namespace(),
+            label: object.get_label(),
+            bbox: [left, top, width, height],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MovedObject {
+    pub baseline: ObjectSummary,
+    pub candidate: ObjectSummary,
+    pub iou: f32,
+}
+
+/// The differences of the objects of a frame present in both journals.
+///
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FrameComparison {
+    pub source_id: String,
+    pub pts: i64,
+    pub added: Vec<ObjectSummary>,
+    pub removed: Vec<ObjectSummary>,
+    pub moved: Vec<MovedObject>,
+}
+
+impl FrameComparison {
+    pub fn is_equal(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.moved.is_empty()
+    }
+}
+
+/// The result of comparing the frames of a candidate journal with the baseline. Frames are
+/// identified by the source and the PTS, only frames with differences are listed.
+///
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ComparisonReport {
+    pub compared_frames: usize,
+    /// The frames of the baseline missing in the candidate.
+    pub missing_frames: Vec<(String, i64)>,
+    /// The frames of the candidate missing in the baseline.
+    pub extra_frames: Vec<(String, i64)>,
+    pub frames: Vec<FrameComparison>,
+}
+
+impl ComparisonReport {
+    pub fn is_equal(&self) -> bool {
+        self.missing_frames.is_empty() && self.extra_frames.is_empty() && self.frames.is_empty()
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Pairs the objects greedily by the highest IoU among objects with the same namespace and
+/// label. Returns the IoU and the indices of the paired objects.
+///
+fn pair_objects(
+    baseline: &[BorrowedVideoObject],
+    candidate: &[BorrowedVideoObject],
+    match_iou: f32,
+) -> Vec<(f32, usize, usize)> {
+    let mut pairs = Vec::new();
+    for (i, b) in baseline.iter().enumerate() {
+        for (j, c) in candidate.iter().enumerate() {
+            if b.get_namespace() != c.get_namespace() || b.get_label() != c.get_label() {
+                continue;
+            }
+            let iou = b
+                .get_detection_box()
+                .iou(&c.get_detection_box())
+                .unwrap_or_default();
+            if iou >= match_iou {
+                pairs.push((iou, i, j));
+            }
+        }
+    }
+    pairs.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut paired_baseline = HashSet::new();
+    let mut paired_candidate = HashSet::new();
+    pairs
+        .into_iter()
+        .filter(|(_, i, j)| {
+            if paired_baseline.contains(i) || paired_candidate.contains(j) {
+                return false;
+            }
+            paired_baseline.insert(*i);
+            paired_candidate.insert(*j);
+            true
+        })
+        .collect()
+}
+
+fn unpaired_objects(
+    objects: &[BorrowedVideoObject],
+    paired: &HashSet<usize>,
+) -> Vec<ObjectSummary> {
+    objects
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !paired.contains(i))
+        .map(|(_, o)| ObjectSummary::new(o))
+        .collect()
+}
+
+/// Pairs the objects greedily by the highest IoU among objects with the same namespace and
+/// label.
+///
+pub fn compare_frames(
+    baseline: &VideoFrameProxy,
+    candidate: &VideoFrameProxy,
+    tolerance: &ComparisonTolerance,
+) -> FrameComparison {
+    let baseline_objects = baseline.get_all_objects();
+    let candidate_objects = candidate.get_all_objects();
+    let pairs = pair_objects(&baseline_objects, &candidate_objects, tolerance.match_iou);
+    let moved = pairs
+        .iter()
+        .filter(|(iou, _, _)| *iou < tolerance.move_iou)
+        .map(|(iou, i, j)| MovedObject {
+            baseline: ObjectSummary::new(&baseline_objects[*i]),
+            candidate: ObjectSummary::new(&candidate_objects[*j]),
+            iou: *iou,
+        })
+        .collect();
+    let paired_baseline = pairs.iter().map(|(_, i, _)| *i).collect();
+    let paired_candidate = pairs.iter().map(|(_, _, j)| *j).collect();
+    FrameComparison {
+        source_id: baseline.get_source_id(),
+        pts: baseline.get_pts(),
+        added: unpaired_objects(&candidate_objects, &paired_candidate),
+        removed: unpaired_objects(&baseline_objects, &paired_baseline),
+        moved,
+    }
+}
+
+/// An attribute differing between the versions, the values are missing when the version
+/// has no attribute.
+///
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AttributeChange {
+    pub namespace: String,
+    pub name: String,
+    pub baseline: Option<Vec<AttributeValue>>,
+    pub candidate: Option<Vec<AttributeValue>>,
+}
+
+fn diff_attributes<T: WithAttributes>(baseline: &T, candidate: &T) -> Vec<AttributeChange> {
+    let mut keys = baseline.get_attributes();
+    keys.extend(candidate.get_attributes());
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter_map(|(namespace, name)| {
+            let values = |o: &T| {
+                o.get_attribute(&namespace, &name)
+                    .map(|a| a.get_values().to_vec())
+            };
+            let (baseline, candidate) = (values(baseline), values(candidate));
+            (baseline != candidate).then_some(AttributeChange {
+                namespace,
+                name,
+                baseline,
+                candidate,
+            })
+        })
+        .collect()
+}
+
+/// A pair of objects differing in the box or the attributes.
+///
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ObjectDiff {
+    pub baseline: ObjectSummary,
+    pub candidate: ObjectSummary,
+    pub iou: f32,
+    pub attributes: Vec<AttributeChange>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FramePresence {
+    Both,
+    BaselineOnly,
+    CandidateOnly,
+}
+
+/// The metadata differences of the versions of a frame with the same uuid, for side-by-side
+/// reviews of two pipeline versions.
+///
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FrameDiff {
+    pub uuid: String,
+    pub source_id: String,
+    pub pts: i64,
+    pub presence: FramePresence,
+    pub attributes: Vec<AttributeChange>,
+    pub added: Vec<ObjectSummary>,
+    pub removed: Vec<ObjectSummary>,
+    pub changed: Vec<ObjectDiff>,
+}
+
+impl FrameDiff {
+    fn new(frame: &VideoFrameProxy, presence: FramePresence) -> Self {
+        Self {
+            uuid: frame.get_uuid_as_string(),
+            source_id: frame.get_source_id(),
+            pts: frame.get_pts(),
+            presence,
+            attributes: Vec::new(),
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+        }
+    }
+
+    pub fn is_equal(&self) -> bool {
+        self.presence == FramePresence::Both
+            && self.attributes.is_empty()
+            && self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+/// Diffs the frame attributes and the objects of the versions of a frame. The objects are
+/// paired like in [`compare_frames`]; a pair is changed when the IoU is below
+/// `move_iou` or the attributes differ.
+///
+pub fn diff_frames(
+    baseline: &VideoFrameProxy,
+    candidate: &VideoFrameProxy,
+    tolerance: &ComparisonTolerance,
+) -> FrameDiff {
+    let mut diff = FrameDiff::new(baseline, FramePresence::Both);
+    diff.attributes = diff_attributes(baseline, candidate);
+    let baseline_objects = baseline.get_all_objects();
+    let candidate_objects = candidate.get_all_objects();
+    let pairs = pair_objects(&baseline_objects, &candidate_objects, tolerance.match_iou);
+    diff.changed = pairs
+        .iter()
+        .filter_map(|(iou, i, j)| {
+            let (b, c) = (&baseline_objects[*i], &candidate_objects[*j]);
+            let attributes = diff_attributes(b, c);
+            (*iou < tolerance.move_iou || !attributes.is_empty()).then(|| ObjectDiff {
+                baseline: ObjectSummary::new(b),
+                candidate: ObjectSummary::new(c),
+                iou: *iou,
+                attributes,
+            })
+        })
+        .collect();
+    let paired_baseline = pairs.iter().map(|(_, i, _)| *i).collect();
+    let paired_candidate = pairs.iter().map(|(_, _, j)| *j).collect();
+    diff.added = unpaired_objects(&candidate_objects, &paired_candidate);
+    diff.removed = unpaired_objects(&baseline_objects, &paired_baseline);
+    diff
+}
+
+fn journal_frames(path: &Path) -> anyhow::Result<Vec<VideoFrameProxy>> {
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open the journal {}", path.display()))?;
+    let mut frames = Vec::new();
+    for message in MessageStreamReader::new(BufReader::new(file)) {
+        let message = message?;
+        if let Some(frame) = message.as_video_frame() {
+            frames.push(frame);
+        } else if let Some(batch) = message.as_video_frame_batch() {
+            frames.extend(batch.frames.values().cloned());
+        }
+    }
+    Ok(frames)
+}
+
+/// Compares the frames of the journals, the messages other than frames and batches are
+/// ignored.
+///
+pub fn compare_journals(
+    baseline: impl AsRef<Path>,
+    candidate: impl AsRef<Path>,
+    tolerance: &ComparisonTolerance,
+) -> anyhow::Result<ComparisonReport> {
+    let key = |f: &VideoFrameProxy| (f.get_source_id(), f.get_pts());
+    let baseline = journal_frames(baseline.as_ref())?;
+    let mut candidate = journal_frames(candidate.as_ref())?
+        .into_iter()
+        .map(|f| (key(&f), f))
+        .collect::<HashMap<_, _>>();
+    let mut report = ComparisonReport::default();
+    for frame in baseline {
+        match candidate.remove(&key(&frame)) {
+            Some(other) => {
+                report.compared_frames += 1;
+                let comparison = compare_frames(&frame, &other, tolerance);
+                if !comparison.is_equal() {
+                    report.frames.push(comparison);
+                }
+            }
+            None => report.missing_frames.push(key(&frame)),
+        }
+    }
+    report.extra_frames = candidate.into_keys().collect();
+    report.extra_frames.sort();
+    Ok(report)
+}
+
+/// Diffs the frames of the journals with the same uuids, in the order of the baseline
+/// followed by the frames only the candidate has.
+///
+pub fn diff_journals(
+    baseline: impl AsRef<Path>,
+    candidate: impl AsRef<Path>,
+    tolerance: &ComparisonTolerance,
+) -> anyhow::Result<Vec<FrameDiff>> {
+    let baseline = journal_frames(baseline.as_ref())?;
+    let candidate_frames = journal_frames(candidate.as_ref())?;
+    let mut candidate = candidate_frames
+        .iter()
+        .map(|f| (f.get_uuid_u128(), f))
+        .collect::<HashMap<_, _>>();
+    let mut diffs = baseline
+        .iter()
+        .map(|frame| match candidate.remove(&frame.get_uuid_u128()) {
+            Some(other) => diff_frames(frame, other, tolerance),
+            None => FrameDiff::new(frame, FramePresence::BaselineOnly),
+        })
+        .collect::<Vec<_>>();
+    diffs.extend(
+        candidate_frames
+            .iter()
+            .filter(|f| candidate.contains_key(&f.get_uuid_u128()))
+            .map(|f| FrameDiff::new(f, FramePresence::CandidateOnly)),
+    );
+    Ok(diffs)
+}
+
+fn message_topic(message: &Message) -> Option<String> {
+    message
+        .as_video_frame()
+        .map(|f| f.get_source_id())
+        .or_else(|| message.as_end_of_stream().map(|e| e.source_id.clone()))
+}
+
+/// Sends the messages of the journal to the writer in the recorded order, one at a time. The
+/// topic is the source of frames and end-of-stream messages, the other messages use the
+/// topic of the previous one, or `topic` before the first frame. Returns the number of sent
+/// messages.
+///
+pub fn replay_journal(
+    path: impl AsRef<Path>,
+    writer: &SyncWriter,
+    topic: &str,
+) -> anyhow::Result<usize> {
+    let path = path.as_ref();
+    if topic.is_empty() {
+        bail!("The replay topic must not be empty");
+    }
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open the journal {}", path.display()))?;
+    let mut topic = topic.to_string();
+    let mut sent = 0;
+    for message in MessageStreamReader::new(BufReader::new(file)) {
+        let message = message?;
+        if let Some(t) = message_topic(&message) {
+            topic = t;
+        }
+        writer.send_message(&topic, &message, &[])?;
+        sent += 1;
+    }
+    Ok(sent)
+}
+
+/// Writes the messages received from the sink to the journal until nothing is received for
+/// `idle_timeout`, counted from the start or the last message, or until `max_messages` are
+/// received. Returns the number of captured messages.
+///
+pub fn capture_journal(
+    reader: &SyncReader,
+    path: impl AsRef<Path>,
+    idle_timeout: Duration,
+    max_messages: Option<usize>,
+) -> anyhow::Result<usize> {
+    let path = path.as_ref();
+    let file = File::create(path)
+        .with_context(|| format!("Failed to create the journal {}", path.display()))?;
+    let mut writer = MessageStreamWriter::new(BufWriter::new(file));
+    let mut captured = 0;
+    let mut last_received = Instant::now();
+    while max_messages.is_none_or(|max| captured < max) {
+        match reader.receive()? {
+            ReaderResult::Message { message, .. } => {
+                writer.write_message(&message)?;
+                captured += 1;
+                last_received = Instant::now();
+            }
+            ReaderResult::Timeout => {
+                if last_received.elapsed() >= idle_timeout {
+                    break;
+                }
+            }
+            ReaderResult::TooShort(_) => bail!("The sink sent a malformed message"),
+            _ => (),
+        }
+    }
+    writer.flush()?;
+    Ok(captured)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::Message;
+    use crate::primitives::attribute_value::AttributeValue;
+    use crate::primitives::object::ObjectOperations;
+    use crate::primitives::RBBox;
+    use crate::primitives::WithAttributes;
+    use crate::protobuf::MessageStreamWriter;
+    use crate::replay::{
+        compare_frames, compare_journals, diff_frames, diff_journals, ComparisonTolerance,
+        FramePresence,
+    };
+    use crate::test::gen_frame;
+    use crate::utils::uuid_v7::incremental_uuid_v7;
+
+    #[test]
+    fn test_compare_frames() -> anyhow::Result<()> {
+        let baseline = gen_frame();
+        let candidate = gen_frame();
+        let tolerance = ComparisonTolerance::default();
+        assert!(compare_frames(&baseline, &candidate, &tolerance).is_equal());
+
+        let mut object = candidate.get_object(1).unwrap();
+        let bbox = object.get_detection_box();
+        object.set_detection_box(RBBox::new(
+            bbox.get_xc() + bbox.get_width() * 0.2,
+            bbox.get_yc(),
+            bbox.get_width(),
+            bbox.get_height(),
+            None,
+        ));
+        candidate.delete_objects_with_ids(&[2]);
+        let comparison = compare_frames(&baseline, &candidate, &tolerance);
+        assert!(comparison.added.is_empty());
+        assert_eq!(comparison.removed.len(), 1);
+        assert_eq!(comparison.removed[0].id, 2);
+        assert_eq!(comparison.moved.len(), 1);
+        assert_eq!(comparison.moved[0].baseline.id, 1);
+        assert!(comparison.moved[0].iou < 0.9);
+        Ok(())
+    }
+
+    #[test]
+    fn test_compare_journals() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("savant-replay-{}", incremental_uuid_v7()));
+        std::fs::create_dir_all(&dir)?;
+        let write = |name: &str, frames: &[_]| -> anyhow::Result<_> {
+            let path = dir.join(name);
+            let mut writer = MessageStreamWriter::new(std::fs::File::create(&path)?);
+            for frame in frames {
+                writer.write_message(&Message::video_frame(frame))?;
+            }
+            writer.flush()?;
+            Ok(path)
+        };
+        let first = gen_frame();
+        let mut second = gen_frame();
+        second.set_pts(second.get_pts() + 1);
+        let mut third = gen_frame();
+        third.set_pts(third.get_pts() + 2);
+        let baseline = write("baseline", &[first.clone(), second.clone()])?;
+
+        let mut changed = gen_frame();
+        changed.set_pts(second.get_pts());
+        changed.delete_objects_with_ids(&[0]);
+        let candidate = write("candidate", &[first, changed, third])?;
+
+        let report = compare_journals(&baseline, &candidate, &ComparisonTolerance::default())?;
+        assert!(!report.is_equal());
+        assert_eq!(report.compared_frames, 2);
+        assert!(report.missing_frames.is_empty());
+        assert_eq!(report.extra_frames.len(), 1);
+        assert_eq!(report.frames.len(), 1);
+        assert_eq!(report.frames[0].removed.len(), 1);
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()?)?;
+        assert_eq!(json["compared_frames"], 2);
+
+        assert!(
+            compare_journals(&baseline, &baseline, &ComparisonTolerance::default())?.is_equal()
+        );
+
+        let diffs = diff_journals(&baseline, &candidate, &ComparisonTolerance::default())?;
+        // only the first frame has the same uuid in both journals
+        assert_eq!(diffs.len(), 4);
+        assert!(diffs[0].is_equal());
+        assert_eq!(diffs[1].presence, FramePresence::BaselineOnly);
+        assert_eq!(diffs[2].presence, FramePresence::CandidateOnly);
+        assert_eq!(diffs[3].presence, FramePresence::CandidateOnly);
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_frames() -> anyhow::Result<()> {
+        let baseline = gen_frame();
+        let mut candidate = gen_frame();
+        let tolerance = ComparisonTolerance::default();
+        assert!(diff_frames(&baseline, &candidate, &tolerance).is_equal());
+
+        candidate.set_persistent_attribute(
+            "camera",
+            "zone",
+            &None,
+            false,
+            vec![AttributeValue::string("gate", None)],
+        );
+        let mut object = candidate.get_object(1).unwrap();
+        object.set_persistent_attribute(
+            "classifier",
+            "color",
+            &None,
+            false,
+            vec![AttributeValue::string("red", None)],
+        );
+        let diff = diff_frames(&baseline, &candidate, &tolerance);
+        assert_eq!(diff.attributes.len(), 1);
+        assert!(diff.attributes[0].baseline.is_none());
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].baseline.id, 1);
+        assert_eq!(diff.changed[0].attributes[0].name, "color");
+        let json: serde_json::Value = serde_json::from_str(&diff.to_json()?)?;
+        assert_eq!(json["presence"], "both");
+        Ok(())
+    }
+}
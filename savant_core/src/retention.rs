@@ -0,0 +1,446 @@
+use crate::clip::get_clip_journal;
+use crate::metrics::{get_or_create_counter_family, get_or_create_gauge_family};
+use anyhow::bail;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use hashbrown::HashMap;
+use parking_lot::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+const DELETED_FILES_METRIC: &str = "retention_deleted_files";
+const DELETED_BYTES_METRIC: &str = "retention_deleted_bytes";
+const STORAGE_BYTES_METRIC: &str = "retention_storage_bytes";
+
+/// The limits of the recorded storage. A file is deleted when it is older than `max_age`,
+/// then the oldest files are deleted while the total size exceeds `max_size`.
+///
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RetentionPolicy {
+    /// The age by the modification time.
+    pub max_age: Option<Duration>,
+    /// The total size of the files in bytes.
+    pub max_size: Option<u64>,
+    /// The glob patterns of the file names managed, all the files when empty.
+    pub patterns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeletionReason {
+    Age,
+    Size,
+}
+
+impl DeletionReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeletionReason::Age => "age",
+            DeletionReason::Size => "size",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeletedFile {
+    pub path: PathBuf,
+    pub size: u64,
+    pub reason: DeletionReason,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RetentionReport {
+    pub deleted: Vec<DeletedFile>,
+    pub freed_bytes: u64,
+    /// The total size of the managed files left.
+    pub remaining_bytes: u64,
+    /// The pinned files and the active clip journal are never deleted, so the size limit is
+    /// not reached when they take more space than allowed.
+    pub over_limit: bool,
+}
+
+#[derive(Debug)]
+struct StoredFile {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+struct RetentionWorker {
+    shutdown: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+/// Enforces the retention policy on the directories holding the recorded journals and the
+/// exported clips, so the devices do not run out of disk when the upload falls behind. The
+/// files related to events are pinned to keep them until they are uploaded, the active
+/// clip journal (see [`crate::clip::set_clip_journal`]) is never deleted.
+///
+/// The deletions are reported with the `retention_deleted_files` and
+/// `retention_deleted_bytes` counters labelled with the name of the manager and the reason,
+/// the size of the storage with the `retention_storage_bytes` gauge.
+///
+pub struct RetentionManager {
+    name: String,
+    directories: Vec<PathBuf>,
+    policy: RetentionPolicy,
+    matcher: GlobSet,
+    /// The pinned files with the time the pin expires, `None` pins until unpinned.
+    pins: Mutex<HashMap<PathBuf, Option<SystemTime>>>,
+    worker: Mutex<Option<RetentionWorker>>,
+}
+
+impl std::fmt::Debug for RetentionManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetentionManager")
+            .field("name", &self.name)
+            .field("directories", &self.directories)
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+impl RetentionManager {
+    pub fn new(
+        name: &str,
+        directories: Vec<PathBuf>,
+        policy: RetentionPolicy,
+    ) -> anyhow::Result<Self> {
+        if directories.is_empty() {
+            bail!("At least one directory must be managed");
+        }
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &policy.patterns {
+            builder.add(Glob::new(pattern)?);
+        }
+        Ok(Self {
+            name: name.to_string(),
+            directories,
+            policy,
+            matcher: builder.build()?,
+            pins: Mutex::new(HashMap::new()),
+            worker: Mutex::new(None),
+        })
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_policy(&self) -> &RetentionPolicy {
+        &self.policy
+    }
+
+    /// Keeps the file until `until`, or until it is unpinned when `None`.
+    ///
+    pub fn pin(&self, path: impl AsRef<Path>, until: Option<SystemTime>) {
+        self.pins.lock().insert(normalize(path.as_ref()), until);
+    }
+
+    pub fn unpin(&self, path: impl AsRef<Path>) -> bool {
+        self.pins.lock().remove(&normalize(path.as_ref())).is_some()
+    }
+
+    /// The pinned files, the expired pins are dropped.
+    ///
+    pub fn get_pins(&self) -> Vec<(PathBuf, Option<SystemTime>)> {
+        let mut pins = self.pins.lock();
+        let now = SystemTime::now();
+        pins.retain(|_, until| until.is_none_or(|t| t > now));
+        pins.iter().map(|(p, u)| (p.clone(), *u)).collect()
+    }
+
+    fn is_managed(&self, path: &Path) -> bool {
+        self.policy.patterns.is_empty()
+            || path
+                .file_name()
+                .is_some_and(|name| self.matcher.is_match(Path::new(name)))
+    }
+
+    fn scan(&self, dir: &Path, files: &mut Vec<StoredFile>) -> anyhow::Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            let path = entry.path();
+            if metadata.is_dir() {
+                self.scan(&path, files)?;
+            } else if metadata.is_file() && self.is_managed(&path) {
+                files.push(StoredFile {
+                    path,
+                    size: metadata.len(),
+                    modified: metadata.modified()?,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn delete(&self, file: StoredFile, reason: DeletionReason, report: &mut RetentionReport) {
+        if let Err(e) = std::fs::remove_file(&file.path) {
+            log::warn!(
+                target: "savant_rs::retention",
+                "Failed to delete {}: {}",
+                file.path.display(),
+                e
+            );
+            report.remaining_bytes += file.size;
+            return;
+        }
+        log::debug!(
+            target: "savant_rs::retention",
+            "Deleted {} ({} bytes) by {}",
+            file.path.display(),
+            file.size,
+            reason.as_str()
+        );
+        report.freed_bytes += file.size;
+        report.deleted.push(DeletedFile {
+            path: file.path,
+            size: file.size,
+            reason,
+        });
+    }
+
+    /// Deletes the files violating the policy, the files which are pinned or the active clip
+    /// journal are kept.
+    ///
+    pub fn enforce(&self) -> anyhow::Result<RetentionReport> {
+        let mut files = Vec::new();
+        for dir in &self.directories {
+            if dir.exists() {
+                self.scan(&normalize(dir), &mut files)?;
+            }
+        }
+        let pinned = self
+            .get_pins()
+            .into_iter()
+            .map(|(path, _)| path)
+            .chain(get_clip_journal().map(|p| normalize(&p)))
+            .collect::<Vec<_>>();
+        // the oldest files first
+        files.sort_by_key(|f| f.modified);
+
+        let now = SystemTime::now();
+        let mut report = RetentionReport::default();
+        let mut kept = Vec::new();
+        let mut protected_bytes = 0;
+        for file in files {
+            if pinned.contains(&file.path) {
+                protected_bytes += file.size;
+            } else if self
+                .policy
+                .max_age
+                .is_some_and(|age| now.duration_since(file.modified).unwrap_or_default() > age)
+            {
+                self.delete(file, DeletionReason::Age, &mut report);
+            } else {
+                kept.push(file);
+            }
+        }
+
+        let mut total = protected_bytes + kept.iter().map(|f| f.size).sum::<u64>();
+        let max_size = self.policy.max_size.unwrap_or(u64::MAX);
+        for file in kept {
+            if total > max_size {
+                total -= file.size;
+                self.delete(file, DeletionReason::Size, &mut report);
+            } else {
+                report.remaining_bytes += file.size;
+            }
+        }
+        report.remaining_bytes += protected_bytes;
+        report.over_limit = report.remaining_bytes > max_size;
+        self.report_metrics(&report)?;
+        Ok(report)
+    }
+
+    fn report_metrics(&self, report: &RetentionReport) -> anyhow::Result<()> {
+        let files = get_or_create_counter_family(
+            DELETED_FILES_METRIC,
+            Some("The number of recorded files deleted by the retention policy"),
+            &["manager", "reason"],
+            None,
+        );
+        let bytes = get_or_create_counter_family(
+            DELETED_BYTES_METRIC,
+            Some("The size of recorded files deleted by the retention policy"),
+            &["manager", "reason"],
+            None,
+        );
+        for file in &report.deleted {
+            let labels = [self.name.as_str(), file.reason.as_str()];
+            files.lock().inc(1, &labels)?;
+            bytes.lock().inc(file.size, &labels)?;
+        }
+        get_or_create_gauge_family(
+            STORAGE_BYTES_METRIC,
+            Some("The size of recorded files kept by the retention policy"),
+            &["manager"],
+            None,
+        )
+        .lock()
+        .set(report.remaining_bytes as f64, &[self.name.as_str()])?;
+        Ok(())
+    }
+
+    /// Enforces the policy in the background every `period`. A running worker is replaced,
+    /// the worker stops when the manager is dropped.
+    ///
+    pub fn start(self: &Arc<Self>, period: Duration) -> anyhow::Result<()> {
+        if period.is_zero() {
+            bail!("The retention period must be greater than 0");
+        }
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let thread_shutdown = shutdown.clone();
+        let manager: Weak<Self> = Arc::downgrade(self);
+        let thread = std::thread::spawn(move || {
+            while !thread_shutdown.load(Ordering::Relaxed) {
+                let Some(manager) = manager.upgrade() else {
+                    break;
+                };
+                if let Err(e) = manager.enforce() {
+                    log::warn!(
+                        target: "savant_rs::retention",
+                        "Failed to enforce the retention policy {}: {}",
+                        manager.name,
+                        e
+                    );
+                }
+                drop(manager);
+                std::thread::park_timeout(period);
+            }
+        });
+        let previous = self
+            .worker
+            .lock()
+            .replace(RetentionWorker { shutdown, thread });
+        if let Some(worker) = previous {
+            Self::stop_worker(worker);
+        }
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        let worker = self.worker.lock().take();
+        if let Some(worker) = worker {
+            Self::stop_worker(worker);
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.worker.lock().is_some()
+    }
+
+    fn stop_worker(worker: RetentionWorker) {
+        worker.shutdown.store(true, Ordering::Relaxed);
+        worker.thread.thread().unpark();
+        if worker.thread.thread().id() == std::thread::current().id() {
+            return;
+        }
+        if worker.thread.join().is_err() {
+            log::error!(target: "savant_rs::retention", "The retention thread panicked");
+        }
+    }
+}
+
+impl Drop for RetentionManager {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clip::set_clip_journal;
+    use crate::metrics::get_counter_family;
+    use crate::retention::{DeletionReason, RetentionManager, RetentionPolicy};
+    use crate::utils::uuid_v7::incremental_uuid_v7;
+    use std::fs::{File, FileTimes};
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
+
+    fn write_file(path: &Path, size: usize, age: Duration) -> anyhow::Result<()> {
+        std::fs::write(path, vec![0u8; size])?;
+        let time = SystemTime::now() - age;
+        File::options()
+            .write(true)
+            .open(path)?
+            .set_times(FileTimes::new().set_modified(time))?;
+        Ok(())
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_enforce() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("savant-retention-{}", incremental_uuid_v7()));
+        std::fs::create_dir_all(dir.join("clips"))?;
+        let hour = Duration::from_secs(3600);
+        let expired = dir.join("expired.journal");
+        let pinned = dir.join("pinned.journal");
+        let oldest = dir.join("clips/oldest.journal");
+        let newest = dir.join("newest.journal");
+        let active = dir.join("active.journal");
+        let other = dir.join("notes.txt");
+        write_file(&expired, 100, hour * 48)?;
+        write_file(&pinned, 100, hour * 47)?;
+        write_file(&oldest, 100, hour * 3)?;
+        write_file(&newest, 100, hour * 2)?;
+        write_file(&active, 100, hour)?;
+        write_file(&other, 100, hour * 100)?;
+
+        let manager = RetentionManager::new(
+            "test",
+            vec![dir.clone()],
+            RetentionPolicy {
+                max_age: Some(hour * 24),
+                max_size: Some(300),
+                patterns: vec!["*.journal".to_string()],
+            },
+        )?;
+        manager.pin(&pinned, None);
+        set_clip_journal(Some(active.clone()));
+        let report = manager.enforce();
+        set_clip_journal(None);
+        let report = report?;
+
+        let deleted = report
+            .deleted
+            .iter()
+            .map(|f| (f.path.file_name().unwrap().to_owned(), f.reason))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            deleted,
+            vec![
+                ("expired.journal".into(), DeletionReason::Age),
+                ("oldest.journal".into(), DeletionReason::Size)
+            ]
+        );
+        assert_eq!(report.freed_bytes, 200);
+        assert_eq!(report.remaining_bytes, 300);
+        assert!(!report.over_limit);
+        for path in [&pinned, &newest, &active, &other] {
+            assert!(path.exists());
+        }
+
+        let files = get_counter_family("retention_deleted_files").unwrap();
+        assert_eq!(files.lock().get(&["test", "age"])?, Some(1));
+
+        assert!(manager.unpin(&pinned));
+        let report = manager.enforce()?;
+        assert_eq!(
+            report
+                .deleted
+                .iter()
+                .map(|f| &f.path)
+                .collect::<Vec<&PathBuf>>(),
+            vec![&std::fs::canonicalize(&dir)?.join("pinned.journal")]
+        );
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,341 @@
+use crate::message::Message;
+use crate::primitives::attribute_value::{AttributeValue, AttributeValueVariant};
+use crate::primitives::userdata::UserData;
+use crate::primitives::{Attribute, WithAttributes};
+use crate::transport::zeromq::{SyncWriter, WriterConfig, WriterResult};
+use anyhow::bail;
+use hashbrown::{HashMap, HashSet};
+
+/// The namespace of the attribute carried by the handoff messages.
+pub const HANDOFF_NAMESPACE: &str = "sharding";
+/// The name of the attribute carried by the handoff messages, its values are the role, the
+/// previous shard and the next shard.
+pub const HANDOFF_ATTRIBUTE: &str = "handoff";
+
+/// The default number of points a shard has on the ring.
+pub const DEFAULT_VIRTUAL_NODES: usize = 64;
+
+fn ring_hash(key: &str) -> u32 {
+    crc32fast::hash(key.as_bytes())
+}
+
+/// A consistent hash ring assigning sources to shards. Each shard is placed on the ring at
+/// several points, so adding or removing a shard moves only the sources of the neighbouring
+/// ring segments. The hash is stable across processes and versions.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShardRing {
+    shards: Vec<String>,
+    points: Vec<(u32, usize)>,
+}
+
+impl ShardRing {
+    pub fn new(shards: &[String], virtual_nodes: usize) -> anyhow::Result<Self> {
+        if shards.is_empty() {
+            bail!("At least one shard is required");
+        }
+        if virtual_nodes == 0 {
+            bail!("The number of virtual nodes must be greater than 0");
+        }
+        let mut unique = HashSet::new();
+        if let Some(s) = shards.iter().find(|s| !unique.insert(s.as_str())) {
+            bail!("Shard {} is listed twice", s);
+        }
+        let mut points = shards
+            .iter()
+            .enumerate()
+            .flat_map(|(i, shard)| {
+                (0..virtual_nodes).map(move |n| (ring_hash(&format!("{}#{}", shard, n)), i))
+            })
+            .collect::<Vec<_>>();
+        // equal hashes are ordered by the shard name, so the ring does not depend on the
+        // order of the shards
+        points.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| shards[a.1].cmp(&shards[b.1])));
+        Ok(Self {
+            shards: shards.to_vec(),
+            points,
+        })
+    }
+
+    pub fn get_shards(&self) -> &[String] {
+        &self.shards
+    }
+
+    /// The shard owning the source: the first point of the ring at or after the hash of the
+    /// source.
+    ///
+    pub fn shard_for(&self, source_id: &str) -> &str {
+        let hash = ring_hash(source_id);
+        let pos = self.points.partition_point(|(h, _)| *h < hash);
+        let (_, shard) = self.points[pos % self.points.len()];
+        &self.shards[shard]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandoffRole {
+    /// Sent to the previous owner, which must flush the state of the source.
+    Release,
+    /// Sent to the next owner, which starts receiving the messages of the source.
+    Acquire,
+}
+
+impl HandoffRole {
+    fn as_str(&self) -> &'static str {
+        match self {
+            HandoffRole::Release => "release",
+            HandoffRole::Acquire => "acquire",
+        }
+    }
+}
+
+/// A source moving between shards after the shard map changed.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Handoff {
+    pub source_id: String,
+    pub from: String,
+    pub to: String,
+}
+
+impl Handoff {
+    /// The user data message announcing the handoff to a shard, the message carries the
+    /// [`HANDOFF_NAMESPACE`]/[`HANDOFF_ATTRIBUTE`] attribute.
+    ///
+    pub fn to_message(&self, role: HandoffRole) -> Message {
+        let mut user_data = UserData::new(&self.source_id);
+        user_data.set_attribute(Attribute::persistent(
+            HANDOFF_NAMESPACE,
+            HANDOFF_ATTRIBUTE,
+            vec![
+                AttributeValue::string(role.as_str(), None),
+                AttributeValue::string(&self.from, None),
+                AttributeValue::string(&self.to, None),
+            ],
+            &None,
+            false,
+        ));
+        Message::user_data(user_data)
+    }
+
+    /// Recognizes a handoff message, so a pipeline can flush or restore the state of the
+    /// source.
+    ///
+    pub fn from_message(message: &Message) -> Option<(HandoffRole, Handoff)> {
+        let user_data = message.as_user_data()?;
+        let attribute = user_data.get_attribute(HANDOFF_NAMESPACE, HANDOFF_ATTRIBUTE)?;
+        let values = attribute
+            .get_values()
+            .iter()
+            .map(|v| match v.get() {
+                AttributeValueVariant::String(s) => Some(s.clone()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let [role, from, to] = values.as_slice() else {
+            return None;
+        };
+        let role = match role.as_str() {
+            "release" => HandoffRole::Release,
+            "acquire" => HandoffRole::Acquire,
+            _ => return None,
+        };
+        Some((
+            role,
+            Handoff {
+                source_id: user_data.get_source_id().to_string(),
+                from: from.clone(),
+                to: to.clone(),
+            },
+        ))
+    }
+}
+
+/// The sources changing their owner when the ring is replaced, sorted by the source.
+///
+pub fn plan_handoffs<'a>(
+    old: &ShardRing,
+    new: &ShardRing,
+    sources: impl IntoIterator<Item = &'a str>,
+) -> Vec<Handoff> {
+    let mut handoffs = sources
+        .into_iter()
+        .filter_map(|source_id| {
+            let (from, to) = (old.shard_for(source_id), new.shard_for(source_id));
+            (from != to).then(|| Handoff {
+                source_id: source_id.to_string(),
+                from: from.to_string(),
+                to: to.to_string(),
+            })
+        })
+        .collect::<Vec<_>>();
+    handoffs.sort_by(|a, b| a.source_id.cmp(&b.source_id));
+    handoffs
+}
+
+/// Routes the messages received from the upstream to the pipeline processes by the topic,
+/// which is the source of the messages. When the shard map changes, the moved sources are
+/// handed off: the previous owner receives the release message and the next owner the
+/// acquire message before any message of the source.
+///
+pub struct ShardCoordinator {
+    virtual_nodes: usize,
+    ring: ShardRing,
+    writers: HashMap<String, SyncWriter>,
+    sources: HashSet<String>,
+}
+
+impl ShardCoordinator {
+    /// Creates the coordinator with the shards named and configured by the pairs.
+    ///
+    pub fn new(shards: &[(String, WriterConfig)], virtual_nodes: usize) -> anyhow::Result<Self> {
+        let names = shards.iter().map(|(n, _)| n.clone()).collect::<Vec<_>>();
+        let ring = ShardRing::new(&names, virtual_nodes)?;
+        let mut writers = HashMap::new();
+        for (name, config) in shards {
+            writers.insert(name.clone(), SyncWriter::new(config)?);
+        }
+        Ok(Self {
+            virtual_nodes,
+            ring,
+            writers,
+            sources: HashSet::new(),
+        })
+    }
+
+    pub fn get_ring(&self) -> &ShardRing {
+        &self.ring
+    }
+
+    /// The sources routed so far.
+    ///
+    pub fn get_sources(&self) -> Vec<String> {
+        let mut sources = self.sources.iter().cloned().collect::<Vec<_>>();
+        sources.sort();
+        sources
+    }
+
+    pub fn route(
+        &mut self,
+        topic: &str,
+        message: &Message,
+        data: &[&[u8]],
+    ) -> anyhow::Result<WriterResult> {
+        let shard = self.ring.shard_for(topic);
+        let writer = self
+            .writers
+            .get(shard)
+            .ok_or_else(|| anyhow::anyhow!("Shard {} has no writer", shard))?;
+        let res = writer.send_message(topic, message, data)?;
+        if message.is_end_of_stream() {
+            self.sources.remove(topic);
+        } else if !self.sources.contains(topic) {
+            self.sources.insert(topic.to_string());
+        }
+        Ok(res)
+    }
+
+    /// Replaces the shard map: the writers of the new shards are created, the moved sources
+    /// are handed off and the writers of the removed shards are shut down. Existing shards
+    /// keep their writers, their configurations are ignored.
+    ///
+    pub fn update_shards(
+        &mut self,
+        shards: &[(String, WriterConfig)],
+    ) -> anyhow::Result<Vec<Handoff>> {
+        let names = shards.iter().map(|(n, _)| n.clone()).collect::<Vec<_>>();
+        let ring = ShardRing::new(&names, self.virtual_nodes)?;
+        for (name, config) in shards {
+            if !self.writers.contains_key(name) {
+                self.writers.insert(name.clone(), SyncWriter::new(config)?);
+            }
+        }
+        let handoffs = plan_handoffs(&self.ring, &ring, self.sources.iter().map(|s| s.as_str()));
+        for handoff in &handoffs {
+            log::info!(
+                target: "savant_rs::sharding",
+                "Handing off source {} from {} to {}",
+                handoff.source_id,
+                handoff.from,
+                handoff.to
+            );
+            for (shard, role) in [
+                (&handoff.from, HandoffRole::Release),
+                (&handoff.to, HandoffRole::Acquire),
+            ] {
+                self.writers[shard].send_message(
+                    &handoff.source_id,
+                    &handoff.to_message(role),
+                    &[],
+                )?;
+            }
+        }
+        self.ring = ring;
+        let removed = self
+            .writers
+            .keys()
+            .filter(|name| !names.contains(name))
+            .cloned()
+            .collect::<Vec<_>>();
+        for name in removed {
+            if let Some(writer) = self.writers.remove(&name) {
+                writer.shutdown()?;
+            }
+        }
+        Ok(handoffs)
+    }
+
+    pub fn shutdown(&mut self) -> anyhow::Result<()> {
+        for (_, writer) in self.writers.drain() {
+            writer.shutdown()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sharding::{plan_handoffs, Handoff, HandoffRole, ShardRing};
+
+    fn shards(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_ring() -> anyhow::Result<()> {
+        assert!(ShardRing::new(&[], 8).is_err());
+        assert!(ShardRing::new(&shards(&["a", "a"]), 8).is_err());
+        let ring = ShardRing::new(&shards(&["a", "b", "c"]), 64)?;
+        let reordered = ShardRing::new(&shards(&["c", "a", "b"]), 64)?;
+        let sources = (0..300).map(|i| format!("cam-{}", i)).collect::<Vec<_>>();
+        let mut counts = [0; 3];
+        for s in &sources {
+            let shard = ring.shard_for(s);
+            assert_eq!(shard, reordered.shard_for(s));
+            counts[(shard.as_bytes()[0] - b'a') as usize] += 1;
+        }
+        assert!(counts.iter().all(|c| *c > 30), "{:?}", counts);
+
+        // only the sources of the added shard move
+        let grown = ShardRing::new(&shards(&["a", "b", "c", "d"]), 64)?;
+        let handoffs = plan_handoffs(&ring, &grown, sources.iter().map(|s| s.as_str()));
+        assert!(!handoffs.is_empty() && handoffs.len() < 150);
+        assert!(handoffs.iter().all(|h| h.to == "d"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_handoff_message() {
+        let handoff = Handoff {
+            source_id: "cam-1".to_string(),
+            from: "a".to_string(),
+            to: "b".to_string(),
+        };
+        let message = handoff.to_message(HandoffRole::Release);
+        assert_eq!(
+            Handoff::from_message(&message),
+            Some((HandoffRole::Release, handoff))
+        );
+        assert!(Handoff::from_message(&crate::message::Message::unknown("x".into())).is_none());
+    }
+}
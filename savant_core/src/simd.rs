@@ -0,0 +1,374 @@
+use std::sync::OnceLock;
+
+/// Disables the vectorized kernels when set to `scalar`, e.g. to compare the results or the
+/// performance with the scalar code.
+///
+pub const SIMD_ENV: &str = "SAVANT_SIMD";
+
+/// The instruction set the vectorized kernels run with. It is selected once at runtime by the
+/// features of the CPU, so the same build runs on any CPU of the architecture and uses AVX2
+/// on x86-64 and NEON on ARM (e.g. Jetson) when they are available.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimdPath {
+    Scalar,
+    Avx2,
+    Neon,
+}
+
+impl SimdPath {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SimdPath::Scalar => "scalar",
+            SimdPath::Avx2 => "avx2",
+            SimdPath::Neon => "neon",
+        }
+    }
+}
+
+static SIMD_PATH: OnceLock<SimdPath> = OnceLock::new();
+
+fn detect_simd_path() -> SimdPath {
+    if std::env::var(SIMD_ENV).is_ok_and(|v| v.eq_ignore_ascii_case("scalar")) {
+        return SimdPath::Scalar;
+    }
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") {
+            return SimdPath::Avx2;
+        }
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            return SimdPath::Neon;
+        }
+    }
+    SimdPath::Scalar
+}
+
+pub fn simd_path() -> SimdPath {
+    *SIMD_PATH.get_or_init(detect_simd_path)
+}
+
+/// The vectorized kernels and the paths selected for them.
+///
+pub fn selected_simd_paths() -> Vec<(&'static str, SimdPath)> {
+    vec![("bbox_iou", simd_path()), ("color_conversion", simd_path())]
+}
+
+/// Axis-aligned boxes stored by columns, the layout the vectorized kernels load.
+///
+#[derive(Debug, Clone, Default)]
+pub struct BoxColumns {
+    left: Vec<f32>,
+    top: Vec<f32>,
+    right: Vec<f32>,
+    bottom: Vec<f32>,
+    area: Vec<f32>,
+}
+
+impl BoxColumns {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            left: Vec::with_capacity(capacity),
+            top: Vec::with_capacity(capacity),
+            right: Vec::with_capacity(capacity),
+            bottom: Vec::with_capacity(capacity),
+            area: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Adds the box given by left, top, right and bottom. The area is passed, so the results
+    /// match the boxes computing it from their own representation.
+    ///
+    pub fn push(&mut self, ltrb: [f32; 4], area: f32) {
+        self.left.push(ltrb[0]);
+        self.top.push(ltrb[1]);
+        self.right.push(ltrb[2]);
+        self.bottom.push(ltrb[3]);
+        self.area.push(area);
+    }
+
+    pub fn len(&self) -> usize {
+        self.area.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.area.is_empty()
+    }
+}
+
+fn iou_scalar(ltrb: [f32; 4], area: f32, boxes: &BoxColumns, from: usize, res: &mut [f32]) {
+    for i in from..boxes.len() {
+        let width = (ltrb[2].min(boxes.right[i]) - ltrb[0].max(boxes.left[i])).max(0.0);
+        let height = (ltrb[3].min(boxes.bottom[i]) - ltrb[1].max(boxes.top[i])).max(0.0);
+        let intersection = width * height;
+        let union = area + boxes.area[i] - intersection;
+        res[i] = if union > 0.0 {
+            intersection / union
+        } else {
+            0.0
+        };
+    }
+}
+
+/// Computes the IoU of the box with every box of the columns into `res`, which must be as
+/// long as the columns. The IoU is 0 when the union of the boxes has no area.
+///
+pub fn iou_one_to_many(ltrb: [f32; 4], area: f32, boxes: &BoxColumns, res: &mut [f32]) {
+    assert_eq!(
+        res.len(),
+        boxes.len(),
+        "The result length must match the boxes"
+    );
+    match simd_path() {
+        #[cfg(target_arch = "x86_64")]
+        // SAFETY: the path is selected only when the CPU supports AVX2
+        SimdPath::Avx2 => unsafe { x86::iou_avx2(ltrb, area, boxes, res) },
+        #[cfg(target_arch = "aarch64")]
+        // SAFETY: the path is selected only when the CPU supports NEON
+        SimdPath::Neon => unsafe { arm::iou_neon(ltrb, area, boxes, res) },
+        _ => iou_scalar(ltrb, area, boxes, 0, res),
+    }
+}
+
+const R_V: f32 = 1.402;
+const G_U: f32 = 0.344136;
+const G_V: f32 = 0.714136;
+const B_U: f32 = 1.772;
+
+/// Rounds half away from zero, the vectorized kernels round the same way.
+///
+fn to_u8(value: f32) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+fn yuv_to_rgb_scalar(l: &[f32], u: &[f32], v: &[f32], from: usize, rgb: &mut [u8]) {
+    for i in from..l.len() {
+        rgb[i * 3] = to_u8(l[i] + R_V * v[i]);
+        rgb[i * 3 + 1] = to_u8(l[i] - G_U * u[i] - G_V * v[i]);
+        rgb[i * 3 + 2] = to_u8(l[i] + B_U * u[i]);
+    }
+}
+
+/// Converts the pixels given by luma and the chroma centered around 0 to interleaved RGB with
+/// the BT.601 coefficients. `rgb` must hold three bytes per pixel.
+///
+pub fn yuv_to_rgb(l: &[f32], u: &[f32], v: &[f32], rgb: &mut [u8]) {
+    assert!(
+        u.len() == l.len() && v.len() == l.len() && rgb.len() == l.len() * 3,
+        "The planes and the result must have the same number of pixels"
+    );
+    match simd_path() {
+        #[cfg(target_arch = "x86_64")]
+        // SAFETY: the path is selected only when the CPU supports AVX2
+        SimdPath::Avx2 => unsafe { x86::yuv_to_rgb_avx2(l, u, v, rgb) },
+        #[cfg(target_arch = "aarch64")]
+        // SAFETY: the path is selected only when the CPU supports NEON
+        SimdPath::Neon => unsafe { arm::yuv_to_rgb_neon(l, u, v, rgb) },
+        _ => yuv_to_rgb_scalar(l, u, v, 0, rgb),
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use super::{iou_scalar, yuv_to_rgb_scalar, BoxColumns, B_U, G_U, G_V, R_V};
+    use std::arch::x86_64::*;
+
+    const LANES: usize = 8;
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn iou_avx2(ltrb: [f32; 4], area: f32, boxes: &BoxColumns, res: &mut [f32]) {
+        let vectorized = boxes.len() / LANES * LANES;
+        let (left, top) = (_mm256_set1_ps(ltrb[0]), _mm256_set1_ps(ltrb[1]));
+        let (right, bottom) = (_mm256_set1_ps(ltrb[2]), _mm256_set1_ps(ltrb[3]));
+        let own_area = _mm256_set1_ps(area);
+        let zero = _mm256_setzero_ps();
+        for i in (0..vectorized).step_by(LANES) {
+            let l = _mm256_loadu_ps(boxes.left.as_ptr().add(i));
+            let t = _mm256_loadu_ps(boxes.top.as_ptr().add(i));
+            let r = _mm256_loadu_ps(boxes.right.as_ptr().add(i));
+            let b = _mm256_loadu_ps(boxes.bottom.as_ptr().add(i));
+            let other_area = _mm256_loadu_ps(boxes.area.as_ptr().add(i));
+            let width = _mm256_max_ps(
+                _mm256_sub_ps(_mm256_min_ps(right, r), _mm256_max_ps(left, l)),
+                zero,
+            );
+            let height = _mm256_max_ps(
+                _mm256_sub_ps(_mm256_min_ps(bottom, b), _mm256_max_ps(top, t)),
+                zero,
+            );
+            let intersection = _mm256_mul_ps(width, height);
+            let union = _mm256_sub_ps(_mm256_add_ps(own_area, other_area), intersection);
+            let valid = _mm256_cmp_ps::<_CMP_GT_OQ>(union, zero);
+            let iou = _mm256_and_ps(_mm256_div_ps(intersection, union), valid);
+            _mm256_storeu_ps(res.as_mut_ptr().add(i), iou);
+        }
+        iou_scalar(ltrb, area, boxes, vectorized, res);
+    }
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn to_i32(value: __m256) -> __m256i {
+        // the value is clamped first, so rounding half up matches rounding half away from zero
+        let clamped = _mm256_min_ps(
+            _mm256_max_ps(value, _mm256_setzero_ps()),
+            _mm256_set1_ps(255.0),
+        );
+        let truncated = _mm256_round_ps::<{ _MM_FROUND_TO_ZERO | _MM_FROUND_NO_EXC }>(clamped);
+        let half_up =
+            _mm256_cmp_ps::<_CMP_GE_OQ>(_mm256_sub_ps(clamped, truncated), _mm256_set1_ps(0.5));
+        let rounded = _mm256_add_ps(truncated, _mm256_and_ps(half_up, _mm256_set1_ps(1.0)));
+        _mm256_cvtps_epi32(rounded)
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn yuv_to_rgb_avx2(l: &[f32], u: &[f32], v: &[f32], rgb: &mut [u8]) {
+        let vectorized = l.len() / LANES * LANES;
+        let (r_v, g_u) = (_mm256_set1_ps(R_V), _mm256_set1_ps(G_U));
+        let (g_v, b_u) = (_mm256_set1_ps(G_V), _mm256_set1_ps(B_U));
+        let mut channels = [[0i32; LANES]; 3];
+        for i in (0..vectorized).step_by(LANES) {
+            let y = _mm256_loadu_ps(l.as_ptr().add(i));
+            let cb = _mm256_loadu_ps(u.as_ptr().add(i));
+            let cr = _mm256_loadu_ps(v.as_ptr().add(i));
+            let r = _mm256_add_ps(y, _mm256_mul_ps(r_v, cr));
+            let g = _mm256_sub_ps(
+                _mm256_sub_ps(y, _mm256_mul_ps(g_u, cb)),
+                _mm256_mul_ps(g_v, cr),
+            );
+            let b = _mm256_add_ps(y, _mm256_mul_ps(b_u, cb));
+            for (channel, value) in channels.iter_mut().zip([r, g, b]) {
+                _mm256_storeu_si256(channel.as_mut_ptr() as *mut __m256i, to_i32(value));
+            }
+            for lane in 0..LANES {
+                let p = (i + lane) * 3;
+                rgb[p] = channels[0][lane] as u8;
+                rgb[p + 1] = channels[1][lane] as u8;
+                rgb[p + 2] = channels[2][lane] as u8;
+            }
+        }
+        yuv_to_rgb_scalar(l, u, v, vectorized, rgb);
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod arm {
+    use super::{iou_scalar, yuv_to_rgb_scalar, BoxColumns, B_U, G_U, G_V, R_V};
+    use std::arch::aarch64::*;
+
+    const LANES: usize = 4;
+
+    #[target_feature(enable = "neon")]
+    pub(super) unsafe fn iou_neon(ltrb: [f32; 4], area: f32, boxes: &BoxColumns, res: &mut [f32]) {
+        let vectorized = boxes.len() / LANES * LANES;
+        let (left, top) = (vdupq_n_f32(ltrb[0]), vdupq_n_f32(ltrb[1]));
+        let (right, bottom) = (vdupq_n_f32(ltrb[2]), vdupq_n_f32(ltrb[3]));
+        let own_area = vdupq_n_f32(area);
+        let zero = vdupq_n_f32(0.0);
+        for i in (0..vectorized).step_by(LANES) {
+            let l = vld1q_f32(boxes.left.as_ptr().add(i));
+            let t = vld1q_f32(boxes.top.as_ptr().add(i));
+            let r = vld1q_f32(boxes.right.as_ptr().add(i));
+            let b = vld1q_f32(boxes.bottom.as_ptr().add(i));
+            let other_area = vld1q_f32(boxes.area.as_ptr().add(i));
+            let width = vmaxq_f32(vsubq_f32(vminq_f32(right, r), vmaxq_f32(left, l)), zero);
+            let height = vmaxq_f32(vsubq_f32(vminq_f32(bottom, b), vmaxq_f32(top, t)), zero);
+            let intersection = vmulq_f32(width, height);
+            let union = vsubq_f32(vaddq_f32(own_area, other_area), intersection);
+            let valid = vcgtq_f32(union, zero);
+            let iou = vreinterpretq_f32_u32(vandq_u32(
+                vreinterpretq_u32_f32(vdivq_f32(intersection, union)),
+                valid,
+            ));
+            vst1q_f32(res.as_mut_ptr().add(i), iou);
+        }
+        iou_scalar(ltrb, area, boxes, vectorized, res);
+    }
+
+    #[target_feature(enable = "neon")]
+    unsafe fn to_u32(value: float32x4_t) -> uint32x4_t {
+        // the value is clamped first, so rounding half up matches rounding half away from zero
+        let clamped = vminq_f32(vmaxq_f32(value, vdupq_n_f32(0.0)), vdupq_n_f32(255.0));
+        let truncated = vrndq_f32(clamped);
+        let half_up = vcgeq_f32(vsubq_f32(clamped, truncated), vdupq_n_f32(0.5));
+        let one = vandq_u32(half_up, vreinterpretq_u32_f32(vdupq_n_f32(1.0)));
+        vcvtq_u32_f32(vaddq_f32(truncated, vreinterpretq_f32_u32(one)))
+    }
+
+    #[target_feature(enable = "neon")]
+    pub(super) unsafe fn yuv_to_rgb_neon(l: &[f32], u: &[f32], v: &[f32], rgb: &mut [u8]) {
+        let vectorized = l.len() / LANES * LANES;
+        let (r_v, g_u) = (vdupq_n_f32(R_V), vdupq_n_f32(G_U));
+        let (g_v, b_u) = (vdupq_n_f32(G_V), vdupq_n_f32(B_U));
+        let mut channels = [[0u32; LANES]; 3];
+        for i in (0..vectorized).step_by(LANES) {
+            let y = vld1q_f32(l.as_ptr().add(i));
+            let cb = vld1q_f32(u.as_ptr().add(i));
+            let cr = vld1q_f32(v.as_ptr().add(i));
+            let r = vaddq_f32(y, vmulq_f32(r_v, cr));
+            let g = vsubq_f32(vsubq_f32(y, vmulq_f32(g_u, cb)), vmulq_f32(g_v, cr));
+            let b = vaddq_f32(y, vmulq_f32(b_u, cb));
+            for (channel, value) in channels.iter_mut().zip([r, g, b]) {
+                vst1q_u32(channel.as_mut_ptr(), to_u32(value));
+            }
+            for lane in 0..LANES {
+                let p = (i + lane) * 3;
+                rgb[p] = channels[0][lane] as u8;
+                rgb[p + 1] = channels[1][lane] as u8;
+                rgb[p + 2] = channels[2][lane] as u8;
+            }
+        }
+        yuv_to_rgb_scalar(l, u, v, vectorized, rgb);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simd::{iou_one_to_many, iou_scalar, yuv_to_rgb, yuv_to_rgb_scalar, BoxColumns};
+
+    #[test]
+    fn test_iou_matches_scalar() {
+        let mut boxes = BoxColumns::with_capacity(19);
+        for i in 0..19 {
+            let offset = i as f32 * 3.5;
+            boxes.push([offset, offset, offset + 20.0, offset + 10.0], 200.0);
+        }
+        // a box with no area
+        boxes.push([5.0, 5.0, 5.0, 5.0], 0.0);
+        let mut res = vec![0.0; boxes.len()];
+        let mut expected = vec![0.0; boxes.len()];
+        iou_one_to_many([0.0, 0.0, 20.0, 10.0], 200.0, &boxes, &mut res);
+        iou_scalar([0.0, 0.0, 20.0, 10.0], 200.0, &boxes, 0, &mut expected);
+        assert_eq!(res, expected);
+        assert_eq!(res[0], 1.0);
+        assert_eq!(res[18], 0.0);
+        iou_one_to_many([5.0, 5.0, 5.0, 5.0], 0.0, &boxes, &mut res);
+        assert_eq!(res[19], 0.0);
+    }
+
+    #[test]
+    fn test_yuv_to_rgb_matches_scalar() {
+        let l = (0..21).map(|i| (i * 12) as f32).collect::<Vec<_>>();
+        let u = (0..21).map(|i| (i * 12) as f32 - 128.0).collect::<Vec<_>>();
+        let v = (0..21).map(|i| 128.0 - (i * 12) as f32).collect::<Vec<_>>();
+        let mut rgb = vec![0; 21 * 3];
+        let mut expected = vec![0; 21 * 3];
+        yuv_to_rgb(&l, &u, &v, &mut rgb);
+        yuv_to_rgb_scalar(&l, &u, &v, 0, &mut expected);
+        assert_eq!(rgb, expected);
+        assert_eq!(&rgb[30..33], &[131, 117, 106]);
+    }
+
+    #[test]
+    fn test_yuv_to_rgb_rounds_half_away_from_zero() {
+        let l = (0..20).map(|i| i as f32 + 0.5).collect::<Vec<_>>();
+        let chroma = vec![0.0; 20];
+        let mut rgb = vec![0; 20 * 3];
+        yuv_to_rgb(&l, &chroma, &chroma, &mut rgb);
+        let expected = (0..20u8)
+            .flat_map(|i| [i + 1, i + 1, i + 1])
+            .collect::<Vec<_>>();
+        assert_eq!(rgb, expected);
+    }
+}
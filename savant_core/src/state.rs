@@ -0,0 +1,420 @@
+use crate::metrics::get_or_create_counter_family;
+use crate::webserver::kvs::synchronous::{get_blob, set_blob};
+use crate::webserver::kvs::KvsBlob;
+use anyhow::{bail, Context};
+use hashbrown::HashMap;
+use parking_lot::Mutex;
+use prost::Message as ProstMessage;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+const STATE_SAVES_METRIC: &str = "state_saves";
+
+/// The content type of the states stored in the KVS.
+pub const CONTENT_TYPE_STATE: &str = "application/x-protobuf";
+
+pub(crate) const STATE_RECORD_VERSION: u32 = 1;
+
+/// A component keeping the state between the frames, e.g. a tracker, a counter or a gallery,
+/// which survives the restarts of the process and moves between the shards with the
+/// sources. The state is an opaque protobuf-encoded blob, the processor is responsible for
+/// its compatibility across versions.
+///
+pub trait StatefulProcessor: Send + Sync {
+    fn save_state(&self) -> anyhow::Result<Vec<u8>>;
+    fn load_state(&self, state: &[u8]) -> anyhow::Result<()>;
+}
+
+/// The state of a processor with the name it is registered with and the time it is saved.
+///
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct StateRecord {
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    #[prost(string, tag = "2")]
+    pub processor: String,
+    #[prost(uint64, tag = "3")]
+    pub saved_at_ms: u64,
+    #[prost(bytes = "vec", tag = "4")]
+    pub data: Vec<u8>,
+}
+
+/// Where [`StateManager`] keeps the states.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum StateStore {
+    /// The blobs of the namespace in the KVS, the name of the blob is the name of the
+    /// processor. The states are limited by the size of the KVS blobs and kept while the
+    /// process runs, unless the KVS snapshot is exported.
+    Kvs(String),
+    /// The `<processor>.state` files in the directory, created when missing. The files are
+    /// replaced atomically.
+    Directory(PathBuf),
+}
+
+impl StateStore {
+    fn write(&self, name: &str, data: Vec<u8>) -> anyhow::Result<()> {
+        match self {
+            StateStore::Kvs(namespace) => set_blob(
+                namespace,
+                name,
+                KvsBlob::new(CONTENT_TYPE_STATE, data),
+                None,
+            )?,
+            StateStore::Directory(dir) => {
+                std::fs::create_dir_all(dir)?;
+                let path = dir.join(format!("{}.state", name));
+                let tmp = dir.join(format!(".{}.state.tmp", name));
+                // the data and the rename are made durable, a crash leaves the previous state
+                let mut file = std::fs::File::create(&tmp)?;
+                file.write_all(&data)?;
+                file.sync_all()?;
+                drop(file);
+                std::fs::rename(&tmp, &path)?;
+                #[cfg(unix)]
+                std::fs::File::open(dir)?.sync_all()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read(&self, name: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        match self {
+            StateStore::Kvs(namespace) => Ok(get_blob(namespace, name).map(|b| b.data)),
+            StateStore::Directory(dir) => {
+                match std::fs::read(dir.join(format!("{}.state", name))) {
+                    Ok(data) => Ok(Some(data)),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                    Err(e) => Err(e.into()),
+                }
+            }
+        }
+    }
+}
+
+struct StateWorker {
+    shutdown: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+/// Saves and restores the states of the registered processors. The states are saved on
+/// demand, periodically by the background worker started with [`StateManager::start`] and
+/// once more when the worker stops, and restored when the process starts. When a source
+/// moves to another shard (see [`crate::sharding::HandoffRole`]), the previous owner passes
+/// the state of the processor to the next one with [`StateManager::export_state`] and
+/// [`StateManager::import_state`], or saves it to a store shared by the shards.
+///
+/// The saves are counted in the `state_saves` counter labelled with the name of the manager
+/// and the outcome.
+///
+pub struct StateManager {
+    name: String,
+    store: StateStore,
+    processors: Mutex<HashMap<String, Arc<dyn StatefulProcessor>>>,
+    worker: Mutex<Option<StateWorker>>,
+}
+
+impl std::fmt::Debug for StateManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StateManager")
+            .field("name", &self.name)
+            .field("store", &self.store)
+            .field("processors", &self.get_processors())
+            .finish()
+    }
+}
+
+impl StateManager {
+    pub fn new(name: &str, store: StateStore) -> Self {
+        Self {
+            name: name.to_string(),
+            store,
+            processors: Mutex::new(HashMap::new()),
+            worker: Mutex::new(None),
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_store(&self) -> &StateStore {
+        &self.store
+    }
+
+    /// Registers the processor, the name is the key of the state in the store and is unique
+    /// within the manager. The name is a file name, so it cannot contain path separators.
+    ///
+    pub fn register(
+        &self,
+        name: &str,
+        processor: Arc<dyn StatefulProcessor>,
+    ) -> anyhow::Result<()> {
+        if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+            bail!("Invalid processor name: {:?}", name);
+        }
+        let mut processors = self.processors.lock();
+        if processors.contains_key(name) {
+            bail!("Processor {} is already registered", name);
+        }
+        processors.insert(name.to_string(), processor);
+        Ok(())
+    }
+
+    pub fn unregister(&self, name: &str) -> bool {
+        self.processors.lock().remove(name).is_some()
+    }
+
+    pub fn get_processors(&self) -> Vec<String> {
+        let mut names = self.processors.lock().keys().cloned().collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    fn get_processor(&self, name: &str) -> anyhow::Result<Arc<dyn StatefulProcessor>> {
+        self.processors
+            .lock()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Processor {} is not registered", name))
+    }
+
+    /// The state of the processor with the header checked by [`StateManager::import_state`].
+    ///
+    pub fn export_state(&self, name: &str) -> anyhow::Result<Vec<u8>> {
+        let processor = self.get_processor(name)?;
+        let data = processor
+            .save_state()
+            .with_context(|| format!("Failed to save the state of {}", name))?;
+        let saved_at_ms = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        Ok(StateRecord {
+            version: STATE_RECORD_VERSION,
+            processor: name.to_string(),
+            saved_at_ms,
+            data,
+        }
+        .encode_to_vec())
+    }
+
+    /// Loads the state exported by [`StateManager::export_state`] into the processor
+    /// registered with the same name.
+    ///
+    pub fn import_state(&self, name: &str, state: &[u8]) -> anyhow::Result<()> {
+        let record = StateRecord::decode(state).context("Invalid state record")?;
+        if record.version != STATE_RECORD_VERSION {
+            bail!("Unsupported state record version: {}", record.version);
+        }
+        if record.processor != name {
+            bail!(
+                "The state of {} cannot be loaded into {}",
+                record.processor,
+                name
+            );
+        }
+        self.get_processor(name)?
+            .load_state(&record.data)
+            .with_context(|| format!("Failed to load the state of {}", name))
+    }
+
+    fn count_save(&self, outcome: &str) {
+        let res = get_or_create_counter_family(
+            STATE_SAVES_METRIC,
+            Some("The number of processor states saved"),
+            &["manager", "outcome"],
+            None,
+        )
+        .lock()
+        .inc(1, &[self.name.as_str(), outcome]);
+        if let Err(e) = res {
+            log::warn!(target: "savant_rs::state", "Failed to count the state save: {}", e);
+        }
+    }
+
+    pub fn save(&self, name: &str) -> anyhow::Result<()> {
+        let res = self
+            .export_state(name)
+            .and_then(|state| self.store.write(name, state));
+        self.count_save(if res.is_ok() { "ok" } else { "error" });
+        res
+    }
+
+    /// Saves the states of all the processors, the failures are logged. Returns the number
+    /// of the states saved.
+    ///
+    pub fn save_all(&self) -> usize {
+        let mut saved = 0;
+        for name in self.get_processors() {
+            match self.save(&name) {
+                Ok(()) => saved += 1,
+                Err(e) => log::warn!(
+                    target: "savant_rs::state",
+                    "Manager {} failed to save the state of {}: {:#}",
+                    self.name,
+                    name,
+                    e
+                ),
+            }
+        }
+        saved
+    }
+
+    /// Loads the stored state into the processor. Returns false when no state is stored.
+    ///
+    pub fn restore(&self, name: &str) -> anyhow::Result<bool> {
+        self.get_processor(name)?;
+        match self.store.read(name)? {
+            Some(state) => self.import_state(name, &state).map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    /// Restores the states of all the processors, usually when the process starts. Returns
+    /// the names of the processors restored, the first failure stops the restoration.
+    ///
+    pub fn restore_all(&self) -> anyhow::Result<Vec<String>> {
+        let mut restored = Vec::new();
+        for name in self.get_processors() {
+            if self.restore(&name)? {
+                restored.push(name);
+            }
+        }
+        Ok(restored)
+    }
+
+    /// Saves the states in the background every `period`. A running worker is replaced, the
+    /// worker stops when the manager is dropped.
+    ///
+    pub fn start(self: &Arc<Self>, period: Duration) -> anyhow::Result<()> {
+        if period.is_zero() {
+            bail!("The state saving period must be greater than 0");
+        }
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let thread_shutdown = shutdown.clone();
+        let manager: Weak<Self> = Arc::downgrade(self);
+        let thread = std::thread::spawn(move || loop {
+            std::thread::park_timeout(period);
+            if thread_shutdown.load(Ordering::Relaxed) {
+                break;
+            }
+            let Some(manager) = manager.upgrade() else {
+                break;
+            };
+            manager.save_all();
+        });
+        let previous = self.worker.lock().replace(StateWorker { shutdown, thread });
+        if let Some(worker) = previous {
+            Self::stop_worker(worker);
+        }
+        Ok(())
+    }
+
+    /// Stops the worker and saves the states.
+    ///
+    pub fn stop(&self) {
+        let worker = self.worker.lock().take();
+        if let Some(worker) = worker {
+            Self::stop_worker(worker);
+            self.save_all();
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.worker.lock().is_some()
+    }
+
+    fn stop_worker(worker: StateWorker) {
+        worker.shutdown.store(true, Ordering::Relaxed);
+        worker.thread.thread().unpark();
+        if worker.thread.thread().id() == std::thread::current().id() {
+            return;
+        }
+        if worker.thread.join().is_err() {
+            log::error!(target: "savant_rs::state", "The state saving thread panicked");
+        }
+    }
+}
+
+impl Drop for StateManager {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metrics::get_counter_family;
+    use crate::state::{StateManager, StateStore, StatefulProcessor};
+    use crate::utils::uuid_v7::incremental_uuid_v7;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Default)]
+    struct Counter(AtomicU64);
+
+    impl StatefulProcessor for Counter {
+        fn save_state(&self) -> anyhow::Result<Vec<u8>> {
+            Ok(self.0.load(Ordering::SeqCst).to_le_bytes().to_vec())
+        }
+
+        fn load_state(&self, state: &[u8]) -> anyhow::Result<()> {
+            self.0
+                .store(u64::from_le_bytes(state.try_into()?), Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_save_restore() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("savant-state-{}", incremental_uuid_v7()));
+        for store in [
+            StateStore::Directory(dir.clone()),
+            StateStore::Kvs(format!("state-{}", incremental_uuid_v7())),
+        ] {
+            let manager = StateManager::new("test", store.clone());
+            let counter = Arc::new(Counter::default());
+            manager.register("counter", counter.clone())?;
+            assert!(manager.register("counter", counter.clone()).is_err());
+            assert!(manager.register("../counter", counter.clone()).is_err());
+            assert!(!manager.restore("counter")?);
+
+            counter.0.store(42, Ordering::SeqCst);
+            assert_eq!(manager.save_all(), 1);
+
+            let restarted = StateManager::new("test", store);
+            let restored = Arc::new(Counter::default());
+            restarted.register("counter", restored.clone())?;
+            assert_eq!(restarted.restore_all()?, vec!["counter".to_string()]);
+            assert_eq!(restored.0.load(Ordering::SeqCst), 42);
+        }
+        std::fs::remove_dir_all(&dir)?;
+        let counter = get_counter_family("state_saves").unwrap();
+        assert_eq!(counter.lock().get(&["test", "ok"])?, Some(2));
+        Ok(())
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_migration() -> anyhow::Result<()> {
+        let previous = StateManager::new("previous", StateStore::Kvs("previous".to_string()));
+        let next = StateManager::new("next", StateStore::Kvs("next".to_string()));
+        let source = Arc::new(Counter(AtomicU64::new(7)));
+        let target = Arc::new(Counter::default());
+        previous.register("cam-1", source)?;
+        next.register("cam-1", target.clone())?;
+        next.register("cam-2", Arc::new(Counter::default()))?;
+
+        let state = previous.export_state("cam-1")?;
+        assert!(next.import_state("cam-2", &state).is_err());
+        next.import_state("cam-1", &state)?;
+        assert_eq!(target.0.load(Ordering::SeqCst), 7);
+        Ok(())
+    }
+}
@@ -27,6 +27,10 @@ pub enum Errors {
     BaseNameParseError(String),
     #[error("For model `{0}({1})` the `{2}({3})` object already exists and policy is set to `ErrorIfNonUnique`.")]
     DuplicateId(String, i64, String, i64),
+    #[error("The object `{0}` must be registered before its display labels are set.")]
+    UnregisteredObject(String),
+    #[error("The locale of the display label `{0}` must not be empty.")]
+    EmptyLocale(String),
 }
 
 #[derive(Debug, Clone, Default)]
@@ -35,6 +39,13 @@ pub struct SymbolMapper {
     reverse_registry: HashMap<(i64, Option<i64>), String>,
     model_next_id: i64,
     model_object_next_ids: HashMap<String, i64>,
+    display_labels: HashMap<(i64, i64), HashMap<String, String>>,
+}
+
+/// Locales are matched case-insensitively, `pt_BR` and `pt-br` are the same locale.
+///
+fn normalize_locale(locale: &str) -> String {
+    locale.replace('_', "-").to_lowercase()
 }
 
 impl SymbolMapper {
@@ -42,6 +53,7 @@ impl SymbolMapper {
         self.registry.clear();
         self.reverse_registry.clear();
         self.model_object_next_ids.clear();
+        self.display_labels.clear();
         self.model_next_id = 0;
     }
 
@@ -219,6 +231,74 @@ impl SymbolMapper {
             .get(&(model_id, Some(object_id)))
             .cloned()
     }
+
+    /// Sets the localized display labels of a registered object class, e.g.
+    /// `{"en": "Car", "de": "Auto"}`, replacing the labels set before.
+    ///
+    pub fn set_display_labels(
+        &mut self,
+        model_name: &str,
+        object_label: &str,
+        labels: &HashMap<String, String>,
+    ) -> anyhow::Result<()> {
+        let key = Self::build_model_object_key(model_name, object_label);
+        let Some(&(model_id, Some(object_id))) = self.registry.get(&key) else {
+            return Err(Errors::UnregisteredObject(key).into());
+        };
+        if let Some(label) = labels.iter().find(|(l, _)| l.is_empty()).map(|(_, v)| v) {
+            return Err(Errors::EmptyLocale(label.clone()).into());
+        }
+        self.display_labels.insert(
+            (model_id, object_id),
+            labels
+                .iter()
+                .map(|(l, v)| (normalize_locale(l), v.clone()))
+                .collect(),
+        );
+        Ok(())
+    }
+
+    pub fn get_display_labels(&self, model_id: i64, object_id: i64) -> HashMap<String, String> {
+        self.display_labels
+            .get(&(model_id, object_id))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Returns the display label of the object class for the locale. The lookup falls back
+    /// from the regional locale (`pt-BR`) to the language (`pt`) and then to the registered
+    /// label. `None` is returned only for unregistered objects.
+    ///
+    pub fn get_display_label(&self, model_id: i64, object_id: i64, locale: &str) -> Option<String> {
+        let label = self.get_object_label(model_id, object_id)?;
+        let locale = normalize_locale(locale);
+        let display_label = self
+            .display_labels
+            .get(&(model_id, object_id))
+            .and_then(|labels| {
+                labels
+                    .get(&locale)
+                    .or_else(|| locale.split('-').next().and_then(|lang| labels.get(lang)))
+            });
+        Some(display_label.cloned().unwrap_or(label))
+    }
+
+    /// The same as [`Self::get_display_label`], but the object is identified by names.
+    ///
+    pub fn get_display_label_by_name(
+        &self,
+        model_name: &str,
+        object_label: &str,
+        locale: &str,
+    ) -> Option<String> {
+        let key = Self::build_model_object_key(model_name, object_label);
+        match self.registry.get(&key) {
+            Some(&(model_id, Some(object_id))) => {
+                self.get_display_label(model_id, object_id, locale)
+            }
+            _ => None,
+        }
+    }
 }
 
 pub fn get_model_id(model_name: &str) -> anyhow::Result<i64> {
@@ -277,6 +357,20 @@ pub fn get_object_ids(model_name: &str, object_labels: Vec<String>) -> Vec<(Stri
         .collect()
 }
 
+pub fn set_display_labels(
+    model_name: &str,
+    object_label: &str,
+    labels: HashMap<String, String>,
+) -> anyhow::Result<()> {
+    let mut mapper = SYMBOL_MAPPER.lock();
+    mapper.set_display_labels(model_name, object_label, &labels)
+}
+
+pub fn get_display_label(model_id: i64, object_id: i64, locale: &str) -> Option<String> {
+    let mapper = SYMBOL_MAPPER.lock();
+    mapper.get_display_label(model_id, object_id, locale)
+}
+
 pub fn clear_symbol_maps() {
     let mut mapper = SYMBOL_MAPPER.lock();
     mapper.clear();
@@ -501,4 +595,37 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_display_labels() -> anyhow::Result<()> {
+        let mut sm = SymbolMapper::default();
+        let labels = [(s("en"), s("Car")), (s("pt_BR"), s("Carro"))]
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        assert!(sm.set_display_labels("model", "car", &labels).is_err());
+
+        let (model_id, object_id) = sm.get_object_id("model", "car")?;
+        sm.set_display_labels("model", "car", &labels)?;
+        assert_eq!(
+            sm.get_display_label(model_id, object_id, "pt-br"),
+            Some(s("Carro"))
+        );
+        assert_eq!(
+            sm.get_display_label(model_id, object_id, "en-GB"),
+            Some(s("Car"))
+        );
+        assert_eq!(
+            sm.get_display_label_by_name("model", "car", "de"),
+            Some(s("car"))
+        );
+        assert_eq!(sm.get_display_label(model_id, object_id + 1, "en"), None);
+        assert!(sm
+            .set_display_labels("model", "car", &[(s(""), s("Car"))].into_iter().collect())
+            .is_err());
+        assert_eq!(sm.get_display_labels(model_id, object_id).len(), 2);
+
+        sm.clear();
+        assert!(sm.get_display_labels(model_id, object_id).is_empty());
+        Ok(())
+    }
 }
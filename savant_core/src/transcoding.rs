@@ -0,0 +1,393 @@
+use crate::primitives::raw_content::{PixelFormat, RawLayout};
+use crate::rwlock::SavantRwLock;
+use crate::simd;
+use lazy_static::lazy_static;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+/// The format of the internal frame content, stored in the frame codec: raw formats are
+/// named `raw-<pixel format>`, like `raw-rgb8`, other codecs are encoded formats.
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Format {
+    Raw(PixelFormat),
+    Encoded(String),
+}
+
+impl Format {
+    pub const GRAY8: Format = Format::Raw(PixelFormat::Gray8);
+    pub const RGB8: Format = Format::Raw(PixelFormat::Rgb8);
+    pub const BGR8: Format = Format::Raw(PixelFormat::Bgr8);
+    pub const RGBA8: Format = Format::Raw(PixelFormat::Rgba8);
+    pub const BGRA8: Format = Format::Raw(PixelFormat::Bgra8);
+    pub const NV12: Format = Format::Raw(PixelFormat::Nv12);
+    pub const I420: Format = Format::Raw(PixelFormat::I420);
+
+    pub fn from_codec(codec: &str) -> Self {
+        PixelFormat::from_codec(codec)
+            .map(Format::Raw)
+            .unwrap_or_else(|| Format::Encoded(codec.to_string()))
+    }
+
+    pub fn codec(&self) -> String {
+        match self {
+            Format::Raw(format) => format.codec(),
+            Format::Encoded(codec) => codec.clone(),
+        }
+    }
+
+    pub fn is_raw(&self) -> bool {
+        matches!(self, Format::Raw(_))
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.codec())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum TranscodingError {
+    #[error("Only internal frame content can be transcoded")]
+    UnsupportedContent,
+    #[error("The frame has no codec, so its format is unknown")]
+    UnknownFormat,
+    #[error("Content size {size} does not match a {width}x{height} {format} frame")]
+    SizeMismatch {
+        format: Format,
+        size: usize,
+        width: i64,
+        height: i64,
+    },
+    #[error("No registered transcoder converts {from} to {to}")]
+    NoTranscoder { from: Format, to: Format },
+}
+
+/// A backend converting frame content between formats: decoders convert encoded content to
+/// raw formats, encoders convert raw formats to encoded content. Conversions between raw
+/// formats are built in and never reach the backends.
+///
+pub trait Transcoder: Send + Sync {
+    /// The unique name of the backend, like `turbojpeg` or `nvdec`.
+    ///
+    fn name(&self) -> &str;
+
+    fn supports(&self, from: &Format, to: &Format) -> bool;
+
+    fn transcode(
+        &self,
+        from: &Format,
+        to: &Format,
+        data: &[u8],
+        width: i64,
+        height: i64,
+    ) -> anyhow::Result<Vec<u8>>;
+}
+
+lazy_static! {
+    static ref TRANSCODERS: SavantRwLock<Vec<Arc<dyn Transcoder>>> = SavantRwLock::new(Vec::new());
+}
+
+/// Registers the backend, replacing the backend with the same name. The backends registered
+/// later take precedence when several of them support a conversion.
+///
+pub fn register_transcoder(transcoder: Arc<dyn Transcoder>) {
+    let mut transcoders = TRANSCODERS.write();
+    transcoders.retain(|t| t.name() != transcoder.name());
+    transcoders.insert(0, transcoder);
+}
+
+pub fn unregister_transcoder(name: &str) -> bool {
+    let mut transcoders = TRANSCODERS.write();
+    let len = transcoders.len();
+    transcoders.retain(|t| t.name() != name);
+    transcoders.len() != len
+}
+
+/// The names of the registered backends in the order of precedence.
+///
+pub fn registered_transcoders() -> Vec<String> {
+    TRANSCODERS
+        .read()
+        .iter()
+        .map(|t| t.name().to_string())
+        .collect()
+}
+
+pub fn find_transcoder(from: &Format, to: &Format) -> Option<Arc<dyn Transcoder>> {
+    TRANSCODERS
+        .read()
+        .iter()
+        .find(|t| t.supports(from, to))
+        .cloned()
+}
+
+fn check_size(format: PixelFormat, data: &[u8], width: i64, height: i64) -> anyhow::Result<()> {
+    let layout = RawLayout::packed(format, usize::try_from(width)?, usize::try_from(height)?)?;
+    if data.len() != layout.required_size()? {
+        return Err(TranscodingError::SizeMismatch {
+            format: Format::Raw(format),
+            size: data.len(),
+            width,
+            height,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// The red, green, blue and alpha of the pixel in single-plane content.
+///
+fn rgba_at(format: PixelFormat, p: &[u8]) -> [u8; 4] {
+    match format {
+        PixelFormat::Gray8 => [p[0], p[0], p[0], u8::MAX],
+        PixelFormat::Rgb8 => [p[0], p[1], p[2], u8::MAX],
+        PixelFormat::Bgr8 => [p[2], p[1], p[0], u8::MAX],
+        PixelFormat::Bgra8 => [p[2], p[1], p[0], p[3]],
+        _ => [p[0], p[1], p[2], p[3]],
+    }
+}
+
+fn push_pixel(to: PixelFormat, [r, g, b, a]: [u8; 4], res: &mut Vec<u8>) {
+    match to {
+        PixelFormat::Gray8 => {
+            res.push(((r as u32 * 77 + g as u32 * 150 + b as u32 * 29) >> 8) as u8)
+        }
+        PixelFormat::Rgb8 => res.extend_from_slice(&[r, g, b]),
+        PixelFormat::Bgr8 => res.extend_from_slice(&[b, g, r]),
+        PixelFormat::Bgra8 => res.extend_from_slice(&[b, g, r, a]),
+        _ => res.extend_from_slice(&[r, g, b, a]),
+    }
+}
+
+/// Converts a row of YUV content to RGB with the BT.601 coefficients; the row is converted
+/// at once by the vectorized kernel.
+///
+fn yuv_row_to_rgb(
+    format: PixelFormat,
+    data: &[u8],
+    width: usize,
+    height: usize,
+    y: usize,
+    planes: &mut [Vec<f32>; 3],
+    rgb: &mut [u8],
+) {
+    let (chroma_width, chroma_height) = (width.div_ceil(2), height.div_ceil(2));
+    let chroma = width * height;
+    let cy = y / 2;
+    let [l, u, v] = planes;
+    let pixels = l.iter_mut().zip(u.iter_mut()).zip(v.iter_mut());
+    for (x, ((l, u), v)) in pixels.enumerate() {
+        let cx = x / 2;
+        let (cb, cr) = match format {
+            PixelFormat::Nv12 => {
+                let i = chroma + (cy * chroma_width + cx) * 2;
+                (data[i], data[i + 1])
+            }
+            _ => {
+                let i = cy * chroma_width + cx;
+                (
+                    data[chroma + i],
+                    data[chroma + chroma_width * chroma_height + i],
+                )
+            }
+        };
+        *l = data[y * width + x] as f32;
+        *u = cb as f32 - 128.0;
+        *v = cr as f32 - 128.0;
+    }
+    let [l, u, v] = planes;
+    simd::yuv_to_rgb(l, u, v, rgb);
+}
+
+/// Converts packed raw content to a single-plane format.
+///
+fn convert_raw(
+    from: PixelFormat,
+    to: PixelFormat,
+    data: &[u8],
+    width: i64,
+    height: i64,
+) -> anyhow::Result<Vec<u8>> {
+    check_size(from, data, width, height)?;
+    let Some(bpp) = to.bytes_per_pixel() else {
+        return Err(TranscodingError::NoTranscoder {
+            from: Format::Raw(from),
+            to: Format::Raw(to),
+        }
+        .into());
+    };
+    let (width, height) = (width as usize, height as usize);
+    let mut res = Vec::with_capacity(width * height * bpp);
+    if let Some(from_bpp) = from.bytes_per_pixel() {
+        for p in data.chunks_exact(from_bpp) {
+            push_pixel(to, rgba_at(from, p), &mut res);
+        }
+        return Ok(res);
+    }
+    let mut planes = [vec![0.0; width], vec![0.0; width], vec![0.0; width]];
+    let mut rgb = vec![0; width * 3];
+    for y in 0..height {
+        yuv_row_to_rgb(from, data, width, height, y, &mut planes, &mut rgb);
+        for p in rgb.chunks_exact(3) {
+            push_pixel(to, [p[0], p[1], p[2], u8::MAX], &mut res);
+        }
+    }
+    Ok(res)
+}
+
+/// Converts packed content between the formats. Conversions to single-plane raw formats
+/// are built in, other conversions are done by a registered backend; when no backend
+/// converts to a raw format directly, the content is decoded to another raw format and
+/// converted.
+///
+pub fn transcode(
+    from: &Format,
+    to: &Format,
+    data: &[u8],
+    width: i64,
+    height: i64,
+) -> anyhow::Result<Vec<u8>> {
+    if from == to {
+        return Ok(data.to_vec());
+    }
+    if let (Format::Raw(from), Format::Raw(to)) = (from, to) {
+        if to.bytes_per_pixel().is_some() {
+            return convert_raw(*from, *to, data, width, height);
+        }
+    }
+    if let Some(transcoder) = find_transcoder(from, to) {
+        return transcoder.transcode(from, to, data, width, height);
+    }
+    let intermediate = PixelFormat::ALL.iter().find_map(|raw| {
+        let raw = Format::Raw(*raw);
+        let found = match (from, to) {
+            (Format::Encoded(_), Format::Raw(_)) => find_transcoder(from, &raw),
+            (Format::Raw(_), Format::Encoded(_)) => find_transcoder(&raw, to),
+            _ => None,
+        };
+        found.map(|t| (raw, t))
+    });
+    match (intermediate, from, to) {
+        (Some((Format::Raw(raw), transcoder)), Format::Encoded(_), Format::Raw(to)) => {
+            let decoded = transcoder.transcode(from, &Format::Raw(raw), data, width, height)?;
+            convert_raw(raw, *to, &decoded, width, height)
+        }
+        (Some((Format::Raw(raw), transcoder)), Format::Raw(from), Format::Encoded(_)) => {
+            let converted = convert_raw(*from, raw, data, width, height)?;
+            transcoder.transcode(&Format::Raw(raw), to, &converted, width, height)
+        }
+        _ => Err(TranscodingError::NoTranscoder {
+            from: from.clone(),
+            to: to.clone(),
+        }
+        .into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::primitives::frame::VideoFrameContent;
+    use crate::primitives::raw_content::{InternalFrame, PixelFormat, RawLayout};
+    use crate::test::gen_frame;
+    use crate::transcoding::{
+        register_transcoder, registered_transcoders, transcode, unregister_transcoder, Format,
+        Transcoder, TranscodingError,
+    };
+    use std::sync::Arc;
+
+    /// Decodes the test codec by repeating the single gray byte of the content.
+    struct FillDecoder;
+
+    impl Transcoder for FillDecoder {
+        fn name(&self) -> &str {
+            "fill"
+        }
+
+        fn supports(&self, from: &Format, to: &Format) -> bool {
+            *from == Format::Encoded("fill".to_string()) && *to == Format::GRAY8
+        }
+
+        fn transcode(
+            &self,
+            _from: &Format,
+            _to: &Format,
+            data: &[u8],
+            width: i64,
+            height: i64,
+        ) -> anyhow::Result<Vec<u8>> {
+            Ok(vec![data[0]; (width * height) as usize])
+        }
+    }
+
+    #[test]
+    fn test_raw_conversion() -> anyhow::Result<()> {
+        let rgb = vec![255, 0, 0, 0, 0, 255];
+        assert_eq!(
+            transcode(&Format::RGB8, &Format::BGR8, &rgb, 2, 1)?,
+            vec![0, 0, 255, 255, 0, 0]
+        );
+        assert_eq!(
+            transcode(&Format::RGB8, &Format::RGBA8, &rgb, 2, 1)?,
+            vec![255, 0, 0, 255, 0, 0, 255, 255]
+        );
+        assert_eq!(
+            transcode(&Format::RGB8, &Format::GRAY8, &rgb, 2, 1)?,
+            vec![76, 28]
+        );
+        assert!(transcode(&Format::RGB8, &Format::GRAY8, &rgb, 2, 2).is_err());
+        assert!(transcode(&Format::RGB8, &Format::NV12, &rgb, 2, 1).is_err());
+        // a 2x2 NV12 frame of neutral chroma
+        let nv12 = vec![16, 64, 128, 235, 128, 128];
+        assert_eq!(
+            transcode(&Format::NV12, &Format::GRAY8, &nv12, 2, 2)?,
+            vec![16, 64, 128, 235]
+        );
+        assert_eq!(
+            transcode(&Format::NV12, &Format::RGB8, &nv12, 2, 2)?[9..],
+            [235, 235, 235]
+        );
+        assert_eq!(Format::from_codec("RAW-RGB8"), Format::RGB8);
+        assert_eq!(Format::BGR8.codec(), "raw-bgr8");
+        assert_eq!(
+            Format::from_codec("jpeg"),
+            Format::Encoded("jpeg".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_ensure_format() -> anyhow::Result<()> {
+        let mut frame = gen_frame();
+        frame.set_width(2);
+        frame.set_height(2);
+        frame.set_codec(Some("fill".to_string()));
+        frame.set_content(VideoFrameContent::Internal(vec![7].into()));
+        let err = frame
+            .ensure_format(Format::RGB8)
+            .unwrap_err()
+            .downcast::<TranscodingError>()?;
+        assert!(matches!(err, TranscodingError::NoTranscoder { .. }));
+
+        register_transcoder(Arc::new(FillDecoder));
+        assert!(registered_transcoders().contains(&"fill".to_string()));
+        frame.ensure_format(Format::RGB8)?;
+        assert_eq!(frame.get_codec(), Some("raw-rgb8".to_string()));
+        assert_eq!(frame.get_format(), Some(Format::RGB8));
+        assert_eq!(
+            *frame.get_content(),
+            VideoFrameContent::Internal(InternalFrame::raw(
+                vec![7; 12],
+                RawLayout::packed(PixelFormat::Rgb8, 2, 2)?
+            )?)
+        );
+        assert!(unregister_transcoder("fill"));
+        assert!(!unregister_transcoder("fill"));
+
+        frame.set_content(VideoFrameContent::None);
+        assert!(frame.ensure_format(Format::GRAY8).is_err());
+        Ok(())
+    }
+}
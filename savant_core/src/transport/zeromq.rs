@@ -18,6 +18,7 @@ pub use nonblocking_writer::{NonBlockingWriter, WriteOperationResult};
 pub use reader::{Reader, ReaderResult};
 pub use reader_config::{ReaderConfig, ReaderConfigBuilder};
 use std::mem;
+#[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 pub use sync_reader::SyncReader;
 pub use sync_writer::SyncWriter;
@@ -394,6 +395,7 @@ fn create_ipc_dirs(endpoint: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[cfg(unix)]
 fn set_ipc_permissions(endpoint: &str, permissions: u32) -> anyhow::Result<()> {
     let endpoint = endpoint.strip_prefix("ipc://").unwrap();
     if endpoint.is_empty() {
@@ -407,6 +409,15 @@ fn set_ipc_permissions(endpoint: &str, permissions: u32) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[cfg(not(unix))]
+fn set_ipc_permissions(endpoint: &str, permissions: u32) -> anyhow::Result<()> {
+    bail!(
+        "Setting the permissions {:o} of the IPC endpoint {} is supported on Unix only",
+        permissions,
+        endpoint
+    );
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,6 +1,6 @@
 use crate::message::Message;
 use crate::primitives::eos::EndOfStream;
-use crate::protobuf::{deserialize, serialize};
+use crate::protobuf::{deserialize, serialize, serialize_for_sink, serialize_with_profile};
 use crate::transport::zeromq::{
     create_ipc_dirs, set_ipc_permissions, MockSocketResponder, Socket, SocketProvider,
     WriterConfig, WriterSocketType, CONFIRMATION_MESSAGE, ZMQ_LINGER,
@@ -147,7 +147,17 @@ impl<R: MockSocketResponder, P: SocketProvider<R> + Default> Writer<R, P> {
         }
         let socket = self.socket.as_mut().unwrap();
         let extra_parts_iter = extra_parts.iter().cloned();
-        let serialized_message = serialize(m)?;
+        let serialized_message = match (
+            self.config.serialization_profile(),
+            self.config.representations(),
+            self.config.content_encoding(),
+        ) {
+            (Some(profile), _, _) => serialize_with_profile(m, profile)?,
+            (None, None, None) => serialize(m)?,
+            (None, representations, content_encoding) => {
+                serialize_for_sink(m, representations.as_deref(), *content_encoding)?
+            }
+        };
         let parts = vec![topic, &serialized_message]
             .into_iter()
             .chain(extra_parts_iter)
@@ -2,6 +2,7 @@ use super::{
     parse_zmq_socket_uri, SocketType, WriterSocketType, ACK_RECEIVE_RETRIES, IPC_PERMISSIONS,
     RECEIVE_HWM, SENDER_RECEIVE_TIMEOUT, SEND_HWM, SEND_RETRIES, SEND_TIMEOUT,
 };
+use crate::primitives::content_encoding::ContentEncoding;
 use crate::utils::default_once::DefaultOnceCell;
 use anyhow::bail;
 
@@ -53,6 +54,18 @@ impl WriterConfig {
     pub fn fix_ipc_permissions(&self) -> &Option<u32> {
         self.0.fix_ipc_permissions.get_or_init()
     }
+
+    pub fn representations(&self) -> &Option<Vec<String>> {
+        self.0.representations.get_or_init()
+    }
+
+    pub fn content_encoding(&self) -> &Option<ContentEncoding> {
+        self.0.content_encoding.get_or_init()
+    }
+
+    pub fn serialization_profile(&self) -> &Option<String> {
+        self.0.serialization_profile.get_or_init()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -67,6 +80,9 @@ pub struct WriterConfigBuilder {
     send_hwm: DefaultOnceCell<i32>,
     receive_hwm: DefaultOnceCell<i32>,
     fix_ipc_permissions: DefaultOnceCell<Option<u32>>,
+    representations: DefaultOnceCell<Option<Vec<String>>>,
+    content_encoding: DefaultOnceCell<Option<ContentEncoding>>,
+    serialization_profile: DefaultOnceCell<Option<String>>,
 }
 
 impl Default for WriterConfigBuilder {
@@ -82,6 +98,9 @@ impl Default for WriterConfigBuilder {
             send_hwm: DefaultOnceCell::new(SEND_HWM),
             receive_hwm: DefaultOnceCell::new(RECEIVE_HWM),
             fix_ipc_permissions: DefaultOnceCell::new(Some(IPC_PERMISSIONS)),
+            representations: DefaultOnceCell::new(None),
+            content_encoding: DefaultOnceCell::new(None),
+            serialization_profile: DefaultOnceCell::new(None),
         }
     }
 }
@@ -91,6 +110,12 @@ impl WriterConfigBuilder {
         if self.endpoint.get_or_init().is_empty() {
             bail!("ZeroMQ endpoint is not set");
         }
+        if self.serialization_profile.get_or_init().is_some()
+            && (self.representations.get_or_init().is_some()
+                || self.content_encoding.get_or_init().is_some())
+        {
+            bail!("The serialization profile cannot be combined with the representations or the content encoding");
+        }
         Ok(WriterConfig(self))
     }
     pub fn url(self, url: &str) -> anyhow::Result<Self> {
@@ -173,6 +198,38 @@ impl WriterConfigBuilder {
         self.fix_ipc_permissions.set(permissions)?;
         Ok(self)
     }
+
+    /// The content representations of the frames sent by the writer, `None` sends all of
+    /// them.
+    ///
+    pub fn with_representations(
+        self,
+        representations: Option<Vec<String>>,
+    ) -> anyhow::Result<Self> {
+        self.representations.set(representations)?;
+        Ok(self)
+    }
+
+    /// The codec compressing the uncompressed internal content of the frames sent by the
+    /// writer, `None` sends the content as it is.
+    ///
+    pub fn with_content_encoding(
+        self,
+        content_encoding: Option<ContentEncoding>,
+    ) -> anyhow::Result<Self> {
+        self.content_encoding.set(content_encoding)?;
+        Ok(self)
+    }
+
+    /// The registered serialization profile the messages are sent with, see
+    /// [`crate::protobuf::SerializationProfile`]. The profile is resolved when a message is
+    /// sent; it selects the representations and the content encoding itself, so it cannot be
+    /// combined with the ones of the writer.
+    ///
+    pub fn with_serialization_profile(self, profile: Option<String>) -> anyhow::Result<Self> {
+        self.serialization_profile.set(profile)?;
+        Ok(self)
+    }
 }
 
 #[cfg(test)]
@@ -214,6 +271,22 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_profile_with_representations_fails() -> anyhow::Result<()> {
+        let config = WriterConfig::new()
+            .url("pub+bind:ipc:///tmp/profile")?
+            .with_serialization_profile(Some("compact".to_string()))?;
+        assert!(config
+            .with_representations(Some(vec!["preview".to_string()]))?
+            .build()
+            .is_err());
+        let config = WriterConfig::new()
+            .url("pub+bind:ipc:///tmp/profile")?
+            .with_serialization_profile(Some("compact".to_string()))?;
+        assert!(config.build().is_ok());
+        Ok(())
+    }
+
     #[test]
     fn set_fix_perms_without_bind_fails() -> anyhow::Result<()> {
         let config = WriterConfig::new()
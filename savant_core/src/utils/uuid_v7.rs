@@ -1,12 +1,15 @@
 use lazy_static::lazy_static;
 use parking_lot::Mutex;
 use rand::Rng;
-use uuid::Uuid;
+use std::sync::atomic::{AtomicBool, Ordering};
+use uuid::{Uuid, Version};
 
 lazy_static! {
     static ref LAST_UUID: Mutex<Uuid> = Mutex::new(Uuid::now_v7());
 }
 
+static RANDOM_FRAME_UUIDS: AtomicBool = AtomicBool::new(false);
+
 pub fn incremental_uuid_v7() -> Uuid {
     let uuid = Uuid::now_v7();
     let timestamp = uuid.get_timestamp();
@@ -21,6 +24,71 @@ pub fn incremental_uuid_v7() -> Uuid {
     *last_uuid
 }
 
+/// The version of the uuids generated for new frames.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FrameUuidVersion {
+    /// Random uuids, they carry no time.
+    V4,
+    /// Time-ordered uuids starting with the creation time in milliseconds, so frames can be
+    /// indexed and queried by uuid ranges.
+    #[default]
+    V7,
+}
+
+impl FrameUuidVersion {
+    pub fn generate(&self) -> Uuid {
+        match self {
+            FrameUuidVersion::V4 => Uuid::new_v4(),
+            FrameUuidVersion::V7 => incremental_uuid_v7(),
+        }
+    }
+}
+
+pub fn set_frame_uuid_version(version: FrameUuidVersion) {
+    RANDOM_FRAME_UUIDS.store(version == FrameUuidVersion::V4, Ordering::Relaxed);
+}
+
+pub fn get_frame_uuid_version() -> FrameUuidVersion {
+    if RANDOM_FRAME_UUIDS.load(Ordering::Relaxed) {
+        FrameUuidVersion::V4
+    } else {
+        FrameUuidVersion::V7
+    }
+}
+
+/// Generates the uuid of a new frame with the configured version.
+///
+pub fn frame_uuid() -> Uuid {
+    get_frame_uuid_version().generate()
+}
+
+/// The time in milliseconds since the UNIX epoch the uuid was generated at, when it is a
+/// version 7 uuid.
+///
+pub fn uuid_v7_timestamp_ms(uuid: &Uuid) -> Option<u64> {
+    if uuid.get_version() != Some(Version::SortRand) {
+        return None;
+    }
+    Some((uuid.as_u128() >> 80) as u64)
+}
+
+const VERSION_7_BITS: u128 = 0x7 << 76;
+const VARIANT_BITS: u128 = 0b10 << 62;
+const RANDOM_BITS: u128 = (0xFFF << 64) | ((1 << 62) - 1);
+
+/// The smallest and the largest version 7 uuids generated in the interval given by
+/// milliseconds since the UNIX epoch, both inclusive. Frames created in the interval have
+/// uuids between them, so a store ordered by uuid answers time queries with a range scan.
+///
+pub fn uuid_v7_range(start_ms: u64, end_ms: u64) -> (Uuid, Uuid) {
+    let timestamp = |ms: u64| ((ms as u128) & ((1 << 48) - 1)) << 80;
+    (
+        Uuid::from_u128(timestamp(start_ms) | VERSION_7_BITS | VARIANT_BITS),
+        Uuid::from_u128(timestamp(end_ms) | VERSION_7_BITS | VARIANT_BITS | RANDOM_BITS),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -33,4 +101,30 @@ mod tests {
             assert!(uuid2.as_u128() > uuid1.as_u128());
         }
     }
+
+    #[test]
+    fn test_frame_uuid_version() {
+        assert_eq!(get_frame_uuid_version(), FrameUuidVersion::V7);
+        assert!(uuid_v7_timestamp_ms(&frame_uuid()).is_some());
+        let uuid = FrameUuidVersion::V4.generate();
+        assert_eq!(uuid.get_version(), Some(Version::Random));
+        assert_eq!(uuid_v7_timestamp_ms(&uuid), None);
+    }
+
+    #[test]
+    fn test_uuid_v7_range() {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        let uuid = incremental_uuid_v7();
+        let ms = uuid_v7_timestamp_ms(&uuid).unwrap();
+        assert!(ms.abs_diff(now) < 1000);
+        let (from, to) = uuid_v7_range(ms, ms);
+        assert!(from <= uuid && uuid <= to);
+        assert_eq!(uuid_v7_timestamp_ms(&from), Some(ms));
+        assert_eq!(uuid_v7_timestamp_ms(&to), Some(ms));
+        let (_, before) = uuid_v7_range(ms - 10, ms - 1);
+        assert!(before < uuid);
+    }
 }
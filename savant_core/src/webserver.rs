@@ -1,20 +1,59 @@
+mod access;
+pub mod audit;
+#[cfg(feature = "chaos")]
+mod chaos_handlers;
+mod clip_handlers;
+#[cfg(unix)]
+pub mod control_socket;
+pub mod diagnostics;
+mod diagnostics_handlers;
+mod ha_handlers;
+mod job_handlers;
 pub mod kvs;
 mod kvs_handlers;
+pub mod lease;
+pub mod openapi;
+mod shutdown_signal;
 
 use std::sync::{Arc, OnceLock};
 use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 
+use crate::capabilities::capabilities;
 use crate::get_or_init_async_runtime;
 use crate::metrics::metric_collector::SystemMetricCollector;
 use crate::metrics::pipeline_metric_builder::PipelineMetricBuilder;
 use crate::pipeline::implementation;
+use crate::pipeline::topology::{render_topology, TopologyFormat};
 use crate::primitives::Attribute;
+use crate::webserver::access::RequestRecord;
+use crate::webserver::audit::{
+    audit, query_audit_log, AuditQuery, AUDIT_SHUTDOWN, AUDIT_STATUS_CHANGE,
+};
+#[cfg(feature = "chaos")]
+use crate::webserver::chaos_handlers::configure_chaos;
+use crate::webserver::clip_handlers::clip_handler;
+use crate::webserver::diagnostics_handlers::diagnostics_handler;
+use crate::webserver::ha_handlers::{ha_promote_handler, ha_snapshot_handler, ha_status_handler};
+use crate::webserver::job_handlers::{
+    get_job_handler, job_action_handler, job_metrics_handler, list_jobs_handler,
+    remove_job_handler, submit_job_handler,
+};
+use crate::webserver::kvs::{KvsBlob, MAX_KVS_BLOB_SIZE, MAX_KVS_SNAPSHOT_SIZE};
 use crate::webserver::kvs_handlers::{
-    delete_handler, delete_single_handler, get_handler, search_handler, search_keys_handler,
-    set_handler, set_handler_ttl,
+    acquire_lease_handler, delete_blob_handler, delete_handler, delete_single_handler,
+    export_snapshot_handler, get_blob_handler, get_handler, get_lease_handler,
+    import_snapshot_handler, release_lease_handler, search_blob_keys_handler, search_handler,
+    search_keys_handler, set_blob_handler, set_blob_handler_ttl, set_handler, set_handler_ttl,
 };
-use actix_web::{get, post, web, App, HttpResponse, HttpServer, Responder};
+use crate::webserver::openapi::openapi_spec;
+use crate::webserver::shutdown_signal::deliver_shutdown;
+pub use crate::webserver::shutdown_signal::{
+    set_shutdown_callback, set_shutdown_signal, ShutdownCallback,
+};
+use actix_web::dev::{ServerHandle, Service};
+use actix_web::{get, post, web, App, HttpRequest, HttpResponse, HttpServer, Responder};
+use derive_builder::Builder;
 use lazy_static::lazy_static;
 use log::{debug, error, info};
 use moka::future::Cache;
@@ -25,11 +64,11 @@ use tokio::task::JoinHandle;
 
 struct RecordExpiration;
 
-impl Expiry<(String, String), (Option<u64>, Attribute)> for RecordExpiration {
+impl<V> Expiry<(String, String), (Option<u64>, V)> for RecordExpiration {
     fn expire_after_create(
         &self,
         _: &(String, String),
-        value: &(Option<u64>, Attribute),
+        value: &(Option<u64>, V),
         _created_at: Instant,
     ) -> Option<Duration> {
         value.0.map(Duration::from_millis)
@@ -47,6 +86,9 @@ pub enum PipelineStatus {
 }
 
 const MAX_TTL_KVS_CAPACITY: u64 = 100_000;
+/// The total size of the blobs kept in the KVS, the least recently used blobs are evicted
+/// when it is exceeded.
+const MAX_KVS_BLOB_CAPACITY: u64 = 64 * 1024 * 1024;
 
 #[allow(clippy::type_complexity)]
 struct WsData {
@@ -55,6 +97,7 @@ struct WsData {
     shutdown_token: Arc<OnceLock<String>>,
     shutdown_status: Arc<OnceLock<bool>>,
     kvs: Arc<Cache<(String, String), (Option<u64>, Attribute)>>,
+    blobs: Arc<Cache<(String, String), (Option<u64>, KvsBlob)>>,
 }
 
 impl WsData {
@@ -63,23 +106,34 @@ impl WsData {
             .max_capacity(MAX_TTL_KVS_CAPACITY)
             .expire_after(RecordExpiration {})
             .build();
+        let blobs = Cache::builder()
+            .max_capacity(MAX_KVS_BLOB_CAPACITY)
+            .weigher(|_, (_, blob): &(Option<u64>, KvsBlob)| {
+                u32::try_from(blob.data.len()).unwrap_or(u32::MAX)
+            })
+            .expire_after(RecordExpiration {})
+            .build();
         WsData {
             pipelines: Arc::new(Mutex::new(Vec::new())),
             status: Arc::new(Mutex::new(PipelineStatus::Stopped)),
             shutdown_token: Arc::new(OnceLock::new()),
             shutdown_status: Arc::new(OnceLock::new()),
             kvs: Arc::new(cache),
+            blobs: Arc::new(blobs),
         }
     }
 
     pub fn set_status(&self, s: PipelineStatus) -> anyhow::Result<()> {
         let runtime = get_or_init_async_runtime();
         let thread_status = self.status.clone();
-        let ws_job = WS_JOB
-            .get()
-            .ok_or_else(|| anyhow::anyhow!("Web server job not started"))?;
-        if ws_job.is_finished() {
-            error!("Web server job is finished unexpectedly, cannot update status.");
+        {
+            let ws_job = WS_JOB.lock();
+            let ws_job = ws_job
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("Web server job not started"))?;
+            if ws_job.join.is_finished() {
+                error!("Web server job is finished unexpectedly, cannot update status.");
+            }
         }
         runtime.spawn(async move {
             let mut bind = thread_status.lock().await;
@@ -100,11 +154,60 @@ impl WsData {
     }
 }
 
-static WS_JOB: OnceLock<JoinHandle<()>> = OnceLock::new();
+#[derive(Builder, Debug, Clone, PartialEq)]
+pub struct WebserverConfig {
+    #[builder(default = "\"0.0.0.0\".to_string()")]
+    pub host: String,
+    #[builder(default = "8080")]
+    pub port: u16,
+    /// The number of worker threads, the number of physical CPU cores by default.
+    #[builder(default)]
+    pub workers: Option<usize>,
+}
+
+impl WebserverConfig {
+    pub fn new(port: u16) -> Self {
+        Self {
+            host: "0.0.0.0".to_string(),
+            port,
+            workers: None,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WebserverError {
+    #[error("Address {0}:{1} is already in use")]
+    PortInUse(String, u16),
+    #[error("Permission denied to bind to {0}:{1}")]
+    PermissionDenied(String, u16),
+    #[error("Address {0}:{1} is not available")]
+    AddressNotAvailable(String, u16),
+    #[error("Failed to bind to {0}:{1}: {2}")]
+    Bind(String, u16, std::io::Error),
+}
+
+impl WebserverError {
+    fn from_bind_error(config: &WebserverConfig, error: std::io::Error) -> Self {
+        let (host, port) = (config.host.clone(), config.port);
+        match error.kind() {
+            std::io::ErrorKind::AddrInUse => Self::PortInUse(host, port),
+            std::io::ErrorKind::PermissionDenied => Self::PermissionDenied(host, port),
+            std::io::ErrorKind::AddrNotAvailable => Self::AddressNotAvailable(host, port),
+            _ => Self::Bind(host, port, error),
+        }
+    }
+}
+
+struct WebserverJob {
+    config: WebserverConfig,
+    handle: ServerHandle,
+    join: JoinHandle<()>,
+}
 
 lazy_static! {
+    static ref WS_JOB: parking_lot::Mutex<Option<WebserverJob>> = parking_lot::Mutex::new(None);
     static ref WS_DATA: web::Data<WsData> = web::Data::new(WsData::new());
-    static ref PID: Mutex<i32> = Mutex::new(0);
 }
 
 pub(crate) fn register_pipeline(pipeline: Arc<implementation::Pipeline>) {
@@ -136,8 +239,27 @@ pub(crate) async fn get_registered_pipelines() -> Vec<Arc<implementation::Pipeli
     s.clone()
 }
 
+/// The aggregate readiness of the registered pipelines and the document describing the
+/// readiness of their stages.
+///
+pub(crate) async fn get_health() -> (bool, serde_json::Value) {
+    let pipelines = get_registered_pipelines()
+        .await
+        .iter()
+        .map(|p| p.get_health())
+        .collect::<Vec<_>>();
+    let ready = pipelines.iter().all(|p| p.ready);
+    (
+        ready,
+        serde_json::json!({ "ready": ready, "pipelines": pipelines }),
+    )
+}
+
 pub fn set_status(s: PipelineStatus) -> anyhow::Result<()> {
-    WS_DATA.set_status(s)
+    let details = format!("{:?}", s);
+    let res = WS_DATA.set_status(s);
+    audit(AUDIT_STATUS_CHANGE, "local", &details, res.is_ok());
+    res
 }
 
 pub async fn get_status() -> PipelineStatus {
@@ -177,6 +299,24 @@ async fn status_handler() -> impl Responder {
     HttpResponse::Ok().json(s)
 }
 
+/// The aggregate readiness of the registered pipelines: 200 when all stages are ready, 503
+/// while any stage is warming up.
+///
+#[get("/health")]
+async fn health_handler() -> HttpResponse {
+    let (ready, body) = get_health().await;
+    if ready {
+        HttpResponse::Ok().json(body)
+    } else {
+        HttpResponse::ServiceUnavailable().json(body)
+    }
+}
+
+#[get("/capabilities")]
+async fn capabilities_handler() -> impl Responder {
+    HttpResponse::Ok().json(capabilities())
+}
+
 #[derive(Deserialize)]
 enum ShutdownMode {
     #[serde(rename = "graceful")]
@@ -191,88 +331,252 @@ struct ShutdownParams {
     mode: ShutdownMode,
 }
 
-static SHUTDOWN_SIGNAL_NO: OnceLock<nix::sys::signal::Signal> = OnceLock::new();
+/// Returns the audit identity of the request: `token@<peer>` when the request is authorized
+/// by the bearer token, `anonymous@<peer>` otherwise. The `X-Requester` header is not
+/// verified, so it is only appended in parentheses.
+///
+pub(crate) fn get_requester(req: &HttpRequest) -> String {
+    format_requester(req, authorize(req).is_ok())
+}
 
-fn get_shutdown_signal() -> nix::sys::signal::Signal {
-    let signal = SHUTDOWN_SIGNAL_NO.get_or_init(|| nix::sys::signal::Signal::SIGINT);
-    *signal
+fn format_requester(req: &HttpRequest, authenticated: bool) -> String {
+    let principal = if authenticated { "token" } else { "anonymous" };
+    let peer = req
+        .peer_addr()
+        .map(|a| a.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    match req
+        .headers()
+        .get("X-Requester")
+        .and_then(|h| h.to_str().ok())
+    {
+        Some(claimed) => format!("{}@{} ({})", principal, peer, claimed),
+        None => format!("{}@{}", principal, peer),
+    }
 }
 
-pub fn set_shutdown_signal(signal: i32) -> anyhow::Result<()> {
-    let signal = nix::sys::signal::Signal::try_from(signal)
-        .map_err(|e| anyhow::anyhow!("Invalid signal number: {}", e))?;
-    SHUTDOWN_SIGNAL_NO
-        .set(signal)
-        .map_err(|s| anyhow::anyhow!("Signal already set: {}", s))
+/// Checks the `Authorization: Bearer <token>` header of a privileged request against the
+/// shutdown token, the requests are rejected when no token is set.
+///
+pub(crate) fn authorize(req: &HttpRequest) -> Result<(), HttpResponse> {
+    let Some(token) = get_shutdown_token() else {
+        return Err(HttpResponse::Forbidden()
+            .body("No shutdown token set. The operation is not supported."));
+    };
+    let provided = req
+        .headers()
+        .get(actix_web::http::header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "));
+    if provided != Some(token.as_str()) {
+        return Err(HttpResponse::Unauthorized().body("Invalid token provided."));
+    }
+    Ok(())
 }
 
 #[post("/shutdown/{token}/{mode}")]
-async fn shutdown_handler(params: web::Path<ShutdownParams>) -> HttpResponse {
+async fn shutdown_handler(req: HttpRequest, params: web::Path<ShutdownParams>) -> HttpResponse {
     let shutdown_params: ShutdownParams = params.into_inner();
+    let details = match shutdown_params.mode {
+        ShutdownMode::Notify => "graceful",
+        ShutdownMode::Signal => "signal",
+    };
     let shutdown_token = get_shutdown_token();
+    // the token of the shutdown request is passed in the path
+    let requester = format_requester(
+        &req,
+        shutdown_token.as_deref() == Some(shutdown_params.token.as_str()),
+    );
     if shutdown_token.is_none() {
+        audit(AUDIT_SHUTDOWN, &requester, details, false);
         return HttpResponse::InternalServerError()
             .body("No shutdown token set. Pipeline shutdown is not supported.");
     } else if shutdown_token.unwrap() != shutdown_params.token {
+        audit(AUDIT_SHUTDOWN, &requester, details, false);
         return HttpResponse::Unauthorized()
             .body("Invalid shutdown token provided (ignoring the command).");
     } else {
         let res = shutdown();
+        audit(AUDIT_SHUTDOWN, &requester, details, res.is_ok());
         if res.is_err() {
             return HttpResponse::InternalServerError()
                 .body("Failed to set shutdown status multiple times (already set).");
         }
-        let res = set_status(PipelineStatus::Shutdown);
+        let res = WS_DATA.set_status(PipelineStatus::Shutdown);
         if res.is_err() {
             return HttpResponse::InternalServerError().body("Failed to set pipeline status.");
         }
         if matches!(shutdown_params.mode, ShutdownMode::Signal) {
-            let pid = PID.lock().await;
-            _ = nix::sys::signal::kill(nix::unistd::Pid::from_raw(*pid), get_shutdown_signal());
+            if let Err(e) = deliver_shutdown() {
+                error!("Failed to deliver the shutdown: {}", e);
+                return HttpResponse::InternalServerError()
+                    .body(format!("Failed to deliver the shutdown: {}", e));
+            }
         }
     }
     HttpResponse::Ok().json("ok")
 }
 
-#[get("/metrics")]
-async fn metrics_handler() -> HttpResponse {
-    let content_type = "application/openmetrics-text; version=1.0.0; charset=utf-8";
-    if let Err(e) = PipelineMetricBuilder::build().await {
-        error!("Failed to build pipeline metrics: {}", e);
-        return HttpResponse::InternalServerError()
-            .content_type(content_type)
-            .body("Failed to build pipeline metrics");
-    }
+#[get("/audit")]
+async fn audit_handler(query: web::Query<AuditQuery>) -> HttpResponse {
+    HttpResponse::Ok().json(query_audit_log(&query))
+}
+
+#[get("/openapi.json")]
+async fn openapi_handler() -> HttpResponse {
+    HttpResponse::Ok().json(openapi_spec())
+}
+
+/// The pipeline and system metrics in the OpenMetrics text format.
+///
+pub(crate) async fn encode_metrics() -> anyhow::Result<String> {
+    PipelineMetricBuilder::build()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to build pipeline metrics: {}", e))?;
     let mut registry = prometheus_client::registry::Registry::default();
     let boxed_collector = Box::new(SystemMetricCollector);
     registry.register_collector(boxed_collector);
     let mut body = String::new();
-    if let Err(e) = encode(&mut body, &registry) {
-        error!("Failed to encode metrics: {}", e);
-        return HttpResponse::InternalServerError()
-            .content_type(content_type)
-            .body("Failed to encode metrics");
+    encode(&mut body, &registry).map_err(|e| anyhow::anyhow!("Failed to encode metrics: {}", e))?;
+    Ok(body)
+}
+
+#[get("/metrics")]
+async fn metrics_handler() -> HttpResponse {
+    let content_type = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+    match encode_metrics().await {
+        Ok(body) => HttpResponse::Ok().content_type(content_type).body(body),
+        Err(e) => {
+            error!("{}", e);
+            HttpResponse::InternalServerError()
+                .content_type(content_type)
+                .body(e.to_string())
+        }
     }
-    HttpResponse::Ok().content_type(content_type).body(body)
 }
 
-pub fn init_webserver(port: u16) -> anyhow::Result<()> {
-    let pid = std::process::id() as i32;
-    let rt = get_or_init_async_runtime();
-    rt.block_on(async {
-        let mut bind = PID.lock().await;
-        *bind = pid;
-    });
+#[get("/pipelines/topology/{format}")]
+async fn topology_handler(format: web::Path<String>) -> HttpResponse {
+    let format = match format.parse::<TopologyFormat>() {
+        Ok(format) => format,
+        Err(e) => return HttpResponse::BadRequest().body(e.to_string()),
+    };
+    let topologies = get_registered_pipelines()
+        .await
+        .iter()
+        .map(|p| p.get_topology())
+        .collect::<Vec<_>>();
+    let content_type = match format {
+        TopologyFormat::Dot => "text/vnd.graphviz; charset=utf-8",
+        TopologyFormat::Mermaid => "text/plain; charset=utf-8",
+    };
+    HttpResponse::Ok()
+        .content_type(content_type)
+        .body(render_topology(&topologies, format))
+}
+
+#[cfg(not(feature = "chaos"))]
+fn configure_chaos(_: &mut web::ServiceConfig) {}
 
-    if WS_JOB.get().is_some() {
-        return Ok(());
+/// Starts the webserver on all interfaces, see [`init_webserver_with_config`].
+///
+pub fn init_webserver(port: u16) -> Result<(), WebserverError> {
+    init_webserver_with_config(WebserverConfig::new(port))
+}
+
+/// Starts the webserver. The socket is bound before the function returns, so binding errors
+/// are reported to the caller. Calling the function when the webserver runs with the same
+/// configuration does nothing, with a different configuration restarts the webserver. The
+/// running webserver is stopped only when the new one is bound and keeps serving when the
+/// new configuration fails to bind.
+///
+pub fn init_webserver_with_config(config: WebserverConfig) -> Result<(), WebserverError> {
+    let previous = {
+        let mut ws_job = WS_JOB.lock();
+        match ws_job.as_ref() {
+            Some(job) if job.config == config && !job.join.is_finished() => return Ok(()),
+            _ => ws_job.take(),
+        }
+    };
+    // the lock is released while stopping, the handlers being completed may need it
+    let job = match (start_job(&config), previous) {
+        (Ok(job), previous) => {
+            if let Some(previous) = previous {
+                info!("Restarting web server with {:?}.", config);
+                stop_job(previous);
+            }
+            job
+        }
+        // the running webserver holds the port, it is released before binding again
+        (Err(WebserverError::PortInUse(..)), Some(previous))
+            if previous.config.port == config.port && !previous.join.is_finished() =>
+        {
+            info!("Restarting web server with {:?}.", config);
+            let previous_config = previous.config.clone();
+            stop_job(previous);
+            match start_job(&config) {
+                Ok(job) => job,
+                Err(e) => {
+                    match start_job(&previous_config) {
+                        Ok(job) => install_job(job),
+                        Err(e) => error!("Failed to restore the web server: {}", e),
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        (Err(e), previous) => {
+            if let Some(previous) = previous {
+                install_job(previous);
+            }
+            return Err(e);
+        }
+    };
+    install_job(job);
+    Ok(())
+}
+
+fn install_job(job: WebserverJob) {
+    let replaced = WS_JOB.lock().replace(job);
+    if let Some(job) = replaced {
+        stop_job(job);
     }
-    let job_id = rt.spawn(async move {
-        HttpServer::new(move || {
+}
+
+fn start_job(config: &WebserverConfig) -> Result<WebserverJob, WebserverError> {
+    let rt = get_or_init_async_runtime();
+    let server = rt.block_on(async {
+        let server = HttpServer::new(move || {
             App::new()
+                .wrap_fn(|req, srv| {
+                    let record = RequestRecord::start(&req);
+                    let response = srv.call(req);
+                    async move {
+                        let response = response.await;
+                        record.finish(match &response {
+                            Ok(r) => r.status().as_u16(),
+                            Err(e) => e.as_response_error().status_code().as_u16(),
+                        });
+                        response
+                    }
+                })
                 .service(status_handler)
+                .service(health_handler)
+                .service(capabilities_handler)
                 .service(shutdown_handler)
+                .service(audit_handler)
+                .service(openapi_handler)
                 .service(metrics_handler)
+                .service(topology_handler)
+                .service(diagnostics_handler)
+                .service(clip_handler)
+                .service(submit_job_handler)
+                .service(list_jobs_handler)
+                .service(job_metrics_handler)
+                .service(get_job_handler)
+                .service(job_action_handler)
+                .service(remove_job_handler)
+                .configure(configure_chaos)
                 .service(set_handler)
                 .service(set_handler_ttl)
                 .service(delete_handler)
@@ -280,25 +584,79 @@ pub fn init_webserver(port: u16) -> anyhow::Result<()> {
                 .service(search_handler)
                 .service(get_handler)
                 .service(search_keys_handler)
-        })
-        .bind(("0.0.0.0", port))
-        .expect("Failed to bind to host:port")
-        .run()
-        .await
-        .expect("Failed to run server");
-        error!("Status web server stopped unexpectedly.");
+                .service(set_blob_handler)
+                .service(set_blob_handler_ttl)
+                .service(get_blob_handler)
+                .service(delete_blob_handler)
+                .service(search_blob_keys_handler)
+                .service(acquire_lease_handler)
+                .service(release_lease_handler)
+                .service(get_lease_handler)
+                .service(ha_status_handler)
+                .service(ha_promote_handler)
+                .service(
+                    web::resource("/ha/snapshot")
+                        .app_data(web::PayloadConfig::new(MAX_KVS_SNAPSHOT_SIZE))
+                        .route(web::post().to(ha_snapshot_handler)),
+                )
+                .service(
+                    web::resource("/kvs/snapshot")
+                        .app_data(web::PayloadConfig::new(MAX_KVS_SNAPSHOT_SIZE))
+                        .route(web::get().to(export_snapshot_handler))
+                        .route(web::post().to(import_snapshot_handler)),
+                )
+                .app_data(web::PayloadConfig::new(MAX_KVS_BLOB_SIZE))
+        });
+        let server = match config.workers {
+            Some(workers) => server.workers(workers),
+            None => server,
+        };
+        server
+            .bind((config.host.as_str(), config.port))
+            .map(|s| s.run())
+            .map_err(|e| WebserverError::from_bind_error(config, e))
+    })?;
+    let handle = server.handle();
+    let join = rt.spawn(async move {
+        if let Err(e) = server.await {
+            error!("Status web server failed: {}", e);
+        }
+        info!("Status web server stopped.");
+    });
+    Ok(WebserverJob {
+        config: config.clone(),
+        handle,
+        join,
+    })
+}
+
+fn stop_job(job: WebserverJob) {
+    let rt = get_or_init_async_runtime();
+    rt.block_on(async {
+        job.handle.stop(true).await;
+        _ = job.join.await;
     });
-    WS_JOB.get_or_init(|| job_id);
-    Ok(())
 }
 
+/// Stops the webserver and releases its socket, does nothing when the webserver is not
+/// started.
+///
 pub fn stop_webserver() {
-    let ws_job = WS_JOB.get().expect("Web server job not started");
-    ws_job.abort();
+    let job = WS_JOB.lock().take();
+    if let Some(job) = job {
+        stop_job(job);
+    }
+}
+
+/// The configuration of the running webserver.
+///
+pub fn get_webserver_config() -> Option<WebserverConfig> {
+    WS_JOB.lock().as_ref().map(|j| j.config.clone())
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::capabilities::{capabilities, Capabilities};
     use crate::get_or_init_async_runtime;
     use crate::metrics::{
         delete_metric_family, get_or_create_counter_family, get_or_create_gauge_family,
@@ -312,8 +670,9 @@ mod tests {
     use crate::webserver::kvs::synchronous::get_attribute;
     use crate::webserver::kvs::synchronous::set_attributes;
     use crate::webserver::{
-        init_webserver, register_pipeline, set_shutdown_token, set_status, stop_webserver,
-        PipelineStatus,
+        get_webserver_config, init_webserver, init_webserver_with_config, register_pipeline,
+        set_shutdown_token, set_status, stop_webserver, PipelineStatus, WebserverConfigBuilder,
+        WebserverError,
     };
     use hashbrown::HashMap;
     use prometheus_client::registry::Unit;
@@ -324,6 +683,55 @@ mod tests {
 
     const TOKEN: &str = "12345";
 
+    #[test]
+    #[serial_test::serial]
+    fn test_init_and_reinit() -> anyhow::Result<()> {
+        let busy = std::net::TcpListener::bind("0.0.0.0:0")?;
+        let port = busy.local_addr()?.port();
+        assert!(matches!(
+            init_webserver(port),
+            Err(WebserverError::PortInUse(_, p)) if p == port
+        ));
+        assert!(get_webserver_config().is_none());
+
+        init_webserver(8888)?;
+        init_webserver(8888)?;
+        let config = WebserverConfigBuilder::default()
+            .host("127.0.0.1".to_string())
+            .port(8889)
+            .workers(Some(1))
+            .build()?;
+        init_webserver_with_config(config.clone())?;
+        assert_eq!(get_webserver_config(), Some(config));
+        let r = reqwest::blocking::get("http://127.0.0.1:8889/status")?;
+        assert_eq!(r.status(), 200);
+        assert!(reqwest::blocking::get("http://127.0.0.1:8888/status").is_err());
+
+        // the running webserver keeps serving when the new configuration fails to bind
+        assert!(matches!(
+            init_webserver(port),
+            Err(WebserverError::PortInUse(_, p)) if p == port
+        ));
+        assert_eq!(get_webserver_config(), Some(config.clone()));
+        let r = reqwest::blocking::get("http://127.0.0.1:8889/status")?;
+        assert_eq!(r.status(), 200);
+        // the port of the running webserver is reused
+        let config = WebserverConfigBuilder::default()
+            .host("127.0.0.1".to_string())
+            .port(8889)
+            .workers(Some(2))
+            .build()?;
+        init_webserver_with_config(config.clone())?;
+        assert_eq!(get_webserver_config(), Some(config));
+        let r = reqwest::blocking::get("http://127.0.0.1:8889/status")?;
+        assert_eq!(r.status(), 200);
+
+        stop_webserver();
+        assert!(get_webserver_config().is_none());
+        stop_webserver();
+        Ok(())
+    }
+
     #[test]
     #[serial_test::serial]
     fn test_attributes_abi_to_api() -> anyhow::Result<()> {
@@ -496,6 +904,10 @@ mod tests {
         assert_eq!(r.status(), 200);
         let s: PipelineStatus = r.json()?;
         assert!(matches!(s, PipelineStatus::Running));
+        let r = reqwest::blocking::get("http://localhost:8888/capabilities")?;
+        assert_eq!(r.status(), 200);
+        let caps: Capabilities = r.json()?;
+        assert_eq!(caps, capabilities());
         stop_webserver();
         Ok(())
     }
@@ -615,6 +1027,8 @@ mod tests {
         assert!(text.contains("metric_gauge_Time"));
         assert!(text.contains("hello"));
         assert!(text.contains("stage_object_counter_total"));
+        assert!(text.contains("webserver_request_latency_seconds_bucket"));
+        assert!(text.contains(r#"route="/status""#));
         delete_metric_family("metric_counter");
         delete_metric_family("metric_gauge");
         stop_webserver();
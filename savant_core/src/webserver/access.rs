@@ -0,0 +1,95 @@
+use crate::metrics::get_or_create_histogram_family;
+use crate::otlp::PropagatedContext;
+use actix_web::dev::ServiceRequest;
+use log::info;
+use opentelemetry::trace::{SpanBuilder, SpanKind, Status, TraceContextExt, Tracer};
+use opentelemetry::{global, Context, KeyValue};
+use std::time::Instant;
+
+pub const REQUEST_LATENCY_METRIC: &str = "webserver_request_latency";
+const LATENCY_BOUNDS: &[f64] = &[
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5,
+];
+
+/// A request being served: the span linked to the context propagated by the client, the
+/// access log entry and the latency observation are completed with the response status.
+/// Requests are identified by the route template rather than by the path, so path
+/// parameters (e.g. the shutdown token) do not leak into logs, spans and metric labels.
+///
+pub(crate) struct RequestRecord {
+    method: String,
+    route: String,
+    peer: String,
+    started: Instant,
+    context: Context,
+}
+
+impl RequestRecord {
+    pub fn start(req: &ServiceRequest) -> Self {
+        let method = req.method().to_string();
+        let route = req
+            .match_pattern()
+            .unwrap_or_else(|| "unmatched".to_string());
+        let peer = req
+            .connection_info()
+            .peer_addr()
+            .unwrap_or("unknown")
+            .to_string();
+        let headers = PropagatedContext(
+            req.headers()
+                .iter()
+                .filter_map(|(k, v)| {
+                    v.to_str()
+                        .ok()
+                        .map(|v| (k.as_str().to_string(), v.to_string()))
+                })
+                .collect(),
+        );
+        let parent = headers.extract();
+        let span = global::tracer("webserver").build_with_context(
+            SpanBuilder::from_name(format!("{} {}", method, route))
+                .with_kind(SpanKind::Server)
+                .with_attributes(vec![
+                    KeyValue::new("http.request.method", method.clone()),
+                    KeyValue::new("http.route", route.clone()),
+                ]),
+            &parent,
+        );
+        Self {
+            method,
+            route,
+            peer,
+            started: Instant::now(),
+            context: parent.with_span(span),
+        }
+    }
+
+    pub fn finish(self, status: u16) {
+        let latency = self.started.elapsed().as_secs_f64();
+        let span = self.context.span();
+        span.set_attribute(KeyValue::new("http.response.status_code", status as i64));
+        if status >= 500 {
+            span.set_status(Status::error(format!("HTTP {}", status)));
+        }
+        span.end();
+        info!(
+            target: "savant_core::webserver::access",
+            "method={} route={} status={} latency_ms={:.3} peer={}",
+            self.method,
+            self.route,
+            status,
+            latency * 1000.0,
+            self.peer
+        );
+        let histogram = get_or_create_histogram_family(
+            REQUEST_LATENCY_METRIC,
+            Some("Latency of webserver requests"),
+            &["method", "route"],
+            Some(prometheus_client::registry::Unit::Seconds),
+            LATENCY_BOUNDS,
+        );
+        _ = histogram
+            .lock()
+            .observe(latency, &[&self.method, &self.route]);
+    }
+}
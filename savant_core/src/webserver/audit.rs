@@ -0,0 +1,328 @@
+use crate::clock::system_now_ms;
+use anyhow::Result;
+use crossbeam::channel::{Receiver, Sender};
+use lazy_static::lazy_static;
+use log::error;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
+
+pub const DEFAULT_AUDIT_CAPACITY: usize = 1000;
+
+pub const AUDIT_SHUTDOWN: &str = "shutdown";
+pub const AUDIT_KVS_DELETE: &str = "kvs_delete";
+pub const AUDIT_KVS_IMPORT: &str = "kvs_import";
+pub const AUDIT_STATUS_CHANGE: &str = "status_change";
+pub const AUDIT_CONFIG_RELOAD: &str = "config_reload";
+pub const AUDIT_CHAOS: &str = "chaos";
+pub const AUDIT_HA_PROMOTE: &str = "ha_promote";
+pub const AUDIT_DIAGNOSTICS: &str = "diagnostics";
+pub const AUDIT_JOB: &str = "job";
+
+lazy_static! {
+    static ref AUDIT_LOG: Mutex<AuditLog> = Mutex::new(AuditLog::new(DEFAULT_AUDIT_CAPACITY));
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub seq_id: u64,
+    /// The time of the operation in milliseconds since the UNIX epoch.
+    pub timestamp_ms: u64,
+    pub operation: String,
+    /// The identity of the requester: `token@<peer>` or `anonymous@<peer>` for HTTP requests
+    /// depending on whether the request is authorized, followed by the unverified
+    /// `X-Requester` header in parentheses when it is set; `local` for operations invoked by
+    /// the process itself.
+    pub requester: String,
+    pub details: String,
+    /// Whether the operation was performed or rejected.
+    pub accepted: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct AuditQuery {
+    pub operation: Option<String>,
+    pub since_ms: Option<u64>,
+    /// The maximum number of the most recent matching records.
+    pub limit: Option<usize>,
+}
+
+/// A ring buffer of control-plane operations. When a path is set, records are appended to
+/// the file as JSON lines and the last records are loaded back on open; the file is compacted
+/// to the buffer content when it grows to twice the capacity. The file is written by a
+/// background thread, so recording an operation never blocks on the disk.
+///
+#[derive(Debug)]
+pub struct AuditLog {
+    capacity: usize,
+    records: VecDeque<AuditRecord>,
+    next_seq_id: u64,
+    writer: Option<AuditWriter>,
+    persisted: usize,
+}
+
+#[derive(Debug)]
+enum AuditWrite {
+    Append(String),
+    /// Replaces the file with the lines.
+    Compact(Vec<String>),
+    Flush(Sender<()>),
+}
+
+#[derive(Debug)]
+struct AuditWriter {
+    sender: Option<Sender<AuditWrite>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl AuditWriter {
+    fn start(path: PathBuf) -> Self {
+        let (sender, receiver) = crossbeam::channel::unbounded();
+        let thread = std::thread::spawn(move || write_audit_file(&path, receiver));
+        Self {
+            sender: Some(sender),
+            thread: Some(thread),
+        }
+    }
+
+    fn send(&self, write: AuditWrite) {
+        if let Some(sender) = &self.sender {
+            if sender.send(write).is_err() {
+                error!("The audit log writer is stopped");
+            }
+        }
+    }
+}
+
+impl Drop for AuditWriter {
+    fn drop(&mut self) {
+        // the thread writes the queued records and exits when the channel is closed
+        self.sender.take();
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                error!("The audit log writer panicked");
+            }
+        }
+    }
+}
+
+fn write_audit_file(path: &Path, receiver: Receiver<AuditWrite>) {
+    for write in receiver {
+        let res = match write {
+            AuditWrite::Append(line) => OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| writeln!(file, "{}", line)),
+            AuditWrite::Compact(lines) => compact_audit_file(path, &lines),
+            AuditWrite::Flush(done) => {
+                let _ = done.send(());
+                Ok(())
+            }
+        };
+        if let Err(e) = res {
+            error!("Failed to persist the audit log {}: {}", path.display(), e);
+        }
+    }
+}
+
+fn compact_audit_file(path: &Path, lines: &[String]) -> std::io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    let mut file = File::create(&tmp)?;
+    for line in lines {
+        writeln!(file, "{}", line)?;
+    }
+    file.sync_all()?;
+    std::fs::rename(&tmp, path)
+}
+
+impl AuditLog {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            records: VecDeque::with_capacity(capacity),
+            next_seq_id: 0,
+            writer: None,
+            persisted: 0,
+        }
+    }
+
+    /// Creates the log persisted to the file, loading the last records from it.
+    ///
+    pub fn open(path: &Path, capacity: usize) -> Result<Self> {
+        let mut log = Self::new(capacity);
+        if path.exists() {
+            for line in BufReader::new(File::open(path)?).lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let record: AuditRecord = serde_json::from_str(&line)?;
+                log.next_seq_id = log.next_seq_id.max(record.seq_id + 1);
+                log.push(record);
+                log.persisted += 1;
+            }
+        }
+        log.writer = Some(AuditWriter::start(path.to_path_buf()));
+        Ok(log)
+    }
+
+    fn push(&mut self, record: AuditRecord) {
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+
+    fn persist(&mut self, record: &AuditRecord) -> Result<()> {
+        let Some(writer) = &self.writer else {
+            return Ok(());
+        };
+        if self.persisted >= self.capacity * 2 {
+            let lines = self
+                .records
+                .iter()
+                .map(serde_json::to_string)
+                .collect::<Result<Vec<_>, _>>()?;
+            writer.send(AuditWrite::Compact(lines));
+            self.persisted = self.records.len();
+        } else {
+            writer.send(AuditWrite::Append(serde_json::to_string(record)?));
+            self.persisted += 1;
+        }
+        Ok(())
+    }
+
+    /// Waits until the records are written to the file.
+    ///
+    pub fn flush(&self) {
+        let Some(writer) = &self.writer else {
+            return;
+        };
+        let (done, wait) = crossbeam::channel::bounded(1);
+        writer.send(AuditWrite::Flush(done));
+        let _ = wait.recv();
+    }
+
+    pub fn record(
+        &mut self,
+        operation: &str,
+        requester: &str,
+        details: &str,
+        accepted: bool,
+    ) -> AuditRecord {
+        let record = AuditRecord {
+            seq_id: self.next_seq_id,
+            timestamp_ms: system_now_ms(),
+            operation: operation.to_string(),
+            requester: requester.to_string(),
+            details: details.to_string(),
+            accepted,
+        };
+        self.next_seq_id += 1;
+        self.push(record.clone());
+        if let Err(e) = self.persist(&record) {
+            error!("Failed to persist audit record {}: {}", record.seq_id, e);
+        }
+        record
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Returns the matching records, the oldest first.
+    ///
+    pub fn query(&self, query: &AuditQuery) -> Vec<AuditRecord> {
+        let mut records = self
+            .records
+            .iter()
+            .rev()
+            .filter(|r| query.operation.as_ref().is_none_or(|o| &r.operation == o))
+            .filter(|r| query.since_ms.is_none_or(|s| r.timestamp_ms >= s))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect::<Vec<_>>();
+        records.reverse();
+        records
+    }
+}
+
+/// Replaces the process audit log, `None` keeps the records in memory only.
+///
+pub fn configure_audit_log(path: Option<&Path>, capacity: usize) -> Result<()> {
+    let log = match path {
+        Some(path) => AuditLog::open(path, capacity)?,
+        None => AuditLog::new(capacity),
+    };
+    // the replaced log finishes writing its file outside of the lock
+    let replaced = std::mem::replace(&mut *AUDIT_LOG.lock(), log);
+    drop(replaced);
+    Ok(())
+}
+
+/// Records a control-plane operation in the process audit log.
+///
+pub fn audit(operation: &str, requester: &str, details: &str, accepted: bool) -> AuditRecord {
+    AUDIT_LOG
+        .lock()
+        .record(operation, requester, details, accepted)
+}
+
+pub fn query_audit_log(query: &AuditQuery) -> Vec<AuditRecord> {
+    AUDIT_LOG.lock().query(query)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::uuid_v7::incremental_uuid_v7;
+    use crate::webserver::audit::{AuditLog, AuditQuery, AUDIT_KVS_DELETE, AUDIT_SHUTDOWN};
+
+    #[test]
+    fn test_ring_buffer_and_persistence() -> anyhow::Result<()> {
+        let path =
+            std::env::temp_dir().join(format!("savant-audit-{}.jsonl", incremental_uuid_v7()));
+        let mut log = AuditLog::open(&path, 3)?;
+        for i in 0..10 {
+            log.record(AUDIT_KVS_DELETE, "10.0.0.1", &format!("ns/{}", i), true);
+        }
+        log.record(AUDIT_SHUTDOWN, "operator", "graceful", false);
+        assert_eq!(log.len(), 3);
+
+        let shutdowns = log.query(&AuditQuery {
+            operation: Some(AUDIT_SHUTDOWN.to_string()),
+            ..Default::default()
+        });
+        assert_eq!(shutdowns.len(), 1);
+        assert!(!shutdowns[0].accepted);
+        let last = log.query(&AuditQuery {
+            limit: Some(2),
+            ..Default::default()
+        });
+        assert_eq!(
+            last.iter().map(|r| r.seq_id).collect::<Vec<_>>(),
+            vec![9, 10]
+        );
+
+        log.flush();
+        let restored = AuditLog::open(&path, 3)?;
+        assert_eq!(
+            restored.query(&AuditQuery::default()),
+            log.query(&AuditQuery::default())
+        );
+        // the file is compacted instead of growing without bound
+        let lines = std::fs::read_to_string(&path)?.lines().count();
+        assert!(lines <= 6, "{} lines", lines);
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+use crate::pipeline::fault_injector::{FaultInjector, FaultInjectorConfig};
+use crate::pipeline::implementation;
+use crate::webserver::audit::{audit, AUDIT_CHAOS};
+use crate::webserver::{get_registered_pipelines, get_requester};
+use actix_web::{get, post, web, HttpRequest, HttpResponse};
+use serde_json::json;
+use std::sync::Arc;
+
+async fn find_pipeline(name: &str) -> Option<Arc<implementation::Pipeline>> {
+    get_registered_pipelines()
+        .await
+        .into_iter()
+        .find(|p| p.get_name().as_deref() == Some(name))
+}
+
+async fn set_fault_injector(
+    req: &HttpRequest,
+    pipeline: &str,
+    stage: &str,
+    config: Option<FaultInjectorConfig>,
+) -> HttpResponse {
+    let details = match &config {
+        Some(config) => format!("{}/{}: {:?}", pipeline, stage, config),
+        None => format!("{}/{}: cleared", pipeline, stage),
+    };
+    let requester = get_requester(req);
+    let injector = match config.map(FaultInjector::new).transpose() {
+        Ok(injector) => injector,
+        Err(e) => {
+            audit(AUDIT_CHAOS, &requester, &details, false);
+            return HttpResponse::BadRequest().body(e.to_string());
+        }
+    };
+    let Some(p) = find_pipeline(pipeline).await else {
+        audit(AUDIT_CHAOS, &requester, &details, false);
+        return HttpResponse::NotFound().body(format!("No pipeline {}", pipeline));
+    };
+    let res = p.set_fault_injector(stage, injector);
+    audit(AUDIT_CHAOS, &requester, &details, res.is_ok());
+    match res {
+        Ok(()) => HttpResponse::Ok().json("ok"),
+        Err(e) => HttpResponse::NotFound().body(e.to_string()),
+    }
+}
+
+#[get("/chaos/faults")]
+async fn list_faults_handler() -> HttpResponse {
+    let mut injectors = Vec::new();
+    for p in get_registered_pipelines().await {
+        for (stage, injector) in p.get_fault_injectors() {
+            injectors.push(json!({
+                "pipeline": p.get_name(),
+                "stage": stage,
+                "config": injector.get_config(),
+                "stats": injector.get_stats(),
+            }));
+        }
+    }
+    HttpResponse::Ok().json(injectors)
+}
+
+#[post("/chaos/faults/{pipeline}/{stage}")]
+async fn set_faults_handler(
+    req: HttpRequest,
+    path: web::Path<(String, String)>,
+    config: web::Json<FaultInjectorConfig>,
+) -> HttpResponse {
+    let (pipeline, stage) = path.into_inner();
+    set_fault_injector(&req, &pipeline, &stage, Some(config.into_inner())).await
+}
+
+#[post("/chaos/faults/{pipeline}/{stage}/clear")]
+async fn clear_faults_handler(req: HttpRequest, path: web::Path<(String, String)>) -> HttpResponse {
+    let (pipeline, stage) = path.into_inner();
+    set_fault_injector(&req, &pipeline, &stage, None).await
+}
+
+pub(crate) fn configure_chaos(cfg: &mut web::ServiceConfig) {
+    cfg.service(list_faults_handler)
+        .service(set_faults_handler)
+        .service(clear_faults_handler);
+}
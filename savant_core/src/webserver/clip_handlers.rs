@@ -0,0 +1,40 @@
+use crate::clip::{extract_recorded_clip, get_clip_journal};
+use crate::protobuf::MessageStreamWriter;
+use actix_web::{get, web, HttpResponse};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct ClipParams {
+    start_ms: u64,
+    end_ms: u64,
+}
+
+/// Returns the clip as a journal of length-delimited messages, readable with
+/// [`crate::protobuf::MessageStreamReader`]. The journal is read on the blocking pool.
+///
+#[get("/clips/{source_id}")]
+async fn clip_handler(
+    source_id: web::Path<String>,
+    params: web::Query<ClipParams>,
+) -> HttpResponse {
+    if get_clip_journal().is_none() {
+        return HttpResponse::NotFound().body("No clip journal is set");
+    }
+    let ClipParams { start_ms, end_ms } = params.into_inner();
+    let res = web::block(move || -> anyhow::Result<Vec<u8>> {
+        let clip = extract_recorded_clip(&source_id, start_ms, end_ms)?;
+        let mut writer = MessageStreamWriter::new(Vec::new());
+        for message in &clip {
+            writer.write_message(message)?;
+        }
+        Ok(writer.into_inner())
+    })
+    .await;
+    match res {
+        Ok(Ok(journal)) => HttpResponse::Ok()
+            .content_type("application/octet-stream")
+            .body(journal),
+        Ok(Err(e)) => HttpResponse::BadRequest().body(e.to_string()),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
@@ -0,0 +1,552 @@
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail};
+use lazy_static::lazy_static;
+use log::{debug, error, info};
+use prost::Message;
+use savant_protobuf::generated;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::task::JoinHandle;
+
+use crate::get_or_init_async_runtime;
+use crate::pipeline::implementation;
+use crate::pipeline::readiness::StageReadiness;
+use crate::pipeline::topology::{render_topology, TopologyFormat};
+use crate::primitives::attribute_set::AttributeSet;
+use crate::primitives::Attribute;
+use crate::protobuf::{from_pb, ToProtobuf};
+use crate::webserver::audit::{audit, AUDIT_KVS_DELETE};
+use crate::webserver::kvs::asynchronous::{
+    del_attribute, del_attributes, get_attribute, get_blob, search_attributes, search_keys,
+    set_attributes, set_blob,
+};
+use crate::webserver::{get_health, get_registered_pipelines, get_status, PipelineStatus};
+
+/// The schema of the messages for generating the clients, e.g. with protoc for JVM.
+pub const CONTROL_SOCKET_PROTO: &str = include_str!("control_socket.proto");
+/// The maximum size of a request, larger requests close the connection.
+pub const MAX_CONTROL_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+const MAX_LENGTH_DELIMITER_SIZE: usize = 10;
+const CONTROL_SOCKET_REQUESTER: &str = "control_socket";
+
+#[derive(Clone, Copy, PartialEq, prost::Message)]
+pub struct Empty {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PipelineStageRequest {
+    #[prost(string, optional, tag = "1")]
+    pub pipeline: Option<String>,
+    #[prost(string, tag = "2")]
+    pub stage: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct StageReadinessRequest {
+    #[prost(string, optional, tag = "1")]
+    pub pipeline: Option<String>,
+    #[prost(string, tag = "2")]
+    pub stage: String,
+    #[prost(string, optional, tag = "3")]
+    pub warming_up: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TopologyRequest {
+    #[prost(string, tag = "1")]
+    pub format: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct KvsKey {
+    #[prost(string, tag = "1")]
+    pub namespace: String,
+    #[prost(string, tag = "2")]
+    pub name: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct KvsSearchRequest {
+    #[prost(string, optional, tag = "1")]
+    pub namespace: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub name: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct KvsSetRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub attributes: Vec<u8>,
+    #[prost(uint64, optional, tag = "2")]
+    pub ttl: Option<u64>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct KvsBlob {
+    #[prost(string, tag = "1")]
+    pub content_type: String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct KvsSetBlobRequest {
+    #[prost(string, tag = "1")]
+    pub namespace: String,
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(message, optional, tag = "3")]
+    pub blob: Option<KvsBlob>,
+    #[prost(uint64, optional, tag = "4")]
+    pub ttl: Option<u64>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+pub enum ControlCommand {
+    #[prost(message, tag = "2")]
+    Status(Empty),
+    #[prost(message, tag = "3")]
+    Health(Empty),
+    #[prost(message, tag = "4")]
+    Topology(TopologyRequest),
+    #[prost(message, tag = "5")]
+    StageQueueLen(PipelineStageRequest),
+    #[prost(message, tag = "6")]
+    SetStageReadiness(StageReadinessRequest),
+    #[prost(message, tag = "7")]
+    KvsSet(KvsSetRequest),
+    #[prost(message, tag = "8")]
+    KvsGet(KvsKey),
+    #[prost(message, tag = "9")]
+    KvsSearch(KvsSearchRequest),
+    #[prost(message, tag = "10")]
+    KvsSearchKeys(KvsSearchRequest),
+    #[prost(message, tag = "11")]
+    KvsDelete(KvsSearchRequest),
+    #[prost(message, tag = "12")]
+    KvsDeleteSingle(KvsKey),
+    #[prost(message, tag = "13")]
+    KvsSetBlob(KvsSetBlobRequest),
+    #[prost(message, tag = "14")]
+    KvsGetBlob(KvsKey),
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ControlRequest {
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    #[prost(
+        oneof = "ControlCommand",
+        tags = "2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14"
+    )]
+    pub command: Option<ControlCommand>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ControlResponse {
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    #[prost(string, optional, tag = "2")]
+    pub error: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub text: Option<String>,
+    #[prost(uint64, optional, tag = "4")]
+    pub value: Option<u64>,
+    #[prost(bytes = "vec", optional, tag = "5")]
+    pub attributes: Option<Vec<u8>>,
+    #[prost(message, repeated, tag = "6")]
+    pub keys: Vec<KvsKey>,
+    #[prost(message, optional, tag = "7")]
+    pub blob: Option<KvsBlob>,
+}
+
+impl ControlResponse {
+    fn text(text: String) -> Self {
+        Self {
+            text: Some(text),
+            ..Default::default()
+        }
+    }
+
+    fn value(value: u64) -> Self {
+        Self {
+            value: Some(value),
+            ..Default::default()
+        }
+    }
+
+    fn attributes(attributes: Vec<Attribute>) -> anyhow::Result<Self> {
+        Ok(Self {
+            attributes: Some(AttributeSet::from(attributes).to_pb()?),
+            ..Default::default()
+        })
+    }
+
+    fn keys(keys: Vec<(String, String)>) -> Self {
+        Self {
+            keys: keys
+                .into_iter()
+                .map(|(namespace, name)| KvsKey { namespace, name })
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+struct ControlSocketJob {
+    path: PathBuf,
+    join: JoinHandle<()>,
+}
+
+lazy_static! {
+    static ref CONTROL_SOCKET_JOB: parking_lot::Mutex<Option<ControlSocketJob>> =
+        parking_lot::Mutex::new(None);
+}
+
+fn find_pipeline(
+    pipelines: &[Arc<implementation::Pipeline>],
+    name: &Option<String>,
+) -> anyhow::Result<Arc<implementation::Pipeline>> {
+    match name {
+        Some(name) => pipelines
+            .iter()
+            .find(|p| p.get_name().as_ref() == Some(name))
+            .cloned()
+            .ok_or_else(|| anyhow!("Pipeline {} is not registered", name)),
+        None => match pipelines {
+            [pipeline] => Ok(pipeline.clone()),
+            _ => bail!(
+                "{} pipelines are registered, the pipeline name is required",
+                pipelines.len()
+            ),
+        },
+    }
+}
+
+async fn execute(command: ControlCommand) -> anyhow::Result<ControlResponse> {
+    Ok(match command {
+        ControlCommand::Status(_) => ControlResponse::text(
+            match get_status().await {
+                PipelineStatus::Running => "running",
+                PipelineStatus::Stopped => "stopped",
+                PipelineStatus::Shutdown => "shutdown",
+            }
+            .to_string(),
+        ),
+        ControlCommand::Health(_) => ControlResponse::text(get_health().await.1.to_string()),
+        ControlCommand::Topology(request) => {
+            let format = request.format.parse::<TopologyFormat>()?;
+            let topologies = get_registered_pipelines()
+                .await
+                .iter()
+                .map(|p| p.get_topology())
+                .collect::<Vec<_>>();
+            ControlResponse::text(render_topology(&topologies, format))
+        }
+        ControlCommand::StageQueueLen(request) => {
+            let pipeline = find_pipeline(&get_registered_pipelines().await, &request.pipeline)?;
+            ControlResponse::value(pipeline.get_stage_queue_len(&request.stage)? as u64)
+        }
+        ControlCommand::SetStageReadiness(request) => {
+            let pipeline = find_pipeline(&get_registered_pipelines().await, &request.pipeline)?;
+            let readiness = match request.warming_up {
+                Some(reason) => StageReadiness::WarmingUp(reason),
+                None => StageReadiness::Ready,
+            };
+            pipeline.set_stage_readiness(&request.stage, readiness)?;
+            ControlResponse::default()
+        }
+        ControlCommand::KvsSet(request) => {
+            let set = from_pb::<generated::AttributeSet, AttributeSet>(&request.attributes)?;
+            set_attributes(&set.attributes, request.ttl).await;
+            ControlResponse::default()
+        }
+        ControlCommand::KvsGet(key) => ControlResponse::attributes(
+            get_attribute(&key.namespace, &key.name)
+                .await
+                .into_iter()
+                .collect(),
+        )?,
+        ControlCommand::KvsSearch(request) => {
+            ControlResponse::attributes(search_attributes(&request.namespace, &request.name).await)?
+        }
+        ControlCommand::KvsSearchKeys(request) => {
+            ControlResponse::keys(search_keys(&request.namespace, &request.name).await)
+        }
+        ControlCommand::KvsDelete(request) => {
+            audit(
+                AUDIT_KVS_DELETE,
+                CONTROL_SOCKET_REQUESTER,
+                &format!(
+                    "{}/{}",
+                    request.namespace.as_deref().unwrap_or("*"),
+                    request.name.as_deref().unwrap_or("*")
+                ),
+                true,
+            );
+            del_attributes(&request.namespace, &request.name).await;
+            ControlResponse::default()
+        }
+        ControlCommand::KvsDeleteSingle(key) => {
+            audit(
+                AUDIT_KVS_DELETE,
+                CONTROL_SOCKET_REQUESTER,
+                &format!("{}/{} (single)", key.namespace, key.name),
+                true,
+            );
+            ControlResponse::attributes(
+                del_attribute(&key.namespace, &key.name)
+                    .await
+                    .into_iter()
+                    .collect(),
+            )?
+        }
+        ControlCommand::KvsSetBlob(request) => {
+            let blob = request
+                .blob
+                .ok_or_else(|| anyhow!("The blob is required"))?;
+            let blob = crate::webserver::kvs::KvsBlob::new(&blob.content_type, blob.data);
+            set_blob(&request.namespace, &request.name, blob, request.ttl).await?;
+            ControlResponse::default()
+        }
+        ControlCommand::KvsGetBlob(key) => ControlResponse {
+            blob: get_blob(&key.namespace, &key.name).await.map(|b| KvsBlob {
+                content_type: b.content_type,
+                data: b.data,
+            }),
+            ..Default::default()
+        },
+    })
+}
+
+/// Executes the request, the failures are reported in the response.
+///
+pub async fn handle_request(request: ControlRequest) -> ControlResponse {
+    let result = match request.command {
+        Some(command) => execute(command).await,
+        None => Err(anyhow!("The request has no command")),
+    };
+    let mut response = result.unwrap_or_else(|e| ControlResponse {
+        error: Some(e.to_string()),
+        ..Default::default()
+    });
+    response.id = request.id;
+    response
+}
+
+/// Reads a message prefixed with its varint-encoded length, `None` when the peer closed the
+/// connection between the messages.
+///
+async fn read_message(stream: &mut UnixStream) -> anyhow::Result<Option<Vec<u8>>> {
+    let mut delimiter = Vec::with_capacity(MAX_LENGTH_DELIMITER_SIZE);
+    loop {
+        let byte = match stream.read_u8().await {
+            Ok(byte) => byte,
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof && delimiter.is_empty() => {
+                return Ok(None)
+            }
+            Err(e) => return Err(e.into()),
+        };
+        delimiter.push(byte);
+        if byte & 0x80 == 0 {
+            break;
+        }
+        if delimiter.len() == MAX_LENGTH_DELIMITER_SIZE {
+            bail!("Invalid message length");
+        }
+    }
+    let len = prost::decode_length_delimiter(delimiter.as_slice())?;
+    if len > MAX_CONTROL_MESSAGE_SIZE {
+        bail!(
+            "Message of {} bytes exceeds the limit of {} bytes",
+            len,
+            MAX_CONTROL_MESSAGE_SIZE
+        );
+    }
+    let mut message = vec![0; len];
+    stream.read_exact(&mut message).await?;
+    Ok(Some(message))
+}
+
+async fn serve_connection(mut stream: UnixStream) -> anyhow::Result<()> {
+    while let Some(message) = read_message(&mut stream).await? {
+        let request = ControlRequest::decode(message.as_slice())?;
+        let response = handle_request(request).await;
+        stream
+            .write_all(&response.encode_length_delimited_to_vec())
+            .await?;
+    }
+    Ok(())
+}
+
+/// Starts the control socket: a Unix socket serving the pipeline status, health, topology,
+/// stage operations and the KVS with length-delimited protobuf messages described in
+/// [`CONTROL_SOCKET_PROTO`]. It is a lightweight alternative to the webserver for the
+/// components on the same host which cannot embed the library, e.g. JVM services. The
+/// access is governed by the permissions of the socket file, set to `mode` when given.
+///
+/// A stale socket file left at the path is replaced, a running control socket is stopped.
+///
+pub fn start_control_socket(path: &Path, mode: Option<u32>) -> anyhow::Result<()> {
+    stop_control_socket();
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path)?,
+        Ok(_) => bail!("{} exists and is not a socket", path.display()),
+        Err(_) => {}
+    }
+    let rt = get_or_init_async_runtime();
+    let listener = {
+        let _guard = rt.enter();
+        UnixListener::bind(path)?
+    };
+    if let Some(mode) = mode {
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+    }
+    let join = rt.spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(async move {
+                        if let Err(e) = serve_connection(stream).await {
+                            debug!("Control socket connection closed: {}", e);
+                        }
+                    });
+                }
+                Err(e) => {
+                    error!("Control socket failed to accept a connection: {}", e);
+                    break;
+                }
+            }
+        }
+    });
+    info!("Control socket started at {}.", path.display());
+    CONTROL_SOCKET_JOB.lock().replace(ControlSocketJob {
+        path: path.to_path_buf(),
+        join,
+    });
+    Ok(())
+}
+
+/// Stops the control socket and removes the socket file, does nothing when the control
+/// socket is not started.
+///
+pub fn stop_control_socket() {
+    let job = CONTROL_SOCKET_JOB.lock().take();
+    if let Some(job) = job {
+        job.join.abort();
+        if let Err(e) = std::fs::remove_file(&job.path) {
+            error!(
+                "Failed to remove the control socket {}: {}",
+                job.path.display(),
+                e
+            );
+        }
+        info!("Control socket at {} stopped.", job.path.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+
+    use prost::Message;
+    use savant_protobuf::generated;
+
+    use crate::primitives::attribute_set::AttributeSet;
+    use crate::primitives::Attribute;
+    use crate::protobuf::{from_pb, ToProtobuf};
+    use crate::webserver::control_socket::{
+        start_control_socket, stop_control_socket, ControlCommand, ControlRequest, ControlResponse,
+        KvsKey, KvsSearchRequest, KvsSetRequest, PipelineStageRequest,
+    };
+
+    fn call(stream: &mut UnixStream, id: u64, command: ControlCommand) -> ControlResponse {
+        let request = ControlRequest {
+            id,
+            command: Some(command),
+        };
+        stream
+            .write_all(&request.encode_length_delimited_to_vec())
+            .unwrap();
+        let mut delimiter = Vec::new();
+        loop {
+            let mut byte = [0u8];
+            stream.read_exact(&mut byte).unwrap();
+            delimiter.push(byte[0]);
+            if byte[0] & 0x80 == 0 {
+                break;
+            }
+        }
+        let len = prost::decode_length_delimiter(delimiter.as_slice()).unwrap();
+        let mut message = vec![0; len];
+        stream.read_exact(&mut message).unwrap();
+        ControlResponse::decode(message.as_slice()).unwrap()
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_control_socket() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join(format!("savant-control-{}.sock", std::process::id()));
+        start_control_socket(&path, Some(0o600))?;
+        let mut stream = UnixStream::connect(&path)?;
+
+        let attributes = vec![Attribute::persistent("uds", "camera", vec![], &None, false)];
+        let response = call(
+            &mut stream,
+            1,
+            ControlCommand::KvsSet(KvsSetRequest {
+                attributes: AttributeSet::from(attributes.clone()).to_pb()?,
+                ttl: None,
+            }),
+        );
+        assert_eq!((response.id, response.error), (1, None));
+
+        let response = call(
+            &mut stream,
+            2,
+            ControlCommand::KvsGet(KvsKey {
+                namespace: "uds".to_string(),
+                name: "camera".to_string(),
+            }),
+        );
+        let set = from_pb::<generated::AttributeSet, AttributeSet>(&response.attributes.unwrap())?;
+        assert_eq!(set.attributes, attributes);
+
+        let response = call(
+            &mut stream,
+            3,
+            ControlCommand::KvsSearchKeys(KvsSearchRequest {
+                namespace: Some("uds".to_string()),
+                name: None,
+            }),
+        );
+        assert_eq!(response.keys.len(), 1);
+
+        let response = call(
+            &mut stream,
+            4,
+            ControlCommand::StageQueueLen(PipelineStageRequest {
+                pipeline: Some("missing".to_string()),
+                stage: "input".to_string(),
+            }),
+        );
+        assert_eq!(response.id, 4);
+        assert!(response.error.is_some());
+
+        call(
+            &mut stream,
+            5,
+            ControlCommand::KvsDelete(KvsSearchRequest {
+                namespace: Some("uds".to_string()),
+                name: None,
+            }),
+        );
+        drop(stream);
+        stop_control_socket();
+        assert!(!path.exists());
+        Ok(())
+    }
+}
@@ -0,0 +1,329 @@
+use crate::capabilities::capabilities;
+use crate::clock::system_now_ms;
+use crate::json_api::ToSerdeJsonValue;
+use crate::pipeline::topology::{render_topology, TopologyFormat};
+use crate::primitives::frame::VideoFrameProxy;
+use crate::protobuf::redact_frame_for_export;
+use crate::version;
+use crate::webserver::audit::{query_audit_log, AuditQuery};
+use crate::webserver::{encode_metrics, get_health, get_registered_pipelines, get_status};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use savant_protobuf::generated;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+/// The number of the log lines kept for the diagnostics bundle.
+///
+pub const RECENT_LOG_CAPACITY: usize = 1000;
+
+/// The maximum number of the frames sampled per stage.
+///
+pub const MAX_SAMPLED_FRAMES: usize = 16;
+
+lazy_static! {
+    static ref RECENT_LOGS: Mutex<VecDeque<String>> =
+        Mutex::new(VecDeque::with_capacity(RECENT_LOG_CAPACITY));
+}
+
+/// Keeps the last log lines, the oldest lines are dropped.
+///
+pub fn record_log_line(line: String) {
+    let mut logs = RECENT_LOGS.lock();
+    if logs.len() == RECENT_LOG_CAPACITY {
+        logs.pop_front();
+    }
+    logs.push_back(line);
+}
+
+/// The last log lines, the oldest first.
+///
+pub fn get_recent_logs() -> Vec<String> {
+    RECENT_LOGS.lock().iter().cloned().collect()
+}
+
+/// A logger keeping the records written by the wrapped logger for the diagnostics bundle.
+///
+pub struct RecentLogsLogger<L> {
+    inner: L,
+}
+
+impl<L: log::Log> RecentLogsLogger<L> {
+    pub fn new(inner: L) -> Self {
+        Self { inner }
+    }
+}
+
+impl<L: log::Log> log::Log for RecentLogsLogger<L> {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.inner.enabled(record.metadata()) {
+            record_log_line(format!(
+                "{} {:<5} {} > {}",
+                system_now_ms(),
+                record.level(),
+                record.target(),
+                record.args()
+            ));
+        }
+        self.inner.log(record)
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+/// The content of a diagnostics bundle.
+///
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiagnosticsOptions {
+    /// The number of frames per stage whose metadata is included, the frame content is
+    /// omitted. At most [`MAX_SAMPLED_FRAMES`]. The metadata is redacted like the serialized
+    /// frames.
+    pub sampled_frames: usize,
+}
+
+fn frame_metadata(frame: &VideoFrameProxy) -> anyhow::Result<Value> {
+    let mut exported = generated::VideoFrame::from(frame);
+    redact_frame_for_export(&mut exported)?;
+    let mut value = VideoFrameProxy::try_from(&exported)?.to_serde_json_value();
+    if let Some(object) = value.as_object_mut() {
+        object.remove("content");
+    }
+    Ok(value)
+}
+
+fn append_file(
+    archive: &mut tar::Builder<GzEncoder<Vec<u8>>>,
+    path: &str,
+    data: &[u8],
+    mtime: u64,
+) -> anyhow::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(mtime);
+    archive.append_data(&mut header, path, data)?;
+    Ok(())
+}
+
+/// Assembles the state of the process support needs into a gzipped tar archive: the
+/// status, the health and the topology of the registered pipelines, the stage queues and
+/// rates, the metrics, the pipeline configurations, the recent log lines (see
+/// [`RecentLogsLogger`]), the audit log and optionally the metadata of the sampled frames.
+///
+pub async fn build_diagnostics_bundle(options: &DiagnosticsOptions) -> anyhow::Result<Vec<u8>> {
+    let created_ms = system_now_ms();
+    let pipelines = get_registered_pipelines().await;
+    let mut files: Vec<(&str, Vec<u8>)> = Vec::new();
+
+    let summary = json!({
+        "version": version(),
+        "created_ms": created_ms,
+        "pid": std::process::id(),
+        "status": get_status().await,
+        "capabilities": capabilities(),
+    });
+    files.push(("summary.json", serde_json::to_vec_pretty(&summary)?));
+    let (_, health) = get_health().await;
+    files.push(("health.json", serde_json::to_vec_pretty(&health)?));
+
+    let topologies = pipelines
+        .iter()
+        .map(|p| p.get_topology())
+        .collect::<Vec<_>>();
+    files.push((
+        "topology.dot",
+        render_topology(&topologies, TopologyFormat::Dot).into_bytes(),
+    ));
+    files.push((
+        "topology.mermaid",
+        render_topology(&topologies, TopologyFormat::Mermaid).into_bytes(),
+    ));
+
+    let queues = pipelines
+        .iter()
+        .zip(&topologies)
+        .map(|(p, topology)| {
+            json!({
+                "pipeline": topology.name,
+                "stages": topology.stages.iter().map(|s| json!({
+                    "name": s.name,
+                    "queue_length": s.queue_length,
+                })).collect::<Vec<_>>(),
+                "rates": p.rates().iter().map(|r| json!({
+                    "stage": r.stage_name,
+                    "source_id": r.source_id,
+                    "frames": r.measurement.frames,
+                    "fps": r.measurement.fps,
+                    "jitter_ms": r.measurement.jitter_ms,
+                })).collect::<Vec<_>>(),
+            })
+        })
+        .collect::<Vec<_>>();
+    files.push(("queues.json", serde_json::to_vec_pretty(&queues)?));
+
+    let metrics = encode_metrics()
+        .await
+        .unwrap_or_else(|e| format!("# {}\n", e));
+    files.push(("metrics.txt", metrics.into_bytes()));
+
+    let mut config = String::new();
+    for (p, topology) in pipelines.iter().zip(&topologies) {
+        writeln!(
+            config,
+            "# pipeline {}\n{:#?}\n",
+            topology.name.as_deref().unwrap_or("<unnamed>"),
+            p.get_configuration()
+        )?;
+    }
+    files.push(("config.txt", config.into_bytes()));
+
+    let mut logs = get_recent_logs().join("\n");
+    logs.push('\n');
+    files.push(("logs.txt", logs.into_bytes()));
+    files.push((
+        "audit.json",
+        serde_json::to_vec_pretty(&query_audit_log(&AuditQuery::default()))?,
+    ));
+
+    let sampled_frames = options.sampled_frames.min(MAX_SAMPLED_FRAMES);
+    if sampled_frames > 0 {
+        let mut frames = Vec::new();
+        for (p, topology) in pipelines.iter().zip(&topologies) {
+            for (stage, frame) in p.sample_frames(sampled_frames) {
+                frames.push(json!({
+                    "pipeline": topology.name,
+                    "stage": stage,
+                    "frame": frame_metadata(&frame)?,
+                }));
+            }
+        }
+        files.push(("frames.json", serde_json::to_vec_pretty(&frames)?));
+    }
+
+    let mut archive = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    let root = format!("savant-diagnostics-{}", created_ms);
+    for (name, data) in files {
+        append_file(
+            &mut archive,
+            &format!("{}/{}", root, name),
+            &data,
+            created_ms / 1000,
+        )?;
+    }
+    Ok(archive.into_inner()?.finish()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::get_or_init_async_runtime;
+    use crate::pipeline::implementation::create_test_pipeline;
+    use crate::primitives::attribute_value::AttributeValue;
+    use crate::primitives::WithAttributes;
+    use crate::protobuf::{set_export_filter, RedactionFilter};
+    use crate::test::gen_frame;
+    use crate::webserver::diagnostics::{
+        build_diagnostics_bundle, get_recent_logs, DiagnosticsOptions, RecentLogsLogger,
+    };
+    use crate::webserver::register_pipeline;
+    use flate2::read::GzDecoder;
+    use hashbrown::HashMap;
+    use std::io::Read;
+    use std::sync::Arc;
+
+    struct NoopLogger;
+
+    impl log::Log for NoopLogger {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.level() <= log::Level::Info
+        }
+
+        fn log(&self, _: &log::Record) {}
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_bundle() -> anyhow::Result<()> {
+        let logger = RecentLogsLogger::new(NoopLogger);
+        log::Log::log(
+            &logger,
+            &log::Record::builder()
+                .level(log::Level::Info)
+                .target("diagnostics")
+                .args(format_args!("test line"))
+                .build(),
+        );
+        log::Log::log(
+            &logger,
+            &log::Record::builder()
+                .level(log::Level::Debug)
+                .args(format_args!("filtered line"))
+                .build(),
+        );
+        let logs = get_recent_logs();
+        assert!(logs.last().unwrap().ends_with("diagnostics > test line"));
+        assert!(!logs.iter().any(|l| l.contains("filtered line")));
+
+        let pipeline = Arc::new(create_test_pipeline()?);
+        let mut frame = gen_frame();
+        frame.set_persistent_attribute(
+            "diagnostics_test",
+            "secret",
+            &None,
+            false,
+            vec![AttributeValue::integer(1, None)],
+        );
+        pipeline.add_frame("input", frame)?;
+        register_pipeline(pipeline.clone());
+
+        set_export_filter(Some(RedactionFilter::new(&["diagnostics_test/*"])?));
+        let bundle =
+            get_or_init_async_runtime().block_on(build_diagnostics_bundle(&DiagnosticsOptions {
+                sampled_frames: 1,
+            }));
+        set_export_filter(None);
+        let bundle = bundle?;
+        crate::webserver::unregister_pipeline(pipeline);
+
+        let mut archive = tar::Archive::new(GzDecoder::new(bundle.as_slice()));
+        let mut files = HashMap::new();
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let path = entry.path()?.to_string_lossy().to_string();
+            let mut content = String::new();
+            entry.read_to_string(&mut content)?;
+            files.insert(path.split_once('/').unwrap().1.to_string(), content);
+        }
+        for name in [
+            "summary.json",
+            "health.json",
+            "topology.dot",
+            "topology.mermaid",
+            "queues.json",
+            "metrics.txt",
+            "config.txt",
+            "logs.txt",
+            "audit.json",
+            "frames.json",
+        ] {
+            assert!(files.contains_key(name), "{} is missing", name);
+        }
+        assert!(files["logs.txt"].contains("test line"));
+        let frames: serde_json::Value = serde_json::from_str(&files["frames.json"])?;
+        assert_eq!(frames[0]["stage"], "input");
+        assert!(frames[0]["frame"].get("content").is_none());
+        assert!(files["frames.json"].contains("system2"));
+        assert!(!files["frames.json"].contains("diagnostics_test"));
+        Ok(())
+    }
+}
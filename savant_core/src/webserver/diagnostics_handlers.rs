@@ -0,0 +1,42 @@
+use crate::webserver::audit::{audit, AUDIT_DIAGNOSTICS};
+use crate::webserver::diagnostics::{build_diagnostics_bundle, DiagnosticsOptions};
+use crate::webserver::get_requester;
+use actix_web::{get, web, HttpRequest, HttpResponse};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct DiagnosticsParams {
+    #[serde(default)]
+    frames: usize,
+}
+
+/// Returns the diagnostics bundle as a gzipped tar archive. The bundle may contain the
+/// metadata of the frames, so the requests are audited.
+///
+#[get("/diagnostics")]
+async fn diagnostics_handler(
+    req: HttpRequest,
+    params: web::Query<DiagnosticsParams>,
+) -> HttpResponse {
+    let requester = get_requester(&req);
+    let options = DiagnosticsOptions {
+        sampled_frames: params.frames,
+    };
+    let res = build_diagnostics_bundle(&options).await;
+    audit(
+        AUDIT_DIAGNOSTICS,
+        &requester,
+        &format!("frames={}", options.sampled_frames),
+        res.is_ok(),
+    );
+    match res {
+        Ok(bundle) => HttpResponse::Ok()
+            .content_type("application/gzip")
+            .insert_header((
+                "Content-Disposition",
+                "attachment; filename=\"savant-diagnostics.tar.gz\"",
+            ))
+            .body(bundle),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
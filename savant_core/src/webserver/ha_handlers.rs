@@ -0,0 +1,38 @@
+use crate::ha::{get_ha_status, request_promotion, restore_snapshot};
+use crate::webserver::audit::{audit, AUDIT_HA_PROMOTE};
+use crate::webserver::get_requester;
+use actix_web::{get, post, web, HttpRequest, HttpResponse};
+
+/// Registered as `POST /ha/snapshot` with the payload limit of the KVS snapshots. The
+/// snapshot is restored on the blocking pool, as it blocks on the KVS.
+///
+pub(crate) async fn ha_snapshot_handler(payload: web::Bytes) -> HttpResponse {
+    match web::block(move || restore_snapshot(&payload)).await {
+        Ok(Ok(())) => HttpResponse::Ok().json("ok"),
+        Ok(Err(e)) => HttpResponse::Conflict().body(e.to_string()),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+#[get("/ha/status")]
+async fn ha_status_handler() -> HttpResponse {
+    HttpResponse::Ok().json(get_ha_status())
+}
+
+/// The promotion callback runs on the HA worker, not on the webserver thread.
+///
+#[post("/ha/promote")]
+async fn ha_promote_handler(req: HttpRequest) -> HttpResponse {
+    let promoted = request_promotion();
+    audit(
+        AUDIT_HA_PROMOTE,
+        &get_requester(&req),
+        "manual switchover",
+        promoted,
+    );
+    if promoted {
+        HttpResponse::Accepted().json(get_ha_status())
+    } else {
+        HttpResponse::Conflict().body("The pipeline is not a standby")
+    }
+}
@@ -0,0 +1,135 @@
+use crate::jobs::{
+    cancel_job, get_job, list_jobs, pause_job, remove_job, resume_job, submit_job, JobRequest,
+};
+use crate::webserver::audit::{audit, AUDIT_JOB};
+use crate::webserver::{authorize, get_requester};
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse};
+use futures_util::stream;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// How often the status of a job is streamed.
+const JOB_METRICS_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Deserialize)]
+struct JobActionParams {
+    id: String,
+    action: String,
+}
+
+/// Starts the job, the journal is opened and the processor is created before the response.
+/// The request is authorized with the shutdown token, as the job reads and writes the files.
+///
+#[post("/jobs")]
+async fn submit_job_handler(req: HttpRequest, request: web::Json<JobRequest>) -> HttpResponse {
+    let request = request.into_inner();
+    let details = format!("processor={}", request.processor);
+    if let Err(response) = authorize(&req) {
+        audit(AUDIT_JOB, &get_requester(&req), &details, false);
+        return response;
+    }
+    let res = web::block(move || submit_job(request)).await;
+    match res {
+        Ok(Ok(id)) => {
+            audit(AUDIT_JOB, &get_requester(&req), &details, true);
+            HttpResponse::Ok().json(get_job(&id))
+        }
+        Ok(Err(e)) => {
+            audit(AUDIT_JOB, &get_requester(&req), &details, false);
+            HttpResponse::BadRequest().body(e.to_string())
+        }
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+#[get("/jobs")]
+async fn list_jobs_handler() -> HttpResponse {
+    HttpResponse::Ok().json(list_jobs())
+}
+
+#[get("/jobs/{id}")]
+async fn get_job_handler(id: web::Path<String>) -> HttpResponse {
+    match get_job(&id) {
+        Some(status) => HttpResponse::Ok().json(status),
+        None => HttpResponse::NotFound().body("No such job"),
+    }
+}
+
+#[post("/jobs/{id}/{action}")]
+async fn job_action_handler(req: HttpRequest, params: web::Path<JobActionParams>) -> HttpResponse {
+    if let Err(response) = authorize(&req) {
+        audit(
+            AUDIT_JOB,
+            &get_requester(&req),
+            &format!("{} {}", params.action, params.id),
+            false,
+        );
+        return response;
+    }
+    let accepted = match params.action.as_str() {
+        "cancel" => cancel_job(&params.id),
+        "pause" => pause_job(&params.id),
+        "resume" => resume_job(&params.id),
+        _ => return HttpResponse::BadRequest().body("Unknown action"),
+    };
+    audit(
+        AUDIT_JOB,
+        &get_requester(&req),
+        &format!("{} {}", params.action, params.id),
+        accepted,
+    );
+    match get_job(&params.id) {
+        None => HttpResponse::NotFound().body("No such job"),
+        Some(status) if accepted => HttpResponse::Ok().json(status),
+        Some(status) => HttpResponse::Conflict().body(format!(
+            "The job is {:?}, cannot {}",
+            status.state, params.action
+        )),
+    }
+}
+
+/// Forgets the finished job and its metrics.
+///
+#[delete("/jobs/{id}")]
+async fn remove_job_handler(req: HttpRequest, id: web::Path<String>) -> HttpResponse {
+    let details = format!("remove {}", id);
+    if let Err(response) = authorize(&req) {
+        audit(AUDIT_JOB, &get_requester(&req), &details, false);
+        return response;
+    }
+    let Some(status) = get_job(&id) else {
+        return HttpResponse::NotFound().body("No such job");
+    };
+    let removed = remove_job(&id);
+    audit(AUDIT_JOB, &get_requester(&req), &details, removed);
+    if removed {
+        HttpResponse::Ok().json(status)
+    } else {
+        HttpResponse::Conflict().body(format!("The job is {:?}, cannot remove", status.state))
+    }
+}
+
+/// Streams the status of the job as JSON lines every second, the stream ends with the
+/// status of the finished job.
+///
+#[get("/jobs/{id}/metrics")]
+async fn job_metrics_handler(id: web::Path<String>) -> HttpResponse {
+    let id = id.into_inner();
+    if get_job(&id).is_none() {
+        return HttpResponse::NotFound().body("No such job");
+    }
+    let updates = stream::unfold(Some((id, true)), |state| async move {
+        let (id, first) = state?;
+        if !first {
+            actix_web::rt::time::sleep(JOB_METRICS_INTERVAL).await;
+        }
+        let status = get_job(&id)?;
+        let next = (!status.state.is_finished()).then_some((id, false));
+        let mut line = serde_json::to_vec(&status).ok()?;
+        line.push(b'\n');
+        Some((Ok::<_, actix_web::Error>(web::Bytes::from(line)), next))
+    });
+    HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(updates)
+}
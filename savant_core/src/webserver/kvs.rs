@@ -1,7 +1,155 @@
+use savant_protobuf::generated;
+use std::str::FromStr;
+
+pub const CONTENT_TYPE_BYTES: &str = "application/octet-stream";
+pub const CONTENT_TYPE_JSON: &str = "application/json";
+/// The maximum size of a blob stored in the KVS.
+pub const MAX_KVS_BLOB_SIZE: usize = 1024 * 1024;
+/// The maximum size of a snapshot imported over HTTP.
+pub const MAX_KVS_SNAPSHOT_SIZE: usize = 128 * 1024 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub enum KvsBlobError {
+    #[error("Blob of {0} bytes exceeds the limit of {} bytes", MAX_KVS_BLOB_SIZE)]
+    TooLarge(usize),
+    #[error("Invalid JSON document: {0}")]
+    InvalidJson(serde_json::Error),
+}
+
+/// A value stored in the KVS besides attributes: raw bytes tagged with the content type, e.g.
+/// calibration matrices or zone definitions shared between modules. JSON documents are
+/// blobs of the `application/json` type and are validated when stored.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct KvsBlob {
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+impl KvsBlob {
+    pub fn new(content_type: &str, data: Vec<u8>) -> Self {
+        Self {
+            content_type: content_type.to_string(),
+            data,
+        }
+    }
+
+    pub fn bytes(data: Vec<u8>) -> Self {
+        Self::new(CONTENT_TYPE_BYTES, data)
+    }
+
+    pub fn json(document: &serde_json::Value) -> Self {
+        Self::new(
+            CONTENT_TYPE_JSON,
+            serde_json::to_vec(document).expect("JSON value is always serializable"),
+        )
+    }
+
+    pub fn is_json(&self) -> bool {
+        self.content_type
+            .split(';')
+            .next()
+            .is_some_and(|t| t.trim().eq_ignore_ascii_case(CONTENT_TYPE_JSON))
+    }
+
+    pub fn as_json(&self) -> Result<serde_json::Value, KvsBlobError> {
+        serde_json::from_slice(&self.data).map_err(KvsBlobError::InvalidJson)
+    }
+
+    pub fn validate(&self) -> Result<(), KvsBlobError> {
+        if self.data.len() > MAX_KVS_BLOB_SIZE {
+            return Err(KvsBlobError::TooLarge(self.data.len()));
+        }
+        if self.is_json() {
+            self.as_json()?;
+        }
+        Ok(())
+    }
+}
+
+/// How an imported snapshot is combined with the records already in the KVS.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergePolicy {
+    /// The KVS is cleared before the import.
+    Replace,
+    /// Imported records overwrite the existing records with the same key.
+    #[default]
+    Overwrite,
+    /// Existing records are kept, only missing keys are imported.
+    KeepExisting,
+}
+
+impl FromStr for MergePolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "replace" => Ok(MergePolicy::Replace),
+            "overwrite" => Ok(MergePolicy::Overwrite),
+            "keep_existing" => Ok(MergePolicy::KeepExisting),
+            _ => anyhow::bail!(
+                "Unknown merge policy: {}, expected replace, overwrite or keep_existing",
+                s
+            ),
+        }
+    }
+}
+
+pub(crate) const KVS_SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct KvsAttributeRecord {
+    #[prost(message, optional, tag = "1")]
+    pub attribute: Option<generated::Attribute>,
+    #[prost(uint64, optional, tag = "2")]
+    pub ttl: Option<u64>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct KvsBlobRecord {
+    #[prost(string, tag = "1")]
+    pub namespace: String,
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(string, tag = "3")]
+    pub content_type: String,
+    #[prost(bytes = "vec", tag = "4")]
+    pub data: Vec<u8>,
+    #[prost(uint64, optional, tag = "5")]
+    pub ttl: Option<u64>,
+}
+
+/// The content of the KVS: attributes and blobs with their TTLs. The TTLs restart when the
+/// snapshot is imported.
+///
+#[derive(Clone, PartialEq, prost::Message)]
+pub(crate) struct KvsSnapshot {
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    #[prost(message, repeated, tag = "2")]
+    pub attributes: Vec<KvsAttributeRecord>,
+    #[prost(message, repeated, tag = "3")]
+    pub blobs: Vec<KvsBlobRecord>,
+}
+
+/// The approximate number of attributes and blobs in the KVS, the expired records may be
+/// counted until they are evicted.
+///
+pub fn kvs_entry_count() -> u64 {
+    WS_DATA.kvs.entry_count() + WS_DATA.blobs.entry_count()
+}
+
 pub mod asynchronous {
     use crate::primitives::attribute::Attribute;
+    use crate::webserver::kvs::{
+        KvsAttributeRecord, KvsBlob, KvsBlobError, KvsBlobRecord, KvsSnapshot, MergePolicy,
+        KVS_SNAPSHOT_VERSION,
+    };
     use crate::webserver::WS_DATA;
     use globset::Glob;
+    use prost::Message;
+    use savant_protobuf::generated;
 
     pub async fn set_attributes(attributes: &[Attribute], ttl: Option<u64>) {
         for attr in attributes {
@@ -109,11 +257,152 @@ pub mod asynchronous {
             .await
             .map(|(_, attr)| attr)
     }
+
+    pub async fn set_blob(
+        ns: &str,
+        name: &str,
+        blob: KvsBlob,
+        ttl: Option<u64>,
+    ) -> Result<(), KvsBlobError> {
+        blob.validate()?;
+        WS_DATA
+            .blobs
+            .insert((ns.to_string(), name.to_string()), (ttl, blob))
+            .await;
+        Ok(())
+    }
+
+    pub async fn get_blob(ns: &str, name: &str) -> Option<KvsBlob> {
+        WS_DATA
+            .blobs
+            .get(&(ns.to_string(), name.to_string()))
+            .await
+            .map(|(_, blob)| blob)
+    }
+
+    pub async fn del_blob(ns: &str, name: &str) -> Option<KvsBlob> {
+        WS_DATA
+            .blobs
+            .remove(&(ns.to_string(), name.to_string()))
+            .await
+            .map(|(_, blob)| blob)
+    }
+
+    pub async fn search_blob_keys(
+        ns: &Option<String>,
+        name: &Option<String>,
+    ) -> Vec<(String, String)> {
+        let ns_glob = ns
+            .as_ref()
+            .map(|s| Glob::new(s.as_str()))
+            .unwrap_or(Glob::new("*"))
+            .unwrap()
+            .compile_matcher();
+
+        let name_glob = name
+            .as_ref()
+            .map(|s| Glob::new(s.as_str()))
+            .unwrap_or(Glob::new("*"))
+            .unwrap()
+            .compile_matcher();
+
+        WS_DATA
+            .blobs
+            .iter()
+            .filter(|(key, _)| ns_glob.is_match(&key.0) && name_glob.is_match(&key.1))
+            .map(|(key, _)| (key.0.clone(), key.1.clone()))
+            .collect()
+    }
+
+    /// Serializes the attributes and the blobs stored in the KVS.
+    ///
+    pub async fn export_snapshot() -> Vec<u8> {
+        let snapshot = KvsSnapshot {
+            version: KVS_SNAPSHOT_VERSION,
+            attributes: WS_DATA
+                .kvs
+                .iter()
+                .map(|(_, (ttl, attr))| KvsAttributeRecord {
+                    attribute: Some(generated::Attribute::from(&attr)),
+                    ttl,
+                })
+                .collect(),
+            blobs: WS_DATA
+                .blobs
+                .iter()
+                .map(|(key, (ttl, blob))| KvsBlobRecord {
+                    namespace: key.0.clone(),
+                    name: key.1.clone(),
+                    content_type: blob.content_type,
+                    data: blob.data,
+                    ttl,
+                })
+                .collect(),
+        };
+        snapshot.encode_to_vec()
+    }
+
+    /// Loads a snapshot produced by [`export_snapshot`] into the KVS. The snapshot is fully
+    /// decoded and validated before the KVS is changed. Returns the number of imported records.
+    ///
+    pub async fn import_snapshot(snapshot: &[u8], policy: MergePolicy) -> anyhow::Result<usize> {
+        let snapshot = KvsSnapshot::decode(snapshot)?;
+        if snapshot.version != KVS_SNAPSHOT_VERSION {
+            anyhow::bail!(
+                "Unsupported KVS snapshot version: {}, expected {}",
+                snapshot.version,
+                KVS_SNAPSHOT_VERSION
+            );
+        }
+        let attributes = snapshot
+            .attributes
+            .iter()
+            .map(|r| {
+                let attr = r
+                    .attribute
+                    .as_ref()
+                    .ok_or_else(|| anyhow::anyhow!("KVS snapshot record has no attribute"))?;
+                Ok((r.ttl, Attribute::try_from(attr)?))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let blobs = snapshot
+            .blobs
+            .into_iter()
+            .map(|r| {
+                let blob = KvsBlob::new(&r.content_type, r.data);
+                blob.validate()?;
+                Ok(((r.namespace, r.name), (r.ttl, blob)))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        if policy == MergePolicy::Replace {
+            WS_DATA.kvs.invalidate_all();
+            WS_DATA.blobs.invalidate_all();
+        }
+        let mut imported = 0;
+        for (ttl, attr) in attributes {
+            let key = (attr.namespace.clone(), attr.name.clone());
+            if policy == MergePolicy::KeepExisting && WS_DATA.kvs.contains_key(&key) {
+                continue;
+            }
+            WS_DATA.kvs.insert(key, (ttl, attr)).await;
+            imported += 1;
+        }
+        for (key, value) in blobs {
+            if policy == MergePolicy::KeepExisting && WS_DATA.blobs.contains_key(&key) {
+                continue;
+            }
+            WS_DATA.blobs.insert(key, value).await;
+            imported += 1;
+        }
+        Ok(imported)
+    }
 }
 
 pub mod synchronous {
     use crate::get_or_init_async_runtime;
     use crate::primitives::attribute::Attribute;
+    use crate::webserver::kvs::{KvsBlob, KvsBlobError, MergePolicy};
 
     pub fn set_attributes(attributes: &[Attribute], ttl: Option<u64>) {
         let rt = get_or_init_async_runtime();
@@ -148,15 +437,173 @@ pub mod synchronous {
         let rt = get_or_init_async_runtime();
         rt.block_on(async { crate::webserver::kvs::asynchronous::del_attribute(ns, name).await })
     }
+
+    pub fn set_blob(
+        ns: &str,
+        name: &str,
+        blob: KvsBlob,
+        ttl: Option<u64>,
+    ) -> Result<(), KvsBlobError> {
+        let rt = get_or_init_async_runtime();
+        rt.block_on(async {
+            crate::webserver::kvs::asynchronous::set_blob(ns, name, blob, ttl).await
+        })
+    }
+
+    pub fn get_blob(ns: &str, name: &str) -> Option<KvsBlob> {
+        let rt = get_or_init_async_runtime();
+        rt.block_on(async { crate::webserver::kvs::asynchronous::get_blob(ns, name).await })
+    }
+
+    pub fn del_blob(ns: &str, name: &str) -> Option<KvsBlob> {
+        let rt = get_or_init_async_runtime();
+        rt.block_on(async { crate::webserver::kvs::asynchronous::del_blob(ns, name).await })
+    }
+
+    pub fn search_blob_keys(ns: &Option<String>, name: &Option<String>) -> Vec<(String, String)> {
+        let rt = get_or_init_async_runtime();
+        rt.block_on(async { crate::webserver::kvs::asynchronous::search_blob_keys(ns, name).await })
+    }
+
+    pub fn export_snapshot() -> Vec<u8> {
+        let rt = get_or_init_async_runtime();
+        rt.block_on(async { crate::webserver::kvs::asynchronous::export_snapshot().await })
+    }
+
+    pub fn import_snapshot(snapshot: &[u8], policy: MergePolicy) -> anyhow::Result<usize> {
+        let rt = get_or_init_async_runtime();
+        rt.block_on(async {
+            crate::webserver::kvs::asynchronous::import_snapshot(snapshot, policy).await
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::primitives::attribute::Attribute;
     use crate::webserver::kvs::synchronous::*;
+    use crate::webserver::kvs::{KvsBlob, KvsBlobError, MergePolicy, MAX_KVS_BLOB_SIZE};
     use std::thread::sleep;
 
     #[test]
+    #[serial_test::serial]
+    fn test_kvs_snapshot() -> anyhow::Result<()> {
+        del_attributes(&None, &None);
+        set_attributes(
+            &[Attribute::persistent(
+                "zones",
+                "entry",
+                vec![],
+                &None,
+                false,
+            )],
+            None,
+        );
+        set_attributes(
+            &[Attribute::persistent("zones", "exit", vec![], &None, false)],
+            Some(60_000),
+        );
+        set_blob(
+            "thresholds",
+            "speed",
+            KvsBlob::json(&serde_json::json!(42)),
+            None,
+        )?;
+        let snapshot = export_snapshot();
+
+        assert!(import_snapshot(b"garbage", MergePolicy::Replace).is_err());
+        assert_eq!(search_keys(&None, &None).len(), 2);
+
+        del_attributes(&None, &None);
+        set_attributes(
+            &[Attribute::persistent(
+                "zones",
+                "local",
+                vec![],
+                &None,
+                false,
+            )],
+            None,
+        );
+        set_blob(
+            "thresholds",
+            "speed",
+            KvsBlob::json(&serde_json::json!(0)),
+            None,
+        )?;
+        assert_eq!(import_snapshot(&snapshot, MergePolicy::KeepExisting)?, 2);
+        assert_eq!(
+            get_blob("thresholds", "speed").unwrap().as_json()?,
+            serde_json::json!(0)
+        );
+        assert_eq!(search_keys(&None, &None).len(), 3);
+
+        assert_eq!(import_snapshot(&snapshot, MergePolicy::Overwrite)?, 3);
+        assert_eq!(
+            get_blob("thresholds", "speed").unwrap().as_json()?,
+            serde_json::json!(42)
+        );
+
+        assert_eq!(import_snapshot(&snapshot, MergePolicy::Replace)?, 3);
+        assert!(get_attribute("zones", "local").is_none());
+        assert!(get_attribute("zones", "exit").is_some());
+
+        del_attributes(&None, &None);
+        del_blob("thresholds", "speed");
+        Ok(())
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_kvs_blobs() -> anyhow::Result<()> {
+        let zones = serde_json::json!({"zones": [[0, 0], [10, 10]]});
+        set_blob("blob_test", "zones", KvsBlob::json(&zones), None)?;
+        set_blob(
+            "blob_test",
+            "matrix",
+            KvsBlob::bytes(vec![1, 2, 3]),
+            Some(10),
+        )?;
+        assert!(matches!(
+            set_blob(
+                "blob_test",
+                "broken",
+                KvsBlob::new("application/json; charset=utf-8", b"{".to_vec()),
+                None
+            ),
+            Err(KvsBlobError::InvalidJson(_))
+        ));
+        assert!(matches!(
+            set_blob(
+                "blob_test",
+                "huge",
+                KvsBlob::bytes(vec![0; MAX_KVS_BLOB_SIZE + 1]),
+                None
+            ),
+            Err(KvsBlobError::TooLarge(_))
+        ));
+
+        let blob = get_blob("blob_test", "zones").unwrap();
+        assert!(blob.is_json());
+        assert_eq!(blob.as_json()?, zones);
+        let mut keys = search_blob_keys(&Some("blob_test".to_string()), &None);
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec![
+                ("blob_test".to_string(), "matrix".to_string()),
+                ("blob_test".to_string(), "zones".to_string())
+            ]
+        );
+        sleep(std::time::Duration::from_millis(11));
+        assert!(get_blob("blob_test", "matrix").is_none());
+        assert!(del_blob("blob_test", "zones").is_some());
+        assert!(get_blob("blob_test", "zones").is_none());
+        Ok(())
+    }
+
+    #[test]
+    #[serial_test::serial]
     fn test_kvs() {
         let attribute_set = vec![
             Attribute::persistent("abc", "xax", vec![], &None, false),
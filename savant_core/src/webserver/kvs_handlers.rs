@@ -1,11 +1,22 @@
 use crate::primitives::attribute_set::AttributeSet;
 use crate::protobuf::{from_pb, ToProtobuf};
+use crate::webserver::audit::{audit, AUDIT_KVS_DELETE, AUDIT_KVS_IMPORT};
+use crate::webserver::get_requester;
 use crate::webserver::kvs::asynchronous::{
-    del_attribute, del_attributes, get_attribute, search_attributes, search_keys, set_attributes,
+    del_attribute, del_attributes, del_blob, export_snapshot, get_attribute, get_blob,
+    import_snapshot, search_attributes, search_blob_keys, search_keys, set_attributes, set_blob,
 };
-use actix_web::{get, post, web, HttpResponse};
+use crate::webserver::kvs::{KvsBlob, KvsBlobError, MergePolicy, CONTENT_TYPE_BYTES};
+use crate::webserver::lease::asynchronous::{acquire_lease, get_lease, release_lease};
+use crate::webserver::lease::{Lease, LeaseError};
+use actix_web::http::header::CONTENT_TYPE;
+use actix_web::{get, post, web, HttpRequest, HttpResponse};
 use lazy_static::lazy_static;
 use savant_protobuf::generated;
+use serde::Deserialize;
+use serde_json::json;
+use std::str::FromStr;
+use std::time::Duration;
 
 lazy_static! {
     static ref EMPTY_SERIALIZED_ATTRIBUTE_SET: Vec<u8> = AttributeSet::new().to_pb().unwrap();
@@ -32,15 +43,30 @@ async fn set_handler(payload: web::Bytes) -> HttpResponse {
 }
 
 #[post("/kvs/delete/{ns}/{name}")]
-async fn delete_handler(path: web::Path<(String, String)>) -> HttpResponse {
+async fn delete_handler(req: HttpRequest, path: web::Path<(String, String)>) -> HttpResponse {
     let (ns, name) = path.into_inner();
+    audit(
+        AUDIT_KVS_DELETE,
+        &get_requester(&req),
+        &format!("{}/{}", ns, name),
+        true,
+    );
     del_attributes(&Some(ns), &Some(name)).await;
     HttpResponse::Ok().finish()
 }
 
 #[post("/kvs/delete-single/{ns}/{name}")]
-async fn delete_single_handler(path: web::Path<(String, String)>) -> HttpResponse {
+async fn delete_single_handler(
+    req: HttpRequest,
+    path: web::Path<(String, String)>,
+) -> HttpResponse {
     let (ns, name) = path.into_inner();
+    audit(
+        AUDIT_KVS_DELETE,
+        &get_requester(&req),
+        &format!("{}/{} (single)", ns, name),
+        true,
+    );
     let attr_opt = del_attribute(&ns, &name).await;
     if attr_opt.is_none() {
         return HttpResponse::Ok().body(EMPTY_SERIALIZED_ATTRIBUTE_SET.clone());
@@ -89,3 +115,171 @@ async fn get_handler(path: web::Path<(String, String)>) -> HttpResponse {
         HttpResponse::InternalServerError().finish()
     }
 }
+
+async fn set_blob_with_ttl(
+    req: HttpRequest,
+    payload: web::Bytes,
+    ns: &str,
+    name: &str,
+    ttl: Option<u64>,
+) -> HttpResponse {
+    let content_type = req
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or(CONTENT_TYPE_BYTES);
+    let blob = KvsBlob::new(content_type, payload.to_vec());
+    match set_blob(ns, name, blob, ttl).await {
+        Ok(()) => HttpResponse::Ok().finish(),
+        Err(e @ KvsBlobError::TooLarge(_)) => HttpResponse::PayloadTooLarge().body(e.to_string()),
+        Err(e @ KvsBlobError::InvalidJson(_)) => HttpResponse::BadRequest().body(e.to_string()),
+    }
+}
+
+#[post("/kvs/blob/set/{ns}/{name}")]
+async fn set_blob_handler(
+    req: HttpRequest,
+    payload: web::Bytes,
+    path: web::Path<(String, String)>,
+) -> HttpResponse {
+    let (ns, name) = path.into_inner();
+    set_blob_with_ttl(req, payload, &ns, &name, None).await
+}
+
+#[post("/kvs/blob/set-with-ttl/{ttl}/{ns}/{name}")]
+async fn set_blob_handler_ttl(
+    req: HttpRequest,
+    payload: web::Bytes,
+    path: web::Path<(u64, String, String)>,
+) -> HttpResponse {
+    let (ttl, ns, name) = path.into_inner();
+    set_blob_with_ttl(req, payload, &ns, &name, Some(ttl)).await
+}
+
+#[get("/kvs/blob/get/{ns}/{name}")]
+async fn get_blob_handler(path: web::Path<(String, String)>) -> HttpResponse {
+    let (ns, name) = path.into_inner();
+    match get_blob(&ns, &name).await {
+        Some(blob) => HttpResponse::Ok()
+            .content_type(blob.content_type)
+            .body(blob.data),
+        None => HttpResponse::NotFound().finish(),
+    }
+}
+
+#[post("/kvs/blob/delete/{ns}/{name}")]
+async fn delete_blob_handler(req: HttpRequest, path: web::Path<(String, String)>) -> HttpResponse {
+    let (ns, name) = path.into_inner();
+    audit(
+        AUDIT_KVS_DELETE,
+        &get_requester(&req),
+        &format!("{}/{} (blob)", ns, name),
+        true,
+    );
+    match del_blob(&ns, &name).await {
+        Some(blob) => HttpResponse::Ok()
+            .content_type(blob.content_type)
+            .body(blob.data),
+        None => HttpResponse::NotFound().finish(),
+    }
+}
+
+#[get("/kvs/blob/search-keys/{ns}/{name}")]
+async fn search_blob_keys_handler(path: web::Path<(String, String)>) -> HttpResponse {
+    let (ns, name) = path.into_inner();
+    let keys = search_blob_keys(&Some(ns), &Some(name)).await;
+    HttpResponse::Ok().json(keys)
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ImportQuery {
+    policy: Option<String>,
+}
+
+/// Registered as `GET /kvs/snapshot`.
+///
+pub(crate) async fn export_snapshot_handler() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("application/x-protobuf")
+        .body(export_snapshot().await)
+}
+
+/// Registered as `POST /kvs/snapshot`, the merge policy is passed as the `policy` query
+/// parameter (`overwrite` by default).
+///
+pub(crate) async fn import_snapshot_handler(
+    req: HttpRequest,
+    payload: web::Bytes,
+    query: web::Query<ImportQuery>,
+) -> HttpResponse {
+    let policy = match query.policy.as_deref().map(MergePolicy::from_str) {
+        None => MergePolicy::default(),
+        Some(Ok(policy)) => policy,
+        Some(Err(e)) => return HttpResponse::BadRequest().body(e.to_string()),
+    };
+    let requester = get_requester(&req);
+    match import_snapshot(&payload, policy).await {
+        Ok(imported) => {
+            audit(
+                AUDIT_KVS_IMPORT,
+                &requester,
+                &format!("{:?}, {} records", policy, imported),
+                true,
+            );
+            HttpResponse::Ok().json(json!({ "imported": imported }))
+        }
+        Err(e) => {
+            audit(
+                AUDIT_KVS_IMPORT,
+                &requester,
+                &format!("{:?}, {}", policy, e),
+                false,
+            );
+            HttpResponse::BadRequest().body(e.to_string())
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct LeaseQuery {
+    holder: String,
+    ttl_ms: Option<u64>,
+}
+
+fn lease_response(res: Result<Lease, LeaseError>) -> HttpResponse {
+    match res {
+        Ok(lease) => HttpResponse::Ok().json(lease),
+        Err(LeaseError::Held(lease)) => HttpResponse::Conflict().json(lease),
+        Err(e @ LeaseError::Persistence(_)) => {
+            HttpResponse::InternalServerError().body(e.to_string())
+        }
+        Err(e) => HttpResponse::Conflict().body(e.to_string()),
+    }
+}
+
+#[post("/kvs/lease/{name}/acquire")]
+async fn acquire_lease_handler(
+    name: web::Path<String>,
+    query: web::Query<LeaseQuery>,
+) -> HttpResponse {
+    let Some(ttl_ms) = query.ttl_ms.filter(|ttl| *ttl > 0) else {
+        return HttpResponse::BadRequest().body("A positive ttl_ms is required");
+    };
+    lease_response(acquire_lease(&name, &query.holder, Duration::from_millis(ttl_ms)).await)
+}
+
+#[post("/kvs/lease/{name}/release")]
+async fn release_lease_handler(
+    name: web::Path<String>,
+    query: web::Query<LeaseQuery>,
+) -> HttpResponse {
+    lease_response(release_lease(&name, &query.holder).await)
+}
+
+#[get("/kvs/lease/{name}")]
+async fn get_lease_handler(name: web::Path<String>) -> HttpResponse {
+    match get_lease(&name).await {
+        Some(lease) => HttpResponse::Ok().json(lease),
+        None => HttpResponse::NotFound().finish(),
+    }
+}
@@ -0,0 +1,252 @@
+use hashbrown::HashMap;
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+
+/// A lease of the KVS. The token grows every time the lease changes its holder, so a
+/// resource guarded by the lease can reject the writes of a previous holder whose lease
+/// expired while it was paused (fencing).
+///
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lease {
+    pub name: String,
+    pub holder: String,
+    pub token: u64,
+    /// The expiration time in milliseconds since the UNIX epoch.
+    pub expires_at: u64,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum LeaseError {
+    #[error("Lease {} is held by {} until {}", .0.name, .0.holder, .0.expires_at)]
+    Held(Lease),
+    #[error("Lease {0} is not held by {1}")]
+    NotHeld(String, String),
+    #[error("Failed to persist the lease token: {0}")]
+    Persistence(String),
+}
+
+impl Lease {
+    pub fn is_expired_at(&self, now_ms: u64) -> bool {
+        self.expires_at <= now_ms
+    }
+}
+
+/// The leases are kept apart from the KVS, so they are never evicted, expired or replaced
+/// by the KVS operations. The tokens of all leases come from one counter, which is
+/// persisted when a token file is set, so the tokens keep growing across restarts.
+///
+#[derive(Default)]
+struct Leases {
+    leases: HashMap<String, Lease>,
+    last_token: u64,
+    token_file: Option<PathBuf>,
+}
+
+impl Leases {
+    fn next_token(&mut self) -> Result<u64, LeaseError> {
+        let token = self.last_token + 1;
+        if let Some(path) = &self.token_file {
+            persist_token(path, token).map_err(|e| LeaseError::Persistence(e.to_string()))?;
+        }
+        self.last_token = token;
+        Ok(token)
+    }
+}
+
+lazy_static! {
+    static ref LEASES: Mutex<Leases> = Mutex::new(Leases::default());
+}
+
+fn persist_token(path: &Path, token: u64) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let mut file = std::fs::File::create(&tmp)?;
+    file.write_all(token.to_string().as_bytes())?;
+    file.sync_all()?;
+    drop(file);
+    std::fs::rename(&tmp, path)?;
+    #[cfg(unix)]
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Persists the token counter of the leases in the file, the counter continues from the
+/// token stored in the file. The file must be set before the leases are acquired to keep
+/// the tokens growing across restarts.
+///
+pub fn set_lease_token_file(path: &Path) -> anyhow::Result<()> {
+    let stored = match std::fs::read_to_string(path) {
+        Ok(s) => s.trim().parse::<u64>()?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+        Err(e) => return Err(e.into()),
+    };
+    let mut leases = LEASES.lock();
+    leases.last_token = leases.last_token.max(stored);
+    persist_token(path, leases.last_token)?;
+    leases.token_file = Some(path.to_path_buf());
+    Ok(())
+}
+
+/// Changes the lease atomically.
+///
+fn update_lease<F>(name: &str, f: F) -> Result<Lease, LeaseError>
+where
+    F: FnOnce(&mut Leases, Option<&Lease>) -> Result<Lease, LeaseError>,
+{
+    let mut leases = LEASES.lock();
+    let current = leases.leases.get(name).cloned();
+    let lease = f(&mut leases, current.as_ref())?;
+    leases.leases.insert(name.to_string(), lease.clone());
+    Ok(lease)
+}
+
+/// The outcome of an acquisition: the current lease when it is held by another holder, the
+/// new lease otherwise. The holder renewing a live lease keeps the token.
+///
+fn next_lease(
+    leases: &mut Leases,
+    current: Option<&Lease>,
+    name: &str,
+    holder: &str,
+    ttl: Duration,
+    now: u64,
+) -> Result<Lease, LeaseError> {
+    let expires_at = now + ttl.as_millis() as u64;
+    let token = match current {
+        Some(c) if c.holder == holder && !c.is_expired_at(now) => c.token,
+        Some(c) if !c.is_expired_at(now) => return Err(LeaseError::Held(c.clone())),
+        _ => leases.next_token()?,
+    };
+    Ok(Lease {
+        name: name.to_string(),
+        holder: holder.to_string(),
+        token,
+        expires_at,
+    })
+}
+
+pub mod synchronous {
+    use crate::clock::system_now_ms;
+    use crate::webserver::lease::{next_lease, update_lease, Lease, LeaseError, LEASES};
+    use std::time::Duration;
+
+    /// Acquires or renews the lease for the TTL.
+    ///
+    pub fn acquire_lease(name: &str, holder: &str, ttl: Duration) -> Result<Lease, LeaseError> {
+        update_lease(name, |leases, current| {
+            next_lease(leases, current, name, holder, ttl, system_now_ms())
+        })
+    }
+
+    /// Expires the lease held by the holder, so another holder acquires it without waiting.
+    ///
+    pub fn release_lease(name: &str, holder: &str) -> Result<Lease, LeaseError> {
+        update_lease(name, |_, current| {
+            let now = system_now_ms();
+            match current {
+                Some(c) if c.holder == holder && !c.is_expired_at(now) => Ok(Lease {
+                    expires_at: now,
+                    ..c.clone()
+                }),
+                _ => Err(LeaseError::NotHeld(name.to_string(), holder.to_string())),
+            }
+        })
+    }
+
+    /// The lease, expired leases are returned too, so their tokens are known.
+    ///
+    pub fn get_lease(name: &str) -> Option<Lease> {
+        LEASES.lock().leases.get(name).cloned()
+    }
+
+    /// Checks the fencing token presented by a writer: only the token of the live lease is
+    /// accepted.
+    ///
+    pub fn is_current_token(name: &str, token: u64) -> bool {
+        get_lease(name).is_some_and(|l| l.token == token && !l.is_expired_at(system_now_ms()))
+    }
+}
+
+/// The leases for the async code, the acquisition may write the token file, so it runs on
+/// the blocking pool.
+///
+pub mod asynchronous {
+    use crate::webserver::lease::{synchronous, Lease, LeaseError};
+    use std::time::Duration;
+
+    pub async fn acquire_lease(
+        name: &str,
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<Lease, LeaseError> {
+        let (name, holder) = (name.to_string(), holder.to_string());
+        tokio::task::spawn_blocking(move || synchronous::acquire_lease(&name, &holder, ttl))
+            .await
+            .map_err(|e| LeaseError::Persistence(e.to_string()))?
+    }
+
+    pub async fn release_lease(name: &str, holder: &str) -> Result<Lease, LeaseError> {
+        synchronous::release_lease(name, holder)
+    }
+
+    pub async fn get_lease(name: &str) -> Option<Lease> {
+        synchronous::get_lease(name)
+    }
+
+    pub async fn is_current_token(name: &str, token: u64) -> bool {
+        synchronous::is_current_token(name, token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::webserver::lease::synchronous::{
+        acquire_lease, get_lease, is_current_token, release_lease,
+    };
+    use crate::webserver::lease::{set_lease_token_file, LeaseError, LEASES};
+    use std::time::Duration;
+
+    #[test]
+    #[serial_test::serial]
+    fn test_lease() -> anyhow::Result<()> {
+        let ttl = Duration::from_millis(50);
+        let token = acquire_lease("test_lease", "a", ttl)?.token;
+        assert!(matches!(
+            acquire_lease("test_lease", "b", ttl),
+            Err(LeaseError::Held(l)) if l.holder == "a"
+        ));
+        assert_eq!(acquire_lease("test_lease", "a", ttl)?.token, token);
+        assert!(is_current_token("test_lease", token));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(!is_current_token("test_lease", token));
+        let lease = acquire_lease("test_lease", "b", ttl)?;
+        assert!(lease.token > token);
+        assert!(release_lease("test_lease", "a").is_err());
+        release_lease("test_lease", "b")?;
+        assert!(acquire_lease("test_lease", "a", ttl)?.token > lease.token);
+        assert_eq!(get_lease("test_lease").map(|l| l.holder), Some("a".into()));
+        Ok(())
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_lease_token_file() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join(format!("lease_token_{}", std::process::id()));
+        std::fs::write(&path, "1000000")?;
+        set_lease_token_file(&path)?;
+        let token = acquire_lease("test_lease_file", "a", Duration::from_millis(50))?.token;
+        assert_eq!(token, 1_000_001);
+        assert_eq!(std::fs::read_to_string(&path)?, "1000001");
+        LEASES.lock().token_file = None;
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
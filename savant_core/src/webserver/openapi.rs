@@ -0,0 +1,752 @@
+use crate::version;
+use serde_json::{json, Map, Value};
+
+const JSON: &str = "application/json";
+const PROTOBUF: &str = "application/x-protobuf";
+const TEXT: &str = "text/plain";
+const BLOB: &str = "*/*";
+const BINARY: &str = "application/octet-stream";
+
+/// A parameter of an endpoint: the name, the schema type and the description. Parameters
+/// named in the path template are path parameters, the others are query parameters.
+///
+type Parameter = (&'static str, &'static str, &'static str);
+
+/// A response of an endpoint: the status code, the content type and the description.
+///
+type Response = (u16, &'static str, &'static str);
+
+/// The description of an endpoint of the embedded webserver. Every handler registered in
+/// `init_webserver` has an entry in [`ENDPOINTS`], the tests compare the entries with the
+/// routes of the webserver sources.
+///
+#[derive(Debug)]
+pub(crate) struct Endpoint {
+    pub method: &'static str,
+    pub path: &'static str,
+    pub tag: &'static str,
+    pub summary: &'static str,
+    pub parameters: &'static [Parameter],
+    pub request: Option<&'static str>,
+    pub responses: &'static [Response],
+}
+
+const KVS_KEY: &[Parameter] = &[
+    ("ns", "string", "The namespace glob"),
+    ("name", "string", "The name glob"),
+];
+
+const KVS_EXACT_KEY: &[Parameter] = &[
+    ("ns", "string", "The namespace"),
+    ("name", "string", "The name"),
+];
+
+const LEASE_RELEASE: &[Parameter] = &[
+    ("name", "string", "The lease name"),
+    ("holder", "string", "The identity of the replica"),
+];
+
+const LEASE_ACQUIRE: &[Parameter] = &[
+    ("name", "string", "The lease name"),
+    ("holder", "string", "The identity of the replica"),
+    (
+        "ttl_ms",
+        "integer",
+        "How long the lease is held without a renewal",
+    ),
+];
+
+const ATTRIBUTE_SET: &[Response] = &[(200, PROTOBUF, "Serialized attribute set")];
+
+const BLOB_SET: &[Response] = &[
+    (200, TEXT, "Blob set"),
+    (400, TEXT, "Invalid JSON document"),
+    (413, TEXT, "Blob is too large"),
+];
+
+const BLOB_FOUND: &[Response] = &[
+    (200, BLOB, "Blob with its content type"),
+    (404, TEXT, "No blob"),
+];
+
+pub(crate) const ENDPOINTS: &[Endpoint] = &[
+    Endpoint {
+        method: "get",
+        path: "/status",
+        tag: "status",
+        summary: "Pipeline status",
+        parameters: &[],
+        request: None,
+        responses: &[(200, JSON, "One of running, stopped, shutdown")],
+    },
+    Endpoint {
+        method: "get",
+        path: "/health",
+        tag: "status",
+        summary: "Readiness of the registered pipelines and their stages",
+        parameters: &[],
+        request: None,
+        responses: &[
+            (200, JSON, "All stages are ready"),
+            (503, JSON, "Some stages are warming up"),
+        ],
+    },
+    Endpoint {
+        method: "get",
+        path: "/capabilities",
+        tag: "status",
+        summary: "Package version, protocol version and compiled features",
+        parameters: &[],
+        request: None,
+        responses: &[(200, JSON, "Capabilities")],
+    },
+    Endpoint {
+        method: "post",
+        path: "/shutdown/{token}/{mode}",
+        tag: "control",
+        summary: "Request the pipeline shutdown",
+        parameters: &[
+            ("token", "string", "The shutdown token"),
+            (
+                "mode",
+                "string",
+                "graceful or signal, the signal mode calls the shutdown callback when it is set",
+            ),
+        ],
+        request: None,
+        responses: &[
+            (200, JSON, "Shutdown requested"),
+            (401, TEXT, "Invalid token"),
+            (
+                500,
+                TEXT,
+                "Shutdown is not supported, already requested or cannot be delivered",
+            ),
+        ],
+    },
+    Endpoint {
+        method: "get",
+        path: "/audit",
+        tag: "control",
+        summary: "Audit records of control-plane operations, the oldest first",
+        parameters: &[
+            ("operation", "string", "Only records of the operation"),
+            (
+                "since_ms",
+                "integer",
+                "Only records since the UNIX time in milliseconds",
+            ),
+            (
+                "limit",
+                "integer",
+                "The maximum number of the most recent records",
+            ),
+        ],
+        request: None,
+        responses: &[(200, JSON, "Audit records")],
+    },
+    Endpoint {
+        method: "get",
+        path: "/metrics",
+        tag: "status",
+        summary: "Pipeline and system metrics",
+        parameters: &[],
+        request: None,
+        responses: &[
+            (
+                200,
+                "application/openmetrics-text",
+                "OpenMetrics exposition",
+            ),
+            (
+                500,
+                "application/openmetrics-text",
+                "Failed to build metrics",
+            ),
+        ],
+    },
+    Endpoint {
+        method: "get",
+        path: "/diagnostics",
+        tag: "status",
+        summary: "Gzipped tar archive with the status, topology, queues, metrics, configuration and recent logs",
+        parameters: &[(
+            "frames",
+            "integer",
+            "The number of frames per stage whose metadata is included, at most 16",
+        )],
+        request: None,
+        responses: &[
+            (200, "application/gzip", "Diagnostics bundle"),
+            (500, TEXT, "Failed to assemble the bundle"),
+        ],
+    },
+    Endpoint {
+        method: "get",
+        path: "/pipelines/topology/{format}",
+        tag: "status",
+        summary: "Topology of the registered pipelines",
+        parameters: &[("format", "string", "dot or mermaid")],
+        request: None,
+        responses: &[
+            (200, TEXT, "Rendered topology"),
+            (400, TEXT, "Unknown format"),
+        ],
+    },
+    Endpoint {
+        method: "get",
+        path: "/clips/{source_id}",
+        tag: "clips",
+        summary: "Recorded frames of the source around the interval, bounded by keyframes",
+        parameters: &[
+            ("source_id", "string", "The source"),
+            (
+                "start_ms",
+                "integer",
+                "The interval start, the frame PTS in milliseconds",
+            ),
+            (
+                "end_ms",
+                "integer",
+                "The interval end, the frame PTS in milliseconds",
+            ),
+        ],
+        request: None,
+        responses: &[
+            (200, BINARY, "Journal of length-delimited messages"),
+            (400, TEXT, "Invalid interval or unreadable journal"),
+            (404, TEXT, "No clip journal"),
+        ],
+    },
+    Endpoint {
+        method: "post",
+        path: "/jobs",
+        tag: "jobs",
+        summary: "Start a job re-processing a recorded journal, authorized with the bearer token",
+        parameters: &[],
+        request: Some(JSON),
+        responses: &[
+            (200, JSON, "The status of the started job"),
+            (
+                400,
+                TEXT,
+                "Unknown processor, invalid configuration, path outside the jobs directory or unreadable journal",
+            ),
+            (401, TEXT, "Invalid token"),
+            (403, TEXT, "No token set"),
+        ],
+    },
+    Endpoint {
+        method: "get",
+        path: "/jobs",
+        tag: "jobs",
+        summary: "Statuses of the jobs, the finished ones included",
+        parameters: &[],
+        request: None,
+        responses: &[(200, JSON, "Job statuses")],
+    },
+    Endpoint {
+        method: "get",
+        path: "/jobs/{id}",
+        tag: "jobs",
+        summary: "Status and progress of the job",
+        parameters: &[("id", "string", "The job id")],
+        request: None,
+        responses: &[(200, JSON, "Job status"), (404, TEXT, "No such job")],
+    },
+    Endpoint {
+        method: "post",
+        path: "/jobs/{id}/{action}",
+        tag: "jobs",
+        summary: "Pause, resume or cancel the job, authorized with the bearer token",
+        parameters: &[
+            ("id", "string", "The job id"),
+            ("action", "string", "pause, resume or cancel"),
+        ],
+        request: None,
+        responses: &[
+            (200, JSON, "The job status after the action"),
+            (400, TEXT, "Unknown action"),
+            (401, TEXT, "Invalid token"),
+            (403, TEXT, "No token set"),
+            (404, TEXT, "No such job"),
+            (409, TEXT, "The action does not apply to the job state"),
+        ],
+    },
+    Endpoint {
+        method: "delete",
+        path: "/jobs/{id}",
+        tag: "jobs",
+        summary: "Forget the finished job, authorized with the bearer token",
+        parameters: &[("id", "string", "The job id")],
+        request: None,
+        responses: &[
+            (200, JSON, "The status of the removed job"),
+            (401, TEXT, "Invalid token"),
+            (403, TEXT, "No token set"),
+            (404, TEXT, "No such job"),
+            (409, TEXT, "The job is not finished"),
+        ],
+    },
+    Endpoint {
+        method: "get",
+        path: "/jobs/{id}/metrics",
+        tag: "jobs",
+        summary: "Stream of the job status every second until the job finishes",
+        parameters: &[("id", "string", "The job id")],
+        request: None,
+        responses: &[
+            (200, "application/x-ndjson", "Job statuses as JSON lines"),
+            (404, TEXT, "No such job"),
+        ],
+    },
+    Endpoint {
+        method: "get",
+        path: "/openapi.json",
+        tag: "status",
+        summary: "This document",
+        parameters: &[],
+        request: None,
+        responses: &[(200, JSON, "OpenAPI 3 document")],
+    },
+    Endpoint {
+        method: "post",
+        path: "/kvs/set",
+        tag: "kvs",
+        summary: "Set attributes",
+        parameters: &[],
+        request: Some(PROTOBUF),
+        responses: &[
+            (200, TEXT, "Attributes set"),
+            (400, TEXT, "Invalid attribute set"),
+        ],
+    },
+    Endpoint {
+        method: "post",
+        path: "/kvs/set-with-ttl/{ttl}",
+        tag: "kvs",
+        summary: "Set attributes expiring after the TTL",
+        parameters: &[("ttl", "integer", "The TTL in milliseconds")],
+        request: Some(PROTOBUF),
+        responses: &[
+            (200, TEXT, "Attributes set"),
+            (400, TEXT, "Invalid attribute set"),
+        ],
+    },
+    Endpoint {
+        method: "post",
+        path: "/kvs/delete/{ns}/{name}",
+        tag: "kvs",
+        summary: "Delete attributes matching the globs",
+        parameters: KVS_KEY,
+        request: None,
+        responses: &[(200, TEXT, "Attributes deleted")],
+    },
+    Endpoint {
+        method: "post",
+        path: "/kvs/delete-single/{ns}/{name}",
+        tag: "kvs",
+        summary: "Delete an attribute and return it",
+        parameters: KVS_EXACT_KEY,
+        request: None,
+        responses: ATTRIBUTE_SET,
+    },
+    Endpoint {
+        method: "get",
+        path: "/kvs/search/{ns}/{name}",
+        tag: "kvs",
+        summary: "Attributes matching the globs",
+        parameters: KVS_KEY,
+        request: None,
+        responses: ATTRIBUTE_SET,
+    },
+    Endpoint {
+        method: "get",
+        path: "/kvs/search-keys/{ns}/{name}",
+        tag: "kvs",
+        summary: "Keys of attributes matching the globs",
+        parameters: KVS_KEY,
+        request: None,
+        responses: &[(200, JSON, "Namespace and name pairs")],
+    },
+    Endpoint {
+        method: "get",
+        path: "/kvs/get/{ns}/{name}",
+        tag: "kvs",
+        summary: "An attribute",
+        parameters: KVS_EXACT_KEY,
+        request: None,
+        responses: ATTRIBUTE_SET,
+    },
+    Endpoint {
+        method: "post",
+        path: "/kvs/blob/set/{ns}/{name}",
+        tag: "kvs",
+        summary: "Set a blob tagged with the request content type",
+        parameters: KVS_EXACT_KEY,
+        request: Some(BLOB),
+        responses: BLOB_SET,
+    },
+    Endpoint {
+        method: "post",
+        path: "/kvs/blob/set-with-ttl/{ttl}/{ns}/{name}",
+        tag: "kvs",
+        summary: "Set a blob expiring after the TTL",
+        parameters: &[
+            ("ttl", "integer", "The TTL in milliseconds"),
+            ("ns", "string", "The namespace"),
+            ("name", "string", "The name"),
+        ],
+        request: Some(BLOB),
+        responses: BLOB_SET,
+    },
+    Endpoint {
+        method: "get",
+        path: "/kvs/blob/get/{ns}/{name}",
+        tag: "kvs",
+        summary: "A blob with its content type",
+        parameters: KVS_EXACT_KEY,
+        request: None,
+        responses: BLOB_FOUND,
+    },
+    Endpoint {
+        method: "post",
+        path: "/kvs/blob/delete/{ns}/{name}",
+        tag: "kvs",
+        summary: "Delete a blob and return it",
+        parameters: KVS_EXACT_KEY,
+        request: None,
+        responses: BLOB_FOUND,
+    },
+    Endpoint {
+        method: "get",
+        path: "/kvs/blob/search-keys/{ns}/{name}",
+        tag: "kvs",
+        summary: "Keys of blobs matching the globs",
+        parameters: KVS_KEY,
+        request: None,
+        responses: &[(200, JSON, "Namespace and name pairs")],
+    },
+    Endpoint {
+        method: "post",
+        path: "/kvs/lease/{name}/acquire",
+        tag: "kvs",
+        summary: "Acquire or renew a lease",
+        parameters: LEASE_ACQUIRE,
+        request: None,
+        responses: &[
+            (200, JSON, "The acquired lease with its fencing token"),
+            (400, TEXT, "No positive TTL"),
+            (409, JSON, "The lease held by another holder"),
+        ],
+    },
+    Endpoint {
+        method: "post",
+        path: "/kvs/lease/{name}/release",
+        tag: "kvs",
+        summary: "Release a lease held by the holder",
+        parameters: LEASE_RELEASE,
+        request: None,
+        responses: &[
+            (200, JSON, "The released lease"),
+            (409, TEXT, "The lease is not held by the holder"),
+        ],
+    },
+    Endpoint {
+        method: "get",
+        path: "/kvs/lease/{name}",
+        tag: "kvs",
+        summary: "A lease, expired leases included",
+        parameters: &[("name", "string", "The lease name")],
+        request: None,
+        responses: &[(200, JSON, "The lease"), (404, TEXT, "No lease")],
+    },
+    Endpoint {
+        method: "get",
+        path: "/kvs/snapshot",
+        tag: "kvs",
+        summary: "Snapshot of the attributes and blobs",
+        parameters: &[],
+        request: None,
+        responses: &[(200, PROTOBUF, "Serialized snapshot")],
+    },
+    Endpoint {
+        method: "post",
+        path: "/kvs/snapshot",
+        tag: "kvs",
+        summary: "Import a snapshot",
+        parameters: &[(
+            "policy",
+            "string",
+            "replace, overwrite (default) or keep_existing",
+        )],
+        request: Some(PROTOBUF),
+        responses: &[
+            (200, JSON, "Number of imported records"),
+            (400, TEXT, "Invalid snapshot or merge policy"),
+        ],
+    },
+    Endpoint {
+        method: "get",
+        path: "/ha/status",
+        tag: "ha",
+        summary: "Role of the pipeline and replication status",
+        parameters: &[],
+        request: None,
+        responses: &[(200, JSON, "Role, last snapshot and failed health checks")],
+    },
+    Endpoint {
+        method: "post",
+        path: "/ha/snapshot",
+        tag: "ha",
+        summary: "Restore the state snapshot sent by the active pipeline",
+        parameters: &[],
+        request: Some(PROTOBUF),
+        responses: &[
+            (200, JSON, "Snapshot restored"),
+            (409, TEXT, "Not a standby, stale or invalid snapshot"),
+        ],
+    },
+    Endpoint {
+        method: "post",
+        path: "/ha/promote",
+        tag: "ha",
+        summary: "Promote the standby to active",
+        parameters: &[],
+        request: None,
+        responses: &[
+            (202, JSON, "The promotion is requested, the status before it"),
+            (409, TEXT, "Not a standby"),
+        ],
+    },
+];
+
+/// The endpoints of the fault injection, available with the `chaos` feature.
+///
+#[cfg(feature = "chaos")]
+pub(crate) const CHAOS_ENDPOINTS: &[Endpoint] = &[
+    Endpoint {
+        method: "get",
+        path: "/chaos/faults",
+        tag: "chaos",
+        summary: "Fault injectors of the registered pipelines",
+        parameters: &[],
+        request: None,
+        responses: &[(200, JSON, "Pipeline, stage, configuration and statistics")],
+    },
+    Endpoint {
+        method: "post",
+        path: "/chaos/faults/{pipeline}/{stage}",
+        tag: "chaos",
+        summary: "Install the fault injector of a stage",
+        parameters: CHAOS_STAGE,
+        request: Some(JSON),
+        responses: &[
+            (200, JSON, "Injector installed"),
+            (400, TEXT, "Invalid configuration"),
+            (404, TEXT, "No pipeline or stage"),
+        ],
+    },
+    Endpoint {
+        method: "post",
+        path: "/chaos/faults/{pipeline}/{stage}/clear",
+        tag: "chaos",
+        summary: "Remove the fault injector of a stage",
+        parameters: CHAOS_STAGE,
+        request: None,
+        responses: &[
+            (200, JSON, "Injector removed"),
+            (404, TEXT, "No pipeline or stage"),
+        ],
+    },
+];
+
+#[cfg(feature = "chaos")]
+const CHAOS_STAGE: &[Parameter] = &[
+    ("pipeline", "string", "The pipeline name"),
+    ("stage", "string", "The stage name"),
+];
+
+fn schema(content_type: &str) -> Value {
+    if content_type == JSON {
+        json!({})
+    } else if content_type == PROTOBUF || content_type == BLOB {
+        json!({"type": "string", "format": "binary"})
+    } else {
+        json!({"type": "string"})
+    }
+}
+
+fn operation(endpoint: &Endpoint) -> Value {
+    let parameters = endpoint
+        .parameters
+        .iter()
+        .map(|(name, kind, description)| {
+            let in_path = endpoint.path.contains(&format!("{{{}}}", name));
+            json!({
+                "name": name,
+                "in": if in_path { "path" } else { "query" },
+                "required": in_path,
+                "description": description,
+                "schema": {"type": kind},
+            })
+        })
+        .collect::<Vec<_>>();
+    let responses = endpoint
+        .responses
+        .iter()
+        .map(|(code, content_type, description)| {
+            (
+                code.to_string(),
+                json!({
+                    "description": description,
+                    "content": {(content_type.to_string()): {"schema": schema(content_type)}},
+                }),
+            )
+        })
+        .collect::<Map<_, _>>();
+    let mut operation = json!({
+        "tags": [endpoint.tag],
+        "summary": endpoint.summary,
+        "operationId": format!(
+            "{}_{}",
+            endpoint.method,
+            endpoint
+                .path
+                .trim_start_matches('/')
+                .replace(['/', '-', '.'], "_")
+                .replace(['{', '}'], "")
+        ),
+        "parameters": parameters,
+        "responses": responses,
+    });
+    if let Some(content_type) = endpoint.request {
+        operation["requestBody"] = json!({
+            "required": true,
+            "content": {(content_type.to_string()): {"schema": schema(content_type)}},
+        });
+    }
+    operation
+}
+
+/// Builds the OpenAPI 3 document of the webserver endpoints.
+///
+pub fn openapi_spec() -> Value {
+    let mut paths = Map::new();
+    let endpoints = ENDPOINTS.iter();
+    #[cfg(feature = "chaos")]
+    let endpoints = endpoints.chain(CHAOS_ENDPOINTS);
+    for endpoint in endpoints {
+        let item = paths
+            .entry(endpoint.path.to_string())
+            .or_insert_with(|| json!({}));
+        item[endpoint.method] = operation(endpoint);
+    }
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Savant pipeline control API",
+            "version": version(),
+        },
+        "paths": paths,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::webserver::openapi::{openapi_spec, ENDPOINTS};
+    use regex::Regex;
+    use std::collections::BTreeSet;
+    use std::path::Path;
+
+    #[test]
+    fn test_spec() {
+        let spec = openapi_spec();
+        assert_eq!(spec["openapi"], "3.0.3");
+        for endpoint in ENDPOINTS {
+            let operation = &spec["paths"][endpoint.path][endpoint.method];
+            assert!(operation.is_object(), "{} is missing", endpoint.path);
+            let path_params = operation["parameters"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter(|p| p["in"] == "path")
+                .count();
+            assert_eq!(
+                path_params,
+                endpoint.path.matches('{').count(),
+                "{} has undocumented path parameters",
+                endpoint.path
+            );
+        }
+        let shutdown = &spec["paths"]["/shutdown/{token}/{mode}"]["post"];
+        assert_eq!(shutdown["operationId"], "post_shutdown_token_mode");
+        assert!(spec["paths"]["/kvs/set"]["post"]["requestBody"].is_object());
+        assert_eq!(
+            spec["paths"]["/audit"]["get"]["parameters"][0]["in"],
+            "query"
+        );
+    }
+
+    /// Compares the documented endpoints with the routes of the webserver sources: the
+    /// handler attributes, whose handlers must be registered as services, and the resources
+    /// registered with their routes.
+    ///
+    #[test]
+    fn test_endpoints_match_routes() -> anyhow::Result<()> {
+        let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        let mut files = vec![src.join("webserver.rs")];
+        for entry in std::fs::read_dir(src.join("webserver"))? {
+            let path = entry?.path();
+            let chaos = path.ends_with("chaos_handlers.rs");
+            if path.extension().is_some_and(|e| e == "rs") && (cfg!(feature = "chaos") || !chaos) {
+                files.push(path);
+            }
+        }
+        let sources = files
+            .iter()
+            .map(std::fs::read_to_string)
+            .collect::<Result<Vec<_>, _>>()?
+            .join("\n");
+
+        let handler = Regex::new(
+            r#"#\[(get|post|put|delete)\("([^"]+)"\)\]\s*(?:pub(?:\(crate\))?\s+)?async\s+fn\s+(\w+)"#,
+        )?;
+        let resource = Regex::new(r#"(?s)web::resource\("([^"]+)"\)(.*?)\)\),"#)?;
+        let route = Regex::new(r"web::(get|post|put|delete)\(\)")?;
+        let mut routes = BTreeSet::new();
+        for c in handler.captures_iter(&sources) {
+            let service = Regex::new(&format!(r"\.service\(\s*{}\s*\)", &c[3]))?;
+            assert!(
+                service.is_match(&sources),
+                "Handler {} of {} is not registered",
+                &c[3],
+                &c[2]
+            );
+            routes.insert((c[1].to_string(), c[2].to_string()));
+        }
+        for c in resource.captures_iter(&sources) {
+            for r in route.captures_iter(&c[2]) {
+                routes.insert((r[1].to_string(), c[1].to_string()));
+            }
+        }
+
+        let endpoints = ENDPOINTS.iter();
+        #[cfg(feature = "chaos")]
+        let endpoints = endpoints.chain(crate::webserver::openapi::CHAOS_ENDPOINTS);
+        let documented = endpoints
+            .map(|e| (e.method.to_string(), e.path.to_string()))
+            .collect::<BTreeSet<_>>();
+        assert_eq!(
+            routes.difference(&documented).collect::<Vec<_>>(),
+            Vec::<&(String, String)>::new(),
+            "The routes are not documented"
+        );
+        assert_eq!(
+            documented.difference(&routes).collect::<Vec<_>>(),
+            Vec::<&(String, String)>::new(),
+            "The documented endpoints have no routes"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// The function called when the shutdown is requested with the `signal` mode.
+///
+pub type ShutdownCallback = Arc<dyn Fn() + Send + Sync>;
+
+lazy_static! {
+    static ref SHUTDOWN_CALLBACK: Mutex<Option<ShutdownCallback>> = Mutex::new(None);
+}
+
+/// Sets the function called instead of sending the shutdown signal when the shutdown is
+/// requested with the `signal` mode, `None` restores the signal. It is the only delivery
+/// mechanism on the platforms without signals (Windows). The function is called on a webserver
+/// thread, so it must not block.
+///
+pub fn set_shutdown_callback(callback: Option<ShutdownCallback>) {
+    *SHUTDOWN_CALLBACK.lock() = callback;
+}
+
+#[cfg(unix)]
+mod signal {
+    use nix::sys::signal::{kill, Signal};
+    use nix::unistd::Pid;
+    use std::sync::OnceLock;
+
+    static SHUTDOWN_SIGNAL_NO: OnceLock<Signal> = OnceLock::new();
+
+    fn get_shutdown_signal() -> Signal {
+        *SHUTDOWN_SIGNAL_NO.get_or_init(|| Signal::SIGINT)
+    }
+
+    /// Sets the signal sent to the process when the shutdown is requested with the `signal`
+    /// mode, `SIGINT` by default. The signal can be set once.
+    ///
+    pub fn set_shutdown_signal(signal: i32) -> anyhow::Result<()> {
+        let signal = Signal::try_from(signal)
+            .map_err(|e| anyhow::anyhow!("Invalid signal number: {}", e))?;
+        SHUTDOWN_SIGNAL_NO
+            .set(signal)
+            .map_err(|s| anyhow::anyhow!("Signal already set: {}", s))
+    }
+
+    pub(super) fn raise() -> anyhow::Result<()> {
+        kill(Pid::this(), get_shutdown_signal())?;
+        Ok(())
+    }
+}
+
+#[cfg(not(unix))]
+mod signal {
+    /// Signals are not supported on this platform, use
+    /// [`super::set_shutdown_callback`] instead.
+    ///
+    pub fn set_shutdown_signal(_signal: i32) -> anyhow::Result<()> {
+        anyhow::bail!(
+            "Shutdown signals are not supported on this platform, set a shutdown callback"
+        )
+    }
+
+    pub(super) fn raise() -> anyhow::Result<()> {
+        anyhow::bail!("No shutdown callback is set, signals are not supported on this platform")
+    }
+}
+
+pub use signal::set_shutdown_signal;
+
+/// Delivers the shutdown requested with the `signal` mode: calls the shutdown callback when it
+/// is set, otherwise sends the shutdown signal to the process.
+///
+pub(crate) fn deliver_shutdown() -> anyhow::Result<()> {
+    let callback = SHUTDOWN_CALLBACK.lock().clone();
+    match callback {
+        Some(callback) => {
+            callback();
+            Ok(())
+        }
+        None => signal::raise(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::webserver::shutdown_signal::{deliver_shutdown, set_shutdown_callback};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_shutdown_callback() -> anyhow::Result<()> {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let thread_calls = calls.clone();
+        set_shutdown_callback(Some(Arc::new(move || {
+            thread_calls.fetch_add(1, Ordering::SeqCst);
+        })));
+        deliver_shutdown()?;
+        set_shutdown_callback(None);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+use savant_core::primitives::rust::AttributeValue;
+use savant_core::primitives::{Attribute, WithAttributes};
+use savant_core::test::gen_frame;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::hint::black_box;
+
+struct CountingAllocator;
+
+thread_local! {
+    // per thread, so the tests running in parallel do not skew the counts
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|c| c.set(c.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn count_allocations<F: FnOnce()>(f: F) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+fn attribute(name: &str, values: usize) -> Attribute {
+    Attribute::persistent(
+        "test",
+        name,
+        (0..values as i64)
+            .map(|i| AttributeValue::integer(i, None))
+            .collect(),
+        &None,
+        false,
+    )
+}
+
+#[test]
+fn test_attribute_creation_allocations() {
+    let empty = count_allocations(|| {
+        black_box(attribute("empty", 0));
+    });
+    // the vector passed in is the only allocation of the values, a single value is inline
+    let single = count_allocations(|| {
+        black_box(attribute("single", 1));
+    });
+    assert_eq!(single, empty + 1);
+    // several values are moved behind an Arc
+    let many = count_allocations(|| {
+        black_box(attribute("many", 2));
+    });
+    assert_eq!(many, empty + 2);
+}
+
+#[test]
+fn test_attribute_clone_allocations() {
+    let empty = attribute("empty", 0);
+    let single = attribute("single", 1);
+    let many = attribute("many", 2);
+    let empty_clone = count_allocations(|| {
+        black_box(empty.clone());
+    });
+    assert_eq!(
+        count_allocations(|| {
+            black_box(single.clone());
+        }),
+        empty_clone
+    );
+    assert_eq!(
+        count_allocations(|| {
+            black_box(many.clone());
+        }),
+        empty_clone
+    );
+}
+
+#[test]
+fn test_attribute_value_access_allocations() {
+    let single = attribute("single", 1);
+    assert_eq!(
+        count_allocations(|| {
+            black_box(single.get_values());
+        }),
+        0
+    );
+
+    let frame = gen_frame();
+    let mut objects = frame.get_all_objects();
+    for o in objects.iter_mut() {
+        o.set_attribute(attribute("empty", 0));
+        o.set_attribute(attribute("single", 1));
+    }
+    let access = |name: &str| {
+        count_allocations(|| {
+            for o in &objects {
+                let attribute = o.get_attribute("test", name).unwrap();
+                black_box(attribute.get_values().len());
+            }
+        })
+    };
+    // reading a single value costs no allocation beyond the attribute copy
+    assert_eq!(access("single"), access("empty"));
+}
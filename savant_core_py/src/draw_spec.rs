@@ -1,3 +1,5 @@
+use crate::primitives::object::BorrowedVideoObject;
+use crate::utils::symbol_mapper::lock_symbol_mapper;
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use savant_core::draw as rust;
@@ -556,7 +558,7 @@ impl LabelDraw {
                         border_color = ColorDraw::transparent(), font_scale = 1.0,
                         thickness = 1, position = LabelPosition::default_position(),
                         padding = PaddingDraw::default_padding(),
-                        format = vec!["{label}".to_string()])
+                        format = vec!["{label}".to_string()], locale = None)
     )]
     pub fn new(
         font_color: ColorDraw,
@@ -567,6 +569,7 @@ impl LabelDraw {
         position: LabelPosition,
         padding: PaddingDraw,
         format: Vec<String>,
+        locale: Option<String>,
     ) -> PyResult<Self> {
         let font_color = font_color.0;
         let background_color = background_color.0;
@@ -583,7 +586,8 @@ impl LabelDraw {
             padding,
             format,
         )
-        .map_err(|e| PyValueError::new_err(format!("Invalid label draw: {:?}", e)))?;
+        .map_err(|e| PyValueError::new_err(format!("Invalid label draw: {:?}", e)))?
+        .with_locale(locale);
 
         Ok(Self(label_draw))
     }
@@ -639,6 +643,62 @@ impl LabelDraw {
     pub fn padding(&self) -> PaddingDraw {
         PaddingDraw(self.0.padding)
     }
+
+    /// Returns the locale of the display labels
+    ///
+    #[getter]
+    pub fn locale(&self) -> Option<String> {
+        self.0.locale.clone()
+    }
+
+    /// Returns the label to display for the object according to the locale, see
+    /// :py:func:`savant_rs.utils.symbol_mapper.get_display_label`. The object label is
+    /// returned when the locale is not set or the object is not registered.
+    ///
+    /// Parameters
+    /// ----------
+    /// model_name : str
+    ///   The name of the model (the object namespace)
+    /// object_label : str
+    ///   The label of the object
+    ///
+    /// Returns
+    /// -------
+    /// str
+    ///   The label substituted for ``{label}``
+    ///
+    pub fn display_label(&self, model_name: &str, object_label: &str) -> String {
+        self.0
+            .display_label(&lock_symbol_mapper(), model_name, object_label)
+    }
+
+    /// Renders the format strings for the object. The format strings are templates like
+    /// ``{label} #{track_id} {speed:.1f} km/h`` with the fields ``id``, ``namespace``,
+    /// ``label`` (the display label), ``draw_label``, ``track_id``, ``confidence`` and the
+    /// attributes ``name``, ``namespace/name`` and ``frame:namespace/name`` with an optional
+    /// value index (``color[1]``) and format (``.Nf`` or ``d``). The compiled templates are
+    /// cached.
+    ///
+    /// Parameters
+    /// ----------
+    /// object : :py:class:`savant_rs.primitives.BorrowedVideoObject`
+    ///   The object the labels are rendered for
+    ///
+    /// Returns
+    /// -------
+    /// List[str]
+    ///   The label lines
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If a format string is not a valid template
+    ///
+    pub fn render_labels(&self, object: &BorrowedVideoObject) -> PyResult<Vec<String>> {
+        self.0
+            .render_labels(&lock_symbol_mapper(), &object.0)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
 }
 
 /// Represents the draw specification for an object.
@@ -0,0 +1,157 @@
+use crate::{release_gil, with_gil};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use savant_core::ha as rust_ha;
+use savant_core::ha::{ActiveConfig, StandbyConfig, StateProvider};
+use std::sync::Arc;
+use std::time::Duration;
+
+struct PyStateProvider {
+    snapshot: PyObject,
+    restore: PyObject,
+}
+
+impl StateProvider for PyStateProvider {
+    fn snapshot(&self) -> anyhow::Result<Vec<u8>> {
+        with_gil!(|py| {
+            let res = self.snapshot.call0(py)?;
+            Ok(res.extract::<Vec<u8>>(py)?)
+        })
+    }
+
+    fn restore(&self, data: &[u8]) -> anyhow::Result<()> {
+        with_gil!(|py| {
+            self.restore.call1(py, (PyBytes::new(py, data),))?;
+            Ok(())
+        })
+    }
+}
+
+/// Registers a state transferred to the standby with the snapshots, e.g. the tracks of a
+/// tracker. The state replaces the previous one when restored.
+///
+/// Parameters
+/// ----------
+/// name : str
+///   The name of the state, the same on the active pipeline and the standby.
+/// snapshot : Callable[[], bytes]
+///   Serializes the state.
+/// restore : Callable[[bytes], None]
+///   Replaces the state with the serialized one.
+///
+#[pyfunction]
+pub fn register_state_provider(name: &str, snapshot: PyObject, restore: PyObject) {
+    rust_ha::register_state_provider(name, Arc::new(PyStateProvider { snapshot, restore }));
+}
+
+#[pyfunction]
+pub fn unregister_state_provider(name: &str) {
+    rust_ha::unregister_state_provider(name);
+}
+
+/// Makes the pipeline active and starts sending the state snapshots to the standby.
+///
+/// Parameters
+/// ----------
+/// standby_url : str
+///   The webserver of the standby, e.g. ``http://standby:8080``.
+/// period_ms : int
+///   How often the snapshots are sent.
+///
+/// Raises
+/// ------
+/// ValueError
+///   If the period is 0.
+///
+/// GIL management: the function is GIL-free, a running HA worker is stopped and joined.
+///
+#[pyfunction]
+pub fn start_active(standby_url: String, period_ms: u64) -> PyResult<()> {
+    release_gil!(true, || rust_ha::start_active(ActiveConfig {
+        standby_url,
+        period: Duration::from_millis(period_ms),
+    }))
+    .map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// Makes the pipeline a standby receiving the state snapshots and checking the status of the
+/// active pipeline. The standby is promoted when the active pipeline fails the consecutive
+/// checks.
+///
+/// Parameters
+/// ----------
+/// active_url : str
+///   The webserver of the active pipeline, e.g. ``http://active:8080``.
+/// check_period_ms : int
+///   How often the status of the active pipeline is checked.
+/// failure_threshold : int
+///   The number of consecutive failed checks which promotes the standby.
+/// on_promote : Optional[Callable[[int], None]]
+///   Called with the fencing token on the promotion to start the ingestion.
+///
+/// Raises
+/// ------
+/// ValueError
+///   If the period or the threshold is 0.
+///
+/// GIL management: the function is GIL-free, a running HA worker is stopped and joined.
+///
+#[pyfunction]
+#[pyo3(signature = (active_url, check_period_ms, failure_threshold, on_promote=None))]
+pub fn start_standby(
+    active_url: String,
+    check_period_ms: u64,
+    failure_threshold: u32,
+    on_promote: Option<PyObject>,
+) -> PyResult<()> {
+    let on_promote = on_promote.map(|f| -> rust_ha::PromotionCallback {
+        Arc::new(move |fencing_token| {
+            with_gil!(|py| {
+                if let Err(e) = f.call1(py, (fencing_token,)) {
+                    log::error!(target: "savant_rs::ha", "The promotion callback failed: {}", e);
+                }
+            })
+        })
+    });
+    release_gil!(true, || rust_ha::start_standby(
+        StandbyConfig {
+            active_url,
+            check_period: Duration::from_millis(check_period_ms),
+            failure_threshold,
+        },
+        on_promote,
+    ))
+    .map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// Stops sending the snapshots or checking the active pipeline, the role is kept.
+///
+/// GIL management: the function is GIL-free.
+///
+#[pyfunction]
+pub fn stop_ha() {
+    release_gil!(true, rust_ha::stop_ha)
+}
+
+/// Promotes the standby to active with the next fencing token and calls the promotion
+/// callback.
+///
+/// Returns
+/// -------
+/// Optional[int]
+///   The fencing token, None if the pipeline is not a standby.
+///
+#[pyfunction]
+pub fn promote() -> Option<u64> {
+    rust_ha::promote()
+}
+
+/// The HA status as JSON: the role, the time of the last snapshot, the number of snapshots,
+/// the failed health checks of the active pipeline and the promotion time.
+///
+#[pyfunction]
+pub fn get_ha_status() -> PyResult<String> {
+    serde_json::to_string(&rust_ha::get_ha_status())
+        .map_err(|e| PyValueError::new_err(e.to_string()))
+}
@@ -2,6 +2,7 @@ pub mod atomic_counter;
 pub mod capi;
 /// The draw specification used to draw objects on the frame when they are visualized.
 pub mod draw_spec;
+pub mod ha;
 pub mod logging;
 pub mod match_query;
 pub mod metrics;
@@ -9,6 +10,7 @@ pub mod pipeline;
 /// # Basic objects
 ///
 pub mod primitives;
+pub mod reid;
 pub mod telemetry;
 pub mod test;
 /// # Utility functions
@@ -18,6 +20,7 @@ pub mod webserver;
 pub mod zmq;
 
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
 
 /// Returns the version of the package set in Cargo.toml
 ///
@@ -30,3 +33,23 @@ use pyo3::prelude::*;
 pub fn version() -> String {
     savant_core::version()
 }
+
+/// Returns the capabilities of the build: the package version, the protocol version of
+/// serialized messages, the compiled features and the instruction sets (``avx2``, ``neon``
+/// or ``scalar``) selected at runtime for the vectorized kernels.
+///
+/// Returns
+/// -------
+/// dict
+///   ``{"version": str, "protocol_version": str, "features": List[str], "simd": Dict[str, str]}``
+///
+#[pyfunction]
+pub fn capabilities(py: Python) -> PyResult<Bound<'_, PyDict>> {
+    let caps = savant_core::capabilities::capabilities();
+    let d = PyDict::new(py);
+    d.set_item("version", caps.version)?;
+    d.set_item("protocol_version", caps.protocol_version)?;
+    d.set_item("features", caps.features)?;
+    d.set_item("simd", caps.simd)?;
+    Ok(d)
+}
@@ -1,4 +1,7 @@
 use crate::primitives::bbox::{BBoxMetricType, RBBox};
+use crate::primitives::objects_view::VideoObjectsView;
+use crate::release_gil;
+use std::sync::Arc;
 
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
@@ -2366,4 +2369,94 @@ impl MatchQuery {
             |e| PyValueError::new_err(format!("Invalid YAML: {}", e)),
         )?))
     }
+
+    /// Compiles the query to an evaluator reused across frames.
+    ///
+    /// Returns
+    /// -------
+    /// :py:class:`CompiledMatchQuery`
+    ///   The compiled query
+    ///
+    fn compile(&self) -> CompiledMatchQuery {
+        CompiledMatchQuery(Arc::new(self.0.compile()))
+    }
+}
+
+/// A query compiled to an evaluator reused across frames. The nested ``and`` and ``or`` are
+/// flattened and the predicates of the combinators are periodically reordered by the
+/// observed selectivity, so the cheap predicates most likely to decide the result are
+/// evaluated first. The results are the same as of the source query.
+///
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct CompiledMatchQuery(pub(crate) Arc<rust::CompiledMatchQuery>);
+
+#[pymethods]
+impl CompiledMatchQuery {
+    fn __repr__(&self) -> String {
+        format!("CompiledMatchQuery({:?})", self.0.get_source())
+    }
+
+    /// The source query.
+    ///
+    #[getter]
+    fn source(&self) -> MatchQuery {
+        MatchQuery(self.0.get_source().clone())
+    }
+
+    /// Returns a new view with the objects matching the query.
+    ///
+    #[pyo3(signature = (v, no_gil = true))]
+    fn filter(&self, v: &VideoObjectsView, no_gil: bool) -> VideoObjectsView {
+        release_gil!(no_gil, || {
+            let objs = v.0.iter().map(|o| o.0.clone()).collect::<Vec<_>>();
+            VideoObjectsView::from(self.0.filter(&objs))
+        })
+    }
+
+    /// Splits the view into the objects matching and not matching the query.
+    ///
+    #[pyo3(signature = (v, no_gil = true))]
+    fn partition(
+        &self,
+        v: &VideoObjectsView,
+        no_gil: bool,
+    ) -> (VideoObjectsView, VideoObjectsView) {
+        release_gil!(no_gil, || {
+            let objs = v.0.iter().map(|o| o.0.clone()).collect::<Vec<_>>();
+            let (a, b) = self.0.partition(&objs);
+            (a.into(), b.into())
+        })
+    }
+
+    /// Reorders the predicates by the statistics collected so far. Called automatically
+    /// every few thousand evaluations.
+    ///
+    fn optimize(&self) {
+        self.0.optimize()
+    }
+
+    /// Resets the statistics, the current order of the predicates is kept.
+    ///
+    fn reset_stats(&self) {
+        self.0.reset_stats()
+    }
+
+    /// The statistics of the predicates.
+    ///
+    /// Returns
+    /// -------
+    /// List[Tuple[str, str, int, int]]
+    ///   The path of the predicate in the source query (``$`` is the root, ``$.1.0`` is the
+    ///   first child of its second child), the predicate JSON or the combinator name, the
+    ///   number of evaluations and the number of evaluations which yielded true.
+    ///
+    #[getter]
+    fn stats(&self) -> Vec<(String, String, u64, u64)> {
+        self.0
+            .get_stats()
+            .into_iter()
+            .map(|s| (s.path, s.query, s.evaluations, s.hits))
+            .collect()
+    }
 }
@@ -1,22 +1,67 @@
 use parking_lot::Mutex;
 use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::Duration;
 
 use pyo3::exceptions::{PySystemError, PyValueError};
 use pyo3::prelude::*;
 
+use savant_core::pipeline::admission::AdmissionFilter;
+use savant_core::pipeline::auto_batch::{
+    AutoBatchConfig, AutoBatchFlusher as RustAutoBatchFlusher,
+};
+use savant_core::pipeline::best_shot::{BestShotConfiguration, BestShotSelector};
+use savant_core::pipeline::circuit_breaker::{
+    CircuitBreaker, CircuitBreakerConfig, QuarantinePolicy,
+};
+use savant_core::pipeline::contracts::{
+    ContractRegistry as RustContractRegistry, ContractViolation, ModuleContract,
+};
+use savant_core::pipeline::debug_tap::{DebugTap, DebugTapSelector, DebugTapSink};
+use savant_core::pipeline::decimator::{DecimationStrategy, Decimator};
+use savant_core::pipeline::events::PipelineEvent as RustPipelineEvent;
+#[cfg(feature = "chaos")]
+use savant_core::pipeline::fault_injector::{FaultInjector, FaultInjectorConfig};
+use savant_core::pipeline::history::FrameHistory;
+use savant_core::pipeline::motion::{MotionDetector, MotionDetectorConfiguration};
+use savant_core::pipeline::object_lifecycle::{
+    ObjectEvent, ObjectLifecycleCallback, ObjectObserver,
+};
+use savant_core::pipeline::quality::{QualityEstimator, QualityEstimatorConfiguration};
+use savant_core::pipeline::readiness::{StageReadiness, WarmupPolicy};
+use savant_core::pipeline::reaper::{
+    EvictedPayload, EvictionCallback, FrameReaper as RustFrameReaper,
+};
+use savant_core::pipeline::result_cache::{ResultCache, ResultCacheConfig};
+use savant_core::pipeline::source_config::{SourceConfigProvider, SourceConfigResolver};
+use savant_core::pipeline::source_stats::{
+    SourceStatistics as RustSourceStatistics, SourceStatsPublisher as RustSourceStatsPublisher,
+};
 use savant_core::pipeline::stage_function_loader::load_stage_function_plugin as rust_load_stage_function_plugin;
+use savant_core::pipeline::user_code::{UserCodeSection as RustUserCodeSection, UserCodeTime};
+use savant_core::pipeline::validation::{
+    FrameValidator, FrameValidatorConfig, ValidationCallback, ValidationEvent,
+};
 use savant_core::pipeline::PipelineStageFunction as RustPipelineStageFunction;
+use savant_core::pipeline::PipelineStageFunctionOrder;
 use savant_core::pipeline::PluginParams;
+use savant_core::primitives::attribute_propagation::AttributePropagation;
+use savant_core::primitives::rust::ContentEncoding;
 use savant_core::rust;
 
 use crate::match_query::MatchQuery;
+use crate::primitives::attribute_propagation::PropagationRule;
 use crate::primitives::attribute_value::AttributeValue;
 use crate::primitives::batch::VideoFrameBatch;
 use crate::primitives::frame::VideoFrame;
 use crate::primitives::frame_update::VideoFrameUpdate;
+use crate::primitives::message::Message;
+use crate::primitives::object::OrphanPolicy;
 use crate::primitives::objects_view::VideoObjectsView;
-use crate::release_gil;
 use crate::utils::otlp::TelemetrySpan;
+use crate::zmq::blocking::BlockingWriter;
+use crate::{release_gil, with_gil};
 
 #[pyclass]
 pub struct StageFunction(Mutex<Option<Box<dyn RustPipelineStageFunction>>>);
@@ -65,6 +110,90 @@ pub fn load_stage_function_plugin(
         .map_err(|e| PySystemError::new_err(e.to_string()))
 }
 
+/// Creates a CPU motion detector for raw frames (GRAY8, RGB or RGBA) to be used as an ingress
+/// or egress function of a frame or batch stage. The detector sets the ``motion_present``,
+/// ``area`` and ``mask`` attributes and, unless ``gate_inference`` is false, the processing
+/// hints of the frames.
+///
+/// Parameters
+/// ----------
+/// params : Dict[str, AttributeValue]
+///   Optional ``cell_size``, ``threshold``, ``learning_rate``, ``min_cells``,
+///   ``gate_inference`` and ``namespace``.
+///
+/// Returns
+/// -------
+/// StageFunction
+///   The detector.
+///
+#[pyfunction]
+#[pyo3(signature = (params = HashMap::new()))]
+pub fn motion_detector(params: HashMap<String, AttributeValue>) -> StageFunction {
+    let params = PluginParams {
+        params: params.into_iter().map(|(k, v)| (k, v.0)).collect(),
+    };
+    StageFunction::new(Box::new(MotionDetector::new(
+        MotionDetectorConfiguration::from(&params),
+    )))
+}
+
+/// Creates a CPU image quality estimator for raw frames (GRAY8, RGB or RGBA) to be used as an
+/// ingress or egress function of a frame or batch stage. The estimator sets the
+/// ``sharpness`` and ``blurred`` attributes and the ``brightness``, ``contrast``,
+/// ``underexposed``, ``overexposed`` and ``histogram`` attributes of the frames. Frames
+/// with external or no content are skipped.
+///
+/// Parameters
+/// ----------
+/// params : Dict[str, AttributeValue]
+///   Optional ``blur``, ``exposure``, ``blur_threshold``, ``dark_level``, ``bright_level``,
+///   ``histogram_bins`` and ``namespace``.
+///
+/// Returns
+/// -------
+/// StageFunction
+///   The estimator.
+///
+#[pyfunction]
+#[pyo3(signature = (params = HashMap::new()))]
+pub fn quality_estimator(params: HashMap<String, AttributeValue>) -> StageFunction {
+    let params = PluginParams {
+        params: params.into_iter().map(|(k, v)| (k, v.0)).collect(),
+    };
+    StageFunction::new(Box::new(QualityEstimator::new(
+        QualityEstimatorConfiguration::from(&params),
+    )))
+}
+
+/// Creates a best-shot selector to be used as an ingress or egress function of a frame or
+/// batch stage. The selector scores the crops of the tracked objects by the box size, the
+/// confidence, the sharpness of raw frames and the weighted object attributes; when a track
+/// is missing for ``max_missing_frames`` frames, the frame gets the attribute named after the
+/// track id with the frame uuid, the PTS, the box and the score of the best shot.
+///
+/// Parameters
+/// ----------
+/// params : Dict[str, AttributeValue]
+///   Optional ``max_missing_frames``, ``size_weight``, ``confidence_weight``,
+///   ``sharpness_weight``, ``sharpness_scale``, ``namespace`` and the attribute weights named
+///   ``attribute:<namespace>/<name>``.
+///
+/// Returns
+/// -------
+/// StageFunction
+///   The selector.
+///
+#[pyfunction]
+#[pyo3(signature = (params = HashMap::new()))]
+pub fn best_shot_selector(params: HashMap<String, AttributeValue>) -> StageFunction {
+    let params = PluginParams {
+        params: params.into_iter().map(|(k, v)| (k, v.0)).collect(),
+    };
+    StageFunction::new(Box::new(BestShotSelector::new(
+        BestShotConfiguration::from(&params),
+    )))
+}
+
 /// Defines which type of payload a stage handles.
 ///
 #[pyclass(eq, eq_int)]
@@ -72,6 +201,8 @@ pub fn load_stage_function_plugin(
 pub enum VideoPipelineStagePayloadType {
     Frame,
     Batch,
+    Audio,
+    Telemetry,
 }
 
 #[pyclass(eq, eq_int)]
@@ -106,6 +237,412 @@ impl From<rust::FrameProcessingStatRecordType> for FrameProcessingStatRecordType
     }
 }
 
+/// The rate of the frames entering a stage, of a source when ``source_id`` is set.
+///
+#[pyclass]
+pub struct StageRate(rust::StageRate);
+
+#[pymethods]
+impl StageRate {
+    #[getter]
+    fn stage_name(&self) -> String {
+        self.0.stage_name.clone()
+    }
+
+    #[getter]
+    fn source_id(&self) -> Option<String> {
+        self.0.source_id.clone()
+    }
+
+    /// The number of frames in the window.
+    ///
+    #[getter]
+    fn frames(&self) -> usize {
+        self.0.measurement.frames
+    }
+
+    #[getter]
+    fn fps(&self) -> f64 {
+        self.0.measurement.fps
+    }
+
+    /// The standard deviation of the intervals between the frames in milliseconds.
+    ///
+    #[getter]
+    fn jitter_ms(&self) -> f64 {
+        self.0.measurement.jitter_ms
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+/// The processing statistics of a source over the rate window of the pipeline.
+///
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct SourceStatistics(RustSourceStatistics);
+
+#[pymethods]
+impl SourceStatistics {
+    #[getter]
+    fn source_id(&self) -> String {
+        self.0.source_id.clone()
+    }
+
+    /// The rate of the frames offered to the pipeline, the rejected ones included.
+    ///
+    #[getter]
+    fn ingest_fps(&self) -> f64 {
+        self.0.ingest_fps
+    }
+
+    /// The smoothed time the recent frames spent in the pipeline in milliseconds.
+    ///
+    #[getter]
+    fn latency_ms(&self) -> f64 {
+        self.0.latency_ms
+    }
+
+    /// The part of the offered frames rejected at ingest or dropped by the stages.
+    ///
+    #[getter]
+    fn drop_rate(&self) -> f64 {
+        self.0.drop_rate
+    }
+
+    /// The rate the pipeline sustained while dropping frames, ``None`` when no frames were
+    /// dropped in the window.
+    ///
+    #[getter]
+    fn allowed_fps(&self) -> Option<f64> {
+        self.0.allowed_fps
+    }
+
+    /// The user data message of the source carrying the statistics.
+    ///
+    fn to_message(&self) -> Message {
+        Message(self.0.to_message())
+    }
+
+    /// Recognizes a source statistics message.
+    ///
+    #[staticmethod]
+    fn from_message(message: &Message) -> Option<Self> {
+        RustSourceStatistics::from_message(&message.0).map(Self)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+/// A change of the payloads in the pipeline, see :py:meth:`VideoPipeline.subscribe`.
+///
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct PipelineEvent(RustPipelineEvent);
+
+#[pymethods]
+impl PipelineEvent {
+    /// One of ``frame_added``, ``frame_moved``, ``batch_created``, ``batch_unpacked``,
+    /// ``frame_deleted`` and ``update_applied``.
+    ///
+    #[getter]
+    fn kind(&self) -> &'static str {
+        match &self.0 {
+            RustPipelineEvent::FrameAdded { .. } => "frame_added",
+            RustPipelineEvent::FrameMoved { .. } => "frame_moved",
+            RustPipelineEvent::BatchCreated { .. } => "batch_created",
+            RustPipelineEvent::BatchUnpacked { .. } => "batch_unpacked",
+            RustPipelineEvent::FrameDeleted { .. } => "frame_deleted",
+            RustPipelineEvent::UpdateApplied { .. } => "update_applied",
+        }
+    }
+
+    /// The id of the frame or the batch.
+    ///
+    #[getter]
+    fn id(&self) -> i64 {
+        match &self.0 {
+            RustPipelineEvent::FrameAdded { id, .. }
+            | RustPipelineEvent::FrameMoved { id, .. }
+            | RustPipelineEvent::BatchCreated { id, .. }
+            | RustPipelineEvent::BatchUnpacked { id, .. }
+            | RustPipelineEvent::FrameDeleted { id, .. }
+            | RustPipelineEvent::UpdateApplied { id, .. } => *id,
+        }
+    }
+
+    /// The stage of the payload, the destination stage of a move.
+    ///
+    #[getter]
+    fn stage(&self) -> String {
+        match &self.0 {
+            RustPipelineEvent::FrameAdded { stage, .. }
+            | RustPipelineEvent::BatchCreated { stage, .. }
+            | RustPipelineEvent::BatchUnpacked { stage, .. }
+            | RustPipelineEvent::FrameDeleted { stage, .. }
+            | RustPipelineEvent::UpdateApplied { stage, .. } => stage.clone(),
+            RustPipelineEvent::FrameMoved { to, .. } => to.clone(),
+        }
+    }
+
+    /// The source stage of a move.
+    ///
+    #[getter]
+    fn from_stage(&self) -> Option<String> {
+        match &self.0 {
+            RustPipelineEvent::FrameMoved { from, .. } => Some(from.clone()),
+            _ => None,
+        }
+    }
+
+    /// The frames of a created or an unpacked batch.
+    ///
+    #[getter]
+    fn frame_ids(&self) -> Vec<i64> {
+        match &self.0 {
+            RustPipelineEvent::BatchCreated { frame_ids, .. }
+            | RustPipelineEvent::BatchUnpacked { frame_ids, .. } => frame_ids.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Whether a deleted payload is dropped by the pipeline, e.g. evicted.
+    ///
+    #[getter]
+    fn dropped(&self) -> bool {
+        matches!(
+            self.0,
+            RustPipelineEvent::FrameDeleted { dropped: true, .. }
+        )
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+/// The events of a subscription, the subscription ends when the object is garbage
+/// collected.
+///
+#[pyclass]
+pub struct PipelineEventReceiver(crossbeam::channel::Receiver<RustPipelineEvent>);
+
+#[pymethods]
+impl PipelineEventReceiver {
+    /// Waits for the next event.
+    ///
+    /// Parameters
+    /// ----------
+    /// timeout_ms : int
+    ///   The longest time to wait.
+    /// no_gil : bool
+    ///   Whether to release the GIL while waiting.
+    ///
+    /// Returns
+    /// -------
+    /// Optional[PipelineEvent]
+    ///   The event or ``None`` when the time is out.
+    ///
+    #[pyo3(signature = (timeout_ms, no_gil = true))]
+    fn recv(&self, timeout_ms: u64, no_gil: bool) -> Option<PipelineEvent> {
+        release_gil!(no_gil, || self
+            .0
+            .recv_timeout(Duration::from_millis(timeout_ms))
+            .ok()
+            .map(PipelineEvent))
+    }
+
+    /// Takes the events received since the last call without waiting.
+    ///
+    /// Returns
+    /// -------
+    /// List[PipelineEvent]
+    ///   The events.
+    ///
+    fn take(&self) -> Vec<PipelineEvent> {
+        self.0.try_iter().map(PipelineEvent).collect()
+    }
+
+    /// The number of the events waiting.
+    ///
+    fn __len__(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// Sends the statistics of the sources of the pipeline to the topic every period, a message
+/// per source, so the encoders adapt to the backpressure. Stops when :py:meth:`stop` is called
+/// or the object is garbage collected.
+///
+/// Parameters
+/// ----------
+/// pipeline : VideoPipeline
+///   The pipeline.
+/// writer : :py:class:`savant_rs.zmq.BlockingWriter`
+///   The started writer.
+/// topic : str
+///   The control topic.
+/// period_ms : int
+///   The publishing period.
+///
+/// Raises
+/// ------
+/// ValueError
+///   If the writer is not started or the period is 0.
+///
+#[pyclass]
+pub struct SourceStatsPublisher(Mutex<RustSourceStatsPublisher>);
+
+#[pymethods]
+impl SourceStatsPublisher {
+    #[new]
+    fn new(
+        pipeline: &Pipeline,
+        writer: &BlockingWriter,
+        topic: &str,
+        period_ms: u64,
+    ) -> PyResult<Self> {
+        let writer = writer
+            .get_writer()
+            .ok_or_else(|| PyValueError::new_err("The writer is not started"))?;
+        RustSourceStatsPublisher::start(
+            &pipeline.0,
+            writer,
+            topic,
+            Duration::from_millis(period_ms),
+        )
+        .map(|p| Self(Mutex::new(p)))
+        .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn stop(&self) {
+        release_gil!(true, || self.0.lock().stop())
+    }
+
+    #[getter]
+    fn is_running(&self) -> bool {
+        self.0.lock().is_running()
+    }
+}
+
+fn evicted_payload_tuple(p: EvictedPayload) -> (String, i64, Vec<i64>, u64) {
+    (p.stage, p.id, p.frame_ids, p.age.as_millis() as u64)
+}
+
+/// Passes the evicted payloads to a Python callable as a list of tuples.
+///
+struct PyEvictionCallback(PyObject);
+
+impl EvictionCallback for PyEvictionCallback {
+    fn on_evicted(&self, payloads: Vec<EvictedPayload>) {
+        let payloads = payloads
+            .into_iter()
+            .map(evicted_payload_tuple)
+            .collect::<Vec<_>>();
+        with_gil!(|py| {
+            if let Err(e) = self.0.call1(py, (payloads,)) {
+                log::error!(
+                    target: "savant_rs::pipeline",
+                    "The eviction callback failed: {}", e
+                );
+            }
+        })
+    }
+}
+
+/// Evicts the payloads staying in the stages for the frame TTL of the pipeline every
+/// period, see :py:meth:`VideoPipeline.evict_stale_frames`. Stops when :py:meth:`stop` is
+/// called or the object is garbage collected.
+///
+/// Parameters
+/// ----------
+/// pipeline : VideoPipeline
+///   The pipeline.
+/// period_ms : int
+///   The eviction period.
+/// callback : Optional[Callable[[List[Tuple[str, int, List[int], int]]], None]]
+///   Called with the stage, the id, the frame ids and the age in milliseconds of the
+///   evicted payloads.
+///
+/// Raises
+/// ------
+/// ValueError
+///   If the period is 0 or the pipeline has no frame TTL.
+///
+#[pyclass]
+pub struct FrameReaper(Mutex<RustFrameReaper>);
+
+#[pymethods]
+impl FrameReaper {
+    #[new]
+    #[pyo3(signature = (pipeline, period_ms, callback=None))]
+    fn new(pipeline: &Pipeline, period_ms: u64, callback: Option<PyObject>) -> PyResult<Self> {
+        let callback =
+            callback.map(|c| Arc::new(PyEvictionCallback(c)) as Arc<dyn EvictionCallback>);
+        RustFrameReaper::start(&pipeline.0, Duration::from_millis(period_ms), callback)
+            .map(|r| Self(Mutex::new(r)))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn stop(&self) {
+        release_gil!(true, || self.0.lock().stop())
+    }
+
+    #[getter]
+    fn is_running(&self) -> bool {
+        self.0.lock().is_running()
+    }
+}
+
+impl Drop for FrameReaper {
+    fn drop(&mut self) {
+        // the reaper thread may wait for the GIL in the callback
+        release_gil!(true, || self.0.lock().stop())
+    }
+}
+
+/// Packs the frames waited for the latency of the auto-batching of the pipeline every
+/// period, see :py:meth:`VideoPipeline.set_auto_batch`. Stops when :py:meth:`stop` is called
+/// or the object is garbage collected.
+///
+/// Parameters
+/// ----------
+/// pipeline : VideoPipeline
+///   The pipeline.
+/// period_ms : int
+///   The flushing period.
+///
+/// Raises
+/// ------
+/// ValueError
+///   If the period is 0.
+///
+#[pyclass]
+pub struct AutoBatchFlusher(Mutex<RustAutoBatchFlusher>);
+
+#[pymethods]
+impl AutoBatchFlusher {
+    #[new]
+    fn new(pipeline: &Pipeline, period_ms: u64) -> PyResult<Self> {
+        RustAutoBatchFlusher::start(&pipeline.0, Duration::from_millis(period_ms))
+            .map(|f| Self(Mutex::new(f)))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn stop(&self) {
+        release_gil!(true, || self.0.lock().stop())
+    }
+
+    #[getter]
+    fn is_running(&self) -> bool {
+        self.0.lock().is_running()
+    }
+}
+
 #[pyclass]
 pub struct StageLatencyMeasurements(rust::StageLatencyMeasurements);
 
@@ -191,6 +728,48 @@ impl StageProcessingStat {
         self.0.batch_counter
     }
 
+    #[getter]
+    fn budget_overrun_counter(&self) -> usize {
+        self.0.budget_overrun_counter
+    }
+
+    /// The number of measured sections of user code, see :py:meth:`VideoPipeline.user_code`.
+    ///
+    #[getter]
+    fn user_code_calls(&self) -> usize {
+        self.0.user_code_time.calls
+    }
+
+    /// The total time spent in user code in seconds, excluding the calls to the library and
+    /// the GIL waits.
+    ///
+    #[getter]
+    fn user_code_time(&self) -> f64 {
+        self.0.user_code_time.total.as_secs_f64()
+    }
+
+    /// The longest section of user code in seconds, excluding the calls to the library and
+    /// the GIL waits.
+    ///
+    #[getter]
+    fn user_code_max_time(&self) -> f64 {
+        self.0.user_code_time.max.as_secs_f64()
+    }
+
+    /// The total time spent in the calls to the library made by user code in seconds.
+    ///
+    #[getter]
+    fn rust_overhead_time(&self) -> f64 {
+        self.0.rust_overhead_time.total.as_secs_f64()
+    }
+
+    /// The total time user code waited for the GIL held by other threads in seconds.
+    ///
+    #[getter]
+    fn gil_wait_time(&self) -> f64 {
+        self.0.gil_wait_time.total.as_secs_f64()
+    }
+
     fn __repr__(&self) -> String {
         format!("{:?}", self.0)
     }
@@ -254,6 +833,8 @@ impl From<VideoPipelineStagePayloadType> for rust::PipelineStagePayloadType {
         match p {
             VideoPipelineStagePayloadType::Frame => rust::PipelineStagePayloadType::Frame,
             VideoPipelineStagePayloadType::Batch => rust::PipelineStagePayloadType::Batch,
+            VideoPipelineStagePayloadType::Audio => rust::PipelineStagePayloadType::Audio,
+            VideoPipelineStagePayloadType::Telemetry => rust::PipelineStagePayloadType::Telemetry,
         }
     }
 }
@@ -263,6 +844,8 @@ impl From<rust::PipelineStagePayloadType> for VideoPipelineStagePayloadType {
         match p {
             rust::PipelineStagePayloadType::Frame => VideoPipelineStagePayloadType::Frame,
             rust::PipelineStagePayloadType::Batch => VideoPipelineStagePayloadType::Batch,
+            rust::PipelineStagePayloadType::Audio => VideoPipelineStagePayloadType::Audio,
+            rust::PipelineStagePayloadType::Telemetry => VideoPipelineStagePayloadType::Telemetry,
         }
     }
 }
@@ -274,27 +857,98 @@ impl From<rust::PipelineStagePayloadType> for VideoPipelineStagePayloadType {
 #[derive(Debug)]
 pub struct Pipeline(rust::Pipeline);
 
+/// Measures a section of user code, created by :py:meth:`VideoPipeline.user_code`.
+///
 #[pyclass]
-#[pyo3(name = "VideoPipelineConfiguration")]
-#[derive(Debug, Clone)]
-pub struct PipelineConfiguration(rust::PipelineConfiguration);
+pub struct UserCodeSection {
+    pipeline: Py<Pipeline>,
+    stage_name: String,
+    ids: Vec<i64>,
+    section: Option<RustUserCodeSection>,
+    time: Option<UserCodeTime>,
+}
 
 #[pymethods]
-impl PipelineConfiguration {
-    #[new]
-    pub fn new() -> PyResult<Self> {
-        Ok(Self(
-            rust::PipelineConfigurationBuilder::default()
-                .build()
-                .map_err(|e| {
-                    PyValueError::new_err(format!("Failed to create pipeline configuration: {}", e))
-                })?,
-        ))
+impl UserCodeSection {
+    fn __enter__(mut slf: PyRefMut<'_, Self>) -> PyRefMut<'_, Self> {
+        slf.section = Some(RustUserCodeSection::start(&slf.stage_name, slf.ids.clone()));
+        slf.time = None;
+        slf
     }
 
-    #[setter]
-    pub fn keyframe_history(&mut self, v: usize) {
-        self.0.keyframe_history = v;
+    /// Records the time in the stage.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist.
+    ///
+    #[pyo3(signature = (exc_type=None, exc_value=None, traceback=None))]
+    fn __exit__(
+        &mut self,
+        py: Python<'_>,
+        exc_type: Option<&Bound<'_, PyAny>>,
+        exc_value: Option<&Bound<'_, PyAny>>,
+        traceback: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<()> {
+        let _ = (exc_type, exc_value, traceback);
+        let Some(section) = self.section.take() else {
+            return Ok(());
+        };
+        let pipeline = self.pipeline.borrow(py);
+        let time = section
+            .finish(&pipeline.0)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.time = Some(time);
+        Ok(())
+    }
+
+    /// The time spent in user code in seconds, excluding the calls to the library, ``None``
+    /// until the section ends.
+    ///
+    #[getter]
+    fn user_time(&self) -> Option<f64> {
+        self.time.map(|t| t.user().as_secs_f64())
+    }
+
+    /// The time spent in the calls to the library in seconds, ``None`` until the section
+    /// ends.
+    ///
+    #[getter]
+    fn rust_time(&self) -> Option<f64> {
+        self.time.map(|t| t.rust.as_secs_f64())
+    }
+
+    /// The time spent waiting for the GIL held by other threads in seconds, ``None`` until
+    /// the section ends.
+    ///
+    #[getter]
+    fn gil_wait_time(&self) -> Option<f64> {
+        self.time.map(|t| t.gil_wait.as_secs_f64())
+    }
+}
+
+#[pyclass]
+#[pyo3(name = "VideoPipelineConfiguration")]
+#[derive(Debug, Clone)]
+pub struct PipelineConfiguration(rust::PipelineConfiguration);
+
+#[pymethods]
+impl PipelineConfiguration {
+    #[new]
+    pub fn new() -> PyResult<Self> {
+        Ok(Self(
+            rust::PipelineConfigurationBuilder::default()
+                .build()
+                .map_err(|e| {
+                    PyValueError::new_err(format!("Failed to create pipeline configuration: {}", e))
+                })?,
+        ))
+    }
+
+    #[setter]
+    pub fn keyframe_history(&mut self, v: usize) {
+        self.0.keyframe_history = v;
     }
 
     #[setter]
@@ -317,6 +971,80 @@ impl PipelineConfiguration {
         self.0.collection_history = v;
     }
 
+    /// Pairs of a stage name and an object namespace frozen when frames leave the stage,
+    /// ``None`` freezes the whole object set.
+    ///
+    #[setter]
+    pub fn freeze_objects(&mut self, v: Vec<(String, Option<String>)>) {
+        self.0.freeze_objects = v;
+    }
+
+    /// Triples of a stage name, a query and an orphan policy: the objects matching the query
+    /// are pruned when frames leave the stage.
+    ///
+    #[setter]
+    pub fn prune_objects(&mut self, v: Vec<(String, MatchQuery, OrphanPolicy)>) {
+        self.0.prune_objects = v
+            .into_iter()
+            .map(|(stage, query, policy)| (stage, query.0, policy.into()))
+            .collect();
+    }
+
+    /// Pairs of a stage name and the expected time in milliseconds a payload spends in the
+    /// stage. Overruns are counted in the ``stage_budget_overrun_total`` metric.
+    ///
+    #[setter]
+    pub fn stage_budgets(&mut self, v: Vec<(String, u64)>) {
+        self.0.stage_budgets = v
+            .into_iter()
+            .map(|(stage, budget)| (stage, Duration::from_millis(budget)))
+            .collect();
+    }
+
+    /// The window of the stage rates in milliseconds, 5 seconds when not set.
+    ///
+    #[setter]
+    pub fn rate_window(&mut self, v: Option<u64>) {
+        self.0.rate_window = v.map(Duration::from_millis);
+    }
+
+    /// The minimum time between the scans of :py:meth:`VideoPipeline.scan_frames` in
+    /// milliseconds, 1 second by default.
+    ///
+    #[setter]
+    pub fn frame_scan_interval(&mut self, v: u64) {
+        self.0.frame_scan_interval = Duration::from_millis(v);
+    }
+
+    /// Triples of a source stage, an edge name and a destination stage making the stages a
+    /// DAG: the payloads of a stage with the edges move only along them, e.g. with
+    /// :py:meth:`VideoPipeline.route`, and a stage the edges lead to receives the payloads
+    /// only from the sources of the edges. The edges must lead forward.
+    ///
+    #[setter]
+    pub fn edges(&mut self, v: Vec<(String, String, String)>) {
+        self.0.edges = v;
+    }
+
+    /// The longest time in milliseconds a payload stays in a stage before it is evicted by
+    /// :py:meth:`VideoPipeline.evict_stale_frames`, the payloads are kept forever when not
+    /// set.
+    ///
+    #[setter]
+    pub fn frame_ttl(&mut self, v: Option<u64>) {
+        self.0.frame_ttl = v.map(Duration::from_millis);
+    }
+
+    /// The origin in milliseconds since the UNIX epoch of the backfill clock of the pipeline,
+    /// the clock is driven by the PTS of the frames added to the pipeline only. The pipeline
+    /// uses the process clock when not set, see :py:func:`savant_rs.utils.enable_backfill_clock`.
+    ///
+    #[setter]
+    pub fn backfill_clock_origin(&mut self, v: Option<u64>) {
+        self.0.clock_mode =
+            v.map(|origin_ms| savant_core::clock::ClockMode::Backfill { origin_ms });
+    }
+
     fn __repr__(&self) -> String {
         format!("{:?}", self.0)
     }
@@ -326,6 +1054,84 @@ impl PipelineConfiguration {
     }
 }
 
+impl Pipeline {
+    fn get_breaker(&self, stage_name: &str) -> PyResult<Arc<CircuitBreaker>> {
+        self.0
+            .get_circuit_breaker(stage_name)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?
+            .ok_or_else(|| {
+                PyValueError::new_err(format!("Stage {} has no circuit breaker", stage_name))
+            })
+    }
+}
+
+/// Passes the object events to a Python callable as a list of tuples, the GIL is acquired
+/// once per batch of events.
+///
+struct PyObjectLifecycleCallback(PyObject);
+
+impl ObjectLifecycleCallback for PyObjectLifecycleCallback {
+    fn on_events(&self, stage: &str, events: Vec<ObjectEvent>) {
+        let events = events
+            .into_iter()
+            .map(|e| {
+                (
+                    e.kind.as_str(),
+                    e.source_id,
+                    e.frame_uuid,
+                    e.object_id,
+                    e.namespace,
+                    e.label,
+                )
+            })
+            .collect::<Vec<_>>();
+        with_gil!(|py| {
+            if let Err(e) = self.0.call1(py, (stage, events)) {
+                log::error!(
+                    target: "savant_rs::pipeline",
+                    "The object lifecycle callback of stage {} failed: {}", stage, e
+                );
+            }
+        })
+    }
+}
+
+/// Passes the invariant violations to a Python callable as a list of tuples.
+///
+struct PyValidationCallback(PyObject);
+
+impl ValidationCallback for PyValidationCallback {
+    fn on_violations(&self, stage: &str, events: Vec<ValidationEvent>) {
+        let events = events
+            .into_iter()
+            .map(|e| {
+                (
+                    e.violation.kind(),
+                    e.source_id,
+                    e.frame_uuid,
+                    e.violation.to_string(),
+                )
+            })
+            .collect::<Vec<_>>();
+        with_gil!(|py| {
+            if let Err(e) = self.0.call1(py, (stage, events)) {
+                log::error!(
+                    target: "savant_rs::pipeline",
+                    "The validation callback of stage {} failed: {}", stage, e
+                );
+            }
+        })
+    }
+}
+
+fn function_order(egress: bool) -> PipelineStageFunctionOrder {
+    if egress {
+        PipelineStageFunctionOrder::Egress
+    } else {
+        PipelineStageFunctionOrder::Ingress
+    }
+}
+
 #[pymethods]
 impl Pipeline {
     #[new]
@@ -378,6 +1184,36 @@ impl Pipeline {
         self.0.log_final_fps();
     }
 
+    /// The sliding-window FPS and jitter of the frames entering the stages, for all the
+    /// frames of a stage and per source. The rates are also exported as the ``stage_fps``
+    /// and ``stage_jitter`` metrics.
+    ///
+    /// Returns
+    /// -------
+    /// List[StageRate]
+    ///   The rates in the order of the stages, the rate of all the sources of a stage goes
+    ///   first.
+    ///
+    pub fn rates(&self) -> Vec<StageRate> {
+        self.0.rates().into_iter().map(StageRate).collect()
+    }
+
+    /// The ingest rate, the latency and the drop rate of the sources over the rate window,
+    /// published to the upstream with :py:class:`SourceStatsPublisher`.
+    ///
+    /// Returns
+    /// -------
+    /// List[SourceStatistics]
+    ///   The statistics ordered by the source.
+    ///
+    pub fn get_source_statistics(&self) -> Vec<SourceStatistics> {
+        self.0
+            .get_source_statistics()
+            .into_iter()
+            .map(SourceStatistics)
+            .collect()
+    }
+
     /// Clears the ordering for source, called on dead stream eviction.
     ///
     /// Parameters
@@ -483,6 +1319,55 @@ impl Pipeline {
             .add_frame_update(frame_id, update.0)
             .map_err(|e| PyValueError::new_err(e.to_string()))
     }
+
+    /// Registers the attribute namespaces the updater may write. Updates with
+    /// :py:attr:`savant_rs.primitives.VideoFrameUpdate.updater` set are rejected by
+    /// :py:func:`apply_updates` when they write other namespaces or the updater is not
+    /// registered. Once an updater is registered, the updates without an updater are
+    /// rejected as well.
+    ///
+    /// Parameters
+    /// ----------
+    /// name : str
+    ///   The name of the updater.
+    /// namespaces : List[str]
+    ///   The allowed namespaces, glob patterns are supported.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If a pattern is invalid.
+    ///
+    fn register_updater(&self, name: &str, namespaces: Vec<String>) -> PyResult<()> {
+        let namespaces = namespaces.iter().map(String::as_str).collect::<Vec<_>>();
+        self.0
+            .register_updater(name, &namespaces)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Removes the updater registration.
+    ///
+    /// Returns
+    /// -------
+    /// bool
+    ///   Whether the updater was registered.
+    ///
+    fn unregister_updater(&self, name: &str) -> bool {
+        self.0.unregister_updater(name).is_some()
+    }
+
+    /// The namespaces the updater may write.
+    ///
+    /// Returns
+    /// -------
+    /// Optional[List[str]]
+    ///   The namespaces or ``None`` when the updater is not registered.
+    ///
+    fn get_updater_namespaces(&self, name: &str) -> Option<Vec<String>> {
+        self.0
+            .get_updater(name)
+            .map(|scope| scope.get_namespaces().to_vec())
+    }
     /// Adds a frame update to the batched frame.
     ///
     /// GIL management: the function is GIL-free.
@@ -521,6 +1406,10 @@ impl Pipeline {
     ///   The name of the stage. Must be a stage of type independent frames.
     /// frame : :py:class:`savant_rs.primitives.VideoFrameProxy`
     ///   The frame to add.
+    /// priority : int
+    ///   The priority of the frame, the frames with the higher priority are listed first by
+    ///   :py:meth:`get_stage_queue`, e.g. the frames of the live sources ahead of the
+    ///   replayed ones. The priority is kept when the frame moves between the stages.
     ///
     /// Returns
     /// -------
@@ -532,9 +1421,10 @@ impl Pipeline {
     /// ValueError
     ///   If the stage does not exist or is not of type independent frames.
     ///
-    fn add_frame(&self, stage_name: &str, frame: VideoFrame) -> PyResult<i64> {
+    #[pyo3(signature = (stage_name, frame, priority=0))]
+    fn add_frame(&self, stage_name: &str, frame: VideoFrame, priority: i32) -> PyResult<i64> {
         self.0
-            .add_frame(stage_name, frame.0)
+            .add_frame_with_priority(stage_name, frame.0, priority)
             .map_err(|e| PyValueError::new_err(e.to_string()))
     }
 
@@ -601,29 +1491,1261 @@ impl Pipeline {
             Err(e) => Err(PyValueError::new_err(e.to_string())),
         }
     }
-    /// Retrieves the length of the queue of a stage.
+
+    /// Subscribes to the changes of the payloads: the frames added, moved, packed, unpacked
+    /// and deleted and the updates applied. The events are sent since the subscription, a
+    /// subscriber keeps at most 10000 events, the later events are dropped until it takes
+    /// them.
+    ///
+    /// Returns
+    /// -------
+    /// PipelineEventReceiver
+    ///   The events of the subscription.
+    ///
+    fn subscribe(&self) -> PipelineEventReceiver {
+        PipelineEventReceiver(self.0.subscribe())
+    }
+
+    /// Evicts the payloads which stay in their stages for
+    /// :py:attr:`VideoPipelineConfiguration.frame_ttl` or longer. The frames are counted as
+    /// dropped, their spans are ended and they are counted in the
+    /// ``pipeline_evicted_frames`` metric. Runs in the background with
+    /// :py:class:`FrameReaper`.
+    ///
+    /// GIL management: the function is GIL-free.
+    ///
+    /// Returns
+    /// -------
+    /// List[Tuple[str, int, List[int], int]]
+    ///   The stage, the id, the frame ids and the age in milliseconds of the evicted
+    ///   payloads.
+    ///
+    fn evict_stale_frames(&self) -> Vec<(String, i64, Vec<i64>, u64)> {
+        release_gil!(true, || self.0.evict_stale_frames())
+            .into_iter()
+            .map(evicted_payload_tuple)
+            .collect()
+    }
+
+    /// Installs a decimator to the frame stage. Exactly one strategy must be set: every Nth
+    /// frame, the target FPS measured by the frame PTS, or the queue depth of the downstream
+    /// stage (``downstream_stage`` with ``max_queue_len``). The state is tracked per source.
+    ///
+    /// Parameters
+    /// ----------
+    /// stage_name : str
+    ///   The name of the stage.
+    /// every_nth : Optional[int]
+    ///   Keep every Nth frame.
+    /// target_fps : Optional[float]
+    ///   Keep at most the given number of frames per second.
+    /// downstream_stage : Optional[str]
+    ///   Keep frames while the queue of the stage is shorter than ``max_queue_len``.
+    /// max_queue_len : Optional[int]
+    ///   The queue length limit of the downstream stage.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist or is not a frame stage, or the strategy is not set
+    ///   properly.
+    ///
+    #[pyo3(signature = (stage_name, every_nth=None, target_fps=None, downstream_stage=None, max_queue_len=None))]
+    fn set_decimator(
+        &self,
+        stage_name: &str,
+        every_nth: Option<u64>,
+        target_fps: Option<f64>,
+        downstream_stage: Option<String>,
+        max_queue_len: Option<usize>,
+    ) -> PyResult<()> {
+        let strategy = match (every_nth, target_fps, downstream_stage, max_queue_len) {
+            (Some(n), None, None, None) => DecimationStrategy::EveryNth(n),
+            (None, Some(fps), None, None) => DecimationStrategy::TargetFps(fps),
+            (None, None, Some(stage), Some(max_len)) => {
+                DecimationStrategy::QueueDepth { stage, max_len }
+            }
+            _ => {
+                return Err(PyValueError::new_err(
+                    "Exactly one of every_nth, target_fps and downstream_stage with max_queue_len must be set",
+                ))
+            }
+        };
+        let decimator =
+            Decimator::new(strategy).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.0
+            .set_decimator(stage_name, Some(decimator))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Removes the decimator of the stage.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist.
+    ///
+    fn clear_decimator(&self, stage_name: &str) -> PyResult<()> {
+        self.0
+            .set_decimator(stage_name, None)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Installs a result cache to the idempotent frame stage. The objects and frame
+    /// attributes of the namespaces produced by the stage are stored by the frame content
+    /// when frames leave the stage and replayed with :py:meth:`replay_cached`.
+    ///
+    /// Parameters
+    /// ----------
+    /// stage_name : str
+    ///   The name of the stage.
+    /// capacity : int
+    ///   The number of distinct frame contents whose results are kept.
+    /// namespaces : List[str]
+    ///   The namespaces produced by the stage.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist or is not a frame stage, the capacity is 0 or no
+    ///   namespaces are given.
+    ///
+    fn set_result_cache(
+        &self,
+        stage_name: &str,
+        capacity: usize,
+        namespaces: Vec<String>,
+    ) -> PyResult<()> {
+        let capacity = NonZeroUsize::new(capacity)
+            .ok_or_else(|| PyValueError::new_err("The capacity must be greater than 0"))?;
+        let cache = ResultCache::new(ResultCacheConfig {
+            capacity,
+            namespaces,
+        })
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.0
+            .set_result_cache(stage_name, Some(cache))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Removes the result cache of the stage.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist.
+    ///
+    fn clear_result_cache(&self, stage_name: &str) -> PyResult<()> {
+        self.0
+            .set_result_cache(stage_name, None)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Returns the hits, the misses and the number of cached contents of the result cache,
+    /// ``None`` when the stage has no cache.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist.
+    ///
+    fn get_result_cache_stats(&self, stage_name: &str) -> PyResult<Option<(u64, u64, usize)>> {
+        self.0
+            .get_result_cache(stage_name)
+            .map(|c| {
+                c.map(|c| {
+                    let stats = c.get_stats();
+                    (stats.hits, stats.misses, stats.entries)
+                })
+            })
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Compresses the internal content of the frames entering the frame or batch stage with
+    /// the codec. The frames decompress the content on access.
+    ///
+    /// Parameters
+    /// ----------
+    /// stage_name : str
+    ///   The name of the stage.
+    /// encoding : Optional[str]
+    ///   ``lz4`` or ``zstd``, ``None`` stops the compression.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist or is not a frame or batch stage, or the codec is
+    ///   unknown.
+    ///
+    #[pyo3(signature = (stage_name, encoding=None))]
+    fn set_content_encoding(&self, stage_name: &str, encoding: Option<&str>) -> PyResult<()> {
+        let encoding = encoding
+            .map(str::parse::<ContentEncoding>)
+            .transpose()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.0
+            .set_content_encoding(stage_name, encoding)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Returns the codec compressing the content of the frames entering the stage.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist.
+    ///
+    fn get_content_encoding(&self, stage_name: &str) -> PyResult<Option<String>> {
+        self.0
+            .get_content_encoding(stage_name)
+            .map(|e| e.map(|e| e.name().to_string()))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Replaces the ingress or egress function of the stage while the pipeline runs. The call
+    /// waits for the in-flight calls of the current function with the GIL released and swaps
+    /// the functions atomically.
+    ///
+    /// Parameters
+    /// ----------
+    /// stage_name : str
+    ///   The name of the stage.
+    /// function : StageFunction
+    ///   The new function, ``StageFunction.none()`` removes the current one. The function is
+    ///   moved into the pipeline.
+    /// egress : bool
+    ///   Whether to replace the egress function instead of the ingress one.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    ///   The version of the installed function, the initial function has version 0.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist.
+    ///
+    #[pyo3(signature = (stage_name, function, egress=false))]
+    fn replace_stage_processor(
+        &self,
+        stage_name: &str,
+        function: &StageFunction,
+        egress: bool,
+    ) -> PyResult<u64> {
+        let function = function.0.lock().take();
+        release_gil!(true, || self
+            .0
+            .replace_stage_processor(stage_name, function_order(egress), function)
+            .map(|v| v.version)
+            .map_err(|e| PyValueError::new_err(e.to_string())))
+    }
+
+    /// Returns the version of the ingress or egress function of the stage.
+    ///
+    /// Parameters
+    /// ----------
+    /// stage_name : str
+    ///   The name of the stage.
+    /// egress : bool
+    ///   Whether to query the egress function instead of the ingress one.
+    ///
+    /// Returns
+    /// -------
+    /// Tuple[int, bool]
+    ///   The version and whether the stage has the function.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist.
+    ///
+    #[pyo3(signature = (stage_name, egress=false))]
+    fn get_stage_processor_version(&self, stage_name: &str, egress: bool) -> PyResult<(u64, bool)> {
+        self.0
+            .get_stage_processor_version(stage_name, function_order(egress))
+            .map(|v| (v.version, v.installed))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Reports the readiness of the stage on behalf of its processor, e.g. while a model is
+    /// loaded. Payloads are not sent to a stage while it or any stage after it is warming up.
+    ///
+    /// Parameters
+    /// ----------
+    /// stage_name : str
+    ///   The name of the stage.
+    /// warming_up : Optional[str]
+    ///   The reason the stage is not ready, ``None`` marks the stage ready.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist.
+    ///
+    #[pyo3(signature = (stage_name, warming_up=None))]
+    fn set_stage_readiness(&self, stage_name: &str, warming_up: Option<String>) -> PyResult<()> {
+        let readiness = match warming_up {
+            Some(reason) => StageReadiness::WarmingUp(reason),
+            None => StageReadiness::Ready,
+        };
+        self.0
+            .set_stage_readiness(stage_name, readiness)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Returns the reason the stage or one of its functions is warming up, ``None`` when the
+    /// stage is ready.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist.
+    ///
+    fn get_stage_readiness(&self, stage_name: &str) -> PyResult<Option<String>> {
+        match self.0.get_stage_readiness(stage_name) {
+            Ok(StageReadiness::Ready) => Ok(None),
+            Ok(StageReadiness::WarmingUp(reason)) => Ok(Some(reason)),
+            Err(e) => Err(PyValueError::new_err(e.to_string())),
+        }
+    }
+
+    /// Sets what happens to the payloads sent to the stage while the pipeline is not ready
+    /// for them. The call sending the payloads raises ValueError in both cases.
+    ///
+    /// Parameters
+    /// ----------
+    /// stage_name : str
+    ///   The name of the stage.
+    /// policy : str
+    ///   ``hold`` keeps the payloads in the previous stage, ``drop`` deletes them.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist or the policy is unknown.
+    ///
+    fn set_warmup_policy(&self, stage_name: &str, policy: &str) -> PyResult<()> {
+        let policy = match policy {
+            "hold" => WarmupPolicy::Hold,
+            "drop" => WarmupPolicy::Drop,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Unknown warm-up policy {}, expected hold or drop",
+                    policy
+                )))
+            }
+        };
+        self.0
+            .set_warmup_policy(stage_name, policy)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Whether all stages of the pipeline are ready.
+    ///
+    #[getter]
+    fn is_ready(&self) -> bool {
+        self.0.is_ready()
+    }
+
+    /// Replays the cached results into the frames of the stage.
+    ///
+    /// GIL management: the function is GIL-free.
+    ///
+    /// Parameters
+    /// ----------
+    /// stage_name : str
+    ///   The name of the stage with the result cache.
+    /// frame_ids : List[int]
+    ///   The ids of the frames in the stage.
+    ///
+    /// Returns
+    /// -------
+    /// Tuple[List[int], List[int]]
+    ///   The ids of the replayed frames, which the stage does not need to process, and the
+    ///   ids of the other frames.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist or has no result cache. If a frame is not in the stage.
+    ///
+    fn replay_cached(
+        &self,
+        stage_name: &str,
+        frame_ids: Vec<i64>,
+    ) -> PyResult<(Vec<i64>, Vec<i64>)> {
+        release_gil!(true, || self.0.replay_cached(stage_name, &frame_ids))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Passes the frames of the stage through its decimator. The skipped frames are deleted
+    /// from the pipeline, so they must not be used after the call.
+    ///
+    /// GIL management: the function is GIL-free.
+    ///
+    /// Parameters
+    /// ----------
+    /// stage_name : str
+    ///   The name of the decimator stage.
+    /// frame_ids : List[int]
+    ///   The ids of the frames in the stage.
+    ///
+    /// Returns
+    /// -------
+    /// Tuple[List[int], dict[int, :py:class:`savant_rs.utils.TelemetrySpan`]]
+    ///   The ids of the kept frames and the telemetry contexts of the deleted frames.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist or has no decimator. If a frame is not in the stage.
+    ///
+    fn decimate(
+        &self,
+        stage_name: &str,
+        frame_ids: Vec<i64>,
+    ) -> PyResult<(Vec<i64>, HashMap<i64, TelemetrySpan>)> {
+        let (kept, dropped) = release_gil!(true, || self.0.decimate(stage_name, &frame_ids))
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok((
+            kept,
+            dropped
+                .into_iter()
+                .map(|(k, v)| (k, TelemetrySpan::from_context(v)))
+                .collect(),
+        ))
+    }
+
+    /// Installs the circuit breaker of a frame stage quarantining the sources which fail
+    /// repeatedly. After the quarantine a single probe frame of the source passes; its
+    /// success closes the circuit, its failure quarantines the source again.
+    ///
+    /// Parameters
+    /// ----------
+    /// stage_name : str
+    ///   The name of the stage.
+    /// failure_threshold : int
+    ///   The number of consecutive failures which quarantines a source.
+    /// quarantine_ms : int
+    ///   How long a source stays quarantined before it is probed, measured by the pipeline
+    ///   clock.
+    /// dead_letter_stage : Optional[str]
+    ///   The stage the frames of quarantined sources are moved to, they are deleted when
+    ///   not set. The stage must follow the breaker stage.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If a stage does not exist or is not a frame stage, or the threshold is 0.
+    ///
+    #[pyo3(signature = (stage_name, failure_threshold, quarantine_ms, dead_letter_stage=None))]
+    fn set_circuit_breaker(
+        &self,
+        stage_name: &str,
+        failure_threshold: u32,
+        quarantine_ms: u64,
+        dead_letter_stage: Option<String>,
+    ) -> PyResult<()> {
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold,
+            quarantine: Duration::from_millis(quarantine_ms),
+            policy: match dead_letter_stage {
+                Some(stage) => QuarantinePolicy::DeadLetter(stage),
+                None => QuarantinePolicy::Drop,
+            },
+        })
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.0
+            .set_circuit_breaker(stage_name, Some(breaker))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Removes the circuit breaker of the stage.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist.
+    ///
+    fn clear_circuit_breaker(&self, stage_name: &str) -> PyResult<()> {
+        self.0
+            .set_circuit_breaker(stage_name, None)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Reports a failure of the stage processor for a frame of the source.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist or has no circuit breaker.
+    ///
+    fn record_source_failure(&self, stage_name: &str, source_id: &str) -> PyResult<()> {
+        self.get_breaker(stage_name)?.record_failure(source_id);
+        Ok(())
+    }
+
+    /// Reports a success of the stage processor for a frame of the source.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist or has no circuit breaker.
+    ///
+    fn record_source_success(&self, stage_name: &str, source_id: &str) -> PyResult<()> {
+        self.get_breaker(stage_name)?.record_success(source_id);
+        Ok(())
+    }
+
+    /// The sources quarantined or probed by the circuit breaker of the stage.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist or has no circuit breaker.
+    ///
+    fn get_quarantined_sources(&self, stage_name: &str) -> PyResult<Vec<String>> {
+        Ok(self.get_breaker(stage_name)?.get_quarantined())
+    }
+
+    /// Holds back the frames of the sources quarantined by the circuit breaker of the stage.
+    /// The held back frames are deleted from the pipeline or moved to the dead-letter stage.
+    ///
+    /// GIL management: the function is GIL-free.
+    ///
+    /// Parameters
+    /// ----------
+    /// stage_name : str
+    ///   The name of the breaker stage.
+    /// frame_ids : List[int]
+    ///   The ids of the frames in the stage.
+    ///
+    /// Returns
+    /// -------
+    /// Tuple[List[int], List[int], dict[int, :py:class:`savant_rs.utils.TelemetrySpan`]]
+    ///   The ids of the kept frames, the ids of the frames moved to the dead-letter stage and
+    ///   the telemetry contexts of the deleted frames.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist or has no circuit breaker. If a frame is not in the
+    ///   stage.
+    ///
+    #[allow(clippy::type_complexity)]
+    fn isolate(
+        &self,
+        stage_name: &str,
+        frame_ids: Vec<i64>,
+    ) -> PyResult<(Vec<i64>, Vec<i64>, HashMap<i64, TelemetrySpan>)> {
+        let isolation = release_gil!(true, || self.0.isolate(stage_name, &frame_ids))
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok((
+            isolation.kept,
+            isolation.routed,
+            isolation
+                .dropped
+                .into_iter()
+                .map(|(k, v)| (k, TelemetrySpan::from_context(v)))
+                .collect(),
+        ))
+    }
+
+    /// Installs the fault injector of a stage, available when the package is built with the
+    /// ``chaos`` feature. Each fault is rolled independently for every payload entering the
+    /// stage, a dropped payload is deleted instead of entering the stage.
+    ///
+    /// Parameters
+    /// ----------
+    /// stage_name : str
+    ///   The name of the stage.
+    /// delay_probability : float
+    ///   The probability of a payload being delayed by ``delay_ms``.
+    /// delay_ms : int
+    ///   The delay in milliseconds.
+    /// drop_probability : float
+    ///   The probability of a payload being deleted from the pipeline.
+    /// corrupt_probability : float
+    ///   The probability of the content of the frames of a payload being corrupted.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist or a probability is not within [0, 1].
+    ///
+    #[cfg(feature = "chaos")]
+    #[pyo3(signature = (stage_name, delay_probability=0.0, delay_ms=0, drop_probability=0.0, corrupt_probability=0.0))]
+    fn set_fault_injector(
+        &self,
+        stage_name: &str,
+        delay_probability: f64,
+        delay_ms: u64,
+        drop_probability: f64,
+        corrupt_probability: f64,
+    ) -> PyResult<()> {
+        let injector = FaultInjector::new(FaultInjectorConfig {
+            delay_probability,
+            delay_ms,
+            drop_probability,
+            corrupt_probability,
+        })
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.0
+            .set_fault_injector(stage_name, Some(injector))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Removes the fault injector of the stage.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist.
+    ///
+    #[cfg(feature = "chaos")]
+    fn clear_fault_injector(&self, stage_name: &str) -> PyResult<()> {
+        self.0
+            .set_fault_injector(stage_name, None)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Passes the payloads of the stage through its fault injector. The dropped payloads are
+    /// deleted from the pipeline, so they must not be used after the call. Without an
+    /// injector all payloads are kept.
+    ///
+    /// GIL management: the function is GIL-free.
+    ///
+    /// Parameters
+    /// ----------
+    /// stage_name : str
+    ///   The name of the stage.
+    /// ids : List[int]
+    ///   The ids of the payloads in the stage.
+    ///
+    /// Returns
+    /// -------
+    /// Tuple[List[int], dict[int, :py:class:`savant_rs.utils.TelemetrySpan`]]
+    ///   The ids of the kept payloads and the telemetry contexts of the deleted payloads.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist. If a payload is not in the stage.
+    ///
+    #[cfg(feature = "chaos")]
+    fn inject_faults(
+        &self,
+        stage_name: &str,
+        ids: Vec<i64>,
+    ) -> PyResult<(Vec<i64>, HashMap<i64, TelemetrySpan>)> {
+        let (kept, dropped) = release_gil!(true, || self.0.inject_faults(stage_name, &ids))
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok((
+            kept,
+            dropped
+                .into_iter()
+                .map(|(k, v)| (k, TelemetrySpan::from_context(v)))
+                .collect(),
+        ))
+    }
+
+    /// Retrieves the length of the queue of a stage.
+    ///
+    /// GIL management: the function is GIL-free.
+    ///
+    /// Parameters
+    /// ----------
+    /// stage_name : str
+    ///   The name of the stage.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    ///   The length of the queue of the stage.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist.
+    ///
+    fn get_stage_queue_len(&self, stage_name: &str) -> PyResult<usize> {
+        self.0
+            .get_stage_queue_len(stage_name)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Retrieves the ids of the payloads of a stage in the order they should be processed
+    /// in: by the priority, the higher first, and then in the order they entered the
+    /// pipeline.
+    ///
+    /// GIL management: the function is GIL-free.
+    ///
+    /// Parameters
+    /// ----------
+    /// stage_name : str
+    ///   The name of the stage.
+    ///
+    /// Returns
+    /// -------
+    /// List[int]
+    ///   The ids of the payloads.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist.
+    ///
+    fn get_stage_queue(&self, stage_name: &str) -> PyResult<Vec<i64>> {
+        self.0
+            .get_stage_queue(stage_name)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Changes the priority of a payload in its current stage. A batch gets the highest
+    /// priority of its frames and the frames unpacked from a batch get the priority of the
+    /// batch.
+    ///
+    /// Parameters
+    /// ----------
+    /// id : int
+    ///   The id of the frame or the batch.
+    /// priority : int
+    ///   The priority.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the payload is not in the pipeline.
+    ///
+    fn set_priority(&self, id: i64, priority: i32) -> PyResult<()> {
+        self.0
+            .set_priority(id, priority)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Retrieves the priority of a payload.
+    ///
+    /// Parameters
+    /// ----------
+    /// id : int
+    ///   The id of the frame or the batch.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    ///   The priority.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the payload is not in the pipeline.
+    ///
+    fn get_priority(&self, id: i64) -> PyResult<i32> {
+        self.0
+            .get_priority(id)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Creates a context manager measuring the user code processing the payloads of the
+    /// stage. The time spent in the calls to savant_rs made by the code is reported
+    /// separately as the Rust overhead, and the time spent taking the GIL back from other
+    /// threads after these calls as the GIL wait. The time is aggregated in the stage
+    /// statistics and added as the ``user-code`` event to the spans of the payloads.
+    ///
+    /// Parameters
+    /// ----------
+    /// stage_name : str
+    ///   The name of the stage.
+    /// ids : List[int]
+    ///   The ids of the frames or batches processed by the code.
+    ///
+    /// Returns
+    /// -------
+    /// :py:class:`UserCodeSection`
+    ///
+    /// Example
+    /// -------
+    ///
+    /// .. code-block:: python
+    ///
+    ///   with pipeline.user_code("detector", [frame_id]) as section:
+    ///       process(frame)
+    ///   print(section.user_time, section.rust_time)
+    ///
+    #[pyo3(signature = (stage_name, ids=vec![]))]
+    fn user_code(slf: Py<Self>, stage_name: &str, ids: Vec<i64>) -> UserCodeSection {
+        UserCodeSection {
+            pipeline: slf,
+            stage_name: stage_name.to_string(),
+            ids,
+            section: None,
+            time: None,
+        }
+    }
+
+    /// Installs a debug tap dumping frames entering the stage: every Nth frame or the frames
+    /// having objects matching the query. The frames are written to a directory, to the
+    /// KVS blob ``<kvs_namespace>/<stage>/<source_id>`` or to a fixture file loaded by
+    /// :py:func:`savant_rs.utils.load_fixture`. Exactly one selector and one sink must be set.
+    ///
+    /// Parameters
+    /// ----------
+    /// stage_name : str
+    ///   The name of the stage.
+    /// every_nth : Optional[int]
+    ///   Dump every Nth frame.
+    /// query : Optional[:py:class:`savant_rs.match_query.MatchQuery`]
+    ///   Dump frames having objects matching the query.
+    /// directory : Optional[str]
+    ///   The directory for the dumps.
+    /// kvs_namespace : Optional[str]
+    ///   The KVS namespace for the dumps.
+    /// kvs_ttl : Optional[int]
+    ///   The TTL of the KVS dumps in milliseconds.
+    /// fixture : Optional[str]
+    ///   The fixture file for the dumps, truncated when the tap is installed.
+    /// fixture_max_frames : int
+    ///   The number of frames the fixture holds.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist or the selector or the sink are not set properly.
+    ///
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (stage_name, every_nth=None, query=None, directory=None, kvs_namespace=None, kvs_ttl=None, fixture=None, fixture_max_frames=100))]
+    fn set_debug_tap(
+        &self,
+        stage_name: &str,
+        every_nth: Option<u64>,
+        query: Option<MatchQuery>,
+        directory: Option<String>,
+        kvs_namespace: Option<String>,
+        kvs_ttl: Option<u64>,
+        fixture: Option<String>,
+        fixture_max_frames: u64,
+    ) -> PyResult<()> {
+        let selector = match (every_nth, query) {
+            (Some(n), None) => DebugTapSelector::EveryNth(n),
+            (None, Some(q)) => DebugTapSelector::Query(q.0),
+            _ => {
+                return Err(PyValueError::new_err(
+                    "Exactly one of every_nth and query must be set",
+                ))
+            }
+        };
+        let sink = match (directory, kvs_namespace, fixture) {
+            (Some(dir), None, None) => DebugTapSink::Directory(dir.into()),
+            (None, Some(namespace), None) => DebugTapSink::Kvs {
+                namespace,
+                ttl: kvs_ttl,
+            },
+            (None, None, Some(path)) => DebugTapSink::Fixture {
+                path: path.into(),
+                max_frames: fixture_max_frames,
+            },
+            _ => {
+                return Err(PyValueError::new_err(
+                    "Exactly one of directory, kvs_namespace and fixture must be set",
+                ))
+            }
+        };
+        let tap =
+            DebugTap::new(selector, sink).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.0
+            .set_debug_tap(stage_name, Some(tap))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Removes the debug tap of the stage.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist.
+    ///
+    fn clear_debug_tap(&self, stage_name: &str) -> PyResult<()> {
+        self.0
+            .set_debug_tap(stage_name, None)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Sets the callable receiving the objects added, modified and deleted while the frames
+    /// are in the stage. The objects are compared when the frames leave the stage, the
+    /// events of the frames leaving together are passed in one call as
+    /// ``callback(stage_name, events)`` where every event is a tuple of the kind (``added``,
+    /// ``modified`` or ``deleted``), the source, the frame UUID, the object id, the namespace
+    /// and the label. The exceptions of the callable are logged. ``None`` removes the
+    /// callable.
+    ///
+    /// Parameters
+    /// ----------
+    /// stage_name : str
+    ///   The stage.
+    /// callback : Optional[Callable[[str, List[Tuple[str, str, str, int, str, str]]], None]]
+    ///   The callable.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist.
+    ///
+    #[pyo3(signature = (stage_name, callback=None))]
+    fn set_object_callback(&self, stage_name: &str, callback: Option<PyObject>) -> PyResult<()> {
+        let observer =
+            callback.map(|f| ObjectObserver::new(Arc::new(PyObjectLifecycleCallback(f))));
+        self.0
+            .set_object_observer(stage_name, observer)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Records the frames leaving the stages to a journal in the directory, so their states
+    /// are reconstructed later with :py:meth:`reconstruct_frame_at`. Every run writes its own
+    /// journal. The frames are recorded before the export redaction. ``None`` stops the
+    /// recording.
+    ///
+    /// Parameters
+    /// ----------
+    /// directory : Optional[str]
+    ///   The directory of the journals.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the journal cannot be created.
+    ///
+    #[pyo3(signature = (directory=None))]
+    fn set_frame_history(&self, directory: Option<String>) -> PyResult<()> {
+        let history = directory
+            .map(FrameHistory::new)
+            .transpose()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.0.set_frame_history(history);
+        Ok(())
+    }
+
+    /// Checks the invariants of the frames leaving the stages: the parents of the objects are
+    /// in the frame, the boxes are finite and within the frame, the track ids are unique per
+    /// namespace and label and the attributes pass the contracts of the stages. The
+    /// violations are logged, counted in the ``frame_invariant_violations`` metric, added as
+    /// the ``invariant-violation`` events to the spans of the frames and passed to the
+    /// callable as ``callback(stage_name, violations)`` where every violation is a tuple of
+    /// the kind, the source, the frame UUID and the message.
+    ///
+    /// Parameters
+    /// ----------
+    /// enabled : bool
+    ///   ``False`` removes the validation.
+    /// sample_every : int
+    ///   Every n-th frame leaving a stage is checked.
+    /// bounds_margin : Optional[float]
+    ///   The margin of the frame bounds in pixels, ``None`` does not check the bounds.
+    /// contracts : Optional[Dict[str, str]]
+    ///   The YAML module contracts the frames leaving the stages must pass, by the stage.
+    /// callback : Optional[Callable[[str, List[Tuple[str, str, str, str]]], None]]
+    ///   The callable.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If ``sample_every`` is 0 or a contract is invalid.
+    ///
+    #[pyo3(signature = (enabled=true, sample_every=1, bounds_margin=Some(0.0), contracts=None, callback=None))]
+    fn set_frame_validator(
+        &self,
+        enabled: bool,
+        sample_every: u64,
+        bounds_margin: Option<f32>,
+        contracts: Option<HashMap<String, String>>,
+        callback: Option<PyObject>,
+    ) -> PyResult<()> {
+        if !enabled {
+            self.0.set_frame_validator(None);
+            return Ok(());
+        }
+        let mut config = FrameValidatorConfig {
+            sample_every,
+            bounds_margin,
+            ..Default::default()
+        };
+        for (stage, contract) in contracts.unwrap_or_default() {
+            config.contracts.insert(stage, parse_contract(&contract)?);
+        }
+        let callback =
+            callback.map(|f| Arc::new(PyValidationCallback(f)) as Arc<dyn ValidationCallback>);
+        let validator = FrameValidator::new(config, callback)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.0.set_frame_validator(Some(validator));
+        Ok(())
+    }
+
+    /// Rebuilds the frame, with its objects and attributes, as it left the stage. The frame
+    /// is searched in the journals of the current and the past runs.
+    ///
+    /// Parameters
+    /// ----------
+    /// uuid : str
+    ///   The UUID of the frame.
+    /// stage_name : str
+    ///   The stage.
+    ///
+    /// Returns
+    /// -------
+    /// Optional[:py:class:`savant_rs.primitives.VideoFrame`]
+    ///   The frame, ``None`` if it did not leave the stage.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist, the history is not set or the journals cannot be read.
+    ///
+    fn reconstruct_frame_at(&self, uuid: &str, stage_name: &str) -> PyResult<Option<VideoFrame>> {
+        let uuid = uuid::Uuid::parse_str(uuid).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        release_gil!(true, || self.0.reconstruct_frame_at(uuid, stage_name))
+            .map(|f| f.map(VideoFrame))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Pauses or resumes the debug tap of the stage.
+    ///
+    /// Returns
+    /// -------
+    /// bool
+    ///   Whether the stage has a debug tap.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist.
+    ///
+    fn set_debug_tap_enabled(&self, stage_name: &str, enabled: bool) -> PyResult<bool> {
+        let tap = self
+            .0
+            .get_debug_tap(stage_name)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(tap.map(|tap| tap.set_enabled(enabled)).is_some())
+    }
+
+    /// The number of frames dumped by the debug tap of the stage, ``None`` when the stage has
+    /// no tap. Waits until the frames queued by the tap are written.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist.
+    ///
+    fn get_debug_tap_dumped(&self, stage_name: &str) -> PyResult<Option<u64>> {
+        let tap = self
+            .0
+            .get_debug_tap(stage_name)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(release_gil!(true, || tap.map(|tap| {
+            tap.flush();
+            tap.get_dumped()
+        })))
+    }
+
+    /// The number of frames dropped by the debug tap of the stage because its sink fell
+    /// behind, ``None`` when the stage has no tap.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist.
+    ///
+    fn get_debug_tap_dropped(&self, stage_name: &str) -> PyResult<Option<u64>> {
+        let tap = self
+            .0
+            .get_debug_tap(stage_name)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(tap.map(|tap| tap.get_dropped()))
+    }
+
+    /// Installs the per-source configuration resolved when frames are added and available
+    /// as :py:attr:`savant_rs.primitives.VideoFrame.source_config`. The overrides of a
+    /// source are looked up in the ``<source_id>.yaml`` or ``<source_id>.json`` file of the
+    /// directory or in the JSON KVS blob ``<kvs_namespace>/<source_id>`` and applied over the
+    /// defaults. At most one provider can be set.
+    ///
+    /// Parameters
+    /// ----------
+    /// defaults : str
+    ///   The global defaults, a JSON object.
+    /// directory : Optional[str]
+    ///   The directory with the source configuration files.
+    /// kvs_namespace : Optional[str]
+    ///   The KVS namespace with the source configuration blobs.
+    /// ttl_ms : int
+    ///   How long a resolved configuration is cached.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the defaults are not a JSON object or both providers are set.
+    ///
+    #[pyo3(signature = (defaults="{}", directory=None, kvs_namespace=None, ttl_ms=60000))]
+    fn set_source_config(
+        &self,
+        defaults: &str,
+        directory: Option<String>,
+        kvs_namespace: Option<String>,
+        ttl_ms: u64,
+    ) -> PyResult<()> {
+        let provider = match (directory, kvs_namespace) {
+            (None, None) => None,
+            (Some(dir), None) => Some(SourceConfigProvider::Directory(dir.into())),
+            (None, Some(namespace)) => Some(SourceConfigProvider::Kvs { namespace }),
+            _ => {
+                return Err(PyValueError::new_err(
+                    "At most one of directory and kvs_namespace can be set",
+                ))
+            }
+        };
+        let defaults =
+            serde_json::from_str(defaults).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let resolver = SourceConfigResolver::new(defaults, provider, Duration::from_millis(ttl_ms))
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.0.set_source_config_resolver(Some(resolver));
+        Ok(())
+    }
+
+    /// Removes the per-source configuration, frames are added without it.
+    ///
+    fn clear_source_config(&self) {
+        self.0.set_source_config_resolver(None);
+    }
+
+    /// Installs the filter checked when frames are added, the rejected frames are not added
+    /// and :py:meth:`add_frame` raises. The rules are a YAML or JSON object with the optional
+    /// keys ``allowed_codecs``, ``max_width``, ``max_height``, ``allow_sources`` and
+    /// ``deny_sources`` (the source globs). ``None`` removes the filter.
+    ///
+    /// Parameters
+    /// ----------
+    /// rules : Optional[str]
+    ///   The rules.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the rules are invalid.
+    ///
+    #[pyo3(signature = (rules=None))]
+    fn set_admission_filter(&self, rules: Option<&str>) -> PyResult<()> {
+        let filter = rules
+            .map(AdmissionFilter::from_yaml)
+            .transpose()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.0.set_admission_filter(filter);
+        Ok(())
+    }
+
+    /// Takes the frames rejected by the admission filter since the last call.
+    ///
+    /// Returns
+    /// -------
+    /// List[Tuple[str, str, str]]
+    ///   The source, the frame UUID and the reason of the rejections.
+    ///
+    fn take_admission_rejections(&self) -> Vec<(String, String, String)> {
+        self.0
+            .get_admission_filter()
+            .map(|filter| {
+                filter
+                    .get_rejections()
+                    .try_iter()
+                    .map(|r| (r.source_id, r.frame_uuid, r.reason.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Sets the frame attributes passed to the objects created in the frames and to the
+    /// events created with :py:meth:`savant_rs.primitives.VideoFrame.create_user_data`. The
+    /// rules are attached to the frames when they are added, ``None`` removes them.
+    ///
+    /// Parameters
+    /// ----------
+    /// rules : Optional[List[savant_rs.primitives.PropagationRule]]
+    ///   The rules, the first rule matching an attribute decides how it is passed.
+    ///
+    #[pyo3(signature = (rules=None))]
+    fn set_attribute_propagation(&self, rules: Option<Vec<PropagationRule>>) {
+        self.0.set_attribute_propagation(
+            rules.map(|rules| AttributePropagation::new(rules.into_iter().map(|r| r.0).collect())),
+        );
+    }
+
+    /// Sets the in-memory overrides of the source applied over the provider overrides,
+    /// ``None`` removes them.
+    ///
+    /// Parameters
+    /// ----------
+    /// source_id : str
+    ///   The source.
+    /// overrides : Optional[str]
+    ///   The overrides, a JSON object.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the per-source configuration is not set or the overrides are not a JSON object.
+    ///
+    #[pyo3(signature = (source_id, overrides=None))]
+    fn set_source_config_override(&self, source_id: &str, overrides: Option<&str>) -> PyResult<()> {
+        let resolver = self
+            .0
+            .get_source_config_resolver()
+            .ok_or_else(|| PyValueError::new_err("The per-source configuration is not set"))?;
+        match overrides {
+            Some(overrides) => {
+                let overrides = serde_json::from_str(overrides)
+                    .map_err(|e| PyValueError::new_err(e.to_string()))?;
+                resolver
+                    .set_override(source_id, overrides)
+                    .map_err(|e| PyValueError::new_err(e.to_string()))
+            }
+            None => {
+                resolver.clear_override(source_id);
+                Ok(())
+            }
+        }
+    }
+
+    /// Drops the cached configuration of the source, ``None`` drops all cached
+    /// configurations, so they are looked up again when the next frame is added.
     ///
-    /// GIL management: the function is GIL-free.
+    #[pyo3(signature = (source_id=None))]
+    fn invalidate_source_config(&self, source_id: Option<&str>) {
+        if let Some(resolver) = self.0.get_source_config_resolver() {
+            resolver.invalidate(source_id);
+        }
+    }
+    /// Renders the pipeline topology: stages, allowed transitions, attached functions and
+    /// current queue lengths.
     ///
     /// Parameters
     /// ----------
-    /// stage_name : str
-    ///   The name of the stage.
+    /// format : str
+    ///   ``dot`` or ``mermaid``.
     ///
     /// Returns
     /// -------
-    /// int
-    ///   The length of the queue of the stage.
+    /// str
+    ///   The rendered graph.
     ///
     /// Raises
     /// ------
     /// ValueError
-    ///   If the stage does not exist.
+    ///   If the format is unknown.
     ///
-    fn get_stage_queue_len(&self, stage_name: &str) -> PyResult<usize> {
-        self.0
-            .get_stage_queue_len(stage_name)
-            .map_err(|e| PyValueError::new_err(e.to_string()))
+    #[pyo3(signature = (format = "dot"))]
+    fn export_topology(&self, format: &str) -> PyResult<String> {
+        let format = format
+            .parse()
+            .map_err(|e: anyhow::Error| PyValueError::new_err(e.to_string()))?;
+        Ok(self.0.export_topology(format))
     }
     /// Retrieves an independent frame from a specified stage.
     ///
@@ -864,6 +2986,257 @@ impl Pipeline {
         })
     }
 
+    /// Packs the independent frames of the source stage into the batches of the batch stage
+    /// when ``max_size`` inference regions accumulate or the oldest frame waits for
+    /// ``max_latency_ms``, the frames with the higher priority first. A frame counts as the
+    /// number of its ``roi_list`` regions, or one when the list is empty. The size is checked
+    /// when the frames enter the source stage, the latency by :py:meth:`flush_auto_batches`,
+    /// called in the background by :py:class:`AutoBatchFlusher`.
+    ///
+    /// Parameters
+    /// ----------
+    /// batch_stage_name : str
+    ///   The stage with batches.
+    /// source_stage_name : str
+    ///   The stage with independent frames before it.
+    /// max_size : int
+    ///   The size of the batches.
+    /// max_latency_ms : int
+    ///   The longest time a frame waits for a batch.
+    /// bypass_stage_name : Optional[str]
+    ///   The stage with independent frames the frames with ``skip_inference`` are moved to
+    ///   instead of being packed.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stages do not exist, have wrong types or the source stage already feeds
+    ///   another stage, or the size is 0.
+    ///
+    #[pyo3(signature = (batch_stage_name, source_stage_name, max_size, max_latency_ms, bypass_stage_name=None))]
+    fn set_auto_batch(
+        &self,
+        batch_stage_name: &str,
+        source_stage_name: &str,
+        max_size: usize,
+        max_latency_ms: u64,
+        bypass_stage_name: Option<&str>,
+    ) -> PyResult<()> {
+        AutoBatchConfig::new(max_size, Duration::from_millis(max_latency_ms))
+            .map(|config| match bypass_stage_name {
+                Some(stage_name) => config.with_bypass(stage_name),
+                None => config,
+            })
+            .and_then(|config| {
+                self.0
+                    .set_auto_batch(batch_stage_name, source_stage_name, config)
+            })
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Removes the auto-batching of the stage.
+    ///
+    /// Returns
+    /// -------
+    /// bool
+    ///   Whether the stage had the auto-batching.
+    ///
+    fn clear_auto_batch(&self, batch_stage_name: &str) -> PyResult<bool> {
+        self.0
+            .clear_auto_batch(batch_stage_name)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Retrieves the auto-batching of the stage.
+    ///
+    /// Returns
+    /// -------
+    /// Optional[Tuple[str, int, int]]
+    ///   The source stage, the size and the latency in milliseconds.
+    ///
+    fn get_auto_batch(&self, batch_stage_name: &str) -> PyResult<Option<(String, usize, u64)>> {
+        self.0
+            .get_auto_batch(batch_stage_name)
+            .map(|b| {
+                b.map(|(source, config)| {
+                    (
+                        source,
+                        config.max_size,
+                        config.max_latency.as_millis() as u64,
+                    )
+                })
+            })
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Packs the frames which waited for the latency of the auto-batching.
+    ///
+    /// GIL management: the function is GIL-free.
+    ///
+    /// Parameters
+    /// ----------
+    /// force : bool
+    ///   Packs all the waiting frames, e.g. at the end of the stream.
+    ///
+    /// Returns
+    /// -------
+    /// List[int]
+    ///   The ids of the created batches.
+    ///
+    #[pyo3(signature = (force = false))]
+    fn flush_auto_batches(&self, force: bool) -> PyResult<Vec<i64>> {
+        release_gil!(true, || self.0.flush_auto_batches(force))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Moves the frames having objects matching the query to one stage and the rest to
+    /// another, so the next stage is chosen without accessing the frames in Python. The
+    /// stages must contain independent frames.
+    ///
+    /// GIL management: the function is GIL-free by default.
+    ///
+    /// Parameters
+    /// ----------
+    /// matched_stage_name : str
+    ///   The stage of the frames having matching objects.
+    /// unmatched_stage_name : str
+    ///   The stage of the other frames.
+    /// query : :py:class:`savant_rs.match_query.MatchQuery`
+    ///   The query evaluated on the objects of the frames.
+    /// frame_ids : List[int]
+    ///   The ids of the frames, all in the same stage.
+    /// no_gil : bool
+    ///   Whether to release the GIL.
+    ///
+    /// Returns
+    /// -------
+    /// Tuple[List[int], List[int]]
+    ///   The ids of the matching and the other frames.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the frames are not in the same stage, a stage does not exist or does not contain
+    ///   independent frames.
+    ///
+    #[pyo3(name = "move_matching")]
+    #[pyo3(signature = (matched_stage_name, unmatched_stage_name, query, frame_ids, no_gil = true))]
+    fn move_matching_gil(
+        &self,
+        matched_stage_name: &str,
+        unmatched_stage_name: &str,
+        query: &MatchQuery,
+        frame_ids: Vec<i64>,
+        no_gil: bool,
+    ) -> PyResult<(Vec<i64>, Vec<i64>)> {
+        release_gil!(no_gil, || {
+            self.0
+                .move_matching(
+                    matched_stage_name,
+                    unmatched_stage_name,
+                    &query.0,
+                    frame_ids,
+                )
+                .map_err(|e| PyValueError::new_err(e.to_string()))
+        })
+    }
+
+    /// Moves a frame or a batch along an edge of its stage, see
+    /// :py:attr:`VideoPipelineConfiguration.edges`. The frames moving to a stage with
+    /// batches are packed into a new batch, the batches moving to a stage with independent
+    /// frames are unpacked.
+    ///
+    /// GIL management: the function is GIL-free.
+    ///
+    /// Parameters
+    /// ----------
+    /// id : int
+    ///   The id of the frame or the batch.
+    /// edge_name : str
+    ///   The name of the edge.
+    ///
+    /// Returns
+    /// -------
+    /// List[int]
+    ///   The ids of the payloads in the destination stage.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the payload does not exist or its stage has no such edge.
+    ///
+    fn route(&self, id: i64, edge_name: &str) -> PyResult<Vec<i64>> {
+        release_gil!(true, || self.0.route(id, edge_name))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Retrieves the edges of a stage.
+    ///
+    /// Parameters
+    /// ----------
+    /// stage_name : str
+    ///   The name of the stage.
+    ///
+    /// Returns
+    /// -------
+    /// List[Tuple[str, str]]
+    ///   The edge names with their destination stages.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the stage does not exist.
+    ///
+    fn get_edges(&self, stage_name: &str) -> PyResult<Vec<(String, String)>> {
+        self.0
+            .get_edges(stage_name)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Evaluates the query on the objects of the frames in the pipeline without moving them,
+    /// e.g. to check whether any frame in flight carries a label. The scans are limited to
+    /// one per :py:attr:`VideoPipelineConfiguration.frame_scan_interval`.
+    ///
+    /// Parameters
+    /// ----------
+    /// query : :py:class:`savant_rs.match_query.MatchQuery`
+    ///   The query.
+    /// max_frames : int
+    ///   The most frames checked, the ones with the lowest ids are checked first.
+    /// no_gil : bool
+    ///   Whether to release the GIL.
+    ///
+    /// Returns
+    /// -------
+    /// List[Tuple[int, :py:class:`savant_rs.primitives.VideoObjectsView`]]
+    ///   The ids of the frames having matching objects with the objects.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the frames were scanned too recently.
+    ///
+    #[pyo3(name = "scan_frames")]
+    #[pyo3(signature = (query, max_frames = 1000, no_gil = true))]
+    fn scan_frames_gil(
+        &self,
+        query: &MatchQuery,
+        max_frames: usize,
+        no_gil: bool,
+    ) -> PyResult<Vec<(i64, VideoObjectsView)>> {
+        release_gil!(no_gil, || {
+            self.0
+                .scan_frames(&query.0, max_frames)
+                .map(|found| {
+                    found
+                        .into_iter()
+                        .map(|(id, objects)| (id, VideoObjectsView::from(objects)))
+                        .collect()
+                })
+                .map_err(|e| PyValueError::new_err(e.to_string()))
+        })
+    }
+
     #[pyo3(name = "access_objects")]
     #[pyo3(signature = (frame_id, query, no_gil = true))]
     pub fn access_objects_gil(
@@ -885,3 +3258,225 @@ impl Pipeline {
         })
     }
 }
+
+/// Aligns frames of several streams (e.g. visual and thermal cameras) by timestamp and packs
+/// the aligned frames into batches of a batch stage.
+///
+/// Parameters
+/// ----------
+/// pipeline : VideoPipeline
+///   The pipeline holding the frames.
+/// dest_stage_name : str
+///   The batch stage receiving the aligned bundles.
+/// streams : List[str]
+///   The source ids of the streams to align.
+/// tolerance_ns : int
+///   The maximum difference between the timestamps of the frames of one bundle.
+/// max_pending : int
+///   The maximum number of frames waiting for a match per stream.
+///
+/// Raises
+/// ------
+/// ValueError
+///   If the destination stage is not a batch stage or less than two streams are given.
+///
+#[pyclass]
+#[derive(Debug)]
+pub struct StreamSynchronizer(rust::StreamSynchronizer);
+
+#[pymethods]
+impl StreamSynchronizer {
+    #[new]
+    #[pyo3(signature = (pipeline, dest_stage_name, streams, tolerance_ns = 20_000_000, max_pending = 64))]
+    fn new(
+        pipeline: &Pipeline,
+        dest_stage_name: &str,
+        streams: Vec<String>,
+        tolerance_ns: i64,
+        max_pending: usize,
+    ) -> PyResult<Self> {
+        let configuration = rust::SynchronizerConfigurationBuilder::default()
+            .streams(streams)
+            .tolerance_ns(tolerance_ns)
+            .max_pending(max_pending)
+            .build()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        rust::StreamSynchronizer::new(pipeline.0.clone(), dest_stage_name, configuration)
+            .map(Self)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Registers a frame already added to the pipeline.
+    ///
+    /// GIL management: the function is GIL-free.
+    ///
+    /// Returns
+    /// -------
+    /// Tuple[List[int], List[int]]
+    ///   The ids of the packed batches and the ids of the dropped frames.
+    ///
+    #[pyo3(signature = (frame_id, no_gil = true))]
+    fn push(&self, frame_id: i64, no_gil: bool) -> PyResult<(Vec<i64>, Vec<i64>)> {
+        release_gil!(no_gil, || {
+            self.0
+                .push(frame_id)
+                .map(|o| (o.batches, o.dropped))
+                .map_err(|e| PyValueError::new_err(e.to_string()))
+        })
+    }
+
+    /// Removes all frames waiting for a match from the pipeline.
+    ///
+    /// Returns
+    /// -------
+    /// List[int]
+    ///   The ids of the removed frames.
+    ///
+    fn flush(&self) -> PyResult<Vec<i64>> {
+        self.0
+            .flush()
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn pending_len(&self, stream: &str) -> usize {
+        self.0.get_pending_len(stream)
+    }
+}
+
+/// Keeps the contracts of the pipeline modules: the producers declare the objects and the
+/// attributes they emit, the consumers declare what they require. The requirements are
+/// validated against the declarations and against real frames, e.g. replayed fixtures.
+///
+/// The contracts are YAML or JSON documents:
+///
+/// .. code-block:: yaml
+///
+///   module: classifier
+///   objects:
+///     - namespace: detector
+///       label: person
+///   attributes:
+///     - object: {namespace: detector, label: person}
+///       namespace: classifier
+///       name: age
+///       kind: integer
+///     - namespace: tracker
+///       name: speed
+///       kind: float
+///       unit: km/h
+///
+#[pyclass]
+#[derive(Debug, Clone, Default)]
+pub struct ContractRegistry(RustContractRegistry);
+
+fn parse_contract(contract: &str) -> PyResult<ModuleContract> {
+    ModuleContract::from_yaml(contract).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+fn violation_messages(violations: Vec<ContractViolation>) -> Vec<String> {
+    violations.iter().map(|v| v.to_string()).collect()
+}
+
+#[pymethods]
+impl ContractRegistry {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers what the module emits, replacing the previous contract of the module.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the contract is malformed.
+    ///
+    fn register_producer(&mut self, contract: &str) -> PyResult<()> {
+        self.0.register_producer(parse_contract(contract)?);
+        Ok(())
+    }
+
+    /// Registers what the module requires, replacing the previous contract of the module.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the contract is malformed.
+    ///
+    fn register_consumer(&mut self, contract: &str) -> PyResult<()> {
+        self.0.register_consumer(parse_contract(contract)?);
+        Ok(())
+    }
+
+    /// Validates the requirements of the consumer against the registered producers.
+    ///
+    /// Returns
+    /// -------
+    /// List[str]
+    ///   The violations, empty when the consumer is compatible.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the consumer is not registered.
+    ///
+    fn check(&self, consumer: &str) -> PyResult<Vec<String>> {
+        self.0
+            .check(consumer)
+            .map(violation_messages)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Validates the requirements of all registered consumers.
+    ///
+    /// Returns
+    /// -------
+    /// List[str]
+    ///   The violations.
+    ///
+    fn check_all(&self) -> Vec<String> {
+        violation_messages(self.0.check_all())
+    }
+
+    /// Checks the frame against the requirements of the consumer.
+    ///
+    /// Returns
+    /// -------
+    /// List[str]
+    ///   The violations.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the consumer is not registered.
+    ///
+    fn check_frame(&self, consumer: &str, frame: &VideoFrame) -> PyResult<Vec<String>> {
+        let contract = self.0.get_consumer(consumer).ok_or_else(|| {
+            PyValueError::new_err(format!("Consumer {} is not registered", consumer))
+        })?;
+        Ok(violation_messages(contract.check_frame(&frame.0)))
+    }
+
+    /// Checks the frames of the fixture captured by a debug tap against the requirements of
+    /// the consumer.
+    ///
+    /// Returns
+    /// -------
+    /// List[str]
+    ///   The violations.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the consumer is not registered or the fixture cannot be loaded.
+    ///
+    fn check_fixture(&self, consumer: &str, path: &str) -> PyResult<Vec<String>> {
+        let contract = self.0.get_consumer(consumer).ok_or_else(|| {
+            PyValueError::new_err(format!("Consumer {} is not registered", consumer))
+        })?;
+        contract
+            .check_fixture(path)
+            .map(violation_messages)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+}
@@ -1,6 +1,7 @@
 /// Attribute module specifies attribute code for [crate::primitives::BorrowedVideoObject] and [crate::primitives::VideoFrame].
 ///
 pub mod attribute;
+pub mod attribute_propagation;
 pub mod attribute_value;
 pub mod batch;
 /// Here are decleared bounding boxes
@@ -8,9 +9,13 @@ pub mod batch;
 pub mod bbox;
 pub mod eos;
 pub mod frame;
+pub mod frame_delta;
 pub mod frame_update;
+/// WGS84 positions and local east-north-up coordinates.
+pub mod geo;
 pub mod message;
 pub mod object;
+pub mod object_fusion;
 pub mod objects_view;
 /// Simple point structure.
 pub mod point;
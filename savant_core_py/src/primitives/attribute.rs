@@ -1,15 +1,18 @@
 use crate::primitives::attribute_value::AttributeValue;
 use crate::primitives::attribute_value::AttributeValuesView;
-use pyo3::exceptions::PyValueError;
-use pyo3::{pyclass, pymethods, Py, PyAny, PyResult};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::types::{PyBytes, PyBytesMethods};
+use pyo3::{pyclass, pymethods, Bound, Py, PyAny, PyResult};
 use savant_core::json_api::ToSerdeJsonValue;
 use savant_core::primitives::rust;
+use savant_core::protobuf::{from_pb, ToProtobuf};
 use std::mem;
-use std::sync::Arc;
+use std::time::Duration;
 
 /// Attribute represents a specific knowledge about certain entity. The attribute is identified by ``(creator, label)`` pair which is unique within the entity.
 /// The attribute value is a list of values, each of which has a confidence score. The attribute may include additional information in the form of a hint.
 /// There are two kinds of attributes: persistent and non-persistent. Persistent attributes are serialized, while non-persistent are not.
+/// An attribute may expire: after the expiry it is invisible to the accessors and is not serialized.
 ///
 /// The list nature of attribute values is used to represent complex values of the same attribute.
 /// For example, the attribute ``(person_profiler, bio)`` may include values in the form ``["Age", 32, "Gender", None, "Height", 186]``. Each element of the
@@ -23,7 +26,7 @@ impl Attribute {
     pub fn values(&mut self, vals: Vec<AttributeValue>) -> &mut Self {
         let vals =
             unsafe { mem::transmute::<Vec<AttributeValue>, Vec<rust::AttributeValue>>(vals) };
-        self.0.values = Arc::new(vals);
+        self.0.values = vals.into();
         self
     }
 }
@@ -39,6 +42,21 @@ impl Attribute {
     #[classattr]
     const __hash__: Option<Py<PyAny>> = None;
 
+    /// Attributes have value semantics: two attributes are equal if their names, values, hints
+    /// and flags are equal. The attributes are mutable, so they are not hashable.
+    ///
+    fn __eq__(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    fn __deepcopy__(&self, _memo: &Bound<'_, PyAny>) -> Self {
+        self.clone()
+    }
+
     fn __repr__(&self) -> String {
         format!("{:?}", &self.0)
     }
@@ -230,9 +248,7 @@ impl Attribute {
     #[getter]
     pub fn get_values(&self) -> Vec<AttributeValue> {
         unsafe {
-            mem::transmute::<Vec<rust::AttributeValue>, Vec<AttributeValue>>(
-                (*self.0.values).clone(),
-            )
+            mem::transmute::<Vec<rust::AttributeValue>, Vec<AttributeValue>>(self.0.values.to_vec())
         }
     }
 
@@ -279,9 +295,55 @@ impl Attribute {
     ///
     #[setter]
     pub fn set_values(&mut self, values: Vec<AttributeValue>) {
-        self.0.values = Arc::new(unsafe {
-            mem::transmute::<Vec<AttributeValue>, Vec<rust::AttributeValue>>(values)
-        });
+        self.0.values =
+            unsafe { mem::transmute::<Vec<AttributeValue>, Vec<rust::AttributeValue>>(values) }
+                .into();
+    }
+
+    /// Returns the expiry of the attribute.
+    ///
+    /// Returns
+    /// -------
+    /// int or None
+    ///   The expiry in milliseconds since the UNIX epoch or ``None`` if the attribute does not expire.
+    ///
+    #[getter]
+    pub fn get_expires_at(&self) -> Option<u64> {
+        self.0.get_expires_at()
+    }
+
+    /// Sets the absolute expiry of the attribute.
+    ///
+    /// Parameters
+    /// ----------
+    /// expires_at : int or None
+    ///   The expiry in milliseconds since the UNIX epoch or ``None`` to never expire.
+    ///
+    #[setter]
+    pub fn set_expires_at(&mut self, expires_at: Option<u64>) {
+        self.0.set_expires_at(expires_at);
+    }
+
+    /// Sets the expiry of the attribute relative to the current time.
+    ///
+    /// Parameters
+    /// ----------
+    /// ttl_ms : int
+    ///   The time the attribute stays alive in milliseconds.
+    ///
+    pub fn set_ttl(&mut self, ttl_ms: u64) {
+        self.0.set_ttl(Duration::from_millis(ttl_ms));
+    }
+
+    /// Returns ``True`` if the attribute has expired.
+    ///
+    /// Returns
+    /// -------
+    /// bool
+    ///   ``True`` if the attribute has expired, ``False`` otherwise.
+    ///
+    pub fn is_expired(&self) -> bool {
+        self.0.is_expired()
     }
 
     #[getter]
@@ -299,4 +361,29 @@ impl Attribute {
             rust::Attribute::from_json(json).map_err(|e| PyValueError::new_err(e.to_string()))?;
         Ok(Self(res))
     }
+
+    /// Returns the protobuf-encoded state of the attribute, used by :mod:`pickle`.
+    ///
+    pub fn __getstate__(&self) -> PyResult<Vec<u8>> {
+        self.0.to_pb().map_err(|e| {
+            PyRuntimeError::new_err(format!("Failed to serialize attribute to protobuf: {}", e))
+        })
+    }
+
+    /// Restores the attribute from the protobuf-encoded state, used by :mod:`pickle`.
+    ///
+    pub fn __setstate__(&mut self, state: &Bound<'_, PyBytes>) -> PyResult<()> {
+        self.0 = from_pb::<savant_core::protobuf::Attribute, rust::Attribute>(state.as_bytes())
+            .map_err(|e| {
+                PyRuntimeError::new_err(format!(
+                    "Failed to deserialize attribute from protobuf: {}",
+                    e
+                ))
+            })?;
+        Ok(())
+    }
+
+    pub fn __getnewargs__(&self) -> (String, String, Vec<AttributeValue>) {
+        (String::new(), String::new(), vec![])
+    }
 }
@@ -0,0 +1,117 @@
+use crate::primitives::attribute::Attribute;
+use pyo3::{pyclass, pymethods, Py, PyAny};
+use savant_core::primitives::attribute_propagation as rust;
+
+/// How a frame attribute is passed to the derived objects and events: ``Copy`` copies the
+/// values, ``Reference`` passes a single string value, the uuid of the frame holding them,
+/// see :py:meth:`PropagationRule.frame_reference`.
+///
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PropagationMode {
+    Copy,
+    Reference,
+}
+
+impl From<PropagationMode> for rust::PropagationMode {
+    fn from(value: PropagationMode) -> Self {
+        match value {
+            PropagationMode::Copy => rust::PropagationMode::Copy,
+            PropagationMode::Reference => rust::PropagationMode::Reference,
+        }
+    }
+}
+
+impl From<rust::PropagationMode> for PropagationMode {
+    fn from(value: rust::PropagationMode) -> Self {
+        match value {
+            rust::PropagationMode::Copy => PropagationMode::Copy,
+            rust::PropagationMode::Reference => PropagationMode::Reference,
+        }
+    }
+}
+
+/// Selects the frame attributes passed to the objects created in the frame and to the
+/// events created with :py:meth:`savant_rs.primitives.VideoFrame.create_user_data`.
+///
+/// Parameters
+/// ----------
+/// namespace : str
+/// name : Optional[str]
+///   The name of the attribute, all the attributes of the namespace when ``None``
+/// mode : PropagationMode
+/// to_objects : bool
+/// to_events : bool
+///
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct PropagationRule(pub(crate) rust::PropagationRule);
+
+#[pymethods]
+impl PropagationRule {
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+
+    #[new]
+    #[pyo3(signature = (namespace, name=None, mode=PropagationMode::Copy, to_objects=true, to_events=true))]
+    fn new(
+        namespace: &str,
+        name: Option<&str>,
+        mode: PropagationMode,
+        to_objects: bool,
+        to_events: bool,
+    ) -> Self {
+        let mut rule = rust::PropagationRule::new(namespace, name, mode.into());
+        rule.to_objects = to_objects;
+        rule.to_events = to_events;
+        Self(rule)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", &self.0)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[getter]
+    fn namespace(&self) -> String {
+        self.0.namespace.clone()
+    }
+
+    #[getter]
+    fn name(&self) -> Option<String> {
+        self.0.name.clone()
+    }
+
+    #[getter]
+    fn mode(&self) -> PropagationMode {
+        self.0.mode.into()
+    }
+
+    #[getter]
+    fn to_objects(&self) -> bool {
+        self.0.to_objects
+    }
+
+    #[getter]
+    fn to_events(&self) -> bool {
+        self.0.to_events
+    }
+    /// Returns the uuid of the frame holding the values when the attribute is passed by
+    /// reference, ``None`` for the attributes holding their values.
+    ///
+    /// Parameters
+    /// ----------
+    /// attribute : savant_rs.primitives.Attribute
+    ///
+    /// Returns
+    /// -------
+    /// Optional[str]
+    ///
+    #[staticmethod]
+    fn frame_reference(attribute: &Attribute) -> Option<String> {
+        rust::frame_reference(&attribute.0).map(String::from)
+    }
+}
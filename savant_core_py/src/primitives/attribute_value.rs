@@ -7,10 +7,10 @@ use pyo3::{pyclass, pymethods, Bound, Py, PyAny, PyObject, PyResult};
 use savant_core::primitives::any_object::AnyObject;
 use savant_core::primitives::attribute_value::AttributeValueVariant;
 use savant_core::primitives::rust;
+use savant_core::primitives::unit::Unit;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::mem;
-use std::sync::Arc;
 
 #[pyclass]
 #[derive(Debug, Clone)]
@@ -50,6 +50,55 @@ impl AttributeValue {
         self.0.confidence = confidence;
     }
 
+    /// The unit of a numeric value, e.g. ``km/h``.
+    ///
+    #[getter]
+    fn get_unit(&self) -> Option<String> {
+        self.0.get_unit().map(|u| u.to_string())
+    }
+
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the unit is unknown or the value is not numeric.
+    ///
+    #[setter]
+    fn set_unit(&mut self, unit: Option<&str>) -> PyResult<()> {
+        let unit = unit
+            .map(|u| u.parse::<Unit>())
+            .transpose()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.0
+            .set_unit(unit)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Converts the value to the unit, the integers become floats unless the unit is the
+    /// same.
+    ///
+    /// Parameters
+    /// ----------
+    /// unit : str
+    ///   The target unit, e.g. ``m/s``.
+    ///
+    /// Returns
+    /// -------
+    /// :class:`AttributeValue`
+    ///   The converted value.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the value has no unit, the units measure different quantities or the value is
+    ///   not numeric.
+    ///
+    fn convert_to(&self, unit: &str) -> PyResult<Self> {
+        unit.parse::<Unit>()
+            .and_then(|unit| self.0.convert_to(unit))
+            .map(Self)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
     /// Returns the confidence of the attribute value.
     ///
     /// Returns
@@ -98,10 +147,10 @@ impl AttributeValue {
     #[staticmethod]
     #[pyo3(signature = (int, confidence = None))]
     pub fn intersection(int: Intersection, confidence: Option<f32>) -> Self {
-        Self(rust::AttributeValue {
+        Self(rust::AttributeValue::new(
+            AttributeValueVariant::Intersection(int.0),
             confidence,
-            value: AttributeValueVariant::Intersection(int.0),
-        })
+        ))
     }
 
     /// Creates a new attribute value of type None
@@ -113,19 +162,16 @@ impl AttributeValue {
     ///
     #[staticmethod]
     pub fn none() -> Self {
-        Self(rust::AttributeValue {
-            confidence: None,
-            value: AttributeValueVariant::None,
-        })
+        Self(rust::AttributeValue::new(AttributeValueVariant::None, None))
     }
 
     #[staticmethod]
     #[pyo3(signature = (pyobj, confidence = None))]
     pub fn temporary_python_object(pyobj: PyObject, confidence: Option<f32>) -> Self {
-        Self(rust::AttributeValue {
+        Self(rust::AttributeValue::new(
+            AttributeValueVariant::TemporaryValue(AnyObject::new(Box::new(pyobj))),
             confidence,
-            value: AttributeValueVariant::TemporaryValue(AnyObject::new(Box::new(pyobj))),
-        })
+        ))
     }
 
     /// Creates a new attribute value of blob type.
@@ -146,10 +192,10 @@ impl AttributeValue {
     #[staticmethod]
     #[pyo3(signature = (dims, blob, confidence = None))]
     pub fn bytes_from_list(dims: Vec<i64>, blob: Vec<u8>, confidence: Option<f32>) -> Self {
-        Self(rust::AttributeValue {
+        Self(rust::AttributeValue::new(
+            AttributeValueVariant::Bytes(dims, blob),
             confidence,
-            value: AttributeValueVariant::Bytes(dims, blob),
-        })
+        ))
     }
 
     /// Creates a new attribute value of blob type.
@@ -170,10 +216,10 @@ impl AttributeValue {
     #[staticmethod]
     #[pyo3(signature = (dims, blob, confidence = None))]
     pub fn bytes(dims: Vec<i64>, blob: &Bound<'_, PyBytes>, confidence: Option<f32>) -> Self {
-        Self(rust::AttributeValue {
+        Self(rust::AttributeValue::new(
+            AttributeValueVariant::Bytes(dims, blob.as_bytes().to_vec()),
             confidence,
-            value: AttributeValueVariant::Bytes(dims, blob.as_bytes().to_vec()),
-        })
+        ))
     }
 
     /// Creates a new attribute value of string type.
@@ -193,10 +239,10 @@ impl AttributeValue {
     #[staticmethod]
     #[pyo3(signature = (s, confidence = None))]
     pub fn string(s: String, confidence: Option<f32>) -> Self {
-        Self(rust::AttributeValue {
+        Self(rust::AttributeValue::new(
+            AttributeValueVariant::String(s),
             confidence,
-            value: AttributeValueVariant::String(s),
-        })
+        ))
     }
 
     /// Creates a new attribute value of list of strings type.
@@ -216,10 +262,10 @@ impl AttributeValue {
     #[staticmethod]
     #[pyo3(signature = (ss, confidence = None))]
     pub fn strings(ss: Vec<String>, confidence: Option<f32>) -> Self {
-        Self(rust::AttributeValue {
+        Self(rust::AttributeValue::new(
+            AttributeValueVariant::StringVector(ss),
             confidence,
-            value: AttributeValueVariant::StringVector(ss),
-        })
+        ))
     }
 
     /// Creates a new attribute value of integer type.
@@ -239,10 +285,10 @@ impl AttributeValue {
     #[staticmethod]
     #[pyo3(signature = (i, confidence = None))]
     pub fn integer(i: i64, confidence: Option<f32>) -> Self {
-        Self(rust::AttributeValue {
+        Self(rust::AttributeValue::new(
+            AttributeValueVariant::Integer(i),
             confidence,
-            value: AttributeValueVariant::Integer(i),
-        })
+        ))
     }
 
     /// Creates a new attribute value of list of integers type.
@@ -262,10 +308,10 @@ impl AttributeValue {
     #[staticmethod]
     #[pyo3(signature = (ii, confidence = None))]
     pub fn integers(ii: Vec<i64>, confidence: Option<f32>) -> Self {
-        Self(rust::AttributeValue {
+        Self(rust::AttributeValue::new(
+            AttributeValueVariant::IntegerVector(ii),
             confidence,
-            value: AttributeValueVariant::IntegerVector(ii),
-        })
+        ))
     }
 
     /// Creates a new attribute value of float type.
@@ -285,10 +331,10 @@ impl AttributeValue {
     #[staticmethod]
     #[pyo3(signature = (f, confidence = None))]
     pub fn float(f: f64, confidence: Option<f32>) -> Self {
-        Self(rust::AttributeValue {
+        Self(rust::AttributeValue::new(
+            AttributeValueVariant::Float(f),
             confidence,
-            value: AttributeValueVariant::Float(f),
-        })
+        ))
     }
 
     /// Creates a new attribute value of list of floats type.
@@ -308,10 +354,10 @@ impl AttributeValue {
     #[staticmethod]
     #[pyo3(signature = (ff, confidence = None))]
     pub fn floats(ff: Vec<f64>, confidence: Option<f32>) -> Self {
-        Self(rust::AttributeValue {
+        Self(rust::AttributeValue::new(
+            AttributeValueVariant::FloatVector(ff),
             confidence,
-            value: AttributeValueVariant::FloatVector(ff),
-        })
+        ))
     }
 
     /// Creates a new attribute value of boolean type.
@@ -331,10 +377,10 @@ impl AttributeValue {
     #[staticmethod]
     #[pyo3(signature = (b, confidence = None))]
     pub fn boolean(b: bool, confidence: Option<f32>) -> Self {
-        Self(rust::AttributeValue {
+        Self(rust::AttributeValue::new(
+            AttributeValueVariant::Boolean(b),
             confidence,
-            value: AttributeValueVariant::Boolean(b),
-        })
+        ))
     }
 
     /// Creates a new attribute value of list of booleans type.
@@ -354,10 +400,10 @@ impl AttributeValue {
     #[staticmethod]
     #[pyo3(signature = (bb, confidence = None))]
     pub fn booleans(bb: Vec<bool>, confidence: Option<f32>) -> Self {
-        Self(rust::AttributeValue {
+        Self(rust::AttributeValue::new(
+            AttributeValueVariant::BooleanVector(bb),
             confidence,
-            value: AttributeValueVariant::BooleanVector(bb),
-        })
+        ))
     }
 
     /// Creates a new attribute value of bounding box type.
@@ -377,10 +423,10 @@ impl AttributeValue {
     #[staticmethod]
     #[pyo3(signature = (bbox, confidence = None))]
     pub fn bbox(bbox: RBBox, confidence: Option<f32>) -> Self {
-        Self(rust::AttributeValue {
+        Self(rust::AttributeValue::new(
+            AttributeValueVariant::BBox(bbox.0.into()),
             confidence,
-            value: AttributeValueVariant::BBox(bbox.0.into()),
-        })
+        ))
     }
 
     /// Creates a new attribute value of list of bounding boxes type.
@@ -399,12 +445,10 @@ impl AttributeValue {
     #[staticmethod]
     #[pyo3(signature = (bboxes, confidence = None))]
     pub fn bboxes(bboxes: Vec<RBBox>, confidence: Option<f32>) -> Self {
-        Self(rust::AttributeValue {
+        Self(rust::AttributeValue::new(
+            AttributeValueVariant::BBoxVector(bboxes.into_iter().map(|b| b.0.into()).collect()),
             confidence,
-            value: AttributeValueVariant::BBoxVector(
-                bboxes.into_iter().map(|b| b.0.into()).collect(),
-            ),
-        })
+        ))
     }
 
     /// Creates a new attribute value of point type.
@@ -424,10 +468,10 @@ impl AttributeValue {
     #[staticmethod]
     #[pyo3(signature = (point, confidence = None))]
     pub fn point(point: Point, confidence: Option<f32>) -> Self {
-        Self(rust::AttributeValue {
+        Self(rust::AttributeValue::new(
+            AttributeValueVariant::Point(point.0),
             confidence,
-            value: AttributeValueVariant::Point(point.0),
-        })
+        ))
     }
 
     /// Creates a new attribute value of list of points type.
@@ -447,12 +491,12 @@ impl AttributeValue {
     #[staticmethod]
     #[pyo3(signature = (points, confidence = None))]
     pub fn points(points: Vec<Point>, confidence: Option<f32>) -> Self {
-        Self(rust::AttributeValue {
-            confidence,
-            value: AttributeValueVariant::PointVector(unsafe {
+        Self(rust::AttributeValue::new(
+            AttributeValueVariant::PointVector(unsafe {
                 mem::transmute::<Vec<Point>, Vec<rust::Point>>(points)
             }),
-        })
+            confidence,
+        ))
     }
 
     /// Creates a new attribute value of polygon type.
@@ -472,10 +516,10 @@ impl AttributeValue {
     #[staticmethod]
     #[pyo3(signature = (polygon, confidence = None))]
     pub fn polygon(polygon: PolygonalArea, confidence: Option<f32>) -> Self {
-        Self(rust::AttributeValue {
+        Self(rust::AttributeValue::new(
+            AttributeValueVariant::Polygon(polygon.0),
             confidence,
-            value: AttributeValueVariant::Polygon(polygon.0),
-        })
+        ))
     }
 
     /// Creates a new attribute value of list of polygons type.
@@ -495,12 +539,12 @@ impl AttributeValue {
     #[staticmethod]
     #[pyo3(signature = (polygons, confidence = None))]
     pub fn polygons(polygons: Vec<PolygonalArea>, confidence: Option<f32>) -> Self {
-        Self(rust::AttributeValue {
-            confidence,
-            value: AttributeValueVariant::PolygonVector(unsafe {
+        Self(rust::AttributeValue::new(
+            AttributeValueVariant::PolygonVector(unsafe {
                 mem::transmute::<Vec<PolygonalArea>, Vec<rust::PolygonalArea>>(polygons)
             }),
-        })
+            confidence,
+        ))
     }
 
     /// Checks if the attribute valus if of None type.
@@ -821,7 +865,7 @@ impl AttributeValueType {
 ///
 #[pyclass]
 #[derive(Debug, Clone)]
-pub struct AttributeValuesView(pub Arc<Vec<rust::AttributeValue>>);
+pub struct AttributeValuesView(pub rust::AttributeValues);
 
 #[pymethods]
 impl AttributeValuesView {
@@ -829,7 +873,7 @@ impl AttributeValuesView {
     const __hash__: Option<Py<PyAny>> = None;
 
     fn __repr__(&self) -> String {
-        format!("{:?}", self.0)
+        format!("{:?}", self.0.as_slice())
     }
 
     fn __str__(&self) -> String {
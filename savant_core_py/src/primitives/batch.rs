@@ -1,15 +1,91 @@
 use crate::match_query::MatchQuery;
+use crate::primitives::bbox::VideoObjectBBoxTransformation;
 use crate::primitives::frame::VideoFrame;
 use crate::primitives::object::BorrowedVideoObject;
 use crate::primitives::objects_view::VideoObjectsView;
 use crate::{release_gil, with_gil};
-use pyo3::exceptions::PyRuntimeError;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
 use pyo3::types::{PyBytes, PyBytesMethods};
 use pyo3::{pyclass, pymethods, Bound, PyObject, PyResult};
+use savant_core::primitives::batch_tensor::{
+    BatchTensor as RustBatchTensor, Normalization, PreprocessingSpec, ResizeMode, TensorData,
+    TensorDtype, TensorLayout,
+};
 use savant_core::primitives::rust;
 use savant_core::protobuf::{from_pb, ToProtobuf};
 use std::collections::HashMap;
 
+/// The contiguous tensor assembled from the raw content of the batch frames, the frames
+/// follow in the ascending order of their ids.
+///
+#[pyclass]
+pub struct BatchTensor(RustBatchTensor);
+
+#[pymethods]
+impl BatchTensor {
+    /// The native-endian bytes of the elements, ready for ``numpy.frombuffer``.
+    ///
+    #[getter]
+    fn data(&self) -> PyResult<PyObject> {
+        let bytes = self.0.to_bytes();
+        with_gil!(|py| Ok(PyObject::from(PyBytes::new(py, &bytes))))
+    }
+
+    /// The dimensions in the order of the layout.
+    ///
+    #[getter]
+    fn shape(&self) -> (usize, usize, usize, usize) {
+        let [a, b, c, d] = self.0.shape;
+        (a, b, c, d)
+    }
+
+    #[getter]
+    fn dtype(&self) -> &'static str {
+        match self.0.data {
+            TensorData::U8(_) => "u8",
+            TensorData::F32(_) => "f32",
+        }
+    }
+
+    #[getter]
+    fn layout(&self) -> &'static str {
+        match self.0.layout {
+            TensorLayout::Nchw => "nchw",
+            TensorLayout::Nhwc => "nhwc",
+        }
+    }
+
+    #[getter]
+    fn frame_ids(&self) -> Vec<i64> {
+        self.0.transforms.iter().map(|t| t.frame_id).collect()
+    }
+
+    /// Returns the operations mapping boxes in the tensor coordinates to the frame.
+    ///
+    /// Parameters
+    /// ----------
+    /// frame_id : int
+    ///   The id of the frame in the batch.
+    ///
+    /// Returns
+    /// -------
+    /// Optional[List[:py:class:`savant_rs.utils.VideoObjectBBoxTransformation`]]
+    ///   The operations, ``None`` if the frame is not in the tensor.
+    ///
+    fn to_frame_operations(&self, frame_id: i64) -> Option<Vec<VideoObjectBBoxTransformation>> {
+        self.0
+            .transforms
+            .iter()
+            .find(|t| t.frame_id == frame_id)
+            .map(|t| {
+                t.to_frame_operations()
+                    .into_iter()
+                    .map(VideoObjectBBoxTransformation)
+                    .collect()
+            })
+    }
+}
+
 #[pyclass]
 pub struct VideoFrameBatch(pub(crate) rust::VideoFrameBatch);
 
@@ -103,6 +179,95 @@ impl VideoFrameBatch {
         })
     }
 
+    /// Assembles the tensor of the frames: the raw content of every frame is converted to
+    /// the pixel format, resized and, for ``f32`` tensors, normalized as
+    /// ``(pixel * scale - mean) / std``.
+    ///
+    /// Parameters
+    /// ----------
+    /// width : int
+    ///   The tensor width.
+    /// height : int
+    ///   The tensor height.
+    /// pixel_format : str
+    ///   ``gray8``, ``rgb8``, ``bgr8``, ``rgba8`` or ``bgra8``.
+    /// letterbox : bool
+    ///   Keep the aspect ratio and pad the frames, otherwise stretch them.
+    /// padding : int
+    ///   The value of the padded pixels before the normalization.
+    /// layout : str
+    ///   ``nchw`` or ``nhwc``.
+    /// dtype : str
+    ///   ``u8`` or ``f32``.
+    /// scale : float
+    ///   The pixel scale.
+    /// mean : Optional[List[float]]
+    ///   The per-channel mean.
+    /// std : Optional[List[float]]
+    ///   The per-channel standard deviation.
+    /// no_gil : bool
+    ///   Whether to release the GIL while assembling the tensor.
+    ///
+    /// Returns
+    /// -------
+    /// :py:class:`BatchTensor`
+    ///   The tensor.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the parameters are invalid or a frame has no convertible raw content.
+    ///
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (width, height, pixel_format="rgb8", letterbox=true, padding=0, layout="nchw", dtype="f32", scale=1.0, mean=None, std=None, no_gil=true))]
+    fn to_tensor(
+        &self,
+        width: usize,
+        height: usize,
+        pixel_format: &str,
+        letterbox: bool,
+        padding: u8,
+        layout: &str,
+        dtype: &str,
+        scale: f32,
+        mean: Option<Vec<f32>>,
+        std: Option<Vec<f32>>,
+        no_gil: bool,
+    ) -> PyResult<BatchTensor> {
+        let spec = PreprocessingSpec {
+            format: pixel_format
+                .parse()
+                .map_err(|e: rust::RawContentError| PyValueError::new_err(e.to_string()))?,
+            resize: if letterbox {
+                ResizeMode::Letterbox
+            } else {
+                ResizeMode::Stretch
+            },
+            padding,
+            ..PreprocessingSpec::new(width, height)
+        };
+        let layout = match layout {
+            "nchw" => TensorLayout::Nchw,
+            "nhwc" => TensorLayout::Nhwc,
+            _ => return Err(PyValueError::new_err("The layout must be nchw or nhwc")),
+        };
+        let dtype = match dtype {
+            "u8" => TensorDtype::U8,
+            "f32" => TensorDtype::F32,
+            _ => return Err(PyValueError::new_err("The dtype must be u8 or f32")),
+        };
+        let normalization = Normalization {
+            scale,
+            mean: mean.unwrap_or_default(),
+            std: std.unwrap_or_default(),
+        };
+        release_gil!(no_gil, || self
+            .0
+            .to_tensor(&spec, layout, dtype, &normalization)
+            .map(BatchTensor)
+            .map_err(|e| PyValueError::new_err(e.to_string())))
+    }
+
     #[getter]
     fn ids(&self) -> Vec<i64> {
         self.0.frames().keys().copied().collect()
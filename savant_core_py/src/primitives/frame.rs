@@ -1,24 +1,32 @@
 use crate::draw_spec::SetDrawLabelKind;
 use crate::match_query::MatchQuery;
 use crate::primitives::attribute::Attribute;
+use crate::primitives::attribute_propagation::PropagationRule;
 use crate::primitives::attribute_value::AttributeValue;
 use crate::primitives::bbox::{RBBox, VideoObjectBBoxTransformation};
-use crate::primitives::frame_update::VideoFrameUpdate;
+use crate::primitives::frame_update::{AttributeUpdatePolicy, ObjectMergePolicy, VideoFrameUpdate};
+use crate::primitives::geo::GeoPosition;
 use crate::primitives::message::Message;
-use crate::primitives::object::{BorrowedVideoObject, IdCollisionResolutionPolicy, VideoObject};
+use crate::primitives::object::{
+    BorrowedVideoObject, DataClassification, IdCollisionResolutionPolicy, OrphanPolicy, VideoObject,
+};
 use crate::primitives::objects_view::VideoObjectsView;
+use crate::primitives::user_data::UserData;
 use crate::release_gil;
 use crate::with_gil;
 use pyo3::exceptions::{PyRuntimeError, PyValueError};
 use pyo3::types::{PyBytes, PyBytesMethods};
 use pyo3::{pyclass, pymethods, Bound, Py, PyAny, PyObject, PyResult};
 use savant_core::json_api::ToSerdeJsonValue;
+use savant_core::primitives::attribute_propagation::AttributePropagation;
 use savant_core::primitives::object::ObjectOperations;
 use savant_core::primitives::{rust, WithAttributes};
 use savant_core::protobuf::{from_pb, ToProtobuf};
+use savant_core::transcoding::Format;
 use serde_json::Value;
 use std::fmt::Debug;
 use std::mem;
+use std::sync::Arc;
 
 #[pyclass]
 pub struct ExternalFrame(pub(crate) rust::ExternalFrame);
@@ -100,7 +108,59 @@ impl VideoFrameContent {
     #[staticmethod]
     pub fn internal(data: &Bound<'_, PyBytes>) -> Self {
         let bytes = data.as_bytes();
-        Self(rust::VideoFrameContent::Internal(bytes.to_vec()))
+        Self(rust::VideoFrameContent::Internal(bytes.to_vec().into()))
+    }
+
+    /// Creates internal raw content with the layout of the pixels. Without the planes the
+    /// rows and the planes follow each other without padding.
+    ///
+    /// Parameters
+    /// ----------
+    /// data : bytes
+    ///   The pixels.
+    /// pixel_format : str
+    ///   ``gray8``, ``rgb8``, ``bgr8``, ``rgba8``, ``bgra8``, ``nv12`` or ``i420``.
+    /// width : int
+    ///   The width in pixels.
+    /// height : int
+    ///   The height in pixels.
+    /// planes : Optional[List[Tuple[int, int]]]
+    ///   The offset and the stride of every plane in bytes.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the pixel format is unknown or the layout does not match the format or the data.
+    ///
+    #[staticmethod]
+    #[pyo3(signature = (data, pixel_format, width, height, planes=None))]
+    pub fn internal_raw(
+        data: &Bound<'_, PyBytes>,
+        pixel_format: &str,
+        width: usize,
+        height: usize,
+        planes: Option<Vec<(usize, usize)>>,
+    ) -> PyResult<Self> {
+        let format = pixel_format
+            .parse::<rust::PixelFormat>()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let layout = match planes {
+            Some(planes) => rust::RawLayout::new(
+                format,
+                width,
+                height,
+                planes
+                    .into_iter()
+                    .map(|(offset, stride)| rust::Plane { offset, stride })
+                    .collect(),
+            )
+            .map_err(|e| PyValueError::new_err(e.to_string()))?,
+            None => rust::RawLayout::packed(format, width, height)
+                .map_err(|e| PyValueError::new_err(e.to_string()))?,
+        };
+        rust::InternalFrame::raw(data.as_bytes().to_vec(), layout)
+            .map(|content| Self(rust::VideoFrameContent::Internal(content)))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
     }
 
     #[staticmethod]
@@ -121,7 +181,7 @@ impl VideoFrameContent {
     }
 
     /// Returns the video data as a Python bytes object if the content is internal,
-    /// otherwise results in the TypeError exception.
+    /// otherwise results in the TypeError exception. Compressed content is decompressed.
     ///
     /// Returns
     /// -------
@@ -132,10 +192,15 @@ impl VideoFrameContent {
     /// ------
     /// TypeError
     ///   If the content is not internal.
+    /// ValueError
+    ///   If the compressed content fails to decompress.
     ///
     pub fn get_data(&self) -> PyResult<PyObject> {
         match &self.0 {
             rust::VideoFrameContent::Internal(data) => {
+                let data = data
+                    .get_data()
+                    .map_err(|e| PyValueError::new_err(e.to_string()))?;
                 with_gil!(|py| {
                     let bytes = PyBytes::new_with(py, data.len(), |b: &mut [u8]| {
                         b.copy_from_slice(data);
@@ -150,6 +215,99 @@ impl VideoFrameContent {
         }
     }
 
+    /// Returns the codec of compressed internal content, ``None`` for other content.
+    ///
+    /// Returns
+    /// -------
+    /// Optional[str]
+    ///   ``lz4`` or ``zstd``.
+    ///
+    pub fn get_encoding(&self) -> Option<String> {
+        match &self.0 {
+            rust::VideoFrameContent::Internal(data) => {
+                data.get_encoding().map(|e| e.name().to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the internal content compressed with the codec, the data is decompressed on
+    /// access. Raw pixels get the packed layout.
+    ///
+    /// Parameters
+    /// ----------
+    /// encoding : str
+    ///   ``lz4`` or ``zstd``.
+    ///
+    /// Raises
+    /// ------
+    /// TypeError
+    ///   If the content is not internal.
+    /// ValueError
+    ///   If the codec is unknown or the compression fails.
+    ///
+    pub fn compress(&self, encoding: &str) -> PyResult<Self> {
+        let encoding = encoding
+            .parse::<rust::ContentEncoding>()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        match &self.0 {
+            rust::VideoFrameContent::Internal(data) => data
+                .compress(encoding)
+                .map(|content| Self(rust::VideoFrameContent::Internal(content)))
+                .map_err(|e| PyValueError::new_err(e.to_string())),
+            _ => Err(pyo3::exceptions::PyTypeError::new_err(
+                "Video data is not stored internally",
+            )),
+        }
+    }
+
+    /// Returns the uncompressed internal content.
+    ///
+    /// Raises
+    /// ------
+    /// TypeError
+    ///   If the content is not internal.
+    /// ValueError
+    ///   If the compressed content fails to decompress.
+    ///
+    pub fn decompress(&self) -> PyResult<Self> {
+        match &self.0 {
+            rust::VideoFrameContent::Internal(data) => data
+                .decompress()
+                .map(|content| Self(rust::VideoFrameContent::Internal(content)))
+                .map_err(|e| PyValueError::new_err(e.to_string())),
+            _ => Err(pyo3::exceptions::PyTypeError::new_err(
+                "Video data is not stored internally",
+            )),
+        }
+    }
+
+    /// Returns the layout of internal raw content, ``None`` for other content.
+    ///
+    /// Returns
+    /// -------
+    /// Optional[Tuple[str, int, int, List[Tuple[int, int]]]]
+    ///   The pixel format, the width, the height and the offset and the stride of every
+    ///   plane.
+    ///
+    pub fn get_layout(&self) -> Option<(String, usize, usize, Vec<(usize, usize)>)> {
+        match &self.0 {
+            rust::VideoFrameContent::Internal(data) => data.get_layout().map(|layout| {
+                (
+                    layout.get_format().name().to_string(),
+                    layout.get_width(),
+                    layout.get_height(),
+                    layout
+                        .get_planes()
+                        .iter()
+                        .map(|p| (p.offset, p.stride))
+                        .collect(),
+                )
+            }),
+            _ => None,
+        }
+    }
+
     /// Returns the method for external video data if the content is external,
     /// otherwise results in the TypeError exception.
     ///
@@ -231,6 +389,66 @@ impl ToSerdeJsonValue for VideoFrameTranscodingMethod {
     }
 }
 
+/// An additional picture of the frame (a half resolution raw copy, a thumbnail, etc.)
+/// carried next to the primary content under a representation key.
+///
+/// Parameters
+/// ----------
+/// content : VideoFrameContent
+///   The content of the representation.
+/// width : int
+///   The width of the representation.
+/// height : int
+///   The height of the representation.
+/// codec : Optional[str]
+///   The codec of the representation.
+///
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct ContentRepresentation(rust::ContentRepresentation);
+
+#[pymethods]
+impl ContentRepresentation {
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", &self.0)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[new]
+    #[pyo3(signature = (content, width, height, codec=None))]
+    pub fn new(content: VideoFrameContent, width: i64, height: i64, codec: Option<String>) -> Self {
+        Self(rust::ContentRepresentation::new(
+            content.0, width, height, codec,
+        ))
+    }
+
+    #[getter]
+    pub fn get_content(&self) -> VideoFrameContent {
+        VideoFrameContent(self.0.content.as_ref().clone())
+    }
+
+    #[getter]
+    pub fn get_width(&self) -> i64 {
+        self.0.width
+    }
+
+    #[getter]
+    pub fn get_height(&self) -> i64 {
+        self.0.height
+    }
+
+    #[getter]
+    pub fn get_codec(&self) -> Option<String> {
+        self.0.codec.clone()
+    }
+}
+
 /// Represents the structure for accessing/defining video frame transformation information.
 ///
 #[pyclass]
@@ -479,8 +697,27 @@ impl VideoFrame {
         self.0.memory_handle()
     }
 
+    /// Frames have identity semantics: two Python objects are equal if they refer to the
+    /// same underlying frame, the hash is consistent with the equality.
+    ///
     fn __hash__(&self) -> usize {
-        self.memory_handle()
+        self.0.shared_handle()
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.0.is_same_frame(&other.0)
+    }
+
+    /// Returns a new reference to the same underlying frame.
+    ///
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    /// Returns an independent copy of the frame, the same as :py:meth:`VideoFrame.copy`.
+    ///
+    fn __deepcopy__(&self, _memo: &Bound<'_, PyAny>) -> Self {
+        self.copy_gil(true)
     }
 
     fn __repr__(&self) -> String {
@@ -593,6 +830,18 @@ impl VideoFrame {
         self.0.get_uuid_as_string()
     }
 
+    /// The creation time in milliseconds since the UNIX epoch carried by the uuid, ``None``
+    /// when the frame uuid is not a version 7 uuid.
+    ///
+    /// Returns
+    /// -------
+    /// Optional[int]
+    ///
+    #[getter]
+    pub fn get_uuid_timestamp_ms(&self) -> Option<u64> {
+        self.0.get_uuid_timestamp_ms()
+    }
+
     #[getter]
     pub fn get_creation_timestamp_ns(&self) -> u128 {
         self.0.get_creation_timestamp_ns()
@@ -683,6 +932,129 @@ impl VideoFrame {
         self.0.set_keyframe(keyframe)
     }
 
+    /// The frame does not need inference and is only passed through.
+    ///
+    #[getter]
+    pub fn get_skip_inference(&self) -> bool {
+        self.0.get_processing_hints().skip_inference
+    }
+
+    #[setter]
+    pub fn set_skip_inference(&mut self, skip_inference: bool) {
+        let mut hints = self.0.get_processing_hints();
+        hints.skip_inference = skip_inference;
+        self.0.set_processing_hints(hints);
+    }
+
+    /// The regions which need inference, the whole frame when empty.
+    ///
+    #[getter]
+    pub fn get_roi_list(&self) -> Vec<RBBox> {
+        self.0
+            .get_processing_hints()
+            .roi_list
+            .into_iter()
+            .map(RBBox)
+            .collect()
+    }
+
+    #[setter]
+    pub fn set_roi_list(&mut self, roi_list: Vec<RBBox>) {
+        let mut hints = self.0.get_processing_hints();
+        hints.roi_list = roi_list.into_iter().map(|b| b.0).collect();
+        self.0.set_processing_hints(hints);
+    }
+
+    /// The allowed degradation of processing, 0 is the full quality.
+    ///
+    #[getter]
+    pub fn get_degrade_level(&self) -> u8 {
+        self.0.get_processing_hints().degrade_level
+    }
+
+    #[setter]
+    pub fn set_degrade_level(&mut self, degrade_level: u8) {
+        let mut hints = self.0.get_processing_hints();
+        hints.degrade_level = degrade_level;
+        self.0.set_processing_hints(hints);
+    }
+
+    /// The pose of the camera of the source, set by the pipeline from the ``geo`` key of the
+    /// source configuration when the frame has none.
+    ///
+    #[getter]
+    pub fn get_geo_pose(&self) -> Option<GeoPosition> {
+        self.0.get_geo_pose().map(GeoPosition)
+    }
+
+    #[setter]
+    pub fn set_geo_pose(&mut self, pose: Option<GeoPosition>) {
+        self.0.set_geo_pose(pose.map(|p| p.0));
+    }
+
+    /// The classification of the frame data, it applies to the content, the attributes and
+    /// the objects of the frame.
+    ///
+    #[getter]
+    pub fn get_classification(&self) -> DataClassification {
+        self.0.get_classification().into()
+    }
+
+    #[setter]
+    pub fn set_classification(&mut self, classification: DataClassification) -> PyResult<()> {
+        self.0
+            .try_set_classification(classification.into())
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// The highest classification of the frame and its objects, used to route the frame,
+    /// e.g. to a restricted storage.
+    ///
+    #[getter]
+    pub fn get_effective_classification(&self) -> DataClassification {
+        self.0.get_effective_classification().into()
+    }
+
+    /// Freezes the objects of the namespace, or all objects when the namespace is not set, after
+    /// the stage. Adding, replacing or updating frozen objects raises ``ValueError`` naming the
+    /// stage which attempted it.
+    ///
+    /// Parameters
+    /// ----------
+    /// stage : str
+    ///   The stage after which the objects are frozen.
+    /// namespace : Optional[str]
+    ///   The namespace of the frozen objects, all objects when ``None``.
+    ///
+    #[pyo3(signature = (stage, namespace=None))]
+    pub fn freeze_objects(&self, stage: &str, namespace: Option<&str>) {
+        self.0.freeze_objects(namespace, stage)
+    }
+
+    /// The frozen parts of the object set as pairs of the namespace (``None`` for all objects)
+    /// and the stage which froze them.
+    ///
+    #[getter]
+    pub fn get_frozen_objects(&self) -> Vec<(Option<String>, String)> {
+        self.0
+            .get_frozen_objects()
+            .into_iter()
+            .map(|f| (f.namespace, f.stage))
+            .collect()
+    }
+
+    /// The regions an inference stage must process: nothing when inference is skipped, the
+    /// regions of interest when they are set, otherwise the whole frame.
+    ///
+    pub fn inference_regions(&self) -> Vec<RBBox> {
+        self.0
+            .get_processing_hints()
+            .inference_regions(self.0.get_width(), self.0.get_height())
+            .into_iter()
+            .map(RBBox)
+            .collect()
+    }
+
     #[getter]
     pub fn get_content(&self) -> VideoFrameContent {
         VideoFrameContent(self.0.get_content().as_ref().clone())
@@ -711,6 +1083,149 @@ impl VideoFrame {
         self.0.get_previous_keyframe_as_string()
     }
 
+    /// Transcodes the internal content to the format with the registered transcoders and
+    /// updates the codec. Raw formats are ``raw-gray8``, ``raw-rgb8``, ``raw-rgba8`` and
+    /// ``raw-bgr8``, other codecs are encoded formats.
+    ///
+    /// Parameters
+    /// ----------
+    /// format : str
+    ///   The codec of the format.
+    /// no_gil : bool
+    ///   Whether to release the GIL while transcoding.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the content is not internal, the frame has no codec or no transcoder supports
+    ///   the conversion.
+    ///
+    #[pyo3(signature = (format, no_gil=true))]
+    fn ensure_format(&mut self, format: &str, no_gil: bool) -> PyResult<()> {
+        release_gil!(no_gil, || self
+            .0
+            .ensure_format(Format::from_codec(format))
+            .map_err(|e| PyValueError::new_err(e.to_string())))
+    }
+
+    /// Compresses the internal content with the codec, the frame keeps the compressed bytes
+    /// and decompresses them on access. Other content is left as is.
+    ///
+    /// Parameters
+    /// ----------
+    /// encoding : str
+    ///   ``lz4`` or ``zstd``.
+    /// no_gil : bool
+    ///   Whether to release the GIL while compressing.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the codec is unknown or the compression fails.
+    ///
+    #[pyo3(signature = (encoding, no_gil=true))]
+    fn compress_content(&mut self, encoding: &str, no_gil: bool) -> PyResult<()> {
+        let encoding = encoding
+            .parse::<rust::ContentEncoding>()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        release_gil!(no_gil, || self
+            .0
+            .compress_content(encoding)
+            .map_err(|e| PyValueError::new_err(e.to_string())))
+    }
+
+    /// Replaces compressed internal content with the decoded bytes.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the content fails to decompress.
+    ///
+    #[pyo3(signature = (no_gil=true))]
+    fn decompress_content(&mut self, no_gil: bool) -> PyResult<()> {
+        release_gil!(no_gil, || self
+            .0
+            .decompress_content()
+            .map_err(|e| PyValueError::new_err(e.to_string())))
+    }
+
+    /// Sets the content representation under the key, replacing the previous one.
+    ///
+    /// Parameters
+    /// ----------
+    /// key : str
+    ///   The representation key, e.g. ``thumbnail``.
+    /// representation : ContentRepresentation
+    ///   The representation.
+    ///
+    pub fn set_representation(&mut self, key: &str, representation: ContentRepresentation) {
+        self.0.set_representation(key, representation.0)
+    }
+
+    /// Returns the content representation with the key.
+    ///
+    /// Parameters
+    /// ----------
+    /// key : str
+    ///   The representation key.
+    ///
+    /// Returns
+    /// -------
+    /// Optional[ContentRepresentation]
+    ///   The representation, ``None`` when the frame has no such representation.
+    ///
+    pub fn get_representation(&self, key: &str) -> Option<ContentRepresentation> {
+        self.0.get_representation(key).map(ContentRepresentation)
+    }
+
+    /// Removes the content representation with the key.
+    ///
+    /// Parameters
+    /// ----------
+    /// key : str
+    ///   The representation key.
+    ///
+    /// Returns
+    /// -------
+    /// Optional[ContentRepresentation]
+    ///   The removed representation.
+    ///
+    pub fn delete_representation(&mut self, key: &str) -> Option<ContentRepresentation> {
+        self.0.delete_representation(key).map(ContentRepresentation)
+    }
+
+    /// Keeps only the content representations with the listed keys.
+    ///
+    /// Parameters
+    /// ----------
+    /// keys : List[str]
+    ///   The representation keys to keep.
+    ///
+    pub fn retain_representations(&mut self, keys: Vec<String>) {
+        let keys = keys.iter().map(String::as_str).collect::<Vec<_>>();
+        self.0.retain_representations(&keys)
+    }
+
+    #[getter]
+    pub fn get_representation_keys(&self) -> Vec<String> {
+        self.0.get_representation_keys()
+    }
+
+    /// Returns the configuration of the source resolved when the frame was added to the
+    /// pipeline, ``None`` when the pipeline has no per-source configuration.
+    ///
+    /// Returns
+    /// -------
+    /// Optional[str]
+    ///   The configuration as a JSON object.
+    ///
+    #[getter]
+    pub fn get_source_config(&self) -> Option<String> {
+        self.0
+            .get_source_config()
+            .map(|config| config.as_json().to_string())
+    }
+
     #[getter]
     #[pyo3(name = "json")]
     pub fn json_gil(&self) -> String {
@@ -832,6 +1347,17 @@ impl VideoFrame {
         self.0.clear_attributes()
     }
 
+    /// Removes the expired attributes.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    ///   The number of removed attributes.
+    ///
+    pub fn purge_expired_attributes(&mut self) -> usize {
+        self.0.purge_expired_attributes()
+    }
+
     pub fn delete_attributes_with_ns(&mut self, namespace: &str) {
         self.0.delete_attributes_with_ns(namespace)
     }
@@ -989,6 +1515,14 @@ impl VideoFrame {
         self.0.get_all_objects().into()
     }
 
+    /// Returns the view of all frame objects. The view supports iteration, ``len()``, indexing,
+    /// slicing and filtering with :py:meth:`VideoObjectsView.filter`.
+    ///
+    #[getter]
+    pub fn objects(&self) -> VideoObjectsView {
+        self.get_all_objects()
+    }
+
     #[pyo3(name = "access_objects")]
     #[pyo3(signature = (q, no_gil = true))]
     pub fn access_objects_gil(&self, q: &MatchQuery, no_gil: bool) -> VideoObjectsView {
@@ -1001,23 +1535,112 @@ impl VideoFrame {
         self.0.access_objects_with_id(&ids).into()
     }
 
+    /// Sets the frame attributes passed to the objects created in the frame and to the
+    /// events created with :py:meth:`create_user_data`, ``None`` disables the propagation.
+    /// The frames added to a pipeline get the rules set with
+    /// :py:meth:`savant_rs.pipeline.VideoPipeline.set_attribute_propagation`.
+    ///
+    /// Parameters
+    /// ----------
+    /// rules : Optional[List[PropagationRule]]
+    ///
+    #[pyo3(signature = (rules=None))]
+    pub fn set_attribute_propagation(&self, rules: Option<Vec<PropagationRule>>) {
+        self.0.set_attribute_propagation(rules.map(|rules| {
+            Arc::new(AttributePropagation::new(
+                rules.into_iter().map(|r| r.0).collect(),
+            ))
+        }));
+    }
+
+    /// Creates an event of the frame source carrying the frame attributes selected by the
+    /// attribute propagation rules.
+    ///
+    /// Returns
+    /// -------
+    /// UserData
+    ///
+    pub fn create_user_data(&self) -> UserData {
+        UserData(self.0.create_user_data())
+    }
+
+    /// Enables or disables the cache of the :py:meth:`access_objects` results. While enabled,
+    /// a query evaluated again against the unmodified frame returns the stored objects, any
+    /// modification of the frame, its objects or their boxes invalidates the results. The
+    /// queries with user-defined functions depending on external state must not be used
+    /// with the cache.
+    ///
+    /// Parameters
+    /// ----------
+    /// enabled : bool
+    ///   Whether the results are cached.
+    ///
+    pub fn set_query_cache(&self, enabled: bool) {
+        self.0.set_query_cache(enabled)
+    }
+
+    /// The ``(hits, misses, entries)`` of the query cache, ``None`` when the cache is
+    /// disabled.
+    ///
+    /// Returns
+    /// -------
+    /// Optional[Tuple[int, int, int]]
+    ///
+    #[getter]
+    pub fn query_cache_stats(&self) -> Option<(u64, u64, usize)> {
+        self.0
+            .get_query_cache_stats()
+            .map(|s| (s.hits, s.misses, s.entries))
+    }
+
     #[pyo3(name = "delete_objects")]
     #[pyo3(signature = (q, no_gil = true))]
-    pub fn delete_objects_gil(&self, q: &MatchQuery, no_gil: bool) -> Vec<VideoObject> {
-        release_gil!(no_gil, || self
-            .0
-            .delete_objects(&q.0)
-            .into_iter()
-            .map(VideoObject)
-            .collect())
+    pub fn delete_objects_gil(&self, q: &MatchQuery, no_gil: bool) -> PyResult<Vec<VideoObject>> {
+        release_gil!(no_gil, || self.0.try_delete_objects(&q.0))
+            .map(|objects| objects.into_iter().map(VideoObject).collect())
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Removes the objects matching the query and fixes up the parents of the retained
+    /// objects according to the policy.
+    ///
+    /// Parameters
+    /// ----------
+    /// q : :py:class:`savant_rs.match_query.MatchQuery`
+    ///   The query selecting the objects to remove.
+    /// policy : :py:class:`OrphanPolicy`
+    ///   What happens to the descendants of the removed objects.
+    /// no_gil : bool
+    ///   Whether to release the GIL.
+    ///
+    /// Returns
+    /// -------
+    /// List[:py:class:`VideoObject`]
+    ///   The removed objects.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If a removed object belongs to a frozen namespace; the frame is not changed.
+    ///
+    #[pyo3(name = "prune")]
+    #[pyo3(signature = (q, policy = OrphanPolicy::Detach, no_gil = true))]
+    pub fn prune_gil(
+        &self,
+        q: &MatchQuery,
+        policy: OrphanPolicy,
+        no_gil: bool,
+    ) -> PyResult<Vec<VideoObject>> {
+        release_gil!(no_gil, || self.0.prune(&q.0, policy.into()))
+            .map(|objects| objects.into_iter().map(VideoObject).collect())
+            .map_err(|e| PyValueError::new_err(e.to_string()))
     }
 
-    pub fn delete_objects_with_ids(&self, ids: Vec<i64>) -> Vec<VideoObject> {
+    pub fn delete_objects_with_ids(&self, ids: Vec<i64>) -> PyResult<Vec<VideoObject>> {
         self.0
-            .delete_objects_with_ids(&ids)
-            .into_iter()
-            .map(VideoObject)
-            .collect()
+            .try_delete_objects_with_ids(&ids)
+            .map(|objects| objects.into_iter().map(VideoObject).collect())
+            .map_err(|e| PyValueError::new_err(e.to_string()))
     }
 
     #[pyo3(name = "set_parent")]
@@ -1056,8 +1679,10 @@ impl VideoFrame {
         release_gil!(no_gil, || VideoObjectsView::from(self.0.clear_parent(&q.0)))
     }
 
-    pub fn clear_objects(&self) {
-        self.0.clear_objects()
+    pub fn clear_objects(&self) -> PyResult<()> {
+        self.0
+            .try_clear_objects()
+            .map_err(|e| PyValueError::new_err(e.to_string()))
     }
 
     pub fn get_children(&self, id: i64) -> VideoObjectsView {
@@ -1098,6 +1723,80 @@ impl VideoFrame {
             .map_err(|e| PyValueError::new_err(e.to_string()))
     }
 
+    /// Merges the objects and the attributes of the other frame, e.g. a copy of the frame
+    /// annotated by a parallel branch. The added objects get new ids and their parents are
+    /// remapped. The frame is not changed when the merge fails.
+    ///
+    /// Parameters
+    /// ----------
+    /// other: :py:class:`savant_rs.primitives.VideoFrame`
+    ///   The frame to merge from
+    /// object_policy: :py:class:`savant_rs.primitives.ObjectMergePolicy`
+    ///   How the objects are merged
+    /// frame_attribute_policy: :py:class:`savant_rs.primitives.AttributeUpdatePolicy`
+    ///   How the frame attributes are merged
+    /// object_attribute_policy: :py:class:`savant_rs.primitives.AttributeUpdatePolicy`
+    ///   How the attributes of the same objects are merged
+    ///
+    /// Returns
+    /// -------
+    /// Dict[int, int]
+    ///   The object ids of the other frame mapped to the ids in the frame
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the frames cannot be merged
+    ///
+    #[pyo3(name = "merge_from")]
+    #[pyo3(signature = (
+        other,
+        object_policy = ObjectMergePolicy::MergeSameObjects,
+        frame_attribute_policy = AttributeUpdatePolicy::ReplaceWithForeignWhenDuplicate,
+        object_attribute_policy = AttributeUpdatePolicy::ReplaceWithForeignWhenDuplicate,
+        no_gil = true
+    ))]
+    pub fn merge_from_gil(
+        &self,
+        other: &VideoFrame,
+        object_policy: ObjectMergePolicy,
+        frame_attribute_policy: AttributeUpdatePolicy,
+        object_attribute_policy: AttributeUpdatePolicy,
+        no_gil: bool,
+    ) -> PyResult<std::collections::HashMap<i64, i64>> {
+        let policy = rust::FrameMergePolicy {
+            object_policy: object_policy.into(),
+            frame_attribute_policy: frame_attribute_policy.into(),
+            object_attribute_policy: object_attribute_policy.into(),
+        };
+        release_gil!(no_gil, || self.0.merge_from(&other.0, &policy))
+            .map(|mapping| mapping.into_iter().collect())
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Returns the protobuf-encoded state of the video frame, used by :mod:`pickle`.
+    ///
+    fn __getstate__(&self) -> PyResult<PyObject> {
+        self.to_protobuf_gil(false)
+    }
+
+    /// Restores the video frame from the protobuf-encoded state, used by :mod:`pickle`.
+    ///
+    fn __setstate__(&mut self, state: &Bound<'_, PyBytes>) -> PyResult<()> {
+        *self = Self::from_protobuf_gil(state, false)?;
+        Ok(())
+    }
+
+    fn __getnewargs__(&self) -> (String, String, i64, i64, VideoFrameContent) {
+        (
+            String::new(),
+            String::new(),
+            0,
+            0,
+            VideoFrameContent::none(),
+        )
+    }
+
     #[pyo3(name = "to_protobuf")]
     #[pyo3(signature = (no_gil = true))]
     fn to_protobuf_gil(&self, no_gil: bool) -> PyResult<PyObject> {
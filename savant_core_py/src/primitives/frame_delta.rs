@@ -0,0 +1,147 @@
+use crate::primitives::frame::VideoFrame;
+use crate::release_gil;
+use pyo3::exceptions::PyValueError;
+use pyo3::{pyclass, pyfunction, pymethods, PyResult};
+use savant_core::primitives::frame_delta;
+use savant_core::primitives::rust;
+
+/// Checks whether the frame is a delta frame produced by :py:class:`DeltaEncoder`.
+///
+/// Parameters
+/// ----------
+/// frame: :py:class:`savant_rs.primitives.VideoFrame`
+///   The frame to check
+///
+/// Returns
+/// -------
+/// bool
+///   True if the frame carries only the changes against the previous frame
+///
+#[pyfunction]
+pub fn is_delta_frame(frame: &VideoFrame) -> bool {
+    frame_delta::is_delta_frame(&frame.0)
+}
+
+/// Encodes the frames of a source relative to the previous frame of the source. A delta frame
+/// carries only the new and changed objects and attributes, every ``keyframe_interval``-th
+/// frame is passed as is.
+///
+/// Parameters
+/// ----------
+/// keyframe_interval: int
+///   The number of frames between two full frames of a source
+///
+/// Raises
+/// ------
+/// ValueError
+///   If the interval is 0
+///
+#[pyclass]
+#[derive(Debug)]
+pub struct DeltaEncoder(rust::DeltaEncoder);
+
+#[pymethods]
+impl DeltaEncoder {
+    #[new]
+    fn new(keyframe_interval: u64) -> PyResult<Self> {
+        rust::DeltaEncoder::new(keyframe_interval)
+            .map(Self)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    #[getter]
+    fn get_keyframe_interval(&self) -> u64 {
+        self.0.get_keyframe_interval()
+    }
+
+    /// Makes the next frame of the source a keyframe.
+    ///
+    /// Parameters
+    /// ----------
+    /// source_id: str
+    ///   The source to reset
+    ///
+    fn reset_source(&self, source_id: &str) {
+        self.0.reset_source(source_id)
+    }
+
+    /// Encodes the frame.
+    ///
+    /// Parameters
+    /// ----------
+    /// frame: :py:class:`savant_rs.primitives.VideoFrame`
+    ///   The frame to encode
+    /// no_gil: bool
+    ///   Whether to release the GIL while encoding
+    ///
+    /// Returns
+    /// -------
+    /// :py:class:`savant_rs.primitives.VideoFrame`
+    ///   The frame itself for a keyframe or a new delta frame
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the frame is already delta-encoded
+    ///
+    #[pyo3(name = "encode")]
+    #[pyo3(signature = (frame, no_gil = true))]
+    fn encode_gil(&self, frame: &VideoFrame, no_gil: bool) -> PyResult<VideoFrame> {
+        release_gil!(no_gil, || self.0.encode(&frame.0))
+            .map(VideoFrame)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+}
+
+/// Restores the frames encoded by :py:class:`DeltaEncoder`, keeping the last restored frame
+/// of every source.
+///
+#[pyclass]
+#[derive(Debug, Default)]
+pub struct DeltaDecoder(rust::DeltaDecoder);
+
+#[pymethods]
+impl DeltaDecoder {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets the last frame of the source.
+    ///
+    /// Parameters
+    /// ----------
+    /// source_id: str
+    ///   The source to reset
+    ///
+    fn reset_source(&self, source_id: &str) {
+        self.0.reset_source(source_id)
+    }
+
+    /// Decodes the frame.
+    ///
+    /// Parameters
+    /// ----------
+    /// frame: :py:class:`savant_rs.primitives.VideoFrame`
+    ///   The received frame
+    /// no_gil: bool
+    ///   Whether to release the GIL while decoding
+    ///
+    /// Returns
+    /// -------
+    /// :py:class:`savant_rs.primitives.VideoFrame`
+    ///   The frame itself for a keyframe or a new restored frame
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the previous frame of the source, which the delta frame refers to, is missing
+    ///
+    #[pyo3(name = "decode")]
+    #[pyo3(signature = (frame, no_gil = true))]
+    fn decode_gil(&self, frame: &VideoFrame, no_gil: bool) -> PyResult<VideoFrame> {
+        release_gil!(no_gil, || self.0.decode(&frame.0))
+            .map(VideoFrame)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+}
@@ -4,6 +4,7 @@ use crate::{release_gil, with_gil};
 use pyo3::exceptions::{PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
 use pyo3::types::PyBytes;
+use savant_core::primitives::frame_merge;
 use savant_core::primitives::frame_update as rust;
 use savant_core::protobuf::{from_pb, ToProtobuf};
 
@@ -92,6 +93,29 @@ impl From<rust::AttributeUpdatePolicy> for AttributeUpdatePolicy {
 
 /// A video frame update object is used to update state of a frame from external source.
 ///
+/// How the objects of the other frame are merged by
+/// :py:meth:`savant_rs.primitives.VideoFrame.merge_from`:
+///   * objects with the same id, namespace and label are merged, the others are added;
+///   * all objects are added.
+///
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ObjectMergePolicy {
+    MergeSameObjects,
+    AddForeignObjects,
+}
+
+impl From<ObjectMergePolicy> for frame_merge::ObjectMergePolicy {
+    fn from(p: ObjectMergePolicy) -> Self {
+        match p {
+            ObjectMergePolicy::MergeSameObjects => frame_merge::ObjectMergePolicy::MergeSameObjects,
+            ObjectMergePolicy::AddForeignObjects => {
+                frame_merge::ObjectMergePolicy::AddForeignObjects
+            }
+        }
+    }
+}
+
 /// It contains a list of attributes and a list of objects.
 ///
 #[pyclass]
@@ -207,6 +231,25 @@ impl VideoFrameUpdate {
         self.0.set_object_policy(p.into());
     }
 
+    /// The name of the updater producing the update. When set, the pipeline applies the update
+    /// only if it writes the namespaces the updater is registered with.
+    ///
+    /// Returns
+    /// -------
+    /// Optional[str]
+    ///
+    #[getter]
+    #[pyo3(name = "updater")]
+    pub fn get_updater(&self) -> Option<String> {
+        self.0.get_updater().map(String::from)
+    }
+
+    #[setter]
+    #[pyo3(name = "updater")]
+    pub fn set_updater(&mut self, updater: Option<String>) {
+        self.0.set_updater(updater.as_deref());
+    }
+
     /// Adds an object to the frame update.
     ///
     /// Parameters
@@ -257,6 +300,19 @@ impl VideoFrameUpdate {
             .map_err(|e| PyValueError::new_err(e.to_string())))
     }
 
+    /// Returns the protobuf-encoded state of the video frame update, used by :mod:`pickle`.
+    ///
+    fn __getstate__(&self) -> PyResult<PyObject> {
+        self.to_protobuf_gil(false)
+    }
+
+    /// Restores the video frame update from the protobuf-encoded state, used by :mod:`pickle`.
+    ///
+    fn __setstate__(&mut self, state: &Bound<'_, PyBytes>) -> PyResult<()> {
+        *self = Self::from_protobuf_gil(state, false)?;
+        Ok(())
+    }
+
     #[pyo3(name = "to_protobuf")]
     #[pyo3(signature = (no_gil = true))]
     fn to_protobuf_gil(&self, no_gil: bool) -> PyResult<PyObject> {
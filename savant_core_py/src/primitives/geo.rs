@@ -0,0 +1,130 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::{pyclass, pymethods, Py, PyAny, PyResult};
+use savant_core::primitives::rust;
+
+/// A WGS84 position: the position of a detected object or, with the heading, the pose of the
+/// camera of a source.
+///
+/// Parameters
+/// ----------
+/// latitude : float
+///   The latitude in degrees, [-90, 90].
+/// longitude : float
+///   The longitude in degrees, [-180, 180].
+/// altitude : float
+///   The height above the ellipsoid in meters.
+/// heading : Optional[float]
+///   The direction in degrees clockwise from the true north, [0, 360).
+///
+/// Raises
+/// ------
+/// ValueError
+///   If the values are out of range.
+///
+#[pyclass]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct GeoPosition(pub(crate) rust::GeoPosition);
+
+/// Local east-north-up coordinates in meters relative to an origin position.
+///
+#[pyclass]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Enu(pub(crate) rust::Enu);
+
+#[pymethods]
+impl GeoPosition {
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", &self.0)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self == other
+    }
+
+    #[new]
+    #[pyo3(signature = (latitude, longitude, altitude=0.0, heading=None))]
+    pub fn new(
+        latitude: f64,
+        longitude: f64,
+        altitude: f64,
+        heading: Option<f64>,
+    ) -> PyResult<Self> {
+        rust::GeoPosition::new(latitude, longitude, altitude, heading)
+            .map(Self)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    #[getter]
+    fn get_latitude(&self) -> f64 {
+        self.0.latitude
+    }
+
+    #[getter]
+    fn get_longitude(&self) -> f64 {
+        self.0.longitude
+    }
+
+    #[getter]
+    fn get_altitude(&self) -> f64 {
+        self.0.altitude
+    }
+
+    #[getter]
+    fn get_heading(&self) -> Option<f64> {
+        self.0.heading
+    }
+
+    /// The coordinates of the position in the local east-north-up frame of the origin.
+    ///
+    fn to_enu(&self, origin: &GeoPosition) -> Enu {
+        Enu(self.0.to_enu(&origin.0))
+    }
+
+    /// The position of the local east-north-up coordinates of the origin, without the heading.
+    ///
+    #[staticmethod]
+    fn from_enu(origin: &GeoPosition, enu: &Enu) -> Self {
+        Self(rust::GeoPosition::from_enu(&origin.0, &enu.0))
+    }
+}
+
+#[pymethods]
+impl Enu {
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", &self.0)
+    }
+
+    fn __str__(&self) -> String {
+        self.__repr__()
+    }
+
+    #[new]
+    pub fn new(east: f64, north: f64, up: f64) -> Self {
+        Self(rust::Enu { east, north, up })
+    }
+
+    #[getter]
+    fn get_east(&self) -> f64 {
+        self.0.east
+    }
+
+    #[getter]
+    fn get_north(&self) -> f64 {
+        self.0.north
+    }
+
+    #[getter]
+    fn get_up(&self) -> f64 {
+        self.0.up
+    }
+}
@@ -2,11 +2,14 @@ pub mod loader;
 pub mod saver;
 
 use crate::primitives::frame_update::VideoFrameUpdate;
+use crate::primitives::object::DataClassification;
 use crate::primitives::user_data::UserData;
 use crate::primitives::VideoFrame;
 use crate::primitives::{EndOfStream, Shutdown, VideoFrameBatch};
 use crate::utils::otlp::PropagatedContext;
-use pyo3::{pyclass, pyfunction, pymethods, Py, PyAny};
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::types::{PyAnyMethods, PyBytes, PyBytesMethods};
+use pyo3::{pyclass, pyfunction, pymethods, Bound, Py, PyAny, PyObject, PyResult};
 use savant_core::primitives::rust as rust_primitives;
 
 #[pyclass]
@@ -144,6 +147,26 @@ impl Message {
         Self(rust_primitives::Message::video_frame_update(update.0))
     }
 
+    /// A hash of the message content which is stable across processes and releases, the
+    /// sequence id, labels and span context are not hashed.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    ///   The 64-bit hash of the canonical encoding of the content
+    ///
+    #[getter]
+    fn get_content_hash(&self) -> u64 {
+        self.0.content_hash()
+    }
+
+    /// The highest classification of the frames and the objects of the message.
+    ///
+    #[getter]
+    fn get_classification(&self) -> DataClassification {
+        self.0.get_classification().into()
+    }
+
     #[getter]
     fn get_labels(&self) -> Vec<String> {
         self.0.meta().routing_labels.clone()
@@ -346,6 +369,35 @@ impl Message {
     pub fn validate_seq_id(&self) -> bool {
         savant_core::message::validate_seq_id(&self.0)
     }
+
+    /// Returns the protobuf-encoded state of the message, used by :mod:`pickle`.
+    ///
+    fn __getstate__(&self) -> PyResult<Vec<u8>> {
+        savant_core::protobuf::serialize(&self.0).map_err(|e| {
+            PyRuntimeError::new_err(format!("Failed to serialize message to protobuf: {}", e))
+        })
+    }
+
+    /// Restores the message from the protobuf-encoded state, used by :mod:`pickle`.
+    ///
+    fn __setstate__(&mut self, state: &Bound<'_, PyBytes>) -> PyResult<()> {
+        self.0 = savant_core::protobuf::deserialize(state.as_bytes()).map_err(|e| {
+            PyRuntimeError::new_err(format!(
+                "Failed to deserialize message from protobuf: {}",
+                e
+            ))
+        })?;
+        Ok(())
+    }
+
+    /// The message has no constructor, so pickle recreates it as an unknown message and
+    /// restores the state afterwards.
+    ///
+    fn __reduce__(slf: &Bound<'_, Self>) -> PyResult<(PyObject, (String,), Vec<u8>)> {
+        let factory = slf.get_type().getattr("unknown")?.unbind();
+        let state = slf.borrow().__getstate__()?;
+        Ok((factory, (String::new(),), state))
+    }
 }
 
 #[pyfunction]
@@ -1,8 +1,10 @@
 use crate::primitives::message::Message;
 use crate::release_gil;
 use crate::utils::byte_buffer::ByteBuffer;
+use pyo3::exceptions::PyValueError;
 use pyo3::types::{PyBytes, PyBytesMethods};
-use pyo3::{pyfunction, Bound};
+use pyo3::{pyfunction, Bound, PyResult};
+use savant_core::protobuf::{CollisionAction, IngestionPolicy};
 
 /// Loads a message from a byte array. The function is optionally GIL-free.
 ///
@@ -72,3 +74,66 @@ pub fn load_message_from_bytes_gil(buffer: &Bound<'_, PyBytes>, no_gil: bool) ->
         bytes
     )))
 }
+
+/// Sets the policy for the attributes of the incoming messages in the namespaces managed by
+/// the local modules, so a misconfigured upstream does not overwrite the local state. The
+/// collisions are counted in the ``ingested_namespace_collisions`` metric.
+///
+/// Parameters
+/// ----------
+/// namespaces: Optional[List[str]]
+///   The glob patterns of the locally-managed namespaces, ``None`` disables the policy
+/// action: str
+///   ``rename`` to prefix the namespace, ``drop`` to remove the attributes, ``reject`` to
+///   raise on the message
+/// prefix: str
+///   The prefix of the renamed namespaces
+///
+#[pyfunction]
+#[pyo3(name = "set_ingestion_policy")]
+#[pyo3(signature = (namespaces=None, action="rename", prefix="upstream."))]
+pub fn set_ingestion_policy(
+    namespaces: Option<Vec<String>>,
+    action: &str,
+    prefix: &str,
+) -> PyResult<()> {
+    let action = match action {
+        "rename" => CollisionAction::Rename(prefix.to_string()),
+        "drop" => CollisionAction::Drop,
+        "reject" => CollisionAction::Reject,
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "Unknown collision action: {}",
+                action
+            )))
+        }
+    };
+    let policy = namespaces
+        .map(|ns| IngestionPolicy::new(&ns.iter().map(|s| s.as_str()).collect::<Vec<_>>(), action))
+        .transpose()
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    savant_core::protobuf::set_ingestion_policy(policy);
+    Ok(())
+}
+
+/// Returns the policy for the attributes of the incoming messages in the locally-managed
+/// namespaces.
+///
+/// Returns
+/// -------
+/// Optional[Tuple[List[str], str, Optional[str]]]
+///   The namespaces, the action and the prefix of the renamed namespaces, ``None`` when the
+///   policy is disabled
+///
+#[pyfunction]
+#[pyo3(name = "get_ingestion_policy")]
+pub fn get_ingestion_policy() -> Option<(Vec<String>, String, Option<String>)> {
+    savant_core::protobuf::get_ingestion_policy().map(|p| {
+        let (action, prefix) = match p.get_action() {
+            CollisionAction::Rename(prefix) => ("rename", Some(prefix.clone())),
+            CollisionAction::Drop => ("drop", None),
+            CollisionAction::Reject => ("reject", None),
+        };
+        (p.get_namespaces().to_vec(), action.to_string(), prefix)
+    })
+}
@@ -1,8 +1,11 @@
 use pyo3::types::PyBytes;
 use pyo3::{pyfunction, PyObject, PyResult};
 use savant_core::fast_hash;
+use savant_core::primitives::content_encoding::ContentEncoding;
+use savant_core::protobuf::{ClassificationAction, ClassificationPolicy, SerializationProfile};
 
 use crate::primitives::message::Message;
+use crate::primitives::object::DataClassification;
 use crate::release_gil;
 use crate::utils::byte_buffer::ByteBuffer;
 use crate::with_gil;
@@ -15,19 +18,44 @@ use crate::with_gil;
 ///   The message to save
 /// no_gil: bool
 ///   Whether to release the GIL while saving the message
+/// canonical: bool
+///   Whether to use the deterministic encoding with sorted objects, attributes and map
+///   entries, so equal messages produce equal bytes
+/// profile: Optional[str]
+///   The registered serialization profile selecting the parts of the message saved, see
+///   :py:func:`register_serialization_profile`
 ///
 /// Returns
 /// -------
 /// bytes
 ///   The byte array containing the message
 ///
+/// Raises
+/// ------
+/// ValueError
+///   If both the canonical encoding and the profile are requested
+///
 #[pyfunction]
 #[pyo3(name = "save_message")]
-#[pyo3(signature = (message, no_gil=true))]
-pub fn save_message_gil(message: &Message, no_gil: bool) -> PyResult<Vec<u8>> {
+#[pyo3(signature = (message, no_gil=true, canonical=false, profile=None))]
+pub fn save_message_gil(
+    message: &Message,
+    no_gil: bool,
+    canonical: bool,
+    profile: Option<&str>,
+) -> PyResult<Vec<u8>> {
+    if canonical && profile.is_some() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "The canonical encoding does not support the serialization profiles",
+        ));
+    }
     release_gil!(no_gil, || {
-        savant_core::message::save_message(&message.0)
-            .map_err(|e| pyo3::exceptions::PyException::new_err(format!("{:?}", e)))
+        match profile {
+            Some(profile) => savant_core::message::save_message_with_profile(&message.0, profile),
+            None if canonical => savant_core::message::save_message_canonical(&message.0),
+            None => savant_core::message::save_message(&message.0),
+        }
+        .map_err(|e| pyo3::exceptions::PyException::new_err(format!("{:?}", e)))
     })
 }
 
@@ -91,3 +119,165 @@ pub fn save_message_to_bytes_gil(message: &Message, no_gil: bool) -> PyResult<Py
         Ok(PyObject::from(bytes))
     })
 }
+
+/// Sets the attributes stripped from every message leaving the process through
+/// serialization and message journals.
+///
+/// Parameters
+/// ----------
+/// patterns: Optional[List[str]]
+///   The ``namespace/name`` glob patterns of the attributes to strip, ``None`` disables
+///   redaction
+///
+#[pyfunction]
+#[pyo3(name = "set_export_redaction")]
+#[pyo3(signature = (patterns=None))]
+pub fn set_export_redaction(patterns: Option<Vec<String>>) -> PyResult<()> {
+    let filter = patterns
+        .map(|p| {
+            savant_core::protobuf::RedactionFilter::new(
+                &p.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
+            )
+        })
+        .transpose()
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+    savant_core::protobuf::set_export_filter(filter);
+    Ok(())
+}
+
+/// Sets the classification level allowed for every message leaving the process through
+/// serialization and message journals. The frames and the objects classified above it are
+/// redacted or the message is rejected. The classified frames and objects are counted in the
+/// ``classified_items_exported`` metric.
+///
+/// Parameters
+/// ----------
+/// allowed: Optional[savant_rs.primitives.DataClassification]
+///   The highest classification exported as is, ``None`` disables the policy
+/// reject: bool
+///   Whether to raise on the messages with the data classified above the allowed level
+///   instead of redacting the data
+///
+#[pyfunction]
+#[pyo3(name = "set_export_classification")]
+#[pyo3(signature = (allowed=None, reject=false))]
+pub fn set_export_classification(allowed: Option<DataClassification>, reject: bool) {
+    let action = if reject {
+        ClassificationAction::Reject
+    } else {
+        ClassificationAction::Redact
+    };
+    savant_core::protobuf::set_classification_policy(
+        allowed.map(|a| ClassificationPolicy::new(a.into(), action)),
+    );
+}
+
+/// Returns the classification level allowed for exported messages.
+///
+/// Returns
+/// -------
+/// Optional[Tuple[savant_rs.primitives.DataClassification, bool]]
+///   The allowed level and whether the messages are rejected, ``None`` when the policy is
+///   disabled
+///
+#[pyfunction]
+#[pyo3(name = "get_export_classification")]
+pub fn get_export_classification() -> Option<(DataClassification, bool)> {
+    savant_core::protobuf::get_classification_policy()
+        .map(|p| (p.allowed.into(), p.action == ClassificationAction::Reject))
+}
+
+/// Returns the patterns of the attributes stripped from exported messages.
+///
+/// Returns
+/// -------
+/// Optional[List[str]]
+///   The patterns or ``None`` when redaction is disabled
+///
+#[pyfunction]
+#[pyo3(name = "get_export_redaction")]
+pub fn get_export_redaction() -> Option<Vec<String>> {
+    savant_core::protobuf::get_export_filter().map(|f| f.get_patterns().to_vec())
+}
+
+/// Registers a named serialization profile selecting the parts of the messages a sink
+/// receives, e.g. the metrics sink gets the messages without the objects while the recorder
+/// gets them all. The profile is selected in :py:func:`save_message` and in the writer
+/// configuration. A profile with the same name is replaced.
+///
+/// Parameters
+/// ----------
+/// name: str
+///   The name of the profile
+/// include_objects: Optional[List[str]]
+///   The ``namespace/label`` glob patterns of the objects kept, ``None`` keeps all of them
+/// exclude_objects: List[str]
+///   The ``namespace/label`` glob patterns of the objects removed
+/// include_attributes: Optional[List[str]]
+///   The ``namespace/name`` glob patterns of the attributes kept, ``None`` keeps all of them
+/// exclude_attributes: List[str]
+///   The ``namespace/name`` glob patterns of the attributes removed
+/// representations: Optional[List[str]]
+///   The content representations kept, ``None`` keeps all of them
+/// content_encoding: Optional[str]
+///   ``lz4`` or ``zstd`` to compress the uncompressed content of the frames
+///
+/// Raises
+/// ------
+/// ValueError
+///   If a pattern or the codec is invalid
+///
+#[pyfunction]
+#[pyo3(signature = (
+    name,
+    include_objects=None,
+    exclude_objects=Vec::new(),
+    include_attributes=None,
+    exclude_attributes=Vec::new(),
+    representations=None,
+    content_encoding=None
+))]
+pub fn register_serialization_profile(
+    name: &str,
+    include_objects: Option<Vec<String>>,
+    exclude_objects: Vec<String>,
+    include_attributes: Option<Vec<String>>,
+    exclude_attributes: Vec<String>,
+    representations: Option<Vec<String>>,
+    content_encoding: Option<&str>,
+) -> PyResult<()> {
+    let strs = |v: &[String]| v.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+    let include_objects = include_objects.as_deref().map(strs);
+    let include_attributes = include_attributes.as_deref().map(strs);
+    let content_encoding = content_encoding
+        .map(str::parse::<ContentEncoding>)
+        .transpose()
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+    let profile = SerializationProfile::new(name)
+        .with_objects(include_objects.as_deref(), &strs(&exclude_objects))
+        .and_then(|p| p.with_attributes(include_attributes.as_deref(), &strs(&exclude_attributes)))
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?
+        .with_representations(representations)
+        .with_content_encoding(content_encoding);
+    savant_core::protobuf::register_serialization_profile(profile);
+    Ok(())
+}
+
+/// Removes the serialization profile.
+///
+/// Returns
+/// -------
+/// bool
+///   Whether the profile was registered
+///
+#[pyfunction]
+pub fn unregister_serialization_profile(name: &str) -> bool {
+    savant_core::protobuf::unregister_serialization_profile(name)
+}
+
+/// Returns the names of the registered serialization profiles.
+///
+#[pyfunction]
+pub fn get_serialization_profiles() -> Vec<String> {
+    savant_core::protobuf::get_serialization_profiles()
+}
@@ -1,15 +1,27 @@
 use crate::primitives::attribute_value::AttributeValue;
 use crate::primitives::bbox::VideoObjectBBoxTransformation;
+use crate::primitives::geo::GeoPosition;
 use crate::primitives::{Attribute, RBBox};
 use crate::{release_gil, with_gil};
-use pyo3::exceptions::PyRuntimeError;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
 use pyo3::types::{PyBytes, PyBytesMethods};
-use pyo3::{pyclass, pymethods, Bound, PyObject, PyResult};
+use pyo3::{pyclass, pymethods, Bound, Py, PyAny, PyObject, PyResult};
 use savant_core::json_api::ToSerdeJsonValue;
 use savant_core::primitives::object::{ObjectAccess, ObjectOperations};
 use savant_core::primitives::{rust, WithAttributes};
 use savant_core::protobuf::{from_pb, ToProtobuf};
 use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+fn payload_to_py(payload: Option<rust::ObjectPayload>) -> Option<(String, PyObject)> {
+    payload.map(|p| {
+        with_gil!(|py| (
+            p.get_content_type().to_string(),
+            PyObject::from(PyBytes::new(py, p.get_data()))
+        ))
+    })
+}
 
 #[pyclass(eq, eq_int)]
 #[derive(Debug, Clone, PartialEq)]
@@ -31,12 +43,83 @@ impl From<IdCollisionResolutionPolicy> for rust::IdCollisionResolutionPolicy {
     }
 }
 
+/// What happens to the descendants of objects removed with
+/// :py:meth:`savant_rs.primitives.VideoFrame.prune`.
+///
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrphanPolicy {
+    Detach,
+    Reparent,
+    Cascade,
+}
+
+impl From<OrphanPolicy> for rust::OrphanPolicy {
+    fn from(value: OrphanPolicy) -> Self {
+        match value {
+            OrphanPolicy::Detach => rust::OrphanPolicy::Detach,
+            OrphanPolicy::Reparent => rust::OrphanPolicy::Reparent,
+            OrphanPolicy::Cascade => rust::OrphanPolicy::Cascade,
+        }
+    }
+}
+
+/// The sensitivity of the data held by a frame or an object. The data classified above the
+/// level allowed for export is redacted or rejected on serialization, see
+/// :py:func:`savant_rs.serialization.set_export_classification`.
+///
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DataClassification {
+    Public,
+    Internal,
+    Pii,
+}
+
+impl From<DataClassification> for rust::DataClassification {
+    fn from(value: DataClassification) -> Self {
+        match value {
+            DataClassification::Public => rust::DataClassification::Public,
+            DataClassification::Internal => rust::DataClassification::Internal,
+            DataClassification::Pii => rust::DataClassification::Pii,
+        }
+    }
+}
+
+impl From<rust::DataClassification> for DataClassification {
+    fn from(value: rust::DataClassification) -> Self {
+        match value {
+            rust::DataClassification::Public => DataClassification::Public,
+            rust::DataClassification::Internal => DataClassification::Internal,
+            rust::DataClassification::Pii => DataClassification::Pii,
+        }
+    }
+}
+
 #[pyclass]
 #[derive(Debug, Clone)]
 pub struct VideoObject(pub(crate) rust::VideoObject);
 
 #[pymethods]
 impl VideoObject {
+    #[classattr]
+    const __hash__: Option<Py<PyAny>> = None;
+
+    /// Detached objects have value semantics: two objects are equal if all their fields
+    /// and attributes are equal. The objects are mutable, so they are not hashable.
+    ///
+    fn __eq__(&self, other: &Self) -> bool {
+        self.0.to_serde_json_value() == other.0.to_serde_json_value()
+    }
+
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    fn __deepcopy__(&self, _memo: &Bound<'_, PyAny>) -> Self {
+        self.clone()
+    }
+
     #[allow(clippy::too_many_arguments)]
     #[new]
     #[pyo3(signature = (id, namespace, label, detection_box, attributes, confidence=None, track_id=None, track_box=None))]
@@ -101,6 +184,29 @@ impl VideoObject {
         })
     }
 
+    /// Returns the protobuf-encoded state of the video object, used by :mod:`pickle`.
+    ///
+    fn __getstate__(&self) -> PyResult<PyObject> {
+        self.to_protobuf_gil(false)
+    }
+
+    /// Restores the video object from the protobuf-encoded state, used by :mod:`pickle`.
+    ///
+    fn __setstate__(&mut self, state: &Bound<'_, PyBytes>) -> PyResult<()> {
+        *self = Self::from_protobuf_gil(state, false)?;
+        Ok(())
+    }
+
+    fn __getnewargs__(&self) -> (i64, String, String, RBBox, Vec<Attribute>) {
+        (
+            0,
+            String::new(),
+            String::new(),
+            RBBox::new(0.0, 0.0, 0.0, 0.0, None),
+            vec![],
+        )
+    }
+
     #[getter]
     fn get_namespace(&self) -> String {
         self.0.get_namespace()
@@ -131,6 +237,21 @@ impl VideoObject {
         self.0.get_track_box().map(RBBox)
     }
 
+    #[getter]
+    fn get_geo_position(&self) -> Option<GeoPosition> {
+        self.0.get_geo_position().map(GeoPosition)
+    }
+
+    #[getter]
+    fn get_classification(&self) -> DataClassification {
+        self.0.get_classification().into()
+    }
+
+    #[getter]
+    fn get_payload(&self) -> Option<(String, PyObject)> {
+        payload_to_py(self.0.get_payload())
+    }
+
     #[getter]
     fn get_confidence(&self) -> Option<f32> {
         self.0.get_confidence()
@@ -196,10 +317,45 @@ impl ToSerdeJsonValue for BorrowedVideoObject {
     }
 }
 
+impl BorrowedVideoObject {
+    /// Changes the object, a change of a frozen object raises ``ValueError``.
+    ///
+    fn modify<F, R>(&mut self, f: F) -> PyResult<R>
+    where
+        F: FnOnce(&mut rust::VideoObject) -> R,
+    {
+        self.0
+            .try_with_object_mut(f)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+}
+
 #[pymethods]
 impl BorrowedVideoObject {
+    /// Borrowed objects have identity semantics: two Python objects are equal if they refer
+    /// to the same object of the same frame, the hash is consistent with the equality.
+    ///
     fn __hash__(&self) -> usize {
-        self.memory_handle()
+        let (frame, id) = self.0.shared_handle();
+        let mut hasher = DefaultHasher::new();
+        (frame, id).hash(&mut hasher);
+        hasher.finish() as usize
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.0.shared_handle() == other.0.shared_handle()
+    }
+
+    /// Returns a new reference to the same object.
+    ///
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    /// Returns a detached copy of the object, the same as :py:meth:`BorrowedVideoObject.detached_copy`.
+    ///
+    fn __deepcopy__(&self, _memo: &Bound<'_, PyAny>) -> VideoObject {
+        self.detached_copy()
     }
 
     fn __repr__(&self) -> String {
@@ -224,8 +380,19 @@ impl BorrowedVideoObject {
 
     /// Clears all object's attributes.
     ///
-    pub fn clear_attributes(&mut self) {
-        self.0.clear_attributes()
+    pub fn clear_attributes(&mut self) -> PyResult<()> {
+        self.modify(|o| o.clear_attributes())
+    }
+
+    /// Removes the expired attributes.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    ///   The number of removed attributes.
+    ///
+    pub fn purge_expired_attributes(&mut self) -> PyResult<usize> {
+        self.modify(|o| o.purge_expired_attributes())
     }
 
     /// Returns the object's id. The setter causes ``RuntimeError`` when the object is attached to a frame.
@@ -253,8 +420,8 @@ impl BorrowedVideoObject {
     }
 
     #[setter]
-    pub fn set_confidence(&mut self, confidence: Option<f32>) {
-        self.0.set_confidence(confidence);
+    pub fn set_confidence(&mut self, confidence: Option<f32>) -> PyResult<()> {
+        self.modify(|o| o.set_confidence(confidence))
     }
 
     /// Returns object's namespace. When used as setter, allows setting object's namespace.
@@ -275,8 +442,8 @@ impl BorrowedVideoObject {
     }
 
     #[setter]
-    pub fn set_namespace(&mut self, namespace: &str) {
-        self.0.set_namespace(namespace);
+    pub fn set_namespace(&mut self, namespace: &str) -> PyResult<()> {
+        self.modify(|o| o.set_namespace(namespace))
     }
 
     #[getter]
@@ -289,8 +456,8 @@ impl BorrowedVideoObject {
         self.0.get_label_id()
     }
     #[setter]
-    pub fn set_label(&mut self, label: &str) {
-        self.0.set_label(label);
+    pub fn set_label(&mut self, label: &str) -> PyResult<()> {
+        self.modify(|o| o.set_label(label))
     }
 
     /// Deletes an attribute from the object.
@@ -308,27 +475,27 @@ impl BorrowedVideoObject {
     /// :py:class:`Attribute` or None
     ///   Deleted attribute or None if the attribute is not found.
     ///
-    pub fn delete_attribute(&mut self, namespace: &str, name: &str) -> Option<Attribute> {
-        self.0.delete_attribute(namespace, name).map(Attribute)
+    pub fn delete_attribute(&mut self, namespace: &str, name: &str) -> PyResult<Option<Attribute>> {
+        self.modify(|o| o.delete_attribute(namespace, name).map(Attribute))
     }
 
-    pub fn delete_attributes_with_ns(&mut self, namespace: &str) {
-        self.0.delete_attributes_with_ns(namespace)
+    pub fn delete_attributes_with_ns(&mut self, namespace: &str) -> PyResult<()> {
+        self.modify(|o| o.delete_attributes_with_ns(namespace))
     }
 
-    pub fn delete_attributes_with_names(&mut self, names: Vec<String>) {
+    pub fn delete_attributes_with_names(&mut self, names: Vec<String>) -> PyResult<()> {
         let label_refs = names.iter().map(|v| v.as_ref()).collect::<Vec<&str>>();
-        self.0.delete_attributes_with_names(&label_refs)
+        self.modify(|o| o.delete_attributes_with_names(&label_refs))
     }
 
-    pub fn delete_attributes_with_hints(&mut self, hints: Vec<Option<String>>) {
+    pub fn delete_attributes_with_hints(&mut self, hints: Vec<Option<String>>) -> PyResult<()> {
         let hint_opts_refs = hints
             .iter()
             .map(|v| v.as_deref())
             .collect::<Vec<Option<&str>>>();
         let hint_refs = hint_opts_refs.iter().collect::<Vec<_>>();
 
-        self.0.delete_attributes_with_hints(&hint_refs)
+        self.modify(|o| o.delete_attributes_with_hints(&hint_refs))
     }
 
     /// Returns a copy of the object with the same properties but detached from the frame and without a parent set.
@@ -356,8 +523,8 @@ impl BorrowedVideoObject {
     }
 
     #[setter]
-    pub fn set_draw_label(&mut self, draw_label: Option<String>) {
-        self.0.set_draw_label(draw_label);
+    pub fn set_draw_label(&mut self, draw_label: Option<String>) -> PyResult<()> {
+        self.modify(|o| o.set_draw_label(draw_label))
     }
 
     pub fn find_attributes_with_ns(&mut self, namespace: &str) -> Vec<(String, String)> {
@@ -415,8 +582,8 @@ impl BorrowedVideoObject {
     /// :py:class:`Attribute` or None
     ///   Attribute that was replaced or None if the attribute was not set.
     ///
-    pub fn set_attribute(&mut self, attribute: &Attribute) -> Option<Attribute> {
-        self.0.set_attribute(attribute.0.clone()).map(Attribute)
+    pub fn set_attribute(&mut self, attribute: &Attribute) -> PyResult<Option<Attribute>> {
+        self.modify(|o| o.set_attribute(attribute.0.clone()).map(Attribute))
     }
 
     /// Sets new persistent attribute for the object. If the attribute is already set, it is replaced.
@@ -442,14 +609,13 @@ impl BorrowedVideoObject {
         is_hidden: bool,
         hint: Option<String>,
         values: Option<Vec<AttributeValue>>,
-    ) {
+    ) -> PyResult<()> {
         let values = match values {
             Some(values) => values.into_iter().map(|v| v.0).collect::<Vec<_>>(),
             None => vec![],
         };
         let hint = hint.as_deref();
-        self.0
-            .set_persistent_attribute(namespace, name, &hint, is_hidden, values)
+        self.modify(|o| o.set_persistent_attribute(namespace, name, &hint, is_hidden, values))
     }
 
     /// Sets new temporary attribute for the object. If the attribute is already set, it is replaced.
@@ -475,14 +641,13 @@ impl BorrowedVideoObject {
         is_hidden: bool,
         hint: Option<String>,
         values: Option<Vec<AttributeValue>>,
-    ) {
+    ) -> PyResult<()> {
         let values = match values {
             Some(values) => values.into_iter().map(|v| v.0).collect::<Vec<_>>(),
             None => vec![],
         };
         let hint = hint.as_deref();
-        self.0
-            .set_temporary_attribute(namespace, name, &hint, is_hidden, values)
+        self.modify(|o| o.set_temporary_attribute(namespace, name, &hint, is_hidden, values))
     }
 
     /// Returns object's bbox by value. Any modifications of the returned value will not affect the object.
@@ -499,8 +664,8 @@ impl BorrowedVideoObject {
     }
 
     #[setter]
-    pub fn set_detection_box(&mut self, bbox: RBBox) {
-        self.0.set_detection_box(bbox.0);
+    pub fn set_detection_box(&mut self, bbox: RBBox) -> PyResult<()> {
+        self.modify(|o| o.set_detection_box(bbox.0))
     }
 
     #[getter]
@@ -509,8 +674,8 @@ impl BorrowedVideoObject {
     }
 
     #[setter]
-    pub fn set_track_id(&mut self, track_id: Option<i64>) {
-        self.0.set_track_id(track_id);
+    pub fn set_track_id(&mut self, track_id: Option<i64>) -> PyResult<()> {
+        self.modify(|o| o.set_track_id(track_id))
     }
 
     #[getter]
@@ -519,21 +684,95 @@ impl BorrowedVideoObject {
     }
 
     #[setter]
-    pub fn set_track_box(&mut self, bbox: RBBox) {
-        self.0.set_track_box(bbox.0);
+    pub fn set_track_box(&mut self, bbox: RBBox) -> PyResult<()> {
+        self.modify(|o| o.set_track_box(bbox.0))
+    }
+
+    /// The geographic position of the object, serialized with the object.
+    ///
+    #[getter]
+    pub fn get_geo_position(&self) -> Option<GeoPosition> {
+        self.0.get_geo_position().map(GeoPosition)
+    }
+
+    #[setter]
+    pub fn set_geo_position(&mut self, position: Option<GeoPosition>) -> PyResult<()> {
+        self.modify(|o| o.set_geo_position(position.map(|p| p.0)))
+    }
+
+    /// The classification of the object data, the classification of the frame applies too.
+    ///
+    #[getter]
+    pub fn get_classification(&self) -> DataClassification {
+        self.0.get_classification().into()
+    }
+
+    #[setter]
+    pub fn set_classification(&mut self, classification: DataClassification) -> PyResult<()> {
+        self.modify(|o| o.set_classification(classification.into()))
+    }
+
+    /// The binary payload of the object as ``(content_type, data)``, it is not copied with the
+    /// object implicitly.
+    ///
+    #[getter]
+    pub fn get_payload(&self) -> Option<(String, PyObject)> {
+        payload_to_py(self.0.get_payload())
+    }
+
+    /// Sets the binary payload of the object, it is serialized with the object only when
+    /// ``serialize`` is set.
+    ///
+    #[pyo3(signature = (content_type, data, serialize=false))]
+    pub fn set_payload(
+        &mut self,
+        content_type: &str,
+        data: &Bound<'_, PyBytes>,
+        serialize: bool,
+    ) -> PyResult<()> {
+        let payload = rust::ObjectPayload::new(content_type, data.as_bytes().to_vec())
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))?
+            .with_serialization(serialize);
+        self.modify(|o| o.set_payload(Some(payload)))
+    }
+
+    pub fn take_payload(&mut self) -> PyResult<Option<(String, PyObject)>> {
+        self.modify(|o| o.take_payload()).map(payload_to_py)
+    }
+
+    pub fn clear_payload(&mut self) -> PyResult<()> {
+        self.modify(|o| o.set_payload(None))
+    }
+
+    /// Copies the payload to the other object, the data is shared.
+    ///
+    pub fn copy_payload_to(&self, other: &mut BorrowedVideoObject) -> PyResult<()> {
+        let payload = self.0.get_payload();
+        other.modify(|o| o.set_payload(payload))
+    }
+
+    /// Moves the payload to the other object, nothing is changed when either object is frozen.
+    ///
+    pub fn transfer_payload_to(&mut self, other: &mut BorrowedVideoObject) -> PyResult<()> {
+        let payload = self.modify(|o| o.take_payload())?;
+        if let Err(e) = other.modify(|o| o.set_payload(payload.clone())) {
+            self.0.set_payload(payload);
+            return Err(e);
+        }
+        Ok(())
     }
 
-    pub fn set_track_info(&mut self, track_id: i64, bbox: RBBox) {
-        self.0.set_track_info(track_id, bbox.0);
+    pub fn set_track_info(&mut self, track_id: i64, bbox: RBBox) -> PyResult<()> {
+        self.modify(|o| o.set_track_info(track_id, bbox.0))
     }
 
-    pub fn clear_track_info(&mut self) {
-        self.0.clear_track_info()
+    pub fn clear_track_info(&mut self) -> PyResult<()> {
+        self.modify(|o| o.clear_track_info())
     }
 
-    fn transform_geometry(&mut self, ops: Vec<VideoObjectBBoxTransformation>) {
+    fn transform_geometry(&mut self, ops: Vec<VideoObjectBBoxTransformation>) -> PyResult<()> {
         let inner_ops = ops.iter().map(|op| op.0).collect::<Vec<_>>();
-        self.0.transform_geometry(&inner_ops);
+        self.modify(|o| o.transform_geometry(&inner_ops))
     }
 
     #[pyo3(name = "to_protobuf")]
@@ -0,0 +1,139 @@
+use crate::primitives::object::VideoObject;
+use crate::primitives::objects_view::VideoObjectsView;
+use crate::release_gil;
+use parking_lot::Mutex;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use savant_core::primitives::object::ObjectOperations;
+use savant_core::primitives::object_fusion as rust;
+use savant_core::primitives::rust as rust_primitives;
+
+#[derive(FromPyObject)]
+pub enum Objects {
+    View(VideoObjectsView),
+    List(Vec<VideoObject>),
+}
+
+impl Objects {
+    fn into_detached(self) -> Vec<rust_primitives::VideoObject> {
+        match self {
+            Objects::View(view) => view.0.iter().map(|o| o.0.detached_copy()).collect(),
+            Objects::List(objects) => objects.into_iter().map(|o| o.0).collect(),
+        }
+    }
+}
+
+/// Fuses the overlapping objects detected by several models or in several frames with the
+/// weighted box fusion. The objects with the same track id are fused regardless of the IoU,
+/// the objects with different track ids are never fused.
+///
+/// Parameters
+/// ----------
+/// objects : Union[List[VideoObject], VideoObjectsView]
+///   The objects to fuse.
+/// iou_threshold : float
+///   The IoU with the fused box of a cluster above which an object joins the cluster.
+/// sources : int
+///   The number of the models or frames the objects come from, the confidence of a cluster
+///   found by fewer of them is lowered proportionally.
+/// cross_label : bool
+///   Whether the objects with different namespaces or labels are fused.
+/// default_confidence : float
+///   The confidence of the objects without one.
+///
+/// Returns
+/// -------
+/// List[VideoObject]
+///   The detached copies of the most confident objects of the clusters with the fused boxes
+///   and confidences.
+///
+/// Raises
+/// ------
+/// ValueError
+///   If the number of the sources is 0.
+///
+#[pyfunction]
+#[pyo3(signature = (objects, iou_threshold = 0.55, sources = 1, cross_label = false, default_confidence = 1.0))]
+pub fn fuse_objects(
+    objects: Objects,
+    iou_threshold: f32,
+    sources: usize,
+    cross_label: bool,
+    default_confidence: f32,
+) -> PyResult<Vec<VideoObject>> {
+    let objects = objects.into_detached();
+    let config = rust::BoxFusionConfig {
+        iou_threshold,
+        sources,
+        cross_label,
+        default_confidence,
+    };
+    release_gil!(true, || rust::fuse_objects(&objects, &config))
+        .map(|fused| fused.into_iter().map(VideoObject).collect())
+        .map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// Keeps the tracked objects alive through the frames the detectors miss them in, with the
+/// confidence decaying by the factor for every missed frame. An instance serves a single
+/// source.
+///
+/// Parameters
+/// ----------
+/// factor : float
+///   The decay of the confidence per missed frame, in (0, 1].
+/// min_confidence : float
+///   The confidence below which a track is dropped.
+/// max_missed : int
+///   The number of the missed frames after which a track is dropped.
+///
+/// Raises
+/// ------
+/// ValueError
+///   If the factor is not in (0, 1].
+///
+#[pyclass]
+pub struct ConfidenceDecay(Mutex<rust::ConfidenceDecay>);
+
+#[pymethods]
+impl ConfidenceDecay {
+    #[new]
+    #[pyo3(signature = (factor, min_confidence = 0.1, max_missed = 5))]
+    fn new(factor: f32, min_confidence: f32, max_missed: u32) -> PyResult<Self> {
+        rust::ConfidenceDecay::new(factor, min_confidence, max_missed)
+            .map(|d| Self(Mutex::new(d)))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Accounts the objects detected in the frame.
+    ///
+    /// Parameters
+    /// ----------
+    /// objects : Union[List[VideoObject], VideoObjectsView]
+    ///   The objects of the frame.
+    ///
+    /// Returns
+    /// -------
+    /// List[VideoObject]
+    ///   The tracked objects missed in the frame with the decayed confidence, to add to the
+    ///   frame.
+    ///
+    fn update(&self, objects: Objects) -> Vec<VideoObject> {
+        let objects = objects.into_detached();
+        self.0
+            .lock()
+            .update(&objects)
+            .into_iter()
+            .map(VideoObject)
+            .collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.lock().len()
+    }
+
+    /// Forgets the tracks, e.g. when the stream restarts.
+    ///
+    fn reset(&self) {
+        self.0.lock().reset();
+    }
+}
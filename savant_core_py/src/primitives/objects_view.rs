@@ -3,6 +3,7 @@ use crate::primitives::object::BorrowedVideoObject;
 use crate::release_gil;
 use pyo3::exceptions::PyIndexError;
 use pyo3::prelude::*;
+use pyo3::types::PySlice;
 use savant_core::match_query::*;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -88,11 +89,62 @@ impl VideoObjectsView {
         self.__repr__()
     }
 
-    fn __getitem__(&self, index: usize) -> PyResult<BorrowedVideoObject> {
-        self.0
-            .get(index)
-            .ok_or(PyIndexError::new_err("index out of range"))
-            .cloned()
+    /// Returns an object by index or a new view by slice. Negative indices are supported.
+    ///
+    fn __getitem__(&self, py: Python<'_>, index: ViewIndex<'_>) -> PyResult<PyObject> {
+        match index {
+            ViewIndex::Int(index) => {
+                let len = self.0.len() as isize;
+                let index = if index < 0 { index + len } else { index };
+                if index < 0 || index >= len {
+                    return Err(PyIndexError::new_err("index out of range"));
+                }
+                Ok(self.0[index as usize]
+                    .clone()
+                    .into_pyobject(py)?
+                    .into_any()
+                    .unbind())
+            }
+            ViewIndex::Slice(slice) => {
+                let indices = slice.indices(self.0.len() as isize)?;
+                let objects = (0..indices.slicelength)
+                    .map(|i| self.0[(indices.start + i as isize * indices.step) as usize].clone())
+                    .collect::<Vec<_>>();
+                Ok(VideoObjectsView::from(objects)
+                    .into_pyobject(py)?
+                    .into_any()
+                    .unbind())
+            }
+        }
+    }
+
+    fn __iter__(&self) -> VideoObjectsViewIterator {
+        VideoObjectsViewIterator {
+            objects: self.0.clone(),
+            position: 0,
+        }
+    }
+
+    fn __bool__(&self) -> bool {
+        !self.0.is_empty()
+    }
+
+    /// Returns a new view with the objects matching the query. The same as
+    /// :py:meth:`QueryFunctions.filter`.
+    ///
+    #[pyo3(name = "filter")]
+    #[pyo3(signature = (q, no_gil = true))]
+    fn filter_gil(&self, q: &MatchQuery, no_gil: bool) -> VideoObjectsView {
+        QueryFunctions::filter_gil(self, q, no_gil)
+    }
+
+    /// Splits the view into the objects matching and not matching the query. The same as
+    /// :py:meth:`QueryFunctions.partition`.
+    ///
+    #[pyo3(name = "partition")]
+    #[pyo3(signature = (q, no_gil = true))]
+    fn partition_gil(&self, q: &MatchQuery, no_gil: bool) -> (VideoObjectsView, VideoObjectsView) {
+        QueryFunctions::partition_gil(self, q, no_gil)
     }
 
     #[getter]
@@ -127,6 +179,31 @@ impl VideoObjectsView {
     }
 }
 
+#[derive(FromPyObject)]
+enum ViewIndex<'py> {
+    Int(isize),
+    Slice(Bound<'py, PySlice>),
+}
+
+#[pyclass]
+pub struct VideoObjectsViewIterator {
+    objects: Arc<Vec<BorrowedVideoObject>>,
+    position: usize,
+}
+
+#[pymethods]
+impl VideoObjectsViewIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> Option<BorrowedVideoObject> {
+        let object = self.objects.get(self.position).cloned()?;
+        self.position += 1;
+        Some(object)
+    }
+}
+
 #[pyclass]
 #[derive(Clone, Debug)]
 pub struct QueryFunctions;
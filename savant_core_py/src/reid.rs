@@ -0,0 +1,124 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use savant_core::reid::gallery::{
+    GalleryConfiguration, JsonFilePersistence, ReidGallery as RustReidGallery,
+};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// A gallery of identities for re-identification. It maps identity ids to embedding
+/// centroids and finds the identities most similar to an embedding by cosine similarity.
+///
+/// Parameters
+/// ----------
+/// dimension : int
+///   The dimension of embeddings.
+/// ttl_ms : Optional[int]
+///   The identities which are not updated for longer are evicted.
+///
+/// Raises
+/// ------
+/// ValueError
+///   If the dimension is zero.
+///
+#[pyclass]
+#[derive(Debug)]
+pub struct ReidGallery(RustReidGallery);
+
+#[pymethods]
+impl ReidGallery {
+    #[new]
+    #[pyo3(signature = (dimension, ttl_ms = None))]
+    fn new(dimension: usize, ttl_ms: Option<u64>) -> PyResult<Self> {
+        RustReidGallery::new(GalleryConfiguration {
+            dimension,
+            ttl: ttl_ms.map(Duration::from_millis),
+        })
+        .map(Self)
+        .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Sets the centroid of the identity to the embedding, replacing the previous samples.
+    ///
+    fn add(&self, id: i64, embedding: Vec<f32>) -> PyResult<()> {
+        self.0
+            .add(id, &embedding)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Adds a sample of the identity to its centroid, creates the identity when it does not
+    /// exist.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    ///   The number of samples of the identity.
+    ///
+    fn update(&self, id: i64, embedding: Vec<f32>) -> PyResult<u64> {
+        self.0
+            .update(id, &embedding)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Returns the centroid of the identity.
+    ///
+    fn get(&self, id: i64) -> Option<Vec<f32>> {
+        self.0.get(id).map(|i| i.centroid)
+    }
+
+    fn remove(&self, id: i64) -> bool {
+        self.0.remove(id).is_some()
+    }
+
+    /// Removes the identities not updated within the TTL.
+    ///
+    /// Returns
+    /// -------
+    /// List[int]
+    ///   The ids of the removed identities.
+    ///
+    fn evict_expired(&self) -> Vec<i64> {
+        self.0.evict_expired()
+    }
+
+    /// Finds the identities most similar to the embedding.
+    ///
+    /// Returns
+    /// -------
+    /// List[Tuple[int, float]]
+    ///   Up to ``k`` pairs of the identity id and the cosine similarity, the most similar
+    ///   first.
+    ///
+    #[pyo3(signature = (embedding, k = 1))]
+    fn query(&self, embedding: Vec<f32>, k: usize) -> PyResult<Vec<(i64, f32)>> {
+        self.0
+            .query(&embedding, k)
+            .map(|m| m.into_iter().map(|m| (m.id, m.similarity)).collect())
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Saves the gallery to a JSON file.
+    ///
+    fn save(&self, path: PathBuf) -> PyResult<()> {
+        self.0
+            .save(&JsonFilePersistence { path })
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Loads the gallery from a JSON file.
+    ///
+    /// Returns
+    /// -------
+    /// bool
+    ///   False when the file does not exist.
+    ///
+    fn load(&self, path: PathBuf) -> PyResult<bool> {
+        self.0
+            .load(&JsonFilePersistence { path })
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+}
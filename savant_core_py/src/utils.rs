@@ -1,14 +1,26 @@
 use evalexpr::Value;
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
+use savant_core::pipeline::fixtures::FixtureScaffold;
+use savant_core::provenance::{Disposition, ExportStatus};
+use savant_core::replay::ComparisonTolerance;
+use savant_core::utils::uuid_v7::FrameUuidVersion;
+use std::path::PathBuf;
+use std::time::Duration;
+use uuid::Uuid;
 
 use crate::logging::{log_level_enabled, LogLevel};
+use crate::primitives::frame::VideoFrame;
+use crate::primitives::message::Message;
 use crate::{release_gil, with_gil};
 
+pub mod aggregation;
 pub mod byte_buffer;
 pub mod eval_resolvers;
 pub mod otlp;
 pub mod python;
+pub mod retention;
+pub mod state;
 pub mod symbol_mapper;
 
 #[pyfunction]
@@ -64,7 +76,490 @@ pub fn enable_dl_detection() {
     savant_core::deadlock_detection::enable_dl_detection();
 }
 
+/// Compares the frames of a candidate journal with the baseline, e.g. the sink output of a
+/// new build replaying recorded traffic with the output of the previous one. Frames are
+/// identified by the source and the PTS, objects are paired by the namespace, the label and
+/// the IoU.
+///
+/// Parameters
+/// ----------
+/// baseline : str
+///   The path to the baseline journal.
+/// candidate : str
+///   The path to the candidate journal.
+/// match_iou : float
+///   The minimum IoU to pair objects.
+/// move_iou : float
+///   Paired objects with a lower IoU are reported as moved.
+///
+/// Returns
+/// -------
+/// Tuple[bool, str]
+///   Whether the journals are equal and the JSON report.
+///
+/// Raises
+/// ------
+/// ValueError
+///   If a journal cannot be read.
+///
+#[pyfunction]
+#[pyo3(signature = (baseline, candidate, match_iou = 0.3, move_iou = 0.9))]
+pub fn compare_journals(
+    baseline: &str,
+    candidate: &str,
+    match_iou: f32,
+    move_iou: f32,
+) -> PyResult<(bool, String)> {
+    let tolerance = ComparisonTolerance {
+        match_iou,
+        move_iou,
+    };
+    let report = savant_core::replay::compare_journals(baseline, candidate, &tolerance)
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let json = report
+        .to_json()
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok((report.is_equal(), json))
+}
+
+/// Diffs the frames of two journals with the same uuids, e.g. the sink outputs of an old and
+/// a new model for a side-by-side review. Every diff lists the changed frame attributes and
+/// the added, removed and changed objects with their changed attributes.
+///
+/// Parameters
+/// ----------
+/// baseline : str
+///   The path to the baseline journal.
+/// candidate : str
+///   The path to the candidate journal.
+/// match_iou : float
+///   The minimum IoU to pair objects.
+/// move_iou : float
+///   Paired objects with a lower IoU are reported as changed.
+///
+/// Returns
+/// -------
+/// List[Tuple[bool, str]]
+///   Whether the versions of the frame are equal and the JSON diff, in the order of the
+///   baseline followed by the frames only the candidate has.
+///
+/// Raises
+/// ------
+/// ValueError
+///   If a journal cannot be read.
+///
+#[pyfunction]
+#[pyo3(signature = (baseline, candidate, match_iou = 0.3, move_iou = 0.9))]
+pub fn diff_journals(
+    baseline: &str,
+    candidate: &str,
+    match_iou: f32,
+    move_iou: f32,
+) -> PyResult<Vec<(bool, String)>> {
+    let tolerance = ComparisonTolerance {
+        match_iou,
+        move_iou,
+    };
+    savant_core::replay::diff_journals(baseline, candidate, &tolerance)
+        .and_then(|diffs| {
+            diffs
+                .iter()
+                .map(|d| Ok((d.is_equal(), d.to_json()?)))
+                .collect()
+        })
+        .map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// Starts the background monitor sampling the root spans, the frame locations and the stage
+/// queues of the registered pipelines and the size of the KVS. Sizes growing at every sample
+/// over a horizon are logged and counted in the ``leak_suspect_counter`` metric. A running
+/// monitor is replaced.
+///
+/// Parameters
+/// ----------
+/// period_ms : int
+///   The sampling period in milliseconds.
+/// horizons_ms : List[int]
+///   The windows over which the growth is detected, in milliseconds.
+/// min_growth : int
+///   The minimum growth over a horizon to report it.
+///
+/// Raises
+/// ------
+/// ValueError
+///   If the period is 0 or a horizon covers less than two periods.
+///
+#[pyfunction]
+#[pyo3(signature = (period_ms = 60_000, horizons_ms = vec![3_600_000, 21_600_000], min_growth = 1))]
+pub fn enable_leak_detection(
+    period_ms: u64,
+    horizons_ms: Vec<u64>,
+    min_growth: u64,
+) -> PyResult<()> {
+    savant_core::leak_detection::enable_leak_detection(
+        savant_core::leak_detection::LeakDetectorConfig {
+            period: Duration::from_millis(period_ms),
+            horizons: horizons_ms.into_iter().map(Duration::from_millis).collect(),
+            min_growth,
+        },
+    )
+    .map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// Stops the leak detection monitor.
+///
+#[pyfunction]
+pub fn disable_leak_detection() {
+    savant_core::leak_detection::disable_leak_detection();
+}
+
+/// The growths detected by the running leak detection monitor.
+///
+/// Returns
+/// -------
+/// List[Tuple[str, int, int, int, int]]
+///   The resource, the horizon in milliseconds, the sizes at the start and the end of the
+///   horizon and the detection time in milliseconds since the UNIX epoch.
+///
+#[pyfunction]
+pub fn get_leak_suspects() -> Vec<(String, u64, u64, u64, u64)> {
+    savant_core::leak_detection::get_leak_suspects()
+        .into_iter()
+        .map(|s| {
+            (
+                s.resource,
+                s.horizon.as_millis() as u64,
+                s.from,
+                s.to,
+                s.detected_at,
+            )
+        })
+        .collect()
+}
+
 #[pyfunction]
 pub fn incremental_uuid_v7() -> String {
     savant_core::utils::uuid_v7::incremental_uuid_v7().to_string()
 }
+
+/// Sets the version of the uuids generated for new frames: 7 (the default) for time-ordered
+/// uuids carrying the creation time, 4 for random uuids.
+///
+/// Parameters
+/// ----------
+/// version : int
+///   The uuid version, 4 or 7.
+///
+#[pyfunction]
+#[pyo3(signature = (version = 7))]
+pub fn set_frame_uuid_version(version: u8) -> PyResult<()> {
+    let version = match version {
+        4 => FrameUuidVersion::V4,
+        7 => FrameUuidVersion::V7,
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "Unsupported frame uuid version: {version}"
+            )))
+        }
+    };
+    savant_core::utils::uuid_v7::set_frame_uuid_version(version);
+    Ok(())
+}
+
+/// The version of the uuids generated for new frames.
+///
+/// Returns
+/// -------
+/// int
+///
+#[pyfunction]
+pub fn get_frame_uuid_version() -> u8 {
+    match savant_core::utils::uuid_v7::get_frame_uuid_version() {
+        FrameUuidVersion::V4 => 4,
+        FrameUuidVersion::V7 => 7,
+    }
+}
+
+/// The time in milliseconds since the UNIX epoch the uuid was generated at.
+///
+/// Parameters
+/// ----------
+/// uuid : str
+///   The uuid.
+///
+/// Returns
+/// -------
+/// Optional[int]
+///   The time, ``None`` when the uuid is not a version 7 uuid.
+///
+#[pyfunction]
+pub fn uuid_v7_timestamp_ms(uuid: &str) -> PyResult<Option<u64>> {
+    let uuid = Uuid::parse_str(uuid).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok(savant_core::utils::uuid_v7::uuid_v7_timestamp_ms(&uuid))
+}
+
+/// The smallest and the largest version 7 uuids of the interval, both inclusive, to query the
+/// frames created in it from a store ordered by uuid.
+///
+/// Parameters
+/// ----------
+/// start_ms : int
+///   The interval start in milliseconds since the UNIX epoch.
+/// end_ms : int
+///   The interval end in milliseconds since the UNIX epoch.
+///
+/// Returns
+/// -------
+/// Tuple[str, str]
+///
+#[pyfunction]
+pub fn uuid_v7_range(start_ms: u64, end_ms: u64) -> (String, String) {
+    let (from, to) = savant_core::utils::uuid_v7::uuid_v7_range(start_ms, end_ms);
+    (from.to_string(), to.to_string())
+}
+
+/// Extracts the frames of the source covering the interval from the journal, together with
+/// the user data of the source. The clip starts at the last keyframe before the interval and
+/// ends before the first keyframe after it.
+///
+/// GIL management: the function is GIL-free.
+///
+/// Parameters
+/// ----------
+/// path : str
+///   The journal.
+/// source_id : str
+///   The source.
+/// start_ms : int
+///   The interval start, the frame PTS in milliseconds.
+/// end_ms : int
+///   The interval end, the frame PTS in milliseconds.
+///
+/// Returns
+/// -------
+/// List[:py:class:`savant_rs.utils.serialization.Message`]
+///   The messages of the clip, empty if the journal has no frames in the interval.
+///
+/// Raises
+/// ------
+/// ValueError
+///   If the journal cannot be read, the start is after the end or the clip exceeds 10000
+///   messages.
+///
+#[pyfunction]
+pub fn extract_clip(
+    path: &str,
+    source_id: &str,
+    start_ms: u64,
+    end_ms: u64,
+) -> PyResult<Vec<Message>> {
+    release_gil!(true, || {
+        savant_core::clip::extract_clip(path, source_id, start_ms, end_ms)
+    })
+    .map(|clip| clip.into_iter().map(Message).collect())
+    .map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// Sets the journal the ``/clips/{source_id}`` endpoint of the webserver extracts the clips
+/// from, ``None`` disables the endpoint.
+///
+#[pyfunction]
+#[pyo3(signature = (path=None))]
+pub fn set_clip_journal(path: Option<String>) {
+    savant_core::clip::set_clip_journal(path.map(PathBuf::from));
+}
+
+/// Sets the directory the journal and the output paths of the ``/jobs`` requests of the
+/// webserver are resolved in, ``None`` rejects the requests with the paths.
+///
+#[pyfunction]
+#[pyo3(signature = (path=None))]
+pub fn set_jobs_dir(path: Option<String>) {
+    savant_core::jobs::set_jobs_dir(path.map(PathBuf::from));
+}
+
+/// Reports the final disposition of a frame in a sink. The report is appended to the index of
+/// the journal set with :py:func:`set_clip_journal`, so the path of the frame from the source
+/// to the storage is reconstructed later with :py:func:`get_frame_provenance`.
+///
+/// Parameters
+/// ----------
+/// frame_uuid : str
+///   The uuid of the frame.
+/// sink : str
+///   The name of the sink.
+/// status : str
+///   ``exported``, ``skipped`` or ``failed``.
+/// stored_path : Optional[str]
+///   Where the sink stored the frame or the results.
+/// event_ids : List[str]
+///   The events the frame contributed to.
+///
+/// Raises
+/// ------
+/// ValueError
+///   If no journal is set, the uuid or the status are invalid or the index is not writable.
+///
+#[pyfunction]
+#[pyo3(signature = (frame_uuid, sink, status="exported", stored_path=None, event_ids=vec![]))]
+pub fn report_frame_disposition(
+    frame_uuid: &str,
+    sink: &str,
+    status: &str,
+    stored_path: Option<&str>,
+    event_ids: Vec<String>,
+) -> PyResult<()> {
+    let frame_uuid =
+        Uuid::parse_str(frame_uuid).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let status = status
+        .parse::<ExportStatus>()
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let mut disposition = Disposition::new(frame_uuid, sink, status).with_event_ids(event_ids);
+    if let Some(stored_path) = stored_path {
+        disposition = disposition.with_stored_path(stored_path);
+    }
+    savant_core::provenance::report_disposition(&disposition)
+        .map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// The dispositions of the frame reported to the journal set with :py:func:`set_clip_journal`.
+///
+/// Parameters
+/// ----------
+/// frame_uuid : str
+///   The uuid of the frame.
+///
+/// Returns
+/// -------
+/// List[Tuple[str, str, Optional[str], List[str], int]]
+///   The sink, the status, the stored path, the event ids and the report time in milliseconds
+///   since the UNIX epoch, in the order of the reports.
+///
+#[pyfunction]
+pub fn get_frame_provenance(
+    frame_uuid: &str,
+) -> PyResult<Vec<(String, String, Option<String>, Vec<String>, u64)>> {
+    let frame_uuid =
+        Uuid::parse_str(frame_uuid).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let dispositions = release_gil!(true, || savant_core::provenance::frame_provenance(
+        frame_uuid
+    ))
+    .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok(dispositions
+        .into_iter()
+        .map(|d| {
+            (
+                d.sink,
+                d.status.as_str().to_string(),
+                d.stored_path,
+                d.event_ids,
+                d.reported_at_ms,
+            )
+        })
+        .collect())
+}
+
+/// Drives the process time of the analytics by the PTS of the frames added to the pipelines
+/// instead of the system time, so recorded video processed faster than real time gets the
+/// same TTLs, latencies and FPS as live video. The time restarts at the origin. The pipelines
+/// configured with their own backfill clock are not affected.
+///
+/// Parameters
+/// ----------
+/// origin_ms : int
+///   The time of the zero PTS in milliseconds since the UNIX epoch, e.g. the start of the
+///   recording.
+///
+#[pyfunction]
+pub fn enable_backfill_clock(origin_ms: u64) {
+    savant_core::clock::set_clock_mode(savant_core::clock::ClockMode::Backfill { origin_ms });
+}
+
+/// Drives the time of the analytics by the system time, the default.
+///
+#[pyfunction]
+pub fn enable_live_clock() {
+    savant_core::clock::set_clock_mode(savant_core::clock::ClockMode::Live);
+}
+
+/// Returns the time of the analytics in milliseconds since the UNIX epoch.
+///
+#[pyfunction]
+pub fn get_clock_now_ms() -> u64 {
+    savant_core::clock::now_ms()
+}
+
+/// Returns the names of the registered frame content transcoders in the order of precedence.
+///
+#[pyfunction]
+pub fn registered_transcoders() -> Vec<String> {
+    savant_core::transcoding::registered_transcoders()
+}
+
+/// Loads the frames of a fixture file captured by a pipeline debug tap.
+///
+/// Parameters
+/// ----------
+/// path : str
+///   The fixture file.
+///
+/// Returns
+/// -------
+/// List[:py:class:`savant_rs.primitives.VideoFrame`]
+///   The captured frames.
+///
+/// Raises
+/// ------
+/// ValueError
+///   If the file cannot be read or does not hold video frames.
+///
+#[pyfunction]
+pub fn load_fixture(path: &str) -> PyResult<Vec<VideoFrame>> {
+    savant_core::pipeline::fixtures::load_fixture(path)
+        .map(|frames| frames.into_iter().map(VideoFrame).collect())
+        .map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// Generates the scaffolding of a regression test loading the fixture.
+///
+/// Parameters
+/// ----------
+/// path : str
+///   The fixture file.
+/// stage : str
+///   The stage the frames were captured at.
+/// test_name : str
+///   The name of the test function.
+/// language : str
+///   ``python`` or ``rust``.
+///
+/// Returns
+/// -------
+/// str
+///   The source code of the test.
+///
+/// Raises
+/// ------
+/// ValueError
+///   If the fixture cannot be loaded, the test name is not an identifier or the language
+///   is unknown.
+///
+#[pyfunction]
+#[pyo3(signature = (path, stage, test_name, language="python"))]
+pub fn generate_fixture_test(
+    path: &str,
+    stage: &str,
+    test_name: &str,
+    language: &str,
+) -> PyResult<String> {
+    let scaffold = FixtureScaffold::new(path, stage, test_name)
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    match language {
+        "python" => Ok(scaffold.python_test()),
+        "rust" => Ok(scaffold.rust_test()),
+        _ => Err(PyValueError::new_err(format!(
+            "Unknown test language: {}",
+            language
+        ))),
+    }
+}
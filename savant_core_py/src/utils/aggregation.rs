@@ -0,0 +1,193 @@
+use crate::match_query::MatchQuery;
+use crate::primitives::frame::VideoFrame;
+use crate::primitives::user_data::UserData;
+use pyo3::exceptions::PyValueError;
+use pyo3::{pyclass, pymethods, PyResult};
+use savant_core::aggregation as rust;
+use std::collections::HashMap;
+
+/// The statistics of a group for a closed window.
+///
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct WindowResult(rust::WindowResult);
+
+#[pymethods]
+impl WindowResult {
+    #[getter]
+    fn get_key(&self) -> Vec<String> {
+        self.0.key.clone()
+    }
+
+    #[getter]
+    fn get_start_ms(&self) -> u64 {
+        self.0.start_ms
+    }
+
+    #[getter]
+    fn get_end_ms(&self) -> u64 {
+        self.0.end_ms
+    }
+
+    /// The statistics by name, e.g. ``{"count": 3.0, "p95": 7.0}``.
+    ///
+    #[getter]
+    fn get_statistics(&self) -> HashMap<String, f64> {
+        self.0
+            .statistics
+            .iter()
+            .map(|(s, v)| (s.name(), *v))
+            .collect()
+    }
+
+    /// Builds a user data message with the ``key`` and ``window`` attributes and an attribute
+    /// per statistic in the namespace.
+    ///
+    /// Parameters
+    /// ----------
+    /// source_id : str
+    ///   The source of the message
+    /// namespace : str
+    ///   The namespace of the attributes
+    ///
+    /// Returns
+    /// -------
+    /// :py:class:`savant_rs.primitives.UserData`
+    ///
+    fn to_user_data(&self, source_id: &str, namespace: &str) -> UserData {
+        UserData(self.0.to_user_data(source_id, namespace))
+    }
+
+    /// Stores the statistics in the KVS as attributes named ``<key>/<statistic>``, the key
+    /// values are joined with ``/``.
+    ///
+    /// Parameters
+    /// ----------
+    /// namespace : str
+    ///   The namespace of the attributes
+    /// ttl : Optional[int]
+    ///   The TTL of the attributes in milliseconds
+    ///
+    #[pyo3(signature = (namespace, ttl = None))]
+    fn store_in_kvs(&self, namespace: &str, ttl: Option<u64>) {
+        self.0.store_in_kvs(namespace, ttl)
+    }
+}
+
+/// Computes windowed statistics of numeric attributes grouped by key. The timestamps are
+/// provided by the caller, windows are emitted by :py:meth:`close` once the time passes their
+/// end.
+///
+/// Parameters
+/// ----------
+/// window_ms : int
+///   The window size
+/// step_ms : Optional[int]
+///   The step of sliding windows, the windows are tumbling when not set
+/// statistics : List[str]
+///   ``count``, ``mean``, ``max`` or ``p<N>`` for the N-th percentile
+/// group_by : List[str]
+///   ``source``, ``class`` (``namespace.label`` of objects) or ``<namespace>/<name>`` of a
+///   string, integer or boolean attribute
+/// value : Optional[Tuple[str, str]]
+///   The namespace and name of the numeric attribute, every observation counts as 1 when not
+///   set
+/// objects : Optional[:py:class:`savant_rs.match_query.MatchQuery`]
+///   The objects observed by :py:meth:`observe_frame`, the frame is observed when not set
+///
+/// Raises
+/// ------
+/// ValueError
+///   If the configuration is invalid
+///
+#[pyclass]
+#[derive(Debug)]
+pub struct WindowedAggregator(rust::WindowedAggregator);
+
+#[pymethods]
+impl WindowedAggregator {
+    #[new]
+    #[pyo3(signature = (window_ms, statistics, group_by, step_ms = None, value = None, objects = None))]
+    fn new(
+        window_ms: u64,
+        statistics: Vec<String>,
+        group_by: Vec<String>,
+        step_ms: Option<u64>,
+        value: Option<(String, String)>,
+        objects: Option<MatchQuery>,
+    ) -> PyResult<Self> {
+        let window = match step_ms {
+            None => rust::Window::Tumbling { size_ms: window_ms },
+            Some(step_ms) => rust::Window::Sliding {
+                size_ms: window_ms,
+                step_ms,
+            },
+        };
+        let configuration = statistics
+            .iter()
+            .map(|s| s.parse::<rust::Statistic>())
+            .collect::<anyhow::Result<Vec<_>>>()
+            .and_then(|statistics| {
+                Ok(rust::AggregationConfiguration {
+                    window,
+                    statistics,
+                    group_by: group_by
+                        .iter()
+                        .map(|k| k.parse::<rust::GroupKey>())
+                        .collect::<anyhow::Result<Vec<_>>>()?,
+                    value,
+                    objects: objects.map(|q| q.0),
+                })
+            });
+        configuration
+            .and_then(rust::WindowedAggregator::new)
+            .map(Self)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// The number of observations dropped because their windows were already emitted.
+    ///
+    #[getter]
+    fn get_late(&self) -> u64 {
+        self.0.get_late()
+    }
+
+    /// Adds the value of the group to the windows covering the timestamp.
+    ///
+    /// Returns
+    /// -------
+    /// bool
+    ///   False if the observation is late
+    ///
+    fn observe(&mut self, key: Vec<String>, value: f64, ts_ms: u64) -> bool {
+        self.0.observe(key, value, ts_ms)
+    }
+
+    /// Observes the frame or its objects according to the configuration.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    ///   The number of observations made
+    ///
+    fn observe_frame(&mut self, frame: &VideoFrame, ts_ms: u64) -> usize {
+        self.0.observe_frame(&frame.0, ts_ms)
+    }
+
+    /// Emits the windows ending at or before the time.
+    ///
+    /// Returns
+    /// -------
+    /// List[WindowResult]
+    ///   The results ordered by the window start and key
+    ///
+    fn close(&mut self, now_ms: u64) -> Vec<WindowResult> {
+        self.0.close(now_ms).into_iter().map(WindowResult).collect()
+    }
+
+    /// Emits all windows, including the open ones.
+    ///
+    fn flush(&mut self) -> Vec<WindowResult> {
+        self.0.flush().into_iter().map(WindowResult).collect()
+    }
+}
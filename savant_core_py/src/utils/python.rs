@@ -66,6 +66,7 @@ macro_rules! with_trace {
         #[allow(clippy::redundant_closure_call)]
         let res = $expression();
         let elapsed = start.elapsed();
+        savant_core::pipeline::user_code::account_rust_time(elapsed);
         $crate::logging::log_message(
             $crate::logging::LogLevel::Trace,
             "savant::trace",
@@ -121,6 +122,8 @@ macro_rules! release_gil {
                 let elapsed_gil_back = start_gil_back.elapsed();
                 (res, elapsed_nogil, elapsed_gil_back)
             });
+            savant_core::pipeline::user_code::account_rust_time(elapsed_nogil);
+            savant_core::pipeline::user_code::account_gil_wait_time(elapsed_gil_back);
             let gf = i64::try_from(elapsed_nogil.as_nanos()).unwrap_or(i64::MAX);
             let gw = i64::try_from(elapsed_gil_back.as_nanos()).unwrap_or(i64::MAX);
             $crate::logging::log_message(
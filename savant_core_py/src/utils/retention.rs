@@ -0,0 +1,177 @@
+use crate::release_gil;
+use pyo3::exceptions::PyValueError;
+use pyo3::{pyclass, pymethods, PyResult};
+use savant_core::retention as rust;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+/// The outcome of :py:meth:`RetentionManager.enforce`.
+///
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct RetentionReport(rust::RetentionReport);
+
+#[pymethods]
+impl RetentionReport {
+    /// The deleted files as ``(path, size, reason)``, the reason is ``age`` or ``size``.
+    ///
+    #[getter]
+    fn get_deleted(&self) -> Vec<(String, u64, String)> {
+        self.0
+            .deleted
+            .iter()
+            .map(|f| {
+                (
+                    f.path.to_string_lossy().to_string(),
+                    f.size,
+                    f.reason.as_str().to_string(),
+                )
+            })
+            .collect()
+    }
+
+    #[getter]
+    fn get_freed_bytes(&self) -> u64 {
+        self.0.freed_bytes
+    }
+
+    #[getter]
+    fn get_remaining_bytes(&self) -> u64 {
+        self.0.remaining_bytes
+    }
+
+    /// True when the pinned files and the active clip journal exceed the size limit.
+    ///
+    #[getter]
+    fn get_over_limit(&self) -> bool {
+        self.0.over_limit
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", &self.0)
+    }
+}
+
+/// Deletes the recorded journals and the exported clips exceeding the age or the size limit,
+/// the oldest files first. The pinned files and the active clip journal are kept.
+///
+/// Parameters
+/// ----------
+/// name : str
+///   The name of the manager in the metrics
+/// directories : List[str]
+///   The directories scanned recursively
+/// max_age_ms : Optional[int]
+///   The age of the files by the modification time
+/// max_size : Optional[int]
+///   The total size of the files in bytes
+/// patterns : List[str]
+///   The glob patterns of the file names managed, all the files when empty
+///
+/// Raises
+/// ------
+/// ValueError
+///   If no directories are given or a pattern is invalid
+///
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct RetentionManager(Arc<rust::RetentionManager>);
+
+#[pymethods]
+impl RetentionManager {
+    #[new]
+    #[pyo3(signature = (name, directories, max_age_ms = None, max_size = None, patterns = vec![]))]
+    fn new(
+        name: &str,
+        directories: Vec<String>,
+        max_age_ms: Option<u64>,
+        max_size: Option<u64>,
+        patterns: Vec<String>,
+    ) -> PyResult<Self> {
+        let policy = rust::RetentionPolicy {
+            max_age: max_age_ms.map(Duration::from_millis),
+            max_size,
+            patterns,
+        };
+        rust::RetentionManager::new(
+            name,
+            directories.into_iter().map(PathBuf::from).collect(),
+            policy,
+        )
+        .map(|m| Self(Arc::new(m)))
+        .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    #[getter]
+    fn get_name(&self) -> String {
+        self.0.get_name().to_string()
+    }
+
+    /// Keeps the file, e.g. a segment with an event which is not uploaded yet.
+    ///
+    /// Parameters
+    /// ----------
+    /// path : str
+    /// until_ms : Optional[int]
+    ///   The time the pin expires in milliseconds since the UNIX epoch, the file is kept
+    ///   until :py:meth:`unpin` when not set
+    ///
+    #[pyo3(signature = (path, until_ms = None))]
+    fn pin(&self, path: &str, until_ms: Option<u64>) {
+        self.0.pin(
+            path,
+            until_ms.map(|ms| SystemTime::UNIX_EPOCH + Duration::from_millis(ms)),
+        );
+    }
+
+    /// Returns
+    /// -------
+    /// bool
+    ///   False if the file is not pinned
+    ///
+    fn unpin(&self, path: &str) -> bool {
+        self.0.unpin(path)
+    }
+
+    /// The pinned files.
+    ///
+    #[getter]
+    fn get_pins(&self) -> Vec<String> {
+        self.0
+            .get_pins()
+            .into_iter()
+            .map(|(p, _)| p.to_string_lossy().to_string())
+            .collect()
+    }
+
+    /// Deletes the files violating the policy.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If a directory cannot be scanned
+    ///
+    fn enforce(&self) -> PyResult<RetentionReport> {
+        release_gil!(true, || self.0.enforce())
+            .map(RetentionReport)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Enforces the policy in a background thread every period, replacing a running one.
+    ///
+    fn start(&self, period_ms: u64) -> PyResult<()> {
+        self.0
+            .start(Duration::from_millis(period_ms))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn stop(&self) {
+        release_gil!(true, || self.0.stop())
+    }
+
+    #[getter]
+    fn get_is_running(&self) -> bool {
+        self.0.is_running()
+    }
+}
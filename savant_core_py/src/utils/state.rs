@@ -0,0 +1,183 @@
+use crate::{release_gil, with_gil};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use savant_core::state as rust;
+use savant_core::state::StatefulProcessor;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+struct PyStatefulProcessor(PyObject);
+
+impl StatefulProcessor for PyStatefulProcessor {
+    fn save_state(&self) -> anyhow::Result<Vec<u8>> {
+        with_gil!(|py| {
+            let res = self.0.call_method0(py, "save_state")?;
+            Ok(res.extract::<Vec<u8>>(py)?)
+        })
+    }
+
+    fn load_state(&self, state: &[u8]) -> anyhow::Result<()> {
+        with_gil!(|py| {
+            self.0
+                .call_method1(py, "load_state", (PyBytes::new(py, state),))?;
+            Ok(())
+        })
+    }
+}
+
+/// Saves and restores the states of the processors, e.g. trackers, counters and galleries,
+/// so they survive the restarts of the process and move between the shards with the sources.
+/// A processor is an object with the ``save_state() -> bytes`` and ``load_state(bytes)``
+/// methods, the state is usually a serialized protobuf message.
+///
+/// Parameters
+/// ----------
+/// name : str
+///   The name of the manager in the metrics
+/// directory : Optional[str]
+///   The directory the states are stored in as ``<processor>.state`` files
+/// kvs_namespace : Optional[str]
+///   The KVS namespace the states are stored in as blobs
+///
+/// Raises
+/// ------
+/// ValueError
+///   If not exactly one of the directory and the KVS namespace is set
+///
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct StateManager(Arc<rust::StateManager>);
+
+#[pymethods]
+impl StateManager {
+    #[new]
+    #[pyo3(signature = (name, directory = None, kvs_namespace = None))]
+    fn new(name: &str, directory: Option<String>, kvs_namespace: Option<String>) -> PyResult<Self> {
+        let store = match (directory, kvs_namespace) {
+            (Some(dir), None) => rust::StateStore::Directory(PathBuf::from(dir)),
+            (None, Some(ns)) => rust::StateStore::Kvs(ns),
+            _ => {
+                return Err(PyValueError::new_err(
+                    "Either the directory or the KVS namespace must be set",
+                ))
+            }
+        };
+        Ok(Self(Arc::new(rust::StateManager::new(name, store))))
+    }
+
+    #[getter]
+    fn get_name(&self) -> String {
+        self.0.get_name().to_string()
+    }
+
+    /// Parameters
+    /// ----------
+    /// name : str
+    ///   The key of the state in the store, unique within the manager
+    /// processor : object
+    ///   The object with the ``save_state`` and ``load_state`` methods
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the name is registered or is not a valid file name
+    ///
+    fn register(&self, name: &str, processor: PyObject) -> PyResult<()> {
+        self.0
+            .register(name, Arc::new(PyStatefulProcessor(processor)))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn unregister(&self, name: &str) -> bool {
+        self.0.unregister(name)
+    }
+
+    #[getter]
+    fn get_processors(&self) -> Vec<String> {
+        self.0.get_processors()
+    }
+
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the processor is not registered or the state cannot be saved
+    ///
+    fn save(&self, name: &str) -> PyResult<()> {
+        release_gil!(true, || self.0.save(name))
+            .map_err(|e| PyValueError::new_err(format!("{:#}", e)))
+    }
+
+    /// Saves the states of all the processors, the failures are logged.
+    ///
+    /// Returns
+    /// -------
+    /// int
+    ///   The number of the states saved
+    ///
+    fn save_all(&self) -> usize {
+        release_gil!(true, || self.0.save_all())
+    }
+
+    /// Returns
+    /// -------
+    /// bool
+    ///   False if no state is stored
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the processor is not registered or the state cannot be loaded
+    ///
+    fn restore(&self, name: &str) -> PyResult<bool> {
+        release_gil!(true, || self.0.restore(name))
+            .map_err(|e| PyValueError::new_err(format!("{:#}", e)))
+    }
+
+    /// Returns
+    /// -------
+    /// List[str]
+    ///   The processors restored
+    ///
+    fn restore_all(&self) -> PyResult<Vec<String>> {
+        release_gil!(true, || self.0.restore_all())
+            .map_err(|e| PyValueError::new_err(format!("{:#}", e)))
+    }
+
+    /// The state of the processor to pass to the shard the source moves to.
+    ///
+    fn export_state(&self, name: &str) -> PyResult<PyObject> {
+        let state = release_gil!(true, || self.0.export_state(name))
+            .map_err(|e| PyValueError::new_err(format!("{:#}", e)))?;
+        with_gil!(|py| Ok(PyObject::from(PyBytes::new(py, &state))))
+    }
+
+    /// Loads the state exported by :py:meth:`export_state` into the processor registered with
+    /// the same name.
+    ///
+    fn import_state(&self, name: &str, state: &Bound<'_, PyBytes>) -> PyResult<()> {
+        let state = state.as_bytes().to_vec();
+        release_gil!(true, || self.0.import_state(name, &state))
+            .map_err(|e| PyValueError::new_err(format!("{:#}", e)))
+    }
+
+    /// Saves the states in a background thread every period, replacing a running one. The
+    /// GIL is released while the running thread is joined.
+    ///
+    fn start(&self, period_ms: u64) -> PyResult<()> {
+        release_gil!(true, || self.0.start(Duration::from_millis(period_ms)))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Stops the background thread and saves the states.
+    ///
+    fn stop(&self) {
+        release_gil!(true, || self.0.stop())
+    }
+
+    #[getter]
+    fn get_is_running(&self) -> bool {
+        self.0.is_running()
+    }
+}
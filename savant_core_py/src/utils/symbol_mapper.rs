@@ -1,7 +1,7 @@
 use crate::release_gil;
 use lazy_static::lazy_static;
 use parking_lot::const_mutex;
-use parking_lot::Mutex;
+use parking_lot::{Mutex, MutexGuard};
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use savant_core::rust;
@@ -70,6 +70,10 @@ pub fn get_model_id_py(model_name: &str) -> PyResult<i64> {
         .map_err(|e| PyValueError::new_err(e.to_string()))
 }
 
+pub(crate) fn lock_symbol_mapper() -> MutexGuard<'static, SymbolMapper> {
+    SYMBOL_MAPPER.lock()
+}
+
 pub fn get_model_id(model_name: &str) -> anyhow::Result<i64> {
     let mut mapper = SYMBOL_MAPPER.lock();
     mapper.get_model_id(model_name)
@@ -194,6 +198,84 @@ pub fn get_object_label_py(model_id: i64, object_id: i64) -> Option<String> {
     get_object_label(model_id, object_id)
 }
 
+/// The function sets the localized display labels of a registered object class. The labels
+/// replace the ones set before.
+///
+/// Parameters
+/// ----------
+/// model_name : str
+///   The name of the model.
+/// object_label : str
+///   The label of the object.
+/// labels : dict[str, str]
+///   The display labels by locale, e.g. ``{"en": "Car", "pt-BR": "Carro"}``.
+///
+/// Raises
+/// ------
+/// ValueError
+///   if the object is not registered or a locale is empty
+///
+#[pyfunction]
+#[pyo3(name = "set_display_labels")]
+pub fn set_display_labels_py(
+    model_name: &str,
+    object_label: &str,
+    labels: HashMap<String, String>,
+) -> PyResult<()> {
+    let mut mapper = SYMBOL_MAPPER.lock();
+    mapper
+        .set_display_labels(model_name, object_label, &labels)
+        .map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// The function returns the localized display labels of the object class.
+///
+/// Parameters
+/// ----------
+/// model_id : int
+///   The id of the model.
+/// object_id : int
+///   The id of the object.
+///
+/// Returns
+/// -------
+/// dict[str, str]
+///   The display labels by the normalized (lowercase, ``-``-separated) locale
+///
+#[pyfunction]
+#[pyo3(name = "get_display_labels")]
+pub fn get_display_labels_py(model_id: i64, object_id: i64) -> HashMap<String, String> {
+    let mapper = SYMBOL_MAPPER.lock();
+    mapper.get_display_labels(model_id, object_id)
+}
+
+/// The function returns the display label of the object for the locale. The lookup falls
+/// back from the regional locale (``pt-BR``) to the language (``pt``) and then to the
+/// registered label.
+///
+/// Parameters
+/// ----------
+/// model_id : int
+///   The id of the model.
+/// object_id : int
+///   The id of the object.
+/// locale : str
+///   The locale, e.g. ``en`` or ``pt_BR``.
+///
+/// Returns
+/// -------
+/// str
+///   The display label
+/// None
+///   If the object is not registered
+///
+#[pyfunction]
+#[pyo3(name = "get_display_label")]
+pub fn get_display_label_py(model_id: i64, object_id: i64, locale: &str) -> Option<String> {
+    let mapper = SYMBOL_MAPPER.lock();
+    mapper.get_display_label(model_id, object_id, locale)
+}
+
 /// The function allows getting the object labels by their ids (bulk operation).
 ///
 /// Parameters
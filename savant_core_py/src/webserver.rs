@@ -2,18 +2,38 @@ pub mod kvs;
 
 use pyo3::exceptions::{PySystemError, PyValueError};
 use pyo3::prelude::*;
-use savant_core::webserver::PipelineStatus;
+use pyo3::types::PyBytes;
+use savant_core::webserver::audit::AuditQuery;
+use savant_core::webserver::diagnostics::{build_diagnostics_bundle, DiagnosticsOptions};
+use savant_core::webserver::{PipelineStatus, WebserverConfig};
+use std::sync::Arc;
 
-/// Starts embedded webserver providing status, shutdown and metrics features.
+/// Starts embedded webserver providing status, shutdown and metrics features. Calling the
+/// function again with the same parameters does nothing, with different parameters restarts
+/// the webserver.
 ///
 /// Parameters
 /// ----------
 /// port : int
+/// host : str
+///   The address to bind to
+/// workers : Optional[int]
+///   The number of worker threads, the number of physical CPU cores by default
+///
+/// Raises
+/// ------
+/// SystemError
+///   If the address cannot be bound (e.g. the port is in use)
 ///
 #[pyfunction]
-pub fn init_webserver(port: u16) -> PyResult<()> {
-    savant_core::webserver::init_webserver(port)
-        .map_err(|e| PySystemError::new_err(e.to_string()))?;
+#[pyo3(signature = (port, host="0.0.0.0".to_string(), workers=None))]
+pub fn init_webserver(port: u16, host: String, workers: Option<usize>) -> PyResult<()> {
+    savant_core::webserver::init_webserver_with_config(WebserverConfig {
+        host,
+        port,
+        workers,
+    })
+    .map_err(|e| PySystemError::new_err(e.to_string()))?;
     Ok(())
 }
 
@@ -25,6 +45,50 @@ pub fn stop_webserver() -> PyResult<()> {
     Ok(())
 }
 
+/// Starts the control socket: a Unix socket serving the pipeline status, health, topology,
+/// stage operations and the KVS with length-delimited protobuf messages, for the components
+/// on the same host which cannot embed the library. A running control socket is stopped.
+///
+/// Parameters
+/// ----------
+/// path : str
+///   The path of the socket file, a stale socket file is replaced
+/// mode : Optional[int]
+///   The permissions of the socket file, e.g. ``0o660``
+///
+/// Raises
+/// ------
+/// SystemError
+///   If the socket cannot be bound
+///
+#[cfg(unix)]
+#[pyfunction]
+#[pyo3(signature = (path, mode=None))]
+pub fn start_control_socket(path: String, mode: Option<u32>) -> PyResult<()> {
+    savant_core::webserver::control_socket::start_control_socket(std::path::Path::new(&path), mode)
+        .map_err(|e| PySystemError::new_err(e.to_string()))
+}
+
+/// Stops the control socket and removes the socket file.
+///
+#[cfg(unix)]
+#[pyfunction]
+pub fn stop_control_socket() {
+    savant_core::webserver::control_socket::stop_control_socket();
+}
+
+/// Returns the protobuf schema of the control socket messages, to generate the clients.
+///
+/// Returns
+/// -------
+/// str
+///
+#[cfg(unix)]
+#[pyfunction]
+pub fn control_socket_proto() -> &'static str {
+    savant_core::webserver::control_socket::CONTROL_SOCKET_PROTO
+}
+
 /// Sets the token to be used to shut down the webserver.
 ///
 /// Parameters
@@ -54,8 +118,156 @@ pub fn set_status_running() -> PyResult<()> {
         .map_err(|e| PyValueError::new_err(e.to_string()))
 }
 
+/// Sets the signal sent to the process when the shutdown is requested with the ``signal``
+/// mode, ``SIGINT`` by default. The signal can be set once.
+///
+/// Parameters
+/// ----------
+/// signal : int
+///
+/// Raises
+/// ------
+/// ValueError
+///   If the signal is invalid, already set or signals are not supported on the platform
+///   (Windows), use :py:func:`set_shutdown_callback` there
+///
 #[pyfunction]
 pub fn set_shutdown_signal(signal: i32) -> PyResult<()> {
     savant_core::webserver::set_shutdown_signal(signal)
         .map_err(|e| PyValueError::new_err(e.to_string()))
 }
+
+/// Sets the function called instead of sending the shutdown signal when the shutdown is
+/// requested with the ``signal`` mode, the only delivery mechanism on Windows. The function is
+/// called on a webserver thread, e.g. it may call ``_thread.interrupt_main()``.
+///
+/// Parameters
+/// ----------
+/// callback : Optional[Callable[[], None]]
+///   The function, ``None`` restores the signal
+///
+#[pyfunction]
+#[pyo3(signature = (callback=None))]
+pub fn set_shutdown_callback(callback: Option<PyObject>) {
+    let callback = callback.map(|callback| -> savant_core::webserver::ShutdownCallback {
+        Arc::new(move || {
+            Python::with_gil(|py| {
+                if let Err(e) = callback.call0(py) {
+                    log::error!(target: "savant_rs::webserver", "The shutdown callback failed: {}", e);
+                }
+            })
+        })
+    });
+    savant_core::webserver::set_shutdown_callback(callback);
+}
+
+/// Configures the audit log of control-plane operations.
+///
+/// Parameters
+/// ----------
+/// path : Optional[str]
+///   The file the records are persisted to, the records are kept in memory only when ``None``
+/// capacity : int
+///   The number of the most recent records kept
+///
+#[pyfunction]
+#[pyo3(signature = (path=None, capacity=1000))]
+pub fn configure_audit_log(path: Option<String>, capacity: usize) -> PyResult<()> {
+    savant_core::webserver::audit::configure_audit_log(
+        path.as_ref().map(std::path::Path::new),
+        capacity,
+    )
+    .map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// Records a control-plane operation (e.g. a configuration reload) in the audit log.
+///
+/// Parameters
+/// ----------
+/// operation : str
+/// details : str
+/// requester : str
+/// accepted : bool
+///
+/// Returns
+/// -------
+/// int
+///   The sequence id of the record
+///
+#[pyfunction]
+#[pyo3(signature = (operation, details, requester="local", accepted=true))]
+pub fn audit_operation(operation: &str, details: &str, requester: &str, accepted: bool) -> u64 {
+    savant_core::webserver::audit::audit(operation, requester, details, accepted).seq_id
+}
+
+/// Returns the audit records, the oldest first.
+///
+/// Parameters
+/// ----------
+/// operation : Optional[str]
+/// since_ms : Optional[int]
+/// limit : Optional[int]
+///   The maximum number of the most recent matching records
+///
+/// Returns
+/// -------
+/// List[Tuple[int, int, str, str, str, bool]]
+///   The sequence id, the timestamp in milliseconds, the operation, the requester, the
+///   details and whether the operation was accepted
+///
+#[pyfunction]
+#[pyo3(signature = (operation=None, since_ms=None, limit=None))]
+pub fn query_audit_log(
+    operation: Option<String>,
+    since_ms: Option<u64>,
+    limit: Option<usize>,
+) -> Vec<(u64, u64, String, String, String, bool)> {
+    savant_core::webserver::audit::query_audit_log(&AuditQuery {
+        operation,
+        since_ms,
+        limit,
+    })
+    .into_iter()
+    .map(|r| {
+        (
+            r.seq_id,
+            r.timestamp_ms,
+            r.operation,
+            r.requester,
+            r.details,
+            r.accepted,
+        )
+    })
+    .collect()
+}
+
+/// Assembles the diagnostics bundle served at ``/diagnostics``: a gzipped tar archive with
+/// the status, the topology, the queues, the metrics, the configuration and the recent logs.
+///
+/// Parameters
+/// ----------
+/// frames : int
+///   The number of frames per stage whose metadata is included, at most 16
+///
+/// Returns
+/// -------
+/// bytes
+///
+/// Raises
+/// ------
+/// SystemError
+///   If the bundle cannot be assembled
+///
+#[pyfunction]
+#[pyo3(signature = (frames=0))]
+pub fn diagnostics_bundle(py: Python<'_>, frames: usize) -> PyResult<Py<PyBytes>> {
+    let options = DiagnosticsOptions {
+        sampled_frames: frames,
+    };
+    let bundle = py
+        .allow_threads(|| {
+            savant_core::get_or_init_async_runtime().block_on(build_diagnostics_bundle(&options))
+        })
+        .map_err(|e| PySystemError::new_err(e.to_string()))?;
+    Ok(PyBytes::new(py, &bundle).unbind())
+}
@@ -1,12 +1,21 @@
 use crate::primitives::attribute::Attribute;
 use crate::{release_gil, with_gil};
+use parking_lot::Mutex;
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use pyo3::types::PyBytes;
+use savant_core::leader_election::{
+    LeaderElector as RustLeaderElector, LeaderElectorConfig, LeaseStore,
+};
 use savant_core::primitives::rust;
 use savant_core::primitives::rust::AttributeSet;
 use savant_core::protobuf::ToProtobuf;
 use savant_core::webserver::kvs::synchronous as sync_kvs;
+use savant_core::webserver::kvs::{KvsBlob, MergePolicy, CONTENT_TYPE_BYTES};
+use savant_core::webserver::lease::synchronous as sync_lease;
+use savant_core::webserver::lease::LeaseError;
+use std::str::FromStr;
+use std::time::Duration;
 
 /// Set attributes in the key-value store.
 ///
@@ -133,6 +142,156 @@ pub fn del_attribute(ns: &str, name: &str) -> Option<Attribute> {
     sync_kvs::del_attribute(ns, name).map(Attribute)
 }
 
+fn blob_to_py(blob: KvsBlob) -> PyResult<(String, PyObject)> {
+    with_gil!(|py| {
+        let bytes = PyBytes::new(py, &blob.data);
+        Ok((blob.content_type, PyObject::from(bytes)))
+    })
+}
+
+/// Set a blob (raw bytes or a JSON document) in the key-value store. Blobs are stored
+/// separately from attributes.
+///
+/// Parameters
+/// ----------
+/// ns : str
+///  Namespace to set.
+///
+/// name : str
+///  Name to set.
+///
+/// data : bytes
+///  The blob content.
+///
+/// content_type : str
+///  The content type, ``application/json`` documents are validated.
+///
+/// ttl : Optional[int]
+///  Time-to-live for the blob.
+///
+/// Raises
+/// ------
+/// ValueError
+///  If the blob is too large or is not a valid JSON document.
+///
+#[pyfunction]
+#[pyo3(signature = (ns, name, data, content_type=CONTENT_TYPE_BYTES, ttl=None))]
+pub fn set_blob(
+    ns: &str,
+    name: &str,
+    data: &Bound<'_, PyBytes>,
+    content_type: &str,
+    ttl: Option<u64>,
+) -> PyResult<()> {
+    let blob = KvsBlob::new(content_type, data.as_bytes().to_vec());
+    sync_kvs::set_blob(ns, name, blob, ttl).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// Get a blob from the key-value store.
+///
+/// Parameters
+/// ----------
+/// ns : str
+///  Namespace to get from.
+///
+/// name : str
+///  Name to get.
+///
+/// Returns
+/// -------
+/// Optional[Tuple[str, bytes]]
+///  The content type and the content of the blob found.
+///
+#[pyfunction]
+pub fn get_blob(ns: &str, name: &str) -> PyResult<Option<(String, PyObject)>> {
+    sync_kvs::get_blob(ns, name).map(blob_to_py).transpose()
+}
+
+/// Delete a blob from the key-value store.
+///
+/// Parameters
+/// ----------
+/// ns : str
+///  Namespace to delete from.
+///
+/// name : str
+///  Name to delete.
+///
+/// Returns
+/// -------
+/// Optional[Tuple[str, bytes]]
+///  The content type and the content of the blob deleted.
+///
+#[pyfunction]
+pub fn del_blob(ns: &str, name: &str) -> PyResult<Option<(String, PyObject)>> {
+    sync_kvs::del_blob(ns, name).map(blob_to_py).transpose()
+}
+
+/// Search for blob keys in the key-value store.
+///
+/// Parameters
+/// ----------
+/// ns : Optional[str]
+///  Namespace to search for (Glob). None means "*".
+///
+/// name : Optional[str]
+///  Name to search for (Glob). None means "*".
+///
+/// Returns
+/// -------
+/// List[Tuple[str, str]]
+///  List of keys found.
+///
+#[pyfunction]
+#[pyo3(signature = (ns=None, name=None))]
+pub fn search_blob_keys(ns: Option<String>, name: Option<String>) -> Vec<(String, String)> {
+    sync_kvs::search_blob_keys(&ns, &name)
+}
+
+/// Export the attributes and the blobs stored in the key-value store, e.g. to capture the
+/// operational state before an upgrade.
+///
+/// Returns
+/// -------
+/// bytes
+///  The serialized snapshot.
+///
+#[pyfunction]
+pub fn export_snapshot() -> PyResult<PyObject> {
+    let snapshot = sync_kvs::export_snapshot();
+    with_gil!(|py| Ok(PyObject::from(PyBytes::new(py, &snapshot))))
+}
+
+/// Import a snapshot produced by :py:func:`export_snapshot`.
+///
+/// Parameters
+/// ----------
+/// snapshot : bytes
+///  The serialized snapshot.
+///
+/// merge_policy : str
+///  ``replace`` clears the store first, ``overwrite`` replaces the existing records with the
+///  same keys, ``keep_existing`` imports only the missing keys.
+///
+/// Returns
+/// -------
+/// int
+///  The number of imported records.
+///
+/// Raises
+/// ------
+/// ValueError
+///  If the policy is unknown or the snapshot is invalid.
+///
+#[pyfunction]
+#[pyo3(signature = (snapshot, merge_policy="overwrite"))]
+pub fn import_snapshot(snapshot: &Bound<'_, PyBytes>, merge_policy: &str) -> PyResult<usize> {
+    let policy =
+        MergePolicy::from_str(merge_policy).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    sync_kvs::import_snapshot(snapshot.as_bytes(), policy)
+        .map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
 /// Serialize a list of attributes to a byte buffer.
 ///
 /// Parameters
@@ -192,3 +351,154 @@ pub fn deserialize_attributes(serialized: &Bound<'_, PyBytes>) -> PyResult<Vec<A
         AttributeSet::deserialize(bytes).map_err(|e| PyValueError::new_err(e.to_string()))?;
     Ok(unsafe { std::mem::transmute::<Vec<rust::Attribute>, Vec<Attribute>>(attributes) })
 }
+
+/// Acquire or renew a lease of the key-value store. The lease is stored in the ``leases``
+/// namespace.
+///
+/// Parameters
+/// ----------
+/// name : str
+///  The lease name.
+///
+/// holder : str
+///  The identity of the replica.
+///
+/// ttl_ms : int
+///  How long the lease is held without a renewal.
+///
+/// Returns
+/// -------
+/// Optional[int]
+///  The fencing token of the lease, None if the lease is held by another holder.
+///
+#[pyfunction]
+pub fn acquire_lease(name: &str, holder: &str, ttl_ms: u64) -> PyResult<Option<u64>> {
+    match sync_lease::acquire_lease(name, holder, Duration::from_millis(ttl_ms)) {
+        Ok(lease) => Ok(Some(lease.token)),
+        Err(LeaseError::Held(_)) => Ok(None),
+        Err(e) => Err(PyValueError::new_err(e.to_string())),
+    }
+}
+
+/// Release a lease held by the holder.
+///
+/// Returns
+/// -------
+/// bool
+///  False if the lease is not held by the holder.
+///
+#[pyfunction]
+pub fn release_lease(name: &str, holder: &str) -> bool {
+    sync_lease::release_lease(name, holder).is_ok()
+}
+
+/// Get a lease, expired leases are returned too.
+///
+/// Returns
+/// -------
+/// Optional[Tuple[str, int, int]]
+///  The holder, the fencing token and the expiration time in milliseconds since the epoch.
+///
+#[pyfunction]
+pub fn get_lease(name: &str) -> Option<(String, u64, u64)> {
+    sync_lease::get_lease(name).map(|l| (l.holder, l.token, l.expires_at))
+}
+
+/// Check the fencing token presented by a writer, only the token of the live lease is
+/// accepted.
+///
+#[pyfunction]
+pub fn is_current_token(name: &str, token: u64) -> bool {
+    sync_lease::is_current_token(name, token)
+}
+
+/// Persist the token counter of the leases in the file, so the fencing tokens keep growing
+/// across restarts. Must be called before the leases are acquired.
+///
+/// Raises
+/// ------
+/// ValueError
+///  If the file cannot be read or written.
+///
+#[pyfunction]
+pub fn set_lease_token_file(path: &str) -> PyResult<()> {
+    savant_core::webserver::lease::set_lease_token_file(std::path::Path::new(path))
+        .map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// Elects a leader among the replicas sharing a key-value store, the lease is acquired and
+/// renewed in the background.
+///
+/// Parameters
+/// ----------
+/// name : str
+///  The name of the singleton task.
+///
+/// holder : str
+///  The identity of the replica, unique among the replicas.
+///
+/// ttl_ms : int
+///  How long the lease is held without a renewal.
+///
+/// renew_interval_ms : int
+///  How often the lease is renewed, must be shorter than the TTL.
+///
+/// url : Optional[str]
+///  The webserver of the process holding the shared store, e.g. ``http://coordinator:8080``.
+///  The store of this process is used when not set.
+///
+/// Raises
+/// ------
+/// ValueError
+///  If the renew interval is not shorter than the TTL.
+///
+#[pyclass]
+pub struct LeaderElector(Mutex<RustLeaderElector>);
+
+#[pymethods]
+impl LeaderElector {
+    #[new]
+    #[pyo3(signature = (name, holder, ttl_ms, renew_interval_ms, url=None))]
+    fn new(
+        name: String,
+        holder: String,
+        ttl_ms: u64,
+        renew_interval_ms: u64,
+        url: Option<String>,
+    ) -> PyResult<Self> {
+        let elector = RustLeaderElector::start(LeaderElectorConfig {
+            name,
+            holder,
+            ttl: Duration::from_millis(ttl_ms),
+            renew_interval: Duration::from_millis(renew_interval_ms),
+            store: url.map(LeaseStore::Remote).unwrap_or(LeaseStore::Local),
+        })
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Self(Mutex::new(elector)))
+    }
+
+    /// Whether the replica holds the lease.
+    ///
+    fn is_leader(&self) -> bool {
+        self.0.lock().is_leader()
+    }
+
+    /// The fencing token of the lease while the replica is the leader.
+    ///
+    /// Returns
+    /// -------
+    /// Optional[int]
+    ///
+    fn get_fencing_token(&self) -> Option<u64> {
+        self.0.lock().get_lease().map(|l| l.token)
+    }
+
+    /// Stops the renewals and releases the lease.
+    ///
+    /// GIL management: the function is GIL-free.
+    ///
+    fn stop(&self) -> PyResult<()> {
+        release_gil!(true, || self.0.lock().stop())
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+}
@@ -17,6 +17,12 @@ use savant_core::transport::zeromq;
 #[pyclass]
 pub struct BlockingWriter(Option<zeromq::SyncWriter>, WriterConfig);
 
+impl BlockingWriter {
+    pub(crate) fn get_writer(&self) -> Option<zeromq::SyncWriter> {
+        self.0.clone()
+    }
+}
+
 #[pymethods]
 impl BlockingWriter {
     #[new]
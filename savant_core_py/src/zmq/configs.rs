@@ -1,6 +1,7 @@
 use crate::zmq::basic_types::{ReaderSocketType, TopicPrefixSpec, WriterSocketType};
 use pyo3::exceptions::PyValueError;
 use pyo3::{pyclass, pymethods, Py, PyAny, PyResult};
+use savant_core::primitives::rust::ContentEncoding;
 use savant_core::transport::zeromq;
 use std::num::NonZeroU64;
 
@@ -86,6 +87,21 @@ impl WriterConfig {
         *self.0.fix_ipc_permissions()
     }
 
+    #[getter]
+    fn representations(&self) -> Option<Vec<String>> {
+        self.0.representations().clone()
+    }
+
+    #[getter]
+    fn content_encoding(&self) -> Option<String> {
+        self.0.content_encoding().map(|e| e.name().to_string())
+    }
+
+    #[getter]
+    fn serialization_profile(&self) -> Option<String> {
+        self.0.serialization_profile().clone()
+    }
+
     #[classattr]
     const __hash__: Option<Py<PyAny>> = None;
 
@@ -316,6 +332,98 @@ impl WriterConfigBuilder {
         Ok(())
     }
 
+    /// Sets the content representations of the frames sent by the writer.
+    ///
+    /// Parameters
+    /// ----------
+    /// representations: Optional[List[str]]
+    ///   The representation keys to send, defaults to ``None`` which sends all of them. The
+    ///   primary content is always sent.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the representations are double set
+    ///
+    #[pyo3(signature = (representations=None))]
+    pub fn with_representations(&mut self, representations: Option<Vec<String>>) -> PyResult<()> {
+        self.0 = Some(
+            self.0
+                .take()
+                .unwrap()
+                .with_representations(representations)
+                .map_err(|e| {
+                    PyValueError::new_err(format!(
+                        "Failed to set the content representations: {:?}",
+                        e
+                    ))
+                })?,
+        );
+        Ok(())
+    }
+
+    /// Sets the codec compressing the uncompressed internal content of the frames sent by
+    /// the writer, the frames themselves are not modified.
+    ///
+    /// Parameters
+    /// ----------
+    /// content_encoding: Optional[str]
+    ///   ``lz4`` or ``zstd``, defaults to ``None`` which sends the content as it is.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the codec is unknown or the encoding is double set
+    ///
+    #[pyo3(signature = (content_encoding=None))]
+    pub fn with_content_encoding(&mut self, content_encoding: Option<&str>) -> PyResult<()> {
+        let content_encoding = content_encoding
+            .map(str::parse::<ContentEncoding>)
+            .transpose()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.0 = Some(
+            self.0
+                .take()
+                .unwrap()
+                .with_content_encoding(content_encoding)
+                .map_err(|e| {
+                    PyValueError::new_err(format!("Failed to set the content encoding: {:?}", e))
+                })?,
+        );
+        Ok(())
+    }
+
+    /// Sets the registered serialization profile the messages are sent with. The profile
+    /// selects the representations and the content encoding itself, so :py:meth:`build`
+    /// fails when they are set too.
+    ///
+    /// Parameters
+    /// ----------
+    /// profile: Optional[str]
+    ///   The name of the profile, defaults to ``None`` which sends the messages as they are.
+    ///
+    /// Raises
+    /// ------
+    /// ValueError
+    ///   If the profile is double set
+    ///
+    #[pyo3(signature = (profile=None))]
+    pub fn with_serialization_profile(&mut self, profile: Option<String>) -> PyResult<()> {
+        self.0 = Some(
+            self.0
+                .take()
+                .unwrap()
+                .with_serialization_profile(profile)
+                .map_err(|e| {
+                    PyValueError::new_err(format!(
+                        "Failed to set the serialization profile: {:?}",
+                        e
+                    ))
+                })?,
+        );
+        Ok(())
+    }
+
     /// Builds the configuration
     ///
     /// Returns
@@ -1,6 +1,8 @@
 from enum import Enum
 from typing import Tuple, Optional
 
+from savant_rs.primitives import BorrowedVideoObject
+
 
 class ColorDraw:
     def copy(self) -> ColorDraw: ...
@@ -135,7 +137,8 @@ class LabelDraw:
                  thickness: int = 1,
                  position: LabelPosition = LabelPosition.default_position(),
                  padding: PaddingDraw = PaddingDraw.default_padding(),
-                 format: list[str] = ["{label}"]): ...
+                 format: list[str] = ["{label}"],
+                 locale: Optional[str] = None): ...
 
     @property
     def font_color(self) -> ColorDraw: ...
@@ -161,6 +164,13 @@ class LabelDraw:
     @property
     def format(self) -> list[str]: ...
 
+    @property
+    def locale(self) -> Optional[str]: ...
+
+    def display_label(self, model_name: str, object_label: str) -> str: ...
+
+    def render_labels(self, object: BorrowedVideoObject) -> list[str]: ...
+
 
 class ObjectDraw:
     def copy(self) -> ObjectDraw: ...
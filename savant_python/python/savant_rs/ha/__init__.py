@@ -0,0 +1 @@
+from .ha import *
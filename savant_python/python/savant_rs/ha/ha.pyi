@@ -0,0 +1,22 @@
+from typing import Callable, Optional
+
+
+def register_state_provider(name: str, snapshot: Callable[[], bytes], restore: Callable[[bytes], None]): ...
+
+
+def unregister_state_provider(name: str): ...
+
+
+def start_active(standby_url: str, period_ms: int): ...
+
+
+def start_standby(active_url: str, check_period_ms: int, failure_threshold: int, on_promote: Optional[Callable[[int], None]] = None): ...
+
+
+def stop_ha(): ...
+
+
+def promote() -> Optional[int]: ...
+
+
+def get_ha_status() -> str: ...
@@ -1,5 +1,6 @@
-from typing import List, Optional, Dict
+from typing import List, Optional, Dict, Tuple
 
+from savant_rs.primitives import VideoObjectsView
 from savant_rs.primitives.geometry import RBBox
 from savant_rs.utils import BBoxMetricType
 
@@ -168,6 +169,19 @@ class MatchQuery:
     def from_json(cls, json_str: str) -> MatchQuery: ...
     @classmethod
     def from_yaml(cls, yaml_str: str) -> MatchQuery: ...
+    def compile(self) -> CompiledMatchQuery: ...
+
+class CompiledMatchQuery:
+    @property
+    def source(self) -> MatchQuery: ...
+    def filter(self, v: VideoObjectsView, no_gil: bool = True) -> VideoObjectsView: ...
+    def partition(
+        self, v: VideoObjectsView, no_gil: bool = True
+    ) -> Tuple[VideoObjectsView, VideoObjectsView]: ...
+    def optimize(self): ...
+    def reset_stats(self): ...
+    @property
+    def stats(self) -> List[Tuple[str, str, int, int]]: ...
 
 class TlsConfig:
     def __init__(self, ca: str, cert: str, key: str): ...
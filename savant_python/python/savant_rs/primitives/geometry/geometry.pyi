@@ -155,6 +155,35 @@ class BBox:
     def copy(self) -> BBox: ...
     def new_padded(self, padding: PaddingDraw) -> BBox: ...
 
+class Enu:
+    def __init__(self, east: float, north: float, up: float): ...
+    @property
+    def east(self) -> float: ...
+    @property
+    def north(self) -> float: ...
+    @property
+    def up(self) -> float: ...
+
+class GeoPosition:
+    def __init__(
+        self,
+        latitude: float,
+        longitude: float,
+        altitude: float = 0.0,
+        heading: Optional[float] = None,
+    ): ...
+    @property
+    def latitude(self) -> float: ...
+    @property
+    def longitude(self) -> float: ...
+    @property
+    def altitude(self) -> float: ...
+    @property
+    def heading(self) -> Optional[float]: ...
+    def to_enu(self, origin: GeoPosition) -> Enu: ...
+    @staticmethod
+    def from_enu(origin: GeoPosition, enu: Enu) -> GeoPosition: ...
+
 def solely_owned_areas(bboxes: List[RBBox], parallel: bool) -> List[float]: ...
 def associate_bboxes(
     candidates: List[RBBox], owners: List[RBBox], metric: str, threshold: float
@@ -1,9 +1,15 @@
 from enum import Enum
-from typing import Optional
+from typing import Iterator, List, Optional, Tuple, Union, overload
 
 from savant_rs.draw_spec import SetDrawLabelKind
 from savant_rs.match_query import MatchQuery
-from savant_rs.primitives.geometry import Intersection, RBBox, Point, PolygonalArea
+from savant_rs.primitives.geometry import (
+    GeoPosition,
+    Intersection,
+    RBBox,
+    Point,
+    PolygonalArea,
+)
 from savant_rs.utils import VideoObjectBBoxTransformation
 from savant_rs.utils.serialization import Message
 
@@ -31,9 +37,12 @@ class AttributeValueType(Enum):
 
 class AttributeValue:
     confidence: Optional[float]
+    unit: Optional[str]
 
     def get_value_type(self) -> AttributeValueType: ...
 
+    def convert_to(self, unit: str) -> AttributeValue: ...
+
     @classmethod
     def intersection(cls,
                      intersection: Intersection,
@@ -230,6 +239,12 @@ class Attribute:
     @property
     def hint(self) -> Optional[str]: ...
 
+    expires_at: Optional[int]
+
+    def set_ttl(self, ttl_ms: int) -> None: ...
+
+    def is_expired(self) -> bool: ...
+
     @property
     def json(self) -> str: ...
 
@@ -249,6 +264,11 @@ class ObjectUpdatePolicy(Enum):
     ReplaceSameLabelObjects: ...
 
 
+class ObjectMergePolicy(Enum):
+    MergeSameObjects: ...
+    AddForeignObjects: ...
+
+
 class EndOfStream:
     def __init__(self, source_id: str): ...
 
@@ -357,6 +377,14 @@ class VideoFrameContent:
     @classmethod
     def internal(cls, data: bytes) -> VideoFrameContent: ...
 
+    @classmethod
+    def internal_raw(cls,
+                     data: bytes,
+                     pixel_format: str,
+                     width: int,
+                     height: int,
+                     planes: Optional[list[tuple[int, int]]] = None) -> VideoFrameContent: ...
+
     @classmethod
     def none(cls) -> VideoFrameContent: ...
 
@@ -368,11 +396,39 @@ class VideoFrameContent:
 
     def get_data(self) -> bytes: ...
 
+    def get_encoding(self) -> Optional[str]: ...
+
+    def compress(self, encoding: str) -> VideoFrameContent: ...
+
+    def decompress(self) -> VideoFrameContent: ...
+
+    def get_layout(self) -> Optional[tuple[str, int, int, list[tuple[int, int]]]]: ...
+
     def get_method(self) -> str: ...
 
     def get_location(self) -> Optional[str]: ...
 
 
+class ContentRepresentation:
+    def __init__(self,
+                 content: VideoFrameContent,
+                 width: int,
+                 height: int,
+                 codec: Optional[str] = None): ...
+
+    @property
+    def content(self) -> VideoFrameContent: ...
+
+    @property
+    def width(self) -> int: ...
+
+    @property
+    def height(self) -> int: ...
+
+    @property
+    def codec(self) -> Optional[str]: ...
+
+
 class VideoFrameTranscodingMethod(Enum):
     Copy: ...
     Encoded: ...
@@ -430,12 +486,50 @@ class VideoFrame:
     transcoding_method: VideoFrameTranscodingMethod
     codec: Optional[str]
     content: VideoFrameContent
+    skip_inference: bool
+    roi_list: list[RBBox]
+    degrade_level: int
+    geo_pose: Optional[GeoPosition]
+    classification: DataClassification
+
+    @property
+    def effective_classification(self) -> DataClassification: ...
+
+    @property
+    def uuid_timestamp_ms(self) -> Optional[int]: ...
+
+    @property
+    def frozen_objects(self) -> list[tuple[Optional[str], str]]: ...
+
+    @property
+    def source_config(self) -> Optional[str]: ...
+
+    @property
+    def representation_keys(self) -> list[str]: ...
+
+    def set_representation(self, key: str, representation: ContentRepresentation) -> None: ...
+
+    def get_representation(self, key: str) -> Optional[ContentRepresentation]: ...
+
+    def delete_representation(self, key: str) -> Optional[ContentRepresentation]: ...
+
+    def retain_representations(self, keys: list[str]) -> None: ...
+
+    def ensure_format(self, format: str, no_gil: bool = True) -> None: ...
+
+    def compress_content(self, encoding: str, no_gil: bool = True) -> None: ...
+
+    def decompress_content(self, no_gil: bool = True) -> None: ...
+
+    def freeze_objects(self, stage: str, namespace: Optional[str] = None) -> None: ...
 
     @classmethod
     def transform_geometry(cls,
                            ops: list[VideoObjectBBoxTransformation],
                            no_gil: bool = True): ...
 
+    def inference_regions(self) -> list[RBBox]: ...
+
     @property
     def memory_handle(self) -> int: ...
 
@@ -492,6 +586,8 @@ class VideoFrame:
 
     def clear_attributes(self): ...
 
+    def purge_expired_attributes(self) -> int: ...
+
     def delete_attributes_with_ns(self, namespace: str): ...
 
     def delete_attributes_with_names(self, names: list[str]): ...
@@ -536,6 +632,9 @@ class VideoFrame:
 
     def get_all_objects(self) -> VideoObjectsView: ...
 
+    @property
+    def objects(self) -> VideoObjectsView: ...
+
     def access_objects(self,
                        q: MatchQuery,
                        no_gil: bool = True) -> VideoObjectsView: ...
@@ -544,10 +643,25 @@ class VideoFrame:
                               ids: list[int],
                               no_gil: bool = True) -> VideoObjectsView: ...
 
+    def set_attribute_propagation(self,
+                                  rules: Optional[list[PropagationRule]] = None): ...
+
+    def create_user_data(self) -> UserData: ...
+
+    def set_query_cache(self, enabled: bool): ...
+
+    @property
+    def query_cache_stats(self) -> Optional[Tuple[int, int, int]]: ...
+
     def delete_objects(self, q: MatchQuery, no_gil: bool = True) -> VideoObjectsView: ...
 
     def delete_objects_with_ids(self, ids: list[int]) -> VideoObjectsView: ...
 
+    def prune(self,
+              q: MatchQuery,
+              policy: OrphanPolicy = OrphanPolicy.Detach,
+              no_gil: bool = True) -> list[VideoObject]: ...
+
     def set_parent(self,
                    q: MatchQuery,
                    parent: VideoObject,
@@ -569,6 +683,15 @@ class VideoFrame:
 
     def update(self, update: VideoFrameUpdate, no_gil: bool = True): ...
 
+    def merge_from(
+        self,
+        other: VideoFrame,
+        object_policy: ObjectMergePolicy = ObjectMergePolicy.MergeSameObjects,
+        frame_attribute_policy: AttributeUpdatePolicy = AttributeUpdatePolicy.ReplaceWithForeignWhenDuplicate,
+        object_attribute_policy: AttributeUpdatePolicy = AttributeUpdatePolicy.ReplaceWithForeignWhenDuplicate,
+        no_gil: bool = True,
+    ) -> dict[int, int]: ...
+
     def to_protobuf(self, no_gil: bool = True) -> bytes: ...
 
     @classmethod
@@ -599,11 +722,65 @@ class VideoFrameBatch:
                       protobuf: bytes,
                       no_gil: bool = True) -> VideoFrameBatch: ...
 
+    def to_tensor(self,
+                  width: int,
+                  height: int,
+                  pixel_format: str = "rgb8",
+                  letterbox: bool = True,
+                  padding: int = 0,
+                  layout: str = "nchw",
+                  dtype: str = "f32",
+                  scale: float = 1.0,
+                  mean: Optional[list[float]] = None,
+                  std: Optional[list[float]] = None,
+                  no_gil: bool = True) -> BatchTensor: ...
+
+
+class BatchTensor:
+    @property
+    def data(self) -> bytes: ...
+
+    @property
+    def shape(self) -> tuple[int, int, int, int]: ...
+
+    @property
+    def dtype(self) -> str: ...
+
+    @property
+    def layout(self) -> str: ...
+
+    @property
+    def frame_ids(self) -> list[int]: ...
+
+    def to_frame_operations(self, frame_id: int) -> Optional[list[VideoObjectBBoxTransformation]]: ...
+
+
+class DeltaEncoder:
+    keyframe_interval: int
+
+    def __init__(self, keyframe_interval: int): ...
+
+    def reset_source(self, source_id: str): ...
+
+    def encode(self, frame: VideoFrame, no_gil: bool = True) -> VideoFrame: ...
+
+
+class DeltaDecoder:
+    def __init__(self): ...
+
+    def reset_source(self, source_id: str): ...
+
+    def decode(self, frame: VideoFrame, no_gil: bool = True) -> VideoFrame: ...
+
+
+def is_delta_frame(frame: VideoFrame) -> bool: ...
+
 
 class VideoFrameUpdate:
     frame_attribute_policy: AttributeUpdatePolicy
     object_attribute_policy: AttributeUpdatePolicy
     object_policy: ObjectUpdatePolicy
+    updater: Optional[str]
 
     def __init__(self): ...
 
@@ -635,6 +812,50 @@ class IdCollisionResolutionPolicy(Enum):
     Error: ...
 
 
+class OrphanPolicy(Enum):
+    Detach: ...
+    Reparent: ...
+    Cascade: ...
+
+
+class DataClassification(Enum):
+    Public: ...
+    Internal: ...
+    Pii: ...
+
+
+class PropagationMode(Enum):
+    Copy: ...
+    Reference: ...
+
+
+class PropagationRule:
+    def __init__(self,
+                 namespace: str,
+                 name: Optional[str] = None,
+                 mode: PropagationMode = PropagationMode.Copy,
+                 to_objects: bool = True,
+                 to_events: bool = True): ...
+
+    @property
+    def namespace(self) -> str: ...
+
+    @property
+    def name(self) -> Optional[str]: ...
+
+    @property
+    def mode(self) -> PropagationMode: ...
+
+    @property
+    def to_objects(self) -> bool: ...
+
+    @property
+    def to_events(self) -> bool: ...
+
+    @staticmethod
+    def frame_reference(attribute: Attribute) -> Optional[str]: ...
+
+
 class BorrowedVideoObject:
     confidence: Optional[float]
     namespace: str
@@ -643,15 +864,34 @@ class BorrowedVideoObject:
     detection_box: RBBox
     track_id: Optional[int]
     track_box: Optional[RBBox]
+    geo_position: Optional[GeoPosition]
+    classification: DataClassification
 
     @property
     def memory_handle(self) -> int: ...
 
+    @property
+    def payload(self) -> Optional[tuple[str, bytes]]: ...
+
+    def set_payload(
+        self, content_type: str, data: bytes, serialize: bool = False
+    ) -> None: ...
+
+    def take_payload(self) -> Optional[tuple[str, bytes]]: ...
+
+    def clear_payload(self) -> None: ...
+
+    def copy_payload_to(self, other: BorrowedVideoObject) -> None: ...
+
+    def transfer_payload_to(self, other: BorrowedVideoObject) -> None: ...
+
     @property
     def attributes(self) -> list[tuple[str, str]]: ...
 
     def clear_attributes(self): ...
 
+    def purge_expired_attributes(self) -> int: ...
+
     @property
     def id(self) -> int: ...
 
@@ -748,6 +988,15 @@ class VideoObject:
     @property
     def track_box(self) -> Optional[RBBox]: ...
 
+    @property
+    def geo_position(self) -> Optional[GeoPosition]: ...
+
+    @property
+    def classification(self) -> DataClassification: ...
+
+    @property
+    def payload(self) -> Optional[tuple[str, bytes]]: ...
+
     @property
     def confidence(self) -> Optional[float]: ...
 
@@ -781,7 +1030,21 @@ class VideoObjectBBoxType(Enum):
 class VideoObjectsView:
     def __len__(self) -> int: ...
 
-    def __getitem__(self, item) -> BorrowedVideoObject: ...
+    def __bool__(self) -> bool: ...
+
+    @overload
+    def __getitem__(self, item: int) -> BorrowedVideoObject: ...
+
+    @overload
+    def __getitem__(self, item: slice) -> VideoObjectsView: ...
+
+    def __iter__(self) -> Iterator[BorrowedVideoObject]: ...
+
+    def filter(self, q: MatchQuery, no_gil: bool = True) -> VideoObjectsView: ...
+
+    def partition(self,
+                  q: MatchQuery,
+                  no_gil: bool = True) -> tuple[VideoObjectsView, VideoObjectsView]: ...
 
     def memory_handle(self) -> int: ...
 
@@ -795,6 +1058,29 @@ class VideoObjectsView:
     def sorted_by_id(self) -> list[BorrowedVideoObject]: ...
 
 
+def fuse_objects(
+    objects: Union[List[VideoObject], VideoObjectsView],
+    iou_threshold: float = 0.55,
+    sources: int = 1,
+    cross_label: bool = False,
+    default_confidence: float = 1.0,
+) -> List[VideoObject]: ...
+
+
+class ConfidenceDecay:
+    def __init__(
+        self, factor: float, min_confidence: float = 0.1, max_missed: int = 5
+    ): ...
+
+    def update(
+        self, objects: Union[List[VideoObject], VideoObjectsView]
+    ) -> List[VideoObject]: ...
+
+    def __len__(self) -> int: ...
+
+    def reset(self) -> None: ...
+
+
 class QueryFunctions:
     @classmethod
     def filter(cls,
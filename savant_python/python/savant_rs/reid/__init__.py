@@ -0,0 +1 @@
+from .reid import *
@@ -0,0 +1,23 @@
+from typing import List, Optional, Tuple
+
+
+class ReidGallery:
+    def __init__(self, dimension: int, ttl_ms: Optional[int] = None): ...
+
+    def __len__(self) -> int: ...
+
+    def add(self, id: int, embedding: List[float]): ...
+
+    def update(self, id: int, embedding: List[float]) -> int: ...
+
+    def get(self, id: int) -> Optional[List[float]]: ...
+
+    def remove(self, id: int) -> bool: ...
+
+    def evict_expired(self) -> List[int]: ...
+
+    def query(self, embedding: List[float], k: int = 1) -> List[Tuple[int, float]]: ...
+
+    def save(self, path: str): ...
+
+    def load(self, path: str) -> bool: ...
@@ -1 +1,4 @@
+from typing import Any, Dict
+
 def version() -> str: ...
+def capabilities() -> Dict[str, Any]: ...
@@ -1,7 +1,9 @@
 from enum import Enum
-from typing import Union, Optional
+from typing import Any, Union, Optional
 
-from savant_rs.primitives import VideoFrame
+from savant_rs.match_query import MatchQuery
+from savant_rs.primitives import UserData, VideoFrame
+from savant_rs.utils.serialization import Message
 
 
 def eval_expr(expr: str, ttl: int, no_gil: bool = True) -> Union[int, float, str, bool, None, list[...]]: ...
@@ -22,9 +24,84 @@ def estimate_gil_contention(): ...
 def enable_dl_detection(): ...
 
 
+def enable_leak_detection(period_ms: int = 60000,
+                          horizons_ms: list[int] = [3600000, 21600000],
+                          min_growth: int = 1): ...
+
+
+def disable_leak_detection(): ...
+
+
+def get_leak_suspects() -> list[tuple[str, int, int, int, int]]: ...
+
+
 def incremental_uuid_v7() -> str: ...
 
 
+def set_frame_uuid_version(version: int = 7): ...
+
+
+def get_frame_uuid_version() -> int: ...
+
+
+def uuid_v7_timestamp_ms(uuid: str) -> Optional[int]: ...
+
+
+def uuid_v7_range(start_ms: int, end_ms: int) -> tuple[str, str]: ...
+
+
+def registered_transcoders() -> list[str]: ...
+
+
+def load_fixture(path: str) -> list[VideoFrame]: ...
+
+
+def generate_fixture_test(path: str,
+                          stage: str,
+                          test_name: str,
+                          language: str = "python") -> str: ...
+
+
+def compare_journals(baseline: str,
+                     candidate: str,
+                     match_iou: float = 0.3,
+                     move_iou: float = 0.9) -> tuple[bool, str]: ...
+
+
+def diff_journals(baseline: str,
+                  candidate: str,
+                  match_iou: float = 0.3,
+                  move_iou: float = 0.9) -> list[tuple[bool, str]]: ...
+
+
+def extract_clip(path: str, source_id: str, start_ms: int, end_ms: int) -> list[Message]: ...
+
+
+def set_clip_journal(path: Optional[str] = None): ...
+
+
+def set_jobs_dir(path: Optional[str] = None): ...
+
+
+def report_frame_disposition(frame_uuid: str,
+                             sink: str,
+                             status: str = "exported",
+                             stored_path: Optional[str] = None,
+                             event_ids: list[str] = []): ...
+
+
+def get_frame_provenance(frame_uuid: str) -> list[tuple[str, str, Optional[str], list[str], int]]: ...
+
+
+def enable_backfill_clock(origin_ms: int): ...
+
+
+def enable_live_clock(): ...
+
+
+def get_clock_now_ms() -> int: ...
+
+
 class TelemetrySpan:
     @classmethod
     def current(cls) -> TelemetrySpan: ...
@@ -155,3 +232,120 @@ class AtomicCounter:
 
     @property
     def get(self) -> int: ...
+
+
+class WindowResult:
+    @property
+    def key(self) -> list[str]: ...
+
+    @property
+    def start_ms(self) -> int: ...
+
+    @property
+    def end_ms(self) -> int: ...
+
+    @property
+    def statistics(self) -> dict[str, float]: ...
+
+    def to_user_data(self, source_id: str, namespace: str) -> UserData: ...
+
+    def store_in_kvs(self, namespace: str, ttl: Optional[int] = None): ...
+
+
+class WindowedAggregator:
+    def __init__(self,
+                 window_ms: int,
+                 statistics: list[str],
+                 group_by: list[str],
+                 step_ms: Optional[int] = None,
+                 value: Optional[tuple[str, str]] = None,
+                 objects: Optional[MatchQuery] = None): ...
+
+    @property
+    def late(self) -> int: ...
+
+    def observe(self, key: list[str], value: float, ts_ms: int) -> bool: ...
+
+    def observe_frame(self, frame: VideoFrame, ts_ms: int) -> int: ...
+
+    def close(self, now_ms: int) -> list[WindowResult]: ...
+
+    def flush(self) -> list[WindowResult]: ...
+
+
+class RetentionReport:
+    @property
+    def deleted(self) -> list[tuple[str, int, str]]: ...
+
+    @property
+    def freed_bytes(self) -> int: ...
+
+    @property
+    def remaining_bytes(self) -> int: ...
+
+    @property
+    def over_limit(self) -> bool: ...
+
+
+class RetentionManager:
+    def __init__(self,
+                 name: str,
+                 directories: list[str],
+                 max_age_ms: Optional[int] = None,
+                 max_size: Optional[int] = None,
+                 patterns: list[str] = []): ...
+
+    @property
+    def name(self) -> str: ...
+
+    @property
+    def pins(self) -> list[str]: ...
+
+    @property
+    def is_running(self) -> bool: ...
+
+    def pin(self, path: str, until_ms: Optional[int] = None): ...
+
+    def unpin(self, path: str) -> bool: ...
+
+    def enforce(self) -> RetentionReport: ...
+
+    def start(self, period_ms: int): ...
+
+    def stop(self): ...
+
+
+class StateManager:
+    def __init__(self,
+                 name: str,
+                 directory: Optional[str] = None,
+                 kvs_namespace: Optional[str] = None): ...
+
+    @property
+    def name(self) -> str: ...
+
+    @property
+    def processors(self) -> list[str]: ...
+
+    @property
+    def is_running(self) -> bool: ...
+
+    def register(self, name: str, processor: Any): ...
+
+    def unregister(self, name: str) -> bool: ...
+
+    def save(self, name: str): ...
+
+    def save_all(self) -> int: ...
+
+    def restore(self, name: str) -> bool: ...
+
+    def restore_all(self) -> list[str]: ...
+
+    def export_state(self, name: str) -> bytes: ...
+
+    def import_state(self, name: str, state: bytes): ...
+
+    def start(self, period_ms: int): ...
+
+    def stop(self): ...
@@ -1,4 +1,4 @@
-from typing import List, Optional
+from typing import List, Optional, Tuple
 from savant_rs.primitives import Attribute
 
 
@@ -31,3 +31,46 @@ def serialize_attributes(attributes: List[Attribute]) -> None: ...
 
 # pub fn deserialize_attributes(serialized: &Bound<'_, PyBytes>) -> PyResult<Vec<Attribute>>
 def deserialize_attributes(serialized: bytes) -> List[Attribute]: ...
+
+
+def set_blob(ns: str, name: str, data: bytes, content_type: str = "application/octet-stream", ttl: Optional[int] = None) -> None: ...
+
+
+def get_blob(ns: str, name: str) -> Optional[Tuple[str, bytes]]: ...
+
+
+def del_blob(ns: str, name: str) -> Optional[Tuple[str, bytes]]: ...
+
+
+def search_blob_keys(ns: Optional[str] = None, name: Optional[str] = None) -> List[Tuple[str, str]]: ...
+
+
+def export_snapshot() -> bytes: ...
+
+
+def import_snapshot(snapshot: bytes, merge_policy: str = "overwrite") -> int: ...
+
+
+def acquire_lease(name: str, holder: str, ttl_ms: int) -> Optional[int]: ...
+
+
+def release_lease(name: str, holder: str) -> bool: ...
+
+
+def get_lease(name: str) -> Optional[Tuple[str, int, int]]: ...
+
+
+def is_current_token(name: str, token: int) -> bool: ...
+
+
+def set_lease_token_file(path: str): ...
+
+
+class LeaderElector:
+    def __init__(self, name: str, holder: str, ttl_ms: int, renew_interval_ms: int, url: Optional[str] = None): ...
+
+    def is_leader(self) -> bool: ...
+
+    def get_fencing_token(self) -> Optional[int]: ...
+
+    def stop(self) -> None: ...
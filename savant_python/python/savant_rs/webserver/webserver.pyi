@@ -1,9 +1,23 @@
-def init_webserver(port: int) -> None: ...
+from typing import Callable, List, Optional, Tuple
+
+
+def init_webserver(
+    port: int, host: str = "0.0.0.0", workers: Optional[int] = None
+) -> None: ...
 
 
 def stop_webserver() -> None: ...
 
 
+def start_control_socket(path: str, mode: Optional[int] = None) -> None: ...
+
+
+def stop_control_socket() -> None: ...
+
+
+def control_socket_proto() -> str: ...
+
+
 def set_shutdown_token(token: str) -> None: ...
 
 
@@ -14,3 +28,24 @@ def set_status_running() -> None: ...
 
 
 def set_shutdown_signal(signal: int) -> None: ...
+
+
+def set_shutdown_callback(callback: Optional[Callable[[], None]] = None) -> None: ...
+
+
+def configure_audit_log(path: Optional[str] = None, capacity: int = 1000) -> None: ...
+
+
+def audit_operation(
+    operation: str, details: str, requester: str = "local", accepted: bool = True
+) -> int: ...
+
+
+def query_audit_log(
+    operation: Optional[str] = None,
+    since_ms: Optional[int] = None,
+    limit: Optional[int] = None,
+) -> List[Tuple[int, int, str, str, str, bool]]: ...
+
+
+def diagnostics_bundle(frames: int = 0) -> bytes: ...
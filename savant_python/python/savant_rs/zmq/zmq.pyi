@@ -1,5 +1,5 @@
 from enum import Enum
-from typing import Optional, Union
+from typing import List, Optional, Union
 
 from savant_rs.utils.serialization import Message
 
@@ -58,6 +58,15 @@ class WriterConfig:
     @property
     def fix_ipc_permissions(self) -> Optional[bool]: ...
 
+    @property
+    def representations(self) -> Optional[List[str]]: ...
+
+    @property
+    def content_encoding(self) -> Optional[str]: ...
+
+    @property
+    def serialization_profile(self) -> Optional[str]: ...
+
 
 class WriterConfigBuilder:
     def __init__(self, url: str): ...
@@ -80,6 +89,12 @@ class WriterConfigBuilder:
 
     def with_fix_ipc_permissions(self, fix_ipc_permissions: Optional[bool]): ...
 
+    def with_representations(self, representations: Optional[List[str]] = None): ...
+
+    def with_content_encoding(self, content_encoding: Optional[str] = None): ...
+
+    def with_serialization_profile(self, profile: Optional[str] = None): ...
+
     def build(self) -> WriterConfig: ...
 
 
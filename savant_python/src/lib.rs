@@ -3,38 +3,47 @@ use pyo3::prelude::*;
 use pyo3::types::PyDict;
 use pyo3::wrap_pymodule;
 
+use savant_core::webserver::diagnostics::RecentLogsLogger;
 use savant_core_py::atomic_counter::AtomicCounter;
 use savant_core_py::draw_spec::*;
 use savant_core_py::logging::*;
 use savant_core_py::match_query::*;
 use savant_core_py::metrics::*;
 use savant_core_py::pipeline::{
-    load_stage_function_plugin, FrameProcessingStatRecord, FrameProcessingStatRecordType, Pipeline,
-    PipelineConfiguration, StageFunction, StageLatencyMeasurements, StageLatencyStat,
-    StageProcessingStat, VideoPipelineStagePayloadType,
+    best_shot_selector, load_stage_function_plugin, motion_detector, quality_estimator,
+    AutoBatchFlusher, ContractRegistry, FrameProcessingStatRecord, FrameProcessingStatRecordType,
+    FrameReaper, Pipeline, PipelineConfiguration, PipelineEvent, PipelineEventReceiver,
+    SourceStatistics, SourceStatsPublisher, StageFunction, StageLatencyMeasurements,
+    StageLatencyStat, StageProcessingStat, StageRate, StreamSynchronizer, UserCodeSection,
+    VideoPipelineStagePayloadType,
 };
 use savant_core_py::primitives::attribute::Attribute;
+use savant_core_py::primitives::attribute_propagation::{PropagationMode, PropagationRule};
 use savant_core_py::primitives::attribute_value::{
     AttributeValue, AttributeValueType, AttributeValuesView,
 };
-use savant_core_py::primitives::batch::VideoFrameBatch;
+use savant_core_py::primitives::batch::{BatchTensor, VideoFrameBatch};
 use savant_core_py::primitives::bbox::utils::*;
 use savant_core_py::primitives::bbox::{
     BBox, BBoxMetricType, RBBox, VideoObjectBBoxTransformation,
 };
 use savant_core_py::primitives::eos::EndOfStream;
 use savant_core_py::primitives::frame::{
-    VideoFrame, VideoFrameContent, VideoFrameTranscodingMethod, VideoFrameTransformation,
+    ContentRepresentation, VideoFrame, VideoFrameContent, VideoFrameTranscodingMethod,
+    VideoFrameTransformation,
 };
+use savant_core_py::primitives::frame_delta::{is_delta_frame, DeltaDecoder, DeltaEncoder};
 use savant_core_py::primitives::frame_update::{
-    AttributeUpdatePolicy, ObjectUpdatePolicy, VideoFrameUpdate,
+    AttributeUpdatePolicy, ObjectMergePolicy, ObjectUpdatePolicy, VideoFrameUpdate,
 };
+use savant_core_py::primitives::geo::{Enu, GeoPosition};
 use savant_core_py::primitives::message::loader::*;
 use savant_core_py::primitives::message::saver::*;
 use savant_core_py::primitives::message::*;
 use savant_core_py::primitives::object::{
-    BorrowedVideoObject, IdCollisionResolutionPolicy, VideoObject,
+    BorrowedVideoObject, DataClassification, IdCollisionResolutionPolicy, OrphanPolicy, VideoObject,
 };
+use savant_core_py::primitives::object_fusion::{fuse_objects, ConfidenceDecay};
 use savant_core_py::primitives::objects_view::{
     QueryFunctions, VideoObjectBBoxType, VideoObjectsView,
 };
@@ -43,11 +52,15 @@ use savant_core_py::primitives::polygonal_area::PolygonalArea;
 use savant_core_py::primitives::segment::{Intersection, IntersectionKind, Segment};
 use savant_core_py::primitives::shutdown::Shutdown;
 use savant_core_py::primitives::user_data::UserData;
+use savant_core_py::reid::ReidGallery;
 use savant_core_py::telemetry::*;
 use savant_core_py::test::utils::*;
+use savant_core_py::utils::aggregation::{WindowResult, WindowedAggregator};
 use savant_core_py::utils::byte_buffer::ByteBuffer;
 use savant_core_py::utils::eval_resolvers::*;
 use savant_core_py::utils::otlp::*;
+use savant_core_py::utils::retention::{RetentionManager, RetentionReport};
+use savant_core_py::utils::state::StateManager;
 use savant_core_py::utils::symbol_mapper::*;
 use savant_core_py::utils::*;
 use savant_core_py::webserver::kvs::*;
@@ -63,6 +76,24 @@ use savant_core_py::zmq::results::{
 use savant_core_py::zmq::{blocking, nonblocking};
 use savant_core_py::*;
 
+#[pymodule(gil_used = false)]
+pub fn ha(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(
+        savant_core_py::ha::register_state_provider,
+        m
+    )?)?;
+    m.add_function(wrap_pyfunction!(
+        savant_core_py::ha::unregister_state_provider,
+        m
+    )?)?;
+    m.add_function(wrap_pyfunction!(savant_core_py::ha::start_active, m)?)?;
+    m.add_function(wrap_pyfunction!(savant_core_py::ha::start_standby, m)?)?;
+    m.add_function(wrap_pyfunction!(savant_core_py::ha::stop_ha, m)?)?;
+    m.add_function(wrap_pyfunction!(savant_core_py::ha::promote, m)?)?;
+    m.add_function(wrap_pyfunction!(savant_core_py::ha::get_ha_status, m)?)?;
+    Ok(())
+}
+
 #[pymodule(gil_used = false)]
 pub fn metrics(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<CounterFamily>()?;
@@ -72,14 +103,31 @@ pub fn metrics(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     Ok(())
 }
 
+#[pymodule(gil_used = false)]
+pub fn reid(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<ReidGallery>()?; // PYI
+    Ok(())
+}
+
 #[pymodule(gil_used = false)]
 pub fn webserver(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(init_webserver, m)?)?;
     m.add_function(wrap_pyfunction!(stop_webserver, m)?)?;
+    #[cfg(unix)]
+    m.add_function(wrap_pyfunction!(start_control_socket, m)?)?;
+    #[cfg(unix)]
+    m.add_function(wrap_pyfunction!(stop_control_socket, m)?)?;
+    #[cfg(unix)]
+    m.add_function(wrap_pyfunction!(control_socket_proto, m)?)?;
     m.add_function(wrap_pyfunction!(set_shutdown_token, m)?)?;
     m.add_function(wrap_pyfunction!(is_shutdown_set, m)?)?;
     m.add_function(wrap_pyfunction!(set_status_running, m)?)?;
     m.add_function(wrap_pyfunction!(set_shutdown_signal, m)?)?;
+    m.add_function(wrap_pyfunction!(set_shutdown_callback, m)?)?;
+    m.add_function(wrap_pyfunction!(configure_audit_log, m)?)?;
+    m.add_function(wrap_pyfunction!(audit_operation, m)?)?;
+    m.add_function(wrap_pyfunction!(query_audit_log, m)?)?;
+    m.add_function(wrap_pyfunction!(diagnostics_bundle, m)?)?;
     m.add_wrapped(wrap_pymodule!(self::kvs))?;
     Ok(())
 }
@@ -94,6 +142,18 @@ pub fn kvs(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(del_attribute, m)?)?;
     m.add_function(wrap_pyfunction!(serialize_attributes, m)?)?;
     m.add_function(wrap_pyfunction!(deserialize_attributes, m)?)?;
+    m.add_function(wrap_pyfunction!(set_blob, m)?)?;
+    m.add_function(wrap_pyfunction!(get_blob, m)?)?;
+    m.add_function(wrap_pyfunction!(del_blob, m)?)?;
+    m.add_function(wrap_pyfunction!(search_blob_keys, m)?)?;
+    m.add_function(wrap_pyfunction!(export_snapshot, m)?)?;
+    m.add_function(wrap_pyfunction!(import_snapshot, m)?)?;
+    m.add_function(wrap_pyfunction!(acquire_lease, m)?)?;
+    m.add_function(wrap_pyfunction!(release_lease, m)?)?;
+    m.add_function(wrap_pyfunction!(get_lease, m)?)?;
+    m.add_function(wrap_pyfunction!(is_current_token, m)?)?;
+    m.add_function(wrap_pyfunction!(set_lease_token_file, m)?)?;
+    m.add_class::<LeaderElector>()?;
     Ok(())
 }
 
@@ -131,6 +191,8 @@ pub fn symbol_mapper(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(build_model_object_key_py, m)?)?;
     m.add_function(wrap_pyfunction!(clear_symbol_maps_py, m)?)?;
     m.add_function(wrap_pyfunction!(dump_registry_gil, m)?)?;
+    m.add_function(wrap_pyfunction!(get_display_label_py, m)?)?;
+    m.add_function(wrap_pyfunction!(get_display_labels_py, m)?)?;
     m.add_function(wrap_pyfunction!(get_model_id_py, m)?)?;
     m.add_function(wrap_pyfunction!(get_model_name_py, m)?)?;
     m.add_function(wrap_pyfunction!(get_object_id_py, m)?)?;
@@ -141,6 +203,7 @@ pub fn symbol_mapper(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(is_object_registered_py, m)?)?;
     m.add_function(wrap_pyfunction!(parse_compound_key_py, m)?)?;
     m.add_function(wrap_pyfunction!(register_model_objects_py, m)?)?;
+    m.add_function(wrap_pyfunction!(set_display_labels_py, m)?)?;
     m.add_function(wrap_pyfunction!(validate_base_key_py, m)?)?;
 
     m.add_class::<RegistrationPolicy>()?;
@@ -154,10 +217,19 @@ pub fn serialization(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(save_message_gil, m)?)?;
     m.add_function(wrap_pyfunction!(save_message_to_bytebuffer_gil, m)?)?;
     m.add_function(wrap_pyfunction!(save_message_to_bytes_gil, m)?)?;
+    m.add_function(wrap_pyfunction!(set_export_redaction, m)?)?;
+    m.add_function(wrap_pyfunction!(get_export_redaction, m)?)?;
+    m.add_function(wrap_pyfunction!(set_export_classification, m)?)?;
+    m.add_function(wrap_pyfunction!(get_export_classification, m)?)?;
+    m.add_function(wrap_pyfunction!(register_serialization_profile, m)?)?;
+    m.add_function(wrap_pyfunction!(unregister_serialization_profile, m)?)?;
+    m.add_function(wrap_pyfunction!(get_serialization_profiles, m)?)?;
 
     m.add_function(wrap_pyfunction!(load_message_gil, m)?)?;
     m.add_function(wrap_pyfunction!(load_message_from_bytebuffer_gil, m)?)?;
     m.add_function(wrap_pyfunction!(load_message_from_bytes_gil, m)?)?;
+    m.add_function(wrap_pyfunction!(set_ingestion_policy, m)?)?;
+    m.add_function(wrap_pyfunction!(get_ingestion_policy, m)?)?;
 
     m.add_class::<Message>()?;
     m.add_function(wrap_pyfunction!(clear_source_seq_id, m)?)?;
@@ -173,7 +245,27 @@ pub fn utils(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(round_2_digits, m)?)?; // PYI
     m.add_function(wrap_pyfunction!(estimate_gil_contention, m)?)?; // PYI
     m.add_function(wrap_pyfunction!(enable_dl_detection, m)?)?; // PYI
+    m.add_function(wrap_pyfunction!(enable_leak_detection, m)?)?; // PYI
+    m.add_function(wrap_pyfunction!(disable_leak_detection, m)?)?; // PYI
+    m.add_function(wrap_pyfunction!(get_leak_suspects, m)?)?; // PYI
     m.add_function(wrap_pyfunction!(incremental_uuid_v7, m)?)?; // PYI
+    m.add_function(wrap_pyfunction!(set_frame_uuid_version, m)?)?; // PYI
+    m.add_function(wrap_pyfunction!(get_frame_uuid_version, m)?)?; // PYI
+    m.add_function(wrap_pyfunction!(uuid_v7_timestamp_ms, m)?)?; // PYI
+    m.add_function(wrap_pyfunction!(uuid_v7_range, m)?)?; // PYI
+    m.add_function(wrap_pyfunction!(registered_transcoders, m)?)?; // PYI
+    m.add_function(wrap_pyfunction!(load_fixture, m)?)?; // PYI
+    m.add_function(wrap_pyfunction!(generate_fixture_test, m)?)?; // PYI
+    m.add_function(wrap_pyfunction!(compare_journals, m)?)?; // PYI
+    m.add_function(wrap_pyfunction!(diff_journals, m)?)?; // PYI
+    m.add_function(wrap_pyfunction!(extract_clip, m)?)?; // PYI
+    m.add_function(wrap_pyfunction!(set_clip_journal, m)?)?; // PYI
+    m.add_function(wrap_pyfunction!(set_jobs_dir, m)?)?; // PYI
+    m.add_function(wrap_pyfunction!(report_frame_disposition, m)?)?; // PYI
+    m.add_function(wrap_pyfunction!(get_frame_provenance, m)?)?; // PYI
+    m.add_function(wrap_pyfunction!(enable_backfill_clock, m)?)?; // PYI
+    m.add_function(wrap_pyfunction!(enable_live_clock, m)?)?; // PYI
+    m.add_function(wrap_pyfunction!(get_clock_now_ms, m)?)?; // PYI
 
     m.add_class::<PropagatedContext>()?; // PYI
     m.add_class::<TelemetrySpan>()?; // PYI
@@ -183,6 +275,11 @@ pub fn utils(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<VideoObjectBBoxTransformation>()?; // PYI
     m.add_class::<BBoxMetricType>()?; // PYI
     m.add_class::<AtomicCounter>()?;
+    m.add_class::<WindowedAggregator>()?; // PYI
+    m.add_class::<WindowResult>()?; // PYI
+    m.add_class::<RetentionManager>()?; // PYI
+    m.add_class::<RetentionReport>()?; // PYI
+    m.add_class::<StateManager>()?; // PYI
 
     m.add_wrapped(wrap_pymodule!(self::symbol_mapper))?;
     m.add_wrapped(wrap_pymodule!(self::serialization))?;
@@ -199,6 +296,8 @@ pub fn geometry(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PolygonalArea>()?;
     m.add_class::<RBBox>()?;
     m.add_class::<BBox>()?;
+    m.add_class::<GeoPosition>()?;
+    m.add_class::<Enu>()?;
 
     m.add_function(wrap_pyfunction!(solely_owned_areas, m)?)?;
     m.add_function(wrap_pyfunction!(associate_bboxes, m)?)?;
@@ -211,6 +310,7 @@ pub fn primitives(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<Attribute>()?; // PYI
     m.add_class::<AttributeUpdatePolicy>()?; // PYI
     m.add_class::<ObjectUpdatePolicy>()?; // PYI
+    m.add_class::<ObjectMergePolicy>()?; // PYI
     m.add_class::<AttributeValue>()?; // PYI
     m.add_class::<AttributeValueType>()?; // PYI
     m.add_class::<AttributeValuesView>()?; // PYI
@@ -220,16 +320,27 @@ pub fn primitives(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
 
     m.add_class::<VideoFrame>()?; // PYI
     m.add_class::<VideoFrameBatch>()?; // PYI
+    m.add_class::<BatchTensor>()?; // PYI
     m.add_class::<VideoFrameContent>()?; // PYI
+    m.add_class::<ContentRepresentation>()?; // PYI
     m.add_class::<VideoFrameTranscodingMethod>()?; // PYI
     m.add_class::<VideoFrameUpdate>()?; // PYI
     m.add_class::<VideoFrameTransformation>()?; // PYI
+    m.add_class::<DeltaEncoder>()?; // PYI
+    m.add_class::<DeltaDecoder>()?; // PYI
+    m.add_function(wrap_pyfunction!(is_delta_frame, m)?)?;
 
     m.add_class::<BorrowedVideoObject>()?; // PYI
     m.add_class::<VideoObject>()?; // PYI
     m.add_class::<VideoObjectsView>()?; // PYI
+    m.add_class::<ConfidenceDecay>()?; // PYI
+    m.add_function(wrap_pyfunction!(fuse_objects, m)?)?;
 
     m.add_class::<IdCollisionResolutionPolicy>()?; // PYI
+    m.add_class::<OrphanPolicy>()?; // PYI
+    m.add_class::<DataClassification>()?; // PYI
+    m.add_class::<PropagationMode>()?; // PYI
+    m.add_class::<PropagationRule>()?; // PYI
 
     m.add_wrapped(wrap_pymodule!(self::geometry))?;
     Ok(())
@@ -243,10 +354,23 @@ pub(crate) fn pipeline(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<FrameProcessingStatRecord>()?;
     m.add_class::<StageLatencyStat>()?;
     m.add_class::<StageProcessingStat>()?;
+    m.add_class::<UserCodeSection>()?;
+    m.add_class::<StageRate>()?;
+    m.add_class::<SourceStatistics>()?;
+    m.add_class::<SourceStatsPublisher>()?;
+    m.add_class::<AutoBatchFlusher>()?;
+    m.add_class::<FrameReaper>()?;
+    m.add_class::<PipelineEvent>()?;
+    m.add_class::<PipelineEventReceiver>()?;
     m.add_class::<StageLatencyMeasurements>()?;
     m.add_class::<FrameProcessingStatRecordType>()?;
     m.add_class::<StageFunction>()?;
+    m.add_class::<StreamSynchronizer>()?;
+    m.add_class::<ContractRegistry>()?;
     m.add_function(wrap_pyfunction!(load_stage_function_plugin, m)?)?;
+    m.add_function(wrap_pyfunction!(motion_detector, m)?)?;
+    m.add_function(wrap_pyfunction!(quality_estimator, m)?)?;
+    m.add_function(wrap_pyfunction!(best_shot_selector, m)?)?;
     Ok(())
 }
 
@@ -256,6 +380,7 @@ pub fn match_query(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<IntExpression>()?;
     m.add_class::<StringExpression>()?;
     m.add_class::<MatchQuery>()?;
+    m.add_class::<CompiledMatchQuery>()?;
     m.add_class::<QueryFunctions>()?;
     m.add_class::<EtcdCredentials>()?;
     m.add_class::<TlsConfig>()?;
@@ -321,11 +446,17 @@ fn savant_rs(py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
             std::env::set_var(log_env_var_name, log_env_var_level);
         }
     }
-    pretty_env_logger::try_init_custom_env(log_env_var_name)
+    let logger = pretty_env_logger::formatted_builder()
+        .parse_filters(&std::env::var(log_env_var_name).unwrap_or_default())
+        .build();
+    let max_level = logger.filter();
+    log::set_boxed_logger(Box::new(RecentLogsLogger::new(logger)))
         .map_err(|_| PyRuntimeError::new_err("Failed to initialize logger"))?;
+    log::set_max_level(max_level);
     set_log_level(LogLevel::Error);
 
     m.add_function(wrap_pyfunction!(version, m)?)?; // PYI
+    m.add_function(wrap_pyfunction!(capabilities, m)?)?; // PYI
 
     m.add_wrapped(wrap_pymodule!(self::primitives))?;
     m.add_wrapped(wrap_pymodule!(self::pipeline))?;
@@ -341,6 +472,8 @@ fn savant_rs(py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_wrapped(wrap_pymodule!(self::webserver))?; // PYI
     m.add_wrapped(wrap_pymodule!(self::metrics))?; // PYI
     m.add_wrapped(wrap_pymodule!(self::kvs))?; // PYI
+    m.add_wrapped(wrap_pymodule!(self::reid))?; // PYI
+    m.add_wrapped(wrap_pymodule!(self::ha))?; // PYI
 
     let sys = PyModule::import(py, "sys")?;
     let sys_modules_bind = sys.as_ref().getattr("modules")?;
@@ -360,6 +493,8 @@ fn savant_rs(py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
     sys_modules.set_item("savant_rs.webserver", m.getattr("webserver")?)?;
     sys_modules.set_item("savant_rs.webserver.kvs", m.getattr("kvs")?)?;
     sys_modules.set_item("savant_rs.metrics", m.getattr("metrics")?)?;
+    sys_modules.set_item("savant_rs.reid", m.getattr("reid")?)?;
+    sys_modules.set_item("savant_rs.ha", m.getattr("ha")?)?;
 
     sys_modules.set_item("savant_rs.utils.symbol_mapper", m.getattr("symbol_mapper")?)?;
 